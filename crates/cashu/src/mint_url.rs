@@ -8,7 +8,7 @@ use core::str::FromStr;
 
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
-use url::{ParseError, Url};
+use url::{Host, ParseError, Url};
 
 use crate::ensure_cdk;
 
@@ -21,6 +21,12 @@ pub enum Error {
     /// Invalid URL structure
     #[error("Invalid URL")]
     InvalidUrl,
+    /// Scheme other than http and https
+    #[error("Unsupported scheme `{0}`")]
+    UnsupportedScheme(String),
+    /// URL without a host
+    #[error("URL has no host")]
+    MissingHost,
 }
 
 /// MintUrl Url
@@ -67,6 +73,11 @@ impl MintUrl {
             .nth(0)
             .ok_or(Error::InvalidUrl)?
             .to_lowercase();
+        let host = if host.is_ascii() {
+            host
+        } else {
+            Self::to_ascii_host(&host)?
+        };
         let path = url
             .split("://")
             .nth(1)
@@ -82,6 +93,54 @@ impl MintUrl {
         Ok(formatted_url)
     }
 
+    /// Punycode encode the internationalized domain of `host`, keeping its port
+    fn to_ascii_host(host: &str) -> Result<String, Error> {
+        let (domain, port) = match host.rsplit_once(':') {
+            Some((domain, port)) => (domain, Some(port)),
+            None => (host, None),
+        };
+
+        let mut host = Host::parse(domain)?.to_string();
+        if let Some(port) = port {
+            host.push(':');
+            host.push_str(port);
+        }
+        Ok(host)
+    }
+
+    /// Scheme of the url
+    pub fn scheme(&self) -> &str {
+        self.0.split("://").next().unwrap_or_default()
+    }
+
+    /// Host of the url, with its port if it has one
+    pub fn host(&self) -> &str {
+        self.0
+            .split("://")
+            .nth(1)
+            .and_then(|rest| rest.split('/').next())
+            .unwrap_or_default()
+    }
+
+    /// Whether the mint is a Tor onion service
+    pub fn is_onion(&self) -> bool {
+        let host = self.host();
+        host.rsplit_once(':')
+            .map(|(domain, _)| domain)
+            .unwrap_or(host)
+            .ends_with(".onion")
+    }
+
+    /// Fails unless the url is an http or https url with a host
+    pub fn validate(&self) -> Result<(), Error> {
+        ensure_cdk!(
+            matches!(self.scheme(), "http" | "https"),
+            Error::UnsupportedScheme(self.scheme().to_owned())
+        );
+        ensure_cdk!(!self.host().is_empty(), Error::MissingHost);
+        Ok(())
+    }
+
     /// Join onto url
     pub fn join(&self, path: &str) -> Result<Url, Error> {
         let url = Url::parse(&self.0)?;
@@ -165,6 +224,40 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_punycode_host() {
+        let url = MintUrl::from_str("https://Bücher.example/Mint/").unwrap();
+        assert_eq!("https://xn--bcher-kva.example/Mint", url.to_string());
+        assert_eq!(
+            url,
+            MintUrl::from_str("https://xn--bcher-kva.example/Mint").unwrap()
+        );
+
+        let url = MintUrl::from_str("http://bücher.example:3338").unwrap();
+        assert_eq!("http://xn--bcher-kva.example:3338", url.to_string());
+    }
+
+    #[test]
+    fn test_onion_and_validate() {
+        let url = MintUrl::from_str("http://mintaddress.onion:3338/path").unwrap();
+        assert!(url.is_onion());
+        assert_eq!(url.scheme(), "http");
+        assert_eq!(url.host(), "mintaddress.onion:3338");
+        assert!(url.validate().is_ok());
+
+        let url = MintUrl::from_str("https://onion.example.com").unwrap();
+        assert!(!url.is_onion());
+
+        let url = MintUrl::from_str("ftp://mint.example.com").unwrap();
+        assert_eq!(
+            url.validate(),
+            Err(Error::UnsupportedScheme("ftp".to_owned()))
+        );
+
+        let url = MintUrl::from_str("https:///path").unwrap();
+        assert_eq!(url.validate(), Err(Error::MissingHost));
+    }
+
     #[test]
     fn test_join_paths() {
         let url_no_path = "http://url-to-check.com";
@@ -41,7 +41,7 @@ pub use auth::{
 };
 pub use nut00::{
     BlindSignature, BlindedMessage, CurrencyUnit, PaymentMethod, Proof, Proofs, ProofsMethods,
-    Token, TokenV3, TokenV4, Witness,
+    Token, TokenSummary, TokenV3, TokenV4, Witness,
 };
 #[cfg(feature = "wallet")]
 pub use nut00::{PreMint, PreMintSecrets};
@@ -57,8 +57,8 @@ pub use nut04::{
     Settings as NUT04Settings,
 };
 pub use nut05::{
-    MeltMethodSettings, MeltQuoteCustomRequest, MeltQuoteCustomResponse, MeltRequest,
-    QuoteState as MeltQuoteState, Settings as NUT05Settings,
+    MeltMethodSettings, MeltQuoteCustomRequest, MeltQuoteCustomResponse, MeltQuoteKeysendRequest,
+    MeltRequest, QuoteState as MeltQuoteState, Settings as NUT05Settings, KEYSEND_METHOD,
 };
 pub use nut06::{ContactInfo, MintInfo, MintVersion, Nuts};
 pub use nut07::{CheckStateRequest, CheckStateResponse, ProofState, State};
@@ -77,8 +77,8 @@ pub use nut18::{
     TransportBuilder, TransportType,
 };
 pub use nut23::{
-    MeltOptions, MeltQuoteBolt11Request, MeltQuoteBolt11Response, MintQuoteBolt11Request,
-    MintQuoteBolt11Response, QuoteState as MintQuoteState,
+    HoldInvoiceState, MeltOptions, MeltQuoteBolt11Request, MeltQuoteBolt11Response,
+    MintQuoteBolt11Request, MintQuoteBolt11Response, QuoteState as MintQuoteState,
 };
 pub use nut25::{
     MeltQuoteBolt12Request, MeltQuoteBolt12Response, MintQuoteBolt12Request,
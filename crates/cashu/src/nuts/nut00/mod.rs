@@ -10,7 +10,7 @@ use std::str::FromStr;
 use std::string::FromUtf8Error;
 
 #[cfg(feature = "mint")]
-use bitcoin::hashes::Hash as BitcoinHash;
+use bitcoin::hashes::{sha256, Hash as BitcoinHash, HashEngine};
 use serde::{Deserialize, Deserializer, Serialize};
 use thiserror::Error;
 use unicode_normalization::UnicodeNormalization;
@@ -36,7 +36,7 @@ use crate::secret::Secret;
 use crate::Amount;
 
 pub mod token;
-pub use token::{Token, TokenV3, TokenV4};
+pub use token::{Token, TokenSummary, TokenV3, TokenV4};
 
 /// List of [Proof]
 pub type Proofs = Vec<Proof>;
@@ -60,6 +60,9 @@ pub trait ProofsMethods {
 
     /// Create a copy of proofs without P2BK nonce
     fn without_p2pk_e(&self) -> Proofs;
+
+    /// Hash of the set of [Proof]s, see [`input_set_hash`]
+    fn input_set_hash(&self) -> Result<sha256::Hash, Error>;
 }
 
 impl ProofsMethods for Proofs {
@@ -98,6 +101,10 @@ impl ProofsMethods for Proofs {
             })
             .collect()
     }
+
+    fn input_set_hash(&self) -> Result<sha256::Hash, Error> {
+        input_set_hash(self.iter())
+    }
 }
 
 impl ProofsMethods for HashSet<Proof> {
@@ -136,6 +143,10 @@ impl ProofsMethods for HashSet<Proof> {
             })
             .collect()
     }
+
+    fn input_set_hash(&self) -> Result<sha256::Hash, Error> {
+        input_set_hash(self.iter())
+    }
 }
 
 fn count_by_keyset<'a, I: Iterator<Item = &'a Proof>>(proofs: I) -> HashMap<Id, u64> {
@@ -162,6 +173,33 @@ fn ys<'a, I: Iterator<Item = &'a Proof>>(proofs: I) -> Result<Vec<PublicKey>, Er
     proofs.map(Proof::y).collect::<Result<Vec<PublicKey>, _>>()
 }
 
+/// Hash of a set of proofs, independent of their order
+///
+/// Proofs are identified by their `Y`, so the same proofs sent with other witnesses or DLEQ
+/// proofs hash the same. Meant to recognize a request replaying the inputs of another one without
+/// a database lookup.
+pub fn input_set_hash<'a, I: Iterator<Item = &'a Proof>>(proofs: I) -> Result<sha256::Hash, Error> {
+    let mut ys = proofs
+        .map(|proof| proof.y().map(|y| y.to_bytes()))
+        .collect::<Result<Vec<_>, _>>()?;
+    ys.sort_unstable();
+
+    let mut engine = sha256::Hash::engine();
+    for y in ys {
+        engine.input(&y);
+    }
+    Ok(sha256::Hash::from_engine(engine))
+}
+
+/// Feed `outputs` to `engine` in order, each as keyset id, amount and `B_`
+pub(crate) fn hash_outputs(engine: &mut sha256::HashEngine, outputs: &[BlindedMessage]) {
+    for output in outputs {
+        engine.input(&output.keyset_id.to_bytes());
+        engine.input(&output.amount.to_u64().to_be_bytes());
+        engine.input(&output.blinded_secret.to_bytes());
+    }
+}
+
 /// NUT00 Error
 #[derive(Debug, Error)]
 pub enum Error {
@@ -28,6 +28,25 @@ pub enum Token {
     TokenV4(TokenV4),
 }
 
+/// What a [`Token`] holds, to show before claiming it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenSummary {
+    /// Mint that issued the token
+    pub mint_url: MintUrl,
+    /// Unit of the token, V3 tokens may not have one
+    pub unit: Option<CurrencyUnit>,
+    /// Total amount of the proofs
+    pub amount: Amount,
+    /// Number of proofs
+    pub proof_count: usize,
+    /// Keysets of the proofs
+    pub keyset_ids: BTreeSet<ShortKeysetId>,
+    /// Memo
+    pub memo: Option<String>,
+    /// Unique spending conditions of the proofs, empty when none is locked
+    pub spending_conditions: HashSet<SpendingConditions>,
+}
+
 impl fmt::Display for Token {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let token = match self {
@@ -113,6 +132,35 @@ impl Token {
         }
     }
 
+    /// Summary of the token, worked out without contacting the mint
+    ///
+    /// Fails for V3 tokens holding proofs of several mints.
+    pub fn summary(&self) -> Result<TokenSummary, Error> {
+        let keyset_ids: Vec<ShortKeysetId> = match self {
+            Self::TokenV3(token) => token
+                .token
+                .iter()
+                .flat_map(|token| token.proofs.iter())
+                .map(|proof| ShortKeysetId::from(proof.keyset_id))
+                .collect(),
+            Self::TokenV4(token) => token
+                .token
+                .iter()
+                .flat_map(|token| token.proofs.iter().map(|_| token.keyset_id.clone()))
+                .collect(),
+        };
+
+        Ok(TokenSummary {
+            mint_url: self.mint_url()?,
+            unit: self.unit(),
+            amount: self.value()?,
+            proof_count: keyset_ids.len(),
+            keyset_ids: keyset_ids.into_iter().collect(),
+            memo: self.memo().clone(),
+            spending_conditions: self.spending_conditions()?,
+        })
+    }
+
     /// To v3 string
     pub fn to_v3_string(&self) -> String {
         let v3_token = match self {
@@ -131,6 +179,14 @@ impl Token {
         }
     }
 
+    /// Size in bytes of the string encoding of the token
+    ///
+    /// This is what ends up in a QR code or an NFC record, so it is the size to budget for on
+    /// constrained transports.
+    pub fn estimated_size(&self) -> usize {
+        self.to_string().len()
+    }
+
     /// Return all proof secrets in this token without keyset-id mapping, across V3/V4
     /// This is intended for spending-condition inspection where only the secret matters.
     pub fn token_secrets(&self) -> Vec<&crate::secret::Secret> {
@@ -702,6 +758,33 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_token_summary() {
+        let token_str_multi_keysets = "cashuBo2F0gqJhaUgA_9SLj17PgGFwgaNhYQFhc3hAYWNjMTI0MzVlN2I4NDg0YzNjZjE4NTAxNDkyMThhZjkwZjcxNmE1MmJmNGE1ZWQzNDdlNDhlY2MxM2Y3NzM4OGFjWCECRFODGd5IXVW-07KaZCvuWHk3WrnnpiDhHki6SCQh88-iYWlIAK0mjE0fWCZhcIKjYWECYXN4QDEzMjNkM2Q0NzA3YTU4YWQyZTIzYWRhNGU5ZjFmNDlmNWE1YjRhYzdiNzA4ZWIwZDYxZjczOGY0ODMwN2U4ZWVhY1ghAjRWqhENhLSsdHrr2Cw7AFrKUL9Ffr1XN6RBT6w659lNo2FhAWFzeEA1NmJjYmNiYjdjYzY0MDZiM2ZhNWQ1N2QyMTc0ZjRlZmY4YjQ0MDJiMTc2OTI2ZDNhNTdkM2MzZGNiYjU5ZDU3YWNYIQJzEpxXGeWZN5qXSmJjY8MzxWyvwObQGr5G1YCCgHicY2FtdWh0dHA6Ly9sb2NhbGhvc3Q6MzMzOGF1Y3NhdA==";
+
+        let summary = Token::from_str(token_str_multi_keysets)
+            .unwrap()
+            .summary()
+            .unwrap();
+
+        assert_eq!(
+            summary.mint_url,
+            MintUrl::from_str("http://localhost:3338").unwrap()
+        );
+        assert_eq!(summary.unit, Some(CurrencyUnit::Sat));
+        assert_eq!(summary.amount, Amount::from(4));
+        assert_eq!(summary.proof_count, 3);
+        assert_eq!(
+            summary.keyset_ids,
+            BTreeSet::from([
+                ShortKeysetId::from_str("00ffd48b8f5ecf80").unwrap(),
+                ShortKeysetId::from_str("00ad268c4d1f5826").unwrap(),
+            ])
+        );
+        assert_eq!(summary.memo, None);
+        assert!(summary.spending_conditions.is_empty());
+    }
+
     #[test]
     fn test_tokenv4_from_tokenv3() {
         let token_v3_str = "cashuAeyJ0b2tlbiI6W3sibWludCI6Imh0dHBzOi8vODMzMy5zcGFjZTozMzM4IiwicHJvb2ZzIjpbeyJhbW91bnQiOjIsImlkIjoiMDA5YTFmMjkzMjUzZTQxZSIsInNlY3JldCI6IjQwNzkxNWJjMjEyYmU2MWE3N2UzZTZkMmFlYjRjNzI3OTgwYmRhNTFjZDA2YTZhZmMyOWUyODYxNzY4YTc4MzciLCJDIjoiMDJiYzkwOTc5OTdkODFhZmIyY2M3MzQ2YjVlNDM0NWE5MzQ2YmQyYTUwNmViNzk1ODU5OGE3MmYwY2Y4NTE2M2VhIn0seyJhbW91bnQiOjgsImlkIjoiMDA5YTFmMjkzMjUzZTQxZSIsInNlY3JldCI6ImZlMTUxMDkzMTRlNjFkNzc1NmIwZjhlZTBmMjNhNjI0YWNhYTNmNGUwNDJmNjE0MzNjNzI4YzcwNTdiOTMxYmUiLCJDIjoiMDI5ZThlNTA1MGI4OTBhN2Q2YzA5NjhkYjE2YmMxZDVkNWZhMDQwZWExZGUyODRmNmVjNjlkNjEyOTlmNjcxMDU5In1dfV0sInVuaXQiOiJzYXQiLCJtZW1vIjoiVGhhbmsgeW91LiJ9";
@@ -861,6 +944,8 @@ mod tests {
                     active: true,
                     input_fee_ppk: 0,
                     final_expiry: None,
+                    rotation_time: None,
+                    retirement_time: None,
                 }
             })
             .collect();
@@ -909,6 +994,8 @@ mod tests {
                     active: true,
                     input_fee_ppk: 0,
                     final_expiry: None,
+                    rotation_time: None,
+                    retirement_time: None,
                 }
             })
             .collect();
@@ -185,6 +185,71 @@ impl Id {
         unit: &CurrencyUnit,
         input_fee_ppk: u64,
         expiry: Option<u64>,
+    ) -> Self {
+        Self::v2_from_preimage(map, unit, input_fee_ppk, expiry, false)
+    }
+
+    /// *** V2 KEYSET, COMMITTED VARIANT ***
+    /// create [`Id`] v2 that commits to the input fee and the amount set in every case
+    ///
+    /// Same as [`Id::v2_from_data`], but the input fee is concatenated even when it is zero
+    /// (e.g. "input_fee_ppk:0") and is followed by the ascending amounts of the keyset
+    /// (e.g. "amounts:1,2,4,8"). It anticipates the hardening of keyset ids in NUT-02. Mints
+    /// derive it with the `keyset-id-commitments` feature, [`Id::is_derived_from`] accepts both.
+    pub fn v2_committed_from_data(
+        map: &Keys,
+        unit: &CurrencyUnit,
+        input_fee_ppk: u64,
+        expiry: Option<u64>,
+    ) -> Self {
+        Self::v2_from_preimage(map, unit, input_fee_ppk, expiry, true)
+    }
+
+    /// V2 [`Id`] the mint derives for new keysets
+    ///
+    /// [`Id::v2_committed_from_data`] with the `keyset-id-commitments` feature,
+    /// [`Id::v2_from_data`] otherwise.
+    pub fn v2_default_from_data(
+        map: &Keys,
+        unit: &CurrencyUnit,
+        input_fee_ppk: u64,
+        expiry: Option<u64>,
+    ) -> Self {
+        Self::v2_from_preimage(
+            map,
+            unit,
+            input_fee_ppk,
+            expiry,
+            cfg!(feature = "keyset-id-commitments"),
+        )
+    }
+
+    /// Whether this id is derived from the given keyset data
+    ///
+    /// V2 ids match either [`Id::v2_from_data`] or [`Id::v2_committed_from_data`], so keysets
+    /// of mints that commit to their fee and amounts are accepted as well.
+    pub fn is_derived_from(
+        &self,
+        map: &Keys,
+        unit: &CurrencyUnit,
+        input_fee_ppk: u64,
+        expiry: Option<u64>,
+    ) -> bool {
+        match self.version {
+            KeySetVersion::Version00 => Self::v1_from_keys(map) == *self,
+            KeySetVersion::Version01 => {
+                Self::v2_from_data(map, unit, input_fee_ppk, expiry) == *self
+                    || Self::v2_committed_from_data(map, unit, input_fee_ppk, expiry) == *self
+            }
+        }
+    }
+
+    fn v2_from_preimage(
+        map: &Keys,
+        unit: &CurrencyUnit,
+        input_fee_ppk: u64,
+        expiry: Option<u64>,
+        committed: bool,
     ) -> Self {
         let mut keys: Vec<(&Amount, &super::PublicKey)> = map.iter().collect();
         keys.sort_by_key(|(amt, _v)| *amt);
@@ -198,10 +263,19 @@ impl Id {
         let mut data = keys_string;
         data.push_str(&format!("|unit:{}", unit));
 
-        if input_fee_ppk > 0 {
+        if input_fee_ppk > 0 || committed {
             data.push_str(&format!("|input_fee_ppk:{}", input_fee_ppk));
         }
 
+        if committed {
+            let amounts = keys
+                .iter()
+                .map(|(amt, _)| amt.to_string())
+                .collect::<Vec<String>>()
+                .join(",");
+            data.push_str(&format!("|amounts:{}", amounts));
+        }
+
         if let Some(expiry) = expiry {
             if expiry > 0 {
                 data.push_str(&format!("|final_expiry:{}", expiry));
@@ -506,27 +580,60 @@ pub struct KeySet {
 impl KeySet {
     /// Verify the keyset id matches keys
     pub fn verify_id(&self) -> Result<(), Error> {
-        let keys_id = match self.id.version {
-            KeySetVersion::Version00 => Id::v1_from_keys(&self.keys),
-            KeySetVersion::Version01 => Id::v2_from_data(
+        ensure_cdk!(
+            self.id.is_derived_from(
                 &self.keys,
                 &self.unit,
                 self.input_fee_ppk,
                 self.final_expiry,
             ),
-        };
-
-        ensure_cdk!(
-            u32::from(keys_id) == u32::from(self.id),
             Error::IncorrectKeysetId
         );
 
-        ensure_cdk!(keys_id == self.id, Error::IncorrectKeysetId);
-
         Ok(())
     }
 }
 
+#[cfg(feature = "mint")]
+impl KeySet {
+    /// Derive the public keyset a mint derives at `derivation_path` of `xpriv`, offline
+    ///
+    /// The amounts are the powers of two below `2^max_order`, as configured on a mint. Lets an
+    /// operator check that a seed backup reproduces the keysets of a mint without starting it.
+    #[allow(clippy::too_many_arguments)]
+    pub fn derive_from_xpriv<C: secp256k1::Signing>(
+        secp: &Secp256k1<C>,
+        xpriv: Xpriv,
+        derivation_path: DerivationPath,
+        unit: CurrencyUnit,
+        max_order: u32,
+        input_fee_ppk: u64,
+        final_expiry: Option<u64>,
+        version: KeySetVersion,
+    ) -> Self {
+        let amounts: Vec<u64> = (0..max_order.min(u64::BITS)).map(|n| 1 << n).collect();
+        let keyset = MintKeySet::generate_from_xpriv(
+            secp,
+            xpriv,
+            &amounts,
+            unit,
+            derivation_path,
+            input_fee_ppk,
+            final_expiry,
+            version,
+        );
+
+        Self {
+            id: keyset.id,
+            unit: keyset.unit,
+            active: None,
+            keys: keyset.keys.into(),
+            input_fee_ppk,
+            final_expiry,
+        }
+    }
+}
+
 /// KeySetInfo
 #[derive(Debug, Clone, Hash, PartialEq, Eq, Deserialize, Serialize)]
 pub struct KeySetInfo {
@@ -546,6 +653,40 @@ pub struct KeySetInfo {
     /// Expiry of the keyset
     #[serde(skip_serializing_if = "Option::is_none")]
     pub final_expiry: Option<u64>,
+    /// Time the mint plans to replace this keyset with a new active one
+    ///
+    /// Not part of NUT-02, wallets can start swapping out of the keyset ahead of it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rotation_time: Option<u64>,
+    /// Time after which the mint stops accepting proofs of this keyset
+    ///
+    /// Not part of NUT-02, set for keysets retired on a schedule rather than through their
+    /// `final_expiry`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retirement_time: Option<u64>,
+}
+
+impl KeySetInfo {
+    /// Earliest time after which the mint stops accepting proofs of this keyset, if any
+    pub fn retires_at(&self) -> Option<u64> {
+        match (self.final_expiry, self.retirement_time) {
+            (Some(expiry), Some(retirement)) => Some(expiry.min(retirement)),
+            (expiry, retirement) => expiry.or(retirement),
+        }
+    }
+
+    /// Whether proofs of this keyset should be swapped into an active keyset by `deadline`
+    ///
+    /// True for inactive keysets and for keysets the mint plans to rotate or retire before
+    /// `deadline`.
+    pub fn needs_migration(&self, deadline: u64) -> bool {
+        !self.active
+            || self
+                .rotation_time
+                .into_iter()
+                .chain(self.retires_at())
+                .any(|time| time <= deadline)
+    }
 }
 
 /// List of [KeySetInfo]
@@ -641,7 +782,7 @@ impl MintKeySet {
         let id = match version {
             KeySetVersion::Version00 => Id::v1_from_keys(&keys.clone().into()),
             KeySetVersion::Version01 => {
-                Id::v2_from_data(&keys.clone().into(), &unit, input_fee_ppk, final_expiry)
+                Id::v2_default_from_data(&keys.clone().into(), &unit, input_fee_ppk, final_expiry)
             }
         };
         Self {
@@ -721,7 +862,7 @@ impl From<MintKeySet> for Id {
         let keys: Keys = keyset.keys.into();
         match keyset.id.version {
             KeySetVersion::Version00 => Id::v1_from_keys(&keys),
-            KeySetVersion::Version01 => Id::v2_from_data(
+            KeySetVersion::Version01 => Id::v2_default_from_data(
                 &keys,
                 &keyset.unit,
                 keyset.input_fee_ppk,
@@ -876,6 +1017,30 @@ mod test {
         assert_eq!(id, id_from_str);
     }
 
+    #[test]
+    fn test_v2_committed_id_is_accepted() {
+        let unit = CurrencyUnit::Sat;
+        let keys: Keys = serde_json::from_str(SHORT_KEYSET).unwrap();
+
+        let standard = Id::v2_from_data(&keys, &unit, 0, None);
+        let committed = Id::v2_committed_from_data(&keys, &unit, 0, None);
+        assert_ne!(standard, committed);
+        assert_eq!(committed.get_version(), KeySetVersion::Version01);
+
+        assert!(standard.is_derived_from(&keys, &unit, 0, None));
+        assert!(committed.is_derived_from(&keys, &unit, 0, None));
+        assert!(!committed.is_derived_from(&keys, &unit, 1, None));
+        assert!(!committed.is_derived_from(&keys, &CurrencyUnit::Msat, 0, None));
+
+        let fewer_amounts = Keys::new(
+            keys.iter()
+                .filter(|(amount, _)| **amount != 8.into())
+                .map(|(amount, key)| (*amount, *key))
+                .collect(),
+        );
+        assert!(!committed.is_derived_from(&fewer_amounts, &unit, 0, None));
+    }
+
     #[test]
     fn test_deserialization_keyset_info() {
         let h = r#"{"id":"009a1f293253e41e","unit":"sat","active":true}"#;
@@ -1083,6 +1248,8 @@ mod test {
             active: true,
             input_fee_ppk: 0,
             final_expiry: None,
+            rotation_time: None,
+            retirement_time: None,
         };
         let keysets = vec![v1_info];
 
@@ -1106,6 +1273,8 @@ mod test {
             active: true,
             input_fee_ppk: 0,
             final_expiry: None,
+            rotation_time: None,
+            retirement_time: None,
         };
         let keysets = vec![v2_info];
 
@@ -1130,6 +1299,8 @@ mod test {
             active: true,
             input_fee_ppk: 0,
             final_expiry: None,
+            rotation_time: None,
+            retirement_time: None,
         };
 
         // v2 id - 32 bytes
@@ -1142,6 +1313,8 @@ mod test {
             active: true,
             input_fee_ppk: 0,
             final_expiry: None,
+            rotation_time: None,
+            retirement_time: None,
         };
 
         let keysets = vec![v1_info, v2_info];
@@ -1168,6 +1341,8 @@ mod test {
                 active: true,
                 input_fee_ppk: 0,
                 final_expiry: None,
+                rotation_time: None,
+                retirement_time: None,
             },
             KeySetInfo {
                 id: v2_id,
@@ -1175,6 +1350,8 @@ mod test {
                 active: true,
                 input_fee_ppk: 0,
                 final_expiry: None,
+                rotation_time: None,
+                retirement_time: None,
             },
         ];
 
@@ -1202,6 +1379,8 @@ mod test {
             active: true,
             input_fee_ppk: 0,
             final_expiry: None,
+            rotation_time: None,
+            retirement_time: None,
         }];
 
         let short_id = ShortKeysetId::from_str("01ffffffffffffff").unwrap();
@@ -1223,6 +1402,8 @@ mod test {
             active: true,
             input_fee_ppk: 0,
             final_expiry: None,
+            rotation_time: None,
+            retirement_time: None,
         }];
 
         // Build a ShortKeysetId whose prefix equals the full 32-byte id.
@@ -1340,6 +1521,8 @@ mod test {
                 active: true,
                 input_fee_ppk: 0,
                 final_expiry: None,
+                rotation_time: None,
+                retirement_time: None,
             },
             KeySetInfo {
                 id: id_b,
@@ -1347,6 +1530,8 @@ mod test {
                 active: false,
                 input_fee_ppk: 0,
                 final_expiry: None,
+                rotation_time: None,
+                retirement_time: None,
             },
             KeySetInfo {
                 id: id_c,
@@ -1354,6 +1539,8 @@ mod test {
                 active: true,
                 input_fee_ppk: 0,
                 final_expiry: None,
+                rotation_time: None,
+                retirement_time: None,
             },
         ];
 
@@ -1379,6 +1566,8 @@ mod test {
                 active: true,
                 input_fee_ppk: 0,
                 final_expiry: None,
+                rotation_time: None,
+                retirement_time: None,
             },
             KeySetInfo {
                 id: id_b,
@@ -1386,6 +1575,8 @@ mod test {
                 active: true,
                 input_fee_ppk: 0,
                 final_expiry: None,
+                rotation_time: None,
+                retirement_time: None,
             },
             KeySetInfo {
                 id: id_c,
@@ -1393,6 +1584,8 @@ mod test {
                 active: false,
                 input_fee_ppk: 0,
                 final_expiry: None,
+                rotation_time: None,
+                retirement_time: None,
             },
         ];
 
@@ -1410,6 +1603,45 @@ mod test {
         assert!(none_ids.is_empty());
     }
 
+    #[cfg(feature = "mint")]
+    #[test]
+    fn test_derive_keyset_from_xpriv() {
+        use bitcoin::bip32::{DerivationPath, Xpriv};
+        use bitcoin::secp256k1::Secp256k1;
+
+        use super::{KeySet, MintKeySet};
+
+        let secp = Secp256k1::new();
+        let seed = [7u8; 64];
+        let xpriv = Xpriv::new_master(bitcoin::Network::Bitcoin, &seed).expect("valid seed");
+        let path = DerivationPath::from_str("m/129372'/0'/0'").expect("valid path");
+
+        let keyset = KeySet::derive_from_xpriv(
+            &secp,
+            xpriv,
+            path.clone(),
+            CurrencyUnit::Sat,
+            4,
+            100,
+            None,
+            KeySetVersion::Version01,
+        );
+        let mint_keyset = MintKeySet::generate_from_seed(
+            &secp,
+            &seed,
+            &[1, 2, 4, 8],
+            CurrencyUnit::Sat,
+            path,
+            100,
+            None,
+            KeySetVersion::Version01,
+        );
+
+        assert_eq!(keyset.id, mint_keyset.id);
+        assert_eq!(keyset.keys, Keys::from(mint_keyset.keys));
+        assert!(keyset.verify_id().is_ok());
+    }
+
     #[test]
     fn test_default_input_fee_ppk_is_zero() {
         // KeySetInfo missing the input_fee_ppk field must default to 0.
@@ -1423,4 +1655,36 @@ mod test {
         let info_null: KeySetInfo = serde_json::from_str(json_null).unwrap();
         assert_eq!(info_null.input_fee_ppk, 0);
     }
+
+    #[test]
+    fn test_keyset_info_needs_migration() {
+        let keyset = KeySetInfo {
+            id: Id::from_str("009a1f293253e41e").unwrap(),
+            unit: CurrencyUnit::Sat,
+            active: true,
+            input_fee_ppk: 0,
+            final_expiry: Some(2_000),
+            rotation_time: None,
+            retirement_time: Some(1_500),
+        };
+
+        assert_eq!(keyset.retires_at(), Some(1_500));
+        assert!(!keyset.needs_migration(1_000));
+        assert!(keyset.needs_migration(1_500));
+
+        let rotating = KeySetInfo {
+            rotation_time: Some(1_200),
+            ..keyset.clone()
+        };
+        assert!(rotating.needs_migration(1_200));
+
+        let inactive = KeySetInfo {
+            active: false,
+            final_expiry: None,
+            retirement_time: None,
+            ..keyset
+        };
+        assert_eq!(inactive.retires_at(), None);
+        assert!(inactive.needs_migration(0));
+    }
 }
@@ -2,12 +2,13 @@
 //!
 //! <https://github.com/cashubtc/nuts/blob/main/03.md>
 
+use bitcoin::hashes::{sha256, Hash, HashEngine};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 #[cfg(feature = "wallet")]
 use super::nut00::PreMintSecrets;
-use super::nut00::{BlindSignature, BlindedMessage, Proofs};
+use super::nut00::{self, BlindSignature, BlindedMessage, Proofs};
 use super::ProofsMethods;
 use crate::Amount;
 
@@ -89,6 +90,18 @@ impl SwapRequest {
             self.outputs.iter().map(|proof| proof.amount),
         )?)
     }
+
+    /// Hash identifying the request, the same for every replay of it
+    ///
+    /// The inputs are hashed as a set with [`ProofsMethods::input_set_hash`], the outputs in
+    /// order since the signatures are returned in that order.
+    pub fn canonical_hash(&self) -> Result<sha256::Hash, nut00::Error> {
+        let mut engine = sha256::Hash::engine();
+        engine.input(b"swap");
+        engine.input(self.inputs.input_set_hash()?.as_byte_array());
+        nut00::hash_outputs(&mut engine, &self.outputs);
+        Ok(sha256::Hash::from_engine(engine))
+    }
 }
 
 impl super::nut10::SpendingConditionVerification for SwapRequest {
@@ -171,6 +184,25 @@ mod tests {
         ]
     }"#;
 
+    #[test]
+    fn canonical_hash_ignores_input_order_and_witnesses() {
+        let req: SwapRequest = serde_json::from_str(SWAP_REQUEST_JSON).unwrap();
+        let hash = req.canonical_hash().unwrap();
+
+        let mut replay = req.clone();
+        replay.inputs_mut().reverse();
+        replay.inputs_mut()[1].witness = None;
+        assert_eq!(replay.canonical_hash().unwrap(), hash);
+
+        let mut other = req.clone();
+        other.inputs_mut().pop();
+        assert_ne!(other.canonical_hash().unwrap(), hash);
+
+        let mut other = req;
+        other.outputs_mut()[0].amount = Amount::from(4);
+        assert_ne!(other.canonical_hash().unwrap(), hash);
+    }
+
     #[test]
     fn test_swap_request_inputs_outputs_getters() {
         // Kills mutations that replace `inputs()` / `outputs()` with empty
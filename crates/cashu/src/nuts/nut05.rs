@@ -5,12 +5,14 @@
 use std::fmt;
 use std::str::FromStr;
 
+use bitcoin::hashes::{sha256, Hash, HashEngine};
 use serde::de::{self, DeserializeOwned, Deserializer, MapAccess, Visitor};
 use serde::ser::{SerializeStruct, Serializer};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use super::nut00::{BlindSignature, BlindedMessage, CurrencyUnit, PaymentMethod, Proofs};
+use super::nut00::{self, BlindSignature, BlindedMessage, CurrencyUnit, PaymentMethod, Proofs};
+use super::nut01::PublicKey;
 use super::ProofsMethods;
 use crate::nut00::KnownMethod;
 #[cfg(feature = "mint")]
@@ -32,6 +34,9 @@ pub enum Error {
     /// Invalid quote id
     #[error("Invalid quote id")]
     InvalidQuote,
+    /// Custom request that is not a valid keysend request
+    #[error("Invalid keysend request")]
+    InvalidKeysendRequest,
 }
 
 /// Possible states of a quote
@@ -135,6 +140,30 @@ impl<Q> MeltRequest<Q> {
     }
 }
 
+impl<Q> MeltRequest<Q>
+where
+    Q: fmt::Display,
+{
+    /// Hash identifying the request, the same for every replay of it
+    ///
+    /// Covers the quote, the inputs as a set with [`ProofsMethods::input_set_hash`], the change
+    /// outputs in order and the processing options.
+    pub fn canonical_hash(&self) -> Result<sha256::Hash, nut00::Error> {
+        let mut engine = sha256::Hash::engine();
+        engine.input(b"melt");
+        engine.input(self.quote.to_string().as_bytes());
+        engine.input(self.inputs.input_set_hash()?.as_byte_array());
+        if let Some(outputs) = &self.outputs {
+            nut00::hash_outputs(&mut engine, outputs);
+        }
+        engine.input(&[u8::from(self.prefer_async)]);
+        if let Some(fee_index) = self.fee_index {
+            engine.input(&fee_index.to_be_bytes());
+        }
+        Ok(sha256::Hash::from_engine(engine))
+    }
+}
+
 impl<Q> MeltRequest<Q>
 where
     Q: Serialize + DeserializeOwned,
@@ -468,6 +497,61 @@ pub struct MeltQuoteCustomRequest {
     pub extra: serde_json::Value,
 }
 
+/// Name of the keysend melt method
+pub const KEYSEND_METHOD: &str = "keysend";
+
+/// Keysend melt quote request
+///
+/// Pays a lightning node spontaneously: the wallet names the node and the amount instead of
+/// handing over an invoice. On the wire it is the custom method [`KEYSEND_METHOD`], with the node
+/// pubkey as `request` and the amount as an extra field, so it is quoted and melted through the
+/// custom method endpoints and answered with a [`MeltQuoteCustomResponse`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MeltQuoteKeysendRequest {
+    /// Node to pay
+    pub pubkey: PublicKey,
+    /// Amount the node receives
+    pub amount: Amount,
+    /// Currency unit
+    pub unit: CurrencyUnit,
+}
+
+impl From<MeltQuoteKeysendRequest> for MeltQuoteCustomRequest {
+    fn from(request: MeltQuoteKeysendRequest) -> Self {
+        Self {
+            method: KEYSEND_METHOD.to_string(),
+            request: request.pubkey.to_hex(),
+            unit: request.unit,
+            extra: serde_json::json!({ "amount": request.amount }),
+        }
+    }
+}
+
+impl TryFrom<&MeltQuoteCustomRequest> for MeltQuoteKeysendRequest {
+    type Error = Error;
+
+    fn try_from(request: &MeltQuoteCustomRequest) -> Result<Self, Self::Error> {
+        if request.method != KEYSEND_METHOD {
+            return Err(Error::InvalidKeysendRequest);
+        }
+
+        let pubkey =
+            PublicKey::from_hex(&request.request).map_err(|_| Error::InvalidKeysendRequest)?;
+        let amount = request
+            .extra
+            .get("amount")
+            .and_then(serde_json::Value::as_u64)
+            .filter(|amount| *amount > 0)
+            .ok_or(Error::InvalidKeysendRequest)?;
+
+        Ok(Self {
+            pubkey,
+            amount: Amount::from(amount),
+            unit: request.unit.clone(),
+        })
+    }
+}
+
 /// Custom payment method melt quote response
 ///
 /// This is a generic response type for custom payment methods.
@@ -745,4 +829,33 @@ mod tests {
 
         assert_eq!(parsed["fee_reserve"], json!(10));
     }
+
+    #[test]
+    fn test_keysend_request_round_trips_through_custom_request() {
+        let pubkey = PublicKey::from_hex(
+            "02194603ffa36356f4a56b7df9371fc3192472351453ec7398b8da8117e7c3e104",
+        )
+        .unwrap();
+        let keysend = MeltQuoteKeysendRequest {
+            pubkey,
+            amount: Amount::from(2100),
+            unit: CurrencyUnit::Sat,
+        };
+
+        let custom = MeltQuoteCustomRequest::from(keysend.clone());
+        assert_eq!(custom.method, KEYSEND_METHOD);
+        assert_eq!(
+            to_string(&custom).unwrap(),
+            r#"{"method":"keysend","request":"02194603ffa36356f4a56b7df9371fc3192472351453ec7398b8da8117e7c3e104","unit":"sat","amount":2100}"#
+        );
+        assert_eq!(MeltQuoteKeysendRequest::try_from(&custom).unwrap(), keysend);
+
+        let mut zero_amount = custom.clone();
+        zero_amount.extra = json!({ "amount": 0 });
+        assert!(MeltQuoteKeysendRequest::try_from(&zero_amount).is_err());
+
+        let mut invalid_pubkey = custom;
+        invalid_pubkey.request = "lnbc1".to_string();
+        assert!(MeltQuoteKeysendRequest::try_from(&invalid_pubkey).is_err());
+    }
 }
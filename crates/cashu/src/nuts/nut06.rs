@@ -2,7 +2,7 @@
 //!
 //! <https://github.com/cashubtc/nuts/blob/main/06.md>
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
@@ -14,7 +14,7 @@ use super::{
     MppMethodSettings, ProtectedEndpoint,
 };
 use crate::util::serde_helpers::deserialize_empty_string_as_none;
-use crate::CurrencyUnit;
+use crate::{Amount, CurrencyUnit};
 
 /// Mint Version
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -107,6 +107,12 @@ pub struct MintInfo {
     /// terms of url service of the mint
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tos_url: Option<String>,
+    /// bitcoin network the mint settles payments on, not part of NUT-06
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network: Option<bitcoin::Network>,
+    /// mint is in read-only maintenance mode and refuses new mint quotes, not part of NUT-06
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub read_only: bool,
 }
 
 impl MintInfo {
@@ -221,6 +227,19 @@ impl MintInfo {
         }
     }
 
+    /// Set network
+    pub fn network(self, network: bitcoin::Network) -> Self {
+        Self {
+            network: Some(network),
+            ..self
+        }
+    }
+
+    /// Set read-only maintenance mode
+    pub fn read_only(self, read_only: bool) -> Self {
+        Self { read_only, ..self }
+    }
+
     /// Get protected endpoints
     pub fn protected_endpoints(&self) -> HashMap<ProtectedEndpoint, AuthRequired> {
         let mut protected_endpoints = HashMap::new();
@@ -337,6 +356,12 @@ pub struct Nuts {
     #[serde(rename = "29")]
     #[serde(skip_serializing_if = "nut29::Settings::is_empty")]
     pub nut29: nut29::Settings,
+    /// Largest proof amount the mint issues for each unit
+    ///
+    /// Not a NUT, outputs above it are refused even when the keyset has a key for their amount.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub max_denominations: BTreeMap<CurrencyUnit, Amount>,
 }
 
 impl Nuts {
@@ -462,6 +487,19 @@ impl Nuts {
         }
     }
 
+    /// Max denomination of each unit
+    pub fn max_denominations(self, max_denominations: BTreeMap<CurrencyUnit, Amount>) -> Self {
+        Self {
+            max_denominations,
+            ..self
+        }
+    }
+
+    /// Largest proof amount issued for `unit`, if capped
+    pub fn max_denomination(&self, unit: &CurrencyUnit) -> Option<Amount> {
+        self.max_denominations.get(unit).copied()
+    }
+
     /// Units where minting is supported
     pub fn supported_mint_units(&self) -> Vec<&CurrencyUnit> {
         self.nut04
@@ -721,4 +759,54 @@ mod tests {
         assert!(parsed["nuts"]["15"]["methods"].is_array());
         assert_eq!(parsed["nuts"]["15"]["methods"].as_array().unwrap().len(), 1);
     }
+
+    #[test]
+    fn test_network_serialization() {
+        let mint_info = MintInfo::new().name("Test Mint");
+        let parsed = serde_json::to_value(&mint_info).unwrap();
+        assert!(parsed.get("network").is_none());
+
+        let mint_info = mint_info.network(bitcoin::Network::Signet);
+        let parsed = serde_json::to_value(&mint_info).unwrap();
+        assert_eq!(parsed["network"], "signet");
+
+        let roundtrip: MintInfo = serde_json::from_value(parsed).unwrap();
+        assert_eq!(roundtrip.network, Some(bitcoin::Network::Signet));
+    }
+
+    #[test]
+    fn test_read_only_serialization() {
+        let mint_info = MintInfo::new().name("Test Mint");
+        let parsed = serde_json::to_value(&mint_info).unwrap();
+        assert!(parsed.get("read_only").is_none());
+
+        let mint_info = mint_info.read_only(true);
+        let parsed = serde_json::to_value(&mint_info).unwrap();
+        assert_eq!(parsed["read_only"], true);
+
+        let roundtrip: MintInfo = serde_json::from_value(parsed).unwrap();
+        assert!(roundtrip.read_only);
+    }
+
+    #[test]
+    fn test_max_denominations_serialization() {
+        let nuts = Nuts::default();
+        let parsed = serde_json::to_value(&nuts).unwrap();
+        assert!(parsed["max_denominations"].is_null());
+
+        let nuts = Nuts::default().max_denominations(BTreeMap::from([(
+            CurrencyUnit::Sat,
+            Amount::from(1_048_576),
+        )]));
+        let json = serde_json::to_string(&nuts).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed["max_denominations"]["sat"], 1_048_576);
+
+        let nuts: Nuts = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            nuts.max_denomination(&CurrencyUnit::Sat),
+            Some(Amount::from(1_048_576))
+        );
+        assert_eq!(nuts.max_denomination(&CurrencyUnit::Usd), None);
+    }
 }
@@ -342,22 +342,30 @@ pub trait SpendingConditionVerification {
     /// This is the main entry point for spending condition verification.
     /// It checks if any input has SIG_ALL and dispatches to the appropriate verification path.
     fn verify_spending_conditions(&self) -> Result<(), Error> {
+        self.verify_spending_conditions_with_grace(0)
+    }
+
+    /// Verify spending conditions for this transaction, treating locktimes as passed
+    /// `grace_secs` early
+    ///
+    /// The grace tolerates clocks running behind the one of the refunding party.
+    fn verify_spending_conditions_with_grace(&self, grace_secs: u64) -> Result<(), Error> {
         // Check if any input has SIG_ALL flag
         if self.has_at_least_one_sig_all()? {
             // at least one input has SIG_ALL
-            self.verify_full_sig_all_check()
+            self.verify_full_sig_all_check(grace_secs)
         } else {
             // none of the inputs are SIG_ALL, so we can simply check
             // each independently and verify any spending conditions
             // that may - or may not - be there.
-            self.verify_inputs_individually()
+            self.verify_inputs_individually(grace_secs)
         }
     }
 
     /// Verify spending conditions when SIG_ALL is present
     ///
     /// When SIG_ALL is set, all proofs in the transaction must be signed together.
-    fn verify_full_sig_all_check(&self) -> Result<(), Error> {
+    fn verify_full_sig_all_check(&self, grace_secs: u64) -> Result<(), Error> {
         debug_assert!(
             self.has_at_least_one_sig_all()?,
             "verify_full_sig_all_check() called on proofs without SIG_ALL. This shouldn't happen"
@@ -371,13 +379,16 @@ pub trait SpendingConditionVerification {
         let first_secret =
             Secret::try_from(&first_input.secret).map_err(|_| Error::IncorrectSecretKind)?;
 
+        // Record current time for locktime evaluation
+        let current_time = crate::util::unix_time().saturating_add(grace_secs);
+
         // Dispatch based on secret kind
         match first_secret.kind() {
             Kind::P2PK => {
-                nut11::verify_sig_all_p2pk(first_input, self.sig_all_msg_to_sign())?;
+                nut11::verify_sig_all_p2pk(first_input, self.sig_all_msg_to_sign(), current_time)?;
             }
             Kind::HTLC => {
-                nut14::verify_sig_all_htlc(first_input, self.sig_all_msg_to_sign())?;
+                nut14::verify_sig_all_htlc(first_input, self.sig_all_msg_to_sign(), current_time)?;
             }
         }
 
@@ -389,7 +400,7 @@ pub trait SpendingConditionVerification {
     /// Handles SIG_INPUTS mode, non-NUT-10 secrets, and any other case where inputs
     /// are verified independently rather than as a group.
     /// This function will NOT be called if any input has SIG_ALL.
-    fn verify_inputs_individually(&self) -> Result<(), Error> {
+    fn verify_inputs_individually(&self, grace_secs: u64) -> Result<(), Error> {
         debug_assert!(
             !(self.has_at_least_one_sig_all()?),
             "verify_inputs_individually() called on SIG_ALL. This shouldn't happen"
@@ -409,10 +420,10 @@ pub trait SpendingConditionVerification {
 
                 match secret.kind() {
                     Kind::P2PK => {
-                        proof.verify_p2pk()?;
+                        proof.verify_p2pk_with_grace(grace_secs)?;
                     }
                     Kind::HTLC => {
-                        proof.verify_htlc()?;
+                        proof.verify_htlc_with_grace(grace_secs)?;
                     }
                 }
             }
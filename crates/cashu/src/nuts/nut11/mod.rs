@@ -141,6 +141,13 @@ impl Proof {
     ///
     /// The verification tries both paths - if either succeeds, the proof is valid.
     pub fn verify_p2pk(&self) -> Result<(), Error> {
+        self.verify_p2pk_with_grace(0)
+    }
+
+    /// Verify P2PK signature on [Proof], treating the locktime as passed `grace_secs` early
+    ///
+    /// The grace tolerates clocks running behind the one of the refunding party.
+    pub fn verify_p2pk_with_grace(&self, grace_secs: u64) -> Result<(), Error> {
         let secret: Nut10Secret = self.secret.clone().try_into()?;
         let spending_conditions: Conditions = secret
             .secret_data()
@@ -159,7 +166,7 @@ impl Proof {
         }
 
         // Get spending requirements (includes both primary and refund paths)
-        let now = unix_time();
+        let now = unix_time().saturating_add(grace_secs);
         let requirements =
             super::nut10::get_pubkeys_and_required_sigs(&secret, now).map_err(|err| match err {
                 super::nut10::Error::NUT11(nut11_err) => nut11_err,
@@ -269,14 +276,17 @@ pub(crate) fn extract_signatures_from_witness(
 /// Per NUT-11, there are two spending pathways after locktime:
 /// 1. Primary path (data + pubkeys): ALWAYS available
 /// 2. Refund path (refund keys): available AFTER locktime
-pub(crate) fn verify_sig_all_p2pk(first_input: &Proof, msg_to_sign: String) -> Result<(), Error> {
+///
+/// `current_time` is the time the locktime is evaluated against.
+pub(crate) fn verify_sig_all_p2pk(
+    first_input: &Proof,
+    msg_to_sign: String,
+    current_time: u64,
+) -> Result<(), Error> {
     // Get the first input, as it's the one with the signatures
     let first_secret =
         Nut10Secret::try_from(&first_input.secret).map_err(|_| Error::IncorrectSecretKind)?;
 
-    // Record current time for locktime evaluation
-    let current_time = crate::util::unix_time();
-
     // Get spending requirements (includes both primary and refund paths)
     let requirements = get_pubkeys_and_required_sigs(&first_secret, current_time)
         .map_err(|_| Error::SpendConditionsNotMet)?;
@@ -664,6 +674,45 @@ mod tests {
         assert!(proof.verify_p2pk().is_ok());
     }
 
+    #[test]
+    fn test_refund_path_with_locktime_grace() {
+        let signing_key = SecretKey::generate();
+        let refund_key = SecretKey::generate();
+
+        let conditions = Conditions {
+            locktime: Some(unix_time() + 10),
+            pubkeys: None,
+            refund_keys: Some(vec![refund_key.public_key()]),
+            num_sigs: None,
+            sig_flag: SigFlag::SigInputs,
+            num_sigs_refund: None,
+        };
+
+        let secret: Secret =
+            SpendingConditions::new_p2pk(signing_key.public_key(), Some(conditions))
+                .try_into()
+                .unwrap();
+
+        let mut proof = Proof {
+            keyset_id: Id::from_str("009a1f293253e41e").unwrap(),
+            amount: Amount::ZERO,
+            secret,
+            c: PublicKey::from_str(
+                "02698c4e2b5f9534cd0687d87513c759790cf829aa5739184a3e3735471fbda904",
+            )
+            .unwrap(),
+            witness: None,
+            dleq: None,
+            p2pk_e: None,
+        };
+        proof.sign_p2pk(refund_key).unwrap();
+
+        // The refund path opens once the locktime is within the grace window
+        assert!(proof.verify_p2pk().is_err());
+        assert!(proof.verify_p2pk_with_grace(5).is_err());
+        assert!(proof.verify_p2pk_with_grace(30).is_ok());
+    }
+
     #[test]
     fn test_verify() {
         // Proof with a valid signature
@@ -115,6 +115,13 @@ impl Proof {
     /// The verification tries to determine which path is being used based on
     /// the witness provided, then validates accordingly.
     pub fn verify_htlc(&self) -> Result<(), Error> {
+        self.verify_htlc_with_grace(0)
+    }
+
+    /// Verify HTLC, treating the locktime as passed `grace_secs` early
+    ///
+    /// The grace tolerates clocks running behind the one of the refunding party.
+    pub fn verify_htlc_with_grace(&self, grace_secs: u64) -> Result<(), Error> {
         let secret: Secret = self.secret.clone().try_into()?;
         let spending_conditions: Conditions = secret
             .secret_data()
@@ -133,7 +140,7 @@ impl Proof {
         }
 
         // Get the spending requirements (includes both receiver and refund paths)
-        let now = unix_time();
+        let now = unix_time().saturating_add(grace_secs);
         let requirements =
             super::nut10::get_pubkeys_and_required_sigs(&secret, now).map_err(|err| match err {
                 super::nut10::Error::NUT14(nut14_err) => nut14_err,
@@ -299,14 +306,17 @@ fn verify_htlc_preimage(witness: &HTLCWitness, secret: &Secret) -> Result<(), Er
 /// Per NUT-14, there are two spending pathways:
 /// 1. Receiver path (preimage + pubkeys): ALWAYS available
 /// 2. Sender/Refund path (refund keys, no preimage): available AFTER locktime
-pub(crate) fn verify_sig_all_htlc(first_input: &Proof, msg_to_sign: String) -> Result<(), Error> {
+///
+/// `current_time` is the time the locktime is evaluated against.
+pub(crate) fn verify_sig_all_htlc(
+    first_input: &Proof,
+    msg_to_sign: String,
+    current_time: u64,
+) -> Result<(), Error> {
     // Get the first input, as it's the one with the signatures
     let first_secret =
         Secret::try_from(&first_input.secret).map_err(|_| Error::IncorrectSecretKind)?;
 
-    // Record current time for locktime evaluation
-    let current_time = crate::util::unix_time();
-
     // Get the spending requirements (includes both receiver and refund paths)
     let requirements = get_pubkeys_and_required_sigs(&first_secret, current_time)
         .map_err(|_| Error::SpendConditionsNotMet)?;
@@ -54,6 +54,9 @@ pub enum QuoteState {
     Paid,
     /// ecash issued for quote
     Issued,
+    /// Hold invoice of the quote cancelled and its payment refunded, the quote can no longer be
+    /// minted
+    Cancelled,
 }
 
 impl fmt::Display for QuoteState {
@@ -62,6 +65,7 @@ impl fmt::Display for QuoteState {
             Self::Unpaid => write!(f, "UNPAID"),
             Self::Paid => write!(f, "PAID"),
             Self::Issued => write!(f, "ISSUED"),
+            Self::Cancelled => write!(f, "CANCELLED"),
         }
     }
 }
@@ -74,6 +78,46 @@ impl FromStr for QuoteState {
             "PAID" => Ok(Self::Paid),
             "UNPAID" => Ok(Self::Unpaid),
             "ISSUED" => Ok(Self::Issued),
+            "CANCELLED" => Ok(Self::Cancelled),
+            _ => Err(Error::UnknownState),
+        }
+    }
+}
+
+/// State of the hold invoice of a mint quote
+///
+/// Mints paying quotes through hold invoices only settle the payment once the ecash is signed.
+/// A payment the backend accepted leaves the invoice open, the quote state tells it is paid. A
+/// cancelled invoice makes the quote [`QuoteState::Cancelled`].
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum HoldInvoiceState {
+    /// Waiting for a payment or holding one
+    Open,
+    /// Payment settled, the ecash of the quote is issued
+    Settled,
+    /// Payment refunded, the quote can no longer be minted
+    Cancelled,
+}
+
+impl fmt::Display for HoldInvoiceState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Open => write!(f, "OPEN"),
+            Self::Settled => write!(f, "SETTLED"),
+            Self::Cancelled => write!(f, "CANCELLED"),
+        }
+    }
+}
+
+impl FromStr for HoldInvoiceState {
+    type Err = Error;
+
+    fn from_str(state: &str) -> Result<Self, Self::Err> {
+        match state {
+            "OPEN" => Ok(Self::Open),
+            "SETTLED" => Ok(Self::Settled),
+            "CANCELLED" => Ok(Self::Cancelled),
             _ => Err(Error::UnknownState),
         }
     }
@@ -104,6 +148,9 @@ pub struct MintQuoteBolt11Response<Q> {
         deserialize_with = "deserialize_empty_string_as_none"
     )]
     pub pubkey: Option<PublicKey>,
+    /// State of the hold invoice, when the quote is paid through one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hold_invoice: Option<HoldInvoiceState>,
 }
 impl<Q: ToString> MintQuoteBolt11Response<Q> {
     /// Convert the MintQuote with a quote type Q to a String
@@ -116,6 +163,7 @@ impl<Q: ToString> MintQuoteBolt11Response<Q> {
             pubkey: self.pubkey,
             amount: self.amount,
             unit: self.unit.clone(),
+            hold_invoice: self.hold_invoice,
         }
     }
 }
@@ -131,6 +179,7 @@ impl From<MintQuoteBolt11Response<QuoteId>> for MintQuoteBolt11Response<String>
             pubkey: value.pubkey,
             amount: value.amount,
             unit: value.unit.clone(),
+            hold_invoice: value.hold_invoice,
         }
     }
 }
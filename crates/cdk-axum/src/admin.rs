@@ -0,0 +1,1170 @@
+//! Admin HTTP API of the mint
+//!
+//! Lets operators rotate keysets, adjust fees, update the mint info and the mint and melt limits,
+//! read the issued and
+//! redeemed totals of every keyset, audit the supply, list, search or expire quotes and list the
+//! melts settled internally while the mint is running. The
+//! router is meant to be served on its own listener, never next to the public mint routes.
+//!
+//! Every request carries `Authorization: Bearer <token>`. Read only tokens may send `GET`
+//! requests, manage tokens may send any request.
+
+use std::sync::Arc;
+
+use axum::extract::{Query, Request, State};
+use axum::http::{header, Method, StatusCode};
+use axum::middleware::{from_fn_with_state, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use cdk::mint::{
+    AuditIssue, AuditReport, ExpiredQuotes, InternalSettlement, MeltQuote, Mint, MintKeySetInfo,
+    MintMeltLimits, MintQuote, QuoteSearch, DEFAULT_QUOTE_SEARCH_LIMIT,
+};
+use cdk::nuts::{
+    ContactInfo, CurrencyUnit, Id, MeltQuoteState, MintInfo, MintQuoteState, PaymentMethod,
+};
+use cdk::Amount;
+use cdk_common::bearer::{BearerError, BearerTokens};
+use serde::{Deserialize, Serialize};
+
+use crate::router_handlers::into_response;
+
+/// What an admin token is allowed to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AdminRole {
+    /// Send `GET` requests only
+    ReadOnly,
+    /// Send any request
+    Manage,
+}
+
+impl AdminRole {
+    /// Role needed to send a request with `method`
+    fn required_for(method: &Method) -> Self {
+        if method == Method::GET {
+            Self::ReadOnly
+        } else {
+            Self::Manage
+        }
+    }
+}
+
+/// Bearer tokens accepted by the admin router
+#[derive(Debug, Clone, Default)]
+pub struct AdminAuth {
+    tokens: Arc<BearerTokens<AdminRole>>,
+}
+
+impl AdminAuth {
+    /// Accept `tokens`, each with its role
+    pub fn new(tokens: impl IntoIterator<Item = (String, AdminRole)>) -> Self {
+        Self {
+            tokens: Arc::new(BearerTokens::new(tokens)),
+        }
+    }
+
+    /// Whether no token is accepted, in which case every request is refused
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    fn authorize(&self, method: &Method, authorization: Option<&str>) -> Result<(), StatusCode> {
+        self.tokens
+            .authorize(authorization, &AdminRole::required_for(method))
+            .map_err(|err| match err {
+                BearerError::Missing | BearerError::Invalid => StatusCode::UNAUTHORIZED,
+                BearerError::Forbidden => StatusCode::FORBIDDEN,
+            })
+    }
+}
+
+async fn admin_auth_middleware(
+    State(auth): State<AdminAuth>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let authorization = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok());
+
+    match auth.authorize(request.method(), authorization) {
+        Ok(()) => next.run(request).await,
+        Err(status) => status.into_response(),
+    }
+}
+
+/// Create the admin [`Router`] of `mint`, accepting the tokens of `auth`
+///
+/// Routes are served under `/v1/admin`:
+///
+/// - `GET /info`, `POST /info`: read and update the mint info
+/// - `GET /limits`, `POST /limits`: read and update the mint and melt limits of a method
+/// - `GET /keysets`: keysets with their issued and redeemed totals
+/// - `POST /keysets/rotate`: rotate the keyset of a unit
+/// - `POST /keysets/fee`: change the input fee of a unit, rotating its keyset if it changed
+/// - `POST /keysets/issuance_cap`: lift or restore the issuance cap of a keyset
+/// - `GET /audit`: issued and redeemed totals checked against pending quotes and backend balances
+/// - `GET /quotes`: mint and melt quotes
+/// - `GET /quotes/mint/search`, `GET /quotes/melt/search`: quotes matching [`QuoteSearchParams`]
+/// - `POST /quotes/expire`: expire and purge stale quotes now
+/// - `GET /settlements`: melts settled internally, newest first
+pub fn create_admin_router(mint: Arc<Mint>, auth: AdminAuth) -> Router {
+    let admin_router = Router::new()
+        .route("/info", get(get_info).post(post_info))
+        .route("/limits", get(get_limits).post(post_limits))
+        .route("/keysets", get(get_keysets))
+        .route("/keysets/rotate", post(post_rotate_keyset))
+        .route("/keysets/fee", post(post_keyset_fee))
+        .route("/keysets/issuance_cap", post(post_issuance_cap_override))
+        .route("/audit", get(get_audit))
+        .route("/quotes", get(get_quotes))
+        .route("/quotes/mint/search", get(get_search_mint_quotes))
+        .route("/quotes/melt/search", get(get_search_melt_quotes))
+        .route("/quotes/expire", post(post_expire_quotes))
+        .route("/settlements", get(get_settlements))
+        .layer(from_fn_with_state(auth, admin_auth_middleware));
+
+    Router::new()
+        .nest("/v1/admin", admin_router)
+        .with_state(mint)
+}
+
+/// Fields of the mint info to change, the missing ones are kept
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MintInfoUpdate {
+    /// Name of the mint
+    pub name: Option<String>,
+    /// Short description
+    pub description: Option<String>,
+    /// Long description
+    pub description_long: Option<String>,
+    /// Message of the day
+    pub motd: Option<String>,
+    /// URL of the icon
+    pub icon_url: Option<String>,
+    /// URL of the terms of service
+    pub tos_url: Option<String>,
+    /// URLs the mint is reachable at
+    pub urls: Option<Vec<String>>,
+    /// Contact methods
+    pub contact: Option<Vec<ContactInfo>>,
+}
+
+impl MintInfoUpdate {
+    fn apply(self, mut info: MintInfo) -> MintInfo {
+        macro_rules! update {
+            ($($field:ident),*) => {
+                $(if let Some(value) = self.$field {
+                    info.$field = Some(value);
+                })*
+            };
+        }
+
+        update!(
+            name,
+            description,
+            description_long,
+            motd,
+            icon_url,
+            tos_url,
+            urls,
+            contact
+        );
+        info
+    }
+}
+
+/// Amount limits of a unit and payment method, listed by `GET /v1/admin/limits`
+///
+/// Sent to `POST /v1/admin/limits`, the missing limits are kept.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdminLimits {
+    /// Unit of the limits
+    pub unit: CurrencyUnit,
+    /// Payment method of the limits
+    pub method: PaymentMethod,
+    /// Smallest amount minted, none when the method is melt only
+    pub mint_min: Option<Amount>,
+    /// Largest amount minted, none when the method is melt only
+    pub mint_max: Option<Amount>,
+    /// Smallest amount melted, none when the method is mint only
+    pub melt_min: Option<Amount>,
+    /// Largest amount melted, none when the method is mint only
+    pub melt_max: Option<Amount>,
+}
+
+impl AdminLimits {
+    /// Limits of every unit and method in the NUT-04 and NUT-05 settings of `info`
+    fn from_info(info: &MintInfo) -> Vec<Self> {
+        let mut limits: Vec<Self> = info
+            .nuts
+            .nut04
+            .methods
+            .iter()
+            .map(|settings| Self {
+                unit: settings.unit.clone(),
+                method: settings.method.clone(),
+                mint_min: settings.min_amount,
+                mint_max: settings.max_amount,
+                melt_min: None,
+                melt_max: None,
+            })
+            .collect();
+
+        for settings in &info.nuts.nut05.methods {
+            match limits
+                .iter_mut()
+                .find(|limit| limit.unit == settings.unit && limit.method == settings.method)
+            {
+                Some(limit) => {
+                    limit.melt_min = settings.min_amount;
+                    limit.melt_max = settings.max_amount;
+                }
+                None => limits.push(Self {
+                    unit: settings.unit.clone(),
+                    method: settings.method.clone(),
+                    mint_min: None,
+                    mint_max: None,
+                    melt_min: settings.min_amount,
+                    melt_max: settings.max_amount,
+                }),
+            }
+        }
+
+        limits
+    }
+
+    /// Limits to set, the missing ones taken from `current`
+    fn merge(self, current: Option<&Self>) -> MintMeltLimits {
+        MintMeltLimits {
+            mint_min: self
+                .mint_min
+                .or_else(|| current.and_then(|current| current.mint_min))
+                .unwrap_or(Amount::ZERO),
+            mint_max: self
+                .mint_max
+                .or_else(|| current.and_then(|current| current.mint_max))
+                .unwrap_or(Amount::from(u64::MAX)),
+            melt_min: self
+                .melt_min
+                .or_else(|| current.and_then(|current| current.melt_min))
+                .unwrap_or(Amount::ZERO),
+            melt_max: self
+                .melt_max
+                .or_else(|| current.and_then(|current| current.melt_max))
+                .unwrap_or(Amount::from(u64::MAX)),
+        }
+    }
+}
+
+/// Keyset with the ecash it issued and redeemed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdminKeyset {
+    /// Keyset id
+    pub id: Id,
+    /// Unit of the keyset
+    pub unit: CurrencyUnit,
+    /// Whether the mint signs with the keyset
+    pub active: bool,
+    /// Amounts of the keys
+    pub amounts: Vec<u64>,
+    /// Input fee in parts per thousand
+    pub input_fee_ppk: u64,
+    /// Unix time after which the keyset expires
+    pub final_expiry: Option<u64>,
+    /// Total of the signatures of the keyset
+    pub issued: Amount,
+    /// Total of the proofs of the keyset spent
+    pub redeemed: Amount,
+}
+
+impl AdminKeyset {
+    fn new(info: MintKeySetInfo, issued: Amount, redeemed: Amount) -> Self {
+        Self {
+            id: info.id,
+            unit: info.unit,
+            active: info.active,
+            amounts: info.amounts,
+            input_fee_ppk: info.input_fee_ppk,
+            final_expiry: info.final_expiry,
+            issued,
+            redeemed,
+        }
+    }
+}
+
+/// Keyset rotation requested with `POST /v1/admin/keysets/rotate`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RotateKeysetRequest {
+    /// Unit to rotate
+    pub unit: CurrencyUnit,
+    /// Amounts of the new keys, those of the active keyset when empty
+    #[serde(default)]
+    pub amounts: Vec<u64>,
+    /// Input fee of the new keyset in parts per thousand, that of the active keyset when unset
+    pub input_fee_ppk: Option<u64>,
+    /// Unix time after which the new keyset expires
+    pub final_expiry: Option<u64>,
+    /// Reason recorded in the rotation log
+    pub reason: Option<String>,
+}
+
+/// Fee change requested with `POST /v1/admin/keysets/fee`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeysetFeeRequest {
+    /// Unit of the keyset
+    pub unit: CurrencyUnit,
+    /// Input fee in parts per thousand
+    pub input_fee_ppk: u64,
+    /// Amounts of the keys, those of the active keyset when empty
+    #[serde(default)]
+    pub amounts: Vec<u64>,
+}
+
+/// Outcome of `POST /v1/admin/keysets/fee`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeysetFeeResponse {
+    /// Whether a new keyset was created for the change
+    pub rotated: bool,
+    /// Active keyset of the unit
+    pub keyset: AdminKeyset,
+}
+
+/// Issuance cap override requested with `POST /v1/admin/keysets/issuance_cap`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IssuanceCapOverrideRequest {
+    /// Keyset whose cap is lifted or restored
+    pub keyset_id: Id,
+    /// Whether the cap is lifted
+    pub overridden: bool,
+}
+
+/// Keyset listed by `GET /v1/admin/audit`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdminKeysetAudit {
+    /// Keyset id
+    pub id: Id,
+    /// Unit of the keyset
+    pub unit: CurrencyUnit,
+    /// Whether the mint signs with the keyset
+    pub active: bool,
+    /// Total of the signatures of the keyset
+    pub issued: Amount,
+    /// Total of the proofs of the keyset spent
+    pub redeemed: Amount,
+    /// Ecash of the keyset still in circulation
+    pub outstanding: Amount,
+}
+
+/// Unit listed by `GET /v1/admin/audit`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdminUnitAudit {
+    /// Unit audited
+    pub unit: CurrencyUnit,
+    /// Ecash of the keysets of the unit still in circulation
+    pub outstanding: Amount,
+    /// Paid on mint quotes and not issued yet
+    pub unissued: Amount,
+    /// Ecash in circulation plus the ecash owed to paid mint quotes
+    pub liabilities: Amount,
+    /// Amount and fee reserve of the melt quotes being paid
+    pub pending_melts: Amount,
+    /// Largest balance reported by a backend of the unit, none when no backend can tell
+    pub backend_balance: Option<Amount>,
+}
+
+/// Problem listed by `GET /v1/admin/audit`, see [`AuditIssue`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AdminAuditIssue {
+    /// A keyset redeemed more ecash than it issued
+    RedeemedExceedsIssued {
+        /// Keyset id
+        keyset_id: Id,
+        /// Amount redeemed above the issued one
+        excess: Amount,
+    },
+    /// The liabilities of a unit are above the balance of its backends
+    Undercollateralized {
+        /// Unit short of funds
+        unit: CurrencyUnit,
+        /// Amount missing to cover the liabilities
+        shortfall: Amount,
+    },
+    /// A melt has been pending for too long
+    StuckMelt {
+        /// Id of the melt quote
+        quote_id: String,
+        /// Unit of the quote
+        unit: CurrencyUnit,
+        /// Amount and fee reserve of the quote
+        amount: Amount,
+    },
+    /// The balance of a backend could not be read
+    BackendUnavailable {
+        /// Unit the backend is registered for
+        unit: CurrencyUnit,
+        /// Name of the backend
+        backend: String,
+        /// Why the balance could not be read
+        error: String,
+    },
+}
+
+impl From<AuditIssue> for AdminAuditIssue {
+    fn from(issue: AuditIssue) -> Self {
+        match issue {
+            AuditIssue::RedeemedExceedsIssued { keyset_id, excess } => {
+                Self::RedeemedExceedsIssued { keyset_id, excess }
+            }
+            AuditIssue::Undercollateralized { unit, shortfall } => {
+                Self::Undercollateralized { unit, shortfall }
+            }
+            AuditIssue::StuckMelt {
+                quote_id,
+                unit,
+                amount,
+            } => Self::StuckMelt {
+                quote_id: quote_id.to_string(),
+                unit,
+                amount,
+            },
+            AuditIssue::BackendUnavailable {
+                unit,
+                backend,
+                error,
+            } => Self::BackendUnavailable {
+                unit,
+                backend,
+                error,
+            },
+        }
+    }
+}
+
+/// Body of `GET /v1/admin/audit`, see [`AuditReport`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdminAuditReport {
+    /// Whether no problem was found
+    pub balanced: bool,
+    /// Unix time of the audit
+    pub generated_at: u64,
+    /// Every keyset, ordered by unit then id
+    pub keysets: Vec<AdminKeysetAudit>,
+    /// Every unit with a keyset or a backend
+    pub units: Vec<AdminUnitAudit>,
+    /// Problems found
+    pub issues: Vec<AdminAuditIssue>,
+}
+
+impl From<AuditReport> for AdminAuditReport {
+    fn from(report: AuditReport) -> Self {
+        Self {
+            balanced: report.is_balanced(),
+            generated_at: report.generated_at,
+            keysets: report
+                .keysets
+                .into_iter()
+                .map(|keyset| AdminKeysetAudit {
+                    outstanding: keyset.outstanding(),
+                    id: keyset.id,
+                    unit: keyset.unit,
+                    active: keyset.active,
+                    issued: keyset.issued,
+                    redeemed: keyset.redeemed,
+                })
+                .collect(),
+            units: report
+                .units
+                .into_iter()
+                .map(|unit| AdminUnitAudit {
+                    liabilities: unit.liabilities(),
+                    unit: unit.unit,
+                    outstanding: unit.outstanding,
+                    unissued: unit.unissued,
+                    pending_melts: unit.pending_melts,
+                    backend_balance: unit.backend_balance,
+                })
+                .collect(),
+            issues: report.issues.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// Quote listed by `GET /v1/admin/quotes` and the quote searches
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdminQuote {
+    /// Quote id
+    pub id: String,
+    /// Unit of the quote
+    pub unit: CurrencyUnit,
+    /// Amount of the quote, none for amountless mint quotes
+    pub amount: Option<u64>,
+    /// State of the quote
+    pub state: String,
+    /// Payment method of the quote
+    pub payment_method: String,
+    /// Unix time the quote was created
+    pub created_time: u64,
+    /// Unix time the quote expires, 0 when it never does
+    pub expiry: u64,
+    /// Payment request of the quote
+    pub request: String,
+    /// Payment hash, or the lookup id of the payment for other methods
+    pub request_lookup_id: Option<String>,
+}
+
+impl From<MintQuote> for AdminQuote {
+    fn from(quote: MintQuote) -> Self {
+        Self {
+            id: quote.id.to_string(),
+            amount: quote.amount.as_ref().map(|amount| amount.value()),
+            state: quote.state().to_string(),
+            payment_method: quote.payment_method.to_string(),
+            created_time: quote.created_time,
+            expiry: quote.expiry,
+            request_lookup_id: Some(quote.request_lookup_id.to_string()),
+            request: quote.request,
+            unit: quote.unit,
+        }
+    }
+}
+
+impl From<MeltQuote> for AdminQuote {
+    fn from(quote: MeltQuote) -> Self {
+        Self {
+            id: quote.id.to_string(),
+            amount: Some(quote.amount().value()),
+            state: quote.state.to_string(),
+            payment_method: quote.payment_method.to_string(),
+            created_time: quote.created_time,
+            expiry: quote.expiry,
+            request: quote.request.to_string(),
+            request_lookup_id: quote.request_lookup_id.as_ref().map(ToString::to_string),
+            unit: quote.unit,
+        }
+    }
+}
+
+/// Most quotes a search returns at once
+pub const MAX_QUOTE_SEARCH_LIMIT: u64 = 1000;
+
+/// Query of `GET /v1/admin/quotes/mint/search` and `GET /v1/admin/quotes/melt/search`
+///
+/// Every parameter set must match.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QuoteSearchParams {
+    /// Payment hash, or the lookup id of the payment for other methods
+    pub payment_hash: Option<String>,
+    /// Part of the payment request, such as a bolt11 invoice
+    pub request: Option<String>,
+    /// Smallest amount
+    pub min_amount: Option<u64>,
+    /// Largest amount
+    pub max_amount: Option<u64>,
+    /// State of the quote, such as `PAID`
+    pub state: Option<String>,
+    /// Created at or after this unix time
+    pub since: Option<u64>,
+    /// Created before this unix time
+    pub until: Option<u64>,
+    /// Quotes skipped
+    #[serde(default)]
+    pub offset: u64,
+    /// Most quotes returned, up to [`MAX_QUOTE_SEARCH_LIMIT`]
+    pub limit: Option<u64>,
+}
+
+impl QuoteSearchParams {
+    /// Search for quotes whose state parses from [`QuoteSearchParams::state`]
+    fn into_search<S: std::str::FromStr>(self) -> Result<QuoteSearch<S>, Response> {
+        let state = self
+            .state
+            .map(|state| state.to_uppercase().parse())
+            .transpose()
+            .map_err(|_| (StatusCode::BAD_REQUEST, "Unknown quote state").into_response())?;
+
+        Ok(QuoteSearch {
+            lookup_id: self.payment_hash,
+            request: self.request,
+            min_amount: self.min_amount,
+            max_amount: self.max_amount,
+            state,
+            created_after: self.since,
+            created_before: self.until,
+            offset: self.offset,
+            limit: self
+                .limit
+                .unwrap_or(DEFAULT_QUOTE_SEARCH_LIMIT)
+                .min(MAX_QUOTE_SEARCH_LIMIT),
+        })
+    }
+}
+
+/// Page of quotes found by a quote search
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AdminQuotePage {
+    /// Quotes found, newest first
+    pub quotes: Vec<AdminQuote>,
+    /// Offset of the next page, none on the last page
+    pub next_offset: Option<u64>,
+}
+
+impl AdminQuotePage {
+    fn new<Q: Into<AdminQuote>, S>(quotes: Vec<Q>, search: &QuoteSearch<S>) -> Self {
+        let full = quotes.len() as u64 == search.limit && search.limit > 0;
+        Self {
+            next_offset: full.then(|| search.offset.saturating_add(search.limit)),
+            quotes: quotes.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// Quotes listed by `GET /v1/admin/quotes`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AdminQuotes {
+    /// Mint quotes
+    pub mint: Vec<AdminQuote>,
+    /// Melt quotes
+    pub melt: Vec<AdminQuote>,
+}
+
+/// Melt settled internally, listed by `GET /v1/admin/settlements`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdminInternalSettlement {
+    /// Unix time of the settlement
+    pub created_time: u64,
+    /// Melt quote that was paid
+    pub melt_quote_id: String,
+    /// Mint quote the payment was credited to
+    pub mint_quote_id: String,
+    /// Unit of both quotes
+    pub unit: CurrencyUnit,
+    /// Amount settled
+    pub amount: Amount,
+    /// Fee reserve of the melt quote, none of it is spent on routing
+    pub fee_reserve: Amount,
+}
+
+impl From<InternalSettlement> for AdminInternalSettlement {
+    fn from(settlement: InternalSettlement) -> Self {
+        Self {
+            created_time: settlement.created_time,
+            melt_quote_id: settlement.melt_quote_id.to_string(),
+            mint_quote_id: settlement.mint_quote_id.to_string(),
+            unit: settlement.unit,
+            amount: settlement.amount,
+            fee_reserve: settlement.fee_reserve,
+        }
+    }
+}
+
+/// Outcome of `POST /v1/admin/quotes/expire`, see [`ExpiredQuotes`]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct AdminExpiredQuotes {
+    /// Unpaid melt quotes marked as failed
+    pub melt_expired: u64,
+    /// Unpaid mint quotes removed from the database
+    pub mint_purged: u64,
+    /// Unpaid and failed melt quotes removed from the database
+    pub melt_purged: u64,
+    /// Hold invoices of mint quotes that expired without being minted cancelled
+    #[serde(default)]
+    pub hold_invoices_cancelled: u64,
+}
+
+impl From<ExpiredQuotes> for AdminExpiredQuotes {
+    fn from(expired: ExpiredQuotes) -> Self {
+        Self {
+            melt_expired: expired.melt_expired,
+            mint_purged: expired.mint_purged,
+            melt_purged: expired.melt_purged,
+            hold_invoices_cancelled: expired.hold_invoices_cancelled,
+        }
+    }
+}
+
+async fn get_info(State(mint): State<Arc<Mint>>) -> Result<Json<MintInfo>, Response> {
+    Ok(Json(mint.mint_info().await.map_err(into_response)?))
+}
+
+async fn post_info(
+    State(mint): State<Arc<Mint>>,
+    Json(update): Json<MintInfoUpdate>,
+) -> Result<Json<MintInfo>, Response> {
+    let info = update.apply(mint.mint_info().await.map_err(into_response)?);
+    mint.set_mint_info(info.clone())
+        .await
+        .map_err(into_response)?;
+
+    Ok(Json(info))
+}
+
+async fn get_limits(State(mint): State<Arc<Mint>>) -> Result<Json<Vec<AdminLimits>>, Response> {
+    let info = mint.mint_info().await.map_err(into_response)?;
+    Ok(Json(AdminLimits::from_info(&info)))
+}
+
+async fn post_limits(
+    State(mint): State<Arc<Mint>>,
+    Json(update): Json<AdminLimits>,
+) -> Result<Json<AdminLimits>, Response> {
+    let info = mint.mint_info().await.map_err(into_response)?;
+    let current = AdminLimits::from_info(&info);
+    let current = current
+        .iter()
+        .find(|limit| limit.unit == update.unit && limit.method == update.method);
+
+    let unit = update.unit.clone();
+    let method = update.method.clone();
+    mint.set_mint_melt_limits(&unit, &method, update.merge(current))
+        .await
+        .map_err(into_response)?;
+
+    let info = mint.mint_info().await.map_err(into_response)?;
+    AdminLimits::from_info(&info)
+        .into_iter()
+        .find(|limit| limit.unit == unit && limit.method == method)
+        .map(Json)
+        .ok_or_else(|| StatusCode::NOT_FOUND.into_response())
+}
+
+/// Keysets of the mint with their totals
+async fn admin_keysets(mint: &Mint) -> Result<Vec<AdminKeyset>, Response> {
+    let issued = mint.total_issued().await.map_err(into_response)?;
+    let redeemed = mint.total_redeemed().await.map_err(into_response)?;
+
+    Ok(mint
+        .keysets()
+        .keysets
+        .into_iter()
+        .filter_map(|keyset| mint.get_keyset_info(&keyset.id))
+        .map(|info| {
+            let issued = issued.get(&info.id).copied().unwrap_or_default();
+            let redeemed = redeemed.get(&info.id).copied().unwrap_or_default();
+            AdminKeyset::new(info, issued, redeemed)
+        })
+        .collect())
+}
+
+async fn get_keysets(State(mint): State<Arc<Mint>>) -> Result<Json<Vec<AdminKeyset>>, Response> {
+    Ok(Json(admin_keysets(&mint).await?))
+}
+
+async fn post_rotate_keyset(
+    State(mint): State<Arc<Mint>>,
+    Json(request): Json<RotateKeysetRequest>,
+) -> Result<Json<AdminKeyset>, Response> {
+    let input_fee_ppk = match request.input_fee_ppk {
+        Some(input_fee_ppk) => input_fee_ppk,
+        None => mint
+            .keysets()
+            .keysets
+            .into_iter()
+            .find(|keyset| keyset.active && keyset.unit == request.unit)
+            .map(|keyset| keyset.input_fee_ppk)
+            .unwrap_or_default(),
+    };
+
+    let info = mint
+        .rotate_keyset_with_reason(
+            request.unit,
+            request.amounts,
+            input_fee_ppk,
+            true,
+            request.final_expiry,
+            request.reason,
+        )
+        .await
+        .map_err(into_response)?;
+
+    Ok(Json(AdminKeyset::new(info, Amount::ZERO, Amount::ZERO)))
+}
+
+async fn post_keyset_fee(
+    State(mint): State<Arc<Mint>>,
+    Json(request): Json<KeysetFeeRequest>,
+) -> Result<Json<KeysetFeeResponse>, Response> {
+    let previous = mint
+        .keysets()
+        .keysets
+        .into_iter()
+        .find(|keyset| keyset.active && keyset.unit == request.unit)
+        .map(|keyset| keyset.id);
+
+    let info = mint
+        .update_keyset_config(request.unit, request.amounts, request.input_fee_ppk)
+        .await
+        .map_err(into_response)?;
+
+    let keyset = admin_keysets(&mint)
+        .await?
+        .into_iter()
+        .find(|keyset| keyset.id == info.id)
+        .unwrap_or_else(|| AdminKeyset::new(info, Amount::ZERO, Amount::ZERO));
+
+    Ok(Json(KeysetFeeResponse {
+        rotated: previous != Some(keyset.id),
+        keyset,
+    }))
+}
+
+async fn post_issuance_cap_override(
+    State(mint): State<Arc<Mint>>,
+    Json(request): Json<IssuanceCapOverrideRequest>,
+) -> Result<StatusCode, Response> {
+    mint.override_issuance_cap(request.keyset_id, request.overridden)
+        .await
+        .map_err(into_response)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn get_audit(State(mint): State<Arc<Mint>>) -> Result<Json<AdminAuditReport>, Response> {
+    Ok(Json(
+        mint.audit_report().await.map_err(into_response)?.into(),
+    ))
+}
+
+async fn get_quotes(State(mint): State<Arc<Mint>>) -> Result<Json<AdminQuotes>, Response> {
+    let mint_quotes = mint.mint_quotes().await.map_err(into_response)?;
+    let melt_quotes = mint.melt_quotes().await.map_err(into_response)?;
+
+    Ok(Json(AdminQuotes {
+        mint: mint_quotes.into_iter().map(Into::into).collect(),
+        melt: melt_quotes.into_iter().map(Into::into).collect(),
+    }))
+}
+
+async fn get_search_mint_quotes(
+    State(mint): State<Arc<Mint>>,
+    Query(params): Query<QuoteSearchParams>,
+) -> Result<Json<AdminQuotePage>, Response> {
+    let search = params.into_search::<MintQuoteState>()?;
+    let quotes = mint
+        .search_mint_quotes(&search)
+        .await
+        .map_err(into_response)?;
+
+    Ok(Json(AdminQuotePage::new(quotes, &search)))
+}
+
+async fn get_search_melt_quotes(
+    State(mint): State<Arc<Mint>>,
+    Query(params): Query<QuoteSearchParams>,
+) -> Result<Json<AdminQuotePage>, Response> {
+    let search = params.into_search::<MeltQuoteState>()?;
+    let quotes = mint
+        .search_melt_quotes(&search)
+        .await
+        .map_err(into_response)?;
+
+    Ok(Json(AdminQuotePage::new(quotes, &search)))
+}
+
+async fn post_expire_quotes(
+    State(mint): State<Arc<Mint>>,
+) -> Result<Json<AdminExpiredQuotes>, Response> {
+    Ok(Json(
+        mint.expire_stale_quotes()
+            .await
+            .map_err(into_response)?
+            .into(),
+    ))
+}
+
+async fn get_settlements(
+    State(mint): State<Arc<Mint>>,
+) -> Result<Json<Vec<AdminInternalSettlement>>, Response> {
+    Ok(Json(
+        mint.internal_settlements(None)
+            .await
+            .map_err(into_response)?
+            .into_iter()
+            .map(Into::into)
+            .collect(),
+    ))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::collections::HashMap;
+
+    use axum::body::Body;
+    use cdk_signatory::db_signatory::DbSignatory;
+    use cdk_sqlite::mint::memory;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    pub(crate) async fn create_test_mint() -> Arc<Mint> {
+        let localstore = Arc::new(memory::empty().await.expect("in-memory db"));
+        let signatory = Arc::new(
+            DbSignatory::new(
+                localstore.clone(),
+                &[0u8; 32],
+                HashMap::from([(
+                    CurrencyUnit::Sat,
+                    (0, (0..32).map(|i| 2u64.pow(i)).collect()),
+                )]),
+                HashMap::new(),
+            )
+            .await
+            .expect("signatory"),
+        );
+
+        let mint = Mint::new(
+            MintInfo::default().name("admin test"),
+            signatory,
+            localstore,
+            HashMap::new(),
+            1000,
+            1000,
+        )
+        .await
+        .expect("mint");
+        mint.set_mint_info(MintInfo::default().name("admin test"))
+            .await
+            .expect("mint info");
+
+        Arc::new(mint)
+    }
+
+    fn test_router(mint: Arc<Mint>) -> Router {
+        create_admin_router(
+            mint,
+            AdminAuth::new([
+                ("reader".to_string(), AdminRole::ReadOnly),
+                ("admin".to_string(), AdminRole::Manage),
+            ]),
+        )
+    }
+
+    fn request(method: Method, path: &str, token: Option<&str>, body: Option<&str>) -> Request {
+        let mut builder = axum::http::Request::builder()
+            .method(method)
+            .uri(path)
+            .header(header::CONTENT_TYPE, "application/json");
+        if let Some(token) = token {
+            builder = builder.header(header::AUTHORIZATION, format!("Bearer {token}"));
+        }
+
+        builder
+            .body(body.map_or_else(Body::empty, |body| Body::from(body.to_owned())))
+            .expect("test request should build")
+    }
+
+    async fn send(router: &Router, request: Request) -> (StatusCode, serde_json::Value) {
+        let response = router
+            .clone()
+            .oneshot(request)
+            .await
+            .expect("test service should respond");
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("body");
+
+        (
+            status,
+            serde_json::from_slice(&body).unwrap_or(serde_json::Value::Null),
+        )
+    }
+
+    #[tokio::test]
+    async fn requests_need_a_token_of_the_right_role() {
+        let router = test_router(create_test_mint().await);
+        let update = Some(r#"{"motd":"maintenance"}"#);
+
+        let (status, _) = send(&router, request(Method::GET, "/v1/admin/info", None, None)).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let (status, _) = send(
+            &router,
+            request(Method::GET, "/v1/admin/info", Some("unknown"), None),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let (status, info) = send(
+            &router,
+            request(Method::GET, "/v1/admin/info", Some("reader"), None),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(info["name"], "admin test");
+
+        let (status, _) = send(
+            &router,
+            request(Method::POST, "/v1/admin/info", Some("reader"), update),
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let (status, info) = send(
+            &router,
+            request(Method::POST, "/v1/admin/info", Some("admin"), update),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(info["motd"], "maintenance");
+        assert_eq!(info["name"], "admin test");
+    }
+
+    #[tokio::test]
+    async fn fees_are_adjusted_by_rotating_the_keyset() {
+        let mint = create_test_mint().await;
+        let router = test_router(Arc::clone(&mint));
+
+        let (status, keysets) = send(
+            &router,
+            request(Method::GET, "/v1/admin/keysets", Some("reader"), None),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(keysets.as_array().map(Vec::len), Some(1));
+        assert_eq!(keysets[0]["issued"], 0);
+        assert_eq!(keysets[0]["redeemed"], 0);
+        let previous = keysets[0]["id"].clone();
+
+        let (status, response) = send(
+            &router,
+            request(
+                Method::POST,
+                "/v1/admin/keysets/fee",
+                Some("admin"),
+                Some(r#"{"unit":"sat","input_fee_ppk":100}"#),
+            ),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(response["rotated"], true);
+        assert_eq!(response["keyset"]["input_fee_ppk"], 100);
+        assert_ne!(response["keyset"]["id"], previous);
+
+        let active: Vec<_> = mint
+            .keysets()
+            .keysets
+            .into_iter()
+            .filter(|keyset| keyset.active && keyset.unit == CurrencyUnit::Sat)
+            .collect();
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].input_fee_ppk, 100);
+
+        // Rotating without a fee keeps the one of the active keyset
+        let (status, response) = send(
+            &router,
+            request(
+                Method::POST,
+                "/v1/admin/keysets/rotate",
+                Some("admin"),
+                Some(r#"{"unit":"sat"}"#),
+            ),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(response["keyset"]["input_fee_ppk"], 100);
+    }
+
+    #[tokio::test]
+    async fn audit_reports_every_keyset() {
+        let router = test_router(create_test_mint().await);
+
+        let (status, report) = send(
+            &router,
+            request(Method::GET, "/v1/admin/audit", Some("reader"), None),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(report["balanced"], true);
+        assert_eq!(report["keysets"].as_array().map(Vec::len), Some(1));
+        assert_eq!(report["keysets"][0]["outstanding"], 0);
+        assert_eq!(report["units"][0]["unit"], "sat");
+        assert_eq!(report["units"][0]["liabilities"], 0);
+        assert!(report["units"][0]["backend_balance"].is_null());
+    }
+
+    #[tokio::test]
+    async fn limits_are_updated() {
+        let mint = create_test_mint().await;
+        let mut info = mint.mint_info().await.expect("mint info");
+        info.nuts.nut05.methods.push(cdk::nuts::MeltMethodSettings {
+            method: PaymentMethod::from("bolt11"),
+            unit: CurrencyUnit::Sat,
+            min_amount: Some(Amount::from(1)),
+            max_amount: Some(Amount::from(10_000)),
+            options: None,
+        });
+        mint.set_mint_info(info).await.expect("mint info");
+        let router = test_router(mint);
+
+        let (status, limits) = send(
+            &router,
+            request(Method::GET, "/v1/admin/limits", Some("reader"), None),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(limits[0]["method"], "bolt11");
+        assert!(limits[0]["mint_max"].is_null());
+        assert_eq!(limits[0]["melt_max"], 10_000);
+
+        let (status, limits) = send(
+            &router,
+            request(
+                Method::POST,
+                "/v1/admin/limits",
+                Some("admin"),
+                Some(r#"{"unit":"sat","method":"bolt11","melt_max":500}"#),
+            ),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(limits["melt_min"], 1);
+        assert_eq!(limits["melt_max"], 500);
+
+        let (status, _) = send(
+            &router,
+            request(
+                Method::POST,
+                "/v1/admin/limits",
+                Some("admin"),
+                Some(r#"{"unit":"sat","method":"bolt12","melt_max":500}"#),
+            ),
+        )
+        .await;
+        assert_ne!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn quotes_are_searched() {
+        let router = test_router(create_test_mint().await);
+
+        let (status, page) = send(
+            &router,
+            request(
+                Method::GET,
+                "/v1/admin/quotes/melt/search?state=paid&min_amount=10&limit=5",
+                Some("reader"),
+                None,
+            ),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(page["quotes"].as_array().map(Vec::len), Some(0));
+        assert!(page["next_offset"].is_null());
+
+        let (status, _) = send(
+            &router,
+            request(
+                Method::GET,
+                "/v1/admin/quotes/mint/search?state=settled",
+                Some("reader"),
+                None,
+            ),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}
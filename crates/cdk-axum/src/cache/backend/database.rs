@@ -0,0 +1,86 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use cdk::mint::Mint;
+use cdk::util::hex;
+
+use crate::cache::{HttpCacheKey, HttpCacheStorage, DEFAULT_TTL_SECS};
+
+/// Writes between two prunes of the expired responses
+const PRUNE_EVERY_WRITES: u64 = 1_000;
+
+/// Mint database storage for the HTTP cache.
+///
+/// The responses are stored in the key-value store of the mint, so a swap or mint retried
+/// after a restart, or against another instance sharing the database, still gets its original
+/// signatures back. Entries expire after the ttl, the tti is not applied.
+pub struct HttpCacheDatabase {
+    mint: Arc<Mint>,
+    cache_ttl: Duration,
+    writes: AtomicU64,
+}
+
+impl std::fmt::Debug for HttpCacheDatabase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HttpCacheDatabase")
+            .field("cache_ttl", &self.cache_ttl)
+            .finish_non_exhaustive()
+    }
+}
+
+impl HttpCacheDatabase {
+    /// Create a cache storage backed by the database of `mint`.
+    pub fn new(mint: Arc<Mint>) -> Self {
+        Self {
+            mint,
+            cache_ttl: Duration::from_secs(DEFAULT_TTL_SECS),
+            writes: AtomicU64::new(0),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl HttpCacheStorage for HttpCacheDatabase {
+    fn set_expiration_times(&mut self, cache_ttl: Duration, _cache_tti: Duration) {
+        self.cache_ttl = cache_ttl;
+    }
+
+    async fn get(&self, key: &HttpCacheKey) -> Option<Vec<u8>> {
+        self.mint
+            .cached_response(&hex::encode(**key))
+            .await
+            .map_err(|err| {
+                tracing::error!("Failed to get value from the mint database: {}", err);
+                err
+            })
+            .ok()?
+    }
+
+    async fn set(&self, key: HttpCacheKey, value: Vec<u8>) {
+        if let Err(err) = self
+            .mint
+            .cache_response(&hex::encode(*key), &value, self.cache_ttl)
+            .await
+        {
+            tracing::error!("Failed to set value in the mint database: {}", err);
+        }
+
+        if self.writes.fetch_add(1, Ordering::Relaxed) % PRUNE_EVERY_WRITES
+            == PRUNE_EVERY_WRITES - 1
+        {
+            let mint = Arc::clone(&self.mint);
+            tokio::spawn(async move {
+                if let Err(err) = mint.prune_cached_responses().await {
+                    tracing::warn!("Failed to prune the cached responses: {}", err);
+                }
+            });
+        }
+    }
+
+    async fn remove(&self, key: &HttpCacheKey) {
+        if let Err(err) = self.mint.remove_cached_response(&hex::encode(**key)).await {
+            tracing::error!("Failed to remove value from the mint database: {}", err);
+        }
+    }
+}
@@ -43,4 +43,8 @@ impl HttpCacheStorage for InMemoryHttpCache {
     async fn set(&self, key: HttpCacheKey, value: Vec<u8>) {
         self.0.insert(key, value).await;
     }
+
+    async fn remove(&self, key: &HttpCacheKey) {
+        self.0.invalidate(key).await;
+    }
 }
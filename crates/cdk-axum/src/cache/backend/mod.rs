@@ -1,7 +1,9 @@
+mod database;
 mod memory;
 #[cfg(feature = "redis")]
 mod redis;
 
+pub use self::database::HttpCacheDatabase;
 pub use self::memory::InMemoryHttpCache;
 #[cfg(feature = "redis")]
 pub use self::redis::{Config as RedisConfig, HttpCacheRedis, RedisClient};
@@ -144,4 +144,24 @@ impl HttpCacheStorage for HttpCacheRedis {
             }
         }
     }
+
+    async fn remove(&self, key: &HttpCacheKey) {
+        let mut db_key = self.prefix.clone().unwrap_or_default();
+        db_key.extend(&**key);
+
+        match &self.client {
+            RedisClient::Single(conn) => {
+                let _: Result<(), _> = conn.clone().del(db_key).await.map_err(|err| {
+                    tracing::error!("Failed to remove value from redis: {}", err);
+                    err
+                });
+            }
+            RedisClient::Cluster(conn) => {
+                let _: Result<(), _> = conn.clone().del(db_key).await.map_err(|err| {
+                    tracing::error!("Failed to remove value from redis cluster: {}", err);
+                    err
+                });
+            }
+        }
+    }
 }
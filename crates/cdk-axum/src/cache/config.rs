@@ -20,6 +20,8 @@ pub const ENV_CDK_MINTD_CACHE_TTL: &str = "CDK_MINTD_CACHE_TTL";
 pub enum Backend {
     #[default]
     Memory,
+    /// Key-value store of the mint database, surviving restarts
+    Database,
     #[cfg(feature = "redis")]
     Redis(super::backend::RedisConfig),
 }
@@ -28,6 +30,7 @@ impl Backend {
     pub fn from_env_str(backend_str: &str) -> Option<Self> {
         match backend_str.to_lowercase().as_str() {
             "memory" => Some(Self::Memory),
+            "database" => Some(Self::Database),
             #[cfg(feature = "redis")]
             "redis" => Some(Self::Redis(redis_config_from_env())),
             _ => None,
@@ -78,6 +81,11 @@ pub struct Config {
 }
 
 impl Config {
+    /// Time to live of the cache entries in seconds, the default one if unset
+    pub fn ttl_secs(&self) -> u64 {
+        self.ttl.unwrap_or(super::DEFAULT_TTL_SECS)
+    }
+
     /// Config from env
     pub fn from_env(mut self) -> Self {
         use std::env;
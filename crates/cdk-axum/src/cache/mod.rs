@@ -7,14 +7,17 @@
 //! idempotent operations.
 //!
 //! This mod also provides common backend implementations as well, such as In
-//! Memory (default) and Redis.
+//! Memory (default), the mint database and Redis.
 use std::ops::Deref;
 use std::sync::Arc;
 use std::time::Duration;
 
+use cdk::mint::{Mint, MintChange};
+use cdk::nuts::Id;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use sha2::{Digest, Sha256};
+use tokio::sync::broadcast::error::RecvError;
 
 mod backend;
 mod config;
@@ -33,6 +36,27 @@ pub trait HttpCacheStorage {
 
     /// Set a value in the cache.
     async fn set(&self, key: HttpCacheKey, value: Vec<u8>);
+
+    /// Remove a value from the cache.
+    ///
+    /// Storages that cannot remove values keep serving them until they expire.
+    async fn remove(&self, _key: &HttpCacheKey) {}
+}
+
+/// Public GET endpoint whose response is cached
+///
+/// The responses only change when the mint rotates its keysets or updates its info, so they
+/// are kept for the configured ttl and tti unless [`HttpCache::bust`] drops them earlier.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum CachedRoute {
+    /// `GET /v1/keys`
+    Keys,
+    /// `GET /v1/keysets`
+    Keysets,
+    /// `GET /v1/keys/{keyset_id}`
+    KeysetPubkeys(Id),
+    /// `GET /v1/info`
+    Info,
 }
 
 /// Http cache with a pluggable storage backend.
@@ -97,12 +121,34 @@ impl HttpCache {
     /// awaiting async operations. Using a sync `From` trait with
     /// `Handle::current().block_on()` would panic when called from within
     /// an active Tokio runtime.
+    ///
+    /// Fails for the database backend, which needs the mint, see
+    /// [`HttpCache::from_config_with_mint`].
     pub async fn from_config(config: config::Config) -> anyhow::Result<Self> {
-        let ttl = Duration::from_secs(config.ttl.unwrap_or(DEFAULT_TTL_SECS));
+        Self::from_config_with_mint(config, None).await
+    }
+
+    /// Create an `HttpCache` from the given configuration, storing the responses in the
+    /// database of `mint` for the database backend.
+    pub async fn from_config_with_mint(
+        config: config::Config,
+        mint: Option<Arc<Mint>>,
+    ) -> anyhow::Result<Self> {
+        let ttl = Duration::from_secs(config.ttl_secs());
         let tti = Duration::from_secs(config.tti.unwrap_or(DEFAULT_TTI_SECS));
 
         match config.backend {
             config::Backend::Memory => Ok(Self::new(ttl, tti, None)),
+            config::Backend::Database => {
+                let mint = mint.ok_or_else(|| {
+                    anyhow::anyhow!("The database cache backend needs the mint to be given")
+                })?;
+                Ok(Self::new(
+                    ttl,
+                    tti,
+                    Some(Box::new(HttpCacheDatabase::new(mint))),
+                ))
+            }
             #[cfg(feature = "redis")]
             config::Backend::Redis(redis_config) => {
                 let redis_client = if redis_config.use_cluster {
@@ -265,4 +311,82 @@ impl HttpCache {
             self.storage.set(key, bytes).await;
         }
     }
+
+    /// Cache key of a swap or melt request from its canonical hash
+    ///
+    /// Replays listing the inputs in another order, or with other witnesses, hit the same entry.
+    pub fn request_key<H: std::fmt::Display>(
+        &self,
+        route: &str,
+        canonical_hash: H,
+    ) -> Option<HttpCacheKey> {
+        self.calculate_key(&("POST", route, canonical_hash.to_string()))
+    }
+
+    /// Cache key of a public GET endpoint, distinct from the keys of request payloads
+    fn route_key(&self, route: &CachedRoute) -> Option<HttpCacheKey> {
+        self.calculate_key(&("GET", route))
+    }
+
+    /// Get the cached response of a public GET endpoint.
+    pub async fn get_route<V>(self: &Arc<Self>, route: &CachedRoute) -> Option<V>
+    where
+        V: DeserializeOwned,
+    {
+        self.get(&self.route_key(route)?).await
+    }
+
+    /// Cache the response of a public GET endpoint.
+    pub async fn set_route<V: Serialize>(self: &Arc<Self>, route: &CachedRoute, value: &V) {
+        if let Some(key) = self.route_key(route) {
+            self.set(key, value).await;
+        }
+    }
+
+    /// Drop the cached responses made stale by `change`.
+    ///
+    /// `GET /v1/keys/{keyset_id}` carries the `active` flag of the keyset, so the responses of
+    /// every keyset of the mint are dropped on rotation.
+    pub async fn bust(self: &Arc<Self>, mint: &Mint, change: MintChange) {
+        let routes = match change {
+            MintChange::Info => vec![CachedRoute::Info],
+            MintChange::Keysets => [CachedRoute::Keys, CachedRoute::Keysets]
+                .into_iter()
+                .chain(
+                    mint.keysets()
+                        .keysets
+                        .into_iter()
+                        .map(|keyset| CachedRoute::KeysetPubkeys(keyset.id)),
+                )
+                .collect(),
+        };
+
+        for route in routes {
+            if let Some(key) = self.route_key(&route) {
+                self.storage.remove(&key).await;
+            }
+        }
+    }
+
+    /// Bust the cache whenever the mint reports a change, until the mint shuts down.
+    pub async fn bust_on_changes(self: Arc<Self>, mint: Arc<Mint>) {
+        let mut changes = mint.subscribe_changes();
+        let shutdown = mint.shutdown_token();
+
+        loop {
+            let changes = tokio::select! {
+                _ = shutdown.cancelled() => break,
+                change = changes.recv() => match change {
+                    Ok(change) => vec![change],
+                    Err(RecvError::Lagged(_)) => vec![MintChange::Info, MintChange::Keysets],
+                    Err(RecvError::Closed) => break,
+                },
+            };
+
+            for change in changes {
+                tracing::debug!("Busting HTTP cache after {:?} change", change);
+                self.bust(&mint, change).await;
+            }
+        }
+    }
 }
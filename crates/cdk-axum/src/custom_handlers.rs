@@ -25,7 +25,10 @@ use serde_json::Value;
 use tracing::instrument;
 
 use crate::auth::AuthHeader;
-use crate::router_handlers::into_response;
+use crate::router_handlers::{
+    into_response, into_response_with_report, record_double_spend, wants_verification_report,
+    ClientFingerprint,
+};
 use crate::MintState;
 
 const PREFER_HEADER_KEY: &str = "Prefer";
@@ -514,6 +517,34 @@ pub async fn get_check_melt_custom_quote(
     Ok(melt_quote_response_to_json(quote))
 }
 
+/// Error response of a failed melt, with its verification report when the inputs or outputs
+/// were refused
+async fn melt_error_response(
+    state: &MintState,
+    method: &str,
+    client: ClientFingerprint,
+    payload: &cdk::nuts::MeltRequest<QuoteId>,
+    err: cdk::Error,
+) -> Response {
+    record_double_spend(
+        state,
+        &err,
+        RoutePath::Melt(method.to_owned()),
+        Some(payload.quote().clone()),
+        client,
+        payload.inputs(),
+    )
+    .await;
+
+    let report = if wants_verification_report(&err) {
+        state.mint.melt_verification_report(payload).await
+    } else {
+        None
+    };
+
+    into_response_with_report(err, report)
+}
+
 async fn process_melt_request(
     prefer: PreferHeader,
     state: &MintState,
@@ -599,6 +630,7 @@ pub async fn cache_post_mint_custom(
 pub async fn cache_post_melt_custom(
     auth: AuthHeader,
     prefer: PreferHeader,
+    client: ClientFingerprint,
     state: State<MintState>,
     method: Path<String>,
     payload: Json<Value>,
@@ -614,20 +646,31 @@ pub async fn cache_post_melt_custom(
         .await
         .map_err(into_response)?;
 
-    let cache_key =
-        match mint_state
+    let cache_key = match parsed_payload.canonical_hash().ok().and_then(|hash| {
+        mint_state
             .cache
-            .calculate_key(&("melt", method.as_str(), &parsed_payload))
-        {
-            Some(key) => key,
-            None => {
-                let result = process_melt_request(prefer, &mint_state, &method, &parsed_payload)
-                    .await
-                    .map_err(into_response)?;
-
-                return Ok(melt_quote_response_to_json(result));
-            }
-        };
+            .request_key(&format!("melt/{method}"), hash)
+    }) {
+        Some(key) => key,
+        None => {
+            let result =
+                match process_melt_request(prefer, &mint_state, &method, &parsed_payload).await {
+                    Ok(result) => result,
+                    Err(err) => {
+                        return Err(melt_error_response(
+                            &mint_state,
+                            &method,
+                            client,
+                            &parsed_payload,
+                            err,
+                        )
+                        .await)
+                    }
+                };
+
+            return Ok(melt_quote_response_to_json(result));
+        }
+    };
 
     if let Some(cached_response) = mint_state
         .cache
@@ -637,9 +680,14 @@ pub async fn cache_post_melt_custom(
         return Ok(melt_quote_response_to_json(cached_response));
     }
 
-    let result = process_melt_request(prefer, &mint_state, &method, &parsed_payload)
-        .await
-        .map_err(into_response)?;
+    let result = match process_melt_request(prefer, &mint_state, &method, &parsed_payload).await {
+        Ok(result) => result,
+        Err(err) => {
+            return Err(
+                melt_error_response(&mint_state, &method, client, &parsed_payload, err).await,
+            )
+        }
+    };
 
     mint_state.cache.set(cache_key, &result).await;
 
@@ -733,13 +781,15 @@ mod tests {
     use bip39::Mnemonic;
     use cdk::mint::{MintBuilder, MintMeltLimits, MintQuoteResponse};
     use cdk::nuts::nut00::KnownMethod;
-    use cdk::nuts::{BlindedMessage, CurrencyUnit, MintQuoteState, PaymentMethod, SecretKey};
+    use cdk::nuts::{
+        BlindedMessage, CurrencyUnit, MintInfo, MintQuoteState, PaymentMethod, SecretKey,
+    };
     use cdk::types::{FeeReserve, QuoteTTL};
     use cdk::Amount;
     use cdk_fake_wallet::FakeWallet;
 
     use super::*;
-    use crate::cache::HttpCache;
+    use crate::cache::{CachedRoute, HttpCache};
 
     fn create_test_request(prefer_header: Option<&str>) -> Request<()> {
         let mut req = Request::builder()
@@ -806,6 +856,7 @@ mod tests {
         mint.start().await.unwrap();
 
         MintState {
+            fingerprint_key: mint.client_fingerprint_key().await.unwrap(),
             mint: Arc::new(mint),
             cache: Arc::new(HttpCache::default()),
         }
@@ -858,6 +909,7 @@ mod tests {
         mint.start().await.unwrap();
 
         MintState {
+            fingerprint_key: mint.client_fingerprint_key().await.unwrap(),
             mint: Arc::new(mint),
             cache: Arc::new(HttpCache::default()),
         }
@@ -1092,4 +1144,43 @@ mod tests {
             "post_batch_check_mint_quote must reject cross-method quote checks"
         );
     }
+
+    #[tokio::test]
+    async fn cached_info_is_busted_when_the_mint_updates_it() {
+        let state = create_test_state().await;
+        tokio::spawn(Arc::clone(&state.cache).bust_on_changes(Arc::clone(&state.mint)));
+
+        let Json(info) = crate::router_handlers::get_mint_info(State(state.clone()))
+            .await
+            .unwrap();
+        assert!(state
+            .cache
+            .get_route::<MintInfo>(&CachedRoute::Info)
+            .await
+            .is_some());
+
+        state
+            .mint
+            .set_mint_info(info.name("renamed".to_string()))
+            .await
+            .unwrap();
+
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while state
+                .cache
+                .get_route::<MintInfo>(&CachedRoute::Info)
+                .await
+                .is_some()
+            {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .expect("info is dropped from the cache");
+
+        let Json(info) = crate::router_handlers::get_mint_info(State(state))
+            .await
+            .unwrap();
+        assert_eq!(info.name.as_deref(), Some("renamed"));
+    }
 }
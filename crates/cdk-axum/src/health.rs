@@ -0,0 +1,210 @@
+//! Liveness and readiness probes of the mint
+//!
+//! `GET /livez` answers as long as the server runs. `GET /healthz` reaches the database, the
+//! signatory and every payment backend and reports the state of each, along with what happened
+//! with every backend since the mint started, answering `503 Service Unavailable` when one of them
+//! fails, so Kubernetes and load balancers only route traffic to a mint that can serve it.
+//! `GET /readyz` answers the same. All answer JSON and need no authentication.
+
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use cdk::mint::{DependencyHealth, Mint, Readiness};
+use cdk::nuts::CurrencyUnit;
+use serde::{Deserialize, Serialize};
+
+/// Overall status reported by a probe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    /// Everything answered
+    Ok,
+    /// At least one dependency failed
+    Unavailable,
+}
+
+impl From<bool> for HealthStatus {
+    fn from(ok: bool) -> Self {
+        if ok {
+            Self::Ok
+        } else {
+            Self::Unavailable
+        }
+    }
+}
+
+/// Body of `GET /livez`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LivenessResponse {
+    /// Always [`HealthStatus::Ok`]
+    pub status: HealthStatus,
+}
+
+/// Outcome of reaching a dependency, see [`DependencyHealth`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DependencyStatus {
+    /// Whether the dependency answered
+    pub status: HealthStatus,
+    /// Time it took to answer or to give up, in milliseconds
+    pub latency_ms: u64,
+    /// Why the dependency failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl From<DependencyHealth> for DependencyStatus {
+    fn from(health: DependencyHealth) -> Self {
+        Self {
+            status: health.ok.into(),
+            latency_ms: u64::try_from(health.latency.as_millis()).unwrap_or(u64::MAX),
+            error: health.error,
+        }
+    }
+}
+
+/// Payment backend listed by `GET /healthz`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackendStatus {
+    /// Unit the backend is registered for
+    pub unit: CurrencyUnit,
+    /// Payment method the backend is registered for
+    pub method: String,
+    /// Name of the backend
+    pub backend: String,
+    /// Outcome of reaching it
+    #[serde(flatten)]
+    pub health: DependencyStatus,
+    /// Whether the last check reached it
+    pub connected: bool,
+    /// Unix time it last reported an incoming payment
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_invoice_seen: Option<u64>,
+    /// Time the last outgoing payment took, in milliseconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_payment_latency_ms: Option<u64>,
+    /// Checks and payments that failed in a row
+    pub error_streak: u32,
+}
+
+/// Body of `GET /healthz` and `GET /readyz`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReadinessResponse {
+    /// Whether every dependency answered
+    pub status: HealthStatus,
+    /// The database
+    pub database: DependencyStatus,
+    /// The signatory
+    pub signatory: DependencyStatus,
+    /// Every payment backend
+    pub payment_backends: Vec<BackendStatus>,
+}
+
+impl From<Readiness> for ReadinessResponse {
+    fn from(readiness: Readiness) -> Self {
+        Self {
+            status: readiness.is_ready().into(),
+            database: readiness.database.into(),
+            signatory: readiness.signatory.into(),
+            payment_backends: readiness
+                .payment_backends
+                .into_iter()
+                .map(|backend| BackendStatus {
+                    unit: backend.unit,
+                    method: backend.method.to_string(),
+                    backend: backend.backend,
+                    health: backend.health.into(),
+                    connected: backend.stats.connected.unwrap_or_default(),
+                    last_invoice_seen: backend.stats.last_invoice_seen,
+                    last_payment_latency_ms: backend
+                        .stats
+                        .last_payment_latency
+                        .map(|latency| u64::try_from(latency.as_millis()).unwrap_or(u64::MAX)),
+                    error_streak: backend.stats.error_streak,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Create the [`Router`] serving `GET /livez`, `GET /healthz` and `GET /readyz` for `mint`
+pub fn create_health_router(mint: Arc<Mint>) -> Router {
+    Router::new()
+        .route("/livez", get(get_liveness))
+        .route("/healthz", get(get_readiness))
+        .route("/readyz", get(get_readiness))
+        .with_state(mint)
+}
+
+async fn get_liveness() -> Json<LivenessResponse> {
+    Json(LivenessResponse {
+        status: HealthStatus::Ok,
+    })
+}
+
+async fn get_readiness(State(mint): State<Arc<Mint>>) -> Response {
+    let readiness = ReadinessResponse::from(mint.readiness().await);
+    let status = match readiness.status {
+        HealthStatus::Ok => StatusCode::OK,
+        HealthStatus::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+    };
+
+    (status, Json(readiness)).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::admin::tests::create_test_mint;
+
+    async fn get(router: &Router, path: &str) -> (StatusCode, serde_json::Value) {
+        let response = router
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(path)
+                    .body(Body::empty())
+                    .expect("test request should build"),
+            )
+            .await
+            .expect("test service should respond");
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("body");
+
+        (status, serde_json::from_slice(&body).expect("json body"))
+    }
+
+    #[tokio::test]
+    async fn probes_report_a_reachable_mint() {
+        let router = create_health_router(create_test_mint().await);
+
+        let (status, body) = get(&router, "/livez").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "ok");
+
+        let (status, body) = get(&router, "/healthz").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "ok");
+        assert_eq!(body["database"]["status"], "ok");
+        assert_eq!(body["signatory"]["status"], "ok");
+        assert!(body["database"].get("error").is_none());
+        for backend in body["payment_backends"].as_array().expect("backends") {
+            assert_eq!(backend["status"], "ok");
+            assert_eq!(backend["connected"], true);
+            assert_eq!(backend["error_streak"], 0);
+        }
+
+        let (status, body) = get(&router, "/readyz").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "ok");
+    }
+}
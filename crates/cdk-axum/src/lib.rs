@@ -4,23 +4,35 @@
 
 use std::sync::Arc;
 
+pub use admin::{create_admin_router, AdminAuth, AdminRole};
 use anyhow::Result;
 use auth::create_auth_router;
-use axum::middleware::from_fn;
+use axum::middleware::{from_fn, from_fn_with_state};
 use axum::response::Response;
-use axum::routing::{get, post};
+#[cfg(feature = "info-page")]
+use axum::routing::get;
 use axum::Router;
 use cache::HttpCache;
 use cdk::mint::Mint;
+pub use health::create_health_router;
+pub use options::MintRouterOptions;
+pub use rate_limit::{RateLimit, RateLimitedEndpoint, RateLimiter};
 use router_handlers::*;
+pub use versioning::{versioned_routes, ApiTypes, ApiVersion, V1};
 
 mod metrics;
 
+pub mod admin;
 mod auth;
 pub mod cache;
 mod custom_handlers;
 mod custom_router;
+pub mod health;
+mod options;
+mod rate_limit;
 mod router_handlers;
+mod sse;
+mod versioning;
 mod ws;
 
 /// CDK Mint State
@@ -29,6 +41,17 @@ mod ws;
 pub struct MintState {
     mint: Arc<Mint>,
     cache: Arc<cache::HttpCache>,
+    fingerprint_key: [u8; 32],
+}
+
+impl MintState {
+    /// Mint served by the router
+    ///
+    /// Lets the handlers of routes added with [`MintRouterOptions::with_routes`] reach the mint
+    /// through `State<MintState>`.
+    pub fn mint(&self) -> &Arc<Mint> {
+        &self.mint
+    }
 }
 
 /// Create mint [`Router`] with required endpoints for cashu mint with the default cache
@@ -87,29 +110,63 @@ async fn cors_middleware(
 ///
 /// The `custom_methods` parameter should include all custom payment methods supported
 /// by the payment processor, including "bolt11" and "bolt12" if they are supported.
-#[allow(unused_mut, unused_variables)]
 pub async fn create_mint_router_with_custom_cache(
     mint: Arc<Mint>,
     cache: HttpCache,
     custom_methods: Vec<String>,
     enable_info_page: bool,
 ) -> Result<Router> {
+    create_mint_router_with_options(
+        mint,
+        MintRouterOptions::new(custom_methods)
+            .with_cache(cache)
+            .with_info_page(enable_info_page),
+    )
+    .await
+}
+
+/// Create mint [`Router`] configured by `options`
+///
+/// The router serves the mint under `/v1`, and the versions added with
+/// [`MintRouterOptions::with_api_version`] under their prefix. It can be nested or merged into an
+/// existing axum app. Routes and middlewares of the options share the state, CORS and metrics layers of the
+/// mint routes.
+#[allow(unused_mut, unused_variables)]
+pub async fn create_mint_router_with_options(
+    mint: Arc<Mint>,
+    options: MintRouterOptions,
+) -> Result<Router> {
+    let MintRouterOptions {
+        cache,
+        custom_methods,
+        enable_info_page,
+        cors,
+        routes,
+        middlewares,
+        rate_limiter,
+        api_versions,
+    } = options;
+
     let state = MintState {
+        fingerprint_key: mint.client_fingerprint_key().await?,
         mint,
         cache: Arc::new(cache),
     };
 
-    let v1_router = Router::new()
-        .route("/keys", get(get_keys))
-        .route("/keysets", get(get_keysets))
-        .route("/keys/{keyset_id}", get(get_keyset_pubkeys))
-        .route("/swap", post(cache_post_swap))
-        .route("/ws", get(ws_handler))
-        .route("/checkstate", post(post_check))
-        .route("/info", get(get_mint_info))
-        .route("/restore", post(post_restore));
+    // Drop cached keys and info as soon as the mint rotates its keysets or updates its info
+    tokio::spawn(Arc::clone(&state.cache).bust_on_changes(Arc::clone(&state.mint)));
 
-    let mut mint_router = Router::new().nest("/v1", v1_router);
+    // `/v1` is always served, with the types of `V1` unless the options replace them
+    let mut mint_router = Router::new();
+    if !api_versions
+        .iter()
+        .any(|(version, _)| *version == V1::VERSION)
+    {
+        mint_router = mint_router.nest(V1::VERSION.prefix(), versioned_routes::<V1>());
+    }
+    for (version, routes) in api_versions {
+        mint_router = mint_router.nest(version.prefix(), routes);
+    }
 
     #[cfg(feature = "info-page")]
     if enable_info_page {
@@ -140,14 +197,30 @@ pub async fn create_mint_router_with_custom_cache(
         mint_router
     };
 
+    let mint_router = middlewares
+        .into_iter()
+        .fold(mint_router.merge(routes), |router, middleware| {
+            middleware(router)
+        });
+
+    let mint_router = match rate_limiter {
+        Some(rate_limiter) if !rate_limiter.is_empty() => mint_router.layer(from_fn_with_state(
+            rate_limiter,
+            rate_limit::rate_limit_middleware,
+        )),
+        _ => mint_router,
+    };
+
     #[cfg(feature = "prometheus")]
     let mint_router = mint_router.layer(axum::middleware::from_fn_with_state(
         state.clone(),
         metrics::global_metrics_middleware,
     ));
-    let mint_router = mint_router
-        .layer(from_fn(cors_middleware))
-        .with_state(state);
+    let mint_router = if cors {
+        mint_router.layer(from_fn(cors_middleware))
+    } else {
+        mint_router
+    };
 
-    Ok(mint_router)
+    Ok(mint_router.with_state(state))
 }
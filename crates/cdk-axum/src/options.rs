@@ -0,0 +1,214 @@
+//! Options of the mint router
+
+use std::sync::Arc;
+
+use axum::Router;
+
+use crate::cache::HttpCache;
+use crate::rate_limit::RateLimiter;
+use crate::versioning::{versioned_routes, ApiTypes, ApiVersion};
+use crate::MintState;
+
+/// Middleware wrapping the mint routes, see [`MintRouterOptions::with_middleware`]
+type Middleware = Box<dyn FnOnce(Router<MintState>) -> Router<MintState> + Send>;
+
+/// Options of the mint [`Router`], see [`create_mint_router_with_options`]
+///
+/// [`create_mint_router_with_options`]: crate::create_mint_router_with_options
+#[allow(missing_debug_implementations)]
+pub struct MintRouterOptions {
+    pub(crate) cache: HttpCache,
+    pub(crate) custom_methods: Vec<String>,
+    pub(crate) enable_info_page: bool,
+    pub(crate) cors: bool,
+    pub(crate) routes: Router<MintState>,
+    pub(crate) middlewares: Vec<Middleware>,
+    pub(crate) rate_limiter: Option<Arc<RateLimiter>>,
+    pub(crate) api_versions: Vec<(ApiVersion, Router<MintState>)>,
+}
+
+impl Default for MintRouterOptions {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl MintRouterOptions {
+    /// Options serving the payment methods of `custom_methods`
+    ///
+    /// `custom_methods` should include all payment methods supported by the payment processors,
+    /// including "bolt11" and "bolt12" if they are supported. The default cache is used, CORS is
+    /// allowed from any origin and the info page is disabled.
+    pub fn new(custom_methods: Vec<String>) -> Self {
+        Self {
+            cache: HttpCache::default(),
+            custom_methods,
+            enable_info_page: false,
+            cors: true,
+            routes: Router::new(),
+            middlewares: Vec::new(),
+            rate_limiter: None,
+            api_versions: Vec::new(),
+        }
+    }
+
+    /// Cache the responses of the mint in `cache`
+    pub fn with_cache(mut self, cache: HttpCache) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Serve the info page at `/`, with the `info-page` feature
+    pub fn with_info_page(mut self, enable: bool) -> Self {
+        self.enable_info_page = enable;
+        self
+    }
+
+    /// Leave CORS to the app the router is mounted in
+    pub fn without_cors(mut self) -> Self {
+        self.cors = false;
+        self
+    }
+
+    /// Serve `routes` next to the mint routes
+    ///
+    /// Their handlers can reach the mint with `State<MintState>`, see [`MintState::mint`].
+    pub fn with_routes(mut self, routes: Router<MintState>) -> Self {
+        self.routes = self.routes.merge(routes);
+        self
+    }
+
+    /// Refuse the requests of a client above the limits of `rate_limiter`
+    ///
+    /// Clients are told apart by their IP, which needs the router to be served with
+    /// `into_make_service_with_connect_info::<SocketAddr>()` unless the limiter trusts the
+    /// address forwarded by a proxy.
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(Arc::new(rate_limiter));
+        self
+    }
+
+    /// Serve the version of the API described by `A` next to `/v1`
+    ///
+    /// The version is served under [`ApiVersion::prefix`], adding it twice serves the types of
+    /// the last one.
+    pub fn with_api_version<A: ApiTypes>(mut self) -> Self {
+        self.api_versions
+            .retain(|(version, _)| *version != A::VERSION);
+        self.api_versions
+            .push((A::VERSION, versioned_routes::<A>()));
+        self
+    }
+
+    /// Wrap the mint routes, and the ones of [`MintRouterOptions::with_routes`], with
+    /// `middleware`
+    ///
+    /// `middleware` is given the router to add its layers to, for instance
+    /// `|router| router.layer(from_fn(log_request))`. Middlewares are applied in the order they
+    /// are added, inside the CORS and metrics layers of the mint.
+    pub fn with_middleware<F>(mut self, middleware: F) -> Self
+    where
+        F: FnOnce(Router<MintState>) -> Router<MintState> + Send + 'static,
+    {
+        self.middlewares.push(Box::new(middleware));
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use axum::body::Body;
+    use axum::extract::State;
+    use axum::http::{HeaderValue, Request, StatusCode};
+    use axum::middleware::{from_fn, Next};
+    use axum::response::Response;
+    use axum::routing::get;
+    use cdk::mint::Mint;
+    use cdk::nuts::MintInfo;
+    use cdk_signatory::db_signatory::DbSignatory;
+    use cdk_sqlite::mint::memory;
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::create_mint_router_with_options;
+
+    async fn create_test_mint() -> Arc<Mint> {
+        let localstore = Arc::new(memory::empty().await.expect("in-memory db"));
+        let signatory = Arc::new(
+            DbSignatory::new(
+                localstore.clone(),
+                &[0u8; 32],
+                HashMap::new(),
+                HashMap::new(),
+            )
+            .await
+            .expect("signatory"),
+        );
+
+        Arc::new(
+            Mint::new(
+                MintInfo::default().name("options test"),
+                signatory,
+                localstore,
+                HashMap::new(),
+                1000,
+                1000,
+            )
+            .await
+            .expect("mint"),
+        )
+    }
+
+    async fn mint_name(State(state): State<MintState>) -> String {
+        state
+            .mint()
+            .mint_info()
+            .await
+            .expect("mint info")
+            .name
+            .unwrap_or_default()
+    }
+
+    async fn tag_response(request: Request<Body>, next: Next) -> Response {
+        let mut response = next.run(request).await;
+        response
+            .headers_mut()
+            .insert("x-integrator", HeaderValue::from_static("yes"));
+        response
+    }
+
+    #[tokio::test]
+    async fn extra_routes_and_middlewares_are_served() {
+        let options = MintRouterOptions::default()
+            .with_routes(Router::new().route("/v1/name", get(mint_name)))
+            .with_middleware(|router| router.layer(from_fn(tag_response)))
+            .without_cors();
+        let router = create_mint_router_with_options(create_test_mint().await, options)
+            .await
+            .expect("router");
+
+        let response = router
+            .oneshot(
+                Request::get("/v1/name")
+                    .body(Body::empty())
+                    .expect("test request should build"),
+            )
+            .await
+            .expect("test service should respond");
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["x-integrator"], "yes");
+        assert!(response
+            .headers()
+            .get("Access-Control-Allow-Origin")
+            .is_none());
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("body");
+        assert_eq!(&body[..], b"options test");
+    }
+}
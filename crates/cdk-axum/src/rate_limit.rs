@@ -0,0 +1,576 @@
+//! Rate limiting of the mint routes
+//!
+//! Creating quotes, swapping, melting and restoring make the mint reach its payment backends or
+//! its signatory, so a client sending many of them can fill the database with quotes or keep the
+//! signatory busy. A [`RateLimiter`] gives every client IP a token bucket per limited endpoint,
+//! and answers `429 Too Many Requests` with a `Retry-After` header once the bucket is empty.
+//! IPv6 clients share the bucket of their /64, the smallest prefix handed to a subscriber.
+//!
+//! Swaps and melts can also be limited by the set of inputs they spend, hashed with
+//! [`ProofsMethods::input_set_hash`], so a request replayed from many addresses shares one
+//! bucket, see [`RateLimiter::with_input_set_limit`].
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::body::{to_bytes, Body};
+use axum::extract::{ConnectInfo, Request, State};
+use axum::http::{header, HeaderMap, HeaderValue, Method, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use cdk::nuts::{Proofs, ProofsMethods};
+use cdk_common::bitcoin::hashes::sha256;
+use serde::{Deserialize, Serialize};
+
+/// Buckets kept before the full ones are dropped
+const MAX_IDLE_BUCKETS: usize = 10_000;
+
+/// Largest swap or melt body read to hash its inputs
+const MAX_INPUT_SET_BODY_BYTES: usize = 1_048_576;
+
+/// Endpoint a [`RateLimit`] applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RateLimitedEndpoint {
+    /// `POST /v1/mint/quote/{method}`
+    MintQuote,
+    /// `POST /v1/mint/{method}`
+    Mint,
+    /// `POST /v1/melt/quote/{method}`
+    MeltQuote,
+    /// `POST /v1/melt/{method}`
+    Melt,
+    /// `POST /v1/swap`
+    Swap,
+    /// `POST /v1/restore`
+    Restore,
+}
+
+impl RateLimitedEndpoint {
+    /// Endpoint a request to `path` with `method` is counted against
+    fn of(method: &Method, path: &str) -> Option<Self> {
+        if method != Method::POST {
+            return None;
+        }
+
+        let path = path
+            .strip_prefix("/v1/")
+            .or_else(|| path.strip_prefix("/v2/"))?;
+        if path.starts_with("mint/quote/") {
+            Some(Self::MintQuote)
+        } else if path.starts_with("mint/") {
+            Some(Self::Mint)
+        } else if path.starts_with("melt/quote/") {
+            Some(Self::MeltQuote)
+        } else if path.starts_with("melt/") {
+            Some(Self::Melt)
+        } else if path == "swap" {
+            Some(Self::Swap)
+        } else if path == "restore" {
+            Some(Self::Restore)
+        } else {
+            None
+        }
+    }
+}
+
+/// Token bucket of a client on an endpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimit {
+    /// Requests refilled every minute
+    pub per_minute: u32,
+    /// Requests a client may send at once, the size of the bucket
+    pub burst: u32,
+}
+
+impl RateLimit {
+    /// Time it takes to refill one request
+    fn refill_interval(&self) -> Duration {
+        Duration::from_secs(60) / self.per_minute.max(1)
+    }
+}
+
+/// Requests left to a client on an endpoint
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+/// Body of a swap or melt request, down to the inputs it spends
+#[derive(Debug, Deserialize)]
+struct SpentInputs {
+    inputs: Proofs,
+}
+
+/// Take a request out of the bucket of `key` at `now`, the bucket following `limit`, or tell
+/// how long until it has one again
+///
+/// The buckets that would be full by now are dropped once there are too many.
+fn take<K: Eq + Hash>(
+    buckets: &Mutex<HashMap<K, Bucket>>,
+    key: K,
+    limit: &RateLimit,
+    is_full: impl Fn(&K, &Bucket) -> bool,
+    now: Instant,
+) -> Result<(), Duration> {
+    let burst = f64::from(limit.burst.max(1));
+    let per_sec = f64::from(limit.per_minute) / 60.0;
+
+    let mut buckets = buckets.lock().unwrap_or_else(|err| err.into_inner());
+
+    if buckets.len() >= MAX_IDLE_BUCKETS {
+        buckets.retain(|key, bucket| !is_full(key, bucket));
+    }
+
+    let bucket = buckets.entry(key).or_insert(Bucket {
+        tokens: burst,
+        updated_at: now,
+    });
+
+    let elapsed = now.duration_since(bucket.updated_at).as_secs_f64();
+    bucket.tokens = (bucket.tokens + elapsed * per_sec).min(burst);
+    bucket.updated_at = now;
+
+    if bucket.tokens >= 1.0 {
+        bucket.tokens -= 1.0;
+        return Ok(());
+    }
+
+    if per_sec <= 0.0 {
+        return Err(Duration::from_secs(60));
+    }
+    Err(Duration::from_secs_f64((1.0 - bucket.tokens) / per_sec))
+}
+
+/// Per IP rate limits of the mint endpoints, see [`MintRouterOptions::with_rate_limiter`]
+///
+/// [`MintRouterOptions::with_rate_limiter`]: crate::MintRouterOptions::with_rate_limiter
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    limits: HashMap<RateLimitedEndpoint, RateLimit>,
+    trusted_proxies: usize,
+    buckets: Mutex<HashMap<(RateLimitedEndpoint, IpAddr), Bucket>>,
+    input_set_limit: Option<RateLimit>,
+    input_set_buckets: Mutex<HashMap<sha256::Hash, Bucket>>,
+}
+
+impl RateLimiter {
+    /// Rate limiter without limits
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limit the requests every client sends to `endpoint`
+    pub fn with_limit(mut self, endpoint: RateLimitedEndpoint, limit: RateLimit) -> Self {
+        self.limits.insert(endpoint, limit);
+        self
+    }
+
+    /// Limit the swaps and melts spending the same set of inputs, whichever clients send them
+    ///
+    /// A wallet spends a set of inputs once, so only replays of a request, which the mint
+    /// refuses, drain the bucket of an input set.
+    pub fn with_input_set_limit(mut self, limit: RateLimit) -> Self {
+        self.input_set_limit = Some(limit);
+        self
+    }
+
+    /// Identify clients by the `X-Forwarded-For` or `X-Real-IP` header set by a single reverse
+    /// proxy instead of the address of the connection, see [`RateLimiter::trusted_proxies`]
+    pub fn trust_forwarded_for(self, trust: bool) -> Self {
+        self.trusted_proxies(usize::from(trust))
+    }
+
+    /// Identify clients by the `X-Forwarded-For` header appended to by `hops` reverse proxies in
+    /// front of the mint, or by `X-Real-IP` when it is missing
+    ///
+    /// Each proxy appends the address it got the request from, so the client is the entry
+    /// `hops` from the right: entries further left were sent by the client and can be anything.
+    /// Requests carrying fewer entries are counted by the address of the connection. Only set it
+    /// behind proxies that append to the header, clients would pick their own address otherwise.
+    pub fn trusted_proxies(mut self, hops: usize) -> Self {
+        self.trusted_proxies = hops;
+        self
+    }
+
+    /// Whether no endpoint is limited
+    pub fn is_empty(&self) -> bool {
+        self.limits.is_empty() && self.input_set_limit.is_none()
+    }
+
+    /// Take a request of `client` to `endpoint` out of its bucket at `now`, or tell how long
+    /// until the bucket has one again
+    fn acquire(
+        &self,
+        endpoint: RateLimitedEndpoint,
+        client: IpAddr,
+        now: Instant,
+    ) -> Result<(), Duration> {
+        let Some(limit) = self.limits.get(&endpoint) else {
+            return Ok(());
+        };
+
+        take(
+            &self.buckets,
+            (endpoint, client),
+            limit,
+            |(endpoint, _), bucket| {
+                self.limits
+                    .get(endpoint)
+                    .is_none_or(|limit| is_full(limit, bucket, now))
+            },
+            now,
+        )
+    }
+
+    /// Take a request spending the input set `inputs` out of its bucket at `now`, or tell how
+    /// long until the bucket has one again
+    fn acquire_input_set(&self, inputs: sha256::Hash, now: Instant) -> Result<(), Duration> {
+        let Some(limit) = &self.input_set_limit else {
+            return Ok(());
+        };
+
+        take(
+            &self.input_set_buckets,
+            inputs,
+            limit,
+            |_, bucket| is_full(limit, bucket, now),
+            now,
+        )
+    }
+
+    /// Hash of the inputs spent by `request`, which is put back together after its body is read
+    ///
+    /// Only swaps and melts are hashed when input sets are limited. A body that is not a swap or
+    /// melt is left for the handler to refuse.
+    async fn input_set(
+        &self,
+        endpoint: RateLimitedEndpoint,
+        request: Request,
+    ) -> Result<(Request, Option<sha256::Hash>), Response> {
+        if self.input_set_limit.is_none()
+            || !matches!(
+                endpoint,
+                RateLimitedEndpoint::Swap | RateLimitedEndpoint::Melt
+            )
+        {
+            return Ok((request, None));
+        }
+
+        let (parts, body) = request.into_parts();
+        let body = to_bytes(body, MAX_INPUT_SET_BODY_BYTES)
+            .await
+            .map_err(|_| StatusCode::PAYLOAD_TOO_LARGE.into_response())?;
+        let inputs = serde_json::from_slice::<SpentInputs>(&body)
+            .ok()
+            .and_then(|spent| spent.inputs.input_set_hash().ok());
+
+        Ok((Request::from_parts(parts, Body::from(body)), inputs))
+    }
+
+    /// Address the client of a request is counted by
+    fn client(&self, headers: &HeaderMap, connection: Option<SocketAddr>) -> IpAddr {
+        let client = self
+            .forwarded_client(headers)
+            .or_else(|| connection.map(|addr| addr.ip()))
+            // Without the address of the connection every client shares one bucket
+            .unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+
+        bucket_address(client)
+    }
+
+    /// Address of the client reported by the trusted proxies, if any
+    fn forwarded_client(&self, headers: &HeaderMap) -> Option<IpAddr> {
+        if self.trusted_proxies == 0 {
+            return None;
+        }
+
+        // Proxies may append a header line instead of extending the last one
+        let forwarded: Vec<&str> = headers
+            .get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(str::trim)
+            .collect();
+
+        if forwarded.is_empty() {
+            return headers
+                .get("x-real-ip")
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.trim().parse().ok());
+        }
+
+        forwarded
+            .iter()
+            .rev()
+            .nth(self.trusted_proxies - 1)
+            .and_then(|value| value.parse().ok())
+    }
+}
+
+/// Whether `bucket` of `limit` would be full by `now`
+fn is_full(limit: &RateLimit, bucket: &Bucket, now: Instant) -> bool {
+    now.duration_since(bucket.updated_at) >= limit.refill_interval() * limit.burst.max(1)
+}
+
+/// Address of the bucket of `client`: itself for IPv4, its /64 for IPv6
+fn bucket_address(client: IpAddr) -> IpAddr {
+    match client {
+        IpAddr::V4(_) => client,
+        IpAddr::V6(v6) => match v6.to_ipv4_mapped() {
+            Some(v4) => IpAddr::V4(v4),
+            None => IpAddr::V6(Ipv6Addr::from(u128::from(v6) & (u128::MAX << 64))),
+        },
+    }
+}
+
+/// Body of a rate limited response, the format nutshell uses
+#[derive(Debug, Serialize)]
+struct RateLimitedResponse {
+    detail: &'static str,
+}
+
+/// Refuse the requests above the limits of `limiter`
+pub(crate) async fn rate_limit_middleware(
+    State(limiter): State<Arc<RateLimiter>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(endpoint) = RateLimitedEndpoint::of(request.method(), request.uri().path()) else {
+        return next.run(request).await;
+    };
+
+    let connection = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| *addr);
+    let client = limiter.client(request.headers(), connection);
+
+    let (request, inputs) = match limiter.input_set(endpoint, request).await {
+        Ok(request) => request,
+        Err(response) => return response,
+    };
+
+    let now = Instant::now();
+    let acquired = limiter
+        .acquire(endpoint, client, now)
+        .and_then(|()| match inputs {
+            Some(inputs) => limiter.acquire_input_set(inputs, now),
+            None => Ok(()),
+        });
+
+    match acquired {
+        Ok(()) => next.run(request).await,
+        Err(retry_after) => {
+            tracing::debug!("Rate limited {:?} request from {}", endpoint, client);
+
+            let mut response = (
+                StatusCode::TOO_MANY_REQUESTS,
+                Json(RateLimitedResponse {
+                    detail: "Rate limit exceeded.",
+                }),
+            )
+                .into_response();
+            response.headers_mut().insert(
+                header::RETRY_AFTER,
+                HeaderValue::from(retry_after.as_secs().max(1)),
+            );
+            response
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cdk_common::bitcoin::hashes::Hash as _;
+
+    use super::*;
+
+    const CLIENT: IpAddr = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+
+    #[test]
+    fn endpoints_are_classified_by_path() {
+        let of = |path| RateLimitedEndpoint::of(&Method::POST, path);
+
+        assert_eq!(
+            of("/v1/mint/quote/bolt11"),
+            Some(RateLimitedEndpoint::MintQuote)
+        );
+        assert_eq!(of("/v1/mint/bolt11"), Some(RateLimitedEndpoint::Mint));
+        assert_eq!(
+            of("/v1/melt/quote/bolt12"),
+            Some(RateLimitedEndpoint::MeltQuote)
+        );
+        assert_eq!(of("/v1/melt/bolt11"), Some(RateLimitedEndpoint::Melt));
+        assert_eq!(of("/v1/swap"), Some(RateLimitedEndpoint::Swap));
+        assert_eq!(of("/v1/restore"), Some(RateLimitedEndpoint::Restore));
+        assert_eq!(of("/v1/checkstate"), None);
+        assert_eq!(of("/v2/swap"), Some(RateLimitedEndpoint::Swap));
+        assert_eq!(
+            RateLimitedEndpoint::of(&Method::GET, "/v1/mint/quote/bolt11/abc"),
+            None
+        );
+    }
+
+    #[test]
+    fn buckets_refill_over_time() {
+        let limiter = RateLimiter::new().with_limit(
+            RateLimitedEndpoint::Swap,
+            RateLimit {
+                per_minute: 60,
+                burst: 2,
+            },
+        );
+        let now = Instant::now();
+
+        assert!(limiter
+            .acquire(RateLimitedEndpoint::Swap, CLIENT, now)
+            .is_ok());
+        assert!(limiter
+            .acquire(RateLimitedEndpoint::Swap, CLIENT, now)
+            .is_ok());
+        let retry_after = limiter
+            .acquire(RateLimitedEndpoint::Swap, CLIENT, now)
+            .unwrap_err();
+        assert!(retry_after <= Duration::from_secs(1));
+
+        // Other clients and endpoints have their own buckets
+        let other = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2));
+        assert!(limiter
+            .acquire(RateLimitedEndpoint::Swap, other, now)
+            .is_ok());
+        assert!(limiter
+            .acquire(RateLimitedEndpoint::Restore, CLIENT, now)
+            .is_ok());
+
+        let later = now + Duration::from_secs(1);
+        assert!(limiter
+            .acquire(RateLimitedEndpoint::Swap, CLIENT, later)
+            .is_ok());
+        assert!(limiter
+            .acquire(RateLimitedEndpoint::Swap, CLIENT, later)
+            .is_err());
+    }
+
+    #[test]
+    fn input_sets_share_a_bucket_across_clients() {
+        let limiter = RateLimiter::new().with_input_set_limit(RateLimit {
+            per_minute: 60,
+            burst: 1,
+        });
+        let inputs = sha256::Hash::hash(b"inputs");
+        let now = Instant::now();
+
+        assert!(limiter.acquire_input_set(inputs, now).is_ok());
+        assert!(limiter.acquire_input_set(inputs, now).is_err());
+        assert!(limiter
+            .acquire_input_set(sha256::Hash::hash(b"other inputs"), now)
+            .is_ok());
+        assert!(limiter
+            .acquire_input_set(inputs, now + Duration::from_secs(1))
+            .is_ok());
+        assert!(!limiter.is_empty());
+    }
+
+    #[tokio::test]
+    async fn input_set_is_read_without_consuming_the_body() {
+        let body = r#"{"inputs":[],"outputs":[]}"#;
+        let request = || {
+            Request::builder()
+                .method(Method::POST)
+                .uri("/v1/swap")
+                .body(Body::from(body))
+                .expect("request should build")
+        };
+        let limiter = RateLimiter::new().with_input_set_limit(RateLimit {
+            per_minute: 60,
+            burst: 1,
+        });
+
+        let (request, inputs) = limiter
+            .input_set(RateLimitedEndpoint::Swap, request())
+            .await
+            .expect("body should be read");
+        assert_eq!(
+            inputs,
+            Some(Proofs::new().input_set_hash().expect("inputs should hash"))
+        );
+        let read = to_bytes(request.into_body(), usize::MAX)
+            .await
+            .expect("body should be read again");
+        assert_eq!(read, body.as_bytes());
+
+        // Without an input set limit the body is not read at all
+        let (_, inputs) = RateLimiter::new()
+            .input_set(RateLimitedEndpoint::Swap, request())
+            .await
+            .expect("request should pass");
+        assert_eq!(inputs, None);
+    }
+
+    #[test]
+    fn forwarded_address_is_only_trusted_when_enabled() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "x-forwarded-for",
+            HeaderValue::from_static("198.51.100.1, 203.0.113.7, 10.0.0.1"),
+        );
+        let connection = Some(SocketAddr::new(CLIENT, 4242));
+        let ip = |value: &str| value.parse::<IpAddr>().unwrap();
+
+        assert_eq!(RateLimiter::new().client(&headers, connection), CLIENT);
+
+        // The entries left of the ones added by the trusted proxies are picked by the client
+        assert_eq!(
+            RateLimiter::new()
+                .trust_forwarded_for(true)
+                .client(&headers, connection),
+            ip("10.0.0.1")
+        );
+        assert_eq!(
+            RateLimiter::new()
+                .trusted_proxies(2)
+                .client(&headers, connection),
+            ip("203.0.113.7")
+        );
+        assert_eq!(
+            RateLimiter::new()
+                .trusted_proxies(4)
+                .client(&headers, connection),
+            CLIENT
+        );
+
+        headers.append("x-forwarded-for", HeaderValue::from_static("192.0.2.9"));
+        assert_eq!(
+            RateLimiter::new()
+                .trusted_proxies(1)
+                .client(&headers, connection),
+            ip("192.0.2.9")
+        );
+    }
+
+    #[test]
+    fn ipv6_clients_are_counted_by_their_prefix() {
+        let limiter = RateLimiter::new();
+        let connection = |addr: &str| Some(SocketAddr::new(addr.parse().unwrap(), 4242));
+
+        assert_eq!(
+            limiter.client(&HeaderMap::new(), connection("2001:db8:1:2:aaaa::1")),
+            limiter.client(&HeaderMap::new(), connection("2001:db8:1:2:bbbb::2"))
+        );
+        assert_ne!(
+            limiter.client(&HeaderMap::new(), connection("2001:db8:1:2::1")),
+            limiter.client(&HeaderMap::new(), connection("2001:db8:1:3::1"))
+        );
+        assert_eq!(
+            limiter.client(&HeaderMap::new(), connection("::ffff:10.0.0.1")),
+            CLIENT
+        );
+    }
+}
@@ -1,48 +1,68 @@
+use std::convert::Infallible;
+
 use anyhow::Result;
 use axum::extract::ws::WebSocketUpgrade;
-use axum::extract::{Json, Path, State};
-use axum::http::StatusCode;
+use axum::extract::{FromRequestParts, Json, Path, State};
+use axum::http::request::Parts;
+use axum::http::{HeaderMap, StatusCode};
 use axum::response::{IntoResponse, Response};
 use cdk::error::ErrorResponse;
+use cdk::mint::{QuoteId, VerificationReport};
 use cdk::nuts::nut21::{Method, ProtectedEndpoint, RoutePath};
 use cdk::nuts::{
-    CheckStateRequest, CheckStateResponse, Id, KeysResponse, KeysetResponse, MintInfo,
+    CheckStateRequest, CheckStateResponse, Id, KeysResponse, KeysetResponse, MintInfo, Proofs,
     RestoreRequest, RestoreResponse, SwapRequest, SwapResponse,
 };
 use cdk::util::unix_time;
+use cdk_common::bitcoin::hashes::hmac::{Hmac, HmacEngine};
+use cdk_common::bitcoin::hashes::{sha256, Hash, HashEngine};
 use paste::paste;
+use serde::Serialize;
 use tracing::instrument;
 
 use crate::auth::AuthHeader;
+use crate::cache::{CachedRoute, HttpCache, HttpCacheKey};
 use crate::ws::main_websocket;
 use crate::MintState;
 
 /// Macro to add cache to endpoint
+///
+/// The request is the cache key, unless a function computing the key from the
+/// [`HttpCache`](crate::cache::HttpCache) and the request is given.
 #[macro_export]
 macro_rules! post_cache_wrapper {
     ($handler:ident, $request_type:ty, $response_type:ty) => {
+        $crate::post_cache_wrapper!(
+            $handler,
+            $request_type,
+            $response_type,
+            |cache: &$crate::cache::HttpCache, payload: &$request_type| cache.calculate_key(payload)
+        );
+    };
+    ($handler:ident, $request_type:ty, $response_type:ty, $cache_key:expr) => {
         paste! {
             /// Cache wrapper function for $handler:
             /// Wrap $handler into a function that caches responses using the request as key
             pub async fn [<cache_ $handler>](
                 auth: AuthHeader,
+                client: ClientFingerprint,
                 state: State<MintState>,
                 payload: Json<$request_type>
             ) -> Result<Json<$response_type>, Response> {
                 use std::ops::Deref;
                 let json_extracted_payload = payload.deref();
                 let State(mint_state) = state.clone();
-                let cache_key = match mint_state.cache.calculate_key(&json_extracted_payload) {
+                let cache_key = match ($cache_key)(&mint_state.cache, json_extracted_payload) {
                     Some(key) => key,
                     None => {
                         // Could not calculate key, just return the handler result
-                        return $handler(auth, state, payload).await;
+                        return $handler(auth, client, state, payload).await;
                     }
                 };
                 if let Some(cached_response) = mint_state.cache.get::<$response_type>(&cache_key).await {
                     return Ok(Json(cached_response));
                 }
-                let response = $handler(auth, state, payload).await?;
+                let response = $handler(auth, client, state, payload).await?;
                 mint_state.cache.set(cache_key, &response.deref()).await;
                 Ok(response)
             }
@@ -85,7 +105,54 @@ macro_rules! post_cache_wrapper_with_prefer {
     };
 }
 
-post_cache_wrapper!(post_swap, SwapRequest, SwapResponse);
+post_cache_wrapper!(post_swap, SwapRequest, SwapResponse, swap_cache_key);
+
+/// Cache key of a swap from its canonical hash, so a replay reordering the inputs is answered
+/// from the cache as well
+fn swap_cache_key(cache: &HttpCache, payload: &SwapRequest) -> Option<HttpCacheKey> {
+    cache.request_key("swap", payload.canonical_hash().ok()?)
+}
+
+/// Headers the [`ClientFingerprint`] is computed from
+const CLIENT_FINGERPRINT_HEADERS: [&str; 3] = ["X-Forwarded-For", "X-Real-IP", "User-Agent"];
+
+/// Extractor identifying the client of a request
+///
+/// Holds an HMAC of the forwarded client address and the user agent, keyed with
+/// [`Mint::client_fingerprint_key`](cdk::mint::Mint::client_fingerprint_key), so the mint can
+/// record who attempted a double spend without storing either, and the address can't be found
+/// back by hashing every address.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClientFingerprint(pub Option<String>);
+
+impl ClientFingerprint {
+    /// Fingerprint of the client sending `headers`, keyed with `key`
+    fn new(key: &[u8; 32], headers: &HeaderMap) -> Self {
+        let mut engine = HmacEngine::<sha256::Hash>::new(key);
+        let mut found = false;
+
+        for header in CLIENT_FINGERPRINT_HEADERS {
+            if let Some(value) = headers.get(header) {
+                found = true;
+                engine.input(value.as_bytes());
+            }
+            engine.input(b"\n");
+        }
+
+        Self(found.then(|| Hmac::from_engine(engine).to_string()))
+    }
+}
+
+impl FromRequestParts<MintState> for ClientFingerprint {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &MintState,
+    ) -> Result<Self, Self::Rejection> {
+        Ok(Self::new(&state.fingerprint_key, &parts.headers))
+    }
+}
 
 /// Get the public keys of the newest mint keyset
 ///
@@ -94,7 +161,14 @@ post_cache_wrapper!(post_swap, SwapRequest, SwapResponse);
 pub(crate) async fn get_keys(
     State(state): State<MintState>,
 ) -> Result<Json<KeysResponse>, Response> {
-    Ok(Json(state.mint.pubkeys()))
+    if let Some(keys) = state.cache.get_route(&CachedRoute::Keys).await {
+        return Ok(Json(keys));
+    }
+
+    let keys = state.mint.pubkeys();
+    state.cache.set_route(&CachedRoute::Keys, &keys).await;
+
+    Ok(Json(keys))
 }
 
 /// Get the public keys of a specific keyset
@@ -105,10 +179,16 @@ pub(crate) async fn get_keyset_pubkeys(
     State(state): State<MintState>,
     Path(keyset_id): Path<Id>,
 ) -> Result<Json<KeysResponse>, Response> {
+    let route = CachedRoute::KeysetPubkeys(keyset_id);
+    if let Some(pubkeys) = state.cache.get_route(&route).await {
+        return Ok(Json(pubkeys));
+    }
+
     let pubkeys = state.mint.keyset_pubkeys(&keyset_id).map_err(|err| {
         tracing::error!("Could not get keyset pubkeys: {}", err);
         into_response(err)
     })?;
+    state.cache.set_route(&route, &pubkeys).await;
 
     Ok(Json(pubkeys))
 }
@@ -120,7 +200,14 @@ pub(crate) async fn get_keyset_pubkeys(
 pub(crate) async fn get_keysets(
     State(state): State<MintState>,
 ) -> Result<Json<KeysetResponse>, Response> {
-    Ok(Json(state.mint.keysets()))
+    if let Some(keysets) = state.cache.get_route(&CachedRoute::Keysets).await {
+        return Ok(Json(keysets));
+    }
+
+    let keysets = state.mint.keysets();
+    state.cache.set_route(&CachedRoute::Keysets, &keysets).await;
+
+    Ok(Json(keysets))
 }
 
 #[instrument(skip_all)]
@@ -172,18 +259,20 @@ pub(crate) async fn post_check(
 pub(crate) async fn get_mint_info(
     State(state): State<MintState>,
 ) -> Result<Json<MintInfo>, Response> {
-    Ok(Json(
-        state
-            .mint
-            .mint_info()
-            .await
-            .map_err(|err| {
+    let mint_info = match state.cache.get_route(&CachedRoute::Info).await {
+        Some(mint_info) => mint_info,
+        None => {
+            let mint_info: MintInfo = state.mint.mint_info().await.map_err(|err| {
                 tracing::error!("Could not get mint info: {}", err);
                 into_response(err)
-            })?
-            .clone()
-            .time(unix_time()),
-    ))
+            })?;
+            state.cache.set_route(&CachedRoute::Info, &mint_info).await;
+            mint_info
+        }
+    };
+
+    // The time is set on every response, it is never served from the cache
+    Ok(Json(mint_info.time(unix_time())))
 }
 
 /// Swap inputs for outputs of the same value
@@ -194,6 +283,7 @@ pub(crate) async fn get_mint_info(
 #[instrument(skip_all, fields(inputs_count = ?payload.inputs().len()))]
 pub(crate) async fn post_swap(
     auth: AuthHeader,
+    client: ClientFingerprint,
     State(state): State<MintState>,
     Json(payload): Json<SwapRequest>,
 ) -> Result<Json<SwapResponse>, Response> {
@@ -206,14 +296,27 @@ pub(crate) async fn post_swap(
         .await
         .map_err(into_response)?;
 
-    let swap_response = state
-        .mint
-        .process_swap_request(payload)
-        .await
-        .map_err(|err| {
+    let swap_response = match state.mint.swap(&payload).await {
+        Ok(swap_response) => swap_response,
+        Err(err) => {
             tracing::error!("Could not process swap request: {}", err);
-            into_response(err)
-        })?;
+            record_double_spend(
+                &state,
+                &err,
+                RoutePath::Swap,
+                None,
+                client,
+                payload.inputs(),
+            )
+            .await;
+            let report = if wants_verification_report(&err) {
+                state.mint.swap_verification_report(&payload).await
+            } else {
+                None
+            };
+            return Err(into_response_with_report(err, report));
+        }
+    };
 
     Ok(Json(swap_response))
 }
@@ -1105,3 +1208,122 @@ where
     // Per NUT-00 spec: "In case of an error, mints respond with the HTTP status code 400"
     (StatusCode::BAD_REQUEST, Json(err_response)).into_response()
 }
+
+/// Error response of a failed swap or melt, with the [`VerificationReport`] of the stage that
+/// refused it
+pub(crate) fn into_response_with_report(
+    error: cdk::Error,
+    report: Option<VerificationReport>,
+) -> Response {
+    let Some(verification) = report else {
+        return into_response(error);
+    };
+
+    let error: ErrorResponse = error.into();
+    tracing::debug!(
+        code = ?error.code,
+        detail = %error.detail,
+        stage = %verification.stage,
+        "mint returning error response with verification report",
+    );
+
+    (
+        StatusCode::BAD_REQUEST,
+        Json(VerificationErrorResponse {
+            error,
+            verification,
+        }),
+    )
+        .into_response()
+}
+
+/// Whether a failed swap or melt should be verified again to build a [`VerificationReport`]
+///
+/// Only errors raised while verifying the inputs and outputs are reported. Building the report
+/// verifies the request again, which must not happen for database, signatory or overload
+/// errors as those are raised when the mint is already struggling.
+pub(crate) fn wants_verification_report(error: &cdk::Error) -> bool {
+    matches!(
+        error,
+        cdk::Error::DuplicateInputs
+            | cdk::Error::DuplicateOutputs
+            | cdk::Error::ExpiredKeyset
+            | cdk::Error::InactiveKeyset
+            | cdk::Error::UnknownKeySet
+            | cdk::Error::MultipleUnits
+            | cdk::Error::UnitMismatch
+            | cdk::Error::UnsupportedUnit
+            | cdk::Error::MaxDenominationExceeded { .. }
+            | cdk::Error::MaxInputsExceeded { .. }
+            | cdk::Error::MaxOutputsExceeded { .. }
+            | cdk::Error::ProofContentTooLarge { .. }
+            | cdk::Error::TransactionUnbalanced(..)
+            | cdk::Error::AmountOverflow
+            | cdk::Error::NutDisabled(_)
+            | cdk::Error::SignatureMissingOrInvalid
+            | cdk::Error::LocktimeNotProvided
+            | cdk::Error::DHKE(_)
+            | cdk::Error::NUT10(_)
+            | cdk::Error::NUT11(_)
+            | cdk::Error::NUT14(_)
+    )
+}
+
+/// Records a request refused because its inputs were already spent in the mint's forensic log
+pub(crate) async fn record_double_spend(
+    state: &MintState,
+    error: &cdk::Error,
+    endpoint: RoutePath,
+    quote_id: Option<QuoteId>,
+    client: ClientFingerprint,
+    inputs: &Proofs,
+) {
+    if !matches!(error, cdk::Error::TokenAlreadySpent) {
+        return;
+    }
+
+    if let Err(err) = state
+        .mint
+        .record_double_spend_attempt(endpoint, quote_id, client.0, inputs)
+        .await
+    {
+        tracing::warn!("Could not record double spend attempt: {}", err);
+    }
+}
+
+/// [`ErrorResponse`] extended with the [`VerificationReport`] of the request
+#[derive(Serialize)]
+struct VerificationErrorResponse {
+    #[serde(flatten)]
+    error: ErrorResponse,
+    verification: VerificationReport,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn client_fingerprint_is_keyed() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(ClientFingerprint::new(&[1; 32], &headers).0, None);
+
+        headers.insert(
+            "X-Forwarded-For",
+            "203.0.113.7".parse().expect("valid header"),
+        );
+        let fingerprint = ClientFingerprint::new(&[1; 32], &headers);
+
+        assert!(fingerprint.0.is_some());
+        assert_eq!(ClientFingerprint::new(&[1; 32], &headers), fingerprint);
+        assert_ne!(ClientFingerprint::new(&[2; 32], &headers), fingerprint);
+
+        // Without the key, hashing the address doesn't give the fingerprint back
+        let mut engine = sha256::Hash::engine();
+        engine.input(b"203.0.113.7\n\n\n");
+        assert_ne!(
+            fingerprint.0,
+            Some(sha256::Hash::from_engine(engine).to_string())
+        );
+    }
+}
@@ -0,0 +1,171 @@
+//! Server-Sent Events fallback for quote updates
+//!
+//! Some clients cannot open a websocket, behind proxies that drop the upgrade or in
+//! environments without a websocket API. `GET /v1/events` streams the same NUT-17 notifications
+//! of mint and melt quotes over a plain HTTP response instead, one subscription per request. A
+//! comment is sent when nothing happened for [`SSE_HEARTBEAT_INTERVAL`], so idle connections are
+//! not closed by proxies.
+
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use cdk::nuts::nut17::Kind;
+use cdk::nuts::nut21::{Method, ProtectedEndpoint, RoutePath};
+use cdk::subscription::{Params, SubId};
+use futures::{stream, Stream, StreamExt};
+use serde::Deserialize;
+use tracing::instrument;
+
+use crate::auth::AuthHeader;
+use crate::router_handlers::into_response;
+use crate::ws::MAX_FILTERS_PER_SUBSCRIPTION;
+use crate::MintState;
+
+/// Time without notification after which a heartbeat is sent
+pub(crate) const SSE_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Query of `GET /v1/events`
+#[derive(Debug, Deserialize)]
+pub(crate) struct SseParams {
+    /// Kind of quote, as in a NUT-17 subscription
+    kind: Kind,
+    /// Comma separated ids of the quotes
+    filters: String,
+}
+
+impl SseParams {
+    fn into_params(self) -> Result<Params, Response> {
+        if matches!(self.kind, Kind::ProofState) {
+            return Err(
+                (StatusCode::BAD_REQUEST, "Only quote updates are streamed").into_response()
+            );
+        }
+
+        let filters: Vec<String> = self
+            .filters
+            .split(',')
+            .map(str::trim)
+            .filter(|filter| !filter.is_empty())
+            .map(str::to_string)
+            .collect();
+        if filters.is_empty() || filters.len() > MAX_FILTERS_PER_SUBSCRIPTION {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("Between 1 and {MAX_FILTERS_PER_SUBSCRIPTION} quote ids are required"),
+            )
+                .into_response());
+        }
+
+        Ok(Params {
+            kind: self.kind,
+            filters,
+            id: Arc::new(SubId::from(uuid::Uuid::new_v4().to_string())),
+        })
+    }
+}
+
+/// Stream the state changes of the quotes of `filters`
+///
+/// Each event carries the quote as NUT-17 notifications do, starting with its current state. The
+/// stream ends when the mint shuts down.
+#[instrument(skip_all)]
+pub(crate) async fn sse_handler(
+    auth: AuthHeader,
+    State(state): State<MintState>,
+    Query(params): Query<SseParams>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, Response> {
+    // Same notifications as the websocket, so the same access
+    state
+        .mint
+        .verify_auth(
+            auth.into(),
+            &ProtectedEndpoint::new(Method::Get, RoutePath::Ws),
+        )
+        .await
+        .map_err(into_response)?;
+
+    let params = params.into_params()?;
+
+    let pubsub = state.mint.pubsub_manager();
+    let subscription = pubsub.subscribe(params).map_err(|err| {
+        tracing::warn!("Could not subscribe to quote updates: {}", err);
+        (StatusCode::BAD_REQUEST, "Invalid subscription").into_response()
+    })?;
+
+    let events = stream::unfold(subscription, |mut subscription| async move {
+        let payload = subscription.recv().await?.into_inner();
+        Some((payload, subscription))
+    })
+    .filter_map(|payload| {
+        let event = Event::default()
+            .json_data(payload)
+            .map_err(|err| tracing::error!("Could not serialize notification: {}", err))
+            .ok()
+            .map(Ok);
+        async move { event }
+    })
+    .take_until(async move { pubsub.cancelled().await });
+
+    Ok(Sse::new(events).keep_alive(KeepAlive::new().interval(SSE_HEARTBEAT_INTERVAL)))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::Body;
+    use axum::http::{header, Request};
+    use axum::Router;
+    use cdk::mint::QuoteId;
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::admin::tests::create_test_mint;
+    use crate::{create_mint_router_with_options, MintRouterOptions};
+
+    async fn get(router: &Router, path: &str) -> Response {
+        router
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(path)
+                    .body(Body::empty())
+                    .expect("test request should build"),
+            )
+            .await
+            .expect("test service should respond")
+    }
+
+    #[tokio::test]
+    async fn quote_updates_are_streamed() {
+        let router = create_mint_router_with_options(
+            create_test_mint().await,
+            MintRouterOptions::new(vec![]),
+        )
+        .await
+        .expect("router");
+
+        let response = get(
+            &router,
+            &format!(
+                "/v1/events?kind=bolt11_mint_quote&filters={}",
+                QuoteId::new()
+            ),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "text/event-stream"
+        );
+
+        let response = get(&router, "/v1/events?kind=proof_state&filters=abc").await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = get(&router, "/v1/events?kind=bolt11_mint_quote&filters=,").await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,255 @@
+//! Versions of the mint HTTP API
+//!
+//! Every version of the API is served under its own prefix, so a protocol revision breaking the
+//! shape of requests or responses can be served under `/v2` while wallets keep using `/v1`. The
+//! handlers are shared by all versions: an [`ApiTypes`] implementation names the request and
+//! response types of a version, converted from and into the types of the mint, and
+//! [`versioned_routes`] builds the routes of the version from them.
+//!
+//! [`V1`] is served by default. Further versions are added with
+//! [`MintRouterOptions::with_api_version`].
+//!
+//! [`MintRouterOptions::with_api_version`]: crate::MintRouterOptions::with_api_version
+
+use axum::extract::{Json, Path, State};
+use axum::response::Response;
+use axum::routing::{get, post};
+use axum::Router;
+use cdk::nuts::{
+    CheckStateRequest, CheckStateResponse, Id, KeysResponse, KeysetResponse, MintInfo,
+    RestoreRequest, RestoreResponse, SwapRequest, SwapResponse,
+};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::auth::AuthHeader;
+use crate::router_handlers::{self, ClientFingerprint};
+use crate::sse;
+use crate::MintState;
+
+/// Version of the mint HTTP API
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ApiVersion {
+    /// `/v1`, the current NUTs
+    V1,
+    /// `/v2`, reserved for the next revision of the NUTs
+    V2,
+}
+
+impl ApiVersion {
+    /// Path prefix the version is served under
+    pub fn prefix(self) -> &'static str {
+        match self {
+            Self::V1 => "/v1",
+            Self::V2 => "/v2",
+        }
+    }
+}
+
+/// Request and response types of a version of the API
+///
+/// Requests are converted into the types of the mint before being handled, and responses are
+/// converted from them, so a version only describes how its messages differ.
+pub trait ApiTypes: Send + Sync + 'static {
+    /// Version the types belong to
+    const VERSION: ApiVersion;
+
+    /// Body of `GET /keys` and `GET /keys/{keyset_id}`
+    type KeysResponse: Serialize + From<KeysResponse> + Send + 'static;
+    /// Body of `GET /keysets`
+    type KeysetResponse: Serialize + From<KeysetResponse> + Send + 'static;
+    /// Body of `GET /info`
+    type MintInfo: Serialize + From<MintInfo> + Send + 'static;
+    /// Body of `POST /swap`
+    type SwapRequest: DeserializeOwned + Into<SwapRequest> + Send + 'static;
+    /// Answer of `POST /swap`
+    type SwapResponse: Serialize + From<SwapResponse> + Send + 'static;
+    /// Body of `POST /checkstate`
+    type CheckStateRequest: DeserializeOwned + Into<CheckStateRequest> + Send + 'static;
+    /// Answer of `POST /checkstate`
+    type CheckStateResponse: Serialize + From<CheckStateResponse> + Send + 'static;
+    /// Body of `POST /restore`
+    type RestoreRequest: DeserializeOwned + Into<RestoreRequest> + Send + 'static;
+    /// Answer of `POST /restore`
+    type RestoreResponse: Serialize + From<RestoreResponse> + Send + 'static;
+}
+
+/// Types of the `/v1` API, the ones of the mint
+#[derive(Debug, Clone, Copy)]
+pub struct V1;
+
+impl ApiTypes for V1 {
+    const VERSION: ApiVersion = ApiVersion::V1;
+
+    type KeysResponse = KeysResponse;
+    type KeysetResponse = KeysetResponse;
+    type MintInfo = MintInfo;
+    type SwapRequest = SwapRequest;
+    type SwapResponse = SwapResponse;
+    type CheckStateRequest = CheckStateRequest;
+    type CheckStateResponse = CheckStateResponse;
+    type RestoreRequest = RestoreRequest;
+    type RestoreResponse = RestoreResponse;
+}
+
+/// Keys, keysets, info, swap, check state, restore, websocket and event stream routes of version
+/// `A`, relative to its prefix
+///
+/// The payment method and blind auth routes are only served under `/v1` until a version changes
+/// them.
+pub fn versioned_routes<A: ApiTypes>() -> Router<MintState> {
+    Router::new()
+        .route("/keys", get(get_keys::<A>))
+        .route("/keysets", get(get_keysets::<A>))
+        .route("/keys/{keyset_id}", get(get_keyset_pubkeys::<A>))
+        .route("/swap", post(post_swap::<A>))
+        .route("/ws", get(router_handlers::ws_handler))
+        .route("/events", get(sse::sse_handler))
+        .route("/checkstate", post(post_check::<A>))
+        .route("/info", get(get_mint_info::<A>))
+        .route("/restore", post(post_restore::<A>))
+}
+
+async fn get_keys<A: ApiTypes>(state: State<MintState>) -> Result<Json<A::KeysResponse>, Response> {
+    let Json(keys) = router_handlers::get_keys(state).await?;
+    Ok(Json(keys.into()))
+}
+
+async fn get_keysets<A: ApiTypes>(
+    state: State<MintState>,
+) -> Result<Json<A::KeysetResponse>, Response> {
+    let Json(keysets) = router_handlers::get_keysets(state).await?;
+    Ok(Json(keysets.into()))
+}
+
+async fn get_keyset_pubkeys<A: ApiTypes>(
+    state: State<MintState>,
+    keyset_id: Path<Id>,
+) -> Result<Json<A::KeysResponse>, Response> {
+    let Json(keys) = router_handlers::get_keyset_pubkeys(state, keyset_id).await?;
+    Ok(Json(keys.into()))
+}
+
+async fn get_mint_info<A: ApiTypes>(
+    state: State<MintState>,
+) -> Result<Json<A::MintInfo>, Response> {
+    let Json(info) = router_handlers::get_mint_info(state).await?;
+    Ok(Json(info.into()))
+}
+
+async fn post_swap<A: ApiTypes>(
+    auth: AuthHeader,
+    client: ClientFingerprint,
+    state: State<MintState>,
+    Json(payload): Json<A::SwapRequest>,
+) -> Result<Json<A::SwapResponse>, Response> {
+    let Json(response) =
+        router_handlers::cache_post_swap(auth, client, state, Json(payload.into())).await?;
+    Ok(Json(response.into()))
+}
+
+async fn post_check<A: ApiTypes>(
+    auth: AuthHeader,
+    state: State<MintState>,
+    Json(payload): Json<A::CheckStateRequest>,
+) -> Result<Json<A::CheckStateResponse>, Response> {
+    let Json(response) = router_handlers::post_check(auth, state, Json(payload.into())).await?;
+    Ok(Json(response.into()))
+}
+
+async fn post_restore<A: ApiTypes>(
+    auth: AuthHeader,
+    state: State<MintState>,
+    Json(payload): Json<A::RestoreRequest>,
+) -> Result<Json<A::RestoreResponse>, Response> {
+    let Json(response) = router_handlers::post_restore(auth, state, Json(payload.into())).await?;
+    Ok(Json(response.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::admin::tests::create_test_mint;
+    use crate::{create_mint_router_with_options, MintRouterOptions};
+
+    /// Info of a made up version wrapping the NUT-06 info
+    #[derive(Debug, Serialize)]
+    struct InfoV2 {
+        info: MintInfo,
+        api_version: u8,
+    }
+
+    impl From<MintInfo> for InfoV2 {
+        fn from(info: MintInfo) -> Self {
+            Self {
+                info,
+                api_version: 2,
+            }
+        }
+    }
+
+    struct V2;
+
+    impl ApiTypes for V2 {
+        const VERSION: ApiVersion = ApiVersion::V2;
+
+        type KeysResponse = KeysResponse;
+        type KeysetResponse = KeysetResponse;
+        type MintInfo = InfoV2;
+        type SwapRequest = SwapRequest;
+        type SwapResponse = SwapResponse;
+        type CheckStateRequest = CheckStateRequest;
+        type CheckStateResponse = CheckStateResponse;
+        type RestoreRequest = RestoreRequest;
+        type RestoreResponse = RestoreResponse;
+    }
+
+    async fn get_json(router: &Router, path: &str) -> serde_json::Value {
+        let response = router
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(path)
+                    .body(Body::empty())
+                    .expect("test request should build"),
+            )
+            .await
+            .expect("test service should respond");
+        assert_eq!(response.status(), StatusCode::OK, "{path}");
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("body");
+        serde_json::from_slice(&body).expect("json body")
+    }
+
+    #[tokio::test]
+    async fn versions_are_served_side_by_side() {
+        let mint = create_test_mint().await;
+        let router = create_mint_router_with_options(
+            Arc::clone(&mint),
+            MintRouterOptions::new(vec![]).with_api_version::<V2>(),
+        )
+        .await
+        .expect("router");
+
+        let v1 = get_json(&router, "/v1/info").await;
+        assert_eq!(v1["name"], "admin test");
+
+        let v2 = get_json(&router, "/v2/info").await;
+        assert_eq!(v2["api_version"], 2);
+        assert_eq!(v2["info"]["name"], "admin test");
+
+        // Routes left unchanged by the version are shared
+        assert_eq!(
+            get_json(&router, "/v1/keysets").await,
+            get_json(&router, "/v2/keysets").await
+        );
+    }
+}
@@ -66,8 +66,18 @@ pub async fn main_websocket(mut socket: WebSocket, state: MintState) {
         publisher,
     };
 
+    let pubsub = context.state.mint.pubsub_manager();
+
     loop {
         tokio::select! {
+            _ = pubsub.cancelled() => {
+                let _ = socket.send(Message::Close(Some(CloseFrame {
+                    code: axum::extract::ws::close_code::AWAY,
+                    reason: "mint shutting down".into(),
+                }))).await;
+                break;
+            }
+
             Some((sub_id, payload)) = subscriber.recv() => {
                 if !context.subscriptions.contains_key(&sub_id) {
                     // It may be possible an incoming message has come from a dropped Subscriptions that has not yet been
@@ -205,6 +215,7 @@ mod tests {
                     input_fee_ppk: *fee,
                     keyset_id_type: KeySetVersion::Version00,
                     final_expiry: None,
+                    reason: None,
                 })
                 .await
                 .expect("rotate keyset");
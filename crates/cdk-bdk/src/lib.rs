@@ -14,7 +14,7 @@ use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
 use bdk_wallet::bitcoin::Network;
-use bdk_wallet::keys::bip39::Mnemonic;
+use bdk_wallet::keys::bip39::MnemonicWithPassphrase;
 use bdk_wallet::keys::{DerivableKey, ExtendedKey};
 use bdk_wallet::rusqlite::Connection;
 use bdk_wallet::template::Bip84;
@@ -233,7 +233,7 @@ impl CdkBdk {
     /// Create a new CdkBdk instance
     #[allow(clippy::too_many_arguments)]
     pub fn new(
-        mnemonic: Mnemonic,
+        mnemonic: MnemonicWithPassphrase,
         network: Network,
         chain_source: ChainSource,
         storage_dir_path: String,
@@ -391,6 +391,10 @@ where
 impl MintPayment for CdkBdk {
     type Err = cdk_common::payment::Error;
 
+    fn backend_name(&self) -> String {
+        "bdk".to_string()
+    }
+
     #[tracing::instrument(skip_all)]
     async fn start(&self) -> Result<(), Self::Err> {
         let mut tasks_lock = self.tasks.lock().await;
@@ -820,7 +824,7 @@ mod tests {
         };
 
         let backend = CdkBdk::new(
-            mnemonic,
+            (mnemonic, None),
             Network::Regtest,
             chain_source,
             tmp.path().to_string_lossy().into_owned(),
@@ -1209,7 +1209,7 @@ mod tests {
             };
 
             CdkBdk::new(
-                mnemonic,
+                (mnemonic, None),
                 Network::Regtest,
                 chain_source,
                 path.to_string_lossy().into_owned(),
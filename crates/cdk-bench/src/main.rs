@@ -0,0 +1,117 @@
+//! Load test harness for Cashu mints
+//!
+//! Runs a number of concurrent wallets against a mint and reports the latency and errors of
+//! every operation, so operators can size a deployment before opening it to users. Meant to
+//! be pointed at a mint running the fake wallet backend, which pays mint quotes and settles
+//! melts on its own.
+
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Result};
+use cdk::nuts::CurrencyUnit;
+use cdk::Amount;
+use clap::Parser;
+use futures::future::join_all;
+use tracing_subscriber::EnvFilter;
+
+mod scenario;
+mod stats;
+
+use scenario::{Scenario, Settings, Worker};
+use stats::Recorder;
+
+/// Generate traffic against a mint and report latencies and errors
+#[derive(Parser)]
+#[command(name = "cdk-bench")]
+#[command(author, version, about, long_about = None)]
+struct Cli {
+    /// Mint under test
+    #[arg(long, default_value = "http://127.0.0.1:8085")]
+    mint_url: String,
+    /// Unit of the wallets
+    #[arg(long, default_value = "sat")]
+    unit: String,
+    /// Traffic pattern
+    #[arg(long, value_enum, default_value_t = Scenario::Mixed)]
+    scenario: Scenario,
+    /// Number of wallets running at the same time
+    #[arg(long, short, default_value_t = 10)]
+    concurrency: usize,
+    /// Iterations of the scenario run by every wallet
+    #[arg(long, short, default_value_t = 10)]
+    iterations: usize,
+    /// Amount of every mint quote
+    #[arg(long, default_value_t = 64)]
+    amount: u64,
+    /// Seconds to wait for a mint quote to be paid
+    #[arg(long, default_value_t = 30)]
+    pay_timeout: u64,
+    /// Exit with an error when any operation failed
+    #[arg(long)]
+    fail_on_error: bool,
+    /// Logging level
+    #[arg(short, long, default_value = "warn")]
+    log_level: tracing::Level,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Cli::parse();
+
+    let env_filter = EnvFilter::new(format!(
+        "{},hyper_util=warn,rustls=warn,reqwest=warn",
+        args.log_level
+    ));
+    tracing_subscriber::fmt()
+        .with_env_filter(env_filter)
+        .with_ansi(false)
+        .init();
+
+    if args.concurrency == 0 {
+        bail!("Concurrency must be at least 1");
+    }
+
+    let settings = Arc::new(Settings {
+        mint_url: args.mint_url,
+        unit: CurrencyUnit::from_str(&args.unit)?,
+        amount: Amount::from(args.amount),
+        pay_timeout: Duration::from_secs(args.pay_timeout),
+    });
+    let recorder = Recorder::default();
+
+    let mut workers = Vec::with_capacity(args.concurrency);
+    for _ in 0..args.concurrency {
+        workers.push(Worker::new(Arc::clone(&settings), recorder.clone()).await?);
+    }
+
+    println!(
+        "Running {} with {} wallets x {} iterations against {}",
+        args.scenario, args.concurrency, args.iterations, settings.mint_url
+    );
+
+    let start = Instant::now();
+    let scenario = args.scenario;
+    let iterations = args.iterations;
+    let tasks = workers.into_iter().map(|worker| {
+        tokio::spawn(async move {
+            worker.run(scenario, iterations).await;
+        })
+    });
+
+    for result in join_all(tasks).await {
+        if let Err(err) = result {
+            tracing::error!("Worker panicked: {}", err);
+        }
+    }
+
+    let report = recorder.report(start.elapsed());
+    println!("{}", report);
+
+    if args.fail_on_error && report.has_failures() {
+        bail!("Some operations failed");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,201 @@
+//! Traffic generated against the mint
+//!
+//! Every worker drives its own wallet, so workers only contend with each other on the mint.
+//! Mint quotes are expected to be paid by the mint's backend on its own, which is what the
+//! fake wallet backend does, and melts pay invoices the fake wallet backend settles right away.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use bip39::Mnemonic;
+use cdk::amount::SplitTarget;
+use cdk::nuts::{CurrencyUnit, PaymentMethod};
+use cdk::wallet::Wallet;
+use cdk::Amount;
+use cdk_fake_wallet::{create_fake_invoice, FakeInvoiceDescription};
+use cdk_sqlite::wallet::memory;
+use clap::ValueEnum;
+
+use crate::stats::Recorder;
+
+/// Traffic pattern run by every worker
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Scenario {
+    /// Create a mint quote, wait for it to be paid and mint it, every iteration
+    Mint,
+    /// Mint once, then swap all proofs of the wallet every iteration
+    Swap,
+    /// Mint and melt half of it every iteration
+    Melt,
+    /// Mint, swap and melt every iteration
+    Mixed,
+}
+
+impl fmt::Display for Scenario {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Mint => write!(f, "mint"),
+            Self::Swap => write!(f, "swap"),
+            Self::Melt => write!(f, "melt"),
+            Self::Mixed => write!(f, "mixed"),
+        }
+    }
+}
+
+/// Settings shared by all workers
+#[derive(Debug, Clone)]
+pub struct Settings {
+    /// Mint under test
+    pub mint_url: String,
+    /// Unit of the wallets
+    pub unit: CurrencyUnit,
+    /// Amount of every mint quote
+    pub amount: Amount,
+    /// How long to wait for a mint quote to be paid
+    pub pay_timeout: Duration,
+}
+
+/// A wallet generating traffic
+#[derive(Debug)]
+pub struct Worker {
+    wallet: Wallet,
+    settings: Arc<Settings>,
+    recorder: Recorder,
+}
+
+impl Worker {
+    /// Worker with a fresh in memory wallet
+    pub async fn new(settings: Arc<Settings>, recorder: Recorder) -> Result<Self> {
+        let seed = Mnemonic::generate(12)?.to_seed_normalized("");
+        let wallet = Wallet::new(
+            &settings.mint_url,
+            settings.unit.clone(),
+            Arc::new(memory::empty().await?),
+            seed,
+            None,
+        )?;
+
+        Ok(Self {
+            wallet,
+            settings,
+            recorder,
+        })
+    }
+
+    /// Run `iterations` iterations of `scenario`
+    ///
+    /// A failed iteration is recorded and the worker moves on to the next one.
+    pub async fn run(&self, scenario: Scenario, iterations: usize) {
+        if scenario == Scenario::Swap {
+            if let Err(err) = self.mint().await {
+                tracing::warn!("Could not fund worker for swaps: {}", err);
+                return;
+            }
+        }
+
+        for _ in 0..iterations {
+            let result = match scenario {
+                Scenario::Mint => self.mint().await,
+                Scenario::Swap => self.swap().await,
+                Scenario::Melt => self.mint_and_melt().await,
+                Scenario::Mixed => self.mixed().await,
+            };
+
+            if let Err(err) = result {
+                tracing::debug!("Iteration failed: {}", err);
+            }
+        }
+    }
+
+    async fn mint(&self) -> Result<()> {
+        let quote = self
+            .recorder
+            .time(
+                "mint_quote",
+                self.wallet.mint_quote(
+                    PaymentMethod::BOLT11,
+                    Some(self.settings.amount),
+                    None,
+                    None,
+                ),
+            )
+            .await?;
+
+        self.recorder
+            .time(
+                "pay_and_mint",
+                self.wallet.wait_and_mint_quote(
+                    quote,
+                    SplitTarget::default(),
+                    None,
+                    self.settings.pay_timeout,
+                ),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn swap(&self) -> Result<()> {
+        let proofs = self.wallet.get_unspent_proofs().await?;
+
+        self.recorder
+            .time(
+                "swap",
+                self.wallet
+                    .swap(None, SplitTarget::default(), proofs, None, false, false),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn melt(&self, amount: Amount) -> Result<()> {
+        let description = serde_json::to_string(&FakeInvoiceDescription::default())?;
+        let invoice = create_fake_invoice(u64::from(amount) * 1000, description);
+
+        let quote = self
+            .recorder
+            .time(
+                "melt_quote",
+                self.wallet
+                    .melt_quote(PaymentMethod::BOLT11, invoice, None, None),
+            )
+            .await?;
+
+        let start = Instant::now();
+        let result = match self.wallet.prepare_melt(&quote.id, HashMap::new()).await {
+            Ok(prepared) => prepared.confirm().await.map(|_| ()),
+            Err(err) => Err(err),
+        };
+
+        match result {
+            Ok(()) => self.recorder.success("melt", start.elapsed()),
+            Err(err) => {
+                self.recorder.failure("melt", err.to_string());
+                return Err(err.into());
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn mint_and_melt(&self) -> Result<()> {
+        self.mint().await?;
+        self.melt(self.melt_amount()).await
+    }
+
+    async fn mixed(&self) -> Result<()> {
+        self.mint().await?;
+        self.swap().await?;
+        self.melt(self.melt_amount()).await
+    }
+
+    /// Amount of every melt, half the minted amount so the fee reserve is covered
+    fn melt_amount(&self) -> Amount {
+        Amount::from(u64::from(self.settings.amount) / 2)
+    }
+}
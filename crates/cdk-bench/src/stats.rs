@@ -0,0 +1,243 @@
+//! Latency and error accounting
+//!
+//! Every operation run against the mint is recorded under its name with how long it took, or
+//! with the error it failed with. The report prints latency percentiles of the successful runs
+//! and the most frequent errors of each operation.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Number of distinct errors printed per operation
+const ERRORS_IN_REPORT: usize = 5;
+
+/// Outcomes of a single operation
+#[derive(Debug, Default, Clone)]
+struct Samples {
+    latencies: Vec<Duration>,
+    errors: HashMap<String, u64>,
+}
+
+/// Shared recorder of operation outcomes
+#[derive(Debug, Default, Clone)]
+pub struct Recorder {
+    samples: Arc<Mutex<BTreeMap<&'static str, Samples>>>,
+}
+
+impl Recorder {
+    /// Run `fut` and record its outcome under `operation`
+    pub async fn time<T, E, F>(&self, operation: &'static str, fut: F) -> Result<T, E>
+    where
+        F: Future<Output = Result<T, E>>,
+        E: fmt::Display,
+    {
+        let start = Instant::now();
+        let result = fut.await;
+        let elapsed = start.elapsed();
+
+        match &result {
+            Ok(_) => self.success(operation, elapsed),
+            Err(err) => self.failure(operation, err.to_string()),
+        }
+
+        result
+    }
+
+    /// Record a successful run of `operation`
+    pub fn success(&self, operation: &'static str, latency: Duration) {
+        if let Ok(mut samples) = self.samples.lock() {
+            samples
+                .entry(operation)
+                .or_default()
+                .latencies
+                .push(latency);
+        }
+    }
+
+    /// Record a failed run of `operation`
+    pub fn failure(&self, operation: &'static str, error: String) {
+        tracing::debug!("{} failed: {}", operation, error);
+        if let Ok(mut samples) = self.samples.lock() {
+            *samples
+                .entry(operation)
+                .or_default()
+                .errors
+                .entry(error)
+                .or_default() += 1;
+        }
+    }
+
+    /// Summary of everything recorded so far
+    pub fn report(&self, elapsed: Duration) -> Report {
+        let samples = self
+            .samples
+            .lock()
+            .map(|samples| samples.clone())
+            .unwrap_or_default();
+
+        let operations = samples
+            .into_iter()
+            .map(|(operation, samples)| OperationReport::new(operation, samples))
+            .collect();
+
+        Report {
+            elapsed,
+            operations,
+        }
+    }
+}
+
+/// Summary of the runs of one operation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OperationReport {
+    /// Name of the operation
+    pub operation: &'static str,
+    /// Number of successful runs
+    pub ok: usize,
+    /// Number of failed runs
+    pub failed: u64,
+    /// Median latency
+    pub p50: Duration,
+    /// 90th percentile latency
+    pub p90: Duration,
+    /// 99th percentile latency
+    pub p99: Duration,
+    /// Slowest successful run
+    pub max: Duration,
+    /// Most frequent errors, most frequent first
+    pub errors: Vec<(String, u64)>,
+}
+
+impl OperationReport {
+    fn new(operation: &'static str, mut samples: Samples) -> Self {
+        samples.latencies.sort();
+
+        let mut errors: Vec<_> = samples.errors.into_iter().collect();
+        errors.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        let failed = errors.iter().map(|(_, count)| count).sum();
+        errors.truncate(ERRORS_IN_REPORT);
+
+        Self {
+            operation,
+            ok: samples.latencies.len(),
+            failed,
+            p50: percentile(&samples.latencies, 50),
+            p90: percentile(&samples.latencies, 90),
+            p99: percentile(&samples.latencies, 99),
+            max: samples.latencies.last().copied().unwrap_or_default(),
+            errors,
+        }
+    }
+}
+
+/// Nearest rank percentile of sorted latencies, zero when there are none
+fn percentile(sorted: &[Duration], percent: usize) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+
+    let rank = (sorted.len() * percent).div_ceil(100).max(1);
+    sorted[rank.min(sorted.len()) - 1]
+}
+
+/// Summary of a whole run
+#[derive(Debug, Clone)]
+pub struct Report {
+    /// Wall clock time of the run
+    pub elapsed: Duration,
+    /// One entry per operation, sorted by name
+    pub operations: Vec<OperationReport>,
+}
+
+impl Report {
+    /// Whether any operation failed
+    pub fn has_failures(&self) -> bool {
+        self.operations.iter().any(|operation| operation.failed > 0)
+    }
+}
+
+fn millis(duration: Duration) -> String {
+    format!("{:.1}", duration.as_secs_f64() * 1000.0)
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let secs = self.elapsed.as_secs_f64();
+
+        writeln!(
+            f,
+            "{:<14} {:>8} {:>8} {:>9} {:>9} {:>9} {:>9} {:>9}",
+            "operation", "ok", "failed", "ops/s", "p50 ms", "p90 ms", "p99 ms", "max ms"
+        )?;
+
+        for operation in &self.operations {
+            let throughput = if secs > 0.0 {
+                operation.ok as f64 / secs
+            } else {
+                0.0
+            };
+
+            writeln!(
+                f,
+                "{:<14} {:>8} {:>8} {:>9.1} {:>9} {:>9} {:>9} {:>9}",
+                operation.operation,
+                operation.ok,
+                operation.failed,
+                throughput,
+                millis(operation.p50),
+                millis(operation.p90),
+                millis(operation.p99),
+                millis(operation.max),
+            )?;
+        }
+
+        for operation in &self.operations {
+            for (error, count) in &operation.errors {
+                writeln!(f, "{} x{}: {}", operation.operation, count, error)?;
+            }
+        }
+
+        write!(f, "elapsed: {:.1}s", secs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentiles_use_nearest_rank() {
+        let latencies: Vec<_> = (1..=100).map(Duration::from_millis).collect();
+
+        assert_eq!(percentile(&latencies, 50), Duration::from_millis(50));
+        assert_eq!(percentile(&latencies, 99), Duration::from_millis(99));
+        assert_eq!(percentile(&latencies[..1], 99), Duration::from_millis(1));
+        assert_eq!(percentile(&[], 50), Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn failures_are_counted_by_error() {
+        let recorder = Recorder::default();
+
+        for _ in 0..3 {
+            let _ = recorder.time("swap", async { Err::<(), _>("spent") }).await;
+        }
+        let _ = recorder
+            .time("swap", async { Err::<(), _>("timeout") })
+            .await;
+        let _ = recorder.time("swap", async { Ok::<_, String>(()) }).await;
+
+        let report = recorder.report(Duration::from_secs(1));
+        assert!(report.has_failures());
+
+        let swap = &report.operations[0];
+        assert_eq!(swap.ok, 1);
+        assert_eq!(swap.failed, 4);
+        assert_eq!(
+            swap.errors,
+            vec![("spent".to_owned(), 3), ("timeout".to_owned(), 1)]
+        );
+    }
+}
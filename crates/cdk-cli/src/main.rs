@@ -1,9 +1,9 @@
 //! CDK CLI
 
-use std::fs;
 use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::{env, fs};
 
 use anyhow::{bail, Result};
 use bip39::rand::{thread_rng, Rng};
@@ -28,6 +28,7 @@ mod utils;
 
 const DEFAULT_WORK_DIR: &str = ".cdk-cli";
 const CARGO_PKG_VERSION: Option<&'static str> = option_env!("CARGO_PKG_VERSION");
+const ENV_MNEMONIC_PASSPHRASE: &str = "CDK_CLI_MNEMONIC_PASSPHRASE";
 
 /// Simple CLI application to interact with cashu
 #[cfg(all(feature = "tor", not(target_arch = "wasm32")))]
@@ -50,6 +51,10 @@ struct Cli {
     /// Path to working dir
     #[arg(short, long)]
     work_dir: Option<PathBuf>,
+    /// Prompt for a BIP-39 passphrase (25th word) applied to the wallet seed, which is otherwise
+    /// read from the CDK_CLI_MNEMONIC_PASSPHRASE environment variable
+    #[arg(long)]
+    mnemonic_passphrase: bool,
     /// Logging level
     #[arg(short, long, default_value = "error")]
     log_level: Level,
@@ -220,7 +225,13 @@ async fn main() -> Result<()> {
             mnemonic
         }
     };
-    let seed = mnemonic.to_seed_normalized("");
+    // Never taken from the command line, where other users and the shell history can see it
+    let passphrase = if args.mnemonic_passphrase {
+        utils::get_secret_input("Enter the BIP-39 passphrase of the wallet seed:")?
+    } else {
+        env::var(ENV_MNEMONIC_PASSPHRASE).unwrap_or_default()
+    };
+    let seed = mnemonic.to_seed_normalized(&passphrase);
 
     // Parse currency unit from args
     let currency_unit = match args.unit {
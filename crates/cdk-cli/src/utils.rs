@@ -28,6 +28,23 @@ pub fn get_user_input(prompt: &str) -> Result<String> {
     Ok(user_input.trim().to_string())
 }
 
+/// Helper function to get a secret from user input with a prompt
+///
+/// Unlike [`get_user_input`] only the line ending is removed, as spaces are part of the secret.
+pub fn get_secret_input(prompt: &str) -> Result<String> {
+    if NON_INTERACTIVE.load(Ordering::Relaxed) {
+        bail!(
+            "Interactive input is disabled (--non-interactive). Missing required argument for prompt: {prompt}"
+        );
+    }
+
+    println!("{prompt}");
+    let mut user_input = String::new();
+    io::stdout().flush()?;
+    io::stdin().read_line(&mut user_input)?;
+    Ok(user_input.trim_end_matches(['\r', '\n']).to_string())
+}
+
 /// Helper function to get a number from user input with a prompt
 pub fn get_number_input<T>(prompt: &str) -> Result<T>
 where
@@ -91,6 +91,10 @@ impl Cln {
 impl MintPayment for Cln {
     type Err = payment::Error;
 
+    fn backend_name(&self) -> String {
+        "cln".to_string()
+    }
+
     async fn get_settings(&self) -> Result<SettingsResponse, Self::Err> {
         use std::collections::HashMap;
         Ok(SettingsResponse {
@@ -405,7 +409,9 @@ impl MintPayment for Cln {
                     fee_options: None,
                 })
             }
-            OutgoingPaymentOptions::Onchain(_) => Err(payment::Error::UnsupportedPaymentOption),
+            OutgoingPaymentOptions::Onchain(_) | OutgoingPaymentOptions::Keysend(_) => {
+                Err(payment::Error::UnsupportedPaymentOption)
+            }
         }
     }
 
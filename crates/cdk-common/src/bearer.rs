@@ -0,0 +1,152 @@
+//! Bearer token authentication
+//!
+//! The admin API, the management RPC, the signatory and the metrics endpoint all take
+//! `Authorization: Bearer <token>` and map each token to what its holder may do. [`BearerTokens`]
+//! holds those tokens and checks the header, each server only decides what it refuses and how.
+
+use std::collections::HashMap;
+use std::fmt;
+
+/// Token carried by an `Authorization` header value, if it is a bearer token
+pub fn bearer_token(authorization: &str) -> Option<&str> {
+    authorization
+        .trim()
+        .strip_prefix("Bearer ")
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+}
+
+/// Why a request was refused
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BearerError {
+    /// No bearer token in the request
+    #[error("Missing bearer token")]
+    Missing,
+    /// Bearer token not issued
+    #[error("Invalid bearer token")]
+    Invalid,
+    /// Bearer token issued with a role lower than required
+    #[error("Bearer token is not allowed to make this request")]
+    Forbidden,
+}
+
+/// Bearer tokens, each issued with a role `R`
+///
+/// The role can be anything identifying the holder, such as a client name, or `()` when every
+/// token is equal. [`BearerTokens::authorize`] compares ordered roles.
+#[derive(Clone)]
+pub struct BearerTokens<R> {
+    tokens: HashMap<String, R>,
+}
+
+impl<R> Default for BearerTokens<R> {
+    fn default() -> Self {
+        Self {
+            tokens: HashMap::new(),
+        }
+    }
+}
+
+impl<R> fmt::Debug for BearerTokens<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BearerTokens")
+            .field("tokens", &self.tokens.len())
+            .finish()
+    }
+}
+
+impl<R> FromIterator<(String, R)> for BearerTokens<R> {
+    fn from_iter<I: IntoIterator<Item = (String, R)>>(tokens: I) -> Self {
+        Self {
+            tokens: tokens.into_iter().collect(),
+        }
+    }
+}
+
+impl<R> BearerTokens<R> {
+    /// Accept `tokens`, each with its role
+    pub fn new(tokens: impl IntoIterator<Item = (String, R)>) -> Self {
+        tokens.into_iter().collect()
+    }
+
+    /// Whether no token is issued
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    /// Number of issued tokens
+    pub fn len(&self) -> usize {
+        self.tokens.len()
+    }
+
+    /// Role of the bearer token in the `authorization` header value
+    pub fn role(&self, authorization: Option<&str>) -> Result<&R, BearerError> {
+        let token = authorization
+            .and_then(bearer_token)
+            .ok_or(BearerError::Missing)?;
+
+        self.tokens.get(token).ok_or(BearerError::Invalid)
+    }
+
+    /// Issue `token` with `role`, replacing its previous role
+    pub fn insert(&mut self, token: String, role: R) -> Option<R> {
+        self.tokens.insert(token, role)
+    }
+
+    /// Keep only the tokens whose role matches `keep`
+    pub fn retain(&mut self, mut keep: impl FnMut(&R) -> bool) {
+        self.tokens.retain(|_, role| keep(role));
+    }
+
+    /// Roles of the issued tokens
+    pub fn roles(&self) -> impl Iterator<Item = &R> {
+        self.tokens.values()
+    }
+}
+
+impl<R> BearerTokens<R>
+where
+    R: PartialOrd,
+{
+    /// Accept the bearer token in `authorization` if its role is at least `required`
+    pub fn authorize(&self, authorization: Option<&str>, required: &R) -> Result<(), BearerError> {
+        if self.role(authorization)? < required {
+            return Err(BearerError::Forbidden);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bearer_token_is_parsed_from_the_header() {
+        assert_eq!(bearer_token("Bearer token"), Some("token"));
+        assert_eq!(bearer_token(" Bearer  token "), Some("token"));
+        assert_eq!(bearer_token("Bearer "), None);
+        assert_eq!(bearer_token("Basic token"), None);
+        assert_eq!(bearer_token("token"), None);
+    }
+
+    #[test]
+    fn tokens_are_checked_against_the_required_role() {
+        let tokens = BearerTokens::new([("reader".to_string(), 0), ("admin".to_string(), 1)]);
+
+        assert_eq!(tokens.role(Some("Bearer admin")), Ok(&1));
+        assert!(tokens.authorize(Some("Bearer reader"), &0).is_ok());
+        assert!(tokens.authorize(Some("Bearer admin"), &1).is_ok());
+        assert_eq!(
+            tokens.authorize(Some("Bearer reader"), &1),
+            Err(BearerError::Forbidden)
+        );
+        assert_eq!(tokens.authorize(None, &0), Err(BearerError::Missing));
+        assert_eq!(
+            tokens.authorize(Some("Bearer other"), &0),
+            Err(BearerError::Invalid)
+        );
+        assert!(!format!("{tokens:?}").contains("admin"));
+    }
+}
@@ -11,11 +11,13 @@ use super::{DbTransactionFinalizer, Error};
 use crate::mint::{
     self, MeltQuote, MintKeySetInfo, MintQuote as MintMintQuote, Operation, ProofsWithState,
 };
+use crate::nuts::nut21::RoutePath;
 use crate::nuts::{
-    BlindSignature, BlindedMessage, CurrencyUnit, Id, MeltQuoteState, Proof, Proofs, PublicKey,
-    State,
+    BlindSignature, BlindedMessage, CurrencyUnit, HoldInvoiceState, Id, MeltQuoteState,
+    MintQuoteState, Proof, Proofs, PublicKey, State,
 };
 use crate::payment::PaymentIdentifier;
+use crate::rotation_log::SignedRotationAttestation;
 
 mod auth;
 
@@ -111,6 +113,125 @@ pub struct MeltRequestInfo {
     pub change_outputs: Vec<BlindedMessage>,
 }
 
+/// Usage counters of a keyset's keys
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KeysetUsage {
+    /// Number of blind signatures issued with the keyset
+    pub signatures_issued: u64,
+    /// Number of proofs of the keyset whose signature was verified
+    pub proofs_verified: u64,
+}
+
+/// Mint and melt activity of one unit over a day
+///
+/// Aggregated over every quote of the unit, so it carries nothing that links back to a user.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DailyUsage {
+    /// Unix time of the start of the day, UTC
+    pub day: u64,
+    /// Unit of the quotes
+    pub unit: CurrencyUnit,
+    /// Number of issuances of mint quotes
+    pub mint_count: u64,
+    /// Total amount issued
+    pub mint_volume: Amount,
+    /// Number of paid melt quotes
+    pub melt_count: u64,
+    /// Total amount melted
+    pub melt_volume: Amount,
+}
+
+/// Attempt to spend proofs that were already spent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DoubleSpendAttempt {
+    /// Unix time of the attempt
+    pub created_time: u64,
+    /// Endpoint the proofs were sent to
+    pub endpoint: RoutePath,
+    /// Quote of the request, if any
+    pub quote_id: Option<QuoteId>,
+    /// Hash identifying the client, if the frontend could tell one
+    pub client_fingerprint: Option<String>,
+    /// `Y` of the inputs that were already spent
+    pub ys: Vec<PublicKey>,
+}
+
+/// Melt paid by a mint quote of the same mint, without reaching the payment backend
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InternalSettlement {
+    /// Unix time of the settlement
+    pub created_time: u64,
+    /// Melt quote that was paid
+    pub melt_quote_id: QuoteId,
+    /// Mint quote the payment was credited to
+    pub mint_quote_id: QuoteId,
+    /// Unit of both quotes
+    pub unit: CurrencyUnit,
+    /// Amount settled
+    pub amount: Amount,
+    /// Fee reserve of the melt quote, none of it is spent on routing
+    pub fee_reserve: Amount,
+}
+
+/// Quotes returned by a search when [`QuoteSearch::limit`] is not set
+pub const DEFAULT_QUOTE_SEARCH_LIMIT: u64 = 100;
+
+/// Filter of [`QuotesDatabase::search_mint_quotes`] and [`QuotesDatabase::search_melt_quotes`]
+///
+/// Every filter set must match. Quotes are returned newest first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuoteSearch<S> {
+    /// Payment hash, or the lookup id of the payment for other methods
+    pub lookup_id: Option<String>,
+    /// Part of the payment request, such as a bolt11 invoice
+    pub request: Option<String>,
+    /// Smallest amount of the quote
+    pub min_amount: Option<u64>,
+    /// Largest amount of the quote
+    pub max_amount: Option<u64>,
+    /// State of the quote
+    pub state: Option<S>,
+    /// Created at or after this unix time
+    pub created_after: Option<u64>,
+    /// Created before this unix time
+    pub created_before: Option<u64>,
+    /// Quotes skipped, for pagination
+    pub offset: u64,
+    /// Most quotes returned
+    pub limit: u64,
+}
+
+impl<S> Default for QuoteSearch<S> {
+    fn default() -> Self {
+        Self {
+            lookup_id: None,
+            request: None,
+            min_amount: None,
+            max_amount: None,
+            state: None,
+            created_after: None,
+            created_before: None,
+            offset: 0,
+            limit: DEFAULT_QUOTE_SEARCH_LIMIT,
+        }
+    }
+}
+
+/// Blind signature issued by the signatory, as kept in its audit log
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SigningAuditRecord {
+    /// Unix time of the signature
+    pub created_time: u64,
+    /// Keyset the message was signed with
+    pub keyset_id: Id,
+    /// Amount signed
+    pub amount: Amount,
+    /// Hex encoded SHA-256 of the blinded secret
+    pub blinded_secret_hash: String,
+    /// Identity of the caller, when the signatory is reached over gRPC
+    pub caller: Option<String>,
+}
+
 /// Result of locking a melt quote and all related quotes atomically.
 ///
 /// This struct is returned by [`QuotesTransaction::lock_melt_quote_and_related`]
@@ -131,6 +252,23 @@ pub trait KeysDatabaseTransaction<'a, Error>: DbTransactionFinalizer<Err = Error
 
     /// Add [`MintKeySetInfo`]
     async fn add_keyset_info(&mut self, keyset: MintKeySetInfo) -> Result<(), Error>;
+
+    /// Deactivate the keyset `id` for good, without activating another one in its place
+    async fn disable_keyset(&mut self, id: &Id) -> Result<(), Error>;
+
+    /// Append records to the signing audit log
+    async fn add_signing_audit_records(
+        &mut self,
+        records: &[SigningAuditRecord],
+    ) -> Result<(), Error>;
+
+    /// Append an attestation to the keyset rotation log
+    ///
+    /// Fails if the log already has an attestation with the same sequence number.
+    async fn add_rotation_attestation(
+        &mut self,
+        attestation: &SignedRotationAttestation,
+    ) -> Result<(), Error>;
 }
 
 /// Mint Keys Database trait
@@ -155,6 +293,18 @@ pub trait KeysDatabase {
 
     /// Get [`MintKeySetInfo`]s
     async fn get_keyset_infos(&self) -> Result<Vec<MintKeySetInfo>, Self::Err>;
+
+    /// Get the ids of the keysets disabled with [`KeysDatabaseTransaction::disable_keyset`]
+    async fn get_disabled_keysets(&self) -> Result<Vec<Id>, Self::Err>;
+
+    /// Get the signing audit records created at or after `since`, oldest first
+    async fn get_signing_audit_records(
+        &self,
+        since: Option<u64>,
+    ) -> Result<Vec<SigningAuditRecord>, Self::Err>;
+
+    /// Get the whole keyset rotation log, oldest first
+    async fn get_rotation_log(&self) -> Result<Vec<SignedRotationAttestation>, Self::Err>;
 }
 
 /// Mint Quote Database writer trait
@@ -239,6 +389,15 @@ pub trait QuotesTransaction {
         quote: &mut Acquired<mint::MintQuote>,
     ) -> Result<(), Self::Err>;
 
+    /// Records the state of the hold invoice of a mint quote.
+    ///
+    /// Requires an [`Acquired`] mint quote to ensure the row is locked before modification.
+    async fn update_mint_quote_hold_invoice(
+        &mut self,
+        quote: &mut Acquired<mint::MintQuote>,
+        state: HoldInvoiceState,
+    ) -> Result<(), Self::Err>;
+
     /// Get [`mint::MeltQuote`] and lock it for update in this transaction
     async fn get_melt_quote(
         &mut self,
@@ -307,6 +466,24 @@ pub trait QuotesTransaction {
         new_request_lookup_id: &PaymentIdentifier,
     ) -> Result<(), Self::Err>;
 
+    /// Updates the expiry of a melt quote.
+    ///
+    /// Requires an [`Acquired`] melt quote to ensure the row is locked before modification.
+    async fn update_melt_quote_expiry(
+        &mut self,
+        quote: &mut Acquired<mint::MeltQuote>,
+        expiry: u64,
+    ) -> Result<(), Self::Err>;
+
+    /// Records the payment backend that made the payment of a melt quote.
+    ///
+    /// Requires an [`Acquired`] melt quote to ensure the row is locked before modification.
+    async fn update_melt_quote_backend(
+        &mut self,
+        quote: &mut Acquired<mint::MeltQuote>,
+        backend: &str,
+    ) -> Result<(), Self::Err>;
+
     /// Update [`mint::MeltQuote`] state.
     ///
     /// Requires an [`Acquired`] melt quote to ensure the row is locked before modification.
@@ -318,6 +495,24 @@ pub trait QuotesTransaction {
         payment_proof: Option<String>,
     ) -> Result<MeltQuoteState, Self::Err>;
 
+    /// Remove the mint quotes that expired before `expired_before` without being paid
+    ///
+    /// Quotes without an expiry, with a recorded payment or issuance, or with outputs stored
+    /// against them are kept. Returns the number of quotes removed.
+    async fn remove_expired_mint_quotes(&mut self, expired_before: u64) -> Result<u64, Self::Err>;
+
+    /// Remove the unpaid and failed melt quotes that expired before `expired_before`
+    ///
+    /// Quotes without an expiry, with a melt request in progress, or with proofs spent against
+    /// them are kept. Returns the number of quotes removed.
+    async fn remove_expired_melt_quotes(&mut self, expired_before: u64) -> Result<u64, Self::Err>;
+
+    /// Record a melt settled internally
+    async fn add_internal_settlement(
+        &mut self,
+        settlement: &InternalSettlement,
+    ) -> Result<(), Self::Err>;
+
     /// Get all [`MintMintQuote`]s and lock it for update in this transaction
     async fn get_mint_quote_by_request(
         &mut self,
@@ -367,6 +562,23 @@ pub trait QuotesDatabase {
     ) -> Result<Option<mint::MeltQuote>, Self::Err>;
     /// Get all [`mint::MeltQuote`]s
     async fn get_melt_quotes(&self) -> Result<Vec<mint::MeltQuote>, Self::Err>;
+    /// Mint and melt activity per day and unit from `since` on, oldest day first
+    async fn get_daily_usage(&self, since: u64) -> Result<Vec<DailyUsage>, Self::Err>;
+    /// Get the melts settled internally at or after `since`, newest first
+    async fn get_internal_settlements(
+        &self,
+        since: Option<u64>,
+    ) -> Result<Vec<InternalSettlement>, Self::Err>;
+    /// Get the [`MintMintQuote`]s matching `search`, newest first
+    async fn search_mint_quotes(
+        &self,
+        search: &QuoteSearch<MintQuoteState>,
+    ) -> Result<Vec<MintMintQuote>, Self::Err>;
+    /// Get the [`mint::MeltQuote`]s matching `search`, newest first
+    async fn search_melt_quotes(
+        &self,
+        search: &QuoteSearch<MeltQuoteState>,
+    ) -> Result<Vec<mint::MeltQuote>, Self::Err>;
 }
 
 /// Mint Proof Transaction trait
@@ -420,6 +632,12 @@ pub trait ProofsTransaction {
         &mut self,
         operation_id: &uuid::Uuid,
     ) -> Result<Vec<PublicKey>, Self::Err>;
+
+    /// Record an attempt to spend already spent proofs
+    async fn add_double_spend_attempt(
+        &mut self,
+        attempt: &DoubleSpendAttempt,
+    ) -> Result<(), Self::Err>;
 }
 
 /// Mint Proof Database trait
@@ -452,6 +670,12 @@ pub trait ProofsDatabase {
         &self,
         operation_id: &uuid::Uuid,
     ) -> Result<Vec<PublicKey>, Self::Err>;
+
+    /// Get the double spend attempts recorded at or after `since`, newest first
+    async fn get_double_spend_attempts(
+        &self,
+        since: Option<u64>,
+    ) -> Result<Vec<DoubleSpendAttempt>, Self::Err>;
 }
 
 #[async_trait]
@@ -473,6 +697,12 @@ pub trait SignaturesTransaction {
         &mut self,
         blinded_messages: &[PublicKey],
     ) -> Result<Vec<Option<BlindSignature>>, Self::Err>;
+
+    /// Atomically add `usage` to the usage counters of each keyset
+    async fn increment_keyset_usage(
+        &mut self,
+        usage: &HashMap<Id, KeysetUsage>,
+    ) -> Result<(), Self::Err>;
 }
 
 #[async_trait]
@@ -502,6 +732,9 @@ pub trait SignaturesDatabase {
     /// Get total amount issued by keyset id
     async fn get_total_issued(&self) -> Result<HashMap<Id, Amount>, Self::Err>;
 
+    /// Get the usage counters by keyset id
+    async fn get_keyset_usage(&self) -> Result<HashMap<Id, KeysetUsage>, Self::Err>;
+
     /// Get blinded secrets (B values) by operation id
     async fn get_blinded_secrets_by_operation_id(
         &self,
@@ -3,11 +3,13 @@
 use std::str::FromStr;
 
 use bitcoin::bip32::DerivationPath;
-use cashu::{CurrencyUnit, Id};
+use cashu::{Amount, CurrencyUnit, Id};
 
 use crate::common::IssuerVersion;
-use crate::database::mint::{Database, Error, KeysDatabase};
+use crate::database::mint::{Database, Error, KeysDatabase, SigningAuditRecord};
 use crate::mint::MintKeySetInfo;
+use crate::nuts::SecretKey;
+use crate::rotation_log::{verify_rotation_log, RotationAttestation};
 
 /// Generate standard keyset amounts as powers of 2
 fn standard_keyset_amounts(max_order: u32) -> Vec<u64> {
@@ -295,3 +297,123 @@ where
     let active_id = db.get_active_keyset_id(&CurrencyUnit::Sat).await.unwrap();
     assert!(active_id.is_none());
 }
+
+/// Test disabling the active keyset
+pub async fn disable_active_keyset<DB>(db: DB)
+where
+    DB: Database<Error> + KeysDatabase<Err = Error>,
+{
+    let keyset_id = Id::from_str("00916bbf7ef91a36").unwrap();
+    let keyset_info = MintKeySetInfo {
+        id: keyset_id,
+        unit: CurrencyUnit::Sat,
+        active: true,
+        valid_from: 0,
+        final_expiry: None,
+        derivation_path: DerivationPath::from_str("m/0'/0'/0'").unwrap(),
+        derivation_path_index: Some(0),
+        input_fee_ppk: 0,
+        amounts: standard_keyset_amounts(32),
+        issuer_version: IssuerVersion::from_str("cdk/0.1.0").ok(),
+    };
+
+    let mut tx = KeysDatabase::begin_transaction(&db).await.unwrap();
+    tx.add_keyset_info(keyset_info).await.unwrap();
+    tx.set_active_keyset(CurrencyUnit::Sat, keyset_id)
+        .await
+        .unwrap();
+    tx.commit().await.unwrap();
+    assert!(db.get_disabled_keysets().await.unwrap().is_empty());
+
+    let mut tx = KeysDatabase::begin_transaction(&db).await.unwrap();
+    tx.disable_keyset(&keyset_id).await.unwrap();
+    tx.commit().await.unwrap();
+
+    let active_id = db.get_active_keyset_id(&CurrencyUnit::Sat).await.unwrap();
+    assert!(active_id.is_none());
+    assert_eq!(db.get_disabled_keysets().await.unwrap(), vec![keyset_id]);
+
+    // The keyset is still known, so proofs signed with it can be verified
+    let retrieved = db.get_keyset_info(&keyset_id).await.unwrap().unwrap();
+    assert!(!retrieved.active);
+}
+
+/// Test appending and querying the signing audit log
+pub async fn add_and_get_signing_audit_records<DB>(db: DB)
+where
+    DB: Database<Error> + KeysDatabase<Err = Error>,
+{
+    let keyset_id = Id::from_str("00916bbf7ef91a36").unwrap();
+    let first = SigningAuditRecord {
+        created_time: 1_000,
+        keyset_id,
+        amount: Amount::from(8),
+        blinded_secret_hash: "aa".repeat(32),
+        caller: None,
+    };
+    let second = SigningAuditRecord {
+        created_time: 2_000,
+        keyset_id,
+        amount: Amount::from(2),
+        blinded_secret_hash: "bb".repeat(32),
+        caller: Some("cert:cc".to_owned()),
+    };
+
+    let mut tx = KeysDatabase::begin_transaction(&db).await.unwrap();
+    tx.add_signing_audit_records(&[first.clone(), second.clone()])
+        .await
+        .unwrap();
+    tx.commit().await.unwrap();
+
+    let records = db.get_signing_audit_records(None).await.unwrap();
+    assert_eq!(records, vec![first, second.clone()]);
+
+    let records = db.get_signing_audit_records(Some(1_500)).await.unwrap();
+    assert_eq!(records, vec![second]);
+}
+
+/// Test appending to the keyset rotation log
+pub async fn append_rotation_log<DB>(db: DB)
+where
+    DB: Database<Error> + KeysDatabase<Err = Error>,
+{
+    let signing_key = SecretKey::generate();
+    let first = RotationAttestation {
+        sequence: 0,
+        previous_hash: None,
+        created_at: 1_000,
+        unit: CurrencyUnit::Sat,
+        old_keyset_id: None,
+        new_keyset_id: Id::from_str("00916bbf7ef91a36").unwrap(),
+        reason: None,
+    }
+    .sign(&signing_key)
+    .unwrap();
+    let second = first
+        .next(
+            2_000,
+            CurrencyUnit::Sat,
+            Some(first.attestation.new_keyset_id),
+            Id::from_str("00aa6bbf7ef91a36").unwrap(),
+            Some("Keyset config updated".to_owned()),
+        )
+        .unwrap()
+        .sign(&signing_key)
+        .unwrap();
+
+    assert!(db.get_rotation_log().await.unwrap().is_empty());
+
+    let mut tx = KeysDatabase::begin_transaction(&db).await.unwrap();
+    tx.add_rotation_attestation(&first).await.unwrap();
+    tx.add_rotation_attestation(&second).await.unwrap();
+    tx.commit().await.unwrap();
+
+    let log = db.get_rotation_log().await.unwrap();
+    assert_eq!(log, vec![first.clone(), second]);
+    verify_rotation_log(&log).unwrap();
+
+    // An entry can not be rewritten
+    let mut tx = KeysDatabase::begin_transaction(&db).await.unwrap();
+    assert!(tx.add_rotation_attestation(&first).await.is_err());
+    tx.rollback().await.unwrap();
+}
@@ -5,7 +5,7 @@ use std::str::FromStr;
 
 use cashu::nut00::KnownMethod;
 use cashu::quote_id::QuoteId;
-use cashu::{Amount, BlindSignature, CurrencyUnit, Id, SecretKey};
+use cashu::{Amount, BlindSignature, CurrencyUnit, HoldInvoiceState, Id, SecretKey};
 
 use crate::database::mint::test::unique_string;
 use crate::database::mint::{Database, Error, KeysDatabase};
@@ -877,6 +877,205 @@ where
     );
 }
 
+/// Test updating melt quote expiry
+pub async fn update_melt_quote_expiry<DB>(db: DB)
+where
+    DB: Database<Error> + KeysDatabase<Err = Error>,
+{
+    let melt_quote = MeltQuote::new(
+        None,
+        MeltPaymentRequest::Bolt11 {
+            bolt11: "lnbc330n1p5d85skpp5344v3ktclujsjl3h09wgsfm7zytumr7h7zhrl857f5w8nv0a52zqdqqcqzzsxqyz5vqrzjqvueefmrckfdwyyu39m0lf24sqzcr9vcrmxrvgfn6empxz7phrjxvrttncqq0lcqqyqqqqlgqqqqqqgq2qsp5j3rrg8kvpemqxtf86j8tjm90wq77c7ende4e5qmrerq4xsg02vhq9qxpqysgqjltywgyk6uc5qcgwh8xnzmawl2tjlhz8d28tgp3yx8xwtz76x0jqkfh6mmq70hervjxs0keun7ur0spldgll29l0dnz3md50d65sfqqqwrwpsu".parse().unwrap()
+        },
+        cashu::CurrencyUnit::Sat,
+        Amount::new(100, cashu::CurrencyUnit::Sat),
+        Amount::new(10, cashu::CurrencyUnit::Sat),
+        0,
+        None,
+        None,
+        cashu::PaymentMethod::Known(KnownMethod::Bolt11),
+        None,
+        None,
+    );
+
+    let mut tx = Database::begin_transaction(&db).await.unwrap();
+    tx.add_melt_quote(melt_quote.clone()).await.unwrap();
+    tx.commit().await.unwrap();
+
+    let mut tx = Database::begin_transaction(&db).await.unwrap();
+    let mut quote = tx.get_melt_quote(&melt_quote.id).await.unwrap().unwrap();
+    tx.update_melt_quote_expiry(&mut quote, 1_000_000)
+        .await
+        .unwrap();
+    assert_eq!(quote.expiry, 1_000_000);
+    tx.commit().await.unwrap();
+
+    let retrieved = db.get_melt_quote(&melt_quote.id).await.unwrap().unwrap();
+    assert_eq!(retrieved.expiry, 1_000_000);
+    assert_eq!(retrieved.state, melt_quote.state);
+}
+
+/// Test recording the state of the hold invoice of a mint quote
+pub async fn update_mint_quote_hold_invoice<DB>(db: DB)
+where
+    DB: Database<Error> + KeysDatabase<Err = Error>,
+{
+    let mut mint_quote = MintQuote::new(
+        None,
+        unique_string(),
+        cashu::CurrencyUnit::Sat,
+        None,
+        0,
+        PaymentIdentifier::CustomId(unique_string()),
+        None,
+        Amount::new(0, cashu::CurrencyUnit::Sat),
+        Amount::new(0, cashu::CurrencyUnit::Sat),
+        cashu::PaymentMethod::Known(KnownMethod::Bolt11),
+        0,
+        vec![],
+        vec![],
+        None,
+    );
+    mint_quote.hold_invoice = Some(HoldInvoiceState::Open);
+
+    let mut tx = Database::begin_transaction(&db).await.unwrap();
+    tx.add_mint_quote(mint_quote.clone()).await.unwrap();
+    tx.commit().await.unwrap();
+
+    let retrieved = db.get_mint_quote(&mint_quote.id).await.unwrap().unwrap();
+    assert_eq!(retrieved.hold_invoice, Some(HoldInvoiceState::Open));
+
+    let mut tx = Database::begin_transaction(&db).await.unwrap();
+    let mut quote = tx.get_mint_quote(&mint_quote.id).await.unwrap().unwrap();
+    tx.update_mint_quote_hold_invoice(&mut quote, HoldInvoiceState::Settled)
+        .await
+        .unwrap();
+    assert_eq!(quote.hold_invoice, Some(HoldInvoiceState::Settled));
+    tx.commit().await.unwrap();
+
+    let retrieved = db.get_mint_quote(&mint_quote.id).await.unwrap().unwrap();
+    assert_eq!(retrieved.hold_invoice, Some(HoldInvoiceState::Settled));
+}
+
+/// Test recording the backend that paid a melt quote
+pub async fn update_melt_quote_backend<DB>(db: DB)
+where
+    DB: Database<Error> + KeysDatabase<Err = Error>,
+{
+    let mut melt_quote = MeltQuote::new(
+        None,
+        MeltPaymentRequest::Bolt11 {
+            bolt11: "lnbc330n1p5d85skpp5344v3ktclujsjl3h09wgsfm7zytumr7h7zhrl857f5w8nv0a52zqdqqcqzzsxqyz5vqrzjqvueefmrckfdwyyu39m0lf24sqzcr9vcrmxrvgfn6empxz7phrjxvrttncqq0lcqqyqqqqlgqqqqqqgq2qsp5j3rrg8kvpemqxtf86j8tjm90wq77c7ende4e5qmrerq4xsg02vhq9qxpqysgqjltywgyk6uc5qcgwh8xnzmawl2tjlhz8d28tgp3yx8xwtz76x0jqkfh6mmq70hervjxs0keun7ur0spldgll29l0dnz3md50d65sfqqqwrwpsu".parse().unwrap()
+        },
+        cashu::CurrencyUnit::Sat,
+        Amount::new(100, cashu::CurrencyUnit::Sat),
+        Amount::new(10, cashu::CurrencyUnit::Sat),
+        0,
+        None,
+        None,
+        cashu::PaymentMethod::Known(KnownMethod::Bolt11),
+        None,
+        None,
+    );
+    melt_quote.backend = Some("primary+secondary".to_string());
+
+    let mut tx = Database::begin_transaction(&db).await.unwrap();
+    tx.add_melt_quote(melt_quote.clone()).await.unwrap();
+    tx.commit().await.unwrap();
+
+    let mut tx = Database::begin_transaction(&db).await.unwrap();
+    let mut quote = tx.get_melt_quote(&melt_quote.id).await.unwrap().unwrap();
+    tx.update_melt_quote_backend(&mut quote, "secondary")
+        .await
+        .unwrap();
+    assert_eq!(quote.backend.as_deref(), Some("secondary"));
+    tx.commit().await.unwrap();
+
+    let retrieved = db.get_melt_quote(&melt_quote.id).await.unwrap().unwrap();
+    assert_eq!(retrieved.backend.as_deref(), Some("secondary"));
+}
+
+/// Test removing the unpaid quotes past their expiry
+pub async fn remove_expired_quotes<DB>(db: DB)
+where
+    DB: Database<Error> + KeysDatabase<Err = Error>,
+{
+    let mint_quote = |expiry| {
+        MintQuote::new(
+            None,
+            unique_string(),
+            cashu::CurrencyUnit::Sat,
+            None,
+            expiry,
+            PaymentIdentifier::CustomId(unique_string()),
+            None,
+            Amount::new(0, cashu::CurrencyUnit::Sat),
+            Amount::new(0, cashu::CurrencyUnit::Sat),
+            cashu::PaymentMethod::Known(KnownMethod::Bolt12),
+            0,
+            vec![],
+            vec![],
+            None,
+        )
+    };
+    let melt_quote = |expiry| {
+        MeltQuote::new(
+            None,
+            MeltPaymentRequest::Onchain {
+                address: unique_string(),
+            },
+            cashu::CurrencyUnit::Sat,
+            Amount::new(100, cashu::CurrencyUnit::Sat),
+            Amount::new(10, cashu::CurrencyUnit::Sat),
+            expiry,
+            None,
+            None,
+            cashu::PaymentMethod::Known(KnownMethod::Onchain),
+            None,
+            None,
+        )
+    };
+
+    let expired_mint = mint_quote(100);
+    let paid_mint = mint_quote(100);
+    let live_mint = mint_quote(10_000);
+    let unexpiring_mint = mint_quote(0);
+    let expired_melt = melt_quote(100);
+    let live_melt = melt_quote(10_000);
+
+    let mut tx = Database::begin_transaction(&db).await.unwrap();
+    tx.add_mint_quote(expired_mint.clone()).await.unwrap();
+    let mut paid = tx.add_mint_quote(paid_mint.clone()).await.unwrap();
+    paid.add_payment(
+        Amount::new(10, cashu::CurrencyUnit::Sat),
+        unique_string(),
+        None,
+    )
+    .unwrap();
+    tx.update_mint_quote(&mut paid).await.unwrap();
+    tx.add_mint_quote(live_mint.clone()).await.unwrap();
+    tx.add_mint_quote(unexpiring_mint.clone()).await.unwrap();
+    tx.add_melt_quote(expired_melt.clone()).await.unwrap();
+    tx.add_melt_quote(live_melt.clone()).await.unwrap();
+    tx.commit().await.unwrap();
+
+    let mut tx = Database::begin_transaction(&db).await.unwrap();
+    assert_eq!(tx.remove_expired_mint_quotes(1_000).await.unwrap(), 1);
+    assert_eq!(tx.remove_expired_melt_quotes(1_000).await.unwrap(), 1);
+    tx.commit().await.unwrap();
+
+    assert!(db.get_mint_quote(&expired_mint.id).await.unwrap().is_none());
+    assert!(db.get_mint_quote(&paid_mint.id).await.unwrap().is_some());
+    assert!(db.get_mint_quote(&live_mint.id).await.unwrap().is_some());
+    assert!(db
+        .get_mint_quote(&unexpiring_mint.id)
+        .await
+        .unwrap()
+        .is_some());
+    assert!(db.get_melt_quote(&expired_melt.id).await.unwrap().is_none());
+    assert!(db.get_melt_quote(&live_melt.id).await.unwrap().is_some());
+}
+
 /// Test getting all mint quotes
 pub async fn get_all_mint_quotes<DB>(db: DB)
 where
@@ -1516,7 +1715,7 @@ where
         Amount::new(20, cashu::CurrencyUnit::Sat),
         0,
         Some(lookup_id.clone()),
-        None,
+        Some(cashu::MeltOptions::new_mpp(200_000u64)),
         cashu::PaymentMethod::Known(KnownMethod::Bolt11),
         Some(serde_json::json!({"item": 2})),
         None,
@@ -1585,7 +1784,7 @@ where
         Amount::new(20, cashu::CurrencyUnit::Sat),
         0,
         Some(lookup_id.clone()),
-        None,
+        Some(cashu::MeltOptions::new_mpp(200_000u64)),
         cashu::PaymentMethod::Known(KnownMethod::Bolt11),
         Some(serde_json::json!({"target": false})),
         None,
@@ -1629,6 +1828,62 @@ where
     tx.commit().await.unwrap();
 }
 
+/// Test only one unpaid bolt11 melt quote can pay a whole invoice
+pub async fn one_unpaid_bolt11_melt_quote_per_lookup_id<DB>(db: DB)
+where
+    DB: Database<Error> + KeysDatabase<Err = Error>,
+{
+    use cashu::{MeltOptions, MeltQuoteState};
+
+    let lookup_id = PaymentIdentifier::CustomId(unique_string());
+    let new_quote = |options: Option<MeltOptions>| {
+        MeltQuote::new(
+            None,
+            MeltPaymentRequest::Bolt11 {
+                bolt11: "lnbc330n1p5d85skpp5344v3ktclujsjl3h09wgsfm7zytumr7h7zhrl857f5w8nv0a52zqdqqcqzzsxqyz5vqrzjqvueefmrckfdwyyu39m0lf24sqzcr9vcrmxrvgfn6empxz7phrjxvrttncqq0lcqqyqqqqlgqqqqqqgq2qsp5j3rrg8kvpemqxtf86j8tjm90wq77c7ende4e5qmrerq4xsg02vhq9qxpqysgqjltywgyk6uc5qcgwh8xnzmawl2tjlhz8d28tgp3yx8xwtz76x0jqkfh6mmq70hervjxs0keun7ur0spldgll29l0dnz3md50d65sfqqqwrwpsu".parse().unwrap()
+            },
+            cashu::CurrencyUnit::Sat,
+            Amount::new(100, cashu::CurrencyUnit::Sat),
+            Amount::new(10, cashu::CurrencyUnit::Sat),
+            0,
+            Some(lookup_id.clone()),
+            options,
+            cashu::PaymentMethod::Known(KnownMethod::Bolt11),
+            None,
+            None,
+        )
+    };
+
+    let first = new_quote(None);
+    let mut tx = Database::begin_transaction(&db).await.unwrap();
+    tx.add_melt_quote(first.clone()).await.unwrap();
+    tx.commit().await.unwrap();
+
+    // A second quote for the whole invoice is refused
+    let mut tx = Database::begin_transaction(&db).await.unwrap();
+    assert!(matches!(
+        tx.add_melt_quote(new_quote(None)).await,
+        Err(Error::Duplicate)
+    ));
+    tx.rollback().await.unwrap();
+
+    // Multi-part quotes only pay part of it
+    let mut tx = Database::begin_transaction(&db).await.unwrap();
+    tx.add_melt_quote(new_quote(Some(MeltOptions::new_mpp(50_000u64))))
+        .await
+        .unwrap();
+    tx.commit().await.unwrap();
+
+    // Once the first quote failed another one can be added
+    let mut tx = Database::begin_transaction(&db).await.unwrap();
+    let mut quote = tx.get_melt_quote(&first.id).await.unwrap().unwrap();
+    tx.update_melt_quote_state(&mut quote, MeltQuoteState::Failed, None)
+        .await
+        .unwrap();
+    tx.add_melt_quote(new_quote(None)).await.unwrap();
+    tx.commit().await.unwrap();
+}
+
 /// Test that duplicate payment IDs are rejected
 pub async fn reject_duplicate_payment_ids<DB>(db: DB)
 where
@@ -1866,3 +2121,235 @@ where
     assert_eq!(quotes[1].as_ref().unwrap().id, quote1.id);
     tx.commit().await.unwrap();
 }
+
+/// Test that issuances and paid melt quotes are aggregated per day and unit
+pub async fn get_daily_usage<DB>(db: DB)
+where
+    DB: Database<Error> + KeysDatabase<Err = Error>,
+{
+    use cashu::MeltQuoteState;
+
+    use crate::util::unix_time;
+
+    let since = unix_time();
+
+    let mint_quote = MintQuote::new(
+        None,
+        unique_string(),
+        CurrencyUnit::Sat,
+        None,
+        0,
+        PaymentIdentifier::CustomId(unique_string()),
+        None,
+        Amount::new(0, CurrencyUnit::Sat),
+        Amount::new(0, CurrencyUnit::Sat),
+        cashu::PaymentMethod::Known(KnownMethod::Bolt11),
+        0,
+        vec![],
+        vec![],
+        None,
+    );
+
+    let mut tx = Database::begin_transaction(&db).await.unwrap();
+    tx.add_mint_quote(mint_quote.clone()).await.unwrap();
+    tx.commit().await.unwrap();
+
+    let mut tx = Database::begin_transaction(&db).await.unwrap();
+    let mut quote = tx.get_mint_quote(&mint_quote.id).await.unwrap().unwrap();
+    quote
+        .add_payment(
+            Amount::from(1000).with_unit(CurrencyUnit::Sat),
+            unique_string(),
+            None,
+        )
+        .unwrap();
+    tx.update_mint_quote(&mut quote).await.unwrap();
+    tx.commit().await.unwrap();
+
+    for amount in [400, 300] {
+        let mut tx = Database::begin_transaction(&db).await.unwrap();
+        let mut quote = tx.get_mint_quote(&mint_quote.id).await.unwrap().unwrap();
+        quote
+            .add_issuance(Amount::from(amount).with_unit(CurrencyUnit::Sat))
+            .unwrap();
+        tx.update_mint_quote(&mut quote).await.unwrap();
+        tx.commit().await.unwrap();
+    }
+
+    let melt_quote = |amount: u64| {
+        MeltQuote::new(
+            None,
+            MeltPaymentRequest::Bolt11 {
+                bolt11: "lnbc330n1p5d85skpp5344v3ktclujsjl3h09wgsfm7zytumr7h7zhrl857f5w8nv0a52zqdqqcqzzsxqyz5vqrzjqvueefmrckfdwyyu39m0lf24sqzcr9vcrmxrvgfn6empxz7phrjxvrttncqq0lcqqyqqqqlgqqqqqqgq2qsp5j3rrg8kvpemqxtf86j8tjm90wq77c7ende4e5qmrerq4xsg02vhq9qxpqysgqjltywgyk6uc5qcgwh8xnzmawl2tjlhz8d28tgp3yx8xwtz76x0jqkfh6mmq70hervjxs0keun7ur0spldgll29l0dnz3md50d65sfqqqwrwpsu".parse().unwrap()
+            },
+            CurrencyUnit::Sat,
+            Amount::new(amount, CurrencyUnit::Sat),
+            Amount::new(10, CurrencyUnit::Sat),
+            0,
+            None,
+            None,
+            cashu::PaymentMethod::Known(KnownMethod::Bolt11),
+            None,
+            None,
+        )
+    };
+    let paid = melt_quote(100);
+    let unpaid = melt_quote(50);
+
+    let mut tx = Database::begin_transaction(&db).await.unwrap();
+    tx.add_melt_quote(paid.clone()).await.unwrap();
+    tx.add_melt_quote(unpaid).await.unwrap();
+    tx.commit().await.unwrap();
+
+    let mut tx = Database::begin_transaction(&db).await.unwrap();
+    let mut quote = tx.get_melt_quote(&paid.id).await.unwrap().unwrap();
+    tx.update_melt_quote_state(&mut quote, MeltQuoteState::Pending, None)
+        .await
+        .unwrap();
+    tx.update_melt_quote_state(&mut quote, MeltQuoteState::Paid, None)
+        .await
+        .unwrap();
+    tx.commit().await.unwrap();
+
+    let usage = db.get_daily_usage(since).await.unwrap();
+    assert_eq!(usage.len(), 1);
+    assert_eq!(usage[0].day % 86400, 0);
+    assert_eq!(usage[0].unit, CurrencyUnit::Sat);
+    assert_eq!(usage[0].mint_count, 2);
+    assert_eq!(usage[0].mint_volume, Amount::from(700));
+    assert_eq!(usage[0].melt_count, 1);
+    assert_eq!(usage[0].melt_volume, Amount::from(100));
+
+    assert!(db.get_daily_usage(since + 86400).await.unwrap().is_empty());
+}
+
+/// Test recording and querying internal settlements
+pub async fn add_and_get_internal_settlements<DB>(db: DB)
+where
+    DB: Database<Error> + KeysDatabase<Err = Error>,
+{
+    use crate::database::mint::InternalSettlement;
+
+    let settlement = |created_time: u64, amount: u64| InternalSettlement {
+        created_time,
+        melt_quote_id: QuoteId::new(),
+        mint_quote_id: QuoteId::new(),
+        unit: CurrencyUnit::Sat,
+        amount: Amount::from(amount),
+        fee_reserve: Amount::from(2),
+    };
+    let first = settlement(1_000, 100);
+    let second = settlement(2_000, 50);
+
+    let mut tx = Database::begin_transaction(&db).await.unwrap();
+    tx.add_internal_settlement(&first).await.unwrap();
+    tx.add_internal_settlement(&second).await.unwrap();
+    tx.commit().await.unwrap();
+
+    let settlements = db.get_internal_settlements(None).await.unwrap();
+    assert_eq!(settlements, vec![second.clone(), first]);
+
+    let settlements = db.get_internal_settlements(Some(1_500)).await.unwrap();
+    assert_eq!(settlements, vec![second]);
+}
+
+/// Test searching mint quotes by lookup id, request, amount, state and time
+pub async fn search_mint_quotes<DB>(db: DB)
+where
+    DB: Database<Error> + KeysDatabase<Err = Error>,
+{
+    use crate::database::mint::QuoteSearch;
+    use crate::nuts::MintQuoteState;
+
+    let tag = unique_string();
+    let quote = |amount: u64, paid: u64, created_time: u64| {
+        MintQuote::new(
+            None,
+            format!("lnbc{amount}{tag}{}", unique_string()),
+            CurrencyUnit::Sat,
+            Some(Amount::new(amount, CurrencyUnit::Sat)),
+            0,
+            PaymentIdentifier::CustomId(unique_string()),
+            None,
+            Amount::new(paid, CurrencyUnit::Sat),
+            Amount::new(0, CurrencyUnit::Sat),
+            cashu::PaymentMethod::Known(KnownMethod::Bolt11),
+            created_time,
+            vec![],
+            vec![],
+            None,
+        )
+    };
+    let small = quote(10, 0, 1_000);
+    let medium = quote(100, 100, 2_000);
+    let large = quote(1_000, 0, 3_000);
+
+    let mut tx = Database::begin_transaction(&db).await.unwrap();
+    for quote in [&small, &medium, &large] {
+        tx.add_mint_quote(quote.clone()).await.unwrap();
+    }
+    tx.commit().await.unwrap();
+
+    let ids = |quotes: Vec<MintQuote>| quotes.into_iter().map(|q| q.id).collect::<Vec<_>>();
+
+    let by_request = QuoteSearch {
+        request: Some(tag.clone()),
+        ..Default::default()
+    };
+    assert_eq!(
+        ids(db.search_mint_quotes(&by_request).await.unwrap()),
+        vec![large.id.clone(), medium.id.clone(), small.id.clone()]
+    );
+
+    let by_lookup_id = QuoteSearch {
+        lookup_id: Some(medium.request_lookup_id.to_string()),
+        ..Default::default()
+    };
+    assert_eq!(
+        ids(db.search_mint_quotes(&by_lookup_id).await.unwrap()),
+        vec![medium.id.clone()]
+    );
+
+    let by_amount = QuoteSearch {
+        request: Some(tag.clone()),
+        min_amount: Some(50),
+        max_amount: Some(1_000),
+        ..Default::default()
+    };
+    assert_eq!(
+        ids(db.search_mint_quotes(&by_amount).await.unwrap()),
+        vec![large.id.clone(), medium.id.clone()]
+    );
+
+    let by_state = QuoteSearch {
+        request: Some(tag.clone()),
+        state: Some(MintQuoteState::Paid),
+        ..Default::default()
+    };
+    assert_eq!(
+        ids(db.search_mint_quotes(&by_state).await.unwrap()),
+        vec![medium.id.clone()]
+    );
+
+    let by_time = QuoteSearch {
+        request: Some(tag.clone()),
+        created_after: Some(1_500),
+        created_before: Some(3_000),
+        ..Default::default()
+    };
+    assert_eq!(
+        ids(db.search_mint_quotes(&by_time).await.unwrap()),
+        vec![medium.id.clone()]
+    );
+
+    let second_page = QuoteSearch {
+        request: Some(tag),
+        offset: 1,
+        limit: 1,
+        ..Default::default()
+    };
+    assert_eq!(
+        ids(db.search_mint_quotes(&second_page).await.unwrap()),
+        vec![medium.id]
+    );
+}
@@ -211,6 +211,10 @@ macro_rules! mint_db_test {
             add_melt_quote_only_once,
             update_melt_quote_state_transition,
             update_melt_quote_request_lookup_id,
+            update_melt_quote_expiry,
+            update_melt_quote_backend,
+            update_mint_quote_hold_invoice,
+            remove_expired_quotes,
             get_all_mint_quotes,
             get_all_melt_quotes,
             get_mint_quote_by_request,
@@ -220,6 +224,7 @@ macro_rules! mint_db_test {
             get_blind_signatures_for_keyset,
             get_blind_signatures_for_quote,
             get_total_issued,
+            increment_keyset_usage,
             get_nonexistent_blind_signatures,
             add_duplicate_blind_signatures,
             add_and_get_keyset_info,
@@ -230,6 +235,9 @@ macro_rules! mint_db_test {
             update_active_keyset,
             get_nonexistent_keyset_info,
             get_active_keyset_when_none_set,
+            disable_active_keyset,
+            add_and_get_signing_audit_records,
+            append_rotation_log,
             get_proofs_states,
             get_nonexistent_proof_states,
             get_proofs_by_nonexistent_ys,
@@ -263,10 +271,15 @@ macro_rules! mint_db_test {
             remove_spent_proofs_should_fail,
             get_proofs_with_inconsistent_states_fails,
             get_proofs_fails_when_some_not_found,
+            add_and_get_double_spend_attempts,
             update_proofs_state_updates_proofs_with_state,
             get_mint_quotes_by_ids,
+            get_daily_usage,
+            add_and_get_internal_settlements,
+            search_mint_quotes,
             get_melt_quotes_by_request_lookup_id,
             lock_melt_quote_and_related,
+            one_unpaid_bolt11_melt_quote_per_lookup_id,
         );
     };
     ($make_db_fn:ident, $($name:ident),+ $(,)?) => {
@@ -6,8 +6,9 @@ use cashu::secret::Secret;
 use cashu::{Amount, Id, SecretKey};
 
 use crate::database::mint::test::setup_keyset;
-use crate::database::mint::{Database, Error, KeysDatabase, Proof, QuoteId};
+use crate::database::mint::{Database, DoubleSpendAttempt, Error, KeysDatabase, Proof, QuoteId};
 use crate::mint::Operation;
+use crate::nuts::nut21::RoutePath;
 use crate::state::check_state_transition;
 
 /// Test get proofs by keyset id
@@ -1041,3 +1042,38 @@ where
 
     tx.rollback().await.unwrap();
 }
+
+/// Test recording and querying double spend attempts
+pub async fn add_and_get_double_spend_attempts<DB>(db: DB)
+where
+    DB: Database<Error>,
+{
+    let swap = DoubleSpendAttempt {
+        created_time: 1_000,
+        endpoint: RoutePath::Swap,
+        quote_id: None,
+        client_fingerprint: Some("fingerprint".to_owned()),
+        ys: vec![
+            SecretKey::generate().public_key(),
+            SecretKey::generate().public_key(),
+        ],
+    };
+    let melt = DoubleSpendAttempt {
+        created_time: 2_000,
+        endpoint: RoutePath::Melt("bolt11".to_owned()),
+        quote_id: Some(QuoteId::new()),
+        client_fingerprint: None,
+        ys: vec![SecretKey::generate().public_key()],
+    };
+
+    let mut tx = Database::begin_transaction(&db).await.unwrap();
+    tx.add_double_spend_attempt(&swap).await.unwrap();
+    tx.add_double_spend_attempt(&melt).await.unwrap();
+    tx.commit().await.unwrap();
+
+    let attempts = db.get_double_spend_attempts(None).await.unwrap();
+    assert_eq!(attempts, vec![melt.clone(), swap]);
+
+    let attempts = db.get_double_spend_attempts(Some(1_500)).await.unwrap();
+    assert_eq!(attempts, vec![melt]);
+}
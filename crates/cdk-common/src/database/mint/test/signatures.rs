@@ -1,10 +1,11 @@
 //! Blind signature tests
 
+use std::collections::HashMap;
 use std::str::FromStr;
 
 use cashu::{Amount, BlindSignature, Id, SecretKey};
 
-use crate::database::mint::{Database, Error, KeysDatabase, QuoteId};
+use crate::database::mint::{Database, Error, KeysDatabase, KeysetUsage, QuoteId};
 use crate::database::MintSignaturesDatabase;
 
 /// Test adding and retrieving blind signatures
@@ -231,6 +232,77 @@ where
     assert!(total >= Amount::from(600));
 }
 
+/// Test incrementing the keyset usage counters
+pub async fn increment_keyset_usage<DB>(db: DB)
+where
+    DB: Database<Error> + KeysDatabase<Err = Error> + MintSignaturesDatabase<Err = Error>,
+{
+    let keyset_id = Id::from_str("001711afb1de20cb").unwrap();
+    let other_keyset_id = Id::from_str("00916bbf7ef91a36").unwrap();
+
+    let mut tx = Database::begin_transaction(&db).await.unwrap();
+    tx.increment_keyset_usage(&HashMap::from([(
+        keyset_id,
+        KeysetUsage {
+            signatures_issued: 3,
+            proofs_verified: 0,
+        },
+    )]))
+    .await
+    .unwrap();
+    tx.commit().await.unwrap();
+
+    let mut tx = Database::begin_transaction(&db).await.unwrap();
+    tx.increment_keyset_usage(&HashMap::from([
+        (
+            keyset_id,
+            KeysetUsage {
+                signatures_issued: 1,
+                proofs_verified: 2,
+            },
+        ),
+        (
+            other_keyset_id,
+            KeysetUsage {
+                signatures_issued: 0,
+                proofs_verified: 5,
+            },
+        ),
+    ]))
+    .await
+    .unwrap();
+    tx.commit().await.unwrap();
+
+    // Rolled back increments are not counted
+    let mut tx = Database::begin_transaction(&db).await.unwrap();
+    tx.increment_keyset_usage(&HashMap::from([(
+        keyset_id,
+        KeysetUsage {
+            signatures_issued: 100,
+            proofs_verified: 100,
+        },
+    )]))
+    .await
+    .unwrap();
+    tx.rollback().await.unwrap();
+
+    let usage = db.get_keyset_usage().await.unwrap();
+    assert_eq!(
+        usage.get(&keyset_id),
+        Some(&KeysetUsage {
+            signatures_issued: 4,
+            proofs_verified: 2,
+        })
+    );
+    assert_eq!(
+        usage.get(&other_keyset_id),
+        Some(&KeysetUsage {
+            signatures_issued: 0,
+            proofs_verified: 5,
+        })
+    );
+}
+
 /// Test retrieving non-existent blind signatures
 pub async fn get_nonexistent_blind_signatures<DB>(db: DB)
 where
@@ -1,6 +1,7 @@
 //! CDK Database
 
 mod kvstore;
+mod retry;
 
 #[cfg(feature = "mint")]
 pub mod mint;
@@ -13,22 +14,26 @@ pub use kvstore::{
     KVSTORE_NAMESPACE_KEY_ALPHABET, KVSTORE_NAMESPACE_KEY_MAX_LEN,
 };
 
+pub use retry::RetryPolicy;
+
 /// Arc-wrapped KV store for shared ownership
 pub type DynKVStore = std::sync::Arc<dyn KVStore<Err = Error> + Send + Sync>;
 
 #[cfg(feature = "mint")]
 pub use mint::{
-    Database as MintDatabase, DynMintDatabase, DynMintTransaction,
-    KeysDatabase as MintKeysDatabase, KeysDatabaseTransaction as MintKeyDatabaseTransaction,
-    ProofsDatabase as MintProofsDatabase, ProofsTransaction as MintProofsTransaction,
+    DailyUsage, Database as MintDatabase, DoubleSpendAttempt, DynMintDatabase, DynMintTransaction,
+    InternalSettlement, KeysDatabase as MintKeysDatabase,
+    KeysDatabaseTransaction as MintKeyDatabaseTransaction, KeysetUsage,
+    ProofsDatabase as MintProofsDatabase, ProofsTransaction as MintProofsTransaction, QuoteSearch,
     QuotesDatabase as MintQuotesDatabase, QuotesTransaction as MintQuotesTransaction,
     SignaturesDatabase as MintSignaturesDatabase,
-    SignaturesTransaction as MintSignatureTransaction, Transaction as MintTransaction,
+    SignaturesTransaction as MintSignatureTransaction, SigningAuditRecord,
+    Transaction as MintTransaction, DEFAULT_QUOTE_SEARCH_LIMIT,
 };
 #[cfg(feature = "mint")]
 pub use mint::{DynMintAuthDatabase, MintAuthDatabase, MintAuthTransaction};
 #[cfg(feature = "wallet")]
-pub use wallet::Database as WalletDatabase;
+pub use wallet::{Database as WalletDatabase, WalletMemoryDatabase};
 
 /// Data conversion error
 #[derive(thiserror::Error, Debug)]
@@ -119,6 +124,11 @@ pub enum Error {
     #[error("Locked resource")]
     Locked,
 
+    /// Failure that may not happen again, such as a lost connection or a serialization
+    /// conflict, see [`Error::is_transient`]
+    #[error("Transient database error: {0}")]
+    Transient(Box<dyn std::error::Error + Send + Sync>),
+
     /// Amount overflow
     #[error("Amount overflow")]
     AmountOverflow,
@@ -222,6 +232,13 @@ pub enum Error {
     ConcurrentUpdate,
 }
 
+impl Error {
+    /// Whether the operation failed without any effect and can be retried as is
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Error::Transient(_))
+    }
+}
+
 #[cfg(feature = "mint")]
 impl From<crate::state::Error> for Error {
     fn from(state: crate::state::Error) -> Self {
@@ -0,0 +1,127 @@
+//! Retry of transient database failures
+//!
+//! A lost connection, a server restarting or a serialization conflict fail a statement without
+//! any effect. Reads and the start of transactions are run again on a fresh connection of the
+//! pool, so a brief outage or failover of the database is not seen by the callers. A statement
+//! inside a transaction, its commit included, fails the whole transaction instead, as the work
+//! done earlier in it is lost: the mint runs the transactions of swaps, melts and mints again
+//! from the start. Both follow a [`RetryPolicy`], a bounded number of attempts after a jittered
+//! exponential backoff.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+use super::Error;
+
+/// How transient failures are retried, see [`Error::is_transient`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts after the first one, 0 disables retries
+    pub max_retries: u32,
+    /// Backoff before the first retry, doubled on every retry
+    pub base_delay: Duration,
+    /// Longest backoff between two attempts
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_millis(50),
+            max_delay: Duration::from_secs(1),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Never retry
+    pub fn disabled() -> Self {
+        Self {
+            max_retries: 0,
+            ..Default::default()
+        }
+    }
+
+    /// Backoff before the retry number `retry`, counted from 0
+    ///
+    /// Between half and the whole of the exponential backoff, picked at random so callers that
+    /// failed together do not all retry at once.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let backoff = self
+            .base_delay
+            .saturating_mul(1u32.checked_shl(retry).unwrap_or(u32::MAX))
+            .min(self.max_delay);
+        let half = backoff / 2;
+
+        half + jitter(backoff - half)
+    }
+
+    /// Backoff before running again an operation that failed with `err` after `retry` retries,
+    /// none when it must not be retried
+    pub fn retry_after(&self, err: &Error, retry: u32) -> Option<Duration> {
+        if !err.is_transient() || retry >= self.max_retries {
+            return None;
+        }
+
+        let backoff = self.backoff(retry);
+        tracing::warn!(
+            "Transient database error, retry {} of {} in {:?}: {}",
+            retry + 1,
+            self.max_retries,
+            backoff,
+            err
+        );
+        Some(backoff)
+    }
+}
+
+/// Random duration up to `max`
+fn jitter(max: Duration) -> Duration {
+    let max = u64::try_from(max.as_nanos()).unwrap_or(u64::MAX);
+    if max == 0 {
+        return Duration::ZERO;
+    }
+
+    // Every `RandomState` is seeded differently, which is random enough to spread retries
+    let random = RandomState::new().build_hasher().finish();
+    Duration::from_nanos(random % max.saturating_add(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_grows_up_to_the_max_delay() {
+        let policy = RetryPolicy::default();
+
+        for retry in 0..10 {
+            let expected = policy
+                .base_delay
+                .saturating_mul(1 << retry)
+                .min(policy.max_delay);
+            let backoff = policy.backoff(retry);
+
+            assert!(backoff >= expected / 2, "retry {retry}: {backoff:?}");
+            assert!(backoff <= expected, "retry {retry}: {backoff:?}");
+        }
+
+        assert!(policy.backoff(u32::MAX) <= policy.max_delay);
+    }
+
+    #[test]
+    fn only_transient_errors_are_retried() {
+        let policy = RetryPolicy::default();
+        let transient = Error::Transient("connection reset".into());
+
+        assert!(policy.retry_after(&transient, 0).is_some());
+        assert!(policy
+            .retry_after(&transient, policy.max_retries - 1)
+            .is_some());
+        assert!(policy.retry_after(&transient, policy.max_retries).is_none());
+        assert!(policy.retry_after(&Error::Duplicate, 0).is_none());
+        assert!(RetryPolicy::disabled().retry_after(&transient, 0).is_none());
+    }
+}
@@ -0,0 +1,709 @@
+//! In-memory wallet database
+//!
+//! Keeps every record in process memory and loses them when the database is dropped. Meant for
+//! ephemeral wallets such as tests, burner wallets or WASM apps without persistent storage.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use bitcoin::bip32::DerivationPath;
+use cashu::nut00::KnownMethod;
+use cashu::KeySet;
+use parking_lot::RwLock;
+
+use super::Database;
+use crate::database::{validate_kvstore_params, Error};
+use crate::mint_url::MintUrl;
+use crate::nuts::{
+    CurrencyUnit, Id, KeySetInfo, Keys, MintInfo, PaymentMethod, PublicKey, SpendingConditions,
+    State,
+};
+use crate::util::unix_time;
+use crate::wallet::{self, MintQuote, ProofInfo, Transaction, TransactionDirection, TransactionId};
+use crate::Amount;
+
+#[derive(Debug, Default)]
+struct Store {
+    mints: HashMap<MintUrl, Option<MintInfo>>,
+    mint_keysets: HashMap<MintUrl, Vec<Id>>,
+    keysets: HashMap<Id, KeySetInfo>,
+    keyset_u32: HashMap<u32, Id>,
+    keys: HashMap<Id, Keys>,
+    keyset_counters: HashMap<Id, u32>,
+    mint_quotes: HashMap<String, MintQuote>,
+    melt_quotes: HashMap<String, wallet::MeltQuote>,
+    proofs: HashMap<PublicKey, ProofInfo>,
+    transactions: BTreeMap<TransactionId, Transaction>,
+    sagas: HashMap<uuid::Uuid, wallet::WalletSaga>,
+    kv_store: BTreeMap<(String, String, String), Vec<u8>>,
+    p2pk_keys: HashMap<PublicKey, wallet::P2PKSigningKey>,
+}
+
+impl Store {
+    /// Map the short u32 keyset id. Returns false if it already belongs to another keyset.
+    fn map_keyset_u32(&mut self, id: Id) -> bool {
+        match self.keyset_u32.get(&u32::from(id)) {
+            Some(existing) if *existing != id => false,
+            _ => {
+                self.keyset_u32.insert(u32::from(id), id);
+                true
+            }
+        }
+    }
+}
+
+/// Wallet database that keeps all records in memory
+#[derive(Debug, Clone, Default)]
+pub struct WalletMemoryDatabase {
+    store: Arc<RwLock<Store>>,
+}
+
+impl WalletMemoryDatabase {
+    /// Create new empty [`WalletMemoryDatabase`]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl Database<Error> for WalletMemoryDatabase {
+    async fn get_mint(&self, mint_url: MintUrl) -> Result<Option<MintInfo>, Error> {
+        Ok(self.store.read().mints.get(&mint_url).cloned().flatten())
+    }
+
+    async fn get_mints(&self) -> Result<HashMap<MintUrl, Option<MintInfo>>, Error> {
+        Ok(self.store.read().mints.clone())
+    }
+
+    async fn get_mint_keysets(&self, mint_url: MintUrl) -> Result<Option<Vec<KeySetInfo>>, Error> {
+        let store = self.store.read();
+
+        let keysets = store
+            .mint_keysets
+            .get(&mint_url)
+            .map(|ids| {
+                ids.iter()
+                    .filter_map(|id| store.keysets.get(id).cloned())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+        match keysets.is_empty() {
+            true => Ok(None),
+            false => Ok(Some(keysets)),
+        }
+    }
+
+    async fn get_keyset_by_id(&self, keyset_id: &Id) -> Result<Option<KeySetInfo>, Error> {
+        Ok(self.store.read().keysets.get(keyset_id).cloned())
+    }
+
+    async fn get_mint_quote(&self, quote_id: &str) -> Result<Option<MintQuote>, Error> {
+        Ok(self.store.read().mint_quotes.get(quote_id).cloned())
+    }
+
+    async fn get_mint_quotes(&self) -> Result<Vec<MintQuote>, Error> {
+        Ok(self.store.read().mint_quotes.values().cloned().collect())
+    }
+
+    async fn get_unissued_mint_quotes(&self) -> Result<Vec<MintQuote>, Error> {
+        Ok(self
+            .store
+            .read()
+            .mint_quotes
+            .values()
+            .filter(|quote| {
+                quote.amount_issued == Amount::ZERO
+                    || quote.payment_method == PaymentMethod::Known(KnownMethod::Bolt12)
+            })
+            .cloned()
+            .collect())
+    }
+
+    async fn get_melt_quote(&self, quote_id: &str) -> Result<Option<wallet::MeltQuote>, Error> {
+        Ok(self.store.read().melt_quotes.get(quote_id).cloned())
+    }
+
+    async fn get_melt_quotes(&self) -> Result<Vec<wallet::MeltQuote>, Error> {
+        Ok(self.store.read().melt_quotes.values().cloned().collect())
+    }
+
+    async fn get_keys(&self, id: &Id) -> Result<Option<Keys>, Error> {
+        Ok(self.store.read().keys.get(id).cloned())
+    }
+
+    async fn get_proofs(
+        &self,
+        mint_url: Option<MintUrl>,
+        unit: Option<CurrencyUnit>,
+        state: Option<Vec<State>>,
+        spending_conditions: Option<Vec<SpendingConditions>>,
+    ) -> Result<Vec<ProofInfo>, Error> {
+        Ok(self
+            .store
+            .read()
+            .proofs
+            .values()
+            .filter(|proof| {
+                proof.matches_conditions(&mint_url, &unit, &state, &spending_conditions)
+            })
+            .cloned()
+            .collect())
+    }
+
+    async fn get_proofs_by_ys(&self, ys: Vec<PublicKey>) -> Result<Vec<ProofInfo>, Error> {
+        let store = self.store.read();
+
+        Ok(ys
+            .iter()
+            .filter_map(|y| store.proofs.get(y).cloned())
+            .collect())
+    }
+
+    async fn get_balance(
+        &self,
+        mint_url: Option<MintUrl>,
+        unit: Option<CurrencyUnit>,
+        state: Option<Vec<State>>,
+    ) -> Result<u64, Error> {
+        let proofs = self.get_proofs(mint_url, unit, state, None).await?;
+        Ok(proofs.iter().map(|p| u64::from(p.proof.amount)).sum())
+    }
+
+    async fn get_transaction(
+        &self,
+        transaction_id: TransactionId,
+    ) -> Result<Option<Transaction>, Error> {
+        Ok(self.store.read().transactions.get(&transaction_id).cloned())
+    }
+
+    async fn list_transactions(
+        &self,
+        mint_url: Option<MintUrl>,
+        direction: Option<TransactionDirection>,
+        unit: Option<CurrencyUnit>,
+    ) -> Result<Vec<Transaction>, Error> {
+        Ok(self
+            .store
+            .read()
+            .transactions
+            .values()
+            .filter(|tx| tx.matches_conditions(&mint_url, &direction, &unit))
+            .cloned()
+            .collect())
+    }
+
+    async fn update_proofs(
+        &self,
+        added: Vec<ProofInfo>,
+        removed_ys: Vec<PublicKey>,
+    ) -> Result<(), Error> {
+        let mut store = self.store.write();
+
+        for proof_info in added {
+            store.proofs.insert(proof_info.y, proof_info);
+        }
+
+        for y in removed_ys {
+            store.proofs.remove(&y);
+        }
+
+        Ok(())
+    }
+
+    async fn update_proofs_state(&self, ys: Vec<PublicKey>, state: State) -> Result<(), Error> {
+        let mut store = self.store.write();
+
+        for y in ys {
+            if let Some(proof) = store.proofs.get_mut(&y) {
+                proof.state = state;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn add_transaction(&self, transaction: Transaction) -> Result<(), Error> {
+        self.store
+            .write()
+            .transactions
+            .insert(transaction.id(), transaction);
+        Ok(())
+    }
+
+    async fn update_mint_url(
+        &self,
+        old_mint_url: MintUrl,
+        new_mint_url: MintUrl,
+    ) -> Result<(), Error> {
+        let mut store = self.store.write();
+
+        for proof in store.proofs.values_mut() {
+            if proof.mint_url == old_mint_url {
+                proof.mint_url = new_mint_url.clone();
+            }
+        }
+
+        let now = unix_time();
+        for quote in store.mint_quotes.values_mut() {
+            if quote.mint_url == old_mint_url && quote.expiry >= now {
+                quote.mint_url = new_mint_url.clone();
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn increment_keyset_counter(&self, keyset_id: &Id, count: u32) -> Result<u32, Error> {
+        let mut store = self.store.write();
+        let counter = store.keyset_counters.entry(*keyset_id).or_default();
+
+        *counter = counter.checked_add(count).ok_or(Error::AmountOverflow)?;
+
+        Ok(*counter)
+    }
+
+    async fn add_mint(&self, mint_url: MintUrl, mint_info: Option<MintInfo>) -> Result<(), Error> {
+        self.store.write().mints.insert(mint_url, mint_info);
+        Ok(())
+    }
+
+    async fn remove_mint(&self, mint_url: MintUrl) -> Result<(), Error> {
+        self.store.write().mints.remove(&mint_url);
+        Ok(())
+    }
+
+    async fn add_mint_keysets(
+        &self,
+        mint_url: MintUrl,
+        keysets: Vec<KeySetInfo>,
+    ) -> Result<(), Error> {
+        let mut store = self.store.write();
+
+        for keyset in keysets {
+            if !store.map_keyset_u32(keyset.id) {
+                tracing::warn!("Keyset already exists for keyset id");
+                return Err(Error::Duplicate);
+            }
+
+            match store.keysets.get_mut(&keyset.id) {
+                Some(existing) => {
+                    existing.active = keyset.active;
+                    existing.input_fee_ppk = keyset.input_fee_ppk;
+                }
+                None => {
+                    let ids = store.mint_keysets.entry(mint_url.clone()).or_default();
+                    if !ids.contains(&keyset.id) {
+                        ids.push(keyset.id);
+                    }
+                    store.keysets.insert(keyset.id, keyset);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn add_mint_quote(&self, quote: MintQuote) -> Result<(), Error> {
+        let mut store = self.store.write();
+        let mut quote_to_save = quote;
+
+        if let Some(existing) = store.mint_quotes.get(&quote_to_save.id) {
+            if existing.version != quote_to_save.version {
+                return Err(Error::ConcurrentUpdate);
+            }
+
+            quote_to_save.version = quote_to_save.version.wrapping_add(1);
+        }
+
+        store
+            .mint_quotes
+            .insert(quote_to_save.id.clone(), quote_to_save);
+        Ok(())
+    }
+
+    async fn remove_mint_quote(&self, quote_id: &str) -> Result<(), Error> {
+        self.store.write().mint_quotes.remove(quote_id);
+        Ok(())
+    }
+
+    async fn add_melt_quote(&self, quote: wallet::MeltQuote) -> Result<(), Error> {
+        let mut store = self.store.write();
+        let mut quote_to_save = quote;
+
+        if let Some(existing) = store.melt_quotes.get(&quote_to_save.id) {
+            if existing.version != quote_to_save.version {
+                return Err(Error::ConcurrentUpdate);
+            }
+
+            quote_to_save.version = quote_to_save.version.wrapping_add(1);
+        }
+
+        store
+            .melt_quotes
+            .insert(quote_to_save.id.clone(), quote_to_save);
+        Ok(())
+    }
+
+    async fn remove_melt_quote(&self, quote_id: &str) -> Result<(), Error> {
+        self.store.write().melt_quotes.remove(quote_id);
+        Ok(())
+    }
+
+    async fn add_keys(&self, keyset: KeySet) -> Result<(), Error> {
+        keyset.verify_id()?;
+
+        let mut store = self.store.write();
+
+        if store.keys.contains_key(&keyset.id) || !store.map_keyset_u32(keyset.id) {
+            tracing::warn!("Keys already exist for keyset id");
+            return Err(Error::Duplicate);
+        }
+
+        store.keys.insert(keyset.id, keyset.keys);
+
+        Ok(())
+    }
+
+    async fn remove_keys(&self, id: &Id) -> Result<(), Error> {
+        self.store.write().keys.remove(id);
+        Ok(())
+    }
+
+    async fn remove_transaction(&self, transaction_id: TransactionId) -> Result<(), Error> {
+        self.store.write().transactions.remove(&transaction_id);
+        Ok(())
+    }
+
+    async fn remove_orphaned_keysets(&self) -> Result<u64, Error> {
+        let mut store = self.store.write();
+        let mut removed = 0;
+
+        let orphaned_mints = store
+            .mint_keysets
+            .keys()
+            .filter(|mint_url| !store.mints.contains_key(*mint_url))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        for mint_url in orphaned_mints {
+            let ids = store.mint_keysets.remove(&mint_url).unwrap_or_default();
+            let (used, unused): (Vec<Id>, Vec<Id>) = ids.into_iter().partition(|id| {
+                store
+                    .proofs
+                    .values()
+                    .any(|proof| proof.proof.keyset_id == *id)
+            });
+
+            for id in unused {
+                store.keysets.remove(&id);
+                removed += 1;
+            }
+
+            if !used.is_empty() {
+                store.mint_keysets.insert(mint_url, used);
+            }
+        }
+
+        let orphaned_keys = store
+            .keys
+            .keys()
+            .filter(|id| !store.keysets.contains_key(*id))
+            .copied()
+            .collect::<Vec<_>>();
+
+        for id in orphaned_keys {
+            store.keys.remove(&id);
+            removed += 1;
+        }
+
+        Ok(removed)
+    }
+
+    async fn add_saga(&self, saga: wallet::WalletSaga) -> Result<(), Error> {
+        self.store.write().sagas.insert(saga.id, saga);
+        Ok(())
+    }
+
+    async fn get_saga(&self, id: &uuid::Uuid) -> Result<Option<wallet::WalletSaga>, Error> {
+        Ok(self.store.read().sagas.get(id).cloned())
+    }
+
+    async fn update_saga(&self, saga: wallet::WalletSaga) -> Result<bool, Error> {
+        let mut store = self.store.write();
+
+        // The caller already incremented the version, so the stored saga must hold the previous
+        // one
+        let expected_version = saga.version.saturating_sub(1);
+
+        match store.sagas.get_mut(&saga.id) {
+            Some(existing) if existing.version == expected_version => {
+                *existing = saga;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn delete_saga(&self, id: &uuid::Uuid) -> Result<(), Error> {
+        self.store.write().sagas.remove(id);
+        Ok(())
+    }
+
+    async fn get_incomplete_sagas(&self) -> Result<Vec<wallet::WalletSaga>, Error> {
+        let mut sagas = self
+            .store
+            .read()
+            .sagas
+            .values()
+            .cloned()
+            .collect::<Vec<_>>();
+
+        sagas.sort_by_key(|saga| saga.created_at);
+
+        Ok(sagas)
+    }
+
+    async fn reserve_proofs(
+        &self,
+        ys: Vec<PublicKey>,
+        operation_id: &uuid::Uuid,
+    ) -> Result<(), Error> {
+        let mut store = self.store.write();
+
+        if ys.iter().any(|y| {
+            store
+                .proofs
+                .get(y)
+                .is_none_or(|proof| proof.state != State::Unspent)
+        }) {
+            return Err(Error::ProofNotUnspent);
+        }
+
+        for y in ys {
+            if let Some(proof) = store.proofs.get_mut(&y) {
+                proof.state = State::Reserved;
+                proof.used_by_operation = Some(*operation_id);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn release_proofs(&self, operation_id: &uuid::Uuid) -> Result<(), Error> {
+        for proof in self.store.write().proofs.values_mut() {
+            if proof.used_by_operation == Some(*operation_id) {
+                proof.state = State::Unspent;
+                proof.used_by_operation = None;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn get_reserved_proofs(
+        &self,
+        operation_id: &uuid::Uuid,
+    ) -> Result<Vec<ProofInfo>, Error> {
+        Ok(self
+            .store
+            .read()
+            .proofs
+            .values()
+            .filter(|proof| proof.used_by_operation == Some(*operation_id))
+            .cloned()
+            .collect())
+    }
+
+    async fn reserve_melt_quote(
+        &self,
+        quote_id: &str,
+        operation_id: &uuid::Uuid,
+    ) -> Result<(), Error> {
+        let mut store = self.store.write();
+        let quote = store
+            .melt_quotes
+            .get_mut(quote_id)
+            .ok_or(Error::UnknownQuote)?;
+
+        if quote.used_by_operation.is_some() {
+            return Err(Error::QuoteAlreadyInUse);
+        }
+
+        quote.used_by_operation = Some(operation_id.to_string());
+
+        Ok(())
+    }
+
+    async fn release_melt_quote(&self, operation_id: &uuid::Uuid) -> Result<(), Error> {
+        let operation_id = operation_id.to_string();
+
+        for quote in self.store.write().melt_quotes.values_mut() {
+            if quote.used_by_operation.as_deref() == Some(&operation_id) {
+                quote.used_by_operation = None;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn reserve_mint_quote(
+        &self,
+        quote_id: &str,
+        operation_id: &uuid::Uuid,
+    ) -> Result<(), Error> {
+        let mut store = self.store.write();
+        let quote = store
+            .mint_quotes
+            .get_mut(quote_id)
+            .ok_or(Error::UnknownQuote)?;
+
+        if quote.used_by_operation.is_some() {
+            return Err(Error::QuoteAlreadyInUse);
+        }
+
+        quote.used_by_operation = Some(operation_id.to_string());
+
+        Ok(())
+    }
+
+    async fn release_mint_quote(&self, operation_id: &uuid::Uuid) -> Result<(), Error> {
+        let operation_id = operation_id.to_string();
+
+        for quote in self.store.write().mint_quotes.values_mut() {
+            if quote.used_by_operation.as_deref() == Some(&operation_id) {
+                quote.used_by_operation = None;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn kv_read(
+        &self,
+        primary_namespace: &str,
+        secondary_namespace: &str,
+        key: &str,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        validate_kvstore_params(primary_namespace, secondary_namespace, Some(key))?;
+
+        Ok(self
+            .store
+            .read()
+            .kv_store
+            .get(&(
+                primary_namespace.to_owned(),
+                secondary_namespace.to_owned(),
+                key.to_owned(),
+            ))
+            .cloned())
+    }
+
+    async fn kv_list(
+        &self,
+        primary_namespace: &str,
+        secondary_namespace: &str,
+    ) -> Result<Vec<String>, Error> {
+        validate_kvstore_params(primary_namespace, secondary_namespace, None)?;
+
+        Ok(self
+            .store
+            .read()
+            .kv_store
+            .keys()
+            .filter(|(p, s, _)| p == primary_namespace && s == secondary_namespace)
+            .map(|(_, _, k)| k.clone())
+            .collect())
+    }
+
+    async fn kv_write(
+        &self,
+        primary_namespace: &str,
+        secondary_namespace: &str,
+        key: &str,
+        value: &[u8],
+    ) -> Result<(), Error> {
+        validate_kvstore_params(primary_namespace, secondary_namespace, Some(key))?;
+
+        self.store.write().kv_store.insert(
+            (
+                primary_namespace.to_owned(),
+                secondary_namespace.to_owned(),
+                key.to_owned(),
+            ),
+            value.to_vec(),
+        );
+        Ok(())
+    }
+
+    async fn kv_remove(
+        &self,
+        primary_namespace: &str,
+        secondary_namespace: &str,
+        key: &str,
+    ) -> Result<(), Error> {
+        validate_kvstore_params(primary_namespace, secondary_namespace, Some(key))?;
+
+        self.store.write().kv_store.remove(&(
+            primary_namespace.to_owned(),
+            secondary_namespace.to_owned(),
+            key.to_owned(),
+        ));
+        Ok(())
+    }
+
+    async fn add_p2pk_key(
+        &self,
+        pubkey: &PublicKey,
+        derivation_path: DerivationPath,
+        derivation_index: u32,
+    ) -> Result<(), Error> {
+        self.store.write().p2pk_keys.insert(
+            *pubkey,
+            wallet::P2PKSigningKey {
+                pubkey: *pubkey,
+                derivation_path,
+                derivation_index,
+                created_time: unix_time(),
+            },
+        );
+        Ok(())
+    }
+
+    async fn get_p2pk_key(
+        &self,
+        pubkey: &PublicKey,
+    ) -> Result<Option<wallet::P2PKSigningKey>, Error> {
+        Ok(self.store.read().p2pk_keys.get(pubkey).cloned())
+    }
+
+    async fn list_p2pk_keys(&self) -> Result<Vec<wallet::P2PKSigningKey>, Error> {
+        Ok(self.store.read().p2pk_keys.values().cloned().collect())
+    }
+
+    async fn latest_p2pk(&self) -> Result<Option<wallet::P2PKSigningKey>, Error> {
+        Ok(self
+            .store
+            .read()
+            .p2pk_keys
+            .values()
+            .max_by_key(|key| key.derivation_index)
+            .cloned())
+    }
+}
+
+#[cfg(all(test, feature = "test"))]
+mod tests {
+    extern crate self as cdk_common;
+
+    use super::WalletMemoryDatabase;
+    use crate::wallet_db_test;
+
+    async fn provide_db(_test_id: String) -> WalletMemoryDatabase {
+        WalletMemoryDatabase::new()
+    }
+
+    wallet_db_test!(provide_db);
+}
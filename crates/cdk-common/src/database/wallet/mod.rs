@@ -16,9 +16,12 @@ use crate::wallet::{
     self, MintQuote as WalletMintQuote, ProofInfo, Transaction, TransactionDirection, TransactionId,
 };
 
+mod memory;
 #[cfg(feature = "test")]
 pub mod test;
 
+pub use memory::WalletMemoryDatabase;
+
 /// Wallet Database trait
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
@@ -149,6 +152,20 @@ where
     /// Remove transaction from storage
     async fn remove_transaction(&self, transaction_id: TransactionId) -> Result<(), Err>;
 
+    /// Remove keysets of mints that are no longer stored and keys of keysets that are no longer
+    /// stored. Keysets still referenced by proofs are kept.
+    ///
+    /// Keyset counters are never removed, so re-adding a mint cannot reuse NUT-13 derivation
+    /// indexes. Returns the number of removed keysets and keys.
+    async fn remove_orphaned_keysets(&self) -> Result<u64, Err> {
+        Ok(0)
+    }
+
+    /// Reclaim storage space freed by removed records
+    async fn vacuum(&self) -> Result<(), Err> {
+        Ok(())
+    }
+
     /// Add a wallet saga to storage.
     ///
     /// The saga should be created with `WalletSaga::new()` which initializes
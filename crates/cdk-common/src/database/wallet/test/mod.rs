@@ -84,6 +84,8 @@ fn test_keyset_info(keyset_id: Id, _mint_url: &MintUrl) -> KeySetInfo {
         active: true,
         input_fee_ppk: 0,
         final_expiry: None,
+        rotation_time: None,
+        retirement_time: None,
     }
 }
 
@@ -206,6 +206,9 @@ pub enum Error {
     /// Lightning Address request error
     #[error("Failed to request invoice from Lightning address service: {0}")]
     LightningAddressRequest(String),
+    /// Submarine swap provider error
+    #[error("Swap provider error: {0}")]
+    SwapProvider(String),
 
     /// Internal Error - Send error
     #[error("Internal send error: {0}")]
@@ -234,6 +237,9 @@ pub enum Error {
     /// Quote is pending
     #[error("Quote pending")]
     PendingQuote,
+    /// Another unpaid quote pays the same request
+    #[error("Another unpaid quote exists for this payment request")]
+    RequestHasUnpaidQuote,
     /// Timed out waiting for a pending melt to complete.
     ///
     /// If the most recent backend status check failed, its error message is
@@ -256,6 +262,9 @@ pub enum Error {
     /// Melting is disabled
     #[error("Melting is disabled")]
     MeltingDisabled,
+    /// Optional NUT is disabled on this mint
+    #[error("NUT-{0:02} is disabled")]
+    NutDisabled(u8),
     /// Unknown Keyset
     #[error("Unknown Keyset")]
     UnknownKeySet,
@@ -268,6 +277,31 @@ pub enum Error {
     /// Keyset has expired
     #[error("Keyset has expired")]
     ExpiredKeyset,
+    /// Stored keyset cannot be re-derived from the configured seed
+    #[error("Keyset `{stored}` does not match the configured seed (derived `{derived}`)")]
+    KeysetSeedMismatch {
+        /// Keyset id persisted in the database
+        stored: Id,
+        /// Keyset id derived from the configured seed
+        derived: Id,
+    },
+    /// Signatory is not configured the way the mint expects
+    #[error("Signatory config for unit `{unit}` does not match the mint: {reason}")]
+    SignatoryConfigMismatch {
+        /// Unit whose settings differ
+        unit: CurrencyUnit,
+        /// What differs
+        reason: String,
+    },
+    /// Signatory refused to sign more than one of its signing limits allows
+    #[error("Signing limit of the signatory exceeded")]
+    SigningLimitExceeded,
+    /// Signatory could not be reached
+    #[error("Signatory unavailable: {0}")]
+    SignatoryUnavailable(String),
+    /// Signatory has too many requests queued
+    #[error("Signatory overloaded")]
+    Overloaded,
     /// Transaction unbalanced
     #[error("Inputs: `{0}`, Outputs: `{1}`, Expected Fee: `{2}`")]
     TransactionUnbalanced(u64, u64, u64),
@@ -293,6 +327,40 @@ pub enum Error {
         /// Maximum allowed outputs
         max: usize,
     },
+    /// Output amount above the max denomination of its unit
+    #[error("Output of {amount} exceeds the max denomination {max}")]
+    MaxDenominationExceeded {
+        /// Amount of the output
+        amount: Amount,
+        /// Largest amount issued for the unit
+        max: Amount,
+    },
+    /// Outstanding ecash of the active keyset of a unit at its issuance cap
+    #[error("Issuance of keyset {keyset_id} reached its cap: {outstanding} outstanding of {cap}")]
+    IssuanceCapReached {
+        /// Active keyset of the unit
+        keyset_id: Id,
+        /// Amount issued and not redeemed with the keyset
+        outstanding: Amount,
+        /// Largest amount allowed outstanding
+        cap: Amount,
+    },
+    /// Payment request for another bitcoin network than the one of the mint
+    #[error("Payment request is for {found}, the mint runs on {expected}")]
+    NetworkMismatch {
+        /// Network of the mint
+        expected: bitcoin::Network,
+        /// Network of the payment request
+        found: bitcoin::Network,
+    },
+    /// Melt above the outbound liquidity of the payment backend
+    #[error("Melt of {amount} exceeds the outbound liquidity {available}")]
+    InsufficientLiquidity {
+        /// Amount of the melt, fee reserve included
+        amount: Amount,
+        /// Amount the backend can send
+        available: Amount,
+    },
     /// Duplicate quote IDs provided in a batch request (NUT-29)
     #[error("Duplicate quote IDs")]
     DuplicateQuoteIds,
@@ -369,6 +437,14 @@ pub enum Error {
     /// Max Fee Ecxeded
     #[error("Max fee exceeded")]
     MaxFeeExceeded,
+    /// Fee to claim received proofs above the receive threshold of the wallet
+    #[error("Receiving {amount} costs a fee of {fee}, above the receive fee threshold")]
+    ReceiveFeeTooHigh {
+        /// Amount of the received proofs
+        amount: Amount,
+        /// Fee to swap the received proofs
+        fee: Amount,
+    },
     /// Invalid NUT-13 restore options
     #[error("Invalid NUT-13 restore options: `{field}` {reason}")]
     InvalidNut13Options {
@@ -419,6 +495,14 @@ pub enum Error {
     /// Insufficient Funds
     #[error("Insufficient funds")]
     InsufficientFunds,
+    /// Token does not fit the requested size budget
+    #[error("Token too large: smallest achievable size is {size} bytes, max {max}")]
+    TokenTooLarge {
+        /// Smallest size the token can be sent with
+        size: usize,
+        /// Maximum size requested
+        max: usize,
+    },
     /// Unexpected proof state
     #[error("Unexpected proof state")]
     UnexpectedProofState,
@@ -585,6 +669,7 @@ mod tests {
         assert!(Error::AmountOverflow.is_definitive_failure());
         assert!(Error::TokenAlreadySpent.is_definitive_failure());
         assert!(Error::MintingDisabled.is_definitive_failure());
+        assert!(Error::NutDisabled(9).is_definitive_failure());
         assert!(Error::MaxInputsExceeded { actual: 2, max: 1 }.is_definitive_failure());
         assert!(Error::MaxOutputsExceeded { actual: 2, max: 1 }.is_definitive_failure());
 
@@ -645,6 +730,21 @@ mod tests {
         ));
         assert!(max_outputs.is_definitive_failure());
     }
+
+    #[test]
+    fn test_proof_content_too_large_has_its_own_code() {
+        let response = ErrorResponse::from(Error::ProofContentTooLarge {
+            actual: 2048,
+            max: 1024,
+        });
+        assert_eq!(response.code, ErrorCode::ProofContentTooLarge);
+        assert_eq!(response.code.to_code(), 11018);
+        assert_eq!(ErrorCode::from_code(11018), ErrorCode::ProofContentTooLarge);
+
+        let err = Error::from(response);
+        assert!(matches!(err, Error::ProofContentTooLarge { .. }));
+        assert!(err.is_definitive_failure());
+    }
 }
 
 impl Error {
@@ -681,9 +781,11 @@ impl Error {
             | Self::ExpiredQuote(_, _)
             | Self::AmountOutofLimitRange(_, _, _)
             | Self::UnpaidQuote
+            | Self::RequestHasUnpaidQuote
             | Self::IssuedQuote
             | Self::PaidQuote
             | Self::MeltingDisabled
+            | Self::NutDisabled(_)
             | Self::UnknownKeySet
             | Self::BlindedMessageAlreadySigned
             | Self::InactiveKeyset
@@ -693,8 +795,13 @@ impl Error {
             | Self::DuplicateOutputs
             | Self::MaxInputsExceeded { .. }
             | Self::MaxOutputsExceeded { .. }
+            | Self::MaxDenominationExceeded { .. }
+            | Self::InsufficientLiquidity { .. }
+            | Self::IssuanceCapReached { .. }
+            | Self::NetworkMismatch { .. }
             | Self::DuplicateQuoteIds
             | Self::BatchSizeExceeded { .. }
+            | Self::ProofContentTooLarge { .. }
             | Self::MultipleUnits
             | Self::UnitMismatch
             | Self::SigAllUsedInMelt
@@ -705,6 +812,7 @@ impl Error {
             | Self::InvalidSpendConditions(_)
             | Self::IncorrectWallet(_)
             | Self::MaxFeeExceeded
+            | Self::ReceiveFeeTooHigh { .. }
             | Self::InvalidNut13Options { .. }
             | Self::DleqProofNotProvided
             | Self::IncorrectMint
@@ -712,6 +820,7 @@ impl Error {
             | Self::PreimageNotProvided
             | Self::UnknownMint { .. }
             | Self::UnexpectedProofState
+            | Self::TokenTooLarge { .. }
             | Self::NoActiveKeyset
             | Self::IncorrectQuoteAmount
             | Self::InvoiceDescriptionUnsupported
@@ -754,7 +863,8 @@ impl Error {
             | Self::RecvError(_)
             | Self::TransferTimeout { .. }
             | Self::Bip353Resolve(_)
-            | Self::LightningAddressRequest(_) => false,
+            | Self::LightningAddressRequest(_)
+            | Self::SwapProvider(_) => false,
 
             // Network/IO/Parsing Errors (Usually ambiguous as they could happen reading response)
             Self::HttpError(None, _) // No status code means network error
@@ -925,6 +1035,10 @@ impl From<Error> for ErrorResponse {
                 code: ErrorCode::QuotePending,
                 detail: err.to_string(),
             },
+            Error::RequestHasUnpaidQuote => ErrorResponse {
+                code: ErrorCode::QuotePending,
+                detail: err.to_string(),
+            },
             Error::PendingMeltTimeout { .. } => ErrorResponse {
                 code: ErrorCode::QuotePending,
                 detail: err.to_string(),
@@ -1135,6 +1249,18 @@ impl From<Error> for ErrorResponse {
                 code: ErrorCode::MaxOutputsExceeded,
                 detail: err.to_string()
             },
+            Error::MaxDenominationExceeded { .. } => ErrorResponse {
+                code: ErrorCode::AmountOutofLimitRange,
+                detail: err.to_string(),
+            },
+            Error::IssuanceCapReached { .. } => ErrorResponse {
+                code: ErrorCode::MintingDisabled,
+                detail: err.to_string(),
+            },
+            Error::InsufficientLiquidity { .. } => ErrorResponse {
+                code: ErrorCode::AmountOutofLimitRange,
+                detail: err.to_string(),
+            },
             Error::DuplicateQuoteIds => ErrorResponse {
                 code: ErrorCode::DuplicateQuoteIds,
                 detail: err.to_string(),
@@ -1143,6 +1269,10 @@ impl From<Error> for ErrorResponse {
                 code: ErrorCode::BatchSizeExceeded,
                 detail: err.to_string(),
             },
+            Error::ProofContentTooLarge { .. } => ErrorResponse {
+                code: ErrorCode::ProofContentTooLarge,
+                detail: err.to_string(),
+            },
             // Fallback for any remaining errors - use Unknown(99999) instead of TokenNotVerified
             _ => ErrorResponse {
                 code: ErrorCode::Unknown(50000),
@@ -1152,6 +1282,13 @@ impl From<Error> for ErrorResponse {
     }
 }
 
+#[cfg(feature = "mint")]
+impl From<crate::state::Error> for Error {
+    fn from(state: crate::state::Error) -> Self {
+        crate::database::Error::InvalidStateTransition(state).into()
+    }
+}
+
 #[cfg(feature = "mint")]
 impl From<crate::database::Error> for Error {
     fn from(db_error: crate::database::Error) -> Self {
@@ -1211,6 +1348,7 @@ impl From<ErrorResponse> for Error {
             }
             ErrorCode::DuplicateQuoteIds => Self::DuplicateQuoteIds,
             ErrorCode::BatchSizeExceeded => Self::BatchSizeExceeded { actual: 0, max: 0 },
+            ErrorCode::ProofContentTooLarge => Self::ProofContentTooLarge { actual: 0, max: 0 },
             ErrorCode::MultipleUnits => Self::MultipleUnits,
             ErrorCode::UnitMismatch => Self::UnitMismatch,
             ErrorCode::AmountlessInvoiceNotSupported => Self::AmountLessNotAllowed,
@@ -1285,6 +1423,8 @@ pub enum ErrorCode {
     DuplicateQuoteIds,
     /// Batch size exceeds mint limit (11017)
     BatchSizeExceeded,
+    /// Secret or witness of an input exceeds the mint limit (11018)
+    ProofContentTooLarge,
     // 12xxx - Keyset errors
     /// Keyset is not known (12001)
     KeysetNotFound,
@@ -1360,6 +1500,7 @@ impl ErrorCode {
             11015 => Self::MaxOutputsExceeded,
             11016 => Self::DuplicateQuoteIds,
             11017 => Self::BatchSizeExceeded,
+            11018 => Self::ProofContentTooLarge,
             // 12xxx - Keyset errors
             12001 => Self::KeysetNotFound,
             12002 => Self::KeysetInactive,
@@ -1409,6 +1550,7 @@ impl ErrorCode {
             Self::MaxOutputsExceeded => 11015,
             Self::DuplicateQuoteIds => 11016,
             Self::BatchSizeExceeded => 11017,
+            Self::ProofContentTooLarge => 11018,
             // 12xxx - Keyset errors
             Self::KeysetNotFound => 12001,
             Self::KeysetInactive => 12002,
@@ -0,0 +1,357 @@
+//! Display and parsing of amounts
+//!
+//! Amounts are integers in the smallest denomination of their unit: sats, msats or cents. These
+//! helpers turn them into the strings shown to users and back, following a [`Locale`] for the
+//! decimal and grouping separators, so every app built on the CDK formats amounts the same way.
+//!
+//! Parsing and unit conversions are lossless: a value that cannot be represented exactly in the
+//! target unit is refused instead of being rounded.
+
+use crate::amount::MSAT_IN_SAT;
+use crate::nuts::CurrencyUnit;
+use crate::Amount;
+
+/// Formatting Error
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Not a number
+    #[error("Invalid amount: `{0}`")]
+    InvalidAmount(String),
+    /// More decimals than the unit can hold
+    #[error("Amount in {0} cannot have more than {1} decimals")]
+    TooManyDecimals(CurrencyUnit, u32),
+    /// Amount does not fit in a u64
+    #[error("Amount overflow")]
+    AmountOverflow,
+    /// No conversion between the units
+    #[error("Cannot convert {0} to {1}")]
+    CannotConvertUnits(CurrencyUnit, CurrencyUnit),
+    /// Conversion would round the amount
+    #[error("{0} {1} cannot be converted to {2} without losing precision")]
+    InexactConversion(u64, CurrencyUnit, CurrencyUnit),
+}
+
+/// Separators and symbol placement used to format amounts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Locale {
+    /// Separator between the integer and the fractional part
+    pub decimal_separator: char,
+    /// Separator between groups of thousands, if any
+    pub group_separator: Option<char>,
+    /// Whether the currency symbol goes before the number, as in `$1.00`, or after it separated by
+    /// a no-break space, as in `1,00 €`
+    pub symbol_first: bool,
+}
+
+impl Locale {
+    /// English, `$1,234.56`
+    pub const EN: Self = Self {
+        decimal_separator: '.',
+        group_separator: Some(','),
+        symbol_first: true,
+    };
+
+    /// German, `1.234,56 €`
+    pub const DE: Self = Self {
+        decimal_separator: ',',
+        group_separator: Some('.'),
+        symbol_first: false,
+    };
+
+    /// French, `1 234,56 €` with a narrow no-break space between groups
+    pub const FR: Self = Self {
+        decimal_separator: ',',
+        group_separator: Some('\u{202f}'),
+        symbol_first: false,
+    };
+
+    /// Create new [`Locale`]
+    pub fn new(decimal_separator: char, group_separator: Option<char>, symbol_first: bool) -> Self {
+        Self {
+            decimal_separator,
+            group_separator,
+            symbol_first,
+        }
+    }
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Self::EN
+    }
+}
+
+/// Number of decimals shown for amounts in `unit`
+///
+/// Fiat units count cents, every other unit is shown as an integer.
+pub fn decimals(unit: &CurrencyUnit) -> u32 {
+    match unit {
+        CurrencyUnit::Usd | CurrencyUnit::Eur => 2,
+        _ => 0,
+    }
+}
+
+/// Symbol of the currency, for the units that have one
+fn symbol(unit: &CurrencyUnit) -> Option<&'static str> {
+    match unit {
+        CurrencyUnit::Usd => Some("$"),
+        CurrencyUnit::Eur => Some("€"),
+        _ => None,
+    }
+}
+
+/// Format `value`, in the smallest denomination, as a number with `decimals` decimals
+fn format_number(value: u64, decimals: u32, locale: &Locale) -> String {
+    let scale = 10u64.pow(decimals);
+    let integer = (value / scale).to_string();
+
+    let mut formatted = String::with_capacity(integer.len() * 2);
+    for (idx, digit) in integer.chars().enumerate() {
+        if idx > 0 && (integer.len() - idx) % 3 == 0 {
+            if let Some(separator) = locale.group_separator {
+                formatted.push(separator);
+            }
+        }
+        formatted.push(digit);
+    }
+
+    if decimals > 0 {
+        formatted.push(locale.decimal_separator);
+        formatted.push_str(&format!(
+            "{:0width$}",
+            value % scale,
+            width = decimals as usize
+        ));
+    }
+
+    formatted
+}
+
+/// Format `amount` for display
+///
+/// Fiat amounts are shown with their symbol and cents, as in `$1,234.56` or `1.234,56 €`. Other
+/// units are shown as an integer followed by the unit, as in `1,234 sat`.
+///
+/// ```
+/// # use cdk_common::format::{format_amount, Locale};
+/// # use cdk_common::{Amount, CurrencyUnit};
+/// let amount = Amount::new(123456, CurrencyUnit::Eur);
+/// assert_eq!(format_amount(&amount, &Locale::DE), "1.234,56\u{a0}€");
+/// ```
+pub fn format_amount(amount: &Amount<CurrencyUnit>, locale: &Locale) -> String {
+    let unit = amount.unit();
+    let number = format_number(amount.value(), decimals(unit), locale);
+
+    match symbol(unit) {
+        Some(symbol) if locale.symbol_first => format!("{symbol}{number}"),
+        Some(symbol) => format!("{number}\u{a0}{symbol}"),
+        None => format!("{number} {unit}"),
+    }
+}
+
+/// Parse an amount typed by a user into the smallest denomination of `unit`
+///
+/// Accepts the output of [`format_amount`] as well as a bare number. Group separators and
+/// whitespace are ignored. Fails with [`Error::TooManyDecimals`] rather than rounding if the input
+/// is more precise than the unit, so `0.001` USD is refused.
+pub fn parse_amount(
+    input: &str,
+    unit: &CurrencyUnit,
+    locale: &Locale,
+) -> Result<Amount<CurrencyUnit>, Error> {
+    let invalid = || Error::InvalidAmount(input.to_owned());
+
+    let mut number = input.trim();
+    let unit_name = unit.to_string();
+    for suffix in [symbol(unit).unwrap_or_default(), unit_name.as_str()] {
+        if suffix.is_empty() {
+            continue;
+        }
+        if let Some(stripped) = number.strip_prefix(suffix) {
+            number = stripped;
+        } else if let Some(stripped) = number
+            .strip_suffix(suffix)
+            .or_else(|| number.strip_suffix(suffix.to_uppercase().as_str()))
+        {
+            number = stripped;
+        }
+    }
+
+    let number: String = number
+        .chars()
+        .filter(|c| !c.is_whitespace() && Some(*c) != locale.group_separator)
+        .collect();
+
+    let (integer, fraction) = match number.split_once(locale.decimal_separator) {
+        Some((integer, fraction)) => (integer, fraction),
+        None => (number.as_str(), ""),
+    };
+
+    if integer.is_empty() && fraction.is_empty() {
+        return Err(invalid());
+    }
+    if !integer
+        .chars()
+        .chain(fraction.chars())
+        .all(|c| c.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+
+    let decimals = decimals(unit);
+    // Trailing zeros carry no precision, `1.50` is the same as `1.5`
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.len() > decimals as usize {
+        return Err(Error::TooManyDecimals(unit.clone(), decimals));
+    }
+
+    let integer: u64 = match integer {
+        "" => 0,
+        integer => integer.parse().map_err(|_| Error::AmountOverflow)?,
+    };
+    let fraction: u64 = format!("{:0<width$}", fraction, width = decimals as usize)
+        .parse()
+        .unwrap_or_default();
+
+    let value = integer
+        .checked_mul(10u64.pow(decimals))
+        .and_then(|value| value.checked_add(fraction))
+        .ok_or(Error::AmountOverflow)?;
+
+    Ok(Amount::new(value, unit.clone()))
+}
+
+/// Convert `amount` to `target` without losing precision
+///
+/// Unlike [`Amount::convert_to`], which truncates msats to whole sats, this fails with
+/// [`Error::InexactConversion`] when the amount is not a multiple of the target unit, so the
+/// result always converts back to the same amount.
+pub fn convert_lossless(
+    amount: &Amount<CurrencyUnit>,
+    target: &CurrencyUnit,
+) -> Result<Amount<CurrencyUnit>, Error> {
+    let unit = amount.unit();
+    let value = match (unit, target) {
+        (unit, target) if unit == target => amount.value(),
+        (CurrencyUnit::Sat, CurrencyUnit::Msat) => amount
+            .value()
+            .checked_mul(MSAT_IN_SAT)
+            .ok_or(Error::AmountOverflow)?,
+        (CurrencyUnit::Msat, CurrencyUnit::Sat) => {
+            if amount.value() % MSAT_IN_SAT != 0 {
+                return Err(Error::InexactConversion(
+                    amount.value(),
+                    unit.clone(),
+                    target.clone(),
+                ));
+            }
+            amount.value() / MSAT_IN_SAT
+        }
+        _ => return Err(Error::CannotConvertUnits(unit.clone(), target.clone())),
+    };
+
+    Ok(Amount::new(value, target.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_amounts_per_locale() {
+        let usd = Amount::new(123456789, CurrencyUnit::Usd);
+        assert_eq!(format_amount(&usd, &Locale::EN), "$1,234,567.89");
+        assert_eq!(format_amount(&usd, &Locale::DE), "1.234.567,89\u{a0}$");
+        assert_eq!(
+            format_amount(&usd, &Locale::FR),
+            "1\u{202f}234\u{202f}567,89\u{a0}$"
+        );
+
+        let cents = Amount::new(5, CurrencyUnit::Eur);
+        assert_eq!(format_amount(&cents, &Locale::EN), "€0.05");
+
+        let sat = Amount::new(1000, CurrencyUnit::Sat);
+        assert_eq!(format_amount(&sat, &Locale::EN), "1,000 sat");
+        assert_eq!(format_amount(&sat, &Locale::DE), "1.000 sat");
+
+        let msat = Amount::new(999, CurrencyUnit::Msat);
+        assert_eq!(format_amount(&msat, &Locale::EN), "999 msat");
+
+        let ungrouped = Locale::new('.', None, true);
+        assert_eq!(format_amount(&usd, &ungrouped), "$1234567.89");
+    }
+
+    #[test]
+    fn parsing_round_trips_formatting() {
+        for locale in [Locale::EN, Locale::DE, Locale::FR] {
+            for unit in [CurrencyUnit::Sat, CurrencyUnit::Msat, CurrencyUnit::Usd] {
+                for value in [0, 7, 1000, 123456789, u64::MAX] {
+                    let amount = Amount::new(value, unit.clone());
+                    let formatted = format_amount(&amount, &locale);
+                    assert_eq!(parse_amount(&formatted, &unit, &locale), Ok(amount));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn parsing_refuses_lossy_input() {
+        let usd = CurrencyUnit::Usd;
+        assert_eq!(
+            parse_amount("1.5", &usd, &Locale::EN).map(|a| a.value()),
+            Ok(150)
+        );
+        assert_eq!(
+            parse_amount("1.500", &usd, &Locale::EN).map(|a| a.value()),
+            Ok(150)
+        );
+        assert_eq!(
+            parse_amount("1.501", &usd, &Locale::EN),
+            Err(Error::TooManyDecimals(usd, 2))
+        );
+        assert_eq!(
+            parse_amount("1.5", &CurrencyUnit::Sat, &Locale::EN),
+            Err(Error::TooManyDecimals(CurrencyUnit::Sat, 0))
+        );
+        assert!(matches!(
+            parse_amount("1,2x", &CurrencyUnit::Sat, &Locale::EN),
+            Err(Error::InvalidAmount(_))
+        ));
+        assert_eq!(
+            parse_amount("184467440737095516.16", &CurrencyUnit::Eur, &Locale::EN),
+            Err(Error::AmountOverflow)
+        );
+    }
+
+    #[test]
+    fn lossless_conversion() {
+        let msat = Amount::new(21_000, CurrencyUnit::Msat);
+        let sat = convert_lossless(&msat, &CurrencyUnit::Sat).unwrap();
+        assert_eq!(sat, Amount::new(21, CurrencyUnit::Sat));
+        assert_eq!(convert_lossless(&sat, &CurrencyUnit::Msat).unwrap(), msat);
+
+        let msat = Amount::new(21_001, CurrencyUnit::Msat);
+        assert_eq!(
+            convert_lossless(&msat, &CurrencyUnit::Sat),
+            Err(Error::InexactConversion(
+                21_001,
+                CurrencyUnit::Msat,
+                CurrencyUnit::Sat
+            ))
+        );
+        assert_eq!(
+            convert_lossless(
+                &Amount::new(u64::MAX, CurrencyUnit::Sat),
+                &CurrencyUnit::Msat
+            ),
+            Err(Error::AmountOverflow)
+        );
+        assert_eq!(
+            convert_lossless(&msat, &CurrencyUnit::Usd),
+            Err(Error::CannotConvertUnits(
+                CurrencyUnit::Msat,
+                CurrencyUnit::Usd
+            ))
+        );
+    }
+}
@@ -0,0 +1,165 @@
+//! Signed keyset exports
+//!
+//! A keyset export is a snapshot of every keyset the mint knows about, including the public keys
+//! and the metadata needed to re-derive them. The snapshot is signed by the signatory of the mint,
+//! with the key that signs its keyset rotation log, so third parties can archive it and later
+//! verify historical key material.
+
+use std::str::FromStr;
+
+use bitcoin::bip32::DerivationPath;
+use bitcoin::secp256k1::schnorr::Signature;
+use serde::{Deserialize, Serialize};
+
+use crate::common::IssuerVersion;
+use crate::error::Error;
+use crate::nuts::{KeySet, PublicKey, SecretKey};
+use crate::util::unix_time;
+
+/// Version of the keyset export format
+pub const KEYSET_EXPORT_VERSION: u32 = 2;
+
+/// A keyset with its derivation metadata
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportedKeyset {
+    /// Keyset id, unit, state, fee, expiry and public keys
+    pub keyset: KeySet,
+    /// Supported amounts
+    pub amounts: Vec<u64>,
+    /// Derivation path of the keyset keys
+    pub derivation_path: DerivationPath,
+    /// Derivation path index of the keyset, if it was derived at an index
+    pub derivation_path_index: Option<u32>,
+    /// Version of the software that created the keyset
+    pub issuer_version: Option<IssuerVersion>,
+}
+
+/// Unsigned snapshot of the mint keysets
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeysetExport {
+    /// Export format version
+    pub version: u32,
+    /// Unix timestamp of the export
+    pub created_at: u64,
+    /// Exported keysets
+    pub keysets: Vec<ExportedKeyset>,
+}
+
+impl KeysetExport {
+    /// Create new [`KeysetExport`] timestamped now
+    pub fn new(keysets: Vec<ExportedKeyset>) -> Self {
+        Self {
+            version: KEYSET_EXPORT_VERSION,
+            created_at: unix_time(),
+            keysets,
+        }
+    }
+
+    /// Message that is signed by the signatory
+    pub fn msg_to_sign(&self) -> Result<Vec<u8>, Error> {
+        Ok(serde_json::to_vec(self)?)
+    }
+
+    /// Sign the export with `signing_key`
+    pub fn sign(self, signing_key: &SecretKey) -> Result<SignedKeysetExport, Error> {
+        let signature = signing_key.sign(&self.msg_to_sign()?)?;
+
+        Ok(SignedKeysetExport {
+            export: self,
+            signer: signing_key.public_key(),
+            signature: signature.to_string(),
+        })
+    }
+}
+
+/// Keyset export signed by the signatory of the mint
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedKeysetExport {
+    /// Signed content
+    pub export: KeysetExport,
+    /// Public key that signed the export, the signer of the keyset rotation log
+    pub signer: PublicKey,
+    /// Hex encoded schnorr signature over the serialized export
+    pub signature: String,
+}
+
+impl SignedKeysetExport {
+    /// Verify the signature and that every keyset id matches its public keys
+    pub fn verify(&self) -> Result<(), Error> {
+        let signature =
+            Signature::from_str(&self.signature).map_err(|_| Error::SignatureMissingOrInvalid)?;
+
+        self.signer
+            .verify(&self.export.msg_to_sign()?, &signature)
+            .map_err(|_| Error::SignatureMissingOrInvalid)?;
+
+        for exported in &self.export.keysets {
+            exported.keyset.verify_id()?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::nuts::{CurrencyUnit, Id, Keys};
+    use crate::Amount;
+
+    fn exported_keyset() -> ExportedKeyset {
+        let keys = Keys::new(
+            (0..4)
+                .map(|i| (Amount::from(1 << i), SecretKey::generate().public_key()))
+                .collect::<BTreeMap<_, _>>(),
+        );
+
+        ExportedKeyset {
+            keyset: KeySet {
+                id: Id::v1_from_keys(&keys),
+                unit: CurrencyUnit::Sat,
+                active: Some(true),
+                keys,
+                input_fee_ppk: 0,
+                final_expiry: None,
+            },
+            amounts: vec![1, 2, 4, 8],
+            derivation_path: DerivationPath::from_str("m/129372'/0'/0'").unwrap(),
+            derivation_path_index: Some(0),
+            issuer_version: None,
+        }
+    }
+
+    #[test]
+    fn signed_export_round_trip() {
+        let signing_key = SecretKey::generate();
+        let signed = KeysetExport::new(vec![exported_keyset()])
+            .sign(&signing_key)
+            .unwrap();
+
+        let json = serde_json::to_string(&signed).unwrap();
+        let decoded: SignedKeysetExport = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(decoded, signed);
+        decoded.verify().unwrap();
+    }
+
+    #[test]
+    fn tampered_export_fails_verification() {
+        let signing_key = SecretKey::generate();
+        let mut signed = KeysetExport::new(vec![exported_keyset()])
+            .sign(&signing_key)
+            .unwrap();
+
+        signed.export.keysets[0].keyset.input_fee_ppk = 100;
+        assert!(signed.verify().is_err());
+
+        let mut signed = KeysetExport::new(vec![exported_keyset()])
+            .sign(&signing_key)
+            .unwrap();
+        signed.signer = SecretKey::generate().public_key();
+        assert!(signed.verify().is_err());
+    }
+}
@@ -21,9 +21,12 @@ pub const PAYMENT_PROCESSOR_PROTOCOL_VERSION: &str = "3.0.0";
 #[cfg(feature = "grpc")]
 pub mod grpc;
 
+pub mod bearer;
 pub mod common;
 pub mod database;
 pub mod error;
+pub mod format;
+pub mod keyset_export;
 pub mod melt;
 #[cfg(feature = "mint")]
 pub mod mint;
@@ -31,6 +34,8 @@ pub mod mint_quote;
 #[cfg(feature = "mint")]
 pub mod payment;
 pub mod pub_sub;
+pub mod redact;
+pub mod rotation_log;
 #[cfg(feature = "mint")]
 pub mod state;
 pub mod subscription;
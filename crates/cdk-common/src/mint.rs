@@ -20,7 +20,7 @@ use uuid::Uuid;
 
 use crate::common::IssuerVersion;
 use crate::mint_quote::MintQuoteResponse;
-use crate::nuts::{MeltQuoteState, MintQuoteState};
+use crate::nuts::{HoldInvoiceState, MeltQuoteState, MintQuoteState};
 use crate::payment::PaymentIdentifier;
 use crate::{Amount, CurrencyUnit, Error, Id, KeySetInfo, PublicKey};
 
@@ -568,6 +568,10 @@ pub struct MintQuote {
     pub issuance: Vec<Issuance>,
     /// Extra payment-method-specific fields
     pub extra_json: Option<serde_json::Value>,
+    /// Name of the payment backend that created the payment request
+    pub backend: Option<String>,
+    /// State of the hold invoice, when the quote is paid through one
+    pub hold_invoice: Option<HoldInvoiceState>,
     /// Accumulated changes since this quote was loaded or created.
     ///
     /// This field is not serialized and is used internally to track modifications
@@ -612,6 +616,8 @@ impl MintQuote {
             payments,
             issuance,
             extra_json,
+            backend: None,
+            hold_invoice: None,
             changes: None,
         }
     }
@@ -622,10 +628,13 @@ impl MintQuote {
         &mut self,
         additional_amount: Amount<CurrencyUnit>,
     ) -> Result<Amount, crate::Error> {
-        self.amount_paid = self
+        let new_amount_paid = self
             .amount_paid
             .checked_add(&additional_amount)
             .map_err(|_| crate::Error::AmountOverflow)?;
+        self.check_state_transition(&new_amount_paid, &self.amount_issued)?;
+
+        self.amount_paid = new_amount_paid;
         Ok(Amount::from(self.amount_paid.value()))
     }
 
@@ -671,6 +680,7 @@ impl MintQuote {
         if new_amount_issued > self.amount_paid {
             return Err(crate::Error::OverIssue);
         }
+        self.check_state_transition(&self.amount_paid, &new_amount_issued)?;
 
         self.changes
             .get_or_insert_default()
@@ -758,10 +768,13 @@ impl MintQuote {
             return Err(crate::Error::DuplicatePaymentId);
         }
 
-        self.amount_paid = self
+        let new_amount_paid = self
             .amount_paid
             .checked_add(&amount)
             .map_err(|_| crate::Error::AmountOverflow)?;
+        self.check_state_transition(&new_amount_paid, &self.amount_issued)?;
+
+        self.amount_paid = new_amount_paid;
 
         let payment = IncomingPayment::new(amount, payment_id, time);
 
@@ -776,16 +789,64 @@ impl MintQuote {
         Ok(())
     }
 
+    /// Whether the quote can be paid more than once
+    ///
+    /// Only BOLT11 quotes are single use, every other payment method can receive further
+    /// payments once issued.
+    pub fn is_reusable(&self) -> bool {
+        !self.payment_method.is_bolt11()
+    }
+
+    /// Fails if moving to the given paid and issued amounts is not a valid state transition
+    fn check_state_transition(
+        &self,
+        amount_paid: &Amount<CurrencyUnit>,
+        amount_issued: &Amount<CurrencyUnit>,
+    ) -> Result<(), crate::Error> {
+        let current_state = self.state();
+        let new_state = Self::quote_state(amount_paid, amount_issued);
+
+        crate::state::check_mint_quote_state_transition(
+            current_state,
+            new_state,
+            self.is_reusable(),
+        )
+        .map_err(|err| {
+            tracing::error!(
+                quote_id = %self.id,
+                payment_method = %self.payment_method,
+                amount_paid = %self.amount_paid.value(),
+                amount_issued = %self.amount_issued.value(),
+                "Rejected mint quote state transition from {} to {}",
+                current_state,
+                new_state
+            );
+            crate::Error::from(err)
+        })
+    }
+
     /// Compute quote state
     #[instrument(skip(self))]
     fn compute_quote_state(&self) -> MintQuoteState {
-        let zero_amount = Amount::new(0, self.unit.clone());
+        match Self::quote_state(&self.amount_paid, &self.amount_issued) {
+            MintQuoteState::Issued => MintQuoteState::Issued,
+            _ if self.hold_invoice == Some(HoldInvoiceState::Cancelled) => {
+                MintQuoteState::Cancelled
+            }
+            state => state,
+        }
+    }
 
-        if self.amount_paid == zero_amount && self.amount_issued == zero_amount {
+    /// State of a quote with the given paid and issued amounts
+    fn quote_state(
+        amount_paid: &Amount<CurrencyUnit>,
+        amount_issued: &Amount<CurrencyUnit>,
+    ) -> MintQuoteState {
+        if amount_paid.value() == 0 && amount_issued.value() == 0 {
             return MintQuoteState::Unpaid;
         }
 
-        match self.amount_paid.value().cmp(&self.amount_issued.value()) {
+        match amount_paid.value().cmp(&amount_issued.value()) {
             std::cmp::Ordering::Less => {
                 tracing::error!("We should not have issued more then has been paid");
                 MintQuoteState::Issued
@@ -881,6 +942,10 @@ pub struct MeltQuote {
     fee_options: Vec<MeltQuoteOnchainFeeOption>,
     /// Selected fee option index once an onchain quote is executed
     pub selected_fee_index: Option<u32>,
+    /// Name of the payment backend that quoted and paid the request
+    pub backend: Option<String>,
+    /// Fee actually paid to the network (e.g. LN routing fees) once the quote is paid
+    pub fee_paid: Option<Amount<CurrencyUnit>>,
 }
 
 impl MeltQuote {
@@ -929,6 +994,8 @@ impl MeltQuote {
             estimated_blocks,
             fee_options,
             selected_fee_index: None,
+            backend: None,
+            fee_paid: None,
         }
     }
 
@@ -992,6 +1059,8 @@ impl MeltQuote {
             estimated_blocks,
             fee_options,
             selected_fee_index: None,
+            backend: None,
+            fee_paid: None,
         })
     }
 
@@ -1040,6 +1109,34 @@ impl MeltQuote {
         Ok(())
     }
 
+    /// Fails if the quote cannot move from its current state to `new_state`
+    ///
+    /// See [`check_melt_quote_state_transition`](crate::state::check_melt_quote_state_transition)
+    /// for the allowed transitions.
+    pub fn check_state_transition(
+        &self,
+        new_state: MeltQuoteState,
+    ) -> Result<(), crate::state::Error> {
+        crate::state::check_melt_quote_state_transition(self.state, new_state).inspect_err(|_| {
+            tracing::error!(
+                quote_id = %self.id,
+                payment_method = %self.payment_method,
+                "Rejected melt quote state transition from {} to {}",
+                self.state,
+                new_state
+            );
+        })
+    }
+
+    /// Move the quote to `new_state`, returning the previous state
+    pub fn transition_state(
+        &mut self,
+        new_state: MeltQuoteState,
+    ) -> Result<MeltQuoteState, crate::state::Error> {
+        self.check_state_transition(new_state)?;
+        Ok(std::mem::replace(&mut self.state, new_state))
+    }
+
     /// Convert into `MeltQuoteResponse`, overriding `change` on the inner
     /// response with the provided signatures.
     ///
@@ -1132,6 +1229,8 @@ impl MeltQuote {
             estimated_blocks,
             fee_options,
             selected_fee_index,
+            backend: None,
+            fee_paid: None,
         })
     }
 }
@@ -1233,6 +1332,8 @@ impl From<MintKeySetInfo> for KeySetInfo {
             active: keyset_info.active,
             input_fee_ppk: keyset_info.input_fee_ppk,
             final_expiry: keyset_info.final_expiry,
+            rotation_time: None,
+            retirement_time: None,
         }
     }
 }
@@ -1247,6 +1348,7 @@ impl From<MintQuote> for MintQuoteBolt11Response<QuoteId> {
             pubkey: mint_quote.pubkey,
             amount: mint_quote.amount.map(Into::into),
             unit: Some(mint_quote.unit),
+            hold_invoice: mint_quote.hold_invoice,
         }
     }
 }
@@ -1348,6 +1450,7 @@ impl TryFrom<MintQuote> for MintQuoteResponse<QuoteId> {
                 amount: quote.amount.as_ref().map(|a| a.clone().into()),
                 unit: Some(quote.unit.clone()),
                 pubkey: quote.pubkey,
+                hold_invoice: quote.hold_invoice,
             }))
         } else if quote.payment_method.is_bolt12() {
             Ok(Self::Bolt12(crate::nuts::nut25::MintQuoteBolt12Response {
@@ -1408,6 +1511,7 @@ impl From<MintQuoteResponse<QuoteId>> for MintQuoteBolt11Response<String> {
                 pubkey: bolt11_response.pubkey,
                 amount: bolt11_response.amount,
                 unit: bolt11_response.unit,
+                hold_invoice: bolt11_response.hold_invoice,
             },
             _ => panic!("Expected Bolt11 response"),
         }
@@ -2148,4 +2252,109 @@ mod tests {
         .expect_err("empty onchain fee_options on reload must be rejected");
         assert!(matches!(err, crate::Error::OnchainFeeOptionsEmpty));
     }
+
+    fn paid_mint_quote(payment_method: PaymentMethod) -> MintQuote {
+        let mut quote = MintQuote::new(
+            Some(QuoteId::new()),
+            "request".to_string(),
+            CurrencyUnit::Sat,
+            Some(Amount::new(100, CurrencyUnit::Sat)),
+            unix_time() + 3600,
+            PaymentIdentifier::Label("test".to_string()),
+            None,
+            Amount::new(0, CurrencyUnit::Sat),
+            Amount::new(0, CurrencyUnit::Sat),
+            payment_method,
+            unix_time(),
+            Vec::new(),
+            Vec::new(),
+            None,
+        );
+        quote
+            .add_payment(
+                Amount::new(100, CurrencyUnit::Sat),
+                "first".to_string(),
+                None,
+            )
+            .unwrap();
+        quote
+            .add_issuance(Amount::new(100, CurrencyUnit::Sat))
+            .unwrap();
+        assert_eq!(quote.state(), MintQuoteState::Issued);
+        quote
+    }
+
+    #[test]
+    fn issued_single_use_mint_quote_rejects_payments() {
+        let mut quote = paid_mint_quote(PaymentMethod::BOLT11);
+
+        let err = quote
+            .add_payment(
+                Amount::new(100, CurrencyUnit::Sat),
+                "second".to_string(),
+                None,
+            )
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            crate::Error::Database(crate::database::Error::InvalidStateTransition(
+                crate::state::Error::InvalidMintQuoteTransition(
+                    MintQuoteState::Issued,
+                    MintQuoteState::Paid
+                )
+            ))
+        ));
+        assert_eq!(quote.amount_paid().value(), 100);
+        assert!(quote
+            .take_changes()
+            .is_some_and(|changes| changes.payments.is_some_and(|payments| payments.len() == 1)));
+    }
+
+    #[test]
+    fn issued_reusable_mint_quote_accepts_payments() {
+        let mut quote = paid_mint_quote(PaymentMethod::BOLT12);
+
+        quote
+            .add_payment(
+                Amount::new(50, CurrencyUnit::Sat),
+                "second".to_string(),
+                None,
+            )
+            .unwrap();
+        assert_eq!(quote.state(), MintQuoteState::Paid);
+    }
+
+    #[test]
+    fn melt_quote_transition_state_is_checked() {
+        let bolt11_str = "lnbc100n1pnvpufspp5djn8hrq49r8cghwye9kqw752qjncwyfnrprhprpqk43mwcy4yfsqdq5g9kxy7fqd9h8vmmfvdjscqzzsxqyz5vqsp5uhpjt36rj75pl7jq2sshaukzfkt7uulj456s4mh7uy7l6vx7lvxs9qxpqysgqedwz08acmqwtk8g4vkwm2w78suwt2qyzz6jkkwcgrjm3r3hs6fskyhvud4fan3keru7emjm8ygqpcrwtlmhfjfmer3afs5hhwamgr4cqtactdq";
+        let mut quote = MeltQuote::new(
+            Some(QuoteId::new()),
+            MeltPaymentRequest::Bolt11 {
+                bolt11: Bolt11Invoice::from_str(bolt11_str).unwrap(),
+            },
+            CurrencyUnit::Sat,
+            Amount::new(100, CurrencyUnit::Sat),
+            Amount::new(2, CurrencyUnit::Sat),
+            unix_time() + 3600,
+            None,
+            None,
+            PaymentMethod::BOLT11,
+            None,
+            None,
+        );
+
+        assert_eq!(
+            quote.transition_state(MeltQuoteState::Pending).unwrap(),
+            MeltQuoteState::Unpaid
+        );
+        assert_eq!(
+            quote.transition_state(MeltQuoteState::Paid).unwrap(),
+            MeltQuoteState::Pending
+        );
+        assert!(matches!(
+            quote.transition_state(MeltQuoteState::Pending),
+            Err(crate::state::Error::AlreadyPaid)
+        ));
+        assert_eq!(quote.state, MeltQuoteState::Paid);
+    }
 }
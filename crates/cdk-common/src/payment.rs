@@ -17,7 +17,7 @@ use thiserror::Error;
 
 use crate::mint::{MeltPaymentRequest, MeltQuote};
 use crate::nuts::nut30::MeltQuoteOnchainFeeOption;
-use crate::nuts::{CurrencyUnit, MeltQuoteState};
+use crate::nuts::{CurrencyUnit, MeltQuoteState, PublicKey, KEYSEND_METHOD};
 use crate::{Amount, QuoteId};
 
 /// CDK Payment Error
@@ -340,6 +340,38 @@ pub struct OnchainOutgoingPaymentOptions {
     pub metadata: Option<String>,
 }
 
+/// Options for keysend outgoing payments
+///
+/// The mint picks the preimage when the keysend quote is created and records its payment hash as
+/// the quote's `request_lookup_id`. Backends must pay with that preimage and never draw their own,
+/// so every attempt at paying a quote pays the same hash.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct KeysendOutgoingPaymentOptions {
+    /// Node to pay
+    pub pubkey: PublicKey,
+    /// Amount the node receives
+    pub amount: Amount<CurrencyUnit>,
+    /// Maximum fee amount allowed for the payment
+    pub max_fee_amount: Option<Amount<CurrencyUnit>>,
+    /// Optional timeout in seconds
+    pub timeout_secs: Option<u64>,
+    /// The mint's quote id for this melt. See [`Bolt11OutgoingPaymentOptions::quote_id`].
+    pub quote_id: QuoteId,
+    /// Preimage the mint picked for the quote
+    ///
+    /// Always set when paying. [`OutgoingPaymentOptions::from_melt_quote_with_fee`] leaves it
+    /// unset, as the preimage is not stored on the quote.
+    pub preimage: Option<[u8; 32]>,
+}
+
+impl KeysendOutgoingPaymentOptions {
+    /// Preimage to pay with, refusing options without one
+    pub fn required_preimage(&self) -> Result<[u8; 32], Error> {
+        self.preimage
+            .ok_or_else(|| Error::Custom("Keysend payment without a preimage".to_string()))
+    }
+}
+
 /// Options for outgoing payments
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum OutgoingPaymentOptions {
@@ -351,6 +383,8 @@ pub enum OutgoingPaymentOptions {
     Custom(Box<CustomOutgoingPaymentOptions>),
     /// Onchain payment options
     Onchain(Box<OnchainOutgoingPaymentOptions>),
+    /// Keysend payment options
+    Keysend(Box<KeysendOutgoingPaymentOptions>),
 }
 
 impl OutgoingPaymentOptions {
@@ -387,6 +421,17 @@ impl OutgoingPaymentOptions {
                     },
                 )))
             }
+            MeltPaymentRequest::Custom { method, request } if method == KEYSEND_METHOD => Ok(
+                OutgoingPaymentOptions::Keysend(Box::new(KeysendOutgoingPaymentOptions {
+                    pubkey: PublicKey::from_hex(request)
+                        .map_err(|_| Error::Custom("Invalid keysend pubkey".to_string()))?,
+                    amount: melt_quote.amount(),
+                    max_fee_amount: Some(fee_reserve),
+                    timeout_secs: None,
+                    quote_id,
+                    preimage: None,
+                })),
+            ),
             MeltPaymentRequest::Custom { method, request } => Ok(OutgoingPaymentOptions::Custom(
                 Box::new(CustomOutgoingPaymentOptions {
                     method: method.to_string(),
@@ -421,6 +466,15 @@ pub trait MintPayment {
     /// Mint Lightning Error
     type Err: Into<Error> + From<Error>;
 
+    /// Name of the payment backend
+    ///
+    /// Recorded on quotes so operators can tell which node handled a payment. Defaults to the
+    /// name of the implementing type.
+    fn backend_name(&self) -> String {
+        let name = std::any::type_name::<Self>();
+        name.rsplit("::").next().unwrap_or(name).to_string()
+    }
+
     /// Start the payment processor
     /// Called when the mint starts up to initialize the payment processor
     async fn start(&self) -> Result<(), Self::Err> {
@@ -482,6 +536,126 @@ pub trait MintPayment {
         &self,
         payment_identifier: &PaymentIdentifier,
     ) -> Result<MakePaymentResponse, Self::Err>;
+
+    /// Pay request, also returning the name of the backend the payment went out through
+    ///
+    /// Backends routing payments across several others return the one that made the payment.
+    /// The mint records it on the melt quote and passes it back to
+    /// [`MintPayment::check_outgoing_payment_with_backend`]. Defaults to
+    /// [`MintPayment::make_payment`] and [`MintPayment::backend_name`].
+    async fn make_payment_with_backend(
+        &self,
+        unit: &CurrencyUnit,
+        options: OutgoingPaymentOptions,
+    ) -> Result<(MakePaymentResponse, String), Self::Err> {
+        let response = self.make_payment(unit, options).await?;
+        Ok((response, self.backend_name()))
+    }
+
+    /// Check the status of an outgoing payment made through `backend`, if known
+    ///
+    /// `backend` is the name returned by [`MintPayment::make_payment_with_backend`]. Defaults to
+    /// [`MintPayment::check_outgoing_payment`].
+    async fn check_outgoing_payment_with_backend(
+        &self,
+        _backend: Option<&str>,
+        payment_identifier: &PaymentIdentifier,
+    ) -> Result<MakePaymentResponse, Self::Err> {
+        self.check_outgoing_payment(payment_identifier).await
+    }
+
+    /// Amount the backend can currently send, in `unit`
+    ///
+    /// Consulted before melt quotes are created so quotes that could never be paid are caught
+    /// early. Defaults to `None`, for backends that cannot tell.
+    async fn outbound_liquidity(
+        &self,
+        _unit: &CurrencyUnit,
+    ) -> Result<Option<Amount<CurrencyUnit>>, Self::Err> {
+        Ok(None)
+    }
+
+    /// Routing fee the backend expects to pay for an outgoing payment, in `unit`
+    ///
+    /// Used as the fee reserve of melt quotes instead of the one of
+    /// [`MintPayment::get_payment_quote`], which is usually a flat percentage of the amount.
+    /// Defaults to `None`, for backends that cannot estimate.
+    async fn estimate_fee(
+        &self,
+        _unit: &CurrencyUnit,
+        _options: &OutgoingPaymentOptions,
+    ) -> Result<Option<Amount<CurrencyUnit>>, Self::Err> {
+        Ok(None)
+    }
+
+    /// Whether the backend can create hold invoices, see [`MintPayment::create_hold_invoice`]
+    fn supports_hold_invoices(&self) -> bool {
+        false
+    }
+
+    /// Create a bolt11 hold invoice paying to `payment_hash`
+    ///
+    /// A payment of a hold invoice is reported as received once its HTLCs are accepted, but the
+    /// funds only reach the backend when the invoice is settled with
+    /// [`MintPayment::settle_hold_invoice`]. Until then the payment can be refunded with
+    /// [`MintPayment::cancel_hold_invoice`].
+    async fn create_hold_invoice(
+        &self,
+        _options: Bolt11IncomingPaymentOptions,
+        _payment_hash: [u8; 32],
+    ) -> Result<CreateIncomingPaymentResponse, Self::Err> {
+        Err(Error::UnsupportedPaymentOption.into())
+    }
+
+    /// Settle the accepted payment of the hold invoice whose payment hash `preimage` opens
+    ///
+    /// Settling an invoice that is already settled succeeds.
+    async fn settle_hold_invoice(&self, _preimage: [u8; 32]) -> Result<(), Self::Err> {
+        Err(Error::UnsupportedPaymentOption.into())
+    }
+
+    /// Cancel the hold invoice paying to `payment_hash`, refunding any accepted payment
+    async fn cancel_hold_invoice(&self, _payment_hash: [u8; 32]) -> Result<(), Self::Err> {
+        Err(Error::UnsupportedPaymentOption.into())
+    }
+
+    /// Create a bolt11 hold invoice, also returning the name of the backend that created it
+    ///
+    /// Backends routing across several others return the one that created the invoice. The mint
+    /// records it on the mint quote and passes it back to
+    /// [`MintPayment::settle_hold_invoice_with_backend`] and
+    /// [`MintPayment::cancel_hold_invoice_with_backend`]. Defaults to
+    /// [`MintPayment::create_hold_invoice`] and [`MintPayment::backend_name`].
+    async fn create_hold_invoice_with_backend(
+        &self,
+        options: Bolt11IncomingPaymentOptions,
+        payment_hash: [u8; 32],
+    ) -> Result<(CreateIncomingPaymentResponse, String), Self::Err> {
+        let response = self.create_hold_invoice(options, payment_hash).await?;
+        Ok((response, self.backend_name()))
+    }
+
+    /// Settle a hold invoice created by `backend`, if known
+    ///
+    /// Defaults to [`MintPayment::settle_hold_invoice`].
+    async fn settle_hold_invoice_with_backend(
+        &self,
+        _backend: Option<&str>,
+        preimage: [u8; 32],
+    ) -> Result<(), Self::Err> {
+        self.settle_hold_invoice(preimage).await
+    }
+
+    /// Cancel a hold invoice created by `backend`, if known
+    ///
+    /// Defaults to [`MintPayment::cancel_hold_invoice`].
+    async fn cancel_hold_invoice_with_backend(
+        &self,
+        _backend: Option<&str>,
+        payment_hash: [u8; 32],
+    ) -> Result<(), Self::Err> {
+        self.cancel_hold_invoice(payment_hash).await
+    }
 }
 
 /// An event emitted which should be handled by the mint
@@ -714,6 +888,10 @@ where
 {
     type Err = T::Err;
 
+    fn backend_name(&self) -> String {
+        self.inner.backend_name()
+    }
+
     async fn start(&self) -> Result<(), Self::Err> {
         let metrics = MintMetricGuard::new("start");
 
@@ -837,6 +1015,153 @@ where
 
         result
     }
+
+    async fn make_payment_with_backend(
+        &self,
+        unit: &CurrencyUnit,
+        options: OutgoingPaymentOptions,
+    ) -> Result<(MakePaymentResponse, String), Self::Err> {
+        let metrics = MintMetricGuard::new("make_payment");
+
+        let result = self.inner.make_payment_with_backend(unit, options).await;
+
+        metrics.record(result.is_ok());
+
+        result
+    }
+
+    async fn check_outgoing_payment_with_backend(
+        &self,
+        backend: Option<&str>,
+        payment_identifier: &PaymentIdentifier,
+    ) -> Result<MakePaymentResponse, Self::Err> {
+        let metrics = MintMetricGuard::new("check_outgoing_payment");
+
+        let result = self
+            .inner
+            .check_outgoing_payment_with_backend(backend, payment_identifier)
+            .await;
+
+        metrics.record(result.is_ok());
+
+        result
+    }
+
+    async fn outbound_liquidity(
+        &self,
+        unit: &CurrencyUnit,
+    ) -> Result<Option<Amount<CurrencyUnit>>, Self::Err> {
+        let metrics = MintMetricGuard::new("outbound_liquidity");
+
+        let result = self.inner.outbound_liquidity(unit).await;
+
+        metrics.record(result.is_ok());
+
+        result
+    }
+
+    async fn estimate_fee(
+        &self,
+        unit: &CurrencyUnit,
+        options: &OutgoingPaymentOptions,
+    ) -> Result<Option<Amount<CurrencyUnit>>, Self::Err> {
+        let metrics = MintMetricGuard::new("estimate_fee");
+
+        let result = self.inner.estimate_fee(unit, options).await;
+
+        metrics.record(result.is_ok());
+
+        result
+    }
+
+    fn supports_hold_invoices(&self) -> bool {
+        self.inner.supports_hold_invoices()
+    }
+
+    async fn create_hold_invoice(
+        &self,
+        options: Bolt11IncomingPaymentOptions,
+        payment_hash: [u8; 32],
+    ) -> Result<CreateIncomingPaymentResponse, Self::Err> {
+        let metrics = MintMetricGuard::new("create_hold_invoice");
+
+        let result = self.inner.create_hold_invoice(options, payment_hash).await;
+
+        metrics.record(result.is_ok());
+
+        result
+    }
+
+    async fn settle_hold_invoice(&self, preimage: [u8; 32]) -> Result<(), Self::Err> {
+        let metrics = MintMetricGuard::new("settle_hold_invoice");
+
+        let result = self.inner.settle_hold_invoice(preimage).await;
+
+        metrics.record(result.is_ok());
+
+        result
+    }
+
+    async fn cancel_hold_invoice(&self, payment_hash: [u8; 32]) -> Result<(), Self::Err> {
+        let metrics = MintMetricGuard::new("cancel_hold_invoice");
+
+        let result = self.inner.cancel_hold_invoice(payment_hash).await;
+
+        metrics.record(result.is_ok());
+
+        result
+    }
+
+    async fn create_hold_invoice_with_backend(
+        &self,
+        options: Bolt11IncomingPaymentOptions,
+        payment_hash: [u8; 32],
+    ) -> Result<(CreateIncomingPaymentResponse, String), Self::Err> {
+        let metrics = MintMetricGuard::new("create_hold_invoice");
+
+        let result = self
+            .inner
+            .create_hold_invoice_with_backend(options, payment_hash)
+            .await;
+
+        metrics.record(result.is_ok());
+
+        result
+    }
+
+    async fn settle_hold_invoice_with_backend(
+        &self,
+        backend: Option<&str>,
+        preimage: [u8; 32],
+    ) -> Result<(), Self::Err> {
+        let metrics = MintMetricGuard::new("settle_hold_invoice");
+
+        let result = self
+            .inner
+            .settle_hold_invoice_with_backend(backend, preimage)
+            .await;
+
+        metrics.record(result.is_ok());
+
+        result
+    }
+
+    async fn cancel_hold_invoice_with_backend(
+        &self,
+        backend: Option<&str>,
+        payment_hash: [u8; 32],
+    ) -> Result<(), Self::Err> {
+        let metrics = MintMetricGuard::new("cancel_hold_invoice");
+
+        let result = self
+            .inner
+            .cancel_hold_invoice_with_backend(backend, payment_hash)
+            .await;
+
+        metrics.record(result.is_ok());
+
+        result
+    }
 }
 
 /// Type alias for Mint Payment trait
@@ -0,0 +1,111 @@
+//! Redaction of sensitive values in logs
+//!
+//! [`Redacted`] marks a value as sensitive at compile time: it can be passed to any log macro or
+//! span field, with `%` or `?`, and only ever prints `[redacted]`. Log subscribers should also
+//! redact fields named like secrets with [`is_sensitive_field`], so a secret recorded without the
+//! marker is not written either.
+
+use std::fmt;
+
+/// Placeholder written instead of a sensitive value
+pub const REDACTED: &str = "[redacted]";
+
+/// Parts of field names that hold sensitive values, such as `seed`, `secret_key` or
+/// `blinded_secret`
+const SENSITIVE_FIELD_PARTS: &[&str] = &[
+    "seed",
+    "mnemonic",
+    "passphrase",
+    "xpriv",
+    "private_key",
+    "secret",
+    "blinded",
+    "blinding_factor",
+    "preimage",
+    "password",
+    "api_key",
+];
+
+/// Whether a field named `name` holds a sensitive value that must not be logged
+pub fn is_sensitive_field(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    SENSITIVE_FIELD_PARTS.iter().any(|part| name.contains(part))
+}
+
+/// Value that is never printed
+///
+/// Both [`fmt::Debug`] and [`fmt::Display`] print [`REDACTED`], whatever the wrapped value. Use
+/// [`Redacted::expose`] to reach the value itself.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Redacted<T>(T);
+
+impl<T> Redacted<T> {
+    /// Mark `value` as sensitive
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    /// The sensitive value
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+
+    /// Unwrap the sensitive value
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for Redacted<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T> fmt::Debug for Redacted<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl<T> fmt::Display for Redacted<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SecretKey;
+
+    #[test]
+    fn redacted_value_is_never_printed() {
+        let secret_key = SecretKey::generate();
+        let redacted = Redacted::new(secret_key.clone());
+
+        assert_eq!(format!("{redacted}"), REDACTED);
+        assert_eq!(format!("{redacted:?}"), REDACTED);
+        assert_eq!(format!("{redacted:#?}"), REDACTED);
+        assert_eq!(redacted.expose(), &secret_key);
+    }
+
+    #[test]
+    fn secret_field_names_are_sensitive() {
+        for name in [
+            "seed",
+            "mnemonic_passphrase",
+            "secret_key",
+            "blinded_secret",
+            "blinded_messages",
+            "Preimage",
+            "signatory_api_key",
+        ] {
+            assert!(is_sensitive_field(name), "{name}");
+        }
+
+        for name in ["message", "quote_id", "keyset_id", "amount", "unit"] {
+            assert!(!is_sensitive_field(name), "{name}");
+        }
+    }
+}
@@ -0,0 +1,190 @@
+//! Signed keyset rotation log
+//!
+//! Every keyset rotation appends an attestation of the keyset it replaced and the one it
+//! activated, signed by a key of the signatory. Each attestation commits to the hash of the one
+//! before it, so an archived copy of the log can later prove that no rotation was dropped or
+//! rewritten.
+
+use std::str::FromStr;
+
+use bitcoin::hashes::{sha256, Hash};
+use bitcoin::secp256k1::schnorr::Signature;
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+use crate::nuts::{CurrencyUnit, Id, PublicKey, SecretKey};
+
+/// Unsigned record of a keyset rotation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RotationAttestation {
+    /// Position in the log, starting at 0
+    pub sequence: u64,
+    /// Hex encoded hash of the previous signed attestation, none for the first one
+    pub previous_hash: Option<String>,
+    /// Unix timestamp of the rotation
+    pub created_at: u64,
+    /// Unit of the rotated keyset
+    pub unit: CurrencyUnit,
+    /// Keyset that was active before the rotation, if any
+    pub old_keyset_id: Option<Id>,
+    /// Keyset activated by the rotation
+    pub new_keyset_id: Id,
+    /// Why the keyset was rotated, if known
+    pub reason: Option<String>,
+}
+
+impl RotationAttestation {
+    /// Message that is signed by the signatory
+    pub fn msg_to_sign(&self) -> Result<Vec<u8>, Error> {
+        Ok(serde_json::to_vec(self)?)
+    }
+
+    /// Sign the attestation with `signing_key`
+    pub fn sign(self, signing_key: &SecretKey) -> Result<SignedRotationAttestation, Error> {
+        let signature = signing_key.sign(&self.msg_to_sign()?)?;
+
+        Ok(SignedRotationAttestation {
+            attestation: self,
+            signer: signing_key.public_key(),
+            signature: signature.to_string(),
+        })
+    }
+}
+
+/// Keyset rotation attestation signed by the signatory
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedRotationAttestation {
+    /// Signed content
+    pub attestation: RotationAttestation,
+    /// Public key that signed the attestation
+    pub signer: PublicKey,
+    /// Hex encoded schnorr signature over the serialized attestation
+    pub signature: String,
+}
+
+impl SignedRotationAttestation {
+    /// Verify the signature of the attestation
+    pub fn verify(&self) -> Result<(), Error> {
+        let signature =
+            Signature::from_str(&self.signature).map_err(|_| Error::SignatureMissingOrInvalid)?;
+
+        self.signer
+            .verify(&self.attestation.msg_to_sign()?, &signature)
+            .map_err(|_| Error::SignatureMissingOrInvalid)
+    }
+
+    /// Hex encoded SHA-256 of the serialized signed attestation
+    ///
+    /// The next attestation of the log commits to it in [`RotationAttestation::previous_hash`].
+    pub fn hash(&self) -> Result<String, Error> {
+        Ok(sha256::Hash::hash(&serde_json::to_vec(self)?).to_string())
+    }
+
+    /// Unsigned attestation following this one in the log
+    pub fn next(
+        &self,
+        created_at: u64,
+        unit: CurrencyUnit,
+        old_keyset_id: Option<Id>,
+        new_keyset_id: Id,
+        reason: Option<String>,
+    ) -> Result<RotationAttestation, Error> {
+        Ok(RotationAttestation {
+            sequence: self.attestation.sequence + 1,
+            previous_hash: Some(self.hash()?),
+            created_at,
+            unit,
+            old_keyset_id,
+            new_keyset_id,
+            reason,
+        })
+    }
+}
+
+/// Verify every signature of `log` and that its attestations are chained without gaps
+///
+/// `log` must be the whole log, oldest first.
+pub fn verify_rotation_log(log: &[SignedRotationAttestation]) -> Result<(), Error> {
+    let mut previous_hash = None;
+
+    for (sequence, entry) in log.iter().enumerate() {
+        entry.verify()?;
+
+        if entry.attestation.sequence != sequence as u64 {
+            return Err(Error::Custom(format!(
+                "Rotation log entry {} found at position {}",
+                entry.attestation.sequence, sequence
+            )));
+        }
+
+        if entry.attestation.previous_hash != previous_hash {
+            return Err(Error::Custom(format!(
+                "Rotation log entry {} does not follow the previous one",
+                sequence
+            )));
+        }
+
+        previous_hash = Some(entry.hash()?);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn first(signing_key: &SecretKey) -> SignedRotationAttestation {
+        RotationAttestation {
+            sequence: 0,
+            previous_hash: None,
+            created_at: 1_000,
+            unit: CurrencyUnit::Sat,
+            old_keyset_id: None,
+            new_keyset_id: Id::from_str("00916bbf7ef91a36").unwrap(),
+            reason: Some("Initial keyset".to_owned()),
+        }
+        .sign(signing_key)
+        .unwrap()
+    }
+
+    #[test]
+    fn chained_log_verifies() {
+        let signing_key = SecretKey::generate();
+        let first = first(&signing_key);
+        let second = first
+            .next(
+                2_000,
+                CurrencyUnit::Sat,
+                Some(first.attestation.new_keyset_id),
+                Id::from_str("00aa6bbf7ef91a36").unwrap(),
+                None,
+            )
+            .unwrap()
+            .sign(&signing_key)
+            .unwrap();
+
+        let json = serde_json::to_string(&second).unwrap();
+        let decoded: SignedRotationAttestation = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, second);
+
+        verify_rotation_log(&[first.clone(), second.clone()]).unwrap();
+
+        // Dropping or reordering entries breaks the chain
+        assert!(verify_rotation_log(&[second.clone()]).is_err());
+        assert!(verify_rotation_log(&[second, first]).is_err());
+    }
+
+    #[test]
+    fn tampered_attestation_fails_verification() {
+        let signing_key = SecretKey::generate();
+
+        let mut signed = first(&signing_key);
+        signed.attestation.reason = None;
+        assert!(signed.verify().is_err());
+
+        let mut signed = first(&signing_key);
+        signed.signer = SecretKey::generate().public_key();
+        assert!(signed.verify().is_err());
+    }
+}
@@ -1,6 +1,6 @@
 //! State transition rules
 
-use cashu::{MeltQuoteState, State};
+use cashu::{MeltQuoteState, MintQuoteState, State};
 
 /// State transition Error
 #[derive(thiserror::Error, Debug)]
@@ -20,6 +20,9 @@ pub enum Error {
     /// Invalid transition
     #[error("Invalid melt quote state transition: From {0} to {1}")]
     InvalidMeltQuoteTransition(MeltQuoteState, MeltQuoteState),
+    /// Invalid transition
+    #[error("Invalid mint quote state transition: From {0} to {1}")]
+    InvalidMintQuoteTransition(MintQuoteState, MintQuoteState),
 }
 
 #[inline]
@@ -82,6 +85,36 @@ pub fn check_melt_quote_state_transition(
     }
 }
 
+#[inline]
+/// Check if the mint quote state transition is allowed
+///
+/// Valid transitions:
+/// - Unpaid -> Paid
+/// - Paid -> Issued
+/// - Issued -> Paid, only for `reusable` quotes receiving another payment
+///
+/// Updates that leave the state unchanged are allowed, they happen when a reusable quote is
+/// paid or issued partially.
+pub fn check_mint_quote_state_transition(
+    current_state: MintQuoteState,
+    new_state: MintQuoteState,
+    reusable: bool,
+) -> Result<(), Error> {
+    let is_valid_transition = current_state == new_state
+        || match current_state {
+            MintQuoteState::Unpaid => new_state == MintQuoteState::Paid,
+            MintQuoteState::Paid => new_state == MintQuoteState::Issued,
+            MintQuoteState::Issued => reusable && new_state == MintQuoteState::Paid,
+            MintQuoteState::Cancelled => false,
+        };
+
+    if !is_valid_transition {
+        Err(Error::InvalidMintQuoteTransition(current_state, new_state))
+    } else {
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -301,4 +334,69 @@ mod tests {
             ));
         }
     }
+
+    mod mint_quote_state_transitions {
+        use super::*;
+
+        #[test]
+        fn single_use_quote() {
+            assert!(check_mint_quote_state_transition(
+                MintQuoteState::Unpaid,
+                MintQuoteState::Paid,
+                false
+            )
+            .is_ok());
+            assert!(check_mint_quote_state_transition(
+                MintQuoteState::Paid,
+                MintQuoteState::Issued,
+                false
+            )
+            .is_ok());
+            assert!(matches!(
+                check_mint_quote_state_transition(
+                    MintQuoteState::Issued,
+                    MintQuoteState::Paid,
+                    false
+                ),
+                Err(Error::InvalidMintQuoteTransition(
+                    MintQuoteState::Issued,
+                    MintQuoteState::Paid
+                ))
+            ));
+            assert!(check_mint_quote_state_transition(
+                MintQuoteState::Unpaid,
+                MintQuoteState::Issued,
+                false
+            )
+            .is_err());
+            assert!(check_mint_quote_state_transition(
+                MintQuoteState::Paid,
+                MintQuoteState::Unpaid,
+                false
+            )
+            .is_err());
+        }
+
+        #[test]
+        fn reusable_quote() {
+            assert!(check_mint_quote_state_transition(
+                MintQuoteState::Issued,
+                MintQuoteState::Paid,
+                true
+            )
+            .is_ok());
+            assert!(check_mint_quote_state_transition(
+                MintQuoteState::Paid,
+                MintQuoteState::Paid,
+                true
+            )
+            .is_ok());
+            assert!(check_mint_quote_state_transition(
+                MintQuoteState::Issued,
+                MintQuoteState::Unpaid,
+                true
+            )
+            .is_err());
+        }
+    }
 }
@@ -403,6 +403,13 @@ pub struct SendOptions {
     pub include_fee: bool,
     /// Maximum number of proofs to include in the token
     pub max_proofs: Option<usize>,
+    /// Maximum size in bytes of the serialized token, see [`Token::estimated_size`]
+    ///
+    /// Proofs that would make the token too large are swapped into the fewest denominations
+    /// before sending. Sending fails with [`Error::TokenTooLarge`] if the token cannot fit.
+    ///
+    /// [`Token::estimated_size`]: crate::nuts::Token::estimated_size
+    pub max_token_bytes: Option<usize>,
     /// Metadata
     pub metadata: HashMap<String, String>,
     /// Use P2BK (NUT-28)
@@ -422,6 +429,7 @@ impl fmt::Debug for SendOptions {
             .field("send_kind", &self.send_kind)
             .field("include_fee", &self.include_fee)
             .field("max_proofs", &self.max_proofs)
+            .field("max_token_bytes", &self.max_token_bytes)
             .field("metadata", &self.metadata)
             .field("use_p2bk", &self.use_p2bk)
             .field("p2pk_signing_keys", &"[redacted]")
@@ -473,6 +481,12 @@ pub struct ReceiveOptions {
     pub preimages: Vec<String>,
     /// Metadata
     pub metadata: HashMap<String, String>,
+    /// Receive even when the fee to claim the proofs is above the receive fee threshold of the
+    /// wallet
+    pub ignore_fee_threshold: bool,
+    /// Keep the swapped proofs out of the balance, until released by the app, when the risk
+    /// score of the received proofs reaches this value
+    pub quarantine_risk_score: Option<u32>,
 }
 
 impl fmt::Debug for ReceiveOptions {
@@ -482,6 +496,8 @@ impl fmt::Debug for ReceiveOptions {
             .field("p2pk_signing_keys", &"[redacted]")
             .field("preimages", &self.preimages)
             .field("metadata", &self.metadata)
+            .field("ignore_fee_threshold", &self.ignore_fee_threshold)
+            .field("quarantine_risk_score", &self.quarantine_risk_score)
             .finish()
     }
 }
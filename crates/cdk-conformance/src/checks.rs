@@ -0,0 +1,400 @@
+//! Checks run against the mint
+//!
+//! The checks run in order with a single wallet, each one building on the proofs the previous
+//! ones left: tokens are minted, swapped, checked, melted and finally restored from the seed
+//! into a second wallet. A check whose prerequisite did not pass is skipped, so one failure
+//! does not show up as a cascade of unrelated ones.
+
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, ensure, Context, Result};
+use bip39::Mnemonic;
+use cdk::amount::SplitTarget;
+use cdk::mint_url::MintUrl;
+use cdk::nuts::nut00::ProofsMethods;
+use cdk::nuts::{CurrencyUnit, MeltQuoteState, MintInfo, PaymentMethod, Proofs, State};
+use cdk::wallet::{HttpClient, MintConnector, Wallet};
+use cdk::Amount;
+use cdk_sqlite::wallet::memory;
+
+use crate::payment;
+use crate::report::{Outcome, Report};
+
+/// Settings of a conformance run
+#[derive(Debug, Clone)]
+pub struct Settings {
+    /// Mint under test
+    pub mint_url: String,
+    /// Unit to test
+    pub unit: CurrencyUnit,
+    /// Amount minted
+    pub amount: Amount,
+    /// How long to wait for a mint quote to be paid
+    pub pay_timeout: Duration,
+    /// Command paying mint quotes, the mint pays them on its own otherwise
+    pub pay_command: Option<String>,
+    /// Command printing an invoice to melt, a fake invoice is melted otherwise
+    pub invoice_command: Option<String>,
+    /// Do not melt
+    pub skip_melt: bool,
+}
+
+/// State of a conformance run
+#[derive(Debug)]
+pub struct Suite {
+    settings: Settings,
+    seed: [u8; 64],
+    wallet: Wallet,
+    info: Option<MintInfo>,
+    /// Proofs handed to the swap, spent once it succeeded
+    swapped: Proofs,
+    report: Report,
+}
+
+fn skip(reason: &str) -> Result<Outcome> {
+    Ok(Outcome::Skip(reason.to_owned()))
+}
+
+impl Suite {
+    /// Suite with a fresh in memory wallet
+    pub async fn new(settings: Settings) -> Result<Self> {
+        let seed = Mnemonic::generate(12)?.to_seed_normalized("");
+        let wallet = Self::wallet(&settings, seed).await?;
+        let report = Report::new(settings.mint_url.clone());
+
+        Ok(Self {
+            settings,
+            seed,
+            wallet,
+            info: None,
+            swapped: Proofs::new(),
+            report,
+        })
+    }
+
+    async fn wallet(settings: &Settings, seed: [u8; 64]) -> Result<Wallet> {
+        Ok(Wallet::new(
+            &settings.mint_url,
+            settings.unit.clone(),
+            Arc::new(memory::empty().await?),
+            seed,
+            None,
+        )?)
+    }
+
+    /// Run every check and report the outcomes
+    pub async fn run(mut self) -> Report {
+        let started = Instant::now();
+        let outcome = Outcome::from_result(self.info().await);
+        self.report.record("info", &[6], outcome, started.elapsed());
+
+        let started = Instant::now();
+        let outcome = Outcome::from_result(self.keys().await);
+        self.report
+            .record("keys", &[1, 2], outcome, started.elapsed());
+
+        let started = Instant::now();
+        let outcome = Outcome::from_result(self.mint().await);
+        self.report.record("mint", &[4], outcome, started.elapsed());
+
+        let started = Instant::now();
+        let outcome = Outcome::from_result(self.dleq().await);
+        self.report
+            .record("dleq", &[12], outcome, started.elapsed());
+
+        let started = Instant::now();
+        let outcome = Outcome::from_result(self.swap().await);
+        self.report.record("swap", &[3], outcome, started.elapsed());
+
+        let started = Instant::now();
+        let outcome = Outcome::from_result(self.check_state().await);
+        self.report
+            .record("check_state", &[7], outcome, started.elapsed());
+
+        let started = Instant::now();
+        let outcome = Outcome::from_result(self.melt().await);
+        self.report
+            .record("melt", &[5, 8], outcome, started.elapsed());
+
+        let started = Instant::now();
+        let outcome = Outcome::from_result(self.restore().await);
+        self.report
+            .record("restore", &[9, 13], outcome, started.elapsed());
+
+        self.report
+    }
+
+    /// NUT-06: the mint describes itself
+    async fn info(&mut self) -> Result<Outcome> {
+        let info = self
+            .wallet
+            .fetch_mint_info()
+            .await?
+            .context("Mint returned no info")?;
+
+        self.report.mint_version = info.version.as_ref().map(|version| version.to_string());
+        self.info = Some(info);
+
+        Ok(Outcome::Pass)
+    }
+
+    /// NUT-01 and NUT-02: the published keys match their keyset ids, the unit has an active
+    /// keyset
+    async fn keys(&mut self) -> Result<Outcome> {
+        let client = HttpClient::new(MintUrl::from_str(&self.settings.mint_url)?, None);
+
+        let keysets = client.get_mint_keysets().await?.keysets;
+        let keys = client.get_mint_keys().await?;
+
+        for keyset in &keys {
+            keyset
+                .verify_id()
+                .with_context(|| format!("Keys of keyset {} do not match its id", keyset.id))?;
+        }
+
+        let active: Vec<_> = keysets
+            .iter()
+            .filter(|keyset| keyset.active && keyset.unit == self.settings.unit)
+            .collect();
+        if active.is_empty() {
+            bail!("No active keyset for unit {}", self.settings.unit);
+        }
+
+        for keyset in active {
+            ensure!(
+                keys.iter().any(|keys| keys.id == keyset.id),
+                "Keys of the active keyset {} are not published",
+                keyset.id
+            );
+        }
+
+        Ok(Outcome::Pass)
+    }
+
+    /// NUT-04: a paid mint quote is minted
+    async fn mint(&mut self) -> Result<Outcome> {
+        if self
+            .info
+            .as_ref()
+            .is_some_and(|info| info.nuts.nut04.disabled)
+        {
+            return skip("minting is disabled");
+        }
+
+        let quote = self
+            .wallet
+            .mint_quote(
+                PaymentMethod::BOLT11,
+                Some(self.settings.amount),
+                None,
+                None,
+            )
+            .await?;
+
+        payment::pay(self.settings.pay_command.as_deref(), &quote.request).await?;
+
+        let proofs = self
+            .wallet
+            .wait_and_mint_quote(
+                quote,
+                SplitTarget::default(),
+                None,
+                self.settings.pay_timeout,
+            )
+            .await?;
+
+        let minted = proofs.total_amount()?;
+        ensure!(
+            minted == self.settings.amount,
+            "Minted {} instead of {}",
+            minted,
+            self.settings.amount
+        );
+
+        Ok(Outcome::Pass)
+    }
+
+    /// NUT-12: minted proofs carry a valid DLEQ proof
+    async fn dleq(&mut self) -> Result<Outcome> {
+        if !self
+            .info
+            .as_ref()
+            .is_some_and(|info| info.nuts.nut12.supported)
+        {
+            return skip("NUT-12 is not advertised");
+        }
+        if !self.report.passed("mint") {
+            return skip("requires mint");
+        }
+
+        for proof in self.wallet.get_unspent_proofs().await? {
+            let keys = self.wallet.load_keyset_keys(proof.keyset_id).await?;
+            let key = keys
+                .amount_key(proof.amount)
+                .with_context(|| format!("No key for amount {}", proof.amount))?;
+            proof
+                .verify_dleq(key)
+                .with_context(|| format!("Invalid DLEQ proof for amount {}", proof.amount))?;
+        }
+
+        Ok(Outcome::Pass)
+    }
+
+    /// NUT-03: proofs are swapped for new ones of the same amount, less fees
+    async fn swap(&mut self) -> Result<Outcome> {
+        if !self.report.passed("mint") {
+            return skip("requires mint");
+        }
+
+        let inputs = self.wallet.get_unspent_proofs().await?;
+        let input_amount = inputs.total_amount()?;
+        let fee = self.wallet.get_proofs_fee(&inputs).await?.total;
+
+        self.wallet
+            .swap(
+                None,
+                SplitTarget::default(),
+                inputs.clone(),
+                None,
+                false,
+                false,
+            )
+            .await?;
+        self.swapped = inputs;
+
+        let balance = self.wallet.total_balance().await?;
+        ensure!(
+            balance.checked_add(fee) == Some(input_amount),
+            "Swapped {} with a fee of {} into {}",
+            input_amount,
+            fee,
+            balance
+        );
+
+        Ok(Outcome::Pass)
+    }
+
+    /// NUT-07: swapped proofs are spent, their replacements unspent
+    async fn check_state(&mut self) -> Result<Outcome> {
+        if !self
+            .info
+            .as_ref()
+            .is_some_and(|info| info.nuts.nut07.supported)
+        {
+            return skip("NUT-07 is not advertised");
+        }
+        if !self.report.passed("swap") {
+            return skip("requires swap");
+        }
+
+        let spent = self.wallet.check_proofs_spent(self.swapped.clone()).await?;
+        ensure!(
+            spent.len() == self.swapped.len(),
+            "Got {} states for {} proofs",
+            spent.len(),
+            self.swapped.len()
+        );
+        ensure!(
+            spent.iter().all(|proof| proof.state == State::Spent),
+            "Swapped proofs are not reported spent"
+        );
+
+        let unspent = self.wallet.get_unspent_proofs().await?;
+        let states = self.wallet.check_proofs_spent(unspent).await?;
+        ensure!(
+            states.iter().all(|proof| proof.state == State::Unspent),
+            "Proofs received from the swap are not reported unspent"
+        );
+
+        Ok(Outcome::Pass)
+    }
+
+    /// NUT-05 and NUT-08: half the balance is melted and the overpaid fee reserve returned
+    async fn melt(&mut self) -> Result<Outcome> {
+        if self.settings.skip_melt {
+            return skip("melting skipped");
+        }
+        if self
+            .info
+            .as_ref()
+            .is_some_and(|info| info.nuts.nut05.disabled)
+        {
+            return skip("melting is disabled");
+        }
+        if !self.report.passed("mint") {
+            return skip("requires mint");
+        }
+
+        let balance = self.wallet.total_balance().await?;
+        let amount = u64::from(balance) / 2;
+        ensure!(amount > 0, "Balance of {} is too low to melt", balance);
+
+        let invoice =
+            payment::invoice(self.settings.invoice_command.as_deref(), amount * 1000).await?;
+        let quote = self
+            .wallet
+            .melt_quote(PaymentMethod::BOLT11, invoice, None, None)
+            .await?;
+
+        let melted = self
+            .wallet
+            .prepare_melt(&quote.id, HashMap::new())
+            .await?
+            .confirm()
+            .await?;
+
+        ensure!(
+            melted.state() == MeltQuoteState::Paid,
+            "Melt quote is {} after melting",
+            melted.state()
+        );
+
+        let remaining = self.wallet.total_balance().await?;
+        ensure!(
+            melted
+                .amount()
+                .checked_add(melted.fee_paid())
+                .and_then(|spent| spent.checked_add(remaining))
+                == Some(balance),
+            "Melted {} and {} of fees from {}, {} left",
+            melted.amount(),
+            melted.fee_paid(),
+            balance,
+            remaining
+        );
+
+        Ok(Outcome::Pass)
+    }
+
+    /// NUT-09 and NUT-13: a wallet with the same seed restores the unspent balance
+    async fn restore(&mut self) -> Result<Outcome> {
+        if !self
+            .info
+            .as_ref()
+            .is_some_and(|info| info.nuts.nut09.supported)
+        {
+            return skip("NUT-09 is not advertised");
+        }
+        if !self.report.passed("mint") {
+            return skip("requires mint");
+        }
+
+        let balance = self.wallet.total_balance().await?;
+        let restored = Self::wallet(&self.settings, self.seed)
+            .await?
+            .restore()
+            .await?;
+
+        ensure!(
+            restored.unspent == balance,
+            "Restored {} unspent instead of {}",
+            restored.unspent,
+            balance
+        );
+
+        Ok(Outcome::Pass)
+    }
+}
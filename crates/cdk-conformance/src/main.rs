@@ -0,0 +1,102 @@
+//! NUT conformance suite for Cashu mints
+//!
+//! Runs a wallet through info, keys, mint, swap, check state, melt and restore against any
+//! mint URL and reports which NUTs the mint conforms to. The same binary runs in the cdk CI
+//! against the fake wallet mint and lets other mint implementations check themselves against
+//! the cdk wallet.
+
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+use cdk::nuts::CurrencyUnit;
+use cdk::Amount;
+use clap::Parser;
+use tracing_subscriber::EnvFilter;
+
+mod checks;
+mod payment;
+mod report;
+
+use checks::{Settings, Suite};
+
+/// Check a mint against the NUTs and report its conformance
+#[derive(Parser)]
+#[command(name = "cdk-conformance")]
+#[command(author, version, about, long_about = None)]
+struct Cli {
+    /// Mint under test
+    #[arg(long, default_value = "http://127.0.0.1:8085")]
+    mint_url: String,
+    /// Unit to test
+    #[arg(long, default_value = "sat")]
+    unit: String,
+    /// Amount to mint
+    #[arg(long, default_value_t = 64)]
+    amount: u64,
+    /// Seconds to wait for the mint quote to be paid
+    #[arg(long, default_value_t = 60)]
+    pay_timeout: u64,
+    /// Command paying the mint quote, run with the payment request as its last argument.
+    /// Without it the mint has to pay its own quotes, as the fake wallet backend does
+    #[arg(long)]
+    pay_command: Option<String>,
+    /// Command printing an invoice to melt, run with the amount in msat as its last argument.
+    /// Without it a fake invoice is melted, which only the fake wallet backend pays
+    #[arg(long)]
+    invoice_command: Option<String>,
+    /// Do not melt
+    #[arg(long)]
+    skip_melt: bool,
+    /// Also write the report as JSON to this file
+    #[arg(long)]
+    json: Option<PathBuf>,
+    /// Exit successfully even when a check failed
+    #[arg(long)]
+    no_fail: bool,
+    /// Logging level
+    #[arg(short, long, default_value = "warn")]
+    log_level: tracing::Level,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Cli::parse();
+
+    let env_filter = EnvFilter::new(format!(
+        "{},hyper_util=warn,rustls=warn,reqwest=warn",
+        args.log_level
+    ));
+    tracing_subscriber::fmt()
+        .with_env_filter(env_filter)
+        .with_ansi(false)
+        .init();
+
+    if args.amount < 2 {
+        bail!("Amount must be at least 2, half of it is melted");
+    }
+
+    let settings = Settings {
+        mint_url: args.mint_url,
+        unit: CurrencyUnit::from_str(&args.unit)?,
+        amount: Amount::from(args.amount),
+        pay_timeout: Duration::from_secs(args.pay_timeout),
+        pay_command: args.pay_command,
+        invoice_command: args.invoice_command,
+        skip_melt: args.skip_melt,
+    };
+
+    let report = Suite::new(settings).await?.run().await;
+    println!("{}", report);
+
+    if let Some(path) = args.json {
+        std::fs::write(&path, serde_json::to_string_pretty(&report)?)?;
+    }
+
+    if !args.no_fail && report.has_failures() {
+        bail!("Some checks failed");
+    }
+
+    Ok(())
+}
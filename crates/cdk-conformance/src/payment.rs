@@ -0,0 +1,68 @@
+//! Pay hooks
+//!
+//! Mints running the fake wallet backend pay their own mint quotes and settle any fake invoice
+//! they are asked to melt. Against a mint with a real backend, the operator provides a command
+//! paying the mint quote request and one creating the invoice to melt, e.g. with a Lightning
+//! node of their own.
+
+use anyhow::{bail, Context, Result};
+use cdk_fake_wallet::{create_fake_invoice, FakeInvoiceDescription};
+use tokio::process::Command;
+
+/// Build the command line `command`, followed by `argument`
+fn command(command: &str, argument: &str) -> Result<Command> {
+    let mut parts = command.split_whitespace();
+    let program = parts.next().context("Empty command")?;
+
+    let mut command = Command::new(program);
+    command.args(parts).arg(argument);
+    Ok(command)
+}
+
+/// Pay the mint quote `request` with `pay_command`
+///
+/// Without a command the mint is expected to pay its quotes on its own.
+pub async fn pay(pay_command: Option<&str>, request: &str) -> Result<()> {
+    let Some(pay_command) = pay_command else {
+        return Ok(());
+    };
+
+    let status = command(pay_command, request)?
+        .status()
+        .await
+        .with_context(|| format!("Could not run pay command `{pay_command}`"))?;
+    if !status.success() {
+        bail!("Pay command `{}` exited with {}", pay_command, status);
+    }
+
+    Ok(())
+}
+
+/// Invoice of `amount_msat` to melt, printed by `invoice_command`
+///
+/// Without a command a fake invoice is created, which only a fake wallet backend pays.
+pub async fn invoice(invoice_command: Option<&str>, amount_msat: u64) -> Result<String> {
+    let Some(invoice_command) = invoice_command else {
+        let description = serde_json::to_string(&FakeInvoiceDescription::default())?;
+        return Ok(create_fake_invoice(amount_msat, description).to_string());
+    };
+
+    let output = command(invoice_command, &amount_msat.to_string())?
+        .output()
+        .await
+        .with_context(|| format!("Could not run invoice command `{invoice_command}`"))?;
+    if !output.status.success() {
+        bail!(
+            "Invoice command `{}` exited with {}",
+            invoice_command,
+            output.status
+        );
+    }
+
+    let invoice = String::from_utf8(output.stdout)?.trim().to_owned();
+    if invoice.is_empty() {
+        bail!("Invoice command `{}` printed no invoice", invoice_command);
+    }
+
+    Ok(invoice)
+}
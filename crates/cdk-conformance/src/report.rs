@@ -0,0 +1,235 @@
+//! Conformance report
+//!
+//! Every check covers one or more NUTs and either passes, fails with the reason, or is skipped
+//! because the mint does not advertise the NUT or a check it depends on did not pass. A NUT
+//! conforms when none of its checks failed and at least one of them passed.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::Duration;
+
+use serde::Serialize;
+
+/// Outcome of a check
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", content = "reason", rename_all = "lowercase")]
+pub enum Outcome {
+    /// The mint behaved as the NUTs require
+    Pass,
+    /// The mint did not behave as the NUTs require
+    Fail(String),
+    /// The check was not run
+    Skip(String),
+}
+
+impl Outcome {
+    /// Outcome of a check that returned an error on failure
+    pub fn from_result(result: anyhow::Result<Outcome>) -> Self {
+        result.unwrap_or_else(|err| Self::Fail(format!("{err:#}")))
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Pass => "PASS",
+            Self::Fail(_) => "FAIL",
+            Self::Skip(_) => "SKIP",
+        }
+    }
+}
+
+/// Result of one check
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CheckResult {
+    /// Name of the check
+    pub name: &'static str,
+    /// NUTs the check covers
+    pub nuts: Vec<u8>,
+    /// Outcome of the check
+    #[serde(flatten)]
+    pub outcome: Outcome,
+    /// How long the check took, in milliseconds
+    pub duration_ms: u128,
+}
+
+/// Conformance of a mint to the NUTs
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Report {
+    /// Mint under test
+    pub mint_url: String,
+    /// Name and version the mint reports, if any
+    pub mint_version: Option<String>,
+    /// Every check, in the order they ran
+    pub checks: Vec<CheckResult>,
+}
+
+impl Report {
+    /// Empty report for `mint_url`
+    pub fn new(mint_url: String) -> Self {
+        Self {
+            mint_url,
+            ..Default::default()
+        }
+    }
+
+    /// Record the outcome of the check `name`, covering `nuts`
+    pub fn record(&mut self, name: &'static str, nuts: &[u8], outcome: Outcome, took: Duration) {
+        match &outcome {
+            Outcome::Pass => tracing::info!("{} passed", name),
+            Outcome::Fail(reason) => tracing::warn!("{} failed: {}", name, reason),
+            Outcome::Skip(reason) => tracing::info!("{} skipped: {}", name, reason),
+        }
+
+        self.checks.push(CheckResult {
+            name,
+            nuts: nuts.to_vec(),
+            outcome,
+            duration_ms: took.as_millis(),
+        });
+    }
+
+    /// Whether the check `name` passed
+    pub fn passed(&self, name: &str) -> bool {
+        self.checks
+            .iter()
+            .any(|check| check.name == name && check.outcome == Outcome::Pass)
+    }
+
+    /// Whether any check failed
+    pub fn has_failures(&self) -> bool {
+        self.checks
+            .iter()
+            .any(|check| matches!(check.outcome, Outcome::Fail(_)))
+    }
+
+    /// Conformance of every NUT covered by a check
+    ///
+    /// `Some(true)` when the NUT conforms, `Some(false)` when any of its checks failed and
+    /// `None` when all of its checks were skipped.
+    pub fn nuts(&self) -> BTreeMap<u8, Option<bool>> {
+        let mut nuts = BTreeMap::new();
+        for check in &self.checks {
+            for nut in &check.nuts {
+                let conforms: &mut Option<bool> = nuts.entry(*nut).or_default();
+                *conforms = match (&check.outcome, *conforms) {
+                    (Outcome::Fail(_), _) | (_, Some(false)) => Some(false),
+                    (Outcome::Pass, _) => Some(true),
+                    (Outcome::Skip(_), conforms) => conforms,
+                };
+            }
+        }
+        nuts
+    }
+}
+
+fn nut_list(nuts: &[u8]) -> String {
+    nuts.iter()
+        .map(|nut| format!("{nut:02}"))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "NUT conformance of {} ({})",
+            self.mint_url,
+            self.mint_version.as_deref().unwrap_or("unknown version")
+        )?;
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{:<14} {:<10} {:<6} {:>8}",
+            "check", "nuts", "status", "ms"
+        )?;
+
+        for check in &self.checks {
+            write!(
+                f,
+                "{:<14} {:<10} {:<6} {:>8}",
+                check.name,
+                nut_list(&check.nuts),
+                check.outcome.label(),
+                check.duration_ms
+            )?;
+            match &check.outcome {
+                Outcome::Pass => writeln!(f)?,
+                Outcome::Fail(reason) | Outcome::Skip(reason) => writeln!(f, "  {reason}")?,
+            }
+        }
+
+        writeln!(f)?;
+        let nuts: Vec<String> = self
+            .nuts()
+            .into_iter()
+            .map(|(nut, conforms)| {
+                let status = match conforms {
+                    Some(true) => "pass",
+                    Some(false) => "fail",
+                    None => "skip",
+                };
+                format!("NUT-{nut:02} {status}")
+            })
+            .collect();
+        write!(f, "{}", nuts.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nuts_conform_when_a_check_passed_and_none_failed() {
+        let mut report = Report::new("http://127.0.0.1:8085".to_owned());
+        report.record("keys", &[1, 2], Outcome::Pass, Duration::ZERO);
+        report.record(
+            "swap",
+            &[3, 2],
+            Outcome::Fail("outputs not signed".to_owned()),
+            Duration::ZERO,
+        );
+        report.record(
+            "restore",
+            &[9],
+            Outcome::Skip("not advertised".to_owned()),
+            Duration::ZERO,
+        );
+
+        assert!(report.has_failures());
+        assert!(report.passed("keys"));
+        assert!(!report.passed("swap"));
+        assert_eq!(
+            report.nuts(),
+            BTreeMap::from([
+                (1, Some(true)),
+                (2, Some(false)),
+                (3, Some(false)),
+                (9, None)
+            ])
+        );
+    }
+
+    #[test]
+    fn outcomes_serialize_with_their_reason() {
+        let mut report = Report::new("http://127.0.0.1:8085".to_owned());
+        report.record(
+            "melt",
+            &[5],
+            Outcome::Fail("quote unpaid".to_owned()),
+            Duration::from_millis(12),
+        );
+
+        let json = serde_json::to_value(&report.checks[0]).expect("serialize");
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "name": "melt",
+                "nuts": [5],
+                "status": "fail",
+                "reason": "quote unpaid",
+                "duration_ms": 12
+            })
+        );
+    }
+}
@@ -26,12 +26,13 @@ use cdk_common::amount::Amount;
 use cdk_common::common::FeeReserve;
 use cdk_common::ensure_cdk;
 use cdk_common::nuts::nut30::MeltQuoteOnchainFeeOption;
-use cdk_common::nuts::{CurrencyUnit, MeltOptions, MeltQuoteState};
+use cdk_common::nuts::{CurrencyUnit, MeltOptions, MeltQuoteState, KEYSEND_METHOD};
 use cdk_common::payment::{
-    self, CreateIncomingPaymentResponse, Event, IncomingPaymentOptions, MakePaymentResponse,
-    MintPayment, OutgoingPaymentOptions, PaymentIdentifier, PaymentQuoteResponse, SettingsResponse,
-    WaitPaymentResponse,
+    self, Bolt11IncomingPaymentOptions, CreateIncomingPaymentResponse, Event,
+    IncomingPaymentOptions, MakePaymentResponse, MintPayment, OutgoingPaymentOptions,
+    PaymentIdentifier, PaymentQuoteResponse, SettingsResponse, WaitPaymentResponse,
 };
+use cdk_common::util::hex;
 use error::Error;
 use futures::stream::StreamExt;
 use futures::Stream;
@@ -351,6 +352,9 @@ pub struct FakeWallet {
     secondary_repayment_queue: SecondaryRepaymentQueue,
     exchange_rate_cache: ExchangeRateCache,
     custom_payment_methods: HashMap<String, String>,
+    outbound_liquidity: Option<Amount<CurrencyUnit>>,
+    /// Payment hash of each hold invoice and whether it was settled
+    hold_invoices: Arc<Mutex<HashMap<[u8; 32], bool>>>,
 }
 
 impl FakeWallet {
@@ -401,9 +405,25 @@ impl FakeWallet {
             secondary_repayment_queue,
             exchange_rate_cache: ExchangeRateCache::new(),
             custom_payment_methods: HashMap::new(),
+            outbound_liquidity: None,
+            hold_invoices: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
+    /// Whether the hold invoice paying to `payment_hash` was settled, none when unknown or
+    /// cancelled
+    pub async fn hold_invoice_settled(&self, payment_hash: &[u8; 32]) -> Option<bool> {
+        self.hold_invoices.lock().await.get(payment_hash).copied()
+    }
+
+    /// Report `liquidity` as the amount the fake wallet can send
+    ///
+    /// Payments do not lower it. By default the outbound liquidity is unknown.
+    pub fn with_outbound_liquidity(mut self, liquidity: Amount<CurrencyUnit>) -> Self {
+        self.outbound_liquidity = Some(liquidity);
+        self
+    }
+
     /// Configure custom payment methods advertised by this fake wallet.
     pub fn with_custom_payment_methods(
         mut self,
@@ -426,6 +446,68 @@ impl FakeWallet {
         Ok(())
     }
 
+    /// Pay `payment_hash` with `amount` after the payment delay, a random amount for any-amount
+    /// invoices
+    async fn schedule_incoming_payment(
+        &self,
+        payment_hash: PaymentIdentifier,
+        amount: Amount<CurrencyUnit>,
+    ) {
+        let sender = self.sender.clone();
+        let duration = time::Duration::from_secs(self.payment_delay);
+        let payment_hash_clone = payment_hash.clone();
+        let incoming_payment = self.incoming_payments.clone();
+
+        let final_amount = if amount.value() == 0 {
+            // For any-amount invoices, generate a random amount for the initial payment
+            use bitcoin::secp256k1::rand::rngs::OsRng;
+            use bitcoin::secp256k1::rand::Rng;
+            let mut rng = OsRng;
+            let random_amount: u64 = rng.gen_range(1000..=10000);
+            // Use the same unit as the invoice for any-amount invoices
+            Amount::new(random_amount, amount.unit().clone())
+        } else {
+            amount
+        };
+
+        // Check if this is an any-amount invoice before moving final_amount
+        let is_any_amount = final_amount.value() == 0;
+
+        // Schedule the immediate payment (original behavior maintained)
+        tokio::spawn(async move {
+            // Wait for the random delay to elapse
+            time::sleep(duration).await;
+
+            let response = WaitPaymentResponse {
+                payment_identifier: payment_hash_clone.clone(),
+                payment_amount: final_amount,
+                payment_id: payment_hash_clone.to_string(),
+            };
+            let mut incoming = incoming_payment.write().await;
+            incoming
+                .entry(payment_hash_clone.clone())
+                .or_insert_with(Vec::new)
+                .push(response.clone());
+
+            // Send the message after waiting for the specified duration
+            if sender.send(response.clone()).await.is_err() {
+                tracing::error!("Failed to send label: {:?}", payment_hash_clone);
+            }
+        });
+
+        // For any-amount invoices ONLY, also add to the secondary repayment queue
+        if is_any_amount {
+            tracing::info!(
+                "Adding any-amount invoice to secondary repayment queue: {:?}",
+                payment_hash
+            );
+
+            self.secondary_repayment_queue
+                .enqueue_for_repayment(payment_hash.clone())
+                .await;
+        }
+    }
+
     fn fee_for_amount(&self, amount: &Amount<CurrencyUnit>) -> Amount<CurrencyUnit> {
         let relative_fee_reserve =
             (self.fee_reserve.percent_fee_reserve * amount.value() as f32) as u64;
@@ -488,6 +570,10 @@ impl Default for FakeInvoiceDescription {
 impl MintPayment for FakeWallet {
     type Err = payment::Error;
 
+    fn backend_name(&self) -> String {
+        "fake-wallet".to_string()
+    }
+
     #[instrument(skip_all)]
     async fn get_settings(&self) -> Result<SettingsResponse, Self::Err> {
         Ok(SettingsResponse {
@@ -507,6 +593,18 @@ impl MintPayment for FakeWallet {
         })
     }
 
+    #[instrument(skip_all)]
+    async fn outbound_liquidity(
+        &self,
+        unit: &CurrencyUnit,
+    ) -> Result<Option<Amount<CurrencyUnit>>, Self::Err> {
+        Ok(self
+            .outbound_liquidity
+            .as_ref()
+            .map(|liquidity| liquidity.convert_to(unit))
+            .transpose()?)
+    }
+
     #[instrument(skip_all)]
     fn is_payment_event_stream_active(&self) -> bool {
         self.wait_invoice_is_active.load(Ordering::SeqCst)
@@ -605,6 +703,23 @@ impl MintPayment for FakeWallet {
                     fee_options: None,
                 });
             }
+            OutgoingPaymentOptions::Keysend(keysend_options) => {
+                self.ensure_custom_method_supported(KEYSEND_METHOD)?;
+
+                let request_lookup_id = keysend_options.preimage.map(|preimage| {
+                    PaymentIdentifier::PaymentHash(sha256::Hash::hash(&preimage).to_byte_array())
+                });
+
+                return Ok(PaymentQuoteResponse {
+                    request_lookup_id,
+                    fee: self.fee_for_amount(&keysend_options.amount),
+                    amount: keysend_options.amount,
+                    state: MeltQuoteState::Unpaid,
+                    extra_json: None,
+                    estimated_blocks: None,
+                    fee_options: None,
+                });
+            }
             OutgoingPaymentOptions::Onchain(onchain_options) => {
                 let fee = self.fee_for_amount(&onchain_options.amount);
 
@@ -773,6 +888,30 @@ impl MintPayment for FakeWallet {
                     total_spent: Amount::new(total_spent.value() + 1, unit.clone()),
                 })
             }
+            OutgoingPaymentOptions::Keysend(keysend_options) => {
+                self.ensure_custom_method_supported(KEYSEND_METHOD)?;
+
+                let preimage = keysend_options.required_preimage()?;
+                let payment_hash = sha256::Hash::hash(&preimage).to_byte_array();
+                let payment_lookup_id = PaymentIdentifier::PaymentHash(payment_hash);
+
+                let total_spent = Amount::new(
+                    keysend_options.amount.value() + 1,
+                    keysend_options.amount.unit().clone(),
+                );
+
+                self.payment_states.lock().await.insert(
+                    payment_lookup_id.to_string(),
+                    (MeltQuoteState::Paid, total_spent.clone()),
+                );
+
+                Ok(MakePaymentResponse {
+                    payment_lookup_id,
+                    payment_proof: Some(hex::encode(preimage)),
+                    status: MeltQuoteState::Paid,
+                    total_spent,
+                })
+            }
             OutgoingPaymentOptions::Onchain(onchain_options) => {
                 let amount = onchain_options.amount;
                 let quote_id = onchain_options.quote_id;
@@ -885,68 +1024,77 @@ impl MintPayment for FakeWallet {
         };
 
         // ALL invoices get immediate payment processing (original behavior)
-        let sender = self.sender.clone();
-        let duration = time::Duration::from_secs(self.payment_delay);
-        let payment_hash_clone = payment_hash.clone();
-        let incoming_payment = self.incoming_payments.clone();
+        self.schedule_incoming_payment(payment_hash.clone(), amount)
+            .await;
 
-        let final_amount = if amount.value() == 0 {
-            // For any-amount invoices, generate a random amount for the initial payment
-            use bitcoin::secp256k1::rand::rngs::OsRng;
-            use bitcoin::secp256k1::rand::Rng;
-            let mut rng = OsRng;
-            let random_amount: u64 = rng.gen_range(1000..=10000);
-            // Use the same unit as the invoice for any-amount invoices
-            Amount::new(random_amount, amount.unit().clone())
-        } else {
-            amount
-        };
-
-        // Check if this is an any-amount invoice before moving final_amount
-        let is_any_amount = final_amount.value() == 0;
-
-        // Schedule the immediate payment (original behavior maintained)
-        tokio::spawn(async move {
-            // Wait for the random delay to elapse
-            time::sleep(duration).await;
+        Ok(CreateIncomingPaymentResponse {
+            request_lookup_id: payment_hash,
+            request,
+            expiry,
+            extra_json: None,
+        })
+    }
 
-            let response = WaitPaymentResponse {
-                payment_identifier: payment_hash_clone.clone(),
-                payment_amount: final_amount,
-                payment_id: payment_hash_clone.to_string(),
-            };
-            let mut incoming = incoming_payment.write().await;
-            incoming
-                .entry(payment_hash_clone.clone())
-                .or_insert_with(Vec::new)
-                .push(response.clone());
+    fn supports_hold_invoices(&self) -> bool {
+        true
+    }
 
-            // Send the message after waiting for the specified duration
-            if sender.send(response.clone()).await.is_err() {
-                tracing::error!("Failed to send label: {:?}", payment_hash_clone);
-            }
-        });
+    #[instrument(skip_all)]
+    async fn create_hold_invoice(
+        &self,
+        options: Bolt11IncomingPaymentOptions,
+        payment_hash: [u8; 32],
+    ) -> Result<CreateIncomingPaymentResponse, Self::Err> {
+        let amount_msat = convert_currency_amount(
+            options.amount.value(),
+            options.amount.unit(),
+            &CurrencyUnit::Msat,
+            &self.exchange_rate_cache,
+        )
+        .await?;
 
-        // For any-amount invoices ONLY, also add to the secondary repayment queue
-        if is_any_amount {
-            tracing::info!(
-                "Adding any-amount invoice to secondary repayment queue: {:?}",
-                payment_hash
-            );
+        let invoice = create_fake_invoice_with_payment_hash(
+            amount_msat.value(),
+            options.description.unwrap_or_default(),
+            sha256::Hash::from_byte_array(payment_hash),
+        );
+        self.hold_invoices.lock().await.insert(payment_hash, false);
 
-            self.secondary_repayment_queue
-                .enqueue_for_repayment(payment_hash.clone())
-                .await;
-        }
+        // The payment is accepted right away, as with other invoices
+        let request_lookup_id = PaymentIdentifier::PaymentHash(payment_hash);
+        self.schedule_incoming_payment(request_lookup_id.clone(), options.amount)
+            .await;
 
         Ok(CreateIncomingPaymentResponse {
-            request_lookup_id: payment_hash,
-            request,
-            expiry,
+            request_lookup_id,
+            request: invoice.to_string(),
+            expiry: options.unix_expiry,
             extra_json: None,
         })
     }
 
+    #[instrument(skip_all)]
+    async fn settle_hold_invoice(&self, preimage: [u8; 32]) -> Result<(), Self::Err> {
+        let payment_hash = sha256::Hash::hash(&preimage).to_byte_array();
+        let mut hold_invoices = self.hold_invoices.lock().await;
+        let settled = hold_invoices
+            .get_mut(&payment_hash)
+            .ok_or(Error::UnknownInvoice)?;
+        *settled = true;
+        Ok(())
+    }
+
+    #[instrument(skip_all)]
+    async fn cancel_hold_invoice(&self, payment_hash: [u8; 32]) -> Result<(), Self::Err> {
+        let mut hold_invoices = self.hold_invoices.lock().await;
+        ensure_cdk!(
+            hold_invoices.get(&payment_hash) != Some(&true),
+            payment::Error::InvoiceAlreadyPaid
+        );
+        hold_invoices.remove(&payment_hash);
+        Ok(())
+    }
+
     #[instrument(skip_all)]
     async fn check_incoming_payment_status(
         &self,
@@ -1002,6 +1150,27 @@ impl MintPayment for FakeWallet {
 /// Panics if the hardcoded secret key or payment hash bytes are invalid.
 #[instrument]
 pub fn create_fake_invoice(amount_msat: u64, description: String) -> Bolt11Invoice {
+    use bitcoin::secp256k1::rand::rngs::OsRng;
+    use bitcoin::secp256k1::rand::Rng;
+    let mut rng = OsRng;
+    let mut random_bytes = [0u8; 32];
+    rng.fill(&mut random_bytes);
+
+    let payment_hash = sha256::Hash::from_slice(&random_bytes).expect("Valid 32-byte hash input");
+
+    create_fake_invoice_with_payment_hash(amount_msat, description, payment_hash)
+}
+
+/// Create fake invoice paying to `payment_hash`
+///
+/// # Panics
+///
+/// Panics if the hardcoded secret key bytes are invalid.
+pub fn create_fake_invoice_with_payment_hash(
+    amount_msat: u64,
+    description: String,
+    payment_hash: sha256::Hash,
+) -> Bolt11Invoice {
     let private_key = SecretKey::from_slice(
         &[
             0xe1, 0x26, 0xf6, 0x8f, 0x7e, 0xaf, 0xcc, 0x8b, 0x74, 0xf5, 0x4d, 0x26, 0x9f, 0xe2,
@@ -1010,14 +1179,6 @@ pub fn create_fake_invoice(amount_msat: u64, description: String) -> Bolt11Invoi
         ][..],
     )
     .expect("Valid 32-byte secret key");
-
-    use bitcoin::secp256k1::rand::rngs::OsRng;
-    use bitcoin::secp256k1::rand::Rng;
-    let mut rng = OsRng;
-    let mut random_bytes = [0u8; 32];
-    rng.fill(&mut random_bytes);
-
-    let payment_hash = sha256::Hash::from_slice(&random_bytes).expect("Valid 32-byte hash input");
     let payment_secret = PaymentSecret([42u8; 32]);
 
     InvoiceBuilder::new(Currency::Bitcoin)
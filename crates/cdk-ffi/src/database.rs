@@ -8,6 +8,7 @@ use cdk_common::database::WalletDatabase as CdkWalletDatabase;
 use cdk_common::wallet::WalletSaga;
 
 use crate::error::FfiError;
+use crate::memory::WalletMemoryDatabase;
 #[cfg(feature = "postgres")]
 use crate::postgres::WalletPostgresDatabase;
 use crate::sqlite::WalletSqliteDatabase;
@@ -159,6 +160,17 @@ pub trait WalletDatabase: Send + Sync {
     /// Remove transaction from storage
     async fn remove_transaction(&self, transaction_id: TransactionId) -> Result<(), FfiError>;
 
+    /// Remove keysets of removed mints and keys of removed keysets
+    ///
+    /// Returns the number of removed keysets and keys. Implementations without storage
+    /// maintenance can return 0.
+    async fn remove_orphaned_keysets(&self) -> Result<u64, FfiError>;
+
+    /// Reclaim storage space freed by removed records
+    ///
+    /// Implementations without storage maintenance can do nothing.
+    async fn vacuum(&self) -> Result<(), FfiError>;
+
     /// Update mint url
     async fn update_mint_url(
         &self,
@@ -910,6 +922,20 @@ impl CdkWalletDatabase<cdk::cdk_database::Error> for WalletDatabaseBridge {
             .map_err(|e| cdk::cdk_database::Error::Database(e.to_string().into()))
     }
 
+    async fn remove_orphaned_keysets(&self) -> Result<u64, cdk::cdk_database::Error> {
+        self.ffi_db
+            .remove_orphaned_keysets()
+            .await
+            .map_err(|e| cdk::cdk_database::Error::Database(e.to_string().into()))
+    }
+
+    async fn vacuum(&self) -> Result<(), cdk::cdk_database::Error> {
+        self.ffi_db
+            .vacuum()
+            .await
+            .map_err(|e| cdk::cdk_database::Error::Database(e.to_string().into()))
+    }
+
     async fn add_saga(&self, saga: WalletSaga) -> Result<(), cdk::cdk_database::Error> {
         let json = serde_json::to_string(&saga)
             .map_err(|e| cdk::cdk_database::Error::Database(e.to_string().into()))?;
@@ -1174,7 +1200,6 @@ where
     ///
     /// This is useful for accessing database-specific methods that are not part
     /// of the standard WalletDatabase trait (e.g., Supabase JWT token management).
-    #[cfg(feature = "supabase")]
     pub fn inner(&self) -> &T {
         &self.inner
     }
@@ -1550,6 +1575,17 @@ where
             .map_err(FfiError::internal)
     }
 
+    async fn remove_orphaned_keysets(&self) -> Result<u64, FfiError> {
+        self.inner
+            .remove_orphaned_keysets()
+            .await
+            .map_err(FfiError::internal)
+    }
+
+    async fn vacuum(&self) -> Result<(), FfiError> {
+        self.inner.vacuum().await.map_err(FfiError::internal)
+    }
+
     async fn update_mint_url(
         &self,
         old_mint_url: MintUrl,
@@ -1971,6 +2007,14 @@ macro_rules! impl_ffi_wallet_database {
                 self.inner.remove_transaction(transaction_id).await
             }
 
+            async fn remove_orphaned_keysets(&self) -> Result<u64, FfiError> {
+                self.inner.remove_orphaned_keysets().await
+            }
+
+            async fn vacuum(&self) -> Result<(), FfiError> {
+                self.inner.vacuum().await
+            }
+
             async fn update_mint_url(
                 &self,
                 old_mint_url: MintUrl,
@@ -2137,6 +2181,8 @@ pub enum WalletDbBackend {
     Sqlite {
         path: String,
     },
+    /// Ephemeral database that keeps everything in memory
+    Memory,
     #[cfg(feature = "postgres")]
     Postgres {
         url: String,
@@ -2154,6 +2200,8 @@ pub enum WalletStore {
     Sqlite {
         path: String,
     },
+    /// Ephemeral database that keeps everything in memory
+    Memory,
     #[cfg(feature = "postgres")]
     Postgres {
         url: String,
@@ -2169,6 +2217,12 @@ pub fn sqlite_wallet_store(path: String) -> WalletStore {
     WalletStore::Sqlite { path }
 }
 
+/// Create an in-memory wallet store. Nothing is persisted.
+#[uniffi::export]
+pub fn memory_wallet_store() -> WalletStore {
+    WalletStore::Memory
+}
+
 /// Create a PostgreSQL-backed wallet store.
 #[cfg(feature = "postgres")]
 #[uniffi::export]
@@ -2189,6 +2243,7 @@ pub fn resolve_wallet_store(store: WalletStore) -> Result<Arc<dyn WalletDatabase
             let sqlite = WalletSqliteDatabase::new(path)?;
             Ok(sqlite as Arc<dyn WalletDatabase>)
         }
+        WalletStore::Memory => Ok(WalletMemoryDatabase::new() as Arc<dyn WalletDatabase>),
         #[cfg(feature = "postgres")]
         WalletStore::Postgres { url } => {
             let pg = WalletPostgresDatabase::new(url)?;
@@ -2206,6 +2261,7 @@ pub fn create_wallet_db(backend: WalletDbBackend) -> Result<Arc<dyn WalletDataba
             let sqlite = WalletSqliteDatabase::new(path)?;
             Ok(sqlite as Arc<dyn WalletDatabase>)
         }
+        WalletDbBackend::Memory => Ok(WalletMemoryDatabase::new() as Arc<dyn WalletDatabase>),
         #[cfg(feature = "postgres")]
         WalletDbBackend::Postgres { url } => {
             let pg = WalletPostgresDatabase::new(url)?;
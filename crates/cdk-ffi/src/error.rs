@@ -29,6 +29,32 @@ pub enum FfiError {
     },
 }
 
+/// Reason a mint URL was rejected
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+pub enum MintUrlError {
+    /// Not a URL
+    #[error("Invalid URL: {url}")]
+    InvalidUrl {
+        /// The rejected URL
+        url: String,
+    },
+    /// Scheme other than http and https
+    #[error("Unsupported scheme `{scheme}`")]
+    UnsupportedScheme {
+        /// The rejected scheme
+        scheme: String,
+    },
+    /// URL without a host
+    #[error("URL has no host")]
+    MissingHost,
+}
+
+impl From<MintUrlError> for FfiError {
+    fn from(err: MintUrlError) -> Self {
+        FfiError::internal(err)
+    }
+}
+
 impl FfiError {
     /// Create an internal error from any type that implements ToString
     pub fn internal(msg: impl ToString) -> Self {
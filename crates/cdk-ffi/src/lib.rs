@@ -10,6 +10,7 @@ pub mod bip321;
 pub mod database;
 pub mod error;
 pub mod logging;
+pub mod memory;
 #[cfg(feature = "npubcash")]
 pub mod npubcash;
 #[cfg(feature = "postgres")]
@@ -21,6 +22,7 @@ pub mod supabase;
 pub mod token;
 pub mod types;
 pub mod wallet;
+pub mod wallet_manager;
 pub mod wallet_repository;
 mod wallet_trait;
 
@@ -31,6 +33,7 @@ pub use logging::*;
 pub use npubcash::*;
 pub use types::*;
 pub use wallet::*;
+pub use wallet_manager::*;
 pub use wallet_repository::*;
 
 uniffi::setup_scaffolding!();
@@ -204,6 +207,7 @@ mod tests {
             },
             include_fee: true,
             max_proofs: Some(10),
+            max_token_bytes: Some(1024),
             metadata,
             use_p2bk: false,
             p2pk_signing_keys: Vec::new(),
@@ -240,6 +244,8 @@ mod tests {
             p2pk_signing_keys: vec![secret_key],
             preimages: vec!["preimage1".to_string(), "preimage2".to_string()],
             metadata,
+            ignore_fee_threshold: true,
+            quarantine_risk_score: None,
         };
 
         assert!(matches!(
@@ -260,6 +266,8 @@ mod tests {
             }],
             preimages: Vec::new(),
             metadata: Default::default(),
+            ignore_fee_threshold: false,
+            quarantine_risk_score: None,
         };
 
         let result: Result<cdk::wallet::ReceiveOptions, _> = options.try_into();
@@ -495,11 +503,15 @@ mod tests {
     fn test_wallet_config() {
         let config = WalletConfig {
             target_proof_count: None,
+            mnemonic_passphrase: None,
+            max_receive_fee_ppk: None,
         };
         assert!(config.target_proof_count.is_none());
 
         let config_with_values = WalletConfig {
             target_proof_count: Some(5),
+            mnemonic_passphrase: Some("passphrase".to_string()),
+            max_receive_fee_ppk: None,
         };
         assert_eq!(config_with_values.target_proof_count, Some(5));
     }
@@ -0,0 +1,33 @@
+use std::sync::Arc;
+
+use cdk_common::database::{
+    Error as CdkDatabaseError, WalletMemoryDatabase as CdkWalletMemoryDatabase,
+};
+
+use crate::{
+    CurrencyUnit, FfiError, FfiWalletDatabaseWrapper, Id, KeySet, KeySetInfo, Keys, MeltQuote,
+    MintInfo, MintQuote, MintUrl, P2PKSigningKey, ProofInfo, ProofState, PublicKey,
+    SpendingConditions, Transaction, TransactionDirection, TransactionId, WalletDatabase,
+};
+
+/// FFI-compatible in-memory wallet database
+///
+/// Nothing is persisted, all records are lost once the database is dropped.
+#[derive(uniffi::Object)]
+pub struct WalletMemoryDatabase {
+    inner: Arc<FfiWalletDatabaseWrapper<CdkWalletMemoryDatabase, CdkDatabaseError>>,
+}
+
+#[uniffi::export]
+impl WalletMemoryDatabase {
+    /// Create a new empty WalletMemoryDatabase
+    #[uniffi::constructor]
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self {
+            inner: FfiWalletDatabaseWrapper::new(CdkWalletMemoryDatabase::new()),
+        })
+    }
+}
+
+// Use macro to implement WalletDatabase trait - delegates all methods to inner
+crate::impl_ffi_wallet_database!(WalletMemoryDatabase);
@@ -4,8 +4,8 @@ use cdk_common::database::Error as CdkDatabaseError;
 use cdk_postgres::WalletPgDatabase;
 
 use crate::{
-    CurrencyUnit, FfiError, FfiWalletDatabaseWrapper, Id, KeySet, KeySetInfo, Keys, MeltQuote,
-    MintInfo, MintQuote, MintUrl, P2PKSigningKey, ProofInfo, ProofState, PublicKey,
+    CurrencyUnit, DatabaseHealth, FfiError, FfiWalletDatabaseWrapper, Id, KeySet, KeySetInfo, Keys,
+    MeltQuote, MintInfo, MintQuote, MintUrl, P2PKSigningKey, ProofInfo, ProofState, PublicKey,
     SpendingConditions, Transaction, TransactionDirection, TransactionId, WalletDatabase,
 };
 
@@ -36,5 +36,19 @@ impl WalletPostgresDatabase {
     }
 }
 
+#[uniffi::export(async_runtime = "tokio")]
+impl WalletPostgresDatabase {
+    /// Report the schema version, pending migrations and row counts
+    pub async fn health_check(&self) -> Result<DatabaseHealth, FfiError> {
+        Ok(self
+            .inner
+            .inner()
+            .health_check()
+            .await
+            .map_err(FfiError::internal)?
+            .into())
+    }
+}
+
 // Use macro to implement WalletDatabase trait - delegates all methods to inner
 crate::impl_ffi_wallet_database!(WalletPostgresDatabase);
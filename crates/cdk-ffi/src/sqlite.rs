@@ -4,8 +4,8 @@ use cdk_common::database::Error as CdkDatabaseError;
 use cdk_sqlite::wallet::WalletSqliteDatabase as CdkWalletSqliteDatabase;
 
 use crate::{
-    CurrencyUnit, FfiError, FfiWalletDatabaseWrapper, Id, KeySet, KeySetInfo, Keys, MeltQuote,
-    MintInfo, MintQuote, MintUrl, P2PKSigningKey, ProofInfo, ProofState, PublicKey,
+    CurrencyUnit, DatabaseHealth, FfiError, FfiWalletDatabaseWrapper, Id, KeySet, KeySetInfo, Keys,
+    MeltQuote, MintInfo, MintQuote, MintUrl, P2PKSigningKey, ProofInfo, ProofState, PublicKey,
     SpendingConditions, Transaction, TransactionDirection, TransactionId, WalletDatabase,
 };
 
@@ -46,5 +46,33 @@ impl WalletSqliteDatabase {
     }
 }
 
+#[uniffi::export(async_runtime = "tokio")]
+impl WalletSqliteDatabase {
+    /// Snapshot the live database into a new file at `path`
+    ///
+    /// Safe to call while the wallet is in use, unlike copying the database file. The target
+    /// file must not exist yet.
+    pub async fn backup_to(&self, path: String) -> Result<(), FfiError> {
+        self.inner
+            .inner()
+            .backup_to(&path)
+            .await
+            .map_err(FfiError::internal)
+    }
+
+    /// Report the schema version, pending migrations, row counts and integrity check results
+    ///
+    /// Lets apps detect corrupted or outdated storage and surface it to the user.
+    pub async fn health_check(&self) -> Result<DatabaseHealth, FfiError> {
+        Ok(self
+            .inner
+            .inner()
+            .health_check()
+            .await
+            .map_err(FfiError::internal)?
+            .into())
+    }
+}
+
 // Use macro to implement WalletDatabase trait - delegates all methods to inner
 crate::impl_ffi_wallet_database!(WalletSqliteDatabase);
@@ -40,6 +40,47 @@ impl From<Token> for cdk::nuts::Token {
     }
 }
 
+/// FFI-compatible summary of a token, to render before claiming it
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct TokenSummary {
+    /// Mint that issued the token
+    pub mint_url: MintUrl,
+    /// Unit of the token
+    pub unit: Option<CurrencyUnit>,
+    /// Total amount of the proofs
+    pub amount: Amount,
+    /// Number of proofs
+    pub proof_count: u64,
+    /// Keyset ids of the proofs (sorted)
+    pub keyset_ids: Vec<String>,
+    /// Memo
+    pub memo: Option<String>,
+    /// Unique spending conditions of the proofs
+    pub spending_conditions: Vec<crate::types::SpendingConditions>,
+}
+
+impl From<cdk::nuts::TokenSummary> for TokenSummary {
+    fn from(summary: cdk::nuts::TokenSummary) -> Self {
+        Self {
+            mint_url: summary.mint_url.into(),
+            unit: summary.unit.map(Into::into),
+            amount: summary.amount.into(),
+            proof_count: summary.proof_count as u64,
+            keyset_ids: summary
+                .keyset_ids
+                .into_iter()
+                .map(|id| id.to_string())
+                .collect(),
+            memo: summary.memo,
+            spending_conditions: summary
+                .spending_conditions
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        }
+    }
+}
+
 #[uniffi::export]
 impl Token {
     /// Create a new Token from string
@@ -70,6 +111,11 @@ impl Token {
         Ok(self.inner.mint_url()?.into())
     }
 
+    /// Summarize the token without contacting the mint
+    pub fn summary(&self) -> Result<TokenSummary, FfiError> {
+        Ok(self.inner.summary()?.into())
+    }
+
     /// Get proofs from the token (simplified - no keyset filtering for now)
     pub fn proofs_simple(&self) -> Result<Proofs, FfiError> {
         // For now, return empty keysets to get all proofs
@@ -182,3 +182,110 @@ impl From<cdk::amount::SplitTarget> for SplitTarget {
         }
     }
 }
+
+/// FFI-compatible Locale used to format amounts
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, uniffi::Record)]
+pub struct Locale {
+    /// Separator between the integer and the fractional part, a single character
+    pub decimal_separator: String,
+    /// Separator between groups of thousands, a single character
+    pub group_separator: Option<String>,
+    /// Whether the currency symbol goes before the number
+    pub symbol_first: bool,
+}
+
+/// Preset locales
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, uniffi::Enum)]
+pub enum LocalePreset {
+    /// English, `$1,234.56`
+    En,
+    /// German, `1.234,56 €`
+    De,
+    /// French, `1 234,56 €`
+    Fr,
+}
+
+fn single_char(value: &str) -> Result<char, FfiError> {
+    let mut chars = value.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(c),
+        _ => Err(FfiError::internal(format!(
+            "Separator must be a single character: `{}`",
+            value
+        ))),
+    }
+}
+
+impl TryFrom<Locale> for cdk_common::format::Locale {
+    type Error = FfiError;
+
+    fn try_from(locale: Locale) -> Result<Self, Self::Error> {
+        Ok(Self::new(
+            single_char(&locale.decimal_separator)?,
+            locale
+                .group_separator
+                .as_deref()
+                .map(single_char)
+                .transpose()?,
+            locale.symbol_first,
+        ))
+    }
+}
+
+impl From<cdk_common::format::Locale> for Locale {
+    fn from(locale: cdk_common::format::Locale) -> Self {
+        Self {
+            decimal_separator: locale.decimal_separator.to_string(),
+            group_separator: locale.group_separator.map(|c| c.to_string()),
+            symbol_first: locale.symbol_first,
+        }
+    }
+}
+
+/// Get a preset locale
+#[uniffi::export]
+pub fn locale_preset(preset: LocalePreset) -> Locale {
+    match preset {
+        LocalePreset::En => cdk_common::format::Locale::EN,
+        LocalePreset::De => cdk_common::format::Locale::DE,
+        LocalePreset::Fr => cdk_common::format::Locale::FR,
+    }
+    .into()
+}
+
+/// Format an amount in the smallest denomination of `unit` for display
+#[uniffi::export]
+pub fn format_amount(
+    amount: Amount,
+    unit: CurrencyUnit,
+    locale: Locale,
+) -> Result<String, FfiError> {
+    let amount = cdk_common::Amount::new(amount.value, unit.into());
+    Ok(cdk_common::format::format_amount(
+        &amount,
+        &locale.try_into()?,
+    ))
+}
+
+/// Parse an amount typed by a user into the smallest denomination of `unit`
+///
+/// Input more precise than the unit is refused rather than rounded.
+#[uniffi::export]
+pub fn parse_amount(input: String, unit: CurrencyUnit, locale: Locale) -> Result<Amount, FfiError> {
+    let amount = cdk_common::format::parse_amount(&input, &unit.into(), &locale.try_into()?)
+        .map_err(FfiError::internal)?;
+    Ok(Amount::new(amount.value()))
+}
+
+/// Convert an amount between sat and msat, failing instead of rounding
+#[uniffi::export]
+pub fn convert_amount_lossless(
+    amount: Amount,
+    unit: CurrencyUnit,
+    target_unit: CurrencyUnit,
+) -> Result<Amount, FfiError> {
+    let amount = cdk_common::Amount::new(amount.value, unit.into());
+    let converted = cdk_common::format::convert_lossless(&amount, &target_unit.into())
+        .map_err(FfiError::internal)?;
+    Ok(Amount::new(converted.value()))
+}
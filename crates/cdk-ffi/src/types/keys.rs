@@ -41,6 +41,8 @@ impl TryFrom<KeySetInfo> for cdk::nuts::KeySetInfo {
             active: keyset.active,
             final_expiry: None,
             input_fee_ppk: keyset.input_fee_ppk,
+            rotation_time: None,
+            retirement_time: None,
         })
     }
 }
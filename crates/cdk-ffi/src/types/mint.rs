@@ -1,12 +1,13 @@
 //! Mint-related FFI types
 
+use std::collections::HashMap;
 use std::str::FromStr;
 
 use serde::{Deserialize, Serialize};
 
 use super::amount::{Amount, CurrencyUnit};
 use super::quote::PaymentMethod;
-use crate::error::FfiError;
+use crate::error::{FfiError, MintUrlError};
 
 /// FFI-compatible Mint URL
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, uniffi::Record)]
@@ -16,14 +17,40 @@ pub struct MintUrl {
 }
 
 impl MintUrl {
+    /// Validated and normalized mint URL, see [`normalize_mint_url`]
     pub fn new(url: String) -> Result<Self, FfiError> {
-        // Validate URL format
-        url::Url::parse(&url).map_err(|e| FfiError::internal(format!("Invalid URL: {}", e)))?;
-
-        Ok(Self { url })
+        Ok(normalize_mint_url(url)?)
     }
 }
 
+/// Parse a mint URL, failing unless it is an http or https URL with a host
+///
+/// The URL is normalized the way the wallet keys its mints: the scheme and host are lower cased,
+/// internationalized domains are punycode encoded and trailing slashes are removed.
+#[uniffi::export]
+pub fn normalize_mint_url(url: String) -> Result<MintUrl, MintUrlError> {
+    let mint_url = cdk::mint_url::MintUrl::from_str(&url)
+        .map_err(|_| MintUrlError::InvalidUrl { url: url.clone() })?;
+
+    mint_url.validate().map_err(|err| match err {
+        cdk::mint_url::Error::UnsupportedScheme(scheme) => {
+            MintUrlError::UnsupportedScheme { scheme }
+        }
+        cdk::mint_url::Error::MissingHost => MintUrlError::MissingHost,
+        _ => MintUrlError::InvalidUrl { url },
+    })?;
+
+    Ok(mint_url.into())
+}
+
+/// Whether the mint is reached through a Tor onion service
+#[uniffi::export]
+pub fn mint_url_is_onion(mint_url: MintUrl) -> Result<bool, MintUrlError> {
+    Ok(cdk::mint_url::MintUrl::from_str(&mint_url.url)
+        .map_err(|_| MintUrlError::InvalidUrl { url: mint_url.url })?
+        .is_onion())
+}
+
 impl From<cdk::mint_url::MintUrl> for MintUrl {
     fn from(mint_url: cdk::mint_url::MintUrl) -> Self {
         Self {
@@ -497,6 +524,9 @@ pub struct Nuts {
     pub nut22: Option<BlindAuthSettings>,
     /// NUT29 Settings - Batch minting
     pub nut29: Nut29Settings,
+    /// Largest proof amount issued for each unit, keyed by unit
+    #[serde(default)]
+    pub max_denominations: HashMap<String, u64>,
     /// Supported currency units for minting
     pub mint_units: Vec<CurrencyUnit>,
     /// Supported currency units for melting
@@ -530,6 +560,11 @@ impl From<cdk::nuts::Nuts> for Nuts {
             nut21: nuts.nut21.map(Into::into),
             nut22: nuts.nut22.map(Into::into),
             nut29: nuts.nut29.into(),
+            max_denominations: nuts
+                .max_denominations
+                .into_iter()
+                .map(|(unit, amount)| (unit.to_string(), amount.to_u64()))
+                .collect(),
             mint_units,
             melt_units,
         }
@@ -573,6 +608,15 @@ impl TryFrom<Nuts> for cdk::nuts::Nuts {
             nut21: n.nut21.map(|s| s.try_into()).transpose()?,
             nut22: n.nut22.map(|s| s.try_into()).transpose()?,
             nut29: n.nut29.into(),
+            max_denominations: n
+                .max_denominations
+                .into_iter()
+                .map(|(unit, amount)| {
+                    cdk::nuts::CurrencyUnit::from_str(&unit)
+                        .map(|unit| (unit, cdk::Amount::from(amount)))
+                        .map_err(|e| FfiError::internal(format!("Invalid unit: {}", e)))
+                })
+                .collect::<Result<_, _>>()?,
         })
     }
 }
@@ -623,6 +667,11 @@ pub struct MintInfo {
     pub time: Option<u64>,
     /// terms of url service of the mint
     pub tos_url: Option<String>,
+    /// bitcoin network the mint settles payments on (bitcoin, testnet, signet or regtest)
+    pub network: Option<String>,
+    /// mint is in read-only maintenance mode and refuses new mint quotes
+    #[serde(default)]
+    pub read_only: bool,
 }
 
 impl From<cdk::nuts::MintInfo> for MintInfo {
@@ -642,6 +691,8 @@ impl From<cdk::nuts::MintInfo> for MintInfo {
             motd: info.motd,
             time: info.time,
             tos_url: info.tos_url,
+            network: info.network.map(|network| network.to_string()),
+            read_only: info.read_only,
         }
     }
 }
@@ -665,6 +716,8 @@ impl TryFrom<MintInfo> for cdk::nuts::MintInfo {
             motd: info.motd,
             time: info.time,
             tos_url: info.tos_url,
+            network: info.network.and_then(|network| network.parse().ok()),
+            read_only: info.read_only,
         })
     }
 }
@@ -748,9 +801,28 @@ mod tests {
                 )],
             }),
             nut29: Default::default(),
+            max_denominations: Default::default(),
         }
     }
 
+    #[test]
+    fn test_normalize_mint_url() {
+        let url = normalize_mint_url("HTTPS://Mint.Example.com/Path/".to_string()).unwrap();
+        assert_eq!(url.url, "https://mint.example.com/Path");
+
+        let url = normalize_mint_url("http://mintaddress.onion".to_string()).unwrap();
+        assert!(mint_url_is_onion(url).unwrap());
+
+        assert!(matches!(
+            normalize_mint_url("ftp://mint.example.com".to_string()),
+            Err(MintUrlError::UnsupportedScheme { scheme }) if scheme == "ftp"
+        ));
+        assert!(matches!(
+            normalize_mint_url("not-a-url".to_string()),
+            Err(MintUrlError::InvalidUrl { .. })
+        ));
+    }
+
     #[test]
     fn test_nuts_from_cdk_to_ffi() {
         let cdk_nuts = create_sample_cdk_nuts();
@@ -888,6 +960,7 @@ mod tests {
             nut21: None,
             nut22: None,
             nut29: Default::default(),
+            max_denominations: Default::default(),
         };
 
         let ffi_nuts: Nuts = cdk_nuts.into();
@@ -920,6 +993,7 @@ mod tests {
             nut21: None,
             nut22: None,
             nut29: Default::default(),
+            max_denominations: Default::default(),
             mint_units: vec![],
             melt_units: vec![],
         };
@@ -1081,6 +1155,7 @@ mod tests {
                     }],
                 }),
                 nut29: Nut29Settings::default(),
+                max_denominations: Default::default(),
                 mint_units: vec![],
                 melt_units: vec![],
             },
@@ -1089,6 +1164,8 @@ mod tests {
             motd: None,
             time: None,
             tos_url: None,
+            network: None,
+            read_only: false,
         };
 
         let result = cdk::nuts::MintInfo::try_from(ffi_mint_info);
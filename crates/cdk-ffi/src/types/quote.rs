@@ -555,6 +555,7 @@ pub enum QuoteState {
     Paid,
     Pending,
     Issued,
+    Cancelled,
 }
 
 impl From<cdk::nuts::nut05::QuoteState> for QuoteState {
@@ -576,6 +577,7 @@ impl From<QuoteState> for cdk::nuts::nut05::QuoteState {
             QuoteState::Paid => cdk::nuts::nut05::QuoteState::Paid,
             QuoteState::Pending => cdk::nuts::nut05::QuoteState::Pending,
             QuoteState::Issued => cdk::nuts::nut05::QuoteState::Paid, // Map issued to paid for melt quotes
+            QuoteState::Cancelled => cdk::nuts::nut05::QuoteState::Failed,
         }
     }
 }
@@ -586,6 +588,7 @@ impl From<cdk::nuts::MintQuoteState> for QuoteState {
             cdk::nuts::MintQuoteState::Unpaid => QuoteState::Unpaid,
             cdk::nuts::MintQuoteState::Paid => QuoteState::Paid,
             cdk::nuts::MintQuoteState::Issued => QuoteState::Issued,
+            cdk::nuts::MintQuoteState::Cancelled => QuoteState::Cancelled,
         }
     }
 }
@@ -596,6 +599,7 @@ impl From<QuoteState> for cdk::nuts::MintQuoteState {
             QuoteState::Unpaid => cdk::nuts::MintQuoteState::Unpaid,
             QuoteState::Paid => cdk::nuts::MintQuoteState::Paid,
             QuoteState::Issued => cdk::nuts::MintQuoteState::Issued,
+            QuoteState::Cancelled => cdk::nuts::MintQuoteState::Cancelled,
             QuoteState::Pending => cdk::nuts::MintQuoteState::Unpaid,
         }
     }
@@ -193,6 +193,9 @@ pub struct SendOptions {
     pub use_p2bk: bool,
     /// Maximum number of proofs to include in the token
     pub max_proofs: Option<u32>,
+    /// Maximum size in bytes of the serialized token
+    #[serde(default)]
+    pub max_token_bytes: Option<u32>,
     /// Metadata
     pub metadata: HashMap<String, String>,
     /// Signing keys for P2PK-locked input proofs
@@ -212,6 +215,7 @@ impl Default for SendOptions {
             send_kind: SendKind::OnlineExact,
             include_fee: false,
             max_proofs: None,
+            max_token_bytes: None,
             metadata: HashMap::new(),
             use_p2bk: false,
             p2pk_signing_keys: Vec::new(),
@@ -237,6 +241,7 @@ impl TryFrom<SendOptions> for cdk::wallet::SendOptions {
             send_kind: opts.send_kind.into(),
             include_fee: opts.include_fee,
             max_proofs: opts.max_proofs.map(|p| p as usize),
+            max_token_bytes: opts.max_token_bytes.map(|b| b as usize),
             metadata: opts.metadata,
             use_p2bk: opts.use_p2bk,
             p2pk_signing_keys,
@@ -254,6 +259,7 @@ impl From<cdk::wallet::SendOptions> for SendOptions {
             send_kind: opts.send_kind.into(),
             include_fee: opts.include_fee,
             max_proofs: opts.max_proofs.map(|p| p as u32),
+            max_token_bytes: opts.max_token_bytes.map(|b| b as u32),
             metadata: opts.metadata,
             use_p2bk: opts.use_p2bk,
             p2pk_signing_keys: opts.p2pk_signing_keys.into_iter().map(Into::into).collect(),
@@ -356,6 +362,12 @@ pub struct ReceiveOptions {
     pub preimages: Vec<String>,
     /// Metadata
     pub metadata: HashMap<String, String>,
+    /// Receive even when the claim fee is above the receive fee threshold of the wallet
+    #[serde(default)]
+    pub ignore_fee_threshold: bool,
+    /// Keep the received proofs out of the balance when their risk score reaches this value
+    #[serde(default)]
+    pub quarantine_risk_score: Option<u32>,
 }
 
 impl Default for ReceiveOptions {
@@ -365,6 +377,8 @@ impl Default for ReceiveOptions {
             p2pk_signing_keys: Vec::new(),
             preimages: Vec::new(),
             metadata: HashMap::new(),
+            ignore_fee_threshold: false,
+            quarantine_risk_score: None,
         }
     }
 }
@@ -384,6 +398,8 @@ impl TryFrom<ReceiveOptions> for cdk::wallet::ReceiveOptions {
             p2pk_signing_keys,
             preimages: opts.preimages,
             metadata: opts.metadata,
+            ignore_fee_threshold: opts.ignore_fee_threshold,
+            quarantine_risk_score: opts.quarantine_risk_score,
         })
     }
 }
@@ -395,6 +411,8 @@ impl From<cdk::wallet::ReceiveOptions> for ReceiveOptions {
             p2pk_signing_keys: opts.p2pk_signing_keys.into_iter().map(Into::into).collect(),
             preimages: opts.preimages,
             metadata: opts.metadata,
+            ignore_fee_threshold: opts.ignore_fee_threshold,
+            quarantine_risk_score: opts.quarantine_risk_score,
         }
     }
 }
@@ -817,6 +835,119 @@ impl From<cdk_common::wallet::Restored> for Restored {
     }
 }
 
+/// Keyset counter moved by a counter sync
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct KeysetCounterSync {
+    /// Keyset of the counter
+    pub keyset_id: String,
+    /// Counter before the sync
+    pub previous: u32,
+    /// Counter after the sync
+    pub counter: u32,
+}
+
+impl From<cdk::wallet::KeysetCounterSync> for KeysetCounterSync {
+    fn from(sync: cdk::wallet::KeysetCounterSync) -> Self {
+        Self {
+            keyset_id: sync.keyset_id.to_string(),
+            previous: sync.previous,
+            counter: sync.counter,
+        }
+    }
+}
+
+/// Wallet balance broken down by proof state and spending condition
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, uniffi::Record)]
+pub struct WalletBalance {
+    /// Unspent proofs without spending conditions, available to spend
+    pub spendable: Amount,
+    /// Unspent proofs locked by a P2PK or HTLC spending condition
+    pub locked: Amount,
+    /// Proofs reserved by a send in progress
+    pub reserved: Amount,
+    /// Proofs used by a melt or swap in progress
+    pub pending: Amount,
+    /// Proofs sent in a token that has not been claimed yet
+    pub pending_spent: Amount,
+}
+
+impl From<cdk::wallet::WalletBalance> for WalletBalance {
+    fn from(balance: cdk::wallet::WalletBalance) -> Self {
+        Self {
+            spendable: balance.spendable.into(),
+            locked: balance.locked.into(),
+            reserved: balance.reserved.into(),
+            pending: balance.pending.into(),
+            pending_spent: balance.pending_spent.into(),
+        }
+    }
+}
+
+/// FFI-compatible options for wallet storage maintenance
+#[derive(Debug, Clone, Serialize, Deserialize, uniffi::Record)]
+pub struct MaintenanceOptions {
+    /// Remove transactions older than this many seconds. All transactions are kept when unset.
+    #[uniffi(default = None)]
+    pub transaction_retention_secs: Option<u64>,
+    /// Reclaim free space in the database once records are removed
+    #[uniffi(default = true)]
+    pub vacuum: bool,
+}
+
+impl From<MaintenanceOptions> for cdk::wallet::MaintenanceOptions {
+    fn from(options: MaintenanceOptions) -> Self {
+        Self {
+            transaction_retention_secs: options.transaction_retention_secs,
+            vacuum: options.vacuum,
+        }
+    }
+}
+
+/// Report of the records removed by wallet storage maintenance
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, uniffi::Record)]
+pub struct MaintenanceReport {
+    /// Keysets and keys of mints that are no longer stored
+    pub removed_keysets: u64,
+    /// Transactions older than the retention period
+    pub removed_transactions: u64,
+}
+
+impl From<cdk::wallet::MaintenanceReport> for MaintenanceReport {
+    fn from(report: cdk::wallet::MaintenanceReport) -> Self {
+        Self {
+            removed_keysets: report.removed_keysets,
+            removed_transactions: report.removed_transactions,
+        }
+    }
+}
+
+/// Schema and storage health of a wallet database
+#[derive(Debug, Clone, PartialEq, Eq, Default, uniffi::Record)]
+pub struct DatabaseHealth {
+    /// Latest applied migration
+    pub schema_version: Option<String>,
+    /// Migrations that are not applied to the database
+    pub pending_migrations: Vec<String>,
+    /// Number of rows per table
+    pub row_counts: HashMap<String, u64>,
+    /// Problems found by the integrity check, empty if the database is intact
+    pub integrity_errors: Vec<String>,
+    /// Whether the schema is up to date and no integrity problems were found
+    pub healthy: bool,
+}
+
+impl From<cdk_sql_common::DatabaseHealth> for DatabaseHealth {
+    fn from(health: cdk_sql_common::DatabaseHealth) -> Self {
+        Self {
+            healthy: health.is_healthy(),
+            schema_version: health.schema_version,
+            pending_migrations: health.pending_migrations,
+            row_counts: health.row_counts.into_iter().collect(),
+            integrity_errors: health.integrity_errors,
+        }
+    }
+}
+
 /// Report of wallet saga recovery operations.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, uniffi::Record)]
 pub struct RecoveryReport {
@@ -841,6 +972,45 @@ impl From<cdk::wallet::RecoveryReport> for RecoveryReport {
     }
 }
 
+/// FFI-compatible outcome of minting one unissued quote during recovery
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct RecoveredMintQuote {
+    /// Id of the mint quote
+    pub quote_id: String,
+    /// Amount minted from the quote
+    pub minted: Amount,
+    /// Why the quote could not be checked or minted, if it failed
+    pub error: Option<String>,
+}
+
+impl From<cdk::wallet::RecoveredMintQuote> for RecoveredMintQuote {
+    fn from(quote: cdk::wallet::RecoveredMintQuote) -> Self {
+        Self {
+            quote_id: quote.quote_id,
+            minted: quote.minted.into(),
+            error: quote.error,
+        }
+    }
+}
+
+/// FFI-compatible report of recovering the quotes left unminted by a crash
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct UnmintedQuotesRecovery {
+    /// Incomplete sagas resumed first
+    pub sagas: RecoveryReport,
+    /// Paid quotes minted, or that failed to mint
+    pub quotes: Vec<RecoveredMintQuote>,
+    /// Total amount minted from the quotes
+    pub total_minted: Amount,
+}
+
+/// Listener notified of every quote handled by `Wallet::recover_unminted_quotes`
+#[uniffi::export(with_foreign)]
+pub trait UnmintedQuoteListener: Send + Sync {
+    /// Called once per quote minted, or that failed to mint
+    fn on_quote_recovered(&self, quote: RecoveredMintQuote);
+}
+
 /// FFI-compatible options for confirming a melt operation
 #[derive(Debug, Clone, Default, Serialize, Deserialize, uniffi::Record)]
 pub struct MeltConfirmOptions {
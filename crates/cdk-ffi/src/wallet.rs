@@ -51,9 +51,10 @@ impl Wallet {
         let db = crate::database::resolve_wallet_store(store)?;
         let localstore = crate::database::create_cdk_database_from_ffi(db);
 
-        let m = Mnemonic::parse(&mnemonic)
-            .map_err(|e| FfiError::internal(format!("Invalid mnemonic: {}", e)))?;
-        let seed = m.to_seed_normalized("");
+        let seed = mnemonic_to_seed(
+            &mnemonic,
+            config.mnemonic_passphrase.as_deref().unwrap_or_default(),
+        )?;
 
         let wallet = CdkWalletBuilder::new()
             .mint_url(mint_url.parse().map_err(|e: cdk::mint_url::Error| {
@@ -63,6 +64,7 @@ impl Wallet {
             .localstore(localstore)
             .seed(seed)
             .target_proof_count(config.target_proof_count.unwrap_or(3) as usize)
+            .max_receive_fee(config.max_receive_fee_ppk)
             .build()
             .map_err(FfiError::from)?;
 
@@ -122,6 +124,12 @@ impl Wallet {
         Ok(balance.into())
     }
 
+    /// Get balance broken down into spendable, locked, reserved and pending amounts
+    pub async fn balance_breakdown(&self) -> Result<WalletBalance, FfiError> {
+        let balance = self.inner.balance_breakdown().await?;
+        Ok(balance.into())
+    }
+
     /// Get mint info from mint
     pub async fn fetch_mint_info(&self) -> Result<Option<MintInfo>, FfiError> {
         let info = self.inner.fetch_mint_info().await?;
@@ -161,6 +169,13 @@ impl Wallet {
         Ok(restored.into())
     }
 
+    /// Move the keyset counters past the outputs the mint already signed, e.g. after restoring
+    /// an older backup
+    pub async fn sync_keyset_counters(&self) -> Result<Vec<KeysetCounterSync>, FfiError> {
+        let synced = self.inner.sync_keyset_counters().await?;
+        Ok(synced.into_iter().map(Into::into).collect())
+    }
+
     /// Verify token DLEQ proofs
     pub async fn verify_token_dleq(&self, token: std::sync::Arc<Token>) -> Result<(), FfiError> {
         let cdk_token = token.inner.clone();
@@ -649,6 +664,16 @@ impl Wallet {
         Ok(keyset.into())
     }
 
+    /// Swap proofs of keysets the mint rotates or retires within `within_secs` into the active
+    /// keyset, returns the amount migrated
+    pub async fn migrate_retiring_proofs(&self, within_secs: u64) -> Result<Amount, FfiError> {
+        let amount = self
+            .inner
+            .migrate_retiring_proofs(std::time::Duration::from_secs(within_secs))
+            .await?;
+        Ok(amount.into())
+    }
+
     /// Get fees and amounts for all keysets
     pub async fn get_keyset_fees_and_amounts(
         &self,
@@ -699,6 +724,41 @@ impl Wallet {
         Ok(report.into())
     }
 
+    /// Complete the mints left unfinished by a crash or a closed app
+    ///
+    /// Resumes the incomplete sagas, then mints every paid quote still unissued. `listener`
+    /// is notified of each quote as the report is returned.
+    pub async fn recover_unminted_quotes(
+        &self,
+        listener: Option<Arc<dyn UnmintedQuoteListener>>,
+    ) -> Result<UnmintedQuotesRecovery, FfiError> {
+        let recovery = self.inner.recover_unminted_quotes().await?;
+        let total_minted = recovery.total_minted()?;
+        let quotes: Vec<RecoveredMintQuote> = recovery.quotes.into_iter().map(Into::into).collect();
+
+        if let Some(listener) = listener {
+            for quote in &quotes {
+                listener.on_quote_recovered(quote.clone());
+            }
+        }
+
+        Ok(UnmintedQuotesRecovery {
+            sagas: recovery.sagas.into(),
+            quotes,
+            total_minted: total_minted.into(),
+        })
+    }
+
+    /// Remove orphaned keysets and keys, compact the transaction history and vacuum the
+    /// database
+    pub async fn maintenance(
+        &self,
+        options: MaintenanceOptions,
+    ) -> Result<MaintenanceReport, FfiError> {
+        let report = self.inner.maintenance(options.into()).await?;
+        Ok(report.into())
+    }
+
     /// Calculate fee for a given number of proofs with the specified keyset
     pub async fn calculate_fee(
         &self,
@@ -856,6 +916,19 @@ impl Wallet {
 #[derive(Debug, Clone, uniffi::Record)]
 pub struct WalletConfig {
     pub target_proof_count: Option<u32>,
+    /// Optional BIP-39 passphrase (25th word) used to derive the seed from the mnemonic
+    #[uniffi(default = None)]
+    pub mnemonic_passphrase: Option<String>,
+    /// Largest fee, in parts per thousand of the amount, paid to claim received tokens
+    #[uniffi(default = None)]
+    pub max_receive_fee_ppk: Option<u64>,
+}
+
+/// Derive the wallet seed from a mnemonic and an optional BIP-39 passphrase
+pub(crate) fn mnemonic_to_seed(mnemonic: &str, passphrase: &str) -> Result<[u8; 64], FfiError> {
+    let m = Mnemonic::parse(mnemonic)
+        .map_err(|e| FfiError::internal(format!("Invalid mnemonic: {}", e)))?;
+    Ok(m.to_seed_normalized(passphrase))
 }
 
 /// Generates a new random mnemonic phrase
@@ -0,0 +1,396 @@
+//! FFI WalletManager bindings
+//!
+//! A [`WalletManager`] hosts several wallet profiles in one app, one per user account. Each
+//! profile has its own seed, its own database and so its own mints, proofs and history. The id
+//! of a profile is derived from its seed by [`wallet_profile_id`], so the same mnemonic always
+//! maps to the same profile and opening a profile checks the mnemonic given. Mnemonics are never
+//! stored by the manager.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use cdk::wallet::wallet_repository::{
+    WalletRepository as CdkWalletRepository, WalletRepositoryBuilder,
+};
+use cdk_common::bitcoin::hashes::sha256::Hash as Sha256Hash;
+use cdk_common::bitcoin::hashes::Hash;
+use cdk_common::database::WalletMemoryDatabase;
+use cdk_common::util::{hex, unix_time};
+use cdk_sqlite::wallet::WalletSqliteDatabase;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+
+use crate::error::FfiError;
+use crate::wallet_repository::WalletRepository;
+
+/// File listing the profiles of a manager, in its directory
+const PROFILES_FILE: &str = "profiles.json";
+
+/// Id of the wallet profile of a mnemonic and passphrase
+///
+/// Hex of the first 8 bytes of the SHA-256 of the seed.
+#[uniffi::export]
+pub fn wallet_profile_id(mnemonic: String, passphrase: String) -> Result<String, FfiError> {
+    let seed = crate::wallet::mnemonic_to_seed(&mnemonic, &passphrase)?;
+    Ok(profile_id(&seed))
+}
+
+fn profile_id(seed: &[u8; 64]) -> String {
+    hex::encode(&Sha256Hash::hash(seed).to_byte_array()[..8])
+}
+
+/// Wallet profile of a [`WalletManager`]
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct WalletProfile {
+    /// Id derived from the seed of the profile
+    pub id: String,
+    /// Name given by the app
+    pub name: String,
+    /// Unix time the profile was created
+    pub created_at: u64,
+    /// Whether the background tasks of the profile run
+    pub background_tasks: bool,
+}
+
+/// Profile as listed in [`PROFILES_FILE`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ProfileRecord {
+    id: String,
+    name: String,
+    created_at: u64,
+}
+
+/// Profile opened by the manager
+struct OpenProfile {
+    repository: Arc<CdkWalletRepository>,
+    background_tasks: Option<JoinHandle<()>>,
+}
+
+impl Drop for OpenProfile {
+    fn drop(&mut self) {
+        if let Some(handle) = self.background_tasks.take() {
+            handle.abort();
+        }
+    }
+}
+
+/// FFI-compatible manager of isolated wallet profiles
+#[derive(uniffi::Object)]
+pub struct WalletManager {
+    /// Directory of the profile databases, none to keep them in memory
+    directory: Option<PathBuf>,
+    profiles: Mutex<BTreeMap<String, ProfileRecord>>,
+    open: Mutex<HashMap<String, OpenProfile>>,
+}
+
+#[uniffi::export(async_runtime = "tokio")]
+impl WalletManager {
+    /// Create a manager keeping a SQLite database per profile in `directory`
+    ///
+    /// The directory is created if missing, the profiles already in it are listed.
+    #[uniffi::constructor]
+    pub fn new(directory: String) -> Result<Self, FfiError> {
+        let directory = PathBuf::from(directory);
+        std::fs::create_dir_all(&directory).map_err(FfiError::internal)?;
+
+        let profiles = match std::fs::read(directory.join(PROFILES_FILE)) {
+            Ok(bytes) => serde_json::from_slice::<Vec<ProfileRecord>>(&bytes)?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(err) => return Err(FfiError::internal(err)),
+        };
+
+        Ok(Self {
+            directory: Some(directory),
+            profiles: Mutex::new(
+                profiles
+                    .into_iter()
+                    .map(|profile| (profile.id.clone(), profile))
+                    .collect(),
+            ),
+            open: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Create a manager keeping every profile in memory, nothing is persisted
+    #[uniffi::constructor]
+    pub fn new_in_memory() -> Self {
+        Self {
+            directory: None,
+            profiles: Mutex::new(BTreeMap::new()),
+            open: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Create the profile of `mnemonic` and open it
+    ///
+    /// Fails if the mnemonic and passphrase already have a profile.
+    pub async fn create_profile(
+        &self,
+        name: String,
+        mnemonic: String,
+        passphrase: Option<String>,
+    ) -> Result<WalletProfile, FfiError> {
+        let seed = crate::wallet::mnemonic_to_seed(&mnemonic, &passphrase.unwrap_or_default())?;
+        let id = profile_id(&seed);
+
+        let mut profiles = self.profiles.lock().await;
+        if profiles.contains_key(&id) {
+            return Err(FfiError::internal(format!("Profile {id} already exists")));
+        }
+
+        let repository = self.build_repository(&id, seed).await?;
+        let record = ProfileRecord {
+            id: id.clone(),
+            name,
+            created_at: unix_time(),
+        };
+        profiles.insert(id.clone(), record.clone());
+        self.save_profiles(&profiles)?;
+
+        self.open.lock().await.insert(
+            id,
+            OpenProfile {
+                repository,
+                background_tasks: None,
+            },
+        );
+
+        Ok(WalletProfile {
+            id: record.id,
+            name: record.name,
+            created_at: record.created_at,
+            background_tasks: false,
+        })
+    }
+
+    /// Every profile, oldest first
+    pub async fn list_profiles(&self) -> Vec<WalletProfile> {
+        let profiles = self.profiles.lock().await;
+        let open = self.open.lock().await;
+
+        let mut list: Vec<WalletProfile> = profiles
+            .values()
+            .map(|record| WalletProfile {
+                id: record.id.clone(),
+                name: record.name.clone(),
+                created_at: record.created_at,
+                background_tasks: open
+                    .get(&record.id)
+                    .is_some_and(|profile| profile.background_tasks.is_some()),
+            })
+            .collect();
+        list.sort_by_key(|profile| profile.created_at);
+        list
+    }
+
+    /// Wallets of the profile `id`, opened with its mnemonic
+    ///
+    /// Fails if the mnemonic and passphrase do not derive the id of the profile.
+    pub async fn open_profile(
+        &self,
+        id: String,
+        mnemonic: String,
+        passphrase: Option<String>,
+    ) -> Result<Arc<WalletRepository>, FfiError> {
+        let seed = crate::wallet::mnemonic_to_seed(&mnemonic, &passphrase.unwrap_or_default())?;
+        if profile_id(&seed) != id {
+            return Err(FfiError::internal(format!(
+                "Mnemonic does not belong to profile {id}"
+            )));
+        }
+        if !self.profiles.lock().await.contains_key(&id) {
+            return Err(FfiError::internal(format!("Profile {id} not found")));
+        }
+
+        let mut open = self.open.lock().await;
+        if let Some(profile) = open.get(&id) {
+            return Ok(Arc::new(WalletRepository::from_inner(Arc::clone(
+                &profile.repository,
+            ))));
+        }
+
+        let repository = self.build_repository(&id, seed).await?;
+        open.insert(
+            id,
+            OpenProfile {
+                repository: Arc::clone(&repository),
+                background_tasks: None,
+            },
+        );
+
+        Ok(Arc::new(WalletRepository::from_inner(repository)))
+    }
+
+    /// Delete the profile `id` and its database
+    ///
+    /// The background tasks of the profile are stopped. Wallets of the profile still held by
+    /// the app must not be used anymore.
+    pub async fn delete_profile(&self, id: String) -> Result<(), FfiError> {
+        let mut profiles = self.profiles.lock().await;
+        if profiles.remove(&id).is_none() {
+            return Err(FfiError::internal(format!("Profile {id} not found")));
+        }
+        self.save_profiles(&profiles)?;
+        self.open.lock().await.remove(&id);
+
+        if let Some(path) = self.database_path(&id) {
+            match std::fs::remove_file(path) {
+                Ok(()) => {}
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                Err(err) => return Err(FfiError::internal(err)),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check the pending mint quotes of every wallet of the open profile `id` every
+    /// `interval_secs`, minting the paid ones
+    ///
+    /// Replaces the background tasks of the profile if they already run.
+    pub async fn start_background_tasks(
+        &self,
+        id: String,
+        interval_secs: u64,
+    ) -> Result<(), FfiError> {
+        let mut open = self.open.lock().await;
+        let profile = open
+            .get_mut(&id)
+            .ok_or_else(|| FfiError::internal(format!("Profile {id} is not open")))?;
+
+        let repository = Arc::clone(&profile.repository);
+        let interval = Duration::from_secs(interval_secs.max(1));
+        let handle = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                if let Err(err) = repository.check_all_mint_quotes(None).await {
+                    tracing::warn!("Background check of profile {} failed: {}", id, err);
+                }
+            }
+        });
+
+        if let Some(previous) = profile.background_tasks.replace(handle) {
+            previous.abort();
+        }
+        Ok(())
+    }
+
+    /// Stop the background tasks of the profile `id`
+    pub async fn stop_background_tasks(&self, id: String) {
+        if let Some(handle) = self
+            .open
+            .lock()
+            .await
+            .get_mut(&id)
+            .and_then(|profile| profile.background_tasks.take())
+        {
+            handle.abort();
+        }
+    }
+}
+
+impl WalletManager {
+    fn database_path(&self, id: &str) -> Option<PathBuf> {
+        self.directory
+            .as_ref()
+            .map(|directory| directory.join(format!("{id}.sqlite")))
+    }
+
+    async fn build_repository(
+        &self,
+        id: &str,
+        seed: [u8; 64],
+    ) -> Result<Arc<CdkWalletRepository>, FfiError> {
+        let builder = WalletRepositoryBuilder::new().seed(seed);
+        let builder = match self.database_path(id) {
+            Some(path) => builder.localstore(Arc::new(
+                WalletSqliteDatabase::new(path.to_string_lossy().as_ref())
+                    .await
+                    .map_err(FfiError::database)?,
+            )),
+            None => builder.localstore(Arc::new(WalletMemoryDatabase::new())),
+        };
+
+        Ok(Arc::new(builder.build().await?))
+    }
+
+    fn save_profiles(&self, profiles: &BTreeMap<String, ProfileRecord>) -> Result<(), FfiError> {
+        let Some(directory) = &self.directory else {
+            return Ok(());
+        };
+
+        let records: Vec<&ProfileRecord> = profiles.values().collect();
+        let bytes = serde_json::to_vec_pretty(&records)?;
+        std::fs::write(directory.join(PROFILES_FILE), bytes).map_err(FfiError::internal)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MNEMONIC: &str =
+        "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    #[test]
+    fn profile_ids_are_derived_from_the_seed() {
+        let id = wallet_profile_id(MNEMONIC.to_string(), String::new()).unwrap();
+        assert_eq!(id.len(), 16);
+        assert_eq!(
+            id,
+            wallet_profile_id(MNEMONIC.to_string(), String::new()).unwrap()
+        );
+        assert_ne!(
+            id,
+            wallet_profile_id(MNEMONIC.to_string(), "passphrase".to_string()).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn profiles_are_created_opened_and_deleted() {
+        let manager = WalletManager::new_in_memory();
+
+        let profile = manager
+            .create_profile("Alice".to_string(), MNEMONIC.to_string(), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            profile.id,
+            wallet_profile_id(MNEMONIC.to_string(), String::new()).unwrap()
+        );
+        assert!(manager
+            .create_profile("Alice again".to_string(), MNEMONIC.to_string(), None)
+            .await
+            .is_err());
+        assert_eq!(manager.list_profiles().await, vec![profile.clone()]);
+
+        let other = crate::wallet::generate_mnemonic().unwrap();
+        assert!(manager
+            .open_profile(profile.id.clone(), other, None)
+            .await
+            .is_err());
+        manager
+            .open_profile(profile.id.clone(), MNEMONIC.to_string(), None)
+            .await
+            .unwrap();
+
+        manager
+            .start_background_tasks(profile.id.clone(), 60)
+            .await
+            .unwrap();
+        assert!(manager.list_profiles().await[0].background_tasks);
+        manager.stop_background_tasks(profile.id.clone()).await;
+        assert!(!manager.list_profiles().await[0].background_tasks);
+
+        manager.delete_profile(profile.id.clone()).await.unwrap();
+        assert!(manager.list_profiles().await.is_empty());
+        assert!(manager
+            .start_background_tasks(profile.id, 60)
+            .await
+            .is_err());
+    }
+}
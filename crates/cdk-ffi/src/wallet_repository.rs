@@ -17,6 +17,13 @@ pub struct WalletRepository {
     inner: Arc<CdkWalletRepository>,
 }
 
+impl WalletRepository {
+    /// Create a WalletRepository from an existing CDK repository (internal use only)
+    pub(crate) fn from_inner(inner: Arc<CdkWalletRepository>) -> Self {
+        Self { inner }
+    }
+}
+
 #[uniffi::export(async_runtime = "tokio")]
 impl WalletRepository {
     /// Create a new WalletRepository
@@ -27,12 +34,19 @@ impl WalletRepository {
     /// - `Custom { db }` — foreign-language implementation of `WalletDatabase`
     #[uniffi::constructor]
     pub fn new(mnemonic: String, store: crate::database::WalletStore) -> Result<Self, FfiError> {
+        Self::new_with_passphrase(mnemonic, String::new(), store)
+    }
+
+    /// Create a new WalletRepository deriving the seed with a BIP-39 passphrase (25th word)
+    #[uniffi::constructor]
+    pub fn new_with_passphrase(
+        mnemonic: String,
+        passphrase: String,
+        store: crate::database::WalletStore,
+    ) -> Result<Self, FfiError> {
         let db = crate::database::resolve_wallet_store(store)?;
 
-        // Parse mnemonic and generate seed without passphrase
-        let m = Mnemonic::parse(&mnemonic)
-            .map_err(|e| FfiError::internal(format!("Invalid mnemonic: {}", e)))?;
-        let seed = m.to_seed_normalized("");
+        let seed = crate::wallet::mnemonic_to_seed(&mnemonic, &passphrase)?;
 
         // Convert the FFI database trait to a CDK database implementation
         let localstore = crate::database::create_cdk_database_from_ffi(db);
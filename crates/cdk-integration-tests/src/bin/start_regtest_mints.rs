@@ -308,12 +308,21 @@ fn create_ldk_settings(
                 "eye survey guilt napkin crystal cup whisper salt luggage manage unveil loyal"
                     .to_string(),
             ),
+            mnemonic_passphrase: None,
             signatory_url: None,
             signatory_certs: None,
+            signatory_api_key: None,
+            signatory_fallback_urls: Default::default(),
             input_fee_ppk: None,
             use_keyset_v2: None,
             http_cache: cdk_axum::cache::Config::default(),
             enable_info_page: None,
+            disabled_nuts: Vec::new(),
+            clock_skew_grace_secs: None,
+            usage_statistics: false,
+            keyset_lifetime_secs: None,
+            keyset_rotate_before_secs: None,
+            keyset_retirements: Default::default(),
             logging: LoggingConfig::default(),
         },
         mint_info: cdk_mintd::config::MintInfo::default(),
@@ -413,12 +422,21 @@ fn create_onchain_settings(port: u16) -> cdk_mintd::config::Settings {
                 "eye survey guilt napkin crystal cup whisper salt luggage manage unveil loyal"
                     .to_string(),
             ),
+            mnemonic_passphrase: None,
             signatory_url: None,
             signatory_certs: None,
+            signatory_api_key: None,
+            signatory_fallback_urls: Default::default(),
             input_fee_ppk: None,
             use_keyset_v2: None,
             http_cache: cdk_axum::cache::Config::default(),
             enable_info_page: None,
+            disabled_nuts: Vec::new(),
+            clock_skew_grace_secs: None,
+            usage_statistics: false,
+            keyset_lifetime_secs: None,
+            keyset_rotate_before_secs: None,
+            keyset_retirements: Default::default(),
             logging: LoggingConfig::default(),
         },
         mint_info: cdk_mintd::config::MintInfo::default(),
@@ -187,10 +187,13 @@ pub fn create_fake_wallet_settings(
             listen_port: port,
             seed: None,
             mnemonic: mnemonic.clone(),
+            mnemonic_passphrase: None,
             signatory_url: signatory_config.as_ref().map(|(url, _)| url.clone()),
             signatory_certs: signatory_config
                 .as_ref()
                 .map(|(_, certs_dir)| certs_dir.clone()),
+            signatory_api_key: None,
+            signatory_fallback_urls: Default::default(),
             input_fee_ppk: None,
             use_keyset_v2: None,
             http_cache: cache::Config::default(),
@@ -198,8 +201,15 @@ pub fn create_fake_wallet_settings(
                 output: cdk_mintd::config::LoggingOutput::Both,
                 console_level: Some("debug".to_string()),
                 file_level: Some("debug".to_string()),
+                sampled_spans: Default::default(),
             },
             enable_info_page: None,
+            disabled_nuts: Vec::new(),
+            clock_skew_grace_secs: None,
+            usage_statistics: false,
+            keyset_lifetime_secs: None,
+            keyset_rotate_before_secs: None,
+            keyset_retirements: Default::default(),
         },
         mint_info: cdk_mintd::config::MintInfo::default(),
         limits: cdk_mintd::config::Limits::default(),
@@ -262,8 +272,11 @@ pub fn create_cln_settings(
             listen_port: port,
             seed: None,
             mnemonic: Some(mnemonic),
+            mnemonic_passphrase: None,
             signatory_url: None,
             signatory_certs: None,
+            signatory_api_key: None,
+            signatory_fallback_urls: Default::default(),
             input_fee_ppk: None,
             use_keyset_v2: None,
             http_cache: cache::Config::default(),
@@ -271,8 +284,15 @@ pub fn create_cln_settings(
                 output: cdk_mintd::config::LoggingOutput::Both,
                 console_level: Some("debug".to_string()),
                 file_level: Some("debug".to_string()),
+                sampled_spans: Default::default(),
             },
             enable_info_page: None,
+            disabled_nuts: Vec::new(),
+            clock_skew_grace_secs: None,
+            usage_statistics: false,
+            keyset_lifetime_secs: None,
+            keyset_rotate_before_secs: None,
+            keyset_retirements: Default::default(),
         },
         mint_info: cdk_mintd::config::MintInfo::default(),
         limits: cdk_mintd::config::Limits::default(),
@@ -318,8 +338,11 @@ pub fn create_lnd_settings(
             listen_port: port,
             seed: None,
             mnemonic: Some(mnemonic),
+            mnemonic_passphrase: None,
             signatory_url: None,
             signatory_certs: None,
+            signatory_api_key: None,
+            signatory_fallback_urls: Default::default(),
             input_fee_ppk: None,
             use_keyset_v2: None,
             http_cache: cache::Config::default(),
@@ -327,8 +350,15 @@ pub fn create_lnd_settings(
                 output: cdk_mintd::config::LoggingOutput::Both,
                 console_level: Some("debug".to_string()),
                 file_level: Some("debug".to_string()),
+                sampled_spans: Default::default(),
             },
             enable_info_page: None,
+            disabled_nuts: Vec::new(),
+            clock_skew_grace_secs: None,
+            usage_statistics: false,
+            keyset_lifetime_secs: None,
+            keyset_rotate_before_secs: None,
+            keyset_retirements: Default::default(),
         },
         mint_info: cdk_mintd::config::MintInfo::default(),
         limits: cdk_mintd::config::Limits::default(),
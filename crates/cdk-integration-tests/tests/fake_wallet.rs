@@ -2033,9 +2033,9 @@ async fn test_chained_confirm_recovers_paid_melt() {
 
 /// Tests that concurrent melt attempts for the same invoice result in exactly one success
 ///
-/// This test verifies the race condition protection: when multiple melt quotes exist for the
-/// same invoice and all are attempted concurrently, only one should succeed due to
-/// the FOR UPDATE locking on quotes with the same request_lookup_id.
+/// This test verifies the race condition protection: the mint answers every melt quote request
+/// for the invoice with the same quote, and when it is melted concurrently only one attempt
+/// should succeed due to the FOR UPDATE locking on quotes with the same request_lookup_id.
 #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
 async fn test_concurrent_melt_same_invoice() {
     const NUM_WALLETS: usize = 4;
@@ -2085,12 +2085,16 @@ async fn test_concurrent_melt_same_invoice() {
         melt_quotes.push(melt_quote);
     }
 
-    // Verify all quotes have the same request (same invoice = same lookup_id)
+    // Verify all wallets got the same quote for the invoice
     for quote in &melt_quotes[1..] {
         assert_eq!(
             melt_quotes[0].request, quote.request,
             "All quotes should be for the same invoice"
         );
+        assert_eq!(
+            melt_quotes[0].id, quote.id,
+            "All requests should get the same quote"
+        );
     }
 
     // Attempt all melts concurrently
@@ -2533,6 +2537,46 @@ async fn test_check_all_mint_quotes_bolt11() {
     assert_eq!(second_check, Amount::ZERO);
 }
 
+/// Tests that a wallet restarted over the same database mints the quotes paid before it closed
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_recover_unminted_quotes_after_restart() {
+    let localstore = Arc::new(memory::empty().await.unwrap());
+    let seed = Mnemonic::generate(12).unwrap().to_seed_normalized("");
+    let wallet = Wallet::new(MINT_URL, CurrencyUnit::Sat, localstore.clone(), seed, None)
+        .expect("failed to create new wallet");
+
+    let paid_quote = wallet
+        .mint_quote(PaymentMethod::BOLT11, Some(100.into()), None, None)
+        .await
+        .unwrap();
+    let mut payment_stream = wallet.payment_stream(&paid_quote);
+    payment_stream
+        .next()
+        .await
+        .expect("payment")
+        .expect("no error");
+    drop(payment_stream);
+    drop(wallet);
+
+    let restarted_wallet = Wallet::new(MINT_URL, CurrencyUnit::Sat, localstore, seed, None)
+        .expect("failed to create new wallet");
+
+    let recovery = restarted_wallet.recover_unminted_quotes().await.unwrap();
+
+    assert_eq!(recovery.quotes.len(), 1);
+    assert_eq!(recovery.quotes[0].quote_id, paid_quote.id);
+    assert_eq!(recovery.quotes[0].error, None);
+    assert_eq!(recovery.total_minted().unwrap(), Amount::from(100));
+    assert_eq!(
+        restarted_wallet.total_balance().await.unwrap(),
+        Amount::from(100)
+    );
+
+    // Nothing is left to recover
+    let recovery = restarted_wallet.recover_unminted_quotes().await.unwrap();
+    assert!(recovery.quotes.is_empty());
+}
+
 /// Tests the get_unissued_mint_quotes wallet method
 ///
 /// This test verifies that:
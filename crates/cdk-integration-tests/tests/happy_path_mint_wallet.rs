@@ -998,7 +998,7 @@ async fn test_pay_invoice_twice() {
         .unwrap();
     let melt = prepared.confirm().await.unwrap();
 
-    // Creating a second quote for the same invoice is allowed
+    // A second quote for the paid invoice is refused
     let melt_quote_two = wallet
         .melt_quote(
             PaymentMethod::Known(KnownMethod::Bolt11),
@@ -1006,31 +1006,17 @@ async fn test_pay_invoice_twice() {
             None,
             None,
         )
-        .await
-        .unwrap();
-
-    // But attempting to melt (pay) the second quote should fail
-    // since the first quote with the same lookup_id is already paid
-    let melt_two = async {
-        let prepared = wallet
-            .prepare_melt(&melt_quote_two.id, std::collections::HashMap::new())
-            .await?;
-        prepared.confirm().await
-    }
-    .await;
+        .await;
 
-    match melt_two {
+    match melt_quote_two {
         Err(err) => {
             let err_str = err.to_string().to_lowercase();
-            if !err_str.contains("duplicate")
-                && !err_str.contains("already paid")
-                && !err_str.contains("request already paid")
-            {
-                panic!("Expected duplicate/already paid error, got: {}", err);
+            if !err_str.contains("already paid") {
+                panic!("Expected already paid error, got: {}", err);
             }
         }
         Ok(_) => {
-            panic!("Should not have allowed second payment");
+            panic!("Should not have quoted a paid invoice again");
         }
     }
 
@@ -2995,6 +2995,72 @@ async fn test_p2pk_locked_proof_sign_and_send_rejects_sig_all() {
     );
 }
 
+/// Test that a token locked to Bob with `SIG_ALL` can be received.
+///
+/// Bob's wallet signs the swap request as a whole, committing to the inputs and the new
+/// outputs, instead of signing each proof.
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_p2pk_receive_sig_all() {
+    setup_tracing();
+
+    let mint = create_and_start_test_mint()
+        .await
+        .expect("Failed to create test mint");
+    let wallet_alice = create_test_wallet_for_mint(mint.clone())
+        .await
+        .expect("Failed to create alice wallet");
+    let wallet_bob = create_test_wallet_for_mint(mint.clone())
+        .await
+        .expect("Failed to create bob wallet");
+
+    fund_wallet(wallet_alice.clone(), 64, None)
+        .await
+        .expect("Failed to fund alice");
+
+    let bob_secret = SecretKey::generate();
+    let spending_conditions = SpendingConditions::new_p2pk(
+        bob_secret.public_key(),
+        Some(Conditions {
+            sig_flag: SigFlag::SigAll,
+            ..Default::default()
+        }),
+    );
+
+    let send_amount = Amount::from(10);
+    let token = wallet_alice
+        .prepare_send(
+            send_amount,
+            SendOptions {
+                conditions: Some(spending_conditions),
+                ..Default::default()
+            },
+        )
+        .await
+        .expect("prepare_send should succeed")
+        .confirm(None)
+        .await
+        .expect("confirm should succeed");
+
+    // Without the key the request can not be signed
+    assert!(wallet_bob
+        .receive(&token.to_string(), ReceiveOptions::default())
+        .await
+        .is_err());
+
+    let received = wallet_bob
+        .receive(
+            &token.to_string(),
+            ReceiveOptions {
+                p2pk_signing_keys: vec![bob_secret],
+                ..Default::default()
+            },
+        )
+        .await
+        .expect("Bob should receive the SIG_ALL token");
+
+    assert_eq!(send_amount, received);
+}
+
 /// The wallet's keyring automatically supplies signing keys at send time.
 ///
 /// Alice generates a P2PK key via `generate_public_key()`, which stores the key in the wallet
@@ -542,6 +542,10 @@ impl CdkLdkNode {
 impl MintPayment for CdkLdkNode {
     type Err = payment::Error;
 
+    fn backend_name(&self) -> String {
+        "ldk-node".to_string()
+    }
+
     /// Start the payment processor
     /// Starts the LDK node and begins event processing
     async fn start(&self) -> Result<(), Self::Err> {
@@ -591,6 +595,24 @@ impl MintPayment for CdkLdkNode {
         Ok(settings)
     }
 
+    /// Outbound capacity of the usable channels
+    async fn outbound_liquidity(
+        &self,
+        unit: &CurrencyUnit,
+    ) -> Result<Option<Amount<CurrencyUnit>>, Self::Err> {
+        let outbound_msat = self
+            .inner
+            .list_channels()
+            .iter()
+            .filter(|channel| channel.is_usable)
+            .map(|channel| channel.outbound_capacity_msat)
+            .sum();
+
+        Ok(Some(
+            Amount::new(outbound_msat, CurrencyUnit::Msat).convert_to(unit)?,
+        ))
+    }
+
     /// Create a new invoice
     #[instrument(skip(self))]
     async fn create_incoming_payment_request(
@@ -786,7 +808,7 @@ impl MintPayment for CdkLdkNode {
                     fee_options: None,
                 })
             }
-            OutgoingPaymentOptions::Onchain(_) => {
+            OutgoingPaymentOptions::Onchain(_) | OutgoingPaymentOptions::Keysend(_) => {
                 Err(cdk_common::payment::Error::UnsupportedPaymentOption)
             }
         }
@@ -968,7 +990,7 @@ impl MintPayment for CdkLdkNode {
                     &payment_details,
                 )
             }
-            OutgoingPaymentOptions::Onchain(_) => {
+            OutgoingPaymentOptions::Onchain(_) | OutgoingPaymentOptions::Keysend(_) => {
                 Err(cdk_common::payment::Error::UnsupportedPaymentOption)
             }
         }
@@ -151,6 +151,10 @@ impl LNbits {
 impl MintPayment for LNbits {
     type Err = payment::Error;
 
+    fn backend_name(&self) -> String {
+        "lnbits".to_string()
+    }
+
     async fn get_settings(&self) -> Result<SettingsResponse, Self::Err> {
         Ok(self.settings.clone())
     }
@@ -281,9 +285,9 @@ impl MintPayment for LNbits {
             OutgoingPaymentOptions::Bolt12(_bolt12_options) => {
                 Err(Self::Err::Anyhow(anyhow!("BOLT12 not supported by LNbits")))
             }
-            OutgoingPaymentOptions::Custom(_) | OutgoingPaymentOptions::Onchain(_) => {
-                Err(payment::Error::UnsupportedPaymentOption)
-            }
+            OutgoingPaymentOptions::Custom(_)
+            | OutgoingPaymentOptions::Onchain(_)
+            | OutgoingPaymentOptions::Keysend(_) => Err(payment::Error::UnsupportedPaymentOption),
         }
     }
 
@@ -347,9 +351,9 @@ impl MintPayment for LNbits {
             OutgoingPaymentOptions::Bolt12(_) => {
                 Err(Self::Err::Anyhow(anyhow!("BOLT12 not supported by LNbits")))
             }
-            OutgoingPaymentOptions::Custom(_) | OutgoingPaymentOptions::Onchain(_) => {
-                Err(payment::Error::UnsupportedPaymentOption)
-            }
+            OutgoingPaymentOptions::Custom(_)
+            | OutgoingPaymentOptions::Onchain(_)
+            | OutgoingPaymentOptions::Keysend(_) => Err(payment::Error::UnsupportedPaymentOption),
         }
     }
 
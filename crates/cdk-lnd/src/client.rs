@@ -20,7 +20,7 @@ use tonic::metadata::MetadataValue;
 use tonic::service::Interceptor;
 use tonic::{Request, Status};
 
-use crate::{lnrpc, routerrpc, Error};
+use crate::{invoicesrpc, lnrpc, routerrpc, Error};
 
 /// Custom certificate verifier for LND's self-signed certificates
 #[derive(Debug)]
@@ -128,6 +128,13 @@ pub type RouterClient = routerrpc::router_client::RouterClient<
     >,
 >;
 
+pub type InvoicesClient = invoicesrpc::invoices_client::InvoicesClient<
+    InterceptedService<
+        HyperClient<hyper_rustls::HttpsConnector<HttpConnector>, Body>,
+        MacaroonInterceptor,
+    >,
+>;
+
 /// The client returned by `connect` function
 #[derive(Clone)]
 pub struct Client {
@@ -138,6 +145,7 @@ pub struct Client {
         >,
     >,
     router: RouterClient,
+    invoices: InvoicesClient,
 }
 
 /// Supplies requests with macaroon
@@ -204,9 +212,14 @@ pub async fn connect<P: AsRef<Path>>(
     // Create LND client
     let lightning =
         lnrpc::lightning_client::LightningClient::with_origin(service.clone(), uri.clone());
-    let router = RouterClient::with_origin(service, uri);
-
-    Ok(Client { lightning, router })
+    let router = RouterClient::with_origin(service.clone(), uri.clone());
+    let invoices = InvoicesClient::with_origin(service, uri);
+
+    Ok(Client {
+        lightning,
+        router,
+        invoices,
+    })
 }
 
 impl Client {
@@ -224,4 +237,8 @@ impl Client {
     pub fn router(&mut self) -> &mut RouterClient {
         &mut self.router
     }
+
+    pub fn invoices(&mut self) -> &mut InvoicesClient {
+        &mut self.invoices
+    }
 }
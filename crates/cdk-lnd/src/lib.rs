@@ -5,6 +5,7 @@
 #![doc = include_str!("../README.md")]
 
 use std::cmp::max;
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::pin::Pin;
 use std::str::FromStr;
@@ -14,14 +15,14 @@ use std::sync::Arc;
 use anyhow::anyhow;
 use async_trait::async_trait;
 use cdk_common::amount::{Amount, MSAT_IN_SAT};
-use cdk_common::bitcoin::hashes::Hash;
+use cdk_common::bitcoin::hashes::{sha256, Hash};
 use cdk_common::common::FeeReserve;
 use cdk_common::database::DynKVStore;
-use cdk_common::nuts::{CurrencyUnit, MeltOptions, MeltQuoteState};
+use cdk_common::nuts::{CurrencyUnit, MeltOptions, MeltQuoteState, KEYSEND_METHOD};
 use cdk_common::payment::{
-    self, CreateIncomingPaymentResponse, Event, IncomingPaymentOptions, MakePaymentResponse,
-    MintPayment, OutgoingPaymentOptions, PaymentIdentifier, PaymentQuoteResponse, SettingsResponse,
-    WaitPaymentResponse,
+    self, Bolt11IncomingPaymentOptions, CreateIncomingPaymentResponse, Event,
+    IncomingPaymentOptions, MakePaymentResponse, MintPayment, OutgoingPaymentOptions,
+    PaymentIdentifier, PaymentQuoteResponse, SettingsResponse, WaitPaymentResponse,
 };
 use cdk_common::util::{hex, unix_time};
 use cdk_common::Bolt11Invoice;
@@ -37,7 +38,7 @@ mod client;
 pub mod error;
 
 mod proto;
-pub(crate) use proto::{lnrpc, routerrpc};
+pub(crate) use proto::{invoicesrpc, lnrpc, routerrpc};
 
 use crate::lnrpc::invoice::InvoiceState;
 
@@ -47,6 +48,9 @@ const LND_KV_SECONDARY_NAMESPACE: &str = "payment_indices";
 const LAST_ADD_INDEX_KV_KEY: &str = "last_add_index";
 const LAST_SETTLE_INDEX_KV_KEY: &str = "last_settle_index";
 
+/// TLV record carrying the preimage of a keysend payment
+const KEYSEND_PREIMAGE_RECORD: u64 = 5482373484;
+
 /// Lnd mint backend
 #[derive(Clone)]
 pub struct Lnd {
@@ -132,12 +136,35 @@ impl Lnd {
                 }),
                 bolt12: None,
                 onchain: None,
-                custom: std::collections::HashMap::new(),
+                custom: HashMap::new(),
             },
             unit,
         })
     }
 
+    /// Allow melting to a node pubkey with keysend
+    ///
+    /// Keysend payments are spontaneous: the mint picks the preimage, so nothing proves the
+    /// payee received the payment. Disabled by default.
+    pub fn with_keysend(mut self, enabled: bool) -> Self {
+        if enabled {
+            self.settings
+                .custom
+                .insert(KEYSEND_METHOD.to_string(), "{}".to_string());
+        } else {
+            self.settings.custom.remove(KEYSEND_METHOD);
+        }
+        self
+    }
+
+    fn ensure_keysend_enabled(&self) -> Result<(), payment::Error> {
+        if self.settings.custom.contains_key(KEYSEND_METHOD) {
+            Ok(())
+        } else {
+            Err(payment::Error::UnsupportedPaymentOption)
+        }
+    }
+
     /// Get last add and settle indices from KV store
     #[instrument(skip_all)]
     async fn get_last_indices(&self) -> Result<(Option<u64>, Option<u64>), Error> {
@@ -188,6 +215,28 @@ impl Lnd {
     }
 }
 
+/// Whether the invoice has received a payment
+///
+/// Hold invoices are paid once their HTLCs are accepted, they have no preimage until settled.
+fn invoice_is_paid(invoice: &lnrpc::Invoice) -> bool {
+    match invoice.state() {
+        InvoiceState::Settled => true,
+        InvoiceState::Accepted => invoice.r_preimage.is_empty(),
+        InvoiceState::Open | InvoiceState::Canceled => false,
+    }
+}
+
+/// Seconds from now until `unix_expiry`, or `0` for lnd's default expiry
+fn invoice_expiry(unix_expiry: Option<u64>) -> Result<i64, payment::Error> {
+    Ok(unix_expiry
+        .map(|t| {
+            t.checked_sub(unix_time())
+                .ok_or(payment::Error::InvalidExpiry)
+        })
+        .transpose()?
+        .unwrap_or_default() as i64)
+}
+
 fn lnrpc_payment_total_spent(payment: &lnrpc::Payment) -> Result<Amount<CurrencyUnit>, Error> {
     let total_msat = payment
         .value_msat
@@ -218,11 +267,87 @@ fn msat_total_spent_for_unit(
 impl MintPayment for Lnd {
     type Err = payment::Error;
 
+    fn backend_name(&self) -> String {
+        "lnd".to_string()
+    }
+
     #[instrument(skip_all)]
     async fn get_settings(&self) -> Result<SettingsResponse, Self::Err> {
         Ok(self.settings.clone())
     }
 
+    /// Local balance of the channels
+    #[instrument(skip_all)]
+    async fn outbound_liquidity(
+        &self,
+        unit: &CurrencyUnit,
+    ) -> Result<Option<Amount<CurrencyUnit>>, Self::Err> {
+        let balance = self
+            .lnd_client
+            .clone()
+            .lightning()
+            .channel_balance(lnrpc::ChannelBalanceRequest {})
+            .await
+            .map_err(Error::LndError)?
+            .into_inner();
+
+        let local_msat = balance.local_balance.map(|local| local.msat).unwrap_or(0);
+
+        Ok(Some(
+            Amount::new(local_msat, CurrencyUnit::Msat).convert_to(unit)?,
+        ))
+    }
+
+    /// Graph based estimate of LND, which does not probe the route
+    ///
+    /// The estimate is a lower bound, so it is never below the minimum fee reserve.
+    #[instrument(skip_all)]
+    async fn estimate_fee(
+        &self,
+        unit: &CurrencyUnit,
+        options: &OutgoingPaymentOptions,
+    ) -> Result<Option<Amount<CurrencyUnit>>, Self::Err> {
+        let OutgoingPaymentOptions::Bolt11(bolt11_options) = options else {
+            return Ok(None);
+        };
+
+        let amount_msat = match bolt11_options.melt_options {
+            Some(melt_options) => u64::from(melt_options.amount_msat()),
+            None => match bolt11_options.bolt11.amount_milli_satoshis() {
+                Some(amount_msat) => amount_msat,
+                None => return Ok(None),
+            },
+        };
+
+        let response = self
+            .lnd_client
+            .clone()
+            .router()
+            .estimate_route_fee(routerrpc::RouteFeeRequest {
+                dest: bolt11_options
+                    .bolt11
+                    .get_payee_pub_key()
+                    .serialize()
+                    .to_vec(),
+                amt_sat: (amount_msat / MSAT_IN_SAT) as i64,
+                ..Default::default()
+            })
+            .await
+            .map_err(Error::LndError)?
+            .into_inner();
+
+        let fee_sat = u64::try_from(response.routing_fee_msat)
+            .unwrap_or_default()
+            .div_ceil(MSAT_IN_SAT);
+        let fee = Amount::new(fee_sat, CurrencyUnit::Sat).convert_to(unit)?;
+        let min_fee_reserve: u64 = self.fee_reserve.min_fee_reserve.into();
+
+        Ok(Some(Amount::new(
+            max(fee.value(), min_fee_reserve),
+            unit.clone(),
+        )))
+    }
+
     #[instrument(skip_all)]
     fn is_payment_event_stream_active(&self) -> bool {
         self.wait_invoice_is_active.load(Ordering::SeqCst)
@@ -329,8 +454,8 @@ impl MintPayment for Lnd {
                                         tracing::warn!("LND: Failed to begin KV transaction for storing indices");
                                     }
 
-                                    // Only emit event for settled invoices
-                                    if msg.state() == InvoiceState::Settled {
+                                    // Only emit event for paid invoices
+                                    if invoice_is_paid(&msg) {
                                         let hash_slice: Result<[u8;32], _> = msg.r_hash.try_into();
 
                                         if let Ok(hash_slice) = hash_slice {
@@ -352,8 +477,8 @@ impl MintPayment for Lnd {
                                             continue;
                                         }
                                     } else {
-                                        // Not a settled invoice, continue but don't emit event
-                                        tracing::debug!("LND: Received non-settled invoice, continuing to wait for settled invoices");
+                                        // Not a paid invoice, continue but don't emit event
+                                        tracing::debug!("LND: Received unpaid invoice, continuing to wait for paid invoices");
                                         // Continue the loop without yielding
                                         continue;
                                     }
@@ -433,6 +558,32 @@ impl MintPayment for Lnd {
             OutgoingPaymentOptions::Bolt12(_) => {
                 Err(Self::Err::Anyhow(anyhow!("BOLT12 not supported by LND")))
             }
+            OutgoingPaymentOptions::Keysend(keysend_options) => {
+                self.ensure_keysend_enabled()?;
+
+                let amount = keysend_options.amount.convert_to(unit)?;
+
+                let relative_fee_reserve =
+                    (self.fee_reserve.percent_fee_reserve * amount.value() as f32) as u64;
+
+                let absolute_fee_reserve: u64 = self.fee_reserve.min_fee_reserve.into();
+
+                let fee = max(relative_fee_reserve, absolute_fee_reserve);
+
+                let request_lookup_id = keysend_options.preimage.map(|preimage| {
+                    PaymentIdentifier::PaymentHash(sha256::Hash::hash(&preimage).to_byte_array())
+                });
+
+                Ok(PaymentQuoteResponse {
+                    request_lookup_id,
+                    amount,
+                    fee: Amount::new(fee, unit.clone()),
+                    state: MeltQuoteState::Unpaid,
+                    extra_json: None,
+                    estimated_blocks: None,
+                    fee_options: None,
+                })
+            }
             OutgoingPaymentOptions::Custom(_) | OutgoingPaymentOptions::Onchain(_) => {
                 Err(payment::Error::UnsupportedPaymentOption)
             }
@@ -665,6 +816,71 @@ impl MintPayment for Lnd {
             OutgoingPaymentOptions::Bolt12(_) => {
                 Err(Self::Err::Anyhow(anyhow!("BOLT12 not supported by LND")))
             }
+            OutgoingPaymentOptions::Keysend(keysend_options) => {
+                self.ensure_keysend_enabled()?;
+
+                let mut lnd_client = self.lnd_client.clone();
+
+                let preimage = keysend_options.required_preimage()?;
+                let payment_hash = sha256::Hash::hash(&preimage).to_byte_array();
+
+                let amount_msat = keysend_options.amount.convert_to(&CurrencyUnit::Msat)?;
+
+                let fee_limit_msat = match keysend_options.max_fee_amount {
+                    Some(fee) => fee.convert_to(&CurrencyUnit::Msat)?.value() as i64,
+                    None => 0,
+                };
+
+                let pay_req = routerrpc::SendPaymentRequest {
+                    dest: keysend_options.pubkey.to_bytes().to_vec(),
+                    amt_msat: amount_msat.value() as i64,
+                    payment_hash: payment_hash.to_vec(),
+                    dest_custom_records: HashMap::from([(
+                        KEYSEND_PREIMAGE_RECORD,
+                        preimage.to_vec(),
+                    )]),
+                    fee_limit_msat,
+                    ..Default::default()
+                };
+
+                let mut payment_stream = lnd_client
+                    .router()
+                    .send_payment_v2(pay_req)
+                    .await
+                    .map_err(|err| {
+                        tracing::warn!("Keysend payment failed: {}", err);
+                        Error::PaymentFailed
+                    })?
+                    .into_inner();
+
+                while let Some(update) = payment_stream.message().await.map_err(|err| {
+                    tracing::warn!("Keysend payment failed: {}", err);
+                    Error::PaymentFailed
+                })? {
+                    let status = match update.status() {
+                        PaymentStatus::InFlight | PaymentStatus::Initiated => continue,
+                        PaymentStatus::Succeeded => MeltQuoteState::Paid,
+                        PaymentStatus::Failed => MeltQuoteState::Failed,
+                        #[allow(deprecated)]
+                        PaymentStatus::Unknown => MeltQuoteState::Unknown,
+                    };
+
+                    let total_msat = update
+                        .value_msat
+                        .checked_add(update.fee_msat)
+                        .ok_or(Error::AmountOverflow)?;
+
+                    return Ok(MakePaymentResponse {
+                        payment_lookup_id: PaymentIdentifier::PaymentHash(payment_hash),
+                        payment_proof: (status == MeltQuoteState::Paid)
+                            .then(|| hex::encode(preimage)),
+                        status,
+                        total_spent: msat_total_spent_for_unit(total_msat as u64, unit)?,
+                    });
+                }
+
+                Err(Error::UnknownPaymentStatus.into())
+            }
             OutgoingPaymentOptions::Custom(_) | OutgoingPaymentOptions::Onchain(_) => {
                 Err(payment::Error::UnsupportedPaymentOption)
             }
@@ -687,13 +903,7 @@ impl MintPayment for Lnd {
                 let invoice_request = lnrpc::Invoice {
                     value_msat: u64::from(amount_msat) as i64,
                     memo: description,
-                    expiry: unix_expiry
-                        .map(|t| {
-                            t.checked_sub(unix_time())
-                                .ok_or(payment::Error::InvalidExpiry)
-                        })
-                        .transpose()?
-                        .unwrap_or_default() as i64,
+                    expiry: invoice_expiry(unix_expiry)?,
                     ..Default::default()
                 };
 
@@ -748,7 +958,7 @@ impl MintPayment for Lnd {
             .map_err(|e| payment::Error::Anyhow(anyhow!(e)))?
             .into_inner();
 
-        if invoice.state() == InvoiceState::Settled {
+        if invoice_is_paid(&invoice) {
             Ok(vec![WaitPaymentResponse {
                 payment_identifier: payment_identifier.clone(),
                 payment_amount: Amount::new(invoice.amt_paid_msat as u64, CurrencyUnit::Msat),
@@ -839,12 +1049,111 @@ impl MintPayment for Lnd {
         // If the stream is exhausted without a final status
         Err(Error::UnknownPaymentStatus.into())
     }
+
+    fn supports_hold_invoices(&self) -> bool {
+        true
+    }
+
+    #[instrument(skip(self, options))]
+    async fn create_hold_invoice(
+        &self,
+        options: Bolt11IncomingPaymentOptions,
+        payment_hash: [u8; 32],
+    ) -> Result<CreateIncomingPaymentResponse, Self::Err> {
+        let amount_msat: Amount = options.amount.convert_to(&CurrencyUnit::Msat)?.into();
+
+        let invoice_request = invoicesrpc::AddHoldInvoiceRequest {
+            memo: options.description.unwrap_or_default(),
+            hash: payment_hash.to_vec(),
+            value_msat: u64::from(amount_msat) as i64,
+            expiry: invoice_expiry(options.unix_expiry)?,
+            ..Default::default()
+        };
+
+        let mut lnd_client = self.lnd_client.clone();
+
+        let invoice = lnd_client
+            .invoices()
+            .add_hold_invoice(tonic::Request::new(invoice_request))
+            .await
+            .map_err(|e| payment::Error::Anyhow(anyhow!(e)))?
+            .into_inner();
+
+        let bolt11 = Bolt11Invoice::from_str(&invoice.payment_request)?;
+
+        if *bolt11.payment_hash().as_ref() != payment_hash {
+            return Err(Error::InvalidHash.into());
+        }
+
+        Ok(CreateIncomingPaymentResponse {
+            request_lookup_id: PaymentIdentifier::PaymentHash(payment_hash),
+            request: bolt11.to_string(),
+            expiry: bolt11.expires_at().map(|t| t.as_secs()),
+            extra_json: None,
+        })
+    }
+
+    #[instrument(skip_all)]
+    async fn settle_hold_invoice(&self, preimage: [u8; 32]) -> Result<(), Self::Err> {
+        let mut lnd_client = self.lnd_client.clone();
+
+        // lnd accepts settling an invoice that is already settled
+        lnd_client
+            .invoices()
+            .settle_invoice(tonic::Request::new(invoicesrpc::SettleInvoiceMsg {
+                preimage: preimage.to_vec(),
+            }))
+            .await
+            .map_err(|e| payment::Error::Anyhow(anyhow!(e)))?;
+
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn cancel_hold_invoice(&self, payment_hash: [u8; 32]) -> Result<(), Self::Err> {
+        let mut lnd_client = self.lnd_client.clone();
+
+        lnd_client
+            .invoices()
+            .cancel_invoice(tonic::Request::new(invoicesrpc::CancelInvoiceMsg {
+                payment_hash: payment_hash.to_vec(),
+            }))
+            .await
+            .map_err(|e| payment::Error::Anyhow(anyhow!(e)))?;
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn accepted_hold_invoices_are_paid() {
+        let invoice = |state: InvoiceState, r_preimage: Vec<u8>| {
+            let mut invoice = lnrpc::Invoice {
+                r_preimage,
+                ..Default::default()
+            };
+            invoice.set_state(state);
+            invoice
+        };
+
+        assert!(invoice_is_paid(&invoice(
+            InvoiceState::Settled,
+            vec![1; 32]
+        )));
+        assert!(invoice_is_paid(&invoice(InvoiceState::Accepted, vec![])));
+        // Invoices lnd knows the preimage of are paid once settled
+        assert!(!invoice_is_paid(&invoice(
+            InvoiceState::Accepted,
+            vec![1; 32]
+        )));
+        assert!(!invoice_is_paid(&invoice(InvoiceState::Open, vec![])));
+        assert!(!invoice_is_paid(&invoice(InvoiceState::Canceled, vec![])));
+    }
+
     #[test]
     fn lnrpc_payment_total_spent_uses_msat_fields() {
         let payment = lnrpc::Payment {
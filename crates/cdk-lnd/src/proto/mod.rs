@@ -8,3 +8,8 @@ pub(crate) mod lnrpc {
 pub(crate) mod routerrpc {
     tonic::include_proto!("routerrpc");
 }
+
+#[allow(clippy::all, clippy::pedantic, clippy::restriction, clippy::nursery)]
+pub(crate) mod invoicesrpc {
+    tonic::include_proto!("invoicesrpc");
+}
@@ -3,10 +3,9 @@
 use std::path::PathBuf;
 
 use anyhow::{anyhow, Result};
-use cdk_common::grpc::{VersionInterceptor, VERSION_HEADER};
 use cdk_mint_rpc::cdk_mint_client::CdkMintClient;
 use cdk_mint_rpc::mint_rpc_cli::subcommands;
-use cdk_mint_rpc::GetInfoRequest;
+use cdk_mint_rpc::{ClientInterceptor, GetInfoRequest};
 use clap::{Parser, Subcommand};
 use tonic::transport::{Certificate, Channel, ClientTlsConfig, Identity};
 use tonic::Request;
@@ -64,6 +63,10 @@ struct Cli {
     #[arg(short, long)]
     work_dir: Option<PathBuf>,
 
+    /// Bearer token, when the RPC server requires one
+    #[arg(long)]
+    token: Option<String>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -104,6 +107,26 @@ enum Commands {
     UpdateNut04QuoteState(subcommands::UpdateNut04QuoteCommand),
     /// Rotate next keyset
     RotateNextKeyset(subcommands::RotateNextKeysetCommand),
+    /// Stop signing with a keyset without rotating to a new one
+    DisableKeyset(subcommands::DisableKeysetCommand),
+    /// Update the fee and amounts of a unit, rotating its keyset only if they changed
+    UpdateKeysetConfig(subcommands::UpdateKeysetConfigCommand),
+    /// Enable or disable read-only maintenance mode
+    SetReadOnly(subcommands::SetReadOnlyCommand),
+    /// Export all keysets' public keys as signed JSON
+    ExportKeysets(subcommands::ExportKeysetsCommand),
+    /// Show payment hash, preimage, fee paid and backend of a quote
+    GetQuoteDetails(subcommands::GetQuoteDetailsCommand),
+    /// List requests refused because their inputs were already spent
+    GetDoubleSpendAttempts(subcommands::GetDoubleSpendAttemptsCommand),
+    /// Show daily mint and melt counts with bucketed volumes
+    GetUsageStatistics(subcommands::GetUsageStatisticsCommand),
+    /// List the background tasks of the mint with their state and last failure
+    GetTaskHealth,
+    /// List the signed keyset rotation log and verify its chain
+    GetRotationLog(subcommands::GetRotationLogCommand),
+    /// Show the outbound liquidity of every payment backend
+    GetLiquidityStatus,
 }
 
 #[tokio::main]
@@ -153,9 +176,8 @@ async fn main() -> Result<()> {
             .await?
     };
 
-    // Create client with version header interceptor
-    let interceptor =
-        VersionInterceptor::new(VERSION_HEADER, cdk_common::MINT_RPC_PROTOCOL_VERSION);
+    // Create client with version header and bearer token interceptor
+    let interceptor = ClientInterceptor::new(cli.token.as_deref())?;
     let mut client = CdkMintClient::with_interceptor(channel, interceptor);
 
     match cli.command {
@@ -191,6 +213,7 @@ async fn main() -> Result<()> {
             }
             println!("total issued:     {} sat", info.total_issued);
             println!("total redeemed:   {} sat", info.total_redeemed);
+            println!("read only:        {}", info.read_only);
         }
         Commands::UpdateMotd(sub_command_args) => {
             subcommands::update_motd(&mut client, &sub_command_args).await?;
@@ -240,6 +263,36 @@ async fn main() -> Result<()> {
         Commands::RotateNextKeyset(sub_command_args) => {
             subcommands::rotate_next_keyset(&mut client, &sub_command_args).await?;
         }
+        Commands::DisableKeyset(sub_command_args) => {
+            subcommands::disable_keyset(&mut client, &sub_command_args).await?;
+        }
+        Commands::UpdateKeysetConfig(sub_command_args) => {
+            subcommands::update_keyset_config(&mut client, &sub_command_args).await?;
+        }
+        Commands::SetReadOnly(sub_command_args) => {
+            subcommands::set_read_only(&mut client, &sub_command_args).await?;
+        }
+        Commands::ExportKeysets(sub_command_args) => {
+            subcommands::export_keysets(&mut client, &sub_command_args).await?;
+        }
+        Commands::GetQuoteDetails(sub_command_args) => {
+            subcommands::get_quote_details(&mut client, &sub_command_args).await?;
+        }
+        Commands::GetDoubleSpendAttempts(sub_command_args) => {
+            subcommands::get_double_spend_attempts(&mut client, &sub_command_args).await?;
+        }
+        Commands::GetUsageStatistics(sub_command_args) => {
+            subcommands::get_usage_statistics(&mut client, &sub_command_args).await?;
+        }
+        Commands::GetTaskHealth => {
+            subcommands::get_task_health(&mut client).await?;
+        }
+        Commands::GetRotationLog(sub_command_args) => {
+            subcommands::get_rotation_log(&mut client, &sub_command_args).await?;
+        }
+        Commands::GetLiquidityStatus => {
+            subcommands::get_liquidity_status(&mut client).await?;
+        }
     }
 
     Ok(())
@@ -0,0 +1,40 @@
+use anyhow::Result;
+use clap::Args;
+use tonic::Request;
+
+use crate::{DisableKeysetRequest, InterceptedCdkMintClient};
+
+/// Command to disable a keyset without rotating to a new one
+///
+/// The mint stops signing with the keyset right away, e.g. after its keys were compromised.
+/// Proofs already signed with it are still accepted, and the unit has no active keyset until
+/// the next rotation.
+#[derive(Args, Debug)]
+pub struct DisableKeysetCommand {
+    /// The id of the keyset to disable
+    id: String,
+}
+
+/// Executes the disable_keyset command against the mint server
+///
+/// # Arguments
+/// * `client` - The RPC client used to communicate with the mint
+/// * `sub_command_args` - The keyset to disable
+pub async fn disable_keyset(
+    client: &mut InterceptedCdkMintClient,
+    sub_command_args: &DisableKeysetCommand,
+) -> Result<()> {
+    let response = client
+        .disable_keyset(Request::new(DisableKeysetRequest {
+            id: sub_command_args.id.clone(),
+        }))
+        .await?
+        .into_inner();
+
+    println!(
+        "Disabled keyset {} for unit {}, rotate the unit to mint it again",
+        response.id, response.unit
+    );
+
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use cdk_common::keyset_export::SignedKeysetExport;
+use clap::Args;
+use tonic::Request;
+
+use crate::{ExportKeysetsRequest, InterceptedCdkMintClient};
+
+/// Command to export all keysets' public keys as signed JSON
+///
+/// The export includes the derivation metadata of every keyset and is signed by the mint, with
+/// the key that signs its keyset rotation log, so third parties can archive it and later verify
+/// historical key material.
+#[derive(Args, Debug)]
+pub struct ExportKeysetsCommand {
+    /// Write the signed export to this file instead of stdout
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+/// Executes the export_keysets command against the mint server
+///
+/// This function fetches the signed keyset export from the mint, verifies it and writes the
+/// signed JSON document.
+///
+/// # Arguments
+/// * `client` - The RPC client used to communicate with the mint
+/// * `sub_command_args` - Where to write the export
+pub async fn export_keysets(
+    client: &mut InterceptedCdkMintClient,
+    sub_command_args: &ExportKeysetsCommand,
+) -> Result<()> {
+    let response = client
+        .export_keysets(Request::new(ExportKeysetsRequest {}))
+        .await?
+        .into_inner();
+
+    let signed: SignedKeysetExport = serde_json::from_str(&response.export)?;
+    signed.verify()?;
+
+    let json = serde_json::to_string_pretty(&signed)?;
+
+    match &sub_command_args.output {
+        Some(output) => {
+            std::fs::write(output, json)?;
+            println!(
+                "Exported keysets to {} signed by {}",
+                output.display(),
+                signed.signer
+            );
+        }
+        None => println!("{json}"),
+    }
+
+    Ok(())
+}
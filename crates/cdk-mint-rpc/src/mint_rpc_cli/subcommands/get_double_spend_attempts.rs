@@ -0,0 +1,46 @@
+use anyhow::Result;
+use clap::Args;
+use tonic::Request;
+
+use crate::{GetDoubleSpendAttemptsRequest, InterceptedCdkMintClient};
+
+/// Command to list the requests refused because their inputs were already spent
+///
+/// Prints one line per attempt, newest first, with the endpoint, quote and client hash so
+/// repeated attempts from the same client stand out.
+#[derive(Args, Debug)]
+pub struct GetDoubleSpendAttemptsCommand {
+    /// Only show attempts recorded at or after this unix timestamp
+    #[arg(long)]
+    since: Option<u64>,
+}
+
+/// Executes the get_double_spend_attempts command against the mint server
+///
+/// # Arguments
+/// * `client` - The RPC client used to communicate with the mint
+/// * `sub_command_args` - The time to list attempts from
+pub async fn get_double_spend_attempts(
+    client: &mut InterceptedCdkMintClient,
+    sub_command_args: &GetDoubleSpendAttemptsCommand,
+) -> Result<()> {
+    let response = client
+        .get_double_spend_attempts(Request::new(GetDoubleSpendAttemptsRequest {
+            since: sub_command_args.since,
+        }))
+        .await?
+        .into_inner();
+
+    for attempt in response.attempts {
+        println!(
+            "{} endpoint={} quote={} client={} ys={}",
+            attempt.created_time,
+            attempt.endpoint,
+            attempt.quote_id.unwrap_or("None".to_string()),
+            attempt.client_fingerprint.unwrap_or("None".to_string()),
+            attempt.ys.join(",")
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,37 @@
+use anyhow::Result;
+use tonic::Request;
+
+use crate::{GetLiquidityStatusRequest, InterceptedCdkMintClient};
+
+/// Executes the get_liquidity_status command against the mint server
+///
+/// Lists the outbound liquidity of every payment backend, and how melt quotes above it are
+/// handled.
+///
+/// # Arguments
+/// * `client` - The RPC client used to communicate with the mint
+pub async fn get_liquidity_status(client: &mut InterceptedCdkMintClient) -> Result<()> {
+    let response = client
+        .get_liquidity_status(Request::new(GetLiquidityStatusRequest {}))
+        .await?
+        .into_inner();
+
+    println!("policy: {}", response.policy);
+    for backend in response.backends {
+        match backend.outbound {
+            Some(outbound) => println!(
+                "{} {} ({}) outbound={} {}",
+                backend.unit, backend.method, backend.backend, outbound, backend.unit
+            ),
+            None => println!(
+                "{} {} ({}) outbound=unknown",
+                backend.unit, backend.method, backend.backend
+            ),
+        }
+        if let Some(error) = backend.error {
+            println!("  error: {error}");
+        }
+    }
+
+    Ok(())
+}
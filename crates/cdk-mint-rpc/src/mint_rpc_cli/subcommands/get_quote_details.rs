@@ -0,0 +1,59 @@
+use anyhow::Result;
+use clap::Args;
+use tonic::Request;
+
+use crate::{GetQuoteDetailsRequest, InterceptedCdkMintClient};
+
+/// Command to show the payment backend details of a mint or melt quote
+///
+/// Prints the payment hash, preimage, fee paid and backend name so a quote can be
+/// matched against the records of the Lightning node.
+#[derive(Args, Debug)]
+pub struct GetQuoteDetailsCommand {
+    /// The ID of the mint or melt quote
+    quote_id: String,
+}
+
+/// Executes the get_quote_details command against the mint server
+///
+/// # Arguments
+/// * `client` - The RPC client used to communicate with the mint
+/// * `sub_command_args` - The quote ID to look up
+pub async fn get_quote_details(
+    client: &mut InterceptedCdkMintClient,
+    sub_command_args: &GetQuoteDetailsCommand,
+) -> Result<()> {
+    let response = client
+        .get_quote_details(Request::new(GetQuoteDetailsRequest {
+            quote_id: sub_command_args.quote_id.clone(),
+        }))
+        .await?
+        .into_inner();
+
+    println!("kind:           {}", response.kind);
+    println!("state:          {}", response.state);
+    println!("unit:           {}", response.unit);
+    println!("payment method: {}", response.payment_method);
+    println!("request:        {}", response.request);
+    println!(
+        "payment hash:   {}",
+        response.payment_hash.unwrap_or("None".to_string())
+    );
+    println!(
+        "preimage:       {}",
+        response.preimage.unwrap_or("None".to_string())
+    );
+    println!(
+        "fee paid:       {}",
+        response
+            .fee_paid
+            .map(|fee| fee.to_string())
+            .unwrap_or("None".to_string())
+    );
+    println!(
+        "backend:        {}",
+        response.backend.unwrap_or("None".to_string())
+    );
+
+    Ok(())
+}
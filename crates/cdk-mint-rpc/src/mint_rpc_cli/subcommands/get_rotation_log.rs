@@ -0,0 +1,65 @@
+use anyhow::Result;
+use cdk_common::rotation_log::{verify_rotation_log, SignedRotationAttestation};
+use clap::Args;
+use tonic::Request;
+
+use crate::{GetRotationLogRequest, InterceptedCdkMintClient};
+
+/// Command to list the signed keyset rotation log
+///
+/// Every keyset rotation is attested by the signatory, each attestation committing to the
+/// previous one. The chain and the signatures are verified before the command succeeds.
+#[derive(Args, Debug)]
+pub struct GetRotationLogCommand {
+    /// Print the signed attestations as JSON, to archive them
+    #[arg(long)]
+    json: bool,
+}
+
+/// Executes the get_rotation_log command against the mint server
+///
+/// # Arguments
+/// * `client` - The RPC client used to communicate with the mint
+/// * `sub_command_args` - How to print the log
+pub async fn get_rotation_log(
+    client: &mut InterceptedCdkMintClient,
+    sub_command_args: &GetRotationLogCommand,
+) -> Result<()> {
+    let response = client
+        .get_rotation_log(Request::new(GetRotationLogRequest {}))
+        .await?
+        .into_inner();
+
+    let log = response
+        .attestations
+        .iter()
+        .map(|attestation| serde_json::from_str(attestation))
+        .collect::<Result<Vec<SignedRotationAttestation>, _>>()?;
+
+    verify_rotation_log(&log)?;
+
+    if sub_command_args.json {
+        println!("{}", serde_json::to_string_pretty(&log)?);
+        return Ok(());
+    }
+
+    for entry in &log {
+        let attestation = &entry.attestation;
+        println!(
+            "#{} {} unit={} old={} new={} signer={} reason={}",
+            attestation.sequence,
+            attestation.created_at,
+            attestation.unit,
+            attestation
+                .old_keyset_id
+                .map(|id| id.to_string())
+                .unwrap_or_else(|| "none".to_owned()),
+            attestation.new_keyset_id,
+            entry.signer,
+            attestation.reason.as_deref().unwrap_or("none"),
+        );
+    }
+    println!("{} attestations verified", log.len());
+
+    Ok(())
+}
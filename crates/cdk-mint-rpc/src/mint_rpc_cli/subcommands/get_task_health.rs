@@ -0,0 +1,29 @@
+use anyhow::Result;
+use tonic::Request;
+
+use crate::{GetTaskHealthRequest, InterceptedCdkMintClient};
+
+/// Executes the get_task_health command against the mint server
+///
+/// Lists the background tasks of the mint with their state and last failure.
+///
+/// # Arguments
+/// * `client` - The RPC client used to communicate with the mint
+pub async fn get_task_health(client: &mut InterceptedCdkMintClient) -> Result<()> {
+    let response = client
+        .get_task_health(Request::new(GetTaskHealthRequest {}))
+        .await?
+        .into_inner();
+
+    for task in response.tasks {
+        println!(
+            "{} status={} failures={} updated_at={}",
+            task.name, task.status, task.failures, task.updated_at
+        );
+        if let Some(last_error) = task.last_error {
+            println!("  last error: {last_error}");
+        }
+    }
+
+    Ok(())
+}
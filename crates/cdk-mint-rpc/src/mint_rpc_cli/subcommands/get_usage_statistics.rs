@@ -0,0 +1,47 @@
+use anyhow::Result;
+use clap::Args;
+use tonic::Request;
+
+use crate::{GetUsageStatisticsRequest, InterceptedCdkMintClient};
+
+/// Command to show the daily mint and melt statistics of the mint
+///
+/// Volumes are rounded down to a power of two by the mint. The mint must be started with
+/// usage statistics enabled.
+#[derive(Args, Debug)]
+pub struct GetUsageStatisticsCommand {
+    /// Only show days from this unix timestamp on
+    #[arg(long, default_value_t = 0)]
+    since: u64,
+}
+
+/// Executes the get_usage_statistics command against the mint server
+///
+/// # Arguments
+/// * `client` - The RPC client used to communicate with the mint
+/// * `sub_command_args` - The time to show statistics from
+pub async fn get_usage_statistics(
+    client: &mut InterceptedCdkMintClient,
+    sub_command_args: &GetUsageStatisticsCommand,
+) -> Result<()> {
+    let response = client
+        .get_usage_statistics(Request::new(GetUsageStatisticsRequest {
+            since: sub_command_args.since,
+        }))
+        .await?
+        .into_inner();
+
+    for usage in response.days {
+        println!(
+            "{} unit={} mints={} minted>={} melts={} melted>={}",
+            usage.day,
+            usage.unit,
+            usage.mint_count,
+            usage.mint_volume,
+            usage.melt_count,
+            usage.melt_volume
+        );
+    }
+
+    Ok(())
+}
@@ -1,11 +1,31 @@
 //! Subcommands for the mint RPC CLI
 
+/// Module for disabling a keyset without rotating
+mod disable_keyset;
+/// Module for exporting signed keyset public keys
+mod export_keysets;
+/// Module for listing recorded double spend attempts
+mod get_double_spend_attempts;
+/// Module for showing the outbound liquidity of the payment backends
+mod get_liquidity_status;
+/// Module for showing payment backend details of a quote
+mod get_quote_details;
+/// Module for listing the signed keyset rotation log
+mod get_rotation_log;
+/// Module for listing the health of the mint background tasks
+mod get_task_health;
+/// Module for showing coarse daily usage statistics
+mod get_usage_statistics;
 /// Module for rotating to the next keyset
 mod rotate_next_keyset;
+/// Module for toggling read-only maintenance mode
+mod set_read_only;
 /// Module for updating mint contact information
 mod update_contact;
 /// Module for updating the mint's icon URL
 mod update_icon_url;
+/// Module for updating the fee and amounts of a unit
+mod update_keyset_config;
 /// Module for updating the mint's long description
 mod update_long_description;
 /// Module for updating the mint's message of the day
@@ -27,9 +47,19 @@ mod update_ttl;
 /// Module for managing mint URLs
 mod update_urls;
 
+pub use disable_keyset::{disable_keyset, DisableKeysetCommand};
+pub use export_keysets::{export_keysets, ExportKeysetsCommand};
+pub use get_double_spend_attempts::{get_double_spend_attempts, GetDoubleSpendAttemptsCommand};
+pub use get_liquidity_status::get_liquidity_status;
+pub use get_quote_details::{get_quote_details, GetQuoteDetailsCommand};
+pub use get_rotation_log::{get_rotation_log, GetRotationLogCommand};
+pub use get_task_health::get_task_health;
+pub use get_usage_statistics::{get_usage_statistics, GetUsageStatisticsCommand};
 pub use rotate_next_keyset::{rotate_next_keyset, RotateNextKeysetCommand};
+pub use set_read_only::{set_read_only, SetReadOnlyCommand};
 pub use update_contact::{add_contact, remove_contact, AddContactCommand, RemoveContactCommand};
 pub use update_icon_url::{update_icon_url, UpdateIconUrlCommand};
+pub use update_keyset_config::{update_keyset_config, UpdateKeysetConfigCommand};
 pub use update_long_description::{update_long_description, UpdateLongDescriptionCommand};
 pub use update_motd::{update_motd, UpdateMotdCommand};
 pub use update_name::{update_name, UpdateNameCommand};
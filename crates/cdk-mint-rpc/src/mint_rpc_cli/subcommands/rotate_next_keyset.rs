@@ -26,6 +26,9 @@ pub struct RotateNextKeysetCommand {
     /// Final expiry unix timestamp for the keyset
     #[arg(long)]
     final_expiry: Option<u64>,
+    /// Why the keyset is rotated, recorded in the rotation log
+    #[arg(long)]
+    reason: Option<String>,
 }
 
 /// Executes the rotate_next_keyset command against the mint server
@@ -56,6 +59,7 @@ pub async fn rotate_next_keyset(
             input_fee_ppk: sub_command_args.input_fee_ppk,
             use_keyset_v2: sub_command_args.use_keyset_v2,
             final_expiry: sub_command_args.final_expiry,
+            reason: sub_command_args.reason.clone(),
         }))
         .await?;
 
@@ -0,0 +1,41 @@
+use anyhow::Result;
+use clap::Args;
+use tonic::Request;
+
+use crate::{InterceptedCdkMintClient, SetReadOnlyRequest};
+
+/// Command to toggle the mint's read-only maintenance mode
+///
+/// While read-only, the mint keeps serving keys, info and proof states and still accepts
+/// swaps and melts, but refuses new mint quotes. Useful for draining the mint before a
+/// shutdown or during Lightning node maintenance.
+#[derive(Args, Debug)]
+pub struct SetReadOnlyCommand {
+    /// Whether read-only mode should be enabled
+    #[arg(action = clap::ArgAction::Set)]
+    enabled: bool,
+    /// Message of the day advertised while read-only mode is enabled
+    #[arg(long)]
+    motd: Option<String>,
+}
+
+/// Executes the set_read_only command against the mint server
+///
+/// This function sends an RPC request to enable or disable read-only mode.
+///
+/// # Arguments
+/// * `client` - The RPC client used to communicate with the mint
+/// * `sub_command_args` - Whether to enable read-only mode and the MOTD to advertise
+pub async fn set_read_only(
+    client: &mut InterceptedCdkMintClient,
+    sub_command_args: &SetReadOnlyCommand,
+) -> Result<()> {
+    let _response = client
+        .set_read_only(Request::new(SetReadOnlyRequest {
+            enabled: sub_command_args.enabled,
+            motd: sub_command_args.motd.clone(),
+        }))
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,72 @@
+use anyhow::Result;
+use clap::Args;
+use tonic::Request;
+
+use crate::{InterceptedCdkMintClient, UpdateKeysetConfigRequest};
+
+/// Command to update the fee and amounts of a unit at runtime
+///
+/// The mint rotates the keyset of the unit only if the fee or the amounts differ from the
+/// active keyset, so running it twice with the same values does not rotate twice.
+#[derive(Args, Debug)]
+pub struct UpdateKeysetConfigCommand {
+    /// The unit type for the keyset (e.g., "sat")
+    #[arg(short, long)]
+    #[arg(default_value = "sat")]
+    unit: String,
+    /// The input fee in parts per thousand
+    #[arg(short, long)]
+    input_fee_ppk: u64,
+    /// The amounts of the keyset (e.g., "1,2,4,8,16"), the active ones when omitted
+    #[arg(short, long, conflicts_with = "max_order")]
+    amounts: Option<String>,
+    /// Use the powers of two below 2^max_order as amounts
+    #[arg(short, long)]
+    max_order: Option<u32>,
+}
+
+/// Executes the update_keyset_config command against the mint server
+///
+/// # Arguments
+/// * `client` - The RPC client used to communicate with the mint
+/// * `sub_command_args` - The unit and its new fee and amounts
+pub async fn update_keyset_config(
+    client: &mut InterceptedCdkMintClient,
+    sub_command_args: &UpdateKeysetConfigCommand,
+) -> Result<()> {
+    let amounts = if let Some(amounts_str) = &sub_command_args.amounts {
+        amounts_str
+            .split(',')
+            .map(|s| s.trim().parse::<u64>())
+            .collect::<Result<Vec<u64>, _>>()?
+    } else {
+        vec![]
+    };
+
+    let response = client
+        .update_keyset_config(Request::new(UpdateKeysetConfigRequest {
+            unit: sub_command_args.unit.clone(),
+            amounts,
+            input_fee_ppk: sub_command_args.input_fee_ppk,
+            max_order: sub_command_args.max_order,
+        }))
+        .await?
+        .into_inner();
+
+    if response.rotated {
+        println!(
+            "Rotated to new keyset {} for unit {} with amounts {} and fee of {}",
+            response.id,
+            response.unit,
+            serde_json::to_string(&response.amounts)?,
+            response.input_fee_ppk
+        );
+    } else {
+        println!(
+            "Keyset {} for unit {} already has this config, not rotated",
+            response.id, response.unit
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,215 @@
+//! Static bearer token authentication of the management RPC
+//!
+//! Clients send `authorization: Bearer <token>` with every call. Read only tokens may call the
+//! `Get*` methods, manage tokens may call every method. Unless token authentication is enabled
+//! every call is let through and the TLS client certificate is the only access control.
+//! Enabled without tokens, every call is refused.
+
+use std::fmt;
+use std::sync::Arc;
+
+use cdk_common::bearer::{BearerError, BearerTokens};
+use cdk_common::grpc::{VersionInterceptor, VERSION_HEADER};
+use tonic::codegen::http;
+use tonic::metadata::errors::InvalidMetadataValue;
+use tonic::metadata::AsciiMetadataValue;
+use tonic::service::Interceptor;
+use tonic::{Request, Status};
+use tower::filter::Predicate;
+use tower::BoxError;
+
+/// Metadata key carrying the bearer token
+pub const AUTHORIZATION_HEADER: &str = "authorization";
+
+/// What a management RPC token is allowed to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Role {
+    /// Call the `Get*` methods only
+    ReadOnly,
+    /// Call every method
+    Manage,
+}
+
+impl Role {
+    /// Role needed to call the method at the gRPC `path`
+    fn required_for(path: &str) -> Self {
+        let method = path.rsplit('/').next().unwrap_or_default();
+
+        if method.starts_with("Get") {
+            Self::ReadOnly
+        } else {
+            Self::Manage
+        }
+    }
+}
+
+/// Bearer tokens accepted by the management RPC server
+///
+/// Installed as a [`Predicate`] in front of the server, so calls are rejected before they
+/// reach any method. The default lets every call through.
+#[derive(Debug, Clone, Default)]
+pub struct RpcAuth {
+    tokens: Arc<BearerTokens<Role>>,
+    enabled: bool,
+}
+
+impl RpcAuth {
+    /// Require one of `tokens`, each with its role, on every call
+    pub fn new(tokens: impl IntoIterator<Item = (String, Role)>) -> Self {
+        Self {
+            tokens: Arc::new(BearerTokens::new(tokens)),
+            enabled: true,
+        }
+    }
+
+    /// Whether calls need a token at all
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn authorize(&self, path: &str, authorization: Option<&str>) -> Result<(), Status> {
+        if !self.is_enabled() {
+            return Ok(());
+        }
+
+        self.tokens
+            .authorize(authorization, &Role::required_for(path))
+            .map_err(|err| match err {
+                BearerError::Missing | BearerError::Invalid => {
+                    Status::unauthenticated(err.to_string())
+                }
+                BearerError::Forbidden => {
+                    Status::permission_denied(format!("Token is not allowed to call {path}"))
+                }
+            })
+    }
+}
+
+impl<B> Predicate<http::Request<B>> for RpcAuth {
+    type Request = http::Request<B>;
+
+    fn check(&mut self, request: http::Request<B>) -> Result<Self::Request, BoxError> {
+        let authorization = request
+            .headers()
+            .get(AUTHORIZATION_HEADER)
+            .and_then(|value| value.to_str().ok());
+
+        if let Err(status) = self.authorize(request.uri().path(), authorization) {
+            tracing::warn!(
+                "Rejected management RPC call to {}: {}",
+                request.uri().path(),
+                status.message()
+            );
+            return Err(Box::new(status));
+        }
+
+        Ok(request)
+    }
+}
+
+/// Client side interceptor adding the protocol version and the bearer token, if any
+#[derive(Clone)]
+pub struct ClientInterceptor {
+    version: VersionInterceptor,
+    authorization: Option<AsciiMetadataValue>,
+}
+
+impl fmt::Debug for ClientInterceptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientInterceptor")
+            .field("version", &self.version)
+            .field(
+                "authorization",
+                &self.authorization.as_ref().map(|_| "[REDACTED]"),
+            )
+            .finish()
+    }
+}
+
+impl ClientInterceptor {
+    /// Interceptor sending `token`, or no token when it is `None`
+    pub fn new(token: Option<&str>) -> Result<Self, InvalidMetadataValue> {
+        Ok(Self {
+            version: VersionInterceptor::new(VERSION_HEADER, cdk_common::MINT_RPC_PROTOCOL_VERSION),
+            authorization: token
+                .map(|token| format!("Bearer {token}").parse())
+                .transpose()?,
+        })
+    }
+}
+
+impl Interceptor for ClientInterceptor {
+    fn call(&mut self, request: Request<()>) -> Result<Request<()>, Status> {
+        let mut request = self.version.call(request)?;
+
+        if let Some(authorization) = &self.authorization {
+            request
+                .metadata_mut()
+                .insert(AUTHORIZATION_HEADER, authorization.clone());
+        }
+
+        Ok(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GET_INFO: &str = "/cdk_mint_management_v1.CdkMint/GetInfo";
+    const UPDATE_MOTD: &str = "/cdk_mint_management_v1.CdkMint/UpdateMotd";
+
+    #[test]
+    fn tokens_are_scoped_by_role() {
+        let auth = RpcAuth::new([
+            ("reader".to_string(), Role::ReadOnly),
+            ("admin".to_string(), Role::Manage),
+        ]);
+
+        assert!(auth.authorize(GET_INFO, Some("Bearer reader")).is_ok());
+        assert!(auth.authorize(GET_INFO, Some("Bearer admin")).is_ok());
+        assert!(auth.authorize(UPDATE_MOTD, Some("Bearer admin")).is_ok());
+
+        let denied = auth
+            .authorize(UPDATE_MOTD, Some("Bearer reader"))
+            .unwrap_err();
+        assert_eq!(denied.code(), tonic::Code::PermissionDenied);
+
+        for authorization in [None, Some("reader"), Some("Bearer unknown")] {
+            let rejected = auth.authorize(GET_INFO, authorization).unwrap_err();
+            assert_eq!(rejected.code(), tonic::Code::Unauthenticated);
+        }
+    }
+
+    #[test]
+    fn disabled_auth_lets_every_call_through() {
+        let auth = RpcAuth::default();
+
+        assert!(!auth.is_enabled());
+        assert!(auth.authorize(UPDATE_MOTD, None).is_ok());
+    }
+
+    #[test]
+    fn enabled_auth_without_tokens_refuses_every_call() {
+        let auth = RpcAuth::new([]);
+
+        assert!(auth.is_enabled());
+        for authorization in [None, Some("Bearer ")] {
+            let rejected = auth.authorize(GET_INFO, authorization).unwrap_err();
+            assert_eq!(rejected.code(), tonic::Code::Unauthenticated);
+        }
+    }
+
+    #[test]
+    fn client_interceptor_sends_bearer_token() {
+        let mut interceptor = ClientInterceptor::new(Some("admin")).unwrap();
+        let request = interceptor.call(Request::new(())).unwrap();
+
+        assert_eq!(
+            request.metadata().get(AUTHORIZATION_HEADER).unwrap(),
+            "Bearer admin"
+        );
+        assert!(request.metadata().get(VERSION_HEADER).is_some());
+        assert!(!format!("{interceptor:?}").contains("admin"));
+    }
+}
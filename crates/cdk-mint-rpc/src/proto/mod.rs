@@ -2,8 +2,10 @@
 
 tonic::include_proto!("cdk_mint_management_v1");
 
+mod auth;
 mod server;
 
+pub use auth::{ClientInterceptor, Role, RpcAuth, AUTHORIZATION_HEADER};
 /// Protocol version for gRPC Mint RPC communication
 pub use cdk_common::MINT_RPC_PROTOCOL_VERSION as PROTOCOL_VERSION;
 pub use server::MintRPCServer;
@@ -3,10 +3,10 @@ use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::Arc;
 
-use cdk::mint::{Mint, MintQuote};
+use cdk::mint::{LiquidityPolicy, Mint, MintQuote, TaskStatus};
 use cdk::nuts::nut04::MintMethodSettings;
 use cdk::nuts::nut05::MeltMethodSettings;
-use cdk::nuts::{CurrencyUnit, MintQuoteState, PaymentMethod};
+use cdk::nuts::{CurrencyUnit, Id, MintQuoteState, PaymentMethod};
 use cdk::types::QuoteTTL;
 use cdk::Amount;
 use cdk_common::grpc::create_version_check_interceptor;
@@ -17,12 +17,20 @@ use tokio::task::JoinHandle;
 use tokio::time::Duration;
 use tonic::transport::{Certificate, Identity, Server, ServerTlsConfig};
 use tonic::{Request, Response, Status};
+use tower::filter::FilterLayer;
 
+use super::RpcAuth;
 use crate::cdk_mint_server::{CdkMint, CdkMintServer};
 use crate::{
-    ContactInfo, GetInfoRequest, GetInfoResponse, GetQuoteTtlRequest, GetQuoteTtlResponse,
-    RotateNextKeysetRequest, RotateNextKeysetResponse, UpdateContactRequest,
-    UpdateDescriptionRequest, UpdateIconUrlRequest, UpdateMotdRequest, UpdateNameRequest,
+    ContactInfo, DailyUsage, DisableKeysetRequest, DisableKeysetResponse, DoubleSpendAttempt,
+    ExportKeysetsRequest, ExportKeysetsResponse, GetDoubleSpendAttemptsRequest,
+    GetDoubleSpendAttemptsResponse, GetInfoRequest, GetInfoResponse, GetLiquidityStatusRequest,
+    GetLiquidityStatusResponse, GetQuoteDetailsRequest, GetQuoteDetailsResponse,
+    GetQuoteTtlRequest, GetQuoteTtlResponse, GetRotationLogRequest, GetRotationLogResponse,
+    GetTaskHealthRequest, GetTaskHealthResponse, GetUsageStatisticsRequest,
+    GetUsageStatisticsResponse, RotateNextKeysetRequest, RotateNextKeysetResponse,
+    SetReadOnlyRequest, UpdateContactRequest, UpdateDescriptionRequest, UpdateIconUrlRequest,
+    UpdateKeysetConfigRequest, UpdateKeysetConfigResponse, UpdateMotdRequest, UpdateNameRequest,
     UpdateNut04QuoteRequest, UpdateNut04Request, UpdateNut05Request, UpdateQuoteTtlRequest,
     UpdateResponse, UpdateTosUrlRequest, UpdateUrlRequest,
 };
@@ -49,6 +57,7 @@ pub struct MintRPCServer {
     mint: Arc<Mint>,
     shutdown: Arc<Notify>,
     handle: Option<Arc<JoinHandle<Result<(), Error>>>>,
+    auth: RpcAuth,
 }
 
 impl MintRPCServer {
@@ -64,9 +73,16 @@ impl MintRPCServer {
             mint,
             shutdown: Arc::new(Notify::new()),
             handle: None,
+            auth: RpcAuth::default(),
         })
     }
 
+    /// Require every call to carry one of the bearer tokens of `auth`
+    pub fn with_auth(mut self, auth: RpcAuth) -> Self {
+        self.auth = auth;
+        self
+    }
+
     /// Starts the RPC server
     ///
     /// # Arguments
@@ -136,25 +152,28 @@ impl MintRPCServer {
                     .identity(server_identity)
                     .client_ca_root(client_ca_cert);
 
-                Server::builder().tls_config(tls_config)?.add_service(
-                    CdkMintServer::with_interceptor(
+                Server::builder()
+                    .tls_config(tls_config)?
+                    .layer(FilterLayer::new(self.auth.clone()))
+                    .add_service(CdkMintServer::with_interceptor(
                         self.clone(),
                         create_version_check_interceptor(
                             cdk_common::grpc::VERSION_HEADER,
                             cdk_common::MINT_RPC_PROTOCOL_VERSION,
                         ),
-                    ),
-                )
+                    ))
             }
             None => {
                 tracing::warn!("No valid TLS configuration found, starting insecure server");
-                Server::builder().add_service(CdkMintServer::with_interceptor(
-                    self.clone(),
-                    create_version_check_interceptor(
-                        cdk_common::grpc::VERSION_HEADER,
-                        cdk_common::MINT_RPC_PROTOCOL_VERSION,
-                    ),
-                ))
+                Server::builder()
+                    .layer(FilterLayer::new(self.auth.clone()))
+                    .add_service(CdkMintServer::with_interceptor(
+                        self.clone(),
+                        create_version_check_interceptor(
+                            cdk_common::grpc::VERSION_HEADER,
+                            cdk_common::MINT_RPC_PROTOCOL_VERSION,
+                        ),
+                    ))
             }
         };
 
@@ -226,6 +245,12 @@ impl CdkMint for MintRPCServer {
         let total_redeemed: Amount = Amount::try_sum(total_redeemed.values().cloned())
             .map_err(|_| Status::internal("Overflow".to_string()))?;
 
+        let read_only = self
+            .mint
+            .is_read_only()
+            .await
+            .map_err(|err| Status::internal(err.to_string()))?;
+
         let contact = info
             .contact
             .unwrap_or_default()
@@ -248,6 +273,7 @@ impl CdkMint for MintRPCServer {
             urls: info.urls.unwrap_or_default(),
             total_issued: total_issued.into(),
             total_redeemed: total_redeemed.into(),
+            read_only,
         });
 
         Ok(response)
@@ -795,12 +821,13 @@ impl CdkMint for MintRPCServer {
 
         let keyset_info = self
             .mint
-            .rotate_keyset(
+            .rotate_keyset_with_reason(
                 unit,
                 amounts,
                 request.input_fee_ppk.unwrap_or(0),
                 request.use_keyset_v2.unwrap_or(true),
                 request.final_expiry,
+                request.reason,
             )
             .await
             .map_err(|_| Status::invalid_argument("Could not rotate keyset".to_string()))?;
@@ -812,6 +839,280 @@ impl CdkMint for MintRPCServer {
             input_fee_ppk: keyset_info.input_fee_ppk,
         }))
     }
+
+    /// Stops signing with a keyset without rotating to a new one
+    async fn disable_keyset(
+        &self,
+        request: Request<DisableKeysetRequest>,
+    ) -> Result<Response<DisableKeysetResponse>, Status> {
+        let id = Id::from_str(&request.into_inner().id)
+            .map_err(|_| Status::invalid_argument("Invalid keyset id".to_string()))?;
+
+        let keyset_info = self
+            .mint
+            .disable_keyset(id)
+            .await
+            .map_err(|err| Status::internal(err.to_string()))?;
+
+        Ok(Response::new(DisableKeysetResponse {
+            id: keyset_info.id.to_string(),
+            unit: keyset_info.unit.to_string(),
+        }))
+    }
+
+    /// Updates the fee and amounts of a unit, rotating its keyset only if they changed
+    async fn update_keyset_config(
+        &self,
+        request: Request<UpdateKeysetConfigRequest>,
+    ) -> Result<Response<UpdateKeysetConfigResponse>, Status> {
+        let request = request.into_inner();
+
+        let unit = CurrencyUnit::from_str(&request.unit)
+            .map_err(|_| Status::invalid_argument("Invalid unit".to_string()))?;
+
+        let amounts = match (request.amounts.is_empty(), request.max_order) {
+            (true, Some(max_order)) if max_order > 64 => {
+                return Err(Status::invalid_argument(
+                    "max_order can not be above 64".to_string(),
+                ))
+            }
+            (true, Some(max_order)) => (0..max_order).map(|i| 2_u64.pow(i)).collect(),
+            _ => request.amounts,
+        };
+
+        let previous = self
+            .mint
+            .keysets()
+            .keysets
+            .into_iter()
+            .find(|keyset| keyset.active && keyset.unit == unit)
+            .map(|keyset| keyset.id);
+
+        let keyset_info = self
+            .mint
+            .update_keyset_config(unit, amounts, request.input_fee_ppk)
+            .await
+            .map_err(|err| Status::internal(err.to_string()))?;
+
+        Ok(Response::new(UpdateKeysetConfigResponse {
+            rotated: previous != Some(keyset_info.id),
+            id: keyset_info.id.to_string(),
+            unit: keyset_info.unit.to_string(),
+            amounts: keyset_info.amounts,
+            input_fee_ppk: keyset_info.input_fee_ppk,
+        }))
+    }
+
+    /// Enables or disables read-only maintenance mode
+    async fn set_read_only(
+        &self,
+        request: Request<SetReadOnlyRequest>,
+    ) -> Result<Response<UpdateResponse>, Status> {
+        let request = request.into_inner();
+
+        self.mint
+            .set_read_only(request.enabled, request.motd)
+            .await
+            .map_err(|err| Status::internal(err.to_string()))?;
+
+        Ok(Response::new(UpdateResponse {}))
+    }
+
+    /// Exports every keyset with its public keys and derivation metadata
+    async fn export_keysets(
+        &self,
+        _request: Request<ExportKeysetsRequest>,
+    ) -> Result<Response<ExportKeysetsResponse>, Status> {
+        let export = self
+            .mint
+            .export_keysets()
+            .await
+            .map_err(|err| Status::internal(err.to_string()))?;
+        let export =
+            serde_json::to_string(&export).map_err(|err| Status::internal(err.to_string()))?;
+
+        Ok(Response::new(ExportKeysetsResponse { export }))
+    }
+
+    /// Returns the payment backend details of a mint or melt quote
+    async fn get_quote_details(
+        &self,
+        request: Request<GetQuoteDetailsRequest>,
+    ) -> Result<Response<GetQuoteDetailsResponse>, Status> {
+        let quote_id = request
+            .into_inner()
+            .quote_id
+            .parse()
+            .map_err(|_| Status::invalid_argument("Invalid quote id".to_string()))?;
+
+        let localstore = self.mint.localstore();
+
+        if let Some(quote) = localstore
+            .get_mint_quote(&quote_id)
+            .await
+            .map_err(|err| Status::internal(err.to_string()))?
+        {
+            return Ok(Response::new(GetQuoteDetailsResponse {
+                kind: "mint".to_string(),
+                state: quote.state().to_string(),
+                unit: quote.unit.to_string(),
+                payment_method: quote.payment_method.to_string(),
+                request: quote.request.clone(),
+                payment_hash: Some(quote.request_lookup_id.to_string()),
+                preimage: None,
+                fee_paid: None,
+                backend: quote.backend,
+            }));
+        }
+
+        let quote = localstore
+            .get_melt_quote(&quote_id)
+            .await
+            .map_err(|err| Status::internal(err.to_string()))?
+            .ok_or(Status::not_found("Could not find quote".to_string()))?;
+
+        Ok(Response::new(GetQuoteDetailsResponse {
+            kind: "melt".to_string(),
+            state: quote.state.to_string(),
+            unit: quote.unit.to_string(),
+            payment_method: quote.payment_method.to_string(),
+            request: quote.request.to_string(),
+            payment_hash: quote.request_lookup_id.as_ref().map(|id| id.to_string()),
+            preimage: quote.payment_proof.clone(),
+            fee_paid: quote.fee_paid.as_ref().map(|fee| fee.value()),
+            backend: quote.backend.clone(),
+        }))
+    }
+
+    async fn get_double_spend_attempts(
+        &self,
+        request: Request<GetDoubleSpendAttemptsRequest>,
+    ) -> Result<Response<GetDoubleSpendAttemptsResponse>, Status> {
+        let attempts = self
+            .mint
+            .double_spend_attempts(request.into_inner().since)
+            .await
+            .map_err(|err| Status::internal(err.to_string()))?;
+
+        Ok(Response::new(GetDoubleSpendAttemptsResponse {
+            attempts: attempts
+                .into_iter()
+                .map(|attempt| DoubleSpendAttempt {
+                    created_time: attempt.created_time,
+                    endpoint: attempt.endpoint.to_string(),
+                    quote_id: attempt.quote_id.map(|id| id.to_string()),
+                    client_fingerprint: attempt.client_fingerprint,
+                    ys: attempt.ys.iter().map(|y| y.to_hex()).collect(),
+                })
+                .collect(),
+        }))
+    }
+
+    async fn get_usage_statistics(
+        &self,
+        request: Request<GetUsageStatisticsRequest>,
+    ) -> Result<Response<GetUsageStatisticsResponse>, Status> {
+        if !self.mint.usage_statistics_enabled() {
+            return Err(Status::failed_precondition(
+                "Usage statistics are not enabled".to_string(),
+            ));
+        }
+
+        let days = self
+            .mint
+            .usage_statistics(request.into_inner().since)
+            .await
+            .map_err(|err| Status::internal(err.to_string()))?;
+
+        Ok(Response::new(GetUsageStatisticsResponse {
+            days: days
+                .into_iter()
+                .map(|usage| DailyUsage {
+                    day: usage.day,
+                    unit: usage.unit.to_string(),
+                    mint_count: usage.mint_count,
+                    mint_volume: usage.mint_volume.to_u64(),
+                    melt_count: usage.melt_count,
+                    melt_volume: usage.melt_volume.to_u64(),
+                })
+                .collect(),
+        }))
+    }
+
+    async fn get_task_health(
+        &self,
+        _request: Request<GetTaskHealthRequest>,
+    ) -> Result<Response<GetTaskHealthResponse>, Status> {
+        Ok(Response::new(GetTaskHealthResponse {
+            tasks: self
+                .mint
+                .task_health()
+                .into_iter()
+                .map(|health| crate::TaskHealth {
+                    name: health.name,
+                    status: match health.status {
+                        TaskStatus::Running => "running",
+                        TaskStatus::Restarting => "restarting",
+                        TaskStatus::Finished => "finished",
+                        TaskStatus::Failed => "failed",
+                        TaskStatus::Panicked => "panicked",
+                    }
+                    .to_owned(),
+                    failures: health.failures,
+                    last_error: health.last_error,
+                    updated_at: health.updated_at,
+                })
+                .collect(),
+        }))
+    }
+
+    /// Returns the signed attestations of the keyset rotations
+    async fn get_rotation_log(
+        &self,
+        _request: Request<GetRotationLogRequest>,
+    ) -> Result<Response<GetRotationLogResponse>, Status> {
+        let log = self
+            .mint
+            .rotation_log()
+            .await
+            .map_err(|err| Status::internal(err.to_string()))?;
+
+        Ok(Response::new(GetRotationLogResponse {
+            attestations: log
+                .iter()
+                .map(serde_json::to_string)
+                .collect::<Result<_, _>>()
+                .map_err(|err| Status::internal(err.to_string()))?,
+        }))
+    }
+
+    /// Returns the outbound liquidity of every payment backend
+    async fn get_liquidity_status(
+        &self,
+        _request: Request<GetLiquidityStatusRequest>,
+    ) -> Result<Response<GetLiquidityStatusResponse>, Status> {
+        Ok(Response::new(GetLiquidityStatusResponse {
+            policy: match self.mint.liquidity_policy() {
+                LiquidityPolicy::Warn => "warn",
+                LiquidityPolicy::Reject => "reject",
+                LiquidityPolicy::Partial => "partial",
+            }
+            .to_owned(),
+            backends: self
+                .mint
+                .liquidity_status()
+                .await
+                .into_iter()
+                .map(|status| crate::LiquidityStatus {
+                    unit: status.unit.to_string(),
+                    method: status.method.to_string(),
+                    backend: status.backend,
+                    outbound: status.outbound.map(|outbound| outbound.value()),
+                    error: status.error,
+                })
+                .collect(),
+        }))
+    }
 }
 
 #[cfg(test)]
@@ -829,7 +1130,10 @@ mod tests {
 
     use super::*;
     use crate::cdk_mint_server::CdkMint;
-    use crate::{GetInfoRequest, UpdateTosUrlRequest};
+    use crate::{
+        ExportKeysetsRequest, GetInfoRequest, GetLiquidityStatusRequest, GetQuoteDetailsRequest,
+        GetRotationLogRequest, RotateNextKeysetRequest, UpdateTosUrlRequest,
+    };
 
     async fn create_test_rpc_server() -> MintRPCServer {
         let db = Arc::new(cdk_sqlite::mint::memory::empty().await.unwrap());
@@ -881,6 +1185,7 @@ mod tests {
             mint: Arc::new(mint),
             shutdown: Arc::new(Notify::new()),
             handle: None,
+            auth: RpcAuth::default(),
         }
     }
 
@@ -932,4 +1237,146 @@ mod tests {
 
         assert_eq!(response.into_inner().tos_url.unwrap(), tos);
     }
+
+    #[tokio::test]
+    async fn test_export_keysets_is_signed_by_the_mint() {
+        let server = create_test_rpc_server().await;
+
+        let response = server
+            .export_keysets(Request::new(ExportKeysetsRequest {}))
+            .await
+            .unwrap();
+
+        let signed: cdk_common::keyset_export::SignedKeysetExport =
+            serde_json::from_str(&response.into_inner().export).unwrap();
+        signed.verify().unwrap();
+        assert_eq!(
+            signed.export.keysets.len(),
+            server.mint.keysets().keysets.len()
+        );
+
+        // Signed by the signer of the rotation log, with the derivation paths exported in full
+        server
+            .rotate_next_keyset(Request::new(RotateNextKeysetRequest {
+                unit: "sat".to_owned(),
+                amounts: vec![1, 2, 4, 8],
+                ..Default::default()
+            }))
+            .await
+            .unwrap();
+        let log = server.mint.rotation_log().await.unwrap();
+        assert_eq!(log.last().unwrap().signer, signed.signer);
+        for exported in &signed.export.keysets {
+            let index = exported.derivation_path_index.unwrap();
+            assert_eq!(exported.derivation_path.len(), 3);
+            assert_eq!(
+                exported.derivation_path.into_iter().last(),
+                Some(&cdk_common::bitcoin::bip32::ChildNumber::from_hardened_idx(index).unwrap())
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rotation_is_attested_in_rotation_log() {
+        let server = create_test_rpc_server().await;
+
+        let before = server
+            .get_rotation_log(Request::new(GetRotationLogRequest {}))
+            .await
+            .unwrap()
+            .into_inner()
+            .attestations
+            .len();
+
+        let rotated = server
+            .rotate_next_keyset(Request::new(RotateNextKeysetRequest {
+                unit: "sat".to_owned(),
+                amounts: vec![1, 2, 4, 8],
+                reason: Some("Suspected key compromise".to_owned()),
+                ..Default::default()
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+
+        let log = server
+            .get_rotation_log(Request::new(GetRotationLogRequest {}))
+            .await
+            .unwrap()
+            .into_inner()
+            .attestations
+            .iter()
+            .map(|attestation| serde_json::from_str(attestation).unwrap())
+            .collect::<Vec<cdk_common::rotation_log::SignedRotationAttestation>>();
+        assert_eq!(log.len(), before + 1);
+        cdk_common::rotation_log::verify_rotation_log(&log).unwrap();
+
+        let last = &log.last().unwrap().attestation;
+        assert_eq!(last.new_keyset_id.to_string(), rotated.id);
+        assert_eq!(last.reason.as_deref(), Some("Suspected key compromise"));
+    }
+
+    #[tokio::test]
+    async fn test_get_liquidity_status_lists_backends() {
+        let server = create_test_rpc_server().await;
+
+        let response = server
+            .get_liquidity_status(Request::new(GetLiquidityStatusRequest {}))
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert_eq!(response.policy, "warn");
+        assert_eq!(response.backends.len(), 1);
+
+        let backend = &response.backends[0];
+        assert_eq!(backend.unit, "sat");
+        assert_eq!(backend.method, "bolt11");
+        assert_eq!(backend.backend, "fake-wallet");
+        // The fake wallet does not report its liquidity unless configured to
+        assert!(backend.outbound.is_none());
+        assert!(backend.error.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_get_quote_details_includes_backend() {
+        let server = create_test_rpc_server().await;
+
+        let quote = server
+            .mint
+            .get_mint_quote(
+                cdk::nuts::MintQuoteBolt11Request {
+                    amount: 100.into(),
+                    unit: CurrencyUnit::Sat,
+                    description: None,
+                    pubkey: None,
+                }
+                .into(),
+            )
+            .await
+            .unwrap();
+        let cdk_common::mint_quote::MintQuoteResponse::Bolt11(quote) = quote else {
+            panic!("expected bolt11 mint quote");
+        };
+
+        let details = server
+            .get_quote_details(Request::new(GetQuoteDetailsRequest {
+                quote_id: quote.quote.to_string(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert_eq!(details.kind, "mint");
+        assert_eq!(details.backend.as_deref(), Some("fake-wallet"));
+        assert!(details.payment_hash.is_some());
+
+        let missing = server
+            .get_quote_details(Request::new(GetQuoteDetailsRequest {
+                quote_id: cdk_common::QuoteId::new().to_string(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(missing.code(), tonic::Code::NotFound);
+    }
 }
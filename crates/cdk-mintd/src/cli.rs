@@ -1,6 +1,6 @@
 use std::path::PathBuf;
 
-use clap::Parser;
+use clap::{Args, Parser, Subcommand};
 
 #[derive(Debug, Parser)]
 #[command(about = "A cashu mint written in rust", author = env!("CARGO_PKG_AUTHORS"), version = env!("CARGO_PKG_VERSION"))]
@@ -36,4 +36,44 @@ pub struct CLIArgs {
         default_value = "true"
     )]
     pub enable_logging: bool,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Print the keyset ids and public keys derived from the seed, without starting the mint
+    ///
+    /// The seed, network and input fee are read like the mint does, from the config file, the
+    /// environment and `--seed-file`. Compare the output with the keysets the mint publishes to
+    /// check that a seed backup reproduces them.
+    DeriveKeysets(DeriveKeysetsArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct DeriveKeysetsArgs {
+    /// Unit of the keysets
+    #[arg(long, default_value = "sat")]
+    pub unit: String,
+    /// Derive the keysets at the derivation path indices from 0 to this one
+    #[arg(long, default_value_t = 0)]
+    pub max_index: u32,
+    /// Derive a single keyset at this derivation path instead of the ones of the unit
+    #[arg(long)]
+    pub derivation_path: Option<bitcoin::bip32::DerivationPath>,
+    /// Amounts of the keysets are the powers of two below 2^max_order
+    #[arg(long, default_value_t = 32)]
+    pub max_order: u32,
+    /// Input fee of the keysets, committed to by their ids. Defaults to the configured one
+    #[arg(long)]
+    pub input_fee_ppk: Option<u64>,
+    /// Final expiry of the keysets, committed to by their ids
+    #[arg(long)]
+    pub final_expiry: Option<u64>,
+    /// Network the keys are derived for. Defaults to the configured one
+    #[arg(long)]
+    pub network: Option<bitcoin::Network>,
+    /// Derive the legacy version 00 ids
+    #[arg(long, default_value_t = false)]
+    pub legacy_id: bool,
 }
@@ -1,9 +1,13 @@
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
+use std::str::FromStr;
 
+use bip39::Mnemonic;
 use bitcoin::hashes::{sha256, Hash};
-use cdk::nuts::{CurrencyUnit, PublicKey};
+use cdk::mint::RoutingPolicy;
+use cdk::nuts::{CurrencyUnit, Id, PublicKey};
 use cdk::Amount;
-use cdk_axum::cache;
+use cdk_axum::{cache, RateLimit, RateLimitedEndpoint, RateLimiter};
 use cdk_common::common::QuoteTTL;
 use config::{Config, ConfigError, File};
 use serde::{Deserialize, Serialize};
@@ -35,6 +39,44 @@ impl std::str::FromStr for LoggingOutput {
     }
 }
 
+/// What the mint does with melt quotes above the outbound liquidity of their backend
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum MeltLiquidityPolicy {
+    /// Create the quote anyway and log a warning (default)
+    #[default]
+    Warn,
+    /// Refuse the quote
+    Reject,
+    /// Quote the part of the invoice the liquidity covers as a multi-part payment
+    Partial,
+}
+
+impl std::str::FromStr for MeltLiquidityPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "warn" => Ok(MeltLiquidityPolicy::Warn),
+            "reject" => Ok(MeltLiquidityPolicy::Reject),
+            "partial" => Ok(MeltLiquidityPolicy::Partial),
+            _ => Err(format!(
+                "Unknown melt liquidity policy: {s}. Valid options: warn, reject, partial"
+            )),
+        }
+    }
+}
+
+impl From<MeltLiquidityPolicy> for cdk::mint::LiquidityPolicy {
+    fn from(policy: MeltLiquidityPolicy) -> Self {
+        match policy {
+            MeltLiquidityPolicy::Warn => Self::Warn,
+            MeltLiquidityPolicy::Reject => Self::Reject,
+            MeltLiquidityPolicy::Partial => Self::Partial,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct LoggingConfig {
     /// Where to output logs: stdout, file, or both
@@ -44,6 +86,9 @@ pub struct LoggingConfig {
     pub console_level: Option<String>,
     /// Log level for file output (when file or both)
     pub file_level: Option<String>,
+    /// Spans logged one time in N by name, with everything inside them. 0 drops them all
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub sampled_spans: HashMap<String, u64>,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -54,11 +99,26 @@ pub struct Info {
     /// Overrides mnemonic
     pub seed: Option<String>,
     pub mnemonic: Option<String>,
+    /// Optional BIP-39 passphrase (25th word) used with `mnemonic`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mnemonic_passphrase: Option<String>,
     pub signatory_url: Option<String>,
     pub signatory_certs: Option<String>,
+    /// API key sent to the remote signatory, when it requires one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signatory_api_key: Option<String>,
+    /// Signatories to fall over to, in order, when `signatory_url` can not be reached. They use
+    /// the same certs and API key and must hold the same keys
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub signatory_fallback_urls: Vec<String>,
     pub input_fee_ppk: Option<u64>,
     /// Use keyset v2
     pub use_keyset_v2: Option<bool>,
+    /// Network the keys are derived for from the seed (bitcoin, testnet, signet or regtest).
+    /// Defaults to bitcoin; the same seed derives unrelated keys on every network. Once set,
+    /// payments and backends on another network are refused
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network: Option<bitcoin::Network>,
 
     pub http_cache: cache::Config,
 
@@ -77,6 +137,66 @@ pub struct Info {
     /// If not provided, defaults are used.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub quote_ttl: Option<QuoteTTL>,
+
+    /// Optional NUTs to disable, e.g. `[9, 11]` turns off restore and P2PK.
+    /// Supported values: 7, 9, 10, 11, 14 and 20
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disabled_nuts: Vec<u8>,
+
+    /// Seconds P2PK and HTLC locktimes are treated as passed early, tolerating wallets whose
+    /// clock runs ahead. Defaults to 0.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clock_skew_grace_secs: Option<u64>,
+
+    /// Allow the daily mint and melt counts and bucketed volumes to be queried through the
+    /// management RPC, for publishing on a transparency page. Defaults to false.
+    #[serde(default)]
+    pub usage_statistics: bool,
+
+    /// Seconds from the creation of a keyset to its final expiry. When set, the active keyset
+    /// of every unit is replaced before it expires. Defaults to keysets that never expire.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keyset_lifetime_secs: Option<u64>,
+
+    /// Seconds before its final expiry the active keyset is replaced. Defaults to a quarter of
+    /// `keyset_lifetime_secs`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keyset_rotate_before_secs: Option<u64>,
+
+    /// Seconds between two looks for quotes past their expiry. When set, unpaid melt quotes
+    /// past their expiry are marked as failed. Defaults to expired quotes being left as they are.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quote_expiry_interval_secs: Option<u64>,
+
+    /// Seconds after their expiry unpaid quotes are removed from the database. Requires
+    /// `quote_expiry_interval_secs`. Defaults to expired quotes being kept.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quote_purge_after_secs: Option<u64>,
+
+    /// Unix time after which the proofs of each keyset are no longer accepted, published to
+    /// wallets so they swap out of the keyset ahead of it
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub keyset_retirements: HashMap<Id, u64>,
+
+    /// Largest proof amount issued for each unit, outputs above it are refused even when the
+    /// keyset has a key for their amount. Advertised in the mint info
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub max_denominations: HashMap<CurrencyUnit, u64>,
+
+    /// Largest amount the active keyset of each unit may have issued and not redeemed. Mint
+    /// quotes above it are refused until the keyset is rotated or its cap is lifted
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub issuance_caps: HashMap<CurrencyUnit, u64>,
+
+    /// What happens to melt quotes above the outbound liquidity of the payment backend: warn,
+    /// reject or partial. Backends that cannot tell their liquidity are not checked
+    #[serde(default)]
+    pub melt_liquidity_policy: MeltLiquidityPolicy,
+
+    /// Pay bolt11 mint quotes through hold invoices, settled only once the ecash is signed, for
+    /// payment backends that support them
+    #[serde(default)]
+    pub hold_invoices: bool,
 }
 
 impl Default for Info {
@@ -87,14 +207,30 @@ impl Default for Info {
             listen_port: 8091, // Default to port 8091 instead of 0
             seed: None,
             mnemonic: None,
+            mnemonic_passphrase: None,
             signatory_url: None,
             signatory_certs: None,
+            signatory_api_key: None,
+            signatory_fallback_urls: Vec::new(),
             input_fee_ppk: None,
             use_keyset_v2: None,
+            network: None,
             http_cache: cache::Config::default(),
             enable_info_page: Some(true),
             logging: LoggingConfig::default(),
             quote_ttl: None,
+            disabled_nuts: Vec::new(),
+            clock_skew_grace_secs: None,
+            usage_statistics: false,
+            keyset_lifetime_secs: None,
+            keyset_rotate_before_secs: None,
+            quote_expiry_interval_secs: None,
+            quote_purge_after_secs: None,
+            keyset_retirements: HashMap::new(),
+            max_denominations: HashMap::new(),
+            issuance_caps: HashMap::new(),
+            melt_liquidity_policy: MeltLiquidityPolicy::default(),
+            hold_invoices: false,
         }
     }
 }
@@ -116,11 +252,36 @@ impl std::fmt::Debug for Info {
             .field("listen_host", &self.listen_host)
             .field("listen_port", &self.listen_port)
             .field("mnemonic", &mnemonic_display)
+            .field(
+                "mnemonic_passphrase",
+                &self.mnemonic_passphrase.as_ref().map(|_| "[REDACTED]"),
+            )
+            .field(
+                "signatory_api_key",
+                &self.signatory_api_key.as_ref().map(|_| "[REDACTED]"),
+            )
+            .field("signatory_fallback_urls", &self.signatory_fallback_urls)
             .field("input_fee_ppk", &self.input_fee_ppk)
             .field("use_keyset_v2", &self.use_keyset_v2)
+            .field("network", &self.network)
             .field("http_cache", &self.http_cache)
             .field("logging", &self.logging)
             .field("enable_info_page", &self.enable_info_page)
+            .field("disabled_nuts", &self.disabled_nuts)
+            .field("clock_skew_grace_secs", &self.clock_skew_grace_secs)
+            .field("usage_statistics", &self.usage_statistics)
+            .field("keyset_lifetime_secs", &self.keyset_lifetime_secs)
+            .field("keyset_rotate_before_secs", &self.keyset_rotate_before_secs)
+            .field(
+                "quote_expiry_interval_secs",
+                &self.quote_expiry_interval_secs,
+            )
+            .field("quote_purge_after_secs", &self.quote_purge_after_secs)
+            .field("keyset_retirements", &self.keyset_retirements)
+            .field("max_denominations", &self.max_denominations)
+            .field("issuance_caps", &self.issuance_caps)
+            .field("melt_liquidity_policy", &self.melt_liquidity_policy)
+            .field("hold_invoices", &self.hold_invoices)
             .finish()
     }
 }
@@ -134,6 +295,8 @@ pub enum LnBackend {
     Cln,
     #[cfg(feature = "lnbits")]
     LNbits,
+    #[cfg(feature = "phoenixd")]
+    Phoenixd,
     #[cfg(feature = "fakewallet")]
     FakeWallet,
     #[cfg(feature = "lnd")]
@@ -153,6 +316,8 @@ impl std::str::FromStr for LnBackend {
             "cln" => Ok(LnBackend::Cln),
             #[cfg(feature = "lnbits")]
             "lnbits" => Ok(LnBackend::LNbits),
+            #[cfg(feature = "phoenixd")]
+            "phoenixd" => Ok(LnBackend::Phoenixd),
             #[cfg(feature = "fakewallet")]
             "fakewallet" => Ok(LnBackend::FakeWallet),
             #[cfg(feature = "lnd")]
@@ -345,6 +510,9 @@ pub struct Bdk {
     pub bitcoind_rpc_password: Option<String>,
     /// BIP-39 mnemonic for the BDK wallet
     pub mnemonic: Option<String>,
+    /// Optional BIP-39 passphrase (25th word) used with `mnemonic`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mnemonic_passphrase: Option<String>,
     /// Batch processor configuration
     #[serde(default)]
     pub batch_config: BatchConfig,
@@ -382,6 +550,7 @@ impl Default for Bdk {
             bitcoind_rpc_user: None,
             bitcoind_rpc_password: None,
             mnemonic: None,
+            mnemonic_passphrase: None,
             batch_config: BatchConfig::default(),
             num_confs: default_bdk_num_confs(),
             min_receive_amount_sat: default_bdk_min_receive_amount_sat(),
@@ -542,6 +711,41 @@ impl Default for LNbits {
     }
 }
 
+#[cfg(feature = "phoenixd")]
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Phoenixd {
+    pub api_password: String,
+    pub api_url: String,
+    #[serde(default = "default_fee_percent")]
+    pub fee_percent: f32,
+    #[serde(default = "default_reserve_fee_min")]
+    pub reserve_fee_min: Amount,
+}
+
+#[cfg(feature = "phoenixd")]
+impl std::fmt::Debug for Phoenixd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Phoenixd")
+            .field("api_password", &"[REDACTED]")
+            .field("api_url", &self.api_url)
+            .field("fee_percent", &self.fee_percent)
+            .field("reserve_fee_min", &self.reserve_fee_min)
+            .finish()
+    }
+}
+
+#[cfg(feature = "phoenixd")]
+impl Default for Phoenixd {
+    fn default() -> Self {
+        Self {
+            api_password: String::new(),
+            api_url: String::new(),
+            fee_percent: 0.02,
+            reserve_fee_min: 2.into(),
+        }
+    }
+}
+
 #[cfg(feature = "cln")]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Cln {
@@ -584,6 +788,9 @@ pub struct Lnd {
     pub fee_percent: f32,
     #[serde(default = "default_reserve_fee_min")]
     pub reserve_fee_min: Amount,
+    /// Allow melting to a node pubkey with keysend
+    #[serde(default)]
+    pub keysend: bool,
 }
 
 #[cfg(feature = "lnd")]
@@ -595,6 +802,7 @@ impl Default for Lnd {
             macaroon_file: PathBuf::new(),
             fee_percent: 0.02,
             reserve_fee_min: 2.into(),
+            keysend: false,
         }
     }
 }
@@ -811,12 +1019,22 @@ impl Default for FakeWallet {
 
 // Helper functions to provide default values
 // Common fee defaults for all backends
-#[cfg(any(feature = "cln", feature = "lnbits", feature = "lnd"))]
+#[cfg(any(
+    feature = "cln",
+    feature = "lnbits",
+    feature = "phoenixd",
+    feature = "lnd"
+))]
 fn default_fee_percent() -> f32 {
     0.02
 }
 
-#[cfg(any(feature = "cln", feature = "lnbits", feature = "lnd"))]
+#[cfg(any(
+    feature = "cln",
+    feature = "lnbits",
+    feature = "phoenixd",
+    feature = "lnd"
+))]
 fn default_reserve_fee_min() -> Amount {
     2.into()
 }
@@ -909,6 +1127,9 @@ pub struct AuthDatabase {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PostgresAuthConfig {
     pub url: String,
+    /// Schema the tables are created in, created if missing. Overrides `schema=` in the URL
+    #[serde(default)]
+    pub schema: Option<String>,
     pub tls_mode: Option<String>,
     pub max_connections: Option<usize>,
     pub connection_timeout_seconds: Option<u64>,
@@ -918,6 +1139,7 @@ impl Default for PostgresAuthConfig {
     fn default() -> Self {
         Self {
             url: String::new(),
+            schema: None,
             tls_mode: Some("disable".to_string()),
             max_connections: Some(20),
             connection_timeout_seconds: Some(10),
@@ -928,6 +1150,9 @@ impl Default for PostgresAuthConfig {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PostgresConfig {
     pub url: String,
+    /// Schema the tables are created in, created if missing. Overrides `schema=` in the URL
+    #[serde(default)]
+    pub schema: Option<String>,
     pub tls_mode: Option<String>,
     pub max_connections: Option<usize>,
     pub connection_timeout_seconds: Option<u64>,
@@ -937,6 +1162,7 @@ impl Default for PostgresConfig {
     fn default() -> Self {
         Self {
             url: String::new(),
+            schema: None,
             tls_mode: Some("disable".to_string()),
             max_connections: Some(20),
             connection_timeout_seconds: Some(10),
@@ -1007,14 +1233,22 @@ pub struct Settings {
     pub mint_info: MintInfo,
     #[serde(default, deserialize_with = "deserialize_ln")]
     pub ln: Vec<Ln>,
+    /// How payments are routed when several `[[ln]]` entries serve one unit
+    #[serde(default)]
+    pub payment_routing: RoutingPolicy,
     pub onchain: Option<Onchain>,
     /// Transaction limits for DoS protection
     #[serde(default)]
     pub limits: Limits,
+    /// Per IP rate limits of the mint endpoints
+    #[serde(default)]
+    pub rate_limit: RateLimits,
     #[cfg(feature = "cln")]
     pub cln: Option<Cln>,
     #[cfg(feature = "lnbits")]
     pub lnbits: Option<LNbits>,
+    #[cfg(feature = "phoenixd")]
+    pub phoenixd: Option<Phoenixd>,
     #[cfg(feature = "lnd")]
     pub lnd: Option<Lnd>,
     #[cfg(feature = "ldk-node")]
@@ -1032,6 +1266,125 @@ pub struct Settings {
     #[cfg(feature = "prometheus")]
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub prometheus: Option<Prometheus>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub admin_api: Option<AdminApi>,
+    /// Additional mints served by this process
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tenants: Vec<Tenant>,
+    #[cfg(feature = "ecash-address")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ecash_address: Option<EcashAddress>,
+}
+
+/// Lightning address style endpoint receiving payments as ecash
+///
+/// Each address is served at `/.well-known/lnurlp/<name>` of the mint URL. Paying it creates a
+/// mint quote locked to the address public key (NUT-20), so only the key owner can mint the
+/// ecash once the invoice is paid.
+#[cfg(feature = "ecash-address")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EcashAddress {
+    pub enabled: bool,
+    /// Unit the received payments are minted in
+    #[serde(default)]
+    pub unit: CurrencyUnit,
+    /// Smallest payment accepted, in `unit`
+    #[serde(default = "default_ecash_address_min")]
+    pub min_amount: u64,
+    /// Largest payment accepted, in `unit`
+    #[serde(default = "default_ecash_address_max")]
+    pub max_amount: u64,
+    /// Addresses served
+    #[serde(default)]
+    pub addresses: Vec<EcashAddressEntry>,
+}
+
+#[cfg(feature = "ecash-address")]
+fn default_ecash_address_min() -> u64 {
+    1
+}
+
+#[cfg(feature = "ecash-address")]
+fn default_ecash_address_max() -> u64 {
+    1_000_000
+}
+
+#[cfg(feature = "ecash-address")]
+impl Default for EcashAddress {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            unit: CurrencyUnit::Sat,
+            min_amount: default_ecash_address_min(),
+            max_amount: default_ecash_address_max(),
+            addresses: Vec::new(),
+        }
+    }
+}
+
+/// An address and the key allowed to mint what it receives
+#[cfg(feature = "ecash-address")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EcashAddressEntry {
+    /// User part of the address, `<name>@<mint domain>`
+    pub name: String,
+    /// Key the mint quotes are locked to
+    pub pubkey: PublicKey,
+    /// Text shown to the payer
+    pub description: Option<String>,
+}
+
+/// An additional mint hosted by the same mintd process
+///
+/// Tenants are served under their own URL path and keep their own seed, keysets and database,
+/// while sharing the listener and the payment backend configuration of the primary mint.
+/// Authentication and the management RPC only apply to the primary mint.
+#[derive(Clone, Serialize, Deserialize, Default)]
+pub struct Tenant {
+    /// Unique name, also used for the tenant data directory
+    pub name: String,
+    /// URL path the tenant is served under. Defaults to `/<name>`
+    pub path: Option<String>,
+    /// Public URL of the tenant
+    pub url: String,
+    /// Mnemonic of the tenant. Must differ from the primary mint and every other tenant
+    pub mnemonic: String,
+    /// Optional BIP-39 passphrase (25th word) used with `mnemonic`
+    pub mnemonic_passphrase: Option<String>,
+    /// Mint info of the tenant. Defaults to the primary `[mint_info]`
+    pub mint_info: Option<MintInfo>,
+    /// Database of the tenant. Defaults to the primary database engine, with SQLite files kept
+    /// in `<work_dir>/tenants/<name>`. PostgreSQL tenants must set their own database or schema.
+    pub database: Option<Database>,
+}
+
+impl std::fmt::Debug for Tenant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let hash = sha256::Hash::hash(self.mnemonic.as_bytes());
+
+        f.debug_struct("Tenant")
+            .field("name", &self.name)
+            .field("path", &self.path)
+            .field("url", &self.url)
+            .field("mnemonic", &format!("<hashed: {hash}>"))
+            .field(
+                "mnemonic_passphrase",
+                &self.mnemonic_passphrase.as_ref().map(|_| "[REDACTED]"),
+            )
+            .field("mint_info", &self.mint_info)
+            .field("database", &self.database)
+            .finish()
+    }
+}
+
+impl Tenant {
+    /// URL path the tenant is served under
+    pub fn path(&self) -> String {
+        match &self.path {
+            Some(path) => format!("/{}", path.trim_matches('/')),
+            None => format!("/{}", self.name),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -1040,6 +1393,92 @@ pub struct Prometheus {
     pub enabled: bool,
     pub address: Option<String>,
     pub port: Option<u16>,
+    /// Bearer tokens allowed to scrape, of any role
+    #[serde(default)]
+    pub tokens: Vec<AccessToken>,
+    /// Only let scrapers with one of `tokens` through. Anyone may scrape when unset.
+    #[serde(default)]
+    pub require_token: bool,
+    /// Directory with `server.pem`, `server.key` and `ca.pem`. When set, scrapers must present
+    /// a client certificate signed by `ca.pem`.
+    pub tls_dir: Option<PathBuf>,
+}
+
+/// Admin HTTP API, served on its own listener
+///
+/// Lets operators rotate keysets, adjust fees, update the mint info, read the keyset totals and
+/// list or expire quotes without restarting the mint. Like the management RPC, the mint info
+/// stored in the database takes precedence over `[mint_info]` once it is enabled.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct AdminApi {
+    pub enabled: bool,
+    pub address: Option<String>,
+    pub port: Option<u16>,
+    /// Bearer tokens allowed to call the API, required to start it. Read only tokens may only
+    /// send `GET` requests.
+    #[serde(default)]
+    pub tokens: Vec<AccessToken>,
+}
+
+/// What a static access token of an operator listener is allowed to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum AccessRole {
+    /// Read metrics and call the read only management RPC and admin API methods
+    #[default]
+    ReadOnly,
+    /// Call every management RPC and admin API method
+    Manage,
+}
+
+impl std::str::FromStr for AccessRole {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "read_only" | "readonly" => Ok(Self::ReadOnly),
+            "manage" => Ok(Self::Manage),
+            _ => Err(format!("Unknown access role: {s}")),
+        }
+    }
+}
+
+/// Static bearer token accepted by the management RPC, the admin API or the metrics listener
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccessToken {
+    pub token: String,
+    #[serde(default)]
+    pub role: AccessRole,
+}
+
+impl std::fmt::Debug for AccessToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AccessToken")
+            .field("token", &"[REDACTED]")
+            .field("role", &self.role)
+            .finish()
+    }
+}
+
+impl std::str::FromStr for AccessToken {
+    type Err = String;
+
+    /// Parses `token` or `token:role`, read only by default
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (token, role) = match s.rsplit_once(':') {
+            Some((token, role)) => (token, role.parse()?),
+            None => (s, AccessRole::default()),
+        };
+
+        if token.is_empty() {
+            return Err("Empty access token".to_string());
+        }
+
+        Ok(Self {
+            token: token.to_string(),
+            role,
+        })
+    }
 }
 
 /// Transaction limits configuration
@@ -1051,6 +1490,24 @@ pub struct Limits {
     /// Maximum number of outputs allowed per transaction (mint/swap/melt)
     #[serde(default = "default_max_outputs")]
     pub max_outputs: usize,
+    /// Longest secret or witness of an input, in bytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_proof_content_len: Option<usize>,
+    /// Largest request body accepted, in bytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_request_bytes: Option<usize>,
+    /// Requests waiting for the local signatory before new ones are refused as overloaded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signatory_queue_depth: Option<usize>,
+    /// Milliseconds a request waits for room in a full signatory queue, 0 to refuse it right away
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signatory_enqueue_timeout_ms: Option<u64>,
+    /// Requests the local signatory handles concurrently
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signatory_workers: Option<usize>,
+    /// Seconds a request to the local signatory may take, 0 to wait indefinitely
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signatory_timeout_secs: Option<u64>,
 }
 
 impl Default for Limits {
@@ -1058,6 +1515,93 @@ impl Default for Limits {
         Self {
             max_inputs: 1000,
             max_outputs: 1000,
+            max_proof_content_len: None,
+            max_request_bytes: None,
+            signatory_queue_depth: None,
+            signatory_enqueue_timeout_ms: None,
+            signatory_workers: None,
+            signatory_timeout_secs: None,
+        }
+    }
+}
+
+impl Limits {
+    /// Queue and concurrency limits of the local signatory, defaults for the unset ones
+    pub fn signatory_service(&self) -> cdk_signatory::embedded::ServiceConfig {
+        let default = cdk_signatory::embedded::ServiceConfig::default();
+
+        cdk_signatory::embedded::ServiceConfig {
+            queue_depth: self.signatory_queue_depth.unwrap_or(default.queue_depth),
+            enqueue_timeout: match self.signatory_enqueue_timeout_ms {
+                Some(0) => None,
+                Some(ms) => Some(std::time::Duration::from_millis(ms)),
+                None => default.enqueue_timeout,
+            },
+            workers: self.signatory_workers.unwrap_or(default.workers),
+            request_timeout: match self.signatory_timeout_secs {
+                Some(0) => None,
+                Some(secs) => Some(std::time::Duration::from_secs(secs)),
+                None => default.request_timeout,
+            },
+        }
+    }
+}
+
+/// Per IP rate limits of the mint endpoints
+///
+/// Each limited endpoint gives every client a bucket of `burst` requests refilled at
+/// `per_minute`, and answers `429 Too Many Requests` once it is empty. Endpoints without a
+/// limit are not limited.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct RateLimits {
+    /// Identify clients by the `X-Forwarded-For` header, only behind proxies appending to it
+    #[serde(default)]
+    pub trust_forwarded_for: bool,
+    /// Reverse proxies in front of the mint appending to `X-Forwarded-For` (default: 1)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trusted_proxies: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mint_quote: Option<RateLimit>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mint: Option<RateLimit>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub melt_quote: Option<RateLimit>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub melt: Option<RateLimit>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub swap: Option<RateLimit>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restore: Option<RateLimit>,
+    /// Limit of the swaps and melts spending the same input set, from any client
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_set: Option<RateLimit>,
+}
+
+impl RateLimits {
+    /// Rate limiter enforcing the configured limits
+    pub fn rate_limiter(&self) -> RateLimiter {
+        let limiter = [
+            (RateLimitedEndpoint::MintQuote, self.mint_quote),
+            (RateLimitedEndpoint::Mint, self.mint),
+            (RateLimitedEndpoint::MeltQuote, self.melt_quote),
+            (RateLimitedEndpoint::Melt, self.melt),
+            (RateLimitedEndpoint::Swap, self.swap),
+            (RateLimitedEndpoint::Restore, self.restore),
+        ]
+        .into_iter()
+        .filter_map(|(endpoint, limit)| Some((endpoint, limit?)))
+        .fold(
+            RateLimiter::new().trusted_proxies(if self.trust_forwarded_for {
+                self.trusted_proxies.unwrap_or(1)
+            } else {
+                0
+            }),
+            |limiter, (endpoint, limit)| limiter.with_limit(endpoint, limit),
+        );
+
+        match self.input_set {
+            Some(limit) => limiter.with_input_set_limit(limit),
+            None => limiter,
         }
     }
 }
@@ -1103,6 +1647,13 @@ pub struct MintManagementRpc {
     pub tls_dir: Option<PathBuf>,
     #[serde(default)]
     pub allow_insecure: bool,
+    /// Bearer tokens allowed to call the RPC
+    #[serde(default)]
+    pub tokens: Vec<AccessToken>,
+    /// Only let calls with one of `tokens` through. Only TLS client certificates are checked
+    /// when unset.
+    #[serde(default)]
+    pub require_token: bool,
 }
 
 impl Settings {
@@ -1114,6 +1665,168 @@ impl Settings {
         Ok(())
     }
 
+    /// Validate the tenant configuration
+    pub fn validate_tenants(&self) -> Result<(), String> {
+        if self.tenants.is_empty() {
+            return Ok(());
+        }
+
+        #[cfg(feature = "ldk-node")]
+        if self.ln.iter().any(|ln| ln.ln_backend == LnBackend::LdkNode) {
+            return Err("Tenants cannot share the embedded ldk-node backend".to_string());
+        }
+
+        #[cfg(feature = "bdk")]
+        if self
+            .onchain
+            .as_ref()
+            .is_some_and(|onchain| onchain.onchain_backend == OnchainBackend::Bdk)
+        {
+            return Err("Tenants cannot share the embedded bdk onchain wallet".to_string());
+        }
+
+        // Keysets are derived from the seed, tenants sharing one would share keysets
+        let primary_seed = match (&self.info.seed, &self.info.mnemonic) {
+            (Some(seed), _) => Some(seed.as_bytes().to_vec()),
+            (None, Some(mnemonic)) => {
+                mnemonic_seed(mnemonic, self.info.mnemonic_passphrase.as_deref())
+                    .ok()
+                    .map(|seed| seed.to_vec())
+            }
+            (None, None) => None,
+        };
+
+        let mut names = HashSet::new();
+        let mut paths = HashSet::new();
+        let mut seeds: HashMap<Vec<u8>, &str> = HashMap::new();
+        // Tenants sharing a database and schema would share quotes, proofs and keysets
+        let mut postgres_targets: HashMap<(String, Option<String>), &str> = HashMap::new();
+
+        for tenant in &self.tenants {
+            if tenant.name.is_empty()
+                || !tenant
+                    .name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            {
+                return Err(format!(
+                    "Tenant name \"{}\" must be non-empty and only contain ASCII letters, digits, '-' or '_'",
+                    tenant.name
+                ));
+            }
+
+            if !names.insert(tenant.name.as_str()) {
+                return Err(format!("Duplicate tenant name \"{}\"", tenant.name));
+            }
+
+            let path = tenant.path();
+            if path == "/" || path == "/v1" || path.starts_with("/v1/") {
+                return Err(format!(
+                    "Tenant \"{}\" path {path} collides with the primary mint",
+                    tenant.name
+                ));
+            }
+
+            if !paths.insert(path.clone()) {
+                return Err(format!("Duplicate tenant path {path}"));
+            }
+
+            if tenant.mnemonic.is_empty() {
+                return Err(format!("Tenant \"{}\" requires a mnemonic", tenant.name));
+            }
+
+            let seed = mnemonic_seed(&tenant.mnemonic, tenant.mnemonic_passphrase.as_deref())
+                .map_err(|err| {
+                    format!("Tenant \"{}\" has an invalid mnemonic: {err}", tenant.name)
+                })?
+                .to_vec();
+
+            if primary_seed.as_ref() == Some(&seed) {
+                return Err(format!(
+                    "Tenant \"{}\" must not reuse the primary mint seed or mnemonic",
+                    tenant.name
+                ));
+            }
+
+            if let Some(other) = seeds.insert(seed, tenant.name.as_str()) {
+                return Err(format!(
+                    "Tenants \"{other}\" and \"{}\" must not share a mnemonic",
+                    tenant.name
+                ));
+            }
+
+            let database = tenant.database.as_ref().unwrap_or(&self.database);
+            if database.engine == DatabaseEngine::Postgres {
+                let target = |pg: &PostgresConfig| (pg.url.clone(), pg.schema.clone());
+                let tenant_target = database.postgres.as_ref().map(target);
+                let primary_target = self.database.postgres.as_ref().map(target);
+
+                let tenant_target = match tenant_target {
+                    Some(target)
+                        if !target.0.is_empty() && Some(&target) != primary_target.as_ref() =>
+                    {
+                        target
+                    }
+                    _ => {
+                        return Err(format!(
+                            "Tenant \"{}\" must set its own [tenants.database.postgres] url or schema",
+                            tenant.name
+                        ));
+                    }
+                };
+
+                if let Some(other) = postgres_targets.insert(tenant_target, tenant.name.as_str()) {
+                    return Err(format!(
+                        "Tenants \"{other}\" and \"{}\" must not share a Postgres database and schema",
+                        tenant.name
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Settings used to build the mint of `tenant`
+    ///
+    /// Payment backends, limits and HTTP settings are inherited from the primary mint.
+    pub fn tenant_settings(&self, tenant: &Tenant) -> Settings {
+        let mut settings = self.clone();
+
+        settings.info.url = tenant.url.clone();
+        settings.info.seed = None;
+        settings.info.mnemonic = Some(tenant.mnemonic.clone());
+        settings.info.mnemonic_passphrase = tenant.mnemonic_passphrase.clone();
+        settings.info.signatory_url = None;
+        settings.info.signatory_certs = None;
+
+        if let Some(mint_info) = &tenant.mint_info {
+            settings.mint_info = mint_info.clone();
+        }
+
+        if let Some(database) = &tenant.database {
+            settings.database = database.clone();
+        }
+
+        settings.auth = None;
+        settings.auth_database = None;
+        #[cfg(feature = "management-rpc")]
+        {
+            settings.mint_management_rpc = None;
+        }
+        #[cfg(feature = "prometheus")]
+        {
+            settings.prometheus = None;
+        }
+        #[cfg(feature = "ecash-address")]
+        {
+            settings.ecash_address = None;
+        }
+        settings.tenants = Vec::new();
+
+        settings
+    }
+
     #[cfg(feature = "fakewallet")]
     fn validate_fake_wallet_backend_pairing(&self) -> Result<(), String> {
         let onchain_backend = self
@@ -1215,6 +1928,13 @@ impl Settings {
     }
 }
 
+/// Seed derived from `mnemonic`, the same way the mint derives its keysets from it
+fn mnemonic_seed(mnemonic: &str, passphrase: Option<&str>) -> Result<[u8; 64], String> {
+    Mnemonic::from_str(mnemonic)
+        .map(|mnemonic| mnemonic.to_seed_normalized(passphrase.unwrap_or_default()))
+        .map_err(|err| err.to_string())
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -1283,6 +2003,129 @@ mod tests {
         assert!(debug_output.contains("<hashed: "));
     }
 
+    #[test]
+    fn test_info_debug_redacts_mnemonic_passphrase() {
+        let info = Info {
+            mnemonic: Some("test secret mnemonic phrase".to_string()),
+            mnemonic_passphrase: Some("secret passphrase".to_string()),
+            ..Default::default()
+        };
+
+        let debug_output = format!("{info:?}");
+
+        assert!(!debug_output.contains("secret passphrase"));
+        assert!(debug_output.contains("mnemonic_passphrase: Some(\"[REDACTED]\")"));
+    }
+
+    #[test]
+    fn test_access_token_parses_role_and_redacts_token() {
+        let token: AccessToken = "secret-token:manage".parse().unwrap();
+
+        assert_eq!(token.token, "secret-token");
+        assert_eq!(token.role, AccessRole::Manage);
+        assert!(!format!("{token:?}").contains("secret-token"));
+
+        let token: AccessToken = "secret-token".parse().unwrap();
+        assert_eq!(token.role, AccessRole::ReadOnly);
+
+        assert!("secret-token:root".parse::<AccessToken>().is_err());
+        assert!(":manage".parse::<AccessToken>().is_err());
+    }
+
+    #[test]
+    fn test_validate_tenants() {
+        const PRIMARY: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        const ALPHA: &str =
+            "legal winner thank year wave sausage worth useful legal winner thank yellow";
+        const BETA: &str =
+            "letter advice cage absurd amount doctor acoustic avoid letter advice cage above";
+
+        let tenant = |name: &str, path: Option<&str>, mnemonic: &str| Tenant {
+            name: name.to_string(),
+            path: path.map(str::to_string),
+            url: format!("http://127.0.0.1:8085/{name}"),
+            mnemonic: mnemonic.to_string(),
+            ..Default::default()
+        };
+
+        let mut settings = Settings {
+            info: Info {
+                mnemonic: Some(PRIMARY.to_string()),
+                ..Default::default()
+            },
+            tenants: vec![
+                tenant("alpha", None, ALPHA),
+                tenant("beta", Some("/b/"), BETA),
+            ],
+            ..Default::default()
+        };
+
+        assert!(settings.validate_tenants().is_ok());
+        assert_eq!(settings.tenants[0].path(), "/alpha");
+        assert_eq!(settings.tenants[1].path(), "/b");
+
+        settings.tenants[1] = tenant("beta", Some("v1"), BETA);
+        assert!(settings.validate_tenants().is_err());
+
+        settings.tenants[1] = tenant("beta", Some("alpha"), BETA);
+        assert!(settings.validate_tenants().is_err());
+
+        settings.tenants[1] = tenant("alpha", Some("b"), BETA);
+        assert!(settings.validate_tenants().is_err());
+
+        settings.tenants[1] = tenant("beta/x", None, BETA);
+        assert!(settings.validate_tenants().is_err());
+
+        settings.tenants[1] = tenant("beta", None, "not a mnemonic");
+        assert!(settings.validate_tenants().is_err());
+
+        settings.tenants[1] = tenant("beta", None, PRIMARY);
+        assert!(settings.validate_tenants().is_err());
+
+        // Tenants must not share a mnemonic with each other either
+        settings.tenants[1] = tenant("beta", None, ALPHA);
+        assert!(settings.validate_tenants().is_err());
+
+        // Unless the passphrases differ, which yields other keysets
+        settings.tenants[1].mnemonic_passphrase = Some("beta".to_string());
+        assert!(settings.validate_tenants().is_ok());
+
+        // The seed overrides the primary mnemonic, which tenants may then use
+        settings.tenants[1] = tenant("beta", None, PRIMARY);
+        settings.info.seed = Some("primary seed".to_string());
+        assert!(settings.validate_tenants().is_ok());
+
+        // Tenants on Postgres need a database and schema of their own
+        let postgres = |schema: &str| Database {
+            engine: DatabaseEngine::Postgres,
+            postgres: Some(PostgresConfig {
+                url: "postgresql://localhost/cdk".to_string(),
+                schema: Some(schema.to_string()),
+                ..Default::default()
+            }),
+        };
+        settings.tenants[0].database = Some(postgres("alpha"));
+        settings.tenants[1].database = Some(postgres("beta"));
+        assert!(settings.validate_tenants().is_ok());
+
+        settings.tenants[1].database = Some(postgres("alpha"));
+        assert!(settings.validate_tenants().is_err());
+    }
+
+    #[test]
+    fn test_tenant_debug_redacts_mnemonic() {
+        let tenant = Tenant {
+            name: "alpha".to_string(),
+            mnemonic: "tenant secret mnemonic".to_string(),
+            ..Default::default()
+        };
+
+        let debug_output = format!("{tenant:?}");
+
+        assert!(!debug_output.contains("tenant secret mnemonic"));
+        assert!(debug_output.contains("<hashed: "));
+    }
+
     #[cfg(feature = "bdk")]
     #[test]
     fn test_bdk_default_min_send_amount_sat() {
@@ -1753,6 +2596,8 @@ target_block_time_secs = 300
         let config_path = temp_dir.join("config.toml");
 
         let config_content = r#"
+payment_routing = "lowest_fee"
+
 [[ln]]
 ln_backend = "fakewallet"
 unit = "sat"
@@ -1774,6 +2619,7 @@ max_melt = 1000
         let settings = Settings::new(Some(&config_path));
 
         assert_eq!(settings.ln.len(), 2);
+        assert_eq!(settings.payment_routing, RoutingPolicy::LowestFee);
 
         assert_eq!(settings.ln[0].ln_backend, LnBackend::FakeWallet);
         assert_eq!(settings.ln[0].unit, CurrencyUnit::Sat);
@@ -1816,6 +2662,55 @@ max_melt = 500000
         let _ = fs::remove_dir_all(&temp_dir);
     }
 
+    #[test]
+    fn test_rate_limit_config_parses() {
+        use std::{env, fs};
+
+        let temp_dir = env::temp_dir().join("cdk_test_rate_limit_config");
+        fs::create_dir_all(&temp_dir).expect("Failed to create temp dir");
+        let config_path = temp_dir.join("config.toml");
+
+        let config_content = r#"
+[rate_limit]
+trust_forwarded_for = true
+trusted_proxies = 2
+mint_quote = { per_minute = 30, burst = 5 }
+swap = { per_minute = 600, burst = 50 }
+input_set = { per_minute = 6, burst = 3 }
+"#;
+        fs::write(&config_path, config_content).expect("Failed to write config file");
+
+        let settings = Settings::new(Some(&config_path));
+
+        assert!(settings.rate_limit.trust_forwarded_for);
+        assert_eq!(settings.rate_limit.trusted_proxies, Some(2));
+        assert_eq!(
+            settings.rate_limit.mint_quote,
+            Some(RateLimit {
+                per_minute: 30,
+                burst: 5
+            })
+        );
+        assert!(settings.rate_limit.melt.is_none());
+        assert_eq!(
+            settings.rate_limit.input_set,
+            Some(RateLimit {
+                per_minute: 6,
+                burst: 3
+            })
+        );
+        assert!(!RateLimits {
+            input_set: settings.rate_limit.input_set,
+            ..Default::default()
+        }
+        .rate_limiter()
+        .is_empty());
+        assert!(!settings.rate_limit.rate_limiter().is_empty());
+        assert!(RateLimits::default().rate_limiter().is_empty());
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
     #[cfg(feature = "fakewallet")]
     #[test]
     fn test_fakewallet_config_without_supported_units_parses() {
@@ -1875,6 +2770,9 @@ max_delay_time = 3
         #[cfg(feature = "lnbits")]
         test_lnbits_env_config();
 
+        #[cfg(feature = "phoenixd")]
+        test_phoenixd_env_config();
+
         #[cfg(feature = "fakewallet")]
         test_fakewallet_env_config();
 
@@ -1967,6 +2865,7 @@ max_melt = 500000
         );
         env::set_var(crate::env_vars::ENV_LND_FEE_PERCENT, "0.01");
         env::set_var(crate::env_vars::ENV_LND_RESERVE_FEE_MIN, "4");
+        env::set_var(crate::env_vars::ENV_LND_KEYSEND, "true");
 
         // Load settings and apply environment variables (same as production code)
         let mut settings = Settings::new(Some(&config_path));
@@ -1984,6 +2883,7 @@ max_melt = 500000
         assert_eq!(lnd_config.fee_percent, 0.01);
         let reserve_fee_u64: u64 = lnd_config.reserve_fee_min.into();
         assert_eq!(reserve_fee_u64, 4);
+        assert!(lnd_config.keysend);
 
         // Cleanup env vars
         env::remove_var(crate::env_vars::ENV_LN_BACKEND);
@@ -1992,6 +2892,7 @@ max_melt = 500000
         env::remove_var(crate::env_vars::ENV_LND_MACAROON_FILE);
         env::remove_var(crate::env_vars::ENV_LND_FEE_PERCENT);
         env::remove_var(crate::env_vars::ENV_LND_RESERVE_FEE_MIN);
+        env::remove_var(crate::env_vars::ENV_LND_KEYSEND);
 
         // Cleanup test file
         let _ = fs::remove_dir_all(&temp_dir);
@@ -2109,6 +3010,62 @@ max_melt = 500000
         let _ = fs::remove_dir_all(&temp_dir);
     }
 
+    #[cfg(feature = "phoenixd")]
+    fn test_phoenixd_env_config() {
+        use std::{env, fs};
+
+        // Create a temporary directory for config file
+        let temp_dir = env::temp_dir().join("cdk_test_env_vars_phoenixd");
+        fs::create_dir_all(&temp_dir).expect("Failed to create temp dir");
+        let config_path = temp_dir.join("config.toml");
+
+        // Create a minimal config.toml with backend set but NO [phoenixd] section
+        let config_content = r#"
+[ln]
+backend = "phoenixd"
+min_mint = 1
+max_mint = 500000
+min_melt = 1
+max_melt = 500000
+"#;
+        fs::write(&config_path, config_content).expect("Failed to write config file");
+
+        // Set environment variables for phoenixd configuration
+        env::set_var(crate::env_vars::ENV_LN_BACKEND, "phoenixd");
+        env::set_var(crate::env_vars::ENV_PHOENIXD_API_PASSWORD, "test_password");
+        env::set_var(
+            crate::env_vars::ENV_PHOENIXD_API_URL,
+            "http://127.0.0.1:9740",
+        );
+        env::set_var(crate::env_vars::ENV_PHOENIXD_FEE_PERCENT, "0.01");
+        env::set_var(crate::env_vars::ENV_PHOENIXD_RESERVE_FEE_MIN, "4");
+
+        // Load settings and apply environment variables (same as production code)
+        let mut settings = Settings::new(Some(&config_path));
+        settings.from_env().expect("Failed to apply env vars");
+
+        // Verify that settings were populated from env vars
+        let phoenixd_config = settings
+            .phoenixd
+            .as_ref()
+            .expect("phoenixd config should be set from env vars");
+        assert_eq!(phoenixd_config.api_password, "test_password");
+        assert_eq!(phoenixd_config.api_url, "http://127.0.0.1:9740");
+        assert_eq!(phoenixd_config.fee_percent, 0.01);
+        let reserve_fee_u64: u64 = phoenixd_config.reserve_fee_min.into();
+        assert_eq!(reserve_fee_u64, 4);
+
+        // Cleanup env vars
+        env::remove_var(crate::env_vars::ENV_LN_BACKEND);
+        env::remove_var(crate::env_vars::ENV_PHOENIXD_API_PASSWORD);
+        env::remove_var(crate::env_vars::ENV_PHOENIXD_API_URL);
+        env::remove_var(crate::env_vars::ENV_PHOENIXD_FEE_PERCENT);
+        env::remove_var(crate::env_vars::ENV_PHOENIXD_RESERVE_FEE_MIN);
+
+        // Cleanup test file
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
     #[cfg(feature = "fakewallet")]
     fn test_fakewallet_env_config() {
         use std::{env, fs};
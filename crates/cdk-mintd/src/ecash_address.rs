@@ -0,0 +1,333 @@
+//! Lightning address style endpoint receiving payments as ecash
+//!
+//! Serves LUD-16 addresses at `/.well-known/lnurlp/<name>`. The LNURL-pay callback creates a
+//! bolt11 mint quote locked to the public key of the address (NUT-20), so only the key owner can
+//! mint the ecash once the invoice is paid. The owner lists the paid quotes at
+//! `/.well-known/lnurlp/<name>/quotes` and mints them with a signed mint request.
+//!
+//! The invoices carry the address description as a plain description, not the hash of the LNURL
+//! metadata, since the payment backends cannot create description hash invoices. Payers enforcing
+//! the LUD-06 description hash check refuse them.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Result};
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use cdk::mint::Mint;
+use cdk::mint_url::MintUrl;
+use cdk::nuts::{CurrencyUnit, MintQuoteBolt11Request, PublicKey};
+use cdk::Amount;
+use cdk_common::format::convert_lossless;
+use cdk_common::mint_quote::{MintQuoteRequest, MintQuoteResponse};
+use serde::{Deserialize, Serialize};
+
+use crate::config::EcashAddress;
+
+/// An address served by the endpoint
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Address {
+    pubkey: PublicKey,
+    description: String,
+    /// LNURL-pay metadata, a JSON array serialized as a string
+    metadata: String,
+    callback: String,
+}
+
+struct AddressState {
+    mint: Arc<Mint>,
+    unit: CurrencyUnit,
+    min_sendable: u64,
+    max_sendable: u64,
+    addresses: HashMap<String, Address>,
+}
+
+/// LNURL-pay response of an address
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PayRequest<'a> {
+    callback: &'a str,
+    min_sendable: u64,
+    max_sendable: u64,
+    metadata: &'a str,
+    tag: &'static str,
+}
+
+/// LNURL-pay callback response
+#[derive(Debug, Serialize)]
+struct PayInvoice {
+    pr: String,
+    routes: Vec<()>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CallbackParams {
+    /// Amount in msat
+    amount: u64,
+}
+
+/// Paid quote waiting to be minted by the address owner
+#[derive(Debug, Serialize)]
+struct PaidQuote {
+    quote: String,
+    amount: u64,
+    unit: CurrencyUnit,
+}
+
+/// LUD-06 error response
+fn lnurl_error(status: StatusCode, reason: impl ToString) -> Response {
+    (
+        status,
+        Json(serde_json::json!({
+            "status": "ERROR",
+            "reason": reason.to_string(),
+        })),
+    )
+        .into_response()
+}
+
+/// Amount of `unit` in msat, failing for units that are not bitcoin
+fn to_msat(value: u64, unit: &CurrencyUnit) -> Result<u64> {
+    Ok(convert_lossless(&Amount::new(value, unit.clone()), &CurrencyUnit::Msat)?.value())
+}
+
+/// Addresses of `settings` by name, served under the mint at `mint_url`
+fn addresses(settings: &EcashAddress, mint_url: &MintUrl) -> Result<HashMap<String, Address>> {
+    let mut addresses = HashMap::new();
+
+    for entry in &settings.addresses {
+        if entry.name.is_empty()
+            || !entry.name.bytes().all(|b| {
+                b.is_ascii_lowercase()
+                    || b.is_ascii_digit()
+                    || matches!(b, b'-' | b'_' | b'.' | b'+')
+            })
+        {
+            bail!("Invalid ecash address name `{}`", entry.name);
+        }
+
+        let identifier = format!("{}@{}", entry.name, mint_url.host());
+        let description = entry
+            .description
+            .clone()
+            .unwrap_or_else(|| format!("Ecash for {identifier}"));
+        let metadata = serde_json::to_string(&[
+            ["text/plain", description.as_str()],
+            ["text/identifier", identifier.as_str()],
+        ])?;
+        let callback = mint_url
+            .join_paths(&[".well-known", "lnurlp", &entry.name, "callback"])?
+            .to_string();
+
+        let address = Address {
+            pubkey: entry.pubkey,
+            description,
+            metadata,
+            callback,
+        };
+
+        if addresses.insert(entry.name.clone(), address).is_some() {
+            bail!("Duplicate ecash address name `{}`", entry.name);
+        }
+    }
+
+    Ok(addresses)
+}
+
+/// Router serving the ecash addresses of `settings` for `mint`, reachable at `mint_url`
+pub fn router(mint: Arc<Mint>, mint_url: &str, settings: &EcashAddress) -> Result<Router> {
+    let mint_url: MintUrl = mint_url.parse()?;
+
+    if settings.min_amount > settings.max_amount {
+        bail!("Ecash address min_amount is above max_amount");
+    }
+
+    let state = AddressState {
+        min_sendable: to_msat(settings.min_amount, &settings.unit).map_err(|_| {
+            anyhow!(
+                "Ecash addresses cannot receive in {}, only sat or msat",
+                settings.unit
+            )
+        })?,
+        max_sendable: to_msat(settings.max_amount, &settings.unit)?,
+        unit: settings.unit.clone(),
+        addresses: addresses(settings, &mint_url)?,
+        mint,
+    };
+
+    Ok(Router::new()
+        .route("/.well-known/lnurlp/{name}", get(get_pay_request))
+        .route("/.well-known/lnurlp/{name}/callback", get(get_invoice))
+        .route("/.well-known/lnurlp/{name}/quotes", get(get_paid_quotes))
+        .with_state(Arc::new(state)))
+}
+
+async fn get_pay_request(
+    State(state): State<Arc<AddressState>>,
+    Path(name): Path<String>,
+) -> Response {
+    let Some(address) = state.addresses.get(&name) else {
+        return lnurl_error(StatusCode::NOT_FOUND, "Unknown address");
+    };
+
+    Json(PayRequest {
+        callback: &address.callback,
+        min_sendable: state.min_sendable,
+        max_sendable: state.max_sendable,
+        metadata: &address.metadata,
+        tag: "payRequest",
+    })
+    .into_response()
+}
+
+async fn get_invoice(
+    State(state): State<Arc<AddressState>>,
+    Path(name): Path<String>,
+    Query(params): Query<CallbackParams>,
+) -> Response {
+    let Some(address) = state.addresses.get(&name) else {
+        return lnurl_error(StatusCode::NOT_FOUND, "Unknown address");
+    };
+
+    if params.amount < state.min_sendable || params.amount > state.max_sendable {
+        return lnurl_error(
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Amount must be between {} and {} msat",
+                state.min_sendable, state.max_sendable
+            ),
+        );
+    }
+
+    let amount =
+        match convert_lossless(&Amount::new(params.amount, CurrencyUnit::Msat), &state.unit) {
+            Ok(amount) => amount,
+            Err(err) => return lnurl_error(StatusCode::BAD_REQUEST, err),
+        };
+
+    let request = MintQuoteRequest::Bolt11(MintQuoteBolt11Request {
+        amount: amount.into(),
+        unit: state.unit.clone(),
+        description: Some(address.description.clone()),
+        pubkey: Some(address.pubkey),
+    });
+
+    match state.mint.get_mint_quote(request).await {
+        Ok(MintQuoteResponse::Bolt11(quote)) => {
+            tracing::debug!(
+                "Created mint quote {} for ecash address {}",
+                quote.quote,
+                name
+            );
+            Json(PayInvoice {
+                pr: quote.request,
+                routes: Vec::new(),
+            })
+            .into_response()
+        }
+        Ok(_) => lnurl_error(StatusCode::INTERNAL_SERVER_ERROR, "Unexpected quote"),
+        Err(err) => {
+            tracing::warn!(
+                "Could not create mint quote for ecash address {}: {}",
+                name,
+                err
+            );
+            lnurl_error(StatusCode::INTERNAL_SERVER_ERROR, err)
+        }
+    }
+}
+
+async fn get_paid_quotes(
+    State(state): State<Arc<AddressState>>,
+    Path(name): Path<String>,
+) -> Response {
+    let Some(address) = state.addresses.get(&name) else {
+        return lnurl_error(StatusCode::NOT_FOUND, "Unknown address");
+    };
+
+    let quotes = match state.mint.mint_quotes().await {
+        Ok(quotes) => quotes,
+        Err(err) => return lnurl_error(StatusCode::INTERNAL_SERVER_ERROR, err),
+    };
+
+    let paid: Vec<PaidQuote> = quotes
+        .into_iter()
+        .filter(|quote| quote.pubkey == Some(address.pubkey))
+        .filter_map(|quote| {
+            let mintable = quote
+                .amount_paid()
+                .value()
+                .checked_sub(quote.amount_issued().value())?;
+            (mintable > 0).then(|| PaidQuote {
+                quote: quote.id.to_string(),
+                amount: mintable,
+                unit: quote.unit.clone(),
+            })
+        })
+        .collect();
+
+    Json(paid).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::config::EcashAddressEntry;
+
+    fn pubkey() -> PublicKey {
+        PublicKey::from_str("02a9acc1e48c25eeeb9289b5031cc57da9fe72f3fe2861d264bdc074209b107ba2")
+            .expect("valid pubkey")
+    }
+
+    fn settings(names: &[&str]) -> EcashAddress {
+        EcashAddress {
+            enabled: true,
+            addresses: names
+                .iter()
+                .map(|name| EcashAddressEntry {
+                    name: name.to_string(),
+                    pubkey: pubkey(),
+                    description: None,
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn addresses_are_served_under_the_mint_domain() {
+        let mint_url = MintUrl::from_str("https://mint.example.com").expect("valid url");
+        let addresses = addresses(&settings(&["alice"]), &mint_url).expect("valid settings");
+
+        let alice = &addresses["alice"];
+        assert_eq!(
+            alice.callback,
+            "https://mint.example.com/.well-known/lnurlp/alice/callback"
+        );
+        assert_eq!(
+            alice.metadata,
+            r#"[["text/plain","Ecash for alice@mint.example.com"],["text/identifier","alice@mint.example.com"]]"#
+        );
+    }
+
+    #[test]
+    fn invalid_and_duplicate_names_are_refused() {
+        let mint_url = MintUrl::from_str("https://mint.example.com").expect("valid url");
+
+        assert!(addresses(&settings(&["Alice"]), &mint_url).is_err());
+        assert!(addresses(&settings(&["a/b"]), &mint_url).is_err());
+        assert!(addresses(&settings(&["alice", "alice"]), &mint_url).is_err());
+    }
+
+    #[test]
+    fn fiat_units_cannot_be_received() {
+        assert_eq!(to_msat(21, &CurrencyUnit::Sat).expect("sat"), 21_000);
+        assert!(to_msat(21, &CurrencyUnit::Usd).is_err());
+    }
+}
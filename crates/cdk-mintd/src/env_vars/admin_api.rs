@@ -0,0 +1,39 @@
+//! Admin HTTP API environment variables
+
+use std::env;
+
+use anyhow::Result;
+
+use super::common::parse_access_tokens;
+use crate::config::AdminApi;
+
+pub const ENV_ADMIN_API_ENABLED: &str = "CDK_MINTD_ADMIN_API_ENABLED";
+pub const ENV_ADMIN_API_ADDRESS: &str = "CDK_MINTD_ADMIN_API_ADDRESS";
+pub const ENV_ADMIN_API_PORT: &str = "CDK_MINTD_ADMIN_API_PORT";
+pub const ENV_ADMIN_API_TOKENS: &str = "CDK_MINTD_ADMIN_API_TOKENS";
+
+impl AdminApi {
+    pub fn from_env(mut self) -> Result<Self> {
+        if let Ok(enabled) = env::var(ENV_ADMIN_API_ENABLED) {
+            if let Ok(enabled) = enabled.parse() {
+                self.enabled = enabled;
+            }
+        }
+
+        if let Ok(address) = env::var(ENV_ADMIN_API_ADDRESS) {
+            self.address = Some(address);
+        }
+
+        if let Ok(port) = env::var(ENV_ADMIN_API_PORT) {
+            if let Ok(port) = port.parse::<u16>() {
+                self.port = Some(port);
+            }
+        }
+
+        if let Ok(tokens) = env::var(ENV_ADMIN_API_TOKENS) {
+            self.tokens = parse_access_tokens(ENV_ADMIN_API_TOKENS, &tokens)?;
+        }
+
+        Ok(self)
+    }
+}
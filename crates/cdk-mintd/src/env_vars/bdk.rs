@@ -5,6 +5,7 @@ use std::env;
 use crate::config::Bdk;
 
 pub const BDK_MNEMONIC_ENV_VAR: &str = "CDK_MINTD_BDK_MNEMONIC";
+pub const BDK_MNEMONIC_PASSPHRASE_ENV_VAR: &str = "CDK_MINTD_BDK_MNEMONIC_PASSPHRASE";
 pub const BDK_NETWORK_ENV_VAR: &str = "CDK_MINTD_BDK_NETWORK";
 pub const BDK_BITCOIND_RPC_HOST_ENV_VAR: &str = "CDK_MINTD_BDK_BITCOIND_RPC_HOST";
 pub const BDK_BITCOIND_RPC_PORT_ENV_VAR: &str = "CDK_MINTD_BDK_BITCOIND_RPC_PORT";
@@ -39,6 +40,10 @@ impl Bdk {
             self.mnemonic = Some(mnemonic);
         }
 
+        if let Ok(passphrase) = env::var(BDK_MNEMONIC_PASSPHRASE_ENV_VAR) {
+            self.mnemonic_passphrase = Some(passphrase);
+        }
+
         if let Ok(network) = env::var(BDK_NETWORK_ENV_VAR) {
             self.network = Some(network);
         }
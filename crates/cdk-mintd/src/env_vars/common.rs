@@ -8,8 +8,11 @@ pub const ENV_LISTEN_HOST: &str = "CDK_MINTD_LISTEN_HOST";
 pub const ENV_LISTEN_PORT: &str = "CDK_MINTD_LISTEN_PORT";
 pub const ENV_SEED: &str = "CDK_MINTD_SEED";
 pub const ENV_MNEMONIC: &str = "CDK_MINTD_MNEMONIC";
+pub const ENV_MNEMONIC_PASSPHRASE: &str = "CDK_MINTD_MNEMONIC_PASSPHRASE";
 pub const ENV_SIGNATORY_URL: &str = "CDK_MINTD_SIGNATORY_URL";
 pub const ENV_SIGNATORY_CERTS: &str = "CDK_MINTD_SIGNATORY_CERTS";
+pub const ENV_SIGNATORY_API_KEY: &str = "CDK_MINTD_SIGNATORY_API_KEY";
+pub const ENV_SIGNATORY_FALLBACK_URLS: &str = "CDK_MINTD_SIGNATORY_FALLBACK_URLS";
 pub const ENV_SECONDS_QUOTE_VALID: &str = "CDK_MINTD_SECONDS_QUOTE_VALID";
 pub const ENV_CACHE_SECONDS: &str = "CDK_MINTD_CACHE_SECONDS";
 pub const ENV_EXTEND_CACHE_SECONDS: &str = "CDK_MINTD_EXTEND_CACHE_SECONDS";
@@ -17,8 +20,42 @@ pub const ENV_INPUT_FEE_PPK: &str = "CDK_MINTD_INPUT_FEE_PPK";
 pub const ENV_QUOTE_TTL_MINT: &str = "CDK_MINTD_QUOTE_TTL_MINT";
 pub const ENV_QUOTE_TTL_MELT: &str = "CDK_MINTD_QUOTE_TTL_MELT";
 pub const ENV_USE_KEYSET_V2: &str = "CDK_MINTD_USE_KEYSET_V2";
+pub const ENV_NETWORK: &str = "CDK_MINTD_NETWORK";
+pub const ENV_DISABLED_NUTS: &str = "CDK_MINTD_DISABLED_NUTS";
+pub const ENV_CLOCK_SKEW_GRACE_SECS: &str = "CDK_MINTD_CLOCK_SKEW_GRACE_SECS";
+pub const ENV_USAGE_STATISTICS: &str = "CDK_MINTD_USAGE_STATISTICS";
+pub const ENV_KEYSET_LIFETIME_SECS: &str = "CDK_MINTD_KEYSET_LIFETIME_SECS";
+pub const ENV_KEYSET_ROTATE_BEFORE_SECS: &str = "CDK_MINTD_KEYSET_ROTATE_BEFORE_SECS";
+pub const ENV_QUOTE_EXPIRY_INTERVAL_SECS: &str = "CDK_MINTD_QUOTE_EXPIRY_INTERVAL_SECS";
+pub const ENV_QUOTE_PURGE_AFTER_SECS: &str = "CDK_MINTD_QUOTE_PURGE_AFTER_SECS";
+pub const ENV_KEYSET_RETIREMENTS: &str = "CDK_MINTD_KEYSET_RETIREMENTS";
+pub const ENV_MAX_DENOMINATIONS: &str = "CDK_MINTD_MAX_DENOMINATIONS";
+pub const ENV_ISSUANCE_CAPS: &str = "CDK_MINTD_ISSUANCE_CAPS";
+pub const ENV_MELT_LIQUIDITY_POLICY: &str = "CDK_MINTD_MELT_LIQUIDITY_POLICY";
+pub const ENV_HOLD_INVOICES: &str = "CDK_MINTD_HOLD_INVOICES";
 
 pub const ENV_ENABLE_INFO_PAGE: &str = "CDK_MINTD_ENABLE_INFO_PAGE";
 pub const ENV_LOGGING_OUTPUT: &str = "CDK_MINTD_LOGGING_OUTPUT";
 pub const ENV_LOGGING_CONSOLE_LEVEL: &str = "CDK_MINTD_LOGGING_CONSOLE_LEVEL";
 pub const ENV_LOGGING_FILE_LEVEL: &str = "CDK_MINTD_LOGGING_FILE_LEVEL";
+pub const ENV_LOGGING_SAMPLED_SPANS: &str = "CDK_MINTD_LOGGING_SAMPLED_SPANS";
+
+/// Parses the comma separated list of `token:role` entries of the `name` env var
+///
+/// An invalid entry fails the whole list, dropping it would leave the listener with fewer
+/// tokens, or none, than the operator set.
+pub(crate) fn parse_access_tokens(
+    name: &str,
+    value: &str,
+) -> anyhow::Result<Vec<crate::config::AccessToken>> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            entry
+                .parse()
+                .map_err(|err| anyhow::anyhow!("Invalid access token in {name}: {err}"))
+        })
+        .collect()
+}
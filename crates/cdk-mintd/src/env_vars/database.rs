@@ -5,11 +5,13 @@ use std::env;
 use crate::config::{PostgresAuthConfig, PostgresConfig};
 
 pub const ENV_POSTGRES_URL: &str = "CDK_MINTD_POSTGRES_URL";
+pub const ENV_POSTGRES_SCHEMA: &str = "CDK_MINTD_POSTGRES_SCHEMA";
 pub const ENV_POSTGRES_TLS_MODE: &str = "CDK_MINTD_POSTGRES_TLS_MODE";
 pub const ENV_POSTGRES_MAX_CONNECTIONS: &str = "CDK_MINTD_POSTGRES_MAX_CONNECTIONS";
 pub const ENV_POSTGRES_CONNECTION_TIMEOUT: &str = "CDK_MINTD_POSTGRES_CONNECTION_TIMEOUT_SECONDS";
 
 pub const ENV_AUTH_POSTGRES_URL: &str = "CDK_MINTD_AUTH_POSTGRES_URL";
+pub const ENV_AUTH_POSTGRES_SCHEMA: &str = "CDK_MINTD_AUTH_POSTGRES_SCHEMA";
 pub const ENV_AUTH_POSTGRES_TLS_MODE: &str = "CDK_MINTD_AUTH_POSTGRES_TLS_MODE";
 pub const ENV_AUTH_POSTGRES_MAX_CONNECTIONS: &str = "CDK_MINTD_AUTH_POSTGRES_MAX_CONNECTIONS";
 pub const ENV_AUTH_POSTGRES_CONNECTION_TIMEOUT: &str =
@@ -25,6 +27,10 @@ impl PostgresConfig {
             self.url = url;
         }
 
+        if let Ok(schema) = env::var(ENV_POSTGRES_SCHEMA) {
+            self.schema = Some(schema);
+        }
+
         if let Ok(tls_mode) = env::var(ENV_POSTGRES_TLS_MODE) {
             self.tls_mode = Some(tls_mode);
         }
@@ -51,6 +57,10 @@ impl PostgresAuthConfig {
             self.url = url;
         }
 
+        if let Ok(schema) = env::var(ENV_AUTH_POSTGRES_SCHEMA) {
+            self.schema = Some(schema);
+        }
+
         if let Ok(tls_mode) = env::var(ENV_AUTH_POSTGRES_TLS_MODE) {
             self.tls_mode = Some(tls_mode);
         }
@@ -1,12 +1,14 @@
 //! Info environment variables
 
+use std::collections::HashMap;
 use std::env;
+use std::hash::Hash;
 use std::str::FromStr;
 
 use cdk_common::common::QuoteTTL;
 
 use super::common::*;
-use crate::config::{Info, LoggingOutput};
+use crate::config::{Info, LoggingOutput, MeltLiquidityPolicy};
 
 impl Info {
     pub fn from_env(mut self) -> Self {
@@ -33,6 +35,19 @@ impl Info {
             self.signatory_certs = Some(signatory_certs);
         }
 
+        if let Ok(signatory_api_key) = env::var(ENV_SIGNATORY_API_KEY) {
+            self.signatory_api_key = Some(signatory_api_key);
+        }
+
+        if let Ok(fallback_urls_str) = env::var(ENV_SIGNATORY_FALLBACK_URLS) {
+            self.signatory_fallback_urls = fallback_urls_str
+                .split(',')
+                .map(str::trim)
+                .filter(|url| !url.is_empty())
+                .map(str::to_owned)
+                .collect();
+        }
+
         if let Ok(seed) = env::var(ENV_SEED) {
             self.seed = Some(seed);
         }
@@ -41,6 +56,10 @@ impl Info {
             self.mnemonic = Some(mnemonic);
         }
 
+        if let Ok(passphrase) = env::var(ENV_MNEMONIC_PASSPHRASE) {
+            self.mnemonic_passphrase = Some(passphrase);
+        }
+
         if let Ok(cache_seconds_str) = env::var(ENV_CACHE_SECONDS) {
             if let Ok(seconds) = cache_seconds_str.parse() {
                 self.http_cache.ttl = Some(seconds);
@@ -71,6 +90,93 @@ impl Info {
             }
         }
 
+        if let Ok(network_str) = env::var(ENV_NETWORK) {
+            match bitcoin::Network::from_str(&network_str) {
+                Ok(network) => self.network = Some(network),
+                Err(err) => tracing::warn!("Ignoring invalid {}: {}", ENV_NETWORK, err),
+            }
+        }
+
+        if let Ok(disabled_nuts_str) = env::var(ENV_DISABLED_NUTS) {
+            self.disabled_nuts = disabled_nuts_str
+                .split(',')
+                .map(str::trim)
+                .filter(|nut| !nut.is_empty())
+                .filter_map(|nut| nut.parse().ok())
+                .collect();
+        }
+
+        if let Ok(grace_str) = env::var(ENV_CLOCK_SKEW_GRACE_SECS) {
+            if let Ok(grace) = grace_str.parse() {
+                self.clock_skew_grace_secs = Some(grace);
+            }
+        }
+
+        if let Ok(enabled_str) = env::var(ENV_USAGE_STATISTICS) {
+            if let Ok(enabled) = enabled_str.parse() {
+                self.usage_statistics = enabled;
+            }
+        }
+
+        if let Ok(lifetime_str) = env::var(ENV_KEYSET_LIFETIME_SECS) {
+            if let Ok(lifetime) = lifetime_str.parse() {
+                self.keyset_lifetime_secs = Some(lifetime);
+            }
+        }
+
+        if let Ok(rotate_before_str) = env::var(ENV_KEYSET_ROTATE_BEFORE_SECS) {
+            if let Ok(rotate_before) = rotate_before_str.parse() {
+                self.keyset_rotate_before_secs = Some(rotate_before);
+            }
+        }
+
+        if let Ok(interval_str) = env::var(ENV_QUOTE_EXPIRY_INTERVAL_SECS) {
+            if let Ok(interval) = interval_str.parse() {
+                self.quote_expiry_interval_secs = Some(interval);
+            }
+        }
+
+        if let Ok(purge_after_str) = env::var(ENV_QUOTE_PURGE_AFTER_SECS) {
+            if let Ok(purge_after) = purge_after_str.parse() {
+                self.quote_purge_after_secs = Some(purge_after);
+            }
+        }
+
+        // Comma separated `<keyset id>=<unix time>` pairs
+        if let Ok(retirements_str) = env::var(ENV_KEYSET_RETIREMENTS) {
+            self.keyset_retirements = retirements_str
+                .split(',')
+                .map(str::trim)
+                .filter(|retirement| !retirement.is_empty())
+                .filter_map(|retirement| {
+                    let (id, time) = retirement.split_once('=')?;
+                    Some((id.trim().parse().ok()?, time.trim().parse().ok()?))
+                })
+                .collect();
+        }
+
+        // Comma separated `<unit>=<amount>` pairs
+        if let Ok(denominations_str) = env::var(ENV_MAX_DENOMINATIONS) {
+            self.max_denominations = parse_unit_amounts(&denominations_str);
+        }
+
+        if let Ok(caps_str) = env::var(ENV_ISSUANCE_CAPS) {
+            self.issuance_caps = parse_unit_amounts(&caps_str);
+        }
+
+        if let Ok(policy_str) = env::var(ENV_MELT_LIQUIDITY_POLICY) {
+            match MeltLiquidityPolicy::from_str(&policy_str) {
+                Ok(policy) => self.melt_liquidity_policy = policy,
+                Err(err) => tracing::warn!("{}", err),
+            }
+        }
+
+        if let Ok(enabled_str) = env::var(ENV_HOLD_INVOICES) {
+            if let Ok(enabled) = enabled_str.parse() {
+                self.hold_invoices = enabled;
+            }
+        }
+
         // Logging configuration
         if let Ok(output_str) = env::var(ENV_LOGGING_OUTPUT) {
             if let Ok(output) = LoggingOutput::from_str(&output_str) {
@@ -91,6 +197,19 @@ impl Info {
             self.logging.file_level = Some(file_level);
         }
 
+        // Comma separated `<span>=<rate>` pairs
+        if let Ok(sampled_spans_str) = env::var(ENV_LOGGING_SAMPLED_SPANS) {
+            self.logging.sampled_spans = sampled_spans_str
+                .split(',')
+                .map(str::trim)
+                .filter(|sampled_span| !sampled_span.is_empty())
+                .filter_map(|sampled_span| {
+                    let (name, rate) = sampled_span.split_once('=')?;
+                    Some((name.trim().to_owned(), rate.trim().parse().ok()?))
+                })
+                .collect();
+        }
+
         self.http_cache = self.http_cache.from_env();
 
         // Quote TTL from env
@@ -117,3 +236,20 @@ impl Info {
         self
     }
 }
+
+/// Parses comma separated `unit=amount` pairs, skipping the invalid ones
+fn parse_unit_amounts<U, A>(value: &str) -> HashMap<U, A>
+where
+    U: FromStr + Eq + Hash,
+    A: FromStr,
+{
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .filter_map(|pair| {
+            let (unit, amount) = pair.split_once('=')?;
+            Some((unit.trim().parse().ok()?, amount.trim().parse().ok()?))
+        })
+        .collect()
+}
@@ -6,6 +6,12 @@ use crate::config::Limits;
 
 pub const ENV_MAX_INPUTS: &str = "CDK_MINTD_MAX_INPUTS";
 pub const ENV_MAX_OUTPUTS: &str = "CDK_MINTD_MAX_OUTPUTS";
+pub const ENV_MAX_PROOF_CONTENT_LEN: &str = "CDK_MINTD_MAX_PROOF_CONTENT_LEN";
+pub const ENV_MAX_REQUEST_BYTES: &str = "CDK_MINTD_MAX_REQUEST_BYTES";
+pub const ENV_SIGNATORY_QUEUE_DEPTH: &str = "CDK_MINTD_SIGNATORY_QUEUE_DEPTH";
+pub const ENV_SIGNATORY_ENQUEUE_TIMEOUT_MS: &str = "CDK_MINTD_SIGNATORY_ENQUEUE_TIMEOUT_MS";
+pub const ENV_SIGNATORY_WORKERS: &str = "CDK_MINTD_SIGNATORY_WORKERS";
+pub const ENV_SIGNATORY_TIMEOUT_SECS: &str = "CDK_MINTD_SIGNATORY_TIMEOUT_SECS";
 
 impl Limits {
     /// Override limits with environment variables if set
@@ -24,6 +30,42 @@ impl Limits {
             }
         }
 
+        if let Ok(content_len_str) = env::var(ENV_MAX_PROOF_CONTENT_LEN) {
+            if let Ok(content_len) = content_len_str.parse::<usize>() {
+                limits.max_proof_content_len = Some(content_len);
+            }
+        }
+
+        if let Ok(request_bytes_str) = env::var(ENV_MAX_REQUEST_BYTES) {
+            if let Ok(request_bytes) = request_bytes_str.parse::<usize>() {
+                limits.max_request_bytes = Some(request_bytes);
+            }
+        }
+
+        if let Ok(queue_depth_str) = env::var(ENV_SIGNATORY_QUEUE_DEPTH) {
+            if let Ok(queue_depth) = queue_depth_str.parse::<usize>() {
+                limits.signatory_queue_depth = Some(queue_depth);
+            }
+        }
+
+        if let Ok(enqueue_timeout_str) = env::var(ENV_SIGNATORY_ENQUEUE_TIMEOUT_MS) {
+            if let Ok(enqueue_timeout) = enqueue_timeout_str.parse::<u64>() {
+                limits.signatory_enqueue_timeout_ms = Some(enqueue_timeout);
+            }
+        }
+
+        if let Ok(workers_str) = env::var(ENV_SIGNATORY_WORKERS) {
+            if let Ok(workers) = workers_str.parse::<usize>() {
+                limits.signatory_workers = Some(workers);
+            }
+        }
+
+        if let Ok(timeout_str) = env::var(ENV_SIGNATORY_TIMEOUT_SECS) {
+            if let Ok(timeout) = timeout_str.parse::<u64>() {
+                limits.signatory_timeout_secs = Some(timeout);
+            }
+        }
+
         limits
     }
 }
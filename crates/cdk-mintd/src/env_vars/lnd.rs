@@ -11,6 +11,7 @@ pub const ENV_LND_CERT_FILE: &str = "CDK_MINTD_LND_CERT_FILE";
 pub const ENV_LND_MACAROON_FILE: &str = "CDK_MINTD_LND_MACAROON_FILE";
 pub const ENV_LND_FEE_PERCENT: &str = "CDK_MINTD_LND_FEE_PERCENT";
 pub const ENV_LND_RESERVE_FEE_MIN: &str = "CDK_MINTD_LND_RESERVE_FEE_MIN";
+pub const ENV_LND_KEYSEND: &str = "CDK_MINTD_LND_KEYSEND";
 
 impl Lnd {
     pub fn from_env(mut self) -> Self {
@@ -38,6 +39,12 @@ impl Lnd {
             }
         }
 
+        if let Ok(keysend_str) = env::var(ENV_LND_KEYSEND) {
+            if let Ok(keysend) = keysend_str.parse() {
+                self.keysend = keysend;
+            }
+        }
+
         self
     }
 }
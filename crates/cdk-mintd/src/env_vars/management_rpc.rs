@@ -2,6 +2,9 @@
 
 use std::env;
 
+use anyhow::Result;
+
+use super::common::parse_access_tokens;
 use crate::config::MintManagementRpc;
 
 // Mint RPC Server environment variables
@@ -11,9 +14,11 @@ pub const ENV_MINT_MANAGEMENT_ADDRESS: &str = "CDK_MINTD_MANAGEMENT_ADDRESS";
 pub const ENV_MINT_MANAGEMENT_PORT: &str = "CDK_MINTD_MANAGEMENT_PORT";
 pub const ENV_MINT_MANAGEMENT_TLS_DIR: &str = "CDK_MINTD_MANAGEMENT_TLS_DIR";
 pub const ENV_MINT_MANAGEMENT_ALLOW_INSECURE: &str = "CDK_MINTD_MANAGEMENT_ALLOW_INSECURE";
+pub const ENV_MINT_MANAGEMENT_TOKENS: &str = "CDK_MINTD_MANAGEMENT_TOKENS";
+pub const ENV_MINT_MANAGEMENT_REQUIRE_TOKEN: &str = "CDK_MINTD_MANAGEMENT_REQUIRE_TOKEN";
 
 impl MintManagementRpc {
-    pub fn from_env(mut self) -> Self {
+    pub fn from_env(mut self) -> Result<Self> {
         if let Ok(enabled) = env::var(ENV_MINT_MANAGEMENT_ENABLED)
             .or_else(|_| env::var(ENV_MINT_MANAGEMENT_ENABLED_LEGACY))
         {
@@ -42,7 +47,17 @@ impl MintManagementRpc {
             }
         }
 
-        self
+        if let Ok(tokens) = env::var(ENV_MINT_MANAGEMENT_TOKENS) {
+            self.tokens = parse_access_tokens(ENV_MINT_MANAGEMENT_TOKENS, &tokens)?;
+        }
+
+        if let Ok(require_token) = env::var(ENV_MINT_MANAGEMENT_REQUIRE_TOKEN) {
+            if let Ok(require_token) = require_token.parse() {
+                self.require_token = require_token;
+            }
+        }
+
+        Ok(self)
     }
 }
 
@@ -52,6 +67,7 @@ mod tests {
     use std::path::PathBuf;
 
     use super::*;
+    use crate::config::{AccessRole, AccessToken};
 
     fn env_lock() -> std::sync::MutexGuard<'static, ()> {
         static ENV_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
@@ -68,6 +84,8 @@ mod tests {
         env::remove_var(ENV_MINT_MANAGEMENT_PORT);
         env::remove_var(ENV_MINT_MANAGEMENT_TLS_DIR);
         env::remove_var(ENV_MINT_MANAGEMENT_ALLOW_INSECURE);
+        env::remove_var(ENV_MINT_MANAGEMENT_TOKENS);
+        env::remove_var(ENV_MINT_MANAGEMENT_REQUIRE_TOKEN);
     }
 
     #[test]
@@ -78,6 +96,8 @@ mod tests {
             ENV_MINT_MANAGEMENT_PORT,
             ENV_MINT_MANAGEMENT_TLS_DIR,
             ENV_MINT_MANAGEMENT_ALLOW_INSECURE,
+            ENV_MINT_MANAGEMENT_TOKENS,
+            ENV_MINT_MANAGEMENT_REQUIRE_TOKEN,
         ];
 
         let prefixes: BTreeSet<&str> = names
@@ -107,8 +127,10 @@ mod tests {
         env::set_var(ENV_MINT_MANAGEMENT_PORT, "10000");
         env::set_var(ENV_MINT_MANAGEMENT_TLS_DIR, "/var/lib/cdk/tls");
         env::set_var(ENV_MINT_MANAGEMENT_ALLOW_INSECURE, "true");
+        env::set_var(ENV_MINT_MANAGEMENT_TOKENS, "reader, admin:manage");
+        env::set_var(ENV_MINT_MANAGEMENT_REQUIRE_TOKEN, "true");
 
-        let management_rpc = MintManagementRpc::default().from_env();
+        let management_rpc = MintManagementRpc::default().from_env().unwrap();
 
         assert!(management_rpc.enabled);
         assert_eq!(management_rpc.address.as_deref(), Some("0.0.0.0"));
@@ -118,6 +140,33 @@ mod tests {
             Some(PathBuf::from("/var/lib/cdk/tls"))
         );
         assert!(management_rpc.allow_insecure);
+        assert!(management_rpc.require_token);
+        assert_eq!(
+            management_rpc.tokens,
+            vec![
+                AccessToken {
+                    token: "reader".to_string(),
+                    role: AccessRole::ReadOnly,
+                },
+                AccessToken {
+                    token: "admin".to_string(),
+                    role: AccessRole::Manage,
+                },
+            ]
+        );
+
+        clear_env_vars();
+    }
+
+    #[test]
+    fn management_rpc_from_env_rejects_invalid_tokens() {
+        let _guard = env_lock();
+        clear_env_vars();
+
+        for tokens in ["reader,admin:root", "secret:with:colons", ":manage"] {
+            env::set_var(ENV_MINT_MANAGEMENT_TOKENS, tokens);
+            assert!(MintManagementRpc::default().from_env().is_err());
+        }
 
         clear_env_vars();
     }
@@ -129,7 +178,7 @@ mod tests {
 
         env::set_var(ENV_MINT_MANAGEMENT_ENABLED_LEGACY, "true");
 
-        let management_rpc = MintManagementRpc::default().from_env();
+        let management_rpc = MintManagementRpc::default().from_env().unwrap();
 
         assert!(management_rpc.enabled);
 
@@ -143,7 +192,7 @@ mod tests {
 
         env::set_var(ENV_MINT_MANAGEMENT_ENABLED, "true");
 
-        let management_rpc = MintManagementRpc::default().from_env();
+        let management_rpc = MintManagementRpc::default().from_env().unwrap();
 
         assert!(management_rpc.enabled);
         assert_eq!(management_rpc.tls_dir, None);
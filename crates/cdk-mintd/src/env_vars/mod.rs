@@ -4,6 +4,7 @@
 //! This module contains all environment variable definitions and parsing logic
 //! organized by component.
 
+mod admin_api;
 mod common;
 mod database;
 mod info;
@@ -11,6 +12,7 @@ mod limits;
 mod ln;
 mod mint_info;
 mod onchain;
+mod rate_limit;
 
 mod auth;
 #[cfg(feature = "bdk")]
@@ -29,12 +31,15 @@ mod lnbits;
 mod lnd;
 #[cfg(feature = "management-rpc")]
 mod management_rpc;
+#[cfg(feature = "phoenixd")]
+mod phoenixd;
 #[cfg(feature = "prometheus")]
 mod prometheus;
 
 use std::env;
 use std::str::FromStr;
 
+pub use admin_api::*;
 use anyhow::{anyhow, bail, Result};
 pub use auth::*;
 #[cfg(feature = "bdk")]
@@ -59,8 +64,11 @@ pub use lnd::*;
 pub use management_rpc::*;
 pub use mint_info::*;
 pub use onchain::*;
+#[cfg(feature = "phoenixd")]
+pub use phoenixd::*;
 #[cfg(feature = "prometheus")]
 pub use prometheus::*;
+pub use rate_limit::*;
 
 use crate::config::{DatabaseEngine, Ln, LnBackend, OnchainBackend, Settings};
 
@@ -117,6 +125,7 @@ impl Settings {
         }
         self.onchain = Some(self.onchain.clone().unwrap_or_default().from_env());
         self.limits = self.limits.clone().from_env();
+        self.rate_limit = self.rate_limit.clone().from_env();
 
         {
             // Check env vars for auth config even if None
@@ -136,15 +145,17 @@ impl Settings {
                 self.mint_management_rpc
                     .clone()
                     .unwrap_or_default()
-                    .from_env(),
+                    .from_env()?,
             );
         }
 
         #[cfg(feature = "prometheus")]
         {
-            self.prometheus = Some(self.prometheus.clone().unwrap_or_default().from_env());
+            self.prometheus = Some(self.prometheus.clone().unwrap_or_default().from_env()?);
         }
 
+        self.admin_api = Some(self.admin_api.clone().unwrap_or_default().from_env()?);
+
         #[cfg(feature = "cln")]
         {
             let cln = self.cln.clone().unwrap_or_default().from_env();
@@ -165,6 +176,16 @@ impl Settings {
             }
         }
 
+        #[cfg(feature = "phoenixd")]
+        {
+            let phoenixd = self.phoenixd.clone().unwrap_or_default().from_env();
+            if phoenixd.api_password.is_empty() {
+                self.phoenixd = None;
+            } else {
+                self.phoenixd = Some(phoenixd);
+            }
+        }
+
         #[cfg(feature = "fakewallet")]
         {
             // Fake wallet has defaults so it is always Some if feature enabled
@@ -231,6 +252,8 @@ impl Settings {
                 LnBackend::Cln => {}
                 #[cfg(feature = "lnbits")]
                 LnBackend::LNbits => {}
+                #[cfg(feature = "phoenixd")]
+                LnBackend::Phoenixd => {}
                 #[cfg(feature = "fakewallet")]
                 LnBackend::FakeWallet => {}
                 #[cfg(feature = "lnd")]
@@ -0,0 +1,37 @@
+//! Phoenixd environment variables
+
+use std::env;
+
+use crate::config::Phoenixd;
+
+// Phoenixd environment variables
+pub const ENV_PHOENIXD_API_PASSWORD: &str = "CDK_MINTD_PHOENIXD_API_PASSWORD";
+pub const ENV_PHOENIXD_API_URL: &str = "CDK_MINTD_PHOENIXD_API_URL";
+pub const ENV_PHOENIXD_FEE_PERCENT: &str = "CDK_MINTD_PHOENIXD_FEE_PERCENT";
+pub const ENV_PHOENIXD_RESERVE_FEE_MIN: &str = "CDK_MINTD_PHOENIXD_RESERVE_FEE_MIN";
+
+impl Phoenixd {
+    pub fn from_env(mut self) -> Self {
+        if let Ok(password) = env::var(ENV_PHOENIXD_API_PASSWORD) {
+            self.api_password = password;
+        }
+
+        if let Ok(api_url) = env::var(ENV_PHOENIXD_API_URL) {
+            self.api_url = api_url;
+        }
+
+        if let Ok(fee_str) = env::var(ENV_PHOENIXD_FEE_PERCENT) {
+            if let Ok(fee) = fee_str.parse() {
+                self.fee_percent = fee;
+            }
+        }
+
+        if let Ok(reserve_fee_str) = env::var(ENV_PHOENIXD_RESERVE_FEE_MIN) {
+            if let Ok(reserve_fee) = reserve_fee_str.parse::<u64>() {
+                self.reserve_fee_min = reserve_fee.into();
+            }
+        }
+
+        self
+    }
+}
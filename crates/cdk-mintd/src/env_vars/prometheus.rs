@@ -2,14 +2,20 @@
 
 use std::env;
 
+use anyhow::Result;
+
+use super::common::parse_access_tokens;
 use crate::config::Prometheus;
 
 pub const ENV_PROMETHEUS_ENABLED: &str = "CDK_MINTD_PROMETHEUS_ENABLED";
 pub const ENV_PROMETHEUS_ADDRESS: &str = "CDK_MINTD_PROMETHEUS_ADDRESS";
 pub const ENV_PROMETHEUS_PORT: &str = "CDK_MINTD_PROMETHEUS_PORT";
+pub const ENV_PROMETHEUS_TOKENS: &str = "CDK_MINTD_PROMETHEUS_TOKENS";
+pub const ENV_PROMETHEUS_REQUIRE_TOKEN: &str = "CDK_MINTD_PROMETHEUS_REQUIRE_TOKEN";
+pub const ENV_PROMETHEUS_TLS_DIR: &str = "CDK_MINTD_PROMETHEUS_TLS_DIR";
 
 impl Prometheus {
-    pub fn from_env(mut self) -> Self {
+    pub fn from_env(mut self) -> Result<Self> {
         if let Ok(enabled_str) = env::var(ENV_PROMETHEUS_ENABLED) {
             if let Ok(enabled) = enabled_str.parse() {
                 self.enabled = enabled;
@@ -26,6 +32,20 @@ impl Prometheus {
             }
         }
 
-        self
+        if let Ok(tokens) = env::var(ENV_PROMETHEUS_TOKENS) {
+            self.tokens = parse_access_tokens(ENV_PROMETHEUS_TOKENS, &tokens)?;
+        }
+
+        if let Ok(require_token) = env::var(ENV_PROMETHEUS_REQUIRE_TOKEN) {
+            if let Ok(require_token) = require_token.parse() {
+                self.require_token = require_token;
+            }
+        }
+
+        if let Ok(tls_dir) = env::var(ENV_PROMETHEUS_TLS_DIR) {
+            self.tls_dir = Some(tls_dir.into());
+        }
+
+        Ok(self)
     }
 }
@@ -0,0 +1,72 @@
+//! Rate limit environment variables
+
+use std::env;
+
+use cdk_axum::RateLimit;
+
+use crate::config::RateLimits;
+
+pub const ENV_RATE_LIMIT_TRUST_FORWARDED_FOR: &str = "CDK_MINTD_RATE_LIMIT_TRUST_FORWARDED_FOR";
+pub const ENV_RATE_LIMIT_TRUSTED_PROXIES: &str = "CDK_MINTD_RATE_LIMIT_TRUSTED_PROXIES";
+pub const ENV_RATE_LIMIT_MINT_QUOTE: &str = "CDK_MINTD_RATE_LIMIT_MINT_QUOTE";
+pub const ENV_RATE_LIMIT_MINT: &str = "CDK_MINTD_RATE_LIMIT_MINT";
+pub const ENV_RATE_LIMIT_MELT_QUOTE: &str = "CDK_MINTD_RATE_LIMIT_MELT_QUOTE";
+pub const ENV_RATE_LIMIT_MELT: &str = "CDK_MINTD_RATE_LIMIT_MELT";
+pub const ENV_RATE_LIMIT_SWAP: &str = "CDK_MINTD_RATE_LIMIT_SWAP";
+pub const ENV_RATE_LIMIT_RESTORE: &str = "CDK_MINTD_RATE_LIMIT_RESTORE";
+pub const ENV_RATE_LIMIT_INPUT_SET: &str = "CDK_MINTD_RATE_LIMIT_INPUT_SET";
+
+/// Parse a `per_minute/burst` limit, the burst defaulting to `per_minute`
+fn parse_rate_limit(value: &str) -> Option<RateLimit> {
+    let (per_minute, burst) = match value.split_once('/') {
+        Some((per_minute, burst)) => (per_minute.trim(), Some(burst.trim())),
+        None => (value.trim(), None),
+    };
+    let per_minute = per_minute.parse().ok()?;
+    let burst = match burst {
+        Some(burst) => burst.parse().ok()?,
+        None => per_minute,
+    };
+
+    Some(RateLimit { per_minute, burst })
+}
+
+impl RateLimits {
+    pub fn from_env(mut self) -> Self {
+        if let Ok(trust) = env::var(ENV_RATE_LIMIT_TRUST_FORWARDED_FOR) {
+            if let Ok(trust) = trust.parse() {
+                self.trust_forwarded_for = trust;
+            }
+        }
+
+        if let Ok(hops) = env::var(ENV_RATE_LIMIT_TRUSTED_PROXIES) {
+            match hops.parse() {
+                Ok(hops) => self.trusted_proxies = Some(hops),
+                Err(_) => tracing::warn!(
+                    "Ignoring invalid {}: {}",
+                    ENV_RATE_LIMIT_TRUSTED_PROXIES,
+                    hops
+                ),
+            }
+        }
+
+        for (var, limit) in [
+            (ENV_RATE_LIMIT_MINT_QUOTE, &mut self.mint_quote),
+            (ENV_RATE_LIMIT_MINT, &mut self.mint),
+            (ENV_RATE_LIMIT_MELT_QUOTE, &mut self.melt_quote),
+            (ENV_RATE_LIMIT_MELT, &mut self.melt),
+            (ENV_RATE_LIMIT_SWAP, &mut self.swap),
+            (ENV_RATE_LIMIT_RESTORE, &mut self.restore),
+            (ENV_RATE_LIMIT_INPUT_SET, &mut self.input_set),
+        ] {
+            if let Ok(value) = env::var(var) {
+                match parse_rate_limit(&value) {
+                    Some(parsed) => *limit = Some(parsed),
+                    None => tracing::warn!("Ignoring invalid {}: {}", var, value),
+                }
+            }
+        }
+
+        self
+    }
+}
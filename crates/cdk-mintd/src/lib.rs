@@ -8,6 +8,7 @@ use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 
 // external crates
 use anyhow::{anyhow, bail, Context, Result};
@@ -15,11 +16,15 @@ use axum::extract::DefaultBodyLimit;
 use axum::Router;
 use bip39::Mnemonic;
 use cdk::cdk_database::{self, KVStore, MintDatabase, MintKeysDatabase};
-use cdk::mint::{Mint, MintBuilder, MintMeltLimits};
+use cdk::mint::{
+    KeysetRotationPolicy, Mint, MintBuilder, MintMeltLimits, PaymentRouter, QuoteExpiryPolicy,
+};
 use cdk::nuts::nut00::KnownMethod;
+use cdk::nuts::nut02::KeySetVersion;
 #[cfg(any(
     feature = "cln",
     feature = "lnbits",
+    feature = "phoenixd",
     feature = "lnd",
     feature = "ldk-node",
     feature = "fakewallet",
@@ -29,22 +34,25 @@ use cdk::nuts::nut00::KnownMethod;
 use cdk::nuts::nut17::SupportedMethods;
 use cdk::nuts::nut19::{CachedEndpoint, Method as NUT19Method, Path as NUT19Path};
 use cdk::nuts::{
-    AuthRequired, ContactInfo, Method, MintVersion, PaymentMethod, ProtectedEndpoint, RoutePath,
+    AuthRequired, ContactInfo, CurrencyUnit, KeySet, Method, MintVersion, PaymentMethod,
+    ProtectedEndpoint, RoutePath,
 };
+use cdk::Amount;
 use cdk_axum::cache::HttpCache;
+use cdk_axum::MintRouterOptions;
 use cdk_common::common::QuoteTTL;
 use cdk_common::database::DynMintDatabase;
 // internal crate modules
 #[cfg(feature = "prometheus")]
 use cdk_common::payment::MetricsMintPayment;
-use cdk_common::payment::MintPayment;
+use cdk_common::payment::{DynMintPayment, MintPayment};
 #[cfg(feature = "postgres")]
 use cdk_postgres::{MintPgAuthDatabase, MintPgDatabase, PgConfig};
 #[cfg(feature = "sqlite")]
 use cdk_sqlite::mint::MintSqliteAuthDatabase;
 #[cfg(feature = "sqlite")]
 use cdk_sqlite::MintSqliteDatabase;
-use cli::CLIArgs;
+use cli::{CLIArgs, DeriveKeysetsArgs};
 use config::{AuthType, DatabaseEngine, LnBackend};
 use env_vars::ENV_WORK_DIR;
 use setup::LnBackendSetup;
@@ -58,7 +66,10 @@ use tracing_subscriber::EnvFilter;
 
 pub mod cli;
 pub mod config;
+#[cfg(feature = "ecash-address")]
+pub mod ecash_address;
 pub mod env_vars;
+pub mod logging;
 pub mod setup;
 
 const CARGO_PKG_VERSION: Option<&'static str> = option_env!("CARGO_PKG_VERSION");
@@ -77,6 +88,21 @@ fn extract_supported_payment_methods(mint_info: &cdk::nuts::MintInfo) -> Vec<Str
         .collect()
 }
 
+/// Carries over the fields of the stored mint info that the config does not set
+///
+/// The pubkey is kept unless one is configured. Read-only mode is toggled at runtime, so it
+/// outlives a restart along with the maintenance MOTD it advertises.
+fn keep_stored_mint_info(mint_info: &mut cdk::nuts::MintInfo, stored: cdk::nuts::MintInfo) {
+    if mint_info.pubkey.is_none() {
+        mint_info.pubkey = stored.pubkey;
+    }
+
+    if stored.read_only {
+        mint_info.read_only = true;
+        mint_info.motd = stored.motd;
+    }
+}
+
 #[cfg(feature = "cln")]
 fn expand_path(path: &str) -> Option<PathBuf> {
     if path == "~" {
@@ -109,7 +135,8 @@ async fn initial_setup(
 }
 
 /// Sets up and initializes a tracing subscriber with custom log filtering.
-/// Logs can be configured to output to stdout only, file only, or both.
+/// Logs can be configured to output to stdout only, file only, or both. Secrets are redacted
+/// and the configured spans sampled, see [`logging`].
 /// Returns a guard that must be kept alive and properly dropped on shutdown.
 pub fn setup_tracing(
     work_dir: &Path,
@@ -141,11 +168,7 @@ pub fn setup_tracing(
 
             let stderr = std::io::stderr.with_max_level(console_level);
 
-            tracing_subscriber::fmt()
-                .with_env_filter(env_filter)
-                .with_ansi(false)
-                .with_writer(stderr)
-                .init();
+            logging::init(env_filter, &logging_config.sampled_spans, stderr);
 
             tracing::info!("Logging initialized: console only ({}+)", console_level);
             Ok(None)
@@ -169,11 +192,7 @@ pub fn setup_tracing(
 
             let file_writer = non_blocking_appender.with_max_level(file_level);
 
-            tracing_subscriber::fmt()
-                .with_env_filter(env_filter)
-                .with_ansi(false)
-                .with_writer(file_writer)
-                .init();
+            logging::init(env_filter, &logging_config.sampled_spans, file_writer);
 
             tracing::info!(
                 "Logging initialized: file only at {}/cdk-mintd.log ({}+)",
@@ -209,11 +228,11 @@ pub fn setup_tracing(
             let stderr = std::io::stderr.with_max_level(console_level);
             let file_writer = non_blocking_appender.with_max_level(file_level);
 
-            tracing_subscriber::fmt()
-                .with_env_filter(env_filter)
-                .with_ansi(false)
-                .with_writer(stderr.and(file_writer))
-                .init();
+            logging::init(
+                env_filter,
+                &logging_config.sampled_spans,
+                stderr.and(file_writer),
+            );
 
             tracing::info!(
                 "Logging initialized: console ({}+) and file at {}/cdk-mintd.log ({}+)",
@@ -273,6 +292,8 @@ pub fn load_settings_from_args(work_dir: &Path, args: &CLIArgs) -> Result<config
 }
 
 /// Overrides the configured mint and active payment backend mnemonic with a seed file.
+///
+/// The BDK wallet derives its keys from the seed phrase with the passphrase of the mint.
 pub fn apply_seed_file(settings: &mut config::Settings, seed_file: &Path) -> Result<()> {
     let mnemonic = std::fs::read_to_string(seed_file)
         .with_context(|| format!("Failed to read seed file {}", seed_file.display()))?;
@@ -296,6 +317,7 @@ pub fn apply_seed_file(settings: &mut config::Settings, seed_file: &Path) -> Res
     {
         let mut bdk = settings.bdk.clone().unwrap_or_default();
         bdk.mnemonic = Some(mnemonic.to_owned());
+        bdk.mnemonic_passphrase = settings.info.mnemonic_passphrase.clone();
         settings.bdk = Some(bdk);
     }
 
@@ -313,6 +335,71 @@ pub fn apply_seed_file(settings: &mut config::Settings, seed_file: &Path) -> Res
     Ok(())
 }
 
+/// Derives the keysets selected by `args` from the configured seed, without starting the mint
+pub fn derive_keysets(
+    settings: &config::Settings,
+    args: &DeriveKeysetsArgs,
+) -> Result<Vec<KeySet>> {
+    let seed: Vec<u8> = if let Some(seed) = settings.info.seed.clone() {
+        seed.into()
+    } else if let Some(mnemonic) = &settings.info.mnemonic {
+        let passphrase = settings
+            .info
+            .mnemonic_passphrase
+            .as_deref()
+            .unwrap_or_default();
+        Mnemonic::from_str(mnemonic)?
+            .to_seed_normalized(passphrase)
+            .to_vec()
+    } else {
+        bail!("No seed nor mnemonic set");
+    };
+
+    let unit = CurrencyUnit::from_str(&args.unit)?;
+    let network = args
+        .network
+        .or(settings.info.network)
+        .unwrap_or(bitcoin::Network::Bitcoin);
+    let input_fee_ppk = args
+        .input_fee_ppk
+        .or(settings.info.input_fee_ppk)
+        .unwrap_or_default();
+    let version = if args.legacy_id || settings.info.use_keyset_v2 == Some(false) {
+        KeySetVersion::Version00
+    } else {
+        KeySetVersion::Version01
+    };
+
+    let derivation_paths = match &args.derivation_path {
+        Some(derivation_path) => vec![derivation_path.clone()],
+        None => (0..=args.max_index)
+            .map(|index| {
+                cdk_signatory::derivation_path_from_unit(unit.clone(), index)
+                    .ok_or_else(|| anyhow!("No derivation path for {} at {}", unit, index))
+            })
+            .collect::<Result<Vec<_>>>()?,
+    };
+
+    let secp = bitcoin::secp256k1::Secp256k1::new();
+    let xpriv = cdk_signatory::network_xpriv(&secp, &seed, network)?;
+
+    Ok(derivation_paths
+        .into_iter()
+        .map(|derivation_path| {
+            KeySet::derive_from_xpriv(
+                &secp,
+                xpriv,
+                derivation_path,
+                unit.clone(),
+                args.max_order,
+                input_fee_ppk,
+                args.final_expiry,
+                version,
+            )
+        })
+        .collect())
+}
+
 async fn setup_database(
     settings: &config::Settings,
     _work_dir: &Path,
@@ -351,6 +438,11 @@ async fn setup_database(
                 pg_config.connection_timeout_seconds,
             );
             #[cfg(feature = "postgres")]
+            let db_config = match pg_config.schema.as_deref() {
+                Some(schema) => db_config.with_schema(schema),
+                None => db_config,
+            };
+            #[cfg(feature = "postgres")]
             let pg_db = Arc::new(MintPgDatabase::new(db_config).await?);
             tracing::info!("PostgreSQL database connection established");
             #[cfg(feature = "postgres")]
@@ -465,11 +557,79 @@ async fn configure_mint_builder(
         .with_batch_minting(Some(DEFAULT_BATCH_MINT_SIZE), Some(payment_methods.clone()));
 
     // Configure caching with payment methods
-    let mint_builder = configure_cache(settings, mint_builder, &payment_methods).await?;
+    let mint_builder = configure_cache(settings, mint_builder, &payment_methods);
 
     // Configure transaction limits
+    let mint_builder = mint_builder
+        .with_limits(settings.limits.max_inputs, settings.limits.max_outputs)
+        .with_signatory_service_config(settings.limits.signatory_service());
+    let mint_builder = match settings.limits.max_proof_content_len {
+        Some(len) => mint_builder.with_max_proof_content_len(len),
+        None => mint_builder,
+    };
+
+    // Turn off the optional NUTs disabled by the operator
+    let mint_builder = mint_builder.with_disabled_nuts(&settings.info.disabled_nuts)?;
+
+    // Tolerate wallets whose clock runs ahead when checking locktimes
+    let mint_builder =
+        mint_builder.with_clock_skew_grace(settings.info.clock_skew_grace_secs.unwrap_or_default());
+
+    // Opt in to the coarse usage statistics served by the management RPC
+    let mint_builder = mint_builder.with_usage_statistics(settings.info.usage_statistics);
+
+    // Give keysets a lifetime and replace them before they expire
+    let mint_builder = match settings.info.keyset_lifetime_secs {
+        Some(lifetime) => {
+            let mut policy = KeysetRotationPolicy::new(Duration::from_secs(lifetime));
+            if let Some(rotate_before) = settings.info.keyset_rotate_before_secs {
+                policy.rotate_before = Duration::from_secs(rotate_before);
+            }
+            mint_builder.with_keyset_rotation_policy(policy)
+        }
+        None => mint_builder,
+    };
+
+    // Expire the quotes left unpaid and purge them once they are old enough
+    let mint_builder = match settings.info.quote_expiry_interval_secs {
+        Some(interval) => {
+            let mut policy = QuoteExpiryPolicy::new(Duration::from_secs(interval));
+            if let Some(purge_after) = settings.info.quote_purge_after_secs {
+                policy = policy.with_purge_after(Duration::from_secs(purge_after));
+            }
+            mint_builder.with_quote_expiry_policy(policy)
+        }
+        None => mint_builder,
+    };
+
+    // Stop accepting keysets the operator scheduled for retirement
     let mint_builder =
-        mint_builder.with_limits(settings.limits.max_inputs, settings.limits.max_outputs);
+        mint_builder.with_keyset_retirements(settings.info.keyset_retirements.clone());
+
+    // Refuse outputs above the configured denominations
+    let mint_builder = mint_builder.with_max_denominations(
+        settings
+            .info
+            .max_denominations
+            .iter()
+            .map(|(unit, amount)| (unit.clone(), Amount::from(*amount)))
+            .collect(),
+    );
+
+    // Bound the ecash each keyset may have in circulation
+    let mint_builder = mint_builder.with_issuance_caps(
+        settings
+            .info
+            .issuance_caps
+            .iter()
+            .map(|(unit, amount)| (unit.clone(), Amount::from(*amount)))
+            .collect(),
+    );
+
+    // Catch melt quotes the payment backends could never pay
+    let mint_builder = mint_builder
+        .with_liquidity_policy(settings.info.melt_liquidity_policy.into())
+        .with_hold_invoices(settings.info.hold_invoices);
 
     // Verify at least one payment processor is configured
     if mint_builder
@@ -554,6 +714,10 @@ fn configure_basic_info(settings: &config::Settings, mint_builder: MintBuilder)
 
     builder = builder.with_keyset_v2(settings.info.use_keyset_v2);
 
+    if let Some(network) = settings.info.network {
+        builder = builder.with_network(network);
+    }
+
     builder
 }
 /// Configures Lightning Network backend based on the specified backend type
@@ -572,6 +736,8 @@ async fn configure_lightning_backend(
     #[cfg(feature = "fakewallet")]
     let mut configure_fake_wallet_keyset_rotations = false;
 
+    let mut backends: Vec<(CurrencyUnit, MintMeltLimits, DynMintPayment)> = Vec::new();
+
     for ln_entry in &settings.ln {
         let mint_melt_limits = MintMeltLimits {
             mint_min: ln_entry.min_mint,
@@ -604,14 +770,7 @@ async fn configure_lightning_backend(
                 #[cfg(feature = "prometheus")]
                 let cln = MetricsMintPayment::new(cln);
 
-                mint_builder = configure_backend_for_unit(
-                    settings,
-                    mint_builder,
-                    ln_entry.unit.clone(),
-                    mint_melt_limits,
-                    Arc::new(cln),
-                )
-                .await?;
+                backends.push((ln_entry.unit.clone(), mint_melt_limits, Arc::new(cln)));
             }
             #[cfg(feature = "lnbits")]
             LnBackend::LNbits => {
@@ -624,14 +783,20 @@ async fn configure_lightning_backend(
                 #[cfg(feature = "prometheus")]
                 let lnbits = MetricsMintPayment::new(lnbits);
 
-                mint_builder = configure_backend_for_unit(
-                    settings,
-                    mint_builder,
-                    ln_entry.unit.clone(),
-                    mint_melt_limits,
-                    Arc::new(lnbits),
-                )
-                .await?;
+                backends.push((ln_entry.unit.clone(), mint_melt_limits, Arc::new(lnbits)));
+            }
+            #[cfg(feature = "phoenixd")]
+            LnBackend::Phoenixd => {
+                let phoenixd_settings = settings.phoenixd.clone().ok_or_else(|| {
+                    anyhow!("Phoenixd backend selected but [phoenixd] config section is missing")
+                })?;
+                let phoenixd = phoenixd_settings
+                    .setup(settings, ln_entry.unit.clone(), None, work_dir, None)
+                    .await?;
+                #[cfg(feature = "prometheus")]
+                let phoenixd = MetricsMintPayment::new(phoenixd);
+
+                backends.push((ln_entry.unit.clone(), mint_melt_limits, Arc::new(phoenixd)));
             }
             #[cfg(feature = "lnd")]
             LnBackend::Lnd => {
@@ -650,14 +815,7 @@ async fn configure_lightning_backend(
                 #[cfg(feature = "prometheus")]
                 let lnd = MetricsMintPayment::new(lnd);
 
-                mint_builder = configure_backend_for_unit(
-                    settings,
-                    mint_builder,
-                    ln_entry.unit.clone(),
-                    mint_melt_limits,
-                    Arc::new(lnd),
-                )
-                .await?;
+                backends.push((ln_entry.unit.clone(), mint_melt_limits, Arc::new(lnd)));
             }
             #[cfg(feature = "fakewallet")]
             LnBackend::FakeWallet => {
@@ -680,14 +838,7 @@ async fn configure_lightning_backend(
                 #[cfg(feature = "prometheus")]
                 let fake = MetricsMintPayment::new(fake);
 
-                mint_builder = configure_backend_for_unit(
-                    settings,
-                    mint_builder,
-                    ln_entry.unit.clone(),
-                    mint_melt_limits,
-                    Arc::new(fake),
-                )
-                .await?;
+                backends.push((ln_entry.unit.clone(), mint_melt_limits, Arc::new(fake)));
 
                 configure_fake_wallet_keyset_rotations = true;
             }
@@ -711,14 +862,7 @@ async fn configure_lightning_backend(
                 #[cfg(feature = "prometheus")]
                 let processor = MetricsMintPayment::new(processor);
 
-                mint_builder = configure_backend_for_unit(
-                    settings,
-                    mint_builder,
-                    ln_entry.unit.clone(),
-                    mint_melt_limits,
-                    Arc::new(processor),
-                )
-                .await?;
+                backends.push((ln_entry.unit.clone(), mint_melt_limits, Arc::new(processor)));
             }
             #[cfg(feature = "ldk-node")]
             LnBackend::LdkNode => {
@@ -737,14 +881,7 @@ async fn configure_lightning_backend(
                     )
                     .await?;
 
-                mint_builder = configure_backend_for_unit(
-                    settings,
-                    mint_builder,
-                    ln_entry.unit.clone(),
-                    mint_melt_limits,
-                    Arc::new(ldk_node),
-                )
-                .await?;
+                backends.push((ln_entry.unit.clone(), mint_melt_limits, Arc::new(ldk_node)));
             }
             LnBackend::None => {
                 tracing::info!(
@@ -755,6 +892,33 @@ async fn configure_lightning_backend(
         };
     }
 
+    // Several entries of one unit are served by a router over their backends
+    let mut units: Vec<(CurrencyUnit, MintMeltLimits, Vec<DynMintPayment>)> = Vec::new();
+    for (unit, mint_melt_limits, backend) in backends {
+        match units.iter_mut().find(|(existing, _, _)| *existing == unit) {
+            Some((_, _, unit_backends)) => unit_backends.push(backend),
+            None => units.push((unit, mint_melt_limits, vec![backend])),
+        }
+    }
+
+    for (unit, mint_melt_limits, mut unit_backends) in units {
+        let backend: DynMintPayment = if unit_backends.len() == 1 {
+            unit_backends.remove(0)
+        } else {
+            tracing::info!(
+                "Routing {} payments across {} backends ({:?})",
+                unit,
+                unit_backends.len(),
+                settings.payment_routing
+            );
+            Arc::new(PaymentRouter::new(unit_backends, settings.payment_routing)?)
+        };
+
+        mint_builder =
+            configure_backend_for_unit(settings, mint_builder, unit, mint_melt_limits, backend)
+                .await?;
+    }
+
     #[cfg(feature = "fakewallet")]
     if configure_fake_wallet_keyset_rotations {
         let fake_wallet = settings.fake_wallet.as_ref().ok_or_else(|| {
@@ -1005,11 +1169,11 @@ fn units_are_compatible(
 }
 
 /// Configures cache settings with support for custom payment methods
-async fn configure_cache(
+fn configure_cache(
     settings: &config::Settings,
     mint_builder: MintBuilder,
     payment_methods: &[String],
-) -> Result<MintBuilder> {
+) -> MintBuilder {
     let mut cached_endpoints = vec![
         // Always include swap endpoint
         CachedEndpoint::new(NUT19Method::Post, NUT19Path::Swap),
@@ -1028,8 +1192,7 @@ async fn configure_cache(
         ));
     }
 
-    let cache: HttpCache = HttpCache::from_config(settings.info.http_cache.clone()).await?;
-    Ok(mint_builder.with_cache(Some(cache.ttl.as_secs()), cached_endpoints))
+    mint_builder.with_cache(Some(settings.info.http_cache.ttl_secs()), cached_endpoints)
 }
 
 async fn setup_authentication(
@@ -1092,6 +1255,10 @@ async fn setup_authentication(
                         auth_pg_config.max_connections,
                         auth_pg_config.connection_timeout_seconds,
                     );
+                    let auth_db_config = match auth_pg_config.schema.as_deref() {
+                        Some(schema) => auth_db_config.with_schema(schema),
+                        None => auth_db_config,
+                    };
                     Arc::new(MintPgAuthDatabase::new(auth_db_config).await?)
                 }
                 #[cfg(not(feature = "postgres"))]
@@ -1212,15 +1379,47 @@ async fn build_mint(
             settings.info.signatory_certs.clone()
         );
 
-        Ok(mint_builder
-            .build_with_signatory(Arc::new(
+        let mut signatories: Vec<Arc<dyn cdk_signatory::signatory::Signatory + Send + Sync>> =
+            Vec::new();
+        for url in
+            std::iter::once(signatory_url).chain(settings.info.signatory_fallback_urls.clone())
+        {
+            #[cfg(unix)]
+            if let Some(path) = url.strip_prefix("unix://") {
+                signatories.push(Arc::new(
+                    cdk_signatory::SignatoryRpcClient::new_uds(
+                        path,
+                        settings.info.signatory_api_key.clone(),
+                    )
+                    .await?,
+                ));
+                continue;
+            }
+
+            signatories.push(Arc::new(
                 cdk_signatory::SignatoryRpcClient::new(
-                    signatory_url,
+                    url,
                     settings.info.signatory_certs.clone(),
+                    settings.info.signatory_api_key.clone(),
                 )
                 .await?,
-            ))
-            .await?)
+            ));
+        }
+
+        let signatory: Arc<dyn cdk_signatory::signatory::Signatory + Send + Sync> =
+            if signatories.len() > 1 {
+                tracing::info!(
+                    "Falling over between {} remote signatories",
+                    signatories.len()
+                );
+                Arc::new(cdk_signatory::failover::FailoverSignatory::new(
+                    signatories,
+                )?)
+            } else {
+                signatories.remove(0)
+            };
+
+        Ok(mint_builder.build_with_signatory(signatory).await?)
     } else if let Some(seed) = settings.info.seed.clone() {
         let seed_bytes: Vec<u8> = seed.into();
         Ok(mint_builder.build_with_seed(keystore, &seed_bytes).await?)
@@ -1231,14 +1430,157 @@ async fn build_mint(
         .map(|s| Mnemonic::from_str(&s))
         .transpose()?
     {
+        let passphrase = settings
+            .info
+            .mnemonic_passphrase
+            .as_deref()
+            .unwrap_or_default();
         Ok(mint_builder
-            .build_with_seed(keystore, &mnemonic.to_seed_normalized(""))
+            .build_with_seed(keystore, &mnemonic.to_seed_normalized(passphrase))
             .await?)
     } else {
         bail!("No seed nor remote signatory set");
     }
 }
 
+/// Mint of a tenant hosted next to the primary mint
+struct TenantMint {
+    name: String,
+    path: String,
+    mint: Arc<Mint>,
+    mint_info: cdk::nuts::MintInfo,
+}
+
+/// Builds the mints of all configured tenants
+///
+/// Each tenant gets its own data directory, database and seed, and its own instance of the
+/// payment backends configured for the primary mint.
+async fn build_tenants(
+    settings: &config::Settings,
+    work_dir: &Path,
+    runtime: Option<std::sync::Arc<tokio::runtime::Runtime>>,
+    db_password: Option<String>,
+) -> Result<Vec<TenantMint>> {
+    settings.validate_tenants().map_err(anyhow::Error::msg)?;
+
+    let mut tenants = Vec::with_capacity(settings.tenants.len());
+
+    for tenant in &settings.tenants {
+        tracing::info!("Building tenant mint {} at {}", tenant.name, tenant.path());
+
+        let tenant_settings = settings.tenant_settings(tenant);
+        let tenant_dir = work_dir.join("tenants").join(&tenant.name);
+        std::fs::create_dir_all(&tenant_dir)?;
+
+        let (localstore, keystore, kv) =
+            initial_setup(&tenant_dir, &tenant_settings, db_password.clone()).await?;
+
+        let mint_builder = configure_mint_builder(
+            &tenant_settings,
+            MintBuilder::new(localstore),
+            runtime.clone(),
+            &tenant_dir,
+            Some(kv),
+        )
+        .await
+        .with_context(|| format!("Failed to configure tenant {}", tenant.name))?;
+
+        let mint_info = mint_builder.current_mint_info();
+        let mint = build_mint(&tenant_settings, keystore, mint_builder).await?;
+
+        tenants.push(TenantMint {
+            name: tenant.name.clone(),
+            path: tenant.path(),
+            mint: Arc::new(mint),
+            mint_info,
+        });
+    }
+
+    Ok(tenants)
+}
+
+/// Stores the tenant mint info and creates the router serving the tenant
+///
+/// The config file is the source of truth for tenants, apart from the stored fields kept by
+/// [`keep_stored_mint_info`].
+async fn tenant_router(tenant: &TenantMint, settings: &config::Settings) -> Result<Router> {
+    let mut mint_info = tenant.mint_info.clone();
+
+    if let Ok(stored_mint_info) = tenant.mint.mint_info().await {
+        keep_stored_mint_info(&mut mint_info, stored_mint_info);
+    }
+
+    tenant.mint.set_mint_info(mint_info).await?;
+    tenant
+        .mint
+        .set_quote_ttl(settings.info.quote_ttl.unwrap_or_default())
+        .await?;
+
+    let custom_methods = router_payment_methods(&tenant.mint).await?;
+    tracing::info!(
+        "Tenant {} payment methods: {:?}",
+        tenant.name,
+        custom_methods
+    );
+
+    Ok(cdk_axum::create_mint_router_with_options(
+        Arc::clone(&tenant.mint),
+        MintRouterOptions::new(custom_methods)
+            .with_cache(
+                HttpCache::from_config_with_mint(
+                    settings.info.http_cache.clone(),
+                    Some(Arc::clone(&tenant.mint)),
+                )
+                .await?,
+            )
+            .with_info_page(settings.info.enable_info_page.unwrap_or(true))
+            .with_rate_limiter(settings.rate_limit.rate_limiter()),
+    )
+    .await?)
+}
+
+/// Payment methods the HTTP router has to expose for `mint`
+async fn router_payment_methods(mint: &Mint) -> Result<Vec<String>> {
+    let mint_info = mint.mint_info().await?;
+    let nut04_methods = mint_info.nuts.nut04.supported_methods();
+    let nut05_methods = mint_info.nuts.nut05.supported_methods();
+
+    // Get custom payment methods from payment processors
+    let mut custom_methods = mint.get_custom_payment_methods().await?;
+
+    // Add bolt11 if it's supported by any payment processor
+    let bolt11_method = PaymentMethod::Known(KnownMethod::Bolt11);
+    let bolt11_supported =
+        nut04_methods.contains(&&bolt11_method) || nut05_methods.contains(&&bolt11_method);
+    // Add bolt12 if it's supported by any payment processor
+    let bolt12_method = PaymentMethod::Known(KnownMethod::Bolt12);
+    let bolt12_supported =
+        nut04_methods.contains(&&bolt12_method) || nut05_methods.contains(&&bolt12_method);
+
+    // Add onchain if it's supported by any payment processor
+    let onchain_method = PaymentMethod::Known(KnownMethod::Onchain);
+    let onchain_supported =
+        nut04_methods.contains(&&onchain_method) || nut05_methods.contains(&&onchain_method);
+
+    if bolt11_supported
+        && !custom_methods.contains(&PaymentMethod::Known(KnownMethod::Bolt11).to_string())
+    {
+        custom_methods.push(PaymentMethod::Known(KnownMethod::Bolt11).to_string());
+    }
+    if bolt12_supported
+        && !custom_methods.contains(&PaymentMethod::Known(KnownMethod::Bolt12).to_string())
+    {
+        custom_methods.push(PaymentMethod::Known(KnownMethod::Bolt12).to_string());
+    }
+    if onchain_supported
+        && !custom_methods.contains(&PaymentMethod::Known(KnownMethod::Onchain).to_string())
+    {
+        custom_methods.push(PaymentMethod::Known(KnownMethod::Onchain).to_string());
+    }
+
+    Ok(custom_methods)
+}
+
 async fn start_services_with_shutdown(
     mint: Arc<cdk::mint::Mint>,
     settings: &config::Settings,
@@ -1247,10 +1589,13 @@ async fn start_services_with_shutdown(
     shutdown_signal: impl std::future::Future<Output = ()> + Send + 'static,
     routers: Vec<Router>,
     auth_localstore: Option<cdk_common::database::DynMintAuthDatabase>,
+    tenants: Vec<TenantMint>,
 ) -> Result<()> {
     let listen_addr = settings.info.listen_host.clone();
     let listen_port = settings.info.listen_port;
-    let cache: HttpCache = HttpCache::from_config(settings.info.http_cache.clone()).await?;
+    let cache: HttpCache =
+        HttpCache::from_config_with_mint(settings.info.http_cache.clone(), Some(Arc::clone(&mint)))
+            .await?;
 
     #[cfg(feature = "management-rpc")]
     let mut rpc_enabled = false;
@@ -1266,7 +1611,25 @@ async fn start_services_with_shutdown(
             if rpc_settings.enabled {
                 let addr = rpc_settings.address.unwrap_or("127.0.0.1".to_string());
                 let port = rpc_settings.port.unwrap_or(8086);
-                let mut mint_rpc = cdk_mint_rpc::MintRPCServer::new(&addr, port, mint.clone())?;
+                let auth = if rpc_settings.require_token {
+                    if rpc_settings.tokens.is_empty() {
+                        bail!("[mint_management_rpc].require_token is set without any token");
+                    }
+                    cdk_mint_rpc::RpcAuth::new(rpc_settings.tokens.iter().map(|token| {
+                        let role = match token.role {
+                            config::AccessRole::ReadOnly => cdk_mint_rpc::Role::ReadOnly,
+                            config::AccessRole::Manage => cdk_mint_rpc::Role::Manage,
+                        };
+                        (token.token.clone(), role)
+                    }))
+                } else {
+                    if !rpc_settings.tokens.is_empty() {
+                        bail!("[mint_management_rpc].tokens are set but require_token is not");
+                    }
+                    cdk_mint_rpc::RpcAuth::default()
+                };
+                let mut mint_rpc =
+                    cdk_mint_rpc::MintRPCServer::new(&addr, port, mint.clone())?.with_auth(auth);
 
                 let tls_dir = rpc_settings.tls_dir.unwrap_or(_work_dir.join("tls"));
 
@@ -1297,10 +1660,15 @@ async fn start_services_with_shutdown(
         }
     }
 
+    let admin_api = settings
+        .admin_api
+        .as_ref()
+        .filter(|admin_api| admin_api.enabled);
+
     // Determine the desired QuoteTTL from config/env or fall back to defaults
     let desired_quote_ttl: QuoteTTL = settings.info.quote_ttl.unwrap_or_default();
 
-    if rpc_enabled {
+    if rpc_enabled || admin_api.is_some() {
         if mint.mint_info().await.is_err() {
             tracing::info!("Mint info not set on mint, setting.");
             // First boot with RPC enabled: seed from config
@@ -1318,6 +1686,8 @@ async fn start_services_with_shutdown(
             );
             let mut stored_mint_info = mint.mint_info().await?;
             stored_mint_info.version = Some(mint_version);
+            // NUTs disabled in the config stay disabled even when the stored info enables them
+            cdk::mint::disable_nuts(&mut stored_mint_info.nuts, &settings.info.disabled_nuts)?;
             mint.set_mint_info(stored_mint_info).await?;
 
             tracing::info!("Mint info already set, not using config file settings.");
@@ -1328,51 +1698,14 @@ async fn start_services_with_shutdown(
         let mut mint_builder_info = mint_builder_info;
 
         if let Ok(mint_info) = mint.mint_info().await {
-            if mint_builder_info.pubkey.is_none() {
-                mint_builder_info.pubkey = mint_info.pubkey;
-            }
+            keep_stored_mint_info(&mut mint_builder_info, mint_info);
         }
 
         mint.set_mint_info(mint_builder_info).await?;
         mint.set_quote_ttl(desired_quote_ttl).await?;
     }
 
-    let mint_info = mint.mint_info().await?;
-    let nut04_methods = mint_info.nuts.nut04.supported_methods();
-    let nut05_methods = mint_info.nuts.nut05.supported_methods();
-
-    // Get custom payment methods from payment processors
-    let mut custom_methods = mint.get_custom_payment_methods().await?;
-
-    // Add bolt11 if it's supported by any payment processor
-    let bolt11_method = PaymentMethod::Known(KnownMethod::Bolt11);
-    let bolt11_supported =
-        nut04_methods.contains(&&bolt11_method) || nut05_methods.contains(&&bolt11_method);
-    // Add bolt12 if it's supported by any payment processor
-    let bolt12_method = PaymentMethod::Known(KnownMethod::Bolt12);
-    let bolt12_supported =
-        nut04_methods.contains(&&bolt12_method) || nut05_methods.contains(&&bolt12_method);
-
-    // Add onchain if it's supported by any payment processor
-    let onchain_method = PaymentMethod::Known(KnownMethod::Onchain);
-    let onchain_supported =
-        nut04_methods.contains(&&onchain_method) || nut05_methods.contains(&&onchain_method);
-
-    if bolt11_supported
-        && !custom_methods.contains(&PaymentMethod::Known(KnownMethod::Bolt11).to_string())
-    {
-        custom_methods.push(PaymentMethod::Known(KnownMethod::Bolt11).to_string());
-    }
-    if bolt12_supported
-        && !custom_methods.contains(&PaymentMethod::Known(KnownMethod::Bolt12).to_string())
-    {
-        custom_methods.push(PaymentMethod::Known(KnownMethod::Bolt12).to_string());
-    }
-    if onchain_supported
-        && !custom_methods.contains(&PaymentMethod::Known(KnownMethod::Onchain).to_string())
-    {
-        custom_methods.push(PaymentMethod::Known(KnownMethod::Onchain).to_string());
-    }
+    let custom_methods = router_payment_methods(&mint).await?;
 
     tracing::info!("Payment methods: {:?}", custom_methods);
 
@@ -1525,17 +1858,38 @@ async fn start_services_with_shutdown(
         }
     }
 
-    let v1_service = cdk_axum::create_mint_router_with_custom_cache(
+    let v1_service = cdk_axum::create_mint_router_with_options(
         Arc::clone(&mint),
-        cache,
-        custom_methods,
-        settings.info.enable_info_page.unwrap_or(true),
+        MintRouterOptions::new(custom_methods)
+            .with_cache(cache)
+            .with_info_page(settings.info.enable_info_page.unwrap_or(true))
+            .with_rate_limiter(settings.rate_limit.rate_limiter()),
     )
     .await?;
 
-    let mut mint_service = Router::new()
-        .merge(v1_service)
-        .layer(DefaultBodyLimit::max(REQUEST_BODY_LIMIT_BYTES))
+    let mut mint_service = Router::new().merge(v1_service);
+
+    #[cfg(feature = "ecash-address")]
+    if let Some(ecash_address) = settings.ecash_address.as_ref().filter(|s| s.enabled) {
+        mint_service = mint_service.merge(ecash_address::router(
+            Arc::clone(&mint),
+            &settings.info.url,
+            ecash_address,
+        )?);
+        tracing::info!("Serving {} ecash addresses", ecash_address.addresses.len());
+    }
+
+    for tenant in &tenants {
+        mint_service = mint_service.nest(&tenant.path, tenant_router(tenant, settings).await?);
+    }
+
+    let mut mint_service = mint_service
+        .layer(DefaultBodyLimit::max(
+            settings
+                .limits
+                .max_request_bytes
+                .unwrap_or(REQUEST_BODY_LIMIT_BYTES),
+        ))
         .layer(
             ServiceBuilder::new()
                 .layer(RequestDecompressionLayer::new())
@@ -1547,6 +1901,9 @@ async fn start_services_with_shutdown(
         mint_service = mint_service.merge(router);
     }
 
+    // Probes are left out of the request tracing, they are polled every few seconds
+    mint_service = mint_service.merge(cdk_axum::create_health_router(Arc::clone(&mint)));
+
     // Create a broadcast channel to share shutdown signal between services
     let (shutdown_tx, _) = tokio::sync::broadcast::channel::<()>(1);
 
@@ -1565,9 +1922,26 @@ async fn start_services_with_shutdown(
                     .parse()
                     .expect("Invalid prometheus address");
 
-                let server = cdk_prometheus::PrometheusBuilder::new()
-                    .bind_address(address)
-                    .build_with_cdk_metrics()?;
+                let mut builder = cdk_prometheus::PrometheusBuilder::new().bind_address(address);
+                if prometheus_settings.require_token {
+                    if prometheus_settings.tokens.is_empty() {
+                        bail!("[prometheus].require_token is set without any token");
+                    }
+                    let tokens = cdk_common::bearer::BearerTokens::new(
+                        prometheus_settings
+                            .tokens
+                            .iter()
+                            .map(|token| (token.token.clone(), ())),
+                    );
+                    builder =
+                        builder.authorizer(move |authorization| tokens.role(authorization).is_ok());
+                } else if !prometheus_settings.tokens.is_empty() {
+                    bail!("[prometheus].tokens are set but require_token is not");
+                }
+                if let Some(tls_dir) = &prometheus_settings.tls_dir {
+                    builder = builder.tls_dir(tls_dir.clone());
+                }
+                let server = builder.build_with_cdk_metrics()?;
 
                 let mut shutdown_rx = shutdown_tx.subscribe();
                 let prometheus_shutdown = async move {
@@ -1587,8 +1961,51 @@ async fn start_services_with_shutdown(
         }
     };
 
+    let admin_handle = match admin_api {
+        Some(admin_api) => {
+            if admin_api.tokens.is_empty() {
+                bail!("The admin API needs at least one token in [admin_api].tokens");
+            }
+
+            let addr = admin_api.address.as_deref().unwrap_or("127.0.0.1");
+            let port = admin_api.port.unwrap_or(8087);
+            let auth = cdk_axum::AdminAuth::new(admin_api.tokens.iter().map(|token| {
+                let role = match token.role {
+                    config::AccessRole::ReadOnly => cdk_axum::AdminRole::ReadOnly,
+                    config::AccessRole::Manage => cdk_axum::AdminRole::Manage,
+                };
+                (token.token.clone(), role)
+            }));
+            let admin_service = cdk_axum::create_admin_router(Arc::clone(&mint), auth)
+                .layer(TraceLayer::new_for_http());
+
+            let socket_addr = SocketAddr::from_str(&format!("{addr}:{port}"))?;
+            let listener = tokio::net::TcpListener::bind(socket_addr).await?;
+            tracing::info!("admin API listening on {}", listener.local_addr()?);
+
+            let mut shutdown_rx = shutdown_tx.subscribe();
+            let admin_shutdown = async move {
+                let _ = shutdown_rx.recv().await;
+            };
+
+            Some(tokio::spawn(async move {
+                if let Err(e) = axum::serve(listener, admin_service)
+                    .with_graceful_shutdown(admin_shutdown)
+                    .await
+                {
+                    tracing::error!("Admin API server failed: {}", e);
+                }
+            }))
+        }
+        None => None,
+    };
+
     mint.start().await?;
 
+    for tenant in &tenants {
+        tenant.mint.start().await?;
+    }
+
     let socket_addr = SocketAddr::from_str(&format!("{listen_addr}:{listen_port}"))?;
 
     let listener = tokio::net::TcpListener::bind(socket_addr).await?;
@@ -1612,7 +2029,12 @@ async fn start_services_with_shutdown(
     };
 
     // Wait for axum server to complete with custom shutdown signal
-    let axum_result = axum::serve(listener, mint_service).with_graceful_shutdown(axum_shutdown);
+    // The address of the connection identifies clients for the rate limits
+    let axum_result = axum::serve(
+        listener,
+        mint_service.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(axum_shutdown);
 
     match axum_result.await {
         Ok(_) => {
@@ -1628,6 +2050,12 @@ async fn start_services_with_shutdown(
     // Wait for the shutdown broadcast task to complete
     let _ = shutdown_broadcast_task.await;
 
+    if let Some(handle) = admin_handle {
+        if let Err(e) = handle.await {
+            tracing::warn!("Admin API server task failed: {}", e);
+        }
+    }
+
     // Wait for prometheus server to shutdown if it was started
     #[cfg(feature = "prometheus")]
     if let Some(handle) = prometheus_handle {
@@ -1636,7 +2064,11 @@ async fn start_services_with_shutdown(
         }
     }
 
-    mint.stop().await?;
+    mint.shutdown().await?;
+
+    for tenant in &tenants {
+        tenant.mint.shutdown().await?;
+    }
 
     #[cfg(feature = "management-rpc")]
     {
@@ -1712,6 +2144,7 @@ pub async fn run_mintd_with_shutdown(
     routers: Vec<Router>,
 ) -> Result<()> {
     let (localstore, keystore, kv) = initial_setup(work_dir, settings, db_password.clone()).await?;
+    let tenants = build_tenants(settings, work_dir, runtime.clone(), db_password.clone()).await?;
 
     let mint_builder = MintBuilder::new(localstore);
 
@@ -1762,6 +2195,7 @@ pub async fn run_mintd_with_shutdown(
         shutdown_signal,
         routers,
         auth_localstore,
+        tenants,
     )
     .await
 }
@@ -1812,6 +2246,52 @@ mod tests {
         let _ = fs::remove_file(&seed_file);
     }
 
+    #[test]
+    fn derive_keysets_from_the_configured_mnemonic() {
+        let settings = config::Settings {
+            info: config::Info {
+                mnemonic: Some(TEST_MNEMONIC.to_string()),
+                input_fee_ppk: Some(100),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let args = DeriveKeysetsArgs {
+            unit: "sat".to_string(),
+            max_index: 1,
+            derivation_path: None,
+            max_order: 8,
+            input_fee_ppk: None,
+            final_expiry: None,
+            network: None,
+            legacy_id: false,
+        };
+
+        let keysets = derive_keysets(&settings, &args).expect("keysets should be derived");
+
+        assert_eq!(keysets.len(), 2);
+        assert_ne!(keysets[0].id, keysets[1].id);
+        for keyset in &keysets {
+            assert_eq!(keyset.keys.len(), 8);
+            assert_eq!(keyset.input_fee_ppk, 100);
+            assert_eq!(keyset.id.get_version(), KeySetVersion::Version01);
+            keyset.verify_id().expect("id should match the keys");
+        }
+
+        // Another network derives unrelated keys from the same seed
+        let testnet = derive_keysets(
+            &settings,
+            &DeriveKeysetsArgs {
+                network: Some(bitcoin::Network::Testnet),
+                legacy_id: true,
+                ..args
+            },
+        )
+        .expect("keysets should be derived");
+        assert_eq!(testnet[0].id.get_version(), KeySetVersion::Version00);
+        assert_ne!(testnet[0].keys, keysets[0].keys);
+    }
+
     #[cfg(feature = "bdk")]
     #[test]
     fn apply_seed_file_sets_active_bdk_mnemonic() {
@@ -1820,6 +2300,10 @@ mod tests {
         let seed_file = temp_seed_file("seed_file_sets_bdk_seed");
         fs::write(&seed_file, TEST_MNEMONIC).expect("seed file should be written");
         let mut settings = config::Settings {
+            info: config::Info {
+                mnemonic_passphrase: Some("mint passphrase".to_string()),
+                ..Default::default()
+            },
             onchain: Some(Onchain {
                 onchain_backend: OnchainBackend::Bdk,
                 ..Default::default()
@@ -1833,13 +2317,9 @@ mod tests {
 
         apply_seed_file(&mut settings, &seed_file).expect("seed file should be applied");
 
-        assert_eq!(
-            settings
-                .bdk
-                .expect("bdk settings should be present")
-                .mnemonic,
-            Some(TEST_MNEMONIC.to_string())
-        );
+        let bdk = settings.bdk.expect("bdk settings should be present");
+        assert_eq!(bdk.mnemonic, Some(TEST_MNEMONIC.to_string()));
+        assert_eq!(bdk.mnemonic_passphrase, Some("mint passphrase".to_string()));
 
         let _ = fs::remove_file(&seed_file);
     }
@@ -1991,7 +2471,7 @@ mod tests {
 
     #[cfg(all(feature = "fakewallet", feature = "sqlite"))]
     #[tokio::test]
-    async fn duplicate_ln_unit_method_pair_is_rejected() {
+    async fn ln_entries_of_one_unit_are_routed() {
         use cdk::mint::MintBuilder;
         use cdk_sqlite::mint::memory;
 
@@ -2016,12 +2496,23 @@ mod tests {
 
         let localstore = Arc::new(memory::empty().await.unwrap());
         let builder = MintBuilder::new(localstore);
-        let err =
+        let builder =
             configure_lightning_backend(&settings, builder, None, &std::env::temp_dir(), None)
                 .await
-                .expect_err("duplicate unit/method pair should be rejected");
+                .expect("entries of one unit should share a router");
 
-        assert!(err.to_string().contains("Duplicate payment processor"));
+        let mint_info = builder.current_mint_info();
+        let sat_bolt11 = mint_info
+            .nuts
+            .nut04
+            .methods
+            .iter()
+            .filter(|method| {
+                method.unit == CurrencyUnit::Sat
+                    && method.method == PaymentMethod::Known(KnownMethod::Bolt11)
+            })
+            .count();
+        assert_eq!(sat_bolt11, 1);
     }
 
     #[cfg(all(feature = "fakewallet", feature = "sqlite"))]
@@ -2405,6 +2896,34 @@ mod tests {
         assert!(!auth_config.url.is_empty());
     }
 
+    #[test]
+    fn keep_stored_mint_info_keeps_read_only_mode() {
+        let stored = cdk::nuts::MintInfo {
+            motd: Some("under maintenance".to_string()),
+            read_only: true,
+            ..Default::default()
+        };
+        let mut mint_info = cdk::nuts::MintInfo {
+            motd: Some("from config".to_string()),
+            ..Default::default()
+        };
+
+        keep_stored_mint_info(&mut mint_info, stored);
+
+        assert!(mint_info.read_only);
+        assert_eq!(mint_info.motd.as_deref(), Some("under maintenance"));
+
+        let mut mint_info = cdk::nuts::MintInfo {
+            motd: Some("from config".to_string()),
+            ..Default::default()
+        };
+
+        keep_stored_mint_info(&mut mint_info, cdk::nuts::MintInfo::default());
+
+        assert!(!mint_info.read_only);
+        assert_eq!(mint_info.motd.as_deref(), Some("from config"));
+    }
+
     #[test]
     fn test_extract_supported_payment_methods_unique_ordered() {
         let mut mint_info = cdk::nuts::MintInfo::default();
@@ -0,0 +1,208 @@
+//! Log output of the mint
+//!
+//! Fields named like secrets (seeds, keys, blinded secrets, preimages) are written as
+//! `[redacted]` at every level, on events and spans alike, so they never reach a log file.
+//! Values wrapped in [`cdk_common::redact::Redacted`] are redacted whatever their field name.
+//!
+//! High volume spans can be sampled: only one span in N with a configured name is logged, with
+//! every span and event inside it.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use cdk_common::redact::{is_sensitive_field, REDACTED};
+use tracing::{span, Metadata, Subscriber};
+use tracing_subscriber::field::MakeExt;
+use tracing_subscriber::filter::DynFilterFn;
+use tracing_subscriber::fmt::format::{debug_fn, FormatFields};
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::layer::{Context, Filter, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer};
+
+/// Field formatter writing [`REDACTED`] instead of the value of sensitive fields
+pub fn redacting_fields() -> impl for<'writer> FormatFields<'writer> + Send + Sync + 'static {
+    debug_fn(|writer, field, value| {
+        if field.name() == "message" {
+            write!(writer, "{value:?}")
+        } else if is_sensitive_field(field.name()) {
+            write!(writer, "{field}={REDACTED}")
+        } else {
+            write!(writer, "{field}={value:?}")
+        }
+    })
+    .delimited(" ")
+}
+
+/// Marks a span left out by the [`SpanSampler`], and every span inside it
+struct Unsampled;
+
+/// Samples the spans of configured names
+///
+/// Of the spans named after a key of the rates, only one in the rate is sampled; a rate of 0
+/// samples none of them. Spans created inside an unsampled span are unsampled too. Pair it with
+/// [`sampled_filter`] on the output layer.
+#[derive(Debug, Default)]
+pub struct SpanSampler {
+    rates: HashMap<String, (u64, AtomicU64)>,
+}
+
+impl SpanSampler {
+    /// Sample one in `rate` spans of each name of `rates`
+    pub fn new(rates: &HashMap<String, u64>) -> Self {
+        Self {
+            rates: rates
+                .iter()
+                .map(|(name, rate)| (name.clone(), (*rate, AtomicU64::new(0))))
+                .collect(),
+        }
+    }
+
+    /// Whether the next span named `name` is sampled
+    fn sample(&self, name: &str) -> bool {
+        match self.rates.get(name) {
+            Some((0, _)) => false,
+            Some((rate, seen)) => seen.fetch_add(1, Ordering::Relaxed) % rate == 0,
+            None => true,
+        }
+    }
+}
+
+impl<S> Layer<S> for SpanSampler
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+
+        let unsampled = span
+            .parent()
+            .is_some_and(|parent| parent.extensions().get::<Unsampled>().is_some())
+            || !self.sample(attrs.metadata().name());
+
+        if unsampled {
+            span.extensions_mut().insert(Unsampled);
+        }
+    }
+}
+
+/// Filter dropping what is recorded inside a span the [`SpanSampler`] left out
+pub fn sampled_filter<S>() -> impl Filter<S> + Send + Sync + 'static
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    DynFilterFn::new(|_metadata: &Metadata<'_>, cx: &Context<'_, S>| {
+        cx.lookup_current()
+            .is_none_or(|span| span.extensions().get::<Unsampled>().is_none())
+    })
+}
+
+/// Layer writing redacted logs to `writer`, sampling the spans of `sampled_spans`
+pub fn layer<S, W>(sampled_spans: &HashMap<String, u64>, writer: W) -> impl Layer<S>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'writer> MakeWriter<'writer> + Send + Sync + 'static,
+{
+    SpanSampler::new(sampled_spans).and_then(
+        tracing_subscriber::fmt::layer()
+            .fmt_fields(redacting_fields())
+            .with_ansi(false)
+            .with_writer(writer)
+            .with_filter(sampled_filter()),
+    )
+}
+
+/// Install the global subscriber writing redacted logs to `writer`
+pub fn init<W>(env_filter: EnvFilter, sampled_spans: &HashMap<String, u64>, writer: W)
+where
+    W: for<'writer> MakeWriter<'writer> + Send + Sync + 'static,
+{
+    tracing_subscriber::registry()
+        .with(env_filter)
+        .with(layer(sampled_spans, writer))
+        .init();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::sync::{Arc, Mutex};
+
+    use cdk_common::redact::Redacted;
+
+    use super::*;
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().expect("buffer lock").extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Buffer {
+        fn output(&self) -> String {
+            String::from_utf8(self.0.lock().expect("buffer lock").clone()).expect("utf8 logs")
+        }
+    }
+
+    fn logged(sampled_spans: HashMap<String, u64>, f: impl FnOnce()) -> String {
+        let buffer = Buffer::default();
+        let writer = buffer.clone();
+        let subscriber = tracing_subscriber::registry()
+            .with(layer(&sampled_spans, move || writer.clone()))
+            .with(tracing_subscriber::filter::LevelFilter::TRACE);
+
+        tracing::subscriber::with_default(subscriber, f);
+        buffer.output()
+    }
+
+    #[test]
+    fn secrets_are_redacted_at_every_level() {
+        let output = logged(HashMap::new(), || {
+            let span = tracing::info_span!("swap", mnemonic = "abandon abandon about");
+            let _guard = span.enter();
+            tracing::trace!(seed = "000102", amount = 8, "signing");
+            tracing::error!(blinded_secret = ?"02aa", quote_id = "quote", "failed");
+            tracing::debug!("key {}", Redacted::new("deadbeef"));
+        });
+
+        for secret in ["abandon", "000102", "02aa", "deadbeef"] {
+            assert!(!output.contains(secret), "{secret} logged in {output}");
+        }
+        assert!(output.contains("seed=[redacted]"));
+        assert!(output.contains("amount=8"));
+        assert!(output.contains("quote_id=\"quote\""));
+        assert!(output.contains("key [redacted]"));
+    }
+
+    #[test]
+    fn unsampled_spans_are_dropped_with_their_events() {
+        let output = logged(HashMap::from([("blind_sign".to_owned(), 3)]), || {
+            for i in 0..6 {
+                let span = tracing::info_span!("blind_sign");
+                let _guard = span.enter();
+                let inner = tracing::info_span!("inner");
+                let _inner = inner.enter();
+                tracing::info!("signed {}", i);
+            }
+            tracing::info!("outside");
+        });
+
+        assert!(output.contains("signed 0"));
+        assert!(output.contains("signed 3"));
+        for dropped in ["signed 1", "signed 2", "signed 4", "signed 5"] {
+            assert!(!output.contains(dropped), "{dropped} logged in {output}");
+        }
+        assert!(output.contains("outside"));
+    }
+}
@@ -3,7 +3,7 @@
 use std::sync::Arc;
 
 use anyhow::Result;
-use cdk_mintd::cli::CLIArgs;
+use cdk_mintd::cli::{CLIArgs, Command};
 use cdk_mintd::{get_work_directory, load_settings_from_args};
 use clap::Parser;
 use tokio::runtime::Runtime;
@@ -18,6 +18,12 @@ fn main() -> Result<()> {
         let work_dir = get_work_directory(&args).await?;
         let settings = load_settings_from_args(&work_dir, &args)?;
 
+        if let Some(Command::DeriveKeysets(derive_args)) = &args.command {
+            let keysets = cdk_mintd::derive_keysets(&settings, derive_args)?;
+            println!("{}", serde_json::to_string_pretty(&keysets)?);
+            return Ok(());
+        }
+
         #[cfg(feature = "sqlcipher")]
         let password = Some(CLIArgs::parse().password);
 
@@ -17,6 +17,7 @@ use cdk::cdk_payment::MintPayment;
 use cdk::nuts::CurrencyUnit;
 #[cfg(any(
     feature = "lnbits",
+    feature = "phoenixd",
     feature = "cln",
     feature = "lnd",
     feature = "ldk-node",
@@ -29,6 +30,24 @@ use crate::config::{self, Settings};
 #[cfg(feature = "cln")]
 use crate::expand_path;
 
+/// Fail when `backend` runs on another network than the `network` of the mint
+#[cfg(any(feature = "ldk-node", feature = "bdk"))]
+fn ensure_mint_network(
+    settings: &Settings,
+    backend: &str,
+    network: bitcoin::Network,
+) -> anyhow::Result<()> {
+    match settings.info.network {
+        Some(mint_network) if mint_network != network => anyhow::bail!(
+            "{} runs on {} but the mint is configured for {}",
+            backend,
+            network,
+            mint_network
+        ),
+        _ => Ok(()),
+    }
+}
+
 #[async_trait]
 pub trait LnBackendSetup {
     async fn setup(
@@ -144,6 +163,44 @@ impl LnBackendSetup for config::LNbits {
     }
 }
 
+#[cfg(feature = "phoenixd")]
+#[async_trait]
+impl LnBackendSetup for config::Phoenixd {
+    async fn setup(
+        &self,
+        _settings: &Settings,
+        _unit: CurrencyUnit,
+        _runtime: Option<std::sync::Arc<tokio::runtime::Runtime>>,
+        _work_dir: &Path,
+        _kv_store: Option<Arc<dyn KVStore<Err = cdk::cdk_database::Error> + Send + Sync>>,
+    ) -> anyhow::Result<cdk_phoenixd::Phoenixd> {
+        use anyhow::bail;
+
+        // Validate required connection fields
+        if self.api_password.is_empty() {
+            bail!("Phoenixd api_password must be set via config or CDK_MINTD_PHOENIXD_API_PASSWORD env var");
+        }
+        if self.api_url.is_empty() {
+            bail!("Phoenixd api_url must be set via config or CDK_MINTD_PHOENIXD_API_URL env var");
+        }
+
+        let fee_reserve = FeeReserve {
+            min_fee_reserve: self.reserve_fee_min,
+            percent_fee_reserve: self.fee_percent,
+        };
+
+        let phoenixd = cdk_phoenixd::Phoenixd::new(
+            self.api_password.clone(),
+            self.api_url.clone(),
+            fee_reserve,
+        )?;
+
+        phoenixd.check_connection().await?;
+
+        Ok(phoenixd)
+    }
+}
+
 #[cfg(feature = "lnd")]
 #[async_trait]
 impl LnBackendSetup for config::Lnd {
@@ -185,7 +242,8 @@ impl LnBackendSetup for config::Lnd {
             fee_reserve,
             kv_store.expect("Lnd needs kv store"),
         )
-        .await?;
+        .await?
+        .with_keysend(self.keysend);
 
         Ok(lnd)
     }
@@ -380,6 +438,7 @@ impl LnBackendSetup for config::LdkNode {
             "regtest" => Network::Regtest,
             _ => bail!("Unknown LDK Node bitcoin_network: {}", network_str),
         };
+        ensure_mint_network(settings, "LDK Node", network)?;
 
         // Parse chain source from config
         let chain_source = match self
@@ -562,6 +621,7 @@ impl OnchainBackendSetup for crate::config::Bdk {
             "regtest" => Network::Regtest,
             _ => bail!("Unknown BDK network: {}", network_str),
         };
+        ensure_mint_network(settings, "BDK", network)?;
 
         let chain_source_type = self
             .chain_source_type
@@ -617,7 +677,7 @@ impl OnchainBackendSetup for crate::config::Bdk {
             .unwrap_or(self.min_receive_amount_sat);
 
         let bdk = cdk_bdk::CdkBdk::new(
-            mnemonic,
+            (mnemonic, self.mnemonic_passphrase.clone()),
             network,
             chain_source,
             work_dir.to_string_lossy().to_string(),
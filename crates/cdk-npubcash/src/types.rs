@@ -145,6 +145,7 @@ impl From<Quote> for MintQuote {
         let state = match quote.state.as_deref() {
             Some("PAID") => MintQuoteState::Paid,
             Some("ISSUED") => MintQuoteState::Issued,
+            Some("CANCELLED") => MintQuoteState::Cancelled,
             _ => MintQuoteState::Unpaid,
         };
 
@@ -134,6 +134,10 @@ impl PaymentProcessorClient {
 impl MintPayment for PaymentProcessorClient {
     type Err = cdk_common::payment::Error;
 
+    fn backend_name(&self) -> String {
+        "grpc-processor".to_string()
+    }
+
     async fn get_settings(&self) -> Result<cdk_common::payment::SettingsResponse, Self::Err> {
         let mut inner = self.inner.clone();
         let response = inner
@@ -253,6 +257,9 @@ impl MintPayment for PaymentProcessorClient {
             cdk_common::payment::OutgoingPaymentOptions::Onchain(_) => {
                 OutgoingPaymentRequestType::Onchain
             }
+            cdk_common::payment::OutgoingPaymentOptions::Keysend(_) => {
+                OutgoingPaymentRequestType::Keysend
+            }
         };
 
         let proto_request = match &options {
@@ -260,13 +267,15 @@ impl MintPayment for PaymentProcessorClient {
             cdk_common::payment::OutgoingPaymentOptions::Bolt11(opts) => opts.bolt11.to_string(),
             cdk_common::payment::OutgoingPaymentOptions::Bolt12(opts) => opts.offer.to_string(),
             cdk_common::payment::OutgoingPaymentOptions::Onchain(opts) => opts.address.clone(),
+            cdk_common::payment::OutgoingPaymentOptions::Keysend(opts) => opts.pubkey.to_hex(),
         };
 
         let proto_options = match &options {
             cdk_common::payment::OutgoingPaymentOptions::Custom(opts) => opts.melt_options,
             cdk_common::payment::OutgoingPaymentOptions::Bolt11(opts) => opts.melt_options,
             cdk_common::payment::OutgoingPaymentOptions::Bolt12(opts) => opts.melt_options,
-            cdk_common::payment::OutgoingPaymentOptions::Onchain(_) => None,
+            cdk_common::payment::OutgoingPaymentOptions::Onchain(_)
+            | cdk_common::payment::OutgoingPaymentOptions::Keysend(_) => None,
         };
 
         let onchain_options = match &options {
@@ -283,6 +292,20 @@ impl MintPayment for PaymentProcessorClient {
             _ => None,
         };
 
+        let keysend_options = match &options {
+            cdk_common::payment::OutgoingPaymentOptions::Keysend(opts) => {
+                Some(super::KeysendOutgoingPaymentOptions {
+                    pubkey: opts.pubkey.to_hex(),
+                    amount: Some(opts.amount.clone().into()),
+                    max_fee_amount: opts.max_fee_amount.clone().into_proto(),
+                    timeout_secs: opts.timeout_secs,
+                    quote_id: opts.quote_id.to_string(),
+                    preimage: opts.preimage.map(|preimage| preimage.to_vec()),
+                })
+            }
+            _ => None,
+        };
+
         let extra_json = match &options {
             cdk_common::payment::OutgoingPaymentOptions::Custom(opts) => opts.extra_json.clone(),
             _ => None,
@@ -293,6 +316,7 @@ impl MintPayment for PaymentProcessorClient {
             cdk_common::payment::OutgoingPaymentOptions::Bolt11(opts) => opts.quote_id.to_string(),
             cdk_common::payment::OutgoingPaymentOptions::Bolt12(opts) => opts.quote_id.to_string(),
             cdk_common::payment::OutgoingPaymentOptions::Onchain(opts) => opts.quote_id.to_string(),
+            cdk_common::payment::OutgoingPaymentOptions::Keysend(opts) => opts.quote_id.to_string(),
         };
 
         let response = inner
@@ -304,6 +328,7 @@ impl MintPayment for PaymentProcessorClient {
                 extra_json,
                 quote_id,
                 onchain_options,
+                keysend_options,
             }))
             .await
             .map_err(|err| {
@@ -381,6 +406,20 @@ impl MintPayment for PaymentProcessorClient {
                     )),
                 }
             }
+            cdk_common::payment::OutgoingPaymentOptions::Keysend(opts) => {
+                super::OutgoingPaymentVariant {
+                    options: Some(super::outgoing_payment_variant::Options::Keysend(
+                        super::KeysendOutgoingPaymentOptions {
+                            pubkey: opts.pubkey.to_hex(),
+                            amount: Some(opts.amount.into()),
+                            max_fee_amount: opts.max_fee_amount.into_proto(),
+                            timeout_secs: opts.timeout_secs,
+                            quote_id: opts.quote_id.to_string(),
+                            preimage: opts.preimage.map(|preimage| preimage.to_vec()),
+                        },
+                    )),
+                }
+            }
         };
 
         let response = inner
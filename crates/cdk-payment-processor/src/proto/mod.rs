@@ -330,6 +330,7 @@ impl From<QuoteState> for cdk_common::nuts::MeltQuoteState {
             QuoteState::Unknown => Self::Unknown,
             QuoteState::Failed => Self::Failed,
             QuoteState::Issued => Self::Unknown,
+            QuoteState::Cancelled => Self::Failed,
             QuoteState::Unspecified => Self::Unknown,
         }
     }
@@ -353,6 +354,7 @@ impl From<cdk_common::nuts::MintQuoteState> for QuoteState {
             cdk_common::nuts::MintQuoteState::Unpaid => Self::Unpaid,
             cdk_common::nuts::MintQuoteState::Paid => Self::Paid,
             cdk_common::nuts::MintQuoteState::Issued => Self::Issued,
+            cdk_common::nuts::MintQuoteState::Cancelled => Self::Cancelled,
         }
     }
 }
@@ -371,6 +371,19 @@ impl CdkPaymentProcessor for PaymentProcessorServer {
                     },
                 ))
             }
+            OutgoingPaymentRequestType::Keysend => {
+                let opts = request.keysend_options.ok_or_else(|| {
+                    Status::invalid_argument("Missing keysend_options for keysend quote")
+                })?;
+                let keysend_options = parse_keysend_options(opts)?;
+                if keysend_options.quote_id != quote_id {
+                    return Err(Status::invalid_argument(
+                        "quote_id does not match keysend_options quote_id",
+                    ));
+                }
+
+                cdk_common::payment::OutgoingPaymentOptions::Keysend(Box::new(keysend_options))
+            }
             OutgoingPaymentRequestType::Unspecified => {
                 return Err(Status::invalid_argument("Unspecified payment request type"));
             }
@@ -488,6 +501,11 @@ impl CdkPaymentProcessor for PaymentProcessorServer {
                     },
                 ))
             }
+            outgoing_payment_variant::Options::Keysend(opts) => {
+                cdk_common::payment::OutgoingPaymentOptions::Keysend(Box::new(
+                    parse_keysend_options(opts)?,
+                ))
+            }
         };
 
         let pay_response = self
@@ -612,3 +630,36 @@ fn parse_quote_id(s: &str) -> Result<QuoteId, Status> {
     s.parse()
         .map_err(|err| Status::invalid_argument(format!("Invalid quote_id: {err}")))
 }
+
+fn parse_keysend_options(
+    opts: KeysendOutgoingPaymentOptions,
+) -> Result<cdk_common::payment::KeysendOutgoingPaymentOptions, Status> {
+    let pubkey = cdk_common::PublicKey::from_hex(&opts.pubkey)
+        .map_err(|_| Status::invalid_argument("Invalid keysend pubkey"))?;
+    let amount = opts
+        .amount
+        .ok_or_else(|| Status::invalid_argument("Missing amount"))?
+        .try_into()
+        .map_err(|_| Status::invalid_argument("Invalid amount"))?;
+    let max_fee_amount = opts
+        .max_fee_amount
+        .try_from_proto()
+        .map_err(|_| Status::invalid_argument("Invalid max_fee_amount"))?;
+    let preimage = opts
+        .preimage
+        .map(|preimage| {
+            preimage
+                .try_into()
+                .map_err(|_| Status::invalid_argument("Invalid keysend preimage"))
+        })
+        .transpose()?;
+
+    Ok(cdk_common::payment::KeysendOutgoingPaymentOptions {
+        pubkey,
+        amount,
+        max_fee_amount,
+        timeout_secs: opts.timeout_secs,
+        quote_id: parse_quote_id(&opts.quote_id)?,
+        preimage,
+    })
+}
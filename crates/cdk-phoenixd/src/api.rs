@@ -0,0 +1,280 @@
+//! Client of the phoenixd http api
+//!
+//! phoenixd authenticates every request, websocket included, with http basic auth: an empty
+//! username and the api password found in `~/.phoenix/phoenix.conf`.
+
+use bitcoin::base64::engine::general_purpose;
+use bitcoin::base64::Engine as _;
+use cdk_http_client::ws::{self, WsReceiver};
+use cdk_http_client::{HttpClient, HttpError};
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+
+/// Client of a phoenixd node
+#[derive(Clone)]
+pub struct PhoenixdApi {
+    api_url: String,
+    authorization: String,
+    client: HttpClient,
+}
+
+impl std::fmt::Debug for PhoenixdApi {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PhoenixdApi")
+            .field("api_url", &self.api_url)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Form of `POST /createinvoice`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateInvoiceRequest {
+    /// Description of the invoice
+    pub description: String,
+    /// Amount of the invoice in sats
+    pub amount_sat: u64,
+    /// Seconds until the invoice expires
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expiry_seconds: Option<u64>,
+}
+
+/// Response of `POST /createinvoice`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateInvoiceResponse {
+    /// Amount of the invoice in sats
+    pub amount_sat: u64,
+    /// Hex payment hash of the invoice
+    pub payment_hash: String,
+    /// Bolt11 invoice
+    pub serialized: String,
+}
+
+/// Form of `POST /payinvoice`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PayInvoiceRequest<'a> {
+    invoice: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    amount_sat: Option<u64>,
+}
+
+/// Response of `POST /payinvoice`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayInvoiceResponse {
+    /// Amount received by the payee in sats
+    pub recipient_amount_sat: u64,
+    /// Routing fee paid in sats
+    pub routing_fee_sat: u64,
+    /// Id of the payment in phoenixd
+    pub payment_id: String,
+    /// Hex payment hash
+    pub payment_hash: String,
+    /// Hex preimage of the payment
+    pub payment_preimage: String,
+}
+
+/// Incoming payment, from `GET /payments/incoming/{paymentHash}`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IncomingPayment {
+    /// Hex payment hash
+    pub payment_hash: String,
+    /// Whether the invoice was paid
+    pub is_paid: bool,
+    /// Amount received in sats
+    pub received_sat: u64,
+}
+
+/// Outgoing payment, from `GET /payments/outgoingbyhash/{paymentHash}`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutgoingPayment {
+    /// Hex payment hash
+    pub payment_hash: String,
+    /// Hex preimage, once paid
+    pub preimage: Option<String>,
+    /// Whether the payment succeeded
+    pub is_paid: bool,
+    /// Amount sent in sats
+    pub sent: u64,
+    /// Routing fee paid in msats
+    pub fees: u64,
+    /// Time the payment succeeded or failed, unset while in flight
+    pub completed_at: Option<u64>,
+}
+
+/// Response of `GET /getbalance`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Balance {
+    /// Spendable balance in sats
+    pub balance_sat: u64,
+}
+
+/// Notification sent over the phoenixd websocket
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Notification {
+    /// An invoice was paid
+    #[serde(rename_all = "camelCase")]
+    PaymentReceived {
+        /// Amount received in sats
+        amount_sat: u64,
+        /// Hex payment hash of the invoice
+        payment_hash: String,
+    },
+    /// Any other notification, which the mint does not use
+    #[serde(other)]
+    Other,
+}
+
+impl PhoenixdApi {
+    /// Create a client of the phoenixd api at `api_url`
+    pub fn new(api_url: &str, api_password: &str) -> Result<Self, Error> {
+        let api_url = api_url.trim_end_matches('/').to_string();
+        if !api_url.starts_with("http://") && !api_url.starts_with("https://") {
+            return Err(Error::InvalidApiUrl(api_url));
+        }
+
+        Ok(Self {
+            authorization: format!(
+                "Basic {}",
+                general_purpose::STANDARD.encode(format!(":{api_password}"))
+            ),
+            api_url,
+            client: HttpClient::new(),
+        })
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}/{}", self.api_url, path)
+    }
+
+    /// Create a bolt11 invoice
+    pub async fn create_invoice(
+        &self,
+        request: &CreateInvoiceRequest,
+    ) -> Result<CreateInvoiceResponse, Error> {
+        Ok(self
+            .client
+            .post(&self.url("createinvoice"))
+            .header("Authorization", &self.authorization)
+            .form(request)
+            .send_json()
+            .await?)
+    }
+
+    /// Pay a bolt11 invoice, `amount_sat` is required for amountless invoices
+    pub async fn pay_bolt11_invoice(
+        &self,
+        invoice: &str,
+        amount_sat: Option<u64>,
+    ) -> Result<PayInvoiceResponse, Error> {
+        Ok(self
+            .client
+            .post(&self.url("payinvoice"))
+            .header("Authorization", &self.authorization)
+            .form(&PayInvoiceRequest {
+                invoice,
+                amount_sat,
+            })
+            .send_json()
+            .await?)
+    }
+
+    /// Incoming payment of the invoice with `payment_hash`
+    pub async fn get_incoming_payment(&self, payment_hash: &str) -> Result<IncomingPayment, Error> {
+        Ok(self
+            .client
+            .get(&self.url(&format!("payments/incoming/{payment_hash}")))
+            .header("Authorization", &self.authorization)
+            .send_json()
+            .await?)
+    }
+
+    /// Outgoing payment with `payment_hash`, `None` if phoenixd never attempted it
+    pub async fn get_outgoing_payment(
+        &self,
+        payment_hash: &str,
+    ) -> Result<Option<OutgoingPayment>, Error> {
+        let response = self
+            .client
+            .get(&self.url(&format!("payments/outgoingbyhash/{payment_hash}")))
+            .header("Authorization", &self.authorization)
+            .send()
+            .await?;
+
+        if response.status() == 404 {
+            return Ok(None);
+        }
+
+        if !response.is_success() {
+            let status = response.status();
+            let message = response.text().await.unwrap_or_default();
+            return Err(HttpError::Status { status, message }.into());
+        }
+
+        Ok(Some(response.json().await?))
+    }
+
+    /// Balance of the node
+    pub async fn get_balance(&self) -> Result<Balance, Error> {
+        Ok(self
+            .client
+            .get(&self.url("getbalance"))
+            .header("Authorization", &self.authorization)
+            .send_json()
+            .await?)
+    }
+
+    /// Open the websocket on which phoenixd notifies received payments
+    pub async fn connect_websocket(&self) -> Result<WsReceiver, Error> {
+        let ws_url = self
+            .api_url
+            .replacen("https://", "wss://", 1)
+            .replacen("http://", "ws://", 1);
+
+        let (_sender, receiver) = ws::connect(
+            &format!("{ws_url}/websocket"),
+            &[("Authorization", &self.authorization)],
+        )
+        .await?;
+
+        Ok(receiver)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn payment_received_notifications_are_parsed() {
+        let notification: Notification = serde_json::from_str(
+            r#"{"type":"payment_received","timestamp":1712785550079,"amountSat":1000,"paymentHash":"f2c1b9b3","externalId":null,"payerNote":null,"payerKey":null}"#,
+        )
+        .expect("payment notification should parse");
+        assert!(matches!(
+            notification,
+            Notification::PaymentReceived { amount_sat: 1000, ref payment_hash } if payment_hash == "f2c1b9b3"
+        ));
+
+        let notification: Notification =
+            serde_json::from_str(r#"{"type":"channel_opened","amountSat":1}"#)
+                .expect("unknown notifications should parse");
+        assert!(matches!(notification, Notification::Other));
+    }
+
+    #[test]
+    fn api_url_must_be_http() {
+        assert!(PhoenixdApi::new("http://127.0.0.1:9740/", "password").is_ok());
+        assert!(matches!(
+            PhoenixdApi::new("127.0.0.1:9740", "password"),
+            Err(Error::InvalidApiUrl(_))
+        ));
+    }
+}
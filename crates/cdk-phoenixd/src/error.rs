@@ -0,0 +1,48 @@
+//! Error for phoenixd ln backend
+
+use thiserror::Error;
+
+/// Phoenixd Error
+#[derive(Debug, Error)]
+pub enum Error {
+    /// Invoice amount not defined
+    #[error("Unknown invoice amount")]
+    UnknownInvoiceAmount,
+    /// Amount overflow
+    #[error("Amount overflow")]
+    AmountOverflow,
+    /// Amount phoenixd cannot pay, as it takes whole sats
+    #[error("Amount of {0} msat is not a whole number of sats")]
+    FractionalSatAmount(u64),
+    /// phoenixd may charge more than the maximum fee of the payment
+    #[error(
+        "phoenixd fee of up to {fee_msat} msat exceeds the maximum fee of {max_fee_msat} msat"
+    )]
+    FeeExceedsMax {
+        /// Most phoenixd may charge
+        fee_msat: u64,
+        /// Maximum fee of the payment
+        max_fee_msat: u64,
+    },
+    /// Invalid payment hash
+    #[error("Invalid payment hash")]
+    InvalidPaymentHash,
+    /// Api url is not an http(s) url
+    #[error("Invalid phoenixd api url: {0}")]
+    InvalidApiUrl(String),
+    /// Http error
+    #[error(transparent)]
+    Http(#[from] cdk_http_client::HttpError),
+    /// Websocket error
+    #[error(transparent)]
+    Ws(#[from] cdk_http_client::ws::WsError),
+    /// Anyhow error
+    #[error(transparent)]
+    Anyhow(#[from] anyhow::Error),
+}
+
+impl From<Error> for cdk_common::payment::Error {
+    fn from(e: Error) -> Self {
+        Self::Lightning(Box::new(e))
+    }
+}
@@ -0,0 +1,638 @@
+//! CDK lightning backend for phoenixd
+
+#![doc = include_str!("../README.md")]
+
+use std::cmp::max;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use cdk_common::amount::{Amount, MSAT_IN_SAT};
+use cdk_common::common::FeeReserve;
+use cdk_common::nuts::{CurrencyUnit, MeltOptions, MeltQuoteState};
+use cdk_common::payment::{
+    self, CreateIncomingPaymentResponse, Event, IncomingPaymentOptions, MakePaymentResponse,
+    MintPayment, OutgoingPaymentOptions, PaymentIdentifier, PaymentQuoteResponse, SettingsResponse,
+    WaitPaymentResponse,
+};
+use cdk_common::util::{hex, unix_time};
+use cdk_common::Bolt11Invoice;
+use error::Error;
+use futures::Stream;
+use tokio_util::sync::CancellationToken;
+
+use crate::api::{CreateInvoiceRequest, Notification, OutgoingPayment, PhoenixdApi};
+
+pub mod api;
+pub mod error;
+
+/// Fixed part of the fee phoenixd charges on outgoing payments
+const PHOENIXD_FEE_BASE_MSAT: u64 = 4_000;
+/// Proportional part of the fee phoenixd charges on outgoing payments, in parts per million
+const PHOENIXD_FEE_PPM: u64 = 4_000;
+
+/// Phoenixd
+#[derive(Clone)]
+pub struct Phoenixd {
+    phoenixd_api: PhoenixdApi,
+    fee_reserve: FeeReserve,
+    wait_invoice_cancel_token: CancellationToken,
+    wait_invoice_is_active: Arc<AtomicBool>,
+    settings: SettingsResponse,
+}
+
+impl std::fmt::Debug for Phoenixd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Phoenixd")
+            .field("phoenixd_api", &self.phoenixd_api)
+            .field("fee_reserve", &self.fee_reserve)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Phoenixd {
+    /// Create new [`Phoenixd`] wallet
+    pub fn new(
+        api_password: String,
+        api_url: String,
+        fee_reserve: FeeReserve,
+    ) -> Result<Self, Error> {
+        let phoenixd_api = PhoenixdApi::new(&api_url, &api_password)?;
+
+        Ok(Self {
+            phoenixd_api,
+            fee_reserve,
+            wait_invoice_cancel_token: CancellationToken::new(),
+            wait_invoice_is_active: Arc::new(AtomicBool::new(false)),
+            settings: SettingsResponse {
+                unit: CurrencyUnit::Sat.to_string(),
+                bolt11: Some(payment::Bolt11Settings {
+                    mpp: false,
+                    amountless: true,
+                    invoice_description: true,
+                }),
+                bolt12: None,
+                onchain: None,
+                custom: std::collections::HashMap::new(),
+            },
+        })
+    }
+
+    /// Check that phoenixd is reachable with the configured password
+    pub async fn check_connection(&self) -> Result<(), Error> {
+        if rustls::crypto::CryptoProvider::get_default().is_none() {
+            let _ = rustls::crypto::ring::default_provider().install_default();
+        }
+
+        self.phoenixd_api.get_balance().await.map_err(|err| {
+            tracing::error!("Could not connect to phoenixd: {}", err);
+            err
+        })?;
+
+        Ok(())
+    }
+
+    /// Received payment of a websocket message, if the message notifies one
+    fn process_message(msg: &str) -> Option<WaitPaymentResponse> {
+        let notification: Notification = match serde_json::from_str(msg) {
+            Ok(notification) => notification,
+            Err(err) => {
+                tracing::warn!("Could not parse phoenixd notification: {}", err);
+                return None;
+            }
+        };
+
+        match notification {
+            Notification::PaymentReceived {
+                amount_sat,
+                payment_hash,
+            } => match Self::decode_payment_hash(&payment_hash) {
+                Ok(hash) => Some(WaitPaymentResponse {
+                    payment_identifier: PaymentIdentifier::PaymentHash(hash),
+                    payment_amount: Amount::new(amount_sat, CurrencyUnit::Sat),
+                    payment_id: payment_hash,
+                }),
+                Err(err) => {
+                    tracing::error!(
+                        "Received payment with invalid hash {}: {}",
+                        payment_hash,
+                        err
+                    );
+                    None
+                }
+            },
+            Notification::Other => None,
+        }
+    }
+
+    /// Most phoenixd charges to pay `amount_msat`, rounded up to a whole sat
+    ///
+    /// phoenixd takes no fee limit, so the fee reserve must cover its fee schedule.
+    fn max_phoenixd_fee_msat(amount_msat: u64) -> u64 {
+        amount_msat
+            .saturating_mul(PHOENIXD_FEE_PPM)
+            .div_ceil(1_000_000)
+            .saturating_add(PHOENIXD_FEE_BASE_MSAT)
+            .div_ceil(MSAT_IN_SAT)
+            .saturating_mul(MSAT_IN_SAT)
+    }
+
+    /// Amount in sat to pay an amountless invoice, refusing amounts phoenixd cannot pay exactly
+    fn amountless_amount_sat(amount_msat: u64) -> Result<u64, Error> {
+        if amount_msat % MSAT_IN_SAT != 0 {
+            return Err(Error::FractionalSatAmount(amount_msat));
+        }
+
+        Ok(amount_msat / MSAT_IN_SAT)
+    }
+
+    /// Decode a hex payment hash string into a byte array
+    fn decode_payment_hash(hash_str: &str) -> Result<[u8; 32], Error> {
+        hex::decode(hash_str)
+            .map_err(|_| Error::InvalidPaymentHash)?
+            .try_into()
+            .map_err(|_| Error::InvalidPaymentHash)
+    }
+
+    /// Payment response of an outgoing payment as known by phoenixd
+    fn outgoing_payment_response(
+        payment_identifier: PaymentIdentifier,
+        payment: Option<OutgoingPayment>,
+    ) -> Result<MakePaymentResponse, Error> {
+        let Some(payment) = payment else {
+            return Ok(MakePaymentResponse {
+                payment_lookup_id: payment_identifier,
+                payment_proof: None,
+                status: MeltQuoteState::Unknown,
+                total_spent: Amount::new(0, CurrencyUnit::Msat),
+            });
+        };
+
+        let status = phoenixd_to_melt_status(&payment);
+        let total_spent = match status {
+            MeltQuoteState::Paid => payment
+                .sent
+                .checked_mul(MSAT_IN_SAT)
+                .and_then(|sent| sent.checked_add(payment.fees))
+                .ok_or(Error::AmountOverflow)?,
+            _ => 0,
+        };
+
+        Ok(MakePaymentResponse {
+            payment_lookup_id: payment_identifier,
+            payment_proof: payment.preimage,
+            status,
+            total_spent: Amount::new(total_spent, CurrencyUnit::Msat),
+        })
+    }
+}
+
+#[async_trait]
+impl MintPayment for Phoenixd {
+    type Err = payment::Error;
+
+    fn backend_name(&self) -> String {
+        "phoenixd".to_string()
+    }
+
+    async fn get_settings(&self) -> Result<SettingsResponse, Self::Err> {
+        Ok(self.settings.clone())
+    }
+
+    fn is_payment_event_stream_active(&self) -> bool {
+        self.wait_invoice_is_active.load(Ordering::SeqCst)
+    }
+
+    fn cancel_payment_event_stream(&self) {
+        self.wait_invoice_cancel_token.cancel()
+    }
+
+    async fn wait_payment_event(
+        &self,
+    ) -> Result<Pin<Box<dyn Stream<Item = Event> + Send>>, Self::Err> {
+        let api = self.phoenixd_api.clone();
+        let cancel_token = self.wait_invoice_cancel_token.clone();
+        let is_active = Arc::clone(&self.wait_invoice_is_active);
+
+        Ok(Box::pin(futures::stream::unfold(
+            (api, cancel_token, is_active, None, 0u32),
+            |(api, cancel_token, is_active, mut receiver, mut retry_count)| async move {
+                is_active.store(true, Ordering::SeqCst);
+
+                loop {
+                    let Some(ws) = receiver.as_mut() else {
+                        if retry_count > 0 {
+                            // Exponential backoff: 1s, 2s, 4s, then 8s
+                            let backoff = Duration::from_secs(2u64.pow(retry_count.min(4) - 1));
+                            tracing::info!(
+                                "Reconnecting to phoenixd websocket in {} seconds (attempt {})",
+                                backoff.as_secs(),
+                                retry_count
+                            );
+
+                            tokio::select! {
+                                _ = cancel_token.cancelled() => {
+                                    is_active.store(false, Ordering::SeqCst);
+                                    tracing::info!("Waiting for phoenixd invoice ending");
+                                    return None;
+                                }
+                                _ = tokio::time::sleep(backoff) => {}
+                            }
+                        }
+
+                        match api.connect_websocket().await {
+                            Ok(ws) => {
+                                tracing::debug!("Connected to phoenixd websocket");
+                                receiver = Some(ws);
+                            }
+                            Err(err) => {
+                                tracing::error!("Could not connect to phoenixd websocket: {}", err);
+                                retry_count += 1;
+                            }
+                        }
+                        continue;
+                    };
+
+                    let msg = tokio::select! {
+                        _ = cancel_token.cancelled() => {
+                            is_active.store(false, Ordering::SeqCst);
+                            tracing::info!("Waiting for phoenixd invoice ending");
+                            return None;
+                        }
+                        msg = ws.recv() => msg,
+                    };
+
+                    match msg {
+                        Some(Ok(msg)) => {
+                            retry_count = 0;
+                            if let Some(response) = Self::process_message(&msg) {
+                                return Some((
+                                    Event::PaymentReceived(response),
+                                    (api, cancel_token, is_active, receiver, retry_count),
+                                ));
+                            }
+                        }
+                        Some(Err(err)) => {
+                            tracing::warn!("Phoenixd websocket error, reconnecting: {}", err);
+                            receiver = None;
+                            retry_count += 1;
+                        }
+                        None => {
+                            tracing::warn!("Phoenixd websocket connection lost, reconnecting");
+                            receiver = None;
+                            retry_count += 1;
+                        }
+                    }
+                }
+            },
+        )))
+    }
+
+    async fn get_payment_quote(
+        &self,
+        unit: &CurrencyUnit,
+        options: OutgoingPaymentOptions,
+    ) -> Result<PaymentQuoteResponse, Self::Err> {
+        match options {
+            OutgoingPaymentOptions::Bolt11(bolt11_options) => {
+                let amount_msat = match bolt11_options.melt_options {
+                    Some(MeltOptions::Amountless { amountless }) => {
+                        let amount_msat = amountless.amount_msat;
+
+                        if let Some(invoice_amount) = bolt11_options.bolt11.amount_milli_satoshis()
+                        {
+                            if invoice_amount != u64::from(amount_msat) {
+                                return Err(payment::Error::AmountMismatch);
+                            }
+                        } else {
+                            Self::amountless_amount_sat(amount_msat.into())?;
+                        }
+
+                        amount_msat
+                    }
+                    Some(MeltOptions::Mpp { mpp: _ }) => {
+                        return Err(payment::Error::UnsupportedPaymentOption);
+                    }
+                    None => bolt11_options
+                        .bolt11
+                        .amount_milli_satoshis()
+                        .ok_or(Error::UnknownInvoiceAmount)?
+                        .into(),
+                };
+
+                let relative_fee_reserve =
+                    (self.fee_reserve.percent_fee_reserve * u64::from(amount_msat) as f32) as u64;
+
+                let absolute_fee_reserve: u64 =
+                    u64::from(self.fee_reserve.min_fee_reserve) * MSAT_IN_SAT;
+
+                let fee = max(relative_fee_reserve, absolute_fee_reserve)
+                    .max(Self::max_phoenixd_fee_msat(amount_msat.into()));
+
+                Ok(PaymentQuoteResponse {
+                    request_lookup_id: Some(PaymentIdentifier::PaymentHash(
+                        *bolt11_options.bolt11.payment_hash().as_ref(),
+                    )),
+                    amount: Amount::new(amount_msat.into(), CurrencyUnit::Msat).convert_to(unit)?,
+                    fee: Amount::new(fee, CurrencyUnit::Msat).convert_to(unit)?,
+                    state: MeltQuoteState::Unpaid,
+                    extra_json: None,
+                    estimated_blocks: None,
+                    fee_options: None,
+                })
+            }
+            OutgoingPaymentOptions::Bolt12(_) | OutgoingPaymentOptions::Custom(_) => {
+                Err(payment::Error::UnsupportedPaymentOption)
+            }
+            OutgoingPaymentOptions::Onchain(_) | OutgoingPaymentOptions::Keysend(_) => {
+                Err(payment::Error::UnsupportedPaymentOption)
+            }
+        }
+    }
+
+    async fn make_payment(
+        &self,
+        unit: &CurrencyUnit,
+        options: OutgoingPaymentOptions,
+    ) -> Result<MakePaymentResponse, Self::Err> {
+        match options {
+            OutgoingPaymentOptions::Bolt11(bolt11_options) => {
+                let payment_hash = *bolt11_options.bolt11.payment_hash().as_ref();
+                let payment_identifier = PaymentIdentifier::PaymentHash(payment_hash);
+
+                // phoenixd only takes an amount for amountless invoices
+                let invoice_amount_msat = bolt11_options.bolt11.amount_milli_satoshis();
+                let amount_sat = match bolt11_options.melt_options {
+                    Some(MeltOptions::Amountless { amountless })
+                        if invoice_amount_msat.is_none() =>
+                    {
+                        Some(Self::amountless_amount_sat(amountless.amount_msat.into())?)
+                    }
+                    _ => None,
+                };
+                let amount_msat = match amount_sat {
+                    Some(amount_sat) => amount_sat
+                        .checked_mul(MSAT_IN_SAT)
+                        .ok_or(Error::AmountOverflow)?,
+                    None => invoice_amount_msat.ok_or(Error::UnknownInvoiceAmount)?,
+                };
+
+                // Refused rather than letting phoenixd spend more than the fee reserve
+                if let Some(max_fee) = &bolt11_options.max_fee_amount {
+                    let max_fee_msat = max_fee.to_msat()?;
+                    let fee_msat = Self::max_phoenixd_fee_msat(amount_msat);
+
+                    if fee_msat > max_fee_msat {
+                        return Err(Error::FeeExceedsMax {
+                            fee_msat,
+                            max_fee_msat,
+                        }
+                        .into());
+                    }
+                }
+
+                let pay_response = match self
+                    .phoenixd_api
+                    .pay_bolt11_invoice(&bolt11_options.bolt11.to_string(), amount_sat)
+                    .await
+                {
+                    Ok(pay_response) => pay_response,
+                    Err(err) => {
+                        // A failed request does not mean a failed payment, ask phoenixd
+                        tracing::error!("Could not pay invoice: {}", err);
+                        let Some(payment) = self
+                            .phoenixd_api
+                            .get_outgoing_payment(&hex::encode(payment_hash))
+                            .await?
+                        else {
+                            return Err(err.into());
+                        };
+                        let response =
+                            Self::outgoing_payment_response(payment_identifier, Some(payment))?;
+
+                        return Ok(MakePaymentResponse {
+                            total_spent: response.total_spent.convert_to(unit)?,
+                            ..response
+                        });
+                    }
+                };
+
+                let total_spent = pay_response
+                    .recipient_amount_sat
+                    .checked_add(pay_response.routing_fee_sat)
+                    .ok_or(Error::AmountOverflow)?;
+
+                Ok(MakePaymentResponse {
+                    payment_lookup_id: PaymentIdentifier::PaymentHash(Self::decode_payment_hash(
+                        &pay_response.payment_hash,
+                    )?),
+                    payment_proof: Some(pay_response.payment_preimage),
+                    status: MeltQuoteState::Paid,
+                    total_spent: Amount::new(total_spent, CurrencyUnit::Sat).convert_to(unit)?,
+                })
+            }
+            OutgoingPaymentOptions::Bolt12(_)
+            | OutgoingPaymentOptions::Custom(_)
+            | OutgoingPaymentOptions::Onchain(_)
+            | OutgoingPaymentOptions::Keysend(_) => Err(payment::Error::UnsupportedPaymentOption),
+        }
+    }
+
+    async fn create_incoming_payment_request(
+        &self,
+        options: IncomingPaymentOptions,
+    ) -> Result<CreateIncomingPaymentResponse, Self::Err> {
+        match options {
+            IncomingPaymentOptions::Bolt11(bolt11_options) => {
+                let time_now = unix_time();
+                let expiry_seconds = bolt11_options
+                    .unix_expiry
+                    .map(|t| t.checked_sub(time_now).ok_or(payment::Error::InvalidExpiry))
+                    .transpose()?;
+
+                let invoice_request = CreateInvoiceRequest {
+                    description: bolt11_options.description.unwrap_or_default(),
+                    amount_sat: bolt11_options.amount.to_sat()?,
+                    expiry_seconds,
+                };
+
+                let create_invoice_response = self
+                    .phoenixd_api
+                    .create_invoice(&invoice_request)
+                    .await
+                    .map_err(|err| {
+                        tracing::error!("Could not create invoice: {}", err);
+                        err
+                    })?;
+
+                let request: Bolt11Invoice = create_invoice_response.serialized.parse()?;
+
+                let expiry = request.expires_at().map(|t| t.as_secs());
+
+                Ok(CreateIncomingPaymentResponse {
+                    request_lookup_id: PaymentIdentifier::PaymentHash(
+                        *request.payment_hash().as_ref(),
+                    ),
+                    request: request.to_string(),
+                    expiry,
+                    extra_json: None,
+                })
+            }
+            IncomingPaymentOptions::Bolt12(_)
+            | IncomingPaymentOptions::Custom(_)
+            | IncomingPaymentOptions::Onchain(_) => Err(payment::Error::UnsupportedPaymentOption),
+        }
+    }
+
+    async fn check_incoming_payment_status(
+        &self,
+        payment_identifier: &PaymentIdentifier,
+    ) -> Result<Vec<WaitPaymentResponse>, Self::Err> {
+        let payment = self
+            .phoenixd_api
+            .get_incoming_payment(&payment_identifier.to_string())
+            .await
+            .map_err(|err| {
+                tracing::error!("Could not check invoice status: {}", err);
+                err
+            })?;
+
+        match payment.is_paid {
+            true => Ok(vec![WaitPaymentResponse {
+                payment_identifier: payment_identifier.clone(),
+                payment_amount: Amount::new(payment.received_sat, CurrencyUnit::Sat),
+                payment_id: payment.payment_hash,
+            }]),
+            false => Ok(vec![]),
+        }
+    }
+
+    async fn check_outgoing_payment(
+        &self,
+        payment_identifier: &PaymentIdentifier,
+    ) -> Result<MakePaymentResponse, Self::Err> {
+        let payment = self
+            .phoenixd_api
+            .get_outgoing_payment(&payment_identifier.to_string())
+            .await
+            .map_err(|err| {
+                tracing::error!("Could not check payment status: {}", err);
+                err
+            })?;
+
+        Ok(Self::outgoing_payment_response(
+            payment_identifier.clone(),
+            payment,
+        )?)
+    }
+
+    async fn outbound_liquidity(
+        &self,
+        unit: &CurrencyUnit,
+    ) -> Result<Option<Amount<CurrencyUnit>>, Self::Err> {
+        let balance = self.phoenixd_api.get_balance().await?;
+
+        Ok(Some(
+            Amount::new(balance.balance_sat, CurrencyUnit::Sat).convert_to(unit)?,
+        ))
+    }
+}
+
+fn phoenixd_to_melt_status(payment: &OutgoingPayment) -> MeltQuoteState {
+    match (payment.is_paid, payment.completed_at) {
+        (true, _) => MeltQuoteState::Paid,
+        (false, Some(_)) => MeltQuoteState::Failed,
+        (false, None) => MeltQuoteState::Pending,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outgoing_payment(is_paid: bool, completed_at: Option<u64>) -> OutgoingPayment {
+        OutgoingPayment {
+            payment_hash: hex::encode([7u8; 32]),
+            preimage: is_paid.then(|| hex::encode([8u8; 32])),
+            is_paid,
+            sent: 1_000,
+            fees: 4_000,
+            completed_at,
+        }
+    }
+
+    #[test]
+    fn outgoing_payments_map_to_melt_states() {
+        assert_eq!(
+            phoenixd_to_melt_status(&outgoing_payment(true, Some(1))),
+            MeltQuoteState::Paid
+        );
+        assert_eq!(
+            phoenixd_to_melt_status(&outgoing_payment(false, Some(1))),
+            MeltQuoteState::Failed
+        );
+        assert_eq!(
+            phoenixd_to_melt_status(&outgoing_payment(false, None)),
+            MeltQuoteState::Pending
+        );
+    }
+
+    #[test]
+    fn outgoing_payment_total_spent_includes_msat_fees() {
+        let response = Phoenixd::outgoing_payment_response(
+            PaymentIdentifier::PaymentHash([7u8; 32]),
+            Some(outgoing_payment(true, Some(1))),
+        )
+        .expect("payment response should be built");
+        assert_eq!(
+            response
+                .total_spent
+                .convert_to(&CurrencyUnit::Msat)
+                .expect("msat amount should convert to msat")
+                .value(),
+            1_004_000
+        );
+
+        let response =
+            Phoenixd::outgoing_payment_response(PaymentIdentifier::PaymentHash([7u8; 32]), None)
+                .expect("unknown payment response should be built");
+        assert_eq!(response.status, MeltQuoteState::Unknown);
+    }
+
+    #[test]
+    fn phoenixd_fee_is_rounded_up_to_whole_sats() {
+        assert_eq!(Phoenixd::max_phoenixd_fee_msat(0), 4_000);
+        assert_eq!(Phoenixd::max_phoenixd_fee_msat(1_000_000), 8_000);
+        assert_eq!(Phoenixd::max_phoenixd_fee_msat(1_001_000), 9_000);
+    }
+
+    #[test]
+    fn amountless_amounts_must_be_whole_sats() {
+        assert_eq!(
+            Phoenixd::amountless_amount_sat(21_000).expect("whole sats are payable"),
+            21
+        );
+        assert!(matches!(
+            Phoenixd::amountless_amount_sat(21_500),
+            Err(Error::FractionalSatAmount(21_500))
+        ));
+    }
+
+    #[test]
+    fn received_payments_are_read_from_notifications() {
+        let payment_hash = hex::encode([9u8; 32]);
+        let response = Phoenixd::process_message(&format!(
+            r#"{{"type":"payment_received","amountSat":21,"paymentHash":"{payment_hash}"}}"#
+        ))
+        .expect("payment notification should yield a payment");
+        assert_eq!(
+            response.payment_identifier,
+            PaymentIdentifier::PaymentHash([9u8; 32])
+        );
+        assert_eq!(response.payment_amount, Amount::new(21, CurrencyUnit::Sat));
+
+        assert!(Phoenixd::process_message("not json").is_none());
+    }
+}
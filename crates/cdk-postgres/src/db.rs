@@ -7,10 +7,20 @@ use tokio_postgres::{Client, Error as PgError};
 
 use crate::value::PgValue;
 
+/// Server errors after which the statement had no effect and can be run again
+const TRANSIENT_STATES: &[SqlState] = &[
+    SqlState::T_R_SERIALIZATION_FAILURE,
+    SqlState::ADMIN_SHUTDOWN,
+    SqlState::CRASH_SHUTDOWN,
+    SqlState::CANNOT_CONNECT_NOW,
+    SqlState::CONNECTION_EXCEPTION,
+    SqlState::CONNECTION_FAILURE,
+];
+
 #[inline(always)]
 fn to_pgsql_error(err: PgError) -> Error {
-    if let Some(err) = err.as_db_error() {
-        let code = err.code().to_owned();
+    if let Some(db_err) = err.as_db_error() {
+        let code = db_err.code().to_owned();
         if code == SqlState::INTEGRITY_CONSTRAINT_VIOLATION || code == SqlState::UNIQUE_VIOLATION {
             return Error::Duplicate;
         }
@@ -18,6 +28,15 @@ fn to_pgsql_error(err: PgError) -> Error {
         if code == SqlState::T_R_DEADLOCK_DETECTED {
             return Error::Locked;
         }
+
+        if TRANSIENT_STATES.contains(&code) {
+            return Error::Transient(Box::new(err));
+        }
+    } else if err.is_closed()
+        || std::error::Error::source(&err).is_some_and(|source| source.is::<std::io::Error>())
+    {
+        // The connection was lost, a new one may succeed
+        return Error::Transient(Box::new(err));
     }
 
     Error::Database(Box::new(err))
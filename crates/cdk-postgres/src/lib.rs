@@ -176,7 +176,24 @@ impl PgConfig {
         }
     }
 
+    /// Set the schema all the tables live in, overriding any `schema=` in the connection string
+    ///
+    /// The schema is created if it does not exist, and every connection of the pool has its
+    /// `search_path` pinned to it, so several cdk services can share one database.
+    pub fn with_schema(mut self, schema: impl Into<String>) -> Self {
+        self.schema = Some(schema.into()).filter(|schema| !schema.is_empty());
+        self
+    }
+
+    /// Schema configured for this database, if any
+    pub fn schema(&self) -> Option<&str> {
+        self.schema.as_deref()
+    }
+
     /// strip schema from the connection string
+    ///
+    /// Both the key/value form (`host=localhost schema=mint`) and the URL form
+    /// (`postgresql://localhost/cdk?schema=mint`) are supported.
     fn strip_schema(input: &str) -> (Option<String>, String) {
         let mut schema: Option<String> = None;
 
@@ -185,16 +202,38 @@ impl PgConfig {
         for token in input.split_whitespace() {
             if let Some(rest) = token.strip_prefix("schema=") {
                 schema = Some(rest.to_string());
+            } else if let Some((url, query)) = token.split_once('?').filter(|(url, _)| {
+                url.starts_with("postgres://") || url.starts_with("postgresql://")
+            }) {
+                let mut params = Vec::new();
+                for param in query.split('&') {
+                    if let Some(rest) = param.strip_prefix("schema=") {
+                        schema = Some(rest.to_string());
+                    } else if !param.is_empty() {
+                        params.push(param);
+                    }
+                }
+
+                if params.is_empty() {
+                    parts.push(url.to_owned());
+                } else {
+                    parts.push(format!("{url}?{}", params.join("&")));
+                }
             } else {
-                parts.push(token);
+                parts.push(token.to_owned());
             }
         }
 
         let cleaned = parts.join(" ");
-        (schema, cleaned)
+        (schema.filter(|schema| !schema.is_empty()), cleaned)
     }
 }
 
+/// Quote a Postgres identifier, escaping embedded double quotes
+fn quote_identifier(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
 impl From<&str> for PgConfig {
     fn from(conn_str: &str) -> Self {
         let (schema, conn_str) = Self::strip_schema(conn_str);
@@ -246,10 +285,11 @@ impl PostgresConnection {
         let notify_clone = notify.clone();
 
         async fn select_schema(conn: &Client, schema: &str) -> Result<(), Error> {
+            let schema = quote_identifier(schema);
             conn.batch_execute(&format!(
                 r#"
-                    CREATE SCHEMA IF NOT EXISTS "{schema}";
-                    SET search_path TO "{schema}"
+                    CREATE SCHEMA IF NOT EXISTS {schema};
+                    SET search_path TO {schema}
                     "#
             ))
             .await
@@ -262,8 +302,9 @@ impl PostgresConnection {
                     let (client, connection) = match connect(&config.url, tls).await {
                         Ok((client, connection)) => (client, connection),
                         Err(err) => {
+                            // The server may be restarting or failing over
                             *error_clone.lock().await =
-                                Some(cdk_common::database::Error::Database(Box::new(err)));
+                                Some(cdk_common::database::Error::Transient(Box::new(err)));
                             stale.store(true, std::sync::atomic::Ordering::Release);
                             notify_clone.notify_waiters();
                             return;
@@ -292,8 +333,9 @@ impl PostgresConnection {
                     let (client, connection) = match connect(&config.url, tls).await {
                         Ok((client, connection)) => (client, connection),
                         Err(err) => {
+                            // The server may be restarting or failing over
                             *error_clone.lock().await =
-                                Some(cdk_common::database::Error::Database(Box::new(err)));
+                                Some(cdk_common::database::Error::Transient(Box::new(err)));
                             stale.store(true, std::sync::atomic::Ordering::Release);
                             notify_clone.notify_waiters();
                             return;
@@ -462,6 +504,37 @@ mod test {
         );
     }
 
+    #[test]
+    fn strip_schema_from_connection_string() {
+        let config = PgConfig::from("host=localhost user=u dbname=d schema=mint");
+        assert_eq!(config.schema(), Some("mint"));
+        assert_eq!(config.url, "host=localhost user=u dbname=d");
+
+        let config = PgConfig::from("postgresql://u:p@localhost:5432/d?schema=wallet");
+        assert_eq!(config.schema(), Some("wallet"));
+        assert_eq!(config.url, "postgresql://u:p@localhost:5432/d");
+
+        let config =
+            PgConfig::from("postgresql://u:p@localhost/d?sslmode=disable&schema=wallet&a=b");
+        assert_eq!(config.schema(), Some("wallet"));
+        assert_eq!(
+            config.url,
+            "postgresql://u:p@localhost/d?sslmode=disable&a=b"
+        );
+
+        let config = PgConfig::from("host=localhost dbname=d").with_schema("mint_a");
+        assert_eq!(config.schema(), Some("mint_a"));
+
+        let config = PgConfig::from("host=localhost dbname=d schema=mint").with_schema("");
+        assert_eq!(config.schema(), None);
+    }
+
+    #[test]
+    fn quote_identifier_escapes_quotes() {
+        assert_eq!(quote_identifier("mint"), "\"mint\"");
+        assert_eq!(quote_identifier("a\"b"), "\"a\"\"b\"");
+    }
+
     #[test]
     fn pgconfig_debug_does_not_leak_password() {
         let config = PgConfig::from("host=localhost user=u password=hunter2secret dbname=d");
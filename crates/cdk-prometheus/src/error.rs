@@ -13,6 +13,10 @@ pub enum PrometheusError {
         source: std::io::Error,
     },
 
+    /// Mutual TLS configuration error
+    #[error("TLS configuration error: {0}")]
+    Tls(String),
+
     /// Metrics collection error
     #[error("Failed to collect metrics: {0}")]
     MetricsCollection(String),
@@ -1,7 +1,9 @@
 use std::sync::Arc;
 use std::time::Instant;
 
-use prometheus::{HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Registry};
+use prometheus::{
+    GaugeVec, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Registry,
+};
 
 /// Global metrics instance
 pub static METRICS: std::sync::LazyLock<CdkMetrics> = std::sync::LazyLock::new(CdkMetrics::default);
@@ -67,6 +69,10 @@ pub struct CdkMetrics {
     payments_total: IntCounterVec,
     payment_amount: HistogramVec,
     payment_fees: HistogramVec,
+    payment_backend_connected: IntGaugeVec,
+    payment_backend_last_invoice_seen: IntGaugeVec,
+    payment_backend_payment_latency: GaugeVec,
+    payment_backend_error_streak: IntGaugeVec,
 
     // Database metrics
     db_operations_total: IntCounter,
@@ -80,6 +86,21 @@ pub struct CdkMetrics {
     mint_operations_total: IntCounterVec,
     mint_in_flight_requests: IntGaugeVec,
     mint_operation_duration: HistogramVec,
+    mint_quotes_expired_total: IntCounterVec,
+    mint_internal_settlements_total: IntCounterVec,
+    mint_internal_settlement_amount_total: IntCounterVec,
+
+    // Signatory metrics
+    signatory_requests_total: IntCounterVec,
+    signatory_failovers_total: IntCounterVec,
+    signatory_healthy: IntGaugeVec,
+    signatory_queue_full_total: IntCounterVec,
+
+    // Signer metrics, recorded by the signatory holding the keys
+    signer_blind_signatures_total: IntCounterVec,
+    signer_rejections_total: IntCounterVec,
+    signer_verify_failures_total: IntCounter,
+    signer_rpc_duration: HistogramVec,
 }
 
 impl CdkMetrics {
@@ -100,6 +121,14 @@ impl CdkMetrics {
         let (payments_total, payment_amount, payment_fees) =
             Self::create_payment_metrics(&registry)?;
 
+        // Create and register payment backend metrics
+        let (
+            payment_backend_connected,
+            payment_backend_last_invoice_seen,
+            payment_backend_payment_latency,
+            payment_backend_error_streak,
+        ) = Self::create_payment_backend_metrics(&registry)?;
+
         // Create and register database metrics
         let (db_operations_total, db_operation_duration, db_connections_active) =
             Self::create_db_metrics(&registry)?;
@@ -110,6 +139,27 @@ impl CdkMetrics {
         // Create and register mint metrics
         let (mint_operations_total, mint_operation_duration, mint_in_flight_requests) =
             Self::create_mint_metrics(&registry)?;
+        let mint_quotes_expired_total = Self::create_quote_expiry_metrics(&registry)?;
+
+        // Create and register internal settlement metrics
+        let (mint_internal_settlements_total, mint_internal_settlement_amount_total) =
+            Self::create_internal_settlement_metrics(&registry)?;
+
+        // Create and register signatory metrics
+        let (
+            signatory_requests_total,
+            signatory_failovers_total,
+            signatory_healthy,
+            signatory_queue_full_total,
+        ) = Self::create_signatory_metrics(&registry)?;
+
+        // Create and register signer metrics
+        let (
+            signer_blind_signatures_total,
+            signer_rejections_total,
+            signer_verify_failures_total,
+            signer_rpc_duration,
+        ) = Self::create_signer_metrics(&registry)?;
 
         Ok(Self {
             registry,
@@ -120,6 +170,10 @@ impl CdkMetrics {
             payments_total,
             payment_amount,
             payment_fees,
+            payment_backend_connected,
+            payment_backend_last_invoice_seen,
+            payment_backend_payment_latency,
+            payment_backend_error_streak,
             db_operations_total,
             db_operation_duration,
             db_connections_active,
@@ -127,6 +181,17 @@ impl CdkMetrics {
             mint_operations_total,
             mint_in_flight_requests,
             mint_operation_duration,
+            mint_quotes_expired_total,
+            mint_internal_settlements_total,
+            mint_internal_settlement_amount_total,
+            signatory_requests_total,
+            signatory_failovers_total,
+            signatory_healthy,
+            signatory_queue_full_total,
+            signer_blind_signatures_total,
+            signer_rejections_total,
+            signer_verify_failures_total,
+            signer_rpc_duration,
         })
     }
 
@@ -310,6 +375,209 @@ impl CdkMetrics {
         ))
     }
 
+    /// Create and register the metrics of the quote expiry task
+    ///
+    /// # Errors
+    /// Returns an error if any of the metrics cannot be created or registered
+    fn create_quote_expiry_metrics(registry: &Registry) -> crate::Result<IntCounterVec> {
+        let mint_quotes_expired_total = IntCounterVec::new(
+            prometheus::Opts::new(
+                "cdk_mint_quotes_expired_total",
+                "Total number of expired quotes marked or purged, by quote kind and action",
+            ),
+            &["kind", "action"],
+        )?;
+        registry.register(Box::new(mint_quotes_expired_total.clone()))?;
+
+        Ok(mint_quotes_expired_total)
+    }
+
+    /// Create and register payment backend metrics
+    ///
+    /// # Errors
+    /// Returns an error if any of the metrics cannot be created or registered
+    fn create_payment_backend_metrics(
+        registry: &Registry,
+    ) -> crate::Result<(IntGaugeVec, IntGaugeVec, GaugeVec, IntGaugeVec)> {
+        let labels = &["unit", "method", "backend"];
+
+        let payment_backend_connected = IntGaugeVec::new(
+            prometheus::Opts::new(
+                "cdk_payment_backend_connected",
+                "Whether the last check reached each payment backend (1) or not (0)",
+            ),
+            labels,
+        )?;
+        registry.register(Box::new(payment_backend_connected.clone()))?;
+
+        let payment_backend_last_invoice_seen = IntGaugeVec::new(
+            prometheus::Opts::new(
+                "cdk_payment_backend_last_invoice_seen_timestamp_seconds",
+                "Unix time each payment backend last reported an incoming payment",
+            ),
+            labels,
+        )?;
+        registry.register(Box::new(payment_backend_last_invoice_seen.clone()))?;
+
+        let payment_backend_payment_latency = GaugeVec::new(
+            prometheus::Opts::new(
+                "cdk_payment_backend_last_payment_latency_seconds",
+                "Duration of the last outgoing payment of each payment backend in seconds",
+            ),
+            labels,
+        )?;
+        registry.register(Box::new(payment_backend_payment_latency.clone()))?;
+
+        let payment_backend_error_streak = IntGaugeVec::new(
+            prometheus::Opts::new(
+                "cdk_payment_backend_error_streak",
+                "Checks and payments of each payment backend that failed in a row",
+            ),
+            labels,
+        )?;
+        registry.register(Box::new(payment_backend_error_streak.clone()))?;
+
+        Ok((
+            payment_backend_connected,
+            payment_backend_last_invoice_seen,
+            payment_backend_payment_latency,
+            payment_backend_error_streak,
+        ))
+    }
+
+    /// Create and register internal settlement metrics
+    ///
+    /// # Errors
+    /// Returns an error if any of the metrics cannot be created or registered
+    fn create_internal_settlement_metrics(
+        registry: &Registry,
+    ) -> crate::Result<(IntCounterVec, IntCounterVec)> {
+        let mint_internal_settlements_total = IntCounterVec::new(
+            prometheus::Opts::new(
+                "cdk_mint_internal_settlements_total",
+                "Total number of melts paid by a mint quote of the same mint, by unit",
+            ),
+            &["unit"],
+        )?;
+        registry.register(Box::new(mint_internal_settlements_total.clone()))?;
+
+        let mint_internal_settlement_amount_total = IntCounterVec::new(
+            prometheus::Opts::new(
+                "cdk_mint_internal_settlement_amount_total",
+                "Total amount of the melts paid by a mint quote of the same mint, by unit",
+            ),
+            &["unit"],
+        )?;
+        registry.register(Box::new(mint_internal_settlement_amount_total.clone()))?;
+
+        Ok((
+            mint_internal_settlements_total,
+            mint_internal_settlement_amount_total,
+        ))
+    }
+
+    /// Create and register signatory metrics
+    ///
+    /// # Errors
+    /// Returns an error if any of the metrics cannot be created or registered
+    fn create_signatory_metrics(
+        registry: &Registry,
+    ) -> crate::Result<(IntCounterVec, IntCounterVec, IntGaugeVec, IntCounterVec)> {
+        let signatory_requests_total = IntCounterVec::new(
+            prometheus::Opts::new(
+                "cdk_signatory_requests_total",
+                "Total number of requests to each signatory",
+            ),
+            &["signatory", "operation", "status"],
+        )?;
+        registry.register(Box::new(signatory_requests_total.clone()))?;
+
+        let signatory_failovers_total = IntCounterVec::new(
+            prometheus::Opts::new(
+                "cdk_signatory_failovers_total",
+                "Total number of requests that failed over from each signatory",
+            ),
+            &["signatory"],
+        )?;
+        registry.register(Box::new(signatory_failovers_total.clone()))?;
+
+        let signatory_healthy = IntGaugeVec::new(
+            prometheus::Opts::new(
+                "cdk_signatory_healthy",
+                "Whether each signatory is considered healthy (1) or not (0)",
+            ),
+            &["signatory"],
+        )?;
+        registry.register(Box::new(signatory_healthy.clone()))?;
+
+        let signatory_queue_full_total = IntCounterVec::new(
+            prometheus::Opts::new(
+                "cdk_signatory_queue_full_total",
+                "Total number of requests finding the signatory queue full, by outcome",
+            ),
+            &["outcome"],
+        )?;
+        registry.register(Box::new(signatory_queue_full_total.clone()))?;
+
+        Ok((
+            signatory_requests_total,
+            signatory_failovers_total,
+            signatory_healthy,
+            signatory_queue_full_total,
+        ))
+    }
+
+    /// Create and register signer metrics
+    ///
+    /// # Errors
+    /// Returns an error if any of the metrics cannot be created or registered
+    fn create_signer_metrics(
+        registry: &Registry,
+    ) -> crate::Result<(IntCounterVec, IntCounterVec, IntCounter, HistogramVec)> {
+        let signer_blind_signatures_total = IntCounterVec::new(
+            prometheus::Opts::new(
+                "cdk_signer_blind_signatures_total",
+                "Total number of blind signatures issued with each keyset",
+            ),
+            &["keyset"],
+        )?;
+        registry.register(Box::new(signer_blind_signatures_total.clone()))?;
+
+        let signer_rejections_total = IntCounterVec::new(
+            prometheus::Opts::new(
+                "cdk_signer_rejections_total",
+                "Total number of blind sign requests refused, by reason",
+            ),
+            &["reason"],
+        )?;
+        registry.register(Box::new(signer_rejections_total.clone()))?;
+
+        let signer_verify_failures_total = IntCounter::new(
+            "cdk_signer_verify_failures_total",
+            "Total number of proof verifications that failed",
+        )?;
+        registry.register(Box::new(signer_verify_failures_total.clone()))?;
+
+        let signer_rpc_duration = HistogramVec::new(
+            prometheus::HistogramOpts::new(
+                "cdk_signer_rpc_duration_seconds",
+                "Duration of signatory RPC calls in seconds",
+            )
+            .buckets(vec![
+                0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0,
+            ]),
+            &["method", "status"],
+        )?;
+        registry.register(Box::new(signer_rpc_duration.clone()))?;
+
+        Ok((
+            signer_blind_signatures_total,
+            signer_rejections_total,
+            signer_verify_failures_total,
+            signer_rpc_duration,
+        ))
+    }
+
     /// Get the metrics registry
     #[must_use]
     pub fn registry(&self) -> Arc<Registry> {
@@ -422,6 +690,136 @@ impl CdkMetrics {
             .with_label_values(&[operation])
             .dec();
     }
+
+    /// Record `count` expired quotes of `kind` (`mint` or `melt`) handled with `action`
+    /// (`expired` or `purged`)
+    pub fn record_quotes_expired(&self, kind: &str, action: &str, count: u64) {
+        self.mint_quotes_expired_total
+            .with_label_values(&[kind, action])
+            .inc_by(count);
+    }
+
+    /// Record a melt of `amount` in `unit` paid by a mint quote of the same mint
+    pub fn record_internal_settlement(&self, unit: &str, amount: u64) {
+        self.mint_internal_settlements_total
+            .with_label_values(&[unit])
+            .inc();
+        self.mint_internal_settlement_amount_total
+            .with_label_values(&[unit])
+            .inc_by(amount);
+    }
+
+    // Payment backend metrics methods
+    /// Set whether the last check reached a payment backend
+    pub fn set_payment_backend_connected(
+        &self,
+        unit: &str,
+        method: &str,
+        backend: &str,
+        connected: bool,
+    ) {
+        self.payment_backend_connected
+            .with_label_values(&[unit, method, backend])
+            .set(i64::from(connected));
+    }
+
+    /// Set the unix time a payment backend last reported an incoming payment
+    pub fn set_payment_backend_last_invoice_seen(
+        &self,
+        unit: &str,
+        method: &str,
+        backend: &str,
+        timestamp: u64,
+    ) {
+        self.payment_backend_last_invoice_seen
+            .with_label_values(&[unit, method, backend])
+            .set(i64::try_from(timestamp).unwrap_or(i64::MAX));
+    }
+
+    /// Set the duration of the last outgoing payment of a payment backend
+    pub fn set_payment_backend_payment_latency(
+        &self,
+        unit: &str,
+        method: &str,
+        backend: &str,
+        duration_seconds: f64,
+    ) {
+        self.payment_backend_payment_latency
+            .with_label_values(&[unit, method, backend])
+            .set(duration_seconds);
+    }
+
+    /// Set how many checks and payments of a payment backend failed in a row
+    pub fn set_payment_backend_error_streak(
+        &self,
+        unit: &str,
+        method: &str,
+        backend: &str,
+        streak: u32,
+    ) {
+        self.payment_backend_error_streak
+            .with_label_values(&[unit, method, backend])
+            .set(i64::from(streak));
+    }
+
+    // Signatory metrics methods
+    /// Record a request to a signatory
+    pub fn record_signatory_request(&self, signatory: &str, operation: &str, success: bool) {
+        let status = if success { "success" } else { "error" };
+        self.signatory_requests_total
+            .with_label_values(&[signatory, operation, status])
+            .inc();
+    }
+
+    /// Record a request failing over from a signatory to the next one
+    pub fn record_signatory_failover(&self, signatory: &str) {
+        self.signatory_failovers_total
+            .with_label_values(&[signatory])
+            .inc();
+    }
+
+    /// Set whether a signatory is considered healthy
+    pub fn set_signatory_healthy(&self, signatory: &str, healthy: bool) {
+        self.signatory_healthy
+            .with_label_values(&[signatory])
+            .set(i64::from(healthy));
+    }
+
+    /// Record a request finding the signatory queue full, `waited` if room was made in time
+    /// and `shed` if it was refused as overloaded
+    pub fn record_signatory_queue_full(&self, outcome: &str) {
+        self.signatory_queue_full_total
+            .with_label_values(&[outcome])
+            .inc();
+    }
+
+    // Signer metrics methods
+    /// Record `count` blind signatures issued with `keyset`
+    pub fn record_signer_blind_signatures(&self, keyset: &str, count: u64) {
+        self.signer_blind_signatures_total
+            .with_label_values(&[keyset])
+            .inc_by(count);
+    }
+
+    /// Record a blind sign request refused for `reason`
+    pub fn record_signer_rejection(&self, reason: &str) {
+        self.signer_rejections_total
+            .with_label_values(&[reason])
+            .inc();
+    }
+
+    /// Record a failed proof verification
+    pub fn record_signer_verify_failure(&self) {
+        self.signer_verify_failures_total.inc();
+    }
+
+    /// Record the duration of a signatory RPC call
+    pub fn record_signer_rpc(&self, method: &str, success: bool, duration_seconds: f64) {
+        let status = if success { "success" } else { "error" };
+        self.signer_rpc_duration
+            .with_label_values(&[method, status])
+            .observe(duration_seconds);
+    }
 }
 
 impl Default for CdkMetrics {
@@ -569,4 +967,44 @@ mod tests {
         assert_eq!(amount.get_sample_count(), amount_count_before + 1);
         assert_eq!(fee.get_sample_count(), fee_count_before + 1);
     }
+
+    #[test]
+    fn signer_metrics_are_labeled_by_keyset_and_reason() {
+        let _lock = metrics_lock();
+        let keyset = "test_signer_keyset";
+        let reason = "test_signer_reason";
+        let signatures = METRICS
+            .signer_blind_signatures_total
+            .with_label_values(&[keyset]);
+        let rejections = METRICS.signer_rejections_total.with_label_values(&[reason]);
+
+        let signatures_before = signatures.get();
+        let rejections_before = rejections.get();
+        let verify_failures_before = METRICS.signer_verify_failures_total.get();
+
+        METRICS.record_signer_blind_signatures(keyset, 3);
+        METRICS.record_signer_rejection(reason);
+        METRICS.record_signer_verify_failure();
+
+        assert_eq!(signatures.get(), signatures_before + 3);
+        assert_eq!(rejections.get(), rejections_before + 1);
+        assert_eq!(
+            METRICS.signer_verify_failures_total.get(),
+            verify_failures_before + 1
+        );
+    }
+
+    #[test]
+    fn expired_quotes_are_labeled_by_kind_and_action() {
+        let _lock = metrics_lock();
+        let purged = METRICS
+            .mint_quotes_expired_total
+            .with_label_values(&["melt", "purged"]);
+        let purged_before = purged.get();
+
+        METRICS.record_quotes_expired("melt", "purged", 2);
+        METRICS.record_quotes_expired("melt", "purged", 0);
+
+        assert_eq!(purged.get(), purged_before + 2);
+    }
 }
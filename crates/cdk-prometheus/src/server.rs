@@ -1,12 +1,18 @@
+use std::fmt;
 use std::io;
 use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
 
 use prometheus::{Registry, TextEncoder};
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::net::{TcpListener, TcpStream};
+use rustls::server::WebPkiClientVerifier;
+use rustls::{RootCertStore, ServerConfig};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio_rustls::TlsAcceptor;
 
+use crate::error::PrometheusError;
 use crate::metrics::METRICS;
 #[cfg(feature = "system-metrics")]
 use crate::process::SystemMetrics;
@@ -17,6 +23,27 @@ const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
 
 type MetricsHandler = Arc<dyn Fn() -> String + Send + Sync + 'static>;
 
+/// Decides whether a scrape is allowed from its `Authorization` header value, if any
+#[derive(Clone)]
+pub struct ScrapeAuthorizer(Arc<dyn Fn(Option<&str>) -> bool + Send + Sync + 'static>);
+
+impl ScrapeAuthorizer {
+    /// Allow the scrapes for which `authorize` returns `true`
+    pub fn new(authorize: impl Fn(Option<&str>) -> bool + Send + Sync + 'static) -> Self {
+        Self(Arc::new(authorize))
+    }
+
+    fn is_authorized(&self, authorization: Option<&str>) -> bool {
+        (self.0)(authorization)
+    }
+}
+
+impl fmt::Debug for ScrapeAuthorizer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ScrapeAuthorizer").finish_non_exhaustive()
+    }
+}
+
 /// Configuration for the Prometheus server
 #[derive(Debug, Clone)]
 pub struct PrometheusConfig {
@@ -24,6 +51,12 @@ pub struct PrometheusConfig {
     pub bind_address: SocketAddr,
     /// Path to serve metrics on (default: "/metrics")
     pub metrics_path: String,
+    /// Check of the `Authorization` header of scrapes, anyone may scrape when unset (default:
+    /// none)
+    pub authorizer: Option<ScrapeAuthorizer>,
+    /// Directory holding `server.pem`, `server.key` and `ca.pem`. When set, metrics are served
+    /// over TLS to scrapers presenting a client certificate signed by `ca.pem` (default: none)
+    pub tls_dir: Option<PathBuf>,
     /// Whether to include system metrics (default: true if feature enabled)
     #[cfg(feature = "system-metrics")]
     pub include_system_metrics: bool,
@@ -37,6 +70,8 @@ impl Default for PrometheusConfig {
         Self {
             bind_address: "127.0.0.1:9090".parse().expect("Invalid default address"),
             metrics_path: "/metrics".to_string(),
+            authorizer: None,
+            tls_dir: None,
             #[cfg(feature = "system-metrics")]
             include_system_metrics: true,
             #[cfg(feature = "system-metrics")]
@@ -75,7 +110,61 @@ fn request_matches_path(request: &str, metrics_path: &str) -> bool {
     target_path == metrics_path
 }
 
-async fn read_request(stream: &mut TcpStream) -> io::Result<Option<String>> {
+/// Value of the `Authorization` header of `request`, if any
+fn request_authorization(request: &str) -> Option<&str> {
+    request
+        .lines()
+        .skip(1)
+        .take_while(|line| !line.trim().is_empty())
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("authorization"))
+        .map(|(_, value)| value.trim())
+}
+
+fn tls_error(err: impl std::fmt::Display) -> PrometheusError {
+    PrometheusError::Tls(err.to_string())
+}
+
+/// Acceptor requiring scrapers to present a client certificate signed by the CA of `tls_dir`
+fn tls_acceptor(tls_dir: &Path) -> crate::Result<TlsAcceptor> {
+    let read_pem = |name: &str| {
+        let path = tls_dir.join(name);
+        std::fs::read(&path)
+            .map_err(|err| PrometheusError::Tls(format!("{}: {err}", path.display())))
+    };
+
+    let certs = rustls_pemfile::certs(&mut read_pem("server.pem")?.as_slice())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(tls_error)?;
+    let key = rustls_pemfile::private_key(&mut read_pem("server.key")?.as_slice())
+        .map_err(tls_error)?
+        .ok_or_else(|| PrometheusError::Tls("server.key holds no private key".to_string()))?;
+
+    let mut client_ca = RootCertStore::empty();
+    for cert in rustls_pemfile::certs(&mut read_pem("ca.pem")?.as_slice()) {
+        client_ca.add(cert.map_err(tls_error)?).map_err(tls_error)?;
+    }
+
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let verifier =
+        WebPkiClientVerifier::builder_with_provider(Arc::new(client_ca), Arc::clone(&provider))
+            .build()
+            .map_err(tls_error)?;
+
+    let config = ServerConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .map_err(tls_error)?
+        .with_client_cert_verifier(verifier)
+        .with_single_cert(certs, key)
+        .map_err(tls_error)?;
+
+    Ok(TlsAcceptor::from(Arc::new(config)))
+}
+
+async fn read_request<S>(stream: &mut S, read_headers: bool) -> io::Result<Option<String>>
+where
+    S: AsyncRead + Unpin,
+{
     let mut request = Vec::with_capacity(1024);
     let mut buffer = [0_u8; 1024];
 
@@ -93,10 +182,15 @@ async fn read_request(stream: &mut TcpStream) -> io::Result<Option<String>> {
 
         request.extend_from_slice(&buffer[..bytes_read]);
 
-        if request.windows(2).any(|window| window == b"\r\n")
-            || request.contains(&b'\n')
-            || request.len() >= MAX_REQUEST_BYTES
-        {
+        // The headers are only needed to check the bearer token
+        let complete = if read_headers {
+            request.windows(4).any(|window| window == b"\r\n\r\n")
+                || request.windows(2).any(|window| window == b"\n\n")
+        } else {
+            request.contains(&b'\n')
+        };
+
+        if complete || request.len() >= MAX_REQUEST_BYTES {
             break;
         }
     }
@@ -104,12 +198,15 @@ async fn read_request(stream: &mut TcpStream) -> io::Result<Option<String>> {
     Ok(Some(String::from_utf8_lossy(&request).to_string()))
 }
 
-async fn write_response(
-    stream: &mut TcpStream,
+async fn write_response<S>(
+    stream: &mut S,
     status: &str,
     content_type: &str,
     body: &str,
-) -> io::Result<()> {
+) -> io::Result<()>
+where
+    S: AsyncWrite + Unpin,
+{
     let response = format!(
         "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
         body.len(),
@@ -119,19 +216,39 @@ async fn write_response(
         .await
         .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "timed out writing response"))??;
 
+    // Lets TLS scrapers see the close_notify instead of a truncated stream
+    let _ = stream.shutdown().await;
+
     Ok(())
 }
 
-async fn handle_connection(
-    mut stream: TcpStream,
+async fn handle_connection<S>(
+    mut stream: S,
     metrics_path: String,
+    authorizer: Option<ScrapeAuthorizer>,
     metrics_handler: MetricsHandler,
-) -> io::Result<()> {
-    let Some(request) = read_request(&mut stream).await? else {
+) -> io::Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let Some(request) = read_request(&mut stream, authorizer.is_some()).await? else {
         return Ok(());
     };
 
-    if request_matches_path(&request, &metrics_path) {
+    if !request_matches_path(&request, &metrics_path) {
+        write_response(&mut stream, "404 Not Found", "text/plain", "Not Found").await
+    } else if authorizer
+        .as_ref()
+        .is_some_and(|authorizer| !authorizer.is_authorized(request_authorization(&request)))
+    {
+        write_response(
+            &mut stream,
+            "401 Unauthorized",
+            "text/plain",
+            "Unauthorized",
+        )
+        .await
+    } else {
         let metrics = metrics_handler();
         write_response(
             &mut stream,
@@ -140,8 +257,6 @@ async fn handle_connection(
             &metrics,
         )
         .await
-    } else {
-        write_response(&mut stream, "404 Not Found", "text/plain", "Not Found").await
     }
 }
 
@@ -228,6 +343,13 @@ impl PrometheusServer {
         let binding = self.config.bind_address;
         let registry_clone = Arc::<Registry>::clone(&self.registry);
         let path = self.config.metrics_path.clone();
+        let authorizer = self.config.authorizer.clone();
+        let tls_acceptor = self
+            .config
+            .tls_dir
+            .as_deref()
+            .map(tls_acceptor)
+            .transpose()?;
 
         #[cfg(feature = "system-metrics")]
         let metrics_handler =
@@ -243,7 +365,16 @@ impl PrometheusServer {
             }
         })?;
 
-        tracing::info!("Started Prometheus server on {} at path {}", binding, path);
+        tracing::info!(
+            "Started Prometheus server on {} at path {}{}",
+            binding,
+            path,
+            if tls_acceptor.is_some() {
+                " with mutual TLS"
+            } else {
+                ""
+            }
+        );
 
         tokio::pin!(shutdown_signal);
 
@@ -257,12 +388,47 @@ impl PrometheusServer {
                     match accept_result {
                         Ok((stream, _peer_addr)) => {
                             let metrics_path = path.clone();
+                            let authorizer = authorizer.clone();
                             let metrics_handler = Arc::clone(&metrics_handler);
+                            let tls_acceptor = tls_acceptor.clone();
 
                             tokio::spawn(async move {
-                                if let Err(e) =
-                                    handle_connection(stream, metrics_path, metrics_handler).await
-                                {
+                                let result = match tls_acceptor {
+                                    Some(tls_acceptor) => {
+                                        match tokio::time::timeout(
+                                            READ_TIMEOUT,
+                                            tls_acceptor.accept(stream),
+                                        )
+                                        .await
+                                        {
+                                            Ok(Ok(stream)) => {
+                                                handle_connection(
+                                                    stream,
+                                                    metrics_path,
+                                                    authorizer,
+                                                    metrics_handler,
+                                                )
+                                                .await
+                                            }
+                                            Ok(Err(e)) => Err(e),
+                                            Err(_) => Err(io::Error::new(
+                                                io::ErrorKind::TimedOut,
+                                                "timed out during TLS handshake",
+                                            )),
+                                        }
+                                    }
+                                    None => {
+                                        handle_connection(
+                                            stream,
+                                            metrics_path,
+                                            authorizer,
+                                            metrics_handler,
+                                        )
+                                        .await
+                                    }
+                                };
+
+                                if let Err(e) = result {
                                     tracing::warn!("Failed to serve Prometheus scrape: {e}");
                                 }
                             });
@@ -284,7 +450,7 @@ impl PrometheusServer {
 
 #[cfg(test)]
 mod tests {
-    use super::request_matches_path;
+    use super::{request_authorization, request_matches_path, tls_acceptor};
 
     #[test]
     fn request_matching_requires_exact_request_target() {
@@ -305,6 +471,34 @@ mod tests {
             "/metrics"
         ));
     }
+
+    #[test]
+    fn request_authorization_is_read_from_headers() {
+        assert_eq!(request_authorization("GET /metrics HTTP/1.1\r\n\r\n"), None);
+        assert_eq!(
+            request_authorization("GET /metrics HTTP/1.1\r\nauthorization: Bearer scraper\r\n\r\n"),
+            Some("Bearer scraper")
+        );
+        assert_eq!(
+            request_authorization("GET /metrics HTTP/1.1\r\nAuthorization:  Bearer other \r\n\r\n"),
+            Some("Bearer other")
+        );
+        assert_eq!(
+            request_authorization("GET /metrics HTTP/1.1\r\n\r\nAuthorization: Bearer scraper"),
+            None
+        );
+    }
+
+    #[test]
+    fn tls_dir_requires_certificates() {
+        let tls_dir =
+            std::env::temp_dir().join(format!("cdk_prometheus_tls_{}", std::process::id()));
+        std::fs::create_dir_all(&tls_dir).unwrap();
+
+        assert!(tls_acceptor(&tls_dir).is_err());
+
+        std::fs::remove_dir_all(&tls_dir).unwrap();
+    }
 }
 
 /// Builder for easy Prometheus server setup
@@ -336,6 +530,24 @@ impl PrometheusBuilder {
         self
     }
 
+    /// Only serve scrapes whose `Authorization` header value is accepted by `authorize`
+    #[must_use]
+    pub fn authorizer(
+        mut self,
+        authorize: impl Fn(Option<&str>) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.config.authorizer = Some(ScrapeAuthorizer::new(authorize));
+        self
+    }
+
+    /// Serve metrics over TLS to scrapers presenting a client certificate signed by the CA in
+    /// `tls_dir`, see [`PrometheusConfig::tls_dir`]
+    #[must_use]
+    pub fn tls_dir(mut self, tls_dir: PathBuf) -> Self {
+        self.config.tls_dir = Some(tls_dir);
+        self
+    }
+
     /// Enable or disable system metrics
     #[cfg(feature = "system-metrics")]
     #[must_use]
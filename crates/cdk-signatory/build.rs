@@ -4,12 +4,20 @@
 
 fn main() {
     println!("cargo:rerun-if-changed=src/proto/signatory.proto");
+    println!("cargo:rerun-if-changed=src/proto/threshold.proto");
 
     #[cfg(feature = "grpc")]
     tonic_prost_build::configure()
         .protoc_arg("--experimental_allow_proto3_optional")
+        .file_descriptor_set_path(
+            std::path::PathBuf::from(std::env::var("OUT_DIR").unwrap())
+                .join("signatory_descriptor.bin"),
+        )
         .type_attribute(".", "#[allow(missing_docs)]")
         .field_attribute(".", "#[allow(missing_docs)]")
-        .compile_protos(&["src/proto/signatory.proto"], &["src/proto"])
+        .compile_protos(
+            &["src/proto/signatory.proto", "src/proto/threshold.proto"],
+            &["src/proto"],
+        )
         .expect("valid proto");
 }
@@ -0,0 +1,304 @@
+//! Audit log of the signing operations
+//!
+//! [`AuditedSignatory`] wraps a signatory and appends a [`SigningAuditRecord`] to an
+//! [`AuditSink`] for every blinded message it signs. Operators running a remote signer keep their
+//! own evidence of what was signed, and for whom, independently of the mint.
+//!
+//! Signatures are only handed out once their records were appended, a failing sink fails the
+//! signing request.
+use std::sync::Arc;
+
+use bitcoin::hashes::{sha256, Hash};
+use cdk_common::database::{self, MintKeysDatabase, SigningAuditRecord};
+use cdk_common::keyset_export::SignedKeysetExport;
+use cdk_common::mint::MintKeySetInfo;
+use cdk_common::rotation_log::SignedRotationAttestation;
+use cdk_common::util::unix_time;
+use cdk_common::{BlindSignature, BlindedMessage, Error, Id, Proof};
+
+use crate::signatory::{
+    RotateKeyArguments, Signatory, SignatoryConfig, SignatoryKeySet, SignatoryKeysets,
+    SigningLimitUsage, UpdateKeysetConfigArguments,
+};
+
+/// Destination of the signing audit records
+#[async_trait::async_trait]
+pub trait AuditSink: Send + Sync {
+    /// Append `records` to the log
+    async fn append(&self, records: &[SigningAuditRecord]) -> Result<(), Error>;
+}
+
+/// Audit sink writing to the `signing_audit` table of the keys database
+#[allow(missing_debug_implementations)]
+pub struct DatabaseAuditSink {
+    localstore: Arc<dyn MintKeysDatabase<Err = database::Error> + Send + Sync>,
+}
+
+impl DatabaseAuditSink {
+    /// Sink appending to `localstore`
+    pub fn new(localstore: Arc<dyn MintKeysDatabase<Err = database::Error> + Send + Sync>) -> Self {
+        Self { localstore }
+    }
+}
+
+#[async_trait::async_trait]
+impl AuditSink for DatabaseAuditSink {
+    async fn append(&self, records: &[SigningAuditRecord]) -> Result<(), Error> {
+        let mut tx = self.localstore.begin_transaction().await?;
+        tx.add_signing_audit_records(records).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+/// Audit sink appending one tab separated line per record to a file
+///
+/// The columns are the unix time, keyset id, amount, blinded secret hash and caller, `-` when
+/// there is no caller. The file is synced to disk after every append.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug)]
+pub struct FileAuditSink {
+    file: tokio::sync::Mutex<tokio::fs::File>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl FileAuditSink {
+    /// Open `path` for appending, creating it if needed
+    pub async fn open(path: impl AsRef<std::path::Path>) -> Result<Self, Error> {
+        let file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path.as_ref())
+            .await
+            .map_err(|err| {
+                Error::Custom(format!(
+                    "Could not open audit log {}: {}",
+                    path.as_ref().display(),
+                    err
+                ))
+            })?;
+
+        Ok(Self {
+            file: tokio::sync::Mutex::new(file),
+        })
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[async_trait::async_trait]
+impl AuditSink for FileAuditSink {
+    async fn append(&self, records: &[SigningAuditRecord]) -> Result<(), Error> {
+        use tokio::io::AsyncWriteExt;
+
+        let lines: String = records
+            .iter()
+            .map(|record| {
+                format!(
+                    "{}\t{}\t{}\t{}\t{}\n",
+                    record.created_time,
+                    record.keyset_id,
+                    record.amount,
+                    record.blinded_secret_hash,
+                    record.caller.as_deref().unwrap_or("-")
+                )
+            })
+            .collect();
+
+        let mut file = self.file.lock().await;
+        file.write_all(lines.as_bytes())
+            .await
+            .map_err(|err| Error::Custom(format!("Could not write audit log: {err}")))?;
+        file.sync_data()
+            .await
+            .map_err(|err| Error::Custom(format!("Could not sync audit log: {err}")))?;
+
+        Ok(())
+    }
+}
+
+/// Signatory recording every blind signature of the signatory it wraps
+#[allow(missing_debug_implementations)]
+pub struct AuditedSignatory {
+    inner: Arc<dyn Signatory + Send + Sync>,
+    sink: Arc<dyn AuditSink>,
+}
+
+impl AuditedSignatory {
+    /// Record the signatures of `inner` to `sink`
+    pub fn new(inner: Arc<dyn Signatory + Send + Sync>, sink: Arc<dyn AuditSink>) -> Self {
+        Self { inner, sink }
+    }
+}
+
+#[async_trait::async_trait]
+impl Signatory for AuditedSignatory {
+    fn name(&self) -> String {
+        self.inner.name()
+    }
+
+    async fn blind_sign(
+        &self,
+        blinded_messages: Vec<BlindedMessage>,
+    ) -> Result<Vec<BlindSignature>, Error> {
+        self.blind_sign_as(blinded_messages, None).await
+    }
+
+    async fn blind_sign_as(
+        &self,
+        blinded_messages: Vec<BlindedMessage>,
+        caller: Option<String>,
+    ) -> Result<Vec<BlindSignature>, Error> {
+        let created_time = unix_time();
+        let records: Vec<_> = blinded_messages
+            .iter()
+            .map(|message| SigningAuditRecord {
+                created_time,
+                keyset_id: message.keyset_id,
+                amount: message.amount,
+                blinded_secret_hash: sha256::Hash::hash(&message.blinded_secret.to_bytes())
+                    .to_string(),
+                caller: caller.clone(),
+            })
+            .collect();
+
+        let signatures = self.inner.blind_sign(blinded_messages).await?;
+
+        if let Err(err) = self.sink.append(&records).await {
+            tracing::error!(
+                "Withholding {} blind signatures, could not append them to the audit log: {}",
+                signatures.len(),
+                err
+            );
+            return Err(err);
+        }
+
+        Ok(signatures)
+    }
+
+    async fn verify_proofs(&self, proofs: Vec<Proof>) -> Result<(), Error> {
+        self.inner.verify_proofs(proofs).await
+    }
+
+    async fn keysets(&self) -> Result<SignatoryKeysets, Error> {
+        self.inner.keysets().await
+    }
+
+    async fn keyset_pubkeys(&self, id: Id) -> Result<SignatoryKeySet, Error> {
+        self.inner.keyset_pubkeys(id).await
+    }
+
+    async fn keyset_info(&self, id: Id) -> Result<MintKeySetInfo, Error> {
+        self.inner.keyset_info(id).await
+    }
+
+    async fn supported_config(&self) -> Result<SignatoryConfig, Error> {
+        self.inner.supported_config().await
+    }
+
+    async fn signing_limits(&self) -> Result<Vec<SigningLimitUsage>, Error> {
+        self.inner.signing_limits().await
+    }
+
+    async fn rotation_log(&self) -> Result<Vec<SignedRotationAttestation>, Error> {
+        self.inner.rotation_log().await
+    }
+
+    async fn export_keysets(&self) -> Result<SignedKeysetExport, Error> {
+        self.inner.export_keysets().await
+    }
+
+    async fn rotate_keyset(&self, args: RotateKeyArguments) -> Result<SignatoryKeySet, Error> {
+        self.inner.rotate_keyset(args).await
+    }
+
+    async fn update_keyset_config(
+        &self,
+        args: UpdateKeysetConfigArguments,
+    ) -> Result<SignatoryKeySet, Error> {
+        self.inner.update_keyset_config(args).await
+    }
+
+    async fn disable_keyset(&self, id: Id) -> Result<SignatoryKeySet, Error> {
+        self.inner.disable_keyset(id).await
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod test {
+    use cdk_common::nut02::KeySetVersion;
+    use cdk_common::nuts::SecretKey;
+    use cdk_common::{Amount, CurrencyUnit};
+
+    use super::*;
+    use crate::db_signatory::DbSignatory;
+
+    #[tokio::test]
+    async fn blind_signatures_are_recorded() {
+        let store: Arc<dyn MintKeysDatabase<Err = database::Error> + Send + Sync> = Arc::new(
+            cdk_sqlite::mint::memory::empty()
+                .await
+                .expect("in-memory db"),
+        );
+        let inner = DbSignatory::new(
+            store.clone(),
+            b"test-seed-for-unit-tests",
+            Default::default(),
+            Default::default(),
+        )
+        .await
+        .expect("DbSignatory::new");
+
+        let signatory = AuditedSignatory::new(
+            Arc::new(inner),
+            Arc::new(DatabaseAuditSink::new(store.clone())),
+        );
+
+        let keyset = signatory
+            .rotate_keyset(RotateKeyArguments {
+                unit: CurrencyUnit::Sat,
+                amounts: vec![1, 2, 4, 8],
+                input_fee_ppk: 0,
+                keyset_id_type: KeySetVersion::Version01,
+                final_expiry: None,
+                reason: None,
+            })
+            .await
+            .expect("rotate_keyset");
+
+        let blinded_secret = SecretKey::generate().public_key();
+        signatory
+            .blind_sign_as(
+                vec![BlindedMessage::new(
+                    Amount::from(4),
+                    keyset.id,
+                    blinded_secret,
+                )],
+                Some("cert:client".to_owned()),
+            )
+            .await
+            .expect("blind_sign_as");
+        signatory
+            .blind_sign(vec![BlindedMessage::new(
+                Amount::from(1),
+                keyset.id,
+                SecretKey::generate().public_key(),
+            )])
+            .await
+            .expect("blind_sign");
+
+        let records = store
+            .get_signing_audit_records(None)
+            .await
+            .expect("audit records");
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].keyset_id, keyset.id);
+        assert_eq!(records[0].amount, Amount::from(4));
+        assert_eq!(
+            records[0].blinded_secret_hash,
+            sha256::Hash::hash(&blinded_secret.to_bytes()).to_string()
+        );
+        assert_eq!(records[0].caller.as_deref(), Some("cert:client"));
+        assert_eq!(records[1].amount, Amount::from(1));
+        assert_eq!(records[1].caller, None);
+    }
+}
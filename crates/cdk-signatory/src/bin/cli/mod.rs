@@ -11,14 +11,18 @@ use {
     bip39::rand::{thread_rng, Rng},
     bip39::Mnemonic,
     cdk_common::database::MintKeysDatabase,
-    cdk_common::CurrencyUnit,
-    cdk_signatory::{db_signatory, start_grpc_server},
+    cdk_common::{CurrencyUnit, Id},
+    cdk_signatory::audit::{AuditSink, AuditedSignatory, DatabaseAuditSink, FileAuditSink},
+    cdk_signatory::signatory::{Signatory, SigningLimit, SigningLimitScope},
+    cdk_signatory::threshold::{SignerNode, ThresholdConfig, ThresholdSignatory},
+    cdk_signatory::{db_signatory, start_grpc_server_with_shutdown, ApiKeys, SignerNodeRpcClient},
     cdk_sqlite::MintSqliteDatabase,
     std::collections::HashMap,
     std::net::SocketAddr,
     std::str::FromStr,
     std::sync::Arc,
     std::{env, fs},
+    tokio_util::sync::CancellationToken,
     tracing_subscriber::EnvFilter,
 };
 
@@ -63,6 +67,10 @@ pub fn init_logging(enable_logging: bool, log_level: tracing::Level) {
 const DEFAULT_WORK_DIR: &str = ".cdk-signatory";
 #[cfg(feature = "sqlite")]
 const ENV_MNEMONIC: &str = "CDK_MINTD_MNEMONIC";
+#[cfg(feature = "sqlite")]
+const ENV_MNEMONIC_PASSPHRASE: &str = "CDK_MINTD_MNEMONIC_PASSPHRASE";
+#[cfg(feature = "sqlite")]
+const ENV_THRESHOLD_API_KEY: &str = "CDK_SIGNATORY_THRESHOLD_API_KEY";
 
 /// Simple CLI application to interact with cashu
 #[derive(Parser)]
@@ -89,9 +97,55 @@ struct Cli {
     listen_port: u32,
     #[arg(long, short)]
     certs: Option<String>,
+    /// Listen on this unix domain socket instead of TCP, for a mint on the same host. Only the
+    /// owner of the signatory process can connect to it.
+    #[cfg(unix)]
+    #[arg(long, conflicts_with = "certs")]
+    listen_socket: Option<PathBuf>,
+    /// Network the keys are derived for (bitcoin, testnet, signet or regtest). The same seed
+    /// derives unrelated keys on every network
+    #[arg(long, default_value = "bitcoin")]
+    network: bitcoin::Network,
     /// Supported units with the format of name,fee and max_order
     #[arg(long, short, default_value = "sat,0,32")]
     units: Vec<String>,
+    /// Append a record of every blind signature to this file
+    #[arg(long, conflicts_with = "audit_db")]
+    audit_log: Option<PathBuf>,
+    /// Record every blind signature in the signatory database
+    #[arg(long, default_value_t = false)]
+    audit_db: bool,
+    /// Signing limits with the format of scope,window_secs and max_amount, the scope being a
+    /// keyset id or a unit
+    #[arg(long)]
+    signing_limit: Vec<String>,
+    /// File with one `<client> <key>` API key per line. Every call then needs one of the keys,
+    /// which identifies the client in the audit log. On unix the file is reloaded on SIGHUP, so
+    /// removing a line revokes its key.
+    #[arg(long)]
+    api_keys: Option<PathBuf>,
+    /// Coordinate threshold signer nodes instead of signing with the seed, with the coordinator
+    /// config written by `signer-node deal`. The units are the ones dealt.
+    #[arg(long, requires = "threshold_node")]
+    threshold_config: Option<PathBuf>,
+    /// URL of a threshold signer node, nodes are asked in the order given
+    #[arg(long)]
+    threshold_node: Vec<String>,
+    /// Directory with the `ca.pem`, `client.pem` and `client.key` used for mTLS with the nodes
+    #[arg(long)]
+    threshold_certs: Option<PathBuf>,
+    /// API key sent to the signer nodes, read from `CDK_SIGNATORY_THRESHOLD_API_KEY` when not
+    /// given
+    #[arg(long)]
+    threshold_api_key: Option<String>,
+    /// Serve Prometheus metrics (signing rate, latency, rejections) on this address
+    #[cfg(feature = "prometheus")]
+    #[arg(long)]
+    metrics_addr: Option<SocketAddr>,
+    /// Bearer token required to scrape the metrics, anyone may scrape when none is given
+    #[cfg(feature = "prometheus")]
+    #[arg(long, requires = "metrics_addr")]
+    metrics_token: Vec<String>,
 }
 
 /// Main function for the signatory standalone binary
@@ -156,12 +210,98 @@ pub async fn cli_main() -> Result<()> {
         })
         .collect::<Result<HashMap<_, _>, _>>()?;
 
+    let signing_limits = args
+        .signing_limit
+        .iter()
+        .map(|limit| {
+            let parts = limit.split(',').collect::<Vec<_>>();
+            let [scope, window_secs, max_amount] = parts[..] else {
+                bail!("Invalid signing limit {limit}, expected scope,window_secs,max_amount");
+            };
+            let scope = match Id::from_str(scope) {
+                Ok(id) => SigningLimitScope::Keyset(id),
+                Err(_) => SigningLimitScope::Unit(scope.parse()?),
+            };
+            Ok(SigningLimit {
+                scope,
+                window_secs: window_secs.parse()?,
+                max_amount: max_amount.parse()?,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
     let certs = Some(
         args.certs
             .map(|x| x.into())
             .unwrap_or_else(|| work_dir.clone()),
     );
 
+    let audit_sink: Option<Arc<dyn AuditSink>> = match (&args.audit_log, args.audit_db) {
+        (Some(path), _) => Some(Arc::new(FileAuditSink::open(path).await?)),
+        (None, true) => Some(Arc::new(DatabaseAuditSink::new(localstore.clone()))),
+        (None, false) => None,
+    };
+
+    let listen = Listen::Tcp(SocketAddr::from_str(&format!(
+        "{}:{}",
+        args.listen_addr, args.listen_port
+    ))?);
+    #[cfg(unix)]
+    let listen = args
+        .listen_socket
+        .clone()
+        .map(Listen::Unix)
+        .unwrap_or(listen);
+
+    let api_keys = match &args.api_keys {
+        Some(path) => {
+            let api_keys = ApiKeys::from_file(path)?;
+            #[cfg(unix)]
+            reload_api_keys_on_sighup(api_keys.clone(), path.clone())?;
+            api_keys
+        }
+        None => ApiKeys::disabled(),
+    };
+
+    #[cfg(feature = "prometheus")]
+    if let Some(metrics_addr) = args.metrics_addr {
+        let mut builder = cdk_prometheus::PrometheusBuilder::new().bind_address(metrics_addr);
+        if !args.metrics_token.is_empty() {
+            let tokens = cdk_common::bearer::BearerTokens::new(
+                args.metrics_token.iter().map(|token| (token.clone(), ())),
+            );
+            builder = builder.authorizer(move |authorization| tokens.role(authorization).is_ok());
+        }
+        let server = builder.build_with_cdk_metrics()?;
+        tokio::spawn(async move {
+            if let Err(err) = server.start(std::future::pending()).await {
+                tracing::error!("Failed to start prometheus server: {}", err);
+            }
+        });
+    }
+
+    if let Some(config_path) = &args.threshold_config {
+        let config = ThresholdConfig::from_file(config_path)?;
+        let api_key = args
+            .threshold_api_key
+            .clone()
+            .or_else(|| env::var(ENV_THRESHOLD_API_KEY).ok());
+        let mut nodes: Vec<Arc<dyn SignerNode>> = Vec::with_capacity(args.threshold_node.len());
+        for url in &args.threshold_node {
+            let node = SignerNodeRpcClient::new(
+                url.clone(),
+                args.threshold_certs.as_ref(),
+                api_key.clone(),
+            )
+            .await?;
+            tracing::info!("Connected to signer node {} at {}", node.index(), url);
+            nodes.push(Arc::new(node));
+        }
+
+        let signatory = ThresholdSignatory::new(config, nodes)?.with_signing_limits(signing_limits);
+        return serve(signatory, audit_sink, listen, certs, api_keys).await;
+    }
+
     let seed_path = work_dir.join("seed");
 
     let mnemonic = if let Ok(mnemonic) = env::var(ENV_MNEMONIC) {
@@ -185,15 +325,131 @@ pub async fn cli_main() -> Result<()> {
             }
         }
     };
-    let seed = mnemonic.to_seed_normalized("");
+    let passphrase = env::var(ENV_MNEMONIC_PASSPHRASE).unwrap_or_default();
+    let seed = mnemonic.to_seed_normalized(&passphrase);
 
-    let signatory =
-        db_signatory::DbSignatory::new(localstore, &seed, supported_units, Default::default())
-            .await?;
+    let signatory = db_signatory::DbSignatory::new_with_network(
+        localstore,
+        &seed,
+        supported_units,
+        Default::default(),
+        args.network,
+    )
+    .await?
+    .with_signing_limits(signing_limits);
 
-    let socket_addr = SocketAddr::from_str(&format!("{}:{}", args.listen_addr, args.listen_port))?;
+    serve(signatory, audit_sink, listen, certs, api_keys).await
+}
 
-    start_grpc_server(Arc::new(signatory), socket_addr, certs).await?;
+/// Where the signatory server listens
+#[cfg(feature = "sqlite")]
+enum Listen {
+    /// TCP address, with TLS when certificates are given
+    Tcp(SocketAddr),
+    /// Unix domain socket path
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+/// Serve `signatory`, recording its signatures to `audit_sink` if any
+#[cfg(feature = "sqlite")]
+async fn serve<S>(
+    signatory: S,
+    audit_sink: Option<Arc<dyn AuditSink>>,
+    listen: Listen,
+    certs: Option<PathBuf>,
+    api_keys: ApiKeys,
+) -> Result<()>
+where
+    S: Signatory + Send + Sync + 'static,
+{
+    match audit_sink {
+        Some(sink) => {
+            tracing::info!("Recording blind signatures in the audit log");
+            let signatory = AuditedSignatory::new(Arc::new(signatory), sink);
+            start(Arc::new(signatory), listen, certs, api_keys).await
+        }
+        None => start(Arc::new(signatory), listen, certs, api_keys).await,
+    }
+}
+
+/// Start the gRPC server for `signatory` on `listen`
+#[cfg(feature = "sqlite")]
+async fn start<S>(
+    signatory: Arc<S>,
+    listen: Listen,
+    certs: Option<PathBuf>,
+    api_keys: ApiKeys,
+) -> Result<()>
+where
+    S: Signatory + Send + Sync + 'static,
+{
+    let shutdown = shutdown_on_signal()?;
+
+    match listen {
+        Listen::Tcp(socket_addr) => {
+            start_grpc_server_with_shutdown(signatory, socket_addr, certs, api_keys, shutdown)
+                .await?
+        }
+        #[cfg(unix)]
+        Listen::Unix(path) => {
+            cdk_signatory::start_grpc_server_uds(signatory, path, api_keys, shutdown).await?
+        }
+    }
+
+    Ok(())
+}
+
+/// Token cancelled when the process is interrupted or, on unix, terminated
+#[cfg(feature = "sqlite")]
+fn shutdown_on_signal() -> Result<CancellationToken> {
+    let shutdown = CancellationToken::new();
+
+    #[cfg(unix)]
+    let mut terminate = {
+        use tokio::signal::unix::{signal, SignalKind};
+        signal(SignalKind::terminate())?
+    };
+
+    let token = shutdown.clone();
+    tokio::spawn(async move {
+        #[cfg(unix)]
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {}
+            _ = terminate.recv() => {}
+        }
+        #[cfg(not(unix))]
+        let _ = tokio::signal::ctrl_c().await;
+
+        tracing::info!("Received shutdown signal");
+        token.cancel();
+    });
+
+    Ok(shutdown)
+}
+
+/// Reload `api_keys` from `path` every time the process receives SIGHUP
+#[cfg(all(unix, feature = "sqlite"))]
+fn reload_api_keys_on_sighup(api_keys: ApiKeys, path: PathBuf) -> Result<()> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut hangup = signal(SignalKind::hangup())?;
+    tokio::spawn(async move {
+        while hangup.recv().await.is_some() {
+            match api_keys.reload(&path) {
+                Ok(()) => tracing::info!(
+                    "Reloaded API keys of {} clients from {}",
+                    api_keys.clients().len(),
+                    path.display()
+                ),
+                Err(err) => tracing::error!(
+                    "Keeping the current API keys, could not reload {}: {}",
+                    path.display(),
+                    err
+                ),
+            }
+        }
+    });
 
     Ok(())
 }
@@ -0,0 +1,286 @@
+//! Signatory operator CLI logic
+//!
+//! Talks to a running signatory over gRPC through [`SignatoryRpcClient`], to debug a remote
+//! signer deployment without a mint in front of it.
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Result};
+use cdk_common::dhke::{blind_message, unblind_message};
+use cdk_common::nut02::KeySetVersion;
+use cdk_common::rotation_log::verify_rotation_log;
+use cdk_common::secret::Secret;
+use cdk_common::{Amount, BlindedMessage, CurrencyUnit, Id, Proof, PublicKey, SecretKey};
+use cdk_signatory::signatory::{RotateKeyArguments, Signatory};
+use cdk_signatory::SignatoryRpcClient;
+use clap::{Parser, Subcommand};
+
+const ENV_API_KEY: &str = "CDK_SIGNATORY_API_KEY";
+
+/// Operator tooling for a remote signatory
+#[derive(Parser)]
+#[command(name = "signatory-cli")]
+#[command(author, version, about, long_about = None)]
+struct Cli {
+    /// Signatory gRPC URL
+    #[arg(long, default_value = "https://127.0.0.1:15060")]
+    url: String,
+    /// Directory with the `ca.pem`, `client.pem` and `client.key` used for mTLS
+    #[arg(long, short)]
+    certs: Option<PathBuf>,
+    /// API key issued to this client, read from `CDK_SIGNATORY_API_KEY` when not given
+    #[arg(long)]
+    api_key: Option<String>,
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// List the keysets of the signatory
+    Keysets,
+    /// Print how a keyset was derived and its public keys
+    Keyset {
+        /// Keyset id
+        id: Id,
+    },
+    /// Print the units and keyset settings the signatory was configured with
+    Config,
+    /// Rotate the active keyset of a unit
+    Rotate {
+        /// Unit of the new keyset
+        #[arg(long, default_value = "sat")]
+        unit: String,
+        /// Input fee in parts per thousand
+        #[arg(long, default_value_t = 0)]
+        fee: u64,
+        /// Amounts are the powers of two below `2^max_order`
+        #[arg(long, default_value_t = 32)]
+        max_order: u32,
+        /// Derive a V1 keyset id instead of V2
+        #[arg(long)]
+        v1: bool,
+        /// Final expiry of the keyset (unix timestamp)
+        #[arg(long)]
+        final_expiry: Option<u64>,
+        /// Why the keyset is rotated, recorded in the rotation log
+        #[arg(long)]
+        reason: Option<String>,
+    },
+    /// Print the signed log of the keyset rotations and verify its chain
+    RotationLog,
+    /// Blind sign a secret and print the resulting proof
+    Sign {
+        /// Keyset to sign with, the active keyset of `unit` by default
+        #[arg(long)]
+        keyset: Option<Id>,
+        /// Unit whose active keyset signs, when no keyset is given
+        #[arg(long, default_value = "sat")]
+        unit: String,
+        /// Amount to sign
+        #[arg(long, default_value_t = 1)]
+        amount: u64,
+        /// Secret to sign, random by default
+        #[arg(long)]
+        secret: Option<String>,
+        /// Blinding factor as hex, random by default
+        #[arg(long)]
+        blinding_factor: Option<String>,
+    },
+    /// Ask the signatory whether a proof carries its signature
+    Verify {
+        /// Keyset of the proof
+        #[arg(long)]
+        keyset: Id,
+        /// Amount of the proof
+        #[arg(long)]
+        amount: u64,
+        /// Secret of the proof
+        #[arg(long)]
+        secret: String,
+        /// Unblinded signature `C` as hex
+        #[arg(long)]
+        c: String,
+    },
+}
+
+/// Main function for the signatory operator CLI
+pub async fn cli_main() -> Result<()> {
+    let args = Cli::parse();
+    let api_key = args.api_key.or_else(|| std::env::var(ENV_API_KEY).ok());
+    let client = SignatoryRpcClient::new(args.url, args.certs, api_key).await?;
+
+    match args.command {
+        Commands::Keysets => {
+            let keysets = client.keysets().await?;
+            println!("pubkey: {}", keysets.pubkey);
+            for keyset in keysets.keysets {
+                println!(
+                    "{} unit={} active={} input_fee_ppk={} amounts={} final_expiry={}",
+                    keyset.id,
+                    keyset.unit,
+                    keyset.active,
+                    keyset.input_fee_ppk,
+                    keyset.amounts.len(),
+                    keyset
+                        .final_expiry
+                        .map(|expiry| expiry.to_string())
+                        .unwrap_or_else(|| "none".to_owned()),
+                );
+            }
+        }
+        Commands::Keyset { id } => {
+            let info = client.keyset_info(id).await?;
+            println!(
+                "{} unit={} active={} input_fee_ppk={} derivation_path={} final_expiry={}",
+                info.id,
+                info.unit,
+                info.active,
+                info.input_fee_ppk,
+                info.derivation_path,
+                info.final_expiry
+                    .map(|expiry| expiry.to_string())
+                    .unwrap_or_else(|| "none".to_owned()),
+            );
+            for (amount, pubkey) in client.keyset_pubkeys(id).await?.keys.iter() {
+                println!("{amount}: {pubkey}");
+            }
+        }
+        Commands::Config => {
+            let config = client.supported_config().await?;
+            for unit in config.units {
+                println!(
+                    "{} input_fee_ppk={} amounts={} max_order={} derivation_path={}",
+                    unit.unit,
+                    unit.input_fee_ppk,
+                    unit.amounts.len(),
+                    unit.max_order()
+                        .map(|max_order| max_order.to_string())
+                        .unwrap_or_else(|| "custom".to_owned()),
+                    unit.derivation_path
+                        .as_ref()
+                        .map(|path| path.to_string())
+                        .unwrap_or_else(|| "default".to_owned()),
+                );
+            }
+        }
+        Commands::Rotate {
+            unit,
+            fee,
+            max_order,
+            v1,
+            final_expiry,
+            reason,
+        } => {
+            let keyset = client
+                .rotate_keyset(RotateKeyArguments {
+                    unit: CurrencyUnit::from_str(&unit)?,
+                    amounts: (0..max_order).map(|i| 2_u64.pow(i)).collect(),
+                    input_fee_ppk: fee,
+                    keyset_id_type: if v1 {
+                        KeySetVersion::Version00
+                    } else {
+                        KeySetVersion::Version01
+                    },
+                    final_expiry,
+                    reason,
+                })
+                .await?;
+            println!("new active keyset {} for unit {}", keyset.id, keyset.unit);
+        }
+        Commands::RotationLog => {
+            let log = client.rotation_log().await?;
+            for entry in &log {
+                let attestation = &entry.attestation;
+                println!(
+                    "#{} {} unit={} old={} new={} signer={} reason={}",
+                    attestation.sequence,
+                    attestation.created_at,
+                    attestation.unit,
+                    attestation
+                        .old_keyset_id
+                        .map(|id| id.to_string())
+                        .unwrap_or_else(|| "none".to_owned()),
+                    attestation.new_keyset_id,
+                    entry.signer,
+                    attestation.reason.as_deref().unwrap_or("none"),
+                );
+            }
+            verify_rotation_log(&log)?;
+            println!("{} attestations verified", log.len());
+        }
+        Commands::Sign {
+            keyset,
+            unit,
+            amount,
+            secret,
+            blinding_factor,
+        } => {
+            let unit = CurrencyUnit::from_str(&unit)?;
+            let keyset = match keyset {
+                Some(id) => client.keyset_pubkeys(id).await?,
+                None => client
+                    .keysets()
+                    .await?
+                    .keysets
+                    .into_iter()
+                    .find(|keyset| keyset.active && keyset.unit == unit)
+                    .ok_or(anyhow!("Keyset not found"))?,
+            };
+
+            let amount = Amount::from(amount);
+            let mint_pubkey = keyset.keys.amount_key(amount).ok_or(anyhow!(
+                "Keyset {} has no key for {}",
+                keyset.id,
+                amount
+            ))?;
+
+            let secret = secret.map(Secret::new).unwrap_or_else(Secret::generate);
+            let blinding_factor = blinding_factor.map(SecretKey::from_hex).transpose()?;
+            let (blinded_secret, r) = blind_message(secret.as_bytes(), blinding_factor)?;
+
+            let signature = client
+                .blind_sign(vec![BlindedMessage::new(amount, keyset.id, blinded_secret)])
+                .await?
+                .pop()
+                .ok_or(anyhow!("Signatory returned no signature"))?;
+
+            let dleq = match signature.verify_dleq(mint_pubkey, blinded_secret) {
+                Ok(()) => "valid",
+                Err(_) => "invalid",
+            };
+            let c = unblind_message(&signature.c, &r, &mint_pubkey)?;
+
+            println!("keyset: {}", keyset.id);
+            println!("amount: {}", amount);
+            println!("K: {}", mint_pubkey);
+            println!("secret: {}", secret);
+            println!("r: {}", r.to_secret_hex());
+            println!("B_: {}", blinded_secret);
+            println!("C_: {}", signature.c);
+            println!("C: {}", c);
+            println!("dleq: {}", dleq);
+        }
+        Commands::Verify {
+            keyset,
+            amount,
+            secret,
+            c,
+        } => {
+            let proof = Proof::new(
+                Amount::from(amount),
+                keyset,
+                Secret::new(secret),
+                PublicKey::from_hex(c)?,
+            );
+
+            match client.verify_proofs(vec![proof]).await {
+                Ok(()) => println!("valid"),
+                Err(cdk_common::Error::SignatureMissingOrInvalid) => bail!("invalid signature"),
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+
+    Ok(())
+}
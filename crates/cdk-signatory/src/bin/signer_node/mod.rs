@@ -0,0 +1,179 @@
+//! Threshold signer node CLI logic
+//!
+//! `deal` splits the keysets of a seed into one share file per node and a coordinator config with
+//! the public keys, `serve` runs a node from its share file. The coordinator is the `signatory`
+//! binary started with `--threshold-config`.
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::{env, fs};
+
+use anyhow::{bail, Result};
+use bip39::rand::{thread_rng, Rng};
+use bip39::Mnemonic;
+use cdk_common::CurrencyUnit;
+use cdk_signatory::threshold::{deal, LocalSignerNode, SignerShares};
+use cdk_signatory::{start_signer_node_server, ApiKeys};
+use clap::{Parser, Subcommand};
+
+const ENV_MNEMONIC: &str = "CDK_MINTD_MNEMONIC";
+const ENV_MNEMONIC_PASSPHRASE: &str = "CDK_MINTD_MNEMONIC_PASSPHRASE";
+
+/// Signer node of a threshold signatory
+#[derive(Parser)]
+#[command(name = "signer-node")]
+#[command(author, version, about, long_about = None)]
+struct Cli {
+    /// Enable logging (default is false)
+    #[arg(long, default_value_t = false)]
+    enable_logging: bool,
+    /// Logging level when enabled (default is debug)
+    #[arg(long, default_value = "debug")]
+    log_level: tracing::Level,
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Split the keysets of a seed into shares for the signer nodes
+    ///
+    /// The seed is read from `CDK_MINTD_MNEMONIC`, or a new one is written to `seed` in the
+    /// output directory. Move it offline once the shares are distributed, it is only needed to
+    /// deal again.
+    Deal {
+        /// Directory the coordinator config and the share files are written to
+        #[arg(long, short)]
+        out_dir: PathBuf,
+        /// Number of nodes needed to sign
+        #[arg(long, short)]
+        threshold: u16,
+        /// Number of nodes holding a share
+        #[arg(long, short)]
+        nodes: u16,
+        /// Derivation path index of the active keysets, the lower ones are dealt inactive
+        #[arg(long, default_value_t = 1)]
+        keyset_index: u32,
+        /// Supported units with the format of name,fee and max_order
+        #[arg(long, short, default_value = "sat,0,32")]
+        units: Vec<String>,
+    },
+    /// Run a signer node
+    Serve {
+        /// Share file written by `deal`
+        #[arg(long, short)]
+        shares: PathBuf,
+        #[arg(long, default_value = "127.0.0.1")]
+        listen_addr: String,
+        #[arg(long, default_value = "15070")]
+        listen_port: u32,
+        /// Directory with the `server.pem`, `server.key` and `ca.pem` used for mTLS
+        #[arg(long, short)]
+        certs: Option<PathBuf>,
+        /// File with one `<client> <key>` API key per line, the coordinator needs one of them
+        #[arg(long)]
+        api_keys: Option<PathBuf>,
+    },
+}
+
+/// Parse `name,fee,max_order` units
+fn parse_units(units: Vec<String>) -> Result<HashMap<CurrencyUnit, (u64, Vec<u64>)>> {
+    units
+        .into_iter()
+        .map(|unit| {
+            let mut parts = unit.split(",").collect::<Vec<_>>();
+            parts.reverse();
+            let unit: CurrencyUnit = parts.pop().unwrap_or_default().parse()?;
+            let fee = parts
+                .pop()
+                .map(|x| x.parse())
+                .transpose()?
+                .unwrap_or_default();
+            let max_order: u32 = parts.pop().map(|x| x.parse()).transpose()?.unwrap_or(32);
+            let amounts: Vec<u64> = (0..max_order).map(|i| 2u64.pow(i)).collect();
+            Ok::<(_, (_, _)), anyhow::Error>((unit, (fee, amounts)))
+        })
+        .collect()
+}
+
+/// Main function for the signer node binary
+pub async fn cli_main() -> Result<()> {
+    let args: Cli = Cli::parse();
+
+    if args.enable_logging {
+        let _ = tracing_subscriber::fmt()
+            .with_max_level(args.log_level)
+            .with_ansi(false)
+            .try_init();
+    }
+
+    match args.command {
+        Commands::Deal {
+            out_dir,
+            threshold,
+            nodes,
+            keyset_index,
+            units,
+        } => {
+            fs::create_dir_all(&out_dir)?;
+
+            let mnemonic = match env::var(ENV_MNEMONIC) {
+                Ok(mnemonic) => Mnemonic::from_str(&mnemonic)?,
+                Err(_) => {
+                    let seed_path = out_dir.join("seed");
+                    if seed_path.exists() {
+                        bail!(
+                            "{} exists, set {} to deal from it",
+                            seed_path.display(),
+                            ENV_MNEMONIC
+                        );
+                    }
+
+                    let random_bytes: [u8; 32] = thread_rng().gen();
+                    let mnemonic = Mnemonic::from_entropy(&random_bytes)?;
+                    fs::write(&seed_path, mnemonic.to_string())?;
+                    tracing::warn!(
+                        "Wrote a new seed to {}, move it offline",
+                        seed_path.display()
+                    );
+                    mnemonic
+                }
+            };
+            let passphrase = env::var(ENV_MNEMONIC_PASSPHRASE).unwrap_or_default();
+            let seed = mnemonic.to_seed_normalized(&passphrase);
+
+            let (config, shares) =
+                deal(&seed, &parse_units(units)?, keyset_index, threshold, nodes)?;
+
+            let config_path = out_dir.join("coordinator.json");
+            fs::write(&config_path, serde_json::to_string_pretty(&config)?)?;
+            println!("Coordinator config: {}", config_path.display());
+
+            for shares in shares {
+                let path = out_dir.join(format!("node-{}.json", shares.index));
+                fs::write(&path, serde_json::to_string_pretty(&shares)?)?;
+                println!("Shares of node {}: {}", shares.index, path.display());
+            }
+        }
+        Commands::Serve {
+            shares,
+            listen_addr,
+            listen_port,
+            certs,
+            api_keys,
+        } => {
+            let node = LocalSignerNode::new(SignerShares::from_file(&shares)?);
+            let api_keys = match &api_keys {
+                Some(path) => ApiKeys::from_file(path)?,
+                None => ApiKeys::disabled(),
+            };
+            let socket_addr = SocketAddr::from_str(&format!("{listen_addr}:{listen_port}"))?;
+
+            start_signer_node_server(Arc::new(node), socket_addr, certs, api_keys).await?;
+        }
+    }
+
+    Ok(())
+}
@@ -4,13 +4,34 @@ use std::sync::Arc;
 
 use bitcoin::bip32::{ChildNumber, DerivationPath, Xpriv};
 use bitcoin::secp256k1::{self, All, Secp256k1};
+use bitcoin::Network;
 use cdk_common::common::IssuerVersion;
 use cdk_common::error::Error;
 use cdk_common::mint::MintKeySetInfo;
-use cdk_common::nuts::{CurrencyUnit, MintKeySet};
+use cdk_common::nuts::{CurrencyUnit, MintKeySet, SecretKey};
 use cdk_common::util::unix_time;
 use cdk_common::{database, nut02};
 
+/// Master key of the signatory for `network`
+///
+/// BIP-32 keys derived from a seed are the same on every network, only their serialization
+/// differs. Keys for any other network than mainnet are therefore derived under a hardened child
+/// of the master key, indexed by the network magic, so a seed reused on a test network never
+/// signs with mainnet keys. Mainnet derives from the master key itself, as it always did.
+pub fn network_xpriv<C: secp256k1::Signing>(
+    secp: &Secp256k1<C>,
+    seed: &[u8],
+    network: Network,
+) -> Result<Xpriv, Error> {
+    let xpriv = Xpriv::new_master(network, seed)?;
+    if network == Network::Bitcoin {
+        return Ok(xpriv);
+    }
+
+    let index = u32::from_be_bytes(network.magic().to_bytes()) & !(1 << 31);
+    Ok(xpriv.derive_priv(secp, &[ChildNumber::from_hardened_idx(index)?])?)
+}
+
 /// Initialize keysets
 pub async fn init_keysets(
     xpriv: Xpriv,
@@ -19,6 +40,7 @@ pub async fn init_keysets(
     supported_units: &HashMap<CurrencyUnit, (u64, Vec<u64>)>,
 ) -> Result<(), Error> {
     let keysets_infos = localstore.get_keyset_infos().await?;
+    let disabled_keysets = localstore.get_disabled_keysets().await?;
     let mut tx = localstore.begin_transaction().await?;
 
     let keysets_by_unit: HashMap<CurrencyUnit, Vec<MintKeySetInfo>> =
@@ -42,6 +64,14 @@ pub async fn init_keysets(
                     continue;
                 }
 
+                if disabled_keysets.contains(&highest_index_keyset.id) {
+                    tracing::info!(
+                        "Highest index keyset for unit {} was disabled, skipping reactivation",
+                        unit
+                    );
+                    continue;
+                }
+
                 // Check if it matches our criteria
                 if highest_index_keyset.input_fee_ppk == *input_fee_ppk
                     && highest_index_keyset.amounts == *amounts
@@ -115,6 +145,25 @@ pub fn create_new_keyset<C: secp256k1::Signing>(
     (keyset, keyset_info)
 }
 
+/// Key signing the keyset rotation attestations and keyset exports
+///
+/// Derived at `m/129373'`, outside of the `m/129372'` tree of the keysets, so it never signs
+/// ecash and a keyset key never signs an attestation.
+pub fn rotation_attestation_key<C: secp256k1::Signing>(
+    secp: &Secp256k1<C>,
+    xpriv: Xpriv,
+) -> SecretKey {
+    xpriv
+        .derive_priv(
+            secp,
+            &[ChildNumber::from_hardened_idx(129373).expect("129373 is a valid index")],
+        )
+        .expect("RNG busted")
+        .private_key
+        .into()
+}
+
+/// Derivation path of the keyset of `unit` at `index`, `m/129372'/<unit>'/<index>'`
 pub fn derivation_path_from_unit(unit: CurrencyUnit, index: u32) -> Option<DerivationPath> {
     let unit_index = unit.hashed_derivation_index();
 
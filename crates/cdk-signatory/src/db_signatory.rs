@@ -6,17 +6,26 @@ use std::sync::Arc;
 
 use bitcoin::bip32::{DerivationPath, Xpriv};
 use bitcoin::secp256k1::{self, Secp256k1};
+use bitcoin::Network;
 use cdk_common::dhke::{sign_message, verify_message};
+use cdk_common::keyset_export::{ExportedKeyset, KeysetExport, SignedKeysetExport};
 use cdk_common::mint::MintKeySetInfo;
 use cdk_common::nuts::{BlindSignature, BlindedMessage, CurrencyUnit, Id, MintKeySet, Proof};
+use cdk_common::rotation_log::{RotationAttestation, SignedRotationAttestation};
+use cdk_common::util::unix_time;
 use cdk_common::{database, Error, PublicKey};
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, RwLock};
 use tracing::instrument;
 
 use crate::common::{
     check_unit_string_collision, create_new_keyset, derivation_path_from_unit, init_keysets,
+    network_xpriv, rotation_attestation_key,
+};
+use crate::limits::SigningLimiter;
+use crate::signatory::{
+    RotateKeyArguments, Signatory, SignatoryConfig, SignatoryKeySet, SignatoryKeysets,
+    SignatoryUnitConfig, SigningLimit, SigningLimitUsage, UpdateKeysetConfigArguments,
 };
-use crate::signatory::{RotateKeyArguments, Signatory, SignatoryKeySet, SignatoryKeysets};
 
 /// In-memory Signatory
 ///
@@ -30,9 +39,11 @@ pub struct DbSignatory {
     active_keysets: RwLock<HashMap<CurrencyUnit, Id>>,
     localstore: Arc<dyn database::MintKeysDatabase<Err = database::Error> + Send + Sync>,
     secp_ctx: Secp256k1<secp256k1::All>,
+    supported_units: RwLock<HashMap<CurrencyUnit, (u64, Vec<u64>)>>,
     custom_paths: HashMap<CurrencyUnit, DerivationPath>,
     xpriv: Xpriv,
     xpub: PublicKey,
+    signing_limiter: Mutex<SigningLimiter>,
 }
 
 impl DbSignatory {
@@ -42,13 +53,39 @@ impl DbSignatory {
     ///
     /// Panics if the seed produces an invalid master key (should never happen with valid entropy).
     pub async fn new(
+        localstore: Arc<dyn database::MintKeysDatabase<Err = database::Error> + Send + Sync>,
+        seed: &[u8],
+        supported_units: HashMap<CurrencyUnit, (u64, Vec<u64>)>,
+        custom_paths: HashMap<CurrencyUnit, DerivationPath>,
+    ) -> Result<Self, Error> {
+        Self::new_with_network(
+            localstore,
+            seed,
+            supported_units,
+            custom_paths,
+            Network::Bitcoin,
+        )
+        .await
+    }
+
+    /// Creates a new MemorySignatory instance deriving its keys for `network`
+    ///
+    /// The same seed derives unrelated keys on every network, so a test deployment can not sign
+    /// with mainnet keysets. Changing the network of an existing signatory makes its active
+    /// keysets underivable and it refuses to start.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the seed produces an invalid master key (should never happen with valid entropy).
+    pub async fn new_with_network(
         localstore: Arc<dyn database::MintKeysDatabase<Err = database::Error> + Send + Sync>,
         seed: &[u8],
         mut supported_units: HashMap<CurrencyUnit, (u64, Vec<u64>)>,
         custom_paths: HashMap<CurrencyUnit, DerivationPath>,
+        network: Network,
     ) -> Result<Self, Error> {
         let secp_ctx = Secp256k1::new();
-        let xpriv = Xpriv::new_master(bitcoin::Network::Bitcoin, seed).expect("RNG busted");
+        let xpriv = network_xpriv(&secp_ctx, seed, network).expect("RNG busted");
         init_keysets(xpriv, &secp_ctx, &localstore, &supported_units).await?;
 
         supported_units
@@ -59,16 +96,51 @@ impl DbSignatory {
             keysets: Default::default(),
             active_keysets: Default::default(),
             localstore,
+            supported_units: RwLock::new(supported_units),
             custom_paths,
             xpub: xpriv.to_keypair(&secp_ctx).public_key().into(),
             secp_ctx,
             xpriv,
+            signing_limiter: Default::default(),
         };
         keys.reload_keys_from_db().await?;
 
         Ok(keys)
     }
 
+    /// Refuse to sign more than `limits` allow
+    ///
+    /// What was signed is only tracked in memory, a restart starts every window afresh.
+    pub fn with_signing_limits(mut self, limits: Vec<SigningLimit>) -> Self {
+        self.signing_limiter = Mutex::new(SigningLimiter::new(limits));
+        self
+    }
+
+    /// Signed attestation of the rotation of `unit` to `new_keyset_id`, appended to the log
+    async fn attest_rotation(
+        &self,
+        unit: CurrencyUnit,
+        old_keyset_id: Option<Id>,
+        new_keyset_id: Id,
+        reason: Option<String>,
+    ) -> Result<SignedRotationAttestation, Error> {
+        let created_at = unix_time();
+        let attestation = match self.localstore.get_rotation_log().await?.pop() {
+            Some(last) => last.next(created_at, unit, old_keyset_id, new_keyset_id, reason)?,
+            None => RotationAttestation {
+                sequence: 0,
+                previous_hash: None,
+                created_at,
+                unit,
+                old_keyset_id,
+                new_keyset_id,
+                reason,
+            },
+        };
+
+        attestation.sign(&rotation_attestation_key(&self.secp_ctx, self.xpriv))
+    }
+
     /// Load all the keysets from the database, even if they are not active.
     ///
     /// Since the database is owned by this process, we can load all the keysets in memory, and use
@@ -76,6 +148,10 @@ impl DbSignatory {
     ///
     /// Any operation performed with keysets, are done through this trait and never to the database
     /// directly.
+    ///
+    /// Every active keyset is re-derived from the seed and its id compared with the stored one. A
+    /// mismatch means the signatory was started with a different seed than the one that created
+    /// the keysets, and it is refused instead of silently signing with unrelated keys.
     async fn reload_keys_from_db(&self) -> Result<(), Error> {
         let mut keysets = self.keysets.write().await;
         let mut active_keysets = self.active_keysets.write().await;
@@ -89,6 +165,18 @@ impl DbSignatory {
             let keyset = self.generate_keyset(&info);
             info.active = db_active_keysets.get(&info.unit) == Some(&info.id);
             if info.active {
+                if keyset.id != id {
+                    tracing::error!(
+                        "Active keyset {} for unit {} cannot be derived from the configured seed (derived {})",
+                        id,
+                        info.unit,
+                        keyset.id
+                    );
+                    return Err(Error::KeysetSeedMismatch {
+                        stored: id,
+                        derived: keyset.id,
+                    });
+                }
                 active_keysets.insert(info.unit.clone(), id);
             }
             keysets.insert(id, (info, keyset));
@@ -98,7 +186,7 @@ impl DbSignatory {
     }
 
     fn generate_keyset(&self, keyset_info: &MintKeySetInfo) -> MintKeySet {
-        MintKeySet::generate_from_xpriv(
+        let mut keyset = MintKeySet::generate_from_xpriv(
             &self.secp_ctx,
             self.xpriv,
             &keyset_info.amounts,
@@ -107,7 +195,49 @@ impl DbSignatory {
             keyset_info.input_fee_ppk,
             keyset_info.final_expiry,
             keyset_info.id.get_version(),
-        )
+        );
+
+        // Keysets created before `keyset-id-commitments` was toggled keep the id they were
+        // published with
+        if keyset.id != keyset_info.id
+            && keyset_info.id.is_derived_from(
+                &keyset.keys.clone().into(),
+                &keyset.unit,
+                keyset.input_fee_ppk,
+                keyset.final_expiry,
+            )
+        {
+            keyset.id = keyset_info.id;
+        }
+
+        keyset
+    }
+}
+
+/// Records the signatures issued per keyset, or why the request was refused
+#[cfg(feature = "prometheus")]
+fn record_blind_sign(result: &Result<Vec<BlindSignature>, Error>) {
+    match result {
+        Ok(signatures) => {
+            let mut per_keyset: HashMap<Id, u64> = HashMap::new();
+            for signature in signatures {
+                *per_keyset.entry(signature.keyset_id).or_default() += 1;
+            }
+            for (keyset_id, count) in per_keyset {
+                cdk_prometheus::METRICS
+                    .record_signer_blind_signatures(&keyset_id.to_string(), count);
+            }
+        }
+        Err(err) => {
+            let reason = match err {
+                Error::InactiveKeyset => "inactive_keyset",
+                Error::ExpiredKeyset => "expired_keyset",
+                Error::UnknownKeySet => "unknown_keyset",
+                Error::SigningLimitExceeded => "signing_limit",
+                _ => "other",
+            };
+            cdk_prometheus::METRICS.record_signer_rejection(reason);
+        }
     }
 }
 
@@ -122,52 +252,76 @@ impl Signatory for DbSignatory {
         &self,
         blinded_messages: Vec<BlindedMessage>,
     ) -> Result<Vec<BlindSignature>, Error> {
-        let keysets = self.keysets.read().await;
+        let result = async {
+            let keysets = self.keysets.read().await;
+            let mut signed = Vec::with_capacity(blinded_messages.len());
 
-        blinded_messages
-            .into_iter()
-            .map(|blinded_message| {
-                let BlindedMessage {
-                    amount,
-                    blinded_secret,
-                    keyset_id,
-                    ..
-                } = blinded_message;
-
-                let (info, key) = keysets.get(&keyset_id).ok_or(Error::UnknownKeySet)?;
-                if !info.active {
-                    return Err(Error::InactiveKeyset);
-                }
-                if info.is_expired() {
-                    return Err(Error::ExpiredKeyset);
-                }
+            let signatures = blinded_messages
+                .into_iter()
+                .map(|blinded_message| {
+                    let BlindedMessage {
+                        amount,
+                        blinded_secret,
+                        keyset_id,
+                        ..
+                    } = blinded_message;
 
-                let key_pair = key.keys.get(&amount).ok_or(Error::UnknownKeySet)?;
-                let c = sign_message(&key_pair.secret_key, &blinded_secret)?;
+                    let (info, key) = keysets.get(&keyset_id).ok_or(Error::UnknownKeySet)?;
+                    if !info.active {
+                        return Err(Error::InactiveKeyset);
+                    }
+                    if info.is_expired() {
+                        return Err(Error::ExpiredKeyset);
+                    }
 
-                let blinded_signature = BlindSignature::new(
-                    amount,
-                    c,
-                    keyset_id,
-                    &blinded_message.blinded_secret,
-                    key_pair.secret_key.clone(),
-                )?;
+                    let key_pair = key.keys.get(&amount).ok_or(Error::UnknownKeySet)?;
+                    let c = sign_message(&key_pair.secret_key, &blinded_secret)?;
+                    signed.push((keyset_id, &info.unit, u64::from(amount)));
 
-                Ok(blinded_signature)
-            })
-            .collect::<Result<Vec<_>, _>>()
+                    let blinded_signature = BlindSignature::new(
+                        amount,
+                        c,
+                        keyset_id,
+                        &blinded_message.blinded_secret,
+                        key_pair.secret_key.clone(),
+                    )?;
+
+                    Ok(blinded_signature)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            self.signing_limiter
+                .lock()
+                .await
+                .consume(&signed, unix_time())?;
+
+            Ok(signatures)
+        }
+        .await;
+
+        #[cfg(feature = "prometheus")]
+        record_blind_sign(&result);
+
+        result
     }
 
     #[tracing::instrument(skip_all)]
     async fn verify_proofs(&self, proofs: Vec<Proof>) -> Result<(), Error> {
         let keysets = self.keysets.read().await;
 
-        proofs.into_iter().try_for_each(|proof| {
+        let result = proofs.into_iter().try_for_each(|proof| {
             let (_, key) = keysets.get(&proof.keyset_id).ok_or(Error::UnknownKeySet)?;
             let key_pair = key.keys.get(&proof.amount).ok_or(Error::UnknownKeySet)?;
             verify_message(&key_pair.secret_key, proof.c, proof.secret.as_bytes())?;
             Ok(())
-        })
+        });
+
+        #[cfg(feature = "prometheus")]
+        if result.is_err() {
+            cdk_prometheus::METRICS.record_signer_verify_failure();
+        }
+
+        result
     }
 
     #[tracing::instrument(skip_all)]
@@ -184,13 +338,90 @@ impl Signatory for DbSignatory {
         })
     }
 
+    #[tracing::instrument(skip(self))]
+    async fn keyset_pubkeys(&self, id: Id) -> Result<SignatoryKeySet, Error> {
+        self.keysets
+            .read()
+            .await
+            .get(&id)
+            .map(|k| k.into())
+            .ok_or(Error::UnknownKeySet)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn keyset_info(&self, id: Id) -> Result<MintKeySetInfo, Error> {
+        self.keysets
+            .read()
+            .await
+            .get(&id)
+            .map(|(info, _)| info.clone())
+            .ok_or(Error::UnknownKeySet)
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn supported_config(&self) -> Result<SignatoryConfig, Error> {
+        Ok(SignatoryConfig {
+            units: self
+                .supported_units
+                .read()
+                .await
+                .iter()
+                .map(|(unit, (input_fee_ppk, amounts))| SignatoryUnitConfig {
+                    unit: unit.clone(),
+                    input_fee_ppk: *input_fee_ppk,
+                    amounts: amounts.clone(),
+                    derivation_path: self.custom_paths.get(unit).cloned(),
+                })
+                .collect(),
+        })
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn signing_limits(&self) -> Result<Vec<SigningLimitUsage>, Error> {
+        Ok(self.signing_limiter.lock().await.usage(unix_time()))
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn rotation_log(&self) -> Result<Vec<SignedRotationAttestation>, Error> {
+        Ok(self.localstore.get_rotation_log().await?)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn export_keysets(&self) -> Result<SignedKeysetExport, Error> {
+        let mut keysets = self
+            .keysets
+            .read()
+            .await
+            .values()
+            .map(|keyset| {
+                let (info, _) = keyset;
+                let signatory_keyset: SignatoryKeySet = keyset.into();
+                (
+                    info.valid_from,
+                    ExportedKeyset {
+                        keyset: signatory_keyset.into(),
+                        amounts: info.amounts.clone(),
+                        derivation_path: info.derivation_path.clone(),
+                        derivation_path_index: info.derivation_path_index,
+                        issuer_version: info.issuer_version.clone(),
+                    },
+                )
+            })
+            .collect::<Vec<_>>();
+        keysets.sort_by_key(|(valid_from, exported)| (*valid_from, exported.keyset.id));
+
+        KeysetExport::new(keysets.into_iter().map(|(_, exported)| exported).collect())
+            .sign(&rotation_attestation_key(&self.secp_ctx, self.xpriv))
+    }
+
     /// Add current keyset to inactive keysets
     /// Generate new keyset
+    ///
+    /// The rotation is appended to the rotation log, signed by a key derived from the seed.
     #[tracing::instrument(skip(self))]
     async fn rotate_keyset(&self, args: RotateKeyArguments) -> Result<SignatoryKeySet, Error> {
-        let (path_index, amounts) = if let Some(current_keyset_id) =
-            self.localstore.get_active_keyset_id(&args.unit).await?
-        {
+        let old_keyset_id = self.localstore.get_active_keyset_id(&args.unit).await?;
+        let (path_index, amounts) = if let Some(current_keyset_id) = old_keyset_id {
             let keyset_info = self
                 .localstore
                 .get_keyset_info(&current_keyset_id)
@@ -201,6 +432,21 @@ impl Signatory for DbSignatory {
                 keyset_info.derivation_path_index.unwrap_or(1) + 1,
                 keyset_info.amounts,
             )
+        } else if let Some(last_keyset_info) = self
+            .keysets
+            .read()
+            .await
+            .values()
+            .map(|(info, _)| info)
+            .filter(|info| info.unit == args.unit)
+            .max_by_key(|info| info.derivation_path_index)
+        {
+            // The unit has no active keyset after its last one was disabled, never derive it
+            // again
+            (
+                last_keyset_info.derivation_path_index.unwrap_or(1) + 1,
+                last_keyset_info.amounts.clone(),
+            )
         } else {
             (1, vec![])
         };
@@ -235,29 +481,119 @@ impl Signatory for DbSignatory {
         let keysets = self.keysets().await?;
         check_unit_string_collision(keysets.keysets, &info)?;
 
+        if self
+            .localstore
+            .get_disabled_keysets()
+            .await?
+            .contains(&info.id)
+        {
+            tracing::error!(
+                "Rotation for unit {} derived disabled keyset {}",
+                args.unit,
+                info.id
+            );
+            return Err(Error::InactiveKeyset);
+        }
+
         let id = info.id;
+        let attestation = self
+            .attest_rotation(args.unit.clone(), old_keyset_id, id, args.reason)
+            .await?;
+
+        // A concurrent rotation taking the same sequence number fails here, before its keyset is
+        // activated, instead of forking the log
         let mut tx = self.localstore.begin_transaction().await?;
         tx.add_keyset_info(info.clone()).await?;
         tx.set_active_keyset(args.unit, id).await?;
+        tx.add_rotation_attestation(&attestation).await?;
         tx.commit().await?;
 
         self.reload_keys_from_db().await?;
 
         Ok((&(info, keyset)).into())
     }
+
+    /// Mark the keyset inactive and disabled in the database
+    ///
+    /// A disabled keyset is never reactivated at startup by `init_keysets`.
+    #[tracing::instrument(skip(self))]
+    async fn disable_keyset(&self, id: Id) -> Result<SignatoryKeySet, Error> {
+        if !self.keysets.read().await.contains_key(&id) {
+            return Err(Error::UnknownKeySet);
+        }
+
+        let mut tx = self.localstore.begin_transaction().await?;
+        tx.disable_keyset(&id).await?;
+        tx.commit().await?;
+
+        tracing::warn!("Keyset {} disabled, it will no longer be signed with", id);
+
+        self.reload_keys_from_db().await?;
+
+        self.keysets
+            .read()
+            .await
+            .get(&id)
+            .map(|k| k.into())
+            .ok_or(Error::UnknownKeySet)
+    }
+
+    /// Rotate the keyset of the unit if its fee or amounts changed
+    ///
+    /// The new fee and amounts are also reported by [`Signatory::supported_config`]. They are not
+    /// persisted, the keyset is rotated back at startup unless the signatory is started with them.
+    #[tracing::instrument(skip(self))]
+    async fn update_keyset_config(
+        &self,
+        args: UpdateKeysetConfigArguments,
+    ) -> Result<SignatoryKeySet, Error> {
+        // Held for the whole update so concurrent updates can not rotate twice
+        let mut supported_units = self.supported_units.write().await;
+
+        let active = self
+            .keysets()
+            .await?
+            .keysets
+            .into_iter()
+            .find(|keyset| keyset.active && keyset.unit == args.unit);
+
+        let keyset = match args.rotation(active.as_ref()) {
+            Some(rotation) => {
+                tracing::info!(
+                    "Rotating keyset of {} for fee {} and amounts {:?}",
+                    args.unit,
+                    rotation.input_fee_ppk,
+                    rotation.amounts
+                );
+                self.rotate_keyset(rotation).await?
+            }
+            None => active.ok_or(Error::UnsupportedUnit)?,
+        };
+
+        supported_units.insert(
+            keyset.unit.clone(),
+            (keyset.input_fee_ppk, keyset.amounts.clone()),
+        );
+
+        Ok(keyset)
+    }
 }
 
 #[cfg(test)]
 mod test {
     use std::collections::HashSet;
+    use std::str::FromStr;
 
     use bitcoin::key::Secp256k1;
     use bitcoin::Network;
+    use cdk_common::dhke::{blind_message, unblind_message};
     use cdk_common::nuts::SecretKey;
-    use cdk_common::util::{hex, unix_time};
+    use cdk_common::secret::Secret;
+    use cdk_common::util::hex;
     use cdk_common::{Amount, MintKeySet, PublicKey};
 
     use super::*;
+    use crate::signatory::SigningLimitScope;
 
     #[tokio::test]
     async fn blind_sign_rejects_expired_keyset() {
@@ -282,6 +618,7 @@ mod test {
                 input_fee_ppk: 0,
                 keyset_id_type: cdk_common::nut02::KeySetVersion::Version00,
                 final_expiry: Some(unix_time() - 1),
+                reason: None,
             })
             .await
             .expect("rotate_keyset");
@@ -299,6 +636,503 @@ mod test {
         );
     }
 
+    #[tokio::test]
+    async fn blind_sign_enforces_signing_limits() {
+        let store = Arc::new(
+            cdk_sqlite::mint::memory::empty()
+                .await
+                .expect("in-memory db"),
+        );
+        let signatory = DbSignatory::new(
+            store,
+            b"test-seed-for-unit-tests",
+            Default::default(),
+            Default::default(),
+        )
+        .await
+        .expect("DbSignatory::new")
+        .with_signing_limits(vec![SigningLimit {
+            scope: SigningLimitScope::Unit(CurrencyUnit::Sat),
+            window_secs: 3600,
+            max_amount: 10,
+        }]);
+
+        let keyset = signatory
+            .rotate_keyset(RotateKeyArguments {
+                unit: CurrencyUnit::Sat,
+                amounts: vec![1, 2, 4, 8],
+                input_fee_ppk: 0,
+                keyset_id_type: cdk_common::nut02::KeySetVersion::Version01,
+                final_expiry: None,
+                reason: None,
+            })
+            .await
+            .expect("rotate_keyset");
+
+        let message = |amount: u64| {
+            BlindedMessage::new(
+                Amount::from(amount),
+                keyset.id,
+                SecretKey::generate().public_key(),
+            )
+        };
+
+        signatory
+            .blind_sign(vec![message(8), message(2)])
+            .await
+            .expect("within the limit");
+
+        let result = signatory.blind_sign(vec![message(1)]).await;
+        assert!(
+            matches!(result, Err(Error::SigningLimitExceeded)),
+            "expected SigningLimitExceeded error, got: {:?}",
+            result
+        );
+
+        let usage = signatory.signing_limits().await.expect("signing_limits");
+        assert_eq!(usage.len(), 1);
+        assert_eq!(usage[0].signed, 10);
+    }
+
+    #[tokio::test]
+    async fn disabled_keyset_is_not_signed_with_nor_reactivated() {
+        let store: Arc<dyn database::MintKeysDatabase<Err = database::Error> + Send + Sync> =
+            Arc::new(
+                cdk_sqlite::mint::memory::empty()
+                    .await
+                    .expect("in-memory db"),
+            );
+        let supported_units = HashMap::from([(CurrencyUnit::Sat, (0, vec![1, 2, 4, 8]))]);
+        let signatory = DbSignatory::new(
+            store.clone(),
+            b"test-seed-for-unit-tests",
+            supported_units.clone(),
+            Default::default(),
+        )
+        .await
+        .expect("DbSignatory::new");
+
+        let keyset = signatory
+            .rotate_keyset(RotateKeyArguments {
+                unit: CurrencyUnit::Sat,
+                amounts: vec![1, 2, 4, 8],
+                input_fee_ppk: 0,
+                keyset_id_type: cdk_common::nut02::KeySetVersion::Version01,
+                final_expiry: None,
+                reason: None,
+            })
+            .await
+            .expect("rotate_keyset");
+
+        let secret = Secret::generate();
+        let (blinded_secret, r) = blind_message(secret.as_bytes(), None).expect("blind");
+        let signature = signatory
+            .blind_sign(vec![BlindedMessage::new(
+                Amount::from(4),
+                keyset.id,
+                blinded_secret,
+            )])
+            .await
+            .expect("blind_sign")
+            .remove(0);
+        let mint_key = keyset.keys.amount_key(Amount::from(4)).expect("key");
+        let c = unblind_message(&signature.c, &r, &mint_key).expect("unblind");
+        let proof = Proof::new(Amount::from(4), keyset.id, secret, c);
+
+        let disabled = signatory
+            .disable_keyset(keyset.id)
+            .await
+            .expect("disable_keyset");
+        assert!(!disabled.active);
+
+        let result = signatory
+            .blind_sign(vec![BlindedMessage::new(
+                Amount::from(1),
+                keyset.id,
+                SecretKey::generate().public_key(),
+            )])
+            .await;
+        assert!(
+            matches!(result, Err(Error::InactiveKeyset)),
+            "expected InactiveKeyset error, got: {:?}",
+            result
+        );
+        signatory
+            .verify_proofs(vec![proof])
+            .await
+            .expect("proofs of a disabled keyset still verify");
+
+        // A restart with the same configuration must not bring the keyset back
+        let restarted = DbSignatory::new(
+            store,
+            b"test-seed-for-unit-tests",
+            supported_units,
+            Default::default(),
+        )
+        .await
+        .expect("DbSignatory::new");
+        assert!(!restarted.keyset_info(keyset.id).await.expect("info").active);
+
+        let rotated = restarted
+            .rotate_keyset(RotateKeyArguments {
+                unit: CurrencyUnit::Sat,
+                amounts: vec![1, 2, 4, 8],
+                input_fee_ppk: 0,
+                keyset_id_type: cdk_common::nut02::KeySetVersion::Version01,
+                final_expiry: None,
+                reason: None,
+            })
+            .await
+            .expect("rotate_keyset");
+        assert_ne!(rotated.id, keyset.id);
+    }
+
+    #[tokio::test]
+    async fn networks_derive_distinct_keysets_from_the_same_seed() {
+        let mut ids = HashSet::new();
+        for network in [
+            Network::Bitcoin,
+            Network::Testnet,
+            Network::Signet,
+            Network::Regtest,
+        ] {
+            let signatory = DbSignatory::new_with_network(
+                Arc::new(
+                    cdk_sqlite::mint::memory::empty()
+                        .await
+                        .expect("in-memory db"),
+                ),
+                b"test-seed-for-unit-tests",
+                Default::default(),
+                Default::default(),
+                network,
+            )
+            .await
+            .expect("DbSignatory::new_with_network");
+
+            let keyset = signatory
+                .rotate_keyset(RotateKeyArguments {
+                    unit: CurrencyUnit::Sat,
+                    amounts: vec![1, 2, 4, 8],
+                    input_fee_ppk: 0,
+                    keyset_id_type: cdk_common::nut02::KeySetVersion::Version01,
+                    final_expiry: None,
+                    reason: None,
+                })
+                .await
+                .expect("rotate_keyset");
+            assert!(ids.insert(keyset.id), "{network} reused a keyset");
+        }
+
+        // Mainnet keeps deriving from the master key
+        let secp = Secp256k1::new();
+        assert_eq!(
+            network_xpriv(&secp, b"test-seed-for-unit-tests", Network::Bitcoin).unwrap(),
+            Xpriv::new_master(Network::Bitcoin, b"test-seed-for-unit-tests").unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn new_rejects_seed_that_does_not_match_active_keysets() {
+        let store: Arc<dyn database::MintKeysDatabase<Err = database::Error> + Send + Sync> =
+            Arc::new(
+                cdk_sqlite::mint::memory::empty()
+                    .await
+                    .expect("in-memory db"),
+            );
+
+        let signatory = DbSignatory::new(
+            store.clone(),
+            b"original-seed-for-unit-tests",
+            Default::default(),
+            Default::default(),
+        )
+        .await
+        .expect("DbSignatory::new");
+
+        let keyset = signatory
+            .rotate_keyset(RotateKeyArguments {
+                unit: CurrencyUnit::Sat,
+                amounts: vec![1, 2, 4, 8],
+                input_fee_ppk: 0,
+                keyset_id_type: cdk_common::nut02::KeySetVersion::Version01,
+                final_expiry: None,
+                reason: None,
+            })
+            .await
+            .expect("rotate_keyset");
+        drop(signatory);
+
+        // Same seed starts fine
+        DbSignatory::new(
+            store.clone(),
+            b"original-seed-for-unit-tests",
+            Default::default(),
+            Default::default(),
+        )
+        .await
+        .expect("same seed must be accepted");
+
+        let result = DbSignatory::new(
+            store,
+            b"another-seed-for-unit-tests",
+            Default::default(),
+            Default::default(),
+        )
+        .await;
+
+        assert!(
+            matches!(
+                result,
+                Err(Error::KeysetSeedMismatch { stored, .. }) if stored == keyset.id
+            ),
+            "expected KeysetSeedMismatch error"
+        );
+    }
+
+    #[tokio::test]
+    async fn supported_config_reports_configured_units() {
+        let store = Arc::new(
+            cdk_sqlite::mint::memory::empty()
+                .await
+                .expect("in-memory db"),
+        );
+        let custom_path = derivation_path_from_unit(CurrencyUnit::Usd, 7).unwrap();
+        let signatory = DbSignatory::new(
+            store,
+            b"test-seed-for-unit-tests",
+            HashMap::from([
+                (CurrencyUnit::Sat, (100, vec![1, 2, 4, 8])),
+                (CurrencyUnit::Usd, (0, vec![1, 2])),
+            ]),
+            HashMap::from([(CurrencyUnit::Usd, custom_path.clone())]),
+        )
+        .await
+        .expect("DbSignatory::new");
+
+        let config = signatory
+            .supported_config()
+            .await
+            .expect("supported_config");
+        assert_eq!(config.units.len(), 3);
+
+        let sat = config.unit(&CurrencyUnit::Sat).expect("sat config");
+        assert_eq!(sat.input_fee_ppk, 100);
+        assert_eq!(sat.max_order(), Some(4));
+        assert_eq!(sat.derivation_path, None);
+
+        let usd = config.unit(&CurrencyUnit::Usd).expect("usd config");
+        assert_eq!(usd.derivation_path, Some(custom_path.clone()));
+
+        // Auth is always supported
+        assert!(config.unit(&CurrencyUnit::Auth).is_some());
+
+        config
+            .ensure_unit(&CurrencyUnit::Sat, 100, &[1, 2, 4, 8], None)
+            .expect("matching config");
+        config
+            .ensure_unit(&CurrencyUnit::Usd, 0, &[1, 2], Some(&custom_path))
+            .expect("matching custom path");
+
+        for result in [
+            config.ensure_unit(&CurrencyUnit::Sat, 0, &[1, 2, 4, 8], None),
+            config.ensure_unit(&CurrencyUnit::Sat, 100, &[1, 2], None),
+            config.ensure_unit(&CurrencyUnit::Sat, 100, &[1, 2, 4, 8], Some(&custom_path)),
+            config.ensure_unit(&CurrencyUnit::Eur, 0, &[1, 2], None),
+        ] {
+            assert!(
+                matches!(result, Err(Error::SignatoryConfigMismatch { .. })),
+                "expected SignatoryConfigMismatch error"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn keyset_info_reports_derivation_of_rotated_keyset() {
+        let store = Arc::new(
+            cdk_sqlite::mint::memory::empty()
+                .await
+                .expect("in-memory db"),
+        );
+        let signatory = DbSignatory::new(
+            store,
+            b"test-seed-for-unit-tests",
+            Default::default(),
+            Default::default(),
+        )
+        .await
+        .expect("DbSignatory::new");
+
+        let keyset = signatory
+            .rotate_keyset(RotateKeyArguments {
+                unit: CurrencyUnit::Sat,
+                amounts: vec![1, 2, 4, 8],
+                input_fee_ppk: 10,
+                keyset_id_type: cdk_common::nut02::KeySetVersion::Version00,
+                final_expiry: None,
+                reason: None,
+            })
+            .await
+            .expect("rotate_keyset");
+
+        let pubkeys = signatory
+            .keyset_pubkeys(keyset.id)
+            .await
+            .expect("keyset_pubkeys");
+        assert_eq!(pubkeys.keys, keyset.keys);
+
+        let info = signatory.keyset_info(keyset.id).await.expect("keyset_info");
+        assert!(info.active);
+        assert_eq!(info.input_fee_ppk, 10);
+        assert_eq!(info.amounts, vec![1, 2, 4, 8]);
+        assert_eq!(
+            info.derivation_path,
+            derivation_path_from_unit(CurrencyUnit::Sat, 1).unwrap()
+        );
+
+        let unknown = Id::from_str("009a1f293253e41e").unwrap();
+        assert!(matches!(
+            signatory.keyset_info(unknown).await,
+            Err(Error::UnknownKeySet)
+        ));
+    }
+
+    #[tokio::test]
+    async fn keyset_config_update_rotates_only_on_change() {
+        let store = Arc::new(
+            cdk_sqlite::mint::memory::empty()
+                .await
+                .expect("in-memory db"),
+        );
+        let signatory = DbSignatory::new(
+            store,
+            b"test-seed-for-unit-tests",
+            HashMap::from([(CurrencyUnit::Sat, (0, vec![1, 2, 4, 8]))]),
+            Default::default(),
+        )
+        .await
+        .expect("DbSignatory::new");
+
+        let update = |input_fee_ppk, amounts: Vec<u64>| UpdateKeysetConfigArguments {
+            unit: CurrencyUnit::Sat,
+            amounts,
+            input_fee_ppk,
+        };
+
+        let current = signatory
+            .update_keyset_config(update(0, vec![1, 2, 4, 8]))
+            .await
+            .expect("unchanged config");
+        let unchanged = signatory
+            .update_keyset_config(update(0, vec![]))
+            .await
+            .expect("unchanged fee");
+        assert_eq!(unchanged.id, current.id);
+
+        let rotated = signatory
+            .update_keyset_config(update(100, vec![]))
+            .await
+            .expect("new fee");
+        assert_ne!(rotated.id, current.id);
+        assert!(rotated.active);
+        assert_eq!(rotated.input_fee_ppk, 100);
+        assert_eq!(rotated.amounts, vec![1, 2, 4, 8]);
+        assert_eq!(rotated.version, current.version + 1);
+
+        let config = signatory
+            .supported_config()
+            .await
+            .expect("supported_config");
+        let sat = config.unit(&CurrencyUnit::Sat).expect("sat config");
+        assert_eq!(sat.input_fee_ppk, 100);
+
+        let again = signatory
+            .update_keyset_config(update(100, vec![1, 2, 4, 8]))
+            .await
+            .expect("same config");
+        assert_eq!(again.id, rotated.id);
+    }
+
+    #[tokio::test]
+    async fn rotations_are_appended_to_signed_log() {
+        let store = Arc::new(
+            cdk_sqlite::mint::memory::empty()
+                .await
+                .expect("in-memory db"),
+        );
+        let signatory = DbSignatory::new(
+            store,
+            b"test-seed-for-unit-tests",
+            HashMap::from([(CurrencyUnit::Sat, (0, vec![1, 2, 4, 8]))]),
+            Default::default(),
+        )
+        .await
+        .expect("DbSignatory::new");
+
+        let initial = signatory.keysets().await.expect("keysets").keysets;
+        let initial = initial
+            .iter()
+            .find(|keyset| keyset.active && keyset.unit == CurrencyUnit::Sat)
+            .expect("active sat keyset");
+        assert!(signatory.rotation_log().await.expect("log").is_empty());
+
+        let updated = signatory
+            .update_keyset_config(UpdateKeysetConfigArguments {
+                unit: CurrencyUnit::Sat,
+                amounts: vec![],
+                input_fee_ppk: 100,
+            })
+            .await
+            .expect("update_keyset_config");
+        let rotated = signatory
+            .rotate_keyset(RotateKeyArguments {
+                unit: CurrencyUnit::Sat,
+                amounts: vec![],
+                input_fee_ppk: 100,
+                keyset_id_type: cdk_common::nut02::KeySetVersion::Version01,
+                final_expiry: None,
+                reason: None,
+            })
+            .await
+            .expect("rotate_keyset");
+
+        let log = signatory.rotation_log().await.expect("log");
+        cdk_common::rotation_log::verify_rotation_log(&log).expect("valid log");
+        assert_eq!(log.len(), 2);
+
+        assert_eq!(log[0].attestation.old_keyset_id, Some(initial.id));
+        assert_eq!(log[0].attestation.new_keyset_id, updated.id);
+        assert_eq!(
+            log[0].attestation.reason.as_deref(),
+            Some("Keyset config updated")
+        );
+        assert_eq!(log[1].attestation.old_keyset_id, Some(updated.id));
+        assert_eq!(log[1].attestation.new_keyset_id, rotated.id);
+        assert_eq!(log[1].attestation.reason, None);
+
+        // Signed by a key of the seed, which is not the key of any keyset
+        let signer = rotation_attestation_key(&signatory.secp_ctx, signatory.xpriv).public_key();
+        assert!(log.iter().all(|entry| entry.signer == signer));
+        assert_ne!(signer, signatory.xpub);
+
+        // Keyset exports are signed by the same key, with the full derivation paths
+        let export = signatory.export_keysets().await.expect("export_keysets");
+        export.verify().expect("valid export");
+        assert_eq!(export.signer, signer);
+        let exported = export
+            .export
+            .keysets
+            .iter()
+            .find(|exported| exported.keyset.id == rotated.id)
+            .expect("rotated keyset is exported");
+        let info = signatory
+            .keyset_info(rotated.id)
+            .await
+            .expect("keyset_info");
+        assert_eq!(exported.derivation_path, info.derivation_path);
+        assert_eq!(exported.derivation_path_index, info.derivation_path_index);
+    }
+
     #[test]
     fn mint_mod_generate_keyset_from_seed() {
         let seed = hex::decode("0000000000000000000000000000000000000000000000000000000000000001")
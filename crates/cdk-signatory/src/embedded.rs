@@ -1,12 +1,20 @@
 //! Run a Signatory in a embedded environment, inside a CDK instance, but this wrapper makes sure to
 //! run the Signatory in another thread, isolated form the main CDK, communicating through messages
 use std::sync::Arc;
+use std::time::Duration;
 
-use cdk_common::{BlindSignature, BlindedMessage, Error, Proof};
-use tokio::sync::{mpsc, oneshot};
-use tokio::task::JoinHandle;
+use cdk_common::keyset_export::SignedKeysetExport;
+use cdk_common::mint::MintKeySetInfo;
+use cdk_common::rotation_log::SignedRotationAttestation;
+use cdk_common::{BlindSignature, BlindedMessage, Error, Id, Proof};
+use tokio::sync::{mpsc, oneshot, Mutex};
+use tokio::task::JoinSet;
+use tokio_util::sync::CancellationToken;
 
-use crate::signatory::{RotateKeyArguments, Signatory, SignatoryKeySet, SignatoryKeysets};
+use crate::signatory::{
+    RotateKeyArguments, Signatory, SignatoryConfig, SignatoryKeySet, SignatoryKeysets,
+    SigningLimitUsage, UpdateKeysetConfigArguments,
+};
 
 enum Request {
     BlindSign(
@@ -17,12 +25,70 @@ enum Request {
     ),
     VerifyProof((Vec<Proof>, oneshot::Sender<Result<(), Error>>)),
     Keysets(oneshot::Sender<Result<SignatoryKeysets, Error>>),
+    KeysetPubkeys((Id, oneshot::Sender<Result<SignatoryKeySet, Error>>)),
+    KeysetInfo((Id, oneshot::Sender<Result<MintKeySetInfo, Error>>)),
+    SupportedConfig(oneshot::Sender<Result<SignatoryConfig, Error>>),
+    SigningLimits(oneshot::Sender<Result<Vec<SigningLimitUsage>, Error>>),
+    RotationLog(oneshot::Sender<Result<Vec<SignedRotationAttestation>, Error>>),
+    ExportKeysets(oneshot::Sender<Result<SignedKeysetExport, Error>>),
     RotateKeyset(
         (
             RotateKeyArguments,
             oneshot::Sender<Result<SignatoryKeySet, Error>>,
         ),
     ),
+    UpdateKeysetConfig(
+        (
+            UpdateKeysetConfigArguments,
+            oneshot::Sender<Result<SignatoryKeySet, Error>>,
+        ),
+    ),
+    DisableKeyset((Id, oneshot::Sender<Result<SignatoryKeySet, Error>>)),
+}
+
+impl Request {
+    /// Whether the caller stopped waiting for the response, after timing out
+    fn is_abandoned(&self) -> bool {
+        match self {
+            Request::BlindSign((_, response)) => response.is_closed(),
+            Request::VerifyProof((_, response)) => response.is_closed(),
+            Request::Keysets(response) => response.is_closed(),
+            Request::KeysetPubkeys((_, response)) => response.is_closed(),
+            Request::KeysetInfo((_, response)) => response.is_closed(),
+            Request::SupportedConfig(response) => response.is_closed(),
+            Request::SigningLimits(response) => response.is_closed(),
+            Request::RotationLog(response) => response.is_closed(),
+            Request::ExportKeysets(response) => response.is_closed(),
+            Request::RotateKeyset((_, response)) => response.is_closed(),
+            Request::UpdateKeysetConfig((_, response)) => response.is_closed(),
+            Request::DisableKeyset((_, response)) => response.is_closed(),
+        }
+    }
+}
+
+/// Queue and concurrency limits of a [`Service`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ServiceConfig {
+    /// Requests waiting for a worker. Requests made while the queue is full wait up to
+    /// `enqueue_timeout` for room before failing with [`Error::Overloaded`]
+    pub queue_depth: usize,
+    /// Time a request waits for room in a full queue, none to fail right away
+    pub enqueue_timeout: Option<Duration>,
+    /// Workers handling requests concurrently
+    pub workers: usize,
+    /// Time a request may take, queueing included, before failing with [`Error::Timeout`]
+    pub request_timeout: Option<Duration>,
+}
+
+impl Default for ServiceConfig {
+    fn default() -> Self {
+        Self {
+            queue_depth: 10_000,
+            enqueue_timeout: Some(Duration::from_millis(500)),
+            workers: 8,
+            request_timeout: Some(Duration::from_secs(30)),
+        }
+    }
 }
 
 /// Creates a service-like to wrap an implementation of the Signatory
@@ -30,17 +96,19 @@ enum Request {
 /// This implements the actor model, ensuring the Signatory and their private key is moved from the
 /// main thread to their own tokio task, and communicates with the main program by passing messages,
 /// an extra layer of security to move the keys to another layer.
+///
+/// Requests are handled by a fixed pool of workers, see [`ServiceConfig`].
 #[allow(missing_debug_implementations)]
 pub struct Service {
     pipeline: mpsc::Sender<Request>,
-    runner: Option<JoinHandle<()>>,
+    enqueue_timeout: Option<Duration>,
+    request_timeout: Option<Duration>,
+    workers: JoinSet<()>,
 }
 
 impl Drop for Service {
     fn drop(&mut self) {
-        if let Some(runner) = self.runner.take() {
-            runner.abort();
-        }
+        self.workers.abort_all();
     }
 }
 
@@ -48,49 +116,206 @@ impl Service {
     /// Takes a signatory and spawns it into a Tokio task, isolating its implementation with the
     /// main thread, communicating with it through messages
     pub fn new(handler: Arc<dyn Signatory + Send + Sync>) -> Self {
-        let (tx, rx) = mpsc::channel(10_000);
-        let runner = Some(tokio::spawn(Self::runner(rx, handler)));
+        Self::new_with_shutdown(handler, CancellationToken::new())
+    }
+
+    /// Like [`Service::new`], but the signatory task stops once `shutdown` is cancelled
+    ///
+    /// Requests made after that fail as if the signatory was unreachable.
+    pub fn new_with_shutdown(
+        handler: Arc<dyn Signatory + Send + Sync>,
+        shutdown: CancellationToken,
+    ) -> Self {
+        Self::new_with_config(handler, ServiceConfig::default(), shutdown)
+    }
+
+    /// Like [`Service::new_with_shutdown`], with the queue and concurrency limits of `config`
+    pub fn new_with_config(
+        handler: Arc<dyn Signatory + Send + Sync>,
+        config: ServiceConfig,
+        shutdown: CancellationToken,
+    ) -> Self {
+        let (tx, rx) = mpsc::channel(config.queue_depth.max(1));
+        let receiver = Arc::new(Mutex::new(rx));
+
+        let mut workers = JoinSet::new();
+        for _ in 0..config.workers.max(1) {
+            workers.spawn(Self::worker(
+                Arc::clone(&receiver),
+                Arc::clone(&handler),
+                shutdown.clone(),
+            ));
+        }
 
         Self {
             pipeline: tx,
-            runner,
+            enqueue_timeout: config.enqueue_timeout,
+            request_timeout: config.request_timeout,
+            workers,
         }
     }
 
     #[tracing::instrument(skip_all)]
-    async fn runner(
-        mut receiver: mpsc::Receiver<Request>,
+    async fn worker(
+        receiver: Arc<Mutex<mpsc::Receiver<Request>>>,
         handler: Arc<dyn Signatory + Send + Sync>,
+        shutdown: CancellationToken,
     ) {
-        while let Some(request) = receiver.recv().await {
-            match request {
-                Request::BlindSign((blinded_message, response)) => {
-                    let output = handler.blind_sign(blinded_message).await;
-                    if let Err(err) = response.send(output) {
-                        tracing::error!("Error sending response: {:?}", err);
-                    }
+        loop {
+            let request = tokio::select! {
+                biased;
+                _ = shutdown.cancelled() => {
+                    tracing::info!("Signatory shutting down");
+                    break;
+                }
+                request = async { receiver.lock().await.recv().await } => match request {
+                    Some(request) => request,
+                    None => break,
+                },
+            };
+
+            if request.is_abandoned() {
+                tracing::debug!("Dropping signatory request abandoned by its caller");
+                continue;
+            }
+
+            Self::handle(handler.as_ref(), request).await;
+        }
+    }
+
+    async fn handle(handler: &(dyn Signatory + Send + Sync), request: Request) {
+        match request {
+            Request::BlindSign((blinded_message, response)) => {
+                let output = handler.blind_sign(blinded_message).await;
+                if let Err(err) = response.send(output) {
+                    tracing::error!("Error sending response: {:?}", err);
+                }
+            }
+            Request::VerifyProof((proof, response)) => {
+                let output = handler.verify_proofs(proof).await;
+                if let Err(err) = response.send(output) {
+                    tracing::error!("Error sending response: {:?}", err);
+                }
+            }
+            Request::Keysets(response) => {
+                let output = handler.keysets().await;
+                if let Err(err) = response.send(output) {
+                    tracing::error!("Error sending response: {:?}", err);
+                }
+            }
+            Request::KeysetPubkeys((id, response)) => {
+                let output = handler.keyset_pubkeys(id).await;
+                if let Err(err) = response.send(output) {
+                    tracing::error!("Error sending response: {:?}", err);
+                }
+            }
+            Request::KeysetInfo((id, response)) => {
+                let output = handler.keyset_info(id).await;
+                if let Err(err) = response.send(output) {
+                    tracing::error!("Error sending response: {:?}", err);
+                }
+            }
+            Request::SupportedConfig(response) => {
+                let output = handler.supported_config().await;
+                if let Err(err) = response.send(output) {
+                    tracing::error!("Error sending response: {:?}", err);
+                }
+            }
+            Request::SigningLimits(response) => {
+                let output = handler.signing_limits().await;
+                if let Err(err) = response.send(output) {
+                    tracing::error!("Error sending response: {:?}", err);
                 }
-                Request::VerifyProof((proof, response)) => {
-                    let output = handler.verify_proofs(proof).await;
-                    if let Err(err) = response.send(output) {
-                        tracing::error!("Error sending response: {:?}", err);
-                    }
+            }
+            Request::RotationLog(response) => {
+                let output = handler.rotation_log().await;
+                if let Err(err) = response.send(output) {
+                    tracing::error!("Error sending response: {:?}", err);
                 }
-                Request::Keysets(response) => {
-                    let output = handler.keysets().await;
-                    if let Err(err) = response.send(output) {
-                        tracing::error!("Error sending response: {:?}", err);
-                    }
+            }
+            Request::ExportKeysets(response) => {
+                let output = handler.export_keysets().await;
+                if let Err(err) = response.send(output) {
+                    tracing::error!("Error sending response: {:?}", err);
                 }
-                Request::RotateKeyset((args, response)) => {
-                    let output = handler.rotate_keyset(args).await;
-                    if let Err(err) = response.send(output) {
-                        tracing::error!("Error sending response: {:?}", err);
-                    }
+            }
+            Request::RotateKeyset((args, response)) => {
+                let output = handler.rotate_keyset(args).await;
+                if let Err(err) = response.send(output) {
+                    tracing::error!("Error sending response: {:?}", err);
+                }
+            }
+            Request::UpdateKeysetConfig((args, response)) => {
+                let output = handler.update_keyset_config(args).await;
+                if let Err(err) = response.send(output) {
+                    tracing::error!("Error sending response: {:?}", err);
+                }
+            }
+            Request::DisableKeyset((id, response)) => {
+                let output = handler.disable_keyset(id).await;
+                if let Err(err) = response.send(output) {
+                    tracing::error!("Error sending response: {:?}", err);
                 }
             }
         }
     }
+
+    /// Put `request` in the queue, waiting up to the enqueue timeout for room if it is full
+    async fn enqueue(&self, request: Request) -> Result<(), Error> {
+        let request = match self.pipeline.try_send(request) {
+            Ok(()) => return Ok(()),
+            Err(mpsc::error::TrySendError::Full(request)) => request,
+            Err(err) => return Err(Error::SendError(err.to_string())),
+        };
+
+        let queued = match self.enqueue_timeout {
+            Some(wait) if !wait.is_zero() => {
+                tokio::time::timeout(wait, self.pipeline.send(request)).await
+            }
+            _ => {
+                tracing::warn!("Signatory queue is full, shedding request");
+                #[cfg(feature = "prometheus")]
+                cdk_prometheus::METRICS.record_signatory_queue_full("shed");
+                return Err(Error::Overloaded);
+            }
+        };
+
+        match queued {
+            Ok(Ok(())) => {
+                #[cfg(feature = "prometheus")]
+                cdk_prometheus::METRICS.record_signatory_queue_full("waited");
+                Ok(())
+            }
+            Ok(Err(err)) => Err(Error::SendError(err.to_string())),
+            Err(_) => {
+                tracing::warn!("Signatory queue stayed full, shedding request");
+                #[cfg(feature = "prometheus")]
+                cdk_prometheus::METRICS.record_signatory_queue_full("shed");
+                Err(Error::Overloaded)
+            }
+        }
+    }
+
+    /// Queue the request made by `request` and wait for its response
+    ///
+    /// Fails with [`Error::Overloaded`] if the queue stays full for the enqueue timeout, and
+    /// with [`Error::Timeout`] if no response comes within the request timeout.
+    async fn call<T>(
+        &self,
+        request: impl FnOnce(oneshot::Sender<Result<T, Error>>) -> Request,
+    ) -> Result<T, Error> {
+        let (tx, rx) = oneshot::channel();
+        self.enqueue(request(tx)).await?;
+
+        let response = match self.request_timeout {
+            Some(timeout) => tokio::time::timeout(timeout, rx)
+                .await
+                .map_err(|_| Error::Timeout)?,
+            None => rx.await,
+        };
+
+        response.map_err(|e| Error::RecvError(e.to_string()))?
+    }
 }
 
 #[async_trait::async_trait]
@@ -104,45 +329,235 @@ impl Signatory for Service {
         &self,
         blinded_messages: Vec<BlindedMessage>,
     ) -> Result<Vec<BlindSignature>, Error> {
-        let (tx, rx) = oneshot::channel();
-        self.pipeline
-            .send(Request::BlindSign((blinded_messages, tx)))
+        self.call(|tx| Request::BlindSign((blinded_messages, tx)))
             .await
-            .map_err(|e| Error::SendError(e.to_string()))?;
-
-        rx.await.map_err(|e| Error::RecvError(e.to_string()))?
     }
 
     #[tracing::instrument(skip_all)]
     async fn verify_proofs(&self, proofs: Vec<Proof>) -> Result<(), Error> {
-        let (tx, rx) = oneshot::channel();
-        self.pipeline
-            .send(Request::VerifyProof((proofs, tx)))
-            .await
-            .map_err(|e| Error::SendError(e.to_string()))?;
-
-        rx.await.map_err(|e| Error::RecvError(e.to_string()))?
+        self.call(|tx| Request::VerifyProof((proofs, tx))).await
     }
 
     #[tracing::instrument(skip_all)]
     async fn keysets(&self) -> Result<SignatoryKeysets, Error> {
-        let (tx, rx) = oneshot::channel();
-        self.pipeline
-            .send(Request::Keysets(tx))
-            .await
-            .map_err(|e| Error::SendError(e.to_string()))?;
+        self.call(Request::Keysets).await
+    }
 
-        rx.await.map_err(|e| Error::RecvError(e.to_string()))?
+    #[tracing::instrument(skip(self))]
+    async fn keyset_pubkeys(&self, id: Id) -> Result<SignatoryKeySet, Error> {
+        self.call(|tx| Request::KeysetPubkeys((id, tx))).await
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn keyset_info(&self, id: Id) -> Result<MintKeySetInfo, Error> {
+        self.call(|tx| Request::KeysetInfo((id, tx))).await
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn supported_config(&self) -> Result<SignatoryConfig, Error> {
+        self.call(Request::SupportedConfig).await
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn signing_limits(&self) -> Result<Vec<SigningLimitUsage>, Error> {
+        self.call(Request::SigningLimits).await
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn rotation_log(&self) -> Result<Vec<SignedRotationAttestation>, Error> {
+        self.call(Request::RotationLog).await
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn export_keysets(&self) -> Result<SignedKeysetExport, Error> {
+        self.call(Request::ExportKeysets).await
     }
 
     #[tracing::instrument(skip(self))]
     async fn rotate_keyset(&self, args: RotateKeyArguments) -> Result<SignatoryKeySet, Error> {
-        let (tx, rx) = oneshot::channel();
-        self.pipeline
-            .send(Request::RotateKeyset((args, tx)))
+        self.call(|tx| Request::RotateKeyset((args, tx))).await
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn update_keyset_config(
+        &self,
+        args: UpdateKeysetConfigArguments,
+    ) -> Result<SignatoryKeySet, Error> {
+        self.call(|tx| Request::UpdateKeysetConfig((args, tx)))
             .await
-            .map_err(|e| Error::SendError(e.to_string()))?;
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn disable_keyset(&self, id: Id) -> Result<SignatoryKeySet, Error> {
+        self.call(|tx| Request::DisableKeyset((id, tx))).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use tokio::sync::Semaphore;
+
+    use super::*;
+
+    /// Signatory whose signing waits for a permit of `gate`
+    struct GatedSignatory {
+        gate: Semaphore,
+        calls: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl Signatory for GatedSignatory {
+        fn name(&self) -> String {
+            "gated".to_owned()
+        }
+
+        async fn blind_sign(
+            &self,
+            _blinded_messages: Vec<BlindedMessage>,
+        ) -> Result<Vec<BlindSignature>, Error> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            self.gate
+                .acquire()
+                .await
+                .map_err(|e| Error::Custom(e.to_string()))?
+                .forget();
+            Ok(Vec::new())
+        }
+
+        async fn verify_proofs(&self, _proofs: Vec<Proof>) -> Result<(), Error> {
+            Ok(())
+        }
+
+        async fn keysets(&self) -> Result<SignatoryKeysets, Error> {
+            Err(Error::Custom("unsupported".to_owned()))
+        }
+
+        async fn supported_config(&self) -> Result<SignatoryConfig, Error> {
+            Ok(SignatoryConfig::default())
+        }
+
+        async fn signing_limits(&self) -> Result<Vec<SigningLimitUsage>, Error> {
+            Ok(Vec::new())
+        }
+
+        async fn rotate_keyset(&self, _args: RotateKeyArguments) -> Result<SignatoryKeySet, Error> {
+            Err(Error::Custom("unsupported".to_owned()))
+        }
+
+        async fn disable_keyset(&self, _id: Id) -> Result<SignatoryKeySet, Error> {
+            Err(Error::Custom("unsupported".to_owned()))
+        }
+    }
+
+    fn gated(config: ServiceConfig) -> (Arc<GatedSignatory>, Arc<Service>) {
+        let signatory = Arc::new(GatedSignatory {
+            gate: Semaphore::new(0),
+            calls: AtomicUsize::new(0),
+        });
+        let service = Service::new_with_config(signatory.clone(), config, CancellationToken::new());
+        (signatory, Arc::new(service))
+    }
+
+    #[tokio::test]
+    async fn full_queue_sheds_requests_as_overloaded() {
+        let (signatory, service) = gated(ServiceConfig {
+            queue_depth: 1,
+            enqueue_timeout: None,
+            workers: 1,
+            request_timeout: None,
+        });
+
+        // The only worker is busy with the first request, the second one fills the queue
+        let busy = tokio::spawn({
+            let service = service.clone();
+            async move { service.blind_sign(Vec::new()).await }
+        });
+        while signatory.calls.load(Ordering::SeqCst) == 0 {
+            tokio::task::yield_now().await;
+        }
+        let queued = tokio::spawn({
+            let service = service.clone();
+            async move { service.blind_sign(Vec::new()).await }
+        });
+        while service.pipeline.capacity() > 0 {
+            tokio::task::yield_now().await;
+        }
+
+        assert!(matches!(
+            service.blind_sign(Vec::new()).await,
+            Err(Error::Overloaded)
+        ));
+
+        signatory.gate.add_permits(2);
+        busy.await.expect("join").expect("signed");
+        queued.await.expect("join").expect("signed");
+        assert_eq!(signatory.calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn full_queue_waits_for_room_before_shedding() {
+        let (signatory, service) = gated(ServiceConfig {
+            queue_depth: 1,
+            enqueue_timeout: Some(Duration::from_millis(50)),
+            workers: 1,
+            request_timeout: None,
+        });
+
+        let busy = tokio::spawn({
+            let service = service.clone();
+            async move { service.blind_sign(Vec::new()).await }
+        });
+        while signatory.calls.load(Ordering::SeqCst) == 0 {
+            tokio::task::yield_now().await;
+        }
+        let queued = tokio::spawn({
+            let service = service.clone();
+            async move { service.blind_sign(Vec::new()).await }
+        });
+        while service.pipeline.capacity() > 0 {
+            tokio::task::yield_now().await;
+        }
+
+        // Nothing leaves the queue in time
+        assert!(matches!(
+            service.blind_sign(Vec::new()).await,
+            Err(Error::Overloaded)
+        ));
+
+        // Room is made while the request waits, so it goes through
+        let waiting = tokio::spawn({
+            let service = service.clone();
+            async move { service.blind_sign(Vec::new()).await }
+        });
+        signatory.gate.add_permits(3);
+        busy.await.expect("join").expect("signed");
+        queued.await.expect("join").expect("signed");
+        waiting.await.expect("join").expect("signed");
+        assert_eq!(signatory.calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn slow_request_times_out_and_is_not_handled_once_abandoned() {
+        let (signatory, service) = gated(ServiceConfig {
+            queue_depth: 10,
+            enqueue_timeout: None,
+            workers: 1,
+            request_timeout: Some(Duration::from_millis(20)),
+        });
+
+        // The first request holds the worker past its timeout, the second one times out queued
+        let (first, second) = tokio::join!(
+            service.blind_sign(Vec::new()),
+            service.blind_sign(Vec::new())
+        );
+        assert!(matches!(first, Err(Error::Timeout)));
+        assert!(matches!(second, Err(Error::Timeout)));
 
-        rx.await.map_err(|e| Error::RecvError(e.to_string()))?
+        // Once the worker is free the abandoned request is dropped without signing
+        signatory.gate.add_permits(1);
+        service.verify_proofs(Vec::new()).await.expect("verified");
+        assert_eq!(signatory.calls.load(Ordering::SeqCst), 1);
     }
 }
@@ -0,0 +1,549 @@
+//! Failover between signatories
+//!
+//! [`FailoverSignatory`] wraps an ordered list of signatories holding the same keys, e.g. a
+//! primary and a standby remote signer, and sends every request to the first healthy one. A
+//! request failing with [`Error::SignatoryUnavailable`] is retried on the next signatory, any
+//! other error is the answer of a reachable signatory and is returned as is.
+//!
+//! A signatory failing `failure_threshold` requests in a row is marked unhealthy and tried after
+//! the healthy ones, until `retry_after_secs` passed and a request gets to probe it again. A
+//! mint keeps swapping while one of its signers restarts.
+use std::future::Future;
+use std::sync::Arc;
+
+use cdk_common::keyset_export::SignedKeysetExport;
+use cdk_common::mint::MintKeySetInfo;
+use cdk_common::rotation_log::SignedRotationAttestation;
+use cdk_common::util::unix_time;
+use cdk_common::{BlindSignature, BlindedMessage, Error, Id, Proof};
+use tokio::sync::Mutex;
+
+use crate::signatory::{
+    RotateKeyArguments, Signatory, SignatoryConfig, SignatoryKeySet, SignatoryKeysets,
+    SigningLimitUsage,
+};
+
+/// Consecutive failures after which a signatory is marked unhealthy, by default
+const DEFAULT_FAILURE_THRESHOLD: u32 = 3;
+
+/// Seconds an unhealthy signatory is tried last before it is probed again, by default
+const DEFAULT_RETRY_AFTER_SECS: u64 = 30;
+
+/// Health of one of the signatories of a [`FailoverSignatory`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatoryHealth {
+    /// Name of the signatory
+    pub name: String,
+    /// Whether requests are sent to the signatory before the unhealthy ones
+    pub healthy: bool,
+    /// Requests the signatory failed in a row
+    pub consecutive_failures: u32,
+    /// Last reason the signatory could not be reached
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct Health {
+    consecutive_failures: u32,
+    unhealthy_since: Option<u64>,
+    last_error: Option<String>,
+}
+
+struct Backend {
+    name: String,
+    signatory: Arc<dyn Signatory + Send + Sync>,
+    health: Mutex<Health>,
+}
+
+/// Signatory falling over to the next of its signatories when one can not be reached
+#[allow(missing_debug_implementations)]
+pub struct FailoverSignatory {
+    backends: Vec<Backend>,
+    failure_threshold: u32,
+    retry_after_secs: u64,
+}
+
+impl FailoverSignatory {
+    /// Send requests to `signatories`, in order of preference
+    ///
+    /// Every signatory must hold the same keys, a request may be answered by any of them.
+    pub fn new(signatories: Vec<Arc<dyn Signatory + Send + Sync>>) -> Result<Self, Error> {
+        if signatories.is_empty() {
+            return Err(Error::Custom(
+                "A failover signatory needs at least one signatory".to_owned(),
+            ));
+        }
+
+        let backends: Vec<Backend> = signatories
+            .into_iter()
+            .map(|signatory| Backend {
+                name: signatory.name(),
+                signatory,
+                health: Default::default(),
+            })
+            .collect();
+
+        #[cfg(feature = "prometheus")]
+        for backend in &backends {
+            cdk_prometheus::METRICS.set_signatory_healthy(&backend.name, true);
+        }
+
+        Ok(Self {
+            backends,
+            failure_threshold: DEFAULT_FAILURE_THRESHOLD,
+            retry_after_secs: DEFAULT_RETRY_AFTER_SECS,
+        })
+    }
+
+    /// Mark a signatory unhealthy after `failure_threshold` consecutive failures, at least one
+    pub fn with_failure_threshold(mut self, failure_threshold: u32) -> Self {
+        self.failure_threshold = failure_threshold.max(1);
+        self
+    }
+
+    /// Probe an unhealthy signatory again `retry_after_secs` after its last failure
+    pub fn with_retry_after_secs(mut self, retry_after_secs: u64) -> Self {
+        self.retry_after_secs = retry_after_secs;
+        self
+    }
+
+    /// Health of every signatory, in order of preference
+    pub async fn health(&self) -> Vec<SignatoryHealth> {
+        let mut health = Vec::with_capacity(self.backends.len());
+        for backend in &self.backends {
+            let state = backend.health.lock().await;
+            health.push(SignatoryHealth {
+                name: backend.name.clone(),
+                healthy: state.unhealthy_since.is_none(),
+                consecutive_failures: state.consecutive_failures,
+                last_error: state.last_error.clone(),
+            });
+        }
+        health
+    }
+
+    /// Indexes of the backends to try, the healthy ones and those due a probe first
+    async fn order(&self) -> Vec<usize> {
+        let now = unix_time();
+        let mut order = Vec::with_capacity(self.backends.len());
+        let mut unhealthy = Vec::new();
+
+        for (index, backend) in self.backends.iter().enumerate() {
+            match backend.health.lock().await.unhealthy_since {
+                Some(since) if since.saturating_add(self.retry_after_secs) > now => {
+                    unhealthy.push(index)
+                }
+                _ => order.push(index),
+            }
+        }
+
+        order.extend(unhealthy);
+        order
+    }
+
+    async fn record_success(&self, backend: &Backend) {
+        let mut health = backend.health.lock().await;
+        if health.unhealthy_since.take().is_some() {
+            tracing::info!("Signatory {} is reachable again", backend.name);
+
+            #[cfg(feature = "prometheus")]
+            cdk_prometheus::METRICS.set_signatory_healthy(&backend.name, true);
+        }
+        health.consecutive_failures = 0;
+    }
+
+    async fn record_failure(&self, backend: &Backend, reason: &str) {
+        let mut health = backend.health.lock().await;
+        health.consecutive_failures = health.consecutive_failures.saturating_add(1);
+        health.last_error = Some(reason.to_owned());
+
+        if health.consecutive_failures >= self.failure_threshold {
+            if health.unhealthy_since.is_none() {
+                tracing::warn!(
+                    "Marking signatory {} unhealthy after {} failures: {}",
+                    backend.name,
+                    health.consecutive_failures,
+                    reason
+                );
+
+                #[cfg(feature = "prometheus")]
+                cdk_prometheus::METRICS.set_signatory_healthy(&backend.name, false);
+            }
+            health.unhealthy_since = Some(unix_time());
+        }
+    }
+
+    /// Run `request` on the signatories in order until one of them can be reached
+    async fn call<T, F, Fut>(&self, operation: &'static str, request: F) -> Result<T, Error>
+    where
+        F: Fn(Arc<dyn Signatory + Send + Sync>) -> Fut + Send + Sync,
+        Fut: Future<Output = Result<T, Error>> + Send,
+        T: Send,
+    {
+        let order = self.order().await;
+        let mut last_error = None;
+
+        for (attempt, index) in order.iter().enumerate() {
+            let backend = &self.backends[*index];
+
+            match request(backend.signatory.clone()).await {
+                Err(Error::SignatoryUnavailable(reason)) => {
+                    #[cfg(feature = "prometheus")]
+                    cdk_prometheus::METRICS.record_signatory_request(
+                        &backend.name,
+                        operation,
+                        false,
+                    );
+
+                    self.record_failure(backend, &reason).await;
+
+                    if attempt + 1 < order.len() {
+                        tracing::warn!(
+                            "Signatory {} unavailable for {}, failing over: {}",
+                            backend.name,
+                            operation,
+                            reason
+                        );
+
+                        #[cfg(feature = "prometheus")]
+                        cdk_prometheus::METRICS.record_signatory_failover(&backend.name);
+                    }
+
+                    last_error = Some(Error::SignatoryUnavailable(reason));
+                }
+                result => {
+                    #[cfg(feature = "prometheus")]
+                    cdk_prometheus::METRICS.record_signatory_request(
+                        &backend.name,
+                        operation,
+                        result.is_ok(),
+                    );
+
+                    self.record_success(backend).await;
+                    return result;
+                }
+            }
+        }
+
+        tracing::error!("No signatory could be reached for {}", operation);
+        Err(last_error
+            .unwrap_or_else(|| Error::SignatoryUnavailable("No signatory configured".to_owned())))
+    }
+}
+
+#[async_trait::async_trait]
+impl Signatory for FailoverSignatory {
+    fn name(&self) -> String {
+        self.backends
+            .first()
+            .map(|backend| backend.name.clone())
+            .unwrap_or_default()
+    }
+
+    async fn blind_sign(
+        &self,
+        blinded_messages: Vec<BlindedMessage>,
+    ) -> Result<Vec<BlindSignature>, Error> {
+        self.blind_sign_as(blinded_messages, None).await
+    }
+
+    async fn blind_sign_as(
+        &self,
+        blinded_messages: Vec<BlindedMessage>,
+        caller: Option<String>,
+    ) -> Result<Vec<BlindSignature>, Error> {
+        self.call("blind_sign", |signatory| {
+            let blinded_messages = blinded_messages.clone();
+            let caller = caller.clone();
+            async move { signatory.blind_sign_as(blinded_messages, caller).await }
+        })
+        .await
+    }
+
+    async fn verify_proofs(&self, proofs: Vec<Proof>) -> Result<(), Error> {
+        self.call("verify_proofs", |signatory| {
+            let proofs = proofs.clone();
+            async move { signatory.verify_proofs(proofs).await }
+        })
+        .await
+    }
+
+    async fn keysets(&self) -> Result<SignatoryKeysets, Error> {
+        self.call(
+            "keysets",
+            |signatory| async move { signatory.keysets().await },
+        )
+        .await
+    }
+
+    async fn keyset_pubkeys(&self, id: Id) -> Result<SignatoryKeySet, Error> {
+        self.call("keyset_pubkeys", |signatory| async move {
+            signatory.keyset_pubkeys(id).await
+        })
+        .await
+    }
+
+    async fn keyset_info(&self, id: Id) -> Result<MintKeySetInfo, Error> {
+        self.call("keyset_info", |signatory| async move {
+            signatory.keyset_info(id).await
+        })
+        .await
+    }
+
+    async fn supported_config(&self) -> Result<SignatoryConfig, Error> {
+        self.call("supported_config", |signatory| async move {
+            signatory.supported_config().await
+        })
+        .await
+    }
+
+    async fn signing_limits(&self) -> Result<Vec<SigningLimitUsage>, Error> {
+        self.call("signing_limits", |signatory| async move {
+            signatory.signing_limits().await
+        })
+        .await
+    }
+
+    async fn rotation_log(&self) -> Result<Vec<SignedRotationAttestation>, Error> {
+        self.call("rotation_log", |signatory| async move {
+            signatory.rotation_log().await
+        })
+        .await
+    }
+
+    async fn export_keysets(&self) -> Result<SignedKeysetExport, Error> {
+        self.call("export_keysets", |signatory| async move {
+            signatory.export_keysets().await
+        })
+        .await
+    }
+
+    /// Rotate the keyset on the first healthy signatory only
+    ///
+    /// A rotation whose answer was lost may still have happened, it is not retried elsewhere.
+    async fn rotate_keyset(&self, args: RotateKeyArguments) -> Result<SignatoryKeySet, Error> {
+        let index = self
+            .order()
+            .await
+            .first()
+            .copied()
+            .ok_or_else(|| Error::SignatoryUnavailable("No signatory configured".to_owned()))?;
+        let backend = &self.backends[index];
+
+        let result = backend.signatory.rotate_keyset(args).await;
+
+        #[cfg(feature = "prometheus")]
+        cdk_prometheus::METRICS.record_signatory_request(
+            &backend.name,
+            "rotate_keyset",
+            result.is_ok(),
+        );
+
+        match &result {
+            Err(Error::SignatoryUnavailable(reason)) => self.record_failure(backend, reason).await,
+            _ => self.record_success(backend).await,
+        }
+
+        result
+    }
+
+    /// Disable the keyset on every signatory
+    ///
+    /// A standby left signing with the keyset would defeat disabling it, so all the signatories
+    /// are asked. Fails if a reachable signatory refuses, or if none could be reached.
+    async fn disable_keyset(&self, id: Id) -> Result<SignatoryKeySet, Error> {
+        let mut disabled = None;
+
+        for backend in &self.backends {
+            let result = backend.signatory.disable_keyset(id).await;
+
+            #[cfg(feature = "prometheus")]
+            cdk_prometheus::METRICS.record_signatory_request(
+                &backend.name,
+                "disable_keyset",
+                result.is_ok(),
+            );
+
+            match result {
+                Ok(keyset) => {
+                    self.record_success(backend).await;
+                    disabled.get_or_insert(keyset);
+                }
+                Err(Error::SignatoryUnavailable(reason)) => {
+                    tracing::warn!(
+                        "Signatory {} could not disable keyset {}: {}",
+                        backend.name,
+                        id,
+                        reason
+                    );
+                    self.record_failure(backend, &reason).await;
+                }
+                Err(err) => {
+                    self.record_success(backend).await;
+                    return Err(err);
+                }
+            }
+        }
+
+        disabled
+            .ok_or_else(|| Error::SignatoryUnavailable("No signatory could be reached".to_owned()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    use super::*;
+
+    /// Signatory answering nothing, or failing as unreachable while `down`
+    struct StubSignatory {
+        name: &'static str,
+        down: AtomicBool,
+        calls: AtomicUsize,
+    }
+
+    impl StubSignatory {
+        fn new(name: &'static str) -> Arc<Self> {
+            Arc::new(Self {
+                name,
+                down: AtomicBool::new(false),
+                calls: AtomicUsize::new(0),
+            })
+        }
+
+        fn answer(&self) -> Result<(), Error> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if self.down.load(Ordering::SeqCst) {
+                Err(Error::SignatoryUnavailable(format!(
+                    "{} is down",
+                    self.name
+                )))
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl Signatory for StubSignatory {
+        fn name(&self) -> String {
+            self.name.to_owned()
+        }
+
+        async fn blind_sign(
+            &self,
+            _blinded_messages: Vec<BlindedMessage>,
+        ) -> Result<Vec<BlindSignature>, Error> {
+            self.answer().map(|_| Vec::new())
+        }
+
+        async fn verify_proofs(&self, _proofs: Vec<Proof>) -> Result<(), Error> {
+            self.answer()
+                .and_then(|_| Err(Error::SignatureMissingOrInvalid))
+        }
+
+        async fn keysets(&self) -> Result<SignatoryKeysets, Error> {
+            Err(Error::Custom("unsupported".to_owned()))
+        }
+
+        async fn supported_config(&self) -> Result<SignatoryConfig, Error> {
+            self.answer().map(|_| SignatoryConfig::default())
+        }
+
+        async fn signing_limits(&self) -> Result<Vec<SigningLimitUsage>, Error> {
+            self.answer().map(|_| Vec::new())
+        }
+
+        async fn rotate_keyset(&self, _args: RotateKeyArguments) -> Result<SignatoryKeySet, Error> {
+            Err(Error::Custom("unsupported".to_owned()))
+        }
+
+        async fn disable_keyset(&self, _id: Id) -> Result<SignatoryKeySet, Error> {
+            Err(Error::Custom("unsupported".to_owned()))
+        }
+    }
+
+    fn failover(signatories: &[Arc<StubSignatory>]) -> FailoverSignatory {
+        FailoverSignatory::new(
+            signatories
+                .iter()
+                .map(|signatory| signatory.clone() as Arc<dyn Signatory + Send + Sync>)
+                .collect(),
+        )
+        .expect("failover signatory")
+    }
+
+    #[tokio::test]
+    async fn unreachable_signatories_are_failed_over_and_skipped_once_unhealthy() {
+        let primary = StubSignatory::new("primary");
+        let standby = StubSignatory::new("standby");
+        let signatory = failover(&[primary.clone(), standby.clone()])
+            .with_failure_threshold(2)
+            .with_retry_after_secs(3600);
+
+        primary.down.store(true, Ordering::SeqCst);
+        for _ in 0..3 {
+            signatory.blind_sign(Vec::new()).await.expect("failed over");
+        }
+
+        // Two failures mark the primary unhealthy, the third request goes to the standby first
+        assert_eq!(primary.calls.load(Ordering::SeqCst), 2);
+        assert_eq!(standby.calls.load(Ordering::SeqCst), 3);
+
+        let health = signatory.health().await;
+        assert!(!health[0].healthy);
+        assert_eq!(health[0].consecutive_failures, 2);
+        assert_eq!(health[0].last_error.as_deref(), Some("primary is down"));
+        assert!(health[1].healthy);
+
+        // With the standby down too the unhealthy primary is still tried, and is healthy again
+        primary.down.store(false, Ordering::SeqCst);
+        standby.down.store(true, Ordering::SeqCst);
+        signatory
+            .supported_config()
+            .await
+            .expect("unhealthy primary tried last");
+        assert!(signatory.health().await[0].healthy);
+    }
+
+    #[tokio::test]
+    async fn unhealthy_signatories_are_probed_after_the_retry_delay() {
+        let primary = StubSignatory::new("primary");
+        let standby = StubSignatory::new("standby");
+        let signatory = failover(&[primary.clone(), standby.clone()])
+            .with_failure_threshold(1)
+            .with_retry_after_secs(0);
+
+        primary.down.store(true, Ordering::SeqCst);
+        signatory.signing_limits().await.expect("failed over");
+        primary.down.store(false, Ordering::SeqCst);
+        signatory.signing_limits().await.expect("primary probed");
+
+        assert_eq!(primary.calls.load(Ordering::SeqCst), 2);
+        assert_eq!(standby.calls.load(Ordering::SeqCst), 1);
+        assert!(signatory.health().await[0].healthy);
+    }
+
+    #[tokio::test]
+    async fn only_unavailable_signatories_are_failed_over() {
+        let primary = StubSignatory::new("primary");
+        let standby = StubSignatory::new("standby");
+        let signatory = failover(&[primary.clone(), standby.clone()]);
+
+        assert!(matches!(
+            signatory.verify_proofs(Vec::new()).await,
+            Err(Error::SignatureMissingOrInvalid)
+        ));
+        assert_eq!(standby.calls.load(Ordering::SeqCst), 0);
+
+        primary.down.store(true, Ordering::SeqCst);
+        standby.down.store(true, Ordering::SeqCst);
+        assert!(matches!(
+            signatory.blind_sign(Vec::new()).await,
+            Err(Error::SignatoryUnavailable(_))
+        ));
+        assert_eq!(primary.calls.load(Ordering::SeqCst), 2);
+        assert_eq!(standby.calls.load(Ordering::SeqCst), 1);
+    }
+}
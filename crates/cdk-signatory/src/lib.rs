@@ -11,12 +11,27 @@ mod proto;
 
 #[cfg(feature = "grpc")]
 pub use proto::{
+    auth::{ApiKeys, ApiKeysError, ClientIdentity},
     client::SignatoryRpcClient,
-    server::{start_grpc_server, start_grpc_server_with_incoming, SignatoryLoader},
+    server::{
+        start_grpc_server, start_grpc_server_with_incoming,
+        start_grpc_server_with_incoming_and_shutdown, start_grpc_server_with_shutdown,
+        SignatoryLoader,
+    },
+    threshold_node::{start_signer_node_server, SignerNodeRpcClient},
 };
 
+#[cfg(all(feature = "grpc", unix))]
+pub use proto::server::start_grpc_server_uds;
+
 mod common;
+mod limits;
+
+pub use common::{derivation_path_from_unit, network_xpriv};
 
+pub mod audit;
 pub mod db_signatory;
 pub mod embedded;
+pub mod failover;
 pub mod signatory;
+pub mod threshold;
@@ -0,0 +1,195 @@
+//! Signing limits
+//!
+//! Caps the amount signed within a rolling window, per keyset or per unit, so a compromised mint
+//! process can only get its allowance signed before an operator notices, not drain the signer.
+use std::collections::VecDeque;
+
+use cdk_common::{CurrencyUnit, Error, Id};
+
+use crate::signatory::{SigningLimit, SigningLimitScope, SigningLimitUsage};
+
+/// A limit and the amounts signed within its window, one entry per second
+#[derive(Debug)]
+struct TrackedLimit {
+    limit: SigningLimit,
+    signed: VecDeque<(u64, u64)>,
+}
+
+impl TrackedLimit {
+    fn applies_to(&self, keyset_id: &Id, unit: &CurrencyUnit) -> bool {
+        match &self.limit.scope {
+            SigningLimitScope::Keyset(id) => id == keyset_id,
+            SigningLimitScope::Unit(limit_unit) => limit_unit == unit,
+        }
+    }
+
+    /// Forget what was signed before the window ending at `now`
+    fn prune(&mut self, now: u64) {
+        let window_start = now.saturating_sub(self.limit.window_secs);
+        while self
+            .signed
+            .front()
+            .is_some_and(|(time, _)| *time <= window_start)
+        {
+            self.signed.pop_front();
+        }
+    }
+
+    fn signed(&self) -> u64 {
+        self.signed
+            .iter()
+            .fold(0, |total, (_, amount)| total.saturating_add(*amount))
+    }
+
+    fn add(&mut self, now: u64, amount: u64) {
+        match self.signed.back_mut() {
+            Some((time, signed)) if *time == now => *signed = signed.saturating_add(amount),
+            _ => self.signed.push_back((now, amount)),
+        }
+    }
+}
+
+/// Signing limits of a signatory and what was signed under each
+#[derive(Debug, Default)]
+pub(crate) struct SigningLimiter {
+    limits: Vec<TrackedLimit>,
+}
+
+impl SigningLimiter {
+    /// Enforce `limits`
+    pub fn new(limits: Vec<SigningLimit>) -> Self {
+        Self {
+            limits: limits
+                .into_iter()
+                .map(|limit| TrackedLimit {
+                    limit,
+                    signed: VecDeque::new(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Count the `(keyset, unit, amount)` of a batch of signatures against the limits
+    ///
+    /// Nothing is counted and [`Error::SigningLimitExceeded`] is returned when the batch would
+    /// take any limit over its maximum.
+    pub fn consume(&mut self, batch: &[(Id, &CurrencyUnit, u64)], now: u64) -> Result<(), Error> {
+        let mut amounts = Vec::with_capacity(self.limits.len());
+
+        for tracked in self.limits.iter_mut() {
+            tracked.prune(now);
+
+            let amount = batch
+                .iter()
+                .filter(|(keyset_id, unit, _)| tracked.applies_to(keyset_id, unit))
+                .try_fold(0_u64, |total, (_, _, amount)| total.checked_add(*amount))
+                .ok_or(Error::SigningLimitExceeded)?;
+
+            let signed = tracked.signed();
+            if signed
+                .checked_add(amount)
+                .is_none_or(|total| total > tracked.limit.max_amount)
+            {
+                tracing::warn!(
+                    "Refusing to sign {} for {}, {} of {} signed in the last {}s",
+                    amount,
+                    tracked.limit.scope,
+                    signed,
+                    tracked.limit.max_amount,
+                    tracked.limit.window_secs
+                );
+                return Err(Error::SigningLimitExceeded);
+            }
+
+            amounts.push(amount);
+        }
+
+        for (tracked, amount) in self.limits.iter_mut().zip(amounts) {
+            if amount > 0 {
+                tracked.add(now, amount);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The limits and how much of each is used at `now`
+    pub fn usage(&mut self, now: u64) -> Vec<SigningLimitUsage> {
+        self.limits
+            .iter_mut()
+            .map(|tracked| {
+                tracked.prune(now);
+                SigningLimitUsage {
+                    limit: tracked.limit.clone(),
+                    signed: tracked.signed(),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn limits_roll_over_their_window() {
+        let keyset_id = Id::from_str("00916bbf7ef91a36").unwrap();
+        let other_keyset_id = Id::from_str("009a1f293253e41e").unwrap();
+        let sat = CurrencyUnit::Sat;
+
+        let mut limiter = SigningLimiter::new(vec![
+            SigningLimit {
+                scope: SigningLimitScope::Keyset(keyset_id),
+                window_secs: 60,
+                max_amount: 10,
+            },
+            SigningLimit {
+                scope: SigningLimitScope::Unit(CurrencyUnit::Sat),
+                window_secs: 3600,
+                max_amount: 16,
+            },
+        ]);
+
+        limiter
+            .consume(&[(keyset_id, &sat, 8), (other_keyset_id, &sat, 4)], 1_000)
+            .unwrap();
+
+        // Over the keyset limit, nothing is counted
+        assert!(matches!(
+            limiter.consume(&[(keyset_id, &sat, 4)], 1_030),
+            Err(Error::SigningLimitExceeded)
+        ));
+        assert_eq!(
+            limiter
+                .usage(1_030)
+                .iter()
+                .map(|usage| usage.signed)
+                .collect::<Vec<_>>(),
+            vec![8, 12]
+        );
+
+        // The keyset window rolled over, the unit one did not
+        limiter.consume(&[(keyset_id, &sat, 4)], 1_060).unwrap();
+        assert!(matches!(
+            limiter.consume(&[(other_keyset_id, &sat, 1)], 1_061),
+            Err(Error::SigningLimitExceeded)
+        ));
+
+        // Other units are not limited
+        limiter
+            .consume(&[(other_keyset_id, &CurrencyUnit::Usd, 1_000)], 1_061)
+            .unwrap();
+
+        assert_eq!(
+            limiter
+                .usage(4_700)
+                .iter()
+                .map(|usage| usage.signed)
+                .collect::<Vec<_>>(),
+            vec![0, 0]
+        );
+    }
+}
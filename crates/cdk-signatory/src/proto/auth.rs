@@ -0,0 +1,333 @@
+//! API key authentication of the signatory gRPC API
+//!
+//! Every mint sharing a signatory host gets its own key and sends it as
+//! `authorization: Bearer <key>` with every call. The server knows each key by the name of the
+//! client it was issued to, which is also the identity recorded in the audit log. Keys are
+//! revoked by removing them, from the [`ApiKeys`] or from the file they were loaded from before
+//! it is reloaded. Whether keys are required is decided when the [`ApiKeys`] is created: with
+//! [`ApiKeys::disabled`] every call is let through and the TLS client certificate is the only
+//! access control, otherwise calls are refused once every key is revoked.
+use std::fmt;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+
+use cdk_common::bearer::{BearerError, BearerTokens};
+use cdk_common::grpc::{VersionInterceptor, VERSION_SIGNATORY_HEADER};
+use tonic::metadata::errors::InvalidMetadataValue;
+use tonic::metadata::AsciiMetadataValue;
+use tonic::service::Interceptor;
+use tonic::{Request, Status};
+
+use crate::proto;
+
+/// Metadata key carrying the API key
+pub const AUTHORIZATION_HEADER: &str = "authorization";
+
+/// Name of the client an authenticated call came from
+///
+/// Added to the extensions of every call let through by [`ApiKeys`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientIdentity(pub String);
+
+/// Error loading API keys
+#[derive(thiserror::Error, Debug)]
+pub enum ApiKeysError {
+    /// Io error
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// Invalid line in the keys file
+    #[error("Invalid API key on line {0}, expected `<client> <key>`")]
+    InvalidLine(usize),
+    /// Key issued twice
+    #[error("API key of {0} is already issued to another client")]
+    Duplicate(String),
+}
+
+/// API keys accepted by the signatory server, each issued to a named client
+///
+/// Clones share the keys, so keys added or revoked through one clone apply to the running
+/// server. The default is [`ApiKeys::disabled`].
+#[derive(Clone, Default)]
+pub struct ApiKeys {
+    enabled: bool,
+    keys: Arc<RwLock<BearerTokens<String>>>,
+}
+
+impl fmt::Debug for ApiKeys {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ApiKeys")
+            .field("enabled", &self.enabled)
+            .field("keys", &self.clients().len())
+            .finish()
+    }
+}
+
+/// Parse `<client> <key>` lines, skipping blank ones and `#` comments
+fn parse_keys(contents: &str) -> Result<BearerTokens<String>, ApiKeysError> {
+    let mut keys = BearerTokens::default();
+
+    for (number, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut parts = line.split_whitespace();
+        let (Some(client), Some(key), None) = (parts.next(), parts.next(), parts.next()) else {
+            return Err(ApiKeysError::InvalidLine(number + 1));
+        };
+
+        if keys.insert(key.to_owned(), client.to_owned()).is_some() {
+            return Err(ApiKeysError::Duplicate(client.to_owned()));
+        }
+    }
+
+    Ok(keys)
+}
+
+impl ApiKeys {
+    /// Let every call through, without requiring a key
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    /// Only accept `keys`, as `(client, key)` pairs
+    pub fn new(keys: impl IntoIterator<Item = (String, String)>) -> Self {
+        Self {
+            enabled: true,
+            keys: Arc::new(RwLock::new(BearerTokens::new(
+                keys.into_iter().map(|(client, key)| (key, client)),
+            ))),
+        }
+    }
+
+    /// Load the keys from a file with one `<client> <key>` pair per line
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ApiKeysError> {
+        let keys = Self::new([]);
+        keys.reload(path)?;
+        Ok(keys)
+    }
+
+    /// Replace the keys with the ones in `path`, revoking every key no longer listed
+    ///
+    /// The current keys are kept when the file can not be read.
+    pub fn reload(&self, path: impl AsRef<Path>) -> Result<(), ApiKeysError> {
+        let keys = parse_keys(&std::fs::read_to_string(path)?)?;
+        *self.keys.write().unwrap_or_else(|err| err.into_inner()) = keys;
+        Ok(())
+    }
+
+    /// Issue `key` to `client`
+    pub fn insert(&self, client: String, key: String) {
+        self.keys
+            .write()
+            .unwrap_or_else(|err| err.into_inner())
+            .insert(key, client);
+    }
+
+    /// Revoke every key of `client`, returns whether it had any
+    pub fn revoke(&self, client: &str) -> bool {
+        let mut keys = self.keys.write().unwrap_or_else(|err| err.into_inner());
+        let before = keys.len();
+        keys.retain(|key_client| key_client != client);
+        keys.len() != before
+    }
+
+    /// Clients holding a key
+    pub fn clients(&self) -> Vec<String> {
+        let mut clients: Vec<_> = self
+            .keys
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .roles()
+            .cloned()
+            .collect();
+        clients.sort();
+        clients.dedup();
+        clients
+    }
+
+    /// Whether calls need a key at all
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Let `request` through if it carries a known key, tagging it with its [`ClientIdentity`]
+    ///
+    /// Every call is refused when keys are required and none is left.
+    pub fn authenticate<T>(&self, mut request: Request<T>) -> Result<Request<T>, Status> {
+        if !self.enabled {
+            return Ok(request);
+        }
+
+        let authorization = request
+            .metadata()
+            .get(AUTHORIZATION_HEADER)
+            .and_then(|value| value.to_str().ok());
+
+        let client = match self
+            .keys
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .role(authorization)
+        {
+            Ok(client) => client.clone(),
+            Err(BearerError::Invalid) => {
+                tracing::warn!("Rejected signatory call with an unknown API key");
+                return Err(Status::unauthenticated("Invalid API key"));
+            }
+            Err(_) => return Err(Status::unauthenticated("Missing API key")),
+        };
+
+        request.extensions_mut().insert(ClientIdentity(client));
+        Ok(request)
+    }
+}
+
+/// Client side interceptor adding the schema version and the API key, if any
+#[derive(Clone)]
+pub struct ClientInterceptor {
+    version: VersionInterceptor,
+    authorization: Option<AsciiMetadataValue>,
+}
+
+impl fmt::Debug for ClientInterceptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientInterceptor")
+            .field("version", &self.version)
+            .field(
+                "authorization",
+                &self.authorization.as_ref().map(|_| "[REDACTED]"),
+            )
+            .finish()
+    }
+}
+
+impl ClientInterceptor {
+    /// Interceptor sending `api_key`, or no key when it is `None`
+    pub fn new(api_key: Option<&str>) -> Result<Self, InvalidMetadataValue> {
+        Ok(Self {
+            version: VersionInterceptor::new(
+                VERSION_SIGNATORY_HEADER,
+                (proto::Constants::SchemaVersion as u8).to_string(),
+            ),
+            authorization: api_key
+                .map(|key| format!("Bearer {key}").parse())
+                .transpose()?,
+        })
+    }
+}
+
+impl Interceptor for ClientInterceptor {
+    fn call(&mut self, request: Request<()>) -> Result<Request<()>, Status> {
+        let mut request = self.version.call(request)?;
+
+        if let Some(authorization) = &self.authorization {
+            request
+                .metadata_mut()
+                .insert(AUTHORIZATION_HEADER, authorization.clone());
+        }
+
+        Ok(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(keys: &ApiKeys, api_key: Option<&str>) -> Result<Option<String>, tonic::Code> {
+        let request = ClientInterceptor::new(api_key)
+            .unwrap()
+            .call(Request::new(()))
+            .unwrap();
+
+        keys.authenticate(request)
+            .map(|request| {
+                request
+                    .extensions()
+                    .get::<ClientIdentity>()
+                    .map(|identity| identity.0.clone())
+            })
+            .map_err(|status| status.code())
+    }
+
+    #[test]
+    fn keys_identify_clients_until_revoked() {
+        let keys = ApiKeys::new([
+            ("mint-a".to_owned(), "key-a".to_owned()),
+            ("mint-b".to_owned(), "key-b".to_owned()),
+        ]);
+
+        assert_eq!(call(&keys, Some("key-a")), Ok(Some("mint-a".to_owned())));
+        assert_eq!(call(&keys, Some("key-b")), Ok(Some("mint-b".to_owned())));
+        assert_eq!(call(&keys, Some("nope")), Err(tonic::Code::Unauthenticated));
+        assert_eq!(call(&keys, None), Err(tonic::Code::Unauthenticated));
+
+        assert!(keys.revoke("mint-a"));
+        assert!(!keys.revoke("mint-a"));
+        assert_eq!(
+            call(&keys, Some("key-a")),
+            Err(tonic::Code::Unauthenticated)
+        );
+        assert_eq!(keys.clients(), vec!["mint-b".to_owned()]);
+    }
+
+    #[test]
+    fn disabled_keys_let_every_call_through() {
+        let keys = ApiKeys::disabled();
+
+        assert!(!keys.is_enabled());
+        assert_eq!(call(&keys, None), Ok(None));
+    }
+
+    #[test]
+    fn revoking_the_last_key_refuses_every_call() {
+        let keys = ApiKeys::new([("mint-a".to_owned(), "key-a".to_owned())]);
+
+        assert!(keys.revoke("mint-a"));
+        assert!(keys.is_enabled());
+        assert!(keys.clients().is_empty());
+        assert_eq!(call(&keys, None), Err(tonic::Code::Unauthenticated));
+        assert_eq!(
+            call(&keys, Some("key-a")),
+            Err(tonic::Code::Unauthenticated)
+        );
+
+        // Same when the last key is removed from the file before it is reloaded
+        let path = std::env::temp_dir().join(format!("cdk_signatory_keys_{}", std::process::id()));
+        std::fs::write(&path, "mint-a key-a\n").unwrap();
+        let keys = ApiKeys::from_file(&path).unwrap();
+        assert_eq!(call(&keys, Some("key-a")), Ok(Some("mint-a".to_owned())));
+
+        std::fs::write(&path, "# every key revoked\n").unwrap();
+        keys.reload(&path).unwrap();
+        assert_eq!(
+            call(&keys, Some("key-a")),
+            Err(tonic::Code::Unauthenticated)
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn keys_file_is_parsed() {
+        let keys = parse_keys("# issued keys\nmint-a key-a\n\n  mint-b   key-b  \n").unwrap();
+        assert_eq!(
+            keys.role(Some("Bearer key-a")).map(String::as_str),
+            Ok("mint-a")
+        );
+        assert_eq!(
+            keys.role(Some("Bearer key-b")).map(String::as_str),
+            Ok("mint-b")
+        );
+
+        assert!(matches!(
+            parse_keys("mint-a key-a\nmint-b"),
+            Err(ApiKeysError::InvalidLine(2))
+        ));
+        assert!(matches!(
+            parse_keys("mint-a key\nmint-b key"),
+            Err(ApiKeysError::Duplicate(_))
+        ));
+    }
+}
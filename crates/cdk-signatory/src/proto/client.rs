@@ -1,19 +1,25 @@
 use std::path::Path;
 
 use cdk_common::error::Error;
-use cdk_common::grpc::{VersionInterceptor, VERSION_SIGNATORY_HEADER};
-use cdk_common::{BlindSignature, BlindedMessage, Proof};
+use cdk_common::keyset_export::SignedKeysetExport;
+use cdk_common::mint::MintKeySetInfo;
+use cdk_common::rotation_log::SignedRotationAttestation;
+use cdk_common::{BlindSignature, BlindedMessage, Id, Proof};
+use tonic::codegen::http::Uri;
 use tonic::codegen::InterceptedService;
-use tonic::transport::{Certificate, Channel, ClientTlsConfig, Identity};
+use tonic::transport::{Certificate, Channel, ClientTlsConfig, Endpoint, Identity};
 
-use crate::proto;
+use crate::proto::auth::ClientInterceptor;
 use crate::proto::signatory_client::SignatoryClient;
-use crate::signatory::{RotateKeyArguments, Signatory, SignatoryKeySet, SignatoryKeysets};
+use crate::signatory::{
+    RotateKeyArguments, Signatory, SignatoryConfig, SignatoryKeySet, SignatoryKeysets,
+    SigningLimitUsage, UpdateKeysetConfigArguments,
+};
 
 /// A client for the Signatory service.
 #[allow(missing_debug_implementations)]
 pub struct SignatoryRpcClient {
-    client: SignatoryClient<InterceptedService<Channel, VersionInterceptor>>,
+    client: SignatoryClient<InterceptedService<Channel, ClientInterceptor>>,
     url: String,
 }
 
@@ -35,44 +41,60 @@ pub enum ClientError {
     /// Invalid URL
     #[error("Invalid URL")]
     InvalidUrl,
+
+    /// API key that can not be sent as metadata
+    #[error("Invalid API key")]
+    InvalidApiKey,
 }
 
 impl SignatoryRpcClient {
     /// Create a new RemoteSigner from a tonic transport channel.
-    pub async fn new<A>(url: String, tls_dir: Option<A>) -> Result<Self, ClientError>
+    ///
+    /// `api_key` is sent with every call when the signatory requires one.
+    pub async fn new<A>(
+        url: String,
+        tls_dir: Option<A>,
+        api_key: Option<String>,
+    ) -> Result<Self, ClientError>
     where
         A: AsRef<Path>,
     {
-        #[cfg(not(target_arch = "wasm32"))]
-        if rustls::crypto::CryptoProvider::get_default().is_none() {
-            let _ = rustls::crypto::ring::default_provider().install_default();
-        }
+        let channel = connect(&url, tls_dir).await?;
 
-        let channel = if let Some(tls_dir) = tls_dir {
-            let tls_dir = tls_dir.as_ref();
-            let server_root_ca_cert = std::fs::read_to_string(tls_dir.join("ca.pem"))?;
-            let server_root_ca_cert = Certificate::from_pem(server_root_ca_cert);
-            let client_cert = std::fs::read_to_string(tls_dir.join("client.pem"))?;
-            let client_key = std::fs::read_to_string(tls_dir.join("client.key"))?;
-            let client_identity = Identity::from_pem(client_cert, client_key);
-            let tls = ClientTlsConfig::new()
-                .ca_certificate(server_root_ca_cert)
-                .identity(client_identity);
-
-            Channel::from_shared(url.clone())
-                .map_err(|_| ClientError::InvalidUrl)?
-                .tls_config(tls)?
-                .connect()
-                .await?
-        } else {
-            Channel::from_shared(url.clone())
-                .map_err(|_| ClientError::InvalidUrl)?
-                .connect()
-                .await?
-        };
+        let interceptor =
+            ClientInterceptor::new(api_key.as_deref()).map_err(|_| ClientError::InvalidApiKey)?;
+
+        Ok(Self {
+            client: SignatoryClient::with_interceptor(channel, interceptor),
+            url,
+        })
+    }
+
+    /// Create a new RemoteSigner connected to the unix domain socket at `path`
+    ///
+    /// For a signatory served with [`crate::start_grpc_server_uds`] on the same host.
+    #[cfg(unix)]
+    pub async fn new_uds<P>(path: P, api_key: Option<String>) -> Result<Self, ClientError>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref().to_path_buf();
+        let url = format!("unix://{}", path.display());
 
-        let version = (proto::Constants::SchemaVersion as u8).to_string();
-        let interceptor = VersionInterceptor::new(VERSION_SIGNATORY_HEADER, version);
+        // The URI is required by the endpoint but never resolved, every connection goes to `path`
+        let channel = Endpoint::from_static("http://[::]:50051")
+            .connect_with_connector(tower::service_fn(move |_: Uri| {
+                let path = path.clone();
+                async move {
+                    Ok::<_, std::io::Error>(hyper_util::rt::TokioIo::new(
+                        tokio::net::UnixStream::connect(path).await?,
+                    ))
+                }
+            }))
+            .await?;
+
+        let interceptor =
+            ClientInterceptor::new(api_key.as_deref()).map_err(|_| ClientError::InvalidApiKey)?;
 
         Ok(Self {
             client: SignatoryClient::with_interceptor(channel, interceptor),
@@ -81,6 +103,50 @@ impl SignatoryRpcClient {
     }
 }
 
+/// Connect to `url`, with mutual TLS from the `ca.pem`, `client.pem` and `client.key` in `tls_dir`
+pub(crate) async fn connect<A>(url: &str, tls_dir: Option<A>) -> Result<Channel, ClientError>
+where
+    A: AsRef<Path>,
+{
+    #[cfg(not(target_arch = "wasm32"))]
+    if rustls::crypto::CryptoProvider::get_default().is_none() {
+        let _ = rustls::crypto::ring::default_provider().install_default();
+    }
+
+    Ok(if let Some(tls_dir) = tls_dir {
+        let tls_dir = tls_dir.as_ref();
+        let server_root_ca_cert = std::fs::read_to_string(tls_dir.join("ca.pem"))?;
+        let server_root_ca_cert = Certificate::from_pem(server_root_ca_cert);
+        let client_cert = std::fs::read_to_string(tls_dir.join("client.pem"))?;
+        let client_key = std::fs::read_to_string(tls_dir.join("client.key"))?;
+        let client_identity = Identity::from_pem(client_cert, client_key);
+        let tls = ClientTlsConfig::new()
+            .ca_certificate(server_root_ca_cert)
+            .identity(client_identity);
+
+        Channel::from_shared(url.to_owned())
+            .map_err(|_| ClientError::InvalidUrl)?
+            .tls_config(tls)?
+            .connect()
+            .await?
+    } else {
+        Channel::from_shared(url.to_owned())
+            .map_err(|_| ClientError::InvalidUrl)?
+            .connect()
+            .await?
+    })
+}
+
+/// Error of a failed call, [`Error::SignatoryUnavailable`] when the signatory could not be reached
+fn status_error(status: tonic::Status) -> Error {
+    match status.code() {
+        tonic::Code::Unavailable | tonic::Code::DeadlineExceeded | tonic::Code::Cancelled => {
+            Error::SignatoryUnavailable(status.message().to_owned())
+        }
+        _ => Error::Custom(status.to_string()),
+    }
+}
+
 macro_rules! handle_error {
     ($x:expr, $y:ident, scalar) => {{
         let mut obj = $x.into_inner();
@@ -128,7 +194,7 @@ impl Signatory for SignatoryRpcClient {
                     .map(|blinded_signature| blinded_signature.try_into())
                     .collect()
             })
-            .map_err(|e| Error::Custom(e.to_string()))?
+            .map_err(status_error)?
     }
 
     #[tracing::instrument(skip_all)]
@@ -145,7 +211,7 @@ impl Signatory for SignatoryRpcClient {
                     Err(Error::SignatureMissingOrInvalid)
                 }
             })
-            .map_err(|e| Error::Custom(e.to_string()))?
+            .map_err(status_error)?
     }
 
     #[tracing::instrument(skip_all)]
@@ -155,7 +221,75 @@ impl Signatory for SignatoryRpcClient {
             .keysets(tonic::Request::new(super::EmptyRequest {}))
             .await
             .map(|response| handle_error!(response, keysets).try_into())
-            .map_err(|e| Error::Custom(e.to_string()))?
+            .map_err(status_error)?
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn keyset_pubkeys(&self, id: Id) -> Result<SignatoryKeySet, Error> {
+        let req = super::KeysetRequest {
+            keyset_id: id.to_bytes(),
+        };
+        self.client
+            .clone()
+            .keyset_pubkeys(tonic::Request::new(req))
+            .await
+            .map(|response| handle_error!(response, keyset).try_into())
+            .map_err(status_error)?
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn keyset_info(&self, id: Id) -> Result<MintKeySetInfo, Error> {
+        let req = super::KeysetRequest {
+            keyset_id: id.to_bytes(),
+        };
+        self.client
+            .clone()
+            .get_keyset_info(tonic::Request::new(req))
+            .await
+            .map(|response| handle_error!(response, info).try_into())
+            .map_err(status_error)?
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn supported_config(&self) -> Result<SignatoryConfig, Error> {
+        self.client
+            .clone()
+            .supported_config(tonic::Request::new(super::EmptyRequest {}))
+            .await
+            .map(|response| handle_error!(response, config).try_into())
+            .map_err(status_error)?
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn signing_limits(&self) -> Result<Vec<SigningLimitUsage>, Error> {
+        self.client
+            .clone()
+            .signing_limits(tonic::Request::new(super::EmptyRequest {}))
+            .await
+            .map(|response| handle_error!(response, limits).try_into())
+            .map_err(status_error)?
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn rotation_log(&self) -> Result<Vec<SignedRotationAttestation>, Error> {
+        self.client
+            .clone()
+            .rotation_log(tonic::Request::new(super::EmptyRequest {}))
+            .await
+            .map(|response| handle_error!(response, log).try_into())
+            .map_err(status_error)?
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn export_keysets(&self) -> Result<SignedKeysetExport, Error> {
+        self.client
+            .clone()
+            .export_keysets(tonic::Request::new(super::EmptyRequest {}))
+            .await
+            .map(|response| {
+                serde_json::from_str(&handle_error!(response, export, scalar)).map_err(Error::from)
+            })
+            .map_err(status_error)?
     }
 
     #[tracing::instrument(skip(self))]
@@ -166,6 +300,33 @@ impl Signatory for SignatoryRpcClient {
             .rotate_keyset(tonic::Request::new(req))
             .await
             .map(|response| handle_error!(response, keyset).try_into())
-            .map_err(|e| Error::Custom(e.to_string()))?
+            .map_err(status_error)?
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn update_keyset_config(
+        &self,
+        args: UpdateKeysetConfigArguments,
+    ) -> Result<SignatoryKeySet, Error> {
+        let req: super::KeysetConfigRequest = args.into();
+        self.client
+            .clone()
+            .update_keyset_config(tonic::Request::new(req))
+            .await
+            .map(|response| handle_error!(response, keyset).try_into())
+            .map_err(status_error)?
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn disable_keyset(&self, id: Id) -> Result<SignatoryKeySet, Error> {
+        let req = super::KeysetRequest {
+            keyset_id: id.to_bytes(),
+        };
+        self.client
+            .clone()
+            .disable_keyset(tonic::Request::new(req))
+            .await
+            .map(|response| handle_error!(response, keyset).try_into())
+            .map_err(status_error)?
     }
 }
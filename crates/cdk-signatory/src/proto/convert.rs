@@ -2,8 +2,10 @@
 use std::collections::BTreeMap;
 use std::str::FromStr;
 
+use bitcoin::bip32::DerivationPath;
 use cdk_common::common::IssuerVersion;
 use cdk_common::nut02::KeySetVersion;
+use cdk_common::rotation_log::SignedRotationAttestation;
 use cdk_common::secret::Secret;
 use cdk_common::util::hex;
 use cdk_common::{Amount, Id, PublicKey};
@@ -97,14 +99,195 @@ impl From<crate::signatory::SignatoryKeySet> for KeySet {
     }
 }
 
+impl From<cdk_common::mint::MintKeySetInfo> for KeysetInfo {
+    fn from(info: cdk_common::mint::MintKeySetInfo) -> Self {
+        Self {
+            id: info.id.to_bytes(),
+            unit: Some(info.unit.into()),
+            active: info.active,
+            valid_from: info.valid_from,
+            derivation_path: info.derivation_path.to_string(),
+            derivation_path_index: info.derivation_path_index,
+            amounts: info.amounts,
+            input_fee_ppk: info.input_fee_ppk,
+            final_expiry: info.final_expiry,
+            issuer_version: info.issuer_version.map(|v| v.to_string()),
+        }
+    }
+}
+
+impl TryInto<cdk_common::mint::MintKeySetInfo> for KeysetInfo {
+    type Error = cdk_common::Error;
+
+    fn try_into(self) -> Result<cdk_common::mint::MintKeySetInfo, Self::Error> {
+        Ok(cdk_common::mint::MintKeySetInfo {
+            id: Id::from_bytes(&self.id)?,
+            unit: self
+                .unit
+                .ok_or(cdk_common::Error::Custom(INTERNAL_ERROR.to_owned()))?
+                .try_into()
+                .map_err(|_| cdk_common::Error::Custom("Invalid currency unit".to_owned()))?,
+            active: self.active,
+            valid_from: self.valid_from,
+            derivation_path: DerivationPath::from_str(&self.derivation_path)
+                .map_err(|e| cdk_common::Error::Custom(e.to_string()))?,
+            derivation_path_index: self.derivation_path_index,
+            amounts: self.amounts,
+            input_fee_ppk: self.input_fee_ppk,
+            final_expiry: self.final_expiry,
+            issuer_version: self
+                .issuer_version
+                .map(|v| IssuerVersion::from_str(&v))
+                .transpose()
+                .map_err(|e| cdk_common::Error::Custom(e.to_string()))?,
+        })
+    }
+}
+
+impl From<crate::signatory::SignatoryConfig> for SignatoryConfig {
+    fn from(config: crate::signatory::SignatoryConfig) -> Self {
+        Self {
+            units: config
+                .units
+                .into_iter()
+                .map(|unit| UnitConfig {
+                    unit: Some(unit.unit.into()),
+                    input_fee_ppk: unit.input_fee_ppk,
+                    amounts: unit.amounts,
+                    derivation_path: unit.derivation_path.map(|path| path.to_string()),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl TryInto<crate::signatory::SignatoryConfig> for SignatoryConfig {
+    type Error = cdk_common::Error;
+
+    fn try_into(self) -> Result<crate::signatory::SignatoryConfig, Self::Error> {
+        Ok(crate::signatory::SignatoryConfig {
+            units: self
+                .units
+                .into_iter()
+                .map(|unit| {
+                    Ok(crate::signatory::SignatoryUnitConfig {
+                        unit: unit
+                            .unit
+                            .ok_or(cdk_common::Error::Custom(INTERNAL_ERROR.to_owned()))?
+                            .try_into()
+                            .map_err(|_| {
+                                cdk_common::Error::Custom("Invalid currency unit".to_owned())
+                            })?,
+                        input_fee_ppk: unit.input_fee_ppk,
+                        amounts: unit.amounts,
+                        derivation_path: unit
+                            .derivation_path
+                            .map(|path| DerivationPath::from_str(&path))
+                            .transpose()
+                            .map_err(|e| cdk_common::Error::Custom(e.to_string()))?,
+                    })
+                })
+                .collect::<Result<Vec<_>, cdk_common::Error>>()?,
+        })
+    }
+}
+
+impl From<Vec<crate::signatory::SigningLimitUsage>> for SigningLimits {
+    fn from(limits: Vec<crate::signatory::SigningLimitUsage>) -> Self {
+        Self {
+            limits: limits
+                .into_iter()
+                .map(|usage| SigningLimitUsage {
+                    scope: Some(match usage.limit.scope {
+                        crate::signatory::SigningLimitScope::Keyset(id) => {
+                            signing_limit_usage::Scope::KeysetId(id.to_bytes())
+                        }
+                        crate::signatory::SigningLimitScope::Unit(unit) => {
+                            signing_limit_usage::Scope::Unit(unit.into())
+                        }
+                    }),
+                    window_secs: usage.limit.window_secs,
+                    max_amount: usage.limit.max_amount,
+                    signed: usage.signed,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<Vec<SignedRotationAttestation>> for RotationLog {
+    type Error = cdk_common::Error;
+
+    fn try_from(log: Vec<SignedRotationAttestation>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            attestations: log
+                .iter()
+                .map(serde_json::to_string)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl TryInto<Vec<SignedRotationAttestation>> for RotationLog {
+    type Error = cdk_common::Error;
+
+    fn try_into(self) -> Result<Vec<SignedRotationAttestation>, Self::Error> {
+        Ok(self
+            .attestations
+            .iter()
+            .map(|attestation| serde_json::from_str(attestation))
+            .collect::<Result<_, _>>()?)
+    }
+}
+
+impl TryInto<Vec<crate::signatory::SigningLimitUsage>> for SigningLimits {
+    type Error = cdk_common::Error;
+
+    fn try_into(self) -> Result<Vec<crate::signatory::SigningLimitUsage>, Self::Error> {
+        self.limits
+            .into_iter()
+            .map(|usage| {
+                let scope = match usage
+                    .scope
+                    .ok_or(cdk_common::Error::Custom(INTERNAL_ERROR.to_owned()))?
+                {
+                    signing_limit_usage::Scope::KeysetId(id) => {
+                        crate::signatory::SigningLimitScope::Keyset(Id::from_bytes(&id)?)
+                    }
+                    signing_limit_usage::Scope::Unit(unit) => {
+                        crate::signatory::SigningLimitScope::Unit(unit.try_into().map_err(
+                            |_| cdk_common::Error::Custom("Invalid currency unit".to_owned()),
+                        )?)
+                    }
+                };
+
+                Ok(crate::signatory::SigningLimitUsage {
+                    limit: crate::signatory::SigningLimit {
+                        scope,
+                        window_secs: usage.window_secs,
+                        max_amount: usage.max_amount,
+                    },
+                    signed: usage.signed,
+                })
+            })
+            .collect()
+    }
+}
+
 impl From<cdk_common::Error> for super::Error {
     fn from(err: cdk_common::Error) -> Self {
         let code = match err {
             cdk_common::Error::AmountError(_) => ErrorCode::AmountOutsideLimit,
             cdk_common::Error::DuplicateInputs => ErrorCode::DuplicateInputsProvided,
-            cdk_common::Error::DuplicateOutputs => ErrorCode::DuplicateInputsProvided,
+            cdk_common::Error::DuplicateOutputs => ErrorCode::DuplicateOutputsProvided,
             cdk_common::Error::UnknownKeySet => ErrorCode::KeysetNotKnown,
             cdk_common::Error::InactiveKeyset => ErrorCode::KeysetInactive,
+            cdk_common::Error::MintingDisabled => ErrorCode::MintingDisabled,
+            cdk_common::Error::SignatureMissingOrInvalid => ErrorCode::InvalidProof,
+            cdk_common::Error::BlindedMessageAlreadySigned => ErrorCode::InvalidBlindMessage,
+            cdk_common::Error::UnsupportedUnit => ErrorCode::UnitNotSupported,
+            cdk_common::Error::SigningLimitExceeded => ErrorCode::SigningLimitExceeded,
+            cdk_common::Error::Overloaded => ErrorCode::Overloaded,
             _ => ErrorCode::Unspecified,
         };
 
@@ -117,15 +300,24 @@ impl From<cdk_common::Error> for super::Error {
 
 impl From<super::Error> for cdk_common::Error {
     fn from(val: super::Error) -> Self {
-        match val.code.try_into().expect("valid code") {
+        // Codes added by newer signatories are not known here, keep their detail
+        match ErrorCode::try_from(val.code).unwrap_or(ErrorCode::Unspecified) {
             ErrorCode::AmountOutsideLimit => {
                 cdk_common::Error::AmountError(cdk_common::amount::Error::AmountOverflow)
             }
             ErrorCode::DuplicateInputsProvided => cdk_common::Error::DuplicateInputs,
+            ErrorCode::DuplicateOutputsProvided => cdk_common::Error::DuplicateOutputs,
             ErrorCode::KeysetNotKnown => cdk_common::Error::UnknownKeySet,
             ErrorCode::KeysetInactive => cdk_common::Error::InactiveKeyset,
-            ErrorCode::Unspecified => cdk_common::Error::Custom(val.detail),
-            _ => todo!(),
+            ErrorCode::MintingDisabled => cdk_common::Error::MintingDisabled,
+            ErrorCode::InvalidProof => cdk_common::Error::SignatureMissingOrInvalid,
+            ErrorCode::InvalidBlindMessage => cdk_common::Error::BlindedMessageAlreadySigned,
+            ErrorCode::UnitNotSupported => cdk_common::Error::UnsupportedUnit,
+            ErrorCode::SigningLimitExceeded => cdk_common::Error::SigningLimitExceeded,
+            ErrorCode::Overloaded => cdk_common::Error::Overloaded,
+            ErrorCode::CouldNotRotateKeyset | ErrorCode::Unspecified => {
+                cdk_common::Error::Custom(val.detail)
+            }
         }
     }
 }
@@ -348,6 +540,7 @@ impl From<crate::signatory::RotateKeyArguments> for RotationRequest {
             input_fee_ppk: value.input_fee_ppk,
             keyset_id_type: value.keyset_id_type.to_proto_i32(),
             final_expiry: value.final_expiry,
+            reason: value.reason,
         }
     }
 }
@@ -366,6 +559,32 @@ impl TryInto<crate::signatory::RotateKeyArguments> for RotationRequest {
             final_expiry: self.final_expiry,
             keyset_id_type: KeySetVersion::from_proto_i32(self.keyset_id_type)
                 .map_err(|err| Status::invalid_argument(err.to_string()))?,
+            reason: self.reason,
+        })
+    }
+}
+
+impl From<crate::signatory::UpdateKeysetConfigArguments> for KeysetConfigRequest {
+    fn from(value: crate::signatory::UpdateKeysetConfigArguments) -> Self {
+        Self {
+            unit: Some(value.unit.into()),
+            amounts: value.amounts,
+            input_fee_ppk: value.input_fee_ppk,
+        }
+    }
+}
+
+impl TryInto<crate::signatory::UpdateKeysetConfigArguments> for KeysetConfigRequest {
+    type Error = Status;
+
+    fn try_into(self) -> Result<crate::signatory::UpdateKeysetConfigArguments, Self::Error> {
+        Ok(crate::signatory::UpdateKeysetConfigArguments {
+            unit: self
+                .unit
+                .ok_or(Status::invalid_argument("unit not set"))?
+                .try_into()?,
+            amounts: self.amounts,
+            input_fee_ppk: self.input_fee_ppk,
         })
     }
 }
@@ -399,6 +618,39 @@ impl TryInto<cdk_common::KeySetInfo> for KeySet {
             active: self.active,
             input_fee_ppk: self.input_fee_ppk,
             final_expiry: self.final_expiry,
+            rotation_time: None,
+            retirement_time: None,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors_survive_the_wire() {
+        for err in [
+            cdk_common::Error::DuplicateInputs,
+            cdk_common::Error::DuplicateOutputs,
+            cdk_common::Error::UnknownKeySet,
+            cdk_common::Error::InactiveKeyset,
+            cdk_common::Error::MintingDisabled,
+            cdk_common::Error::SignatureMissingOrInvalid,
+            cdk_common::Error::BlindedMessageAlreadySigned,
+            cdk_common::Error::UnsupportedUnit,
+        ] {
+            let expected = err.to_string();
+            let wire: super::Error = err.into();
+            let err: cdk_common::Error = wire.into();
+            assert_eq!(err.to_string(), expected);
+        }
+
+        let err: cdk_common::Error = super::Error {
+            code: i32::MAX,
+            detail: "from the future".to_owned(),
+        }
+        .into();
+        assert!(matches!(err, cdk_common::Error::Custom(detail) if detail == "from the future"));
+    }
+}
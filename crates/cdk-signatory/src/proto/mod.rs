@@ -2,5 +2,16 @@ mod convert;
 
 tonic::include_proto!("signatory");
 
+/// Encoded file descriptor set of the signatory protocol, served through gRPC reflection
+pub(crate) const FILE_DESCRIPTOR_SET: &[u8] =
+    tonic::include_file_descriptor_set!("signatory_descriptor");
+
+/// Protocol between the threshold coordinator and its signer nodes
+pub(crate) mod threshold {
+    tonic::include_proto!("threshold");
+}
+
+pub mod auth;
 pub mod client;
 pub mod server;
+pub mod threshold_node;
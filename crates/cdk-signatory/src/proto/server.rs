@@ -3,14 +3,18 @@ use std::net::SocketAddr;
 use std::path::Path;
 use std::sync::Arc;
 
+use bitcoin::hashes::{sha256, Hash};
 use cdk_common::grpc::create_version_check_interceptor;
+use cdk_common::Id;
 use tokio::io::{AsyncRead, AsyncWrite};
 use tokio_stream::Stream;
+use tokio_util::sync::CancellationToken;
 use tonic::metadata::MetadataMap;
 use tonic::transport::server::Connected;
 use tonic::transport::{Certificate, Identity, Server, ServerTlsConfig};
 use tonic::{Request, Response, Status};
 
+use crate::proto::auth::{ApiKeys, ClientIdentity};
 use crate::proto::{self, signatory_server};
 use crate::signatory::Signatory;
 
@@ -55,8 +59,11 @@ where
         &self,
         request: Request<proto::BlindedMessages>,
     ) -> Result<Response<proto::BlindSignResponse>, Status> {
+        #[cfg(feature = "prometheus")]
+        let started = std::time::Instant::now();
         let metadata = request.metadata();
         let signatory = self.load_signatory(metadata).await?;
+        let caller = caller_identity(&request);
 
         let blinded_messages = request.into_inner().blinded_messages;
         let mut converted_messages = Vec::with_capacity(blinded_messages.len());
@@ -64,7 +71,7 @@ where
             converted_messages.push(msg.try_into()?);
         }
 
-        let result = match signatory.blind_sign(converted_messages).await {
+        let result = match signatory.blind_sign_as(converted_messages, caller).await {
             Ok(blind_signatures) => proto::BlindSignResponse {
                 sigs: Some(proto::BlindSignatures {
                     blind_signatures: blind_signatures
@@ -80,6 +87,13 @@ where
             },
         };
 
+        #[cfg(feature = "prometheus")]
+        cdk_prometheus::METRICS.record_signer_rpc(
+            "blind_sign",
+            result.error.is_none(),
+            started.elapsed().as_secs_f64(),
+        );
+
         Ok(Response::new(result))
     }
 
@@ -88,6 +102,8 @@ where
         &self,
         request: Request<proto::Proofs>,
     ) -> Result<Response<proto::BooleanResponse>, Status> {
+        #[cfg(feature = "prometheus")]
+        let started = std::time::Instant::now();
         let metadata = request.metadata();
         let signatory = self.load_signatory(metadata).await?;
 
@@ -114,6 +130,13 @@ where
             },
         };
 
+        #[cfg(feature = "prometheus")]
+        cdk_prometheus::METRICS.record_signer_rpc(
+            "verify_proofs",
+            result.success,
+            started.elapsed().as_secs_f64(),
+        );
+
         Ok(Response::new(result))
     }
 
@@ -159,6 +182,204 @@ where
 
         Ok(Response::new(mint_keyset_info))
     }
+
+    async fn update_keyset_config(
+        &self,
+        request: Request<proto::KeysetConfigRequest>,
+    ) -> Result<Response<proto::KeyRotationResponse>, Status> {
+        let metadata = request.metadata();
+        let signatory = self.load_signatory(metadata).await?;
+        let result = match signatory
+            .update_keyset_config(request.into_inner().try_into()?)
+            .await
+        {
+            Ok(result) => proto::KeyRotationResponse {
+                keyset: Some(result.into()),
+                ..Default::default()
+            },
+            Err(err) => proto::KeyRotationResponse {
+                error: Some(err.into()),
+                ..Default::default()
+            },
+        };
+
+        Ok(Response::new(result))
+    }
+
+    async fn disable_keyset(
+        &self,
+        request: Request<proto::KeysetRequest>,
+    ) -> Result<Response<proto::KeysetPubkeysResponse>, Status> {
+        let metadata = request.metadata();
+        let signatory = self.load_signatory(metadata).await?;
+        let id = keyset_id(request.into_inner())?;
+        let result = match signatory.disable_keyset(id).await {
+            Ok(keyset) => proto::KeysetPubkeysResponse {
+                keyset: Some(keyset.into()),
+                ..Default::default()
+            },
+            Err(err) => proto::KeysetPubkeysResponse {
+                error: Some(err.into()),
+                ..Default::default()
+            },
+        };
+
+        Ok(Response::new(result))
+    }
+
+    async fn keyset_pubkeys(
+        &self,
+        request: Request<proto::KeysetRequest>,
+    ) -> Result<Response<proto::KeysetPubkeysResponse>, Status> {
+        let metadata = request.metadata();
+        let signatory = self.load_signatory(metadata).await?;
+        let id = keyset_id(request.into_inner())?;
+        let result = match signatory.keyset_pubkeys(id).await {
+            Ok(keyset) => proto::KeysetPubkeysResponse {
+                keyset: Some(keyset.into()),
+                ..Default::default()
+            },
+            Err(err) => proto::KeysetPubkeysResponse {
+                error: Some(err.into()),
+                ..Default::default()
+            },
+        };
+
+        Ok(Response::new(result))
+    }
+
+    async fn get_keyset_info(
+        &self,
+        request: Request<proto::KeysetRequest>,
+    ) -> Result<Response<proto::KeysetInfoResponse>, Status> {
+        let metadata = request.metadata();
+        let signatory = self.load_signatory(metadata).await?;
+        let id = keyset_id(request.into_inner())?;
+        let result = match signatory.keyset_info(id).await {
+            Ok(info) => proto::KeysetInfoResponse {
+                info: Some(info.into()),
+                ..Default::default()
+            },
+            Err(err) => proto::KeysetInfoResponse {
+                error: Some(err.into()),
+                ..Default::default()
+            },
+        };
+
+        Ok(Response::new(result))
+    }
+
+    async fn supported_config(
+        &self,
+        request: Request<proto::EmptyRequest>,
+    ) -> Result<Response<proto::SupportedConfigResponse>, Status> {
+        let metadata = request.metadata();
+        let signatory = self.load_signatory(metadata).await?;
+        let result = match signatory.supported_config().await {
+            Ok(config) => proto::SupportedConfigResponse {
+                config: Some(config.into()),
+                ..Default::default()
+            },
+            Err(err) => proto::SupportedConfigResponse {
+                error: Some(err.into()),
+                ..Default::default()
+            },
+        };
+
+        Ok(Response::new(result))
+    }
+
+    async fn signing_limits(
+        &self,
+        request: Request<proto::EmptyRequest>,
+    ) -> Result<Response<proto::SigningLimitsResponse>, Status> {
+        let metadata = request.metadata();
+        let signatory = self.load_signatory(metadata).await?;
+        let result = match signatory.signing_limits().await {
+            Ok(limits) => proto::SigningLimitsResponse {
+                limits: Some(limits.into()),
+                ..Default::default()
+            },
+            Err(err) => proto::SigningLimitsResponse {
+                error: Some(err.into()),
+                ..Default::default()
+            },
+        };
+
+        Ok(Response::new(result))
+    }
+
+    async fn rotation_log(
+        &self,
+        request: Request<proto::EmptyRequest>,
+    ) -> Result<Response<proto::RotationLogResponse>, Status> {
+        let metadata = request.metadata();
+        let signatory = self.load_signatory(metadata).await?;
+        let result = match signatory
+            .rotation_log()
+            .await
+            .and_then(proto::RotationLog::try_from)
+        {
+            Ok(log) => proto::RotationLogResponse {
+                log: Some(log),
+                ..Default::default()
+            },
+            Err(err) => proto::RotationLogResponse {
+                error: Some(err.into()),
+                ..Default::default()
+            },
+        };
+
+        Ok(Response::new(result))
+    }
+
+    async fn export_keysets(
+        &self,
+        request: Request<proto::EmptyRequest>,
+    ) -> Result<Response<proto::KeysetExportResponse>, Status> {
+        let metadata = request.metadata();
+        let signatory = self.load_signatory(metadata).await?;
+        let result = match signatory
+            .export_keysets()
+            .await
+            .and_then(|export| serde_json::to_string(&export).map_err(cdk_common::Error::from))
+        {
+            Ok(export) => proto::KeysetExportResponse {
+                export,
+                ..Default::default()
+            },
+            Err(err) => proto::KeysetExportResponse {
+                error: Some(err.into()),
+                ..Default::default()
+            },
+        };
+
+        Ok(Response::new(result))
+    }
+}
+
+/// Keyset id of a keyset request
+fn keyset_id(request: proto::KeysetRequest) -> Result<Id, Status> {
+    Id::from_bytes(&request.keyset_id).map_err(|_| Status::invalid_argument("Invalid keyset id"))
+}
+
+/// Identity of the client of `request`
+///
+/// The client its API key was issued to, the SHA-256 of its TLS client certificate when it
+/// presented no key, its address otherwise.
+fn caller_identity<T>(request: &Request<T>) -> Option<String> {
+    if let Some(ClientIdentity(client)) = request.extensions().get::<ClientIdentity>() {
+        return Some(format!("key:{client}"));
+    }
+
+    if let Some(cert) = request
+        .peer_certs()
+        .and_then(|certs| certs.first().cloned())
+    {
+        return Some(format!("cert:{}", sha256::Hash::hash(cert.as_ref())));
+    }
+
+    request.remote_addr().map(|addr| format!("addr:{addr}"))
 }
 
 /// Trait for loading a signatory instance from gRPC metadata
@@ -187,26 +408,52 @@ pub enum Error {
     /// Io error
     #[error(transparent)]
     Io(#[from] std::io::Error),
+    /// Reflection service error
+    #[error(transparent)]
+    Reflection(#[from] tonic_reflection::server::Error),
 }
 
-/// Runs the signatory server
-pub async fn start_grpc_server<S, T, I: AsRef<Path>>(
-    signatory_loader: T,
-    addr: SocketAddr,
-    tls_dir: Option<I>,
-) -> Result<(), Error>
-where
-    S: Signatory + Send + Sync + 'static,
-    T: SignatoryLoader<S> + 'static,
-{
-    tracing::info!("Starting RPC server {}", addr);
+/// Builds the gRPC reflection service describing the signatory protocol
+///
+/// It is served next to the signatory service so operators can inspect a remote signer with
+/// generic gRPC tooling.
+fn reflection_service() -> Result<
+    tonic_reflection::server::v1::ServerReflectionServer<
+        impl tonic_reflection::server::v1::ServerReflection,
+    >,
+    Error,
+> {
+    Ok(tonic_reflection::server::Builder::configure()
+        .register_encoded_file_descriptor_set(proto::FILE_DESCRIPTOR_SET)
+        .build_v1()?)
+}
+
+/// Checks the schema version and then the API key of every call
+fn server_interceptor(
+    api_keys: ApiKeys,
+) -> impl Fn(Request<()>) -> Result<Request<()>, Status> + Clone {
+    let version_str = (proto::Constants::SchemaVersion as u8).to_string();
+    let version: &'static str = Box::leak(version_str.into_boxed_str());
+    let check_version =
+        create_version_check_interceptor(cdk_common::grpc::VERSION_SIGNATORY_HEADER, version);
+
+    if api_keys.is_enabled() {
+        tracing::info!("Requiring API keys of {} clients", api_keys.clients().len());
+    }
 
+    move |request| api_keys.authenticate(check_version(request)?)
+}
+
+/// Server builder with mutual TLS from the `server.pem`, `server.key` and `ca.pem` in `tls_dir`
+///
+/// Without a `tls_dir` the server accepts plain connections.
+pub(crate) fn server_builder<I: AsRef<Path>>(tls_dir: Option<I>) -> Result<Server, Error> {
     #[cfg(not(target_arch = "wasm32"))]
     if rustls::crypto::CryptoProvider::get_default().is_none() {
         let _ = rustls::crypto::ring::default_provider().install_default();
     }
 
-    let mut server = match tls_dir {
+    let server = match tls_dir {
         Some(tls_dir) => {
             tracing::info!("TLS configuration found, starting secure server");
             let tls_dir = tls_dir.as_ref();
@@ -267,22 +514,93 @@ where
         }
     };
 
-    let version_str = (proto::Constants::SchemaVersion as u8).to_string();
-    let version: &'static str = Box::leak(version_str.into_boxed_str());
+    Ok(server)
+}
+
+/// Runs the signatory server
+///
+/// Calls have to carry one of the `api_keys`, unless it is empty.
+pub async fn start_grpc_server<S, T, I: AsRef<Path>>(
+    signatory_loader: T,
+    addr: SocketAddr,
+    tls_dir: Option<I>,
+    api_keys: ApiKeys,
+) -> Result<(), Error>
+where
+    S: Signatory + Send + Sync + 'static,
+    T: SignatoryLoader<S> + 'static,
+{
+    start_grpc_server_with_shutdown(
+        signatory_loader,
+        addr,
+        tls_dir,
+        api_keys,
+        CancellationToken::new(),
+    )
+    .await
+}
+
+/// Runs the signatory server until `shutdown` is cancelled
+///
+/// Once cancelled the server stops accepting connections and returns after the calls in flight,
+/// such as a `blind_sign` whose signatures the mint is waiting for, are answered.
+pub async fn start_grpc_server_with_shutdown<S, T, I: AsRef<Path>>(
+    signatory_loader: T,
+    addr: SocketAddr,
+    tls_dir: Option<I>,
+    api_keys: ApiKeys,
+    shutdown: CancellationToken,
+) -> Result<(), Error>
+where
+    S: Signatory + Send + Sync + 'static,
+    T: SignatoryLoader<S> + 'static,
+{
+    tracing::info!("Starting RPC server {}", addr);
+
+    let mut server = server_builder(tls_dir)?;
+
     server
+        .add_service(reflection_service()?)
         .add_service(signatory_server::SignatoryServer::with_interceptor(
             CdkSignatoryServer::new(signatory_loader),
-            create_version_check_interceptor(cdk_common::grpc::VERSION_SIGNATORY_HEADER, version),
+            server_interceptor(api_keys),
         ))
-        .serve(addr)
+        .serve_with_shutdown(addr, drain_on(shutdown))
         .await?;
     Ok(())
 }
 
 /// Starts the gRPC signatory server with an incoming stream of connections.
+///
+/// Calls have to carry one of the `api_keys`, unless it is empty.
 pub async fn start_grpc_server_with_incoming<S, T, I, IO, IE>(
     signatory_loader: T,
     incoming: I,
+    api_keys: ApiKeys,
+) -> Result<(), Error>
+where
+    S: Signatory + Send + Sync + 'static,
+    T: SignatoryLoader<S> + 'static,
+    I: Stream<Item = Result<IO, IE>>,
+    IO: AsyncRead + AsyncWrite + Connected + Unpin + Send + 'static,
+    IE: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    start_grpc_server_with_incoming_and_shutdown(
+        signatory_loader,
+        incoming,
+        api_keys,
+        CancellationToken::new(),
+    )
+    .await
+}
+
+/// Starts the gRPC signatory server with an incoming stream of connections, until `shutdown` is
+/// cancelled and the calls in flight are answered.
+pub async fn start_grpc_server_with_incoming_and_shutdown<S, T, I, IO, IE>(
+    signatory_loader: T,
+    incoming: I,
+    api_keys: ApiKeys,
+    shutdown: CancellationToken,
 ) -> Result<(), Error>
 where
     S: Signatory + Send + Sync + 'static,
@@ -291,14 +609,62 @@ where
     IO: AsyncRead + AsyncWrite + Connected + Unpin + Send + 'static,
     IE: Into<Box<dyn std::error::Error + Send + Sync>>,
 {
-    let version_str = (proto::Constants::SchemaVersion as u8).to_string();
-    let version: &'static str = Box::leak(version_str.into_boxed_str());
     Server::builder()
+        .add_service(reflection_service()?)
         .add_service(signatory_server::SignatoryServer::with_interceptor(
             CdkSignatoryServer::new(signatory_loader),
-            create_version_check_interceptor(cdk_common::grpc::VERSION_SIGNATORY_HEADER, version),
+            server_interceptor(api_keys),
         ))
-        .serve_with_incoming(incoming)
+        .serve_with_incoming_shutdown(incoming, drain_on(shutdown))
         .await?;
     Ok(())
 }
+
+/// Resolves once `shutdown` is cancelled, for the server to drain the calls in flight
+async fn drain_on(shutdown: CancellationToken) {
+    shutdown.cancelled().await;
+    tracing::info!("Shutting down RPC server, answering the calls in flight");
+}
+
+/// Runs the signatory server on the unix domain socket at `path`, until `shutdown` is cancelled
+///
+/// Meant for a mint and a signatory running on the same host: the signatory is not exposed over
+/// TCP and access is restricted to the owner of the socket, so no TLS is set up. A stale socket
+/// left at `path` by a previous run is replaced, and the socket is removed on shutdown.
+#[cfg(unix)]
+pub async fn start_grpc_server_uds<S, T, P>(
+    signatory_loader: T,
+    path: P,
+    api_keys: ApiKeys,
+    shutdown: CancellationToken,
+) -> Result<(), Error>
+where
+    S: Signatory + Send + Sync + 'static,
+    T: SignatoryLoader<S> + 'static,
+    P: AsRef<Path>,
+{
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = path.as_ref();
+    tracing::info!("Starting RPC server on {}", path.display());
+
+    match std::fs::remove_file(path) {
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(err.into()),
+        _ => {}
+    }
+
+    let listener = tokio::net::UnixListener::bind(path)?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+
+    let result = start_grpc_server_with_incoming_and_shutdown(
+        signatory_loader,
+        tokio_stream::wrappers::UnixListenerStream::new(listener),
+        api_keys,
+        shutdown,
+    )
+    .await;
+
+    let _ = std::fs::remove_file(path);
+
+    result
+}
@@ -0,0 +1,295 @@
+//! gRPC transport between the threshold coordinator and its signer nodes
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+
+use cdk_common::{Amount, Error, Id, PublicKey, SecretKey};
+use tonic::codegen::InterceptedService;
+use tonic::transport::Channel;
+use tonic::{Request, Response, Status};
+
+use crate::proto::auth::{ApiKeys, ClientInterceptor};
+use crate::proto::client::{connect, ClientError};
+use crate::proto::server::{self, server_builder};
+use crate::proto::threshold as proto;
+use crate::proto::threshold::signer_node_client::SignerNodeClient;
+use crate::proto::threshold::signer_node_server::{self, SignerNodeServer};
+use crate::threshold::{NonceCommitment, SignerNode, SigningRequest};
+
+impl From<SigningRequest> for proto::SigningRequest {
+    fn from(value: SigningRequest) -> Self {
+        Self {
+            keyset_id: value.keyset_id.to_bytes(),
+            amount: value.amount.into(),
+            point: value.point.to_bytes().to_vec(),
+        }
+    }
+}
+
+impl TryFrom<proto::SigningRequest> for SigningRequest {
+    type Error = Status;
+
+    fn try_from(value: proto::SigningRequest) -> Result<Self, Self::Error> {
+        Ok(Self {
+            keyset_id: Id::from_bytes(&value.keyset_id)
+                .map_err(|err| Status::invalid_argument(err.to_string()))?,
+            amount: Amount::from(value.amount),
+            point: PublicKey::from_slice(&value.point)
+                .map_err(|err| Status::invalid_argument(err.to_string()))?,
+        })
+    }
+}
+
+impl From<NonceCommitment> for proto::NonceCommitment {
+    fn from(value: NonceCommitment) -> Self {
+        Self {
+            partial_signature: value.partial_signature.to_bytes().to_vec(),
+            r1: value.r1.to_bytes().to_vec(),
+            r2: value.r2.to_bytes().to_vec(),
+        }
+    }
+}
+
+impl TryFrom<proto::NonceCommitment> for NonceCommitment {
+    type Error = Error;
+
+    fn try_from(value: proto::NonceCommitment) -> Result<Self, Self::Error> {
+        Ok(Self {
+            partial_signature: PublicKey::from_slice(&value.partial_signature)?,
+            r1: PublicKey::from_slice(&value.r1)?,
+            r2: PublicKey::from_slice(&value.r2)?,
+        })
+    }
+}
+
+/// Error of a signer node call
+fn node_error(index: u16, status: Status) -> Error {
+    Error::Custom(format!("Signer node {}: {}", index, status.message()))
+}
+
+/// A client for a signer node
+#[allow(missing_debug_implementations)]
+pub struct SignerNodeRpcClient {
+    client: SignerNodeClient<InterceptedService<Channel, ClientInterceptor>>,
+    index: u16,
+    dealing_id: String,
+}
+
+impl SignerNodeRpcClient {
+    /// Connect to the signer node at `url` and ask for the share it holds
+    ///
+    /// `api_key` is sent with every call when the node requires one.
+    pub async fn new<A>(
+        url: String,
+        tls_dir: Option<A>,
+        api_key: Option<String>,
+    ) -> Result<Self, ClientError>
+    where
+        A: AsRef<Path>,
+    {
+        let channel = connect(&url, tls_dir).await?;
+        let interceptor =
+            ClientInterceptor::new(api_key.as_deref()).map_err(|_| ClientError::InvalidApiKey)?;
+        let mut client = SignerNodeClient::with_interceptor(channel, interceptor);
+
+        let info = client
+            .info(proto::InfoRequest {})
+            .await
+            .map_err(|status| Error::Custom(format!("Signer node {url}: {}", status.message())))?
+            .into_inner();
+
+        Ok(Self {
+            client,
+            index: u16::try_from(info.index)
+                .map_err(|_| Error::Custom(format!("Invalid signer node index {}", info.index)))?,
+            dealing_id: info.dealing_id,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl SignerNode for SignerNodeRpcClient {
+    fn index(&self) -> u16 {
+        self.index
+    }
+
+    fn dealing_id(&self) -> &str {
+        &self.dealing_id
+    }
+
+    async fn commit(
+        &self,
+        session: &str,
+        requests: Vec<SigningRequest>,
+    ) -> Result<Vec<NonceCommitment>, Error> {
+        self.client
+            .clone()
+            .commit(proto::CommitRequest {
+                session: session.to_owned(),
+                requests: requests.into_iter().map(Into::into).collect(),
+            })
+            .await
+            .map_err(|status| node_error(self.index, status))?
+            .into_inner()
+            .commitments
+            .into_iter()
+            .map(TryInto::try_into)
+            .collect()
+    }
+
+    async fn respond(
+        &self,
+        session: &str,
+        challenges: Vec<SecretKey>,
+    ) -> Result<Vec<SecretKey>, Error> {
+        self.client
+            .clone()
+            .respond(proto::RespondRequest {
+                session: session.to_owned(),
+                challenges: challenges
+                    .iter()
+                    .map(|challenge| challenge.to_secret_bytes().to_vec())
+                    .collect(),
+            })
+            .await
+            .map_err(|status| node_error(self.index, status))?
+            .into_inner()
+            .responses
+            .iter()
+            .map(|response| Ok(SecretKey::from_slice(response)?))
+            .collect()
+    }
+
+    async fn evaluate(&self, requests: Vec<SigningRequest>) -> Result<Vec<PublicKey>, Error> {
+        self.client
+            .clone()
+            .evaluate(proto::EvaluateRequest {
+                requests: requests.into_iter().map(Into::into).collect(),
+            })
+            .await
+            .map_err(|status| node_error(self.index, status))?
+            .into_inner()
+            .points
+            .iter()
+            .map(|point| Ok(PublicKey::from_slice(point)?))
+            .collect()
+    }
+}
+
+/// Serves a [`SignerNode`] to the coordinator
+struct NodeServer<N> {
+    node: Arc<N>,
+}
+
+fn requests(requests: Vec<proto::SigningRequest>) -> Result<Vec<SigningRequest>, Status> {
+    requests.into_iter().map(TryInto::try_into).collect()
+}
+
+#[tonic::async_trait]
+impl<N> signer_node_server::SignerNode for NodeServer<N>
+where
+    N: SignerNode + 'static,
+{
+    async fn info(
+        &self,
+        _request: Request<proto::InfoRequest>,
+    ) -> Result<Response<proto::NodeInfo>, Status> {
+        Ok(Response::new(proto::NodeInfo {
+            index: self.node.index().into(),
+            dealing_id: self.node.dealing_id().to_owned(),
+        }))
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn commit(
+        &self,
+        request: Request<proto::CommitRequest>,
+    ) -> Result<Response<proto::CommitResponse>, Status> {
+        let request = request.into_inner();
+        let commitments = self
+            .node
+            .commit(&request.session, requests(request.requests)?)
+            .await
+            .map_err(|err| Status::failed_precondition(err.to_string()))?;
+
+        Ok(Response::new(proto::CommitResponse {
+            commitments: commitments.into_iter().map(Into::into).collect(),
+        }))
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn respond(
+        &self,
+        request: Request<proto::RespondRequest>,
+    ) -> Result<Response<proto::RespondResponse>, Status> {
+        let request = request.into_inner();
+        let challenges = request
+            .challenges
+            .iter()
+            .map(|challenge| SecretKey::from_slice(challenge))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| Status::invalid_argument(err.to_string()))?;
+
+        let responses = self
+            .node
+            .respond(&request.session, challenges)
+            .await
+            .map_err(|err| Status::failed_precondition(err.to_string()))?;
+
+        Ok(Response::new(proto::RespondResponse {
+            responses: responses
+                .iter()
+                .map(|response| response.to_secret_bytes().to_vec())
+                .collect(),
+        }))
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn evaluate(
+        &self,
+        request: Request<proto::EvaluateRequest>,
+    ) -> Result<Response<proto::EvaluateResponse>, Status> {
+        let points = self
+            .node
+            .evaluate(requests(request.into_inner().requests)?)
+            .await
+            .map_err(|err| Status::failed_precondition(err.to_string()))?;
+
+        Ok(Response::new(proto::EvaluateResponse {
+            points: points
+                .iter()
+                .map(|point| point.to_bytes().to_vec())
+                .collect(),
+        }))
+    }
+}
+
+/// Runs a signer node server
+///
+/// Calls have to carry one of the `api_keys`, unless it is empty. Only the coordinator should be
+/// able to reach a node, a client able to call it can get its share of any point.
+pub async fn start_signer_node_server<N, I>(
+    node: Arc<N>,
+    addr: SocketAddr,
+    tls_dir: Option<I>,
+    api_keys: ApiKeys,
+) -> Result<(), server::Error>
+where
+    N: SignerNode + 'static,
+    I: AsRef<Path>,
+{
+    tracing::info!("Starting signer node {} on {}", node.index(), addr);
+
+    if api_keys.is_enabled() {
+        tracing::info!("Requiring API keys of {} clients", api_keys.clients().len());
+    }
+
+    server_builder(tls_dir)?
+        .add_service(SignerNodeServer::with_interceptor(
+            NodeServer { node },
+            move |request| api_keys.authenticate(request),
+        ))
+        .serve(addr)
+        .await?;
+    Ok(())
+}
@@ -6,10 +6,15 @@
 //! There is an in memory implementation, when the keys are stored in memory, in the same process,
 //! but it is isolated from the rest of the application, and they communicate through a channel with
 //! the defined API.
+use std::fmt;
+
+use bitcoin::bip32::DerivationPath;
 use cdk_common::common::IssuerVersion;
 use cdk_common::error::Error;
+use cdk_common::keyset_export::SignedKeysetExport;
 use cdk_common::mint::MintKeySetInfo;
 use cdk_common::nuts::nut02::KeySetVersion;
+use cdk_common::rotation_log::SignedRotationAttestation;
 use cdk_common::{
     BlindSignature, BlindedMessage, CurrencyUnit, Id, KeySet, Keys, MintKeySet, Proof, PublicKey,
 };
@@ -52,6 +57,46 @@ pub struct RotateKeyArguments {
     pub keyset_id_type: KeySetVersion,
     /// FinalExpiry
     pub final_expiry: Option<u64>,
+    /// Why the keyset is rotated, recorded in the rotation log
+    pub reason: Option<String>,
+}
+
+/// Fee and amounts a unit should be signed with, see [`Signatory::update_keyset_config`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpdateKeysetConfigArguments {
+    /// Unit
+    pub unit: CurrencyUnit,
+    /// List of amounts to support, the amounts of the active keyset are kept when empty
+    pub amounts: Vec<u64>,
+    /// Input fee
+    pub input_fee_ppk: u64,
+}
+
+impl UpdateKeysetConfigArguments {
+    /// Rotation needed for `active`, the active keyset of the unit, to match these arguments
+    ///
+    /// Returns `None` if `active` already has the fee and amounts. The new keyset keeps the
+    /// version and final expiry of `active`.
+    pub fn rotation(&self, active: Option<&SignatoryKeySet>) -> Option<RotateKeyArguments> {
+        if let Some(active) = active {
+            if active.input_fee_ppk == self.input_fee_ppk
+                && (self.amounts.is_empty() || active.amounts == self.amounts)
+            {
+                return None;
+            }
+        }
+
+        Some(RotateKeyArguments {
+            unit: self.unit.clone(),
+            amounts: self.amounts.clone(),
+            input_fee_ppk: self.input_fee_ppk,
+            keyset_id_type: active
+                .map(|active| active.id.get_version())
+                .unwrap_or(KeySetVersion::Version01),
+            final_expiry: active.and_then(|active| active.final_expiry),
+            reason: Some("Keyset config updated".to_owned()),
+        })
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -63,6 +108,137 @@ pub struct SignatoryKeysets {
     pub keysets: Vec<SignatoryKeySet>,
 }
 
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// Signatory config
+///
+/// The units the signatory was configured with, and the settings its keysets for each unit are
+/// expected to have. The mint compares its own unit settings against it at startup.
+pub struct SignatoryConfig {
+    /// Configured units
+    pub units: Vec<SignatoryUnitConfig>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Signatory settings for a unit
+pub struct SignatoryUnitConfig {
+    /// The Currency Unit
+    pub unit: CurrencyUnit,
+    /// Input fee for the keysets (parts per thousand)
+    pub input_fee_ppk: u64,
+    /// Amounts supported by the keysets
+    pub amounts: Vec<u64>,
+    /// Custom derivation path, the path is derived from the unit otherwise
+    pub derivation_path: Option<DerivationPath>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// Keysets a signing limit applies to
+pub enum SigningLimitScope {
+    /// A single keyset
+    Keyset(Id),
+    /// Every keyset of a unit, together
+    Unit(CurrencyUnit),
+}
+
+impl fmt::Display for SigningLimitScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Keyset(id) => write!(f, "keyset {id}"),
+            Self::Unit(unit) => write!(f, "unit {unit}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Cap on the amount the signatory signs within a rolling window
+pub struct SigningLimit {
+    /// Keysets the limit applies to
+    pub scope: SigningLimitScope,
+    /// Length of the window in seconds
+    pub window_secs: u64,
+    /// Largest amount signed within any window
+    pub max_amount: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A signing limit and how much of it is used
+pub struct SigningLimitUsage {
+    /// The limit
+    pub limit: SigningLimit,
+    /// Amount signed within the current window
+    pub signed: u64,
+}
+
+impl SignatoryUnitConfig {
+    /// Returns the max order if the amounts are all the powers of two below `2^max_order`
+    pub fn max_order(&self) -> Option<u32> {
+        let max_order = u32::try_from(self.amounts.len()).ok()?;
+        self.amounts
+            .iter()
+            .enumerate()
+            .all(|(i, amount)| 2_u64.checked_pow(i as u32) == Some(*amount))
+            .then_some(max_order)
+    }
+}
+
+impl SignatoryConfig {
+    /// Get the settings of a unit
+    pub fn unit(&self, unit: &CurrencyUnit) -> Option<&SignatoryUnitConfig> {
+        self.units.iter().find(|config| config.unit == *unit)
+    }
+
+    /// Fails with [`Error::SignatoryConfigMismatch`] if the signatory is not configured for `unit`
+    /// with the same fee, amounts and custom derivation path
+    ///
+    /// A `derivation_path` of `None` accepts whatever path the signatory uses.
+    pub fn ensure_unit(
+        &self,
+        unit: &CurrencyUnit,
+        input_fee_ppk: u64,
+        amounts: &[u64],
+        derivation_path: Option<&DerivationPath>,
+    ) -> Result<(), Error> {
+        let mismatch = |reason: String| Error::SignatoryConfigMismatch {
+            unit: unit.clone(),
+            reason,
+        };
+
+        let config = self
+            .unit(unit)
+            .ok_or_else(|| mismatch("unit is not configured on the signatory".to_owned()))?;
+
+        if config.input_fee_ppk != input_fee_ppk {
+            return Err(mismatch(format!(
+                "input fee {} ppk, signatory has {} ppk",
+                input_fee_ppk, config.input_fee_ppk
+            )));
+        }
+
+        if config.amounts != amounts {
+            return Err(mismatch(format!(
+                "amounts {:?}, signatory has {:?}",
+                amounts, config.amounts
+            )));
+        }
+
+        if let Some(derivation_path) = derivation_path {
+            if config.derivation_path.as_ref() != Some(derivation_path) {
+                return Err(mismatch(format!(
+                    "derivation path {}, signatory has {}",
+                    derivation_path,
+                    config
+                        .derivation_path
+                        .as_ref()
+                        .map(|path| path.to_string())
+                        .unwrap_or_else(|| "the default path".to_owned())
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone)]
 /// SignatoryKeySet
 ///
@@ -170,15 +346,98 @@ pub trait Signatory {
         blinded_messages: Vec<BlindedMessage>,
     ) -> Result<Vec<BlindSignature>, Error>;
 
+    /// Blind sign a message on behalf of `caller`.
+    ///
+    /// `caller` identifies the client when the signatory is reached over gRPC. Signatories that
+    /// do not keep track of their callers sign as [`Signatory::blind_sign`] does.
+    async fn blind_sign_as(
+        &self,
+        blinded_messages: Vec<BlindedMessage>,
+        caller: Option<String>,
+    ) -> Result<Vec<BlindSignature>, Error> {
+        let _ = caller;
+        self.blind_sign(blinded_messages).await
+    }
+
     /// Verify [`Proof`] meets conditions and is signed by the mint (ignores P2PK/HTLC signatures"
     async fn verify_proofs(&self, proofs: Vec<Proof>) -> Result<(), Error>;
 
     /// Retrieve the list of all mint keysets
     async fn keysets(&self) -> Result<SignatoryKeysets, Error>;
 
+    /// Retrieve the keyset `id` with its public keys
+    async fn keyset_pubkeys(&self, id: Id) -> Result<SignatoryKeySet, Error> {
+        self.keysets()
+            .await?
+            .keysets
+            .into_iter()
+            .find(|keyset| keyset.id == id)
+            .ok_or(Error::UnknownKeySet)
+    }
+
+    /// Retrieve the info of the keyset `id`
+    ///
+    /// Signatories that do not keep track of how their keysets were derived leave the derivation
+    /// path empty.
+    async fn keyset_info(&self, id: Id) -> Result<MintKeySetInfo, Error> {
+        self.keyset_pubkeys(id).await.map(Into::into)
+    }
+
+    /// Retrieve the units, fees, amounts and custom derivation paths the signatory was configured
+    /// with
+    async fn supported_config(&self) -> Result<SignatoryConfig, Error>;
+
+    /// Retrieve the signing limits and how much of each is used
+    async fn signing_limits(&self) -> Result<Vec<SigningLimitUsage>, Error>;
+
+    /// Retrieve the signed attestations of the keyset rotations, oldest first
+    ///
+    /// Signatories that keep no rotation log return an empty one.
+    async fn rotation_log(&self) -> Result<Vec<SignedRotationAttestation>, Error> {
+        Ok(Vec::new())
+    }
+
+    /// Export every keyset with its public keys and derivation metadata, signed by the key that
+    /// signs the rotation log
+    ///
+    /// Signatories holding no such key refuse.
+    async fn export_keysets(&self) -> Result<SignedKeysetExport, Error> {
+        Err(Error::Custom(
+            "This signatory can not sign keyset exports".to_owned(),
+        ))
+    }
+
     /// Add current keyset to inactive keysets
     /// Generate new keyset
     async fn rotate_keyset(&self, args: RotateKeyArguments) -> Result<SignatoryKeySet, Error>;
+
+    /// Stop signing with the keyset `id` for good, without activating a new keyset
+    ///
+    /// Proofs already signed with the keyset can still be verified. Unlike
+    /// [`Signatory::rotate_keyset`], the unit is left without an active keyset until the next
+    /// rotation.
+    async fn disable_keyset(&self, id: Id) -> Result<SignatoryKeySet, Error>;
+
+    /// Sign `args.unit` with the fee and amounts of `args` from now on
+    ///
+    /// Rotates the keyset of the unit only if its fee or amounts differ, so it is safe to call
+    /// with an unchanged configuration. Returns the active keyset of the unit.
+    async fn update_keyset_config(
+        &self,
+        args: UpdateKeysetConfigArguments,
+    ) -> Result<SignatoryKeySet, Error> {
+        let active = self
+            .keysets()
+            .await?
+            .keysets
+            .into_iter()
+            .find(|keyset| keyset.active && keyset.unit == args.unit);
+
+        match args.rotation(active.as_ref()) {
+            Some(rotation) => self.rotate_keyset(rotation).await,
+            None => active.ok_or(Error::UnsupportedUnit),
+        }
+    }
 }
 
 #[cfg(test)]
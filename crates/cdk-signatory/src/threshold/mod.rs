@@ -0,0 +1,645 @@
+//! Threshold signatory
+//!
+//! Blind signatures produced cooperatively by `t` of `n` signer nodes, so no single machine holds
+//! a keyset secret. A dealer splits the secret of every keyset key with Shamir secret sharing,
+//! see [`deal`], hands one [`SignerShares`] to each node and publishes the public keys in a
+//! [`ThresholdConfig`]. The dealing is a one-off ceremony: once the shares are distributed the
+//! seed can be stored offline.
+//!
+//! Signing a blinded message `B'` takes two rounds, in the style of FROST:
+//!
+//! 1. Each of `t` nodes returns its share of the signature `k_i * B'` and commits to a fresh nonce
+//!    with `r_i * G` and `r_i * B'`.
+//! 2. The [`ThresholdSignatory`] combines the shares with their Lagrange coefficients into
+//!    `C' = k * B'`, derives the NUT-12 DLEQ challenge `e` from the combined commitments, and each
+//!    node answers with `s_i = r_i + e * k_i`. The combined `s` completes a DLEQ proof
+//!    indistinguishable from one made with the full key.
+//!
+//! The combined signature is checked against its DLEQ proof before it is handed out, so a node
+//! answering with a wrong share fails the request instead of producing an invalid signature.
+//!
+//! Keysets can not be rotated by the coordinator, new keysets come from a new dealing.
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use bitcoin::bip32::Xpriv;
+use bitcoin::secp256k1::Secp256k1;
+use cdk_common::dhke::{hash_e, hash_to_curve};
+use cdk_common::nut02::KeySetVersion;
+use cdk_common::nuts::nut12::BlindSignatureDleq;
+use cdk_common::util::unix_time;
+use cdk_common::{
+    BlindSignature, BlindedMessage, CurrencyUnit, Error, Id, Keys, Proof, PublicKey, SecretKey,
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::common::{create_new_keyset, derivation_path_from_unit};
+use crate::limits::SigningLimiter;
+use crate::signatory::{
+    RotateKeyArguments, Signatory, SignatoryConfig, SignatoryKeySet, SignatoryKeysets,
+    SignatoryUnitConfig, SigningLimit, SigningLimitUsage,
+};
+
+mod node;
+mod shares;
+
+pub use node::{
+    KeySetShares, LocalSignerNode, NonceCommitment, SignerNode, SignerShares, SigningRequest,
+};
+use shares::{combine_points, combine_scalars, split};
+
+/// Public keys of a keyset signed by the signer nodes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThresholdKeySet {
+    /// Keyset id
+    pub id: Id,
+    /// Keyset unit
+    pub unit: CurrencyUnit,
+    /// Whether the keyset is the active one of its unit
+    pub active: bool,
+    /// Public key of each amount
+    pub keys: Keys,
+    /// Amounts supported by the keyset
+    pub amounts: Vec<u64>,
+    /// Input fee for the keyset (parts per thousand)
+    pub input_fee_ppk: u64,
+    /// Final expiry of the keyset
+    pub final_expiry: Option<u64>,
+    /// Derivation path index the keyset was dealt from
+    pub version: u32,
+}
+
+impl From<&ThresholdKeySet> for SignatoryKeySet {
+    fn from(keyset: &ThresholdKeySet) -> Self {
+        Self {
+            id: keyset.id,
+            unit: keyset.unit.clone(),
+            active: keyset.active,
+            keys: keyset.keys.clone(),
+            amounts: keyset.amounts.clone(),
+            input_fee_ppk: keyset.input_fee_ppk,
+            final_expiry: keyset.final_expiry,
+            issuer_version: None,
+            version: keyset.version,
+        }
+    }
+}
+
+/// What the coordinator knows of a dealing, only public keys
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThresholdConfig {
+    /// Random id of the dealing, shared by every node
+    pub dealing_id: String,
+    /// Number of nodes needed to sign
+    pub threshold: u16,
+    /// Number of nodes holding a share
+    pub nodes: u16,
+    /// Public key of the seed the keysets were dealt from
+    pub pubkey: PublicKey,
+    /// Dealt keysets
+    pub keysets: Vec<ThresholdKeySet>,
+}
+
+impl ThresholdConfig {
+    /// Read the config from the JSON file written by the dealer
+    pub fn from_file(path: impl AsRef<std::path::Path>) -> Result<Self, Error> {
+        let contents = std::fs::read_to_string(path.as_ref()).map_err(|err| {
+            Error::Custom(format!(
+                "Could not read threshold config {}: {}",
+                path.as_ref().display(),
+                err
+            ))
+        })?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+}
+
+/// Deal the keysets of `seed` to `nodes` signer nodes, any `threshold` of which can sign
+///
+/// Every unit of `supported_units`, with its fee and amounts, gets the keysets of derivation path
+/// indexes `1..=keyset_index`, the last one active. Dealing again with a higher index from the same
+/// seed rotates the keysets while keeping the old ones verifiable. Shares of different dealings do
+/// not combine, every node needs the shares of the same dealing.
+pub fn deal(
+    seed: &[u8],
+    supported_units: &HashMap<CurrencyUnit, (u64, Vec<u64>)>,
+    keyset_index: u32,
+    threshold: u16,
+    nodes: u16,
+) -> Result<(ThresholdConfig, Vec<SignerShares>), Error> {
+    let secp_ctx = Secp256k1::new();
+    let xpriv = Xpriv::new_master(bitcoin::Network::Bitcoin, seed)?;
+    let dealing_id = SecretKey::generate().to_secret_hex();
+
+    let mut keysets = Vec::new();
+    let mut node_shares: Vec<SignerShares> = (1..=nodes)
+        .map(|index| SignerShares {
+            dealing_id: dealing_id.clone(),
+            index,
+            threshold,
+            keysets: Vec::new(),
+        })
+        .collect();
+
+    for (unit, (input_fee_ppk, amounts)) in supported_units {
+        for path_index in 1..=keyset_index {
+            let derivation_path = derivation_path_from_unit(unit.clone(), path_index)
+                .ok_or(Error::UnsupportedUnit)?;
+            let (keyset, _) = create_new_keyset(
+                &secp_ctx,
+                xpriv,
+                derivation_path,
+                Some(path_index),
+                unit.clone(),
+                amounts,
+                *input_fee_ppk,
+                None,
+                KeySetVersion::Version01,
+            );
+
+            let mut amount_shares: Vec<KeySetShares> = node_shares
+                .iter()
+                .map(|_| KeySetShares {
+                    id: keyset.id,
+                    unit: unit.clone(),
+                    shares: Default::default(),
+                })
+                .collect();
+            for (amount, key_pair) in keyset.keys.iter() {
+                for (keyset_shares, share) in
+                    amount_shares
+                        .iter_mut()
+                        .zip(split(&key_pair.secret_key, threshold, nodes)?)
+                {
+                    keyset_shares.shares.insert(u64::from(*amount), share);
+                }
+            }
+            for (shares, keyset_shares) in node_shares.iter_mut().zip(amount_shares) {
+                shares.keysets.push(keyset_shares);
+            }
+
+            keysets.push(ThresholdKeySet {
+                id: keyset.id,
+                unit: unit.clone(),
+                active: path_index == keyset_index,
+                keys: keyset.keys.clone().into(),
+                amounts: amounts.clone(),
+                input_fee_ppk: *input_fee_ppk,
+                final_expiry: None,
+                version: path_index,
+            });
+        }
+    }
+
+    Ok((
+        ThresholdConfig {
+            dealing_id,
+            threshold,
+            nodes,
+            pubkey: xpriv.to_keypair(&secp_ctx).public_key().into(),
+            keysets,
+        },
+        node_shares,
+    ))
+}
+
+/// Coordinator of the signer nodes
+///
+/// Implements [`Signatory`], so it is served and used as any other signatory. Nodes are asked in
+/// the order they were given, a node that does not answer the first round is skipped.
+#[allow(missing_debug_implementations)]
+pub struct ThresholdSignatory {
+    config: ThresholdConfig,
+    keysets: HashMap<Id, ThresholdKeySet>,
+    nodes: Vec<Arc<dyn SignerNode>>,
+    signing_limiter: Mutex<SigningLimiter>,
+}
+
+impl ThresholdSignatory {
+    /// Coordinate `nodes`, holding the shares of the dealing of `config`
+    pub fn new(config: ThresholdConfig, nodes: Vec<Arc<dyn SignerNode>>) -> Result<Self, Error> {
+        let mut indexes = HashSet::new();
+        for node in &nodes {
+            if node.dealing_id() != config.dealing_id {
+                return Err(Error::Custom(format!(
+                    "Signer node {} holds shares of another dealing",
+                    node.index()
+                )));
+            }
+            if node.index() == 0 || node.index() > config.nodes || !indexes.insert(node.index()) {
+                return Err(Error::Custom(format!(
+                    "Invalid or duplicated signer node index {}",
+                    node.index()
+                )));
+            }
+        }
+
+        if nodes.len() < usize::from(config.threshold) {
+            return Err(Error::Custom(format!(
+                "{} signer nodes given, {} are needed to sign",
+                nodes.len(),
+                config.threshold
+            )));
+        }
+
+        Ok(Self {
+            keysets: config
+                .keysets
+                .iter()
+                .map(|keyset| (keyset.id, keyset.clone()))
+                .collect(),
+            config,
+            nodes,
+            signing_limiter: Default::default(),
+        })
+    }
+
+    /// Refuse to sign more than `limits` allow
+    ///
+    /// What was signed is only tracked in memory, a restart starts every window afresh.
+    pub fn with_signing_limits(mut self, limits: Vec<SigningLimit>) -> Self {
+        self.signing_limiter = Mutex::new(SigningLimiter::new(limits));
+        self
+    }
+
+    /// Public key of `amount` in `keyset_id`
+    fn key(&self, keyset_id: &Id, amount: &cdk_common::Amount) -> Result<PublicKey, Error> {
+        self.keysets
+            .get(keyset_id)
+            .ok_or(Error::UnknownKeySet)?
+            .keys
+            .amount_key(*amount)
+            .ok_or(Error::UnknownKeySet)
+    }
+
+    /// First round: the commitments of the first `threshold` nodes to answer
+    async fn commit(
+        &self,
+        session: &str,
+        requests: &[SigningRequest],
+    ) -> Result<Vec<(&Arc<dyn SignerNode>, Vec<NonceCommitment>)>, Error> {
+        let mut committed = Vec::with_capacity(usize::from(self.config.threshold));
+
+        for node in &self.nodes {
+            if committed.len() == usize::from(self.config.threshold) {
+                break;
+            }
+
+            match node.commit(session, requests.to_vec()).await {
+                Ok(commitments) if commitments.len() == requests.len() => {
+                    committed.push((node, commitments))
+                }
+                Ok(_) => tracing::warn!(
+                    "Signer node {} answered with the wrong number of commitments",
+                    node.index()
+                ),
+                Err(err) => tracing::warn!("Signer node {} did not commit: {}", node.index(), err),
+            }
+        }
+
+        if committed.len() < usize::from(self.config.threshold) {
+            return Err(Error::Custom(format!(
+                "Only {} signer nodes answered, {} are needed to sign",
+                committed.len(),
+                self.config.threshold
+            )));
+        }
+
+        Ok(committed)
+    }
+}
+
+#[async_trait::async_trait]
+impl Signatory for ThresholdSignatory {
+    fn name(&self) -> String {
+        format!(
+            "Threshold Signatory {} of {}",
+            self.config.threshold, self.config.nodes
+        )
+    }
+
+    async fn blind_sign(
+        &self,
+        blinded_messages: Vec<BlindedMessage>,
+    ) -> Result<Vec<BlindSignature>, Error> {
+        let mut requests = Vec::with_capacity(blinded_messages.len());
+        let mut mint_keys = Vec::with_capacity(blinded_messages.len());
+        let mut signed = Vec::with_capacity(blinded_messages.len());
+
+        for message in &blinded_messages {
+            let keyset = self
+                .keysets
+                .get(&message.keyset_id)
+                .ok_or(Error::UnknownKeySet)?;
+            if !keyset.active {
+                return Err(Error::InactiveKeyset);
+            }
+            if keyset
+                .final_expiry
+                .is_some_and(|expiry| expiry < unix_time())
+            {
+                return Err(Error::ExpiredKeyset);
+            }
+
+            mint_keys.push(self.key(&message.keyset_id, &message.amount)?);
+            signed.push((message.keyset_id, &keyset.unit, u64::from(message.amount)));
+            requests.push(SigningRequest {
+                keyset_id: message.keyset_id,
+                amount: message.amount,
+                point: message.blinded_secret,
+            });
+        }
+
+        let session = SecretKey::generate().to_secret_hex();
+        let committed = self.commit(&session, &requests).await?;
+
+        let mut signatures = Vec::with_capacity(requests.len());
+        let mut challenges = Vec::with_capacity(requests.len());
+        for (position, mint_key) in mint_keys.iter().enumerate() {
+            let combine = |point: fn(&NonceCommitment) -> PublicKey| {
+                combine_points(
+                    &committed
+                        .iter()
+                        .map(|(node, commitments)| (node.index(), point(&commitments[position])))
+                        .collect::<Vec<_>>(),
+                )
+            };
+
+            let c = combine(|commitment| commitment.partial_signature)?;
+            let r1 = combine(|commitment| commitment.r1)?;
+            let r2 = combine(|commitment| commitment.r2)?;
+
+            // e = hash(R1,R2,A,C')
+            challenges.push(SecretKey::from_slice(&hash_e([r1, r2, *mint_key, c]))?);
+            signatures.push(c);
+        }
+
+        let mut responses = Vec::with_capacity(committed.len());
+        for (node, _) in &committed {
+            let answers = node.respond(&session, challenges.clone()).await?;
+            if answers.len() != challenges.len() {
+                return Err(Error::Custom(format!(
+                    "Signer node {} answered with the wrong number of responses",
+                    node.index()
+                )));
+            }
+            responses.push((node.index(), answers));
+        }
+
+        let blind_signatures = blinded_messages
+            .iter()
+            .zip(signatures)
+            .zip(challenges)
+            .enumerate()
+            .map(|(position, ((message, c), e))| {
+                let s = combine_scalars(
+                    &responses
+                        .iter()
+                        .map(|(index, answers)| (*index, answers[position].clone()))
+                        .collect::<Vec<_>>(),
+                )?;
+
+                let signature = BlindSignature {
+                    amount: message.amount,
+                    keyset_id: message.keyset_id,
+                    c,
+                    dleq: Some(BlindSignatureDleq { e, s }),
+                };
+
+                if signature
+                    .verify_dleq(mint_keys[position], message.blinded_secret)
+                    .is_err()
+                {
+                    tracing::error!(
+                        "Threshold signature for keyset {} does not verify, one of the signer nodes {} returned a bad share",
+                        message.keyset_id,
+                        responses
+                            .iter()
+                            .map(|(index, _)| index.to_string())
+                            .collect::<Vec<_>>()
+                            .join(",")
+                    );
+                    return Err(Error::Custom(
+                        "Threshold signature does not verify".to_owned(),
+                    ));
+                }
+
+                Ok(signature)
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        self.signing_limiter
+            .lock()
+            .await
+            .consume(&signed, unix_time())?;
+
+        Ok(blind_signatures)
+    }
+
+    async fn verify_proofs(&self, proofs: Vec<Proof>) -> Result<(), Error> {
+        let requests = proofs
+            .iter()
+            .map(|proof| {
+                self.key(&proof.keyset_id, &proof.amount)?;
+                Ok(SigningRequest {
+                    keyset_id: proof.keyset_id,
+                    amount: proof.amount,
+                    point: hash_to_curve(proof.secret.as_bytes())?,
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let mut evaluations = Vec::with_capacity(usize::from(self.config.threshold));
+        for node in &self.nodes {
+            if evaluations.len() == usize::from(self.config.threshold) {
+                break;
+            }
+
+            match node.evaluate(requests.clone()).await {
+                Ok(points) if points.len() == requests.len() => {
+                    evaluations.push((node.index(), points))
+                }
+                Ok(_) => tracing::warn!(
+                    "Signer node {} answered with the wrong number of points",
+                    node.index()
+                ),
+                Err(err) => {
+                    tracing::warn!("Signer node {} did not evaluate: {}", node.index(), err)
+                }
+            }
+        }
+
+        if evaluations.len() < usize::from(self.config.threshold) {
+            return Err(Error::Custom(format!(
+                "Only {} signer nodes answered, {} are needed to verify",
+                evaluations.len(),
+                self.config.threshold
+            )));
+        }
+
+        for (position, proof) in proofs.iter().enumerate() {
+            let expected = combine_points(
+                &evaluations
+                    .iter()
+                    .map(|(index, points)| (*index, points[position]))
+                    .collect::<Vec<_>>(),
+            )?;
+
+            if expected != proof.c {
+                return Err(cdk_common::dhke::Error::TokenNotVerified.into());
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn keysets(&self) -> Result<SignatoryKeysets, Error> {
+        Ok(SignatoryKeysets {
+            pubkey: self.config.pubkey,
+            keysets: self.config.keysets.iter().map(Into::into).collect(),
+        })
+    }
+
+    async fn supported_config(&self) -> Result<SignatoryConfig, Error> {
+        Ok(SignatoryConfig {
+            units: self
+                .config
+                .keysets
+                .iter()
+                .filter(|keyset| keyset.active)
+                .map(|keyset| SignatoryUnitConfig {
+                    unit: keyset.unit.clone(),
+                    input_fee_ppk: keyset.input_fee_ppk,
+                    amounts: keyset.amounts.clone(),
+                    derivation_path: None,
+                })
+                .collect(),
+        })
+    }
+
+    async fn signing_limits(&self) -> Result<Vec<SigningLimitUsage>, Error> {
+        Ok(self.signing_limiter.lock().await.usage(unix_time()))
+    }
+
+    async fn rotate_keyset(&self, args: RotateKeyArguments) -> Result<SignatoryKeySet, Error> {
+        tracing::warn!(
+            "Refusing to rotate the {} keyset, threshold keysets are rotated by a new dealing",
+            args.unit
+        );
+        Err(Error::Custom(
+            "Threshold keysets are rotated by dealing new shares to the signer nodes".to_owned(),
+        ))
+    }
+
+    async fn disable_keyset(&self, id: Id) -> Result<SignatoryKeySet, Error> {
+        tracing::warn!(
+            "Refusing to disable keyset {}, threshold keysets are disabled by a new dealing",
+            id
+        );
+        Err(Error::Custom(
+            "Threshold keysets are disabled by dealing new shares without them".to_owned(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cdk_common::dhke::{blind_message, unblind_message};
+    use cdk_common::secret::Secret;
+    use cdk_common::Amount;
+
+    use super::*;
+
+    const SEED: &[u8] = b"threshold-signatory-test-seed-000000";
+
+    fn signatory(threshold: u16, nodes: u16) -> (ThresholdSignatory, ThresholdConfig) {
+        let units = HashMap::from([(CurrencyUnit::Sat, (0, vec![1, 2, 4, 8]))]);
+        let (config, shares) = deal(SEED, &units, 1, threshold, nodes).unwrap();
+        let nodes = shares
+            .into_iter()
+            .map(|shares| Arc::new(LocalSignerNode::new(shares)) as Arc<dyn SignerNode>)
+            .collect();
+
+        (
+            ThresholdSignatory::new(config.clone(), nodes).unwrap(),
+            config,
+        )
+    }
+
+    #[tokio::test]
+    async fn signatures_verify_against_the_dealt_keys() {
+        let (signatory, config) = signatory(2, 3);
+        let keyset = &config.keysets[0];
+        let secret = Secret::generate();
+        let (blinded, r) = blind_message(secret.as_bytes(), None).unwrap();
+
+        let signatures = signatory
+            .blind_sign(vec![BlindedMessage::new(
+                Amount::from(4),
+                keyset.id,
+                blinded,
+            )])
+            .await
+            .unwrap();
+
+        let mint_key = keyset.keys.amount_key(Amount::from(4)).unwrap();
+        signatures[0].verify_dleq(mint_key, blinded).unwrap();
+
+        let c = unblind_message(&signatures[0].c, &r, &mint_key).unwrap();
+        let proof = Proof::new(Amount::from(4), keyset.id, secret, c);
+        signatory.verify_proofs(vec![proof.clone()]).await.unwrap();
+
+        let mut forged = proof;
+        forged.c = SecretKey::generate().public_key();
+        assert!(signatory.verify_proofs(vec![forged]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn missing_nodes_are_skipped_until_too_few_are_left() {
+        let units = HashMap::from([(CurrencyUnit::Sat, (0, vec![1, 2]))]);
+        let (config, mut shares) = deal(SEED, &units, 1, 2, 3).unwrap();
+        let keyset_id = config.keysets[0].id;
+        let message = BlindedMessage::new(
+            Amount::from(1),
+            keyset_id,
+            SecretKey::generate().public_key(),
+        );
+
+        // Only nodes 2 and 3 are left
+        shares.remove(0);
+        let nodes: Vec<Arc<dyn SignerNode>> = shares
+            .into_iter()
+            .map(|shares| Arc::new(LocalSignerNode::new(shares)) as Arc<dyn SignerNode>)
+            .collect();
+        let signatory = ThresholdSignatory::new(config.clone(), nodes.clone()).unwrap();
+        signatory.blind_sign(vec![message]).await.unwrap();
+
+        assert!(ThresholdSignatory::new(config, nodes[..1].to_vec()).is_err());
+    }
+
+    #[tokio::test]
+    async fn shares_of_another_dealing_are_refused() {
+        let units = HashMap::from([(CurrencyUnit::Sat, (0, vec![1, 2]))]);
+        let (config, _) = deal(SEED, &units, 1, 2, 3).unwrap();
+        let (_, other_shares) = deal(SEED, &units, 1, 2, 3).unwrap();
+
+        let nodes = other_shares
+            .into_iter()
+            .map(|shares| Arc::new(LocalSignerNode::new(shares)) as Arc<dyn SignerNode>)
+            .collect();
+        assert!(ThresholdSignatory::new(config, nodes).is_err());
+    }
+
+    #[tokio::test]
+    async fn dealing_again_keeps_old_keysets() {
+        let units = HashMap::from([(CurrencyUnit::Sat, (0, vec![1, 2]))]);
+        let (first, _) = deal(SEED, &units, 1, 2, 3).unwrap();
+        let (second, _) = deal(SEED, &units, 2, 2, 3).unwrap();
+
+        assert_eq!(second.keysets.len(), 2);
+        assert_eq!(second.keysets[0].id, first.keysets[0].id);
+        assert!(!second.keysets[0].active);
+        assert!(second.keysets[1].active);
+    }
+}
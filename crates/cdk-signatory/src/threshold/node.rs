@@ -0,0 +1,234 @@
+//! Signer nodes
+//!
+//! A node holds one share of every keyset secret and takes part in the two rounds of a signing
+//! session: it first returns its partial signature and commits to a fresh nonce, then answers the
+//! DLEQ challenge the coordinator computed from every commitment. Nonces are used for a single
+//! challenge and forgotten, answering twice with the same nonce would reveal the share.
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use cdk_common::util::unix_time;
+use cdk_common::{Amount, CurrencyUnit, Error, Id, PublicKey, SecretKey};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use super::shares::{add, mul, mul_point};
+
+/// Sessions not answered within this many seconds are dropped
+const SESSION_TTL_SECS: u64 = 60;
+
+/// Share of one keyset held by a node
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeySetShares {
+    /// Keyset id
+    pub id: Id,
+    /// Keyset unit
+    pub unit: CurrencyUnit,
+    /// Share of the secret key of each amount
+    pub shares: BTreeMap<u64, SecretKey>,
+}
+
+/// Everything a node holds, as written by the dealer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignerShares {
+    /// Dealing the shares come from, shares of different dealings do not combine
+    pub dealing_id: String,
+    /// Index of the node, from 1
+    pub index: u16,
+    /// Number of nodes needed to sign
+    pub threshold: u16,
+    /// Shares of every keyset
+    pub keysets: Vec<KeySetShares>,
+}
+
+impl SignerShares {
+    /// Read the shares from the JSON file written by the dealer
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let contents = std::fs::read_to_string(path.as_ref()).map_err(|err| {
+            Error::Custom(format!(
+                "Could not read signer shares {}: {}",
+                path.as_ref().display(),
+                err
+            ))
+        })?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+}
+
+/// Point to be multiplied by the secret of the `amount` key of `keyset_id`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SigningRequest {
+    /// Keyset id
+    pub keyset_id: Id,
+    /// Amount of the key
+    pub amount: Amount,
+    /// Blinded message to sign, or hashed secret of a proof to verify
+    pub point: PublicKey,
+}
+
+/// First round answer of a node for one request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NonceCommitment {
+    /// Share of the signature, `k_i * B'`
+    pub partial_signature: PublicKey,
+    /// Nonce times the generator, `r_i * G`
+    pub r1: PublicKey,
+    /// Nonce times the blinded message, `r_i * B'`
+    pub r2: PublicKey,
+}
+
+/// A node taking part in threshold signing
+#[async_trait::async_trait]
+pub trait SignerNode: Send + Sync {
+    /// Index of the share the node holds
+    fn index(&self) -> u16;
+
+    /// Dealing the share of the node comes from
+    fn dealing_id(&self) -> &str;
+
+    /// Sign `requests` with the share and commit to a nonce for each, under `session`
+    async fn commit(
+        &self,
+        session: &str,
+        requests: Vec<SigningRequest>,
+    ) -> Result<Vec<NonceCommitment>, Error>;
+
+    /// Answer the DLEQ `challenges` of `session`, one per request, ending the session
+    async fn respond(
+        &self,
+        session: &str,
+        challenges: Vec<SecretKey>,
+    ) -> Result<Vec<SecretKey>, Error>;
+
+    /// Multiply the points of `requests` by the share, to check proofs
+    async fn evaluate(&self, requests: Vec<SigningRequest>) -> Result<Vec<PublicKey>, Error>;
+}
+
+/// Nonces and shares of a session waiting for its challenges
+struct Session {
+    created: u64,
+    nonces: Vec<(SecretKey, SecretKey)>,
+}
+
+/// Signer node holding its shares in memory
+#[allow(missing_debug_implementations)]
+pub struct LocalSignerNode {
+    shares: SignerShares,
+    keys: HashMap<(Id, Amount), SecretKey>,
+    sessions: Mutex<HashMap<String, Session>>,
+}
+
+impl LocalSignerNode {
+    /// Node signing with `shares`
+    pub fn new(shares: SignerShares) -> Self {
+        let keys = shares
+            .keysets
+            .iter()
+            .flat_map(|keyset| {
+                keyset
+                    .shares
+                    .iter()
+                    .map(|(amount, share)| ((keyset.id, Amount::from(*amount)), share.clone()))
+            })
+            .collect();
+
+        Self {
+            shares,
+            keys,
+            sessions: Default::default(),
+        }
+    }
+
+    fn share(&self, request: &SigningRequest) -> Result<&SecretKey, Error> {
+        self.keys
+            .get(&(request.keyset_id, request.amount))
+            .ok_or(Error::UnknownKeySet)
+    }
+}
+
+#[async_trait::async_trait]
+impl SignerNode for LocalSignerNode {
+    fn index(&self) -> u16 {
+        self.shares.index
+    }
+
+    fn dealing_id(&self) -> &str {
+        &self.shares.dealing_id
+    }
+
+    async fn commit(
+        &self,
+        session: &str,
+        requests: Vec<SigningRequest>,
+    ) -> Result<Vec<NonceCommitment>, Error> {
+        let mut commitments = Vec::with_capacity(requests.len());
+        let mut nonces = Vec::with_capacity(requests.len());
+
+        for request in &requests {
+            let share = self.share(request)?;
+            let nonce = SecretKey::generate();
+
+            commitments.push(NonceCommitment {
+                partial_signature: mul_point(&request.point, share)?,
+                r1: nonce.public_key(),
+                r2: mul_point(&request.point, &nonce)?,
+            });
+            nonces.push((nonce, share.clone()));
+        }
+
+        let now = unix_time();
+        let mut sessions = self.sessions.lock().await;
+        sessions.retain(|_, pending| pending.created + SESSION_TTL_SECS > now);
+        if sessions.contains_key(session) {
+            return Err(Error::Custom(format!(
+                "Signing session {session} already exists"
+            )));
+        }
+        sessions.insert(
+            session.to_owned(),
+            Session {
+                created: now,
+                nonces,
+            },
+        );
+
+        Ok(commitments)
+    }
+
+    async fn respond(
+        &self,
+        session: &str,
+        challenges: Vec<SecretKey>,
+    ) -> Result<Vec<SecretKey>, Error> {
+        let pending = self
+            .sessions
+            .lock()
+            .await
+            .remove(session)
+            .ok_or_else(|| Error::Custom(format!("Unknown signing session {session}")))?;
+
+        if pending.nonces.len() != challenges.len() {
+            return Err(Error::Custom(format!(
+                "Expected {} challenges for signing session {}, got {}",
+                pending.nonces.len(),
+                session,
+                challenges.len()
+            )));
+        }
+
+        // s_i = r_i + e * k_i
+        pending
+            .nonces
+            .iter()
+            .zip(challenges.iter())
+            .map(|((nonce, share), challenge)| add(nonce, &mul(challenge, share)?))
+            .collect()
+    }
+
+    async fn evaluate(&self, requests: Vec<SigningRequest>) -> Result<Vec<PublicKey>, Error> {
+        requests
+            .iter()
+            .map(|request| mul_point(&request.point, self.share(request)?))
+            .collect()
+    }
+}
@@ -0,0 +1,181 @@
+//! Shamir secret sharing over the secp256k1 scalar field
+//!
+//! A keyset secret `k` is the constant term of a random polynomial `f` of degree `threshold - 1`,
+//! node `i` holds `f(i)`. Any `threshold` nodes reconstruct `f(0) * P` for a point `P` as the
+//! Lagrange combination of their `f(i) * P`, without `k` ever being put back together.
+use cdk_common::{Error, PublicKey, SecretKey, SECP256K1};
+
+/// Order of the secp256k1 group minus two, the exponent inverting a scalar
+const ORDER_MINUS_TWO: [u8; 32] = [
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe,
+    0xba, 0xae, 0xdc, 0xe6, 0xaf, 0x48, 0xa0, 0x3b, 0xbf, 0xd2, 0x5e, 0x8c, 0xd0, 0x36, 0x41, 0x3f,
+];
+
+/// The non-zero scalar `value`
+fn scalar(value: u64) -> Result<SecretKey, Error> {
+    let mut bytes = [0u8; 32];
+    bytes[24..].copy_from_slice(&value.to_be_bytes());
+    Ok(SecretKey::from_slice(&bytes)?)
+}
+
+/// `a * b`
+pub(crate) fn mul(a: &SecretKey, b: &SecretKey) -> Result<SecretKey, Error> {
+    Ok(a.mul_tweak(&b.as_scalar())
+        .map_err(cdk_common::nuts::nut01::Error::from)?
+        .into())
+}
+
+/// `a + b`, failing in the negligible case of a zero sum
+pub(crate) fn add(a: &SecretKey, b: &SecretKey) -> Result<SecretKey, Error> {
+    Ok(a.add_tweak(&b.as_scalar())
+        .map_err(cdk_common::nuts::nut01::Error::from)?
+        .into())
+}
+
+/// `1 / value`
+fn invert(value: &SecretKey) -> Result<SecretKey, Error> {
+    let mut result = scalar(1)?;
+    for byte in ORDER_MINUS_TWO {
+        for bit in (0..8).rev() {
+            result = mul(&result, &result)?;
+            if (byte >> bit) & 1 == 1 {
+                result = mul(&result, value)?;
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// `value * point`
+pub(crate) fn mul_point(point: &PublicKey, value: &SecretKey) -> Result<PublicKey, Error> {
+    Ok(point
+        .mul_tweak(&SECP256K1, &value.as_scalar())
+        .map_err(cdk_common::nuts::nut01::Error::from)?
+        .into())
+}
+
+/// Split `secret` into `nodes` shares, any `threshold` of which recover it
+///
+/// Share `i` is for the node with index `i + 1`.
+pub(crate) fn split(
+    secret: &SecretKey,
+    threshold: u16,
+    nodes: u16,
+) -> Result<Vec<SecretKey>, Error> {
+    if threshold == 0 || threshold > nodes {
+        return Err(Error::Custom(format!(
+            "Invalid threshold {threshold} of {nodes} signer nodes"
+        )));
+    }
+
+    let coefficients: Vec<SecretKey> = (1..threshold).map(|_| SecretKey::generate()).collect();
+
+    (1..=nodes)
+        .map(|index| {
+            let x = scalar(index.into())?;
+            // Horner's rule, from the highest degree down to the secret
+            let mut share: Option<SecretKey> = None;
+            for coefficient in coefficients.iter().rev() {
+                share = Some(match share {
+                    Some(share) => add(&mul(&share, &x)?, coefficient)?,
+                    None => coefficient.clone(),
+                });
+            }
+            match share {
+                Some(share) => add(&mul(&share, &x)?, secret),
+                None => Ok(secret.clone()),
+            }
+        })
+        .collect()
+}
+
+/// Lagrange coefficient at zero of the node `index` among the nodes `indexes`
+pub(crate) fn lagrange_coefficient(index: u16, indexes: &[u16]) -> Result<SecretKey, Error> {
+    let mut numerator = scalar(1)?;
+    let mut denominator = scalar(1)?;
+    let mut negative = false;
+
+    for other in indexes.iter().copied().filter(|other| *other != index) {
+        numerator = mul(&numerator, &scalar(other.into())?)?;
+        denominator = mul(&denominator, &scalar(u64::from(other.abs_diff(index)))?)?;
+        negative ^= other < index;
+    }
+
+    let coefficient = mul(&numerator, &invert(&denominator)?)?;
+    Ok(if negative {
+        coefficient.negate().into()
+    } else {
+        coefficient
+    })
+}
+
+/// Combine the `(index, share * P)` of distinct nodes into `secret * P`
+pub(crate) fn combine_points(partials: &[(u16, PublicKey)]) -> Result<PublicKey, Error> {
+    let indexes: Vec<u16> = partials.iter().map(|(index, _)| *index).collect();
+    let weighted = partials
+        .iter()
+        .map(|(index, point)| mul_point(point, &lagrange_coefficient(*index, &indexes)?))
+        .collect::<Result<Vec<_>, _>>()?;
+    let refs: Vec<&bitcoin::secp256k1::PublicKey> = weighted.iter().map(|point| &**point).collect();
+
+    Ok(bitcoin::secp256k1::PublicKey::combine_keys(&refs)
+        .map_err(cdk_common::nuts::nut01::Error::from)?
+        .into())
+}
+
+/// Combine the `(index, share)` scalars of distinct nodes into the shared scalar
+pub(crate) fn combine_scalars(partials: &[(u16, SecretKey)]) -> Result<SecretKey, Error> {
+    let indexes: Vec<u16> = partials.iter().map(|(index, _)| *index).collect();
+    let mut total: Option<SecretKey> = None;
+
+    for (index, value) in partials {
+        let weighted = mul(value, &lagrange_coefficient(*index, &indexes)?)?;
+        total = Some(match total {
+            Some(total) => add(&total, &weighted)?,
+            None => weighted,
+        });
+    }
+
+    total.ok_or_else(|| Error::Custom("No shares to combine".to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn any_threshold_of_the_shares_recover_the_secret() {
+        let secret = SecretKey::generate();
+        let shares = split(&secret, 3, 5).unwrap();
+        let point = SecretKey::generate().public_key();
+        let expected = mul_point(&point, &secret).unwrap();
+
+        for nodes in [[1u16, 2, 3], [1, 3, 5], [5, 4, 2]] {
+            let scalars: Vec<_> = nodes
+                .iter()
+                .map(|index| (*index, shares[usize::from(*index) - 1].clone()))
+                .collect();
+            assert_eq!(combine_scalars(&scalars).unwrap(), secret);
+
+            let points: Vec<_> = scalars
+                .iter()
+                .map(|(index, share)| (*index, mul_point(&point, share).unwrap()))
+                .collect();
+            assert_eq!(combine_points(&points).unwrap(), expected);
+        }
+
+        // Too few shares give an unrelated value
+        assert_ne!(
+            combine_scalars(&[(1, shares[0].clone()), (2, shares[1].clone())]).unwrap(),
+            secret
+        );
+    }
+
+    #[test]
+    fn invalid_thresholds_are_refused() {
+        let secret = SecretKey::generate();
+        assert!(split(&secret, 0, 3).is_err());
+        assert!(split(&secret, 4, 3).is_err());
+        assert_eq!(split(&secret, 1, 2).unwrap(), vec![secret.clone(), secret]);
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::{BTreeMap, HashSet};
 use std::fmt::Debug;
 use std::future::Future;
 use std::time::Instant;
@@ -6,6 +7,7 @@ use cdk_common::database::Error;
 
 use crate::database::DatabaseExecutor;
 use crate::stmt::query;
+use crate::{column_as_number, column_as_string};
 
 const SLOW_QUERY_THRESHOLD_MS: u128 = 20;
 
@@ -69,6 +71,104 @@ where
     result
 }
 
+/// Writes a consistent snapshot of a live SQLite database to `path` using `VACUUM INTO`
+///
+/// The snapshot is taken by SQLite itself, so it is safe under concurrent writes, unlike copying
+/// the database files. The target file must not exist yet.
+pub async fn backup_to<C>(conn: &C, path: &str) -> Result<(), Error>
+where
+    C: DatabaseExecutor,
+{
+    if C::name() != "sqlite" {
+        return Err(Error::Internal(format!(
+            "Online backup is not supported by {}",
+            C::name()
+        )));
+    }
+
+    query("VACUUM INTO :path")?
+        .bind("path", path.to_owned())
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Schema and storage health of a SQL database
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DatabaseHealth {
+    /// Latest applied migration
+    pub schema_version: Option<String>,
+    /// Migrations shipped with this build that are not applied to the database
+    pub pending_migrations: Vec<String>,
+    /// Number of rows per table
+    pub row_counts: BTreeMap<String, u64>,
+    /// Problems reported by `PRAGMA integrity_check`. Always empty on databases without an
+    /// integrity check, such as PostgreSQL.
+    pub integrity_errors: Vec<String>,
+}
+
+impl DatabaseHealth {
+    /// Whether the schema is up to date and no integrity problems were found
+    pub fn is_healthy(&self) -> bool {
+        self.pending_migrations.is_empty() && self.integrity_errors.is_empty()
+    }
+}
+
+/// Collects the schema version, pending migrations, row counts of `tables` and integrity check
+/// results of a database
+pub async fn health_check<C>(
+    conn: &C,
+    db_prefix: &str,
+    migrations: &[(&str, &str, &str)],
+    tables: &[&str],
+) -> Result<DatabaseHealth, Error>
+where
+    C: DatabaseExecutor,
+{
+    let mut health = DatabaseHealth::default();
+
+    let applied = query("SELECT name FROM migrations")?
+        .fetch_all(conn)
+        .await?
+        .into_iter()
+        .map(|mut row| Ok::<_, Error>(column_as_string!(row.remove(0))))
+        .collect::<Result<HashSet<String>, Error>>()?;
+
+    for (prefix, name, _) in migrations {
+        if !prefix.is_empty() && *prefix != db_prefix {
+            continue;
+        }
+
+        if applied.contains(*name) {
+            health.schema_version = Some(name.to_string());
+        } else {
+            health.pending_migrations.push(name.to_string());
+        }
+    }
+
+    for table in tables {
+        let count = query(&format!("SELECT COUNT(*) FROM {table}"))?
+            .pluck(conn)
+            .await?
+            .ok_or(Error::InvalidDbResponse)?;
+        health
+            .row_counts
+            .insert(table.to_string(), column_as_number!(count));
+    }
+
+    if C::name() == "sqlite" {
+        for mut row in query("PRAGMA integrity_check")?.fetch_all(conn).await? {
+            let result = column_as_string!(row.remove(0));
+            if result != "ok" {
+                health.integrity_errors.push(result);
+            }
+        }
+    }
+
+    Ok(health)
+}
+
 /// Migrates the migration generated by `build.rs`
 #[inline(always)]
 pub async fn migrate<C>(
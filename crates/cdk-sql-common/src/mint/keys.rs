@@ -6,9 +6,12 @@ use std::str::FromStr;
 use async_trait::async_trait;
 use bitcoin::bip32::DerivationPath;
 use cdk_common::common::IssuerVersion;
-use cdk_common::database::{Error, MintKeyDatabaseTransaction, MintKeysDatabase};
+use cdk_common::database::{
+    Error, MintKeyDatabaseTransaction, MintKeysDatabase, SigningAuditRecord,
+};
 use cdk_common::mint::MintKeySetInfo;
-use cdk_common::{CurrencyUnit, Id};
+use cdk_common::rotation_log::SignedRotationAttestation;
+use cdk_common::{Amount, CurrencyUnit, Id};
 
 use super::{SQLMintDatabase, SQLTransaction};
 use crate::database::ConnectionWithTransaction;
@@ -65,6 +68,28 @@ pub(crate) fn sql_row_to_keyset_info(row: Vec<Column>) -> Result<MintKeySetInfo,
     })
 }
 
+fn sql_row_to_signing_audit_record(row: Vec<Column>) -> Result<SigningAuditRecord, Error> {
+    unpack_into!(
+        let (
+            created_time,
+            keyset_id,
+            amount,
+            blinded_secret_hash,
+            caller
+        ) = row
+    );
+
+    let amount: u64 = column_as_number!(amount);
+
+    Ok(SigningAuditRecord {
+        created_time: column_as_number!(created_time),
+        keyset_id: column_as_string!(keyset_id, Id::from_str, Id::from_bytes),
+        amount: Amount::from(amount),
+        blinded_secret_hash: column_as_string!(blinded_secret_hash),
+        caller: column_as_nullable_string!(caller),
+    })
+}
+
 #[async_trait]
 impl<RM> MintKeyDatabaseTransaction<'_, Error> for SQLTransaction<RM>
 where
@@ -127,6 +152,63 @@ where
 
         Ok(())
     }
+
+    async fn disable_keyset(&mut self, id: &Id) -> Result<(), Error> {
+        query(r#"UPDATE keyset SET active=FALSE, disabled=TRUE WHERE id = :id"#)?
+            .bind("id", id.to_string())
+            .execute(&self.inner)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn add_signing_audit_records(
+        &mut self,
+        records: &[SigningAuditRecord],
+    ) -> Result<(), Error> {
+        for record in records {
+            query(
+                r#"
+                INSERT INTO signing_audit
+                (created_time, keyset_id, amount, blinded_secret_hash, caller)
+                VALUES (:created_time, :keyset_id, :amount, :blinded_secret_hash, :caller)
+                "#,
+            )?
+            .bind("created_time", record.created_time as i64)
+            .bind("keyset_id", record.keyset_id.to_string())
+            .bind("amount", record.amount.to_i64())
+            .bind("blinded_secret_hash", record.blinded_secret_hash.clone())
+            .bind("caller", record.caller.clone())
+            .execute(&self.inner)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn add_rotation_attestation(
+        &mut self,
+        attestation: &SignedRotationAttestation,
+    ) -> Result<(), Error> {
+        query(
+            r#"
+            INSERT INTO keyset_rotation_log
+            (sequence, created_time, attestation)
+            VALUES (:sequence, :created_time, :attestation)
+            "#,
+        )?
+        .bind("sequence", attestation.attestation.sequence as i64)
+        .bind("created_time", attestation.attestation.created_at as i64)
+        .bind(
+            "attestation",
+            serde_json::to_string(attestation)
+                .map_err(|e| Error::Internal(format!("Could not serialize attestation: {e}")))?,
+        )
+        .execute(&self.inner)
+        .await?;
+
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -140,13 +222,7 @@ where
         &'a self,
     ) -> Result<Box<dyn MintKeyDatabaseTransaction<'a, Error> + Send + Sync + 'a>, Error> {
         let tx = SQLTransaction {
-            inner: ConnectionWithTransaction::new(
-                self.pool
-                    .get()
-                    .await
-                    .map_err(|e| Error::Database(Box::new(e)))?,
-            )
-            .await?,
+            inner: self.pool.run(ConnectionWithTransaction::new).await?,
         };
 
         Ok(Box::new(tx))
@@ -252,6 +328,79 @@ where
         .map(sql_row_to_keyset_info)
         .collect::<Result<Vec<_>, _>>()?)
     }
+
+    async fn get_disabled_keysets(&self) -> Result<Vec<Id>, Self::Err> {
+        let conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| Error::Database(Box::new(e)))?;
+        query(r#"SELECT id FROM keyset WHERE disabled = :disabled"#)?
+            .bind("disabled", true)
+            .fetch_all(&*conn)
+            .await?
+            .into_iter()
+            .map(|row| Ok(column_as_string!(&row[0], Id::from_str, Id::from_bytes)))
+            .collect()
+    }
+
+    async fn get_signing_audit_records(
+        &self,
+        since: Option<u64>,
+    ) -> Result<Vec<SigningAuditRecord>, Self::Err> {
+        let conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| Error::Database(Box::new(e)))?;
+        query(
+            r#"
+            SELECT
+                created_time,
+                keyset_id,
+                amount,
+                blinded_secret_hash,
+                caller
+            FROM
+                signing_audit
+            WHERE
+                created_time >= :since
+            ORDER BY id
+            "#,
+        )?
+        .bind("since", since.unwrap_or_default() as i64)
+        .fetch_all(&*conn)
+        .await?
+        .into_iter()
+        .map(sql_row_to_signing_audit_record)
+        .collect()
+    }
+
+    async fn get_rotation_log(&self) -> Result<Vec<SignedRotationAttestation>, Self::Err> {
+        let conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| Error::Database(Box::new(e)))?;
+        query(
+            r#"
+            SELECT
+                attestation
+            FROM
+                keyset_rotation_log
+            ORDER BY sequence
+            "#,
+        )?
+        .fetch_all(&*conn)
+        .await?
+        .into_iter()
+        .map(|row| {
+            let attestation = column_as_string!(&row[0]);
+            serde_json::from_str(&attestation)
+                .map_err(|e| Error::Internal(format!("Invalid rotation attestation: {e}")))
+        })
+        .collect()
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,5 @@
+-- Record the payment backend that handled each quote and the network fee
+-- actually paid for melts, so records can be matched against node records.
+ALTER TABLE mint_quote ADD COLUMN backend TEXT;
+ALTER TABLE melt_quote ADD COLUMN backend TEXT;
+ALTER TABLE melt_quote ADD COLUMN fee_paid INTEGER;
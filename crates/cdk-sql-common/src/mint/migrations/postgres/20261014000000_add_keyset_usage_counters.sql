@@ -0,0 +1,3 @@
+-- Add key usage counters to keyset_amounts table
+ALTER TABLE keyset_amounts ADD COLUMN signatures_issued BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE keyset_amounts ADD COLUMN proofs_verified BIGINT NOT NULL DEFAULT 0;
@@ -0,0 +1,6 @@
+-- Append-only log of the signed keyset rotation attestations
+CREATE TABLE IF NOT EXISTS keyset_rotation_log (
+    sequence BIGINT PRIMARY KEY,
+    created_time BIGINT NOT NULL,
+    attestation TEXT NOT NULL
+);
@@ -0,0 +1,4 @@
+-- Support searching quotes by amount and creation time
+CREATE INDEX IF NOT EXISTS idx_mint_quote_amount ON mint_quote(amount);
+CREATE INDEX IF NOT EXISTS idx_melt_quote_amount ON melt_quote(amount);
+CREATE INDEX IF NOT EXISTS idx_melt_quote_created_time ON melt_quote(created_time);
@@ -0,0 +1,2 @@
+-- State of the hold invoice of mint quotes paid through one
+ALTER TABLE mint_quote ADD COLUMN hold_invoice_state TEXT;
@@ -0,0 +1,3 @@
+-- Add key usage counters to keyset_amounts table
+ALTER TABLE keyset_amounts ADD COLUMN signatures_issued INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE keyset_amounts ADD COLUMN proofs_verified INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,11 @@
+-- Forensic log of attempts to spend already spent proofs
+CREATE TABLE IF NOT EXISTS double_spend_attempt (
+    id TEXT PRIMARY KEY NOT NULL,
+    created_time INTEGER NOT NULL,
+    endpoint TEXT NOT NULL,
+    quote_id TEXT,
+    client_fingerprint TEXT,
+    ys TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_double_spend_attempt_time ON double_spend_attempt(created_time);
@@ -0,0 +1,11 @@
+-- Append-only log of the blind signatures issued by the signatory
+CREATE TABLE IF NOT EXISTS signing_audit (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    created_time INTEGER NOT NULL,
+    keyset_id TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    blinded_secret_hash TEXT NOT NULL,
+    caller TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_signing_audit_time ON signing_audit(created_time);
@@ -0,0 +1,2 @@
+-- Keysets disabled by the operator are never signed with nor reactivated again
+ALTER TABLE keyset ADD COLUMN disabled BOOLEAN NOT NULL DEFAULT FALSE;
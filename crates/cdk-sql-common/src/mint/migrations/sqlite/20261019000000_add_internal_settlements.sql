@@ -0,0 +1,11 @@
+-- Melts paid by a mint quote of the same mint, without reaching the payment backend
+CREATE TABLE IF NOT EXISTS internal_settlement (
+    melt_quote_id TEXT PRIMARY KEY NOT NULL,
+    mint_quote_id TEXT NOT NULL,
+    created_time INTEGER NOT NULL,
+    unit TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    fee_reserve INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_internal_settlement_time ON internal_settlement(created_time);
@@ -0,0 +1,32 @@
+-- Only one unpaid or pending bolt11 melt quote per payment request
+-- Concurrent melt quote requests for an invoice both find no quote to reuse, the index lets only
+-- one of them insert its quote. Multi-part quotes each pay part of the invoice and are exempt.
+
+-- Older duplicates are superseded by the quote being paid, or else by the latest one
+UPDATE melt_quote SET state = 'FAILED'
+WHERE state = 'UNPAID'
+  AND payment_method = 'bolt11'
+  AND (options IS NULL OR options NOT LIKE '%"mpp"%')
+  AND EXISTS (
+    SELECT 1 FROM melt_quote AS other
+    WHERE other.request_lookup_id = melt_quote.request_lookup_id
+      AND other.id != melt_quote.id
+      AND other.payment_method = 'bolt11'
+      AND (other.options IS NULL OR other.options NOT LIKE '%"mpp"%')
+      AND (
+        other.state = 'PENDING'
+        OR (
+          other.state = 'UNPAID'
+          AND (
+            other.created_time > melt_quote.created_time
+            OR (other.created_time = melt_quote.created_time AND other.id > melt_quote.id)
+          )
+        )
+      )
+  );
+
+CREATE UNIQUE INDEX IF NOT EXISTS unique_unpaid_bolt11_melt_lookup_id
+ON melt_quote(request_lookup_id)
+WHERE state IN ('UNPAID', 'PENDING')
+  AND payment_method = 'bolt11'
+  AND (options IS NULL OR options NOT LIKE '%"mpp"%');
@@ -14,7 +14,7 @@ use std::sync::Arc;
 use async_trait::async_trait;
 use cdk_common::database::{self, DbTransactionFinalizer, Error, MintDatabase};
 
-use crate::common::migrate;
+use crate::common::{backup_to, migrate};
 use crate::database::{ConnectionWithTransaction, DatabaseExecutor};
 use crate::pool::{DatabasePool, Pool, PooledResource};
 
@@ -71,6 +71,17 @@ where
         Ok(Self { pool })
     }
 
+    /// Snapshot the live database into a new file at `path`. Only supported by SQLite.
+    pub async fn backup_to(&self, path: &str) -> Result<(), Error> {
+        let conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| Error::Database(Box::new(e)))?;
+
+        backup_to(&*conn, path).await
+    }
+
     /// Migrate
     async fn migrate(conn: PooledResource<RM>) -> Result<(), Error> {
         let tx = ConnectionWithTransaction::new(conn).await?;
@@ -127,13 +138,7 @@ where
         &self,
     ) -> Result<Box<dyn database::MintTransaction<Error> + Send + Sync>, Error> {
         let tx = SQLTransaction {
-            inner: ConnectionWithTransaction::new(
-                self.pool
-                    .get()
-                    .await
-                    .map_err(|e| Error::Database(Box::new(e)))?,
-            )
-            .await?,
+            inner: self.pool.run(ConnectionWithTransaction::new).await?,
         };
 
         Ok(Box::new(tx))
@@ -5,9 +5,10 @@ use std::str::FromStr;
 
 use async_trait::async_trait;
 use cdk_common::database::mint::Acquired;
-use cdk_common::database::{self, Error, MintProofsDatabase};
+use cdk_common::database::{self, DoubleSpendAttempt, Error, MintProofsDatabase};
 use cdk_common::mint::{Operation, ProofsWithState};
 use cdk_common::nut00::ProofsMethods;
+use cdk_common::nut21::RoutePath;
 use cdk_common::quote_id::QuoteId;
 use cdk_common::secret::Secret;
 use cdk_common::util::unix_time;
@@ -46,6 +47,32 @@ where
     .collect::<Result<HashMap<_, _>, _>>()
 }
 
+fn sql_row_to_double_spend_attempt(row: Vec<Column>) -> Result<DoubleSpendAttempt, Error> {
+    unpack_into!(
+        let (
+            created_time,
+            endpoint,
+            quote_id,
+            client_fingerprint,
+            ys
+        ) = row
+    );
+
+    let ys = column_as_string!(ys);
+
+    Ok(DoubleSpendAttempt {
+        created_time: column_as_number!(created_time),
+        endpoint: column_as_string!(endpoint, |endpoint| RoutePath::from_str(endpoint)
+            .map_err(cdk_common::Error::from)),
+        quote_id: column_as_nullable_string!(quote_id)
+            .map(|quote_id| QuoteId::from_str(&quote_id))
+            .transpose()?,
+        client_fingerprint: column_as_nullable_string!(client_fingerprint),
+        ys: serde_json::from_str(&ys)
+            .map_err(|e| Error::Internal(format!("Invalid ys column: {e}")))?,
+    })
+}
+
 pub(super) fn sql_row_to_proof(row: Vec<Column>) -> Result<Proof, Error> {
     unpack_into!(
         let (
@@ -344,6 +371,32 @@ where
         .collect::<Result<Vec<_>, _>>()?)
     }
 
+    async fn add_double_spend_attempt(
+        &mut self,
+        attempt: &DoubleSpendAttempt,
+    ) -> Result<(), Self::Err> {
+        let ys = serde_json::to_string(&attempt.ys)
+            .map_err(|e| Error::Internal(format!("Could not serialize ys: {e}")))?;
+
+        query(
+            r#"
+            INSERT INTO double_spend_attempt
+            (id, created_time, endpoint, quote_id, client_fingerprint, ys)
+            VALUES (:id, :created_time, :endpoint, :quote_id, :client_fingerprint, :ys)
+            "#,
+        )?
+        .bind("id", uuid::Uuid::new_v4().to_string())
+        .bind("created_time", attempt.created_time as i64)
+        .bind("endpoint", attempt.endpoint.to_string())
+        .bind("quote_id", attempt.quote_id.as_ref().map(|q| q.to_string()))
+        .bind("client_fingerprint", attempt.client_fingerprint.clone())
+        .bind("ys", ys)
+        .execute(&self.inner)
+        .await?;
+
+        Ok(())
+    }
+
     async fn get_proofs(
         &mut self,
         ys: &[PublicKey],
@@ -407,13 +460,11 @@ where
     type Err = Error;
 
     async fn get_proofs_by_ys(&self, ys: &[PublicKey]) -> Result<Vec<Option<Proof>>, Self::Err> {
-        let conn = self
+        let mut proofs = self
             .pool
-            .get()
-            .await
-            .map_err(|e| Error::Database(Box::new(e)))?;
-        let mut proofs = query(
-            r#"
+            .run(|conn| async move {
+                query(
+                    r#"
             SELECT
                 amount,
                 keyset_id,
@@ -426,22 +477,24 @@ where
             WHERE
                 y IN (:ys)
             "#,
-        )?
-        .bind_vec("ys", ys.iter().map(|y| y.to_bytes().to_vec()).collect())?
-        .fetch_all(&*conn)
-        .await?
-        .into_iter()
-        .map(|mut row| {
-            Ok((
-                column_as_string!(
-                    row.pop().ok_or(Error::InvalidDbResponse)?,
-                    PublicKey::from_hex,
-                    PublicKey::from_slice
-                ),
-                sql_row_to_proof(row)?,
-            ))
-        })
-        .collect::<Result<HashMap<_, _>, Error>>()?;
+                )?
+                .bind_vec("ys", ys.iter().map(|y| y.to_bytes().to_vec()).collect())?
+                .fetch_all(&*conn)
+                .await?
+                .into_iter()
+                .map(|mut row| {
+                    Ok((
+                        column_as_string!(
+                            row.pop().ok_or(Error::InvalidDbResponse)?,
+                            PublicKey::from_hex,
+                            PublicKey::from_slice
+                        ),
+                        sql_row_to_proof(row)?,
+                    ))
+                })
+                .collect::<Result<HashMap<_, _>, Error>>()
+            })
+            .await?;
 
         Ok(ys.iter().map(|y| proofs.remove(y)).collect())
     }
@@ -479,12 +532,10 @@ where
     }
 
     async fn get_proofs_states(&self, ys: &[PublicKey]) -> Result<Vec<Option<State>>, Self::Err> {
-        let conn = self
+        let mut current_states = self
             .pool
-            .get()
-            .await
-            .map_err(|e| Error::Database(Box::new(e)))?;
-        let mut current_states = get_current_states(&*conn, ys, false).await?;
+            .run(|conn| async move { get_current_states(&*conn, ys, false).await })
+            .await?;
 
         Ok(ys.iter().map(|y| current_states.remove(y)).collect())
     }
@@ -581,4 +632,36 @@ where
         })
         .collect::<Result<Vec<_>, _>>()
     }
+
+    async fn get_double_spend_attempts(
+        &self,
+        since: Option<u64>,
+    ) -> Result<Vec<DoubleSpendAttempt>, Self::Err> {
+        let conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| Error::Database(Box::new(e)))?;
+        query(
+            r#"
+            SELECT
+                created_time,
+                endpoint,
+                quote_id,
+                client_fingerprint,
+                ys
+            FROM
+                double_spend_attempt
+            WHERE
+                created_time >= :since
+            ORDER BY created_time DESC
+            "#,
+        )?
+        .bind("since", since.unwrap_or_default() as i64)
+        .fetch_all(&*conn)
+        .await?
+        .into_iter()
+        .map(sql_row_to_double_spend_attempt)
+        .collect()
+    }
 }
@@ -1,10 +1,12 @@
 //! Quotes database implementation
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::str::FromStr;
 
 use async_trait::async_trait;
-use cdk_common::database::mint::{Acquired, LockedMeltQuotes};
+use cdk_common::database::mint::{
+    Acquired, DailyUsage, InternalSettlement, LockedMeltQuotes, QuoteSearch,
+};
 use cdk_common::database::{
     self, ConversionError, Error, MintQuotesDatabase, MintQuotesTransaction,
 };
@@ -14,10 +16,10 @@ use cdk_common::mint::{
 use cdk_common::nuts::nut30::MeltQuoteOnchainFeeOption;
 use cdk_common::payment::PaymentIdentifier;
 use cdk_common::quote_id::QuoteId;
-use cdk_common::state::check_melt_quote_state_transition;
 use cdk_common::util::unix_time;
 use cdk_common::{
-    Amount, BlindedMessage, CurrencyUnit, Id, MeltQuoteState, PaymentMethod, PublicKey,
+    Amount, BlindedMessage, CurrencyUnit, HoldInvoiceState, Id, MeltQuoteState, MintQuoteState,
+    PaymentMethod, PublicKey,
 };
 #[cfg(feature = "prometheus")]
 use cdk_prometheus::MintMetricGuard;
@@ -27,7 +29,7 @@ use tracing::instrument;
 use super::{SQLMintDatabase, SQLTransaction};
 use crate::database::DatabaseExecutor;
 use crate::pool::DatabasePool;
-use crate::stmt::{query, Column};
+use crate::stmt::{query, Column, Statement};
 use crate::{
     column_as_nullable_number, column_as_nullable_string, column_as_number, column_as_string,
     unpack_into,
@@ -127,7 +129,9 @@ where
             amount_issued,
             payment_method,
             request_lookup_id_kind,
-            extra_json
+            extra_json,
+            backend,
+            hold_invoice_state
         FROM
             mint_quote
         WHERE id = :id
@@ -179,7 +183,9 @@ where
             amount_issued,
             payment_method,
             request_lookup_id_kind,
-            extra_json
+            extra_json,
+            backend,
+            hold_invoice_state
         FROM
             mint_quote
         WHERE request = :request
@@ -228,7 +234,9 @@ where
             amount_issued,
             payment_method,
             request_lookup_id_kind,
-            extra_json
+            extra_json,
+            backend,
+            hold_invoice_state
         FROM
             mint_quote
         WHERE request_lookup_id = :request_lookup_id
@@ -284,7 +292,9 @@ where
             request_lookup_id_kind,
             extra_json,
             fee_options,
-            selected_fee_index
+            selected_fee_index,
+            backend,
+            fee_paid
         FROM
             melt_quote
         WHERE
@@ -325,7 +335,9 @@ where
             amount_issued,
             payment_method,
             request_lookup_id_kind,
-            extra_json
+            extra_json,
+            backend,
+            hold_invoice_state
         FROM
             mint_quote
         WHERE id IN (:quote_ids)
@@ -395,7 +407,9 @@ where
             request_lookup_id_kind,
             extra_json,
             fee_options,
-            selected_fee_index
+            selected_fee_index,
+            backend,
+            fee_paid
         FROM
             melt_quote
         WHERE
@@ -452,7 +466,9 @@ where
             request_lookup_id_kind,
             extra_json,
             fee_options,
-            selected_fee_index
+            selected_fee_index,
+            backend,
+            fee_paid
         FROM
             melt_quote
         WHERE
@@ -497,7 +513,7 @@ fn sql_row_to_mint_quote(
         let (
             id, amount, unit, request, expiry, request_lookup_id,
             pubkey, created_time, amount_paid, amount_issued, payment_method, request_lookup_id_kind,
-            extra_json
+            extra_json, backend, hold_invoice_state
         ) = row
     );
 
@@ -521,8 +537,12 @@ fn sql_row_to_mint_quote(
     let unit = column_as_string!(unit, CurrencyUnit::from_str);
     let extra_json = column_as_nullable_string!(&extra_json)
         .and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok());
+    let backend = column_as_nullable_string!(backend);
+    let hold_invoice_state = column_as_nullable_string!(&hold_invoice_state)
+        .map(|state| HoldInvoiceState::from_str(&state))
+        .transpose()?;
 
-    Ok(MintQuote::new(
+    let mut quote = MintQuote::new(
         Some(QuoteId::from_str(&id)?),
         request_str,
         unit.clone(),
@@ -538,7 +558,105 @@ fn sql_row_to_mint_quote(
         payments,
         issueances,
         extra_json,
-    ))
+    );
+    quote.backend = backend;
+    quote.hold_invoice = hold_invoice_state;
+
+    Ok(quote)
+}
+
+fn sql_row_to_internal_settlement(row: Vec<Column>) -> Result<InternalSettlement, Error> {
+    unpack_into!(
+        let (
+            melt_quote_id,
+            mint_quote_id,
+            created_time,
+            unit,
+            amount,
+            fee_reserve
+        ) = row
+    );
+
+    let amount: u64 = column_as_number!(amount);
+    let fee_reserve: u64 = column_as_number!(fee_reserve);
+
+    Ok(InternalSettlement {
+        created_time: column_as_number!(created_time),
+        melt_quote_id: QuoteId::from_str(&column_as_string!(melt_quote_id))?,
+        mint_quote_id: QuoteId::from_str(&column_as_string!(mint_quote_id))?,
+        unit: column_as_string!(unit, CurrencyUnit::from_str),
+        amount: Amount::from(amount),
+        fee_reserve: Amount::from(fee_reserve),
+    })
+}
+
+/// Query selecting `columns` of the quotes of `table` matching `search`, newest first
+///
+/// `state` is the condition on the state of the quote, bound by the caller.
+fn quote_search_query<S>(
+    table: &str,
+    columns: &str,
+    search: &QuoteSearch<S>,
+    state: Option<&str>,
+) -> Result<Statement, Error> {
+    let mut where_clauses = Vec::new();
+
+    if search.lookup_id.is_some() {
+        where_clauses.push("request_lookup_id = :lookup_id");
+    }
+    if search.request.is_some() {
+        where_clauses.push(r"request LIKE :request ESCAPE '\'");
+    }
+    if search.min_amount.is_some() {
+        where_clauses.push("amount >= :min_amount");
+    }
+    if search.max_amount.is_some() {
+        where_clauses.push("amount <= :max_amount");
+    }
+    if let Some(state) = state {
+        where_clauses.push(state);
+    }
+    if search.created_after.is_some() {
+        where_clauses.push("created_time >= :created_after");
+    }
+    if search.created_before.is_some() {
+        where_clauses.push("created_time < :created_before");
+    }
+
+    let mut query_str = format!("SELECT {columns} FROM {table}");
+    if !where_clauses.is_empty() {
+        query_str.push_str(" WHERE ");
+        query_str.push_str(&where_clauses.join(" AND "));
+    }
+    query_str.push_str(" ORDER BY created_time DESC, id LIMIT :limit OFFSET :offset");
+
+    let mut q = query(&query_str)?;
+
+    if let Some(lookup_id) = &search.lookup_id {
+        q = q.bind("lookup_id", lookup_id.clone());
+    }
+    if let Some(request) = &search.request {
+        let escaped = request
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_");
+        q = q.bind("request", format!("%{escaped}%"));
+    }
+    if let Some(min_amount) = search.min_amount {
+        q = q.bind("min_amount", min_amount as i64);
+    }
+    if let Some(max_amount) = search.max_amount {
+        q = q.bind("max_amount", max_amount as i64);
+    }
+    if let Some(created_after) = search.created_after {
+        q = q.bind("created_after", created_after as i64);
+    }
+    if let Some(created_before) = search.created_before {
+        q = q.bind("created_before", created_before as i64);
+    }
+
+    Ok(q.bind("limit", search.limit as i64)
+        .bind("offset", search.offset as i64))
 }
 
 // FIXME: Replace unwrap with proper error handling
@@ -562,7 +680,9 @@ fn sql_row_to_melt_quote(row: Vec<Column>) -> Result<mint::MeltQuote, Error> {
                 request_lookup_id_kind,
                 extra_json,
                 fee_options,
-                selected_fee_index
+                selected_fee_index,
+                backend,
+                fee_paid
         ) = row
     );
 
@@ -583,6 +703,8 @@ fn sql_row_to_melt_quote(row: Vec<Column>) -> Result<mint::MeltQuote, Error> {
         .and_then(|value| serde_json::from_str::<Vec<MeltQuoteOnchainFeeOption>>(&value).ok())
         .unwrap_or_default();
     let selected_fee_index: Option<u32> = column_as_nullable_number!(selected_fee_index);
+    let backend = column_as_nullable_string!(backend);
+    let fee_paid: Option<u64> = column_as_nullable_number!(fee_paid);
 
     let state =
         MeltQuoteState::from_str(&column_as_string!(&state)).map_err(ConversionError::from)?;
@@ -624,9 +746,9 @@ fn sql_row_to_melt_quote(row: Vec<Column>) -> Result<mint::MeltQuote, Error> {
     };
 
     let unit = CurrencyUnit::from_str(&unit)?;
-    MeltQuote::from_db(
+    let mut quote = MeltQuote::from_db(
         QuoteId::from_str(&id)?,
-        unit,
+        unit.clone(),
         request,
         amount,
         fee_reserve,
@@ -643,7 +765,11 @@ fn sql_row_to_melt_quote(row: Vec<Column>) -> Result<mint::MeltQuote, Error> {
         fee_options,
         selected_fee_index,
     )
-    .map_err(|e| Error::Internal(format!("Invalid onchain melt quote row: {e}")))
+    .map_err(|e| Error::Internal(format!("Invalid onchain melt quote row: {e}")))?;
+    quote.backend = backend;
+    quote.fee_paid = fee_paid.map(|fee| Amount::from(fee).with_unit(unit));
+
+    Ok(quote)
 }
 
 #[async_trait]
@@ -914,15 +1040,29 @@ where
         Ok(())
     }
 
+    async fn update_mint_quote_hold_invoice(
+        &mut self,
+        quote: &mut Acquired<mint::MintQuote>,
+        state: HoldInvoiceState,
+    ) -> Result<(), Self::Err> {
+        query(r#"UPDATE mint_quote SET hold_invoice_state = :state WHERE id = :id"#)?
+            .bind("state", state.to_string())
+            .bind("id", quote.id.to_string())
+            .execute(&self.inner)
+            .await?;
+        quote.hold_invoice = Some(state);
+        Ok(())
+    }
+
     #[instrument(skip_all)]
     async fn add_mint_quote(&mut self, quote: MintQuote) -> Result<Acquired<MintQuote>, Self::Err> {
         query(
             r#"
                 INSERT INTO mint_quote (
-                id, amount, unit, request, expiry, request_lookup_id, pubkey, created_time, payment_method, request_lookup_id_kind, extra_json
+                id, amount, unit, request, expiry, request_lookup_id, pubkey, created_time, payment_method, request_lookup_id_kind, extra_json, backend, hold_invoice_state
                 )
                 VALUES (
-                :id, :amount, :unit, :request, :expiry, :request_lookup_id, :pubkey, :created_time, :payment_method, :request_lookup_id_kind, :extra_json
+                :id, :amount, :unit, :request, :expiry, :request_lookup_id, :pubkey, :created_time, :payment_method, :request_lookup_id_kind, :extra_json, :backend, :hold_invoice_state
                 )
             "#,
         )?
@@ -943,6 +1083,11 @@ where
             "extra_json",
             quote.extra_json.as_ref().map(|v| v.to_string()),
         )
+        .bind("backend", quote.backend.clone())
+        .bind(
+            "hold_invoice_state",
+            quote.hold_invoice.map(|state| state.to_string()),
+        )
         .execute(&self.inner)
         .await?;
 
@@ -965,14 +1110,14 @@ where
                 id, unit, amount, request, fee_reserve, state,
                 expiry, payment_proof, estimated_blocks, fee_options, selected_fee_index,
                 request_lookup_id, created_time, paid_time, options, request_lookup_id_kind,
-                payment_method, extra_json
+                payment_method, extra_json, backend
             )
             VALUES
             (
                 :id, :unit, :amount, :request, :fee_reserve, :state,
                 :expiry, :payment_proof, :estimated_blocks, :fee_options, :selected_fee_index,
                 :request_lookup_id, :created_time, :paid_time, :options, :request_lookup_id_kind,
-                :payment_method, :extra_json
+                :payment_method, :extra_json, :backend
             )
         "#,
         )?
@@ -1009,6 +1154,7 @@ where
             "extra_json",
             quote.extra_json.as_ref().map(|value| value.to_string()),
         )
+        .bind("backend", quote.backend)
         .execute(&self.inner)
         .await?;
 
@@ -1030,6 +1176,34 @@ where
         Ok(())
     }
 
+    async fn update_melt_quote_expiry(
+        &mut self,
+        quote: &mut Acquired<mint::MeltQuote>,
+        expiry: u64,
+    ) -> Result<(), Self::Err> {
+        query(r#"UPDATE melt_quote SET expiry = :expiry WHERE id = :id"#)?
+            .bind("expiry", expiry as i64)
+            .bind("id", quote.id.to_string())
+            .execute(&self.inner)
+            .await?;
+        quote.expiry = expiry;
+        Ok(())
+    }
+
+    async fn update_melt_quote_backend(
+        &mut self,
+        quote: &mut Acquired<mint::MeltQuote>,
+        backend: &str,
+    ) -> Result<(), Self::Err> {
+        query(r#"UPDATE melt_quote SET backend = :backend WHERE id = :id"#)?
+            .bind("backend", backend.to_owned())
+            .bind("id", quote.id.to_string())
+            .execute(&self.inner)
+            .await?;
+        quote.backend = Some(backend.to_owned());
+        Ok(())
+    }
+
     async fn update_melt_quote_state(
         &mut self,
         quote: &mut Acquired<mint::MeltQuote>,
@@ -1038,22 +1212,23 @@ where
     ) -> Result<MeltQuoteState, Self::Err> {
         let old_state = quote.state;
 
-        check_melt_quote_state_transition(old_state, state)?;
+        quote.check_state_transition(state)?;
 
         // NOTE: `fee_options` is intentionally omitted from both UPDATE
         // queries below. Per the NUT spec the returned `fee_options` are
         // fixed for the lifetime of the quote, so we never rewrite them
-        // after insert. Only state/paid_time/payment_proof/fee_reserve/
-        // estimated_blocks/selected_fee_index may change over the
-        // quote's lifetime.
+        // after insert. Only state/paid_time/payment_proof/fee_paid/
+        // fee_reserve/estimated_blocks/selected_fee_index may change over
+        // the quote's lifetime.
         let rec = if state == MeltQuoteState::Paid {
             let current_time = unix_time();
             quote.paid_time = Some(current_time);
             quote.payment_proof = payment_proof.clone();
-            query(r#"UPDATE melt_quote SET state = :state, paid_time = :paid_time, payment_proof = :payment_proof, fee_reserve = :fee_reserve, estimated_blocks = :estimated_blocks, selected_fee_index = :selected_fee_index WHERE id = :id"#)?
+            query(r#"UPDATE melt_quote SET state = :state, paid_time = :paid_time, payment_proof = :payment_proof, fee_paid = :fee_paid, fee_reserve = :fee_reserve, estimated_blocks = :estimated_blocks, selected_fee_index = :selected_fee_index WHERE id = :id"#)?
                 .bind("state", state.to_string())
                 .bind("paid_time", current_time as i64)
                 .bind("payment_proof", payment_proof)
+                .bind("fee_paid", quote.fee_paid.as_ref().map(|fee| fee.value() as i64))
                 .bind("fee_reserve", quote.fee_reserve().value() as i64)
                 .bind("estimated_blocks", quote.estimated_blocks.map(i64::from))
                 .bind("selected_fee_index", quote.selected_fee_index.map(i64::from))
@@ -1088,6 +1263,73 @@ where
         Ok(old_state)
     }
 
+    async fn remove_expired_mint_quotes(&mut self, expired_before: u64) -> Result<u64, Self::Err> {
+        let removed = query(
+            r#"
+            DELETE FROM mint_quote
+            WHERE expiry > 0 AND expiry < :expired_before
+              AND amount_paid = 0
+              AND amount_issued = 0
+              AND NOT EXISTS (SELECT 1 FROM mint_quote_payments WHERE quote_id = mint_quote.id)
+              AND NOT EXISTS (SELECT 1 FROM mint_quote_issued WHERE quote_id = mint_quote.id)
+              AND NOT EXISTS (SELECT 1 FROM blind_signature WHERE quote_id = mint_quote.id)
+            "#,
+        )?
+        .bind("expired_before", expired_before as i64)
+        .execute(&self.inner)
+        .await?;
+
+        Ok(removed as u64)
+    }
+
+    async fn remove_expired_melt_quotes(&mut self, expired_before: u64) -> Result<u64, Self::Err> {
+        let removed = query(
+            r#"
+            DELETE FROM melt_quote
+            WHERE expiry > 0 AND expiry < :expired_before
+              AND state IN (:states)
+              AND NOT EXISTS (SELECT 1 FROM melt_request WHERE quote_id = melt_quote.id)
+              AND NOT EXISTS (SELECT 1 FROM proof WHERE quote_id = melt_quote.id)
+              AND NOT EXISTS (SELECT 1 FROM blind_signature WHERE quote_id = melt_quote.id)
+            "#,
+        )?
+        .bind("expired_before", expired_before as i64)
+        .bind_vec(
+            "states",
+            vec![
+                MeltQuoteState::Unpaid.to_string(),
+                MeltQuoteState::Failed.to_string(),
+            ],
+        )?
+        .execute(&self.inner)
+        .await?;
+
+        Ok(removed as u64)
+    }
+
+    async fn add_internal_settlement(
+        &mut self,
+        settlement: &InternalSettlement,
+    ) -> Result<(), Self::Err> {
+        query(
+            r#"
+            INSERT INTO internal_settlement
+            (melt_quote_id, mint_quote_id, created_time, unit, amount, fee_reserve)
+            VALUES (:melt_quote_id, :mint_quote_id, :created_time, :unit, :amount, :fee_reserve)
+            "#,
+        )?
+        .bind("melt_quote_id", settlement.melt_quote_id.to_string())
+        .bind("mint_quote_id", settlement.mint_quote_id.to_string())
+        .bind("created_time", settlement.created_time as i64)
+        .bind("unit", settlement.unit.to_string())
+        .bind("amount", settlement.amount.to_i64())
+        .bind("fee_reserve", settlement.fee_reserve.to_i64())
+        .execute(&self.inner)
+        .await?;
+
+        Ok(())
+    }
+
     async fn get_mint_quote(
         &mut self,
         quote_id: &QuoteId,
@@ -1167,12 +1409,9 @@ where
         let metrics = MintMetricGuard::new("get_mint_quote");
 
         let result = async {
-            let conn = self
-                .pool
-                .get()
+            self.pool
+                .run(|conn| async move { get_mint_quote_inner(&*conn, quote_id, false).await })
                 .await
-                .map_err(|e| Error::Database(Box::new(e)))?;
-            get_mint_quote_inner(&*conn, quote_id, false).await
         }
         .await;
 
@@ -1188,36 +1427,31 @@ where
         &self,
         quote_ids: &[QuoteId],
     ) -> Result<Vec<Option<MintQuote>>, Self::Err> {
-        let conn = self
-            .pool
-            .get()
+        self.pool
+            .run(|conn| async move { get_mint_quotes_inner(&*conn, quote_ids, false).await })
             .await
-            .map_err(|e| Error::Database(Box::new(e)))?;
-        get_mint_quotes_inner(&*conn, quote_ids, false).await
     }
 
     async fn get_mint_quote_by_request(
         &self,
         request: &str,
     ) -> Result<Option<MintQuote>, Self::Err> {
-        let conn = self
-            .pool
-            .get()
+        self.pool
+            .run(
+                |conn| async move { get_mint_quote_by_request_inner(&*conn, request, false).await },
+            )
             .await
-            .map_err(|e| Error::Database(Box::new(e)))?;
-        get_mint_quote_by_request_inner(&*conn, request, false).await
     }
 
     async fn get_mint_quote_by_request_lookup_id(
         &self,
         request_lookup_id: &PaymentIdentifier,
     ) -> Result<Option<MintQuote>, Self::Err> {
-        let conn = self
-            .pool
-            .get()
+        self.pool
+            .run(|conn| async move {
+                get_mint_quote_by_request_lookup_id_inner(&*conn, request_lookup_id, false).await
+            })
             .await
-            .map_err(|e| Error::Database(Box::new(e)))?;
-        get_mint_quote_by_request_lookup_id_inner(&*conn, request_lookup_id, false).await
     }
 
     async fn get_mint_quotes(&self) -> Result<Vec<MintQuote>, Self::Err> {
@@ -1241,7 +1475,9 @@ where
                 amount_issued,
                 payment_method,
                 request_lookup_id_kind,
-                extra_json
+                extra_json,
+                backend,
+                hold_invoice_state
             FROM
                 mint_quote
             "#,
@@ -1270,12 +1506,9 @@ where
         let metrics = MintMetricGuard::new("get_melt_quote");
 
         let result = async {
-            let conn = self
-                .pool
-                .get()
+            self.pool
+                .run(|conn| async move { get_melt_quote_inner(&*conn, quote_id, false).await })
                 .await
-                .map_err(|e| Error::Database(Box::new(e)))?;
-            get_melt_quote_inner(&*conn, quote_id, false).await
         }
         .await;
 
@@ -1313,7 +1546,9 @@ where
                 request_lookup_id_kind,
                 extra_json,
                 fee_options,
-                selected_fee_index
+                selected_fee_index,
+                backend,
+                fee_paid
             FROM
                 melt_quote
             "#,
@@ -1324,4 +1559,227 @@ where
         .map(sql_row_to_melt_quote)
         .collect::<Result<Vec<_>, _>>()?)
     }
+
+    async fn get_daily_usage(&self, since: u64) -> Result<Vec<DailyUsage>, Self::Err> {
+        let conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| Error::Database(Box::new(e)))?;
+
+        let rows = query(
+            r#"
+            SELECT
+                day,
+                unit,
+                kind,
+                COUNT(*),
+                CAST(COALESCE(SUM(amount), 0) AS BIGINT)
+            FROM (
+                SELECT
+                    i.timestamp - (i.timestamp % 86400) AS day,
+                    q.unit AS unit,
+                    'mint' AS kind,
+                    i.amount AS amount
+                FROM
+                    mint_quote_issued i
+                JOIN mint_quote q ON i.quote_id = q.id
+                WHERE
+                    i.timestamp >= :since
+                UNION ALL
+                SELECT
+                    paid_time - (paid_time % 86400) AS day,
+                    unit,
+                    'melt' AS kind,
+                    amount
+                FROM
+                    melt_quote
+                WHERE
+                    state = :paid
+                    AND paid_time >= :since
+            ) activity
+            GROUP BY day, unit, kind
+            "#,
+        )?
+        .bind("since", since as i64)
+        .bind("paid", MeltQuoteState::Paid.to_string())
+        .fetch_all(&*conn)
+        .await?;
+
+        let mut usage: BTreeMap<(u64, CurrencyUnit), DailyUsage> = BTreeMap::new();
+
+        for row in rows {
+            unpack_into!(let (day, unit, kind, count, volume) = row);
+
+            let day: u64 = column_as_number!(day);
+            let unit = column_as_string!(unit, CurrencyUnit::from_str);
+            let kind = column_as_string!(kind);
+            let count: u64 = column_as_number!(count);
+            let volume: u64 = column_as_number!(volume);
+
+            let entry = usage
+                .entry((day, unit.clone()))
+                .or_insert_with(|| DailyUsage {
+                    day,
+                    unit,
+                    mint_count: 0,
+                    mint_volume: Amount::ZERO,
+                    melt_count: 0,
+                    melt_volume: Amount::ZERO,
+                });
+
+            if kind == "mint" {
+                entry.mint_count = count;
+                entry.mint_volume = Amount::from(volume);
+            } else {
+                entry.melt_count = count;
+                entry.melt_volume = Amount::from(volume);
+            }
+        }
+
+        Ok(usage.into_values().collect())
+    }
+    async fn get_internal_settlements(
+        &self,
+        since: Option<u64>,
+    ) -> Result<Vec<InternalSettlement>, Self::Err> {
+        let conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| Error::Database(Box::new(e)))?;
+        query(
+            r#"
+            SELECT
+                melt_quote_id,
+                mint_quote_id,
+                created_time,
+                unit,
+                amount,
+                fee_reserve
+            FROM
+                internal_settlement
+            WHERE
+                created_time >= :since
+            ORDER BY created_time DESC
+            "#,
+        )?
+        .bind("since", since.unwrap_or_default() as i64)
+        .fetch_all(&*conn)
+        .await?
+        .into_iter()
+        .map(sql_row_to_internal_settlement)
+        .collect()
+    }
+
+    async fn search_mint_quotes(
+        &self,
+        search: &QuoteSearch<MintQuoteState>,
+    ) -> Result<Vec<MintQuote>, Self::Err> {
+        let conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| Error::Database(Box::new(e)))?;
+
+        // Mirrors how `MintQuote::state` derives the state from the paid and issued amounts and
+        // the hold invoice state
+        let state = search.state.map(|state| match state {
+            MintQuoteState::Unpaid => {
+                "amount_paid = 0 AND amount_issued = 0 AND (hold_invoice_state IS NULL OR hold_invoice_state <> 'CANCELLED')"
+            }
+            MintQuoteState::Paid => {
+                "amount_paid > amount_issued AND (hold_invoice_state IS NULL OR hold_invoice_state <> 'CANCELLED')"
+            }
+            MintQuoteState::Issued => {
+                "amount_paid <= amount_issued AND NOT (amount_paid = 0 AND amount_issued = 0)"
+            }
+            MintQuoteState::Cancelled => {
+                "hold_invoice_state = 'CANCELLED' AND (amount_paid > amount_issued OR (amount_paid = 0 AND amount_issued = 0))"
+            }
+        });
+
+        let mut mint_quotes = quote_search_query(
+            "mint_quote",
+            r#"
+                id,
+                amount,
+                unit,
+                request,
+                expiry,
+                request_lookup_id,
+                pubkey,
+                created_time,
+                amount_paid,
+                amount_issued,
+                payment_method,
+                request_lookup_id_kind,
+                extra_json,
+                backend,
+                hold_invoice_state
+            "#,
+            search,
+            state,
+        )?
+        .fetch_all(&*conn)
+        .await?
+        .into_iter()
+        .map(|row| sql_row_to_mint_quote(row, vec![], vec![]))
+        .collect::<Result<Vec<_>, _>>()?;
+
+        for quote in mint_quotes.as_mut_slice() {
+            quote.payments = get_mint_quote_payments(&*conn, &quote.id).await?;
+            quote.issuance = get_mint_quote_issuance(&*conn, &quote.id).await?;
+        }
+
+        Ok(mint_quotes)
+    }
+
+    async fn search_melt_quotes(
+        &self,
+        search: &QuoteSearch<MeltQuoteState>,
+    ) -> Result<Vec<mint::MeltQuote>, Self::Err> {
+        let conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| Error::Database(Box::new(e)))?;
+
+        let mut q = quote_search_query(
+            "melt_quote",
+            r#"
+                id,
+                unit,
+                amount,
+                request,
+                fee_reserve,
+                expiry,
+                state,
+                payment_proof,
+                estimated_blocks,
+                request_lookup_id,
+                created_time,
+                paid_time,
+                payment_method,
+                options,
+                request_lookup_id_kind,
+                extra_json,
+                fee_options,
+                selected_fee_index,
+                backend,
+                fee_paid
+            "#,
+            search,
+            search.state.map(|_| "state = :state"),
+        )?;
+        if let Some(state) = search.state {
+            q = q.bind("state", state.to_string());
+        }
+
+        q.fetch_all(&*conn)
+            .await?
+            .into_iter()
+            .map(sql_row_to_melt_quote)
+            .collect()
+    }
 }
@@ -4,7 +4,9 @@ use std::collections::HashMap;
 use std::str::FromStr;
 
 use async_trait::async_trait;
-use cdk_common::database::{self, Error, MintSignatureTransaction, MintSignaturesDatabase};
+use cdk_common::database::{
+    self, Error, KeysetUsage, MintSignatureTransaction, MintSignaturesDatabase,
+};
 use cdk_common::quote_id::QuoteId;
 use cdk_common::util::unix_time;
 use cdk_common::{Amount, BlindSignature, BlindSignatureDleq, Id, PublicKey, SecretKey};
@@ -249,6 +251,36 @@ where
             .map(|y| blinded_signatures.remove(y))
             .collect())
     }
+
+    async fn increment_keyset_usage(
+        &mut self,
+        usage: &HashMap<Id, KeysetUsage>,
+    ) -> Result<(), Self::Err> {
+        for (keyset_id, usage) in usage {
+            if *usage == KeysetUsage::default() {
+                continue;
+            }
+
+            query(
+                r#"
+                INSERT INTO keyset_amounts
+                (keyset_id, total_issued, total_redeemed, signatures_issued, proofs_verified)
+                VALUES (:keyset_id, 0, 0, :signatures_issued, :proofs_verified)
+                ON CONFLICT (keyset_id)
+                DO UPDATE SET
+                    signatures_issued = keyset_amounts.signatures_issued + EXCLUDED.signatures_issued,
+                    proofs_verified = keyset_amounts.proofs_verified + EXCLUDED.proofs_verified
+                "#,
+            )?
+            .bind("keyset_id", keyset_id.to_string())
+            .bind("signatures_issued", usage.signatures_issued as i64)
+            .bind("proofs_verified", usage.proofs_verified as i64)
+            .execute(&self.inner)
+            .await?;
+        }
+
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -262,13 +294,11 @@ where
         &self,
         blinded_messages: &[PublicKey],
     ) -> Result<Vec<Option<BlindSignature>>, Self::Err> {
-        let conn = self
+        let mut blinded_signatures = self
             .pool
-            .get()
-            .await
-            .map_err(|e| Error::Database(Box::new(e)))?;
-        let mut blinded_signatures = query(
-            r#"SELECT
+            .run(|conn| async move {
+                query(
+                    r#"SELECT
                 keyset_id,
                 amount,
                 c,
@@ -279,28 +309,30 @@ where
                 blind_signature
             WHERE blinded_message IN (:b) AND c IS NOT NULL
             "#,
-        )?
-        .bind_vec(
-            "b",
-            blinded_messages
-                .iter()
-                .map(|b_| b_.to_bytes().to_vec())
-                .collect(),
-        )?
-        .fetch_all(&*conn)
-        .await?
-        .into_iter()
-        .map(|mut row| {
-            Ok((
-                column_as_string!(
-                    &row.pop().ok_or(Error::InvalidDbResponse)?,
-                    PublicKey::from_hex,
-                    PublicKey::from_slice
-                ),
-                sql_row_to_blind_signature(row)?,
-            ))
-        })
-        .collect::<Result<HashMap<_, _>, Error>>()?;
+                )?
+                .bind_vec(
+                    "b",
+                    blinded_messages
+                        .iter()
+                        .map(|b_| b_.to_bytes().to_vec())
+                        .collect(),
+                )?
+                .fetch_all(&*conn)
+                .await?
+                .into_iter()
+                .map(|mut row| {
+                    Ok((
+                        column_as_string!(
+                            &row.pop().ok_or(Error::InvalidDbResponse)?,
+                            PublicKey::from_hex,
+                            PublicKey::from_slice
+                        ),
+                        sql_row_to_blind_signature(row)?,
+                    ))
+                })
+                .collect::<Result<HashMap<_, _>, Error>>()
+            })
+            .await?;
         Ok(blinded_messages
             .iter()
             .map(|y| blinded_signatures.remove(y))
@@ -394,6 +426,43 @@ where
         .collect()
     }
 
+    async fn get_keyset_usage(&self) -> Result<HashMap<Id, KeysetUsage>, Self::Err> {
+        let conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| Error::Database(Box::new(e)))?;
+        query(
+            r#"
+            SELECT
+                keyset_id,
+                signatures_issued,
+                proofs_verified
+            FROM
+                keyset_amounts
+        "#,
+        )?
+        .fetch_all(&*conn)
+        .await?
+        .into_iter()
+        .map(|row| -> Result<(Id, KeysetUsage), Error> {
+            unpack_into!(
+                let (
+                    keyset_id, signatures_issued, proofs_verified
+                ) = row
+            );
+
+            Ok((
+                column_as_string!(keyset_id, Id::from_str, Id::from_bytes),
+                KeysetUsage {
+                    signatures_issued: column_as_number!(signatures_issued),
+                    proofs_verified: column_as_number!(proofs_verified),
+                },
+            ))
+        })
+        .collect()
+    }
+
     async fn get_blinded_secrets_by_operation_id(
         &self,
         operation_id: &uuid::Uuid,
@@ -3,11 +3,13 @@
 //! generic crate
 
 use std::fmt::Debug;
+use std::future::Future;
 use std::ops::{Deref, DerefMut};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
+use cdk_common::database::RetryPolicy;
 #[cfg(feature = "prometheus")]
 use cdk_prometheus::metrics::METRICS;
 use tokio::sync::{OwnedSemaphorePermit, Semaphore};
@@ -40,6 +42,11 @@ pub trait DatabaseConfig: Clone + Debug + Send + Sync {
 
     /// Default timeout
     fn default_timeout(&self) -> Duration;
+
+    /// How transient failures of [`Pool::run`] are retried
+    fn retry_policy(&self) -> RetryPolicy {
+        RetryPolicy::default()
+    }
 }
 
 /// Trait to manage resources
@@ -76,6 +83,7 @@ where
     queue: Mutex<Vec<(Arc<AtomicBool>, RM::Connection)>>,
     max_size: usize,
     default_timeout: Duration,
+    retry_policy: RetryPolicy,
     semaphore: Arc<Semaphore>,
 }
 
@@ -88,6 +96,7 @@ where
             .field("config", &self.config)
             .field("max_size", &self.max_size)
             .field("default_timeout", &self.default_timeout)
+            .field("retry_policy", &self.retry_policy)
             .field("available_permits", &self.semaphore.available_permits())
             .finish()
     }
@@ -172,6 +181,7 @@ where
         let max_size = config.max_size();
         Arc::new(Self {
             default_timeout: config.default_timeout(),
+            retry_policy: config.retry_policy(),
             max_size,
             config,
             queue: Default::default(),
@@ -179,6 +189,40 @@ where
         })
     }
 
+    /// Run `operation` on a resource of the pool, running it again on a fresh resource when it
+    /// fails with a transient error, following the [`RetryPolicy`] of the config
+    ///
+    /// Only for operations without effect when they fail, such as reads or starting a
+    /// transaction, never for statements inside a transaction.
+    pub async fn run<T, F, Fut>(
+        self: &Arc<Self>,
+        operation: F,
+    ) -> Result<T, cdk_common::database::Error>
+    where
+        F: Fn(PooledResource<RM>) -> Fut,
+        Fut: Future<Output = Result<T, cdk_common::database::Error>>,
+    {
+        let mut retry = 0;
+
+        loop {
+            let result = match self.get().await {
+                Ok(resource) => operation(resource).await,
+                Err(err) => Err(cdk_common::database::Error::Database(Box::new(err))),
+            };
+
+            match result {
+                Err(err) => match self.retry_policy.retry_after(&err, retry) {
+                    Some(backoff) => {
+                        tokio::time::sleep(backoff).await;
+                        retry += 1;
+                    }
+                    None => return Err(err),
+                },
+                result => return result,
+            }
+        }
+    }
+
     /// Similar to get_timeout but uses the default timeout value.
     #[inline(always)]
     pub async fn get(self: &Arc<Self>) -> Result<PooledResource<RM>, Error<RM::Error>> {
@@ -285,11 +329,11 @@ where
 #[cfg(all(test, feature = "prometheus"))]
 mod tests {
     use std::fmt;
-    use std::sync::atomic::AtomicBool;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
     use std::sync::Arc;
     use std::time::Duration;
 
-    use cdk_common::database::Error as DatabaseError;
+    use cdk_common::database::{Error as DatabaseError, RetryPolicy};
     use cdk_prometheus::METRICS;
 
     use super::{DatabaseConfig, DatabasePool, Error, Pool};
@@ -301,6 +345,8 @@ mod tests {
         max_size: usize,
         default_timeout: Duration,
         fail_new_resource: bool,
+        /// Statements left to fail with a transient error, shared by every connection
+        transient_failures: Arc<AtomicUsize>,
     }
 
     impl DatabaseConfig for TestConfig {
@@ -311,6 +357,14 @@ mod tests {
         fn default_timeout(&self) -> Duration {
             self.default_timeout
         }
+
+        fn retry_policy(&self) -> RetryPolicy {
+            RetryPolicy {
+                base_delay: Duration::from_millis(1),
+                max_delay: Duration::from_millis(1),
+                ..Default::default()
+            }
+        }
     }
 
     #[derive(Debug)]
@@ -325,7 +379,9 @@ mod tests {
     impl std::error::Error for TestResourceError {}
 
     #[derive(Debug)]
-    struct TestConnection;
+    struct TestConnection {
+        transient_failures: Arc<AtomicUsize>,
+    }
 
     #[async_trait::async_trait]
     impl DatabaseExecutor for TestConnection {
@@ -341,7 +397,18 @@ mod tests {
             &self,
             _statement: Statement,
         ) -> Result<Option<Vec<Column>>, DatabaseError> {
-            Ok(None)
+            let failing = self
+                .transient_failures
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |left| {
+                    left.checked_sub(1)
+                })
+                .is_ok();
+
+            if failing {
+                Err(DatabaseError::Transient("connection reset".into()))
+            } else {
+                Ok(None)
+            }
         }
 
         async fn fetch_all(
@@ -398,7 +465,9 @@ mod tests {
             if config.fail_new_resource {
                 Err(Error::Resource(TestResourceError))
             } else {
-                Ok(TestConnection)
+                Ok(TestConnection {
+                    transient_failures: config.transient_failures.clone(),
+                })
             }
         }
     }
@@ -408,9 +477,23 @@ mod tests {
             max_size,
             default_timeout: Duration::from_millis(10),
             fail_new_resource,
+            transient_failures: Arc::new(AtomicUsize::new(0)),
         }
     }
 
+    /// Runs a read on `pool`, returning how many times it was attempted along with its result
+    async fn run_fetch(pool: &Arc<Pool<TestPool>>) -> (usize, Result<(), DatabaseError>) {
+        let attempts = &AtomicUsize::new(0);
+        let result = pool
+            .run(|conn| async move {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                conn.fetch_one(Statement::default()).await.map(|_| ())
+            })
+            .await;
+
+        (attempts.load(Ordering::SeqCst), result)
+    }
+
     fn db_connections_active() -> f64 {
         for family in METRICS.registry().gather() {
             if family.get_name() != "cdk_db_connections_active" {
@@ -467,4 +550,48 @@ mod tests {
         assert_eq!(db_connections_active(), 0.0);
         assert_eq!(pool.semaphore.available_permits(), pool.max_size);
     }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn run_retries_transient_failures_of_the_connector() {
+        let _lock = crate::metrics_test_lock::lock().await;
+
+        let config = test_config(1, false);
+        config.transient_failures.store(2, Ordering::SeqCst);
+        let pool = Pool::<TestPool>::new(config);
+
+        let (attempts, result) = run_fetch(&pool).await;
+
+        assert!(result.is_ok());
+        assert_eq!(attempts, 3);
+        assert_eq!(pool.semaphore.available_permits(), pool.max_size);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn run_gives_up_once_the_retries_are_exhausted() {
+        let _lock = crate::metrics_test_lock::lock().await;
+
+        let config = test_config(1, false);
+        config
+            .transient_failures
+            .store(usize::MAX, Ordering::SeqCst);
+        let pool = Pool::<TestPool>::new(config);
+        let max_retries = pool.retry_policy.max_retries as usize;
+
+        let (attempts, result) = run_fetch(&pool).await;
+
+        assert!(matches!(result, Err(err) if err.is_transient()));
+        assert_eq!(attempts, max_retries + 1);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn run_does_not_retry_when_the_connector_cannot_connect() {
+        let _lock = crate::metrics_test_lock::lock().await;
+
+        let pool = Pool::<TestPool>::new(test_config(1, true));
+
+        let (attempts, result) = run_fetch(&pool).await;
+
+        assert!(matches!(result, Err(DatabaseError::Database(_))));
+        assert_eq!(attempts, 0);
+    }
 }
@@ -22,7 +22,7 @@ use cdk_common::{
 use tracing::instrument;
 use uuid::Uuid;
 
-use crate::common::migrate;
+use crate::common::{backup_to, health_check, migrate, DatabaseHealth};
 use crate::database::{ConnectionWithTransaction, DatabaseExecutor};
 use crate::pool::{DatabasePool, Pool, PooledResource};
 use crate::stmt::{query, Column};
@@ -36,6 +36,21 @@ mod migrations {
     include!(concat!(env!("OUT_DIR"), "/migrations_wallet.rs"));
 }
 
+/// Wallet tables reported by [`SQLWalletDatabase::health_check`]
+const WALLET_TABLES: &[&str] = &[
+    "mint",
+    "keyset",
+    "key",
+    "keyset_counter",
+    "mint_quote",
+    "melt_quote",
+    "proof",
+    "transactions",
+    "wallet_sagas",
+    "kv_store",
+    "p2pk_signing_key",
+];
+
 /// Wallet SQLite Database
 #[derive(Debug, Clone)]
 pub struct SQLWalletDatabase<RM>
@@ -60,6 +75,34 @@ where
         Ok(Self { pool })
     }
 
+    /// Snapshot the live database into a new file at `path`. Only supported by SQLite.
+    pub async fn backup_to(&self, path: &str) -> Result<(), Error> {
+        let conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| Error::Database(Box::new(e)))?;
+
+        backup_to(&*conn, path).await
+    }
+
+    /// Report the schema version, pending migrations, row counts and integrity check results
+    pub async fn health_check(&self) -> Result<DatabaseHealth, Error> {
+        let conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| Error::Database(Box::new(e)))?;
+
+        health_check(
+            &*conn,
+            RM::Connection::name(),
+            migrations::MIGRATIONS,
+            WALLET_TABLES,
+        )
+        .await
+    }
+
     /// Migrate [`WalletSqliteDatabase`]
     async fn migrate(conn: PooledResource<RM>) -> Result<(), Error> {
         let tx = ConnectionWithTransaction::new(conn).await?;
@@ -1044,6 +1087,9 @@ where
                     motd,
                     time,
                     tos_url,
+                    // Not stored, read again from the mint
+                    network: _,
+                    read_only: _,
                 } = mint_info;
 
                 (
@@ -1386,6 +1432,47 @@ where
         Ok(())
     }
 
+    #[instrument(skip(self))]
+    async fn remove_orphaned_keysets(&self) -> Result<u64, database::Error> {
+        let conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| Error::Database(Box::new(e)))?;
+        let tx = ConnectionWithTransaction::new(conn).await?;
+
+        let removed_keysets = query(
+            r#"
+            DELETE FROM keyset
+            WHERE mint_url NOT IN (SELECT mint_url FROM mint)
+            AND id NOT IN (SELECT DISTINCT keyset_id FROM proof)
+            "#,
+        )?
+        .execute(&tx)
+        .await?;
+
+        let removed_keys = query(r#"DELETE FROM key WHERE id NOT IN (SELECT id FROM keyset)"#)?
+            .execute(&tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok((removed_keysets + removed_keys) as u64)
+    }
+
+    #[instrument(skip(self))]
+    async fn vacuum(&self) -> Result<(), database::Error> {
+        let conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| Error::Database(Box::new(e)))?;
+
+        query(r#"VACUUM"#)?.execute(&*conn).await?;
+
+        Ok(())
+    }
+
     #[instrument(skip(self))]
     async fn add_saga(&self, saga: wallet::WalletSaga) -> Result<(), database::Error> {
         let conn = self
@@ -1963,6 +2050,8 @@ fn sql_row_to_mint_info(row: Vec<Column>) -> Result<MintInfo, Error> {
         motd: column_as_nullable_string!(motd),
         time: column_as_nullable_number!(mint_time).map(|t| t),
         tos_url: column_as_nullable_string!(tos_url),
+        network: None,
+        read_only: false,
     })
 }
 
@@ -1984,6 +2073,8 @@ fn sql_row_to_keyset(row: Vec<Column>) -> Result<KeySetInfo, Error> {
         active: matches!(active, Column::Integer(1)),
         input_fee_ppk: column_as_nullable_number!(input_fee_ppk).unwrap_or(0),
         final_expiry: column_as_nullable_number!(final_expiry),
+        rotation_time: None,
+        retirement_time: None,
     })
 }
 
@@ -55,6 +55,48 @@ mod tests {
         assert_eq!("test", &res.unwrap().description.unwrap());
     }
 
+    #[tokio::test]
+    #[cfg(not(feature = "sqlcipher"))]
+    async fn test_backup_to() {
+        use cdk_common::mint_url::MintUrl;
+        use cdk_common::MintInfo;
+
+        let db = memory::empty().await.unwrap();
+        let mint_info = MintInfo::new().description("test");
+        let mint_url = MintUrl::from_str("https://mint.xyz").unwrap();
+
+        db.add_mint(mint_url.clone(), Some(mint_info.clone()))
+            .await
+            .unwrap();
+
+        let path = std::env::temp_dir().join(format!("cdk-backup-{}.sqlite", uuid::Uuid::new_v4()));
+        db.backup_to(path.to_str().unwrap()).await.unwrap();
+
+        // The target must not exist
+        assert!(db.backup_to(path.to_str().unwrap()).await.is_err());
+
+        let backup = WalletSqliteDatabase::new(&path).await.unwrap();
+        assert_eq!(backup.get_mint(mint_url).await.unwrap(), Some(mint_info));
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_health_check() {
+        use cdk_common::mint_url::MintUrl;
+
+        let db = memory::empty().await.unwrap();
+        db.add_mint(MintUrl::from_str("https://mint.xyz").unwrap(), None)
+            .await
+            .unwrap();
+
+        let health = db.health_check().await.unwrap();
+        assert!(health.is_healthy());
+        assert!(health.schema_version.is_some());
+        assert_eq!(health.row_counts.get("mint"), Some(&1));
+        assert_eq!(health.row_counts.get("proof"), Some(&0));
+    }
+
     #[tokio::test]
     async fn test_proof_with_dleq() {
         use cdk_common::mint_url::MintUrl;
@@ -2454,6 +2454,8 @@ impl TryInto<MintInfo> for MintTable {
             motd: self.motd,
             time: self.mint_time.map(|t| t as u64),
             tos_url: self.tos_url,
+            network: None,
+            read_only: false,
         })
     }
 }
@@ -2497,6 +2499,8 @@ impl TryInto<KeySetInfo> for KeySetTable {
             active: self.active,
             input_fee_ppk: self.input_fee_ppk as u64,
             final_expiry: self.final_expiry.map(|v| v as u64),
+            rotation_time: None,
+            retirement_time: None,
         })
     }
 }
@@ -0,0 +1,301 @@
+//! Supply audit of the mint
+//!
+//! [`Mint::audit_report`] cross-checks what every keyset issued against what it redeemed, adds
+//! the paid mint quotes not yet issued to get what the mint owes per unit, and compares that to
+//! the balance its payment backends report. A keyset that redeemed more than it issued points to
+//! an inflation bug or a leaked key, and liabilities above the backend balance to a mint that
+//! could not honour every withdrawal. Melts stuck pending are listed, since the ecash behind them
+//! is neither spent nor available.
+
+use std::collections::BTreeMap;
+
+use cdk_common::util::unix_time;
+use tracing::instrument;
+
+use super::{CurrencyUnit, Id, MeltQuoteState, Mint, QuoteId};
+use crate::{Amount, Error};
+
+/// Issued and redeemed totals of a keyset, see [`AuditReport::keysets`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeysetAudit {
+    /// Keyset id
+    pub id: Id,
+    /// Unit of the keyset
+    pub unit: CurrencyUnit,
+    /// Whether the mint signs with the keyset
+    pub active: bool,
+    /// Total of the signatures of the keyset
+    pub issued: Amount,
+    /// Total of the proofs of the keyset spent
+    pub redeemed: Amount,
+}
+
+impl KeysetAudit {
+    /// Ecash of the keyset still in circulation
+    pub fn outstanding(&self) -> Amount {
+        self.issued.checked_sub(self.redeemed).unwrap_or_default()
+    }
+}
+
+/// What the mint owes in a unit and what its backends hold, see [`AuditReport::units`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnitAudit {
+    /// Unit audited
+    pub unit: CurrencyUnit,
+    /// Ecash of the keysets of the unit still in circulation
+    pub outstanding: Amount,
+    /// Paid on mint quotes and not issued yet
+    pub unissued: Amount,
+    /// Amount and fee reserve of the melt quotes being paid
+    pub pending_melts: Amount,
+    /// Largest balance reported by a backend of the unit, none when no backend can tell
+    ///
+    /// The backends of a unit usually share a node, so their balances are not added.
+    pub backend_balance: Option<Amount>,
+}
+
+impl UnitAudit {
+    /// Ecash in circulation plus the ecash owed to paid mint quotes
+    pub fn liabilities(&self) -> Amount {
+        self.outstanding
+            .checked_add(self.unissued)
+            .unwrap_or(Amount::from(u64::MAX))
+    }
+}
+
+/// Problem found by [`Mint::audit_report`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuditIssue {
+    /// A keyset redeemed more ecash than it issued
+    RedeemedExceedsIssued {
+        /// Keyset id
+        keyset_id: Id,
+        /// Amount redeemed above the issued one
+        excess: Amount,
+    },
+    /// The liabilities of a unit are above the balance of its backends
+    Undercollateralized {
+        /// Unit short of funds
+        unit: CurrencyUnit,
+        /// Amount missing to cover the liabilities
+        shortfall: Amount,
+    },
+    /// A melt has been pending for longer than [`STUCK_MELT_SECS`]
+    StuckMelt {
+        /// Id of the melt quote
+        quote_id: QuoteId,
+        /// Unit of the quote
+        unit: CurrencyUnit,
+        /// Amount and fee reserve of the quote
+        amount: Amount,
+    },
+    /// The balance of a backend could not be read
+    BackendUnavailable {
+        /// Unit the backend is registered for
+        unit: CurrencyUnit,
+        /// Name of the backend
+        backend: String,
+        /// Why the balance could not be read
+        error: String,
+    },
+}
+
+/// Seconds after its creation a pending melt is reported as stuck
+pub const STUCK_MELT_SECS: u64 = 24 * 60 * 60;
+
+/// Outcome of [`Mint::audit_report`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditReport {
+    /// Unix time of the audit
+    pub generated_at: u64,
+    /// Every keyset, ordered by unit then id
+    pub keysets: Vec<KeysetAudit>,
+    /// Every unit with a keyset or a backend
+    pub units: Vec<UnitAudit>,
+    /// Problems found, empty when the books balance
+    pub issues: Vec<AuditIssue>,
+}
+
+impl AuditReport {
+    /// Whether no problem was found
+    pub fn is_balanced(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Audit of `unit` in `units`, added empty if missing
+fn unit_audit<'a>(
+    units: &'a mut BTreeMap<String, UnitAudit>,
+    unit: &CurrencyUnit,
+) -> &'a mut UnitAudit {
+    units.entry(unit.to_string()).or_insert_with(|| UnitAudit {
+        unit: unit.clone(),
+        outstanding: Amount::ZERO,
+        unissued: Amount::ZERO,
+        pending_melts: Amount::ZERO,
+        backend_balance: None,
+    })
+}
+
+/// Add `amount` to `total`
+fn accrue(total: &mut Amount, amount: Amount) -> Result<(), Error> {
+    *total = total.checked_add(amount).ok_or(Error::AmountOverflow)?;
+    Ok(())
+}
+
+impl Mint {
+    /// Cross-check the issued and redeemed totals of every keyset against the pending quotes and
+    /// the balance of the payment backends
+    ///
+    /// Only reads the database and the backends, nothing is changed.
+    #[instrument(skip_all)]
+    pub async fn audit_report(&self) -> Result<AuditReport, Error> {
+        let now = unix_time();
+        let issued = self.localstore.get_total_issued().await?;
+        let redeemed = self.localstore.get_total_redeemed().await?;
+
+        let mut issues = Vec::new();
+        let mut units = BTreeMap::new();
+
+        let mut keysets: Vec<KeysetAudit> = self
+            .keysets()
+            .keysets
+            .into_iter()
+            .map(|keyset| KeysetAudit {
+                id: keyset.id,
+                issued: issued.get(&keyset.id).copied().unwrap_or_default(),
+                redeemed: redeemed.get(&keyset.id).copied().unwrap_or_default(),
+                unit: keyset.unit,
+                active: keyset.active,
+            })
+            .collect();
+        keysets.sort_by(|a, b| (a.unit.to_string(), a.id).cmp(&(b.unit.to_string(), b.id)));
+
+        for keyset in &keysets {
+            if keyset.redeemed > keyset.issued {
+                issues.push(AuditIssue::RedeemedExceedsIssued {
+                    keyset_id: keyset.id,
+                    excess: keyset
+                        .redeemed
+                        .checked_sub(keyset.issued)
+                        .unwrap_or_default(),
+                });
+            }
+
+            accrue(
+                &mut unit_audit(&mut units, &keyset.unit).outstanding,
+                keyset.outstanding(),
+            )?;
+        }
+
+        for quote in self.localstore.get_mint_quotes().await? {
+            let paid = quote.amount_paid().value();
+            let unissued = paid.saturating_sub(quote.amount_issued().value());
+            if unissued == 0 {
+                continue;
+            }
+
+            accrue(
+                &mut unit_audit(&mut units, &quote.unit).unissued,
+                Amount::from(unissued),
+            )?;
+        }
+
+        for quote in self.localstore.get_melt_quotes().await? {
+            if quote.state != MeltQuoteState::Pending {
+                continue;
+            }
+
+            let amount = Amount::from(quote.amount().value())
+                .checked_add(Amount::from(quote.fee_reserve().value()))
+                .ok_or(Error::AmountOverflow)?;
+            accrue(
+                &mut unit_audit(&mut units, &quote.unit).pending_melts,
+                amount,
+            )?;
+
+            if now.saturating_sub(quote.created_time) > STUCK_MELT_SECS {
+                issues.push(AuditIssue::StuckMelt {
+                    quote_id: quote.id.clone(),
+                    unit: quote.unit.clone(),
+                    amount,
+                });
+            }
+        }
+
+        for status in self.liquidity_status().await {
+            let audit = unit_audit(&mut units, &status.unit);
+            if let Some(outbound) = status.outbound {
+                let balance = Amount::from(outbound.value());
+                audit.backend_balance = Some(
+                    audit
+                        .backend_balance
+                        .map_or(balance, |current| current.max(balance)),
+                );
+            }
+
+            if let Some(error) = status.error {
+                issues.push(AuditIssue::BackendUnavailable {
+                    unit: status.unit,
+                    backend: status.backend,
+                    error,
+                });
+            }
+        }
+
+        let units: Vec<UnitAudit> = units.into_values().collect();
+        for audit in &units {
+            if let Some(balance) = audit.backend_balance {
+                if audit.liabilities() > balance {
+                    issues.push(AuditIssue::Undercollateralized {
+                        unit: audit.unit.clone(),
+                        shortfall: audit.liabilities().checked_sub(balance).unwrap_or_default(),
+                    });
+                }
+            }
+        }
+
+        for issue in &issues {
+            tracing::warn!("Audit found {:?}", issue);
+        }
+
+        Ok(AuditReport {
+            generated_at: now,
+            keysets,
+            units,
+            issues,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::mint::{create_test_mint, mint_test_proofs};
+
+    #[tokio::test]
+    async fn issued_ecash_is_accounted_for() {
+        let mint = create_test_mint().await.unwrap();
+        mint_test_proofs(&mint, Amount::from(64)).await.unwrap();
+
+        let report = mint.audit_report().await.unwrap();
+
+        assert!(report.is_balanced(), "{:?}", report.issues);
+        let active = report
+            .keysets
+            .iter()
+            .find(|keyset| keyset.active && keyset.unit == CurrencyUnit::Sat)
+            .unwrap();
+        assert_eq!(active.issued, Amount::from(64));
+        assert_eq!(active.redeemed, Amount::ZERO);
+
+        let sat = report
+            .units
+            .iter()
+            .find(|audit| audit.unit == CurrencyUnit::Sat)
+            .unwrap();
+        assert_eq!(sat.outstanding, Amount::from(64));
+        assert_eq!(sat.unissued, Amount::ZERO);
+        assert_eq!(sat.liabilities(), Amount::from(64));
+    }
+}
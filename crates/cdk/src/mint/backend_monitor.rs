@@ -0,0 +1,160 @@
+//! Health of the payment backends over time
+//!
+//! [`Mint::readiness`] tells whether a backend answers right now. The mint also keeps what
+//! happened since it started: whether the last check reached the backend, when the backend last
+//! reported an incoming payment, how long the last outgoing payment took and how many checks and
+//! payments failed in a row. Every update is mirrored to the Prometheus gauges of the backend.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use cdk_common::common::PaymentProcessorKey;
+use cdk_common::util::unix_time;
+
+use super::Mint;
+
+/// What happened with a payment backend since the mint started, see [`Mint::backend_stats`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BackendStats {
+    /// Whether the last check reached the backend, none before the first check
+    pub connected: Option<bool>,
+    /// Unix time the backend last reported an incoming payment
+    pub last_invoice_seen: Option<u64>,
+    /// Time the last outgoing payment took
+    pub last_payment_latency: Option<Duration>,
+    /// Checks and payments that failed since the last one that succeeded
+    pub error_streak: u32,
+}
+
+impl BackendStats {
+    fn record_outcome(&mut self, ok: bool) {
+        self.error_streak = if ok {
+            0
+        } else {
+            self.error_streak.saturating_add(1)
+        };
+    }
+}
+
+/// [`BackendStats`] of every payment backend
+#[derive(Debug, Default)]
+pub(crate) struct BackendMonitor {
+    stats: Mutex<HashMap<PaymentProcessorKey, BackendStats>>,
+}
+
+impl BackendMonitor {
+    /// Stats of the backend registered under `key`
+    pub fn stats(&self, key: &PaymentProcessorKey) -> BackendStats {
+        self.stats
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .get(key)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Record whether a check reached the backend
+    pub fn record_check(&self, key: &PaymentProcessorKey, backend: &str, ok: bool) {
+        self.update(key, backend, |stats| {
+            stats.connected = Some(ok);
+            stats.record_outcome(ok);
+        });
+    }
+
+    /// Record the backend reporting an incoming payment
+    pub fn record_invoice(&self, key: &PaymentProcessorKey, backend: &str) {
+        self.update(key, backend, |stats| {
+            stats.last_invoice_seen = Some(unix_time());
+        });
+    }
+
+    /// Record an outgoing payment that took `latency`
+    pub fn record_payment(
+        &self,
+        key: &PaymentProcessorKey,
+        backend: &str,
+        latency: Duration,
+        ok: bool,
+    ) {
+        self.update(key, backend, |stats| {
+            stats.last_payment_latency = Some(latency);
+            stats.record_outcome(ok);
+        });
+    }
+
+    fn update(&self, key: &PaymentProcessorKey, backend: &str, f: impl FnOnce(&mut BackendStats)) {
+        let mut all = self.stats.lock().unwrap_or_else(|err| err.into_inner());
+        let stats = all.entry(key.clone()).or_default();
+        f(stats);
+
+        #[cfg(feature = "prometheus")]
+        {
+            let (unit, method) = (key.unit.to_string(), key.method.to_string());
+            let metrics = &cdk_prometheus::METRICS;
+            if let Some(connected) = stats.connected {
+                metrics.set_payment_backend_connected(&unit, &method, backend, connected);
+            }
+            if let Some(seen) = stats.last_invoice_seen {
+                metrics.set_payment_backend_last_invoice_seen(&unit, &method, backend, seen);
+            }
+            if let Some(latency) = stats.last_payment_latency {
+                metrics.set_payment_backend_payment_latency(
+                    &unit,
+                    &method,
+                    backend,
+                    latency.as_secs_f64(),
+                );
+            }
+            metrics.set_payment_backend_error_streak(&unit, &method, backend, stats.error_streak);
+        }
+        #[cfg(not(feature = "prometheus"))]
+        let _ = backend;
+    }
+}
+
+impl Mint {
+    /// What happened with the payment backend registered under `key` since the mint started
+    pub fn backend_stats(&self, key: &PaymentProcessorKey) -> BackendStats {
+        self.backend_monitor.stats(key)
+    }
+
+    /// Name of the payment backend registered under `key`
+    pub(crate) fn backend_name(&self, key: &PaymentProcessorKey) -> String {
+        self.payment_processors
+            .get(key)
+            .map(|backend| backend.backend_name())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cdk_common::nut00::KnownMethod;
+    use cdk_common::{CurrencyUnit, PaymentMethod};
+
+    use super::*;
+
+    #[test]
+    fn error_streak_resets_on_success() {
+        let monitor = BackendMonitor::default();
+        let key =
+            PaymentProcessorKey::new(CurrencyUnit::Sat, PaymentMethod::Known(KnownMethod::Bolt11));
+        assert_eq!(monitor.stats(&key), BackendStats::default());
+
+        monitor.record_check(&key, "fake", false);
+        monitor.record_payment(&key, "fake", Duration::from_millis(250), false);
+        monitor.record_invoice(&key, "fake");
+
+        let stats = monitor.stats(&key);
+        assert_eq!(stats.connected, Some(false));
+        assert_eq!(stats.error_streak, 2);
+        assert_eq!(stats.last_payment_latency, Some(Duration::from_millis(250)));
+        assert!(stats.last_invoice_seen.is_some());
+
+        monitor.record_check(&key, "fake", true);
+        let stats = monitor.stats(&key);
+        assert_eq!(stats.connected, Some(true));
+        assert_eq!(stats.error_streak, 0);
+    }
+}
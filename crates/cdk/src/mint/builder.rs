@@ -1,6 +1,6 @@
 //! Mint Builder
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 
 use bitcoin::bip32::DerivationPath;
@@ -10,18 +10,20 @@ use cdk_common::nut00::KnownMethod;
 use cdk_common::nut04::MintMethodOptions;
 use cdk_common::nut05::MeltMethodOptions;
 use cdk_common::payment::DynMintPayment;
+use cdk_common::util::unix_time;
 use cdk_common::{nut21, nut22};
 use cdk_signatory::signatory::{RotateKeyArguments, Signatory};
+use tokio_util::sync::CancellationToken;
 
 use super::nut17::SupportedMethods;
 use super::nut19::{self, CachedEndpoint};
-use super::Nuts;
+use super::{KeysetRotationPolicy, LiquidityPolicy, Nuts, QuoteExpiryPolicy, VerificationPipeline};
 use crate::amount::Amount;
 use crate::cdk_database;
 use crate::mint::Mint;
 use crate::nuts::{
-    AuthRequired, ContactInfo, CurrencyUnit, MeltMethodSettings, MintInfo, MintMethodSettings,
-    MintVersion, MppMethodSettings, PaymentMethod, ProtectedEndpoint,
+    AuthRequired, ContactInfo, CurrencyUnit, Id, MeltMethodSettings, MintInfo, MintMethodSettings,
+    MintVersion, MppMethodSettings, PaymentMethod, ProtectedEndpoint, KEYSEND_METHOD,
 };
 use crate::types::PaymentProcessorKey;
 
@@ -71,10 +73,24 @@ pub struct MintBuilder {
     supported_units: HashMap<CurrencyUnit, (u64, Vec<u64>)>,
     custom_paths: HashMap<CurrencyUnit, DerivationPath>,
     use_keyset_v2: Option<bool>,
+    network: Option<bitcoin::Network>,
+    signatory_service: cdk_signatory::embedded::ServiceConfig,
     keyset_rotations: Vec<KeysetRotation>,
     max_inputs: usize,
     max_outputs: usize,
+    max_proof_content_len: Option<usize>,
     max_batch_size: Option<u64>,
+    verification_pipeline: VerificationPipeline,
+    clock_skew_grace_secs: u64,
+    usage_statistics: bool,
+    keyset_rotation_policy: Option<KeysetRotationPolicy>,
+    quote_expiry_policy: Option<QuoteExpiryPolicy>,
+    keyset_retirements: HashMap<Id, u64>,
+    max_denominations: BTreeMap<CurrencyUnit, Amount>,
+    liquidity_policy: LiquidityPolicy,
+    hold_invoices: bool,
+    issuance_caps: HashMap<CurrencyUnit, Amount>,
+    shutdown: CancellationToken,
 }
 
 impl std::fmt::Debug for MintBuilder {
@@ -114,10 +130,24 @@ impl MintBuilder {
             supported_units: HashMap::new(),
             custom_paths: HashMap::new(),
             use_keyset_v2: None,
+            network: None,
+            signatory_service: Default::default(),
             keyset_rotations: Vec::new(),
             max_inputs: 1000,
             max_outputs: 1000,
+            max_proof_content_len: None,
             max_batch_size: None,
+            verification_pipeline: VerificationPipeline::default(),
+            clock_skew_grace_secs: 0,
+            usage_statistics: false,
+            keyset_rotation_policy: None,
+            quote_expiry_policy: None,
+            keyset_retirements: HashMap::new(),
+            max_denominations: BTreeMap::new(),
+            liquidity_policy: LiquidityPolicy::default(),
+            hold_invoices: false,
+            issuance_caps: HashMap::new(),
+            shutdown: CancellationToken::new(),
         }
     }
 
@@ -127,6 +157,26 @@ impl MintBuilder {
         self
     }
 
+    /// Set the network the keys are derived for by [`MintBuilder::build_with_seed`], and the
+    /// network payments are settled on, see [`Mint::with_network`]
+    ///
+    /// Defaults to mainnet for the keys, without checking payments. Other networks derive
+    /// unrelated keys from the same seed.
+    pub fn with_network(mut self, network: bitcoin::Network) -> Self {
+        self.network = Some(network);
+        self
+    }
+
+    /// Set the queue depth, workers and request timeout of the signatory started by
+    /// [`MintBuilder::build_with_seed`]
+    pub fn with_signatory_service_config(
+        mut self,
+        config: cdk_signatory::embedded::ServiceConfig,
+    ) -> Self {
+        self.signatory_service = config;
+        self
+    }
+
     /// Add a keyset rotation to execute during build.
     /// Used to create inactive/expired keysets for testing.
     pub fn with_keyset_rotation(mut self, rotation: KeysetRotation) -> Self {
@@ -313,6 +363,100 @@ impl MintBuilder {
         self
     }
 
+    /// Refuse inputs whose secret or witness is longer than `len` bytes, see
+    /// [`Mint::with_max_proof_content_len`]
+    pub fn with_max_proof_content_len(mut self, len: usize) -> Self {
+        self.max_proof_content_len = Some(len);
+        self
+    }
+
+    /// Set the stages inputs, outputs and transactions are verified with
+    ///
+    /// Start from [`VerificationPipeline::default`] to keep the built-in checks.
+    pub fn with_verification_pipeline(mut self, pipeline: VerificationPipeline) -> Self {
+        self.verification_pipeline = pipeline;
+        self
+    }
+
+    /// Set the grace period, in seconds, applied to locktime checks
+    ///
+    /// Refunds of P2PK and HTLC proofs are accepted this long before their locktime, so wallets
+    /// whose clock runs a few seconds ahead are not refused.
+    pub fn with_clock_skew_grace(mut self, secs: u64) -> Self {
+        self.clock_skew_grace_secs = secs;
+        self
+    }
+
+    /// Allow the coarse daily usage statistics of [`Mint::usage_statistics`] to be queried
+    pub fn with_usage_statistics(mut self, enabled: bool) -> Self {
+        self.usage_statistics = enabled;
+        self
+    }
+
+    /// Give keysets a lifetime and replace them before they expire, see [`KeysetRotationPolicy`]
+    ///
+    /// Active keysets without a final expiry are rotated at startup so they get one.
+    pub fn with_keyset_rotation_policy(mut self, policy: KeysetRotationPolicy) -> Self {
+        self.keyset_rotation_policy = Some(policy);
+        self
+    }
+
+    /// Expire and purge stale quotes while the mint is running, see [`QuoteExpiryPolicy`]
+    pub fn with_quote_expiry_policy(mut self, policy: QuoteExpiryPolicy) -> Self {
+        self.quote_expiry_policy = Some(policy);
+        self
+    }
+
+    /// Retire keysets on a schedule, see [`Mint::with_keyset_retirements`]
+    ///
+    /// An active keyset already past its retirement is rotated at startup.
+    pub fn with_keyset_retirements(mut self, retirements: HashMap<Id, u64>) -> Self {
+        self.keyset_retirements = retirements;
+        self
+    }
+
+    /// Cap the largest output amount of each unit, see [`Mint::with_max_denominations`]
+    pub fn with_max_denominations(
+        mut self,
+        max_denominations: BTreeMap<CurrencyUnit, Amount>,
+    ) -> Self {
+        self.mint_info.nuts.max_denominations = max_denominations.clone();
+        self.max_denominations = max_denominations;
+        self
+    }
+
+    /// Cap the outstanding issuance of the active keyset of each unit, see
+    /// [`Mint::with_issuance_caps`]
+    pub fn with_issuance_caps(mut self, caps: HashMap<CurrencyUnit, Amount>) -> Self {
+        self.issuance_caps = caps;
+        self
+    }
+
+    /// Handle melt quotes above the outbound liquidity of their backend following `policy`, see
+    /// [`Mint::with_liquidity_policy`]
+    pub fn with_liquidity_policy(mut self, policy: LiquidityPolicy) -> Self {
+        self.liquidity_policy = policy;
+        self
+    }
+
+    /// Pay bolt11 mint quotes through hold invoices, see [`Mint::with_hold_invoices`]
+    pub fn with_hold_invoices(mut self, enabled: bool) -> Self {
+        self.hold_invoices = enabled;
+        self
+    }
+
+    /// Shut the mint down when `shutdown` is cancelled, see [`Mint::shutdown`]
+    pub fn with_shutdown_token(mut self, shutdown: CancellationToken) -> Self {
+        self.shutdown = shutdown;
+        self
+    }
+
+    /// Disable optional NUTs, see [`DISABLEABLE_NUTS`](super::DISABLEABLE_NUTS)
+    pub fn with_disabled_nuts(mut self, nuts: &[u8]) -> Result<Self, Error> {
+        super::disable_nuts(&mut self.mint_info.nuts, nuts)?;
+        Ok(self)
+    }
+
     /// Set batch minting settings (NUT-29)
     ///
     /// Configures the maximum number of quotes allowed in a single batch request
@@ -480,6 +624,20 @@ impl MintBuilder {
                     self.mint_info.nuts.nut05.disabled = false;
                 }
             }
+            // Keysend can only pay out, so it is a melt method only
+            PaymentMethod::Custom(custom_method) if custom_method == KEYSEND_METHOD => {
+                if settings.custom.contains_key(KEYSEND_METHOD) {
+                    let melt_method_settings = MeltMethodSettings {
+                        method: method.clone(),
+                        unit: unit.clone(),
+                        min_amount: Some(limits.melt_min),
+                        max_amount: Some(limits.melt_max),
+                        options: None,
+                    };
+                    self.mint_info.nuts.nut05.methods.push(melt_method_settings);
+                    self.mint_info.nuts.nut05.disabled = false;
+                }
+            }
             // Handle custom methods
             PaymentMethod::Custom(_) => {
                 // Check if this custom method is supported by the payment processor
@@ -587,6 +745,12 @@ impl MintBuilder {
                 .or_insert((0, vec![1]));
         }
 
+        // Refuse to start with units the signatory is not configured to serve
+        let signatory_config = signatory.supported_config().await?;
+        for (unit, (fee, amounts)) in &self.supported_units {
+            signatory_config.ensure_unit(unit, *fee, amounts, self.custom_paths.get(unit))?;
+        }
+
         for (unit, (fee, amounts)) in &self.supported_units {
             // Check if we have an active keyset for this unit
             let keyset = active_keysets
@@ -594,13 +758,33 @@ impl MintBuilder {
                 .iter()
                 .find(|k| k.active && k.unit == *unit);
 
-            let mut rotate = false;
+            let mut reasons = Vec::new();
 
             if let Some(keyset) = keyset {
-                if keyset.is_expired() {
+                if let Some(policy) = &self.keyset_rotation_policy {
+                    if keyset.final_expiry.is_none() {
+                        tracing::info!(
+                            "Rotating keyset for unit {} to give it a lifetime of {}s",
+                            unit,
+                            policy.lifetime.as_secs()
+                        );
+                        reasons.push("Keyset lifetime set");
+                    } else if keyset.is_expired() {
+                        tracing::info!("Rotating keyset for unit {} due to expiry", unit);
+                        reasons.push("Keyset expired");
+                    }
+                } else if keyset.is_expired() {
                     tracing::warn!("Active keyset for unit {} has expired; not rotating", unit);
                     continue;
                 }
+                if self
+                    .keyset_retirements
+                    .get(&keyset.id)
+                    .is_some_and(|retirement| *retirement < unix_time())
+                {
+                    tracing::info!("Rotating keyset for unit {} due to its retirement", unit);
+                    reasons.push("Keyset retired");
+                }
                 // Check if fee matches
                 if keyset.input_fee_ppk != *fee {
                     tracing::info!(
@@ -609,13 +793,13 @@ impl MintBuilder {
                         keyset.input_fee_ppk,
                         fee
                     );
-                    rotate = true;
+                    reasons.push("Fee changed");
                 }
 
                 // Check if amounts match
                 if keyset.amounts != *amounts {
                     tracing::info!("Rotating keyset for unit {} due to amounts mismatch", unit);
-                    rotate = true;
+                    reasons.push("Amounts changed");
                 }
 
                 // Check if version matches explicit preference
@@ -624,19 +808,19 @@ impl MintBuilder {
                         keyset.id.get_version() == cdk_common::nut02::KeySetVersion::Version01;
                     if want_v2 && !is_v2 {
                         tracing::info!("Rotating keyset for unit {} due to explicit V2 preference (current is V1)", unit);
-                        rotate = true;
+                        reasons.push("Keyset version changed");
                     } else if !want_v2 && is_v2 {
                         tracing::info!("Rotating keyset for unit {} due to explicit V1 preference (current is V2)", unit);
-                        rotate = true;
+                        reasons.push("Keyset version changed");
                     }
                 }
             } else {
                 // No active keyset for this unit
                 tracing::info!("Rotating keyset for unit {} (no active keyset found)", unit);
-                rotate = true;
+                reasons.push("No active keyset");
             }
 
-            if rotate {
+            if !reasons.is_empty() {
                 signatory
                     .rotate_keyset(RotateKeyArguments {
                         unit: unit.clone(),
@@ -647,7 +831,10 @@ impl MintBuilder {
                         } else {
                             cdk_common::nut02::KeySetVersion::Version00
                         },
-                        final_expiry: None,
+                        final_expiry: self
+                            .keyset_rotation_policy
+                            .map(|policy| policy.final_expiry(unix_time())),
+                        reason: Some(reasons.join(", ")),
                     })
                     .await?;
             }
@@ -666,6 +853,7 @@ impl MintBuilder {
                         cdk_common::nut02::KeySetVersion::Version00
                     },
                     final_expiry: rotation.final_expiry,
+                    reason: Some("Configured keyset rotation".to_owned()),
                 })
                 .await?;
         }
@@ -679,7 +867,7 @@ impl MintBuilder {
             ));
         }
 
-        if let Some(auth_localstore) = self.auth_localstore {
+        let mint = if let Some(auth_localstore) = self.auth_localstore {
             let mut protected_endpoints = HashMap::new();
             for endpoint in self.clear_auth_endpoints {
                 protected_endpoints.insert(endpoint, AuthRequired::Clear);
@@ -694,7 +882,7 @@ impl MintBuilder {
                 tx.commit().await?;
             }
 
-            return Mint::new_with_auth(
+            Mint::new_with_auth(
                 self.mint_info,
                 signatory,
                 self.localstore,
@@ -703,17 +891,49 @@ impl MintBuilder {
                 self.max_inputs,
                 self.max_outputs,
             )
-            .await;
-        }
-        Mint::new(
-            self.mint_info,
-            signatory,
-            self.localstore,
-            self.payment_processors,
-            self.max_inputs,
-            self.max_outputs,
-        )
-        .await
+            .await?
+        } else {
+            Mint::new(
+                self.mint_info,
+                signatory,
+                self.localstore,
+                self.payment_processors,
+                self.max_inputs,
+                self.max_outputs,
+            )
+            .await?
+        };
+
+        let mint = mint
+            .with_verification_pipeline(self.verification_pipeline)
+            .with_clock_skew_grace(self.clock_skew_grace_secs)
+            .with_usage_statistics(self.usage_statistics)
+            .with_keyset_retirements(self.keyset_retirements)
+            .with_max_denominations(self.max_denominations)
+            .with_liquidity_policy(self.liquidity_policy)
+            .with_hold_invoices(self.hold_invoices)
+            .with_issuance_caps(self.issuance_caps)
+            .with_shutdown_token(self.shutdown);
+
+        let mint = match self.network {
+            Some(network) => mint.with_network(network),
+            None => mint,
+        };
+
+        let mint = match self.max_proof_content_len {
+            Some(len) => mint.with_max_proof_content_len(len),
+            None => mint,
+        };
+
+        let mint = match self.quote_expiry_policy {
+            Some(policy) => mint.with_quote_expiry_policy(policy),
+            None => mint,
+        };
+
+        Ok(match self.keyset_rotation_policy {
+            Some(policy) => mint.with_keyset_rotation_policy(policy),
+            None => mint,
+        })
     }
 
     /// Build the mint with the provided keystore and seed
@@ -722,17 +942,20 @@ impl MintBuilder {
         keystore: Arc<dyn MintKeysDatabase<Err = cdk_database::Error> + Send + Sync>,
         seed: &[u8],
     ) -> Result<Mint, Error> {
-        let in_memory_signatory = cdk_signatory::db_signatory::DbSignatory::new(
+        let in_memory_signatory = cdk_signatory::db_signatory::DbSignatory::new_with_network(
             keystore,
             seed,
             self.supported_units.clone(),
             self.custom_paths.clone(),
+            self.network.unwrap_or(bitcoin::Network::Bitcoin),
         )
         .await?;
 
-        let signatory = Arc::new(cdk_signatory::embedded::Service::new(Arc::new(
-            in_memory_signatory,
-        )));
+        let signatory = Arc::new(cdk_signatory::embedded::Service::new_with_config(
+            Arc::new(in_memory_signatory),
+            self.signatory_service,
+            self.shutdown.child_token(),
+        ));
 
         self.build_with_signatory(signatory).await
     }
@@ -882,6 +1105,24 @@ mod tests {
             .to_vec()
     }
 
+    #[tokio::test]
+    async fn test_mint_builder_disabled_nuts() {
+        let localstore = Arc::new(memory::empty().await.unwrap());
+        let builder = MintBuilder::new(localstore)
+            .with_disabled_nuts(&[9, 20])
+            .unwrap();
+        let mint_info = builder.current_mint_info();
+
+        assert!(!mint_info.nuts.nut09.supported);
+        assert!(!mint_info.nuts.nut20.supported);
+        assert!(mint_info.nuts.nut07.supported);
+
+        let localstore = Arc::new(memory::empty().await.unwrap());
+        assert!(MintBuilder::new(localstore)
+            .with_disabled_nuts(&[12])
+            .is_err());
+    }
+
     #[tokio::test]
     async fn test_mint_builder_default_nuts_support() {
         let localstore = Arc::new(memory::empty().await.unwrap());
@@ -1042,6 +1283,29 @@ mod tests {
         ));
     }
 
+    #[tokio::test]
+    async fn test_build_with_signatory_rejects_mismatched_unit_config() {
+        let (builder, localstore) = builder_with_bolt11_processor().await;
+        let signatory = cdk_signatory::db_signatory::DbSignatory::new(
+            localstore,
+            &seed(),
+            HashMap::from([(CurrencyUnit::Sat, (100, UnitConfig::default().amounts))]),
+            HashMap::new(),
+        )
+        .await
+        .expect("signatory");
+
+        let err = builder
+            .build_with_signatory(Arc::new(signatory))
+            .await
+            .expect_err("fee differs from the signatory");
+
+        assert!(matches!(
+            err,
+            Error::SignatoryConfigMismatch { unit, .. } if unit == CurrencyUnit::Sat
+        ));
+    }
+
     #[tokio::test]
     async fn test_add_payment_processor_bolt11() {
         let localstore = Arc::new(memory::empty().await.unwrap());
@@ -1337,6 +1601,42 @@ mod tests {
         assert!(melt_method.options.is_none());
     }
 
+    #[tokio::test]
+    async fn test_add_payment_processor_keysend_is_melt_only() {
+        let localstore = Arc::new(memory::empty().await.unwrap());
+        let mut builder = MintBuilder::new(localstore);
+
+        let mut custom_methods = HashMap::new();
+        custom_methods.insert(KEYSEND_METHOD.to_string(), "{}".to_string());
+
+        let settings = SettingsResponse {
+            unit: "sat".to_string(),
+            bolt11: None,
+            bolt12: None,
+            onchain: None,
+            custom: custom_methods,
+        };
+
+        let payment_processor = Arc::new(MockPaymentProcessor { settings });
+        let method = PaymentMethod::from(KEYSEND_METHOD);
+        let limits = MintMeltLimits::new(1, 10000);
+
+        builder
+            .add_payment_processor(CurrencyUnit::Sat, method.clone(), limits, payment_processor)
+            .await
+            .unwrap();
+
+        let mint_info = builder.current_mint_info();
+
+        assert!(mint_info.nuts.nut04.methods.is_empty());
+        assert_eq!(mint_info.nuts.nut05.methods.len(), 1);
+        assert_eq!(mint_info.nuts.nut05.methods[0].method, method);
+        assert_eq!(
+            mint_info.nuts.nut05.methods[0].max_amount,
+            Some(limits.melt_max)
+        );
+    }
+
     #[tokio::test]
     async fn test_add_payment_processor_custom_not_supported() {
         let localstore = Arc::new(memory::empty().await.unwrap());
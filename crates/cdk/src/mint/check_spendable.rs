@@ -12,6 +12,8 @@ impl Mint {
         &self,
         check_state: &CheckStateRequest,
     ) -> Result<CheckStateResponse, Error> {
+        self.ensure_nut_enabled(7).await?;
+
         // Check max inputs limit
         let ys_count = check_state.ys.len();
         if ys_count > self.max_inputs {
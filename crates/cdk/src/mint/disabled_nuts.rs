@@ -0,0 +1,172 @@
+//! Optional NUTs an operator can turn off
+//!
+//! Disabled NUTs are advertised to wallets as unsupported in the stored
+//! [`MintInfo`](crate::nuts::MintInfo). The handlers consult the same flags, so a NUT can be
+//! switched on and off at runtime by updating the mint info.
+
+use cdk_common::nuts::nut10::{self, Kind};
+use cdk_common::Proofs;
+
+use super::Mint;
+use crate::error::Error;
+use crate::nuts::Nuts;
+
+/// NUTs that can be disabled on a mint
+///
+/// Disabling NUT-10 disables both NUT-11 (P2PK) and NUT-14 (HTLC). Disabling NUT-20 refuses
+/// mint quotes with a pubkey, which includes every BOLT12 mint quote.
+pub const DISABLEABLE_NUTS: [u8; 6] = [7, 9, 10, 11, 14, 20];
+
+/// Marks the `disabled` NUTs as unsupported
+///
+/// Fails if any of them is not in [`DISABLEABLE_NUTS`].
+pub fn disable_nuts(nuts: &mut Nuts, disabled: &[u8]) -> Result<(), Error> {
+    for nut in disabled {
+        match nut {
+            7 => nuts.nut07.supported = false,
+            9 => nuts.nut09.supported = false,
+            10 => {
+                nuts.nut10.supported = false;
+                nuts.nut11.supported = false;
+                nuts.nut14.supported = false;
+            }
+            11 => nuts.nut11.supported = false,
+            14 => nuts.nut14.supported = false,
+            20 => nuts.nut20.supported = false,
+            _ => {
+                return Err(Error::Custom(format!(
+                    "NUT-{nut:02} cannot be disabled, supported values are {DISABLEABLE_NUTS:?}"
+                )))
+            }
+        }
+    }
+
+    // Spending conditions are gone once both of its kinds are
+    if !nuts.nut11.supported && !nuts.nut14.supported {
+        nuts.nut10.supported = false;
+    }
+
+    Ok(())
+}
+
+impl Mint {
+    /// Fails with [`Error::NutDisabled`] if `nut` is not supported by the stored mint info
+    pub(crate) async fn ensure_nut_enabled(&self, nut: u8) -> Result<(), Error> {
+        let nuts = self.mint_info().await?.nuts;
+        let supported = match nut {
+            7 => nuts.nut07.supported,
+            9 => nuts.nut09.supported,
+            10 => nuts.nut10.supported,
+            11 => nuts.nut11.supported,
+            14 => nuts.nut14.supported,
+            20 => nuts.nut20.supported,
+            _ => true,
+        };
+
+        if supported {
+            Ok(())
+        } else {
+            Err(Error::NutDisabled(nut))
+        }
+    }
+
+    /// Fails if any input is locked by a spending condition the mint has disabled
+    pub(crate) async fn verify_input_conditions_enabled(
+        &self,
+        inputs: &Proofs,
+    ) -> Result<(), Error> {
+        let kinds = inputs
+            .iter()
+            .filter_map(|proof| nut10::Secret::try_from(&proof.secret).ok())
+            .map(|secret| secret.kind())
+            .collect::<Vec<_>>();
+
+        if kinds.is_empty() {
+            return Ok(());
+        }
+
+        let mint_info = self.mint_info().await?;
+        let nuts = &mint_info.nuts;
+
+        for kind in kinds {
+            let (nut, supported) = match kind {
+                Kind::P2PK => (11, nuts.nut11.supported),
+                Kind::HTLC => (14, nuts.nut14.supported),
+            };
+
+            if !nuts.nut10.supported {
+                return Err(Error::NutDisabled(10));
+            }
+
+            if !supported {
+                return Err(Error::NutDisabled(nut));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cdk_common::nuts::{
+        CheckStateRequest, ProofsMethods, RestoreRequest, SecretKey, SpendingConditions,
+    };
+    use cdk_common::secret::Secret;
+    use cdk_common::Amount;
+
+    use super::*;
+    use crate::test_helpers::mint::{create_test_mint, mint_test_proofs};
+
+    #[test]
+    fn disable_nuts_updates_supported_flags() {
+        let mut nuts = Nuts::new().nut07(true).nut10(true).nut11(true).nut14(true);
+
+        disable_nuts(&mut nuts, &[7, 11]).unwrap();
+        assert!(!nuts.nut07.supported);
+        assert!(!nuts.nut11.supported);
+        assert!(nuts.nut10.supported);
+        assert!(nuts.nut14.supported);
+
+        disable_nuts(&mut nuts, &[14]).unwrap();
+        assert!(!nuts.nut10.supported);
+
+        assert!(disable_nuts(&mut nuts, &[4]).is_err());
+    }
+
+    #[tokio::test]
+    async fn disabled_nuts_are_enforced() {
+        let mint = create_test_mint().await.unwrap();
+        let proofs = mint_test_proofs(&mint, Amount::from(64)).await.unwrap();
+
+        let mut mint_info = mint.mint_info().await.unwrap();
+        disable_nuts(&mut mint_info.nuts, &[7, 9, 11]).unwrap();
+        mint.set_mint_info(mint_info).await.unwrap();
+
+        let err = mint
+            .check_state(&CheckStateRequest {
+                ys: proofs.ys().unwrap(),
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::NutDisabled(7)));
+
+        let err = mint
+            .restore(RestoreRequest { outputs: vec![] })
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::NutDisabled(9)));
+
+        let mut locked = proofs.clone();
+        locked[0].secret = Secret::try_from(SpendingConditions::new_p2pk(
+            SecretKey::generate().public_key(),
+            None,
+        ))
+        .unwrap();
+        let err = mint.verify_inputs(&locked).await.unwrap_err();
+        assert!(matches!(err, Error::NutDisabled(11)));
+
+        // Plain proofs are not affected
+        mint.verify_inputs(&proofs).await.unwrap();
+    }
+}
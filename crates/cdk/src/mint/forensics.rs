@@ -0,0 +1,176 @@
+//! Forensic log of double spend attempts
+//!
+//! Requests refused because some of their inputs were already spent are recorded with the
+//! endpoint they were sent to, their quote and a hash identifying the client, so operators can
+//! look for fraud patterns. The hash is keyed with a secret kept in the KV store of the mint, so
+//! it can't be reversed by hashing every address, yet stays the same for a client across
+//! restarts.
+
+use cdk_common::database::DoubleSpendAttempt;
+use cdk_common::nuts::nut21::RoutePath;
+use cdk_common::util::unix_time;
+use tracing::instrument;
+
+use super::{Mint, QuoteId, CDK_MINT_PRIMARY_NAMESPACE};
+use crate::error::Error;
+use crate::nuts::{Proofs, ProofsMethods, SecretKey, State};
+
+/// KV secondary namespace of the forensic log
+const CDK_MINT_FORENSICS_SECONDARY_NAMESPACE: &str = "forensics";
+/// KV key of the secret client fingerprints are keyed with
+const CDK_MINT_CLIENT_FINGERPRINT_KEY_KV_KEY: &str = "client_fingerprint_key";
+
+impl Mint {
+    /// Secret the frontends key client fingerprints with, created on first use
+    #[instrument(skip(self))]
+    pub async fn client_fingerprint_key(&self) -> Result<[u8; 32], Error> {
+        if let Some(key) = self
+            .localstore
+            .kv_read(
+                CDK_MINT_PRIMARY_NAMESPACE,
+                CDK_MINT_FORENSICS_SECONDARY_NAMESPACE,
+                CDK_MINT_CLIENT_FINGERPRINT_KEY_KV_KEY,
+            )
+            .await?
+            .and_then(|key| <[u8; 32]>::try_from(key).ok())
+        {
+            return Ok(key);
+        }
+
+        let key = SecretKey::generate().to_secret_bytes();
+
+        let mut tx = self.localstore.begin_transaction().await?;
+        tx.kv_write(
+            CDK_MINT_PRIMARY_NAMESPACE,
+            CDK_MINT_FORENSICS_SECONDARY_NAMESPACE,
+            CDK_MINT_CLIENT_FINGERPRINT_KEY_KV_KEY,
+            &key,
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok(key)
+    }
+
+    /// Record that a request to `endpoint` was refused because some of `inputs` were spent
+    ///
+    /// Only the inputs the mint knows as spent are recorded, and nothing is when there are
+    /// none. `client_fingerprint` is an opaque hash of the client computed by the frontend,
+    /// keyed with [`Mint::client_fingerprint_key`].
+    #[instrument(skip_all, fields(endpoint = %endpoint))]
+    pub async fn record_double_spend_attempt(
+        &self,
+        endpoint: RoutePath,
+        quote_id: Option<QuoteId>,
+        client_fingerprint: Option<String>,
+        inputs: &Proofs,
+    ) -> Result<(), Error> {
+        let ys = inputs.ys()?;
+        let states = self.localstore.get_proofs_states(&ys).await?;
+
+        let spent: Vec<_> = ys
+            .into_iter()
+            .zip(states)
+            .filter_map(|(y, state)| (state == Some(State::Spent)).then_some(y))
+            .collect();
+
+        if spent.is_empty() {
+            return Ok(());
+        }
+
+        tracing::warn!(
+            "Double spend attempt of {} proofs on {} for quote {:?}",
+            spent.len(),
+            endpoint,
+            quote_id
+        );
+
+        let attempt = DoubleSpendAttempt {
+            created_time: unix_time(),
+            endpoint,
+            quote_id,
+            client_fingerprint,
+            ys: spent,
+        };
+
+        let mut tx = self.localstore.begin_transaction().await?;
+        tx.add_double_spend_attempt(&attempt).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Double spend attempts recorded at or after `since`, newest first
+    #[instrument(skip(self))]
+    pub async fn double_spend_attempts(
+        &self,
+        since: Option<u64>,
+    ) -> Result<Vec<DoubleSpendAttempt>, Error> {
+        Ok(self.localstore.get_double_spend_attempts(since).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cdk_common::mint::Operation;
+
+    use super::*;
+    use crate::test_helpers::mint::{create_test_mint, mint_test_proofs};
+    use crate::Amount;
+
+    #[tokio::test]
+    async fn client_fingerprint_key_is_kept() {
+        let mint = create_test_mint().await.unwrap();
+
+        let key = mint.client_fingerprint_key().await.unwrap();
+        assert_eq!(mint.client_fingerprint_key().await.unwrap(), key);
+    }
+
+    #[tokio::test]
+    async fn only_spent_inputs_are_recorded() {
+        let mint = create_test_mint().await.unwrap();
+        let spent = mint_test_proofs(&mint, Amount::from(8)).await.unwrap();
+        let unspent = mint_test_proofs(&mint, Amount::from(4)).await.unwrap();
+
+        let db = mint.localstore();
+        let mut tx = db.begin_transaction().await.unwrap();
+        let mut acquired = tx
+            .add_proofs(
+                spent.clone(),
+                None,
+                &Operation::new_swap(Amount::ZERO, Amount::ZERO, Amount::ZERO),
+            )
+            .await
+            .unwrap();
+        Mint::update_proofs_state(&mut tx, &mut acquired, State::Pending)
+            .await
+            .unwrap();
+        Mint::update_proofs_state(&mut tx, &mut acquired, State::Spent)
+            .await
+            .unwrap();
+        tx.commit().await.unwrap();
+
+        mint.record_double_spend_attempt(RoutePath::Swap, None, None, &unspent)
+            .await
+            .unwrap();
+        assert!(mint.double_spend_attempts(None).await.unwrap().is_empty());
+
+        let inputs: Proofs = spent.iter().chain(unspent.iter()).cloned().collect();
+        let quote_id = QuoteId::new();
+        mint.record_double_spend_attempt(
+            RoutePath::Melt("bolt11".to_owned()),
+            Some(quote_id.clone()),
+            Some("client".to_owned()),
+            &inputs,
+        )
+        .await
+        .unwrap();
+
+        let attempts = mint.double_spend_attempts(None).await.unwrap();
+        assert_eq!(attempts.len(), 1);
+        assert_eq!(attempts[0].endpoint, RoutePath::Melt("bolt11".to_owned()));
+        assert_eq!(attempts[0].quote_id, Some(quote_id));
+        assert_eq!(attempts[0].client_fingerprint.as_deref(), Some("client"));
+        assert_eq!(attempts[0].ys, spent.ys().unwrap());
+    }
+}
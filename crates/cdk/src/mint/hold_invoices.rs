@@ -0,0 +1,443 @@
+//! Minting against hold invoices
+//!
+//! With hold invoices enabled, bolt11 mint quotes of backends that support them are paid through
+//! a hold invoice whose preimage only the mint knows. A payment is accepted by the backend and
+//! marks the quote as paid, but the funds only reach the mint when the invoice is settled, which
+//! happens after the ecash is signed and before it is stored and handed out. A mint request that
+//! cannot settle its invoice fails without issuing anything.
+//!
+//! Invoices are settled and cancelled by the backend that created them, recorded on the quote as
+//! [`MintQuote::backend`]. The backend is never called inside a database transaction. Each settlement or cancellation is
+//! recorded on its quote once the backend did it, and a settled quote is not settled again, so a
+//! mint request failing after the settlement can simply be retried.
+//!
+//! Payments the wallet never mints are refunded: once the quote expired, [`Mint::expire_stale_quotes`]
+//! cancels its hold invoice.
+
+use std::time::Duration;
+
+use bitcoin::hashes::sha256::Hash as Sha256Hash;
+use bitcoin::hashes::Hash;
+use cdk_common::database::DynMintTransaction;
+use cdk_common::mint::MintQuote;
+use cdk_common::payment::DynMintPayment;
+use cdk_common::util::unix_time;
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use super::{Mint, QuoteId, CDK_MINT_PRIMARY_NAMESPACE};
+use crate::nuts::{HoldInvoiceState, MintQuoteState, SecretKey};
+use crate::util::hex;
+use crate::Error;
+
+/// KV secondary namespace of the hold invoice preimages, keyed by quote id
+const CDK_MINT_HOLD_INVOICES_SECONDARY_NAMESPACE: &str = "hold_invoices";
+
+/// Attempts at settling a hold invoice before the mint request fails
+const SETTLE_ATTEMPTS: u32 = 3;
+
+/// Delay between two attempts at settling a hold invoice
+const SETTLE_RETRY_DELAY: Duration = Duration::from_millis(200);
+
+/// Hold invoice of a mint quote, as stored
+///
+/// Its state is stored on the quote, see [`MintQuote::hold_invoice`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct HoldInvoice {
+    /// Preimage of the payment hash, hex encoded
+    preimage: String,
+}
+
+impl HoldInvoice {
+    /// Hold invoice with a fresh preimage
+    pub(crate) fn new() -> Self {
+        Self {
+            preimage: hex::encode(SecretKey::generate().to_secret_bytes()),
+        }
+    }
+
+    fn preimage(&self) -> Result<[u8; 32], Error> {
+        hex::decode(&self.preimage)
+            .ok()
+            .and_then(|preimage| preimage.try_into().ok())
+            .ok_or_else(|| Error::Custom("Invalid hold invoice preimage".to_string()))
+    }
+
+    /// Payment hash the invoice pays to
+    pub(crate) fn payment_hash(&self) -> Result<[u8; 32], Error> {
+        Ok(Sha256Hash::hash(&self.preimage()?).to_byte_array())
+    }
+}
+
+impl Mint {
+    /// Pay bolt11 mint quotes through hold invoices, for backends that support them
+    pub fn with_hold_invoices(mut self, enabled: bool) -> Self {
+        self.hold_invoices = enabled;
+        self
+    }
+
+    /// Whether the bolt11 mint quotes of `backend` are paid through hold invoices
+    pub(crate) fn uses_hold_invoices(&self, backend: &DynMintPayment) -> bool {
+        self.hold_invoices && backend.supports_hold_invoices()
+    }
+
+    /// Store the hold invoice of the new mint quote `quote_id`
+    pub(crate) async fn add_hold_invoice(
+        &self,
+        tx: &mut DynMintTransaction,
+        quote_id: &QuoteId,
+        hold_invoice: &HoldInvoice,
+    ) -> Result<(), Error> {
+        tx.kv_write(
+            CDK_MINT_PRIMARY_NAMESPACE,
+            CDK_MINT_HOLD_INVOICES_SECONDARY_NAMESPACE,
+            &quote_id.to_string(),
+            &serde_json::to_vec(hold_invoice)?,
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Hold invoice of the mint quote `quote_id`
+    async fn hold_invoice(&self, quote_id: &QuoteId) -> Result<HoldInvoice, Error> {
+        let bytes = self
+            .localstore
+            .kv_read(
+                CDK_MINT_PRIMARY_NAMESPACE,
+                CDK_MINT_HOLD_INVOICES_SECONDARY_NAMESPACE,
+                &quote_id.to_string(),
+            )
+            .await?
+            .ok_or_else(|| Error::Custom(format!("No hold invoice for mint quote {quote_id}")))?;
+
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+
+    /// Record the state of the hold invoice of `quote_id`, unless it already left `Open`
+    async fn record_hold_invoice_state(
+        &self,
+        quote_id: &QuoteId,
+        state: HoldInvoiceState,
+    ) -> Result<(), Error> {
+        let mut tx = self.localstore.begin_transaction().await?;
+        let mut quote = tx
+            .get_mint_quote(quote_id)
+            .await?
+            .ok_or(Error::UnknownQuote)?;
+
+        if quote.hold_invoice != Some(HoldInvoiceState::Open) {
+            tx.rollback().await?;
+            return Ok(());
+        }
+
+        tx.update_mint_quote_hold_invoice(&mut quote, state).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Settle the open hold invoices of `quotes`, whose ecash is signed but not stored yet
+    ///
+    /// Called before the mint transaction begins, so the backend is not waited on with the quotes
+    /// locked. A quote already settled is skipped, and backends accept settling an invoice again
+    /// when its settlement was not recorded.
+    #[instrument(skip_all)]
+    pub(crate) async fn settle_hold_invoices(&self, quotes: &[&MintQuote]) -> Result<(), Error> {
+        for quote in quotes {
+            match quote.hold_invoice {
+                Some(HoldInvoiceState::Open) => (),
+                None | Some(HoldInvoiceState::Settled) => continue,
+                Some(HoldInvoiceState::Cancelled) => {
+                    return Err(Error::ExpiredQuote(quote.expiry, unix_time()))
+                }
+            }
+
+            let preimage = self.hold_invoice(&quote.id).await?.preimage()?;
+            let backend =
+                self.get_payment_processor(quote.unit.clone(), quote.payment_method.clone())?;
+
+            let mut attempt = 1;
+            while let Err(err) = backend
+                .settle_hold_invoice_with_backend(quote.backend.as_deref(), preimage)
+                .await
+            {
+                if attempt >= SETTLE_ATTEMPTS {
+                    tracing::error!("Could not settle the hold invoice of {}: {}", quote.id, err);
+                    return Err(err.into());
+                }
+
+                tracing::warn!(
+                    "Could not settle the hold invoice of {}, retrying: {}",
+                    quote.id,
+                    err
+                );
+                attempt += 1;
+                tokio::time::sleep(SETTLE_RETRY_DELAY).await;
+            }
+
+            self.record_hold_invoice_state(&quote.id, HoldInvoiceState::Settled)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Cancel the open hold invoices of the mint quotes that expired before `now` without being
+    /// issued, returning how many were cancelled
+    pub(crate) async fn cancel_expired_hold_invoices(&self, now: u64) -> Result<u64, Error> {
+        let mut cancelled = 0;
+
+        for key in self
+            .localstore
+            .kv_list(
+                CDK_MINT_PRIMARY_NAMESPACE,
+                CDK_MINT_HOLD_INVOICES_SECONDARY_NAMESPACE,
+            )
+            .await?
+        {
+            let Ok(quote_id) = key.parse::<QuoteId>() else {
+                continue;
+            };
+            let Some(quote) = self.localstore.get_mint_quote(&quote_id).await? else {
+                continue;
+            };
+
+            if quote.hold_invoice != Some(HoldInvoiceState::Open)
+                || quote.expiry == 0
+                || quote.expiry >= now
+                || quote.state() == MintQuoteState::Issued
+            {
+                continue;
+            }
+
+            // Refused before the cancellation is recorded, so a failure is retried next time.
+            // A mint request settling the invoice meanwhile makes the backend refuse it too.
+            if let Err(err) = self
+                .get_payment_processor(quote.unit.clone(), quote.payment_method.clone())?
+                .cancel_hold_invoice_with_backend(
+                    quote.backend.as_deref(),
+                    self.hold_invoice(&quote_id).await?.payment_hash()?,
+                )
+                .await
+            {
+                tracing::warn!("Could not cancel the hold invoice of {}: {}", quote.id, err);
+                continue;
+            }
+
+            self.record_hold_invoice_state(&quote_id, HoldInvoiceState::Cancelled)
+                .await?;
+
+            tracing::info!(
+                "Cancelled the hold invoice of expired mint quote {}",
+                quote.id
+            );
+            cancelled += 1;
+        }
+
+        Ok(cancelled)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use cdk_common::amount::SplitTarget;
+    use cdk_common::mint_quote::{MintQuoteRequest, MintQuoteResponse};
+    use cdk_common::nut00::KnownMethod;
+    use cdk_common::payment::PaymentIdentifier;
+    use cdk_common::{CurrencyUnit, MintQuoteBolt11Request, MintRequest, PaymentMethod};
+    use cdk_fake_wallet::FakeWallet;
+
+    use super::*;
+    use crate::mint::{MintBuilder, MintInput, MintMeltLimits};
+    use crate::nuts::PreMintSecrets;
+    use crate::types::{FeeReserve, QuoteTTL};
+    use crate::Amount;
+
+    async fn create_hold_invoice_mint(wallet: Arc<FakeWallet>) -> Mint {
+        let db = Arc::new(cdk_sqlite::mint::memory::empty().await.unwrap());
+        let mut mint_builder = MintBuilder::new(db.clone()).with_hold_invoices(true);
+        mint_builder
+            .add_payment_processor(
+                CurrencyUnit::Sat,
+                PaymentMethod::Known(KnownMethod::Bolt11),
+                MintMeltLimits::new(1, 10_000),
+                wallet,
+            )
+            .await
+            .unwrap();
+
+        let mnemonic = bip39::Mnemonic::generate(12).unwrap();
+        let mint = mint_builder
+            .with_name("test mint".to_string())
+            .with_urls(vec!["https://test-mint".to_string()])
+            .build_with_seed(db, &mnemonic.to_seed_normalized(""))
+            .await
+            .unwrap();
+        mint.start().await.unwrap();
+        mint
+    }
+
+    fn fake_wallet() -> Arc<FakeWallet> {
+        Arc::new(FakeWallet::new(
+            FeeReserve {
+                min_fee_reserve: 1.into(),
+                percent_fee_reserve: 0.0,
+            },
+            HashMap::default(),
+            HashSet::default(),
+            0,
+            CurrencyUnit::Sat,
+        ))
+    }
+
+    async fn paid_quote(mint: &Mint) -> MintQuote {
+        let response = mint
+            .get_mint_quote(MintQuoteRequest::Bolt11(MintQuoteBolt11Request {
+                amount: Amount::from(64),
+                unit: CurrencyUnit::Sat,
+                description: None,
+                pubkey: None,
+            }))
+            .await
+            .unwrap();
+        let quote_id = response.quote().clone();
+
+        for _ in 0..50 {
+            let quote = mint
+                .localstore()
+                .get_mint_quote(&quote_id)
+                .await
+                .unwrap()
+                .unwrap();
+            if quote.state() == MintQuoteState::Paid {
+                return quote;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        panic!("mint quote was not paid");
+    }
+
+    async fn hold_invoice_state(mint: &Mint, quote: &MintQuote) -> Option<HoldInvoiceState> {
+        let MintQuoteResponse::Bolt11(response) = mint.check_mint_quote(&quote.id).await.unwrap()
+        else {
+            panic!("expected a bolt11 mint quote");
+        };
+        response.hold_invoice
+    }
+
+    fn mint_request(mint: &Mint, quote: &MintQuote) -> MintInput {
+        let keyset_id = *mint.get_active_keysets().get(&CurrencyUnit::Sat).unwrap();
+        let keys = mint
+            .keyset_pubkeys(&keyset_id)
+            .unwrap()
+            .keysets
+            .first()
+            .unwrap()
+            .keys
+            .clone();
+        let fees: (u64, Vec<u64>) = (0, keys.iter().map(|a| a.0.to_u64()).collect());
+
+        let premint = PreMintSecrets::random(
+            keyset_id,
+            Amount::from(64),
+            &SplitTarget::None,
+            &fees.into(),
+        )
+        .unwrap();
+        MintInput::Single(MintRequest {
+            quote: quote.id.clone(),
+            outputs: premint.blinded_messages().to_vec(),
+            signature: None,
+        })
+    }
+
+    #[tokio::test]
+    async fn hold_invoices_are_settled_when_minting() {
+        let wallet = fake_wallet();
+        let mint = create_hold_invoice_mint(wallet.clone()).await;
+        let quote = paid_quote(&mint).await;
+
+        let PaymentIdentifier::PaymentHash(payment_hash) = quote.request_lookup_id.clone() else {
+            panic!("bolt11 quotes are looked up by payment hash");
+        };
+        assert_eq!(
+            hold_invoice_state(&mint, &quote).await,
+            Some(HoldInvoiceState::Open)
+        );
+        assert_eq!(
+            wallet.hold_invoice_settled(&payment_hash).await,
+            Some(false)
+        );
+
+        mint.process_mint_request(mint_request(&mint, &quote))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            hold_invoice_state(&mint, &quote).await,
+            Some(HoldInvoiceState::Settled)
+        );
+        assert_eq!(wallet.hold_invoice_settled(&payment_hash).await, Some(true));
+    }
+
+    #[tokio::test]
+    async fn settled_hold_invoices_are_not_settled_again() {
+        let wallet = fake_wallet();
+        let mint = create_hold_invoice_mint(wallet.clone()).await;
+        let quote = paid_quote(&mint).await;
+
+        // A mint request that settled the invoice but failed to store the ecash
+        mint.settle_hold_invoices(&[&quote]).await.unwrap();
+        assert_eq!(
+            hold_invoice_state(&mint, &quote).await,
+            Some(HoldInvoiceState::Settled)
+        );
+
+        // Can be retried, the quote is still paid
+        mint.process_mint_request(mint_request(&mint, &quote))
+            .await
+            .unwrap();
+        assert_eq!(
+            mint.localstore()
+                .get_mint_quote(&quote.id)
+                .await
+                .unwrap()
+                .unwrap()
+                .state(),
+            MintQuoteState::Issued
+        );
+    }
+
+    #[tokio::test]
+    async fn expired_hold_invoices_are_cancelled() {
+        let wallet = fake_wallet();
+        let mint = create_hold_invoice_mint(wallet.clone()).await;
+        mint.set_quote_ttl(QuoteTTL::new(1, 1)).await.unwrap();
+        let quote = paid_quote(&mint).await;
+
+        assert_eq!(
+            mint.cancel_expired_hold_invoices(quote.expiry + 1)
+                .await
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            hold_invoice_state(&mint, &quote).await,
+            Some(HoldInvoiceState::Cancelled)
+        );
+
+        // The payment was refunded, so the quote cannot be minted anymore
+        let MintQuoteResponse::Bolt11(response) = mint.check_mint_quote(&quote.id).await.unwrap()
+        else {
+            panic!("expected a bolt11 mint quote");
+        };
+        assert_eq!(response.state, MintQuoteState::Cancelled);
+        assert!(matches!(
+            mint.process_mint_request(mint_request(&mint, &quote)).await,
+            Err(Error::ExpiredQuote(_, _))
+        ));
+    }
+}
@@ -0,0 +1,86 @@
+//! Inputs of the swaps and melts being processed
+//!
+//! A wallet retrying a request, or a client firing the same request several times, sends inputs
+//! the mint is already spending. Each request claims the hash of its input set while it is
+//! processed, so a replay of the same inputs is refused with [`Error::TokenPending`] before any
+//! database lookup.
+
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+use bitcoin::hashes::sha256;
+
+use super::Mint;
+use crate::error::Error;
+use crate::nuts::{Proofs, ProofsMethods};
+
+/// Hashes of the input sets claimed by requests in progress
+#[derive(Debug, Default)]
+pub(crate) struct InFlightInputs {
+    claimed: Mutex<HashSet<sha256::Hash>>,
+}
+
+/// Claim on an input set, released when dropped
+#[derive(Debug)]
+pub(crate) struct InputSetClaim {
+    inputs: Arc<InFlightInputs>,
+    hash: sha256::Hash,
+}
+
+impl InFlightInputs {
+    /// Claim `hash`, failing if another request holds it
+    fn claim(self: &Arc<Self>, hash: sha256::Hash) -> Result<InputSetClaim, Error> {
+        let mut claimed = self.claimed.lock().unwrap_or_else(|err| err.into_inner());
+        if !claimed.insert(hash) {
+            return Err(Error::TokenPending);
+        }
+
+        Ok(InputSetClaim {
+            inputs: Arc::clone(self),
+            hash,
+        })
+    }
+}
+
+impl Drop for InputSetClaim {
+    fn drop(&mut self) {
+        self.inputs
+            .claimed
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .remove(&self.hash);
+    }
+}
+
+impl Mint {
+    /// Claim `inputs` for the request being processed
+    ///
+    /// Fails with [`Error::TokenPending`] if a request spending the same set of inputs is in
+    /// progress. The claim is released when the returned guard is dropped.
+    pub(crate) fn claim_inputs(&self, inputs: &Proofs) -> Result<InputSetClaim, Error> {
+        let hash = inputs.input_set_hash()?;
+        self.in_flight_inputs.claim(hash).inspect_err(|_| {
+            tracing::debug!("Refusing replay of the inputs {} in progress", hash);
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::hashes::Hash;
+
+    use super::*;
+
+    #[test]
+    fn claimed_input_set_is_refused_until_released() {
+        let in_flight = Arc::new(InFlightInputs::default());
+        let hash = sha256::Hash::hash(b"inputs");
+
+        let claim = in_flight.claim(hash).expect("first claim");
+        assert!(matches!(in_flight.claim(hash), Err(Error::TokenPending)));
+        assert!(in_flight.claim(sha256::Hash::hash(b"other")).is_ok());
+
+        drop(claim);
+        assert!(in_flight.claim(hash).is_ok());
+    }
+}
@@ -0,0 +1,303 @@
+//! Cap on the outstanding issuance of each keyset
+//!
+//! A unit with a cap refuses new mint quotes once the ecash issued and not yet redeemed with its
+//! active keyset would exceed the cap. This bounds what a compromised key or an accounting bug
+//! can put in circulation. Rotating the keyset starts a new count, and an operator can lift the
+//! cap of a keyset with [`Mint::override_issuance_cap`] until its next rotation.
+//!
+//! The cap is checked when the quote is created and again before its outputs are signed, since
+//! several quotes may be created under the cap and paid before any of them is issued.
+
+use std::collections::{BTreeSet, HashMap};
+
+use tracing::instrument;
+
+use super::{
+    Mint, CDK_MINT_CONFIG_SECONDARY_NAMESPACE, CDK_MINT_ISSUANCE_CAP_OVERRIDES_KV_KEY,
+    CDK_MINT_PRIMARY_NAMESPACE,
+};
+use crate::nuts::{BlindedMessage, CurrencyUnit, Id};
+use crate::{Amount, Error};
+
+impl Mint {
+    /// Refuse mint quotes once the outstanding issuance of the active keyset of a unit would
+    /// exceed the amount it maps to
+    pub fn with_issuance_caps(mut self, caps: HashMap<CurrencyUnit, Amount>) -> Self {
+        self.issuance_caps = caps.into();
+        self
+    }
+
+    /// Largest amount the active keyset of `unit` may have outstanding, if capped
+    pub fn issuance_cap(&self, unit: &CurrencyUnit) -> Option<Amount> {
+        self.issuance_caps.get(unit).copied()
+    }
+
+    /// Amount issued and not yet redeemed with `keyset_id`
+    #[instrument(skip(self))]
+    pub async fn outstanding_issuance(&self, keyset_id: &Id) -> Result<Amount, Error> {
+        let issued = self
+            .localstore
+            .get_total_issued()
+            .await?
+            .remove(keyset_id)
+            .unwrap_or_default();
+        let redeemed = self
+            .localstore
+            .get_total_redeemed()
+            .await?
+            .remove(keyset_id)
+            .unwrap_or_default();
+
+        Ok(issued.checked_sub(redeemed).unwrap_or_default())
+    }
+
+    /// Lift the issuance cap of `keyset_id`, or restore it
+    ///
+    /// Overrides are kept across restarts. A keyset rotated in is capped again.
+    #[instrument(skip(self))]
+    pub async fn override_issuance_cap(
+        &self,
+        keyset_id: Id,
+        overridden: bool,
+    ) -> Result<(), Error> {
+        let mut overrides = self.issuance_cap_overrides().await?;
+        let changed = if overridden {
+            overrides.insert(keyset_id)
+        } else {
+            overrides.remove(&keyset_id)
+        };
+
+        if !changed {
+            return Ok(());
+        }
+
+        tracing::info!(
+            "Issuance cap of keyset {} {}",
+            keyset_id,
+            if overridden { "lifted" } else { "restored" }
+        );
+
+        let mut tx = self.localstore.begin_transaction().await?;
+        tx.kv_write(
+            CDK_MINT_PRIMARY_NAMESPACE,
+            CDK_MINT_CONFIG_SECONDARY_NAMESPACE,
+            CDK_MINT_ISSUANCE_CAP_OVERRIDES_KV_KEY,
+            &serde_json::to_vec(&overrides)?,
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Keysets whose issuance cap is lifted
+    pub async fn issuance_cap_overrides(&self) -> Result<BTreeSet<Id>, Error> {
+        let bytes = self
+            .localstore
+            .kv_read(
+                CDK_MINT_PRIMARY_NAMESPACE,
+                CDK_MINT_CONFIG_SECONDARY_NAMESPACE,
+                CDK_MINT_ISSUANCE_CAP_OVERRIDES_KV_KEY,
+            )
+            .await?;
+
+        match bytes {
+            Some(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            None => Ok(BTreeSet::new()),
+        }
+    }
+
+    /// Check that a mint quote of `amount` keeps the active keyset of `unit` under its cap
+    pub(crate) async fn check_issuance_cap(
+        &self,
+        unit: &CurrencyUnit,
+        amount: Option<Amount>,
+    ) -> Result<(), Error> {
+        let Some(cap) = self.issuance_cap(unit) else {
+            return Ok(());
+        };
+
+        let keyset_id = self
+            .keysets
+            .load()
+            .iter()
+            .find(|keyset| keyset.active && &keyset.unit == unit)
+            .map(|keyset| keyset.id)
+            .ok_or(Error::UnsupportedUnit)?;
+
+        if self.issuance_cap_overrides().await?.contains(&keyset_id) {
+            return Ok(());
+        }
+
+        self.check_keyset_issuance_cap(keyset_id, cap, amount.unwrap_or_default())
+            .await
+    }
+
+    /// Check that signing `outputs` of `unit` keeps each of their keysets under the cap
+    pub(crate) async fn check_outputs_issuance_cap(
+        &self,
+        unit: &CurrencyUnit,
+        outputs: &[BlindedMessage],
+    ) -> Result<(), Error> {
+        let Some(cap) = self.issuance_cap(unit) else {
+            return Ok(());
+        };
+
+        let mut amounts: HashMap<Id, Amount> = HashMap::new();
+        for output in outputs {
+            let amount = amounts.entry(output.keyset_id).or_default();
+            *amount = amount
+                .checked_add(output.amount)
+                .ok_or(Error::AmountOverflow)?;
+        }
+
+        let overrides = self.issuance_cap_overrides().await?;
+        for (keyset_id, amount) in amounts {
+            if overrides.contains(&keyset_id) {
+                continue;
+            }
+
+            self.check_keyset_issuance_cap(keyset_id, cap, amount)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn check_keyset_issuance_cap(
+        &self,
+        keyset_id: Id,
+        cap: Amount,
+        amount: Amount,
+    ) -> Result<(), Error> {
+        let outstanding = self.outstanding_issuance(&keyset_id).await?;
+        let requested = outstanding
+            .checked_add(amount)
+            .ok_or(Error::AmountOverflow)?;
+
+        if outstanding >= cap || requested > cap {
+            tracing::warn!(
+                "Keyset {} has {} outstanding of its {} cap, {} requested",
+                keyset_id,
+                outstanding,
+                cap,
+                amount
+            );
+            return Err(Error::IssuanceCapReached {
+                keyset_id,
+                outstanding,
+                cap,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use std::time::Duration;
+
+    use cdk_common::nuts::{MintQuoteBolt11Request, MintQuoteBolt11Response, MintQuoteState};
+    use cdk_common::{MintRequest, QuoteId};
+
+    use super::*;
+    use crate::mint::MintInput;
+    use crate::test_helpers::mint::{
+        create_test_blinded_messages, create_test_mint, get_active_keyset_id, mint_test_proofs,
+    };
+
+    fn quote_request(amount: u64) -> MintQuoteBolt11Request {
+        MintQuoteBolt11Request {
+            amount: Amount::from(amount),
+            unit: CurrencyUnit::Sat,
+            description: None,
+            pubkey: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn mint_quotes_are_refused_at_the_cap_until_overridden() {
+        let mint = create_test_mint()
+            .await
+            .unwrap()
+            .with_issuance_caps(HashMap::from([(CurrencyUnit::Sat, Amount::from(100))]));
+        let keyset_id = get_active_keyset_id(&mint).await.unwrap();
+
+        mint_test_proofs(&mint, Amount::from(64)).await.unwrap();
+        assert_eq!(
+            mint.outstanding_issuance(&keyset_id).await.unwrap(),
+            Amount::from(64)
+        );
+
+        // The quote fits under the cap, a larger one does not
+        mint.get_mint_quote(quote_request(36).into()).await.unwrap();
+        let err = mint
+            .get_mint_quote(quote_request(37).into())
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::IssuanceCapReached { keyset_id: id, .. } if id == keyset_id
+        ));
+
+        mint.override_issuance_cap(keyset_id, true).await.unwrap();
+        mint.get_mint_quote(quote_request(37).into()).await.unwrap();
+
+        mint.override_issuance_cap(keyset_id, false).await.unwrap();
+        assert!(mint.issuance_cap_overrides().await.unwrap().is_empty());
+        assert!(mint.get_mint_quote(quote_request(37).into()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn outputs_are_not_signed_over_the_cap() {
+        let mint = create_test_mint()
+            .await
+            .unwrap()
+            .with_issuance_caps(HashMap::from([(CurrencyUnit::Sat, Amount::from(100))]));
+        let keyset_id = get_active_keyset_id(&mint).await.unwrap();
+
+        // Created while nothing is outstanding, paid once another quote has been issued
+        let quote: MintQuoteBolt11Response<_> = mint
+            .get_mint_quote(quote_request(60).into())
+            .await
+            .unwrap()
+            .into();
+        mint_test_proofs(&mint, Amount::from(60)).await.unwrap();
+
+        let quote_id = QuoteId::from_str(&quote.quote).unwrap();
+        loop {
+            let check: MintQuoteBolt11Response<_> = mint
+                .check_mint_quotes(&[quote_id.clone()])
+                .await
+                .unwrap()
+                .remove(0)
+                .into();
+            if check.state == MintQuoteState::Paid {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+
+        let (outputs, _) = create_test_blinded_messages(&mint, Amount::from(60))
+            .await
+            .unwrap();
+        let request = MintRequest {
+            quote: quote.quote,
+            outputs,
+            signature: None,
+        };
+        let err = mint
+            .process_mint_request(MintInput::Single(request.try_into().unwrap()))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::IssuanceCapReached { keyset_id: id, .. } if id == keyset_id
+        ));
+        assert_eq!(
+            mint.outstanding_issuance(&keyset_id).await.unwrap(),
+            Amount::from(60)
+        );
+    }
+}
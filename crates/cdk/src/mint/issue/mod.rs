@@ -1,6 +1,7 @@
+use std::str::FromStr;
 use std::sync::Arc;
 
-use cdk_common::database::mint::Acquired;
+use cdk_common::database::mint::{Acquired, QuoteSearch};
 use cdk_common::mint::{MintQuote, Operation};
 use cdk_common::payment::{
     Bolt11IncomingPaymentOptions, Bolt12IncomingPaymentOptions, CustomIncomingPaymentOptions,
@@ -8,13 +9,16 @@ use cdk_common::payment::{
 };
 use cdk_common::quote_id::QuoteId;
 use cdk_common::util::unix_time;
+use cdk_common::Bolt11Invoice;
 use cdk_common::{
     database, ensure_cdk, Amount, BatchMintRequest, BlindedMessage, CurrencyUnit, Error,
-    MintQuoteBolt11Response, MintQuoteBolt12Response, MintQuoteOnchainResponse, MintQuoteState,
-    MintRequest, MintResponse, NotificationPayload, PaymentMethod, PublicKey,
+    HoldInvoiceState, MintQuoteBolt11Response, MintQuoteBolt12Response, MintQuoteOnchainResponse,
+    MintQuoteState, MintRequest, MintResponse, NotificationPayload, PaymentMethod, PublicKey,
 };
 use tracing::instrument;
 
+use crate::mint::hold_invoices::HoldInvoice;
+use crate::mint::retry::retry_transient;
 use crate::mint::verification::MAX_REQUEST_FIELD_LEN;
 use crate::Mint;
 
@@ -151,10 +155,11 @@ impl Mint {
 
         let nut04 = &mint_info.nuts.nut04;
         ensure_cdk!(!nut04.disabled, Error::MintingDisabled);
+        ensure_cdk!(!mint_info.read_only, Error::MintingDisabled);
 
-        let disabled = nut04.disabled;
-
-        ensure_cdk!(!disabled, Error::MintingDisabled);
+        if mint_quote_request.pubkey().is_some() {
+            ensure_cdk!(mint_info.nuts.nut20.supported, Error::NutDisabled(20));
+        }
 
         let settings = nut04
             .get_settings(&unit, &payment_method)
@@ -213,6 +218,7 @@ impl Mint {
             // Validate the request before processing
             self.check_mint_request_acceptable(&mint_quote_request)
                 .await?;
+            self.check_issuance_cap(&unit, amount).await?;
 
             // Extract pubkey using the getter
             let pubkey = mint_quote_request.pubkey();
@@ -326,15 +332,37 @@ impl Mint {
                 }
             };
 
-            let create_invoice_response = ln
-                .create_incoming_payment_request(payment_options)
-                .await
-                .map_err(|err| {
-                    tracing::error!("Could not create invoice: {}", err);
-                    Error::InvalidPaymentRequest
-                })?;
+            // Hold invoices are settled and cancelled by the backend that created them
+            let (create_invoice_response, hold_invoice, backend) = match payment_options {
+                IncomingPaymentOptions::Bolt11(bolt11_options) if self.uses_hold_invoices(&ln) => {
+                    let hold_invoice = HoldInvoice::new();
+                    ln.create_hold_invoice_with_backend(
+                        bolt11_options,
+                        hold_invoice.payment_hash()?,
+                    )
+                    .await
+                    .map(|(response, backend)| (response, Some(hold_invoice), backend))
+                }
+                payment_options => ln
+                    .create_incoming_payment_request(payment_options)
+                    .await
+                    .map(|response| (response, None, ln.backend_name())),
+            }
+            .map_err(|err| {
+                tracing::error!("Could not create invoice: {}", err);
+                Error::InvalidPaymentRequest
+            })?;
+
+            // A backend on another network must not hand out its invoices as the mint's
+            if payment_method.is_bolt11() {
+                if let Ok(invoice) = Bolt11Invoice::from_str(&create_invoice_response.request) {
+                    self.check_invoice_network(&invoice).inspect_err(|err| {
+                        tracing::error!("Payment backend created an invalid invoice: {}", err)
+                    })?;
+                }
+            }
 
-            let quote = MintQuote::new(
+            let mut quote = MintQuote::new(
                 Some(quote_id),
                 create_invoice_response.request.to_string(),
                 unit.clone(),
@@ -350,6 +378,10 @@ impl Mint {
                 vec![],
                 Some(create_invoice_response.extra_json.unwrap_or_default()),
             );
+            quote.backend = Some(backend);
+            if hold_invoice.is_some() {
+                quote.hold_invoice = Some(HoldInvoiceState::Open);
+            }
 
             tracing::debug!(
                 "New {} mint quote {} for {:?} {} with request id {:?}",
@@ -362,6 +394,10 @@ impl Mint {
 
             let mut tx = self.localstore.begin_transaction().await?;
             tx.add_mint_quote(quote.clone()).await?;
+            if let Some(hold_invoice) = hold_invoice {
+                self.add_hold_invoice(&mut tx, &quote.id, &hold_invoice)
+                    .await?;
+            }
             tx.commit().await?;
 
             if payment_method.is_bolt11() {
@@ -414,6 +450,15 @@ impl Mint {
         result
     }
 
+    /// Mint quotes matching `search`, newest first
+    #[instrument(skip_all)]
+    pub async fn search_mint_quotes(
+        &self,
+        search: &QuoteSearch<MintQuoteState>,
+    ) -> Result<Vec<MintQuote>, Error> {
+        Ok(self.localstore.search_mint_quotes(search).await?)
+    }
+
     /// Marks a mint quote as paid based on the payment request ID
     ///
     /// Looks up the mint quote by the payment request ID and marks it as paid
@@ -756,6 +801,9 @@ impl Mint {
                         }
                         return Err(Error::IssuedQuote);
                     }
+                    MintQuoteState::Cancelled => {
+                        return Err(Error::ExpiredQuote(mint_quote.expiry, unix_time()));
+                    }
                     MintQuoteState::Paid => (),
                 }
 
@@ -890,6 +938,10 @@ impl Mint {
                 );
             }
 
+            // Quotes paid since they were created under the cap may now exceed it together
+            self.check_outputs_issuance_cap(&batch_unit, input.outputs())
+                .await?;
+
             // Phase 4: Generate blind signatures (stateless, safe outside transaction)
             let all_blind_signatures = self.blind_sign(input.outputs().to_vec()).await?;
             let blinded_secrets = input
@@ -898,93 +950,116 @@ impl Mint {
                 .map(|p| p.blinded_secret)
                 .collect::<Vec<PublicKey>>();
 
-            // Phase 5: Atomic database transaction
-            let mut tx = self.localstore.begin_transaction().await?;
-
-            // For batch minting, outputs are shared across all quotes and should be persisted once.
-            if input.is_batch() {
-                let batch_operation =
-                    Operation::new_batch_mint(outputs_amount.clone().into(), batch_method.clone());
-                tx.add_blinded_messages(None, input.outputs(), &batch_operation)
-                    .await?;
-                tx.add_blind_signatures(&blinded_secrets, &all_blind_signatures, None)
-                    .await?;
-                let fee_by_keyset = std::collections::HashMap::new();
-                tx.add_completed_operation(&batch_operation, &fee_by_keyset)
-                    .await?;
-            }
-
-            for quote_id in &quote_ids {
-                // Get the mutable quote from transaction
-                let mut mint_quote = tx
-                    .get_mint_quote(quote_id)
-                    .await?
-                    .ok_or(Error::UnknownQuote)?;
+            // The ecash is only stored and handed out once the payments of hold invoices are
+            // settled, which happens before the transaction so no quote is locked meanwhile
+            let quotes: Vec<&MintQuote> = quote_map.values().collect();
+            self.settle_hold_invoices(&quotes).await?;
 
-                // Re-validate state within transaction (protects against race conditions)
-                match mint_quote.state() {
-                    MintQuoteState::Unpaid => {
-                        return Err(Error::UnpaidQuote);
-                    }
-                    MintQuoteState::Issued => {
-                        return Err(Error::IssuedQuote);
+            // Phase 5: Atomic database transaction, run again from the start on a transient
+            // database failure
+            {
+                let (input, quote_ids, expected_amounts) = (&input, &quote_ids, &expected_amounts);
+                let (outputs_amount, batch_method) = (&outputs_amount, &batch_method);
+                let (blinded_secrets, all_blind_signatures) =
+                    (&blinded_secrets, &all_blind_signatures);
+                retry_transient(|| async move {
+                    let mut tx = self.localstore.begin_transaction().await?;
+
+                    // For batch minting, outputs are shared across all quotes and should be persisted once.
+                    if input.is_batch() {
+                        let batch_operation = Operation::new_batch_mint(
+                            outputs_amount.clone().into(),
+                            batch_method.clone(),
+                        );
+                        tx.add_blinded_messages(None, input.outputs(), &batch_operation)
+                            .await?;
+                        tx.add_blind_signatures(blinded_secrets, all_blind_signatures, None)
+                            .await?;
+                        let fee_by_keyset = std::collections::HashMap::new();
+                        tx.add_completed_operation(&batch_operation, &fee_by_keyset)
+                            .await?;
                     }
-                    MintQuoteState::Paid => (),
-                }
 
-                let amount_issued = if input.is_batch() {
-                    // For batch: each quote is issued for its expected amount
-                    // (outputs are shared, not split per-quote)
-                    expected_amounts
-                        .get(quote_id)
-                        .cloned()
-                        .ok_or(Error::UnknownQuote)?
-                } else {
-                    // For single: issued amount = total outputs amount
-                    outputs_amount.clone()
-                };
+                    for quote_id in quote_ids {
+                        // Get the mutable quote from transaction
+                        let mut mint_quote = tx
+                            .get_mint_quote(quote_id)
+                            .await?
+                            .ok_or(Error::UnknownQuote)?;
+
+                        // Re-validate state within transaction (protects against race conditions)
+                        match mint_quote.state() {
+                            MintQuoteState::Unpaid => {
+                                return Err(Error::UnpaidQuote);
+                            }
+                            MintQuoteState::Issued => {
+                                return Err(Error::IssuedQuote);
+                            }
+                            MintQuoteState::Cancelled => {
+                                return Err(Error::ExpiredQuote(mint_quote.expiry, unix_time()));
+                            }
+                            MintQuoteState::Paid => (),
+                        }
 
-                let operation = Operation::new_mint(
-                    amount_issued.clone().into(),
-                    mint_quote.payment_method.clone(),
-                );
+                        let amount_issued = if input.is_batch() {
+                            // For batch: each quote is issued for its expected amount
+                            // (outputs are shared, not split per-quote)
+                            expected_amounts
+                                .get(quote_id)
+                                .cloned()
+                                .ok_or(Error::UnknownQuote)?
+                        } else {
+                            // For single: issued amount = total outputs amount
+                            outputs_amount.clone()
+                        };
+
+                        let operation = Operation::new_mint(
+                            amount_issued.clone().into(),
+                            mint_quote.payment_method.clone(),
+                        );
+
+                        if !input.is_batch() {
+                            tx.add_blinded_messages(Some(quote_id), input.outputs(), &operation)
+                                .await?;
+
+                            tx.add_blind_signatures(
+                                blinded_secrets,
+                                all_blind_signatures,
+                                Some(quote_id.clone()),
+                            )
+                            .await?;
+                        }
 
-                if !input.is_batch() {
-                    tx.add_blinded_messages(Some(quote_id), input.outputs(), &operation)
-                        .await?;
+                        mint_quote.add_issuance(amount_issued)?;
+                        tx.update_mint_quote(&mut mint_quote).await?;
 
-                    tx.add_blind_signatures(
-                        &blinded_secrets,
-                        &all_blind_signatures,
-                        Some(quote_id.clone()),
-                    )
-                    .await?;
-                }
+                        // Mint operations have no input fees
+                        // Only persist operation for non-batch mints (batch operations are persisted above)
+                        if !input.is_batch() {
+                            let fee_by_keyset = std::collections::HashMap::new();
+                            tx.add_completed_operation(&operation, &fee_by_keyset)
+                                .await?;
+                        }
+                    }
 
-                mint_quote.add_issuance(amount_issued)?;
-                tx.update_mint_quote(&mut mint_quote).await?;
+                    tx.commit().await?;
 
-                // Mint operations have no input fees
-                // Only persist operation for non-batch mints (batch operations are persisted above)
-                if !input.is_batch() {
-                    let fee_by_keyset = std::collections::HashMap::new();
-                    tx.add_completed_operation(&operation, &fee_by_keyset)
-                        .await?;
-                }
+                    Ok(())
+                })
+                .await?;
             }
 
-            tx.commit().await?;
-
             let localstore = Arc::clone(&self.localstore);
             let pubsub_manager = Arc::clone(&self.pubsub_manager);
-            tokio::spawn(async move {
-                // Publish notifications after successful commit
-                if let Ok(quotes) = localstore.get_mint_quotes_by_ids(&quote_ids).await {
+            self.tasks
+                .spawn_detached("mint_quote_notifications", async move {
+                    // Publish notifications after successful commit
+                    let quotes = localstore.get_mint_quotes_by_ids(&quote_ids).await?;
                     for mint_quote in quotes.iter().flatten() {
                         pubsub_manager.mint_quote_issue(mint_quote, mint_quote.amount_issued());
                     }
-                }
-            });
+                    Ok(())
+                });
 
             Ok(MintResponse {
                 signatures: all_blind_signatures,
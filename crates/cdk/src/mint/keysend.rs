@@ -0,0 +1,56 @@
+//! Keysend preimages
+//!
+//! A keysend payment carries its preimage to the receiving node, so the mint picks it when the
+//! quote is created. Only the payment hash is recorded on the quote, as its `request_lookup_id`,
+//! which lets the payment be looked up before and after it is made. The preimage itself is kept
+//! in the KV store, keyed by quote id, and handed to the backend when paying, so every attempt
+//! at paying a quote pays the same hash.
+
+use bitcoin::hashes::sha256::Hash as Sha256Hash;
+use bitcoin::hashes::Hash;
+use cdk_common::database::DynMintTransaction;
+
+use super::{Mint, QuoteId, CDK_MINT_PRIMARY_NAMESPACE};
+use crate::nuts::SecretKey;
+use crate::Error;
+
+/// KV secondary namespace of the keysend preimages, keyed by quote id
+const CDK_MINT_KEYSEND_PREIMAGES_SECONDARY_NAMESPACE: &str = "keysend_preimages";
+
+/// Fresh keysend preimage and its payment hash
+pub(crate) fn new_keysend_preimage() -> ([u8; 32], [u8; 32]) {
+    let preimage = SecretKey::generate().to_secret_bytes();
+    (preimage, Sha256Hash::hash(&preimage).to_byte_array())
+}
+
+impl Mint {
+    /// Store the keysend preimage of the new melt quote `quote_id`
+    pub(crate) async fn add_keysend_preimage(
+        &self,
+        tx: &mut DynMintTransaction,
+        quote_id: &QuoteId,
+        preimage: &[u8; 32],
+    ) -> Result<(), Error> {
+        tx.kv_write(
+            CDK_MINT_PRIMARY_NAMESPACE,
+            CDK_MINT_KEYSEND_PREIMAGES_SECONDARY_NAMESPACE,
+            &quote_id.to_string(),
+            preimage,
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Keysend preimage of the melt quote `quote_id`
+    pub(crate) async fn keysend_preimage(&self, quote_id: &QuoteId) -> Result<[u8; 32], Error> {
+        self.localstore
+            .kv_read(
+                CDK_MINT_PRIMARY_NAMESPACE,
+                CDK_MINT_KEYSEND_PREIMAGES_SECONDARY_NAMESPACE,
+                &quote_id.to_string(),
+            )
+            .await?
+            .and_then(|preimage| preimage.try_into().ok())
+            .ok_or_else(|| Error::Custom(format!("No keysend preimage for melt quote {quote_id}")))
+    }
+}
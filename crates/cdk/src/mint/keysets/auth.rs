@@ -40,6 +40,8 @@ impl Mint {
                             active: key.active,
                             input_fee_ppk: key.input_fee_ppk,
                             final_expiry: key.final_expiry,
+                            rotation_time: None,
+                            retirement_time: None,
                         })
                     } else {
                         None
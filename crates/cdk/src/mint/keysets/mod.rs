@@ -1,12 +1,18 @@
-use cdk_signatory::signatory::RotateKeyArguments;
+use cdk_common::keyset_export::SignedKeysetExport;
+use cdk_common::rotation_log::SignedRotationAttestation;
+use cdk_signatory::signatory::{RotateKeyArguments, UpdateKeysetConfigArguments};
 use tracing::instrument;
 
 use super::{
-    CurrencyUnit, Id, KeySet, KeySetInfo, KeysResponse, KeysetResponse, Mint, MintKeySetInfo,
+    CurrencyUnit, Id, KeySet, KeySetInfo, KeysResponse, KeysetResponse, Mint, MintChange,
+    MintKeySetInfo,
 };
 use crate::Error;
 
 mod auth;
+mod rotation;
+
+pub use rotation::KeysetRotationPolicy;
 
 impl Mint {
     /// Retrieve the public keys of the active keyset for distribution to wallet
@@ -39,6 +45,8 @@ impl Mint {
     }
 
     /// Return a list of all supported keysets
+    ///
+    /// Each keyset carries the time the mint plans to rotate and retire it, when known.
     #[instrument(skip_all)]
     pub fn keysets(&self) -> KeysetResponse {
         KeysetResponse {
@@ -53,6 +61,8 @@ impl Mint {
                     active: k.active,
                     input_fee_ppk: k.input_fee_ppk,
                     final_expiry: k.final_expiry,
+                    rotation_time: self.planned_keyset_rotation(k),
+                    retirement_time: self.keyset_retirement(&k.id),
                 })
                 .collect(),
         }
@@ -68,6 +78,23 @@ impl Mint {
             .map(|x| x.into())
     }
 
+    /// Export every keyset known to the mint, with its public keys and derivation metadata
+    ///
+    /// The export is signed by the signatory, with the key that signs the rotation log. Verify it
+    /// with [`SignedKeysetExport::verify`].
+    #[instrument(skip_all)]
+    pub async fn export_keysets(&self) -> Result<SignedKeysetExport, Error> {
+        Ok(self.signatory.export_keysets().await?)
+    }
+
+    /// Signed attestations of the keyset rotations, oldest first
+    ///
+    /// Verify the log with [`cdk_common::rotation_log::verify_rotation_log`].
+    #[instrument(skip_all)]
+    pub async fn rotation_log(&self) -> Result<Vec<SignedRotationAttestation>, Error> {
+        Ok(self.signatory.rotation_log().await?)
+    }
+
     /// Add current keyset to inactive keysets
     /// Generate new keyset
     #[instrument(skip(self))]
@@ -78,6 +105,28 @@ impl Mint {
         input_fee_ppk: u64,
         use_keyset_v2: bool,
         final_expiry: Option<u64>,
+    ) -> Result<MintKeySetInfo, Error> {
+        self.rotate_keyset_with_reason(
+            unit,
+            amounts,
+            input_fee_ppk,
+            use_keyset_v2,
+            final_expiry,
+            None,
+        )
+        .await
+    }
+
+    /// Rotate the keyset of `unit`, recording `reason` in the rotation log
+    #[instrument(skip(self))]
+    pub async fn rotate_keyset_with_reason(
+        &self,
+        unit: CurrencyUnit,
+        amounts: Vec<u64>,
+        input_fee_ppk: u64,
+        use_keyset_v2: bool,
+        final_expiry: Option<u64>,
+        reason: Option<String>,
     ) -> Result<MintKeySetInfo, Error> {
         let result = self
             .signatory
@@ -91,11 +140,62 @@ impl Mint {
                     cdk_common::nut02::KeySetVersion::Version00
                 },
                 final_expiry,
+                reason,
+            })
+            .await?;
+
+        let new_keyset = self.signatory.keysets().await?;
+        self.keysets.store(new_keyset.keysets.into());
+        let _ = self.changes.send(MintChange::Keysets);
+
+        Ok(result.into())
+    }
+
+    /// Set the fee and amounts of `unit` at runtime, rotating its keyset only if they changed
+    ///
+    /// Empty `amounts` keep the amounts of the active keyset. Returns the active keyset of the
+    /// unit, the new one if it was rotated.
+    #[instrument(skip(self))]
+    pub async fn update_keyset_config(
+        &self,
+        unit: CurrencyUnit,
+        amounts: Vec<u64>,
+        input_fee_ppk: u64,
+    ) -> Result<MintKeySetInfo, Error> {
+        let result = self
+            .signatory
+            .update_keyset_config(UpdateKeysetConfigArguments {
+                unit,
+                amounts,
+                input_fee_ppk,
             })
             .await?;
 
+        let rotated = !self
+            .keysets
+            .load()
+            .iter()
+            .any(|keyset| keyset.id == result.id);
+        if rotated {
+            let new_keyset = self.signatory.keysets().await?;
+            self.keysets.store(new_keyset.keysets.into());
+            let _ = self.changes.send(MintChange::Keysets);
+        }
+
+        Ok(result.into())
+    }
+
+    /// Stop signing with the keyset `id` right away, e.g. after its keys were compromised
+    ///
+    /// Unlike [`Mint::rotate_keyset`] no keyset takes its place, the unit can not be minted
+    /// until it is rotated. Proofs already signed with the keyset are still accepted.
+    #[instrument(skip(self))]
+    pub async fn disable_keyset(&self, id: Id) -> Result<MintKeySetInfo, Error> {
+        let result = self.signatory.disable_keyset(id).await?;
+
         let new_keyset = self.signatory.keysets().await?;
         self.keysets.store(new_keyset.keysets.into());
+        let _ = self.changes.send(MintChange::Keysets);
 
         Ok(result.into())
     }
@@ -0,0 +1,263 @@
+//! Scheduled keyset rotation
+//!
+//! With a [`KeysetRotationPolicy`] every keyset the mint creates expires `lifetime` after it
+//! was created, and a background task replaces the active keyset of a unit with a fresh one
+//! shortly before it expires. Wallets swap their proofs into the new keyset before the old one
+//! stops being accepted.
+//!
+//! Operators can also retire keysets at a given time, see [`Mint::with_keyset_retirements`],
+//! which works for keysets created without a final expiry. The planned rotation and retirement
+//! times are published with the keysets so wallets can migrate ahead of them.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use cdk_common::nut02::KeySetVersion;
+use cdk_common::util::unix_time;
+use cdk_signatory::signatory::SignatoryKeySet;
+use tokio_util::sync::CancellationToken;
+use tracing::instrument;
+
+use super::{CurrencyUnit, Id, Mint, MintKeySetInfo};
+use crate::Error;
+
+/// How long keysets live and when they are replaced
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeysetRotationPolicy {
+    /// Time from the creation of a keyset to its final expiry
+    pub lifetime: Duration,
+    /// How long before its final expiry the active keyset is replaced
+    pub rotate_before: Duration,
+    /// How often the active keysets are checked
+    pub check_interval: Duration,
+}
+
+impl KeysetRotationPolicy {
+    /// Keysets living `lifetime`, replaced when a quarter of it is left
+    pub fn new(lifetime: Duration) -> Self {
+        Self {
+            lifetime,
+            rotate_before: lifetime / 4,
+            check_interval: Duration::from_secs(60),
+        }
+    }
+
+    /// Final expiry of a keyset created at `now`
+    pub fn final_expiry(&self, now: u64) -> u64 {
+        now.saturating_add(self.lifetime.as_secs())
+    }
+
+    /// When the `keyset` retiring at `retires_at` is due to be replaced, if it is active
+    fn rotation_time(&self, keyset: &SignatoryKeySet, retires_at: Option<u64>) -> Option<u64> {
+        if !keyset.active {
+            return None;
+        }
+
+        retires_at.map(|time| time.saturating_sub(self.rotate_before.as_secs()))
+    }
+
+    /// Whether the active `keyset` retiring at `retires_at` has to be replaced at `now`
+    ///
+    /// Keysets that neither expire nor are retired are never due.
+    fn rotation_due(&self, keyset: &SignatoryKeySet, retires_at: Option<u64>, now: u64) -> bool {
+        self.rotation_time(keyset, retires_at)
+            .is_some_and(|time| time <= now)
+    }
+}
+
+impl Mint {
+    /// Time the operator scheduled `keyset_id` to be retired at, if any
+    pub fn keyset_retirement(&self, keyset_id: &Id) -> Option<u64> {
+        self.keyset_retirements.get(keyset_id).copied()
+    }
+
+    /// Whether the mint stopped accepting proofs of `keyset_id` on its retirement schedule
+    pub(crate) fn is_keyset_retired(&self, keyset_id: &Id) -> bool {
+        self.keyset_retirement(keyset_id)
+            .is_some_and(|retirement| retirement < unix_time())
+    }
+
+    /// Earliest of the final expiry and the scheduled retirement of `keyset`
+    fn keyset_retires_at(&self, keyset: &SignatoryKeySet) -> Option<u64> {
+        match (keyset.final_expiry, self.keyset_retirement(&keyset.id)) {
+            (Some(expiry), Some(retirement)) => Some(expiry.min(retirement)),
+            (expiry, retirement) => expiry.or(retirement),
+        }
+    }
+
+    /// When the rotation policy replaces `keyset`, if it is active and a policy is set
+    pub(crate) fn planned_keyset_rotation(&self, keyset: &SignatoryKeySet) -> Option<u64> {
+        self.keyset_rotation_policy?
+            .rotation_time(keyset, self.keyset_retires_at(keyset))
+    }
+
+    /// Replace the active keyset of `unit` with one expiring `lifetime` from now
+    #[instrument(skip(self))]
+    pub async fn rotate_keyset_with_lifetime(
+        &self,
+        unit: CurrencyUnit,
+        amounts: Vec<u64>,
+        input_fee_ppk: u64,
+        use_keyset_v2: bool,
+        lifetime: Duration,
+    ) -> Result<MintKeySetInfo, Error> {
+        let final_expiry = unix_time().saturating_add(lifetime.as_secs());
+
+        self.rotate_keyset(
+            unit,
+            amounts,
+            input_fee_ppk,
+            use_keyset_v2,
+            Some(final_expiry),
+        )
+        .await
+    }
+
+    /// Replace every active keyset the rotation policy says is due
+    ///
+    /// The new keyset keeps the amounts, fee and id version of the one it replaces. Returns the
+    /// keysets created.
+    pub async fn rotate_expiring_keysets(&self) -> Result<Vec<MintKeySetInfo>, Error> {
+        let Some(policy) = self.keyset_rotation_policy else {
+            return Ok(Vec::new());
+        };
+
+        let now = unix_time();
+        let due: Vec<_> = self
+            .keysets
+            .load()
+            .iter()
+            .filter(|keyset| policy.rotation_due(keyset, self.keyset_retires_at(keyset), now))
+            .cloned()
+            .collect();
+
+        let mut rotated = Vec::with_capacity(due.len());
+        for keyset in due {
+            tracing::info!(
+                "Rotating keyset {} of unit {} retiring at {}",
+                keyset.id,
+                keyset.unit,
+                self.keyset_retires_at(&keyset).unwrap_or_default()
+            );
+
+            rotated.push(
+                self.rotate_keyset_with_reason(
+                    keyset.unit,
+                    keyset.amounts,
+                    keyset.input_fee_ppk,
+                    keyset.id.get_version() == KeySetVersion::Version01,
+                    Some(policy.final_expiry(now)),
+                    Some("Keyset retiring".to_owned()),
+                )
+                .await?,
+            );
+        }
+
+        Ok(rotated)
+    }
+
+    /// Check the active keysets every `check_interval` until `shutdown` is cancelled
+    pub(crate) async fn rotate_keysets_on_schedule(
+        mint: Arc<Mint>,
+        policy: KeysetRotationPolicy,
+        shutdown: CancellationToken,
+    ) {
+        let mut interval = tokio::time::interval(policy.check_interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => break,
+                _ = interval.tick() => {
+                    if let Err(err) = mint.rotate_expiring_keysets().await {
+                        tracing::error!("Scheduled keyset rotation failed: {}", err);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::test_helpers::mint::create_test_mint;
+
+    #[tokio::test]
+    async fn expiring_keysets_are_replaced() {
+        let policy = KeysetRotationPolicy::new(Duration::from_secs(3600));
+        let mint = create_test_mint()
+            .await
+            .unwrap()
+            .with_keyset_rotation_policy(policy);
+
+        // Keysets without a final expiry are left alone
+        assert!(mint.rotate_expiring_keysets().await.unwrap().is_empty());
+
+        let expiring = mint
+            .rotate_keyset(
+                CurrencyUnit::Sat,
+                vec![1, 2, 4, 8],
+                0,
+                true,
+                Some(unix_time() + 60),
+            )
+            .await
+            .unwrap();
+
+        let rotated = mint.rotate_expiring_keysets().await.unwrap();
+        assert_eq!(rotated.len(), 1);
+
+        let replacement = &rotated[0];
+        assert_ne!(replacement.id, expiring.id);
+        assert_eq!(replacement.unit, CurrencyUnit::Sat);
+        assert_eq!(replacement.amounts, expiring.amounts);
+        assert!(replacement
+            .final_expiry
+            .is_some_and(|expiry| expiry >= policy.final_expiry(unix_time()) - 5));
+
+        // The replacement is far from its expiry
+        assert!(mint.rotate_expiring_keysets().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn retiring_keysets_are_published_and_replaced() {
+        let policy = KeysetRotationPolicy::new(Duration::from_secs(3600));
+        let mint = create_test_mint().await.unwrap();
+        let keyset_id = mint.keysets().keysets[0].id;
+        let retirement = unix_time() + 600;
+
+        let mint = mint
+            .with_keyset_rotation_policy(policy)
+            .with_keyset_retirements(HashMap::from([(keyset_id, retirement)]));
+
+        let published = mint
+            .keysets()
+            .keysets
+            .into_iter()
+            .find(|keyset| keyset.id == keyset_id)
+            .unwrap();
+        assert_eq!(published.retirement_time, Some(retirement));
+        assert_eq!(
+            published.rotation_time,
+            Some(retirement - policy.rotate_before.as_secs())
+        );
+        assert!(!mint.is_keyset_retired(&keyset_id));
+
+        // Retiring within the last quarter of the lifetime, the keyset is due
+        let rotated = mint.rotate_expiring_keysets().await.unwrap();
+        assert_eq!(rotated.len(), 1);
+
+        let published = mint
+            .keysets()
+            .keysets
+            .into_iter()
+            .find(|keyset| keyset.id == keyset_id)
+            .unwrap();
+        assert!(!published.active);
+        assert_eq!(published.rotation_time, None);
+        assert_eq!(published.retirement_time, Some(retirement));
+    }
+}
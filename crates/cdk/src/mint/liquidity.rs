@@ -0,0 +1,288 @@
+//! Outbound liquidity of the payment backends
+//!
+//! Before a lightning melt quote is created, the mint asks its backend how much it can send. A
+//! quote whose amount and fee reserve exceed that could never be paid, and the
+//! [`LiquidityPolicy`] decides what happens to it. Backends that cannot tell their liquidity are
+//! not checked.
+
+use cdk_common::payment::DynMintPayment;
+use tracing::instrument;
+
+use super::{CurrencyUnit, Mint, PaymentMethod};
+use crate::{Amount, Error};
+
+/// What the mint does with a melt quote above the outbound liquidity of its backend
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LiquidityPolicy {
+    /// Create the quote anyway and log a warning
+    #[default]
+    Warn,
+    /// Refuse the quote with [`Error::InsufficientLiquidity`]
+    Reject,
+    /// Quote the part of a bolt11 invoice the liquidity covers as a multi-part payment (NUT-15),
+    /// leaving the rest to be paid through another mint
+    ///
+    /// Quotes that already carry options, and units without multi-part payments, are refused as
+    /// with [`LiquidityPolicy::Reject`].
+    Partial,
+}
+
+/// Outbound liquidity of a payment backend, see [`Mint::liquidity_status`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LiquidityStatus {
+    /// Unit the backend is registered for
+    pub unit: CurrencyUnit,
+    /// Payment method the backend is registered for
+    pub method: PaymentMethod,
+    /// Name of the backend
+    pub backend: String,
+    /// Amount the backend can send, none when it cannot tell
+    pub outbound: Option<Amount<CurrencyUnit>>,
+    /// Why the liquidity could not be read, if it failed
+    pub error: Option<String>,
+}
+
+/// Outcome of [`Mint::check_outbound_liquidity`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum LiquidityCheck {
+    /// The quote can be created as requested
+    Sufficient,
+    /// Only this amount can be sent, fee reserve excluded, quote it as a multi-part payment
+    Partial(Amount<CurrencyUnit>),
+}
+
+impl Mint {
+    /// Handle melt quotes above the outbound liquidity of their backend following `policy`
+    ///
+    /// Defaults to [`LiquidityPolicy::Warn`].
+    pub fn with_liquidity_policy(mut self, policy: LiquidityPolicy) -> Self {
+        self.liquidity_policy = policy;
+        self
+    }
+
+    /// How melt quotes above the outbound liquidity of their backend are handled
+    pub fn liquidity_policy(&self) -> LiquidityPolicy {
+        self.liquidity_policy
+    }
+
+    /// Outbound liquidity of every payment backend, ordered by unit and method
+    #[instrument(skip_all)]
+    pub async fn liquidity_status(&self) -> Vec<LiquidityStatus> {
+        let mut statuses = Vec::with_capacity(self.payment_processors.len());
+
+        for (key, backend) in self.payment_processors.iter() {
+            let (outbound, error) = match backend.outbound_liquidity(&key.unit).await {
+                Ok(outbound) => (outbound, None),
+                Err(err) => (None, Some(err.to_string())),
+            };
+
+            statuses.push(LiquidityStatus {
+                unit: key.unit.clone(),
+                method: key.method.clone(),
+                backend: backend.backend_name(),
+                outbound,
+                error,
+            });
+        }
+
+        statuses.sort_by(|a, b| {
+            (a.unit.to_string(), a.method.to_string())
+                .cmp(&(b.unit.to_string(), b.method.to_string()))
+        });
+        statuses
+    }
+
+    /// Check that `backend` can send `amount` plus `fee` to pay `request`
+    ///
+    /// Payments of a request the mint issued itself settle internally and are not checked. When
+    /// `partial_allowed` is false, [`LiquidityPolicy::Partial`] refuses the quote instead.
+    #[instrument(skip_all)]
+    pub(crate) async fn check_outbound_liquidity(
+        &self,
+        backend: &DynMintPayment,
+        request: &str,
+        amount: &Amount<CurrencyUnit>,
+        fee: &Amount<CurrencyUnit>,
+        partial_allowed: bool,
+    ) -> Result<LiquidityCheck, Error> {
+        let available = match backend.outbound_liquidity(amount.unit()).await {
+            Ok(Some(available)) => available,
+            Ok(None) => return Ok(LiquidityCheck::Sufficient),
+            Err(err) => {
+                tracing::warn!(
+                    "Could not get the outbound liquidity of {}: {}",
+                    backend.backend_name(),
+                    err
+                );
+                return Ok(LiquidityCheck::Sufficient);
+            }
+        };
+
+        let required = amount.checked_add(fee)?;
+        if required.value() <= available.value()
+            || self
+                .localstore
+                .get_mint_quote_by_request(request)
+                .await?
+                .is_some()
+        {
+            return Ok(LiquidityCheck::Sufficient);
+        }
+
+        match self.liquidity_policy {
+            LiquidityPolicy::Warn => {
+                tracing::warn!(
+                    "Melt quote of {} exceeds the outbound liquidity {} of {}",
+                    required,
+                    available,
+                    backend.backend_name()
+                );
+                Ok(LiquidityCheck::Sufficient)
+            }
+            LiquidityPolicy::Partial if partial_allowed && available.value() > fee.value() => {
+                Ok(LiquidityCheck::Partial(available.checked_sub(fee)?))
+            }
+            LiquidityPolicy::Partial | LiquidityPolicy::Reject => {
+                tracing::info!(
+                    "Refusing melt quote of {} above the outbound liquidity {} of {}",
+                    required,
+                    available,
+                    backend.backend_name()
+                );
+                Err(Error::InsufficientLiquidity {
+                    amount: required.into(),
+                    available: available.into(),
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+    use std::sync::Arc;
+
+    use cdk_common::melt::MeltQuoteRequest;
+    use cdk_common::nut00::KnownMethod;
+    use cdk_common::MeltOptions;
+    use cdk_fake_wallet::{create_fake_invoice, FakeInvoiceDescription, FakeWallet};
+
+    use super::*;
+    use crate::mint::{MeltQuoteBolt11Request, MintBuilder, MintMeltLimits};
+    use crate::types::{FeeReserve, QuoteTTL};
+
+    async fn create_liquidity_test_mint(liquidity: Option<u64>, policy: LiquidityPolicy) -> Mint {
+        let db = Arc::new(cdk_sqlite::mint::memory::empty().await.unwrap());
+        let mut mint_builder = MintBuilder::new(db.clone());
+
+        let mut backend = FakeWallet::new(
+            FeeReserve {
+                min_fee_reserve: 1.into(),
+                percent_fee_reserve: 0.01,
+            },
+            HashMap::default(),
+            HashSet::default(),
+            2,
+            CurrencyUnit::Sat,
+        );
+        if let Some(liquidity) = liquidity {
+            backend = backend.with_outbound_liquidity(Amount::new(liquidity, CurrencyUnit::Sat));
+        }
+
+        mint_builder
+            .add_payment_processor(
+                CurrencyUnit::Sat,
+                PaymentMethod::Known(KnownMethod::Bolt11),
+                MintMeltLimits::new(1, 100_000),
+                Arc::new(backend),
+            )
+            .await
+            .unwrap();
+
+        let mnemonic = bip39::Mnemonic::generate(12).unwrap();
+        let mint = mint_builder
+            .build_with_seed(db.clone(), &mnemonic.to_seed_normalized(""))
+            .await
+            .unwrap()
+            .with_liquidity_policy(policy);
+
+        mint.set_quote_ttl(QuoteTTL::new(10000, 10000))
+            .await
+            .unwrap();
+        mint
+    }
+
+    async fn melt_quote(
+        mint: &Mint,
+        amount_sat: u64,
+    ) -> Result<cdk_common::MeltQuoteBolt11Response<cdk_common::QuoteId>, Error> {
+        let invoice = create_fake_invoice(
+            amount_sat * 1_000,
+            serde_json::to_string(&FakeInvoiceDescription::default()).unwrap(),
+        );
+
+        mint.get_melt_quote(MeltQuoteRequest::Bolt11(MeltQuoteBolt11Request {
+            request: invoice,
+            unit: CurrencyUnit::Sat,
+            options: None,
+        }))
+        .await
+        .map(|response| match response {
+            cdk_common::MeltQuoteCreateResponse::Bolt11(quote) => quote,
+            _ => panic!("bolt11 quote expected"),
+        })
+    }
+
+    #[tokio::test]
+    async fn quotes_above_the_liquidity_follow_the_policy() {
+        // Unknown liquidity is never checked
+        let mint = create_liquidity_test_mint(None, LiquidityPolicy::Reject).await;
+        assert!(melt_quote(&mint, 50_000).await.is_ok());
+
+        let mint = create_liquidity_test_mint(Some(10_000), LiquidityPolicy::Warn).await;
+        assert_eq!(
+            melt_quote(&mint, 50_000).await.unwrap().amount,
+            50_000.into()
+        );
+
+        let mint = create_liquidity_test_mint(Some(10_000), LiquidityPolicy::Reject).await;
+        assert!(melt_quote(&mint, 5_000).await.is_ok());
+        assert!(matches!(
+            melt_quote(&mint, 50_000).await,
+            Err(Error::InsufficientLiquidity { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn partial_policy_quotes_the_liquidity_as_mpp() {
+        let mint = create_liquidity_test_mint(Some(10_000), LiquidityPolicy::Partial).await;
+
+        let quote = melt_quote(&mint, 50_000).await.unwrap();
+        let stored = mint
+            .localstore()
+            .get_melt_quote(&quote.quote)
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert!(quote.amount + quote.fee_reserve <= 10_000.into());
+        assert!(matches!(stored.options, Some(MeltOptions::Mpp { .. })));
+    }
+
+    #[tokio::test]
+    async fn liquidity_status_lists_every_backend() {
+        let mint = create_liquidity_test_mint(Some(10_000), LiquidityPolicy::Warn).await;
+
+        let statuses = mint.liquidity_status().await;
+
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].unit, CurrencyUnit::Sat);
+        assert_eq!(statuses[0].backend, "fake-wallet");
+        assert_eq!(
+            statuses[0].outbound,
+            Some(Amount::new(10_000, CurrencyUnit::Sat))
+        );
+        assert!(statuses[0].error.is_none());
+    }
+}
@@ -2,6 +2,7 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use cdk_common::common::PaymentProcessorKey;
+use cdk_common::database::mint::QuoteSearch;
 use cdk_common::database::DynMintDatabase;
 use cdk_common::mint::MintQuote;
 use cdk_common::payment::DynMintPayment;
@@ -9,7 +10,7 @@ use cdk_common::MintQuoteState;
 use tracing::instrument;
 
 use super::subscription::PubSubManager;
-use super::Mint;
+use super::{Mint, PaymentEventOutage};
 use crate::Error;
 
 impl Mint {
@@ -126,4 +127,75 @@ impl Mint {
         )
         .await
     }
+
+    /// Look up the payments of the mint quotes a backend may have reported while its event
+    /// stream was closed
+    ///
+    /// Every quote of the backend that had not expired when the outage began is checked.
+    /// Returns the number of quotes checked.
+    #[instrument(skip_all, fields(backend = ?outage.backend))]
+    pub(crate) async fn reconcile_mint_quotes_after_outage(
+        &self,
+        outage: &PaymentEventOutage,
+    ) -> Result<usize, Error> {
+        let Some(processor) = self.payment_processors.get(&outage.backend) else {
+            return Ok(0);
+        };
+
+        // A backend registered under several keys missed the events of all of them
+        let keys: Vec<&PaymentProcessorKey> = self
+            .payment_processors
+            .iter()
+            .filter(|(_, other)| Arc::ptr_eq(other, processor))
+            .map(|(key, _)| key)
+            .collect();
+
+        // Collected before checking any, checks move quotes between the states searched
+        let mut candidates = Vec::new();
+
+        for state in [
+            MintQuoteState::Unpaid,
+            MintQuoteState::Paid,
+            MintQuoteState::Issued,
+        ] {
+            let mut search = QuoteSearch {
+                state: Some(state),
+                ..Default::default()
+            };
+
+            loop {
+                let quotes = self.localstore.search_mint_quotes(&search).await?;
+                let page_len = quotes.len() as u64;
+
+                // Bolt11 quotes are paid once, already paid ones cannot have missed anything
+                candidates.extend(quotes.into_iter().filter(|quote| {
+                    !(quote.payment_method.is_bolt11() && state != MintQuoteState::Unpaid)
+                        && quote.expiry >= outage.since
+                        && keys
+                            .iter()
+                            .any(|key| key.unit == quote.unit && key.method == quote.payment_method)
+                }));
+
+                if page_len < search.limit {
+                    break;
+                }
+                search.offset += page_len;
+            }
+        }
+
+        let checked = candidates.len();
+        for mut quote in candidates {
+            if let Err(err) = self.check_mint_quote_paid(&mut quote).await {
+                tracing::warn!("Could not check mint quote {}: {}", quote.id, err);
+            }
+        }
+
+        tracing::info!(
+            "Checked {} mint quotes of {:?} for payments missed during its outage",
+            checked,
+            outage.backend
+        );
+
+        Ok(checked)
+    }
 }
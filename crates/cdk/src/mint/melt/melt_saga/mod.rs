@@ -1,11 +1,12 @@
 use std::collections::VecDeque;
 use std::sync::Arc;
 
-use cdk_common::database::DynMintDatabase;
+use cdk_common::database::{DynMintDatabase, InternalSettlement};
 use cdk_common::mint::{MeltFinalizationData, MeltSagaState, Operation, Saga, SagaStateEnum};
 use cdk_common::nut00::KnownMethod;
 use cdk_common::nuts::MeltQuoteState;
 use cdk_common::payment::OutgoingPaymentOptions;
+use cdk_common::util::unix_time;
 use cdk_common::{
     Amount, CurrencyUnit, Error, ProofsMethods, PublicKey, QuoteId, SpendingConditionVerification,
     State,
@@ -188,6 +189,7 @@ impl MeltSaga<Initial> {
     ///
     /// - `PendingQuote`: Quote is already in Pending state
     /// - `PaidQuote`: Quote has already been paid
+    /// - `RequestHasUnpaidQuote`: Quote failed and another quote for the whole invoice is unpaid
     /// - `TokenAlreadySpent`: Input proofs have already been spent
     /// - `UnitMismatch`: Input unit doesn't match quote unit
     #[instrument(skip_all)]
@@ -213,7 +215,7 @@ impl MeltSaga<Initial> {
 
         // Verify spending conditions (NUT-10/NUT-11/NUT-14), i.e. P2PK
         // and HTLC (including SIGALL)
-        melt_request.verify_spending_conditions()?;
+        melt_request.verify_spending_conditions_with_grace(self.mint.clock_skew_grace_secs)?;
 
         let mut tx = self.db.begin_transaction().await?;
 
@@ -324,6 +326,11 @@ impl MeltSaga<Initial> {
             .await
         {
             Ok(_) => {}
+            // Related quotes are locked, only a quote for the invoice created meanwhile conflicts
+            Err(cdk_common::database::Error::Duplicate) => {
+                tx.rollback().await?;
+                return Err(Error::RequestHasUnpaidQuote);
+            }
             Err(err) => {
                 tx.rollback().await?;
                 return Err(err.into());
@@ -448,8 +455,9 @@ impl MeltSaga<SetupComplete> {
     /// 2. If not a match or different unit: returns (self, RequiresExternalPayment)
     /// 3. If match found: validates quote state and amount
     /// 4. Increments the mint quote's paid amount
-    /// 5. Publishes mint quote payment notification
-    /// 6. Returns (self, Internal{amount})
+    /// 5. Records the [`InternalSettlement`]
+    /// 6. Publishes mint quote payment and internal settlement notifications
+    /// 7. Returns (self, Internal{amount})
     ///
     /// # Compensation
     ///
@@ -543,9 +551,20 @@ impl MeltSaga<SetupComplete> {
         mint_quote.add_payment(amount.clone(), self.state_data.quote.id.to_string(), None)?;
         tx.update_mint_quote(&mut mint_quote).await?;
 
+        let settlement = InternalSettlement {
+            created_time: unix_time(),
+            melt_quote_id: self.state_data.quote.id.clone(),
+            mint_quote_id: mint_quote.id.clone(),
+            unit: mint_quote.unit.clone(),
+            amount: Amount::from(amount.value()),
+            fee_reserve: Amount::from(self.state_data.quote.fee_reserve().value()),
+        };
+        tx.add_internal_settlement(&settlement).await?;
+
         tx.commit().await?;
         self.pubsub
             .mint_quote_payment(&mint_quote, mint_quote.amount_paid());
+        self.mint.notify_internal_settlement(settlement);
 
         tracing::info!(
             "Melt quote {} paid Mint quote {}",
@@ -603,13 +622,22 @@ impl MeltSaga<SetupComplete> {
     /// - `PendingQuote`: Payment is pending (will be resolved by startup check)
     #[instrument(skip_all)]
     pub async fn make_payment(
-        self,
+        mut self,
         settlement: SettlementDecision,
     ) -> Result<PaymentOutcome, Error> {
         let payment_result = match settlement {
             SettlementDecision::Internal { amount } => self.handle_internal_payment(amount),
             SettlementDecision::RequiresExternalPayment => {
-                let response = self.attempt_external_payment().await?;
+                let (response, backend) = self.attempt_external_payment().await?;
+
+                if let Some(backend) = backend.filter(|_| {
+                    !matches!(
+                        response.status,
+                        MeltQuoteState::Unpaid | MeltQuoteState::Failed
+                    )
+                }) {
+                    self.persist_payment_backend(&backend).await;
+                }
 
                 match response.status {
                     MeltQuoteState::Paid => response,
@@ -688,7 +716,10 @@ impl MeltSaga<SetupComplete> {
         }
     }
 
-    async fn attempt_external_payment(&self) -> Result<MakePaymentResponse, Error> {
+    /// Returns the response and the backend that made the payment, when it did not error
+    async fn attempt_external_payment(
+        &self,
+    ) -> Result<(MakePaymentResponse, Option<String>), Error> {
         // Get LN payment processor
         let ln = self
             .mint
@@ -726,15 +757,36 @@ impl MeltSaga<SetupComplete> {
         ln: Arc<
             dyn cdk_common::payment::MintPayment<Err = cdk_common::payment::Error> + Send + Sync,
         >,
-    ) -> Result<MakePaymentResponse, Error> {
+    ) -> Result<(MakePaymentResponse, Option<String>), Error> {
         // Make payment with idempotent verification
         let quote = &self.state_data.quote;
-        let payment_options = OutgoingPaymentOptions::from_melt_quote_with_fee(quote.clone())?;
+        let mut payment_options = OutgoingPaymentOptions::from_melt_quote_with_fee(quote.clone())?;
+        if let OutgoingPaymentOptions::Keysend(keysend_options) = &mut payment_options {
+            keysend_options.preimage = Some(self.mint.keysend_preimage(&quote.id).await?);
+        }
 
-        match ln.make_payment(&quote.unit, payment_options).await {
-            Ok(pay) if pay.status == MeltQuoteState::Paid => Ok(pay),
-            Ok(pay) => self.verify_ambiguous_payment(ln, pay).await,
-            Err(err) => self.handle_payment_error(ln, err).await,
+        let key = crate::types::PaymentProcessorKey::new(
+            quote.unit.clone(),
+            quote.payment_method.clone(),
+        );
+        let start = std::time::Instant::now();
+        let result = ln
+            .make_payment_with_backend(&quote.unit, payment_options)
+            .await;
+        self.mint.backend_monitor.record_payment(
+            &key,
+            &ln.backend_name(),
+            start.elapsed(),
+            result.is_ok(),
+        );
+
+        match result {
+            Ok((pay, backend)) if pay.status == MeltQuoteState::Paid => Ok((pay, Some(backend))),
+            Ok((pay, backend)) => {
+                let pay = self.verify_ambiguous_payment(ln, pay, &backend).await?;
+                Ok((pay, Some(backend)))
+            }
+            Err(err) => Ok((self.handle_payment_error(ln, err).await?, None)),
         }
     }
 
@@ -744,6 +796,7 @@ impl MeltSaga<SetupComplete> {
             dyn cdk_common::payment::MintPayment<Err = cdk_common::payment::Error> + Send + Sync,
         >,
         pay: MakePaymentResponse,
+        backend: &str,
     ) -> Result<MakePaymentResponse, Error> {
         tracing::warn!(
             "Got {} status when paying melt quote {} for {} {}. Verifying with backend...",
@@ -753,7 +806,9 @@ impl MeltSaga<SetupComplete> {
             self.state_data.quote.unit
         );
 
-        let mut check_response = self.check_payment_state(ln, &pay.payment_lookup_id).await?;
+        let mut check_response = self
+            .check_payment_state(ln, Some(backend), &pay.payment_lookup_id)
+            .await?;
 
         if check_response.status == MeltQuoteState::Paid {
             // Race condition: Payment succeeded during verification
@@ -814,7 +869,9 @@ impl MeltSaga<SetupComplete> {
                 Error::Internal
             })?;
 
-        let mut check_response = self.check_payment_state(ln, lookup_id).await?;
+        let mut check_response = self
+            .check_payment_state(ln, self.state_data.quote.backend.as_deref(), lookup_id)
+            .await?;
 
         tracing::info!(
             "Initial payment attempt for {} errored. Follow up check status: {}",
@@ -892,15 +949,56 @@ impl MeltSaga<SetupComplete> {
         }
     }
 
+    /// Records the backend that made the payment on the quote, so later checks ask it first.
+    ///
+    /// A backend routing payments across several others only learns which one made a payment
+    /// from the quote. Best-effort like [`Self::persist_pending_payment_lookup_id`]: without
+    /// it the payment is checked on every backend.
+    async fn persist_payment_backend(&mut self, backend: &str) {
+        let quote_id = &self.state_data.quote.id;
+
+        if self.state_data.quote.backend.as_deref() == Some(backend) {
+            return;
+        }
+
+        let result: Result<(), Error> = async {
+            let mut tx = self.db.begin_transaction().await?;
+
+            let mut quote = tx
+                .get_melt_quote(quote_id)
+                .await?
+                .ok_or(Error::UnknownQuote)?;
+            tx.update_melt_quote_backend(&mut quote, backend).await?;
+
+            tx.commit().await?;
+            Ok(())
+        }
+        .await;
+
+        match result {
+            Ok(()) => self.state_data.quote.backend = Some(backend.to_owned()),
+            Err(err) => tracing::error!(
+                "Failed to record backend {} of the payment of melt quote {}: {}",
+                backend,
+                quote_id,
+                err
+            ),
+        }
+    }
+
     /// Helper to check payment state with LN backend
     async fn check_payment_state(
         &self,
         ln: Arc<
             dyn cdk_common::payment::MintPayment<Err = cdk_common::payment::Error> + Send + Sync,
         >,
+        backend: Option<&str>,
         lookup_id: &cdk_common::payment::PaymentIdentifier,
     ) -> Result<MakePaymentResponse, Error> {
-        match ln.check_outgoing_payment(lookup_id).await {
+        match ln
+            .check_outgoing_payment_with_backend(backend, lookup_id)
+            .await
+        {
             Ok(response) => Ok(response),
             Err(check_err) => {
                 tracing::error!(
@@ -1027,7 +1125,9 @@ impl MeltSaga<PaymentConfirmed> {
         }
 
         self.state_data.quote.payment_proof = payment_proof;
-        self.state_data.quote.state = MeltQuoteState::Paid;
+        self.state_data
+            .quote
+            .transition_state(MeltQuoteState::Paid)?;
         let response = self.state_data.quote.into_response(change);
 
         Ok(response)
@@ -722,6 +722,72 @@ async fn test_finalizing_recovery_without_metadata_uses_internal_settlement() {
     assert_eq!(paid_mint_quote.state(), MintQuoteState::Paid);
 }
 
+#[tokio::test]
+async fn test_internal_settlement_is_recorded() {
+    let mint = create_test_mint().await.unwrap();
+    let proofs = mint_test_proofs(&mint, Amount::from(10_000)).await.unwrap();
+    let mint_quote_response: cdk_common::MintQuoteBolt11Response<_> = mint
+        .get_mint_quote(
+            MintQuoteBolt11Request {
+                amount: Amount::from(4_000),
+                unit: cdk_common::CurrencyUnit::Sat,
+                description: None,
+                pubkey: None,
+            }
+            .into(),
+        )
+        .await
+        .unwrap()
+        .into();
+    let mint_quote_id = cdk_common::QuoteId::from_str(&mint_quote_response.quote).unwrap();
+
+    let melt_quote_request = MeltQuoteRequest::Bolt11(MeltQuoteBolt11Request {
+        request: mint_quote_response.request.parse().unwrap(),
+        unit: cdk_common::CurrencyUnit::Sat,
+        options: None,
+    });
+    let quote_response = mint.get_melt_quote(melt_quote_request).await.unwrap();
+    let quote = mint
+        .localstore
+        .get_melt_quote(quote_response.quote().expect("single-quote method"))
+        .await
+        .unwrap()
+        .expect("Melt quote should exist");
+    let melt_request = create_test_melt_request(&proofs, &quote);
+
+    let mut settlements = mint.subscribe_internal_settlements();
+    let verification = mint.verify_inputs(melt_request.inputs()).await.unwrap();
+    let saga = MeltSaga::new(
+        std::sync::Arc::new(mint.clone()),
+        mint.localstore(),
+        mint.pubsub_manager(),
+    );
+    let (_payment_saga, _decision) = saga
+        .setup_melt(
+            &melt_request,
+            verification,
+            PaymentMethod::Known(KnownMethod::Bolt11),
+        )
+        .await
+        .unwrap()
+        .attempt_internal_settlement(&melt_request)
+        .await
+        .unwrap();
+
+    let recorded = mint.internal_settlements(None).await.unwrap();
+    assert_eq!(recorded.len(), 1);
+    assert_eq!(recorded[0].melt_quote_id, quote.id);
+    assert_eq!(recorded[0].mint_quote_id, mint_quote_id);
+    assert_eq!(recorded[0].unit, CurrencyUnit::Sat);
+    assert_eq!(recorded[0].amount, Amount::from(4_000));
+    assert_eq!(
+        recorded[0].fee_reserve,
+        Amount::from(quote.fee_reserve().value())
+    );
+
+    assert_eq!(settlements.try_recv().unwrap(), recorded[0]);
+}
+
 /// Test: Saga remains in database if finalize fails
 #[tokio::test]
 async fn test_saga_persists_on_finalize_failure() {
@@ -3270,6 +3336,69 @@ async fn test_saga_drop_after_payment() {
     // SUCCESS: Drop after payment correctly finalizes (doesn't compensate)!
 }
 
+/// Test: Pending quotes without a saga are settled from the LN backend on startup
+///
+/// A crash mid-payment can leave a melt quote pending with no saga to recover
+/// it from; the startup check must still ask the LN backend and finalize it.
+#[tokio::test]
+async fn test_check_pending_melt_quotes_finalizes_paid_quote_without_saga() {
+    let mint = create_test_mint().await.unwrap();
+    let proofs = mint_test_proofs(&mint, Amount::from(10_000)).await.unwrap();
+    let input_ys = proofs.ys().unwrap();
+    let quote = create_test_melt_quote(&mint, Amount::from(9_000)).await;
+    let melt_request = create_test_melt_request(&proofs, &quote);
+
+    let verification = mint.verify_inputs(melt_request.inputs()).await.unwrap();
+    let saga = MeltSaga::new(
+        std::sync::Arc::new(mint.clone()),
+        mint.localstore(),
+        mint.pubsub_manager(),
+    );
+    let setup_saga = saga
+        .setup_melt(
+            &melt_request,
+            verification,
+            PaymentMethod::Known(KnownMethod::Bolt11),
+        )
+        .await
+        .unwrap();
+    let operation_id = setup_saga.operation_id;
+
+    let (payment_saga, decision) = setup_saga
+        .attempt_internal_settlement(&melt_request)
+        .await
+        .unwrap();
+    let PaymentOutcome::Confirmed(confirmed_saga) =
+        payment_saga.make_payment(decision).await.unwrap()
+    else {
+        panic!("Expected Confirmed outcome");
+    };
+    drop(confirmed_saga);
+
+    // Lose the saga, only the pending quote and proofs are left
+    let mut tx = mint.localstore.begin_transaction().await.unwrap();
+    tx.delete_saga(&operation_id).await.unwrap();
+    tx.commit().await.unwrap();
+
+    mint.recover_from_incomplete_melt_sagas().await.unwrap();
+    assert_proofs_state(&mint, &input_ys, Some(State::Pending)).await;
+
+    let resolved = mint.check_pending_melt_quotes().await.unwrap();
+    assert_eq!(resolved, 1);
+
+    assert_proofs_state(&mint, &input_ys, Some(State::Spent)).await;
+    let final_quote = mint
+        .localstore
+        .get_melt_quote(&quote.id)
+        .await
+        .unwrap()
+        .expect("Quote should exist");
+    assert_eq!(final_quote.state, MeltQuoteState::Paid);
+
+    // Nothing is left pending for the next start
+    assert_eq!(mint.check_pending_melt_quotes().await.unwrap(), 0);
+}
+
 /// Test: PaymentAttempted state triggers LN backend check during recovery
 ///
 /// This test verifies that when recovery finds a saga in PaymentAttempted state,
@@ -3510,6 +3639,26 @@ async fn create_test_melt_quote(
     quote
 }
 
+/// Stores a copy of `quote` under a new id, another quote for the same invoice
+///
+/// The copy is a multi-part quote, only those may share an invoice with an unpaid quote.
+async fn store_duplicate_melt_quote(
+    mint: &crate::mint::Mint,
+    quote: &cdk_common::mint::MeltQuote,
+) -> cdk_common::mint::MeltQuote {
+    let mut duplicate = quote.clone();
+    duplicate.id = cdk_common::QuoteId::new();
+    duplicate.options = Some(cdk_common::MeltOptions::new_mpp(
+        quote.amount().value() * 1000,
+    ));
+
+    let mut tx = mint.localstore.begin_transaction().await.unwrap();
+    tx.add_melt_quote(duplicate.clone()).await.unwrap();
+    tx.commit().await.unwrap();
+
+    duplicate
+}
+
 async fn create_test_onchain_melt_quote(mint: &crate::mint::Mint) -> cdk_common::mint::MeltQuote {
     let quote = MeltQuote::new_onchain(
         None,
@@ -3644,9 +3793,11 @@ async fn test_duplicate_lookup_id_prevents_second_pending() {
         serde_json::to_string(&fake_description).unwrap(),
     );
 
-    // STEP 2: Create two melt quotes for the same invoice (same request_lookup_id)
+    // STEP 2: Create two melt quotes for the same invoice (same request_lookup_id).
+    // The mint answers a second request with the first quote, so the second one is
+    // stored directly, as a quote created before requests were deduplicated.
     let bolt11_request1 = MeltQuoteBolt11Request {
-        request: invoice.clone(),
+        request: invoice,
         unit: CurrencyUnit::Sat,
         options: None,
     };
@@ -3655,16 +3806,6 @@ async fn test_duplicate_lookup_id_prevents_second_pending() {
         .await
         .unwrap();
 
-    let bolt11_request2 = MeltQuoteBolt11Request {
-        request: invoice,
-        unit: CurrencyUnit::Sat,
-        options: None,
-    };
-    let quote_response2 = mint
-        .get_melt_quote(MeltQuoteRequest::Bolt11(bolt11_request2))
-        .await
-        .unwrap();
-
     // Retrieve full quotes
     let quote1 = mint
         .localstore
@@ -3673,12 +3814,7 @@ async fn test_duplicate_lookup_id_prevents_second_pending() {
         .unwrap()
         .expect("Quote 1 should exist");
 
-    let quote2 = mint
-        .localstore
-        .get_melt_quote(quote_response2.quote().expect("single-quote method"))
-        .await
-        .unwrap()
-        .expect("Quote 2 should exist");
+    let quote2 = store_duplicate_melt_quote(&mint, &quote1).await;
 
     // Verify both quotes have the same lookup_id
     assert_eq!(
@@ -3813,9 +3949,11 @@ async fn test_paid_lookup_id_prevents_pending() {
         serde_json::to_string(&fake_description).unwrap(),
     );
 
-    // STEP 2: Create two melt quotes for the same invoice (same request_lookup_id)
+    // STEP 2: Create two melt quotes for the same invoice (same request_lookup_id).
+    // The mint answers a second request with the first quote, so the second one is
+    // stored directly, as a quote created before requests were deduplicated.
     let bolt11_request1 = MeltQuoteBolt11Request {
-        request: invoice.clone(),
+        request: invoice,
         unit: CurrencyUnit::Sat,
         options: None,
     };
@@ -3824,16 +3962,6 @@ async fn test_paid_lookup_id_prevents_pending() {
         .await
         .unwrap();
 
-    let bolt11_request2 = MeltQuoteBolt11Request {
-        request: invoice,
-        unit: CurrencyUnit::Sat,
-        options: None,
-    };
-    let quote_response2 = mint
-        .get_melt_quote(MeltQuoteRequest::Bolt11(bolt11_request2))
-        .await
-        .unwrap();
-
     // Retrieve full quotes
     let quote1 = mint
         .localstore
@@ -3841,12 +3969,7 @@ async fn test_paid_lookup_id_prevents_pending() {
         .await
         .unwrap()
         .expect("Quote 1 should exist");
-    let quote2 = mint
-        .localstore
-        .get_melt_quote(quote_response2.quote().expect("single-quote method"))
-        .await
-        .unwrap()
-        .expect("Quote 2 should exist");
+    let quote2 = store_duplicate_melt_quote(&mint, &quote1).await;
 
     // STEP 3: Complete the first melt (marks quote1 as PAID)
     let proofs1 = mint_test_proofs(&mint, Amount::from(10_000)).await.unwrap();
@@ -4,6 +4,7 @@ use std::str::FromStr;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
+use cdk_common::database::mint::{Acquired, QuoteSearch};
 use cdk_common::database::DynMintDatabase;
 use cdk_common::melt::MeltQuoteRequest;
 use cdk_common::mint::MeltPaymentRequest;
@@ -12,13 +13,15 @@ use cdk_common::nut05::MeltMethodOptions;
 use cdk_common::nuts::nut17::{Kind, NotificationPayload};
 use cdk_common::payment::{
     Bolt11OutgoingPaymentOptions, Bolt12OutgoingPaymentOptions, CustomOutgoingPaymentOptions,
-    OutgoingPaymentOptions, PaymentIdentifier,
+    DynMintPayment, KeysendOutgoingPaymentOptions, OutgoingPaymentOptions, PaymentIdentifier,
+    PaymentQuoteResponse,
 };
 use cdk_common::quote_id::QuoteId;
 use cdk_common::subscription::Params;
 use cdk_common::{
     MeltOptions, MeltQuoteBolt12Request, MeltQuoteCreateResponse, MeltQuoteCustomRequest,
-    MeltQuoteCustomResponse, MeltQuoteOnchainRequest, MeltQuoteOnchainResponse, MeltQuoteResponse,
+    MeltQuoteCustomResponse, MeltQuoteKeysendRequest, MeltQuoteOnchainRequest,
+    MeltQuoteOnchainResponse, MeltQuoteResponse, KEYSEND_METHOD,
 };
 use lightning::offers::offer::Offer;
 use tracing::instrument;
@@ -27,6 +30,9 @@ use super::{
     CurrencyUnit, MeltQuote, MeltQuoteBolt11Request, MeltQuoteBolt11Response,
     MeltQuoteBolt12Response, MeltRequest, Mint, PaymentMethod,
 };
+use crate::mint::keysend::new_keysend_preimage;
+use crate::mint::liquidity::LiquidityCheck;
+use crate::mint::retry::retry_transient;
 use crate::mint::verification::MAX_REQUEST_FIELD_LEN;
 use crate::nuts::MeltQuoteState;
 use crate::types::PaymentProcessorKey;
@@ -105,6 +111,43 @@ impl std::future::IntoFuture for PendingMelt {
     }
 }
 
+/// Melt quote to answer a bolt11 melt quote request with, out of the `quotes` of its invoice
+///
+/// A request for an invoice with an unexpired unpaid quote in the same unit and with the same
+/// options gets that quote back, so retried or concurrent requests do not leave several quotes
+/// able to pay the invoice. Invoices with a quote being paid, or paid, are refused. Multi-part
+/// requests only pay part of the invoice and always get a quote of their own.
+fn reusable_bolt11_melt_quote(
+    quotes: &[Acquired<MeltQuote>],
+    unit: &CurrencyUnit,
+    options: Option<MeltOptions>,
+) -> Result<Option<MeltQuote>, Error> {
+    if matches!(options, Some(MeltOptions::Mpp { .. })) {
+        return Ok(None);
+    }
+
+    let now = unix_time();
+    let mut reusable = None;
+    for quote in quotes {
+        match quote.state {
+            MeltQuoteState::Paid => return Err(Error::RequestAlreadyPaid),
+            MeltQuoteState::Pending => return Err(Error::PendingQuote),
+            MeltQuoteState::Unpaid
+                if reusable.is_none()
+                    && quote.payment_method.is_bolt11()
+                    && &quote.unit == unit
+                    && quote.options == options
+                    && quote.expiry > now =>
+            {
+                reusable = Some(MeltQuote::clone(quote));
+            }
+            _ => {}
+        }
+    }
+
+    Ok(reusable)
+}
+
 impl Mint {
     /// Loads a settled (non-pending, non-unknown) melt response from the database.
     ///
@@ -291,6 +334,15 @@ impl Mint {
             MeltQuoteRequest::Onchain(onchain_request) => Ok(MeltQuoteCreateResponse::Onchain(
                 self.get_melt_onchain_quote_impl(&onchain_request).await?,
             )),
+            MeltQuoteRequest::Custom(request) if request.method == KEYSEND_METHOD => {
+                let keysend_request = MeltQuoteKeysendRequest::try_from(&request)?;
+                let quote = self.get_melt_keysend_quote_impl(&keysend_request).await?;
+
+                Ok(MeltQuoteCreateResponse::Custom((
+                    PaymentMethod::from(KEYSEND_METHOD),
+                    quote,
+                )))
+            }
             MeltQuoteRequest::Custom(request) => {
                 let quote = self.get_melt_custom_quote_impl(&request).await?;
                 let method = PaymentMethod::from(request.method.as_str());
@@ -300,6 +352,45 @@ impl Mint {
         }
     }
 
+    /// Replace the fee reserve of `quote` by the routing fee `backend` estimates for the payment
+    ///
+    /// Backends that cannot estimate, or fail to, keep the fee reserve of their quote.
+    async fn apply_fee_estimate(
+        &self,
+        backend: &DynMintPayment,
+        options: &OutgoingPaymentOptions,
+        mut quote: PaymentQuoteResponse,
+    ) -> PaymentQuoteResponse {
+        match backend.estimate_fee(quote.unit(), options).await {
+            Ok(Some(fee)) if fee.unit() == quote.unit() => {
+                tracing::debug!(
+                    "{} estimates a routing fee of {} instead of the fee reserve {}",
+                    backend.backend_name(),
+                    fee,
+                    quote.fee
+                );
+                quote.fee = fee;
+            }
+            Ok(Some(fee)) => {
+                tracing::warn!(
+                    "{} estimated a fee in {} for a quote in {}",
+                    backend.backend_name(),
+                    fee.unit(),
+                    quote.unit()
+                );
+            }
+            Ok(None) => {}
+            Err(err) => {
+                tracing::warn!(
+                    "Could not estimate the routing fee with {}: {}",
+                    backend.backend_name(),
+                    err
+                );
+            }
+        }
+        quote
+    }
+
     /// Implementation of get_melt_bolt11_quote
     #[instrument(skip_all)]
     async fn get_melt_bolt11_quote_impl(
@@ -317,6 +408,8 @@ impl Mint {
                 ..
             } = melt_request;
 
+            self.check_invoice_network(request)?;
+
             let ln = self
                 .payment_processors
                 .get(&PaymentProcessorKey::new(
@@ -329,24 +422,38 @@ impl Mint {
                     Error::UnsupportedUnit
                 })?;
 
+            let payment_hash = PaymentIdentifier::PaymentHash(*request.payment_hash().as_ref());
+
+            let mut tx = self.localstore.begin_transaction().await?;
+            let quotes = tx
+                .get_melt_quotes_by_request_lookup_id(&payment_hash)
+                .await?;
+            tx.rollback().await?;
+
+            if let Some(quote) = reusable_bolt11_melt_quote(&quotes, unit, *options)? {
+                tracing::debug!(
+                    "Returning melt quote {} again for invoice {}",
+                    quote.id,
+                    payment_hash
+                );
+                return Ok(quote.into());
+            }
+
             // Pre-generate the quote id so we can pass it to the backend in both
             // `get_payment_quote` and the eventual `make_payment`, and use the same
             // id when we persist the quote below.
             let quote_id = cdk_common::QuoteId::new();
 
-            let bolt11 = Bolt11OutgoingPaymentOptions {
+            let bolt11 = OutgoingPaymentOptions::Bolt11(Box::new(Bolt11OutgoingPaymentOptions {
                 bolt11: melt_request.request.clone(),
                 max_fee_amount: None,
                 timeout_secs: None,
                 melt_options: melt_request.options,
                 quote_id: quote_id.clone(),
-            };
+            }));
 
             let payment_quote = ln
-                .get_payment_quote(
-                    &melt_request.unit,
-                    OutgoingPaymentOptions::Bolt11(Box::new(bolt11)),
-                )
+                .get_payment_quote(&melt_request.unit, bolt11.clone())
                 .await
                 .map_err(|err| {
                     tracing::error!(
@@ -362,12 +469,55 @@ impl Mint {
                 return Err(Error::UnitMismatch);
             }
 
+            let payment_quote = self.apply_fee_estimate(ln, &bolt11, payment_quote).await;
+
+            // Catch quotes the backend could never pay, or quote the part it can as a
+            // multi-part payment
+            let (payment_quote, options) = match self
+                .check_outbound_liquidity(
+                    ln,
+                    &request.to_string(),
+                    &payment_quote.amount,
+                    &payment_quote.fee,
+                    options.is_none(),
+                )
+                .await?
+            {
+                LiquidityCheck::Sufficient => (payment_quote, *options),
+                LiquidityCheck::Partial(available) => {
+                    let options = Some(MeltOptions::new_mpp(
+                        available.convert_to(&CurrencyUnit::Msat)?.value(),
+                    ));
+
+                    let bolt11 =
+                        OutgoingPaymentOptions::Bolt11(Box::new(Bolt11OutgoingPaymentOptions {
+                            bolt11: melt_request.request.clone(),
+                            max_fee_amount: None,
+                            timeout_secs: None,
+                            melt_options: options,
+                            quote_id: quote_id.clone(),
+                        }));
+
+                    let payment_quote = ln
+                        .get_payment_quote(&melt_request.unit, bolt11.clone())
+                        .await?;
+                    let payment_quote = self.apply_fee_estimate(ln, &bolt11, payment_quote).await;
+
+                    tracing::info!(
+                        "Quoting {} of the bolt11 invoice as a multi-part payment",
+                        payment_quote.amount
+                    );
+
+                    (payment_quote, options)
+                }
+            };
+
             // Validate using processor quote amount for currency conversion
             self.check_melt_request_acceptable(
                 payment_quote.amount.clone(),
                 PaymentMethod::Known(KnownMethod::Bolt11),
                 request.to_string(),
-                *options,
+                options,
             )
             .await?;
 
@@ -377,7 +527,7 @@ impl Mint {
 
             let melt_ttl = self.quote_ttl().await?.melt_ttl;
 
-            let quote = MeltQuote::new(
+            let mut quote = MeltQuote::new(
                 Some(quote_id),
                 MeltPaymentRequest::Bolt11 {
                     bolt11: request.clone(),
@@ -387,11 +537,12 @@ impl Mint {
                 quote_fee,
                 unix_time() + melt_ttl,
                 payment_quote.request_lookup_id.clone(),
-                *options,
+                options,
                 PaymentMethod::Known(KnownMethod::Bolt11),
                 payment_quote.extra_json,
                 payment_quote.estimated_blocks,
             );
+            quote.backend = Some(ln.backend_name());
 
             tracing::debug!(
                 "New {} melt quote {} for {} {} with request id {:?}",
@@ -402,11 +553,69 @@ impl Mint {
                 payment_quote.request_lookup_id
             );
 
-            let mut tx = self.localstore.begin_transaction().await?;
-            tx.add_melt_quote(quote.clone()).await?;
-            tx.commit().await?;
+            // A concurrent request may have created a quote for the invoice meanwhile. There is
+            // no row to lock before either quote exists, the unique index on unpaid bolt11
+            // quotes refuses the second insert and the quote stored first is looked up again.
+            let mut attempts = 0;
+            loop {
+                attempts += 1;
+
+                let mut tx = self.localstore.begin_transaction().await?;
+                let quotes = tx
+                    .get_melt_quotes_by_request_lookup_id(&payment_hash)
+                    .await?;
+                match reusable_bolt11_melt_quote(&quotes, unit, options) {
+                    Ok(None) => {}
+                    Ok(Some(existing)) => {
+                        tx.rollback().await?;
+                        return Ok(existing.into());
+                    }
+                    Err(err) => {
+                        tx.rollback().await?;
+                        return Err(err);
+                    }
+                }
 
-            Ok(quote.into())
+                // An unpaid quote in another unit or with other options was asked for by another
+                // request and is left alone. Once expired it no longer holds the invoice.
+                if shared::is_unpaid_bolt11_melt_quote(&quote) {
+                    let now = unix_time();
+                    for mut other in quotes {
+                        if !shared::is_unpaid_bolt11_melt_quote(&other) {
+                            continue;
+                        }
+
+                        if other.expiry > now {
+                            tx.rollback().await?;
+                            return Err(Error::RequestHasUnpaidQuote);
+                        }
+
+                        tx.update_melt_quote_state(&mut other, MeltQuoteState::Failed, None)
+                            .await?;
+                    }
+                }
+
+                match tx.add_melt_quote(quote.clone()).await {
+                    Ok(()) => {
+                        tx.commit().await?;
+                        return Ok(quote.into());
+                    }
+                    Err(cdk_common::database::Error::Duplicate) => {
+                        tx.rollback().await?;
+                        if attempts >= 2 {
+                            return Err(Error::RequestHasUnpaidQuote);
+                        }
+                        tracing::debug!(
+                            "Melt quote for invoice {} created concurrently, looking it up",
+                            payment_hash
+                        );
+                    }
+                    Err(err) => {
+                        tx.rollback().await?;
+                        return Err(err.into());
+                    }
+                }
+            }
         }
         .await;
 
@@ -450,19 +659,17 @@ impl Mint {
 
             let quote_id = cdk_common::QuoteId::new();
 
-            let outgoing_payment_options = Bolt12OutgoingPaymentOptions {
-                offer: offer.clone(),
-                max_fee_amount: None,
-                timeout_secs: None,
-                melt_options: *options,
-                quote_id: quote_id.clone(),
-            };
+            let outgoing_payment_options =
+                OutgoingPaymentOptions::Bolt12(Box::new(Bolt12OutgoingPaymentOptions {
+                    offer: offer.clone(),
+                    max_fee_amount: None,
+                    timeout_secs: None,
+                    melt_options: *options,
+                    quote_id: quote_id.clone(),
+                }));
 
             let payment_quote = ln
-                .get_payment_quote(
-                    &melt_request.unit,
-                    OutgoingPaymentOptions::Bolt12(Box::new(outgoing_payment_options)),
-                )
+                .get_payment_quote(&melt_request.unit, outgoing_payment_options.clone())
                 .await
                 .map_err(|err| {
                     tracing::error!(
@@ -478,6 +685,20 @@ impl Mint {
                 return Err(Error::UnitMismatch);
             }
 
+            let payment_quote = self
+                .apply_fee_estimate(ln, &outgoing_payment_options, payment_quote)
+                .await;
+
+            // Catch quotes the backend could never pay
+            self.check_outbound_liquidity(
+                ln,
+                request,
+                &payment_quote.amount,
+                &payment_quote.fee,
+                false,
+            )
+            .await?;
+
             // Validate using processor quote amount for currency conversion
             self.check_melt_request_acceptable(
                 payment_quote.amount.clone(),
@@ -495,7 +716,7 @@ impl Mint {
                 offer: Box::new(offer),
             };
 
-            let quote = MeltQuote::new(
+            let mut quote = MeltQuote::new(
                 Some(quote_id),
                 payment_request,
                 unit.clone(),
@@ -508,6 +729,7 @@ impl Mint {
                 payment_quote.extra_json,
                 payment_quote.estimated_blocks,
             );
+            quote.backend = Some(ln.backend_name());
 
             tracing::debug!(
                 "New {} melt quote {} for {} {} with request id {:?}",
@@ -639,7 +861,7 @@ impl Mint {
             // `MeltQuote::new_onchain` applies the NUT validation. Failures are
             // returned before the quote is persisted, so a backend that violates
             // the contract never leaves state behind in the mint.
-            let quote = MeltQuote::new_onchain(
+            let mut quote = MeltQuote::new_onchain(
                 Some(quote_id),
                 MeltPaymentRequest::Onchain {
                     address: melt_request.request.clone(),
@@ -651,6 +873,7 @@ impl Mint {
                 payment_quote.extra_json,
                 fee_options,
             )?;
+            quote.backend = Some(ln.backend_name());
 
             let mut tx = self.localstore.begin_transaction().await?;
             tx.add_melt_quote(quote.clone()).await?;
@@ -760,7 +983,7 @@ impl Mint {
             let quote_amount = payment_quote.amount;
             let quote_fee = payment_quote.fee;
 
-            let quote = MeltQuote::new(
+            let mut quote = MeltQuote::new(
                 Some(quote_id),
                 MeltPaymentRequest::Custom {
                     method: method.to_string(),
@@ -776,6 +999,7 @@ impl Mint {
                 payment_quote.extra_json,
                 payment_quote.estimated_blocks,
             );
+            quote.backend = Some(ln.backend_name());
 
             tracing::debug!(
                 "New {} melt quote {} for {} {} with request id {:?}",
@@ -802,6 +1026,173 @@ impl Mint {
         result
     }
 
+    /// Implementation of the keysend melt quote, paying a node pubkey instead of a request
+    #[instrument(skip_all)]
+    async fn get_melt_keysend_quote_impl(
+        &self,
+        melt_request: &MeltQuoteKeysendRequest,
+    ) -> Result<MeltQuoteCustomResponse<QuoteId>, Error> {
+        #[cfg(feature = "prometheus")]
+        let metrics = super::MintMetricGuard::new("get_melt_keysend_quote");
+
+        let result = async {
+            let MeltQuoteKeysendRequest {
+                pubkey,
+                amount,
+                unit,
+            } = melt_request;
+
+            let method = PaymentMethod::from(KEYSEND_METHOD);
+            let request = pubkey.to_hex();
+
+            let ln = self
+                .payment_processors
+                .get(&PaymentProcessorKey::new(unit.clone(), method.clone()))
+                .ok_or_else(|| {
+                    tracing::info!("Could not get ln backend for {}, keysend", unit);
+                    Error::UnsupportedUnit
+                })?;
+
+            let quote_id = cdk_common::QuoteId::new();
+            let (preimage, payment_hash) = new_keysend_preimage();
+
+            let keysend =
+                OutgoingPaymentOptions::Keysend(Box::new(KeysendOutgoingPaymentOptions {
+                    pubkey: *pubkey,
+                    amount: amount.with_unit(unit.clone()),
+                    max_fee_amount: None,
+                    timeout_secs: None,
+                    quote_id: quote_id.clone(),
+                    preimage: Some(preimage),
+                }));
+
+            let payment_quote =
+                ln.get_payment_quote(unit, keysend.clone())
+                    .await
+                    .map_err(|err| {
+                        tracing::error!(
+                            "Could not get payment quote for melt quote, {} keysend, {}",
+                            unit,
+                            err
+                        );
+                        err
+                    })?;
+
+            if payment_quote.unit() != unit {
+                return Err(Error::UnitMismatch);
+            }
+
+            let payment_quote = self.apply_fee_estimate(ln, &keysend, payment_quote).await;
+
+            self.check_outbound_liquidity(
+                ln,
+                &request,
+                &payment_quote.amount,
+                &payment_quote.fee,
+                false,
+            )
+            .await?;
+
+            self.check_melt_request_acceptable(
+                payment_quote.amount.clone(),
+                method.clone(),
+                request.clone(),
+                None,
+            )
+            .await?;
+
+            let melt_ttl = self.quote_ttl().await?.melt_ttl;
+
+            let quote_amount = payment_quote.amount;
+            let quote_fee = payment_quote.fee;
+
+            let mut quote = MeltQuote::new(
+                Some(quote_id),
+                MeltPaymentRequest::Custom {
+                    method: KEYSEND_METHOD.to_string(),
+                    request,
+                },
+                unit.clone(),
+                quote_amount.clone(),
+                quote_fee,
+                unix_time() + melt_ttl,
+                Some(PaymentIdentifier::PaymentHash(payment_hash)),
+                None,
+                method,
+                payment_quote.extra_json,
+                payment_quote.estimated_blocks,
+            );
+            quote.backend = Some(ln.backend_name());
+
+            tracing::debug!(
+                "New keysend melt quote {} for {} {} to {}",
+                quote.id,
+                quote_amount,
+                unit,
+                pubkey
+            );
+
+            let mut tx = self.localstore.begin_transaction().await?;
+            tx.add_melt_quote(quote.clone()).await?;
+            self.add_keysend_preimage(&mut tx, &quote.id, &preimage)
+                .await?;
+            tx.commit().await?;
+
+            Ok(quote.into())
+        }
+        .await;
+
+        #[cfg(feature = "prometheus")]
+        {
+            metrics.record(result.is_ok());
+        }
+
+        result
+    }
+
+    /// Keep `quote` from expiring while its payment is in flight
+    ///
+    /// A pending quote past its expiry gets a fresh melt quote TTL, so the check endpoints report
+    /// when the wallet should look again rather than an expiry that already passed. Quotes in any
+    /// other state are left alone.
+    pub(crate) async fn extend_pending_melt_quote(
+        &self,
+        quote: &mut MeltQuote,
+    ) -> Result<(), Error> {
+        let now = unix_time();
+        if quote.state != MeltQuoteState::Pending || quote.expiry > now {
+            return Ok(());
+        }
+
+        let expiry = now + self.quote_ttl().await?.melt_ttl;
+
+        let mut tx = self.localstore.begin_transaction().await?;
+        let Some(mut locked) = tx.get_melt_quote(&quote.id).await? else {
+            tx.rollback().await?;
+            return Err(Error::UnknownQuote);
+        };
+
+        // The payment may have settled since the quote was read
+        if locked.state != MeltQuoteState::Pending {
+            tx.rollback().await?;
+            *quote = locked.inner();
+            return Ok(());
+        }
+
+        tx.update_melt_quote_expiry(&mut locked, expiry).await?;
+        tx.commit().await?;
+
+        tracing::info!(
+            "Extended expiry of pending melt quote {} from {} to {}",
+            quote.id,
+            quote.expiry,
+            expiry
+        );
+        quote.expiry = expiry;
+
+        Ok(())
+    }
+
     /// Check melt quote status
     #[instrument(skip(self))]
     pub async fn check_melt_quote(
@@ -819,6 +1210,7 @@ impl Mint {
                 .ok_or(Error::UnknownQuote)?;
 
             self.handle_pending_melt_quote(&mut quote).await?;
+            self.extend_pending_melt_quote(&mut quote).await?;
 
             let blind_signatures = self
                 .localstore
@@ -896,6 +1288,15 @@ impl Mint {
         Ok(quotes)
     }
 
+    /// Melt quotes matching `search`, newest first
+    #[instrument(skip_all)]
+    pub async fn search_melt_quotes(
+        &self,
+        search: &QuoteSearch<MeltQuoteState>,
+    ) -> Result<Vec<MeltQuote>, Error> {
+        Ok(self.localstore.search_melt_quotes(search).await?)
+    }
+
     /// Melt
     ///
     /// Uses MeltSaga typestate pattern for atomic transaction handling with automatic rollback on failure.
@@ -919,6 +1320,9 @@ impl Mint {
 
         let verification = self.verify_inputs(melt_request.inputs()).await?;
 
+        // Refuse a replay of inputs being melted before touching the database
+        let _claim = self.claim_inputs(melt_request.inputs())?;
+
         // Fetch the quote to get payment_method for operation tracking
         let quote_id = melt_request.quote().clone();
         let quote = self
@@ -927,16 +1331,22 @@ impl Mint {
             .await?
             .ok_or(Error::UnknownQuote)?;
 
-        let init_saga = MeltSaga::new(
-            std::sync::Arc::new(self.clone()),
-            self.localstore.clone(),
-            std::sync::Arc::clone(&self.pubsub_manager),
-        );
+        // Step 1: Setup (TX1 - reserves inputs and outputs), run again on a transient database
+        // failure as nothing was paid yet
+        let verification = &verification;
+        let payment_method = &quote.payment_method;
+        let setup_saga = retry_transient(|| async move {
+            let init_saga = MeltSaga::new(
+                std::sync::Arc::new(self.clone()),
+                self.localstore.clone(),
+                std::sync::Arc::clone(&self.pubsub_manager),
+            );
 
-        // Step 1: Setup (TX1 - reserves inputs and outputs)
-        let setup_saga = init_saga
-            .setup_melt(melt_request, verification, quote.payment_method.clone())
-            .await?;
+            init_saga
+                .setup_melt(melt_request, verification.clone(), payment_method.clone())
+                .await
+        })
+        .await?;
 
         let melt_request_owned = melt_request.clone();
         let quote_id_for_log = quote_id.clone();
@@ -1136,7 +1546,7 @@ impl Mint {
 
         // Return immediately with the quote in PENDING state and an awaitable completion future.
         let mut quote_clone = quote.clone();
-        quote_clone.state = MeltQuoteState::Pending;
+        quote_clone.transition_state(MeltQuoteState::Pending)?;
         let response = quote_clone.into_response(None);
 
         Ok(PendingMelt {
@@ -11,7 +11,7 @@ use cdk_common::database::mint::Acquired;
 use cdk_common::database::{self, DynMintDatabase};
 use cdk_common::mint::{self as mint_types};
 use cdk_common::nuts::{BlindSignature, BlindedMessage, MeltQuoteState, Proofs, State};
-use cdk_common::{Amount, CurrencyUnit, Error, PublicKey, QuoteId};
+use cdk_common::{Amount, CurrencyUnit, Error, MeltOptions, PublicKey, QuoteId};
 #[cfg(feature = "prometheus")]
 use cdk_prometheus::METRICS;
 use cdk_signatory::signatory::SignatoryKeySet;
@@ -403,6 +403,23 @@ pub async fn load_melt_quotes_exclusively(
 
     let quote = locked.target.ok_or(Error::UnknownQuote)?;
 
+    // A failed quote cannot be paid again while another quote for the whole invoice is unpaid,
+    // the database keeps one such quote per invoice
+    if quote.state == MeltQuoteState::Failed && is_bolt11_melt_quote_for_whole_invoice(&quote) {
+        if let Some(other) = locked
+            .all_related
+            .iter()
+            .find(|other| other.id != quote.id && is_unpaid_bolt11_melt_quote(other))
+        {
+            tracing::warn!(
+                "Cannot transition quote {} to Pending: quote {} pays the same invoice",
+                quote.id,
+                other.id
+            );
+            return Err(Error::RequestHasUnpaidQuote);
+        }
+    }
+
     // Check if any sibling quote (same lookup_id) is already pending or paid
     if let Some(conflict) = locked.all_related.iter().find(|locked_quote| {
         locked_quote.id != quote.id
@@ -428,6 +445,18 @@ pub async fn load_melt_quotes_exclusively(
     Ok(quote)
 }
 
+/// Whether `quote` is a bolt11 quote paying the whole invoice, not a part of it
+pub fn is_bolt11_melt_quote_for_whole_invoice(quote: &MeltQuote) -> bool {
+    quote.payment_method.is_bolt11() && !matches!(quote.options, Some(MeltOptions::Mpp { .. }))
+}
+
+/// Whether `quote` is an unpaid bolt11 quote paying the whole invoice
+///
+/// The database keeps at most one such quote, or one being paid, per invoice.
+pub fn is_unpaid_bolt11_melt_quote(quote: &MeltQuote) -> bool {
+    quote.state == MeltQuoteState::Unpaid && is_bolt11_melt_quote_for_whole_invoice(quote)
+}
+
 /// Finalizes a melt quote by updating proofs, quote state, and publishing changes.
 ///
 /// This function performs the core finalization operations that are common to both
@@ -542,6 +571,9 @@ pub(crate) async fn finalize_melt_core(
         // Payment is already done - continue finalization but no change will be returned
     }
 
+    // Record the network fee actually paid so it can be matched against node records
+    quote.fee_paid = total_spent.checked_sub(&quote.amount()).ok();
+
     // Update quote state to Paid
     if let Err(err) = tx
         .update_melt_quote_state(&mut quote, MeltQuoteState::Paid, payment_proof.clone())
@@ -551,8 +583,6 @@ pub(crate) async fn finalize_melt_core(
         return Err(err.into());
     }
 
-    quote.state = MeltQuoteState::Paid;
-
     // Update payment lookup ID if changed
     if quote.request_lookup_id.as_ref() != Some(payment_lookup_id) {
         tracing::info!(
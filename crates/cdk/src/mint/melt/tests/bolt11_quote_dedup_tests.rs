@@ -0,0 +1,186 @@
+use cdk_common::melt::MeltQuoteRequest;
+use cdk_common::mint::MeltQuote;
+use cdk_common::nuts::{CurrencyUnit, MeltQuoteBolt11Request, MeltQuoteState};
+use cdk_common::util::unix_time;
+use cdk_common::{MeltOptions, QuoteId};
+use cdk_fake_wallet::{create_fake_invoice, FakeInvoiceDescription};
+use lightning_invoice::Bolt11Invoice;
+
+use crate::mint::melt::shared::load_melt_quotes_exclusively;
+use crate::mint::Mint;
+use crate::test_helpers::mint::create_test_mint;
+use crate::Error;
+
+fn bolt11_request(invoice: &Bolt11Invoice) -> MeltQuoteRequest {
+    MeltQuoteRequest::Bolt11(MeltQuoteBolt11Request {
+        request: invoice.clone(),
+        unit: CurrencyUnit::Sat,
+        options: None,
+    })
+}
+
+async fn request_quote(mint: &Mint, invoice: &Bolt11Invoice) -> Result<QuoteId, Error> {
+    Ok(mint
+        .get_melt_quote(bolt11_request(invoice))
+        .await?
+        .quote()
+        .expect("single-quote method")
+        .clone())
+}
+
+async fn set_quote_state(mint: &Mint, quote_id: &QuoteId, state: MeltQuoteState) {
+    let mut tx = mint.localstore.begin_transaction().await.unwrap();
+    let mut quote = tx.get_melt_quote(quote_id).await.unwrap().unwrap();
+    tx.update_melt_quote_state(&mut quote, state, None)
+        .await
+        .unwrap();
+    tx.commit().await.unwrap();
+}
+
+/// Stores a copy of quote `quote_id`, unpaid and with `options`, as another request would
+async fn store_quote_with_options(
+    mint: &Mint,
+    quote_id: &QuoteId,
+    options: Option<MeltOptions>,
+) -> MeltQuote {
+    let mut quote = mint
+        .localstore
+        .get_melt_quote(quote_id)
+        .await
+        .unwrap()
+        .unwrap();
+    quote.id = QuoteId::new();
+    quote.state = MeltQuoteState::Unpaid;
+    quote.options = options;
+
+    let mut tx = mint.localstore.begin_transaction().await.unwrap();
+    tx.add_melt_quote(quote.clone()).await.unwrap();
+    tx.commit().await.unwrap();
+
+    quote
+}
+
+async fn quote_state(mint: &Mint, quote_id: &QuoteId) -> MeltQuoteState {
+    mint.localstore
+        .get_melt_quote(quote_id)
+        .await
+        .unwrap()
+        .unwrap()
+        .state
+}
+
+fn fake_invoice() -> Bolt11Invoice {
+    create_fake_invoice(
+        9_000,
+        serde_json::to_string(&FakeInvoiceDescription::default()).unwrap(),
+    )
+}
+
+#[tokio::test]
+async fn same_invoice_gets_the_same_quote() {
+    let mint = create_test_mint().await.unwrap();
+    let invoice = fake_invoice();
+
+    let first = request_quote(&mint, &invoice).await.unwrap();
+    let second = request_quote(&mint, &invoice).await.unwrap();
+    assert_eq!(first, second);
+
+    let other = request_quote(&mint, &fake_invoice()).await.unwrap();
+    assert_ne!(first, other);
+}
+
+#[tokio::test]
+async fn invoice_being_paid_is_refused() {
+    let mint = create_test_mint().await.unwrap();
+    let invoice = fake_invoice();
+
+    let quote_id = request_quote(&mint, &invoice).await.unwrap();
+    set_quote_state(&mint, &quote_id, MeltQuoteState::Pending).await;
+
+    assert!(matches!(
+        request_quote(&mint, &invoice).await,
+        Err(Error::PendingQuote)
+    ));
+
+    set_quote_state(&mint, &quote_id, MeltQuoteState::Paid).await;
+
+    assert!(matches!(
+        request_quote(&mint, &invoice).await,
+        Err(Error::RequestAlreadyPaid)
+    ));
+}
+
+#[tokio::test]
+async fn failed_quote_is_not_returned_again() {
+    let mint = create_test_mint().await.unwrap();
+    let invoice = fake_invoice();
+
+    let quote_id = request_quote(&mint, &invoice).await.unwrap();
+    set_quote_state(&mint, &quote_id, MeltQuoteState::Pending).await;
+    set_quote_state(&mint, &quote_id, MeltQuoteState::Failed).await;
+
+    let retried = request_quote(&mint, &invoice).await.unwrap();
+    assert_ne!(quote_id, retried);
+}
+
+#[tokio::test]
+async fn concurrent_requests_get_the_same_quote() {
+    let mint = create_test_mint().await.unwrap();
+    let invoice = fake_invoice();
+
+    let (first, second) = tokio::join!(
+        request_quote(&mint, &invoice),
+        request_quote(&mint, &invoice)
+    );
+    assert_eq!(first.unwrap(), second.unwrap());
+}
+
+#[tokio::test]
+async fn live_quote_of_another_request_is_left_alone() {
+    let mint = create_test_mint().await.unwrap();
+    let invoice = fake_invoice();
+
+    let quote_id = request_quote(&mint, &invoice).await.unwrap();
+    set_quote_state(&mint, &quote_id, MeltQuoteState::Failed).await;
+    let other = store_quote_with_options(
+        &mint,
+        &quote_id,
+        Some(MeltOptions::new_amountless(9_000_000u64)),
+    )
+    .await;
+
+    assert!(matches!(
+        request_quote(&mint, &invoice).await,
+        Err(Error::RequestHasUnpaidQuote)
+    ));
+    assert_eq!(quote_state(&mint, &other.id).await, MeltQuoteState::Unpaid);
+
+    // Once expired it no longer holds the invoice
+    let mut tx = mint.localstore.begin_transaction().await.unwrap();
+    let mut quote = tx.get_melt_quote(&other.id).await.unwrap().unwrap();
+    tx.update_melt_quote_expiry(&mut quote, unix_time() - 1)
+        .await
+        .unwrap();
+    tx.commit().await.unwrap();
+
+    let retried = request_quote(&mint, &invoice).await.unwrap();
+    assert_ne!(retried, other.id);
+    assert_eq!(quote_state(&mint, &other.id).await, MeltQuoteState::Failed);
+}
+
+#[tokio::test]
+async fn failed_quote_is_not_paid_while_another_is_unpaid() {
+    let mint = create_test_mint().await.unwrap();
+    let invoice = fake_invoice();
+
+    let failed = request_quote(&mint, &invoice).await.unwrap();
+    set_quote_state(&mint, &failed, MeltQuoteState::Failed).await;
+    request_quote(&mint, &invoice).await.unwrap();
+
+    let mut tx = mint.localstore.begin_transaction().await.unwrap();
+    assert!(matches!(
+        load_melt_quotes_exclusively(&mut tx, &failed).await,
+        Err(Error::RequestHasUnpaidQuote)
+    ));
+    tx.rollback().await.unwrap();
+}
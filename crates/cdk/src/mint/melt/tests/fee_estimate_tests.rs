@@ -0,0 +1,169 @@
+use std::collections::{HashMap, HashSet};
+use std::pin::Pin;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use cdk_common::nut00::KnownMethod;
+use cdk_common::nuts::{CurrencyUnit, MeltQuoteState};
+use cdk_common::payment::{
+    self, CreateIncomingPaymentResponse, Event, IncomingPaymentOptions, MakePaymentResponse,
+    MintPayment, OutgoingPaymentOptions, PaymentIdentifier, PaymentQuoteResponse, SettingsResponse,
+    WaitPaymentResponse,
+};
+use cdk_common::{Amount, MeltQuoteBolt11Request, PaymentMethod};
+use cdk_fake_wallet::{create_fake_invoice, FakeInvoiceDescription, FakeWallet};
+use futures::Stream;
+
+use crate::mint::{Mint, MintBuilder, MintMeltLimits};
+use crate::types::FeeReserve;
+
+/// Fake wallet with a fee reserve of 10% that estimates `estimate` when given one
+struct EstimatingBackend {
+    inner: FakeWallet,
+    estimate: Option<u64>,
+}
+
+#[async_trait]
+impl MintPayment for EstimatingBackend {
+    type Err = payment::Error;
+
+    async fn get_settings(&self) -> Result<SettingsResponse, Self::Err> {
+        self.inner.get_settings().await
+    }
+
+    async fn create_incoming_payment_request(
+        &self,
+        options: IncomingPaymentOptions,
+    ) -> Result<CreateIncomingPaymentResponse, Self::Err> {
+        self.inner.create_incoming_payment_request(options).await
+    }
+
+    async fn get_payment_quote(
+        &self,
+        unit: &CurrencyUnit,
+        options: OutgoingPaymentOptions,
+    ) -> Result<PaymentQuoteResponse, Self::Err> {
+        self.inner.get_payment_quote(unit, options).await
+    }
+
+    async fn make_payment(
+        &self,
+        unit: &CurrencyUnit,
+        options: OutgoingPaymentOptions,
+    ) -> Result<MakePaymentResponse, Self::Err> {
+        self.inner.make_payment(unit, options).await
+    }
+
+    async fn wait_payment_event(
+        &self,
+    ) -> Result<Pin<Box<dyn Stream<Item = Event> + Send>>, Self::Err> {
+        Ok(Box::pin(futures::stream::pending()))
+    }
+
+    fn is_payment_event_stream_active(&self) -> bool {
+        false
+    }
+
+    fn cancel_payment_event_stream(&self) {}
+
+    async fn check_incoming_payment_status(
+        &self,
+        payment_identifier: &PaymentIdentifier,
+    ) -> Result<Vec<WaitPaymentResponse>, Self::Err> {
+        self.inner
+            .check_incoming_payment_status(payment_identifier)
+            .await
+    }
+
+    async fn check_outgoing_payment(
+        &self,
+        payment_identifier: &PaymentIdentifier,
+    ) -> Result<MakePaymentResponse, Self::Err> {
+        self.inner.check_outgoing_payment(payment_identifier).await
+    }
+
+    async fn estimate_fee(
+        &self,
+        unit: &CurrencyUnit,
+        _options: &OutgoingPaymentOptions,
+    ) -> Result<Option<Amount<CurrencyUnit>>, Self::Err> {
+        Ok(self
+            .estimate
+            .map(|estimate| Amount::new(estimate, unit.clone())))
+    }
+}
+
+async fn create_estimating_mint(estimate: Option<u64>) -> Mint {
+    let backend = EstimatingBackend {
+        inner: FakeWallet::new(
+            FeeReserve {
+                min_fee_reserve: 1.into(),
+                percent_fee_reserve: 0.1,
+            },
+            HashMap::default(),
+            HashSet::default(),
+            2,
+            CurrencyUnit::Sat,
+        ),
+        estimate,
+    };
+
+    let db = Arc::new(cdk_sqlite::mint::memory::empty().await.unwrap());
+    let mut mint_builder = MintBuilder::new(db.clone());
+    mint_builder
+        .add_payment_processor(
+            CurrencyUnit::Sat,
+            PaymentMethod::Known(KnownMethod::Bolt11),
+            MintMeltLimits::new(1, 10_000),
+            Arc::new(backend),
+        )
+        .await
+        .unwrap();
+
+    let mnemonic = bip39::Mnemonic::generate(12).unwrap();
+    mint_builder
+        .with_name("test mint".to_string())
+        .with_urls(vec!["https://test-mint".to_string()])
+        .build_with_seed(db, &mnemonic.to_seed_normalized(""))
+        .await
+        .unwrap()
+}
+
+async fn melt_fee_reserve(mint: &Mint) -> u64 {
+    let description = FakeInvoiceDescription {
+        pay_invoice_state: MeltQuoteState::Paid,
+        check_payment_state: MeltQuoteState::Paid,
+        pay_err: false,
+        check_err: false,
+    };
+    let invoice = create_fake_invoice(1_000_000, serde_json::to_string(&description).unwrap());
+
+    let response = mint
+        .get_melt_quote(cdk_common::melt::MeltQuoteRequest::Bolt11(
+            MeltQuoteBolt11Request {
+                request: invoice,
+                unit: CurrencyUnit::Sat,
+                options: None,
+            },
+        ))
+        .await
+        .unwrap();
+
+    mint.localstore()
+        .get_melt_quote(response.quote().unwrap())
+        .await
+        .unwrap()
+        .unwrap()
+        .fee_reserve()
+        .value()
+}
+
+#[tokio::test]
+async fn melt_quotes_use_the_fee_estimate_of_the_backend() {
+    let mint = create_estimating_mint(Some(3)).await;
+    assert_eq!(melt_fee_reserve(&mint).await, 3);
+
+    // Without an estimate the fee reserve of the backend quote is kept
+    let mint = create_estimating_mint(None).await;
+    assert_eq!(melt_fee_reserve(&mint).await, 100);
+}
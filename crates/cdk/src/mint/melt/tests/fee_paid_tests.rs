@@ -0,0 +1,56 @@
+use cdk_common::nuts::{CurrencyUnit, MeltQuoteState};
+use cdk_common::{Amount, MeltQuoteBolt11Request};
+use cdk_fake_wallet::{create_fake_invoice, FakeInvoiceDescription};
+
+use crate::test_helpers::mint::{create_test_mint, mint_test_proofs};
+
+#[tokio::test]
+async fn paid_melt_quote_records_the_fee_paid() {
+    let mint = create_test_mint().await.unwrap();
+    let proofs = mint_test_proofs(&mint, Amount::from(10_000)).await.unwrap();
+
+    let fake_description = FakeInvoiceDescription {
+        pay_invoice_state: MeltQuoteState::Paid,
+        check_payment_state: MeltQuoteState::Paid,
+        pay_err: false,
+        check_err: false,
+    };
+    let invoice = create_fake_invoice(9_000_000, serde_json::to_string(&fake_description).unwrap());
+
+    let quote_response = mint
+        .get_melt_quote(cdk_common::melt::MeltQuoteRequest::Bolt11(
+            MeltQuoteBolt11Request {
+                request: invoice,
+                unit: CurrencyUnit::Sat,
+                options: None,
+            },
+        ))
+        .await
+        .unwrap();
+    let quote_id = quote_response.quote().unwrap().clone();
+
+    let stored_quote = mint
+        .localstore()
+        .get_melt_quote(&quote_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored_quote.fee_paid, None);
+
+    let melt_request = cdk_common::nuts::MeltRequest::new(quote_id.clone(), proofs, None);
+    let response = mint.melt(&melt_request).await.unwrap().await.unwrap();
+    assert_eq!(response.state(), MeltQuoteState::Paid);
+
+    // The fake wallet spends one sat on top of the quote amount
+    let stored_quote = mint
+        .localstore()
+        .get_melt_quote(&quote_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored_quote.state, MeltQuoteState::Paid);
+    assert_eq!(
+        stored_quote.fee_paid,
+        Some(Amount::new(1, CurrencyUnit::Sat))
+    );
+}
@@ -0,0 +1,130 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use bitcoin::hashes::sha256::Hash as Sha256Hash;
+use bitcoin::hashes::Hash;
+use cdk_common::melt::MeltQuoteRequest;
+use cdk_common::mint::MeltPaymentRequest;
+use cdk_common::nuts::{CurrencyUnit, PublicKey, SecretKey};
+use cdk_common::payment::PaymentIdentifier;
+use cdk_common::{Amount, MeltQuoteCustomRequest, MeltQuoteKeysendRequest, KEYSEND_METHOD};
+use cdk_fake_wallet::FakeWallet;
+
+use crate::mint::{Mint, MintBuilder, MintMeltLimits};
+use crate::nuts::PaymentMethod;
+use crate::types::FeeReserve;
+
+async fn create_keysend_mint() -> Mint {
+    let fake_wallet = FakeWallet::new(
+        FeeReserve {
+            min_fee_reserve: 1.into(),
+            percent_fee_reserve: 0.1,
+        },
+        HashMap::default(),
+        HashSet::default(),
+        2,
+        CurrencyUnit::Sat,
+    )
+    .with_custom_payment_methods(HashMap::from([(
+        KEYSEND_METHOD.to_string(),
+        "{}".to_string(),
+    )]));
+
+    let db = Arc::new(cdk_sqlite::mint::memory::empty().await.unwrap());
+    let mut mint_builder = MintBuilder::new(db.clone());
+    mint_builder
+        .add_payment_processor(
+            CurrencyUnit::Sat,
+            PaymentMethod::from(KEYSEND_METHOD),
+            MintMeltLimits::new(1, 10_000),
+            Arc::new(fake_wallet),
+        )
+        .await
+        .unwrap();
+
+    let mnemonic = bip39::Mnemonic::generate(12).unwrap();
+    mint_builder
+        .with_name("test mint".to_string())
+        .with_urls(vec!["https://test-mint".to_string()])
+        .build_with_seed(db, &mnemonic.to_seed_normalized(""))
+        .await
+        .unwrap()
+}
+
+fn keysend_request(pubkey: PublicKey, amount: u64) -> MeltQuoteRequest {
+    MeltQuoteRequest::Custom(MeltQuoteCustomRequest::from(MeltQuoteKeysendRequest {
+        pubkey,
+        amount: Amount::from(amount),
+        unit: CurrencyUnit::Sat,
+    }))
+}
+
+#[tokio::test]
+async fn keysend_quotes_pay_the_node_pubkey() {
+    let mint = create_keysend_mint().await;
+    let pubkey = SecretKey::generate().public_key();
+
+    let response = mint
+        .get_melt_quote(keysend_request(pubkey, 1_000))
+        .await
+        .unwrap();
+
+    let quote = mint
+        .localstore()
+        .get_melt_quote(response.quote().unwrap())
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(quote.payment_method, PaymentMethod::from(KEYSEND_METHOD));
+    assert_eq!(
+        quote.request,
+        MeltPaymentRequest::Custom {
+            method: KEYSEND_METHOD.to_string(),
+            request: pubkey.to_hex(),
+        }
+    );
+    assert_eq!(quote.amount().value(), 1_000);
+    assert_eq!(quote.fee_reserve().value(), 100);
+
+    // The preimage is picked with the quote, which is looked up by its payment hash
+    let preimage = mint.keysend_preimage(&quote.id).await.unwrap();
+    assert_eq!(
+        quote.request_lookup_id,
+        Some(PaymentIdentifier::PaymentHash(
+            Sha256Hash::hash(&preimage).to_byte_array()
+        ))
+    );
+}
+
+#[tokio::test]
+async fn keysend_quotes_are_melt_only_and_validated() {
+    let mint = create_keysend_mint().await;
+    let pubkey = SecretKey::generate().public_key();
+
+    let mint_info = mint.mint_info().await.unwrap();
+    assert!(mint_info.nuts.nut04.methods.is_empty());
+    assert!(mint_info
+        .nuts
+        .nut05
+        .get_settings(&CurrencyUnit::Sat, &PaymentMethod::from(KEYSEND_METHOD))
+        .is_some());
+
+    // Above the melt limit
+    assert!(mint
+        .get_melt_quote(keysend_request(pubkey, 20_000))
+        .await
+        .is_err());
+
+    // Without an amount
+    let mut request = MeltQuoteCustomRequest::from(MeltQuoteKeysendRequest {
+        pubkey,
+        amount: Amount::from(1),
+        unit: CurrencyUnit::Sat,
+    });
+    request.extra = serde_json::Value::Null;
+    assert!(mint
+        .get_melt_quote(MeltQuoteRequest::Custom(request))
+        .await
+        .is_err());
+}
@@ -1,5 +1,9 @@
+mod bolt11_quote_dedup_tests;
+mod fee_estimate_tests;
+mod fee_paid_tests;
 mod htlc_sigall_spending_conditions_tests;
 mod htlc_spending_conditions_tests;
+mod keysend_quote_tests;
 mod locktime_spending_conditions_tests;
 mod onchain_quote_id_tests;
 mod p2pk_sigall_spending_conditions_tests;
@@ -449,3 +449,39 @@ async fn pending_melt_without_quote_lookup_id_resolves_via_status_check() {
     assert_eq!(stored_quote.state, MeltQuoteState::Paid);
     assert!(stored_quote.request_lookup_id.is_some());
 }
+
+#[tokio::test]
+async fn pending_melt_quote_expiry_is_extended_when_checked() {
+    let backend: Arc<dyn MintPayment<Err = payment::Error> + Send + Sync> =
+        Arc::new(NoEventPendingBackend::new(usize::MAX, None));
+    let mint = create_pending_test_mint(backend).await.unwrap();
+    let proofs = mint_test_proofs(&mint, Amount::from(10_000)).await.unwrap();
+    let quote = create_test_melt_quote(&mint, Amount::from(9_000)).await;
+    let melt_request = create_test_melt_request(&proofs, &quote);
+
+    let pending = mint.melt(&melt_request).await.unwrap();
+    let err = pending.await.unwrap_err();
+    assert!(matches!(err, Error::PendingMeltTimeout { .. }));
+
+    // The quote expires while the payment is still in flight
+    let expired = crate::util::unix_time() - 1;
+    let mut tx = mint.localstore().begin_transaction().await.unwrap();
+    let mut locked = tx.get_melt_quote(&quote.id).await.unwrap().unwrap();
+    tx.update_melt_quote_expiry(&mut locked, expired)
+        .await
+        .unwrap();
+    tx.commit().await.unwrap();
+
+    let response = mint.check_melt_quote(&quote.id).await.unwrap();
+    assert_eq!(response.state(), MeltQuoteState::Pending);
+
+    let stored_quote = mint
+        .localstore()
+        .get_melt_quote(&quote.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored_quote.state, MeltQuoteState::Pending);
+    assert!(stored_quote.expiry > crate::util::unix_time());
+    assert_eq!(response.expiry(), stored_quote.expiry);
+}
@@ -1,13 +1,14 @@
 //! Cashu Mint
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
-use std::time::Duration;
 
 use arc_swap::ArcSwap;
 use cdk_common::common::{PaymentProcessorKey, QuoteTTL};
 use cdk_common::database::mint::Acquired;
-use cdk_common::database::{self, DynMintAuthDatabase, DynMintDatabase};
+use cdk_common::database::{
+    self, DynMintAuthDatabase, DynMintDatabase, InternalSettlement, KeysetUsage,
+};
 use cdk_common::nuts::{BlindSignature, BlindedMessage, CurrencyUnit, Id};
 use cdk_common::payment::{DynMintPayment, WaitPaymentResponse};
 pub use cdk_common::quote_id::QuoteId;
@@ -15,10 +16,11 @@ pub use cdk_common::quote_id::QuoteId;
 use cdk_prometheus::MintMetricGuard;
 use cdk_signatory::signatory::{Signatory, SignatoryKeySet};
 use futures::StreamExt;
+use lightning_invoice::Bolt11Invoice;
 use nut21::ProtectedEndpoint;
 use subscription::PubSubManager;
-use tokio::sync::{Mutex, Notify};
-use tokio::task::{JoinHandle, JoinSet};
+use tokio::sync::{broadcast, Mutex};
+use tokio_util::sync::CancellationToken;
 use tracing::instrument;
 
 use crate::error::Error;
@@ -26,31 +28,76 @@ use crate::fees::calculate_fee;
 use crate::nuts::*;
 use crate::{Amount, OidcClient};
 
+mod audit;
 pub(crate) mod auth;
+mod backend_monitor;
 mod builder;
 mod check_spendable;
+mod disabled_nuts;
+mod forensics;
+mod hold_invoices;
+mod in_flight;
+mod issuance_cap;
 mod issue;
+mod keysend;
 mod keysets;
+mod liquidity;
 mod ln;
 mod melt;
+mod payment_events;
+mod payment_router;
 mod proofs;
+mod quote_expiry;
+mod read_only;
+mod readiness;
+mod response_cache;
+mod retry;
 mod saga_recovery;
+mod settlements;
 mod start_up_check;
 mod subscription;
 mod swap;
+mod tasks;
+mod usage_statistics;
 mod verification;
+mod vouchers;
 
+pub use audit::{AuditIssue, AuditReport, KeysetAudit, UnitAudit, STUCK_MELT_SECS};
+pub use backend_monitor::BackendStats;
 pub use builder::{KeysetRotation, MintBuilder, MintMeltLimits, UnitConfig};
+pub use cdk_common::database::{InternalSettlement, QuoteSearch, DEFAULT_QUOTE_SEARCH_LIMIT};
 pub use cdk_common::mint::{MeltQuote, MintKeySetInfo, MintQuote};
 pub use cdk_common::mint_quote::{MintQuoteRequest, MintQuoteResponse};
+pub use disabled_nuts::{disable_nuts, DISABLEABLE_NUTS};
 pub use issue::MintInput;
+pub use keysets::KeysetRotationPolicy;
+pub use liquidity::{LiquidityPolicy, LiquidityStatus};
 pub use melt::PendingMelt;
-pub use verification::Verification;
+use payment_events::{BackendEvent, BackendUpdate, PaymentEventMultiplexer};
+pub use payment_events::{
+    PaymentEventOutage, PaymentOutageHook, RestartPolicy, DEFAULT_PAYMENT_OUTAGE_ALERT,
+};
+pub use payment_router::{PaymentRouter, RoutingPolicy};
+pub use quote_expiry::{ExpiredQuotes, QuoteExpiryPolicy};
+pub use read_only::DEFAULT_READ_ONLY_MOTD;
+pub use readiness::{BackendHealth, DependencyHealth, Readiness, READINESS_CHECK_TIMEOUT};
+pub use tasks::{TaskHealth, TaskStatus};
+pub use verification::{
+    BalanceVerifier, DuplicatesVerifier, KeysetVerifier, LimitsVerifier, SignatureVerifier,
+    SpendingConditionsVerifier, Verification, VerificationPipeline, VerificationReport, Verifier,
+    MAX_PROOF_CONTENT_LEN,
+};
+pub use vouchers::{
+    IssuedVouchers, Voucher, VoucherBatch, VoucherBatchRequest, VoucherLock, VoucherReport,
+    MAX_VOUCHER_BATCH_SIZE,
+};
 
 const CDK_MINT_PRIMARY_NAMESPACE: &str = "cdk_mint";
 const CDK_MINT_CONFIG_SECONDARY_NAMESPACE: &str = "config";
 const CDK_MINT_CONFIG_KV_KEY: &str = "mint_info";
 const CDK_MINT_QUOTE_TTL_KV_KEY: &str = "quote_ttl";
+const CDK_MINT_READ_ONLY_KV_KEY: &str = "read_only";
+const CDK_MINT_ISSUANCE_CAP_OVERRIDES_KV_KEY: &str = "issuance_cap_overrides";
 
 /// Cashu Mint
 #[derive(Clone)]
@@ -73,10 +120,52 @@ pub struct Mint {
     keysets: Arc<ArcSwap<Vec<SignatoryKeySet>>>,
     /// Background task management
     task_state: Arc<Mutex<TaskState>>,
+    /// Owner of every task spawned by the mint
+    tasks: Arc<tasks::TaskSupervisor>,
     /// Maximum number of inputs allowed per transaction
     max_inputs: usize,
     /// Maximum number of outputs allowed per transaction
     max_outputs: usize,
+    /// Largest secret or witness of an input, in bytes
+    max_proof_content_len: usize,
+    /// Stages inputs, outputs and transactions are verified with
+    verification_pipeline: Arc<VerificationPipeline>,
+    /// Seconds locktimes are treated as passed early, to tolerate wallet clock skew
+    clock_skew_grace_secs: u64,
+    /// How the payment event stream of each backend is reopened, the default when missing
+    payment_event_restart_policies: Arc<HashMap<PaymentProcessorKey, RestartPolicy>>,
+    /// Payment event streams closed for longer are reported
+    payment_outage_alert_after: std::time::Duration,
+    /// Called with the reported payment event stream outages
+    payment_outage_hook: Option<PaymentOutageHook>,
+    /// Cancelled by [`Mint::shutdown`], every background task listens to a child of it
+    shutdown: CancellationToken,
+    /// Whether [`Mint::usage_statistics`] may be queried
+    usage_statistics: bool,
+    /// Lifetime of new keysets and when the active ones are replaced, none by default
+    keyset_rotation_policy: Option<KeysetRotationPolicy>,
+    /// How often stale quotes are expired and when they are purged, none by default
+    quote_expiry_policy: Option<QuoteExpiryPolicy>,
+    /// Time each keyset on a retirement schedule stops being accepted
+    keyset_retirements: Arc<HashMap<Id, u64>>,
+    /// Largest output amount signed for each capped unit
+    max_denominations: Arc<BTreeMap<CurrencyUnit, Amount>>,
+    /// Bitcoin network payments are settled on, not checked when none
+    network: Option<bitcoin::Network>,
+    /// How melt quotes above the outbound liquidity of their backend are handled
+    liquidity_policy: LiquidityPolicy,
+    /// Whether bolt11 mint quotes are paid through hold invoices when the backend supports them
+    hold_invoices: bool,
+    /// Largest amount the active keyset of each capped unit may have outstanding
+    issuance_caps: Arc<HashMap<CurrencyUnit, Amount>>,
+    /// Input sets of the swaps and melts in progress
+    in_flight_inputs: Arc<in_flight::InFlightInputs>,
+    /// Checks, incoming and outgoing payments of every payment backend
+    backend_monitor: Arc<backend_monitor::BackendMonitor>,
+    /// Notifies [`Mint::subscribe_changes`] subscribers
+    changes: broadcast::Sender<MintChange>,
+    /// Notifies [`Mint::subscribe_internal_settlements`] subscribers
+    internal_settlements: broadcast::Sender<InternalSettlement>,
 }
 
 impl std::fmt::Debug for Mint {
@@ -85,13 +174,20 @@ impl std::fmt::Debug for Mint {
     }
 }
 
+/// Public data of the mint that changed, see [`Mint::subscribe_changes`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MintChange {
+    /// The keysets were rotated
+    Keysets,
+    /// The mint info was updated
+    Info,
+}
+
 /// State for managing background tasks
 #[derive(Default)]
 struct TaskState {
-    /// Shutdown signal for all background tasks
-    shutdown_notify: Option<Arc<Notify>>,
-    /// Handle to the main supervisor task
-    supervisor_handle: Option<JoinHandle<Result<(), Error>>>,
+    /// Shutdown signal for all background tasks, a child of the mint's shutdown token
+    shutdown: Option<CancellationToken>,
 }
 
 impl Mint {
@@ -231,10 +327,14 @@ impl Mint {
         }
 
         let payment_processors = Arc::new(payment_processors);
+        let shutdown = CancellationToken::new();
 
         Ok(Self {
             signatory,
-            pubsub_manager: PubSubManager::new((localstore.clone(), payment_processors.clone())),
+            pubsub_manager: PubSubManager::new(
+                (localstore.clone(), payment_processors.clone()),
+                shutdown.child_token(),
+            ),
             localstore,
             oidc_client: computed_info.nuts.nut21.as_ref().map(|nut21| {
                 OidcClient::new(
@@ -246,11 +346,163 @@ impl Mint {
             auth_localstore,
             keysets: Arc::new(ArcSwap::new(keysets.keysets.into())),
             task_state: Arc::new(Mutex::new(TaskState::default())),
+            tasks: Arc::default(),
             max_inputs,
             max_outputs,
+            max_proof_content_len: MAX_PROOF_CONTENT_LEN,
+            verification_pipeline: Arc::new(VerificationPipeline::default()),
+            clock_skew_grace_secs: 0,
+            payment_event_restart_policies: Arc::new(HashMap::new()),
+            payment_outage_alert_after: DEFAULT_PAYMENT_OUTAGE_ALERT,
+            payment_outage_hook: None,
+            shutdown,
+            usage_statistics: false,
+            keyset_rotation_policy: None,
+            quote_expiry_policy: None,
+            keyset_retirements: Arc::new(HashMap::new()),
+            max_denominations: Arc::new(BTreeMap::new()),
+            network: None,
+            liquidity_policy: LiquidityPolicy::default(),
+            hold_invoices: false,
+            issuance_caps: Arc::new(HashMap::new()),
+            in_flight_inputs: Arc::default(),
+            backend_monitor: Arc::default(),
+            changes: broadcast::channel(16).0,
+            internal_settlements: broadcast::channel(64).0,
         })
     }
 
+    /// Replace the [`VerificationPipeline`] inputs, outputs and transactions are verified with
+    pub fn with_verification_pipeline(mut self, pipeline: VerificationPipeline) -> Self {
+        self.verification_pipeline = Arc::new(pipeline);
+        self
+    }
+
+    /// Refuse inputs whose secret or witness is longer than `len` bytes
+    ///
+    /// Defaults to [`MAX_PROOF_CONTENT_LEN`]. Witnesses are measured serialized.
+    pub fn with_max_proof_content_len(mut self, len: usize) -> Self {
+        self.max_proof_content_len = len;
+        self
+    }
+
+    /// Treat locktimes as passed `secs` early, tolerating wallets whose clock runs ahead
+    pub fn with_clock_skew_grace(mut self, secs: u64) -> Self {
+        self.clock_skew_grace_secs = secs;
+        self
+    }
+
+    /// Allow the coarse daily usage statistics of [`Mint::usage_statistics`] to be queried
+    pub fn with_usage_statistics(mut self, enabled: bool) -> Self {
+        self.usage_statistics = enabled;
+        self
+    }
+
+    /// Rotate the active keysets following `policy` while the mint is running
+    ///
+    /// Keysets created by [`Mint::rotate_expiring_keysets`] expire `policy.lifetime` after
+    /// their creation.
+    pub fn with_keyset_rotation_policy(mut self, policy: KeysetRotationPolicy) -> Self {
+        self.keyset_rotation_policy = Some(policy);
+        self
+    }
+
+    /// Stop accepting proofs of each keyset after the unix time it maps to
+    ///
+    /// The times are published with the keysets so wallets swap out of them ahead of it. An
+    /// active keyset due for retirement is replaced by the rotation policy, if any.
+    pub fn with_keyset_retirements(mut self, retirements: HashMap<Id, u64>) -> Self {
+        self.keyset_retirements = Arc::new(retirements);
+        self
+    }
+
+    /// Refuse outputs above the amount each unit maps to, whatever the amounts of its keysets
+    ///
+    /// The caps are advertised in the mint info, replacing any stored with it.
+    pub fn with_max_denominations(
+        mut self,
+        max_denominations: BTreeMap<CurrencyUnit, Amount>,
+    ) -> Self {
+        self.max_denominations = Arc::new(max_denominations);
+        self
+    }
+
+    /// Largest output amount signed for `unit`, if capped
+    pub fn max_denomination(&self, unit: &CurrencyUnit) -> Option<Amount> {
+        self.max_denominations.get(unit).copied()
+    }
+
+    /// Settle payments on `network`
+    ///
+    /// The network is advertised in the mint info, and bolt11 invoices for another network are
+    /// refused, whether a wallet asks to pay them or a backend created them.
+    pub fn with_network(mut self, network: bitcoin::Network) -> Self {
+        self.network = Some(network);
+        self
+    }
+
+    /// Bitcoin network payments are settled on, if set
+    pub fn network(&self) -> Option<bitcoin::Network> {
+        self.network
+    }
+
+    /// Check that `invoice` is for the network of the mint
+    pub(crate) fn check_invoice_network(&self, invoice: &Bolt11Invoice) -> Result<(), Error> {
+        match self.network {
+            Some(expected) if invoice.network() != expected => Err(Error::NetworkMismatch {
+                expected,
+                found: invoice.network(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Reopen the payment event stream of the backend registered under `key` with `policy`
+    ///
+    /// Backends without a policy use [`RestartPolicy::default`].
+    pub fn with_payment_event_restart_policy(
+        mut self,
+        key: PaymentProcessorKey,
+        policy: RestartPolicy,
+    ) -> Self {
+        Arc::make_mut(&mut self.payment_event_restart_policies).insert(key, policy);
+        self
+    }
+
+    /// Call `hook` when the payment event stream of a backend stays closed longer than `after`,
+    /// and again once it reopens
+    ///
+    /// Outages are logged and counted in the metrics even without a hook, after
+    /// [`DEFAULT_PAYMENT_OUTAGE_ALERT`] unless set here.
+    pub fn with_payment_outage_alert(
+        mut self,
+        after: std::time::Duration,
+        hook: PaymentOutageHook,
+    ) -> Self {
+        self.payment_outage_alert_after = after;
+        self.payment_outage_hook = Some(hook);
+        self
+    }
+
+    /// Replace the token whose cancellation shuts the mint down
+    ///
+    /// Lets the mint share a token with the rest of the application, such as the embedded
+    /// signatory built by [`MintBuilder`]. Call it before the mint is started, it replaces the
+    /// pubsub manager and subscriptions made before are not carried over.
+    pub fn with_shutdown_token(mut self, shutdown: CancellationToken) -> Self {
+        self.pubsub_manager = PubSubManager::new(
+            (self.localstore.clone(), self.payment_processors.clone()),
+            shutdown.child_token(),
+        );
+        self.shutdown = shutdown;
+        self
+    }
+
+    /// Token cancelled when the mint shuts down, for tasks that run alongside the mint
+    pub fn shutdown_token(&self) -> CancellationToken {
+        self.shutdown.child_token()
+    }
+
     /// Start the mint's background services and operations
     ///
     /// This function immediately starts background services and returns. The background
@@ -266,7 +518,14 @@ impl Mint {
     /// Currently manages:
     /// - Payment processor initialization and startup
     /// - Invoice payment monitoring across all configured payment processors
+    /// - Scheduled keyset rotation, when a [`KeysetRotationPolicy`] is set
+    ///
+    /// The tasks are restarted when they fail or panic, see [`Mint::task_health`].
     pub async fn start(&self) -> Result<(), Error> {
+        if self.shutdown.is_cancelled() {
+            return Err(Error::Custom("The mint has been shut down".to_owned()));
+        }
+
         // Recover from incomplete swap sagas
         // This cleans up incomplete swap operations using persisted saga state
         if let Err(e) = self.recover_from_incomplete_sagas().await {
@@ -283,10 +542,17 @@ impl Mint {
             // Don't fail startup
         }
 
+        // Settle or fail the melt quotes still pending without a saga, such as
+        // ones left by a crash mid-payment before sagas were persisted
+        if let Err(e) = self.check_pending_melt_quotes().await {
+            tracing::error!("Failed to check pending melt quotes: {}", e);
+            // Don't fail startup
+        }
+
         let mut task_state = self.task_state.lock().await;
 
         // Prevent starting if already running
-        if task_state.shutdown_notify.is_some() {
+        if task_state.shutdown.is_some() {
             return Err(Error::Internal); // Already started
         }
 
@@ -318,30 +584,71 @@ impl Mint {
         tracing::info!("Payment processor startup completed");
 
         // Create shutdown signal
-        let shutdown_notify = Arc::new(Notify::new());
-
-        // Clone required components for the background task
-        let mint_clone = Arc::new(self.clone());
-        let payment_processors = self.payment_processors.clone();
-        let localstore = Arc::clone(&self.localstore);
-        let pubsub_manager = Arc::clone(&self.pubsub_manager);
-        let shutdown_clone = shutdown_notify.clone();
-
-        // Spawn the supervisor task
-        let supervisor_handle = tokio::spawn(async move {
-            Self::wait_for_paid_invoices(
-                mint_clone,
-                &payment_processors,
-                localstore,
-                pubsub_manager,
-                shutdown_clone,
-            )
-            .await
-        });
+        let shutdown = self.shutdown.child_token();
+
+        // Listen to the payment events of every backend, restarted if it fails
+        let mint = Arc::new(self.clone());
+        let task_shutdown = shutdown.clone();
+        self.tasks.spawn(
+            "payment_events",
+            Some(RestartPolicy::default()),
+            shutdown.clone(),
+            move || {
+                let mint = Arc::clone(&mint);
+                let shutdown = task_shutdown.clone();
+                async move {
+                    Self::wait_for_paid_invoices(
+                        Arc::clone(&mint),
+                        &mint.payment_processors,
+                        &mint.payment_event_restart_policies,
+                        Arc::clone(&mint.localstore),
+                        Arc::clone(&mint.pubsub_manager),
+                        shutdown,
+                    )
+                    .await
+                }
+            },
+        );
+
+        // Replace keysets before they expire
+        if let Some(policy) = self.keyset_rotation_policy {
+            let mint = Arc::new(self.clone());
+            let task_shutdown = shutdown.clone();
+            self.tasks.spawn(
+                "keyset_rotation",
+                Some(RestartPolicy::default()),
+                shutdown.clone(),
+                move || {
+                    let mint = Arc::clone(&mint);
+                    let shutdown = task_shutdown.clone();
+                    async move {
+                        Self::rotate_keysets_on_schedule(mint, policy, shutdown).await;
+                        Ok(())
+                    }
+                },
+            );
+        }
+
+        // Expire and purge the quotes left unpaid
+        if let Some(policy) = self.quote_expiry_policy {
+            let mint = Arc::new(self.clone());
+            let task_shutdown = shutdown.clone();
+            self.tasks.spawn(
+                "quote_expiry",
+                Some(RestartPolicy::default()),
+                shutdown.clone(),
+                move || {
+                    let mint = Arc::clone(&mint);
+                    let shutdown = task_shutdown.clone();
+                    async move {
+                        Self::expire_quotes_on_schedule(mint, policy, shutdown).await;
+                        Ok(())
+                    }
+                },
+            );
+        }
 
-        // Store the handles
-        task_state.shutdown_notify = Some(shutdown_notify);
-        task_state.supervisor_handle = Some(supervisor_handle);
+        task_state.shutdown = Some(shutdown);
 
         // Give the background task a tiny bit of time to start waiting
         tokio::time::sleep(std::time::Duration::from_millis(10)).await;
@@ -362,18 +669,11 @@ impl Mint {
     pub async fn stop(&self) -> Result<(), Error> {
         let mut task_state = self.task_state.lock().await;
 
-        // Take the handles out of the state
-        let shutdown_notify = task_state.shutdown_notify.take();
-        let supervisor_handle = task_state.supervisor_handle.take();
-
         // If nothing to stop, return early
-        let (shutdown_notify, supervisor_handle) = match (shutdown_notify, supervisor_handle) {
-            (Some(notify), Some(handle)) => (notify, handle),
-            _ => {
-                tracing::debug!("Stop called but no background services were running");
-                // Still try to stop payment processors
-                return self.stop_payment_processors().await;
-            }
+        let Some(shutdown) = task_state.shutdown.take() else {
+            tracing::debug!("Stop called but no background services were running");
+            // Still try to stop payment processors
+            return self.stop_payment_processors().await;
         };
 
         // Drop the lock before waiting
@@ -382,19 +682,13 @@ impl Mint {
         tracing::info!("Stopping mint background services...");
 
         // Signal shutdown
-        shutdown_notify.notify_waiters();
+        shutdown.cancel();
 
-        // Wait for supervisor to complete
-        let result = match supervisor_handle.await {
-            Ok(result) => {
-                tracing::info!("Mint background services stopped");
-                result
-            }
-            Err(join_error) => {
-                tracing::error!("Background service task panicked: {:?}", join_error);
-                Err(Error::Internal)
-            }
-        };
+        // Wait for the supervised tasks to complete
+        let result = self.tasks.join().await;
+        if result.is_ok() {
+            tracing::info!("Mint background services stopped");
+        }
 
         // Stop all payment processors
         self.stop_payment_processors().await?;
@@ -402,6 +696,18 @@ impl Mint {
         result
     }
 
+    /// Stop all background work of the mint for good
+    ///
+    /// Cancels the mint's shutdown token, which stops the payment event listeners, closes the
+    /// long lived pubsub subscribers and stops the embedded signatory built by [`MintBuilder`],
+    /// then waits for the background services as [`Mint::stop`] does. The mint can not be
+    /// started again afterwards.
+    pub async fn shutdown(&self) -> Result<(), Error> {
+        tracing::info!("Shutting down mint");
+        self.shutdown.cancel();
+        self.stop().await
+    }
+
     /// Stop all payment processors
     async fn stop_payment_processors(&self) -> Result<(), Error> {
         tracing::info!("Stopping payment processors...");
@@ -489,6 +795,18 @@ impl Mint {
         self.max_inputs
     }
 
+    /// Get the maximum number of outputs allowed per transaction
+    #[inline]
+    pub fn max_outputs(&self) -> usize {
+        self.max_outputs
+    }
+
+    /// Get the maximum length of the secret or witness of an input, in bytes
+    #[inline]
+    pub fn max_proof_content_len(&self) -> usize {
+        self.max_proof_content_len
+    }
+
     /// Pub Sub manager
     #[inline]
     pub fn pubsub_manager(&self) -> Arc<PubSubManager> {
@@ -543,9 +861,66 @@ impl Mint {
             mint_info
         };
 
+        let mut mint_info = mint_info;
+        mint_info.nuts.max_denominations = (*self.max_denominations).clone();
+        if let Some(network) = self.network {
+            mint_info.network = Some(network);
+        }
+
         Ok(mint_info)
     }
 
+    /// Set the amount limits of minting and melting with `method` in `unit`
+    ///
+    /// The limits are stored in the NUT-04 and NUT-05 settings of the mint info, which quotes
+    /// are checked against, so they apply from the next quote on. Methods the mint only melts
+    /// with, such as keysend, ignore the mint limits.
+    #[instrument(skip(self))]
+    pub async fn set_mint_melt_limits(
+        &self,
+        unit: &CurrencyUnit,
+        method: &PaymentMethod,
+        limits: MintMeltLimits,
+    ) -> Result<(), Error> {
+        if limits.mint_min > limits.mint_max || limits.melt_min > limits.melt_max {
+            return Err(Error::Custom(format!(
+                "Minimum amount above the maximum for {unit} {method}"
+            )));
+        }
+
+        let mut mint_info = self.mint_info().await?;
+
+        let mint_settings = mint_info
+            .nuts
+            .nut04
+            .methods
+            .iter_mut()
+            .find(|settings| &settings.unit == unit && &settings.method == method);
+        let found_mint = mint_settings.is_some();
+        if let Some(settings) = mint_settings {
+            settings.min_amount = Some(limits.mint_min);
+            settings.max_amount = Some(limits.mint_max);
+        }
+
+        let melt_settings = mint_info
+            .nuts
+            .nut05
+            .methods
+            .iter_mut()
+            .find(|settings| &settings.unit == unit && &settings.method == method);
+        let found_melt = melt_settings.is_some();
+        if let Some(settings) = melt_settings {
+            settings.min_amount = Some(limits.melt_min);
+            settings.max_amount = Some(limits.melt_max);
+        }
+
+        if !found_mint && !found_melt {
+            return Err(Error::UnsupportedPaymentMethod);
+        }
+
+        self.set_mint_info(mint_info).await
+    }
+
     /// Set mint info
     #[instrument(skip_all)]
     pub async fn set_mint_info(&self, mint_info: MintInfo) -> Result<(), Error> {
@@ -560,9 +935,18 @@ impl Mint {
         )
         .await?;
         tx.commit().await?;
+        let _ = self.changes.send(MintChange::Info);
         Ok(())
     }
 
+    /// Be notified when the keysets or the mint info change
+    ///
+    /// Lets frontends drop cached responses as soon as they are stale. A receiver that lags
+    /// behind should assume everything changed.
+    pub fn subscribe_changes(&self) -> broadcast::Receiver<MintChange> {
+        self.changes.subscribe()
+    }
+
     /// Get quote ttl
     #[instrument(skip_all)]
     pub async fn quote_ttl(&self) -> Result<QuoteTTL, Error> {
@@ -625,183 +1009,176 @@ impl Mint {
     async fn wait_for_paid_invoices(
         mint: Arc<Mint>,
         payment_processors: &HashMap<PaymentProcessorKey, DynMintPayment>,
+        restart_policies: &HashMap<PaymentProcessorKey, RestartPolicy>,
         localstore: DynMintDatabase,
         pubsub_manager: Arc<PubSubManager>,
-        shutdown: Arc<Notify>,
+        shutdown: CancellationToken,
     ) -> Result<(), Error> {
-        let mut join_set = JoinSet::new();
-
-        // Group processors by unique instance (using Arc pointer equality)
-        let mut seen_processors = Vec::new();
-        for (key, processor) in payment_processors {
-            // Skip if processor is already active
-            if processor.is_payment_event_stream_active() {
-                continue;
-            }
-
-            // Skip if we've already spawned a task for this processor instance
-            if seen_processors.iter().any(|p| Arc::ptr_eq(p, processor)) {
-                continue;
-            }
-
-            seen_processors.push(Arc::clone(processor));
-
-            tracing::info!("Starting payment wait task for {:?}", key);
-
-            // Clone for the spawned task
-            let mint = Arc::clone(&mint);
-            let processor = Arc::clone(processor);
-            let localstore = Arc::clone(&localstore);
-            let pubsub_manager = Arc::clone(&pubsub_manager);
-            let shutdown = Arc::clone(&shutdown);
-
-            join_set.spawn(async move {
-                let result = Self::wait_for_processor_payments(
-                    mint,
-                    processor,
-                    localstore,
-                    pubsub_manager,
-                    shutdown,
-                )
-                .await;
-
-                if let Err(e) = result {
-                    tracing::error!("Payment processor task failed: {:?}", e);
-                }
-            });
-        }
+        let mut events = PaymentEventMultiplexer::new(
+            payment_processors,
+            restart_policies,
+            Some(mint.payment_outage_alert_after),
+        );
 
         // If no payment processors, just wait for shutdown
-        if join_set.is_empty() {
-            shutdown.notified().await;
-        } else {
-            let shutdown_future = shutdown.notified();
-            tokio::pin!(shutdown_future);
-            // Wait for shutdown or all tasks to complete
-            loop {
-                tokio::select! {
-                    _ = &mut shutdown_future => {
-                        tracing::info!("Shutting down payment processors");
-                        break;
-                    }
-                    Some(result) = join_set.join_next() => {
-                        if let Err(e) = result {
-                            tracing::warn!("Task panicked: {:?}", e);
-                        }
-                    }
-                    else => break, // All tasks completed
-                }
-            }
+        if events.is_empty() {
+            shutdown.cancelled().await;
+            return Ok(());
         }
 
-        join_set.shutdown().await;
-        Ok(())
-    }
+        tracing::info!("Waiting for payment events of {} backends", events.len());
 
-    /// Handles payment waiting for a single processor
-    #[instrument(skip_all)]
-    async fn wait_for_processor_payments(
-        mint: Arc<Mint>,
-        processor: DynMintPayment,
-        localstore: DynMintDatabase,
-        pubsub_manager: Arc<PubSubManager>,
-        shutdown: Arc<Notify>,
-    ) -> Result<(), Error> {
-        let shutdown_future = shutdown.notified();
+        let shutdown_future = shutdown.cancelled();
         tokio::pin!(shutdown_future);
 
         loop {
             tokio::select! {
                 _ = &mut shutdown_future => {
-                    processor.cancel_payment_event_stream();
+                    tracing::info!("Shutting down payment processors");
+                    events.cancel();
                     break;
                 }
-                result = processor.wait_payment_event() => {
-                    match result {
-                        Ok(mut stream) => {
-                            loop {
-                                tokio::select! {
-                                    _ = &mut shutdown_future => {
-                                        processor.cancel_payment_event_stream();
-                                        return Ok(());
-                                    }
-                                    maybe_event = stream.next() => {
-                                        let Some(event) = maybe_event else {
-                                            break;
-                                        };
-
-                                        match event {
-                                            cdk_common::payment::Event::PaymentReceived(wait_payment_response) => {
-                                                if let Err(e) = Self::handle_payment_notification(
-                                                    &localstore,
-                                                    &pubsub_manager,
-                                                    wait_payment_response,
-                                                ).await {
-                                                    tracing::warn!("Payment notification error: {:?}", e);
-                                                }
-                                            }
-                                            cdk_common::payment::Event::PaymentSuccessful { quote_id, details } => {
-                                                tracing::info!(
-                                                    "Outgoing payment confirmed for quote {}: status {}",
-                                                    quote_id,
-                                                    details.status,
-                                                );
-
-                                                if let Err(e) = Self::handle_successful_melt_payment_event(
-                                                    &mint,
-                                                    &localstore,
-                                                    &pubsub_manager,
-                                                    &quote_id,
-                                                    details,
-                                                )
-                                                .await
-                                                {
-                                                    tracing::warn!(
-                                                        "Failed to process successful payment event for quote {}: {}",
-                                                        quote_id,
-                                                        e
-                                                    );
-                                                }
-                                            }
-                                            cdk_common::payment::Event::PaymentFailed { quote_id, reason } => {
-                                                tracing::warn!(
-                                                    "Outgoing payment failed for quote {}: {}",
-                                                    quote_id,
-                                                    reason,
-                                                );
-
-                                                if let Err(e) = Self::handle_failed_melt_payment_event(
-                                                    &mint,
-                                                    &localstore,
-                                                    &pubsub_manager,
-                                                    &quote_id,
-                                                )
-                                                .await
-                                                {
-                                                    tracing::warn!(
-                                                        "Failed to process failed payment event for quote {}: {}",
-                                                        quote_id,
-                                                        e
-                                                    );
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
-                            }
+                maybe_update = events.next() => {
+                    let Some(update) = maybe_update else {
+                        tracing::warn!("No payment backend left to wait for events from");
+                        break;
+                    };
+
+                    match update {
+                        BackendUpdate::Event(event) => {
+                            // Failures are logged by the handler, the next event is independent
+                            // of them
+                            let _ = Self::handle_backend_event(
+                                &mint,
+                                &localstore,
+                                &pubsub_manager,
+                                event,
+                            )
+                            .await;
                         }
+                        BackendUpdate::Outage(outage) => {
+                            tracing::error!(
+                                "Payment event stream of {:?} closed for {:?}, {} attempts to reopen it failed",
+                                outage.backend,
+                                outage.duration,
+                                outage.failures
+                            );
+                            mint.report_payment_outage(&outage);
+                        }
+                        BackendUpdate::Reconnected { outage, alerted } => {
+                            tracing::info!(
+                                "Payment event stream of {:?} reopened after {:?}",
+                                outage.backend,
+                                outage.duration
+                            );
+                            if alerted {
+                                mint.report_payment_outage(&outage);
+                            }
 
-                        Err(e) => {
-                            tracing::warn!("Failed to get payment stream: {}", e);
-                            tokio::time::sleep(Duration::from_secs(5)).await;
+                            // Look up the payments sent while the stream was closed without
+                            // holding back the events of the other backends
+                            let mint = Arc::clone(&mint);
+                            tokio::spawn(async move {
+                                if let Err(err) = mint.reconcile_mint_quotes_after_outage(&outage).await {
+                                    tracing::warn!(
+                                        "Could not check the mint quotes of {:?} after its outage: {}",
+                                        outage.backend,
+                                        err
+                                    );
+                                }
+                            });
                         }
                     }
                 }
             }
         }
+
         Ok(())
     }
 
+    /// Count `outage` in the metrics and pass it to the outage hook, if any
+    fn report_payment_outage(&self, outage: &PaymentEventOutage) {
+        #[cfg(feature = "prometheus")]
+        cdk_prometheus::METRICS
+            .record_mint_operation("payment_event_stream_outage", outage.resolved);
+
+        if let Some(hook) = &self.payment_outage_hook {
+            hook(outage);
+        }
+    }
+
+    /// Handles a payment event of any backend
+    #[instrument(skip_all, fields(backend = ?event.backend, lookup_id = %event.lookup_id))]
+    async fn handle_backend_event(
+        mint: &Arc<Mint>,
+        localstore: &DynMintDatabase,
+        pubsub_manager: &Arc<PubSubManager>,
+        event: BackendEvent,
+    ) -> Result<(), Error> {
+        #[cfg(feature = "prometheus")]
+        let metrics = MintMetricGuard::new(event.kind());
+
+        if let Some(amount) = &event.amount {
+            tracing::debug!("Payment event of {} from {:?}", amount, event.backend);
+        }
+
+        if matches!(event.event, cdk_common::payment::Event::PaymentReceived(_)) {
+            mint.backend_monitor
+                .record_invoice(&event.backend, &mint.backend_name(&event.backend));
+        }
+
+        let result = match event.event {
+            cdk_common::payment::Event::PaymentReceived(wait_payment_response) => {
+                Self::handle_payment_notification(localstore, pubsub_manager, wait_payment_response)
+                    .await
+                    .inspect_err(|e| tracing::warn!("Payment notification error: {:?}", e))
+            }
+            cdk_common::payment::Event::PaymentSuccessful { quote_id, details } => {
+                tracing::info!(
+                    "Outgoing payment confirmed for quote {}: status {}",
+                    quote_id,
+                    details.status,
+                );
+
+                Self::handle_successful_melt_payment_event(
+                    mint,
+                    localstore,
+                    pubsub_manager,
+                    &quote_id,
+                    details,
+                )
+                .await
+                .inspect_err(|e| {
+                    tracing::warn!(
+                        "Failed to process successful payment event for quote {}: {}",
+                        quote_id,
+                        e
+                    )
+                })
+            }
+            cdk_common::payment::Event::PaymentFailed { quote_id, reason } => {
+                tracing::warn!("Outgoing payment failed for quote {}: {}", quote_id, reason,);
+
+                Self::handle_failed_melt_payment_event(mint, localstore, pubsub_manager, &quote_id)
+                    .await
+                    .inspect_err(|e| {
+                        tracing::warn!(
+                            "Failed to process failed payment event for quote {}: {}",
+                            quote_id,
+                            e
+                        )
+                    })
+            }
+        };
+
+        #[cfg(feature = "prometheus")]
+        {
+            metrics.record(result.is_ok());
+        }
+
+        result
+    }
+
     #[instrument(skip_all)]
     pub(crate) async fn handle_successful_melt_payment_event(
         mint: &Arc<Mint>,
@@ -1126,6 +1503,17 @@ impl Mint {
             metrics.record(result.is_ok());
         }
 
+        if let Ok(signatures) = &result {
+            let mut usage: HashMap<Id, KeysetUsage> = HashMap::new();
+            for signature in signatures {
+                usage
+                    .entry(signature.keyset_id)
+                    .or_default()
+                    .signatures_issued += 1;
+            }
+            self.record_keyset_usage(usage).await;
+        }
+
         result
     }
 
@@ -1137,6 +1525,11 @@ impl Mint {
         #[cfg(feature = "prometheus")]
         let metrics = MintMetricGuard::new("verify_proofs");
 
+        let mut usage: HashMap<Id, KeysetUsage> = HashMap::new();
+        for proof in &proofs {
+            usage.entry(proof.keyset_id).or_default().proofs_verified += 1;
+        }
+
         let result = self.signatory.verify_proofs(proofs).await;
 
         #[cfg(feature = "prometheus")]
@@ -1144,9 +1537,34 @@ impl Mint {
             metrics.record(result.is_ok());
         }
 
+        if result.is_ok() {
+            self.record_keyset_usage(usage).await;
+        }
+
         result
     }
 
+    /// Adds `usage` to the persisted key usage counters
+    ///
+    /// The counters are statistics only, failing to persist them does not fail the operation
+    /// that used the keys.
+    async fn record_keyset_usage(&self, usage: HashMap<Id, KeysetUsage>) {
+        if usage.is_empty() {
+            return;
+        }
+
+        let result = async {
+            let mut tx = self.localstore.begin_transaction().await?;
+            tx.increment_keyset_usage(&usage).await?;
+            tx.commit().await
+        }
+        .await;
+
+        if let Err(err) = result {
+            tracing::warn!("Could not persist keyset usage counters: {}", err);
+        }
+    }
+
     /// Restore
     #[instrument(skip_all)]
     pub async fn restore(&self, request: RestoreRequest) -> Result<RestoreResponse, Error> {
@@ -1154,6 +1572,8 @@ impl Mint {
         let metrics = MintMetricGuard::new("restore");
 
         let result = async {
+            self.ensure_nut_enabled(9).await?;
+
             let output_len = request.outputs.len();
 
             // Check max outputs limit
@@ -1271,6 +1691,16 @@ impl Mint {
         result
     }
 
+    /// Get the key usage counters by keyset
+    #[instrument(skip_all)]
+    pub async fn keyset_usage(&self) -> Result<HashMap<Id, KeysetUsage>, Error> {
+        let mut usage = self.localstore.get_keyset_usage().await?;
+        for keyset in self.keysets().keysets {
+            usage.entry(keyset.id).or_default();
+        }
+        Ok(usage)
+    }
+
     /// Total redeemed for keyset
     #[instrument(skip_all)]
     pub async fn total_redeemed(&self) -> Result<HashMap<Id, Amount>, Error> {
@@ -1362,6 +1792,7 @@ mod tests {
                     input_fee_ppk: *fee,
                     keyset_id_type: cdk_common::nut02::KeySetVersion::Version00,
                     final_expiry: None,
+                    reason: None,
                 })
                 .await
                 .unwrap();
@@ -1441,6 +1872,70 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn mint_mod_disable_keyset() {
+        let mut supported_units = HashMap::new();
+        let amounts: Vec<u64> = (0..32).map(|i| 2u64.pow(i)).collect();
+        supported_units.insert(CurrencyUnit::default(), (0, amounts));
+
+        let config = MintConfig::<'_> {
+            supported_units,
+            ..Default::default()
+        };
+        let mint = create_mint(config).await;
+
+        let keyset_id = mint.keysets().keysets[0].id;
+
+        let disabled = mint.disable_keyset(keyset_id).await.expect("test");
+        assert!(!disabled.active);
+
+        // The keyset is still listed, so its proofs can be verified, but no keyset replaced it
+        let keysets = mint.keysets();
+        assert_eq!(1, keysets.keysets.len());
+        assert!(!keysets.keysets[0].active);
+        assert!(mint.pubkeys().keysets.is_empty());
+    }
+
+    #[tokio::test]
+    async fn mint_mod_update_keyset_config() {
+        let mut supported_units = HashMap::new();
+        let amounts: Vec<u64> = (0..32).map(|i| 2u64.pow(i)).collect();
+        supported_units.insert(CurrencyUnit::default(), (0, amounts.clone()));
+
+        let config = MintConfig::<'_> {
+            supported_units,
+            ..Default::default()
+        };
+        let mint = create_mint(config).await;
+
+        let keyset_id = mint.keysets().keysets[0].id;
+
+        // Same config, the keyset is kept
+        let unchanged = mint
+            .update_keyset_config(CurrencyUnit::default(), amounts, 0)
+            .await
+            .expect("unchanged config");
+        assert_eq!(unchanged.id, keyset_id);
+        assert_eq!(1, mint.keysets().keysets.len());
+
+        let updated = mint
+            .update_keyset_config(CurrencyUnit::default(), vec![], 100)
+            .await
+            .expect("new fee");
+        assert_ne!(updated.id, keyset_id);
+        assert_eq!(updated.input_fee_ppk, 100);
+
+        let keysets = mint.keysets();
+        assert_eq!(2, keysets.keysets.len());
+        let active = keysets
+            .keysets
+            .iter()
+            .find(|keyset| keyset.active)
+            .expect("active keyset");
+        assert_eq!(active.id, updated.id);
+        assert_eq!(active.input_fee_ppk, 100);
+    }
+
     #[tokio::test]
     async fn mint_mod_rotate_keyset_with_expiry() {
         let mut supported_units = HashMap::new();
@@ -1731,6 +2226,24 @@ mod tests {
         assert_eq!(expected_keys, serde_json::to_string(&keys.clone()).unwrap());
     }
 
+    #[tokio::test]
+    async fn test_keyset_usage_is_persisted() {
+        let mint = create_test_mint().await.unwrap();
+        let proofs = mint_test_proofs(&mint, Amount::from(64)).await.unwrap();
+        let keyset_id = proofs[0].keyset_id;
+
+        let after_mint = mint.keyset_usage().await.unwrap()[&keyset_id];
+        assert!(after_mint.signatures_issued >= proofs.len() as u64);
+
+        mint.verify_proofs(proofs.clone()).await.unwrap();
+        let after_verify = mint.keyset_usage().await.unwrap()[&keyset_id];
+        assert_eq!(
+            after_verify.proofs_verified,
+            after_mint.proofs_verified + proofs.len() as u64
+        );
+        assert_eq!(after_verify.signatures_issued, after_mint.signatures_issued);
+    }
+
     #[tokio::test]
     async fn test_start_stop_lifecycle() {
         let mut supported_units = HashMap::new();
@@ -1777,6 +2290,7 @@ mod tests {
             input_fee_ppk: 100,
             keyset_id_type: cdk_common::nut02::KeySetVersion::Version00,
             final_expiry: None,
+            reason: None,
         };
         let rotation_result = mint.signatory.rotate_keyset(rotate_argument).await;
 
@@ -1867,4 +2381,129 @@ mod tests {
             result
         );
     }
+
+    #[tokio::test]
+    async fn shutdown_stops_background_work() {
+        let mint = create_test_mint().await.unwrap();
+        let pubsub = mint.pubsub_manager();
+        let token = mint.shutdown_token();
+
+        mint.shutdown().await.unwrap();
+
+        assert!(token.is_cancelled());
+        tokio::time::timeout(std::time::Duration::from_secs(1), pubsub.cancelled())
+            .await
+            .expect("pubsub subscribers are told to close");
+
+        // The embedded signatory stopped with the mint
+        assert!(mint.signatory.keysets().await.is_err());
+
+        assert!(mint.start().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn info_and_keyset_changes_are_announced() {
+        let mint = create_test_mint().await.unwrap();
+        let mut changes = mint.subscribe_changes();
+
+        let info = mint.mint_info().await.unwrap();
+        mint.set_mint_info(info).await.unwrap();
+        assert_eq!(changes.recv().await.unwrap(), MintChange::Info);
+
+        mint.rotate_keyset(CurrencyUnit::Sat, vec![1, 2, 4], 0, true, None)
+            .await
+            .unwrap();
+        assert_eq!(changes.recv().await.unwrap(), MintChange::Keysets);
+    }
+
+    #[tokio::test]
+    async fn mint_melt_limits_are_updated_at_runtime() {
+        let mint = create_test_mint().await.unwrap();
+        let bolt11 = PaymentMethod::Known(KnownMethod::Bolt11);
+        let limits = MintMeltLimits {
+            mint_min: 10.into(),
+            mint_max: 5_000.into(),
+            melt_min: 1.into(),
+            melt_max: 500.into(),
+        };
+
+        mint.set_mint_melt_limits(&CurrencyUnit::Sat, &bolt11, limits)
+            .await
+            .unwrap();
+
+        let info = mint.mint_info().await.unwrap();
+        let nut04 = info
+            .nuts
+            .nut04
+            .get_settings(&CurrencyUnit::Sat, &bolt11)
+            .unwrap();
+        assert_eq!(nut04.min_amount, Some(10.into()));
+        assert_eq!(nut04.max_amount, Some(5_000.into()));
+        let nut05 = info
+            .nuts
+            .nut05
+            .get_settings(&CurrencyUnit::Sat, &bolt11)
+            .unwrap();
+        assert_eq!(nut05.max_amount, Some(500.into()));
+
+        let invoice = create_fake_invoice(
+            1_000_000,
+            serde_json::to_string(&FakeInvoiceDescription::default()).unwrap(),
+        );
+        let err = mint
+            .get_melt_quote(MeltQuoteRequest::Bolt11(MeltQuoteBolt11Request {
+                request: invoice,
+                unit: CurrencyUnit::Sat,
+                options: None,
+            }))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::AmountOutofLimitRange(_, _, _)));
+
+        let inverted = MintMeltLimits::new(100, 10);
+        assert!(mint
+            .set_mint_melt_limits(&CurrencyUnit::Sat, &bolt11, inverted)
+            .await
+            .is_err());
+        assert!(matches!(
+            mint.set_mint_melt_limits(&CurrencyUnit::Usd, &bolt11, limits)
+                .await,
+            Err(Error::UnsupportedPaymentMethod)
+        ));
+    }
+
+    #[tokio::test]
+    async fn invoices_for_another_network_are_refused() {
+        let mint = create_test_mint()
+            .await
+            .unwrap()
+            .with_network(bitcoin::Network::Regtest);
+
+        assert_eq!(
+            mint.mint_info().await.unwrap().network,
+            Some(bitcoin::Network::Regtest)
+        );
+
+        // The fake wallet issues mainnet invoices
+        let invoice = create_fake_invoice(
+            1_000,
+            serde_json::to_string(&FakeInvoiceDescription::default()).unwrap(),
+        );
+        let err = mint
+            .get_melt_quote(MeltQuoteRequest::Bolt11(MeltQuoteBolt11Request {
+                request: invoice,
+                unit: CurrencyUnit::Sat,
+                options: None,
+            }))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            Error::NetworkMismatch {
+                expected: bitcoin::Network::Regtest,
+                found: bitcoin::Network::Bitcoin,
+            }
+        ));
+    }
 }
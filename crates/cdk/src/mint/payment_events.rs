@@ -0,0 +1,475 @@
+//! Multiplexed payment event streams
+//!
+//! Every payment backend exposes its own event stream. The streams of all backends are merged
+//! into a single [`SelectAll`], so the mint handles the events of every backend in one loop.
+//! Each backend stream reopens itself when it ends or fails to open, following the
+//! [`RestartPolicy`] of that backend.
+//!
+//! While a stream is closed the backend may report payments the mint never hears about. The
+//! multiplexer reports such outages: once when one lasts longer than the alert threshold of the
+//! mint, and once the stream reopens, so the payments it missed can be looked up.
+
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use cdk_common::common::PaymentProcessorKey;
+use cdk_common::payment::{DynMintPayment, Event};
+use cdk_common::util::unix_time;
+use cdk_common::CurrencyUnit;
+use futures::stream::{self, BoxStream, SelectAll};
+use futures::{Stream, StreamExt};
+
+use crate::Amount;
+
+/// How the event stream of a payment backend is reopened
+///
+/// A stream that ends is reopened right away. A stream that fails to open is retried after a
+/// delay that starts at `initial_delay` and doubles with every consecutive failure, up to
+/// `max_delay`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RestartPolicy {
+    /// Delay before the first retry
+    pub initial_delay: Duration,
+    /// Upper bound of the delay between retries
+    pub max_delay: Duration,
+    /// Stop listening to the backend after this many consecutive failures, never by default
+    pub max_failures: Option<u32>,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_secs(5),
+            max_delay: Duration::from_secs(60),
+            max_failures: None,
+        }
+    }
+}
+
+impl RestartPolicy {
+    /// Delay before retrying after `failures` consecutive failures
+    pub fn delay(&self, failures: u32) -> Duration {
+        let factor = 2_u32.saturating_pow(failures.saturating_sub(1));
+        self.initial_delay
+            .saturating_mul(factor)
+            .min(self.max_delay)
+    }
+
+    pub(crate) fn gives_up(&self, failures: u32) -> bool {
+        self.max_failures.is_some_and(|max| failures >= max)
+    }
+}
+
+/// Default time the event stream of a payment backend may stay closed before it is reported
+pub const DEFAULT_PAYMENT_OUTAGE_ALERT: Duration = Duration::from_secs(300);
+
+/// Time the event stream of a payment backend was closed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaymentEventOutage {
+    /// Backend whose event stream was closed
+    pub backend: PaymentProcessorKey,
+    /// Unix time the stream closed
+    pub since: u64,
+    /// How long the stream has been closed, or was once `resolved`
+    pub duration: Duration,
+    /// Consecutive failures to reopen the stream
+    pub failures: u32,
+    /// Whether the stream reopened
+    pub resolved: bool,
+}
+
+/// Called with the outages of the payment event streams, see
+/// [`Mint::with_payment_outage_alert`](super::Mint::with_payment_outage_alert)
+pub type PaymentOutageHook = Arc<dyn Fn(&PaymentEventOutage) + Send + Sync>;
+
+/// What the multiplexer reports about the backends
+#[derive(Debug, Clone)]
+pub(crate) enum BackendUpdate {
+    /// A backend emitted a payment event
+    Event(BackendEvent),
+    /// The event stream of a backend has been closed longer than the alert threshold
+    Outage(PaymentEventOutage),
+    /// The event stream of a backend reopened, the events sent while it was closed are lost
+    Reconnected {
+        /// The outage that ended
+        outage: PaymentEventOutage,
+        /// Whether the outage had been reported as [`BackendUpdate::Outage`]
+        alerted: bool,
+    },
+}
+
+/// Payment event tagged with the backend that emitted it
+#[derive(Debug, Clone)]
+pub(crate) struct BackendEvent {
+    /// Backend the event comes from
+    pub backend: PaymentProcessorKey,
+    /// Lookup id of the payment, or the quote id when the backend only reports the quote
+    pub lookup_id: String,
+    /// Amount received or spent, if the event carries one
+    pub amount: Option<Amount<CurrencyUnit>>,
+    /// The event itself
+    pub event: Event,
+}
+
+impl BackendEvent {
+    fn new(backend: PaymentProcessorKey, event: Event) -> Self {
+        let (lookup_id, amount) = match &event {
+            Event::PaymentReceived(response) => (
+                response.payment_identifier.to_string(),
+                Some(response.payment_amount.clone()),
+            ),
+            Event::PaymentSuccessful { details, .. } => (
+                details.payment_lookup_id.to_string(),
+                Some(details.total_spent.clone()),
+            ),
+            Event::PaymentFailed { quote_id, .. } => (quote_id.to_string(), None),
+        };
+
+        Self {
+            backend,
+            lookup_id,
+            amount,
+            event,
+        }
+    }
+
+    /// Name of the event, used as the metrics label
+    pub fn kind(&self) -> &'static str {
+        match self.event {
+            Event::PaymentReceived(_) => "payment_event_received",
+            Event::PaymentSuccessful { .. } => "payment_event_successful",
+            Event::PaymentFailed { .. } => "payment_event_failed",
+        }
+    }
+}
+
+/// State of the event stream of a single backend
+struct BackendStream {
+    backend: PaymentProcessorKey,
+    processor: DynMintPayment,
+    policy: RestartPolicy,
+    stream: Option<Pin<Box<dyn Stream<Item = Event> + Send>>>,
+    failures: u32,
+    /// Unix time and instant the stream closed, none while it is open and before it first opens
+    outage: Option<(u64, Instant)>,
+    /// Whether the current outage was reported
+    alerted: bool,
+    /// Outages lasting longer are reported, never when none
+    alert_after: Option<Duration>,
+}
+
+impl BackendStream {
+    fn begin_outage(&mut self) {
+        if self.outage.is_none() {
+            self.outage = Some((unix_time(), Instant::now()));
+            self.alerted = false;
+        }
+    }
+
+    fn outage_report(&self, since: u64, started: Instant, resolved: bool) -> PaymentEventOutage {
+        PaymentEventOutage {
+            backend: self.backend.clone(),
+            since,
+            duration: started.elapsed(),
+            failures: self.failures,
+            resolved,
+        }
+    }
+
+    /// The current outage, once it lasts longer than the alert threshold and was not reported
+    fn due_alert(&mut self) -> Option<PaymentEventOutage> {
+        let (since, started) = self.outage?;
+        let alert_after = self.alert_after?;
+
+        if self.alerted || started.elapsed() < alert_after {
+            return None;
+        }
+
+        self.alerted = true;
+        Some(self.outage_report(since, started, false))
+    }
+
+    /// Events of the backend, reopening the underlying stream as the policy allows
+    ///
+    /// Outages are only checked against the alert threshold between reopening attempts, so an
+    /// alert may come up to the largest retry delay late.
+    fn into_stream(self) -> BoxStream<'static, BackendUpdate> {
+        stream::unfold(self, |mut state| async move {
+            loop {
+                if let Some(stream) = state.stream.as_mut() {
+                    match stream.next().await {
+                        Some(event) => {
+                            let event = BackendEvent::new(state.backend.clone(), event);
+                            return Some((BackendUpdate::Event(event), state));
+                        }
+                        None => {
+                            tracing::info!(
+                                "Payment event stream of {:?} ended, reopening",
+                                state.backend
+                            );
+                            state.stream = None;
+                            state.begin_outage();
+                        }
+                    }
+                }
+
+                if let Some(outage) = state.due_alert() {
+                    return Some((BackendUpdate::Outage(outage), state));
+                }
+
+                let result = state.processor.wait_payment_event().await;
+
+                #[cfg(feature = "prometheus")]
+                cdk_prometheus::METRICS
+                    .record_mint_operation("payment_event_stream_open", result.is_ok());
+
+                match result {
+                    Ok(stream) => {
+                        state.stream = Some(stream);
+
+                        let reconnected = state.outage.take().map(|(since, started)| {
+                            BackendUpdate::Reconnected {
+                                outage: state.outage_report(since, started, true),
+                                alerted: state.alerted,
+                            }
+                        });
+                        state.failures = 0;
+
+                        if let Some(reconnected) = reconnected {
+                            return Some((reconnected, state));
+                        }
+                    }
+                    Err(err) => {
+                        state.failures = state.failures.saturating_add(1);
+                        state.begin_outage();
+
+                        if state.policy.gives_up(state.failures) {
+                            tracing::error!(
+                                "Giving up on payment event stream of {:?} after {} failures: {}",
+                                state.backend,
+                                state.failures,
+                                err
+                            );
+                            return None;
+                        }
+
+                        let delay = state.policy.delay(state.failures);
+                        tracing::warn!(
+                            "Failed to get payment stream of {:?}, retrying in {:?}: {}",
+                            state.backend,
+                            delay,
+                            err
+                        );
+                        tokio::time::sleep(delay).await;
+                    }
+                }
+            }
+        })
+        .boxed()
+    }
+}
+
+/// Event streams of all payment backends merged into one
+pub(crate) struct PaymentEventMultiplexer {
+    streams: SelectAll<BoxStream<'static, BackendUpdate>>,
+    processors: Vec<DynMintPayment>,
+}
+
+impl PaymentEventMultiplexer {
+    /// Listen to every backend that is not streaming its events already
+    ///
+    /// A backend registered under several keys is listened to once, with the policy of the
+    /// first of its keys that has one in `policies`. Outages longer than `alert_after` are
+    /// reported.
+    pub fn new(
+        payment_processors: &HashMap<PaymentProcessorKey, DynMintPayment>,
+        policies: &HashMap<PaymentProcessorKey, RestartPolicy>,
+        alert_after: Option<Duration>,
+    ) -> Self {
+        let mut backends: Vec<(PaymentProcessorKey, DynMintPayment, Option<RestartPolicy>)> =
+            Vec::new();
+
+        for (key, processor) in payment_processors {
+            if processor.is_payment_event_stream_active() {
+                continue;
+            }
+
+            let policy = policies.get(key).copied();
+
+            match backends
+                .iter_mut()
+                .find(|(_, seen, _)| Arc::ptr_eq(seen, processor))
+            {
+                Some((_, _, seen_policy)) => {
+                    if seen_policy.is_none() {
+                        *seen_policy = policy;
+                    }
+                }
+                None => backends.push((key.clone(), Arc::clone(processor), policy)),
+            }
+        }
+
+        let mut streams = SelectAll::new();
+        let mut processors = Vec::with_capacity(backends.len());
+
+        for (backend, processor, policy) in backends {
+            tracing::info!("Listening to payment events of {:?}", backend);
+
+            processors.push(Arc::clone(&processor));
+            streams.push(
+                BackendStream {
+                    backend,
+                    processor,
+                    policy: policy.unwrap_or_default(),
+                    stream: None,
+                    failures: 0,
+                    outage: None,
+                    alerted: false,
+                    alert_after,
+                }
+                .into_stream(),
+            );
+        }
+
+        Self {
+            streams,
+            processors,
+        }
+    }
+
+    /// Number of backends listened to
+    pub fn len(&self) -> usize {
+        self.processors.len()
+    }
+
+    /// Whether no backend is listened to
+    pub fn is_empty(&self) -> bool {
+        self.processors.is_empty()
+    }
+
+    /// Next update of any backend, `None` once every backend has been given up on
+    ///
+    /// Cancel safe, an update is never lost when the returned future is dropped.
+    pub async fn next(&mut self) -> Option<BackendUpdate> {
+        self.streams.next().await
+    }
+
+    /// Ask every backend to close its event stream
+    pub fn cancel(&self) {
+        for processor in &self.processors {
+            processor.cancel_payment_event_stream();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use cdk_common::nut00::KnownMethod;
+    use cdk_common::nuts::PaymentMethod;
+    use cdk_fake_wallet::FakeWallet;
+
+    use super::*;
+    use crate::types::FeeReserve;
+
+    #[test]
+    fn restart_delay_doubles_up_to_max() {
+        let policy = RestartPolicy {
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(10),
+            max_failures: Some(6),
+        };
+
+        let delays: Vec<_> = (1..=6).map(|failures| policy.delay(failures)).collect();
+        assert_eq!(
+            delays,
+            [1, 2, 4, 8, 10, 10].map(Duration::from_secs).to_vec()
+        );
+
+        assert!(!policy.gives_up(5));
+        assert!(policy.gives_up(6));
+        assert!(!RestartPolicy::default().gives_up(u32::MAX));
+    }
+
+    #[tokio::test]
+    async fn closed_stream_is_reported_until_it_reopens() {
+        let backend =
+            PaymentProcessorKey::new(CurrencyUnit::Sat, PaymentMethod::Known(KnownMethod::Bolt11));
+        let processor: DynMintPayment = Arc::new(FakeWallet::new(
+            FeeReserve {
+                min_fee_reserve: 1.into(),
+                percent_fee_reserve: 1.0,
+            },
+            HashMap::default(),
+            HashSet::default(),
+            0,
+            CurrencyUnit::Sat,
+        ));
+
+        // A stream that ends right away, the fake wallet opens a new one
+        let mut updates = BackendStream {
+            backend: backend.clone(),
+            processor,
+            policy: RestartPolicy::default(),
+            stream: Some(Box::pin(stream::empty())),
+            failures: 0,
+            outage: None,
+            alerted: false,
+            alert_after: Some(Duration::ZERO),
+        }
+        .into_stream();
+
+        let Some(BackendUpdate::Outage(outage)) = updates.next().await else {
+            panic!("the outage should be reported");
+        };
+        assert_eq!(outage.backend, backend);
+        assert!(!outage.resolved);
+
+        let Some(BackendUpdate::Reconnected { outage, alerted }) = updates.next().await else {
+            panic!("the stream should reopen");
+        };
+        assert!(alerted);
+        assert!(outage.resolved);
+        assert!(outage.since <= unix_time());
+    }
+
+    #[tokio::test]
+    async fn backend_under_several_keys_is_listened_to_once() {
+        let backend: DynMintPayment = Arc::new(FakeWallet::new(
+            FeeReserve {
+                min_fee_reserve: 1.into(),
+                percent_fee_reserve: 1.0,
+            },
+            HashMap::default(),
+            HashSet::default(),
+            0,
+            CurrencyUnit::Sat,
+        ));
+
+        let processors = HashMap::from([
+            (
+                PaymentProcessorKey::new(
+                    CurrencyUnit::Sat,
+                    PaymentMethod::Known(KnownMethod::Bolt11),
+                ),
+                Arc::clone(&backend),
+            ),
+            (
+                PaymentProcessorKey::new(
+                    CurrencyUnit::Sat,
+                    PaymentMethod::Known(KnownMethod::Bolt12),
+                ),
+                backend,
+            ),
+        ]);
+
+        let events = PaymentEventMultiplexer::new(&processors, &HashMap::new(), None);
+        assert_eq!(events.len(), 1);
+
+        let events = PaymentEventMultiplexer::new(&HashMap::new(), &HashMap::new(), None);
+        assert!(events.is_empty());
+    }
+}
@@ -0,0 +1,990 @@
+//! Routing of payments across several backends of one unit
+//!
+//! The mint maps each unit and payment method to a single backend. A [`PaymentRouter`] is such a
+//! backend made of several others: every melt is routed to the backend the [`RoutingPolicy`]
+//! ranks first, and a payment that fails is retried on the next one. A payment that errored is
+//! only retried once the backend confirms it did not go out, so an invoice is never paid twice.
+//!
+//! The backend a payment went out through is returned by
+//! [`MintPayment::make_payment_with_backend`] and recorded on the melt quote, so the router
+//! keeps no state. Payments are checked on that backend first. When it cannot tell, or its name
+//! is unknown, every backend is asked and a payment any of them reports paid or pending is never
+//! taken for failed.
+//!
+//! Incoming payments are requested from the first backend that answers and events of all
+//! backends are merged, so mint quotes work as with a single backend. Hold invoices are created
+//! by the first backend supporting them that answers, and settled and cancelled by the backend
+//! recorded on the mint quote, see [`MintPayment::create_hold_invoice_with_backend`].
+
+use std::pin::Pin;
+
+use async_trait::async_trait;
+use cdk_common::nuts::{CurrencyUnit, MeltQuoteState};
+use cdk_common::payment::{
+    self, Bolt11IncomingPaymentOptions, CreateIncomingPaymentResponse, DynMintPayment, Event,
+    IncomingPaymentOptions, MakePaymentResponse, MintPayment, OutgoingPaymentOptions,
+    PaymentIdentifier, PaymentQuoteResponse, SettingsResponse, WaitPaymentResponse,
+};
+use cdk_common::Amount;
+use futures::future::join_all;
+use futures::Stream;
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+/// How a [`PaymentRouter`] orders its backends for a payment
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RoutingPolicy {
+    /// In the order the backends were given
+    #[default]
+    Priority,
+    /// Lowest fee quoted first, backends that cannot quote the payment are skipped
+    LowestFee,
+    /// Most outbound liquidity first, backends that cannot tell last
+    Liquidity,
+}
+
+/// Backend routing payments of one unit across several backends, see the [module docs](self)
+pub struct PaymentRouter {
+    backends: Vec<DynMintPayment>,
+    policy: RoutingPolicy,
+}
+
+impl std::fmt::Debug for PaymentRouter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PaymentRouter")
+            .field("backends", &self.backend_name())
+            .field("policy", &self.policy)
+            .finish()
+    }
+}
+
+impl PaymentRouter {
+    /// Route payments across `backends`, the first one being the primary
+    ///
+    /// The settings of the router are those of the primary backend.
+    pub fn new(
+        backends: Vec<DynMintPayment>,
+        policy: RoutingPolicy,
+    ) -> Result<Self, payment::Error> {
+        if backends.is_empty() {
+            return Err(payment::Error::Custom(
+                "A payment router needs at least one backend".to_string(),
+            ));
+        }
+
+        Ok(Self { backends, policy })
+    }
+
+    /// Policy the backends are ordered with
+    pub fn policy(&self) -> RoutingPolicy {
+        self.policy
+    }
+
+    /// Backends that may hold the hold invoice created by `backend`
+    ///
+    /// Only that backend if its name is known, otherwise every backend supporting hold invoices.
+    fn hold_invoice_backends(&self, backend: Option<&str>) -> Vec<&DynMintPayment> {
+        if let Some(owner) = backend.and_then(|backend| {
+            self.backends
+                .iter()
+                .find(|candidate| candidate.backend_name() == backend)
+        }) {
+            return vec![owner];
+        }
+
+        self.backends
+            .iter()
+            .filter(|backend| backend.supports_hold_invoices())
+            .collect()
+    }
+
+    /// Indexes of the backends to try for a payment, best first
+    async fn rank(&self, unit: &CurrencyUnit, options: &OutgoingPaymentOptions) -> Vec<usize> {
+        match self.policy {
+            RoutingPolicy::Priority => (0..self.backends.len()).collect(),
+            RoutingPolicy::LowestFee => self
+                .quote_all(unit, options)
+                .await
+                .0
+                .into_iter()
+                .map(|(index, _)| index)
+                .collect(),
+            RoutingPolicy::Liquidity => {
+                let liquidity = join_all(
+                    self.backends
+                        .iter()
+                        .map(|backend| backend.outbound_liquidity(unit)),
+                )
+                .await;
+
+                let mut ranked: Vec<(usize, Option<u64>)> = liquidity
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, liquidity)| {
+                        let liquidity = liquidity.ok().flatten().map(|amount| amount.value());
+                        (index, liquidity)
+                    })
+                    .collect();
+                // Stable, so backends that cannot tell keep their priority after the others
+                ranked.sort_by(|(_, a), (_, b)| b.cmp(a));
+                ranked.into_iter().map(|(index, _)| index).collect()
+            }
+        }
+    }
+
+    /// Quotes of the backends that can make the payment, lowest fee first, and the last error
+    async fn quote_all(
+        &self,
+        unit: &CurrencyUnit,
+        options: &OutgoingPaymentOptions,
+    ) -> (Vec<(usize, PaymentQuoteResponse)>, Option<payment::Error>) {
+        let quotes = join_all(
+            self.backends
+                .iter()
+                .map(|backend| backend.get_payment_quote(unit, options.clone())),
+        )
+        .await;
+
+        let mut ranked = Vec::with_capacity(quotes.len());
+        let mut last_err = None;
+        for (index, quote) in quotes.into_iter().enumerate() {
+            match quote {
+                Ok(quote) => ranked.push((index, quote)),
+                Err(err) => {
+                    tracing::debug!(
+                        "{} cannot quote the payment: {}",
+                        self.backends[index].backend_name(),
+                        err
+                    );
+                    last_err = Some(err);
+                }
+            }
+        }
+        ranked.sort_by_key(|(_, quote)| quote.fee.value());
+        (ranked, last_err)
+    }
+
+    /// State of a payment that errored on `backend`, none when it cannot be told
+    ///
+    /// Only bolt11 payments can be looked up without an identifier from the backend, other
+    /// payments are never retried after an error.
+    async fn check_errored_payment(
+        &self,
+        backend: &DynMintPayment,
+        options: &OutgoingPaymentOptions,
+    ) -> Option<MakePaymentResponse> {
+        let OutgoingPaymentOptions::Bolt11(bolt11) = options else {
+            return None;
+        };
+
+        let lookup_id = PaymentIdentifier::PaymentHash(*bolt11.bolt11.payment_hash().as_ref());
+        backend.check_outgoing_payment(&lookup_id).await.ok()
+    }
+}
+
+/// How much an answer about an outgoing payment can be relied on, answers of several backends
+/// are merged by keeping the highest
+///
+/// A payment reported paid or pending by any backend went out, so it must never be taken for
+/// failed because another backend does not know it.
+fn check_precedence(check: &Result<MakePaymentResponse, payment::Error>) -> u8 {
+    match check {
+        Ok(response) => match response.status {
+            MeltQuoteState::Paid => 4,
+            MeltQuoteState::Pending => 3,
+            MeltQuoteState::Failed | MeltQuoteState::Unpaid => 2,
+            MeltQuoteState::Unknown => 1,
+        },
+        Err(_) => 0,
+    }
+}
+
+#[async_trait]
+impl MintPayment for PaymentRouter {
+    type Err = payment::Error;
+
+    fn backend_name(&self) -> String {
+        self.backends
+            .iter()
+            .map(|backend| backend.backend_name())
+            .collect::<Vec<_>>()
+            .join("+")
+    }
+
+    async fn start(&self) -> Result<(), Self::Err> {
+        for backend in &self.backends {
+            backend.start().await?;
+        }
+        Ok(())
+    }
+
+    async fn stop(&self) -> Result<(), Self::Err> {
+        let mut result = Ok(());
+        for backend in &self.backends {
+            if let Err(err) = backend.stop().await {
+                tracing::error!("Could not stop {}: {}", backend.backend_name(), err);
+                result = Err(err);
+            }
+        }
+        result
+    }
+
+    async fn get_settings(&self) -> Result<SettingsResponse, Self::Err> {
+        self.backends[0].get_settings().await
+    }
+
+    async fn create_incoming_payment_request(
+        &self,
+        options: IncomingPaymentOptions,
+    ) -> Result<CreateIncomingPaymentResponse, Self::Err> {
+        let mut last_err = None;
+        for backend in &self.backends {
+            match backend
+                .create_incoming_payment_request(options.clone())
+                .await
+            {
+                Ok(response) => return Ok(response),
+                Err(err) => {
+                    tracing::warn!(
+                        "{} could not create a payment request: {}",
+                        backend.backend_name(),
+                        err
+                    );
+                    last_err = Some(err);
+                }
+            }
+        }
+        Err(last_err.unwrap_or_else(|| {
+            payment::Error::Custom("No backend can create the payment request".to_string())
+        }))
+    }
+
+    #[instrument(skip_all)]
+    async fn get_payment_quote(
+        &self,
+        unit: &CurrencyUnit,
+        options: OutgoingPaymentOptions,
+    ) -> Result<PaymentQuoteResponse, Self::Err> {
+        if self.policy == RoutingPolicy::LowestFee {
+            let (quotes, last_err) = self.quote_all(unit, &options).await;
+            return match (quotes.into_iter().next(), last_err) {
+                (Some((_, quote)), _) => Ok(quote),
+                (None, Some(err)) => Err(err),
+                (None, None) => Err(payment::Error::Custom(
+                    "No backend can quote the payment".to_string(),
+                )),
+            };
+        }
+
+        let mut last_err = None;
+        for index in self.rank(unit, &options).await {
+            match self.backends[index]
+                .get_payment_quote(unit, options.clone())
+                .await
+            {
+                Ok(quote) => return Ok(quote),
+                Err(err) => last_err = Some(err),
+            }
+        }
+        Err(last_err.unwrap_or_else(|| {
+            payment::Error::Custom("No backend can quote the payment".to_string())
+        }))
+    }
+
+    async fn make_payment(
+        &self,
+        unit: &CurrencyUnit,
+        options: OutgoingPaymentOptions,
+    ) -> Result<MakePaymentResponse, Self::Err> {
+        self.make_payment_with_backend(unit, options)
+            .await
+            .map(|(response, _)| response)
+    }
+
+    #[instrument(skip_all)]
+    async fn make_payment_with_backend(
+        &self,
+        unit: &CurrencyUnit,
+        options: OutgoingPaymentOptions,
+    ) -> Result<(MakePaymentResponse, String), Self::Err> {
+        let mut last = None;
+        for index in self.rank(unit, &options).await {
+            let backend = &self.backends[index];
+            let err = match backend.make_payment(unit, options.clone()).await {
+                Ok(response) => match response.status {
+                    MeltQuoteState::Paid | MeltQuoteState::Pending | MeltQuoteState::Unknown => {
+                        return Ok((response, backend.backend_name()));
+                    }
+                    MeltQuoteState::Unpaid | MeltQuoteState::Failed => {
+                        tracing::warn!(
+                            "Payment failed on {}, trying the next backend",
+                            backend.backend_name()
+                        );
+                        last = Some(Ok((response, backend.backend_name())));
+                        continue;
+                    }
+                },
+                Err(err) => err,
+            };
+
+            match self.check_errored_payment(backend, &options).await {
+                Some(check)
+                    if matches!(check.status, MeltQuoteState::Paid | MeltQuoteState::Pending) =>
+                {
+                    return Ok((check, backend.backend_name()));
+                }
+                Some(check)
+                    if matches!(
+                        check.status,
+                        MeltQuoteState::Unpaid | MeltQuoteState::Failed
+                    ) =>
+                {
+                    tracing::warn!(
+                        "Payment errored on {}, trying the next backend: {}",
+                        backend.backend_name(),
+                        err
+                    );
+                    last = Some(Err(err));
+                }
+                // The backend may still be paying, another one must not pay the invoice too
+                _ => return Err(err),
+            }
+        }
+
+        last.unwrap_or_else(|| {
+            Err(payment::Error::Custom(
+                "No backend can make the payment".to_string(),
+            ))
+        })
+    }
+
+    async fn wait_payment_event(
+        &self,
+    ) -> Result<Pin<Box<dyn Stream<Item = Event> + Send>>, Self::Err> {
+        let mut streams = Vec::with_capacity(self.backends.len());
+        for backend in &self.backends {
+            streams.push(backend.wait_payment_event().await?);
+        }
+        Ok(Box::pin(futures::stream::select_all(streams)))
+    }
+
+    fn is_payment_event_stream_active(&self) -> bool {
+        self.backends
+            .iter()
+            .any(|backend| backend.is_payment_event_stream_active())
+    }
+
+    fn cancel_payment_event_stream(&self) {
+        for backend in &self.backends {
+            backend.cancel_payment_event_stream();
+        }
+    }
+
+    async fn check_incoming_payment_status(
+        &self,
+        payment_identifier: &PaymentIdentifier,
+    ) -> Result<Vec<WaitPaymentResponse>, Self::Err> {
+        let mut last_err = None;
+        for backend in &self.backends {
+            match backend
+                .check_incoming_payment_status(payment_identifier)
+                .await
+            {
+                Ok(payments) if !payments.is_empty() => return Ok(payments),
+                Ok(_) => {}
+                Err(err) => last_err = Some(err),
+            }
+        }
+        last_err.map_or_else(|| Ok(Vec::new()), Err)
+    }
+
+    async fn check_outgoing_payment(
+        &self,
+        payment_identifier: &PaymentIdentifier,
+    ) -> Result<MakePaymentResponse, Self::Err> {
+        self.check_outgoing_payment_with_backend(None, payment_identifier)
+            .await
+    }
+
+    async fn check_outgoing_payment_with_backend(
+        &self,
+        backend: Option<&str>,
+        payment_identifier: &PaymentIdentifier,
+    ) -> Result<MakePaymentResponse, Self::Err> {
+        let owner = backend.and_then(|backend| {
+            self.backends
+                .iter()
+                .position(|candidate| candidate.backend_name() == backend)
+        });
+
+        let mut best = None;
+        if let Some(owner) = owner {
+            let check = self.backends[owner]
+                .check_outgoing_payment(payment_identifier)
+                .await;
+            if matches!(
+                &check,
+                Ok(response) if matches!(response.status, MeltQuoteState::Paid | MeltQuoteState::Pending)
+            ) {
+                return check;
+            }
+            best = Some(check);
+        }
+
+        // The owner is unknown or says the payment did not go out, any backend that made it wins
+        let checks = join_all(
+            self.backends
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| Some(*index) != owner)
+                .map(|(_, backend)| backend.check_outgoing_payment(payment_identifier)),
+        )
+        .await;
+
+        for check in checks {
+            if best
+                .as_ref()
+                .is_none_or(|best| check_precedence(&check) > check_precedence(best))
+            {
+                best = Some(check);
+            }
+        }
+
+        best.unwrap_or_else(|| Err(payment::Error::UnknownPaymentState))
+    }
+
+    async fn outbound_liquidity(
+        &self,
+        unit: &CurrencyUnit,
+    ) -> Result<Option<Amount<CurrencyUnit>>, Self::Err> {
+        let mut total: Option<Amount<CurrencyUnit>> = None;
+        for backend in &self.backends {
+            let Some(liquidity) = backend.outbound_liquidity(unit).await? else {
+                continue;
+            };
+            total = Some(match total {
+                Some(total) => total.checked_add(&liquidity)?,
+                None => liquidity,
+            });
+        }
+        Ok(total)
+    }
+
+    async fn estimate_fee(
+        &self,
+        unit: &CurrencyUnit,
+        options: &OutgoingPaymentOptions,
+    ) -> Result<Option<Amount<CurrencyUnit>>, Self::Err> {
+        // Estimate of the backend that would make the payment
+        match self.rank(unit, options).await.first() {
+            Some(index) => self.backends[*index].estimate_fee(unit, options).await,
+            None => Ok(None),
+        }
+    }
+
+    fn supports_hold_invoices(&self) -> bool {
+        self.backends
+            .iter()
+            .any(|backend| backend.supports_hold_invoices())
+    }
+
+    async fn create_hold_invoice(
+        &self,
+        options: Bolt11IncomingPaymentOptions,
+        payment_hash: [u8; 32],
+    ) -> Result<CreateIncomingPaymentResponse, Self::Err> {
+        self.create_hold_invoice_with_backend(options, payment_hash)
+            .await
+            .map(|(response, _)| response)
+    }
+
+    async fn create_hold_invoice_with_backend(
+        &self,
+        options: Bolt11IncomingPaymentOptions,
+        payment_hash: [u8; 32],
+    ) -> Result<(CreateIncomingPaymentResponse, String), Self::Err> {
+        let mut last_err = None;
+        for backend in self.hold_invoice_backends(None) {
+            match backend
+                .create_hold_invoice(options.clone(), payment_hash)
+                .await
+            {
+                Ok(response) => return Ok((response, backend.backend_name())),
+                Err(err) => {
+                    tracing::warn!(
+                        "{} could not create a hold invoice: {}",
+                        backend.backend_name(),
+                        err
+                    );
+                    last_err = Some(err);
+                }
+            }
+        }
+        Err(last_err.unwrap_or(payment::Error::UnsupportedPaymentOption))
+    }
+
+    async fn settle_hold_invoice(&self, preimage: [u8; 32]) -> Result<(), Self::Err> {
+        self.settle_hold_invoice_with_backend(None, preimage).await
+    }
+
+    async fn settle_hold_invoice_with_backend(
+        &self,
+        backend: Option<&str>,
+        preimage: [u8; 32],
+    ) -> Result<(), Self::Err> {
+        // Backends that did not create the invoice refuse to settle it
+        let mut last_err = None;
+        for backend in self.hold_invoice_backends(backend) {
+            match backend.settle_hold_invoice(preimage).await {
+                Ok(()) => return Ok(()),
+                Err(err) => last_err = Some(err),
+            }
+        }
+        Err(last_err.unwrap_or(payment::Error::UnsupportedPaymentOption))
+    }
+
+    async fn cancel_hold_invoice(&self, payment_hash: [u8; 32]) -> Result<(), Self::Err> {
+        self.cancel_hold_invoice_with_backend(None, payment_hash)
+            .await
+    }
+
+    async fn cancel_hold_invoice_with_backend(
+        &self,
+        backend: Option<&str>,
+        payment_hash: [u8; 32],
+    ) -> Result<(), Self::Err> {
+        let mut last_err = None;
+        for backend in self.hold_invoice_backends(backend) {
+            match backend.cancel_hold_invoice(payment_hash).await {
+                Ok(()) => return Ok(()),
+                Err(err) => last_err = Some(err),
+            }
+        }
+        Err(last_err.unwrap_or(payment::Error::UnsupportedPaymentOption))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use cdk_common::payment::Bolt11OutgoingPaymentOptions;
+    use cdk_common::quote_id::QuoteId;
+    use cdk_fake_wallet::create_fake_invoice;
+
+    use super::*;
+
+    /// Backend quoting `fee` whose payments end in `status`, or error without one
+    struct TestBackend {
+        name: &'static str,
+        fee: u64,
+        status: Option<MeltQuoteState>,
+        /// State payments are checked in, `status` if not set
+        checked: Option<MeltQuoteState>,
+        payments: AtomicUsize,
+        /// Whether the backend creates hold invoices
+        hold_invoices: bool,
+        /// Hold invoices settled or cancelled
+        resolved: AtomicUsize,
+    }
+
+    impl TestBackend {
+        fn new(name: &'static str, fee: u64, status: Option<MeltQuoteState>) -> Arc<Self> {
+            Arc::new(Self {
+                name,
+                fee,
+                status,
+                checked: None,
+                payments: AtomicUsize::new(0),
+                hold_invoices: false,
+                resolved: AtomicUsize::new(0),
+            })
+        }
+
+        /// Backend creating hold invoices
+        fn holding(name: &'static str) -> Arc<Self> {
+            Arc::new(Self {
+                name,
+                fee: 1,
+                status: None,
+                checked: None,
+                payments: AtomicUsize::new(0),
+                hold_invoices: true,
+                resolved: AtomicUsize::new(0),
+            })
+        }
+
+        /// Backend whose payments error and are then checked in `checked`
+        fn erroring(name: &'static str, checked: MeltQuoteState) -> Arc<Self> {
+            Arc::new(Self {
+                name,
+                fee: 1,
+                status: None,
+                checked: Some(checked),
+                payments: AtomicUsize::new(0),
+                hold_invoices: false,
+                resolved: AtomicUsize::new(0),
+            })
+        }
+
+        fn response(&self, status: MeltQuoteState) -> MakePaymentResponse {
+            MakePaymentResponse {
+                payment_lookup_id: PaymentIdentifier::CustomId(self.name.to_string()),
+                payment_proof: None,
+                status,
+                total_spent: Amount::new(100 + self.fee, CurrencyUnit::Sat),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl MintPayment for TestBackend {
+        type Err = payment::Error;
+
+        fn backend_name(&self) -> String {
+            self.name.to_string()
+        }
+
+        async fn get_settings(&self) -> Result<SettingsResponse, Self::Err> {
+            Err(payment::Error::UnsupportedPaymentOption)
+        }
+
+        async fn create_incoming_payment_request(
+            &self,
+            _options: IncomingPaymentOptions,
+        ) -> Result<CreateIncomingPaymentResponse, Self::Err> {
+            Err(payment::Error::UnsupportedPaymentOption)
+        }
+
+        async fn get_payment_quote(
+            &self,
+            unit: &CurrencyUnit,
+            _options: OutgoingPaymentOptions,
+        ) -> Result<PaymentQuoteResponse, Self::Err> {
+            Ok(PaymentQuoteResponse {
+                request_lookup_id: None,
+                amount: Amount::new(100, unit.clone()),
+                fee: Amount::new(self.fee, unit.clone()),
+                state: MeltQuoteState::Unpaid,
+                extra_json: None,
+                estimated_blocks: None,
+                fee_options: None,
+            })
+        }
+
+        async fn make_payment(
+            &self,
+            _unit: &CurrencyUnit,
+            _options: OutgoingPaymentOptions,
+        ) -> Result<MakePaymentResponse, Self::Err> {
+            self.payments.fetch_add(1, Ordering::SeqCst);
+            self.status
+                .map(|status| self.response(status))
+                .ok_or(payment::Error::InvoicePaymentPending)
+        }
+
+        async fn wait_payment_event(
+            &self,
+        ) -> Result<Pin<Box<dyn Stream<Item = Event> + Send>>, Self::Err> {
+            Ok(Box::pin(futures::stream::pending()))
+        }
+
+        fn is_payment_event_stream_active(&self) -> bool {
+            false
+        }
+
+        fn cancel_payment_event_stream(&self) {}
+
+        async fn check_incoming_payment_status(
+            &self,
+            _payment_identifier: &PaymentIdentifier,
+        ) -> Result<Vec<WaitPaymentResponse>, Self::Err> {
+            Ok(Vec::new())
+        }
+
+        async fn check_outgoing_payment(
+            &self,
+            _payment_identifier: &PaymentIdentifier,
+        ) -> Result<MakePaymentResponse, Self::Err> {
+            Ok(self.response(
+                self.checked
+                    .or(self.status)
+                    .unwrap_or(MeltQuoteState::Unknown),
+            ))
+        }
+
+        fn supports_hold_invoices(&self) -> bool {
+            self.hold_invoices
+        }
+
+        async fn create_hold_invoice(
+            &self,
+            _options: Bolt11IncomingPaymentOptions,
+            payment_hash: [u8; 32],
+        ) -> Result<CreateIncomingPaymentResponse, Self::Err> {
+            if !self.hold_invoices {
+                return Err(payment::Error::UnsupportedPaymentOption);
+            }
+            Ok(CreateIncomingPaymentResponse {
+                request_lookup_id: PaymentIdentifier::PaymentHash(payment_hash),
+                request: self.name.to_string(),
+                expiry: None,
+                extra_json: None,
+            })
+        }
+
+        async fn settle_hold_invoice(&self, _preimage: [u8; 32]) -> Result<(), Self::Err> {
+            if !self.hold_invoices {
+                return Err(payment::Error::UnsupportedPaymentOption);
+            }
+            self.resolved.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+
+        async fn cancel_hold_invoice(&self, _payment_hash: [u8; 32]) -> Result<(), Self::Err> {
+            if !self.hold_invoices {
+                return Err(payment::Error::UnsupportedPaymentOption);
+            }
+            self.resolved.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    fn bolt11_options() -> OutgoingPaymentOptions {
+        OutgoingPaymentOptions::Bolt11(Box::new(Bolt11OutgoingPaymentOptions {
+            bolt11: create_fake_invoice(100_000, "router".to_string()),
+            max_fee_amount: None,
+            timeout_secs: None,
+            melt_options: None,
+            quote_id: QuoteId::new(),
+        }))
+    }
+
+    #[tokio::test]
+    async fn lowest_fee_backend_pays_first() {
+        let expensive = TestBackend::new("expensive", 5, Some(MeltQuoteState::Paid));
+        let cheap = TestBackend::new("cheap", 1, Some(MeltQuoteState::Paid));
+        let router = PaymentRouter::new(
+            vec![expensive.clone(), cheap.clone()],
+            RoutingPolicy::LowestFee,
+        )
+        .unwrap();
+
+        let quote = router
+            .get_payment_quote(&CurrencyUnit::Sat, bolt11_options())
+            .await
+            .unwrap();
+        assert_eq!(quote.fee.value(), 1);
+
+        let paid = router
+            .make_payment(&CurrencyUnit::Sat, bolt11_options())
+            .await
+            .unwrap();
+        assert_eq!(paid.status, MeltQuoteState::Paid);
+        assert_eq!(cheap.payments.load(Ordering::SeqCst), 1);
+        assert_eq!(expensive.payments.load(Ordering::SeqCst), 0);
+        assert_eq!(router.backend_name(), "expensive+cheap");
+    }
+
+    #[tokio::test]
+    async fn failed_payments_fail_over() {
+        let failing = TestBackend::new("failing", 1, Some(MeltQuoteState::Failed));
+        let erroring = TestBackend::erroring("erroring", MeltQuoteState::Failed);
+        let working = TestBackend::new("working", 1, Some(MeltQuoteState::Pending));
+        let router = PaymentRouter::new(
+            vec![failing.clone(), erroring.clone(), working.clone()],
+            RoutingPolicy::Priority,
+        )
+        .unwrap();
+
+        let (pending, backend) = router
+            .make_payment_with_backend(&CurrencyUnit::Sat, bolt11_options())
+            .await
+            .unwrap();
+        assert_eq!(pending.status, MeltQuoteState::Pending);
+        assert_eq!(backend, "working");
+        for backend in [&failing, &erroring, &working] {
+            assert_eq!(backend.payments.load(Ordering::SeqCst), 1);
+        }
+
+        // Asked to the backend that made the payment
+        let checked = router
+            .check_outgoing_payment_with_backend(Some(&backend), &pending.payment_lookup_id)
+            .await
+            .unwrap();
+        assert_eq!(checked.payment_lookup_id, pending.payment_lookup_id);
+        assert_eq!(checked.status, MeltQuoteState::Pending);
+
+        // Without it, the backend that failed does not hide the pending payment
+        let checked = router
+            .check_outgoing_payment(&pending.payment_lookup_id)
+            .await
+            .unwrap();
+        assert_eq!(checked.status, MeltQuoteState::Pending);
+    }
+
+    #[tokio::test]
+    async fn errored_payments_not_known_failed_are_not_retried() {
+        let erroring = TestBackend::erroring("erroring", MeltQuoteState::Unknown);
+        let working = TestBackend::new("working", 1, Some(MeltQuoteState::Paid));
+        let router = PaymentRouter::new(
+            vec![erroring.clone(), working.clone()],
+            RoutingPolicy::Priority,
+        )
+        .unwrap();
+
+        assert!(router
+            .make_payment_with_backend(&CurrencyUnit::Sat, bolt11_options())
+            .await
+            .is_err());
+        assert_eq!(erroring.payments.load(Ordering::SeqCst), 1);
+        assert_eq!(working.payments.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn payments_sent_by_any_backend_are_not_failed() {
+        let failing = TestBackend::new("failing", 1, Some(MeltQuoteState::Failed));
+        let unknown = TestBackend::new("unknown", 1, None);
+        let paid = TestBackend::new("paid", 1, Some(MeltQuoteState::Paid));
+        let router = PaymentRouter::new(
+            vec![failing.clone(), unknown.clone(), paid.clone()],
+            RoutingPolicy::Priority,
+        )
+        .unwrap();
+        let lookup_id = PaymentIdentifier::CustomId("payment".to_string());
+
+        for backend in [Some("failing"), Some("failing+unknown+paid"), None] {
+            let checked = router
+                .check_outgoing_payment_with_backend(backend, &lookup_id)
+                .await
+                .unwrap();
+            assert_eq!(checked.status, MeltQuoteState::Paid);
+        }
+
+        // Failed once every backend that knows the payment says so
+        let router = PaymentRouter::new(vec![failing, unknown], RoutingPolicy::Priority).unwrap();
+        let checked = router.check_outgoing_payment(&lookup_id).await.unwrap();
+        assert_eq!(checked.status, MeltQuoteState::Failed);
+    }
+
+    #[tokio::test]
+    async fn errored_payments_that_went_out_are_not_retried() {
+        /// Errors on payment while the payment is in flight
+        struct InFlight(Arc<TestBackend>);
+
+        #[async_trait]
+        impl MintPayment for InFlight {
+            type Err = payment::Error;
+
+            async fn get_settings(&self) -> Result<SettingsResponse, Self::Err> {
+                self.0.get_settings().await
+            }
+
+            async fn create_incoming_payment_request(
+                &self,
+                options: IncomingPaymentOptions,
+            ) -> Result<CreateIncomingPaymentResponse, Self::Err> {
+                self.0.create_incoming_payment_request(options).await
+            }
+
+            async fn get_payment_quote(
+                &self,
+                unit: &CurrencyUnit,
+                options: OutgoingPaymentOptions,
+            ) -> Result<PaymentQuoteResponse, Self::Err> {
+                self.0.get_payment_quote(unit, options).await
+            }
+
+            async fn make_payment(
+                &self,
+                unit: &CurrencyUnit,
+                options: OutgoingPaymentOptions,
+            ) -> Result<MakePaymentResponse, Self::Err> {
+                self.0.make_payment(unit, options).await?;
+                Err(payment::Error::Custom("connection reset".to_string()))
+            }
+
+            async fn wait_payment_event(
+                &self,
+            ) -> Result<Pin<Box<dyn Stream<Item = Event> + Send>>, Self::Err> {
+                self.0.wait_payment_event().await
+            }
+
+            fn is_payment_event_stream_active(&self) -> bool {
+                false
+            }
+
+            fn cancel_payment_event_stream(&self) {}
+
+            async fn check_incoming_payment_status(
+                &self,
+                payment_identifier: &PaymentIdentifier,
+            ) -> Result<Vec<WaitPaymentResponse>, Self::Err> {
+                self.0
+                    .check_incoming_payment_status(payment_identifier)
+                    .await
+            }
+
+            async fn check_outgoing_payment(
+                &self,
+                payment_identifier: &PaymentIdentifier,
+            ) -> Result<MakePaymentResponse, Self::Err> {
+                self.0.check_outgoing_payment(payment_identifier).await
+            }
+        }
+
+        let in_flight = TestBackend::new("in flight", 1, Some(MeltQuoteState::Pending));
+        let other = TestBackend::new("other", 1, Some(MeltQuoteState::Paid));
+        let router = PaymentRouter::new(
+            vec![Arc::new(InFlight(in_flight.clone())), other.clone()],
+            RoutingPolicy::Priority,
+        )
+        .unwrap();
+
+        let pending = router
+            .make_payment(&CurrencyUnit::Sat, bolt11_options())
+            .await
+            .unwrap();
+        assert_eq!(pending.status, MeltQuoteState::Pending);
+        assert_eq!(other.payments.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn hold_invoices_stay_on_their_backend() {
+        let plain = TestBackend::new("plain", 1, None);
+        let first = TestBackend::holding("first");
+        let second = TestBackend::holding("second");
+        let router = PaymentRouter::new(
+            vec![plain.clone(), first.clone(), second.clone()],
+            RoutingPolicy::Priority,
+        )
+        .unwrap();
+        assert!(router.supports_hold_invoices());
+
+        // Created by the first backend supporting hold invoices
+        let (_, backend) = router
+            .create_hold_invoice_with_backend(Bolt11IncomingPaymentOptions::default(), [1; 32])
+            .await
+            .unwrap();
+        assert_eq!(backend, "first");
+
+        // Settled and cancelled by the backend recorded on the quote only
+        router
+            .settle_hold_invoice_with_backend(Some("second"), [2; 32])
+            .await
+            .unwrap();
+        router
+            .cancel_hold_invoice_with_backend(Some("second"), [1; 32])
+            .await
+            .unwrap();
+        assert_eq!(second.resolved.load(Ordering::SeqCst), 2);
+        assert_eq!(first.resolved.load(Ordering::SeqCst), 0);
+
+        // A backend that does not create hold invoices is not used
+        assert!(!PaymentRouter::new(vec![plain], RoutingPolicy::Priority)
+            .unwrap()
+            .supports_hold_invoices());
+    }
+}
@@ -0,0 +1,316 @@
+//! Expiry of stale quotes
+//!
+//! Quotes are only checked against their expiry when a wallet uses them, so quotes that are
+//! never paid stay in the database. With a [`QuoteExpiryPolicy`] a background task marks the
+//! unpaid melt quotes past their expiry as failed, notifying their subscribers, and can purge
+//! the unpaid mint and melt quotes once they expired long enough ago.
+//!
+//! Mint quotes carry no state of their own, an unpaid mint quote is expired as soon as its
+//! expiry passes, so they are only ever purged. The hold invoices of mint quotes that expired
+//! without being minted are cancelled, refunding their payments.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use cdk_common::nuts::MeltQuoteState;
+use cdk_common::util::unix_time;
+#[cfg(feature = "prometheus")]
+use cdk_prometheus::METRICS;
+use tokio_util::sync::CancellationToken;
+use tracing::instrument;
+
+use super::Mint;
+use crate::Error;
+
+/// How often stale quotes are looked for and when they are purged
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuoteExpiryPolicy {
+    /// How often expired quotes are looked for
+    pub check_interval: Duration,
+    /// Time after their expiry unpaid quotes are removed from the database, never when none
+    pub purge_after: Option<Duration>,
+}
+
+impl QuoteExpiryPolicy {
+    /// Look for expired quotes every `check_interval`, keeping them in the database
+    pub fn new(check_interval: Duration) -> Self {
+        Self {
+            check_interval,
+            purge_after: None,
+        }
+    }
+
+    /// Remove unpaid quotes `purge_after` their expiry
+    pub fn with_purge_after(mut self, purge_after: Duration) -> Self {
+        self.purge_after = Some(purge_after);
+        self
+    }
+}
+
+impl Default for QuoteExpiryPolicy {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(300))
+    }
+}
+
+/// Quotes handled by [`Mint::expire_stale_quotes`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExpiredQuotes {
+    /// Unpaid melt quotes marked as failed
+    pub melt_expired: u64,
+    /// Unpaid mint quotes removed from the database
+    pub mint_purged: u64,
+    /// Unpaid and failed melt quotes removed from the database
+    pub melt_purged: u64,
+    /// Hold invoices of mint quotes that expired without being minted cancelled
+    pub hold_invoices_cancelled: u64,
+}
+
+impl Mint {
+    /// Expire and purge stale quotes following `policy` while the mint is running
+    pub fn with_quote_expiry_policy(mut self, policy: QuoteExpiryPolicy) -> Self {
+        self.quote_expiry_policy = Some(policy);
+        self
+    }
+
+    /// Mark the unpaid melt quotes past their expiry as failed, and purge the unpaid quotes
+    /// that expired longer ago than the `purge_after` of the policy
+    ///
+    /// Without a policy expired quotes are marked but never purged.
+    #[instrument(skip_all)]
+    pub async fn expire_stale_quotes(&self) -> Result<ExpiredQuotes, Error> {
+        let now = unix_time();
+        let mut expired = ExpiredQuotes::default();
+
+        let stale: Vec<_> = self
+            .localstore
+            .get_melt_quotes()
+            .await?
+            .into_iter()
+            .filter(|quote| {
+                quote.state == MeltQuoteState::Unpaid && quote.expiry > 0 && quote.expiry < now
+            })
+            .map(|quote| quote.id)
+            .collect();
+
+        for quote_id in stale {
+            let mut tx = self.localstore.begin_transaction().await?;
+
+            // The quote may have been melted since it was listed
+            let Some(mut quote) = tx.get_melt_quote(&quote_id).await? else {
+                tx.rollback().await?;
+                continue;
+            };
+            if quote.state != MeltQuoteState::Unpaid || quote.expiry >= now {
+                tx.rollback().await?;
+                continue;
+            }
+
+            tx.update_melt_quote_state(&mut quote, MeltQuoteState::Failed, None)
+                .await?;
+            tx.commit().await?;
+
+            self.pubsub_manager
+                .melt_quote_status(&quote, None, None, MeltQuoteState::Failed);
+            expired.melt_expired += 1;
+        }
+
+        expired.hold_invoices_cancelled = self.cancel_expired_hold_invoices(now).await?;
+
+        if let Some(purge_after) = self
+            .quote_expiry_policy
+            .and_then(|policy| policy.purge_after)
+        {
+            let expired_before = now.saturating_sub(purge_after.as_secs());
+
+            let mut tx = self.localstore.begin_transaction().await?;
+            expired.mint_purged = tx.remove_expired_mint_quotes(expired_before).await?;
+            expired.melt_purged = tx.remove_expired_melt_quotes(expired_before).await?;
+            tx.commit().await?;
+        }
+
+        if expired != ExpiredQuotes::default() {
+            tracing::info!(
+                "Expired {} melt quotes, purged {} mint and {} melt quotes, cancelled {} hold invoices",
+                expired.melt_expired,
+                expired.mint_purged,
+                expired.melt_purged,
+                expired.hold_invoices_cancelled
+            );
+        }
+
+        #[cfg(feature = "prometheus")]
+        {
+            METRICS.record_quotes_expired("melt", "expired", expired.melt_expired);
+            METRICS.record_quotes_expired("mint", "purged", expired.mint_purged);
+            METRICS.record_quotes_expired("melt", "purged", expired.melt_purged);
+        }
+
+        Ok(expired)
+    }
+
+    /// Expire stale quotes every `check_interval` until `shutdown` is cancelled
+    pub(crate) async fn expire_quotes_on_schedule(
+        mint: Arc<Mint>,
+        policy: QuoteExpiryPolicy,
+        shutdown: CancellationToken,
+    ) {
+        let mut interval = tokio::time::interval(policy.check_interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => break,
+                _ = interval.tick() => {
+                    if let Err(err) = mint.expire_stale_quotes().await {
+                        tracing::error!("Scheduled quote expiry failed: {}", err);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cdk_common::mint::{MeltPaymentRequest, MeltQuote, MintQuote};
+    use cdk_common::nut00::KnownMethod;
+    use cdk_common::payment::PaymentIdentifier;
+    use cdk_common::{Amount, CurrencyUnit, PaymentMethod, QuoteId};
+
+    use super::*;
+    use crate::test_helpers::mint::create_test_mint;
+
+    const ADDRESS: &str = "bc1qxy2kgdygjrsqtzq2n0yrf2493p83kkfjhx0wlh";
+
+    async fn add_melt_quote(mint: &Mint, expiry: u64) -> QuoteId {
+        let quote = MeltQuote::new(
+            None,
+            MeltPaymentRequest::Onchain {
+                address: ADDRESS.to_owned(),
+            },
+            CurrencyUnit::Sat,
+            Amount::new(100, CurrencyUnit::Sat),
+            Amount::new(1, CurrencyUnit::Sat),
+            expiry,
+            None,
+            None,
+            PaymentMethod::Known(KnownMethod::Onchain),
+            None,
+            None,
+        );
+        let quote_id = quote.id.clone();
+
+        let mut tx = mint.localstore().begin_transaction().await.unwrap();
+        tx.add_melt_quote(quote).await.unwrap();
+        tx.commit().await.unwrap();
+
+        quote_id
+    }
+
+    async fn add_mint_quote(mint: &Mint, expiry: u64) -> QuoteId {
+        let quote_id = QuoteId::new();
+        let quote = MintQuote::new(
+            Some(quote_id.clone()),
+            ADDRESS.to_owned(),
+            CurrencyUnit::Sat,
+            None,
+            expiry,
+            PaymentIdentifier::QuoteId(quote_id.clone()),
+            None,
+            Amount::new(0, CurrencyUnit::Sat),
+            Amount::new(0, CurrencyUnit::Sat),
+            PaymentMethod::Known(KnownMethod::Onchain),
+            unix_time(),
+            vec![],
+            vec![],
+            None,
+        );
+
+        let mut tx = mint.localstore().begin_transaction().await.unwrap();
+        tx.add_mint_quote(quote).await.unwrap();
+        tx.commit().await.unwrap();
+
+        quote_id
+    }
+
+    #[tokio::test]
+    async fn expired_melt_quotes_are_marked_failed() {
+        let mint = create_test_mint().await.unwrap();
+        let stale = add_melt_quote(&mint, unix_time() - 60).await;
+        let fresh = add_melt_quote(&mint, unix_time() + 600).await;
+
+        let expired = mint.expire_stale_quotes().await.unwrap();
+        assert_eq!(
+            expired,
+            ExpiredQuotes {
+                melt_expired: 1,
+                ..Default::default()
+            }
+        );
+
+        let localstore = mint.localstore();
+        let state = |quote: Option<MeltQuote>| quote.unwrap().state;
+        assert_eq!(
+            state(localstore.get_melt_quote(&stale).await.unwrap()),
+            MeltQuoteState::Failed
+        );
+        assert_eq!(
+            state(localstore.get_melt_quote(&fresh).await.unwrap()),
+            MeltQuoteState::Unpaid
+        );
+
+        // Without a purge delay the quotes are kept
+        assert_eq!(
+            mint.expire_stale_quotes().await.unwrap(),
+            ExpiredQuotes::default()
+        );
+    }
+
+    #[tokio::test]
+    async fn unpaid_quotes_are_purged_after_the_delay() {
+        let policy = QuoteExpiryPolicy::default().with_purge_after(Duration::from_secs(3600));
+        let mint = create_test_mint()
+            .await
+            .unwrap()
+            .with_quote_expiry_policy(policy);
+
+        let old_melt = add_melt_quote(&mint, unix_time() - 7200).await;
+        let recent_melt = add_melt_quote(&mint, unix_time() - 60).await;
+        let old_mint = add_mint_quote(&mint, unix_time() - 7200).await;
+        let recent_mint = add_mint_quote(&mint, unix_time() - 60).await;
+
+        let expired = mint.expire_stale_quotes().await.unwrap();
+        assert_eq!(
+            expired,
+            ExpiredQuotes {
+                melt_expired: 2,
+                mint_purged: 1,
+                melt_purged: 1,
+                hold_invoices_cancelled: 0,
+            }
+        );
+
+        let localstore = mint.localstore();
+        assert!(localstore
+            .get_melt_quote(&old_melt)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(localstore
+            .get_melt_quote(&recent_melt)
+            .await
+            .unwrap()
+            .is_some());
+        assert!(localstore
+            .get_mint_quote(&old_mint)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(localstore
+            .get_mint_quote(&recent_mint)
+            .await
+            .unwrap()
+            .is_some());
+    }
+}
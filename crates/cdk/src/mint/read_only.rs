@@ -0,0 +1,192 @@
+//! Read-only maintenance mode
+//!
+//! While the mint is read-only it keeps serving keys, info and proof states, and it keeps
+//! accepting swaps and melts, but it refuses to create new mint quotes. This is meant for
+//! draining the mint before a shutdown or while the Lightning node is under maintenance.
+//!
+//! The mode is advertised to wallets through the `read_only` flag and the MOTD of the stored
+//! [`MintInfo`]. The MOTD in place before entering the mode is persisted so it can be restored
+//! once the maintenance window is over.
+//!
+//! [`MintInfo`]: cdk_common::MintInfo
+
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use super::{
+    Mint, MintChange, CDK_MINT_CONFIG_KV_KEY, CDK_MINT_CONFIG_SECONDARY_NAMESPACE,
+    CDK_MINT_PRIMARY_NAMESPACE, CDK_MINT_READ_ONLY_KV_KEY,
+};
+use crate::error::Error;
+use crate::util::unix_time;
+
+/// MOTD advertised while read-only mode is enabled and no message was provided
+pub const DEFAULT_READ_ONLY_MOTD: &str =
+    "The mint is under maintenance: new mint quotes are disabled, swaps and melts are available";
+
+/// Persisted state of the read-only mode
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct ReadOnlyState {
+    /// Unix timestamp at which read-only mode was enabled
+    enabled_at: u64,
+    /// MOTD in place before read-only mode was enabled
+    previous_motd: Option<String>,
+}
+
+impl Mint {
+    /// Returns true if the mint is in read-only maintenance mode
+    #[instrument(skip_all)]
+    pub async fn is_read_only(&self) -> Result<bool, Error> {
+        Ok(self.mint_info().await?.read_only)
+    }
+
+    /// Enable or disable read-only maintenance mode
+    ///
+    /// When enabling, the `read_only` flag of the mint info is set and the MOTD is replaced
+    /// with `motd` (or [`DEFAULT_READ_ONLY_MOTD`]). When disabling, the previous MOTD is
+    /// restored. Enabling an already read-only mint only updates the MOTD. The mint info and
+    /// the persisted state are written in one transaction.
+    #[instrument(skip(self))]
+    pub async fn set_read_only(&self, enabled: bool, motd: Option<String>) -> Result<(), Error> {
+        let mut mint_info = self.mint_info().await?;
+        let current = self.read_only_state().await?;
+
+        let state = match (enabled, current) {
+            (true, current) => {
+                let state = match current {
+                    Some(state) => state,
+                    None => ReadOnlyState {
+                        enabled_at: unix_time(),
+                        previous_motd: mint_info.motd.clone(),
+                    },
+                };
+
+                mint_info.read_only = true;
+                mint_info.motd = Some(motd.unwrap_or_else(|| DEFAULT_READ_ONLY_MOTD.to_string()));
+
+                tracing::info!("Enabling read-only mode");
+                Some(state)
+            }
+            (false, Some(state)) => {
+                mint_info.read_only = false;
+                mint_info.motd = state.previous_motd;
+
+                tracing::info!("Disabling read-only mode");
+                None
+            }
+            (false, None) => return Ok(()),
+        };
+
+        let mint_info_bytes = serde_json::to_vec(&mint_info)?;
+        let mut tx = self.localstore.begin_transaction().await?;
+        match state {
+            Some(state) => {
+                tx.kv_write(
+                    CDK_MINT_PRIMARY_NAMESPACE,
+                    CDK_MINT_CONFIG_SECONDARY_NAMESPACE,
+                    CDK_MINT_READ_ONLY_KV_KEY,
+                    &serde_json::to_vec(&state)?,
+                )
+                .await?;
+            }
+            None => {
+                tx.kv_remove(
+                    CDK_MINT_PRIMARY_NAMESPACE,
+                    CDK_MINT_CONFIG_SECONDARY_NAMESPACE,
+                    CDK_MINT_READ_ONLY_KV_KEY,
+                )
+                .await?;
+            }
+        }
+        tx.kv_write(
+            CDK_MINT_PRIMARY_NAMESPACE,
+            CDK_MINT_CONFIG_SECONDARY_NAMESPACE,
+            CDK_MINT_CONFIG_KV_KEY,
+            &mint_info_bytes,
+        )
+        .await?;
+        tx.commit().await?;
+
+        let _ = self.changes.send(MintChange::Info);
+        Ok(())
+    }
+
+    async fn read_only_state(&self) -> Result<Option<ReadOnlyState>, Error> {
+        let bytes = self
+            .localstore
+            .kv_read(
+                CDK_MINT_PRIMARY_NAMESPACE,
+                CDK_MINT_CONFIG_SECONDARY_NAMESPACE,
+                CDK_MINT_READ_ONLY_KV_KEY,
+            )
+            .await?;
+
+        match bytes {
+            Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cdk_common::nuts::{CheckStateRequest, MintQuoteBolt11Request, ProofsMethods};
+    use cdk_common::{Amount, CurrencyUnit};
+
+    use super::*;
+    use crate::test_helpers::mint::{create_test_mint, mint_test_proofs};
+
+    fn quote_request() -> MintQuoteBolt11Request {
+        MintQuoteBolt11Request {
+            amount: Amount::from(32),
+            unit: CurrencyUnit::Sat,
+            description: None,
+            pubkey: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn read_only_refuses_mint_quotes_and_restores_info() {
+        let mint = create_test_mint().await.unwrap();
+        let proofs = mint_test_proofs(&mint, Amount::from(64)).await.unwrap();
+        let original = mint.mint_info().await.unwrap();
+
+        mint.set_read_only(true, None).await.unwrap();
+        assert!(mint.is_read_only().await.unwrap());
+
+        let info = mint.mint_info().await.unwrap();
+        assert!(info.read_only);
+        assert!(!info.nuts.nut04.disabled);
+        assert_eq!(info.motd.as_deref(), Some(DEFAULT_READ_ONLY_MOTD));
+
+        let err = mint
+            .get_mint_quote(quote_request().into())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::MintingDisabled));
+
+        // Proof states can still be checked
+        let states = mint
+            .check_state(&CheckStateRequest {
+                ys: proofs.ys().unwrap(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(states.states.len(), proofs.len());
+
+        // Enabling again keeps the original values around
+        mint.set_read_only(true, Some("LN node upgrade".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(
+            mint.mint_info().await.unwrap().motd.as_deref(),
+            Some("LN node upgrade")
+        );
+
+        mint.set_read_only(false, None).await.unwrap();
+        assert!(!mint.is_read_only().await.unwrap());
+        assert_eq!(mint.mint_info().await.unwrap(), original);
+
+        mint.get_mint_quote(quote_request().into()).await.unwrap();
+    }
+}
@@ -0,0 +1,165 @@
+//! Readiness of the services the mint depends on
+//!
+//! [`Mint::readiness`] reaches the database, the signatory and every payment backend, so a
+//! load balancer or an orchestrator only sends traffic to a mint that can serve it. A remote
+//! signatory or payment processor that went away is caught here instead of on the next request.
+
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use futures::future::join_all;
+use tracing::instrument;
+
+use super::{BackendStats, CurrencyUnit, Mint, PaymentMethod};
+
+/// Time a dependency has to answer before it is reported as failing
+pub const READINESS_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Outcome of reaching a dependency of the mint
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DependencyHealth {
+    /// Whether the dependency answered in time
+    pub ok: bool,
+    /// Time it took to answer or to give up
+    pub latency: Duration,
+    /// Why the dependency failed, if it did
+    pub error: Option<String>,
+}
+
+/// Health of a payment backend, see [`Readiness::payment_backends`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackendHealth {
+    /// Unit the backend is registered for
+    pub unit: CurrencyUnit,
+    /// Payment method the backend is registered for
+    pub method: PaymentMethod,
+    /// Name of the backend
+    pub backend: String,
+    /// Outcome of reaching it
+    pub health: DependencyHealth,
+    /// What happened with it since the mint started, this check included
+    pub stats: BackendStats,
+}
+
+/// Health of every dependency of the mint, see [`Mint::readiness`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Readiness {
+    /// The database
+    pub database: DependencyHealth,
+    /// The signatory, local or remote
+    pub signatory: DependencyHealth,
+    /// Every payment backend, ordered by unit and method
+    pub payment_backends: Vec<BackendHealth>,
+}
+
+impl Readiness {
+    /// Whether every dependency answered
+    pub fn is_ready(&self) -> bool {
+        self.database.ok
+            && self.signatory.ok
+            && self
+                .payment_backends
+                .iter()
+                .all(|backend| backend.health.ok)
+    }
+}
+
+/// Run `check`, giving up after [`READINESS_CHECK_TIMEOUT`]
+async fn check<F, T, E>(check: F) -> DependencyHealth
+where
+    F: Future<Output = Result<T, E>>,
+    E: ToString,
+{
+    let start = Instant::now();
+    let error = match tokio::time::timeout(READINESS_CHECK_TIMEOUT, check).await {
+        Ok(Ok(_)) => None,
+        Ok(Err(err)) => Some(err.to_string()),
+        Err(_) => Some(format!(
+            "No answer within {}s",
+            READINESS_CHECK_TIMEOUT.as_secs()
+        )),
+    };
+
+    DependencyHealth {
+        ok: error.is_none(),
+        latency: start.elapsed(),
+        error,
+    }
+}
+
+impl Mint {
+    /// Reach the database, the signatory and every payment backend concurrently
+    ///
+    /// Each dependency has [`READINESS_CHECK_TIMEOUT`] to answer.
+    #[instrument(skip_all)]
+    pub async fn readiness(&self) -> Readiness {
+        let database = check(self.localstore.get_keyset_infos());
+        let signatory = check(self.signatory.keysets());
+        let payment_backends = join_all(self.payment_processors.iter().map(
+            |(key, backend)| async move {
+                let name = backend.backend_name();
+                let health = check(backend.get_settings()).await;
+                self.backend_monitor.record_check(key, &name, health.ok);
+
+                BackendHealth {
+                    unit: key.unit.clone(),
+                    method: key.method.clone(),
+                    backend: name,
+                    health,
+                    stats: self.backend_monitor.stats(key),
+                }
+            },
+        ));
+
+        let (database, signatory, mut payment_backends) =
+            tokio::join!(database, signatory, payment_backends);
+
+        payment_backends.sort_by(|a, b| {
+            (a.unit.to_string(), a.method.to_string())
+                .cmp(&(b.unit.to_string(), b.method.to_string()))
+        });
+
+        for (name, health) in [("database", &database), ("signatory", &signatory)] {
+            if let Some(err) = &health.error {
+                tracing::warn!("Mint not ready, the {} failed: {}", name, err);
+            }
+        }
+        for backend in &payment_backends {
+            if let Some(err) = &backend.health.error {
+                tracing::warn!(
+                    "Mint not ready, the {} backend for {} {} failed: {}",
+                    backend.backend,
+                    backend.unit,
+                    backend.method,
+                    err
+                );
+            }
+        }
+
+        Readiness {
+            database,
+            signatory,
+            payment_backends,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_helpers::mint::create_test_mint;
+
+    #[tokio::test]
+    async fn test_mint_is_ready() {
+        let mint = create_test_mint().await.unwrap();
+
+        let readiness = mint.readiness().await;
+
+        assert!(readiness.is_ready(), "{readiness:?}");
+        assert!(readiness.database.error.is_none());
+        assert!(!readiness.payment_backends.is_empty());
+        for backend in &readiness.payment_backends {
+            assert_eq!(backend.stats.connected, Some(true));
+            assert_eq!(backend.stats.error_streak, 0);
+        }
+    }
+}
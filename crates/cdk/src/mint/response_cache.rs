@@ -0,0 +1,166 @@
+//! Responses cached in the mint database
+//!
+//! Frontends keep the responses of swap, mint and melt requests so a request retried after a
+//! network failure is answered with the original signatures instead of failing on spent inputs
+//! (NUT-19). Kept in the mint database, the responses survive restarts and are shared by every
+//! instance serving the same database.
+
+use std::time::Duration;
+
+use tracing::instrument;
+
+use super::{Mint, CDK_MINT_PRIMARY_NAMESPACE};
+use crate::error::Error;
+use crate::util::unix_time;
+
+const CDK_MINT_RESPONSE_CACHE_SECONDARY_NAMESPACE: &str = "response_cache";
+
+/// Cached response, prefixed by its big endian expiry timestamp
+fn encode_entry(response: &[u8], expires_at: u64) -> Vec<u8> {
+    let mut entry = Vec::with_capacity(8 + response.len());
+    entry.extend_from_slice(&expires_at.to_be_bytes());
+    entry.extend_from_slice(response);
+    entry
+}
+
+/// Response of an entry, unless it is malformed or expired at `now`
+fn decode_entry(entry: &[u8], now: u64) -> Option<&[u8]> {
+    let (expires_at, response) = entry.split_first_chunk::<8>()?;
+    (u64::from_be_bytes(*expires_at) > now).then_some(response)
+}
+
+impl Mint {
+    /// Cached response under `key`, unless it expired
+    ///
+    /// Keys are made of ASCII letters, digits, `_` and `-`, such as the hex of a request hash.
+    #[instrument(skip(self))]
+    pub async fn cached_response(&self, key: &str) -> Result<Option<Vec<u8>>, Error> {
+        let entry = self
+            .localstore
+            .kv_read(
+                CDK_MINT_PRIMARY_NAMESPACE,
+                CDK_MINT_RESPONSE_CACHE_SECONDARY_NAMESPACE,
+                key,
+            )
+            .await?;
+
+        Ok(entry.and_then(|entry| decode_entry(&entry, unix_time()).map(<[u8]>::to_vec)))
+    }
+
+    /// Cache `response` under `key` for `ttl`
+    #[instrument(skip(self, response))]
+    pub async fn cache_response(
+        &self,
+        key: &str,
+        response: &[u8],
+        ttl: Duration,
+    ) -> Result<(), Error> {
+        let entry = encode_entry(response, unix_time().saturating_add(ttl.as_secs()));
+
+        let mut tx = self.localstore.begin_transaction().await?;
+        tx.kv_write(
+            CDK_MINT_PRIMARY_NAMESPACE,
+            CDK_MINT_RESPONSE_CACHE_SECONDARY_NAMESPACE,
+            key,
+            &entry,
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Drop the cached response under `key`
+    #[instrument(skip(self))]
+    pub async fn remove_cached_response(&self, key: &str) -> Result<(), Error> {
+        let mut tx = self.localstore.begin_transaction().await?;
+        tx.kv_remove(
+            CDK_MINT_PRIMARY_NAMESPACE,
+            CDK_MINT_RESPONSE_CACHE_SECONDARY_NAMESPACE,
+            key,
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Drop every expired cached response
+    ///
+    /// Returns the number of responses dropped.
+    #[instrument(skip_all)]
+    pub async fn prune_cached_responses(&self) -> Result<usize, Error> {
+        let now = unix_time();
+        let keys = self
+            .localstore
+            .kv_list(
+                CDK_MINT_PRIMARY_NAMESPACE,
+                CDK_MINT_RESPONSE_CACHE_SECONDARY_NAMESPACE,
+            )
+            .await?;
+
+        let mut expired = Vec::new();
+        for key in keys {
+            let entry = self
+                .localstore
+                .kv_read(
+                    CDK_MINT_PRIMARY_NAMESPACE,
+                    CDK_MINT_RESPONSE_CACHE_SECONDARY_NAMESPACE,
+                    &key,
+                )
+                .await?;
+
+            if entry.is_some_and(|entry| decode_entry(&entry, now).is_none()) {
+                expired.push(key);
+            }
+        }
+
+        if expired.is_empty() {
+            return Ok(0);
+        }
+
+        let mut tx = self.localstore.begin_transaction().await?;
+        for key in &expired {
+            tx.kv_remove(
+                CDK_MINT_PRIMARY_NAMESPACE,
+                CDK_MINT_RESPONSE_CACHE_SECONDARY_NAMESPACE,
+                key,
+            )
+            .await?;
+        }
+        tx.commit().await?;
+
+        tracing::debug!("Pruned {} expired cached responses", expired.len());
+        Ok(expired.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::mint::create_test_mint;
+
+    #[tokio::test]
+    async fn cached_responses_expire_and_are_pruned() {
+        let mint = create_test_mint().await.unwrap();
+
+        mint.cache_response("fresh", b"signatures", Duration::from_secs(60))
+            .await
+            .unwrap();
+        mint.cache_response("stale", b"old signatures", Duration::ZERO)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            mint.cached_response("fresh").await.unwrap().as_deref(),
+            Some(&b"signatures"[..])
+        );
+        assert!(mint.cached_response("stale").await.unwrap().is_none());
+        assert!(mint.cached_response("unknown").await.unwrap().is_none());
+
+        assert_eq!(mint.prune_cached_responses().await.unwrap(), 1);
+        assert_eq!(mint.prune_cached_responses().await.unwrap(), 0);
+        assert!(mint.cached_response("fresh").await.unwrap().is_some());
+
+        mint.remove_cached_response("fresh").await.unwrap();
+        assert!(mint.cached_response("fresh").await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,98 @@
+//! Retry of mint operations on transient database failures
+//!
+//! A serialization conflict or a lost connection inside a database transaction, most often at
+//! its commit, fails the whole transaction. Swaps, melts and mints run their transactions again
+//! from the start when that happens, once the failed attempt rolled back and compensated what it
+//! did, instead of handing the error to the wallet. Steps with effects outside of the database,
+//! such as paying a melt quote, are never part of the retried operation.
+
+use std::future::Future;
+
+use cdk_common::database::RetryPolicy;
+
+use crate::Error;
+
+/// Run `operation` again while it fails with a transient database error, following the default
+/// [`RetryPolicy`]
+pub(crate) async fn retry_transient<T, F, Fut>(operation: F) -> Result<T, Error>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<T, Error>>,
+{
+    let policy = RetryPolicy::default();
+    let mut retry = 0;
+
+    loop {
+        match operation().await {
+            Err(Error::Database(err)) => match policy.retry_after(&err, retry) {
+                Some(backoff) => {
+                    tokio::time::sleep(backoff).await;
+                    retry += 1;
+                }
+                None => return Err(Error::Database(err)),
+            },
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use cdk_common::database::Error as DatabaseError;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn transient_failures_are_run_again() {
+        let attempts = &AtomicU32::new(0);
+
+        let result = retry_transient(|| async move {
+            if attempts.fetch_add(1, Ordering::SeqCst) < 2 {
+                Err(Error::Database(DatabaseError::Transient(
+                    "could not serialize access".into(),
+                )))
+            } else {
+                Ok(())
+            }
+        })
+        .await;
+
+        assert!(result.is_ok());
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn other_failures_are_returned_at_once() {
+        let attempts = &AtomicU32::new(0);
+
+        let result: Result<(), Error> = retry_transient(|| async move {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err(Error::TokenPending)
+        })
+        .await;
+
+        assert!(matches!(result, Err(Error::TokenPending)));
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn transient_failures_are_returned_after_the_last_retry() {
+        let attempts = &AtomicU32::new(0);
+
+        let result: Result<(), Error> = retry_transient(|| async move {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err(Error::Database(DatabaseError::Transient(
+                "connection reset".into(),
+            )))
+        })
+        .await;
+
+        assert!(matches!(result, Err(Error::Database(err)) if err.is_transient()));
+        assert_eq!(
+            attempts.load(Ordering::SeqCst),
+            RetryPolicy::default().max_retries + 1
+        );
+    }
+}
@@ -111,7 +111,10 @@ pub(crate) async fn process_melt_saga_outcome(
             )
             .await?;
 
-            quote.state = MeltQuoteState::Unpaid;
+            // The rollback above already reset the stored quote
+            if quote.state != MeltQuoteState::Unpaid {
+                quote.transition_state(MeltQuoteState::Unpaid)?;
+            }
         }
         MeltQuoteState::Pending | MeltQuoteState::Unknown => {
             tracing::debug!(
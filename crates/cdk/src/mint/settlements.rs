@@ -0,0 +1,42 @@
+//! Melts settled internally
+//!
+//! A melt quote paying a request the mint issued itself is settled by crediting the mint quote,
+//! without reaching the payment backend. Each such settlement is recorded, counted in the
+//! metrics and sent to [`Mint::subscribe_internal_settlements`] subscribers, so operators can
+//! tell how much volume bypasses lightning and reconcile the fee reserves that were not spent
+//! on routing.
+
+use cdk_common::database::InternalSettlement;
+use tokio::sync::broadcast;
+use tracing::instrument;
+
+use super::Mint;
+use crate::error::Error;
+
+impl Mint {
+    /// Melts settled internally at or after `since`, newest first
+    #[instrument(skip(self))]
+    pub async fn internal_settlements(
+        &self,
+        since: Option<u64>,
+    ) -> Result<Vec<InternalSettlement>, Error> {
+        Ok(self.localstore.get_internal_settlements(since).await?)
+    }
+
+    /// Be notified of every melt settled internally
+    ///
+    /// A receiver that lags behind misses settlements, [`Mint::internal_settlements`] lists them
+    /// all.
+    pub fn subscribe_internal_settlements(&self) -> broadcast::Receiver<InternalSettlement> {
+        self.internal_settlements.subscribe()
+    }
+
+    /// Count a recorded internal settlement and notify the subscribers
+    pub(crate) fn notify_internal_settlement(&self, settlement: InternalSettlement) {
+        #[cfg(feature = "prometheus")]
+        cdk_prometheus::METRICS
+            .record_internal_settlement(&settlement.unit.to_string(), settlement.amount.into());
+
+        let _ = self.internal_settlements.send(settlement);
+    }
+}
@@ -5,6 +5,7 @@
 
 use std::str::FromStr;
 
+use cdk_common::database::mint::QuoteSearch;
 use cdk_common::mint::{OperationKind, Saga};
 use cdk_common::QuoteId;
 
@@ -62,19 +63,18 @@ impl Mint {
             Error::Internal
         })?;
 
-        // Check payment status with LN backend
-        let pay_invoice_response =
-            ln_backend
-                .check_outgoing_payment(lookup_id)
-                .await
-                .map_err(|err| {
-                    tracing::error!(
-                        "Failed to check payment status for quote {}: {}",
-                        quote.id,
-                        err
-                    );
-                    Error::Internal
-                })?;
+        // Check payment status with the backend recorded as having made the payment
+        let pay_invoice_response = ln_backend
+            .check_outgoing_payment_with_backend(quote.backend.as_deref(), lookup_id)
+            .await
+            .map_err(|err| {
+                tracing::error!(
+                    "Failed to check payment status for quote {}: {}",
+                    quote.id,
+                    err
+                );
+                Error::Internal
+            })?;
 
         tracing::info!(
             "Payment status for melt quote {}: {}",
@@ -493,7 +493,12 @@ impl Mint {
                                 continue;
                             }
 
-                            quote.state = MeltQuoteState::Paid;
+                            // TX1 already marked the stored quote paid
+                            if quote.state != MeltQuoteState::Paid
+                                && quote.transition_state(MeltQuoteState::Paid).is_err()
+                            {
+                                continue;
+                            }
                             quote.payment_proof = payment_proof;
                             quote.request_lookup_id = Some(payment_lookup_id);
 
@@ -689,6 +694,159 @@ impl Mint {
         Ok(())
     }
 
+    /// Checks every melt quote left `Pending` with its LN backend
+    ///
+    /// Runs after [`Mint::recover_from_incomplete_melt_sagas`], which already
+    /// resolved the quotes with an incomplete saga, so those are skipped. The
+    /// remaining pending quotes, such as ones left by a crash before sagas
+    /// were persisted, are looked up by their payment lookup id:
+    /// - **Paid**: the quote is finalized, marking its proofs spent
+    /// - **Unpaid/Failed**: the melt is rolled back, releasing its proofs
+    /// - **Pending/Unknown**: the quote is left pending
+    ///
+    /// Returns the number of quotes moved out of `Pending`.
+    pub async fn check_pending_melt_quotes(&self) -> Result<usize, Error> {
+        let mut pending_quotes = Vec::new();
+        let mut search = QuoteSearch {
+            state: Some(MeltQuoteState::Pending),
+            ..Default::default()
+        };
+
+        loop {
+            let quotes = self.localstore.search_melt_quotes(&search).await?;
+            let page_len = quotes.len() as u64;
+            pending_quotes.extend(quotes);
+
+            if page_len < search.limit {
+                break;
+            }
+            search.offset += page_len;
+        }
+
+        if pending_quotes.is_empty() {
+            tracing::info!("No pending melt quotes found to check.");
+            return Ok(0);
+        }
+
+        tracing::info!("Checking {} pending melt quotes.", pending_quotes.len());
+
+        let mut resolved = 0;
+        for quote in pending_quotes {
+            if self
+                .localstore
+                .get_melt_saga_by_quote_id(&quote.id)
+                .await?
+                .is_some()
+            {
+                // Left pending by saga recovery, the backend still reports it in flight
+                continue;
+            }
+
+            if quote.request_lookup_id.is_none() {
+                tracing::warn!(
+                    "Pending melt quote {} has no lookup_id, cannot check payment status. Manual intervention required.",
+                    quote.id
+                );
+                continue;
+            }
+
+            let payment_response = match self.check_melt_payment_status(&quote).await {
+                Ok(payment_response) => payment_response,
+                Err(err) => {
+                    tracing::warn!(
+                        "Failed to check payment status of pending melt quote {}: {}. Leaving it pending.",
+                        quote.id,
+                        err
+                    );
+                    continue;
+                }
+            };
+
+            let outcome = match payment_response.status {
+                MeltQuoteState::Paid => super::melt::shared::finalize_melt_quote(
+                    self,
+                    &self.localstore,
+                    &self.pubsub_manager,
+                    &quote,
+                    payment_response.total_spent,
+                    payment_response.payment_proof,
+                    &payment_response.payment_lookup_id,
+                    None,
+                )
+                .await
+                .map(|_| ()),
+                MeltQuoteState::Unpaid | MeltQuoteState::Failed => {
+                    self.rollback_pending_melt_quote(&quote).await
+                }
+                MeltQuoteState::Pending | MeltQuoteState::Unknown => {
+                    tracing::info!(
+                        "Melt quote {} is {} on LN backend, leaving it pending",
+                        quote.id,
+                        payment_response.status
+                    );
+                    continue;
+                }
+            };
+
+            match outcome {
+                Ok(()) => {
+                    tracing::info!(
+                        "Resolved pending melt quote {} as {}",
+                        quote.id,
+                        payment_response.status
+                    );
+                    resolved += 1;
+                }
+                Err(err) => {
+                    tracing::error!(
+                        "Failed to resolve pending melt quote {} as {}: {}",
+                        quote.id,
+                        payment_response.status,
+                        err
+                    );
+                }
+            }
+        }
+
+        Ok(resolved)
+    }
+
+    /// Rolls back a pending melt quote that has no saga, whose payment failed
+    async fn rollback_pending_melt_quote(&self, quote: &MeltQuote) -> Result<(), Error> {
+        let mut tx = self.localstore.begin_transaction().await?;
+        let input_ys = tx.get_proof_ys_by_quote_id(&quote.id).await?;
+        let blinded_secrets: Vec<_> = tx
+            .get_melt_request_and_blinded_messages(&quote.id)
+            .await?
+            .map(|info| {
+                info.change_outputs
+                    .iter()
+                    .map(|output| output.blinded_secret)
+                    .collect()
+            })
+            .unwrap_or_default();
+        tx.rollback().await?;
+
+        if input_ys.is_empty() && blinded_secrets.is_empty() {
+            tracing::warn!(
+                "Pending melt quote {} has no proofs or change outputs to roll back",
+                quote.id
+            );
+            return Err(Error::UnknownPaymentState);
+        }
+
+        // No saga exists, the id only labels the rollback
+        super::melt::shared::rollback_melt_quote(
+            &self.localstore,
+            &self.pubsub_manager,
+            &quote.id,
+            &input_ys,
+            &blinded_secrets,
+            &uuid::Uuid::new_v4(),
+        )
+        .await
+    }
+
     /// Handle pending melt quote by resuming the saga
     pub(crate) async fn handle_pending_melt_quote(
         &self,
@@ -17,6 +17,7 @@ use cdk_common::{
     MintQuoteCustomResponse, MintQuoteOnchainResponse, MintQuoteState, NotificationPayload,
     ProofState, PublicKey, QuoteId,
 };
+use tokio_util::sync::CancellationToken;
 
 use super::Mint;
 use crate::event::MintEvent;
@@ -206,17 +207,28 @@ impl Spec for MintPubSubSpec {
 
 /// PubsubManager
 #[allow(missing_debug_implementations)]
-pub struct PubSubManager(Pubsub<MintPubSubSpec>);
+pub struct PubSubManager(Pubsub<MintPubSubSpec>, CancellationToken);
 
 impl PubSubManager {
     /// Create a new instance
+    ///
+    /// Subscribers are told to close their streams once `shutdown` is cancelled.
     pub fn new(
         context: (
             DynMintDatabase,
             Arc<HashMap<PaymentProcessorKey, DynMintPayment>>,
         ),
+        shutdown: CancellationToken,
     ) -> Arc<Self> {
-        Arc::new(Self(Pubsub::new(MintPubSubSpec::new_instance(context))))
+        Arc::new(Self(
+            Pubsub::new(MintPubSubSpec::new_instance(context)),
+            shutdown,
+        ))
+    }
+
+    /// Resolves once the mint shuts down, long lived subscribers should then close
+    pub async fn cancelled(&self) {
+        self.1.cancelled().await
     }
 
     /// Helper function to emit a ProofState status
@@ -2,6 +2,7 @@ use cdk_common::SpendingConditionVerification;
 use swap_saga::SwapSaga;
 use tracing::instrument;
 
+use super::retry::retry_transient;
 use super::{Mint, SwapRequest, SwapResponse};
 use crate::Error;
 
@@ -17,6 +18,14 @@ impl Mint {
         &self,
         swap_request: SwapRequest,
     ) -> Result<SwapResponse, Error> {
+        self.swap(&swap_request).await
+    }
+
+    /// Swap, keeping the request with the caller
+    ///
+    /// Lets the caller use the request once the swap failed, without cloning it up front.
+    #[instrument(skip_all)]
+    pub async fn swap(&self, swap_request: &SwapRequest) -> Result<SwapResponse, Error> {
         #[cfg(feature = "prometheus")]
         let metrics = super::MintMetricGuard::new("process_swap_request");
 
@@ -42,29 +51,36 @@ impl Mint {
 
             // Verify spending conditions (NUT-10/NUT-11/NUT-14), i.e. P2PK
             // and HTLC (including SIGALL)
-            swap_request.verify_spending_conditions()?;
-
-            // Step 1: Initialize the swap saga
-            let init_saga =
-                SwapSaga::new(self, self.localstore.clone(), self.pubsub_manager.clone());
-
-            // Step 2: TX1 - Setup swap (verify balance + add inputs as pending + add output blinded messages)
-            let setup_saga = init_saga
-                .setup_swap(
-                    swap_request.inputs(),
-                    swap_request.outputs(),
-                    None,
-                    input_verification,
-                )
-                .await?;
-
-            // Step 3: Blind sign outputs (no DB transaction)
-            let signed_saga = setup_saga.sign_outputs().await?;
-
-            // Step 4: TX2 - Finalize swap (add signatures + mark inputs spent)
-            let response = signed_saga.finalize().await?;
-
-            Ok(response)
+            swap_request.verify_spending_conditions_with_grace(self.clock_skew_grace_secs)?;
+
+            // Refuse a replay of inputs being swapped before touching the database
+            let _claim = self.claim_inputs(input_proofs)?;
+
+            // A failed attempt is compensated, so the whole saga runs again on a transient
+            // database failure
+            let input_verification = &input_verification;
+            retry_transient(|| async move {
+                // Step 1: Initialize the swap saga
+                let init_saga =
+                    SwapSaga::new(self, self.localstore.clone(), self.pubsub_manager.clone());
+
+                // Step 2: TX1 - Setup swap (verify balance + add inputs as pending + add output blinded messages)
+                let setup_saga = init_saga
+                    .setup_swap(
+                        swap_request.inputs(),
+                        swap_request.outputs(),
+                        None,
+                        input_verification.clone(),
+                    )
+                    .await?;
+
+                // Step 3: Blind sign outputs (no DB transaction)
+                let signed_saga = setup_saga.sign_outputs().await?;
+
+                // Step 4: TX2 - Finalize swap (add signatures + mark inputs spent)
+                signed_saga.finalize().await
+            })
+            .await
         }
         .await;
 
@@ -1366,3 +1366,59 @@ async fn test_sig_all_should_reject_if_the_output_amounts_are_swapped() {
     );
     println!("✓ Swap succeeded after restoring original amounts!");
 }
+
+/// Test: verification report of a SIG_ALL swap with a missing transaction signature
+///
+/// The SIG_ALL signature covers the whole request, so the report names the spending
+/// conditions stage without blaming any single input, and no report is produced once the
+/// request is signed.
+#[tokio::test]
+async fn test_p2pk_sig_all_verification_report() {
+    let test_mint = TestMintHelper::new().await.unwrap();
+    let mint = test_mint.mint();
+
+    let (alice_secret, alice_pubkey) = create_test_keypair();
+
+    let input_amount = Amount::from(10);
+    let input_proofs = test_mint.mint_proofs(input_amount).await.unwrap();
+
+    let spending_conditions = SpendingConditions::new_p2pk(
+        alice_pubkey,
+        Some(Conditions::new(None, None, None, None, Some(SigFlag::SigAll), None).unwrap()),
+    );
+
+    let split_amounts = test_mint.split_amount(input_amount).unwrap();
+    let (p2pk_outputs, blinding_factors, secrets) = unzip3(
+        split_amounts
+            .iter()
+            .map(|&amt| test_mint.create_blinded_message(amt, &spending_conditions))
+            .collect(),
+    );
+
+    let swap_request = cdk_common::nuts::SwapRequest::new(input_proofs, p2pk_outputs);
+    let swap_response = mint.process_swap_request(swap_request).await.unwrap();
+
+    let p2pk_proofs = construct_proofs(
+        swap_response.signatures,
+        blinding_factors,
+        secrets,
+        &test_mint.public_keys_of_the_active_sat_keyset,
+    )
+    .unwrap();
+
+    let (new_outputs, _) = create_test_blinded_messages(mint, input_amount)
+        .await
+        .unwrap();
+    let mut swap_request = cdk_common::nuts::SwapRequest::new(p2pk_proofs, new_outputs);
+
+    let report = mint
+        .swap_verification_report(&swap_request)
+        .await
+        .expect("Unsigned SIG_ALL swap should be reported");
+    assert_eq!(report.stage, "spending_conditions");
+    assert!(report.inputs.is_empty());
+    assert!(report.outputs.is_empty());
+
+    swap_request.sign_sig_all(alice_secret).unwrap();
+    assert!(mint.swap_verification_report(&swap_request).await.is_none());
+}
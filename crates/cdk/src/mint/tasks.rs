@@ -0,0 +1,321 @@
+//! Supervision of the mint background tasks
+//!
+//! Every task the mint spawns goes through the [`TaskSupervisor`], so a task that fails or
+//! panics is logged and reported by [`Mint::task_health`] instead of dying silently. Long running
+//! tasks are restarted following their [`RestartPolicy`], and [`Mint::stop`] waits for all of
+//! them.
+
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::sync::{Arc, Mutex};
+
+use futures::FutureExt;
+use tokio::task::JoinSet;
+use tokio_util::sync::CancellationToken;
+
+use super::{Mint, RestartPolicy};
+use crate::error::Error;
+use crate::util::unix_time;
+
+/// State of a supervised task
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskStatus {
+    /// The task is running
+    Running,
+    /// The task failed or panicked and waits to be restarted
+    Restarting,
+    /// The task returned successfully
+    Finished,
+    /// The task failed and will not be restarted, or a task spawned once per request failed
+    Failed,
+    /// The task panicked and will not be restarted
+    Panicked,
+}
+
+/// Health of a task spawned by the mint, see [`Mint::task_health`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskHealth {
+    /// Name of the task
+    pub name: String,
+    /// Current state
+    pub status: TaskStatus,
+    /// Times the task failed or panicked
+    pub failures: u32,
+    /// Error or panic message of the last failure
+    pub last_error: Option<String>,
+    /// Unix timestamp of the last status change
+    pub updated_at: u64,
+}
+
+/// Owner of the tasks spawned by the mint
+#[derive(Debug, Default)]
+pub(crate) struct TaskSupervisor {
+    /// Long running tasks, joined on [`TaskSupervisor::join`]
+    tasks: Mutex<JoinSet<()>>,
+    /// Health of every task by name
+    health: Arc<Mutex<BTreeMap<String, TaskHealth>>>,
+}
+
+/// Outcome of a single run of a task
+enum RunOutcome {
+    Finished,
+    Failed(String),
+    Panicked(String),
+}
+
+/// Run `task` to completion, catching its panic
+async fn run<Fut>(task: Fut) -> RunOutcome
+where
+    Fut: Future<Output = Result<(), Error>>,
+{
+    match AssertUnwindSafe(task).catch_unwind().await {
+        Ok(Ok(())) => RunOutcome::Finished,
+        Ok(Err(err)) => RunOutcome::Failed(err.to_string()),
+        Err(panic) => RunOutcome::Panicked(panic_message(panic.as_ref())),
+    }
+}
+
+fn panic_message(panic: &(dyn Any + Send)) -> String {
+    panic
+        .downcast_ref::<&str>()
+        .map(|message| (*message).to_owned())
+        .or_else(|| panic.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_owned())
+}
+
+/// Update the health of `name`
+fn report(
+    health: &Mutex<BTreeMap<String, TaskHealth>>,
+    name: &str,
+    status: TaskStatus,
+    error: Option<String>,
+) {
+    let mut health = health.lock().unwrap_or_else(|err| err.into_inner());
+    let entry = health.entry(name.to_owned()).or_insert_with(|| TaskHealth {
+        name: name.to_owned(),
+        status,
+        failures: 0,
+        last_error: None,
+        updated_at: 0,
+    });
+
+    entry.status = status;
+    if error.is_some() {
+        entry.failures += 1;
+        entry.last_error = error;
+    }
+    entry.updated_at = unix_time();
+}
+
+impl TaskSupervisor {
+    /// Spawn the long running task `name`, made by `make_task`
+    ///
+    /// A task that fails or panics is made again and restarted after the delay of `restart`, or
+    /// left failed without a policy or once the policy gives up. Nothing is restarted after
+    /// `shutdown` is cancelled.
+    pub(crate) fn spawn<F, Fut>(
+        &self,
+        name: &str,
+        restart: Option<RestartPolicy>,
+        shutdown: CancellationToken,
+        make_task: F,
+    ) where
+        F: Fn() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<(), Error>> + Send + 'static,
+    {
+        let name = name.to_owned();
+        let health = Arc::clone(&self.health);
+
+        self.tasks
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .spawn(async move {
+                let mut failures = 0;
+                loop {
+                    report(&health, &name, TaskStatus::Running, None);
+
+                    let (status, error) = match run(make_task()).await {
+                        RunOutcome::Finished => {
+                            report(&health, &name, TaskStatus::Finished, None);
+                            return;
+                        }
+                        RunOutcome::Failed(err) => {
+                            tracing::error!("Mint task {} failed: {}", name, err);
+                            (TaskStatus::Failed, err)
+                        }
+                        RunOutcome::Panicked(panic) => {
+                            tracing::error!("Mint task {} panicked: {}", name, panic);
+                            (TaskStatus::Panicked, panic)
+                        }
+                    };
+
+                    failures += 1;
+                    let policy = match restart {
+                        Some(policy) if !policy.gives_up(failures) && !shutdown.is_cancelled() => {
+                            policy
+                        }
+                        _ => {
+                            report(&health, &name, status, Some(error));
+                            return;
+                        }
+                    };
+
+                    report(&health, &name, TaskStatus::Restarting, Some(error));
+                    tokio::select! {
+                        _ = shutdown.cancelled() => return,
+                        _ = tokio::time::sleep(policy.delay(failures)) => {}
+                    }
+                }
+            });
+    }
+
+    /// Spawn a task that runs once, such as the notifications sent after a request
+    ///
+    /// The task is not waited for on [`TaskSupervisor::join`]. Its failures and panics are
+    /// logged and counted in the health of `name`.
+    pub(crate) fn spawn_detached<Fut>(&self, name: &'static str, task: Fut)
+    where
+        Fut: Future<Output = Result<(), Error>> + Send + 'static,
+    {
+        let health = Arc::clone(&self.health);
+
+        tokio::spawn(async move {
+            let (status, error) = match run(task).await {
+                RunOutcome::Finished => return,
+                RunOutcome::Failed(err) => {
+                    tracing::error!("Mint task {} failed: {}", name, err);
+                    (TaskStatus::Failed, err)
+                }
+                RunOutcome::Panicked(panic) => {
+                    tracing::error!("Mint task {} panicked: {}", name, panic);
+                    (TaskStatus::Panicked, panic)
+                }
+            };
+
+            report(&health, name, status, Some(error));
+        });
+    }
+
+    /// Wait for the long running tasks, once their shutdown token is cancelled
+    ///
+    /// Fails with [`Error::Internal`] if a task could not be joined.
+    pub(crate) async fn join(&self) -> Result<(), Error> {
+        let mut tasks =
+            std::mem::take(&mut *self.tasks.lock().unwrap_or_else(|err| err.into_inner()));
+
+        let mut result = Ok(());
+        while let Some(joined) = tasks.join_next().await {
+            if let Err(join_error) = joined {
+                tracing::error!("Mint task could not be joined: {}", join_error);
+                result = Err(Error::Internal);
+            }
+        }
+        result
+    }
+
+    /// Health of every task spawned so far
+    pub(crate) fn health(&self) -> Vec<TaskHealth> {
+        self.health
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .values()
+            .cloned()
+            .collect()
+    }
+}
+
+impl Mint {
+    /// Health of the background tasks of the mint
+    ///
+    /// Lists the long running tasks, such as the payment event listener, and the tasks spawned
+    /// per request that failed at least once.
+    pub fn task_health(&self) -> Vec<TaskHealth> {
+        self.tasks.health()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::time::Duration;
+
+    use super::*;
+
+    fn policy() -> RestartPolicy {
+        RestartPolicy {
+            initial_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(1),
+            max_failures: Some(3),
+        }
+    }
+
+    #[tokio::test]
+    async fn panicking_task_is_restarted_until_the_policy_gives_up() {
+        let supervisor = TaskSupervisor::default();
+        let runs = Arc::new(AtomicU32::new(0));
+
+        let counter = Arc::clone(&runs);
+        supervisor.spawn(
+            "panics",
+            Some(policy()),
+            CancellationToken::new(),
+            move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+                async { panic!("boom") }
+            },
+        );
+        supervisor.join().await.expect("join");
+
+        assert_eq!(runs.load(Ordering::SeqCst), 3);
+        let health = supervisor.health();
+        assert_eq!(health.len(), 1);
+        assert_eq!(health[0].status, TaskStatus::Panicked);
+        assert_eq!(health[0].failures, 3);
+        assert_eq!(health[0].last_error.as_deref(), Some("boom"));
+    }
+
+    #[tokio::test]
+    async fn failing_task_recovers_after_restart() {
+        let supervisor = TaskSupervisor::default();
+        let runs = Arc::new(AtomicU32::new(0));
+
+        let counter = Arc::clone(&runs);
+        supervisor.spawn(
+            "flaky",
+            Some(policy()),
+            CancellationToken::new(),
+            move || {
+                let run = counter.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    if run == 0 {
+                        Err(Error::Internal)
+                    } else {
+                        Ok(())
+                    }
+                }
+            },
+        );
+        supervisor.join().await.expect("join");
+
+        let health = supervisor.health();
+        assert_eq!(health[0].status, TaskStatus::Finished);
+        assert_eq!(health[0].failures, 1);
+        assert!(health[0].last_error.is_some());
+    }
+
+    #[tokio::test]
+    async fn task_without_policy_is_not_restarted() {
+        let supervisor = TaskSupervisor::default();
+
+        supervisor.spawn("once", None, CancellationToken::new(), || async {
+            Err(Error::Internal)
+        });
+        supervisor.join().await.expect("join");
+
+        let health = supervisor.health();
+        assert_eq!(health[0].status, TaskStatus::Failed);
+        assert_eq!(health[0].failures, 1);
+    }
+}
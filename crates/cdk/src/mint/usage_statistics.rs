@@ -0,0 +1,77 @@
+//! Coarse usage statistics for mint transparency pages
+//!
+//! Operators can publish how much their mint is used without exposing its users. The database
+//! aggregates mint and melt quotes per day and unit, and the volumes are rounded down to a power
+//! of two before they leave the mint, so a single large payment cannot be picked out of a quiet
+//! day. The statistics are off unless enabled with [`Mint::with_usage_statistics`].
+
+use cdk_common::database::DailyUsage;
+use tracing::instrument;
+
+use super::Mint;
+use crate::error::Error;
+use crate::Amount;
+
+/// Largest power of two not above `amount`, zero stays zero
+fn volume_bucket(amount: Amount) -> Amount {
+    match amount.to_u64() {
+        0 => Amount::ZERO,
+        value => Amount::from(1_u64 << (u64::BITS - 1 - value.leading_zeros())),
+    }
+}
+
+impl Mint {
+    /// Whether [`Mint::usage_statistics`] may be queried
+    pub fn usage_statistics_enabled(&self) -> bool {
+        self.usage_statistics
+    }
+
+    /// Mint and melt activity per day and unit from `since` on, oldest day first
+    ///
+    /// Counts are exact, volumes are rounded down to a power of two.
+    #[instrument(skip(self))]
+    pub async fn usage_statistics(&self, since: u64) -> Result<Vec<DailyUsage>, Error> {
+        if !self.usage_statistics {
+            return Err(Error::Custom(
+                "Usage statistics are not enabled".to_string(),
+            ));
+        }
+
+        Ok(self
+            .localstore
+            .get_daily_usage(since)
+            .await?
+            .into_iter()
+            .map(|usage| DailyUsage {
+                mint_volume: volume_bucket(usage.mint_volume),
+                melt_volume: volume_bucket(usage.melt_volume),
+                ..usage
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::mint::create_test_mint;
+
+    #[test]
+    fn volumes_round_down_to_a_power_of_two() {
+        let buckets: Vec<u64> = [0, 1, 2, 3, 700, 1024, u64::MAX]
+            .into_iter()
+            .map(|amount| volume_bucket(Amount::from(amount)).to_u64())
+            .collect();
+
+        assert_eq!(buckets, vec![0, 1, 2, 2, 512, 1024, 1 << 63]);
+    }
+
+    #[tokio::test]
+    async fn statistics_are_opt_in() {
+        let mint = create_test_mint().await.unwrap();
+        assert!(mint.usage_statistics(0).await.is_err());
+
+        let mint = mint.with_usage_statistics(true);
+        assert!(mint.usage_statistics(0).await.unwrap().is_empty());
+    }
+}
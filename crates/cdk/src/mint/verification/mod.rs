@@ -1,12 +1,23 @@
 use std::collections::HashSet;
 
-use cdk_common::{Amount, BlindedMessage, CurrencyUnit, Id, Proofs, ProofsMethods, PublicKey};
+use cdk_common::{
+    Amount, BlindedMessage, CurrencyUnit, Id, MeltRequest, Proofs, ProofsMethods, PublicKey,
+    SpendingConditionVerification, SwapRequest,
+};
 use tracing::instrument;
 
 use super::{Error, Mint};
 
-/// Maximum allowed length in bytes for proof secret or witness content
-const MAX_PROOF_CONTENT_LEN: usize = 1024;
+mod pipeline;
+
+pub use pipeline::{
+    BalanceVerifier, DuplicatesVerifier, KeysetVerifier, LimitsVerifier, SignatureVerifier,
+    SpendingConditionsVerifier, VerificationPipeline, VerificationReport, Verifier,
+};
+
+/// Default maximum length in bytes for proof secret or witness content, see
+/// [`Mint::with_max_proof_content_len`]
+pub const MAX_PROOF_CONTENT_LEN: usize = 1024;
 
 /// Maximum allowed length in bytes for request fields (description, extra)
 pub(crate) const MAX_REQUEST_FIELD_LEN: usize = 1024;
@@ -18,6 +29,31 @@ pub struct Verification {
     pub amount: Amount<CurrencyUnit>,
 }
 
+/// Indices of the inputs of `request` whose witness does not unlock them
+///
+/// A `SIG_ALL` signature commits to every input and output of the request, so when an input
+/// is locked with it no single input is blamed and the list is empty.
+fn offending_witnesses(
+    request: &(dyn SpendingConditionVerification + Sync),
+    grace_secs: u64,
+) -> Vec<usize> {
+    if request.has_at_least_one_sig_all().unwrap_or(true) {
+        return Vec::new();
+    }
+
+    request
+        .inputs()
+        .iter()
+        .enumerate()
+        .filter(|(_, proof)| {
+            SwapRequest::new(vec![(*proof).clone()], Vec::new())
+                .verify_spending_conditions_with_grace(grace_secs)
+                .is_err()
+        })
+        .map(|(index, _)| index)
+        .collect()
+}
+
 impl Mint {
     /// Verify that the inputs to the transaction are unique
     #[instrument(skip_all)]
@@ -76,7 +112,7 @@ impl Mint {
                         );
                         return Err(Error::InactiveKeyset);
                     }
-                    if keyset.is_expired() {
+                    if keyset.is_expired() || self.is_keyset_retired(id) {
                         tracing::debug!(
                             "Transaction attempted with expired keyset in outputs: {}.",
                             id
@@ -121,7 +157,7 @@ impl Mint {
         for id in &inputs_keyset_ids {
             match self.get_keyset_info(id) {
                 Some(keyset) => {
-                    if keyset.is_expired() {
+                    if keyset.is_expired() || self.is_keyset_retired(id) {
                         tracing::debug!(
                             "Transaction attempted with expired keyset in inputs: {}.",
                             id
@@ -156,7 +192,8 @@ impl Mint {
 
     /// Verifies outputs
     ///
-    /// Checks outputs are unique, of the same unit and not signed before.
+    /// Runs the outputs through the verification pipeline, which by default checks outputs are
+    /// unique, of the same unit and within the limits.
     /// Returns an error if outputs are empty - callers should guard against
     /// empty outputs before calling this function.
     #[instrument(skip_all)]
@@ -166,129 +203,159 @@ impl Mint {
             return Err(Error::TransactionUnbalanced(0, 0, 0));
         }
 
-        // Check max outputs limit
-        let outputs_count = outputs.len();
-        if outputs_count > self.max_outputs {
-            tracing::warn!(
-                "Mint request exceeds max outputs limit: {} > {}",
-                outputs_count,
-                self.max_outputs
-            );
-            return Err(Error::MaxOutputsExceeded {
-                actual: outputs_count,
-                max: self.max_outputs,
-            });
-        }
-
-        Mint::check_outputs_unique(outputs)?;
-
-        let unit = self.verify_outputs_keyset(outputs)?;
-
-        let amount = Amount::try_sum(outputs.iter().map(|o| o.amount))?.with_unit(unit);
+        self.verification_pipeline.verify_outputs(self, outputs)?;
 
-        Ok(Verification { amount })
+        self.outputs_verification(outputs)
     }
 
     /// Verifies inputs
     ///
-    /// Checks that inputs are unique and of the same unit.
+    /// Runs the inputs through the verification pipeline, which by default checks that inputs
+    /// are unique, of the same unit and correctly signed.
     /// **NOTE: This does not check if inputs have been spent
     #[instrument(skip_all)]
     pub async fn verify_inputs(&self, inputs: &Proofs) -> Result<Verification, Error> {
-        // Check max inputs limit
-        let inputs_count = inputs.len();
-        if inputs_count > self.max_inputs {
-            tracing::warn!(
-                "Melt request exceeds max inputs limit: {} > {}",
-                inputs_count,
-                self.max_inputs
-            );
-            return Err(Error::MaxInputsExceeded {
-                actual: inputs_count,
-                max: self.max_inputs,
-            });
-        }
+        self.verification_pipeline
+            .verify_inputs(self, inputs)
+            .await?;
+
+        self.inputs_verification(inputs)
+    }
+
+    /// Verify that inputs and outputs are valid and balanced
+    #[instrument(skip_all)]
+    pub async fn verify_transaction_balanced(
+        &self,
+        input_verification: Verification,
+        output_verification: Verification,
+        inputs: &Proofs,
+    ) -> Result<(), Error> {
+        self.verification_pipeline
+            .verify_transaction(self, inputs, &input_verification, &output_verification)
+            .await
+    }
+
+    /// Report of the verification stage refusing a swap request
+    ///
+    /// Returns `None` when the request passes verification. The report is meant for error
+    /// responses, the request is verified again to build it.
+    #[instrument(skip_all)]
+    pub async fn swap_verification_report(
+        &self,
+        request: &SwapRequest,
+    ) -> Option<VerificationReport> {
+        self.verification_report(request, request.outputs(), true)
+            .await
+    }
 
-        // Check proof content lengths (secret and witness) are within limits
-        for proof in inputs {
-            let secret_len = proof.secret.len();
-            if secret_len > MAX_PROOF_CONTENT_LEN {
-                tracing::warn!(
-                    "Proof secret exceeds max content length: {} > {}",
-                    secret_len,
-                    MAX_PROOF_CONTENT_LEN
-                );
-                return Err(Error::ProofContentTooLarge {
-                    actual: secret_len,
-                    max: MAX_PROOF_CONTENT_LEN,
+    /// Report of the verification stage refusing a melt request
+    ///
+    /// See [`Mint::swap_verification_report`].
+    #[instrument(skip_all)]
+    pub async fn melt_verification_report<Q>(
+        &self,
+        request: &MeltRequest<Q>,
+    ) -> Option<VerificationReport>
+    where
+        Q: std::fmt::Display + Sync,
+    {
+        let outputs = request.outputs().as_deref().unwrap_or_default();
+        self.verification_report(request, outputs, false).await
+    }
+
+    async fn verification_report(
+        &self,
+        request: &(dyn SpendingConditionVerification + Sync),
+        outputs: &[BlindedMessage],
+        balanced: bool,
+    ) -> Option<VerificationReport> {
+        let inputs = request.inputs();
+        let stages = self.verification_pipeline.stages();
+
+        for stage in stages {
+            if stage.verify_inputs(self, inputs).await.is_err() {
+                return Some(VerificationReport {
+                    stage: stage.name().to_string(),
+                    inputs: stage.offending_inputs(self, inputs).await,
+                    outputs: Vec::new(),
                 });
             }
+        }
 
-            if let Some(witness) = &proof.witness {
-                let witness_str = serde_json::to_string(witness)?;
-                let witness_len = witness_str.len();
-                if witness_len > MAX_PROOF_CONTENT_LEN {
-                    tracing::warn!(
-                        "Proof witness exceeds max content length: {} > {}",
-                        witness_len,
-                        MAX_PROOF_CONTENT_LEN
-                    );
-                    return Err(Error::ProofContentTooLarge {
-                        actual: witness_len,
-                        max: MAX_PROOF_CONTENT_LEN,
+        // Witnesses are verified against the whole request, as swaps and melts do
+        if request
+            .verify_spending_conditions_with_grace(self.clock_skew_grace_secs)
+            .is_err()
+        {
+            return Some(VerificationReport {
+                stage: SpendingConditionsVerifier::NAME.to_string(),
+                inputs: offending_witnesses(request, self.clock_skew_grace_secs),
+                outputs: Vec::new(),
+            });
+        }
+
+        if !outputs.is_empty() {
+            for stage in stages {
+                if stage.verify_outputs(self, outputs).is_err() {
+                    return Some(VerificationReport {
+                        stage: stage.name().to_string(),
+                        inputs: Vec::new(),
+                        outputs: stage.offending_outputs(self, outputs),
                     });
                 }
             }
         }
 
-        Mint::check_inputs_unique(inputs)?;
-        let unit = self.verify_inputs_keyset(inputs).await?;
+        if !balanced {
+            return None;
+        }
 
-        if unit == CurrencyUnit::Auth {
-            return Err(Error::UnsupportedUnit);
+        let input_verification = self.inputs_verification(inputs).ok()?;
+        let output_verification = self.outputs_verification(outputs).ok()?;
+        for stage in stages {
+            if stage
+                .verify_transaction(self, inputs, &input_verification, &output_verification)
+                .await
+                .is_err()
+            {
+                return Some(VerificationReport {
+                    stage: stage.name().to_string(),
+                    ..Default::default()
+                });
+            }
         }
 
-        let amount = inputs.total_amount()?.with_unit(unit);
+        None
+    }
 
-        self.verify_proofs(inputs.clone()).await?;
+    /// Total of verified inputs, the keyset checks are left to the pipeline
+    fn inputs_verification(&self, inputs: &Proofs) -> Result<Verification, Error> {
+        let unit = inputs
+            .first()
+            .ok_or(Error::UnknownKeySet)
+            .and_then(|proof| self.keyset_unit(&proof.keyset_id))?;
 
-        Ok(Verification { amount })
+        Ok(Verification {
+            amount: inputs.total_amount()?.with_unit(unit),
+        })
     }
 
-    /// Verify that inputs and outputs are valid and balanced
-    #[instrument(skip_all)]
-    pub async fn verify_transaction_balanced(
-        &self,
-        input_verification: Verification,
-        output_verification: Verification,
-        inputs: &Proofs,
-    ) -> Result<(), Error> {
-        let fee_breakdown = self.get_proofs_fee(inputs).await?;
+    /// Total of verified outputs, the keyset checks are left to the pipeline
+    fn outputs_verification(&self, outputs: &[BlindedMessage]) -> Result<Verification, Error> {
+        let unit = outputs
+            .first()
+            .ok_or(Error::UnknownKeySet)
+            .and_then(|output| self.keyset_unit(&output.keyset_id))?;
 
-        // Units are now embedded in the typed amounts - check they match
-        if output_verification.amount.unit() != input_verification.amount.unit() {
-            tracing::debug!(
-                "Output unit {:?} does not match input unit {:?}",
-                output_verification.amount.unit(),
-                input_verification.amount.unit()
-            );
-            return Err(Error::UnitMismatch);
-        }
-
-        // Check amounts are balanced (inputs = outputs + fee)
-        let fee_typed = fee_breakdown
-            .total
-            .with_unit(input_verification.amount.unit().clone());
-        let expected_output = input_verification.amount.checked_sub(&fee_typed)?;
-
-        if output_verification.amount != expected_output {
-            return Err(Error::TransactionUnbalanced(
-                input_verification.amount.value(),
-                output_verification.amount.value(),
-                fee_breakdown.total.into(),
-            ));
-        }
+        Ok(Verification {
+            amount: Amount::try_sum(outputs.iter().map(|o| o.amount))?.with_unit(unit),
+        })
+    }
 
-        Ok(())
+    /// Unit of a keyset
+    fn keyset_unit(&self, keyset_id: &Id) -> Result<CurrencyUnit, Error> {
+        self.get_keyset_info(keyset_id)
+            .map(|keyset| keyset.unit)
+            .ok_or(Error::UnknownKeySet)
     }
 }
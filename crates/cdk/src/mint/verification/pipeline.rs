@@ -0,0 +1,638 @@
+//! Verification pipeline
+//!
+//! Inputs, outputs and balanced transactions are checked by an ordered list of [`Verifier`]
+//! stages. The default pipeline holds the built-in stages; forks can insert their own stages
+//! around them, or replace them, with [`MintBuilder::with_verification_pipeline`].
+//!
+//! [`MintBuilder::with_verification_pipeline`]: crate::mint::MintBuilder::with_verification_pipeline
+
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use cdk_common::{BlindedMessage, CurrencyUnit, Proofs};
+use serde::{Deserialize, Serialize};
+
+use super::Verification;
+use crate::mint::{Error, Mint};
+
+/// A stage of the [`VerificationPipeline`]
+///
+/// Every check defaults to accepting, so a stage only implements the checks it cares about.
+#[async_trait]
+pub trait Verifier: Send + Sync {
+    /// Unique name of the stage, used to position other stages around it
+    fn name(&self) -> &str;
+
+    /// Verify the inputs of a swap or melt
+    async fn verify_inputs(&self, _mint: &Mint, _inputs: &Proofs) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Verify the outputs of a mint, swap or melt
+    fn verify_outputs(&self, _mint: &Mint, _outputs: &[BlindedMessage]) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Indices of the inputs refused by [`Verifier::verify_inputs`]
+    ///
+    /// Only called to build a [`VerificationReport`] once the stage refused `inputs`. Defaults
+    /// to verifying every input on its own; an empty list blames the inputs as a whole.
+    async fn offending_inputs(&self, mint: &Mint, inputs: &Proofs) -> Vec<usize> {
+        let mut offending = Vec::new();
+        for (index, proof) in inputs.iter().enumerate() {
+            if self
+                .verify_inputs(mint, &vec![proof.clone()])
+                .await
+                .is_err()
+            {
+                offending.push(index);
+            }
+        }
+        offending
+    }
+
+    /// Indices of the outputs refused by [`Verifier::verify_outputs`]
+    ///
+    /// Same as [`Verifier::offending_inputs`], for outputs.
+    fn offending_outputs(&self, mint: &Mint, outputs: &[BlindedMessage]) -> Vec<usize> {
+        outputs
+            .iter()
+            .enumerate()
+            .filter(|(_, output)| {
+                self.verify_outputs(mint, std::slice::from_ref(*output))
+                    .is_err()
+            })
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Verify a transaction once its inputs and outputs passed verification
+    async fn verify_transaction(
+        &self,
+        _mint: &Mint,
+        _inputs: &Proofs,
+        _input_verification: &Verification,
+        _output_verification: &Verification,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// Stage that refused a request and the items it blamed
+///
+/// Only indices into the request are reported, never secrets or signatures.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerificationReport {
+    /// Name of the stage that refused the request
+    pub stage: String,
+    /// Indices of the offending inputs
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub inputs: Vec<usize>,
+    /// Indices of the offending outputs
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub outputs: Vec<usize>,
+}
+
+/// Ordered list of [`Verifier`] stages
+///
+/// Stages run in order and the first failing stage aborts the verification.
+#[derive(Clone)]
+pub struct VerificationPipeline {
+    stages: Vec<Arc<dyn Verifier>>,
+}
+
+impl fmt::Debug for VerificationPipeline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.stages.iter().map(|stage| stage.name()))
+            .finish()
+    }
+}
+
+impl Default for VerificationPipeline {
+    /// Pipeline with the built-in stages
+    fn default() -> Self {
+        Self {
+            stages: vec![
+                Arc::new(LimitsVerifier),
+                Arc::new(SpendingConditionsVerifier),
+                Arc::new(DuplicatesVerifier),
+                Arc::new(KeysetVerifier),
+                Arc::new(SignatureVerifier),
+                Arc::new(BalanceVerifier),
+            ],
+        }
+    }
+}
+
+impl VerificationPipeline {
+    /// Pipeline without any stage
+    pub fn empty() -> Self {
+        Self { stages: Vec::new() }
+    }
+
+    /// Names of the stages, in order
+    pub fn stage_names(&self) -> Vec<&str> {
+        self.stages.iter().map(|stage| stage.name()).collect()
+    }
+
+    /// Append a stage at the end of the pipeline
+    pub fn push(mut self, stage: Arc<dyn Verifier>) -> Self {
+        self.stages.push(stage);
+        self
+    }
+
+    /// Insert a stage before the stage named `name`
+    pub fn insert_before(mut self, name: &str, stage: Arc<dyn Verifier>) -> Result<Self, Error> {
+        let index = self.position(name)?;
+        self.stages.insert(index, stage);
+        Ok(self)
+    }
+
+    /// Insert a stage after the stage named `name`
+    pub fn insert_after(mut self, name: &str, stage: Arc<dyn Verifier>) -> Result<Self, Error> {
+        let index = self.position(name)?;
+        self.stages.insert(index + 1, stage);
+        Ok(self)
+    }
+
+    /// Remove the stage named `name`
+    pub fn remove(mut self, name: &str) -> Result<Self, Error> {
+        let index = self.position(name)?;
+        self.stages.remove(index);
+        Ok(self)
+    }
+
+    fn position(&self, name: &str) -> Result<usize, Error> {
+        self.stages
+            .iter()
+            .position(|stage| stage.name() == name)
+            .ok_or_else(|| Error::Custom(format!("Unknown verification stage {name}")))
+    }
+
+    pub(super) fn stages(&self) -> &[Arc<dyn Verifier>] {
+        &self.stages
+    }
+
+    /// Run every stage over `inputs`
+    pub async fn verify_inputs(&self, mint: &Mint, inputs: &Proofs) -> Result<(), Error> {
+        for stage in &self.stages {
+            stage.verify_inputs(mint, inputs).await?;
+        }
+        Ok(())
+    }
+
+    /// Run every stage over `outputs`
+    pub fn verify_outputs(&self, mint: &Mint, outputs: &[BlindedMessage]) -> Result<(), Error> {
+        for stage in &self.stages {
+            stage.verify_outputs(mint, outputs)?;
+        }
+        Ok(())
+    }
+
+    /// Run every stage over a transaction
+    pub async fn verify_transaction(
+        &self,
+        mint: &Mint,
+        inputs: &Proofs,
+        input_verification: &Verification,
+        output_verification: &Verification,
+    ) -> Result<(), Error> {
+        for stage in &self.stages {
+            stage
+                .verify_transaction(mint, inputs, input_verification, output_verification)
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+/// Enforces the input and output count limits, the proof content length and the max
+/// denomination of each unit, see [`MintBuilder::with_limits`] and
+/// [`MintBuilder::with_max_proof_content_len`]
+///
+/// [`MintBuilder::with_limits`]: crate::mint::MintBuilder::with_limits
+/// [`MintBuilder::with_max_proof_content_len`]: crate::mint::MintBuilder::with_max_proof_content_len
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LimitsVerifier;
+
+impl LimitsVerifier {
+    /// Name of the stage
+    pub const NAME: &'static str = "limits";
+}
+
+#[async_trait]
+impl Verifier for LimitsVerifier {
+    fn name(&self) -> &str {
+        Self::NAME
+    }
+
+    async fn verify_inputs(&self, mint: &Mint, inputs: &Proofs) -> Result<(), Error> {
+        let inputs_count = inputs.len();
+        if inputs_count > mint.max_inputs {
+            tracing::warn!(
+                "Melt request exceeds max inputs limit: {} > {}",
+                inputs_count,
+                mint.max_inputs
+            );
+            return Err(Error::MaxInputsExceeded {
+                actual: inputs_count,
+                max: mint.max_inputs,
+            });
+        }
+
+        // Check proof content lengths (secret and witness) are within limits
+        for proof in inputs {
+            let secret_len = proof.secret.len();
+            if secret_len > mint.max_proof_content_len {
+                tracing::warn!(
+                    "Proof secret exceeds max content length: {} > {}",
+                    secret_len,
+                    mint.max_proof_content_len
+                );
+                return Err(Error::ProofContentTooLarge {
+                    actual: secret_len,
+                    max: mint.max_proof_content_len,
+                });
+            }
+
+            if let Some(witness) = &proof.witness {
+                let witness_str = serde_json::to_string(witness)?;
+                let witness_len = witness_str.len();
+                if witness_len > mint.max_proof_content_len {
+                    tracing::warn!(
+                        "Proof witness exceeds max content length: {} > {}",
+                        witness_len,
+                        mint.max_proof_content_len
+                    );
+                    return Err(Error::ProofContentTooLarge {
+                        actual: witness_len,
+                        max: mint.max_proof_content_len,
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn verify_outputs(&self, mint: &Mint, outputs: &[BlindedMessage]) -> Result<(), Error> {
+        let outputs_count = outputs.len();
+        if outputs_count > mint.max_outputs {
+            tracing::warn!(
+                "Mint request exceeds max outputs limit: {} > {}",
+                outputs_count,
+                mint.max_outputs
+            );
+            return Err(Error::MaxOutputsExceeded {
+                actual: outputs_count,
+                max: mint.max_outputs,
+            });
+        }
+
+        if mint.max_denominations.is_empty() {
+            return Ok(());
+        }
+
+        for output in outputs {
+            let Some(max) = mint
+                .get_keyset_info(&output.keyset_id)
+                .and_then(|keyset| mint.max_denomination(&keyset.unit))
+            else {
+                continue;
+            };
+
+            if output.amount > max {
+                tracing::warn!(
+                    "Output exceeds max denomination: {} > {}",
+                    output.amount,
+                    max
+                );
+                return Err(Error::MaxDenominationExceeded {
+                    amount: output.amount,
+                    max,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Refuses inputs locked by a spending condition the mint has disabled
+///
+/// The witnesses themselves are verified against the whole request, see
+/// `SwapRequest::verify_spending_conditions`. Verification reports still name this stage when
+/// a witness is refused, without blaming any input when one of them is locked with `SIG_ALL`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SpendingConditionsVerifier;
+
+impl SpendingConditionsVerifier {
+    /// Name of the stage
+    pub const NAME: &'static str = "spending_conditions";
+}
+
+#[async_trait]
+impl Verifier for SpendingConditionsVerifier {
+    fn name(&self) -> &str {
+        Self::NAME
+    }
+
+    async fn verify_inputs(&self, mint: &Mint, inputs: &Proofs) -> Result<(), Error> {
+        mint.verify_input_conditions_enabled(inputs).await
+    }
+}
+
+/// Refuses duplicated inputs and outputs
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DuplicatesVerifier;
+
+impl DuplicatesVerifier {
+    /// Name of the stage
+    pub const NAME: &'static str = "duplicates";
+}
+
+#[async_trait]
+impl Verifier for DuplicatesVerifier {
+    fn name(&self) -> &str {
+        Self::NAME
+    }
+
+    async fn verify_inputs(&self, _mint: &Mint, inputs: &Proofs) -> Result<(), Error> {
+        Mint::check_inputs_unique(inputs)
+    }
+
+    fn verify_outputs(&self, _mint: &Mint, outputs: &[BlindedMessage]) -> Result<(), Error> {
+        Mint::check_outputs_unique(outputs)
+    }
+
+    async fn offending_inputs(&self, _mint: &Mint, inputs: &Proofs) -> Vec<usize> {
+        let mut seen = HashSet::new();
+        inputs
+            .iter()
+            .enumerate()
+            .filter(|(_, proof)| proof.y().is_ok_and(|y| !seen.insert(y)))
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    fn offending_outputs(&self, _mint: &Mint, outputs: &[BlindedMessage]) -> Vec<usize> {
+        let mut seen = HashSet::new();
+        outputs
+            .iter()
+            .enumerate()
+            .filter(|(_, output)| !seen.insert(&output.blinded_secret))
+            .map(|(index, _)| index)
+            .collect()
+    }
+}
+
+/// Checks that keysets are known, usable and of a single unit
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KeysetVerifier;
+
+impl KeysetVerifier {
+    /// Name of the stage
+    pub const NAME: &'static str = "keyset";
+}
+
+#[async_trait]
+impl Verifier for KeysetVerifier {
+    fn name(&self) -> &str {
+        Self::NAME
+    }
+
+    async fn verify_inputs(&self, mint: &Mint, inputs: &Proofs) -> Result<(), Error> {
+        if mint.verify_inputs_keyset(inputs).await? == CurrencyUnit::Auth {
+            return Err(Error::UnsupportedUnit);
+        }
+        Ok(())
+    }
+
+    fn verify_outputs(&self, mint: &Mint, outputs: &[BlindedMessage]) -> Result<(), Error> {
+        mint.verify_outputs_keyset(outputs).map(|_| ())
+    }
+}
+
+/// Verifies the signatures of the inputs
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SignatureVerifier;
+
+impl SignatureVerifier {
+    /// Name of the stage
+    pub const NAME: &'static str = "signatures";
+}
+
+#[async_trait]
+impl Verifier for SignatureVerifier {
+    fn name(&self) -> &str {
+        Self::NAME
+    }
+
+    async fn verify_inputs(&self, mint: &Mint, inputs: &Proofs) -> Result<(), Error> {
+        mint.verify_proofs(inputs.clone()).await
+    }
+}
+
+/// Checks that the inputs cover the outputs plus the input fee
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BalanceVerifier;
+
+impl BalanceVerifier {
+    /// Name of the stage
+    pub const NAME: &'static str = "balance";
+}
+
+#[async_trait]
+impl Verifier for BalanceVerifier {
+    fn name(&self) -> &str {
+        Self::NAME
+    }
+
+    async fn verify_transaction(
+        &self,
+        mint: &Mint,
+        inputs: &Proofs,
+        input_verification: &Verification,
+        output_verification: &Verification,
+    ) -> Result<(), Error> {
+        let fee_breakdown = mint.get_proofs_fee(inputs).await?;
+
+        // Units are embedded in the typed amounts - check they match
+        if output_verification.amount.unit() != input_verification.amount.unit() {
+            tracing::debug!(
+                "Output unit {:?} does not match input unit {:?}",
+                output_verification.amount.unit(),
+                input_verification.amount.unit()
+            );
+            return Err(Error::UnitMismatch);
+        }
+
+        // Check amounts are balanced (inputs = outputs + fee)
+        let fee_typed = fee_breakdown
+            .total
+            .with_unit(input_verification.amount.unit().clone());
+        let expected_output = input_verification.amount.checked_sub(&fee_typed)?;
+
+        if output_verification.amount != expected_output {
+            return Err(Error::TransactionUnbalanced(
+                input_verification.amount.value(),
+                output_verification.amount.value(),
+                fee_breakdown.total.into(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cdk_common::{Amount, SwapRequest};
+
+    use super::*;
+    use crate::mint::MAX_PROOF_CONTENT_LEN;
+    use crate::test_helpers::mint::{create_test_mint, mint_test_proofs};
+
+    struct MaxInputAmount(Amount);
+
+    #[async_trait]
+    impl Verifier for MaxInputAmount {
+        fn name(&self) -> &str {
+            "max_input_amount"
+        }
+
+        async fn verify_inputs(&self, _mint: &Mint, inputs: &Proofs) -> Result<(), Error> {
+            if inputs.iter().any(|proof| proof.amount > self.0) {
+                return Err(Error::Custom("input too large".to_string()));
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn pipeline_positions_stages() {
+        let pipeline = VerificationPipeline::default()
+            .insert_before(
+                SignatureVerifier::NAME,
+                Arc::new(MaxInputAmount(Amount::ONE)),
+            )
+            .unwrap()
+            .remove(BalanceVerifier::NAME)
+            .unwrap();
+
+        assert_eq!(
+            pipeline.stage_names(),
+            vec![
+                LimitsVerifier::NAME,
+                SpendingConditionsVerifier::NAME,
+                DuplicatesVerifier::NAME,
+                KeysetVerifier::NAME,
+                "max_input_amount",
+                SignatureVerifier::NAME,
+            ]
+        );
+
+        assert!(VerificationPipeline::empty()
+            .insert_after(KeysetVerifier::NAME, Arc::new(KeysetVerifier))
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn custom_stage_runs_with_builtin_stages() {
+        let mint = create_test_mint().await.unwrap();
+        let proofs = mint_test_proofs(&mint, Amount::from(64)).await.unwrap();
+        mint.verify_inputs(&proofs).await.unwrap();
+
+        let mint = mint.with_verification_pipeline(
+            VerificationPipeline::default()
+                .insert_after(KeysetVerifier::NAME, Arc::new(MaxInputAmount(Amount::ONE)))
+                .unwrap(),
+        );
+
+        let err = mint.verify_inputs(&proofs).await.unwrap_err();
+        assert!(matches!(err, Error::Custom(_)));
+
+        // Built-in stages still run ahead of the custom one
+        let mut duplicated = proofs.clone();
+        duplicated.push(proofs[0].clone());
+        let err = mint.verify_inputs(&duplicated).await.unwrap_err();
+        assert!(matches!(err, Error::DuplicateInputs));
+    }
+
+    #[tokio::test]
+    async fn outputs_above_max_denomination_are_refused() {
+        let mint = create_test_mint().await.unwrap();
+        let keyset_id = mint
+            .keysets()
+            .keysets
+            .into_iter()
+            .find(|keyset| keyset.active && keyset.unit == CurrencyUnit::Sat)
+            .unwrap()
+            .id;
+        let output = |amount: u64| {
+            BlindedMessage::new(
+                Amount::from(amount),
+                keyset_id,
+                cdk_common::SecretKey::generate().public_key(),
+            )
+        };
+
+        mint.verify_outputs(&[output(128)]).unwrap();
+
+        let mint = mint.with_max_denominations(
+            [(CurrencyUnit::Sat, Amount::from(64))]
+                .into_iter()
+                .collect(),
+        );
+        mint.verify_outputs(&[output(64)]).unwrap();
+
+        let err = mint.verify_outputs(&[output(32), output(128)]).unwrap_err();
+        assert!(matches!(err, Error::MaxDenominationExceeded { .. }));
+
+        let info = mint.mint_info().await.unwrap();
+        assert_eq!(
+            info.nuts.max_denomination(&CurrencyUnit::Sat),
+            Some(Amount::from(64))
+        );
+    }
+
+    #[tokio::test]
+    async fn proof_content_length_is_configurable() {
+        let mint = create_test_mint().await.unwrap();
+        let proofs = mint_test_proofs(&mint, Amount::from(64)).await.unwrap();
+        assert_eq!(mint.max_proof_content_len(), MAX_PROOF_CONTENT_LEN);
+        mint.verify_inputs(&proofs).await.unwrap();
+
+        let mint = mint.with_max_proof_content_len(16);
+        let err = mint.verify_inputs(&proofs).await.unwrap_err();
+        assert!(matches!(
+            err,
+            Error::ProofContentTooLarge { actual, max: 16 } if actual > 16
+        ));
+    }
+
+    #[tokio::test]
+    async fn report_names_failing_stage_and_inputs() {
+        let mint = create_test_mint().await.unwrap();
+        let proofs = mint_test_proofs(&mint, Amount::from(64)).await.unwrap();
+
+        let mut duplicated = proofs.clone();
+        duplicated.push(proofs[0].clone());
+
+        let report = mint
+            .swap_verification_report(&SwapRequest::new(duplicated, vec![]))
+            .await
+            .unwrap();
+        assert_eq!(
+            report,
+            VerificationReport {
+                stage: DuplicatesVerifier::NAME.to_string(),
+                inputs: vec![proofs.len()],
+                outputs: vec![],
+            }
+        );
+    }
+}
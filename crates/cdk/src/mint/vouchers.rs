@@ -0,0 +1,462 @@
+//! Vouchers pre-issued by the mint
+//!
+//! [`Mint::issue_vouchers`] signs a batch of tokens for gift cards and promotions handed out
+//! outside of the mint. Each voucher is locked with NUT-11 to a claim key shared by the batch, or
+//! with NUT-14 to the hash of a claim code generated for it, so a leaked token is worth nothing
+//! without its key or code. The ecash is issued without a payment and counts as outstanding in
+//! [`Mint::audit_report`] like any other. An expiry sets the locktime after which the refund keys
+//! of the issuer can take back what was not claimed. [`Mint::voucher_report`] tells how many
+//! vouchers of a batch were claimed.
+
+use cdk_common::dhke::construct_proofs;
+use cdk_common::mint::{Operation, OperationKind};
+use cdk_common::util::unix_time;
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+use uuid::Uuid;
+
+use super::{CurrencyUnit, Mint, CDK_MINT_PRIMARY_NAMESPACE};
+use crate::amount::{FeeAndAmounts, SplitTarget};
+use crate::mint_url::MintUrl;
+use crate::nuts::{
+    Conditions, PreMintSecrets, Proofs, PublicKey, SpendingConditions, State, Token,
+};
+use crate::secret::Secret;
+use crate::{ensure_cdk, Amount, Error};
+
+/// Largest number of vouchers issued in a batch
+pub const MAX_VOUCHER_BATCH_SIZE: usize = 1_000;
+
+const CDK_MINT_VOUCHERS_SECONDARY_NAMESPACE: &str = "vouchers";
+
+/// How the vouchers of a batch are locked
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VoucherLock {
+    /// NUT-11 lock to a claim key shared by every voucher of the batch
+    ClaimKey(PublicKey),
+    /// NUT-14 lock to the hash of a claim code generated for each voucher
+    ClaimCode,
+}
+
+/// Batch of vouchers to issue, see [`Mint::issue_vouchers`]
+#[derive(Debug, Clone)]
+pub struct VoucherBatchRequest {
+    /// Url of the mint written in the tokens
+    pub mint_url: MintUrl,
+    /// Unit of the vouchers
+    pub unit: CurrencyUnit,
+    /// Value of each voucher
+    pub amount: Amount,
+    /// Number of vouchers
+    pub count: usize,
+    /// How the vouchers are locked
+    pub lock: VoucherLock,
+    /// Unix time after which the refund keys can spend the vouchers not claimed
+    pub expiry: Option<u64>,
+    /// Keys of the issuer taking back the vouchers not claimed, required with an expiry
+    pub refund_keys: Vec<PublicKey>,
+    /// Memo of the tokens
+    pub memo: Option<String>,
+}
+
+/// Voucher handed out to its holder
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Voucher {
+    /// Token of the voucher
+    pub token: Token,
+    /// Code to claim the voucher, for [`VoucherLock::ClaimCode`]
+    ///
+    /// The mint does not keep it.
+    pub claim_code: Option<String>,
+}
+
+/// Batch of vouchers as recorded by the mint, see [`Mint::voucher_batches`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VoucherBatch {
+    /// Id of the batch, also the id of the operation that issued it
+    pub id: Uuid,
+    /// Unix time the batch was issued
+    pub created_at: u64,
+    /// Unit of the vouchers
+    pub unit: CurrencyUnit,
+    /// Value of each voucher
+    pub amount: Amount,
+    /// How the vouchers are locked
+    pub lock: VoucherLock,
+    /// Unix time after which the refund keys can spend the vouchers not claimed
+    pub expiry: Option<u64>,
+    /// Memo of the tokens
+    pub memo: Option<String>,
+    /// `Y` of the proofs of each voucher
+    pub vouchers: Vec<Vec<PublicKey>>,
+}
+
+/// Batch issued by [`Mint::issue_vouchers`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IssuedVouchers {
+    /// Batch as recorded by the mint
+    pub batch: VoucherBatch,
+    /// Vouchers, in the order of [`VoucherBatch::vouchers`]
+    pub vouchers: Vec<Voucher>,
+}
+
+/// How many vouchers of a batch were claimed, see [`Mint::voucher_report`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VoucherReport {
+    /// Id of the batch
+    pub batch_id: Uuid,
+    /// Unit of the vouchers
+    pub unit: CurrencyUnit,
+    /// Value of each voucher
+    pub amount: Amount,
+    /// Vouchers issued
+    pub issued: usize,
+    /// Vouchers with every proof spent, by their holder or by the refund keys once expired
+    pub claimed: usize,
+    /// Vouchers with only part of their proofs spent, or being spent
+    pub pending: usize,
+    /// Vouchers with no proof spent
+    pub outstanding: usize,
+    /// Whether the expiry of the batch passed
+    pub expired: bool,
+}
+
+impl VoucherReport {
+    /// Value of the vouchers with no proof spent
+    pub fn outstanding_amount(&self) -> Amount {
+        self.amount
+            .checked_mul(Amount::from(self.outstanding as u64))
+            .unwrap_or(Amount::from(u64::MAX))
+    }
+}
+
+impl Mint {
+    /// Issue a batch of locked vouchers
+    ///
+    /// The vouchers are signed with the active keyset of the unit, without a payment, and are
+    /// held to the issuance cap of the unit. Claiming a voucher swaps its proofs, so the holder
+    /// pays the input fee of the keyset.
+    #[instrument(skip(self, request), fields(unit = %request.unit, count = request.count))]
+    pub async fn issue_vouchers(
+        &self,
+        request: VoucherBatchRequest,
+    ) -> Result<IssuedVouchers, Error> {
+        ensure_cdk!(
+            request.count > 0 && request.amount > Amount::ZERO,
+            Error::Custom("A voucher batch needs a count and an amount".to_string())
+        );
+        ensure_cdk!(
+            request.count <= MAX_VOUCHER_BATCH_SIZE,
+            Error::BatchSizeExceeded {
+                actual: request.count,
+                max: MAX_VOUCHER_BATCH_SIZE,
+            }
+        );
+
+        let now = unix_time();
+        if let Some(expiry) = request.expiry {
+            ensure_cdk!(
+                expiry > now,
+                Error::InvalidSpendConditions("Voucher expiry is in the past".to_string())
+            );
+            // Past the locktime, proofs without refund keys can be spent by anyone
+            ensure_cdk!(
+                !request.refund_keys.is_empty(),
+                Error::InvalidSpendConditions("Voucher expiry needs refund keys".to_string())
+            );
+        }
+
+        let keyset = self
+            .keysets
+            .load()
+            .iter()
+            .find(|keyset| keyset.active && keyset.unit == request.unit)
+            .cloned()
+            .ok_or(Error::UnsupportedUnit)?;
+
+        let total = request
+            .amount
+            .checked_mul(Amount::from(request.count as u64))
+            .ok_or(Error::AmountOverflow)?;
+        self.check_issuance_cap(&request.unit, Some(total)).await?;
+
+        let conditions = match request.expiry {
+            Some(expiry) => Some(Conditions {
+                locktime: Some(expiry),
+                refund_keys: Some(request.refund_keys.clone()),
+                ..Default::default()
+            }),
+            None => None,
+        };
+        let fee_and_amounts: FeeAndAmounts = (keyset.input_fee_ppk, keyset.amounts.clone()).into();
+
+        let mut premints = Vec::with_capacity(request.count);
+        for _ in 0..request.count {
+            let (spending_conditions, claim_code) = match &request.lock {
+                VoucherLock::ClaimKey(claim_key) => (
+                    SpendingConditions::new_p2pk(*claim_key, conditions.clone()),
+                    None,
+                ),
+                VoucherLock::ClaimCode => {
+                    let code = Secret::generate().to_string();
+                    (
+                        SpendingConditions::new_htlc(code.clone(), conditions.clone())?,
+                        Some(code),
+                    )
+                }
+            };
+
+            let premint = PreMintSecrets::with_conditions(
+                keyset.id,
+                request.amount,
+                &SplitTarget::None,
+                &spending_conditions,
+                &fee_and_amounts,
+            )?;
+            premints.push((premint, claim_code));
+        }
+
+        let blinded_messages: Vec<_> = premints
+            .iter()
+            .flat_map(|(premint, _)| premint.blinded_messages())
+            .collect();
+        let blinded_secrets: Vec<PublicKey> = blinded_messages
+            .iter()
+            .map(|message| message.blinded_secret)
+            .collect();
+
+        let signatures = self.blind_sign(blinded_messages.clone()).await?;
+        ensure_cdk!(
+            signatures.len() == blinded_messages.len(),
+            Error::SignatureMissingOrInvalid
+        );
+
+        let mut vouchers = Vec::with_capacity(request.count);
+        let mut voucher_ys = Vec::with_capacity(request.count);
+        let mut signed = 0;
+        for (premint, claim_code) in premints {
+            let proofs: Proofs = construct_proofs(
+                signatures[signed..signed + premint.len()].to_vec(),
+                premint.rs(),
+                premint.secrets(),
+                &keyset.keys,
+            )?;
+            signed += premint.len();
+
+            voucher_ys.push(
+                proofs
+                    .iter()
+                    .map(|proof| proof.y())
+                    .collect::<Result<Vec<_>, _>>()?,
+            );
+            vouchers.push(Voucher {
+                token: Token::new(
+                    request.mint_url.clone(),
+                    proofs,
+                    request.memo.clone(),
+                    request.unit.clone(),
+                ),
+                claim_code,
+            });
+        }
+
+        let operation = Operation::new(
+            Uuid::now_v7(),
+            OperationKind::Mint,
+            total,
+            Amount::ZERO,
+            Amount::ZERO,
+            None,
+            None,
+        );
+        let batch = VoucherBatch {
+            id: *operation.id(),
+            created_at: now,
+            unit: request.unit,
+            amount: request.amount,
+            lock: request.lock,
+            expiry: request.expiry,
+            memo: request.memo,
+            vouchers: voucher_ys,
+        };
+
+        let mut tx = self.localstore.begin_transaction().await?;
+        tx.add_blinded_messages(None, &blinded_messages, &operation)
+            .await?;
+        tx.add_blind_signatures(&blinded_secrets, &signatures, None)
+            .await?;
+        tx.add_completed_operation(&operation, &Default::default())
+            .await?;
+        tx.kv_write(
+            CDK_MINT_PRIMARY_NAMESPACE,
+            CDK_MINT_VOUCHERS_SECONDARY_NAMESPACE,
+            &batch.id.to_string(),
+            &serde_json::to_vec(&batch)?,
+        )
+        .await?;
+        tx.commit().await?;
+
+        tracing::info!(
+            "Issued voucher batch {} of {} x {} {}",
+            batch.id,
+            request.count,
+            batch.amount,
+            batch.unit
+        );
+
+        Ok(IssuedVouchers { batch, vouchers })
+    }
+    /// Every voucher batch issued, oldest first
+    #[instrument(skip(self))]
+    pub async fn voucher_batches(&self) -> Result<Vec<VoucherBatch>, Error> {
+        let mut batches = Vec::new();
+        for key in self
+            .localstore
+            .kv_list(
+                CDK_MINT_PRIMARY_NAMESPACE,
+                CDK_MINT_VOUCHERS_SECONDARY_NAMESPACE,
+            )
+            .await?
+        {
+            if let Some(batch) = self.voucher_batch(&key).await? {
+                batches.push(batch);
+            }
+        }
+
+        batches.sort_by_key(|batch| batch.created_at);
+        Ok(batches)
+    }
+
+    /// How many vouchers of the batch `batch_id` were claimed
+    #[instrument(skip(self))]
+    pub async fn voucher_report(&self, batch_id: Uuid) -> Result<VoucherReport, Error> {
+        let batch = self
+            .voucher_batch(&batch_id.to_string())
+            .await?
+            .ok_or(Error::OperationNotFound)?;
+
+        let ys: Vec<PublicKey> = batch.vouchers.iter().flatten().copied().collect();
+        let mut states = self.localstore.get_proofs_states(&ys).await?.into_iter();
+
+        let mut report = VoucherReport {
+            batch_id: batch.id,
+            unit: batch.unit.clone(),
+            amount: batch.amount,
+            issued: batch.vouchers.len(),
+            claimed: 0,
+            pending: 0,
+            outstanding: 0,
+            expired: batch.expiry.is_some_and(|expiry| expiry <= unix_time()),
+        };
+
+        for voucher in &batch.vouchers {
+            let states: Vec<Option<State>> = states.by_ref().take(voucher.len()).collect();
+            if states.iter().all(|state| *state == Some(State::Spent)) {
+                report.claimed += 1;
+            } else if states.iter().all(Option::is_none) {
+                report.outstanding += 1;
+            } else {
+                report.pending += 1;
+            }
+        }
+
+        Ok(report)
+    }
+
+    async fn voucher_batch(&self, key: &str) -> Result<Option<VoucherBatch>, Error> {
+        self.localstore
+            .kv_read(
+                CDK_MINT_PRIMARY_NAMESPACE,
+                CDK_MINT_VOUCHERS_SECONDARY_NAMESPACE,
+                key,
+            )
+            .await?
+            .map(|bytes| serde_json::from_slice(&bytes))
+            .transpose()
+            .map_err(Error::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nuts::{ProofsMethods, SecretKey, SwapRequest};
+    use crate::test_helpers::mint::{create_test_blinded_messages, create_test_mint};
+
+    #[tokio::test]
+    async fn claimed_vouchers_are_reported() {
+        let mint = create_test_mint().await.unwrap();
+
+        let issued = mint
+            .issue_vouchers(VoucherBatchRequest {
+                mint_url: "https://mint.example.com".parse().unwrap(),
+                unit: CurrencyUnit::Sat,
+                amount: Amount::from(10),
+                count: 3,
+                lock: VoucherLock::ClaimCode,
+                expiry: None,
+                refund_keys: Vec::new(),
+                memo: Some("Happy birthday".to_string()),
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(issued.vouchers.len(), 3);
+        assert_eq!(
+            mint.voucher_batches().await.unwrap(),
+            vec![issued.batch.clone()]
+        );
+
+        let voucher = &issued.vouchers[0];
+        let mut proofs = voucher.token.proofs(&mint.keysets().keysets).unwrap();
+        assert_eq!(proofs.total_amount().unwrap(), Amount::from(10));
+
+        // Without the claim code the proofs cannot be spent
+        let (outputs, _) = create_test_blinded_messages(&mint, Amount::from(10))
+            .await
+            .unwrap();
+        assert!(mint
+            .process_swap_request(SwapRequest::new(proofs.clone(), outputs))
+            .await
+            .is_err());
+
+        let (outputs, _) = create_test_blinded_messages(&mint, Amount::from(10))
+            .await
+            .unwrap();
+        let code = voucher.claim_code.clone().unwrap();
+        for proof in proofs.iter_mut() {
+            proof.add_preimage(code.clone());
+        }
+        mint.process_swap_request(SwapRequest::new(proofs, outputs))
+            .await
+            .unwrap();
+
+        let report = mint.voucher_report(issued.batch.id).await.unwrap();
+        assert_eq!(report.issued, 3);
+        assert_eq!(report.claimed, 1);
+        assert_eq!(report.outstanding, 2);
+        assert_eq!(report.outstanding_amount(), Amount::from(20));
+        assert!(!report.expired);
+    }
+
+    #[tokio::test]
+    async fn expiry_needs_refund_keys() {
+        let mint = create_test_mint().await.unwrap();
+
+        let result = mint
+            .issue_vouchers(VoucherBatchRequest {
+                mint_url: "https://mint.example.com".parse().unwrap(),
+                unit: CurrencyUnit::Sat,
+                amount: Amount::from(10),
+                count: 1,
+                lock: VoucherLock::ClaimKey(SecretKey::generate().public_key()),
+                expiry: Some(unix_time() + 3600),
+                refund_keys: Vec::new(),
+                memo: None,
+            })
+            .await;
+
+        assert!(matches!(result, Err(Error::InvalidSpendConditions(_))));
+    }
+}
@@ -0,0 +1,300 @@
+//! Audit of stored proofs against the mint's keys
+//!
+//! Proofs restored from a backup or imported from another wallet may have been signed by keys
+//! the mint never published. The audit fetches the keys from the mint again, bypassing the
+//! local cache, and checks every stored proof against them.
+
+use std::collections::HashMap;
+
+use cdk_common::wallet::ProofInfo;
+use cdk_common::Id;
+use tracing::instrument;
+
+use crate::nuts::{KeySet, PublicKey, State};
+use crate::{Amount, Error, Wallet};
+
+/// KV store namespace holding quarantined proofs, keyed by their `Y`
+const AUDIT_KV_NAMESPACE: &str = "proof_audit";
+const QUARANTINE_KV_SECONDARY_NAMESPACE: &str = "quarantine";
+
+/// Problem found with a stored proof by [`Wallet::audit_proofs`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProofAnomaly {
+    /// The mint does not list the keyset of the proof
+    UnknownKeyset,
+    /// The keys served by the mint do not hash to the keyset id
+    InvalidKeysetId,
+    /// The keyset has no key for the amount of the proof
+    UnknownAmount,
+    /// The proof carries no DLEQ proof, so its signature cannot be checked offline
+    MissingDleq,
+    /// The DLEQ proof does not verify against the mint's key
+    InvalidDleq,
+}
+
+impl ProofAnomaly {
+    /// Whether the proof was shown not to be signed by the mint
+    ///
+    /// Proofs without a DLEQ proof are only unverified, they are never quarantined.
+    pub fn is_invalid(&self) -> bool {
+        !matches!(self, Self::MissingDleq)
+    }
+}
+
+/// Proof flagged by [`Wallet::audit_proofs`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlaggedProof {
+    /// `Y` of the proof
+    pub y: PublicKey,
+    /// Keyset of the proof
+    pub keyset_id: Id,
+    /// Amount of the proof
+    pub amount: Amount,
+    /// State of the proof in the wallet
+    pub state: State,
+    /// What is wrong with the proof
+    pub anomaly: ProofAnomaly,
+}
+
+/// Options for [`Wallet::audit_proofs`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AuditOptions {
+    /// Move unspent proofs shown to be invalid out of the wallet's proofs
+    ///
+    /// Quarantined proofs no longer count towards the balance and are kept aside in the
+    /// database, see [`Wallet::quarantined_proofs`].
+    pub quarantine: bool,
+}
+
+/// Report of [`Wallet::audit_proofs`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AuditReport {
+    /// Number of proofs checked
+    pub checked: usize,
+    /// Proofs that failed a check
+    pub flagged: Vec<FlaggedProof>,
+    /// Number of flagged proofs moved to quarantine
+    pub quarantined: usize,
+}
+
+impl Wallet {
+    /// Re-verify the stored proofs of this wallet against freshly fetched mint keys
+    ///
+    /// Every proof must belong to a keyset the mint lists, whose keys hash to its id, and
+    /// carry a DLEQ proof that verifies against the key for its amount.
+    #[instrument(skip(self))]
+    pub async fn audit_proofs(&self, options: AuditOptions) -> Result<AuditReport, Error> {
+        let proofs = self
+            .localstore
+            .get_proofs(
+                Some(self.mint_url.clone()),
+                Some(self.unit.clone()),
+                None,
+                None,
+            )
+            .await?;
+
+        let mint_keysets = self.client.get_mint_keysets().await?.keysets;
+        let mut keysets: HashMap<Id, Result<KeySet, ProofAnomaly>> = HashMap::new();
+
+        let mut report = AuditReport {
+            checked: proofs.len(),
+            ..Default::default()
+        };
+        let mut quarantine = Vec::new();
+
+        for proof_info in proofs {
+            let keyset_id = proof_info.proof.keyset_id;
+
+            if !keysets.contains_key(&keyset_id) {
+                let keyset = if mint_keysets.iter().any(|keyset| keyset.id == keyset_id) {
+                    match self.client.get_mint_keyset(keyset_id).await {
+                        Ok(keyset) => keyset
+                            .verify_id()
+                            .map(|_| keyset)
+                            .map_err(|_| ProofAnomaly::InvalidKeysetId),
+                        Err(Error::UnknownKeySet) => Err(ProofAnomaly::UnknownKeyset),
+                        Err(err) => return Err(err),
+                    }
+                } else {
+                    Err(ProofAnomaly::UnknownKeyset)
+                };
+                keysets.insert(keyset_id, keyset);
+            }
+
+            let anomaly = match keysets.get(&keyset_id) {
+                Some(Ok(keyset)) => match keyset.keys.amount_key(proof_info.proof.amount) {
+                    Some(key) => match proof_info.proof.verify_dleq(key) {
+                        Ok(()) => None,
+                        Err(crate::nuts::nut12::Error::MissingDleqProof) => {
+                            Some(ProofAnomaly::MissingDleq)
+                        }
+                        Err(_) => Some(ProofAnomaly::InvalidDleq),
+                    },
+                    None => Some(ProofAnomaly::UnknownAmount),
+                },
+                Some(Err(anomaly)) => Some(*anomaly),
+                None => Some(ProofAnomaly::UnknownKeyset),
+            };
+
+            let Some(anomaly) = anomaly else {
+                continue;
+            };
+
+            tracing::warn!(
+                "Proof {} of keyset {} failed audit: {:?}",
+                proof_info.y,
+                keyset_id,
+                anomaly
+            );
+
+            report.flagged.push(FlaggedProof {
+                y: proof_info.y,
+                keyset_id,
+                amount: proof_info.proof.amount,
+                state: proof_info.state,
+                anomaly,
+            });
+
+            if options.quarantine && anomaly.is_invalid() && proof_info.state == State::Unspent {
+                quarantine.push(proof_info);
+            }
+        }
+
+        for proof_info in &quarantine {
+            let value = serde_json::to_vec(proof_info)?;
+            self.localstore
+                .kv_write(
+                    AUDIT_KV_NAMESPACE,
+                    QUARANTINE_KV_SECONDARY_NAMESPACE,
+                    &proof_info.y.to_hex(),
+                    &value,
+                )
+                .await?;
+        }
+
+        if !quarantine.is_empty() {
+            report.quarantined = quarantine.len();
+            self.localstore
+                .update_proofs(
+                    Vec::new(),
+                    quarantine
+                        .into_iter()
+                        .map(|proof_info| proof_info.y)
+                        .collect(),
+                )
+                .await?;
+        }
+
+        tracing::debug!(
+            "Audited {} proofs, {} flagged, {} quarantined",
+            report.checked,
+            report.flagged.len(),
+            report.quarantined
+        );
+
+        Ok(report)
+    }
+
+    /// Proofs of this wallet moved to quarantine by [`Wallet::audit_proofs`]
+    #[instrument(skip(self))]
+    pub async fn quarantined_proofs(&self) -> Result<Vec<ProofInfo>, Error> {
+        let mut proofs = Vec::new();
+
+        for key in self
+            .localstore
+            .kv_list(AUDIT_KV_NAMESPACE, QUARANTINE_KV_SECONDARY_NAMESPACE)
+            .await?
+        {
+            let Some(value) = self
+                .localstore
+                .kv_read(AUDIT_KV_NAMESPACE, QUARANTINE_KV_SECONDARY_NAMESPACE, &key)
+                .await?
+            else {
+                continue;
+            };
+
+            let proof_info: ProofInfo = serde_json::from_slice(&value)?;
+            if proof_info.mint_url == self.mint_url && proof_info.unit == self.unit {
+                proofs.push(proof_info);
+            }
+        }
+
+        Ok(proofs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use std::sync::Arc;
+
+    use cdk_common::nuts::{ProofDleq, SecretKey};
+
+    use super::*;
+    use crate::wallet::test_utils::{
+        create_test_db, create_test_wallet_with_mock, test_keyset, test_mint_url, test_proof_info,
+        MockMintConnector,
+    };
+
+    #[tokio::test]
+    async fn audit_flags_and_quarantines_invalid_proofs() {
+        let db = create_test_db().await;
+        let mock = Arc::new(MockMintConnector::new());
+        let wallet = create_test_wallet_with_mock(db.clone(), mock).await;
+
+        let keyset = test_keyset();
+        let unknown_keyset = Id::from_str("00deadbeef123456").unwrap();
+
+        let missing_dleq = test_proof_info(keyset.id, 1, test_mint_url());
+        let mut invalid_dleq = test_proof_info(keyset.id, 2, test_mint_url());
+        invalid_dleq.proof.dleq = Some(ProofDleq::new(
+            SecretKey::generate(),
+            SecretKey::generate(),
+            SecretKey::generate(),
+        ));
+        let unknown = test_proof_info(unknown_keyset, 1, test_mint_url());
+
+        db.update_proofs(
+            vec![missing_dleq.clone(), invalid_dleq.clone(), unknown.clone()],
+            vec![],
+        )
+        .await
+        .unwrap();
+
+        let report = wallet
+            .audit_proofs(AuditOptions { quarantine: true })
+            .await
+            .unwrap();
+
+        assert_eq!(report.checked, 3);
+        assert_eq!(report.quarantined, 2);
+
+        let anomaly = |y: PublicKey| {
+            report
+                .flagged
+                .iter()
+                .find(|flagged| flagged.y == y)
+                .map(|flagged| flagged.anomaly)
+        };
+        assert_eq!(anomaly(missing_dleq.y), Some(ProofAnomaly::MissingDleq));
+        assert_eq!(anomaly(invalid_dleq.y), Some(ProofAnomaly::InvalidDleq));
+        assert_eq!(anomaly(unknown.y), Some(ProofAnomaly::UnknownKeyset));
+
+        // Only the unverified proof is left to spend
+        let remaining = wallet.get_unspent_proofs().await.unwrap();
+        assert_eq!(remaining, vec![missing_dleq.proof]);
+
+        let mut quarantined: Vec<_> = wallet
+            .quarantined_proofs()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|proof_info| proof_info.y)
+            .collect();
+        quarantined.sort();
+        let mut expected = vec![invalid_dleq.y, unknown.y];
+        expected.sort();
+        assert_eq!(quarantined, expected);
+    }
+}
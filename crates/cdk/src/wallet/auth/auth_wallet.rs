@@ -611,6 +611,8 @@ mod tests {
                         active: true,
                         input_fee_ppk: 0,
                         final_expiry: None,
+                        rotation_time: None,
+                        retirement_time: None,
                     },
                     KeySetInfo {
                         id: self.keyset_inactive.id,
@@ -618,6 +620,8 @@ mod tests {
                         active: false,
                         input_fee_ppk: 0,
                         final_expiry: None,
+                        rotation_time: None,
+                        retirement_time: None,
                     },
                 ],
             })
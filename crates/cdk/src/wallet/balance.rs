@@ -1,9 +1,41 @@
+use serde::{Deserialize, Serialize};
 use tracing::instrument;
 
 use crate::nuts::nut00::ProofsMethods;
 use crate::nuts::State;
 use crate::{Amount, Error, Wallet};
 
+/// Wallet balance broken down by proof state and spending condition
+///
+/// The raw proof sum ([`Wallet::total_balance`]) includes proofs the wallet cannot spend right
+/// away. Apps should show [`WalletBalance::spendable`] as the available amount.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalletBalance {
+    /// Unspent proofs without spending conditions
+    pub spendable: Amount,
+    /// Unspent proofs locked by a P2PK or HTLC spending condition
+    pub locked: Amount,
+    /// Proofs reserved by a send in progress
+    pub reserved: Amount,
+    /// Proofs used by a melt or swap in progress
+    pub pending: Amount,
+    /// Proofs sent in a token that has not been claimed yet
+    pub pending_spent: Amount,
+}
+
+impl WalletBalance {
+    /// Sum of all the balances
+    pub fn total(&self) -> Result<Amount, Error> {
+        Ok(Amount::try_sum([
+            self.spendable,
+            self.locked,
+            self.reserved,
+            self.pending,
+            self.pending_spent,
+        ])?)
+    }
+}
+
 impl Wallet {
     /// Total unspent balance of wallet
     #[instrument(skip(self))]
@@ -31,4 +63,114 @@ impl Wallet {
     pub async fn total_reserved_balance(&self) -> Result<Amount, Error> {
         Ok(self.get_reserved_proofs().await?.total_amount()?)
     }
+
+    /// Balance broken down into spendable, locked, reserved and pending amounts
+    #[instrument(skip(self))]
+    pub async fn balance_breakdown(&self) -> Result<WalletBalance, Error> {
+        let proofs = self
+            .localstore
+            .get_proofs(
+                Some(self.mint_url.clone()),
+                Some(self.unit.clone()),
+                Some(vec![
+                    State::Unspent,
+                    State::Reserved,
+                    State::Pending,
+                    State::PendingSpent,
+                ]),
+                None,
+            )
+            .await?;
+
+        let mut balance = WalletBalance::default();
+        for proof in proofs {
+            let bucket = match proof.state {
+                State::Unspent if proof.spending_condition.is_some() => &mut balance.locked,
+                State::Unspent => &mut balance.spendable,
+                State::Reserved => &mut balance.reserved,
+                State::Pending => &mut balance.pending,
+                State::PendingSpent => &mut balance.pending_spent,
+                State::Spent => continue,
+            };
+            *bucket = bucket
+                .checked_add(proof.proof.amount)
+                .ok_or(Error::AmountOverflow)?;
+        }
+
+        Ok(balance)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cdk_common::nuts::{SecretKey, SpendingConditions};
+    use cdk_common::secret::Secret;
+    use cdk_common::wallet::ProofInfo;
+    use cdk_common::CurrencyUnit;
+
+    use super::*;
+    use crate::wallet::test_utils::{
+        create_test_db, create_test_wallet, test_keyset_id, test_proof,
+    };
+
+    #[tokio::test]
+    async fn balance_breakdown_splits_by_state_and_condition() {
+        let db = create_test_db().await;
+        let wallet = create_test_wallet(db.clone()).await;
+
+        let proof_info = |amount: u64, state: State| {
+            ProofInfo::new(
+                test_proof(test_keyset_id(), amount),
+                wallet.mint_url.clone(),
+                state,
+                CurrencyUnit::Sat,
+            )
+            .unwrap()
+        };
+
+        let mut locked = test_proof(test_keyset_id(), 16);
+        locked.secret = Secret::try_from(SpendingConditions::new_p2pk(
+            SecretKey::generate().public_key(),
+            None,
+        ))
+        .unwrap();
+        let locked = ProofInfo::new(
+            locked,
+            wallet.mint_url.clone(),
+            State::Unspent,
+            CurrencyUnit::Sat,
+        )
+        .unwrap();
+
+        db.update_proofs(
+            vec![
+                proof_info(1, State::Unspent),
+                proof_info(2, State::Unspent),
+                locked,
+                proof_info(4, State::Reserved),
+                proof_info(8, State::Pending),
+                proof_info(32, State::PendingSpent),
+                proof_info(64, State::Spent),
+            ],
+            vec![],
+        )
+        .await
+        .unwrap();
+
+        let balance = wallet.balance_breakdown().await.unwrap();
+        assert_eq!(
+            balance,
+            WalletBalance {
+                spendable: Amount::from(3),
+                locked: Amount::from(16),
+                reserved: Amount::from(4),
+                pending: Amount::from(8),
+                pending_spent: Amount::from(32),
+            }
+        );
+        assert_eq!(balance.total().unwrap(), Amount::from(63));
+
+        // The raw unspent sum still includes locked proofs
+        assert_eq!(wallet.total_balance().await.unwrap(), Amount::from(19));
+    }
 }
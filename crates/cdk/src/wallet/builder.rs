@@ -13,7 +13,7 @@ use crate::mint_url::MintUrl;
 use crate::nuts::CurrencyUnit;
 use crate::wallet::auth::AuthWallet;
 use crate::wallet::mint_metadata_cache::MintMetadataCache;
-use crate::wallet::{HttpClient, MintConnector, SubscriptionManager, Wallet};
+use crate::wallet::{HttpClient, MintConnector, SeedProvider, SubscriptionManager, Wallet};
 
 /// Builder for creating a new [`Wallet`]
 pub struct WalletBuilder {
@@ -21,6 +21,8 @@ pub struct WalletBuilder {
     unit: Option<CurrencyUnit>,
     localstore: Option<Arc<dyn WalletDatabase<database::Error> + Send + Sync>>,
     target_proof_count: Option<usize>,
+    clock_skew_grace_secs: u64,
+    max_receive_fee_ppk: Option<u64>,
     auth_wallet: Option<AuthWallet>,
     seed: Option<[u8; 64]>,
     use_http_subscription: bool,
@@ -36,6 +38,8 @@ impl std::fmt::Debug for WalletBuilder {
             .field("mint_url", &self.mint_url)
             .field("unit", &self.unit)
             .field("target_proof_count", &self.target_proof_count)
+            .field("clock_skew_grace_secs", &self.clock_skew_grace_secs)
+            .field("max_receive_fee_ppk", &self.max_receive_fee_ppk)
             .finish_non_exhaustive()
     }
 }
@@ -47,6 +51,8 @@ impl Default for WalletBuilder {
             unit: None,
             localstore: None,
             target_proof_count: Some(3),
+            clock_skew_grace_secs: 0,
+            max_receive_fee_ppk: None,
             auth_wallet: None,
             seed: None,
             client: None,
@@ -125,6 +131,18 @@ impl WalletBuilder {
         self
     }
 
+    /// Set the clock skew grace period in seconds, see [`Wallet::set_clock_skew_grace`]
+    pub fn clock_skew_grace(mut self, secs: u64) -> Self {
+        self.clock_skew_grace_secs = secs;
+        self
+    }
+
+    /// Set the receive fee threshold in parts per thousand, see [`Wallet::set_max_receive_fee`]
+    pub fn max_receive_fee(mut self, max_fee_ppk: Option<u64>) -> Self {
+        self.max_receive_fee_ppk = max_fee_ppk;
+        self
+    }
+
     /// Set the auth wallet
     pub fn auth_wallet(mut self, auth_wallet: AuthWallet) -> Self {
         self.auth_wallet = Some(auth_wallet);
@@ -138,6 +156,12 @@ impl WalletBuilder {
         self
     }
 
+    /// Set the seed bytes from a [`SeedProvider`], such as a hardware wallet
+    pub async fn seed_provider(self, provider: &dyn SeedProvider) -> Result<Self, Error> {
+        let seed = provider.derivation_seed().await?;
+        Ok(self.seed(seed))
+    }
+
     /// Set a custom client connector
     pub fn client<C: MintConnector + 'static + Send + Sync>(mut self, client: C) -> Self {
         self.client = Some(Arc::new(client));
@@ -253,6 +277,8 @@ impl WalletBuilder {
             metadata_cache,
             metadata_cache_ttl,
             target_proof_count: self.target_proof_count.unwrap_or(3),
+            clock_skew_grace_secs: self.clock_skew_grace_secs,
+            max_receive_fee_ppk: self.max_receive_fee_ppk,
             auth_wallet: Arc::new(TokioRwLock::new(auth_wallet)),
             #[cfg(feature = "npubcash")]
             npubcash_client: Arc::new(TokioRwLock::new(None)),
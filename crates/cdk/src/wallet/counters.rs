@@ -0,0 +1,153 @@
+//! Reconciliation of the deterministic secret counters with the mint
+//!
+//! A wallet restored from an older backup has counters behind the outputs it already had signed,
+//! and the mint refuses the outputs it derives next as already signed. The mint's restore
+//! endpoint tells which outputs it signed, so the counters can be moved past them.
+
+use std::collections::HashSet;
+
+use cdk_common::wallet::NUT13Options;
+use cdk_common::Id;
+use tracing::instrument;
+
+use crate::nuts::{PreMintSecrets, RestoreRequest};
+use crate::wallet::KeysetFilter;
+use crate::{Error, Wallet};
+
+/// Counter of a keyset moved by [`Wallet::sync_keyset_counters`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeysetCounterSync {
+    /// Keyset of the counter
+    pub keyset_id: Id,
+    /// Counter before the sync
+    pub previous: u32,
+    /// Counter after the sync, next to the highest output the mint signed
+    pub counter: u32,
+}
+
+impl Wallet {
+    /// Move the counter of every keyset past the outputs the mint already signed
+    ///
+    /// Each keyset is probed from its local counter onwards in batches of the restore endpoint,
+    /// until [`NUT13Options::DEFAULT_MAX_GAP`] batches in a row come back empty. Counters are
+    /// only moved forward. No proofs are restored, use [`Wallet::restore`] for that.
+    ///
+    /// Returns the counters that were moved.
+    #[instrument(skip(self))]
+    pub async fn sync_keyset_counters(&self) -> Result<Vec<KeysetCounterSync>, Error> {
+        let opts = NUT13Options::default();
+        let mut synced = Vec::new();
+
+        for keyset in self.get_mint_keysets(KeysetFilter::All).await? {
+            let previous = self
+                .localstore
+                .increment_keyset_counter(&keyset.id, 0)
+                .await?;
+
+            let mut start_counter = previous;
+            let mut next_counter = previous;
+            let mut empty_batch: u32 = 0;
+
+            while empty_batch < opts.max_gap {
+                let batch_end = start_counter.saturating_add(opts.batch_size);
+                let premint_secrets =
+                    PreMintSecrets::restore_batch(keyset.id, &self.seed, start_counter, batch_end)?;
+
+                let response = self
+                    .client
+                    .post_restore(RestoreRequest {
+                        outputs: premint_secrets.blinded_messages(),
+                    })
+                    .await?;
+
+                let signed: HashSet<_> = response
+                    .outputs
+                    .iter()
+                    .map(|output| output.blinded_secret)
+                    .collect();
+
+                match premint_secrets
+                    .secrets
+                    .iter()
+                    .rposition(|premint| signed.contains(&premint.blinded_message.blinded_secret))
+                {
+                    Some(idx) => {
+                        next_counter = start_counter + idx as u32 + 1;
+                        empty_batch = 0;
+                    }
+                    None => empty_batch += 1,
+                }
+
+                start_counter = batch_end;
+            }
+
+            if next_counter > previous {
+                let counter = self
+                    .localstore
+                    .increment_keyset_counter(&keyset.id, next_counter - previous)
+                    .await?;
+
+                tracing::info!(
+                    "Moved counter of keyset {} from {} to {}, past the outputs the mint signed",
+                    keyset.id,
+                    previous,
+                    counter
+                );
+
+                synced.push(KeysetCounterSync {
+                    keyset_id: keyset.id,
+                    previous,
+                    counter,
+                });
+            }
+        }
+
+        Ok(synced)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::wallet::test_utils::{
+        create_test_db, create_test_wallet_with_mock, test_keyset, MockMintConnector,
+    };
+
+    #[tokio::test]
+    async fn counter_is_moved_past_the_outputs_signed_by_the_mint() {
+        let db = create_test_db().await;
+        let mock = Arc::new(MockMintConnector::new());
+        let wallet = create_test_wallet_with_mock(db.clone(), mock.clone()).await;
+        let keyset_id = test_keyset().id;
+
+        // The backup only knew about the first 5 outputs, the mint signed 120 of them
+        db.increment_keyset_counter(&keyset_id, 5).await.unwrap();
+        let signed = PreMintSecrets::restore_batch(keyset_id, &wallet.seed, 110, 120).unwrap();
+        mock.set_signed_outputs(
+            signed
+                .blinded_messages()
+                .into_iter()
+                .map(|output| output.blinded_secret)
+                .collect(),
+        );
+
+        let synced = wallet.sync_keyset_counters().await.unwrap();
+        assert_eq!(
+            synced,
+            vec![KeysetCounterSync {
+                keyset_id,
+                previous: 5,
+                counter: 120,
+            }]
+        );
+
+        // Nothing is signed past the counter anymore
+        assert!(wallet.sync_keyset_counters().await.unwrap().is_empty());
+        assert_eq!(
+            db.increment_keyset_counter(&keyset_id, 0).await.unwrap(),
+            120
+        );
+    }
+}
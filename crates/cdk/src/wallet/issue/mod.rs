@@ -13,7 +13,7 @@ use tracing::instrument;
 use crate::amount::SplitTarget;
 use crate::nuts::{BatchCheckMintQuoteRequest, Proofs, SecretKey, SpendingConditions};
 use crate::util::unix_time;
-use crate::wallet::recovery::RecoveryAction;
+use crate::wallet::recovery::{RecoveredMintQuote, RecoveryAction, UnmintedQuotesRecovery};
 use crate::wallet::{MintQuote, MintQuoteState};
 use crate::{Amount, Error, Wallet};
 
@@ -30,7 +30,7 @@ fn apply_mint_quote_response(quote: &mut MintQuote, response: &MintQuoteResponse
                     quote.amount_paid = amount;
                     quote.amount_issued = amount;
                 }
-                MintQuoteState::Unpaid => (),
+                MintQuoteState::Unpaid | MintQuoteState::Cancelled => (),
             }
         }
         MintQuoteResponse::Bolt12(response) => {
@@ -338,50 +338,84 @@ impl Wallet {
     /// linking all these quotes to a single wallet session.
     #[instrument(skip(self))]
     pub async fn mint_unissued_quotes(&self) -> Result<Amount, Error> {
-        let mint_quotes = self.localstore.get_unissued_mint_quotes().await?;
         let mut total_amount = Amount::ZERO;
 
-        for mint_quote in mint_quotes {
-            if mint_quote.mint_url != self.mint_url || mint_quote.unit != self.unit {
-                continue;
+        for mint_quote in self.get_unissued_mint_quotes().await? {
+            let quote_id = mint_quote.id.clone();
+
+            match self.mint_unissued_quote(mint_quote).await {
+                Ok(minted) => {
+                    total_amount = total_amount
+                        .checked_add(minted)
+                        .ok_or(Error::AmountOverflow)?;
+                }
+                Err(err) => tracing::warn!("Could not mint quote {}: {}", quote_id, err),
             }
+        }
+        Ok(total_amount)
+    }
 
-            let current_amount_issued = mint_quote.amount_issued;
+    /// Complete the mints left unfinished by a crash or a closed app
+    ///
+    /// Resumes the incomplete sagas first, so a mint interrupted after the mint signed its
+    /// outputs is restored from the counters stored with it, then mints every paid quote
+    /// still unissued. Quotes that fail are reported and left for the next call.
+    ///
+    /// # Privacy
+    ///
+    /// Like [`Wallet::mint_unissued_quotes`], this links every unissued quote to a single
+    /// wallet session.
+    #[instrument(skip(self))]
+    pub async fn recover_unminted_quotes(&self) -> Result<UnmintedQuotesRecovery, Error> {
+        let mut recovery = UnmintedQuotesRecovery {
+            sagas: self.recover_incomplete_sagas().await?,
+            quotes: Vec::new(),
+        };
+
+        for mint_quote in self.get_unissued_mint_quotes().await? {
+            let quote_id = mint_quote.id.clone();
 
-            let mint_quote = match self.inner_check_mint_quote_status(mint_quote).await {
-                Ok(q) => q,
+            let (minted, error) = match self.mint_unissued_quote(mint_quote).await {
+                Ok(minted) if minted == Amount::ZERO => continue,
+                Ok(minted) => (minted, None),
                 Err(err) => {
-                    tracing::warn!("Could not check quote state: {}", err);
-                    continue;
+                    tracing::warn!("Could not recover quote {}: {}", quote_id, err);
+                    (Amount::ZERO, Some(err.to_string()))
                 }
             };
 
-            if mint_quote.amount_mintable() > Amount::ZERO {
-                if let Err(err) = self
-                    .mint(&mint_quote.id, SplitTarget::default(), None)
-                    .await
-                {
-                    tracing::warn!("Could not mint quote {}: {}", mint_quote.id, err);
-                    continue;
-                }
-            }
+            recovery.quotes.push(RecoveredMintQuote {
+                quote_id,
+                minted,
+                error,
+            });
+        }
 
-            // Get updated quote to calculate minted amount
-            let updated_quote = match self.localstore.get_mint_quote(&mint_quote.id).await {
-                Ok(Some(q)) => q,
-                _ => continue,
-            };
+        Ok(recovery)
+    }
+
+    /// Check `mint_quote` with the mint and mint what it allows, returning the amount minted
+    async fn mint_unissued_quote(&self, mint_quote: MintQuote) -> Result<Amount, Error> {
+        let amount_issued = mint_quote.amount_issued;
+        let mint_quote = self.inner_check_mint_quote_status(mint_quote).await?;
 
-            total_amount = total_amount
-                .checked_add(
-                    updated_quote
-                        .amount_issued
-                        .checked_sub(current_amount_issued)
-                        .unwrap_or_default(),
-                )
-                .ok_or(Error::AmountOverflow)?;
+        if mint_quote.amount_mintable() == Amount::ZERO {
+            return Ok(Amount::ZERO);
         }
-        Ok(total_amount)
+
+        self.mint(&mint_quote.id, SplitTarget::default(), None)
+            .await?;
+
+        let updated_quote = self
+            .localstore
+            .get_mint_quote(&mint_quote.id)
+            .await?
+            .ok_or(Error::UnknownQuote)?;
+
+        Ok(updated_quote
+            .amount_issued
+            .checked_sub(amount_issued)
+            .unwrap_or_default())
     }
 
     /// Get active mint quotes
@@ -394,7 +428,7 @@ impl Wallet {
             quote.mint_url == self.mint_url
                 && quote.unit == self.unit
                 && quote.state != MintQuoteState::Issued
-                && quote.expiry > unix_time
+                && quote.expiry.saturating_add(self.clock_skew_grace_secs) > unix_time
         });
         Ok(mint_quotes)
     }
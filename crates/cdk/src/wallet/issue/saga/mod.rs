@@ -178,7 +178,12 @@ impl<'a> MintSaga<'a, Initial> {
         }
 
         let unix_time = unix_time();
-        if quote_info.expiry < unix_time && quote_info.expiry != 0 {
+        if quote_info
+            .expiry
+            .saturating_add(self.wallet.clock_skew_grace_secs)
+            < unix_time
+            && quote_info.expiry != 0
+        {
             tracing::warn!("Attempting to mint with expired quote.");
         }
 
@@ -1,12 +1,16 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
 
 use cdk_common::amount::{FeeAndAmounts, KeysetFeeAndAmounts};
 use cdk_common::nut02::KeySetInfosMethods;
+use cdk_common::util::unix_time;
 pub use cdk_common::wallet::KeysetFilter;
 use tracing::instrument;
 
+use crate::amount::SplitTarget;
+use crate::nuts::nut00::ProofsMethods;
 use crate::nuts::{Id, KeySetInfo, Keys, Proofs, Token};
-use crate::{Error, Wallet};
+use crate::{Amount, Error, Wallet};
 
 impl Wallet {
     /// Load keys for mint keyset
@@ -147,6 +151,49 @@ impl Wallet {
             .ok_or(Error::NoActiveKeyset)
     }
 
+    /// Swap the unspent proofs of keysets the mint is phasing out into the active keyset
+    ///
+    /// Fetches fresh keysets and migrates the proofs of every keyset that is inactive, or that
+    /// the mint plans to rotate or retire within `within`, see [`KeySetInfo::needs_migration`].
+    /// Returns the amount of the migrated proofs, before fees.
+    #[instrument(skip(self))]
+    pub async fn migrate_retiring_proofs(&self, within: Duration) -> Result<Amount, Error> {
+        self.refresh_keysets().await?;
+
+        let deadline = unix_time().saturating_add(within.as_secs());
+        let retiring: HashSet<Id> = self
+            .get_mint_keysets(KeysetFilter::All)
+            .await?
+            .into_iter()
+            .filter(|keyset| keyset.needs_migration(deadline))
+            .map(|keyset| keyset.id)
+            .collect();
+
+        let proofs: Proofs = self
+            .get_unspent_proofs()
+            .await?
+            .into_iter()
+            .filter(|proof| retiring.contains(&proof.keyset_id))
+            .collect();
+
+        if proofs.is_empty() {
+            return Ok(Amount::ZERO);
+        }
+
+        let amount = proofs.total_amount()?;
+        tracing::info!(
+            "Migrating {} proofs worth {} out of {} retiring keysets",
+            proofs.len(),
+            amount,
+            retiring.len()
+        );
+
+        self.swap(None, SplitTarget::default(), proofs, None, false, false)
+            .await?;
+
+        Ok(amount)
+    }
+
     /// Get keyset fees and amounts for all keysets from metadata cache
     ///
     /// Returns a HashMap of keyset IDs to their input fee rates (per-proof-per-thousand)
@@ -236,6 +283,8 @@ mod tests {
                     active: true,
                     input_fee_ppk: 100,
                     final_expiry: None,
+                    rotation_time: None,
+                    retirement_time: None,
                 },
                 KeySetInfo {
                     id: inactive_id,
@@ -243,6 +292,8 @@ mod tests {
                     active: false,
                     input_fee_ppk: 0,
                     final_expiry: None,
+                    rotation_time: None,
+                    retirement_time: None,
                 },
             ],
         )
@@ -0,0 +1,135 @@
+//! Wallet storage maintenance
+//!
+//! Removes data the wallet no longer needs so apps with tight storage budgets can keep the
+//! database small. Maintenance works on the whole database, not only on the wallet's mint.
+
+use tracing::instrument;
+
+use crate::util::unix_time;
+use crate::{Error, Wallet};
+
+/// Options for [`Wallet::maintenance`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MaintenanceOptions {
+    /// Remove transactions older than this many seconds. All transactions are kept when `None`.
+    pub transaction_retention_secs: Option<u64>,
+    /// Reclaim free space in the database once records are removed
+    pub vacuum: bool,
+}
+
+impl Default for MaintenanceOptions {
+    fn default() -> Self {
+        Self {
+            transaction_retention_secs: None,
+            vacuum: true,
+        }
+    }
+}
+
+/// Report of the records removed by [`Wallet::maintenance`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MaintenanceReport {
+    /// Keysets and keys of mints that are no longer stored
+    pub removed_keysets: u64,
+    /// Transactions older than the retention period
+    pub removed_transactions: u64,
+}
+
+impl Wallet {
+    /// Remove orphaned keysets and keys, compact the transaction history and vacuum the
+    /// database
+    #[instrument(skip(self))]
+    pub async fn maintenance(
+        &self,
+        options: MaintenanceOptions,
+    ) -> Result<MaintenanceReport, Error> {
+        let mut report = MaintenanceReport {
+            removed_keysets: self.localstore.remove_orphaned_keysets().await?,
+            ..Default::default()
+        };
+
+        if let Some(retention) = options.transaction_retention_secs {
+            let cutoff = unix_time().saturating_sub(retention);
+
+            for transaction in self.localstore.list_transactions(None, None, None).await? {
+                if transaction.timestamp < cutoff {
+                    self.localstore.remove_transaction(transaction.id()).await?;
+                    report.removed_transactions += 1;
+                }
+            }
+        }
+
+        if options.vacuum {
+            self.localstore.vacuum().await?;
+        }
+
+        tracing::debug!("Wallet maintenance finished: {:?}", report);
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cdk_common::nuts::SecretKey;
+    use cdk_common::wallet::{Transaction, TransactionDirection};
+    use cdk_common::{Amount, CurrencyUnit};
+
+    use super::*;
+    use crate::wallet::test_utils::{create_test_db, create_test_wallet, test_keyset};
+
+    fn transaction(wallet: &Wallet, timestamp: u64) -> Transaction {
+        Transaction {
+            mint_url: wallet.mint_url.clone(),
+            direction: TransactionDirection::Incoming,
+            amount: Amount::from(timestamp),
+            fee: Amount::ZERO,
+            unit: CurrencyUnit::Sat,
+            ys: vec![SecretKey::generate().public_key()],
+            timestamp,
+            memo: None,
+            metadata: Default::default(),
+            quote_id: None,
+            payment_request: None,
+            payment_proof: None,
+            payment_method: None,
+            saga_id: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn maintenance_removes_orphans_and_old_transactions() {
+        let db = create_test_db().await;
+        let wallet = create_test_wallet(db.clone()).await;
+
+        let removed_mint = "https://removed-mint.example.com".parse().unwrap();
+        let keyset = test_keyset();
+        db.add_mint(removed_mint, None).await.unwrap();
+        db.add_keys(keyset.clone()).await.unwrap();
+        db.remove_mint("https://removed-mint.example.com".parse().unwrap())
+            .await
+            .unwrap();
+
+        let now = unix_time();
+        db.add_transaction(transaction(&wallet, now - 1_000))
+            .await
+            .unwrap();
+        db.add_transaction(transaction(&wallet, now)).await.unwrap();
+
+        let report = wallet
+            .maintenance(MaintenanceOptions {
+                transaction_retention_secs: Some(100),
+                vacuum: true,
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(report.removed_keysets, 1);
+        assert_eq!(report.removed_transactions, 1);
+        assert!(db.get_keys(&keyset.id).await.unwrap().is_none());
+
+        let remaining = db.list_transactions(None, None, None).await.unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].timestamp, now);
+    }
+}
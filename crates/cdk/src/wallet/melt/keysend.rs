@@ -0,0 +1,32 @@
+//! Melt keysend
+//!
+//! Implementation of melt functionality for keysend payments to a node pubkey
+
+use cdk_common::wallet::MeltQuote;
+use tracing::instrument;
+
+use crate::nuts::{MeltQuoteCustomRequest, MeltQuoteKeysendRequest, PublicKey, KEYSEND_METHOD};
+use crate::{Amount, Error, Wallet};
+
+impl Wallet {
+    /// Melt Quote paying `amount` to the lightning node `pubkey` with keysend
+    ///
+    /// There is no invoice, so the payment proof returned by the mint is the preimage it chose,
+    /// which does not prove the node received the payment.
+    #[instrument(skip(self))]
+    pub async fn melt_keysend_quote(
+        &self,
+        pubkey: PublicKey,
+        amount: Amount,
+    ) -> Result<MeltQuote, Error> {
+        let MeltQuoteCustomRequest { request, extra, .. } =
+            MeltQuoteCustomRequest::from(MeltQuoteKeysendRequest {
+                pubkey,
+                amount,
+                unit: self.unit.clone(),
+            });
+
+        self.melt_quote_custom(KEYSEND_METHOD, request, None, Some(extra))
+            .await
+    }
+}
@@ -56,13 +56,18 @@ use crate::{ensure_cdk, Amount, Wallet};
 mod bolt11;
 mod bolt12;
 mod custom;
+mod keysend;
 #[cfg(all(feature = "bip353", not(target_arch = "wasm32")))]
 mod melt_bip353;
 #[cfg(feature = "wallet")]
 mod melt_lightning_address;
 mod onchain;
+mod onchain_swap;
 pub(crate) mod saga;
 
+pub use onchain_swap::{
+    ResumedSwap, SubmarineSwap, SwapMelt, SwapProgress, SwapProvider, SwapQuote, SwapStatus,
+};
 use saga::state::Prepared;
 use saga::{MeltSaga, MeltSagaResult};
 
@@ -974,7 +979,8 @@ impl Wallet {
             .filter(|q| {
                 q.unit == self.unit
                     && (q.state == MeltQuoteState::Pending
-                        || (q.state == MeltQuoteState::Unpaid && q.expiry > unix_time()))
+                        || (q.state == MeltQuoteState::Unpaid
+                            && q.expiry.saturating_add(self.clock_skew_grace_secs) > unix_time()))
             })
             .collect())
     }
@@ -0,0 +1,1255 @@
+//! Melt to an on-chain address through a submarine swap provider
+//!
+//! Mints without on-chain melts can still pay out on-chain through a reverse submarine swap
+//! (Boltz style). The provider locks on-chain funds in an HTLC for a hold invoice whose preimage
+//! only the wallet knows. The HTLC pays to a claim key of the wallet with the preimage, or back to
+//! the provider after a timeout. The wallet melts to the invoice, and once the lockup confirms it
+//! signs and broadcasts the claim to the address, which reveals the preimage and lets the provider
+//! settle the invoice.
+//!
+//! The preimage, the claim key and the signed claim are stored in the KV store before they are
+//! used, so an interrupted swap is picked up again by [`Wallet::resume_onchain_swaps`].
+//!
+//! If the provider never locks the funds, or the swap expires, the invoice is cancelled, the melt
+//! fails and the proofs go back to the wallet.
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use bitcoin::consensus::encode::{deserialize_hex, serialize_hex};
+use bitcoin::hashes::{ripemd160, sha256, Hash};
+use bitcoin::opcodes::all::{
+    OP_CHECKSIG, OP_CLTV, OP_DROP, OP_ELSE, OP_ENDIF, OP_EQUAL, OP_EQUALVERIFY, OP_HASH160, OP_IF,
+    OP_SIZE,
+};
+use bitcoin::script::Builder;
+use bitcoin::secp256k1::Message;
+use bitcoin::sighash::{EcdsaSighashType, SighashCache};
+use bitcoin::{
+    absolute, transaction, Address, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut,
+    Witness,
+};
+use cdk_common::mint_url::MintUrl;
+use cdk_common::util::unix_time;
+use cdk_common::wallet::MeltQuote;
+use cdk_common::{Error, MeltQuoteState};
+use lightning_invoice::Bolt11Invoice;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tracing::instrument;
+
+use crate::nuts::{CurrencyUnit, PublicKey, SecretKey};
+use crate::types::FinalizedMelt;
+use crate::util::hex;
+use crate::{Amount, Wallet, SECP256K1};
+
+/// KV primary namespace of the swaps of [`Wallet::melt_onchain_via_swap`]
+const ONCHAIN_SWAP_KV_NAMESPACE: &str = "onchain_swap";
+
+/// KV secondary namespace of the swaps not yet claimed or refunded, keyed by preimage hash
+const SWAPS_KV_SECONDARY_NAMESPACE: &str = "swaps";
+
+/// Attempts at broadcasting the claim of a confirmed lockup before giving up until resumed
+const CLAIM_ATTEMPTS: u32 = 3;
+
+/// Minimum fee rate of the claim transaction, in sat/vB
+const MIN_CLAIM_FEE_RATE: u64 = 1;
+
+/// Quote of a [`SwapProvider`] for an on-chain payout
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SwapQuote {
+    /// Name of the provider
+    pub provider: String,
+    /// Amount received on chain, in sat
+    pub onchain_amount: Amount,
+    /// Amount of the Lightning invoice to pay, in sat, including the provider and miner fees
+    pub invoice_amount: Amount,
+    /// Unix timestamp until which the quote is valid
+    pub expiry: u64,
+}
+
+impl SwapQuote {
+    /// Fees of the provider, including the miner fees of the lockup and claim transactions
+    pub fn fee(&self) -> Amount {
+        self.invoice_amount
+            .checked_sub(self.onchain_amount)
+            .unwrap_or(Amount::ZERO)
+    }
+}
+
+/// Swap created by a [`SwapProvider`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubmarineSwap {
+    /// Id of the swap at the provider
+    pub id: String,
+    /// Name of the provider
+    pub provider: String,
+    /// Hold invoice to pay, settled once the on-chain funds are claimed
+    pub invoice: String,
+    /// Address receiving the on-chain funds
+    pub address: String,
+    /// Amount received on chain, in sat
+    ///
+    /// The lockup pays this amount plus the miner fee of the claim transaction.
+    pub onchain_amount: Amount,
+    /// Key of the provider taking the lockup back after the timeout
+    pub refund_pubkey: PublicKey,
+    /// Block height after which the provider takes its lockup back
+    pub timeout_block_height: u32,
+}
+
+/// State of a [`SubmarineSwap`] at the provider
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SwapStatus {
+    /// Waiting for the invoice to be paid
+    Created,
+    /// The provider broadcast the lockup transaction
+    LockupBroadcast {
+        /// Id of the lockup transaction
+        txid: String,
+    },
+    /// The lockup transaction confirmed, the funds can be claimed
+    LockupConfirmed {
+        /// Id of the lockup transaction
+        txid: String,
+    },
+    /// The funds were claimed to the address
+    Claimed {
+        /// Id of the claim transaction
+        txid: String,
+    },
+    /// The swap failed, the provider cancels the invoice
+    Failed {
+        /// Reason given by the provider
+        reason: String,
+    },
+    /// The swap expired before the invoice was paid, the provider cancels the invoice
+    Expired,
+}
+
+/// Provider of reverse submarine swaps, paying on chain for a Lightning invoice
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait SwapProvider: Debug + Send + Sync {
+    /// Name of the provider, reported in quotes and progress
+    fn name(&self) -> &str;
+
+    /// Quote a swap paying `amount` sat on chain
+    async fn quote(&self, amount: Amount) -> Result<SwapQuote, Error>;
+
+    /// Create the swap of `quote` paying to `address`
+    ///
+    /// The invoice of the swap must be a hold invoice for `preimage_hash`, and its lockup must
+    /// pay to the HTLC of `preimage_hash`, `claim_pubkey` and the refund key and timeout of the
+    /// returned swap.
+    async fn create_swap(
+        &self,
+        quote: &SwapQuote,
+        address: &str,
+        preimage_hash: sha256::Hash,
+        claim_pubkey: PublicKey,
+    ) -> Result<SubmarineSwap, Error>;
+
+    /// Wait until the status of `swap` is no longer `current`, and return it
+    async fn wait_status_change(
+        &self,
+        swap: &SubmarineSwap,
+        current: &SwapStatus,
+    ) -> Result<SwapStatus, Error>;
+
+    /// Transaction `txid`, such as the lockup of a swap
+    async fn get_transaction(&self, txid: &str) -> Result<Transaction, Error>;
+
+    /// Broadcast `transaction`, returning its id
+    async fn broadcast_transaction(&self, transaction: &Transaction) -> Result<String, Error>;
+}
+
+/// Progress of [`Wallet::melt_onchain_via_swap`] and [`Wallet::resume_onchain_swaps`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SwapProgress {
+    /// Quotes of the providers, cheapest first
+    Quoted(Vec<SwapQuote>),
+    /// The swap was created with the cheapest provider within the fee limit
+    SwapCreated(SubmarineSwap),
+    /// The mint quoted the melt of the swap invoice
+    MeltQuoted {
+        /// Id of the melt quote
+        quote_id: String,
+        /// Lightning fee reserve of the mint
+        fee_reserve: Amount,
+    },
+    /// The swap changed state at the provider
+    Status(SwapStatus),
+    /// The wallet broadcast the claim of the on-chain funds
+    ClaimBroadcast {
+        /// Id of the claim transaction
+        txid: String,
+    },
+    /// The melt failed and its proofs are back in the wallet
+    Refunded {
+        /// Id of the melt quote
+        quote_id: String,
+    },
+}
+
+/// Completed melt to an on-chain address through a swap
+///
+/// Until the provider reports the claim, the swap stays stored and is followed again by
+/// [`Wallet::resume_onchain_swaps`].
+#[derive(Debug, Clone)]
+pub struct SwapMelt {
+    /// The swap paying on chain
+    pub swap: SubmarineSwap,
+    /// Id of the claim transaction signed by the wallet
+    pub claim_txid: Option<String>,
+    /// The melt paying the swap invoice
+    pub melt: FinalizedMelt,
+}
+
+/// Swap picked up again by [`Wallet::resume_onchain_swaps`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResumedSwap {
+    /// The melt was paid and the provider reported the claim
+    Claimed {
+        /// The swap paying on chain
+        swap: SubmarineSwap,
+        /// Id of the claim transaction signed by the wallet
+        claim_txid: Option<String>,
+    },
+    /// The invoice of the swap was not paid, the proofs of its melt are back in the wallet
+    Refunded {
+        /// The swap
+        swap: SubmarineSwap,
+    },
+    /// The swap is still in progress and stays stored to be resumed again
+    Pending {
+        /// The swap
+        swap: SubmarineSwap,
+    },
+}
+
+/// Swap of the wallet, as stored until it is claimed or refunded
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredSwap {
+    /// Mint of the wallet melting to the swap invoice
+    mint_url: MintUrl,
+    /// Unit of the wallet melting to the swap invoice
+    unit: CurrencyUnit,
+    /// The swap
+    swap: SubmarineSwap,
+    /// Preimage of the swap invoice, hex encoded
+    preimage: String,
+    /// Secret key of the claim pubkey given to the provider
+    claim_key: SecretKey,
+    /// Melt quote paying the swap invoice, once quoted
+    melt_quote_id: Option<String>,
+    /// Signed claim transaction, hex encoded, once built
+    claim_transaction: Option<String>,
+}
+
+impl StoredSwap {
+    fn preimage(&self) -> Result<[u8; 32], Error> {
+        hex::decode(&self.preimage)
+            .ok()
+            .and_then(|preimage| preimage.try_into().ok())
+            .ok_or_else(|| {
+                Error::SwapProvider(format!("Invalid preimage of swap {}", self.swap.id))
+            })
+    }
+
+    /// Id of the claim transaction, once built
+    fn claim_txid(&self) -> Option<String> {
+        self.claim_transaction
+            .as_ref()
+            .and_then(|claim| deserialize_hex::<Transaction>(claim).ok())
+            .map(|claim| claim.compute_txid().to_string())
+    }
+
+    /// Key of the swap in the KV store
+    fn key(&self) -> Result<String, Error> {
+        Ok(sha256::Hash::hash(&self.preimage()?).to_string())
+    }
+
+    /// Script of the HTLC the lockup of the swap pays to
+    fn script(&self) -> Result<ScriptBuf, Error> {
+        Ok(swap_script(
+            &sha256::Hash::hash(&self.preimage()?),
+            &self.claim_key.public_key(),
+            &self.swap.refund_pubkey,
+            self.swap.timeout_block_height,
+        ))
+    }
+
+    /// Sign the claim of the swap output of `lockup` to the address of the swap
+    fn build_claim(&self, lockup: &Transaction) -> Result<Transaction, Error> {
+        let script = self.script()?;
+        let script_pubkey = ScriptBuf::new_p2wsh(&script.wscript_hash());
+
+        let (vout, lockup_output) = lockup
+            .output
+            .iter()
+            .enumerate()
+            .find(|(_, output)| output.script_pubkey == script_pubkey)
+            .ok_or_else(|| {
+                Error::SwapProvider(format!(
+                    "Lockup of swap {} does not pay to its script",
+                    self.swap.id
+                ))
+            })?;
+
+        let onchain_amount = u64::from(self.swap.onchain_amount);
+        let fee = lockup_output
+            .value
+            .to_sat()
+            .checked_sub(onchain_amount)
+            .ok_or_else(|| {
+                Error::SwapProvider(format!(
+                    "Lockup of swap {} pays less than the quoted amount",
+                    self.swap.id
+                ))
+            })?;
+
+        let address = Address::from_str(&self.swap.address)
+            .map_err(|err| Error::SwapProvider(format!("Invalid swap address: {err}")))?
+            .assume_checked();
+
+        let mut claim = Transaction {
+            version: transaction::Version::TWO,
+            lock_time: absolute::LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint {
+                    txid: lockup.compute_txid(),
+                    vout: vout as u32,
+                },
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                witness: Witness::new(),
+            }],
+            output: vec![TxOut {
+                value: bitcoin::Amount::from_sat(onchain_amount),
+                script_pubkey: address.script_pubkey(),
+            }],
+        };
+
+        let sighash = SighashCache::new(&claim)
+            .p2wsh_signature_hash(0, &script, lockup_output.value, EcdsaSighashType::All)
+            .map_err(|err| Error::SwapProvider(format!("Could not sign claim: {err}")))?;
+        let signature = bitcoin::ecdsa::Signature::sighash_all(SECP256K1.sign_ecdsa(
+            &Message::from_digest(sighash.to_byte_array()),
+            &self.claim_key,
+        ));
+
+        claim.input[0].witness = Witness::from_slice(&[
+            signature.to_vec(),
+            self.preimage()?.to_vec(),
+            script.to_bytes(),
+        ]);
+
+        if fee < claim.vsize() as u64 * MIN_CLAIM_FEE_RATE {
+            return Err(Error::SwapProvider(format!(
+                "Lockup of swap {} leaves {} sat for the claim fee",
+                self.swap.id, fee
+            )));
+        }
+
+        Ok(claim)
+    }
+}
+
+/// Script of the HTLC paying to `claim_pubkey` with the preimage of `preimage_hash`, or to
+/// `refund_pubkey` after `timeout_block_height`
+fn swap_script(
+    preimage_hash: &sha256::Hash,
+    claim_pubkey: &PublicKey,
+    refund_pubkey: &PublicKey,
+    timeout_block_height: u32,
+) -> ScriptBuf {
+    Builder::new()
+        .push_opcode(OP_SIZE)
+        .push_int(32)
+        .push_opcode(OP_EQUAL)
+        .push_opcode(OP_IF)
+        .push_opcode(OP_HASH160)
+        .push_slice(ripemd160::Hash::hash(preimage_hash.as_byte_array()).to_byte_array())
+        .push_opcode(OP_EQUALVERIFY)
+        .push_slice(claim_pubkey.to_bytes())
+        .push_opcode(OP_ELSE)
+        .push_opcode(OP_DROP)
+        .push_int(i64::from(timeout_block_height))
+        .push_opcode(OP_CLTV)
+        .push_opcode(OP_DROP)
+        .push_slice(refund_pubkey.to_bytes())
+        .push_opcode(OP_ENDIF)
+        .push_opcode(OP_CHECKSIG)
+        .into_script()
+}
+
+fn report(progress: Option<&mpsc::UnboundedSender<SwapProgress>>, event: SwapProgress) {
+    if let Some(progress) = progress {
+        let _ = progress.send(event);
+    }
+}
+
+/// Quotes of `providers` for `amount`, cheapest first
+///
+/// Providers failing to quote are skipped.
+async fn cheapest_quotes(
+    providers: &[Arc<dyn SwapProvider>],
+    amount: Amount,
+) -> Result<Vec<SwapQuote>, Error> {
+    let mut quotes = Vec::new();
+
+    for provider in providers {
+        match provider.quote(amount).await {
+            Ok(quote) if quote.onchain_amount == amount => quotes.push(quote),
+            Ok(quote) => tracing::warn!(
+                "Swap provider {} quoted {} on chain instead of {}",
+                provider.name(),
+                quote.onchain_amount,
+                amount
+            ),
+            Err(err) => {
+                tracing::warn!("Swap provider {} failed to quote: {}", provider.name(), err)
+            }
+        }
+    }
+
+    if quotes.is_empty() {
+        return Err(Error::SwapProvider(
+            "No provider quoted the swap".to_string(),
+        ));
+    }
+
+    quotes.sort_by_key(|quote| quote.invoice_amount);
+    Ok(quotes)
+}
+
+/// Fails unless `swap` pays `quote` to `address` for an invoice of the quoted amount to
+/// `preimage_hash`
+fn check_swap(
+    swap: &SubmarineSwap,
+    quote: &SwapQuote,
+    address: &str,
+    preimage_hash: &sha256::Hash,
+) -> Result<(), Error> {
+    if swap.address != address || swap.onchain_amount != quote.onchain_amount {
+        return Err(Error::SwapProvider(format!(
+            "Swap {} does not pay the quoted amount to the address",
+            swap.id
+        )));
+    }
+
+    let invoice = Bolt11Invoice::from_str(&swap.invoice)?;
+
+    let invoice_hash: &[u8; 32] = invoice.payment_hash().as_ref();
+    if invoice_hash != preimage_hash.as_byte_array() {
+        return Err(Error::SwapProvider(format!(
+            "Invoice of swap {} is not locked to the wallet preimage",
+            swap.id
+        )));
+    }
+
+    let expected_msat = u64::from(quote.invoice_amount)
+        .checked_mul(1000)
+        .ok_or(Error::AmountOverflow)?;
+    if invoice.amount_milli_satoshis() != Some(expected_msat) {
+        return Err(Error::SwapProvider(format!(
+            "Invoice of swap {} does not match the quoted amount",
+            swap.id
+        )));
+    }
+
+    Ok(())
+}
+
+/// What finished first while melting to a swap invoice
+enum SwapStep {
+    Melted(Result<FinalizedMelt, Error>),
+    Followed(Result<SwapStatus, Error>),
+}
+
+impl Wallet {
+    /// Quotes of `providers` for paying `amount` sat on chain, cheapest first
+    #[instrument(skip(self, providers))]
+    pub async fn quote_onchain_swaps(
+        &self,
+        providers: &[Arc<dyn SwapProvider>],
+        amount: Amount,
+    ) -> Result<Vec<SwapQuote>, Error> {
+        cheapest_quotes(providers, amount).await
+    }
+
+    /// Melt `amount` sat to the on-chain `address` through the cheapest of `providers`
+    ///
+    /// Swaps with fees above `max_fee` are not considered. The wallet claims the lockup once it
+    /// confirms, the melt stays pending until then. Steps are reported to `progress`.
+    ///
+    /// If the swap fails, the melt fails with it and its proofs are returned to the wallet,
+    /// reported as [`SwapProgress::Refunded`]. A swap interrupted before it is claimed or
+    /// refunded is picked up again by [`Wallet::resume_onchain_swaps`].
+    #[instrument(skip(self, providers, progress))]
+    pub async fn melt_onchain_via_swap(
+        &self,
+        providers: &[Arc<dyn SwapProvider>],
+        address: &str,
+        amount: Amount,
+        max_fee: Option<Amount>,
+        progress: Option<mpsc::UnboundedSender<SwapProgress>>,
+    ) -> Result<SwapMelt, Error> {
+        let progress = progress.as_ref();
+
+        let quotes = cheapest_quotes(providers, amount).await?;
+        report(progress, SwapProgress::Quoted(quotes.clone()));
+
+        let now = unix_time();
+        let quote = quotes
+            .into_iter()
+            .filter(|quote| quote.expiry > now)
+            .find(|quote| max_fee.is_none_or(|max_fee| quote.fee() <= max_fee))
+            .ok_or(Error::MaxFeeExceeded)?;
+        let provider = providers
+            .iter()
+            .find(|provider| provider.name() == quote.provider)
+            .ok_or_else(|| Error::SwapProvider(format!("Unknown provider {}", quote.provider)))?;
+
+        let preimage = SecretKey::generate().to_secret_bytes();
+        let preimage_hash = sha256::Hash::hash(&preimage);
+        let claim_key = SecretKey::generate();
+
+        let swap = provider
+            .create_swap(&quote, address, preimage_hash, claim_key.public_key())
+            .await?;
+        check_swap(&swap, &quote, address, &preimage_hash)?;
+        tracing::info!(
+            "Created swap {} with {} paying {} sat to {}",
+            swap.id,
+            swap.provider,
+            swap.onchain_amount,
+            swap.address
+        );
+        report(progress, SwapProgress::SwapCreated(swap.clone()));
+
+        let mut stored = StoredSwap {
+            mint_url: self.mint_url.clone(),
+            unit: self.unit.clone(),
+            swap,
+            preimage: hex::encode(preimage),
+            claim_key,
+            melt_quote_id: None,
+            claim_transaction: None,
+        };
+        self.store_swap(&stored).await?;
+
+        let melt_quote: MeltQuote = match self
+            .melt_bolt11_quote(stored.swap.invoice.clone(), None)
+            .await
+        {
+            Ok(melt_quote) => melt_quote,
+            Err(err) => {
+                self.remove_swap(&stored).await?;
+                return Err(err);
+            }
+        };
+        stored.melt_quote_id = Some(melt_quote.id.clone());
+        self.store_swap(&stored).await?;
+        report(
+            progress,
+            SwapProgress::MeltQuoted {
+                quote_id: melt_quote.id.clone(),
+                fee_reserve: melt_quote.fee_reserve,
+            },
+        );
+
+        let metadata = HashMap::from([
+            ("swap_provider".to_string(), stored.swap.provider.clone()),
+            ("swap_id".to_string(), stored.swap.id.clone()),
+            ("onchain_address".to_string(), stored.swap.address.clone()),
+        ]);
+        let prepared = match self.prepare_melt(&melt_quote.id, metadata).await {
+            Ok(prepared) => prepared,
+            Err(err) => {
+                self.remove_swap(&stored).await?;
+                return Err(err);
+            }
+        };
+
+        // The hold invoice is only settled after the claim, so the melt is awaited while the
+        // swap is followed
+        let (result, followed) = {
+            let melt = prepared.confirm();
+            let follow = self.follow_swap(provider.as_ref(), &mut stored, progress);
+            tokio::pin!(melt, follow);
+
+            let step = tokio::select! {
+                result = &mut melt => SwapStep::Melted(result),
+                followed = &mut follow => SwapStep::Followed(followed),
+            };
+
+            match step {
+                SwapStep::Melted(result) => {
+                    // Unless the invoice is left unpaid, the lockup must still be claimed
+                    let followed = if self.swap_melt_failed(&melt_quote.id, &result).await {
+                        None
+                    } else {
+                        Some(follow.await)
+                    };
+                    (result, followed)
+                }
+                SwapStep::Followed(followed) => (melt.await, Some(followed)),
+            }
+        };
+
+        let claim_txid = stored.claim_txid();
+
+        if let Some(Err(err)) = &followed {
+            tracing::warn!("Stopped following swap {}: {}", stored.swap.id, err);
+        }
+
+        match result {
+            Ok(melt) if melt.state() == MeltQuoteState::Paid => {
+                if matches!(followed, Some(Ok(SwapStatus::Claimed { .. }))) {
+                    self.remove_swap(&stored).await?;
+                }
+
+                Ok(SwapMelt {
+                    swap: stored.swap,
+                    claim_txid,
+                    melt,
+                })
+            }
+            result => {
+                if self.swap_melt_failed(&melt_quote.id, &result).await {
+                    tracing::info!(
+                        "Swap {} failed, melt {} refunded",
+                        stored.swap.id,
+                        melt_quote.id
+                    );
+                    self.remove_swap(&stored).await?;
+                    report(
+                        progress,
+                        SwapProgress::Refunded {
+                            quote_id: melt_quote.id.clone(),
+                        },
+                    );
+                }
+
+                match result {
+                    Ok(melt) => Err(Error::SwapProvider(format!(
+                        "Melt of swap {} ended {:?}",
+                        stored.swap.id,
+                        melt.state()
+                    ))),
+                    Err(err) => Err(err),
+                }
+            }
+        }
+    }
+
+    /// Resume the swaps of [`Wallet::melt_onchain_via_swap`] that were not claimed or refunded
+    ///
+    /// Each swap is followed again until it ends, its lockup claimed once confirmed and a claim
+    /// built before broadcast again. Melts whose invoice was not paid are refunded. Swaps of
+    /// providers missing from `providers` are left for later.
+    #[instrument(skip(self, providers, progress))]
+    pub async fn resume_onchain_swaps(
+        &self,
+        providers: &[Arc<dyn SwapProvider>],
+        progress: Option<mpsc::UnboundedSender<SwapProgress>>,
+    ) -> Result<Vec<ResumedSwap>, Error> {
+        let progress = progress.as_ref();
+        let mut resumed = Vec::new();
+
+        for mut stored in self.stored_swaps().await? {
+            let Some(provider) = providers
+                .iter()
+                .find(|provider| provider.name() == stored.swap.provider)
+            else {
+                tracing::warn!(
+                    "Cannot resume swap {}, provider {} is missing",
+                    stored.swap.id,
+                    stored.swap.provider
+                );
+                continue;
+            };
+
+            // Without a melt quote the invoice was never paid
+            let Some(quote_id) = stored.melt_quote_id.clone() else {
+                self.remove_swap(&stored).await?;
+                resumed.push(ResumedSwap::Refunded { swap: stored.swap });
+                continue;
+            };
+
+            let quote = self.check_melt_quote_status(&quote_id).await?;
+            if matches!(quote.state, MeltQuoteState::Unpaid | MeltQuoteState::Failed) {
+                tracing::info!("Swap {} failed, melt {} refunded", stored.swap.id, quote_id);
+                self.remove_swap(&stored).await?;
+                report(progress, SwapProgress::Refunded { quote_id });
+                resumed.push(ResumedSwap::Refunded { swap: stored.swap });
+                continue;
+            }
+
+            tracing::info!("Resuming swap {} of melt {}", stored.swap.id, quote_id);
+            let followed = self
+                .follow_swap(provider.as_ref(), &mut stored, progress)
+                .await;
+            if let Err(err) = &followed {
+                tracing::warn!("Stopped following swap {}: {}", stored.swap.id, err);
+            }
+
+            let quote = self.check_melt_quote_status(&quote_id).await?;
+            match quote.state {
+                MeltQuoteState::Paid if matches!(followed, Ok(SwapStatus::Claimed { .. })) => {
+                    let claim_txid = stored.claim_txid();
+                    self.remove_swap(&stored).await?;
+                    resumed.push(ResumedSwap::Claimed {
+                        swap: stored.swap,
+                        claim_txid,
+                    });
+                }
+                MeltQuoteState::Unpaid | MeltQuoteState::Failed => {
+                    tracing::info!("Swap {} failed, melt {} refunded", stored.swap.id, quote_id);
+                    self.remove_swap(&stored).await?;
+                    report(progress, SwapProgress::Refunded { quote_id });
+                    resumed.push(ResumedSwap::Refunded { swap: stored.swap });
+                }
+                _ => resumed.push(ResumedSwap::Pending { swap: stored.swap }),
+            }
+        }
+
+        Ok(resumed)
+    }
+
+    /// Follow `stored` at its provider until the swap ends, claiming the lockup once confirmed
+    ///
+    /// Returns the last status of the swap.
+    async fn follow_swap(
+        &self,
+        provider: &dyn SwapProvider,
+        stored: &mut StoredSwap,
+        progress: Option<&mpsc::UnboundedSender<SwapProgress>>,
+    ) -> Result<SwapStatus, Error> {
+        let mut status = SwapStatus::Created;
+        let mut claimed = false;
+
+        loop {
+            let update = provider.wait_status_change(&stored.swap, &status).await?;
+            tracing::debug!("Swap {} is now {:?}", stored.swap.id, update);
+            report(progress, SwapProgress::Status(update.clone()));
+
+            match &update {
+                SwapStatus::LockupConfirmed { txid } if !claimed => {
+                    let mut attempt = 1;
+                    let claim_txid = loop {
+                        match self.claim_swap(provider, stored, txid).await {
+                            Ok(claim_txid) => break claim_txid,
+                            Err(err) if attempt < CLAIM_ATTEMPTS => {
+                                tracing::warn!(
+                                    "Could not claim swap {}, retrying: {}",
+                                    stored.swap.id,
+                                    err
+                                );
+                                attempt += 1;
+                            }
+                            Err(err) => return Err(err),
+                        }
+                    };
+
+                    tracing::info!("Claimed swap {} in {}", stored.swap.id, claim_txid);
+                    report(progress, SwapProgress::ClaimBroadcast { txid: claim_txid });
+                    claimed = true;
+                }
+                SwapStatus::Claimed { .. } | SwapStatus::Failed { .. } | SwapStatus::Expired => {
+                    return Ok(update)
+                }
+                _ => {}
+            }
+
+            status = update;
+        }
+    }
+
+    /// Broadcast the claim of the lockup `lockup_txid` of `stored`, returning its id
+    ///
+    /// The claim is signed and stored before it is first broadcast, which reveals the preimage.
+    /// A stored claim is broadcast again rather than signed anew.
+    async fn claim_swap(
+        &self,
+        provider: &dyn SwapProvider,
+        stored: &mut StoredSwap,
+        lockup_txid: &str,
+    ) -> Result<String, Error> {
+        let claim = match &stored.claim_transaction {
+            Some(claim) => deserialize_hex(claim)
+                .map_err(|err| Error::SwapProvider(format!("Invalid stored claim: {err}")))?,
+            None => {
+                let lockup = provider.get_transaction(lockup_txid).await?;
+                let claim = stored.build_claim(&lockup)?;
+                stored.claim_transaction = Some(serialize_hex(&claim));
+                self.store_swap(stored).await?;
+                claim
+            }
+        };
+
+        provider.broadcast_transaction(&claim).await
+    }
+
+    /// Whether the melt of the swap invoice with quote `quote_id`, ended with `result`, left the
+    /// invoice unpaid
+    async fn swap_melt_failed(
+        &self,
+        quote_id: &str,
+        result: &Result<FinalizedMelt, Error>,
+    ) -> bool {
+        match result {
+            Ok(melt) => matches!(
+                melt.state(),
+                MeltQuoteState::Unpaid | MeltQuoteState::Failed
+            ),
+            Err(_) => matches!(
+                self.check_melt_quote_status(quote_id).await,
+                Ok(quote) if matches!(quote.state, MeltQuoteState::Unpaid | MeltQuoteState::Failed)
+            ),
+        }
+    }
+
+    async fn store_swap(&self, stored: &StoredSwap) -> Result<(), Error> {
+        self.localstore
+            .kv_write(
+                ONCHAIN_SWAP_KV_NAMESPACE,
+                SWAPS_KV_SECONDARY_NAMESPACE,
+                &stored.key()?,
+                &serde_json::to_vec(stored)?,
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn remove_swap(&self, stored: &StoredSwap) -> Result<(), Error> {
+        self.localstore
+            .kv_remove(
+                ONCHAIN_SWAP_KV_NAMESPACE,
+                SWAPS_KV_SECONDARY_NAMESPACE,
+                &stored.key()?,
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Swaps of this wallet not yet claimed or refunded
+    async fn stored_swaps(&self) -> Result<Vec<StoredSwap>, Error> {
+        let mut swaps = Vec::new();
+
+        for key in self
+            .localstore
+            .kv_list(ONCHAIN_SWAP_KV_NAMESPACE, SWAPS_KV_SECONDARY_NAMESPACE)
+            .await?
+        {
+            let Some(value) = self
+                .localstore
+                .kv_read(
+                    ONCHAIN_SWAP_KV_NAMESPACE,
+                    SWAPS_KV_SECONDARY_NAMESPACE,
+                    &key,
+                )
+                .await?
+            else {
+                continue;
+            };
+
+            let stored: StoredSwap = serde_json::from_slice(&value)?;
+            if stored.mint_url == self.mint_url && stored.unit == self.unit {
+                swaps.push(stored);
+            }
+        }
+
+        Ok(swaps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use bitcoin::secp256k1::ecdsa::Signature;
+    use cdk_common::{MeltQuoteCreateResponse, MeltQuoteResponse};
+    use cdk_fake_wallet::create_fake_invoice_with_payment_hash;
+
+    use super::*;
+    use crate::nuts::MeltQuoteBolt11Response;
+    use crate::wallet::test_utils::{
+        create_test_db, create_test_wallet_with_mock, test_keyset_id, test_melt_quote,
+        test_mint_url, test_proof_info, MockMintConnector,
+    };
+
+    #[derive(Debug)]
+    struct QuotingProvider {
+        name: &'static str,
+        quote: Option<(u64, u64)>,
+    }
+
+    #[async_trait]
+    impl SwapProvider for QuotingProvider {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        async fn quote(&self, _amount: Amount) -> Result<SwapQuote, Error> {
+            let (onchain_amount, invoice_amount) = self
+                .quote
+                .ok_or_else(|| Error::SwapProvider("unavailable".to_string()))?;
+            Ok(SwapQuote {
+                provider: self.name.to_string(),
+                onchain_amount: Amount::from(onchain_amount),
+                invoice_amount: Amount::from(invoice_amount),
+                expiry: u64::MAX,
+            })
+        }
+
+        async fn create_swap(
+            &self,
+            _quote: &SwapQuote,
+            _address: &str,
+            _preimage_hash: sha256::Hash,
+            _claim_pubkey: PublicKey,
+        ) -> Result<SubmarineSwap, Error> {
+            Err(Error::SwapProvider(
+                "quoting provider does not create swaps".to_string(),
+            ))
+        }
+
+        async fn wait_status_change(
+            &self,
+            _swap: &SubmarineSwap,
+            _current: &SwapStatus,
+        ) -> Result<SwapStatus, Error> {
+            Err(Error::SwapProvider(
+                "quoting provider does not track swaps".to_string(),
+            ))
+        }
+
+        async fn get_transaction(&self, _txid: &str) -> Result<Transaction, Error> {
+            Err(Error::SwapProvider(
+                "quoting provider has no transactions".to_string(),
+            ))
+        }
+
+        async fn broadcast_transaction(&self, _transaction: &Transaction) -> Result<String, Error> {
+            Err(Error::SwapProvider(
+                "quoting provider does not broadcast".to_string(),
+            ))
+        }
+    }
+
+    /// Provider locking its funds as soon as the swap is created
+    #[derive(Debug)]
+    struct LockingProvider {
+        refund_key: SecretKey,
+        claim_pubkey: Mutex<Option<PublicKey>>,
+        lockup: Mutex<Option<Transaction>>,
+        broadcast: Mutex<Vec<Transaction>>,
+    }
+
+    impl LockingProvider {
+        fn new() -> Self {
+            Self {
+                refund_key: SecretKey::generate(),
+                claim_pubkey: Mutex::new(None),
+                lockup: Mutex::new(None),
+                broadcast: Mutex::new(Vec::new()),
+            }
+        }
+
+        fn lockup(&self) -> Transaction {
+            self.lockup.lock().unwrap().clone().expect("swap created")
+        }
+    }
+
+    #[async_trait]
+    impl SwapProvider for LockingProvider {
+        fn name(&self) -> &str {
+            "locking"
+        }
+
+        async fn quote(&self, amount: Amount) -> Result<SwapQuote, Error> {
+            Ok(SwapQuote {
+                provider: self.name().to_string(),
+                onchain_amount: amount,
+                invoice_amount: amount + Amount::from(10),
+                expiry: u64::MAX,
+            })
+        }
+
+        async fn create_swap(
+            &self,
+            quote: &SwapQuote,
+            address: &str,
+            preimage_hash: sha256::Hash,
+            claim_pubkey: PublicKey,
+        ) -> Result<SubmarineSwap, Error> {
+            let swap = SubmarineSwap {
+                id: "swap".to_string(),
+                provider: self.name().to_string(),
+                invoice: create_fake_invoice_with_payment_hash(
+                    u64::from(quote.invoice_amount) * 1000,
+                    "swap".to_string(),
+                    preimage_hash,
+                )
+                .to_string(),
+                address: address.to_string(),
+                onchain_amount: quote.onchain_amount,
+                refund_pubkey: self.refund_key.public_key(),
+                timeout_block_height: 800_000,
+            };
+
+            let script = swap_script(
+                &preimage_hash,
+                &claim_pubkey,
+                &swap.refund_pubkey,
+                swap.timeout_block_height,
+            );
+            let lockup = Transaction {
+                version: transaction::Version::TWO,
+                lock_time: absolute::LockTime::ZERO,
+                input: vec![TxIn::default()],
+                output: vec![TxOut {
+                    value: bitcoin::Amount::from_sat(u64::from(quote.onchain_amount) + 500),
+                    script_pubkey: ScriptBuf::new_p2wsh(&script.wscript_hash()),
+                }],
+            };
+
+            *self.claim_pubkey.lock().unwrap() = Some(claim_pubkey);
+            *self.lockup.lock().unwrap() = Some(lockup);
+            Ok(swap)
+        }
+
+        async fn wait_status_change(
+            &self,
+            _swap: &SubmarineSwap,
+            current: &SwapStatus,
+        ) -> Result<SwapStatus, Error> {
+            if let Some(claim) = self.broadcast.lock().unwrap().last() {
+                return Ok(SwapStatus::Claimed {
+                    txid: claim.compute_txid().to_string(),
+                });
+            }
+
+            match current {
+                SwapStatus::LockupConfirmed { .. } => Err(Error::SwapProvider(
+                    "lockup confirmed but never claimed".to_string(),
+                )),
+                _ => Ok(SwapStatus::LockupConfirmed {
+                    txid: self.lockup().compute_txid().to_string(),
+                }),
+            }
+        }
+
+        async fn get_transaction(&self, txid: &str) -> Result<Transaction, Error> {
+            let lockup = self.lockup();
+            match lockup.compute_txid().to_string() == txid {
+                true => Ok(lockup),
+                false => Err(Error::SwapProvider("unknown transaction".to_string())),
+            }
+        }
+
+        async fn broadcast_transaction(&self, transaction: &Transaction) -> Result<String, Error> {
+            self.broadcast.lock().unwrap().push(transaction.clone());
+            Ok(transaction.compute_txid().to_string())
+        }
+    }
+
+    fn regtest_address() -> String {
+        Address::p2wpkh(
+            &bitcoin::CompressedPublicKey(*SecretKey::generate().public_key()),
+            bitcoin::Network::Regtest,
+        )
+        .to_string()
+    }
+
+    fn melt_quote_response(quote: &str, state: MeltQuoteState) -> MeltQuoteBolt11Response<String> {
+        MeltQuoteBolt11Response {
+            quote: quote.to_string(),
+            amount: Amount::from(1000),
+            fee_reserve: Amount::from(23),
+            state,
+            expiry: 9999999999,
+            payment_preimage: None,
+            change: None,
+            request: None,
+            unit: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn quotes_are_compared_skipping_failing_and_wrong_providers() {
+        let providers: Vec<Arc<dyn SwapProvider>> = vec![
+            Arc::new(QuotingProvider {
+                name: "expensive",
+                quote: Some((50_000, 50_900)),
+            }),
+            Arc::new(QuotingProvider {
+                name: "down",
+                quote: None,
+            }),
+            Arc::new(QuotingProvider {
+                name: "short",
+                quote: Some((49_000, 49_100)),
+            }),
+            Arc::new(QuotingProvider {
+                name: "cheap",
+                quote: Some((50_000, 50_300)),
+            }),
+        ];
+
+        let quotes = cheapest_quotes(&providers, Amount::from(50_000))
+            .await
+            .expect("quotes");
+        let names: Vec<_> = quotes.iter().map(|quote| quote.provider.as_str()).collect();
+        assert_eq!(names, vec!["cheap", "expensive"]);
+        assert_eq!(quotes[0].fee(), Amount::from(300));
+
+        assert!(cheapest_quotes(&providers[1..2], Amount::from(50_000))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn swap_is_claimed_by_the_wallet() {
+        let db = create_test_db().await;
+        // Melt amount, fee reserve and input fee add up to the single proof
+        db.update_proofs(
+            vec![test_proof_info(test_keyset_id(), 1024, test_mint_url())],
+            vec![],
+        )
+        .await
+        .unwrap();
+
+        let mock_client = Arc::new(MockMintConnector::new());
+        mock_client.reset_default_mint_state();
+        mock_client.set_post_melt_quote_response(Ok(MeltQuoteCreateResponse::Bolt11(
+            melt_quote_response("swap-melt", MeltQuoteState::Unpaid),
+        )));
+        mock_client.set_post_melt_response(Ok(MeltQuoteResponse::Bolt11(melt_quote_response(
+            "swap-melt",
+            MeltQuoteState::Paid,
+        ))));
+        let wallet = create_test_wallet_with_mock(db, mock_client).await;
+
+        let provider = Arc::new(LockingProvider::new());
+        let providers: Vec<Arc<dyn SwapProvider>> = vec![provider.clone()];
+        let address = regtest_address();
+
+        let swap_melt = wallet
+            .melt_onchain_via_swap(&providers, &address, Amount::from(990), None, None)
+            .await
+            .unwrap();
+        assert_eq!(swap_melt.melt.state(), MeltQuoteState::Paid);
+
+        // The claim spends the lockup to the address with the preimage of the invoice
+        let broadcast = provider.broadcast.lock().unwrap().clone();
+        assert_eq!(broadcast.len(), 1);
+        let claim = &broadcast[0];
+        assert_eq!(swap_melt.claim_txid, Some(claim.compute_txid().to_string()));
+
+        let lockup = provider.lockup();
+        assert_eq!(
+            claim.input[0].previous_output,
+            OutPoint {
+                txid: lockup.compute_txid(),
+                vout: 0
+            }
+        );
+        assert_eq!(claim.output.len(), 1);
+        assert_eq!(claim.output[0].value.to_sat(), 990);
+        assert_eq!(
+            claim.output[0].script_pubkey,
+            Address::from_str(&address)
+                .unwrap()
+                .assume_checked()
+                .script_pubkey()
+        );
+
+        let witness: Vec<&[u8]> = claim.input[0].witness.iter().collect();
+        assert_eq!(witness.len(), 3);
+        let invoice = Bolt11Invoice::from_str(&swap_melt.swap.invoice).unwrap();
+        assert_eq!(
+            sha256::Hash::hash(witness[1]).as_byte_array(),
+            invoice.payment_hash().as_byte_array()
+        );
+
+        // Signed with the claim key the wallet gave the provider
+        let script = ScriptBuf::from_bytes(witness[2].to_vec());
+        let sighash = SighashCache::new(claim)
+            .p2wsh_signature_hash(0, &script, lockup.output[0].value, EcdsaSighashType::All)
+            .unwrap();
+        let signature = Signature::from_der(&witness[0][..witness[0].len() - 1]).unwrap();
+        let claim_pubkey = provider.claim_pubkey.lock().unwrap().expect("claim pubkey");
+        SECP256K1
+            .verify_ecdsa(
+                &Message::from_digest(sighash.to_byte_array()),
+                &signature,
+                &claim_pubkey,
+            )
+            .unwrap();
+
+        // The claimed swap is no longer stored
+        assert!(wallet.stored_swaps().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn resumed_swap_with_failed_melt_is_refunded() {
+        let db = create_test_db().await;
+
+        let mut quote = test_melt_quote();
+        quote.request = create_fake_invoice_with_payment_hash(
+            1_000_000,
+            "swap".to_string(),
+            sha256::Hash::hash(&[1; 32]),
+        )
+        .to_string();
+        let quote_id = quote.id.clone();
+        db.add_melt_quote(quote).await.unwrap();
+
+        let mock_client = Arc::new(MockMintConnector::new());
+        mock_client.reset_default_mint_state();
+        mock_client.set_melt_quote_status_response(Ok(melt_quote_response(
+            &quote_id,
+            MeltQuoteState::Failed,
+        )));
+        let wallet = create_test_wallet_with_mock(db, mock_client).await;
+
+        let provider = Arc::new(LockingProvider::new());
+        let stored = StoredSwap {
+            mint_url: test_mint_url(),
+            unit: CurrencyUnit::Sat,
+            swap: SubmarineSwap {
+                id: "swap".to_string(),
+                provider: provider.name().to_string(),
+                invoice: String::new(),
+                address: regtest_address(),
+                onchain_amount: Amount::from(990),
+                refund_pubkey: provider.refund_key.public_key(),
+                timeout_block_height: 800_000,
+            },
+            preimage: hex::encode([1; 32]),
+            claim_key: SecretKey::generate(),
+            melt_quote_id: Some(quote_id),
+            claim_transaction: None,
+        };
+        wallet.store_swap(&stored).await.unwrap();
+
+        let providers: Vec<Arc<dyn SwapProvider>> = vec![provider.clone()];
+        let resumed = wallet.resume_onchain_swaps(&providers, None).await.unwrap();
+
+        assert_eq!(
+            resumed,
+            vec![ResumedSwap::Refunded {
+                swap: stored.swap.clone()
+            }]
+        );
+        assert!(provider.broadcast.lock().unwrap().is_empty());
+        assert!(wallet.stored_swaps().await.unwrap().is_empty());
+    }
+}
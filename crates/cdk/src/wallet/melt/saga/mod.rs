@@ -48,13 +48,14 @@ use self::compensation::{ReleaseMeltQuote, RevertProofReservation};
 use self::state::{Finalized, Initial, MeltRequested, PaymentPending, Prepared};
 use super::MeltConfirmOptions;
 use crate::nuts::nut00::{KnownMethod, ProofsMethods};
-use crate::nuts::{MeltRequest, PreMintSecrets, Proofs, State};
+use crate::nuts::{MeltRequest, PreMintSecrets, Proofs, SpendingConditionVerification, State};
 use crate::util::unix_time;
 use crate::wallet::blind_signature::{
     validate_mint_response_signatures, SignatureAmountValidation,
 };
 use crate::wallet::keysets::KeysetFilter;
 use crate::wallet::saga::{add_compensation, new_compensations, Compensations};
+use crate::wallet::util::sig_all_signing_keys;
 use crate::{ensure_cdk, Amount, Error, Wallet};
 
 pub(crate) mod compensation;
@@ -250,9 +251,13 @@ impl<'a> MeltSaga<'a, Initial> {
             .await?
             .ok_or(Error::UnknownQuote)?;
 
+        let now = unix_time();
         ensure_cdk!(
-            quote_info.expiry.gt(&unix_time()),
-            Error::ExpiredQuote(quote_info.expiry, unix_time())
+            quote_info
+                .expiry
+                .saturating_add(self.wallet.clock_skew_grace_secs)
+                > now,
+            Error::ExpiredQuote(quote_info.expiry, now)
         );
 
         // Reserve the quote to prevent concurrent operations from using it
@@ -872,6 +877,21 @@ impl std::fmt::Debug for MeltSaga<'_, PaymentPending> {
 }
 
 impl<'a> MeltSaga<'a, MeltRequested> {
+    /// Sign `request` with SIG_ALL when any of its inputs requires it
+    async fn sign_melt_sig_all(&self, request: &mut MeltRequest<String>) -> Result<(), Error> {
+        let Some(keys) = sig_all_signing_keys(self.wallet, request.inputs(), &[]).await? else {
+            return Ok(());
+        };
+
+        for key in keys {
+            request.sign_sig_all(key)?;
+        }
+
+        request.verify_spending_conditions_with_grace(self.wallet.clock_skew_grace_secs)?;
+
+        Ok(())
+    }
+
     /// Execute the melt request with async support.
     #[instrument(skip_all)]
     pub async fn execute_async(
@@ -898,12 +918,19 @@ impl<'a> MeltSaga<'a, MeltRequested> {
         )
         .prefer_async(true);
 
-        let request = if quote_info.payment_method == PaymentMethod::Known(KnownMethod::Onchain) {
+        let mut request = if quote_info.payment_method == PaymentMethod::Known(KnownMethod::Onchain)
+        {
             request.fee_index(quote_info.fee_index.ok_or(Error::InvalidPaymentRequest)?)
         } else {
             request
         };
 
+        // Inputs locked with SIG_ALL are signed together with the outputs and the quote
+        if let Err(err) = self.sign_melt_sig_all(&mut request).await {
+            self.handle_failure().await;
+            return Err(err);
+        }
+
         let melt_result = self
             .wallet
             .client
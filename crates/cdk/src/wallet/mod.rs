@@ -38,6 +38,7 @@ use crate::wallet::mint_metadata_cache::MintMetadataCache;
 use crate::wallet::p2pk::{P2PK_ACCOUNT, P2PK_PURPOSE};
 use crate::Amount;
 
+mod audit;
 mod auth;
 pub mod bip321;
 mod blind_signature;
@@ -47,8 +48,10 @@ mod nostr_backup;
 pub use mint_connector::TorHttpClient;
 mod balance;
 mod builder;
+mod counters;
 mod issue;
 mod keysets;
+mod maintenance;
 mod melt;
 mod mint_connector;
 mod mint_metadata_cache;
@@ -61,6 +64,7 @@ mod receive;
 mod reclaim;
 mod recovery;
 pub(crate) mod saga;
+mod seed_provider;
 mod send;
 #[cfg(not(target_arch = "wasm32"))]
 mod streams;
@@ -72,7 +76,9 @@ pub mod util;
 pub mod wallet_repository;
 mod wallet_trait;
 
+pub use audit::{AuditOptions, AuditReport, FlaggedProof, ProofAnomaly};
 pub use auth::{AuthMintConnector, AuthWallet};
+pub use balance::WalletBalance;
 #[cfg(all(feature = "bip353", not(target_arch = "wasm32")))]
 pub use bip321::resolve_bip353_payment_instruction;
 pub use bip321::{
@@ -83,8 +89,13 @@ pub use cdk_common::wallet as types;
 pub use cdk_common::wallet::{
     NUT13Options, P2PKLockedProofSendMode, ReceiveOptions, SendMemo, SendOptions,
 };
+pub use counters::KeysetCounterSync;
 pub use keysets::KeysetFilter;
-pub use melt::{MeltConfirmOptions, MeltOutcome, PendingMelt, PreparedMelt};
+pub use maintenance::{MaintenanceOptions, MaintenanceReport};
+pub use melt::{
+    MeltConfirmOptions, MeltOutcome, PendingMelt, PreparedMelt, ResumedSwap, SubmarineSwap,
+    SwapMelt, SwapProgress, SwapProvider, SwapQuote, SwapStatus,
+};
 pub use mint_connector::transport::Transport as HttpTransport;
 pub use mint_connector::{
     AuthHttpClient, HttpClient, LnurlPayInvoiceResponse, LnurlPayResponse, MintConnector,
@@ -93,10 +104,12 @@ pub use mint_connector::{
 pub use nostr_backup::{BackupOptions, BackupResult, RestoreOptions, RestoreResult};
 #[cfg(feature = "npubcash")]
 pub use npubcash::derive_npubcash_secret_key_from_seed;
-pub use payment_request::CreateRequestParams;
 #[cfg(feature = "nostr")]
 pub use payment_request::NostrWaitInfo;
-pub use recovery::RecoveryReport;
+pub use payment_request::{CreateRequestParams, PaymentRequestPart, PaymentRequestReceipt};
+pub use receive::{QuarantinedReceive, ReceiveRisk, ReceiveRiskAssessment, VoucherClaim};
+pub use recovery::{RecoveredMintQuote, RecoveryReport, UnmintedQuotesRecovery};
+pub use seed_provider::{ExternalSigner, ExternalSignerSeedProvider, SeedProvider};
 pub use send::PreparedSend;
 #[cfg(all(feature = "npubcash", not(target_arch = "wasm32")))]
 pub use streams::npubcash::NpubCashProofStream;
@@ -131,6 +144,12 @@ pub struct Wallet {
     pub metadata_cache: Arc<MintMetadataCache>,
     /// The targeted amount of proofs to have at each size
     pub target_proof_count: usize,
+    /// Seconds quote expiries and locktimes are extended by, to tolerate clock skew with the
+    /// mint
+    pub clock_skew_grace_secs: u64,
+    /// Largest fee, in parts per thousand of the amount, paid to claim received proofs, none to
+    /// receive whatever the fee
+    pub max_receive_fee_ppk: Option<u64>,
     metadata_cache_ttl: Arc<RwLock<Option<Duration>>>,
     auth_wallet: Arc<TokioRwLock<Option<AuthWallet>>>,
     #[cfg(feature = "npubcash")]
@@ -918,6 +937,24 @@ impl Wallet {
         self.target_proof_count = count;
     }
 
+    /// Set the clock skew grace period, in seconds, for this wallet
+    ///
+    /// Quotes are considered valid this long after their expiry, and locktimes are treated as
+    /// passed this long before they are reached.
+    pub fn set_clock_skew_grace(&mut self, secs: u64) {
+        self.clock_skew_grace_secs = secs;
+    }
+
+    /// Refuse to receive proofs whose claim fee is above `max_fee_ppk` parts per thousand of
+    /// their amount
+    ///
+    /// Spares users from claiming dust that costs more in input fees than it is worth. A receive
+    /// can go ahead anyway with [`ReceiveOptions::ignore_fee_threshold`]. `None`, the default,
+    /// receives whatever the fee.
+    pub fn set_max_receive_fee(&mut self, max_fee_ppk: Option<u64>) {
+        self.max_receive_fee_ppk = max_fee_ppk;
+    }
+
     /// generates and stores public key in database
     pub async fn generate_public_key(&self) -> Result<PublicKey, Error> {
         let public_keys = self.localstore.list_p2pk_keys().await?;
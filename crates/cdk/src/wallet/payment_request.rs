@@ -17,17 +17,190 @@ use nostr_sdk::prelude::*;
 #[cfg(feature = "nostr")]
 use nostr_sdk::{Client as NostrClient, EventBuilder, FromBech32, Keys, ToBech32};
 
+use crate::amount::SplitTarget;
 use crate::error::Error;
 use crate::mint_url::MintUrl;
 use crate::nuts::nut10::{Conditions, SpendingConditions};
 use crate::nuts::nut11::SigFlag;
 use crate::nuts::nut18::Nut10SecretRequest;
-use crate::nuts::{CurrencyUnit, Nut10Secret, Transport};
+use crate::nuts::{CurrencyUnit, Nut10Secret, ProofsMethods, Token, Transport};
 #[cfg(feature = "nostr")]
 use crate::wallet::ReceiveOptions;
-use crate::wallet::{SendOptions, WalletRepository};
+use crate::wallet::{PreparedSend, SendOptions, WalletRepository};
 use crate::Wallet;
 
+/// Share of a payment request paid from one mint
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaymentRequestPart {
+    /// Mint the token of this share was created at
+    pub mint_url: MintUrl,
+    /// Amount paid to the receiver
+    pub amount: Amount,
+    /// Fee paid by the sender on top of `amount`
+    pub fee: Amount,
+}
+
+/// Combined receipt of a payment request paid from one or several mints
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaymentRequestReceipt {
+    /// Id of the payment request, if it has one
+    pub payment_id: Option<String>,
+    /// Unit of the payment
+    pub unit: CurrencyUnit,
+    /// One share per mint, each delivered to the receiver as its own payload
+    pub parts: Vec<PaymentRequestPart>,
+}
+
+impl PaymentRequestReceipt {
+    /// Total amount paid to the receiver
+    pub fn amount(&self) -> Result<Amount, Error> {
+        Ok(Amount::try_sum(self.parts.iter().map(|part| part.amount))?)
+    }
+
+    /// Total fee paid by the sender
+    pub fn fee(&self) -> Result<Amount, Error> {
+        Ok(Amount::try_sum(self.parts.iter().map(|part| part.fee))?)
+    }
+}
+
+/// Amount the request asks for, or `custom_amount` when it does not say
+fn request_amount(
+    payment_request: &PaymentRequest,
+    custom_amount: Option<Amount>,
+) -> Result<Amount, Error> {
+    payment_request
+        .amount
+        .or(custom_amount)
+        .ok_or(Error::AmountUndefined)
+}
+
+/// Spending conditions the paid ecash must be locked to
+///
+/// NUT-18 encodes spending conditions in the optional `nut10` field using
+/// `Nut10SecretRequest` (kind + data + tags). To actually create locked
+/// ecash, we need full NUT-10 secrets, so we:
+///   1. Convert `Nut10SecretRequest` -> `Nut10Secret` (adds nonce, keeps tags)
+///   2. Convert `Nut10Secret` -> `SpendingConditions` (NUT-11 helper)
+fn request_conditions(
+    payment_request: &PaymentRequest,
+) -> Result<Option<SpendingConditions>, Error> {
+    payment_request
+        .nut10
+        .as_ref()
+        .map(|nut10_request| {
+            let secret: Nut10Secret = nut10_request.clone().into();
+            SpendingConditions::try_from(secret)
+        })
+        .transpose()
+        .map_err(Error::from)
+}
+
+/// Transport to deliver the payment with, preferring Nostr to avoid revealing the IP
+fn request_transport(payment_request: &PaymentRequest) -> Option<&Transport> {
+    let transports = &payment_request.transports;
+
+    transports
+        .iter()
+        .find(|t| t._type == TransportType::Nostr)
+        .or_else(|| {
+            transports
+                .iter()
+                .find(|t| t._type == TransportType::HttpPost)
+        })
+}
+
+/// Deliver `payload` to the receiver over `transport`
+async fn deliver_payload(
+    transport: &Transport,
+    payload: &PaymentRequestPayload,
+) -> Result<(), Error> {
+    match transport._type {
+        TransportType::Nostr => {
+            #[cfg(feature = "nostr")]
+            {
+                let keys = Keys::generate();
+                let client = NostrClient::new(keys.clone());
+                let nprofile = Nip19Profile::from_bech32(&transport.target)
+                    .map_err(|e| Error::Custom(format!("Invalid nprofile: {e}")))?;
+
+                let rumor = EventBuilder::new(
+                    nostr_sdk::Kind::from_u16(14),
+                    serde_json::to_string(payload)
+                        .map_err(|e| Error::Custom(format!("Serialize payload: {e}")))?,
+                )
+                .build(keys.public_key);
+                let relays = nprofile.relays;
+
+                for relay in relays.iter() {
+                    client
+                        .add_write_relay(relay)
+                        .await
+                        .map_err(|e| Error::Custom(format!("Add relay {relay}: {e}")))?;
+                }
+
+                client.connect().await;
+
+                let gift_wrap = client
+                    .gift_wrap_to(relays, &nprofile.public_key, rumor, None)
+                    .await
+                    .map_err(|e| Error::Custom(format!("Publish Nostr event: {e}")))?;
+
+                tracing::info!(
+                    "Published event {} successfully to {}",
+                    gift_wrap.val,
+                    gift_wrap
+                        .success
+                        .iter()
+                        .map(|s| s.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                );
+
+                if !gift_wrap.failed.is_empty() {
+                    tracing::warn!(
+                        "Could not publish to {}",
+                        gift_wrap
+                            .failed
+                            .keys()
+                            .map(|relay| relay.to_string())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    );
+                }
+
+                Ok(())
+            }
+            #[cfg(not(feature = "nostr"))]
+            {
+                let _ = payload;
+                Err(Error::Custom(
+                    "Nostr is not enabled in this build".to_string(),
+                ))
+            }
+        }
+
+        TransportType::HttpPost => {
+            let client = HttpClient::new();
+
+            let res = client
+                .post(&transport.target)
+                .json(payload)
+                .send()
+                .await
+                .map_err(|e| Error::HttpError(None, e.to_string()))?;
+
+            if res.is_success() {
+                tracing::info!("Successfully posted payment");
+                Ok(())
+            } else {
+                let status = res.status();
+                let body = res.text().await.unwrap_or_default();
+                Err(Error::HttpError(Some(status), body))
+            }
+        }
+    }
+}
+
 impl Wallet {
     /// Pay a NUT-18 PaymentRequest using a specific wallet.
     ///
@@ -39,154 +212,122 @@ impl Wallet {
         payment_request: PaymentRequest,
         custom_amount: Option<Amount>,
     ) -> Result<(), Error> {
-        let amount = match payment_request.amount {
-            Some(amount) => amount,
-            None => match custom_amount {
-                Some(a) => a,
-                None => return Err(Error::AmountUndefined),
-            },
-        };
+        let amount = request_amount(&payment_request, custom_amount)?;
+        let conditions = request_conditions(&payment_request)?;
+        let transport = request_transport(&payment_request);
+
+        let token = self
+            .prepare_request_send(amount, conditions)
+            .await?
+            .confirm(None)
+            .await?;
 
-        // Extract optional NUT-10 spending conditions from the payment request.
-        //
-        // NUT-18 encodes spending conditions in the optional `nut10` field using
-        // `Nut10SecretRequest` (kind + data + tags). To actually create locked
-        // ecash, we need full NUT-10 secrets, so we:
-        //   1. Convert `Nut10SecretRequest` -> `Nut10Secret` (adds nonce, keeps tags)
-        //   2. Convert `Nut10Secret` -> `SpendingConditions` (NUT-11 helper)
-        let conditions = if let Some(nut10_request) = &payment_request.nut10 {
-            let secret: Nut10Secret = nut10_request.clone().into();
-            Some(SpendingConditions::try_from(secret)?)
-        } else {
-            None
-        };
+        if let Some(transport) = transport {
+            let payload = self
+                .request_payload(payment_request.payment_id.clone(), &token)
+                .await?;
 
-        let transports = payment_request.transports.clone();
-
-        // Prefer Nostr to avoid revealing IP, fall back to HTTP POST.
-        let transport = transports
-            .iter()
-            .find(|t| t._type == TransportType::Nostr)
-            .or_else(|| {
-                transports
-                    .iter()
-                    .find(|t| t._type == TransportType::HttpPost)
-            });
-
-        let prepared_send = self
-            .prepare_send(
-                amount,
-                SendOptions {
-                    conditions,
-                    include_fee: true,
-                    ..Default::default()
-                },
-            )
-            .await?;
+            deliver_payload(transport, &payload).await
+        } else {
+            // If no transport is available, return an error instead of printing the token
+            Err(Error::Custom(
+                "No transport available in payment request".to_string(),
+            ))
+        }
+    }
 
-        let token = prepared_send.confirm(None).await?;
+    /// Prepare sending `amount` for a payment request, the sender paying the receiver's fee
+    async fn prepare_request_send(
+        &self,
+        amount: Amount,
+        conditions: Option<SpendingConditions>,
+    ) -> Result<PreparedSend<'_>, Error> {
+        self.prepare_send(
+            amount,
+            SendOptions {
+                conditions,
+                include_fee: true,
+                ..Default::default()
+            },
+        )
+        .await
+    }
 
+    /// Payload carrying the proofs of `token` to the receiver
+    async fn request_payload(
+        &self,
+        payment_id: Option<String>,
+        token: &Token,
+    ) -> Result<PaymentRequestPayload, Error> {
         // We need the keysets information to properly convert from token proof to proof
-        let proofs = self.token_proofs(&token).await?;
+        let proofs = self.token_proofs(token).await?;
+
+        Ok(PaymentRequestPayload {
+            id: payment_id,
+            memo: None,
+            mint: self.mint_url.clone(),
+            unit: self.unit.clone(),
+            proofs,
+        })
+    }
 
-        if let Some(transport) = transport {
-            let payload = PaymentRequestPayload {
-                id: payment_request.payment_id.clone(),
-                memo: None,
-                mint: self.mint_url.clone(),
-                unit: self.unit.clone(),
-                proofs,
-            };
+    /// Largest amount this wallet can pay towards a request, fees included
+    ///
+    /// Conservative: assumes every unspent proof is an input and the whole balance is sent.
+    async fn request_spendable(&self) -> Result<Amount, Error> {
+        let proofs = self.get_unspent_proofs().await?;
+        let balance = proofs.total_amount()?;
 
-            match transport._type {
-                TransportType::Nostr => {
-                    #[cfg(feature = "nostr")]
-                    {
-                        let keys = Keys::generate();
-                        let client = NostrClient::new(keys.clone());
-                        let nprofile = Nip19Profile::from_bech32(&transport.target)
-                            .map_err(|e| Error::Custom(format!("Invalid nprofile: {e}")))?;
-
-                        let rumor = EventBuilder::new(
-                            nostr_sdk::Kind::from_u16(14),
-                            serde_json::to_string(&payload)
-                                .map_err(|e| Error::Custom(format!("Serialize payload: {e}")))?,
-                        )
-                        .build(keys.public_key);
-                        let relays = nprofile.relays;
-
-                        for relay in relays.iter() {
-                            client
-                                .add_write_relay(relay)
-                                .await
-                                .map_err(|e| Error::Custom(format!("Add relay {relay}: {e}")))?;
-                        }
+        if balance == Amount::ZERO {
+            return Ok(Amount::ZERO);
+        }
 
-                        client.connect().await;
-
-                        let gift_wrap = client
-                            .gift_wrap_to(relays, &nprofile.public_key, rumor, None)
-                            .await
-                            .map_err(|e| Error::Custom(format!("Publish Nostr event: {e}")))?;
-
-                        tracing::info!(
-                            "Published event {} successfully to {}",
-                            gift_wrap.val,
-                            gift_wrap
-                                .success
-                                .iter()
-                                .map(|s| s.to_string())
-                                .collect::<Vec<_>>()
-                                .join(", ")
-                        );
-
-                        if !gift_wrap.failed.is_empty() {
-                            tracing::warn!(
-                                "Could not publish to {}",
-                                gift_wrap
-                                    .failed
-                                    .keys()
-                                    .map(|relay| relay.to_string())
-                                    .collect::<Vec<_>>()
-                                    .join(", ")
-                            );
-                        }
+        let input_fee = self.get_proofs_fee(&proofs).await?.total;
 
-                        Ok(())
-                    }
-                    #[cfg(not(feature = "nostr"))]
-                    Err(Error::Custom(
-                        "Nostr is not enabled in this build".to_string(),
-                    ))
-                }
+        let active_keyset_id = self.fetch_active_keyset().await?.id;
+        let fee_and_amounts = self
+            .get_keyset_fees_and_amounts_by_id(active_keyset_id)
+            .await?;
+        let output_count = balance
+            .split_targeted(&SplitTarget::default(), &fee_and_amounts)?
+            .len();
+        let redeem_fee = self
+            .get_keyset_count_fee(&active_keyset_id, output_count as u64)
+            .await?;
 
-                TransportType::HttpPost => {
-                    let client = HttpClient::new();
+        Ok(balance
+            .checked_sub(input_fee)
+            .and_then(|amount| amount.checked_sub(redeem_fee))
+            .unwrap_or(Amount::ZERO))
+    }
+}
 
-                    let res = client
-                        .post(&transport.target)
-                        .json(&payload)
-                        .send()
-                        .await
-                        .map_err(|e| Error::HttpError(None, e.to_string()))?;
+/// Split `amount` over `spendable` greedily, largest first
+///
+/// Returns the index into `spendable` and the share of each mint used, or `None` when the
+/// mints can not cover `amount` together.
+fn plan_split(spendable: &[Amount], amount: Amount) -> Option<Vec<(usize, Amount)>> {
+    let mut order: Vec<usize> = (0..spendable.len()).collect();
+    order.sort_by(|a, b| spendable[*b].cmp(&spendable[*a]));
+
+    let mut remaining = amount;
+    let mut parts = Vec::new();
+
+    for index in order {
+        if remaining == Amount::ZERO {
+            break;
+        }
 
-                    if res.is_success() {
-                        tracing::info!("Successfully posted payment");
-                        Ok(())
-                    } else {
-                        let status = res.status();
-                        let body = res.text().await.unwrap_or_default();
-                        Err(Error::HttpError(Some(status), body))
-                    }
-                }
-            }
-        } else {
-            // If no transport is available, return an error instead of printing the token
-            Err(Error::Custom(
-                "No transport available in payment request".to_string(),
-            ))
+        let share = spendable[index].min(remaining);
+        if share == Amount::ZERO {
+            continue;
         }
+
+        parts.push((index, share));
+        remaining = remaining.checked_sub(share)?;
     }
+
+    (remaining == Amount::ZERO).then_some(parts)
 }
 
 /// Parameters for creating a PaymentRequest
@@ -246,6 +387,9 @@ impl WalletRepository {
     ///   1. Is accepted by the payment request (matches one of the request's mints, or request accepts any mint)
     ///   2. Has the highest balance among matching mints
     ///
+    ///   When no single mint has enough balance, the payment is split across the accepted mints
+    ///   with [`WalletRepository::pay_request_split`].
+    ///
     /// # Arguments
     ///
     /// * `payment_request` - The NUT-18 payment request to pay
@@ -320,9 +464,16 @@ impl WalletRepository {
                 }
             }
 
-            best_wallet
-                .map(|w| (*w).clone())
-                .ok_or(Error::InsufficientFunds)?
+            match best_wallet {
+                Some(wallet) => (*wallet).clone(),
+                None => {
+                    // No single mint can pay, split the payment across the accepted mints
+                    return self
+                        .pay_request_split(payment_request, custom_amount)
+                        .await
+                        .map(|_| ());
+                }
+            }
         };
 
         // Use the selected wallet to pay the request
@@ -331,6 +482,125 @@ impl WalletRepository {
             .await
     }
 
+    /// Pay a NUT-18 PaymentRequest with tokens from several mints
+    ///
+    /// The amount is split over the accepted mints of the request unit, taking as much as
+    /// possible from the mints with the highest balance first. Every share is sent as its own
+    /// payload carrying the payment id of the request, the sender paying the fees.
+    ///
+    /// All shares are prepared before any is confirmed, and the prepared shares are cancelled
+    /// when one of them can not be prepared.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The payment request has no amount and no custom amount is provided
+    /// - No transport is available in the payment request
+    /// - The payment request is single use and the payment needs more than one mint
+    /// - The accepted mints together do not have sufficient balance
+    /// - Delivering a share fails, the shares delivered before it are not taken back
+    pub async fn pay_request_split(
+        &self,
+        payment_request: PaymentRequest,
+        custom_amount: Option<Amount>,
+    ) -> Result<PaymentRequestReceipt, Error> {
+        let amount = request_amount(&payment_request, custom_amount)?;
+        let conditions = request_conditions(&payment_request)?;
+        let transport = request_transport(&payment_request).ok_or_else(|| {
+            Error::Custom("No transport available in payment request".to_string())
+        })?;
+
+        let accepted_mints = &payment_request.mints;
+        let unit = payment_request.unit.clone().unwrap_or(CurrencyUnit::Sat);
+
+        let mut wallets = Vec::new();
+        let mut spendable = Vec::new();
+
+        for wallet_key in self.get_balances().await?.keys() {
+            if wallet_key.unit != unit
+                || (!accepted_mints.is_empty() && !accepted_mints.contains(&wallet_key.mint_url))
+            {
+                continue;
+            }
+
+            let Ok(wallet) = self.get_wallet(&wallet_key.mint_url, &unit).await else {
+                continue;
+            };
+
+            match wallet.request_spendable().await {
+                Ok(available) => {
+                    wallets.push(wallet);
+                    spendable.push(available);
+                }
+                Err(err) => {
+                    tracing::warn!(
+                        "Could not get spendable balance of {}: {}",
+                        wallet_key.mint_url,
+                        err
+                    );
+                }
+            }
+        }
+
+        let plan = plan_split(&spendable, amount).ok_or(Error::InsufficientFunds)?;
+
+        if plan.len() > 1 && payment_request.single_use == Some(true) {
+            return Err(Error::Custom(
+                "Single use payment request can not be paid from several mints".to_string(),
+            ));
+        }
+
+        let mut prepared = Vec::with_capacity(plan.len());
+        for (index, share) in plan {
+            let wallet = &wallets[index];
+
+            match wallet.prepare_request_send(share, conditions.clone()).await {
+                Ok(prepared_send) => prepared.push((wallet, prepared_send)),
+                Err(err) => {
+                    for (wallet, prepared_send) in prepared {
+                        if let Err(cancel_err) = prepared_send.cancel().await {
+                            tracing::warn!(
+                                "Could not cancel prepared send at {}: {}",
+                                wallet.mint_url,
+                                cancel_err
+                            );
+                        }
+                    }
+                    return Err(err);
+                }
+            }
+        }
+
+        let mut payloads = Vec::with_capacity(prepared.len());
+        let mut parts = Vec::with_capacity(prepared.len());
+
+        for (wallet, prepared_send) in prepared {
+            let part = PaymentRequestPart {
+                mint_url: wallet.mint_url.clone(),
+                amount: prepared_send.amount(),
+                fee: prepared_send.fee(),
+            };
+
+            let token = prepared_send.confirm(None).await?;
+            payloads.push(
+                wallet
+                    .request_payload(payment_request.payment_id.clone(), &token)
+                    .await?,
+            );
+            parts.push(part);
+        }
+
+        for payload in &payloads {
+            deliver_payload(transport, payload).await?;
+        }
+
+        Ok(PaymentRequestReceipt {
+            payment_id: payment_request.payment_id.clone(),
+            unit,
+            parts,
+        })
+    }
+
     /// Derive enforceable NUT-10 spending conditions from high-level request params.
     ///
     /// Why:
@@ -757,3 +1027,32 @@ impl WalletRepository {
         Ok(Amount::ZERO)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_takes_largest_mints_first() {
+        let spendable = [
+            Amount::from(30),
+            Amount::from(50),
+            Amount::from(0),
+            Amount::from(40),
+        ];
+
+        assert_eq!(
+            plan_split(&spendable, Amount::from(80)),
+            Some(vec![(1, Amount::from(50)), (3, Amount::from(30))])
+        );
+        assert_eq!(
+            plan_split(&spendable, Amount::from(120)),
+            Some(vec![
+                (1, Amount::from(50)),
+                (3, Amount::from(40)),
+                (0, Amount::from(30))
+            ])
+        );
+        assert_eq!(plan_split(&spendable, Amount::from(121)), None);
+    }
+}
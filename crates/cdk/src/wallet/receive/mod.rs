@@ -9,12 +9,48 @@ use tracing::instrument;
 use crate::nuts::{Proofs, Token};
 use crate::{ensure_cdk, Amount, Error, Wallet};
 
+mod quarantine;
 pub(crate) mod saga;
+mod voucher;
 
 pub use cdk_common::wallet::ReceiveOptions;
+pub use quarantine::{QuarantinedReceive, ReceiveRisk, ReceiveRiskAssessment};
 use saga::ReceiveSaga;
+pub use voucher::VoucherClaim;
+
+/// Whether claiming `amount` for `fee` goes above `max_fee_ppk` parts per thousand
+fn exceeds_receive_fee_threshold(amount: Amount, fee: Amount, max_fee_ppk: u64) -> bool {
+    u128::from(u64::from(fee)) * 1_000 > u128::from(u64::from(amount)) * u128::from(max_fee_ppk)
+}
 
 impl Wallet {
+    /// Refuse `proofs` if the fee to claim them is above the receive fee threshold
+    ///
+    /// See [`Wallet::set_max_receive_fee`].
+    pub(crate) async fn check_receive_fee(
+        &self,
+        proofs: &Proofs,
+        amount: Amount,
+    ) -> Result<(), Error> {
+        let Some(max_fee_ppk) = self.max_receive_fee_ppk else {
+            return Ok(());
+        };
+
+        let fee = self.get_proofs_fee(proofs).await?.total;
+
+        if exceeds_receive_fee_threshold(amount, fee, max_fee_ppk) {
+            tracing::warn!(
+                "Refusing to receive {} for a fee of {}, above {} ppk",
+                amount,
+                fee,
+                max_fee_ppk
+            );
+            return Err(Error::ReceiveFeeTooHigh { amount, fee });
+        }
+
+        Ok(())
+    }
+
     /// Receive proofs using the saga pattern
     ///
     /// This is the internal implementation that uses the saga pattern
@@ -124,3 +160,62 @@ impl Wallet {
         self.receive(token_str.as_str(), opts).await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::wallet::test_utils::{
+        create_test_db, create_test_wallet_with_mock, test_keyset_id, test_proof, MockMintConnector,
+    };
+
+    #[test]
+    fn receive_fee_threshold() {
+        assert!(!exceeds_receive_fee_threshold(
+            Amount::from(100),
+            Amount::from(5),
+            50
+        ));
+        assert!(exceeds_receive_fee_threshold(
+            Amount::from(100),
+            Amount::from(6),
+            50
+        ));
+        assert!(exceeds_receive_fee_threshold(
+            Amount::from(u64::MAX),
+            Amount::from(u64::MAX),
+            999
+        ));
+    }
+
+    #[tokio::test]
+    async fn receive_refuses_proofs_above_the_fee_threshold() {
+        let db = create_test_db().await;
+        let mock_client = Arc::new(MockMintConnector::new());
+        let mut wallet = create_test_wallet_with_mock(db, mock_client).await;
+        wallet.set_max_receive_fee(Some(50));
+
+        // A 1 sat proof of the 101 ppk test keyset costs 1 sat to claim
+        let proofs = vec![test_proof(test_keyset_id(), 1)];
+
+        wallet
+            .check_receive_fee(&proofs, Amount::from(1))
+            .await
+            .unwrap_err();
+        let result = wallet
+            .receive_proofs(proofs, ReceiveOptions::default(), None, None)
+            .await;
+        assert!(matches!(
+            result,
+            Err(Error::ReceiveFeeTooHigh { amount, fee })
+                if amount == Amount::from(1) && fee == Amount::from(1)
+        ));
+
+        // 64 sat in one proof is worth the fee
+        wallet
+            .check_receive_fee(&vec![test_proof(test_keyset_id(), 64)], Amount::from(64))
+            .await
+            .unwrap();
+    }
+}
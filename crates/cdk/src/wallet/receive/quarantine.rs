@@ -0,0 +1,324 @@
+//! Quarantine of received proofs
+//!
+//! Tokens from unknown senders may come from a mint the wallet never dealt with, carry proofs
+//! of a keyset the mint no longer issues, or lack the DLEQ proofs that let the wallet check the
+//! signatures offline. [`Wallet::assess_receive_risk`] scores these signs before the proofs are
+//! claimed. Once the score reaches [`ReceiveOptions::quarantine_risk_score`], the proofs are
+//! still swapped, so the sender can no longer spend them, but the new proofs are kept aside
+//! instead of counting towards the balance until the app releases them with
+//! [`Wallet::release_quarantined_receive`].
+//!
+//! [`ReceiveOptions::quarantine_risk_score`]: super::ReceiveOptions::quarantine_risk_score
+
+use std::collections::HashMap;
+
+use cdk_common::util::unix_time;
+use cdk_common::wallet::{ProofInfo, Transaction, TransactionDirection};
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::nuts::{Proofs, PublicKey};
+use crate::wallet::KeysetFilter;
+use crate::{Amount, Error, Wallet};
+
+/// KV store namespace holding quarantined receives, keyed by their operation id
+const RECEIVE_KV_NAMESPACE: &str = "receive";
+const QUARANTINE_KV_SECONDARY_NAMESPACE: &str = "quarantine";
+
+/// Sign that received proofs may not be what they claim, see [`Wallet::assess_receive_risk`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiveRisk {
+    /// The wallet never transacted with the mint of the proofs
+    UnknownMint,
+    /// A proof belongs to a keyset the mint does not list as active
+    UnusualKeyset,
+    /// A proof carries no DLEQ proof, so its signature cannot be checked offline
+    MissingDleq,
+}
+
+impl ReceiveRisk {
+    /// Weight of the risk in [`ReceiveRiskAssessment::score`]
+    pub fn weight(&self) -> u32 {
+        match self {
+            Self::UnknownMint => 40,
+            Self::UnusualKeyset => 30,
+            Self::MissingDleq => 30,
+        }
+    }
+}
+
+/// Risks found with proofs about to be received
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReceiveRiskAssessment {
+    /// Every risk found, each listed once
+    pub risks: Vec<ReceiveRisk>,
+    /// Sum of the weights of the risks, from 0 to 100
+    pub score: u32,
+}
+
+impl ReceiveRiskAssessment {
+    fn new(risks: Vec<ReceiveRisk>) -> Self {
+        Self {
+            score: risks.iter().map(ReceiveRisk::weight).sum(),
+            risks,
+        }
+    }
+}
+
+/// Proofs received and kept aside, see [`Wallet::quarantined_receives`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuarantinedReceive {
+    /// Operation id of the receive
+    pub id: Uuid,
+    /// Amount of the new proofs
+    pub amount: Amount,
+    /// Fee paid to swap the received proofs
+    pub fee: Amount,
+    /// Why the receive was quarantined
+    pub assessment: ReceiveRiskAssessment,
+    /// New proofs, not counted in the balance until released
+    pub proofs: Vec<ProofInfo>,
+    /// `Y` of the proofs that were received
+    pub input_ys: Vec<PublicKey>,
+    /// Memo of the token
+    pub memo: Option<String>,
+    /// Metadata of the receive, recorded with its transaction
+    pub metadata: HashMap<String, String>,
+    /// Unix time the proofs were received
+    pub received_at: u64,
+}
+
+impl Wallet {
+    /// Look for signs that `proofs` may not be settled once received
+    ///
+    /// Only local data and the cached keysets of the mint are used, the proofs are not checked
+    /// with the mint.
+    #[instrument(skip_all)]
+    pub async fn assess_receive_risk(
+        &self,
+        proofs: &Proofs,
+    ) -> Result<ReceiveRiskAssessment, Error> {
+        let mut risks = Vec::new();
+
+        if self
+            .localstore
+            .list_transactions(Some(self.mint_url.clone()), None, Some(self.unit.clone()))
+            .await?
+            .is_empty()
+        {
+            risks.push(ReceiveRisk::UnknownMint);
+        }
+
+        let active_keysets = self.get_mint_keysets(KeysetFilter::Active).await?;
+        if proofs.iter().any(|proof| {
+            !active_keysets
+                .iter()
+                .any(|keyset| keyset.id == proof.keyset_id)
+        }) {
+            risks.push(ReceiveRisk::UnusualKeyset);
+        }
+
+        if proofs.iter().any(|proof| proof.dleq.is_none()) {
+            risks.push(ReceiveRisk::MissingDleq);
+        }
+
+        Ok(ReceiveRiskAssessment::new(risks))
+    }
+
+    /// Receives of this wallet kept in quarantine
+    #[instrument(skip(self))]
+    pub async fn quarantined_receives(&self) -> Result<Vec<QuarantinedReceive>, Error> {
+        let mut receives = Vec::new();
+
+        for key in self
+            .localstore
+            .kv_list(RECEIVE_KV_NAMESPACE, QUARANTINE_KV_SECONDARY_NAMESPACE)
+            .await?
+        {
+            let Some(receive) = self.quarantined_receive(&key).await? else {
+                continue;
+            };
+
+            if receive
+                .proofs
+                .iter()
+                .all(|proof| proof.mint_url == self.mint_url && proof.unit == self.unit)
+            {
+                receives.push(receive);
+            }
+        }
+
+        receives.sort_by_key(|receive| receive.received_at);
+        Ok(receives)
+    }
+
+    /// Add the proofs of a quarantined receive to the balance and record its transaction
+    #[instrument(skip(self))]
+    pub async fn release_quarantined_receive(&self, id: Uuid) -> Result<Amount, Error> {
+        let key = id.to_string();
+        let receive = self
+            .quarantined_receive(&key)
+            .await?
+            .ok_or(Error::OperationNotFound)?;
+
+        self.localstore
+            .update_proofs(receive.proofs, Vec::new())
+            .await?;
+
+        self.localstore
+            .add_transaction(Transaction {
+                mint_url: self.mint_url.clone(),
+                direction: TransactionDirection::Incoming,
+                amount: receive.amount,
+                fee: receive.fee,
+                unit: self.unit.clone(),
+                ys: receive.input_ys,
+                timestamp: unix_time(),
+                memo: receive.memo,
+                metadata: receive.metadata,
+                quote_id: None,
+                payment_request: None,
+                payment_proof: None,
+                payment_method: None,
+                saga_id: Some(id),
+            })
+            .await?;
+
+        self.localstore
+            .kv_remove(
+                RECEIVE_KV_NAMESPACE,
+                QUARANTINE_KV_SECONDARY_NAMESPACE,
+                &key,
+            )
+            .await?;
+
+        tracing::info!("Released quarantined receive {} of {}", id, receive.amount);
+
+        Ok(receive.amount)
+    }
+
+    /// Keep the proofs of a receive aside until [`Wallet::release_quarantined_receive`]
+    pub(crate) async fn quarantine_receive(
+        &self,
+        receive: &QuarantinedReceive,
+    ) -> Result<(), Error> {
+        tracing::warn!(
+            "Quarantining receive {} of {} with risk score {}: {:?}",
+            receive.id,
+            receive.amount,
+            receive.assessment.score,
+            receive.assessment.risks
+        );
+
+        self.localstore
+            .kv_write(
+                RECEIVE_KV_NAMESPACE,
+                QUARANTINE_KV_SECONDARY_NAMESPACE,
+                &receive.id.to_string(),
+                &serde_json::to_vec(receive)?,
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn quarantined_receive(&self, key: &str) -> Result<Option<QuarantinedReceive>, Error> {
+        self.localstore
+            .kv_read(RECEIVE_KV_NAMESPACE, QUARANTINE_KV_SECONDARY_NAMESPACE, key)
+            .await?
+            .map(|value| serde_json::from_slice(&value))
+            .transpose()
+            .map_err(Error::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use std::sync::Arc;
+
+    use cdk_common::nuts::Id;
+
+    use super::*;
+    use crate::wallet::test_utils::{
+        create_test_db, create_test_wallet_with_mock, test_keyset_id, test_mint_url, test_proof,
+        test_proof_info, MockMintConnector,
+    };
+
+    #[tokio::test]
+    async fn risks_are_scored() {
+        let db = create_test_db().await;
+        let mock = Arc::new(MockMintConnector::new());
+        let wallet = create_test_wallet_with_mock(db, mock).await;
+
+        let unknown_keyset = Id::from_str("00deadbeef123456").unwrap();
+        let assessment = wallet
+            .assess_receive_risk(&vec![
+                test_proof(test_keyset_id(), 1),
+                test_proof(unknown_keyset, 2),
+            ])
+            .await
+            .unwrap();
+
+        assert_eq!(
+            assessment.risks,
+            vec![
+                ReceiveRisk::UnknownMint,
+                ReceiveRisk::UnusualKeyset,
+                ReceiveRisk::MissingDleq
+            ]
+        );
+        assert_eq!(assessment.score, 100);
+    }
+
+    #[tokio::test]
+    async fn quarantined_receives_are_released_into_the_balance() {
+        let db = create_test_db().await;
+        let mock = Arc::new(MockMintConnector::new());
+        let wallet = create_test_wallet_with_mock(db, mock).await;
+
+        let proof = test_proof_info(test_keyset_id(), 8, test_mint_url());
+        let receive = QuarantinedReceive {
+            id: Uuid::now_v7(),
+            amount: Amount::from(8),
+            fee: Amount::ZERO,
+            assessment: ReceiveRiskAssessment::new(vec![ReceiveRisk::UnknownMint]),
+            proofs: vec![proof.clone()],
+            input_ys: vec![test_proof(test_keyset_id(), 8).y().unwrap()],
+            memo: Some("thanks".to_string()),
+            metadata: HashMap::new(),
+            received_at: unix_time(),
+        };
+        wallet.quarantine_receive(&receive).await.unwrap();
+
+        assert_eq!(
+            wallet.quarantined_receives().await.unwrap(),
+            vec![receive.clone()]
+        );
+        assert!(wallet.get_unspent_proofs().await.unwrap().is_empty());
+
+        let amount = wallet
+            .release_quarantined_receive(receive.id)
+            .await
+            .unwrap();
+        assert_eq!(amount, Amount::from(8));
+        assert_eq!(
+            wallet.get_unspent_proofs().await.unwrap(),
+            vec![proof.proof]
+        );
+        assert!(wallet.quarantined_receives().await.unwrap().is_empty());
+
+        // Transacting with the mint makes it known
+        let assessment = wallet
+            .assess_receive_risk(&vec![test_proof(test_keyset_id(), 1)])
+            .await
+            .unwrap();
+        assert!(!assessment.risks.contains(&ReceiveRisk::UnknownMint));
+
+        assert!(matches!(
+            wallet.release_quarantined_receive(receive.id).await,
+            Err(Error::OperationNotFound)
+        ));
+    }
+}
@@ -45,6 +45,7 @@ use tracing::instrument;
 
 use self::compensation::RemovePendingProofs;
 use self::state::{Finalized, Initial, Prepared};
+use super::quarantine::QuarantinedReceive;
 use super::ReceiveOptions;
 use crate::dhke::construct_proofs;
 use crate::nuts::nut00::ProofsMethods;
@@ -112,6 +113,21 @@ impl<'a> ReceiveSaga<'a, Initial> {
         let mut proofs = proofs;
         let proofs_amount = proofs.total_amount()?;
 
+        if !opts.ignore_fee_threshold {
+            self.wallet
+                .check_receive_fee(&proofs, proofs_amount)
+                .await?;
+        }
+
+        // Assessed on the proofs as received, before any signature or preimage is added
+        let quarantine = match opts.quarantine_risk_score {
+            Some(threshold) => {
+                let assessment = self.wallet.assess_receive_risk(&proofs).await?;
+                (assessment.score >= threshold).then_some(assessment)
+            }
+            None => None,
+        };
+
         let mut _sig_flag = SigFlag::SigInputs;
 
         // Map hash of preimage to preimage
@@ -151,6 +167,8 @@ impl<'a> ReceiveSaga<'a, Initial> {
                     .unwrap_or_default()
                     .try_into();
                 if let Ok(conditions) = conditions {
+                    // SIG_ALL proofs are signed together with the swap outputs in `execute`
+                    let sig_all = conditions.sig_flag == SigFlag::SigAll;
                     let mut pubkeys = Vec::new();
 
                     match secret.kind() {
@@ -192,6 +210,10 @@ impl<'a> ReceiveSaga<'a, Initial> {
                             }
                         }
 
+                        if sig_all {
+                            continue;
+                        }
+
                         if let Some(ephemeral_key) = proof.p2pk_e {
                             for signing_key in p2pk_signing_keys.values() {
                                 if let Ok(r) =
@@ -214,13 +236,18 @@ impl<'a> ReceiveSaga<'a, Initial> {
                         }
                     }
 
-                    match secret.kind() {
-                        Kind::P2PK => proof.verify_p2pk()?,
-                        Kind::HTLC => proof.verify_htlc()?,
+                    if sig_all {
+                        _sig_flag = SigFlag::SigAll;
+                        continue;
                     }
 
-                    if conditions.sig_flag.eq(&SigFlag::SigAll) {
-                        _sig_flag = SigFlag::SigAll;
+                    match secret.kind() {
+                        Kind::P2PK => {
+                            proof.verify_p2pk_with_grace(self.wallet.clock_skew_grace_secs)?
+                        }
+                        Kind::HTLC => {
+                            proof.verify_htlc_with_grace(self.wallet.clock_skew_grace_secs)?
+                        }
                     }
                 }
             }
@@ -238,6 +265,7 @@ impl<'a> ReceiveSaga<'a, Initial> {
                 proofs_amount,
                 active_keyset_id,
                 p2pk_signing_keys,
+                quarantine,
             },
         })
     }
@@ -336,16 +364,20 @@ impl<'a> ReceiveSaga<'a, Prepared> {
             )
             .await?;
 
-        // Determine if SigAll signing is needed
-        let sig_flag = self.determine_sig_flag()?;
-        if sig_flag == SigFlag::SigAll {
-            for blinded_message in pre_swap.swap_request.outputs_mut() {
-                for signing_key in self.state_data.p2pk_signing_keys.values() {
-                    // Sign the outputs of the swap using standard P2PK since output
-                    // P2BK requires ephemeral keys which is handled at creation.
-                    blinded_message.sign_p2pk(signing_key.to_owned().clone())?
-                }
-            }
+        // Inputs locked with SIG_ALL are signed together with the outputs
+        let p2pk_signing_keys: Vec<SecretKey> = self
+            .state_data
+            .p2pk_signing_keys
+            .values()
+            .cloned()
+            .collect();
+        if let Err(err) = self
+            .wallet
+            .sign_swap_sig_all(&mut pre_swap.swap_request, &p2pk_signing_keys)
+            .await
+        {
+            execute_compensations(&mut self.compensations).await?;
+            return Err(err);
         }
 
         // Get counter range for recovery (before the swap request is sent)
@@ -412,33 +444,55 @@ impl<'a> ReceiveSaga<'a, Prepared> {
             })
             .collect::<Result<Vec<ProofInfo>, _>>()?;
 
-        self.wallet
-            .localstore
-            .update_proofs(
-                recv_proof_infos,
-                proofs_info.into_iter().map(|p| p.y).collect(),
-            )
-            .await?;
-
-        self.wallet
-            .localstore
-            .add_transaction(Transaction {
-                mint_url: self.wallet.mint_url.clone(),
-                direction: TransactionDirection::Incoming,
-                amount: total_amount,
-                fee,
-                unit: self.wallet.unit.clone(),
-                ys: proofs_ys,
-                timestamp: unix_time(),
-                memo: self.state_data.memo.clone(),
-                metadata: self.state_data.options.metadata.clone(),
-                quote_id: None,
-                payment_request: None,
-                payment_proof: None,
-                payment_method: None,
-                saga_id: Some(operation_id),
-            })
-            .await?;
+        if let Some(assessment) = self.state_data.quarantine.take() {
+            // The sender can no longer spend the proofs, but they stay out of the balance
+            self.wallet
+                .quarantine_receive(&QuarantinedReceive {
+                    id: operation_id,
+                    amount: total_amount,
+                    fee,
+                    assessment,
+                    proofs: recv_proof_infos,
+                    input_ys: proofs_ys,
+                    memo: self.state_data.memo.clone(),
+                    metadata: self.state_data.options.metadata.clone(),
+                    received_at: unix_time(),
+                })
+                .await?;
+
+            self.wallet
+                .localstore
+                .update_proofs(vec![], proofs_info.into_iter().map(|p| p.y).collect())
+                .await?;
+        } else {
+            self.wallet
+                .localstore
+                .update_proofs(
+                    recv_proof_infos,
+                    proofs_info.into_iter().map(|p| p.y).collect(),
+                )
+                .await?;
+
+            self.wallet
+                .localstore
+                .add_transaction(Transaction {
+                    mint_url: self.wallet.mint_url.clone(),
+                    direction: TransactionDirection::Incoming,
+                    amount: total_amount,
+                    fee,
+                    unit: self.wallet.unit.clone(),
+                    ys: proofs_ys,
+                    timestamp: unix_time(),
+                    memo: self.state_data.memo.clone(),
+                    metadata: self.state_data.options.metadata.clone(),
+                    quote_id: None,
+                    payment_request: None,
+                    payment_proof: None,
+                    payment_method: None,
+                    saga_id: Some(operation_id),
+                })
+                .await?;
+        }
 
         clear_compensations(&mut self.compensations).await;
 
@@ -459,30 +513,6 @@ impl<'a> ReceiveSaga<'a, Prepared> {
             },
         })
     }
-
-    /// Determine the signature flag based on the proofs
-    fn determine_sig_flag(&self) -> Result<SigFlag, Error> {
-        for proof in &self.state_data.proofs {
-            if let Ok(secret) =
-                <crate::secret::Secret as TryInto<crate::nuts::nut10::Secret>>::try_into(
-                    proof.secret.clone(),
-                )
-            {
-                let conditions: Result<Conditions, _> = secret
-                    .secret_data()
-                    .tags()
-                    .cloned()
-                    .unwrap_or_default()
-                    .try_into();
-                if let Ok(conditions) = conditions {
-                    if conditions.sig_flag == SigFlag::SigAll {
-                        return Ok(SigFlag::SigAll);
-                    }
-                }
-            }
-        }
-        Ok(SigFlag::SigInputs)
-    }
 }
 
 impl<'a> ReceiveSaga<'a, Finalized> {
@@ -18,7 +18,7 @@ use bitcoin::XOnlyPublicKey;
 use uuid::Uuid;
 
 use crate::nuts::{Id, Proofs, SecretKey};
-use crate::wallet::receive::ReceiveOptions;
+use crate::wallet::receive::{ReceiveOptions, ReceiveRiskAssessment};
 use crate::Amount;
 
 /// Initial state - operation ID assigned but no work done yet.
@@ -49,6 +49,8 @@ pub struct Prepared {
     pub active_keyset_id: Id,
     /// P2PK signing keys (from options + wallet database lookups)
     pub p2pk_signing_keys: HashMap<XOnlyPublicKey, SecretKey>,
+    /// Risks of the proofs when they reach the quarantine score of the options
+    pub quarantine: Option<ReceiveRiskAssessment>,
 }
 
 /// Finalized state - receive operation completed successfully.
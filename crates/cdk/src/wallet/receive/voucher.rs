@@ -0,0 +1,111 @@
+//! Claim of vouchers
+//!
+//! Vouchers pre-issued by a mint are tokens locked with NUT-11 to a claim key or with NUT-14 to
+//! the hash of a claim code. [`Wallet::claim_voucher`] checks that the key or code opens every
+//! proof of the token before receiving it, so a mistyped code is refused without reaching the
+//! mint.
+
+use std::fmt;
+use std::str::FromStr;
+
+use bitcoin::hashes::sha256::Hash as Sha256Hash;
+use bitcoin::hashes::Hash;
+use tracing::instrument;
+
+use super::ReceiveOptions;
+use crate::nuts::{Proof, SecretKey, SpendingConditions, Token};
+use crate::util::hex;
+use crate::{ensure_cdk, Amount, Error, Wallet};
+
+/// What opens a voucher, see [`Wallet::claim_voucher`]
+#[derive(Clone)]
+pub enum VoucherClaim {
+    /// Secret key of the claim key the voucher is locked to
+    Key(SecretKey),
+    /// Claim code the voucher is locked to the hash of
+    Code(String),
+}
+
+impl fmt::Debug for VoucherClaim {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Key(_) => f.write_str("Key([redacted])"),
+            Self::Code(_) => f.write_str("Code([redacted])"),
+        }
+    }
+}
+
+impl VoucherClaim {
+    /// Whether the claim opens `proof`
+    pub fn opens(&self, proof: &Proof) -> bool {
+        match (SpendingConditions::try_from(&proof.secret), self) {
+            (Ok(SpendingConditions::P2PKConditions { data, .. }), Self::Key(key)) => {
+                data == key.public_key()
+            }
+            (Ok(SpendingConditions::HTLCConditions { data, .. }), Self::Code(code)) => {
+                hex::decode(code).is_ok_and(|code| Sha256Hash::hash(&code) == data)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Wallet {
+    /// Receive a voucher opened by `claim`
+    ///
+    /// The key or code of `claim` is added to `opts`.
+    #[instrument(skip_all)]
+    pub async fn claim_voucher(
+        &self,
+        encoded_token: &str,
+        claim: VoucherClaim,
+        mut opts: ReceiveOptions,
+    ) -> Result<Amount, Error> {
+        let token = Token::from_str(encoded_token)?;
+        let proofs = self.token_proofs(&token).await?;
+
+        ensure_cdk!(
+            !proofs.is_empty() && proofs.iter().all(|proof| claim.opens(proof)),
+            Error::InvalidSpendConditions("Voucher is not opened by the claim".to_string())
+        );
+
+        match claim {
+            VoucherClaim::Key(key) => opts.p2pk_signing_keys.push(key),
+            VoucherClaim::Code(code) => opts.preimages.push(code),
+        }
+
+        self.receive(encoded_token, opts).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secret::Secret;
+    use crate::wallet::test_utils::{test_keyset_id, test_proof};
+
+    fn locked_proof(conditions: SpendingConditions) -> Proof {
+        let mut proof = test_proof(test_keyset_id(), 8);
+        proof.secret = Secret::try_from(conditions).unwrap();
+        proof
+    }
+
+    #[test]
+    fn claims_open_their_vouchers_only() {
+        let key = SecretKey::generate();
+        let code = Secret::generate().to_string();
+
+        let key_locked = locked_proof(SpendingConditions::new_p2pk(key.public_key(), None));
+        let code_locked = locked_proof(SpendingConditions::new_htlc(code.clone(), None).unwrap());
+
+        assert!(VoucherClaim::Key(key.clone()).opens(&key_locked));
+        assert!(!VoucherClaim::Key(SecretKey::generate()).opens(&key_locked));
+        assert!(!VoucherClaim::Key(key).opens(&code_locked));
+
+        assert!(VoucherClaim::Code(code.clone()).opens(&code_locked));
+        assert!(!VoucherClaim::Code(Secret::generate().to_string()).opens(&code_locked));
+        assert!(!VoucherClaim::Code("not hex".to_string()).opens(&code_locked));
+        assert!(!VoucherClaim::Code(code).opens(&key_locked));
+        assert!(!VoucherClaim::Code(String::new()).opens(&test_proof(test_keyset_id(), 8)));
+    }
+}
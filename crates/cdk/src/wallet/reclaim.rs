@@ -1,10 +1,15 @@
 use std::collections::HashMap;
 
+use cdk_common::util::unix_time;
+use cdk_common::wallet::ProofInfo;
 use cdk_common::{CheckStateRequest, ProofsMethods};
 use tracing::instrument;
+use uuid::Uuid;
 
-use crate::nuts::Proofs;
-use crate::{Error, Wallet};
+use crate::amount::SplitTarget;
+use crate::nuts::{Proof, Proofs, SpendingConditions, State};
+use crate::wallet::util::{merge_keyring_keys, sign_proofs};
+use crate::{Amount, Error, Wallet};
 
 impl Wallet {
     /// Synchronizes the states with the mint
@@ -40,4 +45,219 @@ impl Wallet {
 
         Ok(())
     }
+
+    /// Locked proofs of the wallet that can be spent through their refund path
+    ///
+    /// A P2PK or HTLC proof qualifies once its locktime, extended by
+    /// [`Wallet::clock_skew_grace_secs`], has passed and the wallet holds one of its refund keys.
+    /// Unspent proofs are listed, as are the proofs of tokens sent and not claimed yet, when
+    /// every proof of the token qualifies. The proofs are not checked with the mint.
+    #[instrument(skip(self))]
+    pub async fn expired_locked_proofs(&self) -> Result<Vec<ProofInfo>, Error> {
+        let now = unix_time();
+        let mut expired = Vec::new();
+
+        for info in self
+            .localstore
+            .get_proofs(
+                Some(self.mint_url.clone()),
+                Some(self.unit.clone()),
+                Some(vec![State::Unspent]),
+                None,
+            )
+            .await?
+        {
+            if info.used_by_operation.is_none() && self.is_refundable(&info.proof, now).await? {
+                expired.push(info);
+            }
+        }
+
+        for operation_id in self.get_pending_sends().await? {
+            let proofs = self.localstore.get_reserved_proofs(&operation_id).await?;
+            if proofs.is_empty() {
+                continue;
+            }
+
+            let mut refundable = true;
+            for info in &proofs {
+                if !self.is_refundable(&info.proof, now).await? {
+                    refundable = false;
+                    break;
+                }
+            }
+
+            if refundable {
+                expired.extend(proofs);
+            }
+        }
+
+        Ok(expired)
+    }
+
+    /// Swap the proofs of [`Wallet::expired_locked_proofs`] back into unconditional proofs
+    ///
+    /// The proofs are signed with the refund keys of the wallet. Pending sends are revoked, so
+    /// their sagas are completed, and a send the recipient claimed meanwhile is skipped. Returns
+    /// the amount reclaimed, after fees.
+    #[instrument(skip(self))]
+    pub async fn reclaim_expired(&self) -> Result<Amount, Error> {
+        let mut unspent = Proofs::new();
+        let mut sends: Vec<Uuid> = Vec::new();
+
+        for info in self.expired_locked_proofs().await? {
+            match info.used_by_operation {
+                None => unspent.push(info.proof),
+                Some(operation_id) => {
+                    if !sends.contains(&operation_id) {
+                        sends.push(operation_id);
+                    }
+                }
+            }
+        }
+
+        let mut reclaimed = Amount::ZERO;
+
+        if !unspent.is_empty() {
+            // Proofs spent meanwhile are dropped from the wallet by the state check
+            let spent: Vec<_> = self
+                .check_proofs_spent(unspent.clone())
+                .await?
+                .into_iter()
+                .filter(|state| state.state == State::Spent)
+                .map(|state| state.y)
+                .collect();
+            unspent.retain(|proof| !matches!(proof.y(), Ok(y) if spent.contains(&y)));
+        }
+
+        if !unspent.is_empty() {
+            let signing_keys = merge_keyring_keys(self, &unspent, &[]).await?;
+            sign_proofs(&mut unspent, &signing_keys)?;
+
+            let input_amount = unspent.total_amount()?;
+            let fee = self.get_proofs_fee(&unspent).await?.total;
+
+            self.swap(None, SplitTarget::default(), unspent, None, false, false)
+                .await?;
+
+            reclaimed = reclaimed
+                .checked_add(input_amount.checked_sub(fee).unwrap_or(Amount::ZERO))
+                .ok_or(Error::AmountOverflow)?;
+        }
+
+        for operation_id in sends {
+            match self.revoke_send(operation_id).await {
+                Ok(amount) => {
+                    reclaimed = reclaimed.checked_add(amount).ok_or(Error::AmountOverflow)?;
+                }
+                Err(err) => {
+                    tracing::warn!("Could not reclaim send {}: {}", operation_id, err);
+                }
+            }
+        }
+
+        if reclaimed > Amount::ZERO {
+            tracing::info!("Reclaimed {} from expired locked proofs", reclaimed);
+        }
+
+        Ok(reclaimed)
+    }
+
+    /// Whether `proof` is locked, its locktime passed and the wallet holds one of its refund keys
+    async fn is_refundable(&self, proof: &Proof, now: u64) -> Result<bool, Error> {
+        let conditions = match SpendingConditions::try_from(&proof.secret) {
+            Ok(SpendingConditions::P2PKConditions { conditions, .. })
+            | Ok(SpendingConditions::HTLCConditions { conditions, .. }) => conditions,
+            Err(_) => None,
+        };
+
+        let Some(conditions) = conditions else {
+            return Ok(false);
+        };
+
+        let (Some(locktime), Some(refund_keys)) = (conditions.locktime, conditions.refund_keys)
+        else {
+            return Ok(false);
+        };
+
+        if locktime.saturating_add(self.clock_skew_grace_secs) > now {
+            return Ok(false);
+        }
+
+        for refund_key in refund_keys {
+            if self.get_signing_key(&refund_key).await?.is_some() {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cdk_common::nuts::{Conditions, SecretKey};
+    use cdk_common::secret::Secret;
+    use cdk_common::CurrencyUnit;
+
+    use super::*;
+    use crate::nuts::PublicKey;
+    use crate::wallet::test_utils::{
+        create_test_db, create_test_wallet, test_keyset_id, test_proof,
+    };
+
+    fn locked_proof(amount: u64, locktime: u64, refund_key: PublicKey) -> Proof {
+        let mut proof = test_proof(test_keyset_id(), amount);
+        proof.secret = Secret::try_from(SpendingConditions::new_p2pk(
+            SecretKey::generate().public_key(),
+            Some(Conditions {
+                locktime: Some(locktime),
+                refund_keys: Some(vec![refund_key]),
+                ..Default::default()
+            }),
+        ))
+        .unwrap();
+        proof
+    }
+
+    #[tokio::test]
+    async fn expired_proofs_with_a_refund_key_are_detected() {
+        let db = create_test_db().await;
+        let wallet = create_test_wallet(db.clone()).await;
+        let refund_key = wallet.generate_public_key().await.unwrap();
+        let now = unix_time();
+
+        let expired = locked_proof(1, now - 3600, refund_key);
+        let proofs = vec![
+            expired.clone(),
+            // Locktime not passed yet
+            locked_proof(2, now + 3600, refund_key),
+            // Refund key of someone else
+            locked_proof(4, now - 3600, SecretKey::generate().public_key()),
+            test_proof(test_keyset_id(), 8),
+        ];
+
+        db.update_proofs(
+            proofs
+                .into_iter()
+                .map(|proof| {
+                    ProofInfo::new(
+                        proof,
+                        wallet.mint_url.clone(),
+                        State::Unspent,
+                        CurrencyUnit::Sat,
+                    )
+                    .unwrap()
+                })
+                .collect(),
+            vec![],
+        )
+        .await
+        .unwrap();
+
+        let found = wallet.expired_locked_proofs().await.unwrap();
+        assert_eq!(
+            found.into_iter().map(|info| info.proof).collect::<Vec<_>>(),
+            vec![expired]
+        );
+    }
 }
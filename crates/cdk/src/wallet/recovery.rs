@@ -26,7 +26,7 @@ use crate::nuts::{CheckStateRequest, PreMintSecrets, Proofs, RestoreRequest, Sta
 use crate::wallet::blind_signature::{
     validate_mint_response_signatures, SignatureAmountValidation,
 };
-use crate::{Error, Wallet};
+use crate::{Amount, Error, Wallet};
 
 /// Parameters for recovering outputs using stored blinded messages.
 ///
@@ -61,6 +61,35 @@ impl RecoveryReport {
     }
 }
 
+/// Outcome of minting one unissued quote in [`Wallet::recover_unminted_quotes`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecoveredMintQuote {
+    /// Id of the mint quote
+    pub quote_id: String,
+    /// Amount minted from the quote
+    pub minted: Amount,
+    /// Why the quote could not be checked or minted, if it failed
+    pub error: Option<String>,
+}
+
+/// Report of [`Wallet::recover_unminted_quotes`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct UnmintedQuotesRecovery {
+    /// Incomplete sagas, including mints interrupted after their outputs were signed
+    pub sagas: RecoveryReport,
+    /// Paid quotes minted, or that failed to mint, in this call
+    pub quotes: Vec<RecoveredMintQuote>,
+}
+
+impl UnmintedQuotesRecovery {
+    /// Total amount minted from the quotes
+    pub fn total_minted(&self) -> Result<Amount, Error> {
+        Ok(Amount::try_sum(
+            self.quotes.iter().map(|quote| quote.minted),
+        )?)
+    }
+}
+
 /// Result of a saga recovery operation.
 ///
 /// Used by individual saga resume functions to indicate the outcome.
@@ -0,0 +1,145 @@
+//! Wallet seed providers
+//!
+//! The wallet derives every NUT-13 secret and blinding factor from a 64 byte seed. A
+//! [`SeedProvider`] lets the application obtain that seed from somewhere other than a mnemonic
+//! stored in the app, such as a hardware wallet.
+//!
+//! Hardware wallets never export private keys, they only sign and compute ECDH shared secrets
+//! with the keys they hold. Signatures are no seed material, as devices randomise their nonces
+//! against key exfiltration. [`ExternalSignerSeedProvider`] instead has the device compute the
+//! ECDH shared secret of the key at a path chosen by the application and a point hashed from a
+//! message chosen by the application, and derives the wallet seed from it. The shared secret only
+//! depends on the key and the point, so the same device, path and message always yield the same
+//! seed and proofs can be restored with NUT-09 as usual.
+
+use std::fmt::Debug;
+
+use async_trait::async_trait;
+use bitcoin::bip32::DerivationPath;
+use bitcoin::hashes::hmac::{Hmac, HmacEngine};
+use bitcoin::hashes::{sha512, Hash, HashEngine};
+use bitcoin::secp256k1::PublicKey;
+
+use crate::dhke::hash_to_curve;
+use crate::error::Error;
+
+/// HMAC key the wallet seed is derived from the ECDH shared secret with
+const SEED_HMAC_KEY: &[u8] = b"Cashu external signer seed";
+
+/// Source of the 64 byte seed used for deterministic secret derivation (NUT-13)
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait SeedProvider: Debug + Send + Sync {
+    /// Obtain the wallet derivation seed
+    async fn derivation_seed(&self) -> Result<[u8; 64], Error>;
+}
+
+/// External signer, such as a hardware wallet, holding keys it never exports
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait ExternalSigner: Debug + Send + Sync {
+    /// X coordinate of the ECDH shared point between the key at `path` and `public_key`
+    async fn ecdh(&self, path: &DerivationPath, public_key: &PublicKey) -> Result<[u8; 32], Error>;
+}
+
+/// [`SeedProvider`] backed by an [`ExternalSigner`]
+///
+/// The seed is derived from the ECDH shared secret of the key at `path` and the point
+/// `message` hashes to (NUT-00 `hash_to_curve`), whose discrete log nobody knows. Both are
+/// picked by the application and must be kept to restore the wallet, a different path or
+/// message yields an unrelated seed.
+#[derive(Debug)]
+pub struct ExternalSignerSeedProvider<S>
+where
+    S: ExternalSigner,
+{
+    signer: S,
+    path: DerivationPath,
+    message: Vec<u8>,
+}
+
+impl<S> ExternalSignerSeedProvider<S>
+where
+    S: ExternalSigner,
+{
+    /// Create new [`ExternalSignerSeedProvider`]
+    pub fn new(signer: S, path: DerivationPath, message: impl Into<Vec<u8>>) -> Self {
+        Self {
+            signer,
+            path,
+            message: message.into(),
+        }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<S> SeedProvider for ExternalSignerSeedProvider<S>
+where
+    S: ExternalSigner,
+{
+    async fn derivation_seed(&self) -> Result<[u8; 64], Error> {
+        let point = hash_to_curve(&self.message)?;
+        let shared_secret = self.signer.ecdh(&self.path, &point).await?;
+
+        let mut engine = HmacEngine::<sha512::Hash>::new(SEED_HMAC_KEY);
+        engine.input(&shared_secret);
+        Ok(Hmac::<sha512::Hash>::from_engine(engine).to_byte_array())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use bitcoin::bip32::Xpriv;
+    use bitcoin::secp256k1::Scalar;
+    use bitcoin::Network;
+
+    use super::*;
+    use crate::SECP256K1;
+
+    /// Software signer standing in for a hardware device
+    #[derive(Debug)]
+    struct SoftwareSigner(Xpriv);
+
+    #[async_trait]
+    impl ExternalSigner for SoftwareSigner {
+        async fn ecdh(
+            &self,
+            path: &DerivationPath,
+            public_key: &PublicKey,
+        ) -> Result<[u8; 32], Error> {
+            let xpriv = self.0.derive_priv(&SECP256K1, path)?;
+            let point = public_key
+                .mul_tweak(&SECP256K1, &Scalar::from(xpriv.private_key))
+                .map_err(|err| Error::Custom(err.to_string()))?;
+            Ok(point.x_only_public_key().0.serialize())
+        }
+    }
+
+    fn signer(byte: u8) -> SoftwareSigner {
+        SoftwareSigner(Xpriv::new_master(Network::Bitcoin, &[byte; 64]).unwrap())
+    }
+
+    fn path(account: u32) -> DerivationPath {
+        DerivationPath::from_str(&format!("m/84'/0'/{account}'/0/0")).unwrap()
+    }
+
+    #[tokio::test]
+    async fn external_signer_seed_is_deterministic() {
+        let provider = ExternalSignerSeedProvider::new(signer(1), path(0), "cashu wallet");
+
+        let seed = provider.derivation_seed().await.unwrap();
+        assert_eq!(seed, provider.derivation_seed().await.unwrap());
+
+        let other_path = ExternalSignerSeedProvider::new(signer(1), path(1), "cashu wallet");
+        assert_ne!(seed, other_path.derivation_seed().await.unwrap());
+
+        let other_message = ExternalSignerSeedProvider::new(signer(1), path(0), "other wallet");
+        assert_ne!(seed, other_message.derivation_seed().await.unwrap());
+
+        let other_device = ExternalSignerSeedProvider::new(signer(2), path(0), "cashu wallet");
+        assert_ne!(seed, other_device.derivation_seed().await.unwrap());
+    }
+}
@@ -5,13 +5,14 @@
 use std::collections::HashMap;
 use std::fmt::Debug;
 
+use cdk_common::mint_url::MintUrl;
 use cdk_common::Id;
 use tracing::instrument;
 use uuid::Uuid;
 
 use crate::fees::calculate_fee;
 use crate::nuts::nut00::ProofsMethods;
-use crate::nuts::{Proofs, Token};
+use crate::nuts::{CurrencyUnit, PreMintSecrets, Proof, ProofDleq, Proofs, Token};
 use crate::{Amount, Error, Wallet};
 
 pub(crate) mod saga;
@@ -445,8 +446,45 @@ pub(crate) fn split_proofs_for_send(
     })
 }
 
+/// Size of the token made of `proofs_to_send` and the proofs the mint will sign for `outputs`
+///
+/// The proofs of `outputs` do not exist yet, so they are stood in for by proofs with the same
+/// amounts and secrets carrying a DLEQ proof. The estimate is an upper bound for mints that
+/// do not return DLEQ proofs.
+pub(crate) fn estimate_token_size(
+    mint_url: &MintUrl,
+    unit: &CurrencyUnit,
+    memo: Option<String>,
+    proofs_to_send: &Proofs,
+    outputs: &PreMintSecrets,
+) -> usize {
+    let proofs = proofs_to_send
+        .iter()
+        .cloned()
+        .chain(outputs.iter().map(|pre_mint| {
+            let mut proof = Proof::new(
+                pre_mint.amount,
+                outputs.keyset_id,
+                pre_mint.secret.clone(),
+                pre_mint.blinded_message.blinded_secret,
+            );
+            proof.dleq = Some(ProofDleq::new(
+                pre_mint.r.clone(),
+                pre_mint.r.clone(),
+                pre_mint.r.clone(),
+            ));
+            proof
+        }))
+        .collect();
+
+    Token::new(mint_url.clone(), proofs, memo, unit.clone()).estimated_size()
+}
+
 #[cfg(test)]
 mod tests {
+    use std::str::FromStr;
+
+    use cdk_common::amount::{FeeAndAmounts, SplitTarget};
     use cdk_common::secret::Secret;
     use cdk_common::{Amount, Id, Proof, PublicKey};
 
@@ -1684,4 +1722,44 @@ mod tests {
             .collect();
         assert!(swap_amounts.contains(&16));
     }
+
+    // ========================================================================
+    // Token Size Tests
+    // ========================================================================
+
+    #[test]
+    fn test_estimate_token_size_counts_future_proofs() {
+        let mint_url = MintUrl::from_str("https://mint.example.com").unwrap();
+        let fee_and_amounts: FeeAndAmounts = (0, (0..32).map(|i| 2_u64.pow(i)).collect()).into();
+        let no_outputs =
+            PreMintSecrets::random(id(), Amount::ZERO, &SplitTarget::None, &fee_and_amounts)
+                .unwrap();
+
+        let four_ones = proofs(&[1, 1, 1, 1]);
+        assert_eq!(
+            estimate_token_size(&mint_url, &CurrencyUnit::Sat, None, &four_ones, &no_outputs),
+            Token::new(mint_url.clone(), four_ones.clone(), None, CurrencyUnit::Sat)
+                .estimated_size()
+        );
+
+        // Swapping the four proofs into a single one makes a smaller token, even with its DLEQ
+        let one_four =
+            PreMintSecrets::random(id(), Amount::from(4), &SplitTarget::None, &fee_and_amounts)
+                .unwrap();
+        assert!(
+            estimate_token_size(
+                &mint_url,
+                &CurrencyUnit::Sat,
+                None,
+                &Proofs::new(),
+                &one_four
+            ) < estimate_token_size(&mint_url, &CurrencyUnit::Sat, None, &four_ones, &no_outputs)
+        );
+
+        let memo = Some("thanks for the coffee".to_owned());
+        assert!(
+            estimate_token_size(&mint_url, &CurrencyUnit::Sat, memo, &four_ones, &no_outputs)
+                > estimate_token_size(&mint_url, &CurrencyUnit::Sat, None, &four_ones, &no_outputs)
+        );
+    }
 }
@@ -61,9 +61,8 @@
 //! | `[compensated]` | Send cancelled before token created, reserved proofs released |
 //! | `[skipped]` | Recovery deferred (mint unreachable), will retry on next recovery |
 
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 
-use bitcoin::XOnlyPublicKey;
 use cdk_common::amount::KeysetFeeAndAmounts;
 use cdk_common::nut02::KeySetInfosMethods;
 use cdk_common::util::unix_time;
@@ -80,22 +79,23 @@ use crate::amount::SplitTarget;
 use crate::fees::calculate_fee;
 use crate::nuts::nut00::ProofsMethods;
 use crate::nuts::nut11::{enforce_sig_flag, SigFlag};
-use crate::nuts::{Proofs, State, Token};
+use crate::nuts::{PreMintSecrets, Proofs, State, Token};
 use crate::wallet::keysets::KeysetFilter;
 use crate::wallet::saga::{
     add_compensation, execute_compensations, new_compensations, Compensations,
     RevertProofReservation,
 };
+use crate::wallet::util::merge_keyring_keys;
 use crate::wallet::SendKind;
 use crate::{Amount, Error, Wallet};
 
 pub(crate) mod resume;
 pub(crate) mod state;
 
-fn verify_p2pk_proofs(proofs: &crate::nuts::Proofs) -> Result<(), Error> {
+fn verify_p2pk_proofs(proofs: &crate::nuts::Proofs, grace_secs: u64) -> Result<(), Error> {
     for proof in proofs {
         if crate::wallet::util::is_p2pk_locked(proof) {
-            proof.verify_p2pk()?;
+            proof.verify_p2pk_with_grace(grace_secs)?;
         }
     }
 
@@ -132,7 +132,7 @@ async fn filter_signable_proofs(
             Err(err) => return Err(err),
         }
 
-        if verify_p2pk_proofs(&signed).is_ok() {
+        if verify_p2pk_proofs(&signed, wallet.clock_skew_grace_secs).is_ok() {
             out.push(proof);
         }
     }
@@ -140,35 +140,7 @@ async fn filter_signable_proofs(
     Ok(out)
 }
 
-/// Build the signing key list for the given proofs by merging explicitly-provided keys with
-/// any matching keys found in the wallet keyring.
-///
-/// Explicit keys (from `SendOptions.p2pk_signing_keys`) take precedence; the keyring is only
-/// consulted for pubkeys not already covered by the explicit set.
-async fn merge_keyring_keys(
-    wallet: &Wallet,
-    proofs: &crate::nuts::Proofs,
-    explicit_keys: &[crate::nuts::SecretKey],
-) -> Result<Vec<crate::nuts::SecretKey>, Error> {
-    let mut keys = explicit_keys.to_vec();
-    let covered: HashSet<XOnlyPublicKey> = keys
-        .iter()
-        .map(|k| k.x_only_public_key(&crate::SECP256K1).0)
-        .collect();
-
-    let pubkeys = crate::wallet::util::collect_p2pk_pubkeys(proofs)?;
-    for pubkey in pubkeys {
-        let x_only = pubkey.x_only_public_key();
-        if !covered.contains(&x_only) {
-            if let Some(secret_key) = wallet.get_signing_key(&pubkey).await? {
-                keys.push(secret_key);
-            }
-        }
-    }
-
-    Ok(keys)
-}
-
+#[derive(Clone, Copy)]
 struct SendSplitContext<'a> {
     send_amounts: &'a [Amount],
     amount: Amount,
@@ -606,19 +578,88 @@ impl<'a> SendSaga<'a, Initial> {
             .map(|(key, values)| (*key, values.fee()))
             .collect();
 
-        let split_result = split_proofs_for_send_respecting_p2pk_locks(
+        let split_context = SendSplitContext {
+            send_amounts: &send_amounts,
+            amount,
+            send_fee: send_fee.total,
+            keyset_fees: &keyset_fees,
+            force_swap,
+            is_exact_or_offline,
+        };
+        let unsplit_proofs = opts.max_token_bytes.map(|_| proofs.clone());
+        let mut split_result = split_proofs_for_send_respecting_p2pk_locks(
             proofs,
             opts.p2pk_locked_proof_send_mode,
-            SendSplitContext {
-                send_amounts: &send_amounts,
-                amount,
-                send_fee: send_fee.total,
-                keyset_fees: &keyset_fees,
-                force_swap,
-                is_exact_or_offline,
-            },
+            split_context,
         )?;
 
+        if let (Some(max_token_bytes), Some(unsplit_proofs)) =
+            (opts.max_token_bytes, unsplit_proofs)
+        {
+            let estimate = |split: &super::ProofSplitResult| -> Result<usize, Error> {
+                let swap_output = (amount + send_fee.total)
+                    .checked_sub(split.proofs_to_send.total_amount()?)
+                    .unwrap_or(Amount::ZERO);
+                let outputs = match &opts.conditions {
+                    Some(conditions) => PreMintSecrets::with_conditions(
+                        active_keyset_id,
+                        swap_output,
+                        &SplitTarget::None,
+                        conditions,
+                        &fee_and_amounts,
+                    )?,
+                    None => PreMintSecrets::random(
+                        active_keyset_id,
+                        swap_output,
+                        &SplitTarget::None,
+                        &fee_and_amounts,
+                    )?,
+                };
+                let memo = opts
+                    .memo
+                    .as_ref()
+                    .and_then(|m| m.include_memo.then(|| m.memo.clone()));
+
+                Ok(super::estimate_token_size(
+                    &self.wallet.mint_url,
+                    &self.wallet.unit,
+                    memo,
+                    &split.proofs_to_send,
+                    &outputs,
+                ))
+            };
+
+            let mut size = estimate(&split_result)?;
+
+            // Swapping everything yields the fewest proofs, which offline sends cannot do
+            if size > max_token_bytes
+                && !split_result.proofs_to_send.is_empty()
+                && !opts.send_kind.is_offline()
+            {
+                let swapped = split_proofs_for_send_respecting_p2pk_locks(
+                    unsplit_proofs,
+                    opts.p2pk_locked_proof_send_mode,
+                    SendSplitContext {
+                        force_swap: true,
+                        ..split_context
+                    },
+                )?;
+                let swapped_size = estimate(&swapped)?;
+
+                if swapped_size < size {
+                    size = swapped_size;
+                    split_result = swapped;
+                }
+            }
+
+            if size > max_token_bytes {
+                return Err(Error::TokenTooLarge {
+                    size,
+                    max: max_token_bytes,
+                });
+            }
+        }
+
         let mut proof_ys = split_result.proofs_to_swap.ys()?;
         proof_ys.extend(split_result.proofs_to_send.ys()?);
 
@@ -806,7 +847,7 @@ impl<'a> SendSaga<'a, Prepared> {
                 if !keys.is_empty() {
                     crate::wallet::util::sign_proofs(&mut final_proofs_to_send, &keys)?;
                 }
-                verify_p2pk_proofs(&final_proofs_to_send)?;
+                verify_p2pk_proofs(&final_proofs_to_send, self.wallet.clock_skew_grace_secs)?;
             }
 
             if !proofs_to_swap.is_empty() {
@@ -1002,13 +1043,19 @@ impl<'a> SendSaga<'a, TokenCreated> {
             return Err(Error::ConcurrentUpdate);
         }
 
+        // Sign with the keys the wallet holds, so locked proofs are taken back through their
+        // refund path once the locktime passed
+        let mut proofs = self.state_data.proofs.clone();
+        let signing_keys = merge_keyring_keys(self.wallet, &proofs, &[]).await?;
+        crate::wallet::util::sign_proofs(&mut proofs, &signing_keys)?;
+
         // Swap proofs back to wallet with fresh secrets
         let swap_result = self
             .wallet
             .swap_no_reserve(
                 None, // Swap all
                 SplitTarget::default(),
-                self.state_data.proofs.clone(),
+                proofs,
                 None,
                 false,
                 false,
@@ -9,7 +9,10 @@ use tracing::instrument;
 use crate::amount::SplitTarget;
 use crate::fees::ProofsFeeBreakdown;
 use crate::nuts::nut00::ProofsMethods;
-use crate::nuts::{PreMintSecrets, PreSwap, Proofs, PublicKey, SpendingConditions, SwapRequest};
+use crate::nuts::{
+    PreMintSecrets, PreSwap, Proofs, PublicKey, SecretKey, SpendingConditionVerification,
+    SpendingConditions, SwapRequest,
+};
 use crate::{Amount, Error, Wallet};
 
 pub(crate) mod saga;
@@ -116,6 +119,35 @@ impl Wallet {
         Ok(saga.into_send_proofs())
     }
 
+    /// Sign `swap_request` with SIG_ALL when any of its inputs requires it
+    ///
+    /// The signature commits to every input and output of the request and goes on the first
+    /// input. The signed request is checked locally so a missing key fails before the mint is
+    /// contacted.
+    pub(crate) async fn sign_swap_sig_all(
+        &self,
+        swap_request: &mut SwapRequest,
+        p2pk_signing_keys: &[SecretKey],
+    ) -> Result<(), Error> {
+        let Some(keys) = crate::wallet::util::sig_all_signing_keys(
+            self,
+            swap_request.inputs(),
+            p2pk_signing_keys,
+        )
+        .await?
+        else {
+            return Ok(());
+        };
+
+        for key in keys {
+            swap_request.sign_sig_all(key)?;
+        }
+
+        swap_request.verify_spending_conditions_with_grace(self.clock_skew_grace_secs)?;
+
+        Ok(())
+    }
+
     /// Create Swap Payload
     #[instrument(skip(self, proofs))]
     #[allow(clippy::too_many_arguments)]
@@ -210,6 +210,16 @@ impl<'a> SwapSaga<'a, Prepared> {
         let unit = &self.wallet.unit;
         let operation_id = self.state_data.operation_id;
 
+        // Inputs locked with SIG_ALL are signed together with the outputs
+        if let Err(err) = self
+            .wallet
+            .sign_swap_sig_all(&mut self.state_data.pre_swap.swap_request, &[])
+            .await
+        {
+            execute_compensations(&mut self.compensations).await?;
+            return Err(err);
+        }
+
         let mut saga = self.state_data.saga.clone();
         saga.update_state(WalletSagaState::Swap(SwapSagaState::SwapRequested));
         if let OperationData::Swap(ref mut data) = saga.data {
@@ -408,12 +408,16 @@ pub struct MockMintConnector {
     pub check_state_response: Mutex<Option<Result<CheckStateResponse, Error>>>,
     /// Response for post_restore calls
     pub restore_response: Mutex<Option<Result<RestoreResponse, Error>>>,
+    /// Blinded secrets post_restore finds signatures for, when no response is configured
+    pub signed_outputs: Mutex<Vec<cdk_common::PublicKey>>,
     /// Response for get_melt_quote_status calls
     pub melt_quote_status_response: Mutex<Option<Result<MeltQuoteBolt11Response<String>, Error>>>,
     /// Response for post_mint calls
     pub post_mint_response: Mutex<Option<Result<MintResponse, Error>>>,
     /// Response for post_swap calls
     pub post_swap_response: Mutex<Option<Result<SwapResponse, Error>>>,
+    /// Response for post_melt_quote calls
+    pub post_melt_quote_response: Mutex<Option<Result<MeltQuoteCreateResponse<String>, Error>>>,
     /// Response for post_melt calls
     pub post_melt_response: Mutex<Option<Result<MeltQuoteResponse<String>, Error>>>,
     /// Last post_melt method/request captured by the mock
@@ -445,9 +449,11 @@ impl MockMintConnector {
             mint_info: Mutex::new(mint_info),
             check_state_response: Mutex::new(None),
             restore_response: Mutex::new(None),
+            signed_outputs: Mutex::new(Vec::new()),
             melt_quote_status_response: Mutex::new(None),
             post_mint_response: Mutex::new(None),
             post_swap_response: Mutex::new(None),
+            post_melt_quote_response: Mutex::new(None),
             post_melt_response: Mutex::new(None),
             last_post_melt_request: Mutex::new(None),
             lnurl_pay_request_response: Mutex::new(None),
@@ -516,6 +522,8 @@ impl MockMintConnector {
                 active: keyset.active.unwrap_or(true),
                 input_fee_ppk: keyset.input_fee_ppk,
                 final_expiry: keyset.final_expiry,
+                rotation_time: None,
+                retirement_time: None,
             }],
         }));
     }
@@ -529,6 +537,10 @@ impl MockMintConnector {
         *self.restore_response.lock().unwrap() = Some(response);
     }
 
+    pub fn set_signed_outputs(&self, blinded_secrets: Vec<cdk_common::PublicKey>) {
+        *self.signed_outputs.lock().unwrap() = blinded_secrets;
+    }
+
     pub fn set_melt_quote_status_response(
         &self,
         response: Result<MeltQuoteBolt11Response<String>, Error>,
@@ -544,6 +556,13 @@ impl MockMintConnector {
         *self.post_swap_response.lock().unwrap() = Some(response);
     }
 
+    pub fn set_post_melt_quote_response(
+        &self,
+        response: Result<MeltQuoteCreateResponse<String>, Error>,
+    ) {
+        *self.post_melt_quote_response.lock().unwrap() = Some(response);
+    }
+
     pub fn set_post_melt_response(&self, response: Result<MeltQuoteResponse<String>, Error>) {
         *self.post_melt_response.lock().unwrap() = Some(response);
     }
@@ -629,6 +648,8 @@ impl MintConnector for MockMintConnector {
                 active: keyset.active.unwrap_or(true),
                 input_fee_ppk: keyset.input_fee_ppk,
                 final_expiry: keyset.final_expiry,
+                rotation_time: None,
+                retirement_time: None,
             }],
         })
     }
@@ -664,7 +685,11 @@ impl MintConnector for MockMintConnector {
         &self,
         _request: MeltQuoteRequest,
     ) -> Result<MeltQuoteCreateResponse<String>, Error> {
-        unimplemented!()
+        self.post_melt_quote_response
+            .lock()
+            .unwrap()
+            .take()
+            .expect("MockMintConnector: post_melt_quote called without configured response")
     }
 
     async fn get_melt_quote_status(
@@ -706,12 +731,36 @@ impl MintConnector for MockMintConnector {
             .expect("MockMintConnector: post_check_state called without configured response")
     }
 
-    async fn post_restore(&self, _request: RestoreRequest) -> Result<RestoreResponse, Error> {
-        self.restore_response
-            .lock()
-            .unwrap()
-            .take()
-            .expect("MockMintConnector: post_restore called without configured response")
+    async fn post_restore(&self, request: RestoreRequest) -> Result<RestoreResponse, Error> {
+        if let Some(response) = self.restore_response.lock().unwrap().take() {
+            return response;
+        }
+
+        let signed_outputs = self.signed_outputs.lock().unwrap();
+        assert!(
+            !signed_outputs.is_empty(),
+            "MockMintConnector: post_restore called without configured response"
+        );
+
+        let outputs: Vec<_> = request
+            .outputs
+            .into_iter()
+            .filter(|output| signed_outputs.contains(&output.blinded_secret))
+            .collect();
+        let signatures = outputs
+            .iter()
+            .map(|output| cdk_common::BlindSignature {
+                amount: Amount::from(1),
+                keyset_id: output.keyset_id,
+                c: SecretKey::generate().public_key(),
+                dleq: None,
+            })
+            .collect();
+
+        Ok(RestoreResponse {
+            outputs,
+            signatures,
+        })
     }
 
     async fn get_auth_wallet(&self) -> Option<crate::wallet::AuthWallet> {
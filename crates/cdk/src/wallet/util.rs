@@ -1,13 +1,13 @@
 //! Wallet Utility Functions
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 
 use bitcoin::XOnlyPublicKey;
 
 use crate::nuts::nut10::Kind;
-use crate::nuts::{Conditions, Proof, Proofs, PublicKey, SecretKey};
-use crate::{Error, SECP256K1};
+use crate::nuts::{Conditions, Proof, Proofs, PublicKey, SecretKey, SigFlag, SpendingConditions};
+use crate::{Error, Wallet, SECP256K1};
 
 /// Returns `true` if the proof has a P2PK (NUT-11) spending condition.
 ///
@@ -93,67 +93,154 @@ pub(crate) fn sign_proofs(
         .collect();
 
     for proof in proofs.iter_mut() {
-        let Ok(secret) = <crate::secret::Secret as TryInto<crate::nuts::nut10::Secret>>::try_into(
-            proof.secret.clone(),
-        ) else {
-            continue;
-        };
-
-        let conditions: Result<Conditions, _> = secret
-            .secret_data()
-            .tags()
-            .cloned()
-            .unwrap_or_default()
-            .try_into();
+        for signing_key in proof_signing_keys(proof, &key_map)? {
+            proof.sign_p2pk(signing_key)?;
+        }
+    }
 
-        let Ok(conditions) = conditions else {
-            continue;
-        };
+    Ok(())
+}
 
-        let mut pubkeys = Vec::new();
+/// Build the signing key list for the given proofs by merging explicitly-provided keys with
+/// any matching keys found in the wallet keyring.
+///
+/// Explicit keys take precedence; the keyring is only consulted for pubkeys not already covered
+/// by the explicit set.
+pub(crate) async fn merge_keyring_keys(
+    wallet: &Wallet,
+    proofs: &Proofs,
+    explicit_keys: &[SecretKey],
+) -> Result<Vec<SecretKey>, Error> {
+    let mut keys = explicit_keys.to_vec();
+    let covered: HashSet<XOnlyPublicKey> = keys
+        .iter()
+        .map(|k| k.x_only_public_key(&SECP256K1).0)
+        .collect();
 
-        match secret.kind() {
-            Kind::P2PK => {
-                let data_key = PublicKey::from_str(secret.secret_data().data())?;
-                pubkeys.push(data_key);
-            }
-            Kind::HTLC => {
-                // HTLC slot 0 is a hash, not a pubkey.
-                // Condition keys (slots 1+) may still need signing.
-                // Preimage injection is handled separately by the caller.
+    let pubkeys = collect_p2pk_pubkeys(proofs)?;
+    for pubkey in pubkeys {
+        let x_only = pubkey.x_only_public_key();
+        if !covered.contains(&x_only) {
+            if let Some(secret_key) = wallet.get_signing_key(&pubkey).await? {
+                keys.push(secret_key);
             }
         }
+    }
+
+    Ok(keys)
+}
+
+/// Returns `true` if any of the proofs carries the SIG_ALL flag.
+pub(crate) fn has_sig_all(proofs: &Proofs) -> bool {
+    proofs.iter().any(|proof| {
+        let conditions = match SpendingConditions::try_from(&proof.secret) {
+            Ok(SpendingConditions::P2PKConditions { conditions, .. })
+            | Ok(SpendingConditions::HTLCConditions { conditions, .. }) => conditions,
+            Err(_) => None,
+        };
+
+        conditions.is_some_and(|conditions| conditions.sig_flag == SigFlag::SigAll)
+    })
+}
+
+/// Keys signing a SIG_ALL request spending `inputs`, or `None` when no input carries SIG_ALL.
+///
+/// The signature over the whole request goes on the first input, so the keys are the ones
+/// signing for it, taken from `explicit_keys` and the wallet keyring and derived per slot when
+/// the input is P2BK blinded.
+pub(crate) async fn sig_all_signing_keys(
+    wallet: &Wallet,
+    inputs: &Proofs,
+    explicit_keys: &[SecretKey],
+) -> Result<Option<Vec<SecretKey>>, Error> {
+    if !has_sig_all(inputs) {
+        return Ok(None);
+    }
 
-        if let Some(mut cond_pubkeys) = conditions.pubkeys {
-            pubkeys.append(&mut cond_pubkeys);
+    let Some(first_input) = inputs.first() else {
+        return Ok(None);
+    };
+
+    let available = merge_keyring_keys(wallet, inputs, explicit_keys).await?;
+    let key_map: HashMap<XOnlyPublicKey, &SecretKey> = available
+        .iter()
+        .map(|s| (s.x_only_public_key(&SECP256K1).0, s))
+        .collect();
+
+    let mut keys = proof_signing_keys(first_input, &key_map)?;
+    let mut seen = HashSet::new();
+    keys.retain(|key| seen.insert(key.x_only_public_key(&SECP256K1).0));
+
+    Ok(Some(keys))
+}
+
+/// Keys signing for each pubkey slot of `proof` that has a matching key in `key_map`
+fn proof_signing_keys(
+    proof: &Proof,
+    key_map: &HashMap<XOnlyPublicKey, &SecretKey>,
+) -> Result<Vec<SecretKey>, Error> {
+    let Ok(secret) = <crate::secret::Secret as TryInto<crate::nuts::nut10::Secret>>::try_into(
+        proof.secret.clone(),
+    ) else {
+        return Ok(Vec::new());
+    };
+
+    let conditions: Result<Conditions, _> = secret
+        .secret_data()
+        .tags()
+        .cloned()
+        .unwrap_or_default()
+        .try_into();
+
+    let Ok(conditions) = conditions else {
+        return Ok(Vec::new());
+    };
+
+    let mut pubkeys = Vec::new();
+
+    match secret.kind() {
+        Kind::P2PK => {
+            let data_key = PublicKey::from_str(secret.secret_data().data())?;
+            pubkeys.push(data_key);
         }
-        if let Some(mut refund_keys) = conditions.refund_keys {
-            pubkeys.append(&mut refund_keys);
+        Kind::HTLC => {
+            // HTLC slot 0 is a hash, not a pubkey.
+            // Condition keys (slots 1+) may still need signing.
+            // Preimage injection is handled separately by the caller.
         }
+    }
 
-        for (i, pubkey) in pubkeys.iter().enumerate() {
-            let slot = match secret.kind() {
-                Kind::P2PK => i as u8,
-                Kind::HTLC => (i + 1) as u8,
-            };
-            if let Some(ephemeral_key) = proof.p2pk_e {
-                for signing_key in key_map.values() {
-                    if let Ok(r) = crate::nuts::nut28::ecdh_kdf(signing_key, &ephemeral_key, slot) {
-                        if let Ok(derived_key) =
-                            crate::nuts::nut28::derive_signing_key_bip340(signing_key, &r, pubkey)
-                        {
-                            proof.sign_p2pk(derived_key)?;
-                            break;
-                        }
+    if let Some(mut cond_pubkeys) = conditions.pubkeys {
+        pubkeys.append(&mut cond_pubkeys);
+    }
+    if let Some(mut refund_keys) = conditions.refund_keys {
+        pubkeys.append(&mut refund_keys);
+    }
+
+    let mut signing_keys = Vec::new();
+
+    for (i, pubkey) in pubkeys.iter().enumerate() {
+        let slot = match secret.kind() {
+            Kind::P2PK => i as u8,
+            Kind::HTLC => (i + 1) as u8,
+        };
+        if let Some(ephemeral_key) = proof.p2pk_e {
+            for signing_key in key_map.values() {
+                if let Ok(r) = crate::nuts::nut28::ecdh_kdf(signing_key, &ephemeral_key, slot) {
+                    if let Ok(derived_key) =
+                        crate::nuts::nut28::derive_signing_key_bip340(signing_key, &r, pubkey)
+                    {
+                        signing_keys.push(derived_key);
+                        break;
                     }
                 }
-            } else if let Some(signing) = key_map.get(&pubkey.x_only_public_key()) {
-                proof.sign_p2pk((*signing).clone())?;
             }
+        } else if let Some(signing) = key_map.get(&pubkey.x_only_public_key()) {
+            signing_keys.push((*signing).clone());
         }
     }
 
-    Ok(())
+    Ok(signing_keys)
 }
 
 /// Extract token from text
@@ -15,7 +15,7 @@ use tracing::instrument;
 use zeroize::Zeroize;
 
 use super::builder::WalletBuilder;
-use super::{Error, MintConnector};
+use super::{Error, MintConnector, SeedProvider};
 use crate::mint_url::MintUrl;
 use crate::nuts::CurrencyUnit;
 #[cfg(all(feature = "tor", not(target_arch = "wasm32")))]
@@ -63,6 +63,10 @@ pub struct WalletConfig {
     ///
     /// The default value is 1 hour (3600 seconds).
     pub metadata_cache_ttl: Option<std::time::Duration>,
+    /// Clock skew grace period in seconds, see [`Wallet::set_clock_skew_grace`]
+    pub clock_skew_grace_secs: Option<u64>,
+    /// Receive fee threshold in parts per thousand, see [`Wallet::set_max_receive_fee`]
+    pub max_receive_fee_ppk: Option<u64>,
 }
 
 impl WalletConfig {
@@ -107,6 +111,18 @@ impl WalletConfig {
         self.metadata_cache_ttl = ttl;
         self
     }
+
+    /// Set the clock skew grace period in seconds
+    pub fn with_clock_skew_grace(mut self, secs: u64) -> Self {
+        self.clock_skew_grace_secs = Some(secs);
+        self
+    }
+
+    /// Set the receive fee threshold in parts per thousand
+    pub fn with_max_receive_fee(mut self, max_fee_ppk: u64) -> Self {
+        self.max_receive_fee_ppk = Some(max_fee_ppk);
+        self
+    }
 }
 
 /// Builder for creating [`WalletRepository`] instances
@@ -183,6 +199,12 @@ impl WalletRepositoryBuilder {
         self
     }
 
+    /// Set the wallet seed from a [`SeedProvider`], such as a hardware wallet
+    pub async fn seed_provider(self, provider: &dyn SeedProvider) -> Result<Self, Error> {
+        let seed = provider.derivation_seed().await?;
+        Ok(self.seed(seed))
+    }
+
     /// Set the proxy URL for HTTP clients
     pub fn proxy_url(mut self, proxy_url: url::Url) -> Self {
         self.proxy_config = Some(proxy_url);
@@ -528,6 +550,8 @@ impl WalletRepository {
                     .localstore(self.localstore.clone())
                     .seed(self.seed)
                     .target_proof_count(cfg.target_proof_count.unwrap_or(3))
+                    .clock_skew_grace(cfg.clock_skew_grace_secs.unwrap_or_default())
+                    .max_receive_fee(cfg.max_receive_fee_ppk)
                     .shared_client(custom_connector.clone());
 
                 if let Some(ttl) = cfg.metadata_cache_ttl {
@@ -541,8 +565,12 @@ impl WalletRepository {
         // Fall back to existing logic: proxy/Tor/default
         let target_proof_count = config.and_then(|c| c.target_proof_count).unwrap_or(3);
         let metadata_cache_ttl = config.and_then(|c| c.metadata_cache_ttl);
+        let clock_skew_grace_secs = config
+            .and_then(|c| c.clock_skew_grace_secs)
+            .unwrap_or_default();
+        let max_receive_fee_ppk = config.and_then(|c| c.max_receive_fee_ppk);
 
-        let wallet = if let Some(proxy_url) = &self.proxy_config {
+        let mut wallet = if let Some(proxy_url) = &self.proxy_config {
             // Create wallet with proxy-configured client
             let client = proxy_http_client(
                 mint_url.clone(),
@@ -616,6 +644,8 @@ impl WalletRepository {
                 wallet
             }
         };
+        wallet.set_clock_skew_grace(clock_skew_grace_secs);
+        wallet.set_max_receive_fee(max_receive_fee_ppk);
 
         Ok(wallet)
     }
@@ -513,6 +513,7 @@
                 -p cdk-cln \
                 -p cdk-lnd \
                 -p cdk-lnbits \
+                -p cdk-phoenixd \
                 -p cdk-fake-wallet \
                 -p cdk-mint-rpc \
                 -p cdk-payment-processor \
@@ -756,6 +757,7 @@
             "-p cdk-cln"
             "-p cdk-lnd"
             "-p cdk-lnbits"
+            "-p cdk-phoenixd"
             "-p cdk-fake-wallet"
             "-p cdk-payment-processor"
             "-p cdk-ldk-node"
@@ -207,6 +207,16 @@ if [ $status4 -ne 0 ]; then
     exit $status4
 fi
 
+# Run the NUT conformance suite against the same mint
+echo "Running conformance suite"
+run_bin cdk-conformance --mint-url "$CDK_TEST_MINT_URL"
+status5=$?
+
+if [ $status5 -ne 0 ]; then
+    echo "Conformance suite failed with status $status5, exiting"
+    exit $status5
+fi
+
 # All tests passed
 echo "All tests passed successfully"
 exit 0